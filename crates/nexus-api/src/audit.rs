@@ -0,0 +1,186 @@
+//! Server audit log — who did what to whom, and why.
+//!
+//! Routes call [`record`] after a moderation or admin action succeeds. The
+//! entry is written to the `audit_log` table and broadcast to the server as
+//! `AUDIT_LOG_ENTRY_CREATE`. Moderators can attach a reason with the
+//! `X-Audit-Log-Reason` header on any audited request.
+//!
+//! Recording never fails the action itself: a write error is logged and the
+//! request carries on.
+
+use axum::http::HeaderMap;
+use nexus_common::gateway_event::{event_types, GatewayEvent};
+use nexus_db::repository::audit_log::{self, AuditLogRow};
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::AppState;
+
+/// Header carrying a moderator-supplied reason for the action.
+pub const REASON_HEADER: &str = "x-audit-log-reason";
+/// Longest reason kept; longer ones are truncated.
+const MAX_REASON_CHARS: usize = 512;
+
+/// Audit log action names, as stored in `audit_log.action`.
+pub mod actions {
+    pub const MESSAGE_DELETE: &str = "MESSAGE_DELETE";
+    pub const MESSAGE_BULK_DELETE: &str = "MESSAGE_BULK_DELETE";
+    pub const MESSAGE_MOVE: &str = "MESSAGE_MOVE";
+    pub const MEMBER_BAN_ADD: &str = "MEMBER_BAN_ADD";
+    pub const MEMBER_BAN_REMOVE: &str = "MEMBER_BAN_REMOVE";
+    pub const MEMBER_KICK: &str = "MEMBER_KICK";
+    pub const MEMBER_UPDATE: &str = "MEMBER_UPDATE";
+    pub const MEMBER_ROLE_UPDATE: &str = "MEMBER_ROLE_UPDATE";
+    pub const MEMBER_BULK_UPDATE: &str = "MEMBER_BULK_UPDATE";
+    pub const ROLE_CREATE: &str = "ROLE_CREATE";
+    pub const ROLE_UPDATE: &str = "ROLE_UPDATE";
+    pub const ROLE_DELETE: &str = "ROLE_DELETE";
+    pub const CHANNEL_UPDATE: &str = "CHANNEL_UPDATE";
+    pub const SERVER_SETTINGS_ROLLBACK: &str = "SERVER_SETTINGS_ROLLBACK";
+    pub const CHANNEL_DELETE: &str = "CHANNEL_DELETE";
+    pub const CHANNEL_ARCHIVE: &str = "CHANNEL_ARCHIVE";
+    pub const CHANNEL_UNARCHIVE: &str = "CHANNEL_UNARCHIVE";
+    pub const CHANNEL_FEDERATE: &str = "CHANNEL_FEDERATE";
+    pub const CHANNEL_DEFEDERATE: &str = "CHANNEL_DEFEDERATE";
+    pub const WEBHOOK_CREATE: &str = "WEBHOOK_CREATE";
+    pub const WEBHOOK_UPDATE: &str = "WEBHOOK_UPDATE";
+    pub const WEBHOOK_DELETE: &str = "WEBHOOK_DELETE";
+}
+
+/// What an audited action was applied to.
+#[derive(Debug, Clone, Copy)]
+pub enum Target {
+    User(Uuid),
+    Message(Uuid),
+    Channel(Uuid),
+    Role(Uuid),
+    Webhook(Uuid),
+}
+
+impl Target {
+    fn parts(&self) -> (&'static str, Uuid) {
+        match *self {
+            Target::User(id) => ("user", id),
+            Target::Message(id) => ("message", id),
+            Target::Channel(id) => ("channel", id),
+            Target::Role(id) => ("role", id),
+            Target::Webhook(id) => ("webhook", id),
+        }
+    }
+}
+
+/// The reason a moderator gave in the `X-Audit-Log-Reason` header, if any.
+pub fn reason(headers: &HeaderMap) -> Option<String> {
+    clean_reason(headers.get(REASON_HEADER)?.to_str().ok()?)
+}
+
+/// Trim a moderator-supplied reason and cap its length; blank means none.
+pub fn clean_reason(raw: &str) -> Option<String> {
+    let raw = raw.trim();
+    if raw.is_empty() {
+        return None;
+    }
+    Some(raw.chars().take(MAX_REASON_CHARS).collect())
+}
+
+/// Changes to `fields` between two serialized snapshots of the same object,
+/// as `{ "field": { "old": ..., "new": ... } }`. Unchanged fields are left
+/// out, so an update only logs what it actually touched.
+pub fn diff<T: Serialize>(before: &T, after: &T, fields: &[&str]) -> serde_json::Value {
+    let before = serde_json::to_value(before).unwrap_or_default();
+    let after = serde_json::to_value(after).unwrap_or_default();
+    let mut changes = serde_json::Map::new();
+    for &field in fields {
+        let old = before.get(field).unwrap_or(&serde_json::Value::Null);
+        let new = after.get(field).unwrap_or(&serde_json::Value::Null);
+        if old != new {
+            changes.insert(field.to_owned(), serde_json::json!({ "old": old, "new": new }));
+        }
+    }
+    serde_json::Value::Object(changes)
+}
+
+pub fn entry_json(e: &AuditLogRow) -> serde_json::Value {
+    serde_json::json!({
+        "id": e.id,
+        "server_id": e.server_id,
+        "user_id": e.user_id,
+        "action": e.action,
+        "target_type": e.target_type,
+        "target_id": e.target_id,
+        "changes": e.changes,
+        "reason": e.reason,
+        "created_at": e.created_at,
+    })
+}
+
+/// Write an audit log entry and broadcast it to the server.
+pub async fn record(
+    state: &AppState,
+    server_id: Uuid,
+    actor_id: Uuid,
+    action: &str,
+    target: Option<Target>,
+    changes: Option<serde_json::Value>,
+    reason: Option<&str>,
+) {
+    let (target_type, target_id) = target.map(|t| t.parts()).unzip();
+    let pool = &state.db.pool;
+    let created = audit_log::create_entry(
+        pool,
+        server_id,
+        actor_id,
+        action,
+        target_type,
+        target_id,
+        changes.as_ref(),
+        reason,
+    )
+    .await;
+    let entry = match created {
+        Ok(id) => audit_log::get_entry(pool, id).await,
+        Err(e) => Err(e),
+    };
+    match entry {
+        Ok(Some(entry)) => {
+            let _ = state.gateway_tx.send(GatewayEvent {
+                event_type: event_types::AUDIT_LOG_ENTRY_CREATE.into(),
+                data: entry_json(&entry),
+                server_id: Some(server_id),
+                channel_id: None,
+                user_id: Some(actor_id),
+                trace_context: None,
+            });
+        }
+        Ok(None) => {}
+        Err(e) => {
+            tracing::warn!(%server_id, action, error = %e, "Failed to record audit log entry");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diff_reports_only_changed_fields() {
+        let before = serde_json::json!({ "name": "general", "topic": null, "nsfw": false });
+        let after = serde_json::json!({ "name": "chat", "topic": null, "nsfw": false });
+        assert_eq!(
+            diff(&before, &after, &["name", "topic"]),
+            serde_json::json!({ "name": { "old": "general", "new": "chat" } })
+        );
+        assert_eq!(diff(&before, &after, &["nsfw"]), serde_json::json!({}));
+    }
+
+    #[test]
+    fn reason_is_trimmed_and_capped() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(reason(&headers), None);
+        headers.insert(REASON_HEADER, "  spam  ".parse().unwrap());
+        assert_eq!(reason(&headers).as_deref(), Some("spam"));
+        headers.insert(REASON_HEADER, "x".repeat(600).parse().unwrap());
+        assert_eq!(reason(&headers).map(|r| r.len()), Some(MAX_REASON_CHARS));
+    }
+}
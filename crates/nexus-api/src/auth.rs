@@ -9,20 +9,10 @@ use argon2::{
 };
 use chrono::{Duration, Utc};
 use jsonwebtoken::{encode, EncodingKey, Header};
-use serde::Serialize;
 use uuid::Uuid;
 
-// Re-export Claims and validate_token from nexus-common so existing code keeps working
-pub use nexus_common::auth::{validate_token, Claims};
-
-/// Token pair returned on login/register.
-#[derive(Debug, Serialize)]
-pub struct TokenPair {
-    pub access_token: String,
-    pub refresh_token: String,
-    pub expires_in: u64,
-    pub token_type: String,
-}
+// Re-export Claims, TokenPair and validate_token from nexus-common so existing code keeps working
+pub use nexus_common::auth::{validate_token, Claims, TokenPair};
 
 /// Hash a password using Argon2id (the gold standard for password hashing).
 pub fn hash_password(password: &str) -> Result<String, argon2::password_hash::Error> {
@@ -40,10 +30,14 @@ pub fn verify_password(password: &str, hash: &str) -> Result<bool, argon2::passw
         .is_ok())
 }
 
-/// Generate a JWT access token.
+/// The gateway authenticates bot tokens too, so the hash lives in common.
+pub use nexus_common::auth::hash_bot_token;
+
+/// Generate a JWT access token for a login session.
 pub fn generate_access_token(
     user_id: Uuid,
     username: &str,
+    session_id: Uuid,
     secret: &str,
     ttl_secs: u64,
 ) -> Result<String, jsonwebtoken::errors::Error> {
@@ -54,6 +48,7 @@ pub fn generate_access_token(
         iat: now.timestamp(),
         exp: (now + Duration::seconds(ttl_secs as i64)).timestamp(),
         token_type: "access".to_string(),
+        sid: Some(session_id.to_string()),
     };
 
     encode(
@@ -63,41 +58,29 @@ pub fn generate_access_token(
     )
 }
 
-/// Generate a JWT refresh token (longer-lived).
-pub fn generate_refresh_token(
-    user_id: Uuid,
-    username: &str,
-    secret: &str,
-    ttl_secs: u64,
-) -> Result<String, jsonwebtoken::errors::Error> {
-    let now = Utc::now();
-    let claims = Claims {
-        sub: user_id.to_string(),
-        username: username.to_string(),
-        iat: now.timestamp(),
-        exp: (now + Duration::seconds(ttl_secs as i64)).timestamp(),
-        token_type: "refresh".to_string(),
-    };
-
-    encode(
-        &Header::default(),
-        &claims,
-        &EncodingKey::from_secret(secret.as_bytes()),
-    )
+/// Generate an opaque refresh token. Only its hash ([`hash_refresh_token`])
+/// is stored, in the `sessions` repository.
+pub fn generate_refresh_token() -> String {
+    crate::oauth2::generate_token()
 }
 
-/// Generate both access and refresh tokens.
+/// Generate an access token and a fresh refresh token for a session.
 pub fn generate_token_pair(
     user_id: Uuid,
     username: &str,
+    session_id: Uuid,
     secret: &str,
     access_ttl: u64,
-    refresh_ttl: u64,
 ) -> Result<TokenPair, jsonwebtoken::errors::Error> {
     Ok(TokenPair {
-        access_token: generate_access_token(user_id, username, secret, access_ttl)?,
-        refresh_token: generate_refresh_token(user_id, username, secret, refresh_ttl)?,
+        access_token: generate_access_token(user_id, username, session_id, secret, access_ttl)?,
+        refresh_token: generate_refresh_token(),
         expires_in: access_ttl,
         token_type: "Bearer".to_string(),
     })
 }
+
+/// Refresh tokens are stored hashed, like OAuth2 tokens.
+pub fn hash_refresh_token(token: &str) -> String {
+    crate::oauth2::hash_token(token)
+}
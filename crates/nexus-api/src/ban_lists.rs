@@ -0,0 +1,348 @@
+//! Ban list sharing — export a server's bans, import them elsewhere, and
+//! keep subscribed servers in sync with a source list.
+//!
+//! The exchange format is [`BanList`]. Blocklists published at a URL may
+//! also be a bare JSON array of user IDs. They are fetched like link
+//! previews: only from public addresses, checking every redirect.
+
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use nexus_common::{
+    error::NexusResult,
+    gateway_event::{event_types, GatewayEvent},
+    models::{member::Member, role::Role, server::Server},
+    permissions::Permissions,
+};
+use nexus_db::repository::{bans, members, roles, servers};
+use serde::{Deserialize, Serialize};
+use url::Url;
+use uuid::Uuid;
+
+use crate::{
+    audit::{self, actions, Target},
+    unfurl, AppState,
+};
+
+/// Current export format version.
+pub const FORMAT_VERSION: u32 = 1;
+
+/// Largest blocklist accepted from a URL.
+const MAX_LIST_BYTES: usize = 5 * 1024 * 1024;
+
+/// Most entries applied by one import or sync.
+pub const MAX_ENTRIES: usize = 10_000;
+
+/// A portable list of bans.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BanList {
+    pub version: u32,
+    /// Human-readable origin, e.g. the exporting server's name.
+    #[serde(default)]
+    pub source: Option<String>,
+    #[serde(default)]
+    pub exported_at: Option<DateTime<Utc>>,
+    pub bans: Vec<BanEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BanEntry {
+    pub user_id: Uuid,
+    #[serde(default)]
+    pub reason: Option<String>,
+    #[serde(default)]
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+/// Outcome of an import.
+#[derive(Debug, Default, Clone, Copy, Serialize)]
+pub struct ImportSummary {
+    /// New bans created.
+    pub imported: usize,
+    /// Entries skipped: already banned, unknown user, the server owner, the
+    /// importer, or a member the importer doesn't outrank.
+    pub skipped: usize,
+}
+
+/// Who an import acts as: a member of the server allowed to ban there.
+pub struct Actor {
+    server: Server,
+    member: Member,
+    roles: Vec<Role>,
+}
+
+impl Actor {
+    /// `user_id` acting in `server_id`, or `None` if they lack BAN_MEMBERS
+    /// there (or the server is gone).
+    pub async fn load(state: &AppState, server_id: Uuid, user_id: Uuid) -> NexusResult<Option<Self>> {
+        let pool = &state.db.pool;
+        let Some(server) = servers::find_by_id(pool, server_id).await? else {
+            return Ok(None);
+        };
+        let allowed = crate::permissions::resolve(pool, &server, None, user_id)
+            .await?
+            .is_some_and(|p| p.has(Permissions::BAN_MEMBERS));
+        if !allowed {
+            return Ok(None);
+        }
+        let Some(member) = members::find_member(pool, user_id, server_id).await? else {
+            return Ok(None);
+        };
+        let roles = roles::list_server_roles(pool, server_id).await?;
+        Ok(Some(Self { server, member, roles }))
+    }
+}
+
+/// Parse a blocklist document: either a [`BanList`] or a bare array of IDs.
+pub fn parse(body: &[u8]) -> Result<BanList, String> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Document {
+        List(BanList),
+        Ids(Vec<Uuid>),
+    }
+
+    match serde_json::from_slice::<Document>(body).map_err(|e| format!("invalid ban list: {e}"))? {
+        Document::List(list) if list.version > FORMAT_VERSION => {
+            Err(format!("unsupported ban list version {}", list.version))
+        }
+        Document::List(list) => Ok(list),
+        Document::Ids(ids) => Ok(BanList {
+            version: FORMAT_VERSION,
+            source: None,
+            exported_at: None,
+            bans: ids
+                .into_iter()
+                .map(|user_id| BanEntry { user_id, reason: None, created_at: None })
+                .collect(),
+        }),
+    }
+}
+
+/// Export every ban in a server.
+pub async fn export(pool: &sqlx::AnyPool, server_id: Uuid, source: &str) -> Result<BanList, sqlx::Error> {
+    let rows = bans::list_bans(pool, server_id).await?;
+    Ok(BanList {
+        version: FORMAT_VERSION,
+        source: Some(source.to_owned()),
+        exported_at: Some(Utc::now()),
+        bans: rows
+            .into_iter()
+            .map(|b| BanEntry {
+                user_id: b.user_id,
+                reason: b.reason,
+                created_at: Some(b.created_at),
+            })
+            .collect(),
+    })
+}
+
+/// Ban every listed user in the actor's server, removing any who are
+/// members.
+///
+/// The same limits apply as to banning by hand: the owner, the actor and
+/// members the actor doesn't outrank are skipped. `origin` is recorded in
+/// the ban reason so moderators can tell imported bans from their own.
+/// Each new ban gets an audit log entry.
+pub async fn import(
+    state: &AppState,
+    actor: &Actor,
+    entries: &[BanEntry],
+    origin: &str,
+) -> NexusResult<ImportSummary> {
+    let pool = &state.db.pool;
+    let server_id = actor.server.id;
+    let banned_by = actor.member.user_id;
+    let mut summary = ImportSummary::default();
+
+    for entry in entries {
+        if entry.user_id == actor.server.owner_id || entry.user_id == banned_by {
+            summary.skipped += 1;
+            continue;
+        }
+        let target = members::find_member(pool, entry.user_id, server_id).await?;
+        if target
+            .as_ref()
+            .is_some_and(|t| !crate::permissions::outranks(&actor.server, &actor.member, t, &actor.roles))
+        {
+            summary.skipped += 1;
+            continue;
+        }
+        let reason = match &entry.reason {
+            Some(reason) => format!("[{origin}] {reason}"),
+            None => format!("[{origin}]"),
+        };
+        if !bans::create_ban(pool, server_id, entry.user_id, Some(&reason), banned_by).await? {
+            summary.skipped += 1;
+            continue;
+        }
+        summary.imported += 1;
+        let _ = state.gateway_tx.send(GatewayEvent {
+            event_type: event_types::SERVER_BAN_ADD.into(),
+            data: serde_json::json!({
+                "server_id": server_id,
+                "user_id": entry.user_id,
+                "reason": reason,
+            }),
+            server_id: Some(server_id),
+            channel_id: None,
+            user_id: Some(entry.user_id),
+            trace_context: None,
+        });
+        audit::record(
+            state,
+            server_id,
+            banned_by,
+            actions::MEMBER_BAN_ADD,
+            Some(Target::User(entry.user_id)),
+            Some(serde_json::json!({ "source": origin })),
+            Some(&reason),
+        )
+        .await;
+
+        if target.is_some() {
+            members::remove_member(pool, entry.user_id, server_id).await?;
+            servers::decrement_member_count(pool, server_id).await?;
+            let _ = state.gateway_tx.send(GatewayEvent {
+                event_type: event_types::SERVER_MEMBER_REMOVE.into(),
+                data: serde_json::json!({ "server_id": server_id, "user_id": entry.user_id }),
+                server_id: Some(server_id),
+                channel_id: None,
+                user_id: Some(entry.user_id),
+                trace_context: None,
+            });
+        }
+    }
+    Ok(summary)
+}
+
+/// Download and parse a blocklist published at `url`.
+pub async fn fetch(url: &str) -> Result<BanList, String> {
+    let mut url = Url::parse(url).map_err(|e| format!("invalid URL: {e}"))?;
+    for _ in 0..=unfurl::MAX_REDIRECTS {
+        let (host, addr) = unfurl::resolve(&url, "")
+            .await
+            .ok_or_else(|| format!("{url} is not a public address"))?;
+        // Pin the connection to the address just checked, and follow
+        // redirects by hand so each hop is checked too
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(30))
+            .redirect(reqwest::redirect::Policy::none())
+            .resolve(&host, addr)
+            .build()
+            .map_err(|e| e.to_string())?;
+        let response = client
+            .get(url.clone())
+            .header(reqwest::header::ACCEPT, "application/json")
+            .send()
+            .await
+            .map_err(|e| format!("fetch failed: {e}"))?;
+        if response.status().is_redirection() {
+            let location = response
+                .headers()
+                .get(reqwest::header::LOCATION)
+                .and_then(|l| l.to_str().ok())
+                .ok_or("redirect without a location")?;
+            url = url.join(location).map_err(|e| format!("bad redirect: {e}"))?;
+            continue;
+        }
+        let mut response = response.error_for_status().map_err(|e| format!("fetch failed: {e}"))?;
+        if response.content_length().is_some_and(|len| len as usize > MAX_LIST_BYTES) {
+            return Err("ban list too large".into());
+        }
+        // The length header may be missing or wrong; count as we read.
+        let mut body = Vec::new();
+        while let Some(chunk) = response.chunk().await.map_err(|e| format!("fetch failed: {e}"))? {
+            if body.len() + chunk.len() > MAX_LIST_BYTES {
+                return Err("ban list too large".into());
+            }
+            body.extend_from_slice(&chunk);
+        }
+        return parse(&body);
+    }
+    Err("too many redirects".into())
+}
+
+/// Pull a subscription's source list into its server once.
+///
+/// The subscription acts as its creator, who must still be allowed to ban
+/// in the subscribing server and, for a server source, in the source too.
+/// If they aren't, the subscription is disabled.
+pub async fn sync(state: &AppState, sub: &bans::BanSubscriptionRow) -> Result<ImportSummary, String> {
+    let pool = &state.db.pool;
+    let actor = Actor::load(state, sub.server_id, sub.created_by)
+        .await
+        .map_err(|e| e.to_string())?;
+    let source_allowed = match sub.source_server_id {
+        Some(source_id) => Actor::load(state, source_id, sub.created_by)
+            .await
+            .map_err(|e| e.to_string())?
+            .is_some(),
+        None => true,
+    };
+    let Some(actor) = actor.filter(|_| source_allowed) else {
+        let reason = "subscription creator can no longer ban in the subscribing or source server";
+        bans::disable_subscription(pool, sub.id, reason)
+            .await
+            .map_err(|e| e.to_string())?;
+        return Err(reason.into());
+    };
+
+    let (list, origin) = match (sub.source_server_id, sub.source_url.as_deref()) {
+        (Some(source_id), _) => {
+            let source = servers::find_by_id(pool, source_id)
+                .await
+                .map_err(|e| e.to_string())?
+                .ok_or("source server no longer exists")?;
+            let list = export(pool, source_id, &source.name).await.map_err(|e| e.to_string())?;
+            (list, source.name)
+        }
+        (None, Some(url)) => (fetch(url).await?, url.to_owned()),
+        (None, None) => return Err("subscription has no source".into()),
+    };
+    if list.bans.len() > MAX_ENTRIES {
+        return Err(format!("ban list has more than {MAX_ENTRIES} entries"));
+    }
+    import(state, &actor, &list.bans, &origin)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_export_format() {
+        let id = Uuid::new_v4();
+        let body = serde_json::json!({
+            "version": 1,
+            "source": "Allied Server",
+            "bans": [{ "user_id": id, "reason": "spam" }],
+        });
+        let list = parse(body.to_string().as_bytes()).unwrap();
+        assert_eq!(list.bans.len(), 1);
+        assert_eq!(list.bans[0].user_id, id);
+        assert_eq!(list.bans[0].reason.as_deref(), Some("spam"));
+    }
+
+    #[test]
+    fn parses_bare_id_array() {
+        let ids = [Uuid::new_v4(), Uuid::new_v4()];
+        let list = parse(serde_json::to_string(&ids).unwrap().as_bytes()).unwrap();
+        assert_eq!(list.bans.iter().map(|b| b.user_id).collect::<Vec<_>>(), ids);
+    }
+
+    #[test]
+    fn rejects_future_versions_and_garbage() {
+        assert!(parse(br#"{"version": 99, "bans": []}"#).is_err());
+        assert!(parse(b"not json").is_err());
+    }
+
+    #[tokio::test]
+    async fn refuses_non_public_hosts() {
+        for url in ["http://127.0.0.1/bans.json", "https://[::1]/bans.json", "http://10.0.0.1/bans.json"] {
+            assert!(fetch(url).await.unwrap_err().contains("not a public address"), "{url}");
+        }
+    }
+}
@@ -0,0 +1,44 @@
+//! Outbound protocol bridge dispatch — hands new messages to the
+//! [`BridgeRegistry`](nexus_federation::BridgeRegistry) in `AppState`.
+
+use nexus_common::models::{
+    channel::{Channel, ChannelType},
+    server::Server,
+};
+use nexus_db::repository::{messages::MessageRow, servers};
+use nexus_federation::OutboundPost;
+
+use crate::AppState;
+
+/// Whether posts in `channel` may be published to open networks.
+pub fn is_public_announcement(channel: &Channel, server: &Server) -> bool {
+    channel.channel_type == ChannelType::Announcement && server.is_public
+}
+
+/// Relay a freshly created message through every interested bridge.
+///
+/// Returns immediately; delivery happens on a background task.
+pub(crate) async fn relay_message(state: &AppState, channel: &Channel, message: &MessageRow, author_name: &str) {
+    if state.bridges.is_empty() {
+        return;
+    }
+
+    let public_announcement = match (channel.channel_type == ChannelType::Announcement, channel.server_id) {
+        (true, Some(server_id)) => servers::find_by_id(&state.db.pool, server_id)
+            .await
+            .ok()
+            .flatten()
+            .is_some_and(|server| is_public_announcement(channel, &server)),
+        _ => false,
+    };
+
+    state.bridges.dispatch(OutboundPost {
+        message_id: message.id,
+        channel_id: message.channel_id,
+        server_id: channel.server_id,
+        author_name: author_name.to_owned(),
+        content: message.content.clone(),
+        created_at: message.created_at,
+        public_announcement,
+    });
+}
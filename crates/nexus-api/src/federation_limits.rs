@@ -0,0 +1,143 @@
+//! Inbound federation limits — token buckets per origin server on
+//! `PUT /_nexus/federation/v1/send/{txnId}`.
+//!
+//! Each transaction takes one token from its origin's transaction bucket and
+//! one per PDU from its PDU bucket, both refilling over a minute. A refused
+//! transaction is answered with 429 and counts as a strike against the
+//! origin in `federated_servers`; enough consecutive strikes throttle the
+//! origin, refusing everything it sends for `federation.throttle_secs`.
+//!
+//! Buckets are kept in memory per process; the throttle lives in the
+//! database, so it holds across every process receiving federation traffic.
+//!
+//! Limits come from [`FederationConfig`].
+
+use nexus_common::config::FederationConfig;
+use nexus_common::ratelimit::{Limit, Outcome, TokenBucket};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Full in-memory buckets are dropped every this many checks.
+const PRUNE_EVERY: u64 = 1024;
+/// Window the per-minute limits refill over.
+const WINDOW_SECS: u64 = 60;
+
+struct OriginBuckets {
+    txns: TokenBucket,
+    pdus: TokenBucket,
+}
+
+/// Buckets for every origin server that has sent us a transaction.
+pub struct OriginLimiter {
+    config: FederationConfig,
+    buckets: Mutex<HashMap<String, OriginBuckets>>,
+    checks: AtomicU64,
+}
+
+impl OriginLimiter {
+    pub fn new(config: FederationConfig) -> Self {
+        Self {
+            config,
+            buckets: Mutex::new(HashMap::new()),
+            checks: AtomicU64::new(0),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.config.rate_limit_enabled
+    }
+
+    /// Consecutive refusals that throttle an origin.
+    pub fn throttle_strikes(&self) -> u32 {
+        self.config.throttle_strikes
+    }
+
+    /// How long a throttled origin is refused.
+    pub fn throttle_duration(&self) -> chrono::Duration {
+        chrono::Duration::seconds(self.config.throttle_secs as i64)
+    }
+
+    fn txn_limit(&self) -> Limit {
+        Limit::new(self.config.txn_limit, WINDOW_SECS)
+    }
+
+    fn pdu_limit(&self) -> Limit {
+        Limit::new(self.config.pdu_limit, WINDOW_SECS)
+    }
+
+    /// Charge one transaction carrying `pdu_count` PDUs to `origin`. The PDU
+    /// bucket is only charged when the transaction bucket allowed it; the
+    /// outcome returned is the refusing bucket's, or the PDU bucket's.
+    pub fn check(&self, origin: &str, pdu_count: u32) -> Outcome {
+        self.check_at(origin, pdu_count, now_ms())
+    }
+
+    fn check_at(&self, origin: &str, pdu_count: u32, now: u64) -> Outcome {
+        let (txn_limit, pdu_limit) = (self.txn_limit(), self.pdu_limit());
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        if self.checks.fetch_add(1, Ordering::Relaxed) % PRUNE_EVERY == PRUNE_EVERY - 1 {
+            buckets.retain(|_, b| !(b.txns.is_full(txn_limit, now) && b.pdus.is_full(pdu_limit, now)));
+        }
+        let b = buckets.entry(origin.to_owned()).or_insert_with(|| OriginBuckets {
+            txns: TokenBucket::new(txn_limit, now),
+            pdus: TokenBucket::new(pdu_limit, now),
+        });
+        let txns = b.txns.take(txn_limit, now);
+        if !txns.allowed {
+            return txns;
+        }
+        b.pdus.take_n(pdu_limit, pdu_count, now)
+    }
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limiter() -> OriginLimiter {
+        OriginLimiter::new(FederationConfig {
+            max_future_skew_secs: 300,
+            acceptance_window_secs: 604_800,
+            txn_log_max_rows: 100_000,
+            rate_limit_enabled: true,
+            txn_limit: 3,
+            pdu_limit: 10,
+            throttle_strikes: 10,
+            throttle_secs: 600,
+        })
+    }
+
+    #[test]
+    fn transactions_run_out_per_origin() {
+        let l = limiter();
+        for _ in 0..3 {
+            assert!(l.check_at("a.example", 1, 0).allowed);
+        }
+        let refused = l.check_at("a.example", 1, 0);
+        assert!(!refused.allowed);
+        assert_eq!(refused.limit, 3);
+        // Another origin is unaffected.
+        assert!(l.check_at("b.example", 1, 0).allowed);
+    }
+
+    #[test]
+    fn pdus_are_counted_across_transactions() {
+        let l = limiter();
+        assert!(l.check_at("a.example", 6, 0).allowed);
+        let refused = l.check_at("a.example", 6, 0);
+        assert!(!refused.allowed);
+        assert_eq!(refused.limit, 10);
+        assert!(refused.retry_after.as_secs() >= 1);
+        // Smaller transactions still fit what is left.
+        assert!(l.check_at("a.example", 4, 0).allowed);
+    }
+}
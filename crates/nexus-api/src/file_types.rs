@@ -0,0 +1,220 @@
+//! Which files may be uploaded, and checking that one is what it claims.
+//!
+//! Uploads are served back from the file host (MinIO, or `/files` in lite
+//! mode) with the content type they were uploaded with. Browsers trust that
+//! type, so a file whose bytes don't match it is refused, and so is anything
+//! that looks like HTML, SVG or XML whatever it is declared as: served inline,
+//! a browser would render it and run its scripts on the file host's origin.
+
+/// Content types accepted for attachments. Executables, HTML and SVG are not.
+pub fn is_allowed(content_type: &str) -> bool {
+    !expected(content_type).is_empty()
+}
+
+/// Content types accepted for custom emoji.
+pub fn is_allowed_emoji(content_type: &str) -> bool {
+    matches!(content_type, "image/png" | "image/gif" | "image/webp" | "image/jpeg")
+}
+
+/// What a file's first bytes say it is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Jpeg,
+    Png,
+    Gif,
+    Webp,
+    Avif,
+    Bmp,
+    Tiff,
+    /// ISO base media (`ftyp`): MP4, QuickTime, M4A.
+    IsoMedia,
+    /// EBML: WebM and Matroska.
+    Ebml,
+    Ogg,
+    Wav,
+    Flac,
+    Mp3,
+    /// Raw AAC in ADTS frames.
+    Adts,
+    Pdf,
+    Zip,
+    Tar,
+    /// HTML, SVG or XML — never accepted.
+    Markup,
+    /// UTF-8 text that isn't markup.
+    Text,
+}
+
+/// The kinds a declared content type may contain; empty for types that
+/// aren't allowed.
+fn expected(content_type: &str) -> &'static [Kind] {
+    use Kind::*;
+    match content_type {
+        "image/jpeg" => &[Jpeg],
+        "image/png" => &[Png],
+        "image/gif" => &[Gif],
+        "image/webp" => &[Webp],
+        "image/avif" => &[Avif],
+        "image/bmp" => &[Bmp],
+        "image/tiff" => &[Tiff],
+        "video/mp4" | "video/quicktime" => &[IsoMedia],
+        "video/webm" | "audio/webm" => &[Ebml],
+        "video/ogg" | "audio/ogg" | "audio/opus" => &[Ogg],
+        "audio/mpeg" => &[Mp3],
+        "audio/wav" => &[Wav],
+        "audio/flac" => &[Flac],
+        "audio/aac" => &[Adts, IsoMedia],
+        "application/pdf" => &[Pdf],
+        "application/zip" => &[Zip],
+        "application/x-tar" => &[Tar],
+        "text/plain" | "text/markdown" => &[Text],
+        _ => &[],
+    }
+}
+
+/// Identify a file from its content.
+pub fn sniff(data: &[u8]) -> Option<Kind> {
+    let at = |offset: usize, magic: &[u8]| data.get(offset..offset + magic.len()) == Some(magic);
+
+    if at(0, b"\xFF\xD8\xFF") {
+        return Some(Kind::Jpeg);
+    }
+    if at(0, b"\x89PNG\r\n\x1a\n") {
+        return Some(Kind::Png);
+    }
+    if at(0, b"GIF87a") || at(0, b"GIF89a") {
+        return Some(Kind::Gif);
+    }
+    if at(0, b"RIFF") && at(8, b"WEBP") {
+        return Some(Kind::Webp);
+    }
+    if at(0, b"RIFF") && at(8, b"WAVE") {
+        return Some(Kind::Wav);
+    }
+    if at(4, b"ftyp") {
+        return Some(if at(8, b"avif") || at(8, b"avis") {
+            Kind::Avif
+        } else {
+            Kind::IsoMedia
+        });
+    }
+    if at(0, b"BM") && data.len() >= 26 {
+        return Some(Kind::Bmp);
+    }
+    if at(0, b"II*\0") || at(0, b"MM\0*") {
+        return Some(Kind::Tiff);
+    }
+    if at(0, b"\x1A\x45\xDF\xA3") {
+        return Some(Kind::Ebml);
+    }
+    if at(0, b"OggS") {
+        return Some(Kind::Ogg);
+    }
+    if at(0, b"fLaC") {
+        return Some(Kind::Flac);
+    }
+    if at(0, b"%PDF-") {
+        return Some(Kind::Pdf);
+    }
+    if at(0, b"PK\x03\x04") || at(0, b"PK\x05\x06") {
+        return Some(Kind::Zip);
+    }
+    if at(257, b"ustar") {
+        return Some(Kind::Tar);
+    }
+    if at(0, b"ID3") {
+        // ID3 tags front MP3s, and sometimes raw AAC; the MPEG frame header
+        // after the tag tells them apart, but both are harmless.
+        return Some(Kind::Mp3);
+    }
+    if let [0xFF, second, ..] = *data {
+        // MPEG audio frame sync: layer bits 01 in ADTS, anything else in MP3
+        if second & 0xF6 == 0xF0 {
+            return Some(Kind::Adts);
+        }
+        if second & 0xE0 == 0xE0 && second & 0x06 != 0 {
+            return Some(Kind::Mp3);
+        }
+    }
+
+    let text = std::str::from_utf8(data).ok()?;
+    if text.contains('\0') {
+        return None;
+    }
+    Some(if is_markup(text) { Kind::Markup } else { Kind::Text })
+}
+
+/// Whether text starts like a document a browser would render as HTML or
+/// XML (which covers SVG).
+fn is_markup(text: &str) -> bool {
+    const OPENINGS: &[&str] = &[
+        "<!doctype", "<html", "<head", "<body", "<script", "<iframe", "<svg", "<?xml", "<!--",
+    ];
+    let start = text
+        .trim_start_matches('\u{FEFF}')
+        .trim_start()
+        .chars()
+        .take(16)
+        .collect::<String>()
+        .to_ascii_lowercase();
+    OPENINGS.iter().any(|opening| start.starts_with(opening))
+}
+
+/// Check an upload against its declared content type. The error says why
+/// it was refused.
+pub fn check(content_type: &str, data: &[u8]) -> Result<(), String> {
+    let expected = expected(content_type);
+    if expected.is_empty() {
+        return Err(format!("File type '{content_type}' is not allowed"));
+    }
+    match sniff(data) {
+        Some(Kind::Markup) => Err("HTML, SVG and XML files can't be uploaded".into()),
+        Some(kind) if expected.contains(&kind) => Ok(()),
+        _ => Err(format!("File content doesn't match its type '{content_type}'")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matching_content_passes() {
+        assert_eq!(check("image/png", b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR"), Ok(()));
+        assert_eq!(check("image/jpeg", b"\xFF\xD8\xFF\xE0\0\x10JFIF"), Ok(()));
+        assert_eq!(check("video/mp4", b"\0\0\0\x20ftypisom\0\0\x02\0"), Ok(()));
+        assert_eq!(check("image/avif", b"\0\0\0\x1cftypavif\0\0\0\0"), Ok(()));
+        assert_eq!(check("audio/mpeg", b"ID3\x04\0\0\0\0\0\0"), Ok(()));
+        assert_eq!(check("audio/mpeg", b"\xFF\xFB\x90\x64"), Ok(()));
+        assert_eq!(check("audio/aac", b"\xFF\xF1\x50\x80"), Ok(()));
+        assert_eq!(check("text/markdown", "# Notes\n\n- café".as_bytes()), Ok(()));
+    }
+
+    #[test]
+    fn mismatched_content_is_refused() {
+        assert!(check("image/png", b"\xFF\xD8\xFF\xE0").is_err());
+        assert!(check("application/pdf", b"MZ\x90\0\x03").is_err());
+        assert!(check("text/plain", b"\x7FELF\x02\x01\x01\0").is_err());
+        assert!(check("image/gif", b"").is_err());
+    }
+
+    #[test]
+    fn markup_is_refused_whatever_it_claims() {
+        let svg = b"<?xml version=\"1.0\"?><svg xmlns=\"http://www.w3.org/2000/svg\"><script>alert(1)</script></svg>";
+        assert_eq!(sniff(svg), Some(Kind::Markup));
+        assert!(check("text/plain", svg).is_err());
+        assert!(check("text/plain", b"\xEF\xBB\xBF  <!DOCTYPE html><p>hi").is_err());
+        assert!(check("text/markdown", b"\n<HTML><body onload=alert(1)>").is_err());
+        // Markup further down a text file isn't sniffed as HTML
+        assert_eq!(check("text/markdown", b"Example:\n\n<b>bold</b>"), Ok(()));
+    }
+
+    #[test]
+    fn dangerous_types_are_not_allowed() {
+        assert!(!is_allowed("image/svg+xml"));
+        assert!(!is_allowed("text/html"));
+        assert!(!is_allowed("application/x-msdownload"));
+        assert!(is_allowed("image/webp"));
+        assert!(!is_allowed_emoji("image/svg+xml"));
+    }
+}
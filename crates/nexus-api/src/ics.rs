@@ -0,0 +1,180 @@
+//! iCalendar (RFC 5545) rendering for scheduled events, plus
+//! "Add to Calendar" deep links for the common web calendars.
+
+use chrono::{DateTime, Duration, Utc};
+use nexus_db::repository::scheduled_events::{ScheduledEventRow, STATUS_CANCELLED};
+
+/// Assumed length of events created without an end time.
+const DEFAULT_EVENT_LENGTH: Duration = Duration::hours(1);
+/// RFC 5545 §3.1: content lines SHOULD NOT exceed 75 octets.
+const MAX_LINE_OCTETS: usize = 75;
+
+/// Render a full `VCALENDAR` containing `events`.
+///
+/// `server_name` becomes the UID domain so event identities stay stable
+/// across feed refreshes; `calendar_name` is shown by subscribing clients.
+pub fn render_calendar(server_name: &str, calendar_name: &str, events: &[ScheduledEventRow]) -> String {
+    let now = Utc::now();
+    let mut out = String::new();
+    push_line(&mut out, "BEGIN:VCALENDAR");
+    push_line(&mut out, "VERSION:2.0");
+    push_line(&mut out, "PRODID:-//Nexus//Scheduled Events//EN");
+    push_line(&mut out, "CALSCALE:GREGORIAN");
+    push_line(&mut out, "METHOD:PUBLISH");
+    push_line(&mut out, &format!("X-WR-CALNAME:{}", escape_text(calendar_name)));
+
+    for event in events {
+        push_line(&mut out, "BEGIN:VEVENT");
+        push_line(&mut out, &format!("UID:{}@{}", event.id, server_name));
+        push_line(&mut out, &format!("DTSTAMP:{}", format_utc(now)));
+        push_line(&mut out, &format!("DTSTART:{}", format_utc(event.start_time)));
+        push_line(&mut out, &format!("DTEND:{}", format_utc(end_time(event))));
+        push_line(&mut out, &format!("LAST-MODIFIED:{}", format_utc(event.updated_at)));
+        push_line(&mut out, &format!("SUMMARY:{}", escape_text(&event.name)));
+        if let Some(description) = event.description.as_deref().filter(|d| !d.is_empty()) {
+            push_line(&mut out, &format!("DESCRIPTION:{}", escape_text(description)));
+        }
+        if let Some(location) = event.location.as_deref().filter(|l| !l.is_empty()) {
+            push_line(&mut out, &format!("LOCATION:{}", escape_text(location)));
+        }
+        let status = if event.status == STATUS_CANCELLED { "CANCELLED" } else { "CONFIRMED" };
+        push_line(&mut out, &format!("STATUS:{status}"));
+        push_line(&mut out, "END:VEVENT");
+    }
+
+    push_line(&mut out, "END:VCALENDAR");
+    out
+}
+
+/// Google Calendar "create event" template link.
+pub fn google_calendar_url(event: &ScheduledEventRow) -> String {
+    let dates = format!("{}/{}", format_utc(event.start_time), format_utc(end_time(event)));
+    let mut query = url::form_urlencoded::Serializer::new(String::new());
+    query
+        .append_pair("action", "TEMPLATE")
+        .append_pair("text", &event.name)
+        .append_pair("dates", &dates);
+    if let Some(description) = &event.description {
+        query.append_pair("details", description);
+    }
+    if let Some(location) = &event.location {
+        query.append_pair("location", location);
+    }
+    format!("https://calendar.google.com/calendar/render?{}", query.finish())
+}
+
+/// Outlook.com "compose event" deep link.
+pub fn outlook_calendar_url(event: &ScheduledEventRow) -> String {
+    let mut query = url::form_urlencoded::Serializer::new(String::new());
+    query
+        .append_pair("path", "/calendar/action/compose")
+        .append_pair("rru", "addevent")
+        .append_pair("subject", &event.name)
+        .append_pair("startdt", &event.start_time.to_rfc3339())
+        .append_pair("enddt", &end_time(event).to_rfc3339());
+    if let Some(description) = &event.description {
+        query.append_pair("body", description);
+    }
+    if let Some(location) = &event.location {
+        query.append_pair("location", location);
+    }
+    format!("https://outlook.live.com/calendar/0/deeplink/compose?{}", query.finish())
+}
+
+fn end_time(event: &ScheduledEventRow) -> DateTime<Utc> {
+    event.end_time.unwrap_or(event.start_time + DEFAULT_EVENT_LENGTH)
+}
+
+/// `DATE-TIME` in UTC form, e.g. `20260301T180000Z`.
+fn format_utc(t: DateTime<Utc>) -> String {
+    t.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// Escape a `TEXT` value (RFC 5545 §3.3.11).
+fn escape_text(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            ';' => out.push_str("\\;"),
+            ',' => out.push_str("\\,"),
+            '\n' => out.push_str("\\n"),
+            '\r' => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Append a content line, folding at 75 octets without splitting UTF-8
+/// sequences, terminated by CRLF.
+fn push_line(out: &mut String, line: &str) {
+    let mut width = 0;
+    for c in line.chars() {
+        // Continuation lines start with a space, which counts towards the limit.
+        if width + c.len_utf8() > MAX_LINE_OCTETS {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(c);
+        width += c.len_utf8();
+    }
+    out.push_str("\r\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use uuid::Uuid;
+
+    fn event() -> ScheduledEventRow {
+        let start = Utc.with_ymd_and_hms(2026, 3, 1, 18, 0, 0).unwrap();
+        ScheduledEventRow {
+            id: Uuid::nil(),
+            server_id: Uuid::nil(),
+            channel_id: None,
+            creator_id: None,
+            name: "Game night; bring snacks, please".into(),
+            description: Some("Line one\nLine two".into()),
+            location: None,
+            start_time: start,
+            end_time: None,
+            status: "scheduled".into(),
+            created_at: start,
+            updated_at: start,
+        }
+    }
+
+    #[test]
+    fn test_render_calendar() {
+        let ics = render_calendar("nexus.example.com", "Test Server", &[event()]);
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+        assert!(ics.contains("UID:00000000-0000-0000-0000-000000000000@nexus.example.com\r\n"));
+        assert!(ics.contains("DTSTART:20260301T180000Z\r\n"));
+        // No end time → default one-hour event.
+        assert!(ics.contains("DTEND:20260301T190000Z\r\n"));
+        assert!(ics.contains("SUMMARY:Game night\\; bring snacks\\, please\r\n"));
+        assert!(ics.contains("DESCRIPTION:Line one\\nLine two\r\n"));
+    }
+
+    #[test]
+    fn test_line_folding() {
+        let mut out = String::new();
+        let long = format!("SUMMARY:{}", "é".repeat(60));
+        push_line(&mut out, &long);
+        for line in out.split("\r\n").filter(|l| !l.is_empty()) {
+            assert!(line.len() <= MAX_LINE_OCTETS);
+        }
+        let unfolded = out.replace("\r\n ", "");
+        assert_eq!(unfolded, format!("{long}\r\n"));
+    }
+
+    #[test]
+    fn test_google_url() {
+        let url = google_calendar_url(&event());
+        assert!(url.starts_with("https://calendar.google.com/calendar/render?action=TEMPLATE"));
+        assert!(url.contains("dates=20260301T180000Z%2F20260301T190000Z"));
+    }
+}
@@ -0,0 +1,62 @@
+//! Ban list sync job — refreshes every ban list subscription hourly.
+//!
+//! Sync only ever adds bans. Lifting a ban on the source does not lift it
+//! on subscribers; moderators do that themselves.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use nexus_db::repository::bans::{self, BanSubscriptionRow};
+
+use crate::{ban_lists, AppState};
+
+/// How often every subscription is re-synced.
+const SYNC_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+pub fn spawn(state: Arc<AppState>) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(SYNC_INTERVAL);
+        loop {
+            ticker.tick().await;
+            run_once(&state).await;
+        }
+    })
+}
+
+/// Sync every subscription once.
+pub async fn run_once(state: &AppState) {
+    let subs = match bans::list_all_subscriptions(&state.db.pool).await {
+        Ok(subs) => subs,
+        Err(e) => {
+            tracing::warn!(error = %e, "Failed to list ban list subscriptions");
+            return;
+        }
+    };
+    for sub in &subs {
+        sync_one(state, sub).await;
+    }
+}
+
+/// Sync a single subscription and record the outcome on it.
+pub async fn sync_one(state: &AppState, sub: &BanSubscriptionRow) {
+    let error = match ban_lists::sync(state, sub).await {
+        Ok(summary) => {
+            if summary.imported > 0 {
+                tracing::info!(
+                    subscription_id = %sub.id,
+                    server_id = %sub.server_id,
+                    imported = summary.imported,
+                    "Ban list subscription synced"
+                );
+            }
+            None
+        }
+        Err(e) => {
+            tracing::warn!(subscription_id = %sub.id, error = %e, "Ban list sync failed");
+            Some(e)
+        }
+    };
+    if let Err(e) = bans::record_sync(&state.db.pool, sub.id, error.as_deref()).await {
+        tracing::warn!(subscription_id = %sub.id, error = %e, "Failed to record ban list sync");
+    }
+}
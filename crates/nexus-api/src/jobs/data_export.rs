@@ -0,0 +1,209 @@
+//! Data export job — builds the archives users request with
+//! `POST /users/@me/export` and deletes them once they expire.
+//!
+//! An archive is a zip of:
+//! - `profile.json` — the account, as `GET /users/@me` returns it
+//! - `messages.json` — messages the user sent outside DMs, newest first
+//! - `dms/<channel_id>.json` — each DM and group DM, with both sides
+//! - `uploads/<attachment_id>-<filename>` — every file the user uploaded
+//!
+//! It is built in memory and stored under `exports/<user_id>/`, where it
+//! stays for `exports.retention_hours` or until it is downloaded.
+
+use std::io::{Cursor, Write};
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::Utc;
+use nexus_common::models::user::UserResponse;
+use nexus_db::repository::{attachments, channels, data_exports, messages, messages::MessageRow, users};
+use uuid::Uuid;
+use zip::write::SimpleFileOptions;
+use zip::ZipWriter;
+
+use crate::routes::messages::attachment_json;
+use crate::AppState;
+
+/// How often the queue is polled when idle.
+const POLL_INTERVAL: Duration = Duration::from_secs(10);
+/// Exports claimed per poll. Archives are held in memory while built.
+const BATCH_SIZE: i64 = 2;
+/// Rows read per query while paging through messages and uploads.
+const PAGE_SIZE: i64 = 100;
+
+/// Spawn the export worker on its own task.
+pub fn spawn(state: Arc<AppState>) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        // Anything still `processing` belonged to a previous process.
+        match data_exports::requeue_stale(&state.db.pool).await {
+            Ok(0) => {}
+            Ok(n) => tracing::info!(count = n, "Requeued interrupted data exports"),
+            Err(e) => tracing::warn!(error = %e, "Failed to requeue interrupted data exports"),
+        }
+
+        let mut ticker = tokio::time::interval(POLL_INTERVAL);
+        loop {
+            ticker.tick().await;
+            expire(&state).await;
+            run_once(&state).await;
+        }
+    })
+}
+
+/// Claim and build one batch of pending exports.
+pub async fn run_once(state: &AppState) {
+    let batch = match data_exports::claim_pending(&state.db.pool, BATCH_SIZE).await {
+        Ok(batch) => batch,
+        Err(e) => {
+            tracing::warn!(error = %e, "Failed to claim pending data exports");
+            return;
+        }
+    };
+
+    for export in batch {
+        if let Err(e) = process(state, export.id, export.user_id).await {
+            tracing::warn!(export_id = %export.id, user_id = %export.user_id, error = %e, "Data export failed");
+            if let Err(e) = data_exports::fail(&state.db.pool, export.id, &e.to_string()).await {
+                tracing::warn!(export_id = %export.id, error = %e, "Failed to record data export failure");
+            }
+        }
+    }
+}
+
+/// Delete the archives of exports that expired undownloaded.
+async fn expire(state: &AppState) {
+    let expired = match data_exports::expire(&state.db.pool).await {
+        Ok(expired) => expired,
+        Err(e) => {
+            tracing::warn!(error = %e, "Failed to expire data exports");
+            return;
+        }
+    };
+    for export in expired {
+        let Some(key) = export.storage_key else { continue };
+        if let Err(e) = state.storage.delete_object(&key).await {
+            tracing::warn!(export_id = %export.id, error = %e, "Failed to delete expired data export");
+        }
+    }
+}
+
+async fn process(state: &AppState, export_id: Uuid, user_id: Uuid) -> anyhow::Result<()> {
+    let pool = &state.db.pool;
+    let user = users::find_by_id(pool, user_id)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("user {user_id} no longer exists"))?;
+
+    let mut archive = ZipWriter::new(Cursor::new(Vec::new()));
+    let json = SimpleFileOptions::default();
+    // Uploads are mostly compressed already
+    let stored = SimpleFileOptions::default().compression_method(zip::CompressionMethod::Stored);
+
+    archive.start_file("profile.json", json)?;
+    serde_json::to_writer_pretty(&mut archive, &UserResponse::own(user))?;
+    data_exports::set_progress(pool, export_id, 5).await?;
+
+    let dms = channels::list_dm_channels(pool, user_id).await?;
+    let mut sent = Vec::new();
+    let mut before = None;
+    loop {
+        let page = messages::list_by_author(pool, user_id, before, PAGE_SIZE).await?;
+        before = page.last().map(|m| m.id);
+        let done = (page.len() as i64) < PAGE_SIZE;
+        sent.extend(
+            page.iter()
+                .filter(|m| !dms.iter().any(|dm| dm.id == m.channel_id))
+                .map(message_json),
+        );
+        if done {
+            break;
+        }
+    }
+    archive.start_file("messages.json", json)?;
+    serde_json::to_writer_pretty(&mut archive, &sent)?;
+    data_exports::set_progress(pool, export_id, 30).await?;
+
+    for (i, dm) in dms.iter().enumerate() {
+        let participants = channels::list_dm_participants(pool, dm.id).await?;
+        let mut history = Vec::new();
+        let mut before = None;
+        loop {
+            let page = messages::list_channel_messages(pool, dm.id, before, None, PAGE_SIZE).await?;
+            before = page.last().map(|m| m.id);
+            let done = (page.len() as i64) < PAGE_SIZE;
+            history.extend(page.iter().map(message_json));
+            if done {
+                break;
+            }
+        }
+        archive.start_file(format!("dms/{}.json", dm.id), json)?;
+        serde_json::to_writer_pretty(
+            &mut archive,
+            &serde_json::json!({
+                "channel": dm,
+                "participants": participants,
+                "messages": history,
+            }),
+        )?;
+        let progress = 30 + 30 * (i + 1) / dms.len();
+        data_exports::set_progress(pool, export_id, progress as i32).await?;
+    }
+
+    let mut uploads = Vec::new();
+    let mut before = None;
+    loop {
+        let page = attachments::list_for_uploader(pool, user_id, PAGE_SIZE, before).await?;
+        before = page.last().map(|a| a.id);
+        let done = (page.len() as i64) < PAGE_SIZE;
+        uploads.extend(page);
+        if done {
+            break;
+        }
+    }
+    for (i, upload) in uploads.iter().enumerate() {
+        // Purged or never finished uploading
+        let Some(data) = state.storage.get_object(&upload.storage_key).await? else {
+            continue;
+        };
+        archive.start_file(format!("uploads/{}-{}", upload.id, archive_name(&upload.filename)), stored)?;
+        archive.write_all(&data)?;
+        let progress = 60 + 35 * (i + 1) / uploads.len();
+        data_exports::set_progress(pool, export_id, progress as i32).await?;
+    }
+    archive.start_file("uploads.json", json)?;
+    serde_json::to_writer_pretty(&mut archive, &uploads.iter().map(attachment_json).collect::<Vec<_>>())?;
+
+    let data = archive.finish()?.into_inner();
+    let size = data.len() as i64;
+    let key = format!("exports/{user_id}/{export_id}.zip");
+    state.storage.put_object(&key, data, "application/zip").await?;
+
+    let retention = nexus_common::config::get().exports.retention_hours;
+    let expires_at = Utc::now() + chrono::Duration::hours(retention.into());
+    if !data_exports::complete(pool, export_id, &key, size, expires_at).await? {
+        // The account was deleted while the archive was being built
+        state.storage.delete_object(&key).await?;
+        return Ok(());
+    }
+    tracing::info!(%export_id, %user_id, size, "Data export completed");
+    Ok(())
+}
+
+fn message_json(m: &MessageRow) -> serde_json::Value {
+    serde_json::json!({
+        "id": m.id,
+        "channel_id": m.channel_id,
+        "author_id": m.author_id,
+        "content": m.content,
+        "created_at": m.created_at,
+        "edited_at": m.edited_at,
+        "attachments": m.attachments,
+        "embeds": m.embeds,
+        "reference_message_id": m.reference_message_id,
+        "thread_id": m.thread_id,
+    })
+}
+
+/// `filename` without path separators, so it can't escape `uploads/`.
+fn archive_name(filename: &str) -> String {
+    filename.replace(['/', '\\'], "_")
+}
@@ -0,0 +1,118 @@
+//! Federated profile refresh — re-fetches the display name and avatar of
+//! recently active remote users from their home servers.
+//!
+//! `federated_users` is otherwise only written when a PDU arrives, so a remote
+//! user who renames themselves keeps their old name here until they next join
+//! a room. Refreshes are spread out with jitter so a burst of new users doesn't
+//! turn into a burst of requests every interval, and failures back off
+//! exponentially. When a profile changes, `PROFILE_UPDATE` is sent to every
+//! local server where the user has posted.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::Utc;
+use nexus_common::gateway_event::{event_types, GatewayEvent};
+use nexus_db::repository::federated_users::{self, FederatedUserRow};
+use rand::Rng;
+
+use crate::AppState;
+
+/// How often due profiles are looked for.
+const POLL_INTERVAL: Duration = Duration::from_secs(60);
+/// Profiles fetched per poll.
+const BATCH_SIZE: i64 = 25;
+/// Only users with a PDU in this window are kept fresh.
+const ACTIVE_WINDOW_DAYS: i64 = 30;
+/// Time between refreshes of a reachable profile, ± `JITTER_PERCENT`.
+const REFRESH_INTERVAL_SECS: i64 = 6 * 60 * 60;
+const JITTER_PERCENT: i64 = 20;
+/// First retry delay; doubles per consecutive failure up to `MAX_BACKOFF_SECS`.
+const BASE_BACKOFF_SECS: i64 = 5 * 60;
+const MAX_BACKOFF_SECS: i64 = 24 * 60 * 60;
+
+/// Spawn the refresh job on its own task.
+pub fn spawn(state: Arc<AppState>) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(POLL_INTERVAL);
+        loop {
+            ticker.tick().await;
+            run_once(&state).await;
+        }
+    })
+}
+
+/// Refresh every due profile once.
+pub async fn run_once(state: &AppState) {
+    let pool = &state.db.pool;
+    let active_since = Utc::now() - chrono::Duration::days(ACTIVE_WINDOW_DAYS);
+    let due = match federated_users::list_due_for_refresh(pool, active_since, BATCH_SIZE).await {
+        Ok(rows) => rows,
+        Err(e) => {
+            tracing::warn!(error = %e, "Failed to load federated profiles due for refresh");
+            return;
+        }
+    };
+
+    for user in due {
+        let Err(e) = refresh(state, &user).await else {
+            continue;
+        };
+        let backoff = (BASE_BACKOFF_SECS << user.refresh_failures.min(16)).min(MAX_BACKOFF_SECS);
+        tracing::debug!(mxid = %user.mxid, error = %e, backoff, "Federated profile refresh failed");
+        let retry_at = Utc::now() + chrono::Duration::seconds(jittered(backoff));
+        if let Err(e) = federated_users::record_refresh_failure(pool, user.id, retry_at).await {
+            tracing::warn!(mxid = %user.mxid, error = %e, "Failed to record federated profile failure");
+        }
+    }
+}
+
+/// Fetch one profile, store it, and announce it if it changed.
+async fn refresh(state: &AppState, user: &FederatedUserRow) -> anyhow::Result<()> {
+    let Some((_, server)) = user.mxid.split_once(':') else {
+        anyhow::bail!("malformed MXID");
+    };
+    let profile = state.federation_client.get_user_profile(server, &user.mxid).await?;
+    // A server only speaks for its own users.
+    if profile.user_id != user.mxid {
+        anyhow::bail!("profile is for {}", profile.user_id);
+    }
+
+    let next = Utc::now() + chrono::Duration::seconds(jittered(REFRESH_INTERVAL_SECS));
+    federated_users::record_refresh(
+        &state.db.pool,
+        user.id,
+        profile.displayname.as_deref(),
+        profile.avatar_url.as_deref(),
+        next,
+    )
+    .await?;
+
+    if profile.displayname == user.display_name && profile.avatar_url == user.avatar_url {
+        return Ok(());
+    }
+    tracing::debug!(mxid = %user.mxid, "Federated profile changed");
+    let data = serde_json::json!({
+        "id": user.id,
+        "user_id": user.mxid,
+        "display_name": profile.displayname,
+        "avatar_url": profile.avatar_url,
+    });
+    for server_id in federated_users::list_local_servers(&state.db.pool, &user.mxid).await? {
+        let _ = state.gateway_tx.send(GatewayEvent {
+            event_type: event_types::PROFILE_UPDATE.into(),
+            data: data.clone(),
+            server_id: Some(server_id),
+            channel_id: None,
+            user_id: None,
+            trace_context: None,
+        });
+    }
+    Ok(())
+}
+
+/// `secs` spread by up to ± `JITTER_PERCENT`.
+fn jittered(secs: i64) -> i64 {
+    let spread = secs * JITTER_PERCENT / 100;
+    secs + rand::rng().random_range(-spread..=spread)
+}
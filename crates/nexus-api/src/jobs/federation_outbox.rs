@@ -0,0 +1,107 @@
+//! Federation outbox worker — delivers queued ActivityPub activities to
+//! remote inboxes, retrying failures with exponential backoff.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::Utc;
+use nexus_common::shutdown::Shutdown;
+use nexus_db::repository::federation_outbox;
+use nexus_federation::ActivityPubBridge;
+
+use crate::AppState;
+
+/// How often the outbox is polled.
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+/// Deliveries attempted per poll.
+const BATCH_SIZE: i64 = 50;
+/// First retry delay; doubles per attempt up to `MAX_BACKOFF_SECS`.
+const BASE_BACKOFF_SECS: i64 = 30;
+const MAX_BACKOFF_SECS: i64 = 6 * 60 * 60;
+/// Delivered rows are kept this long for debugging before being pruned.
+const RETENTION_DAYS: i64 = 7;
+
+/// Spawn the outbox worker on its own task. Once `shutdown` is triggered it
+/// makes one last pass over what is due and exits; await the handle to let
+/// it finish.
+pub fn spawn(
+    state: Arc<AppState>,
+    bridge: Arc<ActivityPubBridge>,
+    shutdown: Shutdown,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(POLL_INTERVAL);
+        let mut ticks: u64 = 0;
+        let stopping = shutdown.triggered();
+        tokio::pin!(stopping);
+        loop {
+            tokio::select! {
+                _ = ticker.tick() => {}
+                () = &mut stopping => {
+                    run_once(&state, &bridge).await;
+                    tracing::info!("Federation outbox flushed");
+                    return;
+                }
+            }
+            run_once(&state, &bridge).await;
+
+            ticks += 1;
+            // Roughly hourly.
+            if ticks % 720 == 0 {
+                let before = Utc::now() - chrono::Duration::days(RETENTION_DAYS);
+                if let Err(e) = federation_outbox::prune_delivered(&state.db.pool, before).await {
+                    tracing::warn!(error = %e, "Failed to prune federation outbox");
+                }
+            }
+        }
+    })
+}
+
+/// Attempt every due delivery once.
+pub async fn run_once(state: &AppState, bridge: &ActivityPubBridge) {
+    let pool = &state.db.pool;
+    let max_attempts = nexus_common::config::get().activitypub.max_delivery_attempts as i32;
+    let due = match federation_outbox::list_due(pool, BATCH_SIZE).await {
+        Ok(rows) => rows,
+        Err(e) => {
+            tracing::warn!(error = %e, "Failed to load federation outbox");
+            return;
+        }
+    };
+
+    for row in due {
+        // Only ActivityPub deliveries (addressed by inbox URL) go through the
+        // outbox today.
+        if !row.destination.starts_with("https://") {
+            continue;
+        }
+
+        let result = match bridge.deliver(&row.destination, &row.payload).await {
+            Ok(()) => federation_outbox::mark_delivered(pool, row.id).await,
+            Err(e) if row.attempts + 1 >= max_attempts => {
+                tracing::warn!(
+                    destination = %row.destination,
+                    txn_id = %row.txn_id,
+                    error = %e,
+                    "Dropping federation delivery after {} attempts",
+                    row.attempts + 1
+                );
+                federation_outbox::discard(pool, row.id).await
+            }
+            Err(e) => {
+                let backoff = (BASE_BACKOFF_SECS << row.attempts.min(16)).min(MAX_BACKOFF_SECS);
+                tracing::debug!(destination = %row.destination, error = %e, backoff, "Federation delivery failed");
+                federation_outbox::mark_failed(
+                    pool,
+                    row.id,
+                    &e.to_string(),
+                    Utc::now() + chrono::Duration::seconds(backoff),
+                )
+                .await
+            }
+        };
+        if let Err(e) = result {
+            tracing::warn!(outbox_id = %row.id, error = %e, "Failed to update federation outbox row");
+        }
+    }
+}
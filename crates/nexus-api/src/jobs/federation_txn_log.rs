@@ -0,0 +1,39 @@
+//! Federation transaction log pruning — hourly, forgets inbound transaction
+//! IDs older than `federation.acceptance_window_secs` and trims the log to
+//! `federation.txn_log_max_rows`.
+//!
+//! Events older than the window are rejected on receipt, so a transaction
+//! replayed after its ID is forgotten has nothing left to apply.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::Utc;
+use nexus_db::repository::federation_txn_log;
+
+use crate::AppState;
+
+/// How often the log is pruned.
+const PRUNE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+pub fn spawn(state: Arc<AppState>) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(PRUNE_INTERVAL);
+        loop {
+            ticker.tick().await;
+            run_once(&state).await;
+        }
+    })
+}
+
+/// Prune the log once.
+pub async fn run_once(state: &AppState) {
+    let cfg = &nexus_common::config::get().federation;
+    let cutoff = Utc::now() - chrono::Duration::seconds(cfg.acceptance_window_secs as i64);
+    let max_rows = i64::try_from(cfg.txn_log_max_rows).unwrap_or(i64::MAX);
+    match federation_txn_log::prune(&state.db.pool, cutoff, max_rows).await {
+        Ok(0) => {}
+        Ok(pruned) => tracing::info!(pruned, "Pruned federation transaction log"),
+        Err(e) => tracing::warn!(error = %e, "Failed to prune federation transaction log"),
+    }
+}
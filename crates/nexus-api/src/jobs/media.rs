@@ -0,0 +1,156 @@
+//! Media job — drains the `media_jobs` queue, storing a thumbnail and the
+//! display size and blurhash of each image attachment.
+//!
+//! Attachments are usable as soon as they are uploaded; this only fills in
+//! the derived assets. Once an image linked to a message is processed, the
+//! message's attachment list is refreshed and `ATTACHMENT_UPDATE` is sent.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use nexus_common::gateway_event::{event_types, GatewayEvent};
+use nexus_common::models::rich::AttachmentRow;
+use nexus_db::repository::{attachments, channels, media_jobs, messages};
+use uuid::Uuid;
+
+use crate::media::MediaProcessor;
+use crate::routes::messages::attachment_json;
+use crate::AppState;
+
+/// How often the queue is polled when idle.
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+/// Jobs claimed per poll.
+const BATCH_SIZE: i64 = 8;
+/// Lifetime of the presigned thumbnail URL, matching the original's.
+const THUMBNAIL_URL_EXPIRY_SECS: u64 = 3600 * 24 * 7;
+
+/// Spawn the media worker on its own task.
+pub fn spawn(state: Arc<AppState>, processor: MediaProcessor) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        // Anything still `processing` belonged to a previous process.
+        match media_jobs::requeue_stale(&state.db.pool).await {
+            Ok(0) => {}
+            Ok(n) => tracing::info!(count = n, "Requeued interrupted media jobs"),
+            Err(e) => tracing::warn!(error = %e, "Failed to requeue interrupted media jobs"),
+        }
+
+        let mut ticker = tokio::time::interval(POLL_INTERVAL);
+        loop {
+            ticker.tick().await;
+            run_once(&state, &processor).await;
+        }
+    })
+}
+
+/// Claim and process one batch of pending media jobs.
+pub async fn run_once(state: &AppState, processor: &MediaProcessor) {
+    let max_attempts = nexus_common::config::get().media.max_attempts as i32;
+    let batch = match media_jobs::claim_pending(&state.db.pool, BATCH_SIZE).await {
+        Ok(batch) => batch,
+        Err(e) => {
+            tracing::warn!(error = %e, "Failed to claim pending media jobs");
+            return;
+        }
+    };
+
+    for job in batch {
+        match process(state, processor, job.attachment_id).await {
+            Ok(()) => {
+                if let Err(e) = media_jobs::complete(&state.db.pool, job.id).await {
+                    tracing::warn!(job_id = %job.id, error = %e, "Failed to complete media job");
+                }
+            }
+            Err(e) => {
+                tracing::warn!(
+                    attachment_id = %job.attachment_id,
+                    attempt = job.attempts,
+                    error = %e,
+                    "Media processing failed"
+                );
+                if let Err(e) =
+                    media_jobs::fail(&state.db.pool, job.id, &e.to_string(), max_attempts).await
+                {
+                    tracing::warn!(job_id = %job.id, error = %e, "Failed to record media job failure");
+                }
+            }
+        }
+    }
+}
+
+async fn process(state: &AppState, processor: &MediaProcessor, attachment_id: Uuid) -> anyhow::Result<()> {
+    let pool = &state.db.pool;
+    // Deleted since it was queued — nothing left to do.
+    let Some(attachment) = attachments::find_by_id(pool, attachment_id).await? else {
+        return Ok(());
+    };
+    let data = state
+        .storage
+        .get_object(&attachment.storage_key)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("image object '{}' is missing", attachment.storage_key))?;
+    let image = processor.process(&data, &attachment.content_type).await?;
+
+    let (thumbnail_key, thumbnail_url) = match image.thumbnail {
+        Some(thumbnail) => {
+            let key = format!(
+                "thumbnails/{}/{}.{}",
+                attachment.uploader_id, attachment.id, thumbnail.extension
+            );
+            state
+                .storage
+                .put_object(&key, thumbnail.data, thumbnail.content_type)
+                .await?;
+            let url = state
+                .storage
+                .presigned_get_url(&key, THUMBNAIL_URL_EXPIRY_SECS)
+                .await
+                .ok();
+            (Some(key), url)
+        }
+        None => (None, None),
+    };
+
+    let row = attachments::set_media_metadata(
+        pool,
+        attachment.id,
+        image.width as i32,
+        image.height as i32,
+        Some(&image.blurhash),
+        thumbnail_key.as_deref(),
+        thumbnail_url.as_deref(),
+    )
+    .await?;
+    tracing::debug!(attachment_id = %row.id, "Media processed");
+
+    if row.message_id.is_some() {
+        publish_attachment_update(state, &row).await?;
+    }
+    Ok(())
+}
+
+/// Refresh the parent message's attachment list and tell its channel.
+async fn publish_attachment_update(state: &AppState, row: &AttachmentRow) -> anyhow::Result<()> {
+    let pool = &state.db.pool;
+    let Some(message_id) = row.message_id else {
+        return Ok(());
+    };
+    let linked = attachments::list_for_message(pool, message_id).await?;
+    let json = serde_json::Value::Array(linked.iter().map(attachment_json).collect());
+    let message = messages::set_attachments(pool, message_id, &json).await?;
+
+    let server_id = channels::find_by_id(pool, message.channel_id)
+        .await?
+        .and_then(|c| c.server_id);
+    let mut data = attachment_json(row);
+    data["message_id"] = serde_json::json!(message_id);
+    data["channel_id"] = serde_json::json!(message.channel_id);
+    let _ = state.gateway_tx.send(GatewayEvent {
+        event_type: event_types::ATTACHMENT_UPDATE.into(),
+        data,
+        server_id,
+        channel_id: Some(message.channel_id),
+        user_id: None,
+        trace_context: None,
+    });
+    Ok(())
+}
@@ -0,0 +1,57 @@
+//! Message retention job — hourly, permanently removes messages that
+//! deletion or a server's retention policy has expired.
+//!
+//! Deleted messages are kept for `limits.deleted_message_retention_days` so
+//! moderators can review them, then purged everywhere. Servers with
+//! `message_retention_days` set also lose every message older than that,
+//! deleted or not.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::Utc;
+use nexus_db::repository::{messages, servers};
+
+use crate::AppState;
+
+/// How often expired messages are purged.
+const PURGE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+pub fn spawn(state: Arc<AppState>) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(PURGE_INTERVAL);
+        loop {
+            ticker.tick().await;
+            run_once(&state).await;
+        }
+    })
+}
+
+/// Purge everything that has expired as of now.
+pub async fn run_once(state: &AppState) {
+    let pool = &state.db.pool;
+    let now = Utc::now();
+
+    let grace_days = nexus_common::config::get().limits.deleted_message_retention_days;
+    match messages::purge_deleted(pool, now - chrono::Duration::days(grace_days.into())).await {
+        Ok(0) => {}
+        Ok(purged) => tracing::info!(purged, "Purged deleted messages"),
+        Err(e) => tracing::warn!(error = %e, "Failed to purge deleted messages"),
+    }
+
+    let policies = match servers::list_retention_policies(pool).await {
+        Ok(policies) => policies,
+        Err(e) => {
+            tracing::warn!(error = %e, "Failed to list message retention policies");
+            return;
+        }
+    };
+    for (server_id, days) in policies {
+        let cutoff = now - chrono::Duration::days(days.into());
+        match messages::purge_server_messages(pool, server_id, cutoff).await {
+            Ok(0) => {}
+            Ok(purged) => tracing::info!(%server_id, days, purged, "Purged messages past retention"),
+            Err(e) => tracing::warn!(%server_id, error = %e, "Failed to enforce message retention"),
+        }
+    }
+}
@@ -0,0 +1,17 @@
+//! Background jobs — periodic tasks spawned alongside the API server.
+//!
+//! Each job owns a clone of the shared [`AppState`](crate::AppState) and runs
+//! on its own Tokio task for the lifetime of the process.
+
+pub mod ban_list_sync;
+pub mod data_export;
+pub mod federated_profiles;
+pub mod federation_outbox;
+pub mod federation_txn_log;
+pub mod media;
+pub mod message_retention;
+pub mod push;
+pub mod reminders;
+pub mod security_webhooks;
+pub mod status_check;
+pub mod transcription;
@@ -0,0 +1,227 @@
+//! Push notification job — turns `MESSAGE_CREATE` events into pushes for
+//! recipients who have no live gateway session.
+//!
+//! Recipients are the other participants of a DM or group DM, and users
+//! directly @mentioned in a server channel they can see. Silent messages
+//! never push, and nor does anything from a user the recipient blocked or
+//! in a channel they muted. Each recipient's [`PushPreferences`] decide
+//! what is sent.
+//!
+//! Presence comes from the caller (the gateway's session manager), so in a
+//! split deployment this job must run next to the gateway it asks.
+
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::Arc;
+
+use nexus_common::{
+    gateway_event::GatewayEvent,
+    models::{
+        channel::{Channel, ChannelType},
+        message::MessageFlags,
+        push::PushPreferences,
+    },
+    permissions::Permissions,
+    timestamps::{self, ViewerClock},
+};
+use nexus_db::repository::{channels, push, read_states};
+use tokio::sync::broadcast::error::RecvError;
+use uuid::Uuid;
+
+use crate::{
+    push::{Delivery, Notification, PushClient},
+    AppState,
+};
+
+/// Longest message excerpt put in a notification body.
+const BODY_PREVIEW_CHARS: usize = 200;
+
+/// Spawn the push job on its own task. `is_online` reports whether a user
+/// currently has a gateway session.
+pub fn spawn<F, Fut>(
+    state: Arc<AppState>,
+    client: PushClient,
+    is_online: F,
+) -> tokio::task::JoinHandle<()>
+where
+    F: Fn(Uuid) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = bool> + Send,
+{
+    let is_online = Arc::new(is_online);
+    // Only this node's messages: every API node runs this job, and relayed
+    // events would be notified once per node.
+    let mut events = state.gateway_tx.subscribe_local();
+    tokio::spawn(async move {
+        loop {
+            let event = match events.recv().await {
+                Ok(event) => event,
+                Err(RecvError::Lagged(skipped)) => {
+                    tracing::warn!(skipped, "Push job fell behind; some notifications were dropped");
+                    continue;
+                }
+                Err(RecvError::Closed) => break,
+            };
+            if event.event_type != "MESSAGE_CREATE" {
+                continue;
+            }
+            // Deliveries are slow HTTP calls; don't hold up the event stream.
+            let (state, client, is_online) = (state.clone(), client.clone(), is_online.clone());
+            tokio::spawn(async move {
+                if let Err(e) = notify(&state, &client, &*is_online, &event).await {
+                    tracing::warn!(error = %e, "Failed to send push notifications");
+                }
+            });
+        }
+    })
+}
+
+/// Why a recipient is being notified.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Reason {
+    DirectMessage,
+    Mention,
+}
+
+impl Reason {
+    fn allowed_by(self, prefs: &PushPreferences) -> bool {
+        prefs.enabled
+            && match self {
+                Reason::DirectMessage => prefs.direct_messages,
+                Reason::Mention => prefs.mentions,
+            }
+    }
+}
+
+async fn notify<F, Fut>(
+    state: &AppState,
+    client: &PushClient,
+    is_online: &F,
+    event: &GatewayEvent,
+) -> anyhow::Result<()>
+where
+    F: Fn(Uuid) -> Fut,
+    Fut: Future<Output = bool>,
+{
+    let data = &event.data;
+    let flags = data["flags"].as_i64().unwrap_or(0) as i32;
+    if MessageFlags::from_bits_truncate(flags).is_silent() {
+        return Ok(());
+    }
+    let (Some(channel_id), Some(author_id)) = (
+        event.channel_id,
+        data["author_id"].as_str().and_then(|s| s.parse::<Uuid>().ok()),
+    ) else {
+        return Ok(());
+    };
+    let pool = &state.db.pool;
+    let Some(channel) = channels::find_by_id(pool, channel_id).await? else {
+        return Ok(());
+    };
+
+    let (reason, candidates) = match channel.channel_type {
+        ChannelType::Dm | ChannelType::GroupDm => (
+            Reason::DirectMessage,
+            channels::list_dm_participants(pool, channel_id).await?,
+        ),
+        _ => (
+            Reason::Mention,
+            data["mentions"]
+                .as_array()
+                .map(|ids| {
+                    ids.iter()
+                        .filter_map(|id| id.as_str()?.parse().ok())
+                        .collect()
+                })
+                .unwrap_or_default(),
+        ),
+    };
+
+    let author = data["author_username"].as_str().unwrap_or("Someone");
+    let content = data["content"].as_str().unwrap_or_default();
+    let has_attachments = data["attachments"].as_array().is_some_and(|a| !a.is_empty());
+    let message_id = data["id"].as_str().unwrap_or_default();
+
+    for user_id in candidates {
+        if user_id == author_id || is_online(user_id).await {
+            continue;
+        }
+        if reason == Reason::Mention && !can_view(state, &channel, user_id).await {
+            continue;
+        }
+        if read_states::notifications_suppressed(pool, user_id, author_id, channel_id).await? {
+            continue;
+        }
+        let prefs = push::get_preferences(pool, user_id).await?;
+        if !reason.allowed_by(&prefs) {
+            continue;
+        }
+        let tokens = push::list_tokens(pool, user_id).await?;
+        if tokens.is_empty() {
+            continue;
+        }
+
+        let notification = Notification {
+            title: match (&channel.name, reason) {
+                (Some(name), Reason::Mention) => format!("{author} in #{name}"),
+                (Some(name), Reason::DirectMessage) => format!("{author} in {name}"),
+                (None, _) => author.to_owned(),
+            },
+            body: body(content, has_attachments, reason, &prefs),
+            data: BTreeMap::from([
+                ("channel_id".to_owned(), channel_id.to_string()),
+                ("message_id".to_owned(), message_id.to_owned()),
+                (
+                    "server_id".to_owned(),
+                    channel.server_id.map(|id| id.to_string()).unwrap_or_default(),
+                ),
+            ]),
+            tag: Some(channel_id.to_string()),
+        };
+
+        for token in &tokens {
+            match client.send(token, &notification).await {
+                Ok(Delivery::Sent) => {
+                    let _ = push::touch_token(pool, token.id).await;
+                }
+                Ok(Delivery::Expired) => {
+                    tracing::debug!(token_id = %token.id, "Dropping expired push token");
+                    let _ = push::delete_token_by_id(pool, token.id).await;
+                }
+                Ok(Delivery::Unsupported) => {}
+                Err(e) => {
+                    tracing::warn!(token_id = %token.id, error = %e, "Push delivery failed");
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Whether a mentioned user can actually read the channel.
+async fn can_view(state: &AppState, channel: &Channel, user_id: Uuid) -> bool {
+    matches!(
+        crate::permissions::in_channel(&state.db.pool, channel, user_id).await,
+        Ok(Some(p)) if p.has(Permissions::VIEW_CHANNEL)
+    )
+}
+
+/// Notification text: an excerpt of the message, or a generic line when the
+/// recipient hides message content.
+fn body(content: &str, has_attachments: bool, reason: Reason, prefs: &PushPreferences) -> String {
+    if !prefs.show_content {
+        return match reason {
+            Reason::DirectMessage => "New message".into(),
+            Reason::Mention => "Mentioned you".into(),
+        };
+    }
+    if content.is_empty() && has_attachments {
+        return "Sent an attachment".into();
+    }
+    // Devices have no viewer clock to hand; timestamps render in UTC.
+    let rendered = timestamps::render(content, &ViewerClock::default(), chrono::Utc::now());
+    let mut preview: String = rendered.chars().take(BODY_PREVIEW_CHARS).collect();
+    if rendered.chars().count() > BODY_PREVIEW_CHARS {
+        preview.push('…');
+    }
+    preview
+}
@@ -0,0 +1,139 @@
+//! Reminders job — delivers reminders once they fall due.
+//!
+//! A reminder with a channel is posted there as a message from its owner
+//! that mentions them (and replies to the message it is about, if any),
+//! subject to the same checks as any message they send. One without a
+//! channel — or whose owner can no longer post in it — is sent privately
+//! to the owner's sessions as `REMINDER_DUE`, and stays listed as due until
+//! they dismiss it, in case none of their sessions were online.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use nexus_common::error::NexusResult;
+use nexus_common::gateway_event::{event_types, GatewayEvent};
+use nexus_common::snowflake;
+use nexus_db::repository::reminders::ReminderRow;
+use nexus_db::repository::{channels, reminders, users};
+
+use crate::routes::messages::{insert_message, publish_message, require_can_post};
+use crate::routes::reminders::reminder_json;
+use crate::AppState;
+
+/// How often due reminders are checked for.
+const POLL_INTERVAL: Duration = Duration::from_secs(15);
+/// Reminders delivered per poll.
+const BATCH_SIZE: i64 = 100;
+
+pub fn spawn(state: Arc<AppState>) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(POLL_INTERVAL);
+        loop {
+            ticker.tick().await;
+            run_once(&state).await;
+        }
+    })
+}
+
+/// Deliver everything due as of now.
+pub async fn run_once(state: &AppState) {
+    deliver_due(state, Utc::now()).await;
+}
+
+/// Deliver everything due as of `now`.
+pub async fn deliver_due(state: &AppState, now: DateTime<Utc>) {
+    let due = match reminders::claim_due(&state.db.pool, now, BATCH_SIZE).await {
+        Ok(due) => due,
+        Err(e) => {
+            tracing::warn!(error = %e, "Failed to claim due reminders");
+            return;
+        }
+    };
+    for reminder in due {
+        if reminder.channel_id.is_some() && post_in_channel(state, &reminder).await {
+            // The message in the channel is the record of it.
+            if let Err(e) = reminders::acknowledge(&state.db.pool, reminder.user_id, reminder.id).await {
+                tracing::warn!(reminder_id = %reminder.id, error = %e, "Failed to acknowledge posted reminder");
+            }
+            continue;
+        }
+        send_privately(state, &reminder);
+    }
+}
+
+/// Post `reminder` in its channel. Returns false if the owner can no longer
+/// post there, the post looks like spam, or the message could not be
+/// created.
+async fn post_in_channel(state: &AppState, reminder: &ReminderRow) -> bool {
+    let pool = &state.db.pool;
+    let Some(channel_id) = reminder.channel_id else {
+        return false;
+    };
+    let Ok(Some(channel)) = channels::find_by_id(pool, channel_id).await else {
+        return false;
+    };
+    if require_can_post(state, &channel, reminder.user_id, false).await.is_err() {
+        return false;
+    }
+    if channel.server_id.is_none()
+        && !channels::is_dm_participant(pool, channel.id, reminder.user_id)
+            .await
+            .unwrap_or(false)
+    {
+        return false;
+    }
+
+    let content = format!("⏰ <@{}> {}", reminder.user_id, reminder.content);
+    // Nobody is timed out over their own reminder; it is just not posted.
+    if state.spam.check(reminder.user_id, None, channel.id, &content).is_some() {
+        return false;
+    }
+
+    let created: NexusResult<_> = async {
+        let mut tx = pool.begin().await?;
+        let msg = insert_message(
+            &mut tx,
+            &channel,
+            reminder.user_id,
+            snowflake::generate_id(),
+            &content,
+            (reminder.message_id, reminder.message_id.map(|_| channel.id)),
+            0,
+            &[],
+            &[],
+            &[],
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(msg)
+    }
+    .await;
+    let msg = match created {
+        Ok(msg) => msg,
+        Err(e) => {
+            tracing::warn!(reminder_id = %reminder.id, error = %e, "Failed to post reminder");
+            return false;
+        }
+    };
+
+    let username = users::find_by_id(pool, reminder.user_id)
+        .await
+        .ok()
+        .flatten()
+        .map(|u| u.username)
+        .unwrap_or_default();
+    publish_message(state, &channel, &msg, &username).await;
+    true
+}
+
+fn send_privately(state: &AppState, reminder: &ReminderRow) {
+    let _ = state.gateway_tx.send(GatewayEvent {
+        event_type: event_types::REMINDER_DUE.into(),
+        data: reminder_json(reminder),
+        server_id: None,
+        channel_id: None,
+        user_id: Some(reminder.user_id),
+        trace_context: None,
+    });
+}
@@ -0,0 +1,114 @@
+//! Security webhook worker — delivers queued security events to the
+//! operator's URLs, signed, retrying failures with exponential backoff.
+//! See [`crate::security_events`] for the request format.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::Utc;
+use nexus_db::repository::security_events::{self, DeliveryRow};
+
+use crate::security_events::{sign, EVENT_HEADER, SIGNATURE_HEADER, TIMESTAMP_HEADER};
+use crate::AppState;
+
+/// How often the queue is polled.
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+/// Deliveries attempted per poll.
+const BATCH_SIZE: i64 = 50;
+/// Per-request timeout.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+/// First retry delay; doubles per attempt up to `MAX_BACKOFF_SECS`.
+const BASE_BACKOFF_SECS: i64 = 30;
+const MAX_BACKOFF_SECS: i64 = 60 * 60;
+/// Delivered rows are kept this long before being pruned.
+const RETENTION_DAYS: i64 = 7;
+
+/// Spawn the delivery worker on its own task.
+pub fn spawn(state: Arc<AppState>) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let client = match reqwest::Client::builder().timeout(REQUEST_TIMEOUT).build() {
+            Ok(client) => client,
+            Err(e) => {
+                tracing::error!(error = %e, "Failed to build security webhook client");
+                return;
+            }
+        };
+        let mut ticker = tokio::time::interval(POLL_INTERVAL);
+        let mut ticks: u64 = 0;
+        loop {
+            ticker.tick().await;
+            run_once(&state, &client).await;
+
+            ticks += 1;
+            // Roughly hourly.
+            if ticks % 720 == 0 {
+                let before = Utc::now() - chrono::Duration::days(RETENTION_DAYS);
+                if let Err(e) = security_events::prune_delivered(&state.db.pool, before).await {
+                    tracing::warn!(error = %e, "Failed to prune security event deliveries");
+                }
+            }
+        }
+    })
+}
+
+/// Attempt every due delivery once.
+pub async fn run_once(state: &AppState, client: &reqwest::Client) {
+    let pool = &state.db.pool;
+    let config = &nexus_common::config::get().security_webhooks;
+    let max_attempts = config.max_delivery_attempts as i32;
+    let due = match security_events::list_due(pool, BATCH_SIZE).await {
+        Ok(rows) => rows,
+        Err(e) => {
+            tracing::warn!(error = %e, "Failed to load security event deliveries");
+            return;
+        }
+    };
+
+    for row in due {
+        let result = match deliver(client, &config.secret, &row).await {
+            Ok(()) => security_events::mark_delivered(pool, row.id).await,
+            Err(e) if row.attempts + 1 >= max_attempts => {
+                tracing::warn!(
+                    url = %row.url,
+                    event = %row.event,
+                    error = %e,
+                    "Dropping security event after {} attempts",
+                    row.attempts + 1
+                );
+                security_events::discard(pool, row.id).await
+            }
+            Err(e) => {
+                let backoff = (BASE_BACKOFF_SECS << row.attempts.min(16)).min(MAX_BACKOFF_SECS);
+                tracing::debug!(url = %row.url, error = %e, backoff, "Security event delivery failed");
+                security_events::mark_failed(
+                    pool,
+                    row.id,
+                    &e,
+                    Utc::now() + chrono::Duration::seconds(backoff),
+                )
+                .await
+            }
+        };
+        if let Err(e) = result {
+            tracing::warn!(delivery_id = %row.id, error = %e, "Failed to update security event delivery");
+        }
+    }
+}
+
+/// POST one delivery, signed at the time of sending.
+async fn deliver(client: &reqwest::Client, secret: &str, row: &DeliveryRow) -> Result<(), String> {
+    let body = row.payload.to_string();
+    let timestamp = Utc::now().timestamp();
+    client
+        .post(&row.url)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .header(EVENT_HEADER, &row.event)
+        .header(TIMESTAMP_HEADER, timestamp.to_string())
+        .header(SIGNATURE_HEADER, sign(secret, timestamp, body.as_bytes()))
+        .body(body)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
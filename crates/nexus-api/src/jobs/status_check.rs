@@ -0,0 +1,186 @@
+//! Self-check job — probes every component once a minute and records the
+//! result in `status_checks`, which backs the public status page.
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use nexus_db::repository::status;
+
+use crate::AppState;
+
+/// How often every component is probed.
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+/// Upper bound on a single probe before it is considered down.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(3);
+/// Federation is reported as degraded once the oldest undelivered outbox
+/// entry is older than this.
+const FEDERATION_LAG_DEGRADED_SECS: i64 = 300;
+/// Check history older than this is pruned.
+const RETENTION_DAYS: i64 = 90;
+
+/// Sibling listeners that are probed over TCP. `None` for a service that
+/// runs in another process (`nexus serve --role`); it isn't probed from here.
+#[derive(Debug, Clone, Copy)]
+pub struct ProbeTargets {
+    pub gateway: Option<SocketAddr>,
+    pub voice: Option<SocketAddr>,
+}
+
+/// Component health as shown on the status page.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ComponentStatus {
+    Operational,
+    Degraded,
+    Down,
+}
+
+impl ComponentStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Operational => "operational",
+            Self::Degraded => "degraded",
+            Self::Down => "down",
+        }
+    }
+
+    pub fn parse(s: &str) -> Self {
+        match s {
+            "operational" => Self::Operational,
+            "degraded" => Self::Degraded,
+            _ => Self::Down,
+        }
+    }
+}
+
+/// Outcome of probing a single component.
+#[derive(Debug)]
+struct Probe {
+    component: &'static str,
+    status: ComponentStatus,
+    latency_ms: Option<i64>,
+    detail: Option<String>,
+}
+
+/// Spawn the self-check loop on its own task.
+pub fn spawn(state: Arc<AppState>, targets: ProbeTargets) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(CHECK_INTERVAL);
+        loop {
+            ticker.tick().await;
+            run_once(&state, targets).await;
+        }
+    })
+}
+
+/// Probe every component once and persist the results.
+pub async fn run_once(state: &AppState, targets: ProbeTargets) {
+    let mut probes = vec![probe_api(state).await];
+    if let Some(addr) = targets.gateway {
+        probes.push(probe_tcp("gateway", addr).await);
+    }
+    if let Some(addr) = targets.voice {
+        probes.push(probe_tcp("voice", addr).await);
+    }
+    probes.push(probe_federation(state).await);
+    if state.search.is_enabled() {
+        probes.push(probe_search(state).await);
+    }
+
+    for p in probes {
+        if let Err(e) = status::record_check(
+            &state.db.pool,
+            p.component,
+            p.status.as_str(),
+            p.latency_ms,
+            p.detail.as_deref(),
+        )
+        .await
+        {
+            tracing::warn!(component = p.component, error = %e, "Failed to record status check");
+        }
+    }
+
+    let cutoff = chrono::Utc::now() - chrono::Duration::days(RETENTION_DAYS);
+    if let Err(e) = status::prune_checks(&state.db.pool, cutoff).await {
+        tracing::warn!(error = %e, "Failed to prune status history");
+    }
+}
+
+/// The API is up if we are running; it is only useful if the database answers.
+async fn probe_api(state: &AppState) -> Probe {
+    let started = Instant::now();
+    let ok = tokio::time::timeout(PROBE_TIMEOUT, nexus_db::postgres::health_check(&state.db.pool))
+        .await
+        .unwrap_or(false);
+    Probe {
+        component: "api",
+        status: if ok { ComponentStatus::Operational } else { ComponentStatus::Degraded },
+        latency_ms: Some(started.elapsed().as_millis() as i64),
+        detail: (!ok).then(|| "database unreachable".to_string()),
+    }
+}
+
+/// Gateway and voice run their own listeners — a TCP connect is enough to
+/// prove they are accepting connections.
+async fn probe_tcp(component: &'static str, addr: SocketAddr) -> Probe {
+    let started = Instant::now();
+    match tokio::time::timeout(PROBE_TIMEOUT, tokio::net::TcpStream::connect(addr)).await {
+        Ok(Ok(_)) => Probe {
+            component,
+            status: ComponentStatus::Operational,
+            latency_ms: Some(started.elapsed().as_millis() as i64),
+            detail: None,
+        },
+        Ok(Err(e)) => Probe {
+            component,
+            status: ComponentStatus::Down,
+            latency_ms: None,
+            detail: Some(e.to_string()),
+        },
+        Err(_) => Probe {
+            component,
+            status: ComponentStatus::Down,
+            latency_ms: None,
+            detail: Some("probe timed out".to_string()),
+        },
+    }
+}
+
+async fn probe_search(state: &AppState) -> Probe {
+    let started = Instant::now();
+    let ok = tokio::time::timeout(PROBE_TIMEOUT, state.search.health())
+        .await
+        .unwrap_or(false);
+    Probe {
+        component: "search",
+        status: if ok { ComponentStatus::Operational } else { ComponentStatus::Down },
+        latency_ms: ok.then(|| started.elapsed().as_millis() as i64),
+        detail: (!ok).then(|| "MeiliSearch health check failed".to_string()),
+    }
+}
+
+/// Federation health is measured by how far the outbound queue has fallen behind.
+async fn probe_federation(state: &AppState) -> Probe {
+    match status::federation_outbox_lag(&state.db.pool).await {
+        Ok(lag) => {
+            let lag_secs = lag.map(|d| d.num_seconds().max(0)).unwrap_or(0);
+            Probe {
+                component: "federation",
+                status: if lag_secs > FEDERATION_LAG_DEGRADED_SECS {
+                    ComponentStatus::Degraded
+                } else {
+                    ComponentStatus::Operational
+                },
+                latency_ms: None,
+                detail: Some(format!("outbox lag {lag_secs}s")),
+            }
+        }
+        Err(e) => Probe {
+            component: "federation",
+            status: ComponentStatus::Down,
+            latency_ms: None,
+            detail: Some(e.to_string()),
+        },
+    }
+}
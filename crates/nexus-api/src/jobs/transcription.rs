@@ -0,0 +1,154 @@
+//! Transcription job — drains the `transcripts` queue through the configured
+//! speech-to-text provider.
+//!
+//! Completed voice-message transcripts are pushed into the search index with
+//! their parent message and announced to clients via `TRANSCRIPT_READY`.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use nexus_common::gateway_event::{event_types, GatewayEvent};
+use nexus_db::repository::{attachments, channels, messages, transcripts, users};
+use nexus_db::search::{MessageDocument, SearchClient};
+
+use crate::transcription::TranscriptionClient;
+use crate::AppState;
+
+/// How often the queue is polled when idle.
+const POLL_INTERVAL: Duration = Duration::from_secs(10);
+/// Transcripts claimed per poll. Transcription is CPU/network heavy, so keep it small.
+const BATCH_SIZE: i64 = 4;
+
+/// Spawn the transcription worker on its own task.
+pub fn spawn(state: Arc<AppState>, client: TranscriptionClient) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        // Anything still `processing` belonged to a previous process.
+        match transcripts::requeue_stale(&state.db.pool).await {
+            Ok(0) => {}
+            Ok(n) => tracing::info!(count = n, "Requeued interrupted transcripts"),
+            Err(e) => tracing::warn!(error = %e, "Failed to requeue interrupted transcripts"),
+        }
+
+        let mut ticker = tokio::time::interval(POLL_INTERVAL);
+        loop {
+            ticker.tick().await;
+            run_once(&state, &client).await;
+        }
+    })
+}
+
+/// Claim and process one batch of pending transcripts.
+pub async fn run_once(state: &AppState, client: &TranscriptionClient) {
+    let max_attempts = nexus_common::config::get().transcription.max_attempts as i32;
+    let batch = match transcripts::claim_pending(&state.db.pool, BATCH_SIZE).await {
+        Ok(batch) => batch,
+        Err(e) => {
+            tracing::warn!(error = %e, "Failed to claim pending transcripts");
+            return;
+        }
+    };
+
+    for row in batch {
+        let result = async {
+            let audio = state
+                .storage
+                .get_object(&row.storage_key)
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("audio object '{}' is missing", row.storage_key))?;
+            client.transcribe(audio, &row.content_type).await
+        }
+        .await;
+
+        match result {
+            Ok(transcript) => {
+                if let Err(e) = transcripts::complete(
+                    &state.db.pool,
+                    row.id,
+                    &transcript.text,
+                    transcript.language.as_deref(),
+                    &transcript.segments,
+                    client.provider_name(),
+                )
+                .await
+                {
+                    tracing::warn!(transcript_id = %row.id, error = %e, "Failed to store transcript");
+                    continue;
+                }
+                tracing::debug!(transcript_id = %row.id, "Transcript completed");
+                if row.source_type == transcripts::SOURCE_ATTACHMENT {
+                    publish_attachment_transcript(state, row.source_id, &transcript.text).await;
+                }
+            }
+            Err(e) => {
+                tracing::warn!(
+                    transcript_id = %row.id,
+                    attempt = row.attempts,
+                    error = %e,
+                    "Transcription failed"
+                );
+                if let Err(e) =
+                    transcripts::fail(&state.db.pool, row.id, &e.to_string(), max_attempts).await
+                {
+                    tracing::warn!(transcript_id = %row.id, error = %e, "Failed to record transcription failure");
+                }
+            }
+        }
+    }
+}
+
+/// Re-index the parent message with its transcript and notify the channel.
+async fn publish_attachment_transcript(state: &AppState, attachment_id: uuid::Uuid, text: &str) {
+    let pool = &state.db.pool;
+    let Ok(Some(attachment)) = attachments::find_by_id(pool, attachment_id).await else {
+        return;
+    };
+    // Voice messages are uploaded before the message exists; the transcript
+    // is still served from the attachment endpoint until it gets linked.
+    let Some(message_id) = attachment.message_id else {
+        return;
+    };
+    let Ok(Some(message)) = messages::find_by_id(pool, message_id).await else {
+        return;
+    };
+    let server_id = channels::find_by_id(pool, message.channel_id)
+        .await
+        .ok()
+        .flatten()
+        .and_then(|c| c.server_id);
+
+    let author_username = users::find_by_id(pool, message.author_id)
+        .await
+        .ok()
+        .flatten()
+        .map(|u| u.username)
+        .unwrap_or_default();
+    let doc = MessageDocument {
+        id: message.id.to_string(),
+        channel_id: message.channel_id.to_string(),
+        server_id: server_id.map(|s| s.to_string()),
+        author_id: message.author_id.to_string(),
+        author_username,
+        content: message.content.clone(),
+        has_attachments: true,
+        has_embeds: message.embeds.as_array().is_some_and(|e| !e.is_empty()),
+        transcript: Some(text.to_string()),
+        created_at: message.created_at.timestamp(),
+    };
+    if let Err(e) = SearchClient::enqueue_message_index(pool, message.id, &doc).await {
+        tracing::warn!(message_id = %message.id, error = %e, "Failed to enqueue transcript for indexing");
+    }
+
+    let _ = state.gateway_tx.send(GatewayEvent {
+        event_type: event_types::TRANSCRIPT_READY.into(),
+        data: serde_json::json!({
+            "attachment_id": attachment_id,
+            "message_id": message.id,
+            "channel_id": message.channel_id,
+            "text": text,
+        }),
+        server_id,
+        channel_id: Some(message.channel_id),
+        user_id: None,
+        trace_context: None,
+    });
+}
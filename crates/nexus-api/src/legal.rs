@@ -0,0 +1,94 @@
+//! Terms of service consent — which callers must consent before using the
+//! API, and a cache of who already has.
+//!
+//! When `legal.version` is set, every authenticated request outside
+//! [`exempt`] routes needs a consent record for that version, or it is
+//! refused with 451 `CONSENT_REQUIRED` (see
+//! [`crate::middleware::require_consent`]). Clients then show the documents
+//! from `GET /legal/terms` and `GET /legal/privacy` and post the version to
+//! `POST /legal/consent`.
+//!
+//! Consent is never withdrawn and the required version only changes with the
+//! config, on restart, so the cache only remembers users who have consented
+//! and never needs invalidating.
+
+use axum::http::Method;
+use std::collections::HashSet;
+use std::sync::Mutex;
+use uuid::Uuid;
+
+/// Users known to have consented to the current version.
+#[derive(Default)]
+pub struct ConsentCache {
+    users: Mutex<HashSet<Uuid>>,
+}
+
+impl ConsentCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn contains(&self, user_id: Uuid) -> bool {
+        self.users.lock().unwrap_or_else(|e| e.into_inner()).contains(&user_id)
+    }
+
+    pub fn insert(&self, user_id: Uuid) {
+        self.users.lock().unwrap_or_else(|e| e.into_inner()).insert(user_id);
+    }
+}
+
+/// Whether a route (its template under `/api/v1`) is usable without
+/// consent: signing in, reading and accepting the terms, the caller's own
+/// profile, public invite previews, and health checks. Taking one's data
+/// out and deleting the account don't need consent either.
+pub fn exempt(method: &Method, template: &str) -> bool {
+    template.starts_with("/auth/")
+        || template == "/legal"
+        || template.starts_with("/legal/")
+        || template == "/health"
+        || template == "/status"
+        || template.starts_with("/status/")
+        || (method == Method::GET && template == "/users/@me")
+        || (method == Method::GET && template == "/invites/{code}/preview")
+        || (method == Method::DELETE && template == "/users/@me")
+        || template.starts_with("/users/@me/export")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signing_in_and_the_terms_are_exempt() {
+        assert!(exempt(&Method::POST, "/auth/login"));
+        assert!(exempt(&Method::GET, "/legal/terms"));
+        assert!(exempt(&Method::POST, "/legal/consent"));
+        assert!(exempt(&Method::GET, "/users/@me"));
+        assert!(exempt(&Method::GET, "/status/history"));
+        assert!(exempt(&Method::GET, "/invites/{code}/preview"));
+    }
+
+    #[test]
+    fn exporting_data_and_deleting_the_account_are_exempt() {
+        assert!(exempt(&Method::POST, "/users/@me/export"));
+        assert!(exempt(&Method::GET, "/users/@me/export/{export_id}"));
+        assert!(exempt(&Method::DELETE, "/users/@me"));
+    }
+
+    #[test]
+    fn everything_else_needs_consent() {
+        assert!(!exempt(&Method::PATCH, "/users/@me"));
+        assert!(!exempt(&Method::POST, "/channels/{channel_id}/messages"));
+        assert!(!exempt(&Method::GET, "/legalese"));
+        assert!(!exempt(&Method::GET, "/statuses"));
+    }
+
+    #[test]
+    fn the_cache_remembers_consenting_users() {
+        let cache = ConsentCache::new();
+        let user = Uuid::new_v4();
+        assert!(!cache.contains(user));
+        cache.insert(user);
+        assert!(cache.contains(user));
+    }
+}
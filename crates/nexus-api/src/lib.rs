@@ -3,17 +3,42 @@
 //! REST API layer for Nexus. Provides HTTP endpoints for all CRUD operations,
 //! authentication, and client-facing functionality.
 
+pub mod audit;
 pub mod auth;
+pub mod ban_lists;
+pub mod bridges;
+pub mod federation_limits;
+pub mod file_types;
+pub mod ics;
+pub mod jobs;
+pub mod legal;
+pub mod media;
 pub mod middleware;
+pub mod moderation_queue;
+pub mod oauth2;
+pub mod permissions;
+pub mod ratelimit;
+pub mod push;
+pub mod registration;
+pub mod reminders;
 pub mod routes;
+pub mod rpc;
+pub mod security_events;
+pub mod session_cache;
+pub mod spam;
+pub mod starboard;
+#[cfg(any(test, feature = "test-support"))]
+pub mod test_support;
+pub mod transcription;
+pub mod unfurl;
+pub mod webhook_formats;
 
 use axum::Router;
-use nexus_common::gateway_event::GatewayEvent;
+use nexus_common::gateway_event::EventBus;
 use nexus_db::{search::SearchClient, storage::StorageClient, Database};
-use nexus_federation::{client::FederationClient, ServerKeyPair};
+use nexus_federation::{client::FederationClient, ActivityPubBridge, BridgeRegistry, ServerKeyPair};
 use nexus_voice::state::VoiceStateManager;
 use std::sync::Arc;
-use tokio::sync::broadcast;
 
 /// Shared application state available to all route handlers.
 #[derive(Clone)]
@@ -22,7 +47,7 @@ pub struct AppState {
     /// Broadcast sender to push events to the WebSocket gateway.
     /// API mutations (message create, channel update, etc.) use this
     /// to notify all connected clients in real-time.
-    pub gateway_tx: broadcast::Sender<GatewayEvent>,
+    pub gateway_tx: EventBus,
     /// Voice state manager — shared with the voice server for REST-based
     /// voice operations (state queries, moderation actions).
     pub voice_state: VoiceStateManager,
@@ -37,25 +62,71 @@ pub struct AppState {
     pub federation_key: Arc<ServerKeyPair>,
     /// Signed HTTP client for outbound server-to-server federation requests.
     pub federation_client: Arc<FederationClient>,
+    /// Outbound protocol bridges (Matrix, ActivityPub, …) new messages are relayed through.
+    pub bridges: BridgeRegistry,
+    /// ActivityPub bridge; `None` unless `activitypub.enabled` is set.
+    pub activitypub: Option<Arc<ActivityPubBridge>>,
+    /// Per-caller request limits on the `/api/v1` routes.
+    pub rate_limits: Arc<ratelimit::RateLimiter>,
+    /// Per-origin limits on inbound federation transactions.
+    pub federation_limits: Arc<federation_limits::OriginLimiter>,
+    /// Users known to have consented to the current terms of service.
+    pub consents: Arc<legal::ConsentCache>,
+    /// Login sessions known to exist, for authenticating access tokens.
+    pub live_sessions: Arc<session_cache::SessionCache>,
+    /// Process start time — reported as uptime by `/health` and `/status`.
+    pub started_at: chrono::DateTime<chrono::Utc>,
+    /// Message spam heuristics (duplicate bursts, link and invite spam).
+    pub spam: Arc<spam::SpamDetector>,
+    /// Speech-to-text client; `None` when transcription is disabled.
+    pub transcription: Option<transcription::TranscriptionClient>,
+    /// Thumbnail/blurhash generator; `None` when media processing is disabled.
+    pub media: Option<media::MediaProcessor>,
+    /// Users' presence across gateway sessions, shared with the gateway.
+    pub presence: Arc<nexus_db::presence::PresenceService>,
 }
 
 /// Build the complete API router with all routes and middleware.
 pub fn build_router(state: AppState) -> Router {
+    let state = Arc::new(state);
     let api_routes = Router::new()
         .merge(routes::auth::router())
+        .merge(routes::legal::router())
         .merge(routes::users::router())
+        .merge(routes::exports::router())
+        .merge(routes::blocks::router())
+        .merge(routes::user_settings::router())
+        .merge(routes::sessions::router())
+        .merge(routes::admin::router().route_layer(
+            axum::middleware::from_fn_with_state(state.clone(), middleware::staff_middleware),
+        ))
         .merge(routes::servers::router())
+        .merge(routes::invites::router())
+        .merge(routes::bans::router())
+        .merge(routes::moderation::router())
+        .merge(routes::permissions::router())
+        .merge(routes::audit_log::router())
+        .merge(routes::welcome::router())
+        .merge(routes::starboard::router())
+        .merge(routes::scheduled_events::router())
         .merge(routes::channels::router())
+        .merge(routes::channel_mutes::router())
+        .merge(routes::federated_rooms::router())
         .merge(routes::messages::router())
+        .merge(routes::moderation_queue::router())
         .merge(routes::dms::router())
         .merge(routes::voice::router())
         .merge(routes::health::router())
+        .merge(routes::status::router())
         // v0.4 Rich Features
         .merge(routes::uploads::router())
         .merge(routes::threads::router())
+        .merge(routes::forums::router())
         .merge(routes::emoji::router())
         .merge(routes::search::router())
         .merge(routes::presence::router())
+        .merge(routes::push::router())
+        .merge(routes::reminders::router())
         // v0.5 Encryption
         .merge(routes::keys::router())
         .merge(routes::e2ee::router())
@@ -64,25 +135,37 @@ pub fn build_router(state: AppState) -> Router {
         .merge(routes::bots::router())
         .merge(routes::webhooks::router())
         .merge(routes::slash_commands::router())
+        .merge(routes::slash_commands::application_router().route_layer(
+            axum::middleware::from_fn_with_state(state.clone(), middleware::bot_auth_middleware),
+        ))
         .merge(routes::extensibility::router())
+        .merge(routes::oauth2::router())
+        .merge(routes::oauth2::resource_router().route_layer(
+            axum::middleware::from_fn_with_state(state.clone(), middleware::oauth2_auth_middleware),
+        ))
         // v0.8 Federation — client-facing directory endpoints
-        .merge(routes::directory::router());
+        .merge(routes::directory::router())
+        .layer(axum::middleware::from_fn_with_state(state.clone(), middleware::require_consent))
+        .layer(axum::middleware::from_fn_with_state(state.clone(), middleware::rate_limit))
+        // For `auth_middleware`, which the route modules add without the state
+        .layer(axum::Extension(state.clone()));
 
     Router::new()
         .nest("/api/v1", api_routes)
         // v0.8 Federation — server-to-server endpoints (live outside /api/v1)
         .merge(routes::federation::federation_router())
+        // ActivityPub actors for public announcement channels
+        .merge(routes::activitypub::router())
         // Local file serving (lite mode — no-op in full mode)
         .merge(routes::files::router())
-        .layer(
-            tower_http::cors::CorsLayer::new()
-                .allow_origin(tower_http::cors::Any)
-                .allow_methods(tower_http::cors::Any)
-                .allow_headers(tower_http::cors::Any),
-        )
-        .layer(tower_http::trace::TraceLayer::new_for_http())
+        .layer(axum::extract::DefaultBodyLimit::max(
+            nexus_common::config::get().limits.max_request_body_bytes as usize,
+        ))
+        .layer(middleware::cors_layer(&nexus_common::config::get().http))
+        .layer(axum::middleware::from_fn(middleware::track_metrics))
+        .layer(tower_http::trace::TraceLayer::new_for_http().make_span_with(middleware::request_span))
         .layer(tower_http::compression::CompressionLayer::new())
         .layer(axum::middleware::from_fn(middleware::security_headers))
-        .with_state(Arc::new(state))
+        .with_state(state)
 }
 
@@ -0,0 +1,735 @@
+//! Image processing for attachments — metadata stripping, dimension probing,
+//! thumbnails and blurhash placeholders.
+//!
+//! Stripping and probing only walk the container format, so they run inline
+//! on upload and location data never reaches storage. Thumbnails and blurhash
+//! need a real decoder; the media job (see [`crate::jobs::media`]) produces
+//! them through ffmpeg, which reads every image type uploads accept.
+
+use anyhow::{bail, Context, Result};
+use nexus_common::config::MediaConfig;
+
+use crate::transcription::{run, ScratchDir};
+
+/// Image types the media job can thumbnail.
+pub fn is_processable(content_type: &str) -> bool {
+    matches!(
+        content_type,
+        "image/jpeg" | "image/png" | "image/gif" | "image/webp" | "image/avif" | "image/bmp" | "image/tiff"
+    )
+}
+
+// ── Dimensions ───────────────────────────────────────────────────────────────
+
+/// Display size of an image, read from its header.
+///
+/// JPEGs whose EXIF orientation turns them on their side report the rotated
+/// size, matching what browsers draw.
+pub fn dimensions(data: &[u8]) -> Option<(u32, u32)> {
+    if data.starts_with(b"\x89PNG\r\n\x1a\n") && data.get(12..16) == Some(b"IHDR") {
+        return Some((be32(data, 16)?, be32(data, 20)?));
+    }
+    if data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a") {
+        return Some((le16(data, 6)? as u32, le16(data, 8)? as u32));
+    }
+    if data.starts_with(b"BM") {
+        let width = le32(data, 18)? as i32;
+        let height = le32(data, 22)? as i32;
+        return Some((width.unsigned_abs(), height.unsigned_abs()));
+    }
+    if data.starts_with(b"RIFF") && data.get(8..12) == Some(b"WEBP") {
+        return webp_dimensions(data);
+    }
+    if data.starts_with(&[0xFF, 0xD8]) {
+        let (width, height) = jpeg_dimensions(data)?;
+        return Some(match jpeg_orientation(data) {
+            Some(5..=8) => (height, width),
+            _ => (width, height),
+        });
+    }
+    if data.starts_with(b"II*\0") || data.starts_with(b"MM\0*") {
+        let tiff = Tiff::new(data)?;
+        let ifd = tiff.first_ifd()?;
+        return Some((tiff.find(ifd, TAG_IMAGE_WIDTH)?, tiff.find(ifd, TAG_IMAGE_LENGTH)?));
+    }
+    if data.get(4..8) == Some(b"ftyp") {
+        // AVIF: the first `ispe` box is the primary item's spatial extent.
+        let at = data.windows(4).position(|w| w == b"ispe")?;
+        return Some((be32(data, at + 8)?, be32(data, at + 12)?));
+    }
+    None
+}
+
+fn webp_dimensions(data: &[u8]) -> Option<(u32, u32)> {
+    match data.get(12..16)? {
+        b"VP8 " => {
+            if data.get(23..26)? != [0x9D, 0x01, 0x2A] {
+                return None;
+            }
+            Some(((le16(data, 26)? & 0x3FFF) as u32, (le16(data, 28)? & 0x3FFF) as u32))
+        }
+        b"VP8L" => {
+            if *data.get(20)? != 0x2F {
+                return None;
+            }
+            let bits = le32(data, 21)?;
+            Some(((bits & 0x3FFF) + 1, ((bits >> 14) & 0x3FFF) + 1))
+        }
+        b"VP8X" => Some((le24(data, 24)? + 1, le24(data, 27)? + 1)),
+        _ => None,
+    }
+}
+
+fn jpeg_dimensions(data: &[u8]) -> Option<(u32, u32)> {
+    for segment in JpegSegments::new(data) {
+        let segment = segment?;
+        // SOF0–SOF15, except DHT (C4), JPG (C8) and DAC (CC).
+        if matches!(segment.marker, 0xC0..=0xCF) && !matches!(segment.marker, 0xC4 | 0xC8 | 0xCC) {
+            let body = segment.body;
+            return Some((be16(body, 3)? as u32, be16(body, 1)? as u32));
+        }
+        if segment.marker == SOS {
+            break;
+        }
+    }
+    None
+}
+
+// ── Metadata stripping ───────────────────────────────────────────────────────
+
+/// Remove EXIF, XMP, IPTC and text metadata (GPS position, camera serials,
+/// editing history) from a JPEG, PNG or WebP without re-encoding it.
+///
+/// A JPEG keeps its orientation in a minimal EXIF block so photos taken on
+/// their side still display upright. Files that can't be parsed are returned
+/// untouched.
+pub fn strip_metadata(content_type: &str, data: Vec<u8>) -> Vec<u8> {
+    let stripped = match content_type {
+        "image/jpeg" => strip_jpeg(&data),
+        "image/png" => strip_png(&data),
+        "image/webp" => strip_webp(&data),
+        _ => None,
+    };
+    stripped.unwrap_or(data)
+}
+
+const SOS: u8 = 0xDA;
+const APP1: u8 = 0xE1;
+const APP13: u8 = 0xED;
+const COM: u8 = 0xFE;
+
+fn strip_jpeg(data: &[u8]) -> Option<Vec<u8>> {
+    let orientation = jpeg_orientation(data);
+    let mut out = Vec::with_capacity(data.len());
+    out.extend_from_slice(&data[..2]);
+    for segment in JpegSegments::new(data) {
+        let segment = segment?;
+        match segment.marker {
+            // APP1 is EXIF or XMP; APP13 is Photoshop/IPTC; COM is free text.
+            APP1 | APP13 | COM => {
+                if segment.marker == APP1
+                    && segment.body.starts_with(b"Exif\0\0")
+                    && let Some(o @ 2..=8) = orientation
+                {
+                    out.extend_from_slice(&orientation_exif(o));
+                }
+            }
+            SOS => {
+                // Entropy-coded data runs to EOI; copy the rest verbatim.
+                out.extend_from_slice(&data[segment.start..]);
+                return Some(out);
+            }
+            _ => out.extend_from_slice(&data[segment.start..segment.end]),
+        }
+    }
+    None
+}
+
+/// An APP1 segment holding a big-endian TIFF header and a single IFD with
+/// just the orientation tag.
+fn orientation_exif(orientation: u16) -> Vec<u8> {
+    let mut payload = b"Exif\0\0MM\0\x2a".to_vec();
+    payload.extend_from_slice(&8u32.to_be_bytes()); // offset of IFD0
+    payload.extend_from_slice(&1u16.to_be_bytes()); // one entry
+    payload.extend_from_slice(&TAG_ORIENTATION.to_be_bytes());
+    payload.extend_from_slice(&3u16.to_be_bytes()); // SHORT
+    payload.extend_from_slice(&1u32.to_be_bytes()); // count
+    payload.extend_from_slice(&orientation.to_be_bytes());
+    payload.extend_from_slice(&[0, 0]);
+    payload.extend_from_slice(&0u32.to_be_bytes()); // no next IFD
+
+    let mut segment = vec![0xFF, APP1];
+    segment.extend_from_slice(&((payload.len() + 2) as u16).to_be_bytes());
+    segment.extend_from_slice(&payload);
+    segment
+}
+
+fn strip_png(data: &[u8]) -> Option<Vec<u8>> {
+    if !data.starts_with(b"\x89PNG\r\n\x1a\n") {
+        return None;
+    }
+    let mut out = data[..8].to_vec();
+    let mut at = 8;
+    while at < data.len() {
+        let len = be32(data, at)? as usize;
+        let end = at.checked_add(12)?.checked_add(len)?;
+        let kind = data.get(at + 4..at + 8)?;
+        let chunk = data.get(at..end)?;
+        if !matches!(kind, b"eXIf" | b"tEXt" | b"zTXt" | b"iTXt" | b"tIME") {
+            out.extend_from_slice(chunk);
+        }
+        at = end;
+        if kind == b"IEND" {
+            break;
+        }
+    }
+    Some(out)
+}
+
+fn strip_webp(data: &[u8]) -> Option<Vec<u8>> {
+    if !data.starts_with(b"RIFF") || data.get(8..12) != Some(b"WEBP") {
+        return None;
+    }
+    let mut out = data[..12].to_vec();
+    let mut at = 12;
+    while at + 8 <= data.len() {
+        let kind = data.get(at..at + 4)?;
+        let len = le32(data, at + 4)? as usize;
+        // Chunks are padded to an even length.
+        let end = (at + 8).checked_add(len + (len & 1))?.min(data.len());
+        match kind {
+            b"EXIF" | b"XMP " => {}
+            b"VP8X" => {
+                let start = out.len();
+                out.extend_from_slice(data.get(at..end)?);
+                // Clear the EXIF (bit 3) and XMP (bit 2) presence flags.
+                *out.get_mut(start + 8)? &= !0b0000_1100;
+            }
+            _ => out.extend_from_slice(data.get(at..end)?),
+        }
+        at = end;
+    }
+    let riff_len = (out.len() - 8) as u32;
+    out[4..8].copy_from_slice(&riff_len.to_le_bytes());
+    Some(out)
+}
+
+// ── JPEG / TIFF parsing ──────────────────────────────────────────────────────
+
+struct JpegSegment<'a> {
+    marker: u8,
+    /// Offset of the segment's `FF xx` marker.
+    start: usize,
+    /// Offset just past the segment.
+    end: usize,
+    /// Segment payload, without marker and length.
+    body: &'a [u8],
+}
+
+/// Marker segments of a JPEG up to and including SOS. Yields `None` once on
+/// malformed input.
+struct JpegSegments<'a> {
+    data: &'a [u8],
+    at: usize,
+    done: bool,
+}
+
+impl<'a> JpegSegments<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, at: 2, done: false }
+    }
+
+    fn read(&mut self) -> Option<JpegSegment<'a>> {
+        let start = self.at;
+        if *self.data.get(start)? != 0xFF {
+            return None;
+        }
+        let mut at = start + 1;
+        // Any number of 0xFF fill bytes may precede a marker.
+        while *self.data.get(at)? == 0xFF {
+            at += 1;
+        }
+        let marker = self.data[at];
+        at += 1;
+        if matches!(marker, 0x01 | 0xD0..=0xD7) {
+            self.at = at;
+            return Some(JpegSegment { marker, start, end: at, body: &[] });
+        }
+        let len = be16(self.data, at)? as usize;
+        if len < 2 {
+            return None;
+        }
+        let end = at + len;
+        let body = self.data.get(at + 2..end)?;
+        self.at = end;
+        Some(JpegSegment { marker, start, end, body })
+    }
+}
+
+impl<'a> Iterator for JpegSegments<'a> {
+    type Item = Option<JpegSegment<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done || self.at >= self.data.len() {
+            return None;
+        }
+        let segment = self.read();
+        self.done = segment.as_ref().is_none_or(|s| s.marker == SOS);
+        Some(segment)
+    }
+}
+
+const TAG_IMAGE_WIDTH: u16 = 0x0100;
+const TAG_IMAGE_LENGTH: u16 = 0x0101;
+const TAG_ORIENTATION: u16 = 0x0112;
+
+/// EXIF orientation (1–8) of a JPEG, if it has one.
+pub fn jpeg_orientation(data: &[u8]) -> Option<u16> {
+    for segment in JpegSegments::new(data) {
+        let segment = segment?;
+        if segment.marker == APP1
+            && let Some(tiff) = segment.body.strip_prefix(b"Exif\0\0")
+        {
+            let tiff = Tiff::new(tiff)?;
+            let orientation = tiff.find(tiff.first_ifd()?, TAG_ORIENTATION)? as u16;
+            return (1..=8).contains(&orientation).then_some(orientation);
+        }
+        if segment.marker == SOS {
+            break;
+        }
+    }
+    None
+}
+
+/// Just enough of a TIFF reader to look up integer tags in an IFD.
+struct Tiff<'a> {
+    data: &'a [u8],
+    big_endian: bool,
+}
+
+impl<'a> Tiff<'a> {
+    fn new(data: &'a [u8]) -> Option<Self> {
+        let big_endian = match data.get(..4)? {
+            b"MM\0*" => true,
+            b"II*\0" => false,
+            _ => return None,
+        };
+        Some(Self { data, big_endian })
+    }
+
+    fn u16(&self, at: usize) -> Option<u16> {
+        if self.big_endian { be16(self.data, at) } else { le16(self.data, at) }
+    }
+
+    fn u32(&self, at: usize) -> Option<u32> {
+        if self.big_endian { be32(self.data, at) } else { le32(self.data, at) }
+    }
+
+    fn first_ifd(&self) -> Option<usize> {
+        Some(self.u32(4)? as usize)
+    }
+
+    /// Value of a SHORT or LONG tag in the IFD at `ifd`.
+    fn find(&self, ifd: usize, tag: u16) -> Option<u32> {
+        let count = self.u16(ifd)? as usize;
+        (0..count).find_map(|i| {
+            let entry = ifd + 2 + i * 12;
+            if self.u16(entry)? != tag {
+                return None;
+            }
+            match self.u16(entry + 2)? {
+                3 => self.u16(entry + 8).map(u32::from),
+                4 => self.u32(entry + 8),
+                _ => None,
+            }
+        })
+    }
+}
+
+fn be16(data: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_be_bytes(data.get(at..at + 2)?.try_into().ok()?))
+}
+
+fn le16(data: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(data.get(at..at + 2)?.try_into().ok()?))
+}
+
+fn be32(data: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(data.get(at..at + 4)?.try_into().ok()?))
+}
+
+fn le24(data: &[u8], at: usize) -> Option<u32> {
+    let b = data.get(at..at + 3)?;
+    Some(u32::from_le_bytes([b[0], b[1], b[2], 0]))
+}
+
+fn le32(data: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(data.get(at..at + 4)?.try_into().ok()?))
+}
+
+// ── Blurhash ─────────────────────────────────────────────────────────────────
+
+const BASE83: &[u8; 83] =
+    b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz#$%*+,-.:;=?@[]^_{|}~";
+
+/// Encode packed RGB pixels as a blurhash with `x × y` components (1–9 each).
+pub fn blurhash(rgb: &[u8], width: usize, height: usize, x: usize, y: usize) -> String {
+    debug_assert_eq!(rgb.len(), width * height * 3);
+    let mut factors = Vec::with_capacity(x * y);
+    for j in 0..y {
+        for i in 0..x {
+            let normalisation = if i == 0 && j == 0 { 1.0 } else { 2.0 };
+            let mut sum = [0.0f64; 3];
+            for py in 0..height {
+                let cy = (std::f64::consts::PI * j as f64 * py as f64 / height as f64).cos();
+                for px in 0..width {
+                    let basis = normalisation
+                        * cy
+                        * (std::f64::consts::PI * i as f64 * px as f64 / width as f64).cos();
+                    let pixel = &rgb[(py * width + px) * 3..][..3];
+                    for (s, &c) in sum.iter_mut().zip(pixel) {
+                        *s += basis * srgb_to_linear(c);
+                    }
+                }
+            }
+            let scale = 1.0 / (width * height) as f64;
+            factors.push(sum.map(|s| s * scale));
+        }
+    }
+
+    let (dc, ac) = factors.split_first().expect("at least one component");
+    let mut hash = String::with_capacity(6 + 2 * ac.len());
+    push_base83(&mut hash, ((x - 1) + (y - 1) * 9) as u32, 1);
+
+    let max_ac = if ac.is_empty() {
+        push_base83(&mut hash, 0, 1);
+        1.0
+    } else {
+        let actual = ac.iter().flatten().fold(0.0f64, |m, v| m.max(v.abs()));
+        let quantised = (actual * 166.0 - 0.5).floor().clamp(0.0, 82.0) as u32;
+        push_base83(&mut hash, quantised, 1);
+        (quantised + 1) as f64 / 166.0
+    };
+
+    let dc_value = dc.iter().fold(0, |acc, &c| (acc << 8) | linear_to_srgb(c));
+    push_base83(&mut hash, dc_value, 4);
+    for component in ac {
+        let value = component.iter().fold(0, |acc, &c| {
+            let q = (sign_pow(c / max_ac, 0.5) * 9.0 + 9.5).floor().clamp(0.0, 18.0) as u32;
+            acc * 19 + q
+        });
+        push_base83(&mut hash, value, 2);
+    }
+    hash
+}
+
+fn push_base83(out: &mut String, value: u32, length: u32) {
+    for i in 1..=length {
+        let digit = (value / 83u32.pow(length - i)) % 83;
+        out.push(BASE83[digit as usize] as char);
+    }
+}
+
+fn srgb_to_linear(value: u8) -> f64 {
+    let v = value as f64 / 255.0;
+    if v <= 0.04045 { v / 12.92 } else { ((v + 0.055) / 1.055).powf(2.4) }
+}
+
+fn linear_to_srgb(value: f64) -> u32 {
+    let v = value.clamp(0.0, 1.0);
+    let srgb = if v <= 0.003_130_8 { v * 12.92 } else { 1.055 * v.powf(1.0 / 2.4) - 0.055 };
+    (srgb * 255.0 + 0.5) as u32
+}
+
+fn sign_pow(value: f64, exp: f64) -> f64 {
+    value.abs().powf(exp).copysign(value)
+}
+
+// ── ffmpeg processing ────────────────────────────────────────────────────────
+
+/// Side length of the pixel grid sampled for blurhash.
+const BLURHASH_SAMPLE: u32 = 32;
+
+/// What the media job derives from an image.
+#[derive(Debug)]
+pub struct ProcessedImage {
+    pub width: u32,
+    pub height: u32,
+    pub blurhash: String,
+    /// `None` when the image already fits within the thumbnail size.
+    pub thumbnail: Option<Thumbnail>,
+}
+
+#[derive(Debug)]
+pub struct Thumbnail {
+    pub data: Vec<u8>,
+    pub content_type: &'static str,
+    pub extension: &'static str,
+}
+
+/// Thumbnail and blurhash generator backed by an ffmpeg binary.
+#[derive(Clone)]
+pub struct MediaProcessor {
+    ffmpeg: String,
+    thumbnail_size: u32,
+}
+
+impl MediaProcessor {
+    /// Build a processor from config. Returns `None` when processing is disabled.
+    pub fn from_config(cfg: &MediaConfig) -> Option<Self> {
+        cfg.enabled.then(|| Self {
+            ffmpeg: cfg.ffmpeg_binary.clone(),
+            thumbnail_size: cfg.thumbnail_size.max(1),
+        })
+    }
+
+    /// Decode an image once and write both the thumbnail and the blurhash
+    /// sample from it. Animated images use their first frame.
+    pub async fn process(&self, data: &[u8], content_type: &str) -> Result<ProcessedImage> {
+        let (width, height) = dimensions(data).context("Unrecognised image header")?;
+        if width == 0 || height == 0 {
+            bail!("Image has no pixels");
+        }
+
+        // Undo EXIF rotation ourselves; `-noautorotate` stops ffmpeg from
+        // applying it a second time where it would.
+        let orient = match content_type {
+            "image/jpeg" => orientation_filter(jpeg_orientation(data).unwrap_or(1)),
+            _ => None,
+        };
+        let filter = |scale: String| match orient {
+            Some(o) => format!("{o},{scale}"),
+            None => scale,
+        };
+
+        let work = ScratchDir::new("nexus-media").await?;
+        let input = work.path().join("input");
+        tokio::fs::write(&input, data).await.context("Failed to write image scratch file")?;
+
+        let longest = width.max(height);
+        let thumbnail = (longest > self.thumbnail_size).then(|| {
+            let (w, h) = fit(width, height, self.thumbnail_size);
+            // Keep transparency for formats that can have it.
+            let (content_type, extension) = match content_type {
+                "image/png" | "image/gif" | "image/webp" => ("image/png", "png"),
+                _ => ("image/jpeg", "jpg"),
+            };
+            (work.path().join(format!("thumbnail.{extension}")), w, h, content_type, extension)
+        });
+        let (sample_w, sample_h) = fit(width, height, BLURHASH_SAMPLE);
+        let sample = work.path().join("sample.rgb");
+
+        let mut args: Vec<String> = ["-nostdin", "-loglevel", "error", "-y", "-noautorotate", "-i"]
+            .map(String::from)
+            .to_vec();
+        args.push(input.to_string_lossy().into_owned());
+        if let Some((path, w, h, _, _)) = &thumbnail {
+            args.extend([
+                "-frames:v".into(), "1".into(),
+                "-vf".into(), filter(format!("scale={w}:{h}:flags=lanczos")),
+                "-q:v".into(), "3".into(),
+                path.to_string_lossy().into_owned(),
+            ]);
+        }
+        args.extend([
+            "-frames:v".into(), "1".into(),
+            "-vf".into(), filter(format!("scale={sample_w}:{sample_h}")),
+            "-f".into(), "rawvideo".into(),
+            "-pix_fmt".into(), "rgb24".into(),
+            sample.to_string_lossy().into_owned(),
+        ]);
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        run(&self.ffmpeg, &args).await.context("ffmpeg image processing failed")?;
+
+        let pixels = tokio::fs::read(&sample).await.context("ffmpeg produced no sample")?;
+        let (sample_w, sample_h) = (sample_w as usize, sample_h as usize);
+        if pixels.len() != sample_w * sample_h * 3 {
+            bail!("Unexpected blurhash sample size {} bytes", pixels.len());
+        }
+        let (x, y) = if width >= height { (4, 3) } else { (3, 4) };
+
+        let thumbnail = match thumbnail {
+            Some((path, _, _, content_type, extension)) => Some(Thumbnail {
+                data: tokio::fs::read(&path).await.context("ffmpeg produced no thumbnail")?,
+                content_type,
+                extension,
+            }),
+            None => None,
+        };
+
+        Ok(ProcessedImage {
+            width,
+            height,
+            blurhash: blurhash(&pixels, sample_w, sample_h, x, y),
+            thumbnail,
+        })
+    }
+}
+
+/// Scale `width × height` down so the longest edge is `max`.
+fn fit(width: u32, height: u32, max: u32) -> (u32, u32) {
+    let longest = width.max(height);
+    if longest <= max {
+        return (width, height);
+    }
+    let scale = |v: u32| ((v as u64 * max as u64 + longest as u64 / 2) / longest as u64).max(1) as u32;
+    (scale(width), scale(height))
+}
+
+/// ffmpeg filter that turns an image with the given EXIF orientation upright.
+fn orientation_filter(orientation: u16) -> Option<&'static str> {
+    Some(match orientation {
+        2 => "hflip",
+        3 => "hflip,vflip",
+        4 => "vflip",
+        5 => "transpose=0",
+        6 => "transpose=1",
+        7 => "transpose=3",
+        8 => "transpose=2",
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn png(width: u32, height: u32, extra: &[(&[u8; 4], &[u8])]) -> Vec<u8> {
+        let mut out = b"\x89PNG\r\n\x1a\n".to_vec();
+        let mut chunk = |kind: &[u8; 4], body: &[u8]| {
+            out.extend_from_slice(&(body.len() as u32).to_be_bytes());
+            out.extend_from_slice(kind);
+            out.extend_from_slice(body);
+            out.extend_from_slice(&[0; 4]); // CRC isn't checked here
+        };
+        let mut ihdr = width.to_be_bytes().to_vec();
+        ihdr.extend_from_slice(&height.to_be_bytes());
+        ihdr.extend_from_slice(&[8, 6, 0, 0, 0]);
+        chunk(b"IHDR", &ihdr);
+        for (kind, body) in extra {
+            chunk(kind, body);
+        }
+        chunk(b"IEND", &[]);
+        out
+    }
+
+    /// A JPEG skeleton: SOI, an EXIF block with orientation and a GPS-ish
+    /// tag, a comment, SOF0, SOS and a few bytes of "scan data".
+    fn jpeg(width: u16, height: u16, orientation: u16) -> Vec<u8> {
+        let mut tiff = b"II*\0".to_vec();
+        tiff.extend_from_slice(&8u32.to_le_bytes());
+        tiff.extend_from_slice(&2u16.to_le_bytes());
+        for (tag, value) in [(TAG_ORIENTATION, orientation), (0x8825, 99)] {
+            tiff.extend_from_slice(&tag.to_le_bytes());
+            tiff.extend_from_slice(&3u16.to_le_bytes());
+            tiff.extend_from_slice(&1u32.to_le_bytes());
+            tiff.extend_from_slice(&value.to_le_bytes());
+            tiff.extend_from_slice(&[0, 0]);
+        }
+        tiff.extend_from_slice(&0u32.to_le_bytes());
+
+        let segment = |marker: u8, body: &[u8]| {
+            let mut s = vec![0xFF, marker];
+            s.extend_from_slice(&((body.len() + 2) as u16).to_be_bytes());
+            s.extend_from_slice(body);
+            s
+        };
+        let mut out = vec![0xFF, 0xD8];
+        out.extend(segment(0xE0, b"JFIF\0\x01\x01\0\0\x01\0\x01\0\0"));
+        out.extend(segment(APP1, &[b"Exif\0\0".as_slice(), &tiff].concat()));
+        out.extend(segment(COM, b"taken at home"));
+        let mut sof = vec![8];
+        sof.extend_from_slice(&height.to_be_bytes());
+        sof.extend_from_slice(&width.to_be_bytes());
+        sof.extend_from_slice(&[1, 1, 0x11, 0]);
+        out.extend(segment(0xC0, &sof));
+        out.extend(segment(SOS, &[1, 1, 0, 0, 0x3F, 0]));
+        out.extend_from_slice(&[0x12, 0xFF, 0x00, 0x34, 0xFF, 0xD9]);
+        out
+    }
+
+    #[test]
+    fn reads_header_dimensions() {
+        assert_eq!(dimensions(&png(640, 480, &[])), Some((640, 480)));
+
+        let mut gif = b"GIF89a".to_vec();
+        gif.extend_from_slice(&[0x20, 0x03, 0x58, 0x02]);
+        assert_eq!(dimensions(&gif), Some((800, 600)));
+
+        let mut webp = b"RIFF\0\0\0\0WEBPVP8X\x0a\0\0\0\0\0\0\0".to_vec();
+        webp.extend_from_slice(&[0x7F, 0x07, 0x00, 0x37, 0x04, 0x00]);
+        assert_eq!(dimensions(&webp), Some((1920, 1080)));
+
+        assert_eq!(dimensions(&jpeg(4000, 3000, 1)), Some((4000, 3000)));
+        assert_eq!(dimensions(b"not an image"), None);
+    }
+
+    #[test]
+    fn rotated_jpeg_reports_display_size() {
+        assert_eq!(jpeg_orientation(&jpeg(4000, 3000, 6)), Some(6));
+        assert_eq!(dimensions(&jpeg(4000, 3000, 6)), Some((3000, 4000)));
+    }
+
+    #[test]
+    fn strips_jpeg_metadata_but_keeps_orientation() {
+        let original = jpeg(4000, 3000, 6);
+        let stripped = strip_metadata("image/jpeg", original.clone());
+
+        assert!(stripped.len() < original.len());
+        assert!(!stripped.windows(13).any(|w| w == b"taken at home"));
+        assert_eq!(jpeg_orientation(&stripped), Some(6));
+        assert_eq!(dimensions(&stripped), Some((3000, 4000)));
+        // Scan data is untouched.
+        assert!(stripped.ends_with(&[0x12, 0xFF, 0x00, 0x34, 0xFF, 0xD9]));
+
+        let upright = strip_metadata("image/jpeg", jpeg(10, 10, 1));
+        assert_eq!(jpeg_orientation(&upright), None);
+        assert!(!upright.windows(4).any(|w| w == b"Exif"));
+    }
+
+    #[test]
+    fn strips_png_text_chunks() {
+        let original = png(2, 2, &[(b"tEXt", b"Author\0someone"), (b"eXIf", b"MM\0*")]);
+        let stripped = strip_metadata("image/png", original);
+        assert_eq!(stripped, png(2, 2, &[]));
+    }
+
+    #[test]
+    fn strips_webp_exif_and_flags() {
+        let mut webp = b"RIFF\0\0\0\0WEBP".to_vec();
+        webp.extend_from_slice(b"VP8X\x0a\0\0\0\x08\0\0\0\x01\0\0\x01\0\0");
+        webp.extend_from_slice(b"EXIF\x03\0\0\0abc\0");
+        let stripped = strip_metadata("image/webp", webp);
+        assert!(!stripped.windows(4).any(|w| w == b"EXIF"));
+        assert_eq!(stripped[20] & 0x08, 0);
+        assert_eq!(le32(&stripped, 4), Some(stripped.len() as u32 - 8));
+        assert_eq!(dimensions(&stripped), Some((2, 2)));
+    }
+
+    #[test]
+    fn unparseable_files_are_kept() {
+        let data = b"\xFF\xD8garbage".to_vec();
+        assert_eq!(strip_metadata("image/jpeg", data.clone()), data);
+    }
+
+    #[test]
+    fn blurhash_of_black_image() {
+        let black = vec![0u8; 8 * 6 * 3];
+        assert_eq!(blurhash(&black, 8, 6, 4, 3), "L00000fQfQfQfQfQfQfQfQfQfQfQ");
+    }
+
+    #[test]
+    fn blurhash_length_follows_components() {
+        let pixels: Vec<u8> = (0..16 * 16 * 3).map(|i| (i * 7 % 256) as u8).collect();
+        let hash = blurhash(&pixels, 16, 16, 3, 4);
+        assert_eq!(hash.len(), 4 + 2 * 3 * 4);
+        assert_eq!(&hash[..1], "T"); // (3 - 1) + (4 - 1) * 9 = 29
+    }
+
+    #[test]
+    fn fits_longest_edge() {
+        assert_eq!(fit(4000, 3000, 400), (400, 300));
+        assert_eq!(fit(100, 5000, 400), (8, 400));
+        assert_eq!(fit(200, 100, 400), (200, 100));
+    }
+}
@@ -1,27 +1,72 @@
 //! Middleware — authentication extraction, rate limiting, security headers, etc.
 
 use axum::{
-    extract::Request,
-    http::header,
+    extract::{ConnectInfo, Extension, Request, State},
+    http::{header, HeaderValue, Method},
     middleware::Next,
     response::Response,
 };
-use nexus_common::error::NexusError;
+use nexus_common::{error::NexusError, models::user::user_flags};
+use nexus_db::repository::{bots, legal_consents, oauth2, sessions, users};
+use std::{
+    net::{IpAddr, SocketAddr},
+    sync::Arc,
+    time::Instant,
+};
 
-use crate::auth;
+use crate::{auth, AppState};
 
 /// Authentication context extracted from the Authorization header.
 #[derive(Debug, Clone)]
 pub struct AuthContext {
     pub user_id: uuid::Uuid,
     pub username: String,
+    /// Client IP address, when it can be determined (see [`client_ip`]).
+    pub ip: Option<IpAddr>,
+    /// Login session the access token belongs to; `None` for tokens issued
+    /// before sessions existed.
+    pub session_id: Option<uuid::Uuid>,
 }
 
 /// Extract and validate the JWT from the Authorization: Bearer <token> header.
+///
+/// Route layers are added without the state, so it comes from the
+/// `Extension` [`crate::build_router`] puts on every request.
 pub async fn auth_middleware(
+    Extension(state): Extension<Arc<AppState>>,
     mut request: Request,
     next: Next,
 ) -> Result<Response, NexusError> {
+    let auth_ctx = authenticate_user(&state, &request).await?;
+
+    // Insert auth context into request extensions for handlers to use
+    request.extensions_mut().insert(auth_ctx);
+
+    Ok(next.run(request).await)
+}
+
+/// Like [`auth_middleware`], but only lets instance staff (users with the
+/// `STAFF` flag) through. Guards the `/admin` routes.
+pub async fn staff_middleware(
+    State(state): State<Arc<AppState>>,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, NexusError> {
+    let auth_ctx = authenticate_user(&state, &request).await?;
+    let user = users::find_by_id(&state.db.pool, auth_ctx.user_id)
+        .await?
+        .ok_or(NexusError::Forbidden)?;
+    if user.flags & user_flags::STAFF == 0 {
+        return Err(NexusError::Forbidden);
+    }
+
+    request.extensions_mut().insert(auth_ctx);
+    Ok(next.run(request).await)
+}
+
+/// Validate the `Bearer` access token on `request`. A token whose session
+/// has been revoked is refused, though it hasn't expired.
+async fn authenticate_user(state: &AppState, request: &Request) -> Result<AuthContext, NexusError> {
     let auth_header = request
         .headers()
         .get(header::AUTHORIZATION)
@@ -45,18 +90,316 @@ pub async fn auth_middleware(
         .sub
         .parse::<uuid::Uuid>()
         .map_err(|_| NexusError::InvalidToken)?;
+    let session_id: Option<uuid::Uuid> = claims.sid.and_then(|sid| sid.parse().ok());
+    if let Some(session_id) = session_id
+        && !state.live_sessions.contains(session_id)
+    {
+        if !sessions::exists(&state.db.pool, session_id).await? {
+            return Err(NexusError::InvalidToken);
+        }
+        state.live_sessions.insert(session_id, user_id);
+    }
 
-    let auth_ctx = AuthContext {
+    Ok(AuthContext {
         user_id,
         username: claims.username,
+        ip: client_ip(request),
+        session_id,
+    })
+}
+
+/// Authentication context for requests made with a bot token.
+#[derive(Debug, Clone)]
+pub struct BotContext {
+    pub application_id: uuid::Uuid,
+    pub owner_id: uuid::Uuid,
+    pub ip: Option<IpAddr>,
+}
+
+/// Who is calling an application-scoped route: the application's developer
+/// (user JWT) or the bot itself (bot token).
+#[derive(Debug, Clone)]
+pub enum AppCaller {
+    User(AuthContext),
+    Bot(BotContext),
+}
+
+impl AppCaller {
+    /// Whether this caller may manage `application_id`, owned by `owner_id`.
+    pub fn can_manage(&self, application_id: uuid::Uuid, owner_id: uuid::Uuid) -> bool {
+        match self {
+            AppCaller::User(auth) => auth.user_id == owner_id,
+            AppCaller::Bot(bot) => bot.application_id == application_id,
+        }
+    }
+}
+
+/// Authenticate `Authorization: Bot <token>` or `Bearer <jwt>` and insert an
+/// [`AppCaller`] (plus the matching [`BotContext`] / [`AuthContext`]).
+///
+/// Bot tokens are looked up by hash on every request, so a regenerated token
+/// stops working at once. When the application has an IP allowlist, requests
+/// from other addresses are refused. Every bot-token request is counted in
+/// the application's daily metrics.
+pub async fn bot_auth_middleware(
+    State(state): State<Arc<AppState>>,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, NexusError> {
+    let token = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bot "))
+        .map(str::to_owned);
+    let Some(token) = token else {
+        // Developer portal request — regular user auth
+        let auth_ctx = authenticate_user(&state, &request).await?;
+        request.extensions_mut().insert(auth_ctx.clone());
+        request.extensions_mut().insert(AppCaller::User(auth_ctx));
+        return Ok(next.run(request).await);
     };
 
-    // Insert auth context into request extensions for handlers to use
-    request.extensions_mut().insert(auth_ctx);
+    let bot = bots::get_bot_by_token_hash(&state.db.pool, &auth::hash_bot_token(&token))
+        .await?
+        .ok_or(NexusError::InvalidToken)?;
+    let ip = client_ip(&request);
+
+    if !nexus_common::net::ip_allowed(&bot.ip_allowlist, ip) {
+        record_bot_request(&state, bot.id, true, false, 0);
+        tracing::info!(bot_id = %bot.id, ip = ?ip, "Bot request refused by IP allowlist");
+        return Err(NexusError::Forbidden);
+    }
+
+    let ctx = BotContext {
+        application_id: bot.id,
+        owner_id: bot.owner_id,
+        ip,
+    };
+    request.extensions_mut().insert(ctx.clone());
+    request.extensions_mut().insert(AppCaller::Bot(ctx));
 
+    let started = Instant::now();
+    let response = next.run(request).await;
+    let status = response.status();
+    record_bot_request(
+        &state,
+        bot.id,
+        false,
+        status.is_client_error() || status.is_server_error(),
+        started.elapsed().as_millis() as i64,
+    );
+    Ok(response)
+}
+
+/// Count a bot request without holding up the response.
+fn record_bot_request(
+    state: &AppState,
+    bot_id: uuid::Uuid,
+    rejected: bool,
+    error: bool,
+    latency_ms: i64,
+) {
+    let pool = state.db.pool.clone();
+    tokio::spawn(async move {
+        if let Err(e) = bots::record_request(&pool, bot_id, rejected, error, latency_ms).await {
+            tracing::warn!(%bot_id, error = %e, "Failed to record bot request metrics");
+        }
+    });
+}
+
+/// Authentication context for requests made with an OAuth2 access token.
+#[derive(Debug, Clone)]
+pub struct OAuth2Context {
+    pub application_id: uuid::Uuid,
+    pub user_id: uuid::Uuid,
+    pub scopes: Vec<String>,
+}
+
+impl OAuth2Context {
+    /// Fail with `Forbidden` unless the token was granted `scope`.
+    pub fn require(&self, scope: &str) -> Result<(), NexusError> {
+        if self.scopes.iter().any(|s| s == scope) {
+            Ok(())
+        } else {
+            Err(NexusError::Forbidden)
+        }
+    }
+}
+
+/// Authenticate `Authorization: Bearer <access token>` issued by
+/// [`crate::oauth2`] and insert an [`OAuth2Context`]. Expired and revoked
+/// tokens are refused.
+pub async fn oauth2_auth_middleware(
+    State(state): State<Arc<AppState>>,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, NexusError> {
+    let token = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .ok_or(NexusError::Unauthorized)?;
+    let grant = oauth2::find_by_access_token(&state.db.pool, &crate::oauth2::hash_token(token))
+        .await?
+        .ok_or(NexusError::InvalidToken)?;
+
+    request.extensions_mut().insert(OAuth2Context {
+        application_id: grant.application_id,
+        user_id: grant.user_id,
+        scopes: grant.scopes,
+    });
     Ok(next.run(request).await)
 }
 
+// ── Rate limits ───────────────────────────────────────────────────────────────
+
+/// Apply [`crate::ratelimit`] limits to a request.
+///
+/// Every response carries the route bucket's state, Discord-style:
+/// `X-RateLimit-Limit`, `X-RateLimit-Remaining`, `X-RateLimit-Reset` (Unix
+/// seconds), `X-RateLimit-Reset-After` (seconds) and `X-RateLimit-Bucket`. A
+/// refused request gets a 429 `RATE_LIMITED` error with `retry_after_ms`,
+/// `Retry-After`, and `X-RateLimit-Global: true` when the global limit was
+/// the one hit.
+pub async fn rate_limit(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    use crate::ratelimit::RouteClass;
+    use axum::extract::{FromRequestParts, MatchedPath, RawPathParams};
+    use axum::response::IntoResponse;
+
+    if !state.rate_limits.enabled() {
+        return next.run(request).await;
+    }
+
+    let caller = rate_limit_caller(&state, &request).await;
+    let (mut parts, body) = request.into_parts();
+    let template = parts
+        .extensions
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_owned())
+        .unwrap_or_else(|| "unmatched".to_owned());
+    let major = RawPathParams::from_request_parts(&mut parts, &())
+        .await
+        .ok()
+        .and_then(|params| {
+            let find = |name: &str| params.iter().find(|(k, _)| *k == name).map(|(_, v)| v.to_owned());
+            find("channel_id").or_else(|| find("server_id"))
+        });
+    let request = Request::from_parts(parts, body);
+
+    let bucket = format!("{} {template}", request.method());
+    let class = if request.method() == Method::POST && template.ends_with("/channels/{channel_id}/messages") {
+        RouteClass::MessageSend
+    } else {
+        RouteClass::Default
+    };
+    let key = match &major {
+        Some(major) => format!("{bucket}:{major}"),
+        None => bucket.clone(),
+    };
+    let decision = state
+        .rate_limits
+        .check(state.db.redis.as_ref(), &caller, &key, class)
+        .await;
+
+    let mut response = if decision.allowed() {
+        next.run(request).await
+    } else {
+        let refused = if decision.global.allowed { decision.route } else { decision.global };
+        let mut response = NexusError::RateLimited {
+            retry_after_ms: refused.retry_after.as_millis() as u64,
+        }
+        .into_response();
+        let h = response.headers_mut();
+        h.insert(header::RETRY_AFTER, HeaderValue::from(refused.retry_after.as_secs_f64().ceil() as u64));
+        if !decision.global.allowed {
+            h.insert("x-ratelimit-global", HeaderValue::from_static("true"));
+        }
+        tracing::debug!(%caller, %bucket, global = !decision.global.allowed, "Request rate limited");
+        response
+    };
+
+    let route = decision.route;
+    let reset_at = std::time::SystemTime::now() + route.reset_after;
+    let reset_at = reset_at
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs_f64())
+        .unwrap_or(0.0);
+    let h = response.headers_mut();
+    h.insert("x-ratelimit-limit", HeaderValue::from(route.limit));
+    h.insert("x-ratelimit-remaining", HeaderValue::from(route.remaining));
+    for (name, value) in [
+        ("x-ratelimit-reset", format!("{reset_at:.3}")),
+        ("x-ratelimit-reset-after", format!("{:.3}", route.reset_after.as_secs_f64())),
+        ("x-ratelimit-bucket", bucket),
+    ] {
+        if let Ok(value) = HeaderValue::from_str(&value) {
+            h.insert(name, value);
+        }
+    }
+    response
+}
+
+/// Who a request is counted against: the user of a valid access token, the
+/// bot behind a valid bot token, or else the client IP. Tokens that don't
+/// resolve count against the IP, so made-up ones can't buy fresh buckets.
+async fn rate_limit_caller(state: &AppState, request: &Request) -> String {
+    let authorization = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok());
+    if let Some(token) = authorization.and_then(|v| v.strip_prefix("Bot "))
+        && let Ok(Some(bot)) = bots::get_bot_by_token_hash(&state.db.pool, &auth::hash_bot_token(token)).await
+    {
+        return format!("bot:{}", bot.id);
+    }
+    if authorization.is_some()
+        && let Ok(auth) = authenticate_user(state, request).await
+    {
+        return format!("user:{}", auth.user_id);
+    }
+    match client_ip(request) {
+        Some(ip) => format!("ip:{ip}"),
+        None => "ip:unknown".to_owned(),
+    }
+}
+
+/// The client IP, for abuse heuristics and bot IP allowlists.
+///
+/// The socket peer address, unless the peer is one of `http.trusted_proxies`
+/// — then the address those proxies forwarded (see
+/// [`nexus_common::net::client_ip`]).
+pub fn client_ip(request: &Request) -> Option<IpAddr> {
+    ip_from(request.extensions(), request.headers())
+}
+
+fn ip_from(extensions: &axum::http::Extensions, headers: &axum::http::HeaderMap) -> Option<IpAddr> {
+    let peer = extensions
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ci| ci.0.ip());
+    let trusted = nexus_common::config::get().http.trusted_proxy_list();
+    nexus_common::net::client_ip(peer, headers, &trusted)
+}
+
+/// [`client_ip`] as an extractor, for handlers outside the auth middleware.
+pub struct ClientIp(pub Option<IpAddr>);
+
+impl<S: Send + Sync> axum::extract::FromRequestParts<S> for ClientIp {
+    type Rejection = std::convert::Infallible;
+
+    async fn from_request_parts(
+        parts: &mut axum::http::request::Parts,
+        _state: &S,
+    ) -> Result<Self, Self::Rejection> {
+        Ok(ClientIp(ip_from(&parts.extensions, &parts.headers)))
+    }
+}
+
 /// Extract AuthContext from request extensions.
 ///
 /// Usage in handlers:
@@ -71,67 +414,193 @@ impl AuthContext {
     }
 }
 
-// ── Security headers ──────────────────────────────────────────────────────────
+// ── Terms of service consent ──────────────────────────────────────────────────
+
+/// Refuse authenticated requests from users who haven't consented to the
+/// current terms (see [`crate::legal`]) with 451 `CONSENT_REQUIRED`.
+///
+/// Requests without a valid access token pass through untouched; the
+/// routes' own authentication decides what to do with them.
+pub async fn require_consent(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Result<Response, NexusError> {
+    use axum::extract::MatchedPath;
+
+    let Some(version) = nexus_common::config::get().legal.required_version() else {
+        return Ok(next.run(request).await);
+    };
+    let Ok(auth) = authenticate_user(&state, &request).await else {
+        return Ok(next.run(request).await);
+    };
+    let template = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().trim_start_matches("/api/v1"))
+        .unwrap_or_default();
+    if crate::legal::exempt(request.method(), template) || state.consents.contains(auth.user_id) {
+        return Ok(next.run(request).await);
+    }
+
+    if !legal_consents::has_consented(&state.db.pool, auth.user_id, version).await? {
+        return Err(NexusError::ConsentRequired {
+            version: version.to_owned(),
+        });
+    }
+    state.consents.insert(auth.user_id);
+    Ok(next.run(request).await)
+}
+
+// ── Tracing ───────────────────────────────────────────────────────────────────
+
+/// The span each request is handled in. A `traceparent` header (from a
+/// traced client, or a federating server) makes it part of the caller's
+/// trace.
+pub fn request_span<B>(request: &axum::http::Request<B>) -> tracing::Span {
+    let span = tracing::info_span!(
+        "request",
+        method = %request.method(),
+        uri = %request.uri(),
+        version = ?request.version(),
+    );
+    if let Some(traceparent) = request
+        .headers()
+        .get(nexus_common::telemetry::TRACEPARENT)
+        .and_then(|v| v.to_str().ok())
+    {
+        nexus_common::telemetry::set_parent(&span, traceparent);
+    }
+    span
+}
+
+// ── Metrics ───────────────────────────────────────────────────────────────────
+
+/// Count every request and time it, by method, route and status:
+/// `nexus_http_requests_total` and `nexus_http_request_duration_seconds`.
+///
+/// The route is the matched pattern (`/api/v1/channels/{channel_id}/messages`),
+/// never the raw path, so IDs don't each get their own series.
+pub async fn track_metrics(request: Request, next: Next) -> Response {
+    let route = request
+        .extensions()
+        .get::<axum::extract::MatchedPath>()
+        .map(|path| path.as_str().to_owned())
+        .unwrap_or_else(|| "unmatched".to_owned());
+    let method = request.method().to_string();
+    let started = Instant::now();
+
+    let response = next.run(request).await;
+
+    let status = response.status().as_u16().to_string();
+    let labels = [("method", method), ("route", route), ("status", status)];
+    metrics::counter!("nexus_http_requests_total", &labels).increment(1);
+    metrics::histogram!("nexus_http_request_duration_seconds", &labels).record(started.elapsed());
+    response
+}
+
+// ── CORS and security headers ─────────────────────────────────────────────────
+
+/// Response headers browsers may read on a cross-origin response.
+const EXPOSED_HEADERS: [&str; 7] = [
+    "retry-after",
+    "x-ratelimit-limit",
+    "x-ratelimit-remaining",
+    "x-ratelimit-reset",
+    "x-ratelimit-reset-after",
+    "x-ratelimit-bucket",
+    "x-ratelimit-global",
+];
+
+/// CSP for the API's own (JSON) responses: nothing may load or frame them.
+const API_CSP: &str = "default-src 'none'; frame-ancestors 'none'";
+
+/// CSP for uploaded files under `/files`. Uploads are user content and may be
+/// HTML or SVG, so they are sandboxed: no scripts, no same-origin access to
+/// the API, no loading anything but themselves.
+const FILES_CSP: &str = "default-src 'none'; img-src 'self'; media-src 'self'; \
+                         style-src 'unsafe-inline'; sandbox; frame-ancestors 'none'";
+
+/// The CORS layer for the whole router, from `http.cors_*`.
+///
+/// With `"*"` any origin may call the API; that's safe because the API is
+/// authenticated with bearer tokens, never cookies, and credentials are
+/// refused. An explicit list may allow credentials.
+pub fn cors_layer(config: &nexus_common::config::HttpConfig) -> tower_http::cors::CorsLayer {
+    use tower_http::cors::{AllowHeaders, AllowOrigin, CorsLayer};
+
+    let layer = CorsLayer::new()
+        .allow_methods([
+            Method::GET,
+            Method::POST,
+            Method::PUT,
+            Method::PATCH,
+            Method::DELETE,
+            Method::OPTIONS,
+        ])
+        .allow_headers(AllowHeaders::mirror_request())
+        .expose_headers(EXPOSED_HEADERS.map(header::HeaderName::from_static))
+        .max_age(std::time::Duration::from_secs(config.cors_max_age_secs));
+
+    let Some(origins) = config.cors_origin_list() else {
+        if config.cors_allow_credentials {
+            tracing::warn!("http.cors_allow_credentials ignored: it needs an explicit http.cors_origins list");
+        }
+        return layer.allow_origin(AllowOrigin::any());
+    };
+
+    let origins: Vec<HeaderValue> = origins
+        .iter()
+        .filter_map(|origin| match origin.parse() {
+            Ok(value) => Some(value),
+            Err(_) => {
+                tracing::warn!(%origin, "Ignoring invalid origin in http.cors_origins");
+                None
+            }
+        })
+        .collect();
+    layer
+        .allow_origin(AllowOrigin::list(origins))
+        .allow_credentials(config.cors_allow_credentials)
+}
 
 /// Add defensive security headers to every HTTP response.
 ///
 /// Headers applied:
 /// - `X-Content-Type-Options: nosniff` — prevents MIME sniffing
 /// - `X-Frame-Options: DENY` — prevents clickjacking
-/// - `X-XSS-Protection: 1; mode=block` — legacy XSS protection
 /// - `Referrer-Policy: strict-origin-when-cross-origin`
 /// - `Permissions-Policy` — disables camera, mic, geolocation
-/// - `Strict-Transport-Security` — HSTS (max-age 2 years + preload)
-/// - `Content-Security-Policy` — restrictive CSP for API endpoints
+/// - `Strict-Transport-Security` — HSTS, unless `http.hsts_max_age_secs` is 0
+/// - `Content-Security-Policy` — [`API_CSP`], or the sandboxing [`FILES_CSP`]
+///   for uploaded files
 pub async fn security_headers(request: Request, next: Next) -> Response {
+    let is_file = request.uri().path().starts_with("/files/");
     let mut response = next.run(request).await;
     let h = response.headers_mut();
 
-    macro_rules! set {
-        ($name:expr, $val:expr) => {
-            if let Ok(v) = $val.parse::<axum::http::HeaderValue>() {
-                h.insert($name, v);
-            }
-        };
-    }
-
-    set!(
-        axum::http::header::HeaderName::from_static("x-content-type-options"),
-        "nosniff"
+    h.insert(header::X_CONTENT_TYPE_OPTIONS, HeaderValue::from_static("nosniff"));
+    h.insert(header::X_FRAME_OPTIONS, HeaderValue::from_static("DENY"));
+    h.insert(
+        header::REFERRER_POLICY,
+        HeaderValue::from_static("strict-origin-when-cross-origin"),
     );
-    set!(
-        axum::http::header::HeaderName::from_static("x-frame-options"),
-        "DENY"
-    );
-    set!(
-        axum::http::header::HeaderName::from_static("x-xss-protection"),
-        "1; mode=block"
-    );
-    set!(
-        axum::http::header::HeaderName::from_static("referrer-policy"),
-        "strict-origin-when-cross-origin"
-    );
-    set!(
-        axum::http::header::HeaderName::from_static("permissions-policy"),
-        "camera=(), microphone=(), geolocation=(), payment=()"
-    );
-    set!(
-        axum::http::header::HeaderName::from_static("strict-transport-security"),
-        "max-age=63072000; includeSubDomains; preload"
-    );
-    set!(
-        axum::http::header::HeaderName::from_static("content-security-policy"),
-        "default-src 'self'; \
-         script-src 'self'; \
-         style-src 'self' 'unsafe-inline'; \
-         img-src 'self' data: blob:; \
-         connect-src 'self' wss:; \
-         font-src 'self'; \
-         media-src 'self' blob:; \
-         worker-src 'self' blob:; \
-         frame-ancestors 'none'"
+    h.insert(
+        header::HeaderName::from_static("permissions-policy"),
+        HeaderValue::from_static("camera=(), microphone=(), geolocation=(), payment=()"),
     );
 
+    let hsts_max_age = nexus_common::config::get().http.hsts_max_age_secs;
+    if hsts_max_age > 0 {
+        h.insert(
+            header::STRICT_TRANSPORT_SECURITY,
+            HeaderValue::from_str(&format!("max-age={hsts_max_age}; includeSubDomains"))
+                .expect("valid header value"),
+        );
+    }
+
+    let csp = if is_file { FILES_CSP } else { API_CSP };
+    h.insert(header::CONTENT_SECURITY_POLICY, HeaderValue::from_static(csp));
+
     response
 }
-
@@ -0,0 +1,103 @@
+//! Pre-moderation — decides whether a new message is held for review.
+//!
+//! Channels opt in through their moderation settings. A message is held
+//! when it contains a link (`hold_links`), or when its author is not yet
+//! trusted (`hold_new_members`): they joined within `new_member_minutes`,
+//! or have never posted in the server. Members with MANAGE_MESSAGES are
+//! never held. Held messages wait in the queue until a moderator approves
+//! or rejects them.
+
+use chrono::{DateTime, Utc};
+use nexus_common::models::member::Member;
+use nexus_db::repository::{messages, moderation_queue::ModerationSettingsRow};
+
+/// Why a message was held.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HoldReason {
+    Link,
+    NewMember,
+    FirstMessage,
+}
+
+impl HoldReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Link => "link",
+            Self::NewMember => "new_member",
+            Self::FirstMessage => "first_message",
+        }
+    }
+}
+
+/// The checks that need no database access: links and join age.
+pub fn quick_reason(
+    settings: &ModerationSettingsRow,
+    joined_at: DateTime<Utc>,
+    now: DateTime<Utc>,
+    content: &str,
+) -> Option<HoldReason> {
+    if settings.hold_links && crate::spam::has_link(&content.to_lowercase()) {
+        return Some(HoldReason::Link);
+    }
+    let window = chrono::Duration::minutes(settings.new_member_minutes.max(0) as i64);
+    if settings.hold_new_members && now - joined_at < window {
+        return Some(HoldReason::NewMember);
+    }
+    None
+}
+
+/// Whether `member`'s message should be held. The message-history lookup
+/// only runs when the cheaper checks pass.
+pub async fn hold_reason(
+    pool: &sqlx::AnyPool,
+    settings: &ModerationSettingsRow,
+    member: &Member,
+    content: &str,
+) -> Result<Option<HoldReason>, sqlx::Error> {
+    if let Some(reason) = quick_reason(settings, member.joined_at, Utc::now(), content) {
+        return Ok(Some(reason));
+    }
+    if settings.hold_new_members
+        && !messages::has_posted_in_server(pool, member.user_id, member.server_id).await?
+    {
+        return Ok(Some(HoldReason::FirstMessage));
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    fn settings(hold_new_members: bool, hold_links: bool) -> ModerationSettingsRow {
+        ModerationSettingsRow {
+            channel_id: Uuid::nil(),
+            hold_new_members,
+            new_member_minutes: 60,
+            hold_links,
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn holds_links_only_when_enabled() {
+        let now = Utc::now();
+        let joined = now - chrono::Duration::days(30);
+        let msg = "check out HTTPS://example.com";
+        assert_eq!(quick_reason(&settings(false, true), joined, now, msg), Some(HoldReason::Link));
+        assert_eq!(quick_reason(&settings(false, false), joined, now, msg), None);
+        assert_eq!(quick_reason(&settings(false, true), joined, now, "no links here"), None);
+    }
+
+    #[test]
+    fn holds_recent_joins() {
+        let now = Utc::now();
+        let s = settings(true, false);
+        let recent = now - chrono::Duration::minutes(5);
+        let settled = now - chrono::Duration::minutes(61);
+        assert_eq!(quick_reason(&s, recent, now, "hello"), Some(HoldReason::NewMember));
+        assert_eq!(quick_reason(&s, settled, now, "hello"), None);
+        assert_eq!(quick_reason(&settings(false, false), recent, now, "hello"), None);
+    }
+}
@@ -0,0 +1,148 @@
+//! OAuth2 provider — lets third-party apps act for the users who authorize
+//! them, and add bots to servers.
+//!
+//! Every bot application is also an OAuth2 client: `client_id` is the
+//! application id and the client secret is generated from the developer
+//! portal. The authorization-code flow ([RFC 6749] §4.1, optionally with
+//! PKCE S256 from [RFC 7636]) runs through
+//! [`routes::oauth2`](crate::routes::oauth2):
+//!
+//! 1. The client sends the user to its consent page, which calls
+//!    `GET /oauth2/authorize` for what to show and `POST /oauth2/authorize`
+//!    once the user agrees. That returns the client's `redirect_uri` with a
+//!    single-use `code`.
+//! 2. The client exchanges the code at `POST /oauth2/token` for an access
+//!    token (valid [`ACCESS_TOKEN_TTL_SECS`]) and a refresh token.
+//! 3. Access tokens are sent as `Authorization: Bearer <token>` to the
+//!    `/oauth2/@me` routes, which return only what the granted [`scopes`]
+//!    allow.
+//!
+//! The `bot` scope adds the application's bot to a server the user manages
+//! instead of issuing a code; it can be combined with user scopes.
+//!
+//! [RFC 6749]: https://www.rfc-editor.org/rfc/rfc6749
+//! [RFC 7636]: https://www.rfc-editor.org/rfc/rfc7636
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use nexus_common::error::{NexusError, NexusResult};
+use rand::Rng;
+use sha2::{Digest, Sha256};
+
+/// How long an authorization code can be exchanged.
+pub const CODE_TTL_SECS: i64 = 10 * 60;
+/// How long an access token works before it must be refreshed.
+pub const ACCESS_TOKEN_TTL_SECS: i64 = 7 * 24 * 60 * 60;
+
+/// Scopes an application can ask for.
+pub mod scopes {
+    /// The user's profile, without their email.
+    pub const IDENTIFY: &str = "identify";
+    /// The user's email address.
+    pub const EMAIL: &str = "email";
+    /// The servers the user is in.
+    pub const GUILDS: &str = "guilds";
+    /// Add the application's bot to a server.
+    pub const BOT: &str = "bot";
+
+    pub const ALL: &[&str] = &[IDENTIFY, EMAIL, GUILDS, BOT];
+}
+
+/// Parse a space-separated `scope` parameter, dropping duplicates.
+pub fn parse_scopes(scope: &str) -> NexusResult<Vec<String>> {
+    let mut parsed: Vec<String> = Vec::new();
+    for name in scope.split_whitespace() {
+        if !scopes::ALL.contains(&name) {
+            return Err(NexusError::Validation {
+                message: format!("Unknown scope '{name}'"),
+            });
+        }
+        if !parsed.iter().any(|s| s == name) {
+            parsed.push(name.to_owned());
+        }
+    }
+    if parsed.is_empty() {
+        return Err(NexusError::Validation {
+            message: "At least one scope is required".into(),
+        });
+    }
+    Ok(parsed)
+}
+
+/// Whether `scopes` include any that act for the user, so a code is issued.
+pub fn has_user_scopes(scopes: &[String]) -> bool {
+    scopes.iter().any(|s| s != scopes::BOT)
+}
+
+/// A random code, token or client secret (256 bits, URL-safe).
+pub fn generate_token() -> String {
+    let bytes: [u8; 32] = rand::rng().random();
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+/// Codes, tokens and client secrets are stored hashed like bot tokens.
+pub fn hash_token(token: &str) -> String {
+    nexus_common::auth::hash_bot_token(token)
+}
+
+/// Whether `verifier` matches an S256 PKCE `challenge`.
+pub fn verify_pkce(challenge: &str, verifier: &str) -> bool {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes())) == challenge
+}
+
+/// `redirect_uri` with the authorization response appended.
+pub fn redirect_location(
+    redirect_uri: &str,
+    code: Option<&str>,
+    state: Option<&str>,
+) -> NexusResult<String> {
+    let mut url = url::Url::parse(redirect_uri).map_err(|_| NexusError::Validation {
+        message: "redirect_uri is not a valid URL".into(),
+    })?;
+    {
+        let mut query = url.query_pairs_mut();
+        if let Some(code) = code {
+            query.append_pair("code", code);
+        }
+        if let Some(state) = state {
+            query.append_pair("state", state);
+        }
+    }
+    // Don't leave a bare `?` when there was nothing to add.
+    if url.query() == Some("") {
+        url.set_query(None);
+    }
+    Ok(url.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scopes_are_validated_and_deduplicated() {
+        assert_eq!(parse_scopes("identify guilds identify").unwrap(), vec!["identify", "guilds"]);
+        assert!(parse_scopes("identify admin").is_err());
+        assert!(parse_scopes("  ").is_err());
+        assert!(!has_user_scopes(&parse_scopes("bot").unwrap()));
+        assert!(has_user_scopes(&parse_scopes("bot email").unwrap()));
+    }
+
+    #[test]
+    fn pkce_s256_matches_rfc_7636_example() {
+        let verifier = "dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk";
+        assert!(verify_pkce("E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuJlQIiw-cM", verifier));
+        assert!(!verify_pkce("E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuJlQIiw-cM", "other"));
+    }
+
+    #[test]
+    fn redirect_keeps_existing_query() {
+        assert_eq!(
+            redirect_location("https://app.example/cb?x=1", Some("abc"), Some("s t")).unwrap(),
+            "https://app.example/cb?x=1&code=abc&state=s+t"
+        );
+        assert_eq!(
+            redirect_location("https://app.example/cb", None, None).unwrap(),
+            "https://app.example/cb"
+        );
+    }
+}
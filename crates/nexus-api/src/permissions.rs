@@ -0,0 +1,324 @@
+//! Effective permission resolution for server members.
+//!
+//! Wraps [`nexus_common::permissions::compute_permissions`] with the
+//! database lookups it needs (member roles, role bits, channel overwrites).
+//! Route handlers go through [`in_channel`] / [`require`] rather than
+//! comparing against the server owner, so moderator roles are honoured.
+
+use nexus_common::{
+    error::{NexusError, NexusResult},
+    models::{channel::Channel, member::Member, role::Role, server::Server},
+    permissions::{
+        compute_permissions, explain_permissions, PermissionExplanation, PermissionOverwrite,
+        PermissionSource, Permissions,
+    },
+};
+use nexus_db::repository::{channels, members, roles, servers, voice_chat};
+use uuid::Uuid;
+
+/// A member's effective permissions in `server` (and `channel`, if given).
+///
+/// Returns `None` when the user is not a member. The server owner always
+/// has every permission.
+pub async fn resolve(
+    pool: &sqlx::AnyPool,
+    server: &Server,
+    channel: Option<&Channel>,
+    user_id: Uuid,
+) -> NexusResult<Option<Permissions>> {
+    let Some(member) = members::find_member(pool, user_id, server.id).await? else {
+        return Ok(None);
+    };
+    if server.owner_id == user_id {
+        return Ok(Some(Permissions::all()));
+    }
+
+    let server_roles = roles::list_server_roles(pool, server.id).await?;
+    let Some(voice_channel_id) = channel.and_then(|c| c.voice_channel_id) else {
+        return Ok(Some(for_member(server, channel, &member, &server_roles)));
+    };
+
+    // A voice channel's text chat follows the voice channel's overwrites.
+    let voice = channels::find_by_id(pool, voice_channel_id).await?;
+    let permissions = for_member(server, voice.as_ref(), &member, &server_roles);
+    let since = chrono::Utc::now() - chrono::Duration::hours(VOICE_CHAT_HISTORY_HOURS);
+    let participated = voice_chat::has_participated(pool, voice_channel_id, user_id, since).await?;
+    Ok(Some(for_voice_chat(permissions, participated)))
+}
+
+/// How long after leaving a voice channel its text chat stays visible.
+pub const VOICE_CHAT_HISTORY_HOURS: i64 = voice_chat::HISTORY_HOURS;
+
+/// Permissions in a voice channel's text chat, given those in the voice
+/// channel. Only members who are in the call, or were within
+/// [`VOICE_CHAT_HISTORY_HOURS`], can see it; MANAGE_MESSAGES always can.
+pub fn for_voice_chat(voice_permissions: Permissions, participated: bool) -> Permissions {
+    if participated || voice_permissions.has(Permissions::MANAGE_MESSAGES) {
+        voice_permissions
+    } else {
+        voice_permissions.difference(Permissions::VIEW_CHANNEL)
+    }
+}
+
+/// A user's effective permissions in `channel`.
+///
+/// Returns `None` for DM and group DM channels, which have no roles or
+/// overwrites; callers decide what participants may do there. Fails with
+/// `Forbidden` when the user isn't a member of the channel's server.
+pub async fn in_channel(
+    pool: &sqlx::AnyPool,
+    channel: &Channel,
+    user_id: Uuid,
+) -> NexusResult<Option<Permissions>> {
+    let Some(server_id) = channel.server_id else {
+        return Ok(None);
+    };
+    let server = servers::find_by_id(pool, server_id)
+        .await?
+        .ok_or(NexusError::NotFound {
+            resource: "Server".into(),
+        })?;
+    resolve(pool, &server, Some(channel), user_id)
+        .await?
+        .ok_or(NexusError::Forbidden)
+        .map(Some)
+}
+
+/// Fail with `MissingPermission`, naming what's missing, unless
+/// `permissions` cover all of `required`.
+pub fn require(permissions: Permissions, required: Permissions) -> NexusResult<()> {
+    if permissions.has(required) {
+        return Ok(());
+    }
+    let missing: Vec<&str> = required
+        .difference(permissions)
+        .iter_names()
+        .map(|(name, _)| name)
+        .collect();
+    Err(NexusError::MissingPermission {
+        permission: missing.join(", "),
+    })
+}
+
+/// Like [`resolve`], for callers that already hold the member and the
+/// server's roles (e.g. when checking many members at once).
+pub fn for_member(
+    server: &Server,
+    channel: Option<&Channel>,
+    member: &Member,
+    server_roles: &[Role],
+) -> Permissions {
+    if server.owner_id == member.user_id {
+        return Permissions::all();
+    }
+
+    let everyone = server_roles.iter().find(|r| r.is_default);
+    let base = everyone
+        .map(|r| Permissions::from_bits_truncate(r.permissions))
+        .unwrap_or_else(Permissions::default_everyone);
+    let role_permissions: Vec<Permissions> = server_roles
+        .iter()
+        .filter(|r| member.roles.contains(&r.id))
+        .map(|r| Permissions::from_bits_truncate(r.permissions))
+        .collect();
+
+    compute_permissions(
+        base,
+        &role_permissions,
+        &channel_overwrites(channel),
+        &member.roles,
+        member.user_id,
+        everyone.map(|r| r.id).unwrap_or(server.id),
+    )
+}
+
+/// What @everyone alone may do in `server` (and `channel`, if given) —
+/// what any member can, whatever their roles.
+pub fn for_everyone(server: &Server, channel: Option<&Channel>, server_roles: &[Role]) -> Permissions {
+    let everyone = server_roles.iter().find(|r| r.is_default);
+    let base = everyone
+        .map(|r| Permissions::from_bits_truncate(r.permissions))
+        .unwrap_or_else(Permissions::default_everyone);
+    compute_permissions(
+        base,
+        &[],
+        &channel_overwrites(channel),
+        &[],
+        Uuid::nil(),
+        everyone.map(|r| r.id).unwrap_or(server.id),
+    )
+}
+
+/// Like [`for_member`], but reports for every bit which role or channel
+/// overwrite granted or denied it.
+pub fn explain_for_member(
+    server: &Server,
+    channel: Option<&Channel>,
+    member: &Member,
+    server_roles: &[Role],
+) -> Vec<PermissionExplanation> {
+    if server.owner_id == member.user_id {
+        return Permissions::all()
+            .iter_names()
+            .map(|(name, _)| PermissionExplanation {
+                permission: name,
+                granted: true,
+                source: PermissionSource::Owner,
+            })
+            .collect();
+    }
+
+    let everyone = server_roles.iter().find(|r| r.is_default);
+    let base = everyone
+        .map(|r| Permissions::from_bits_truncate(r.permissions))
+        .unwrap_or_else(Permissions::default_everyone);
+    let roles: Vec<(Uuid, Permissions)> = server_roles
+        .iter()
+        .filter(|r| !r.is_default && member.roles.contains(&r.id))
+        .map(|r| (r.id, Permissions::from_bits_truncate(r.permissions)))
+        .collect();
+
+    explain_permissions(
+        base,
+        &roles,
+        &channel_overwrites(channel),
+        member.user_id,
+        everyone.map(|r| r.id).unwrap_or(server.id),
+    )
+}
+
+/// A channel's permission overwrites. Malformed overwrites are ignored
+/// rather than locking everyone out.
+fn channel_overwrites(channel: Option<&Channel>) -> Vec<PermissionOverwrite> {
+    channel
+        .and_then(|c| serde_json::from_value(c.permission_overwrites.clone()).ok())
+        .unwrap_or_default()
+}
+
+/// Position of a member's highest role (0 when they only have @everyone).
+pub fn top_role_position(member: &Member, server_roles: &[Role]) -> i32 {
+    server_roles
+        .iter()
+        .filter(|r| member.roles.contains(&r.id))
+        .map(|r| r.position)
+        .max()
+        .unwrap_or(0)
+}
+
+/// Whether `actor` may moderate `target`: the owner outranks everyone, no
+/// one outranks the owner, and otherwise the actor's highest role must sit
+/// strictly above the target's.
+pub fn outranks(server: &Server, actor: &Member, target: &Member, server_roles: &[Role]) -> bool {
+    if target.user_id == server.owner_id {
+        return false;
+    }
+    actor.user_id == server.owner_id
+        || top_role_position(actor, server_roles) > top_role_position(target, server_roles)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn require_names_missing_permissions() {
+        let moderator = Permissions::default_everyone() | Permissions::MANAGE_MESSAGES;
+        assert!(require(moderator, Permissions::MANAGE_MESSAGES).is_ok());
+        assert!(require(Permissions::ADMINISTRATOR, Permissions::PIN_MESSAGES).is_ok());
+
+        let err = require(
+            Permissions::default_everyone(),
+            Permissions::SEND_MESSAGES | Permissions::MANAGE_MESSAGES | Permissions::PIN_MESSAGES,
+        )
+        .unwrap_err();
+        match err {
+            NexusError::MissingPermission { permission } => {
+                assert_eq!(permission, "MANAGE_MESSAGES, PIN_MESSAGES");
+            }
+            other => panic!("unexpected error: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn voice_chat_needs_recent_participation() {
+        let everyone = Permissions::default_everyone();
+        assert_eq!(for_voice_chat(everyone, true), everyone);
+        assert!(!for_voice_chat(everyone, false).has(Permissions::VIEW_CHANNEL));
+        assert!(for_voice_chat(everyone, false).has(Permissions::CONNECT));
+
+        let moderator = everyone | Permissions::MANAGE_MESSAGES;
+        assert_eq!(for_voice_chat(moderator, false), moderator);
+        assert!(for_voice_chat(Permissions::ADMINISTRATOR, false).has(Permissions::VIEW_CHANNEL));
+    }
+
+    fn role(server_id: Uuid, position: i32) -> Role {
+        Role {
+            id: Uuid::new_v4(),
+            server_id,
+            name: format!("role-{position}"),
+            color: None,
+            hoist: false,
+            icon: None,
+            position,
+            permissions: 0,
+            mentionable: false,
+            is_default: false,
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+        }
+    }
+
+    fn member(server_id: Uuid, roles: &[&Role]) -> Member {
+        Member {
+            user_id: Uuid::new_v4(),
+            server_id,
+            nickname: None,
+            avatar: None,
+            roles: roles.iter().map(|r| r.id).collect(),
+            muted: false,
+            deafened: false,
+            joined_at: chrono::Utc::now(),
+            communication_disabled_until: None,
+            pending: false,
+        }
+    }
+
+    #[test]
+    fn role_hierarchy_decides_who_can_moderate() {
+        let server_id = Uuid::new_v4();
+        let (admin, moderator) = (role(server_id, 10), role(server_id, 5));
+        let roles = vec![admin.clone(), moderator.clone()];
+        let owner = member(server_id, &[]);
+        let server = Server {
+            id: server_id,
+            name: "test".into(),
+            description: None,
+            icon: None,
+            banner: None,
+            owner_id: owner.user_id,
+            region: None,
+            is_public: false,
+            features: serde_json::json!({}),
+            settings: serde_json::json!({}),
+            vanity_code: None,
+            member_count: 4,
+            max_file_size: None,
+            message_history: true,
+            preferred_locale: "en-US".into(),
+            message_retention_days: 0,
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+        };
+        let a = member(server_id, &[&admin, &moderator]);
+        let m = member(server_id, &[&moderator]);
+        let m2 = member(server_id, &[&moderator]);
+        let plain = member(server_id, &[]);
+
+        assert!(outranks(&server, &a, &m, &roles));
+        assert!(outranks(&server, &m, &plain, &roles));
+        assert!(!outranks(&server, &m, &m2, &roles));
+        assert!(!outranks(&server, &m, &a, &roles));
+        assert!(outranks(&server, &owner, &a, &roles));
+        assert!(!outranks(&server, &a, &owner, &roles));
+    }
+}
@@ -0,0 +1,601 @@
+//! Push notification delivery — WebPush, Firebase Cloud Messaging and APNs.
+//!
+//! Each service is enabled by its own credentials under `push.*`:
+//! - WebPush — VAPID key pair; payloads are encrypted per RFC 8291.
+//! - FCM     — a service account; OAuth tokens are minted and cached here.
+//! - APNs    — a `.p8` token-auth key; requests go over HTTP/2.
+//!
+//! The client is only used from the push background job
+//! (see [`crate::jobs::push`]), which decides who gets notified.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Context, Result};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use jsonwebtoken::{Algorithm, EncodingKey, Header};
+use nexus_common::{
+    config::PushConfig,
+    models::push::{PushPlatform, PushToken},
+};
+use ring::{aead, agreement, hkdf, rand::SecureRandom, signature};
+use serde::Deserialize;
+use tokio::sync::Mutex;
+
+/// Record size advertised in the WebPush content-coding header.
+const WEB_PUSH_RECORD_SIZE: u32 = 4096;
+/// How long push services should hold a notification for an offline device.
+const TTL_SECS: u64 = 24 * 60 * 60;
+/// VAPID and APNs provider tokens are reused for this long.
+const PROVIDER_TOKEN_LIFETIME: Duration = Duration::from_secs(50 * 60);
+
+const FCM_SCOPE: &str = "https://www.googleapis.com/auth/firebase.messaging";
+
+/// One notification, rendered for a single recipient.
+#[derive(Debug, Clone)]
+pub struct Notification {
+    pub title: String,
+    pub body: String,
+    /// Extra key/values delivered to the app (IDs for deep-linking).
+    pub data: BTreeMap<String, String>,
+    /// Notifications with the same tag replace each other on the device.
+    pub tag: Option<String>,
+}
+
+/// What happened to a delivery attempt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Delivery {
+    Sent,
+    /// The service no longer knows this token; it should be forgotten.
+    Expired,
+    /// No service is configured for the token's platform.
+    Unsupported,
+}
+
+/// Push client for every configured service.
+#[derive(Clone)]
+pub struct PushClient {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    http: reqwest::Client,
+    web_push: Option<WebPush>,
+    fcm: Option<Fcm>,
+    apns: Option<Apns>,
+}
+
+impl PushClient {
+    /// Build a client from config. Returns `Ok(None)` when push is disabled
+    /// or no service has credentials.
+    pub fn from_config(cfg: &PushConfig) -> Result<Option<Self>> {
+        if !cfg.enabled {
+            return Ok(None);
+        }
+        let web_push = (!cfg.vapid_private_key.is_empty())
+            .then(|| WebPush::new(cfg))
+            .transpose()?;
+        let fcm = (!cfg.fcm_service_account.is_empty())
+            .then(|| Fcm::new(&cfg.fcm_service_account))
+            .transpose()?;
+        let apns = (!cfg.apns_key_path.is_empty())
+            .then(|| Apns::new(cfg))
+            .transpose()?;
+        if web_push.is_none() && fcm.is_none() && apns.is_none() {
+            tracing::warn!("push.enabled is set but no push service has credentials");
+            return Ok(None);
+        }
+        Ok(Some(Self {
+            inner: Arc::new(Inner {
+                http: reqwest::Client::builder()
+                    .timeout(Duration::from_secs(15))
+                    .build()?,
+                web_push,
+                fcm,
+                apns,
+            }),
+        }))
+    }
+
+    /// Names of the configured services, for startup logging.
+    pub fn services(&self) -> Vec<&'static str> {
+        let inner = &self.inner;
+        [
+            inner.web_push.as_ref().map(|_| "webpush"),
+            inner.fcm.as_ref().map(|_| "fcm"),
+            inner.apns.as_ref().map(|_| "apns"),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+
+    /// Deliver `notification` to one device.
+    pub async fn send(&self, token: &PushToken, notification: &Notification) -> Result<Delivery> {
+        let inner = &self.inner;
+        match token.platform {
+            PushPlatform::WebPush => match &inner.web_push {
+                Some(w) => w.send(&inner.http, token, notification).await,
+                None => Ok(Delivery::Unsupported),
+            },
+            PushPlatform::Fcm => match &inner.fcm {
+                Some(f) => f.send(&inner.http, &token.token, notification).await,
+                None => Ok(Delivery::Unsupported),
+            },
+            PushPlatform::Apns => match &inner.apns {
+                Some(a) => a.send(&token.token, notification).await,
+                None => Ok(Delivery::Unsupported),
+            },
+        }
+    }
+}
+
+/// Whether WebPush subscription keys decode to a P-256 point and a 16-byte
+/// auth secret.
+pub fn valid_web_push_keys(p256dh: &str, auth: &str) -> bool {
+    let point = URL_SAFE_NO_PAD.decode(p256dh.trim_end_matches('='));
+    let secret = URL_SAFE_NO_PAD.decode(auth.trim_end_matches('='));
+    matches!((point, secret), (Ok(p), Ok(a)) if p.len() == 65 && p[0] == 0x04 && a.len() == 16)
+}
+
+fn b64(bytes: &[u8]) -> String {
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+fn unix_now() -> u64 {
+    chrono::Utc::now().timestamp().max(0) as u64
+}
+
+// ============================================================
+// WebPush
+// ============================================================
+
+struct WebPush {
+    subject: String,
+    /// Uncompressed public key, base64url — sent as the `k` parameter.
+    public_key: String,
+    key_pair: signature::EcdsaKeyPair,
+    rng: ring::rand::SystemRandom,
+}
+
+impl WebPush {
+    fn new(cfg: &PushConfig) -> Result<Self> {
+        if cfg.vapid_subject.is_empty() {
+            bail!("push.vapid_subject must be set for WebPush (e.g. mailto:admin@example.com)");
+        }
+        let private = URL_SAFE_NO_PAD
+            .decode(cfg.vapid_private_key.trim_end_matches('='))
+            .context("push.vapid_private_key is not base64url")?;
+        let public = URL_SAFE_NO_PAD
+            .decode(cfg.vapid_public_key.trim_end_matches('='))
+            .context("push.vapid_public_key is not base64url")?;
+        let rng = ring::rand::SystemRandom::new();
+        let key_pair = signature::EcdsaKeyPair::from_private_key_and_public_key(
+            &signature::ECDSA_P256_SHA256_FIXED_SIGNING,
+            &private,
+            &public,
+            &rng,
+        )
+        .map_err(|e| anyhow::anyhow!("Invalid VAPID key pair: {e}"))?;
+        Ok(Self {
+            subject: cfg.vapid_subject.clone(),
+            public_key: b64(&public),
+            key_pair,
+            rng,
+        })
+    }
+
+    /// VAPID JWT (RFC 8292) for the push service at `audience`.
+    fn vapid_token(&self, audience: &str) -> Result<String> {
+        let header = b64(br#"{"typ":"JWT","alg":"ES256"}"#);
+        let claims = serde_json::json!({
+            "aud": audience,
+            "exp": unix_now() + PROVIDER_TOKEN_LIFETIME.as_secs(),
+            "sub": self.subject,
+        });
+        let signing_input = format!("{header}.{}", b64(claims.to_string().as_bytes()));
+        let sig = self
+            .key_pair
+            .sign(&self.rng, signing_input.as_bytes())
+            .map_err(|_| anyhow::anyhow!("VAPID signing failed"))?;
+        Ok(format!("{signing_input}.{}", b64(sig.as_ref())))
+    }
+
+    async fn send(
+        &self,
+        http: &reqwest::Client,
+        token: &PushToken,
+        notification: &Notification,
+    ) -> Result<Delivery> {
+        let (Some(p256dh), Some(auth)) = (&token.p256dh, &token.auth) else {
+            return Ok(Delivery::Expired);
+        };
+        let endpoint = url::Url::parse(&token.token).context("Invalid WebPush endpoint")?;
+        let audience = endpoint.origin().ascii_serialization();
+
+        let payload = serde_json::json!({
+            "title": notification.title,
+            "body": notification.body,
+            "tag": notification.tag,
+            "data": notification.data,
+        });
+        let body = encrypt_web_push(
+            payload.to_string().as_bytes(),
+            &URL_SAFE_NO_PAD.decode(p256dh.trim_end_matches('='))?,
+            &URL_SAFE_NO_PAD.decode(auth.trim_end_matches('='))?,
+            &self.rng,
+        )?;
+
+        let response = http
+            .post(endpoint)
+            .header(
+                "Authorization",
+                format!("vapid t={}, k={}", self.vapid_token(&audience)?, self.public_key),
+            )
+            .header("Content-Encoding", "aes128gcm")
+            .header("Content-Type", "application/octet-stream")
+            .header("TTL", TTL_SECS.to_string())
+            .header("Urgency", "high")
+            .body(body)
+            .send()
+            .await?;
+        match response.status().as_u16() {
+            200..=299 => Ok(Delivery::Sent),
+            404 | 410 => Ok(Delivery::Expired),
+            status => bail!("WebPush endpoint returned {status}"),
+        }
+    }
+}
+
+/// Output length for ring's HKDF.
+struct Len(usize);
+
+impl hkdf::KeyType for Len {
+    fn len(&self) -> usize {
+        self.0
+    }
+}
+
+fn hkdf_sha256(salt: &[u8], ikm: &[u8], info: &[u8], out: &mut [u8]) -> Result<()> {
+    hkdf::Salt::new(hkdf::HKDF_SHA256, salt)
+        .extract(ikm)
+        .expand(&[info], Len(out.len()))
+        .and_then(|okm| okm.fill(out))
+        .map_err(|_| anyhow::anyhow!("HKDF output too long"))
+}
+
+/// Content encryption key and nonce for one WebPush message (RFC 8291 §3.4).
+fn web_push_keys(
+    ecdh_secret: &[u8],
+    auth_secret: &[u8],
+    ua_public: &[u8],
+    as_public: &[u8],
+    salt: &[u8],
+) -> Result<([u8; 16], [u8; 12])> {
+    let mut key_info = b"WebPush: info\0".to_vec();
+    key_info.extend_from_slice(ua_public);
+    key_info.extend_from_slice(as_public);
+    let mut ikm = [0u8; 32];
+    hkdf_sha256(auth_secret, ecdh_secret, &key_info, &mut ikm)?;
+
+    let mut cek = [0u8; 16];
+    let mut nonce = [0u8; 12];
+    hkdf_sha256(salt, &ikm, b"Content-Encoding: aes128gcm\0", &mut cek)?;
+    hkdf_sha256(salt, &ikm, b"Content-Encoding: nonce\0", &mut nonce)?;
+    Ok((cek, nonce))
+}
+
+/// Encrypt a WebPush payload with the `aes128gcm` content coding (RFC 8188,
+/// RFC 8291) as a single record.
+fn encrypt_web_push(
+    payload: &[u8],
+    ua_public: &[u8],
+    auth_secret: &[u8],
+    rng: &dyn SecureRandom,
+) -> Result<Vec<u8>> {
+    let as_private = agreement::EphemeralPrivateKey::generate(&agreement::ECDH_P256, rng)
+        .map_err(|_| anyhow::anyhow!("Failed to generate ECDH key"))?;
+    let as_public = as_private
+        .compute_public_key()
+        .map_err(|_| anyhow::anyhow!("Failed to compute ECDH public key"))?;
+    let as_public = as_public.as_ref().to_vec();
+    let mut salt = [0u8; 16];
+    rng.fill(&mut salt)
+        .map_err(|_| anyhow::anyhow!("Failed to generate salt"))?;
+
+    let peer = agreement::UnparsedPublicKey::new(&agreement::ECDH_P256, ua_public);
+    let ecdh_secret = agreement::agree_ephemeral(as_private, &peer, |s| s.to_vec())
+        .map_err(|_| anyhow::anyhow!("Invalid subscription public key"))?;
+    let (cek, nonce) = web_push_keys(&ecdh_secret, auth_secret, ua_public, &as_public, &salt)?;
+
+    // Single record: payload, then the 0x02 last-record delimiter, no padding
+    let mut record = payload.to_vec();
+    record.push(0x02);
+    let key = aead::LessSafeKey::new(
+        aead::UnboundKey::new(&aead::AES_128_GCM, &cek).map_err(|_| anyhow::anyhow!("Bad CEK"))?,
+    );
+    key.seal_in_place_append_tag(
+        aead::Nonce::assume_unique_for_key(nonce),
+        aead::Aad::empty(),
+        &mut record,
+    )
+    .map_err(|_| anyhow::anyhow!("WebPush encryption failed"))?;
+
+    let mut body = Vec::with_capacity(21 + as_public.len() + record.len());
+    body.extend_from_slice(&salt);
+    body.extend_from_slice(&WEB_PUSH_RECORD_SIZE.to_be_bytes());
+    body.push(as_public.len() as u8);
+    body.extend_from_slice(&as_public);
+    body.extend_from_slice(&record);
+    Ok(body)
+}
+
+// ============================================================
+// FCM
+// ============================================================
+
+#[derive(Deserialize)]
+struct ServiceAccount {
+    project_id: String,
+    client_email: String,
+    private_key: String,
+    token_uri: String,
+}
+
+struct Fcm {
+    project_id: String,
+    client_email: String,
+    token_uri: String,
+    key: EncodingKey,
+    /// OAuth access token and when it expires.
+    access_token: Mutex<Option<(String, Instant)>>,
+}
+
+impl Fcm {
+    fn new(path: &str) -> Result<Self> {
+        let raw = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read FCM service account {path}"))?;
+        let account: ServiceAccount =
+            serde_json::from_str(&raw).context("Invalid FCM service account JSON")?;
+        Ok(Self {
+            key: EncodingKey::from_rsa_pem(account.private_key.as_bytes())
+                .context("Invalid FCM service account private key")?,
+            project_id: account.project_id,
+            client_email: account.client_email,
+            token_uri: account.token_uri,
+            access_token: Mutex::new(None),
+        })
+    }
+
+    /// A cached OAuth access token, refreshed a minute before it expires.
+    async fn access_token(&self, http: &reqwest::Client) -> Result<String> {
+        let mut cached = self.access_token.lock().await;
+        if let Some((token, expires)) = cached.as_ref()
+            && *expires > Instant::now() + Duration::from_secs(60)
+        {
+            return Ok(token.clone());
+        }
+
+        let now = unix_now();
+        let assertion = jsonwebtoken::encode(
+            &Header::new(Algorithm::RS256),
+            &serde_json::json!({
+                "iss": self.client_email,
+                "scope": FCM_SCOPE,
+                "aud": self.token_uri,
+                "iat": now,
+                "exp": now + 3600,
+            }),
+            &self.key,
+        )?;
+
+        #[derive(Deserialize)]
+        struct TokenResponse {
+            access_token: String,
+            expires_in: u64,
+        }
+        let response: TokenResponse = http
+            .post(&self.token_uri)
+            .form(&[
+                ("grant_type", "urn:ietf:params:oauth:grant-type:jwt-bearer"),
+                ("assertion", assertion.as_str()),
+            ])
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        let expires = Instant::now() + Duration::from_secs(response.expires_in);
+        *cached = Some((response.access_token.clone(), expires));
+        Ok(response.access_token)
+    }
+
+    async fn send(
+        &self,
+        http: &reqwest::Client,
+        token: &str,
+        notification: &Notification,
+    ) -> Result<Delivery> {
+        let access_token = self.access_token(http).await?;
+        let mut message = serde_json::json!({
+            "token": token,
+            "notification": { "title": notification.title, "body": notification.body },
+            "data": notification.data,
+            "android": { "priority": "high", "ttl": format!("{TTL_SECS}s") },
+        });
+        if let Some(tag) = &notification.tag {
+            message["android"]["notification"] = serde_json::json!({ "tag": tag });
+        }
+
+        let response = http
+            .post(format!(
+                "https://fcm.googleapis.com/v1/projects/{}/messages:send",
+                self.project_id
+            ))
+            .bearer_auth(access_token)
+            .json(&serde_json::json!({ "message": message }))
+            .send()
+            .await?;
+        let status = response.status().as_u16();
+        if response.status().is_success() {
+            return Ok(Delivery::Sent);
+        }
+        let body = response.text().await.unwrap_or_default();
+        if status == 404 || body.contains("UNREGISTERED") {
+            return Ok(Delivery::Expired);
+        }
+        bail!("FCM returned {status}: {body}")
+    }
+}
+
+// ============================================================
+// APNs
+// ============================================================
+
+struct Apns {
+    /// APNs requires HTTP/2.
+    http: reqwest::Client,
+    host: &'static str,
+    topic: String,
+    key_id: String,
+    team_id: String,
+    key: EncodingKey,
+    provider_token: Mutex<Option<(String, Instant)>>,
+}
+
+impl Apns {
+    fn new(cfg: &PushConfig) -> Result<Self> {
+        if cfg.apns_key_id.is_empty() || cfg.apns_team_id.is_empty() || cfg.apns_topic.is_empty() {
+            bail!("push.apns_key_id, push.apns_team_id and push.apns_topic must be set for APNs");
+        }
+        let pem = std::fs::read(&cfg.apns_key_path)
+            .with_context(|| format!("Failed to read APNs key {}", cfg.apns_key_path))?;
+        Ok(Self {
+            http: reqwest::Client::builder()
+                .http2_prior_knowledge()
+                .timeout(Duration::from_secs(15))
+                .build()?,
+            host: if cfg.apns_sandbox {
+                "https://api.sandbox.push.apple.com"
+            } else {
+                "https://api.push.apple.com"
+            },
+            topic: cfg.apns_topic.clone(),
+            key_id: cfg.apns_key_id.clone(),
+            team_id: cfg.apns_team_id.clone(),
+            key: EncodingKey::from_ec_pem(&pem).context("Invalid APNs auth key")?,
+            provider_token: Mutex::new(None),
+        })
+    }
+
+    /// Provider JWT; Apple rejects tokens older than an hour and throttles
+    /// ones refreshed too often, so it's reused for 50 minutes.
+    async fn provider_token(&self) -> Result<String> {
+        let mut cached = self.provider_token.lock().await;
+        if let Some((token, issued)) = cached.as_ref()
+            && issued.elapsed() < PROVIDER_TOKEN_LIFETIME
+        {
+            return Ok(token.clone());
+        }
+        let mut header = Header::new(Algorithm::ES256);
+        header.kid = Some(self.key_id.clone());
+        let token = jsonwebtoken::encode(
+            &header,
+            &serde_json::json!({ "iss": self.team_id, "iat": unix_now() }),
+            &self.key,
+        )?;
+        *cached = Some((token.clone(), Instant::now()));
+        Ok(token)
+    }
+
+    async fn send(&self, device_token: &str, notification: &Notification) -> Result<Delivery> {
+        let mut payload = serde_json::json!({
+            "aps": {
+                "alert": { "title": notification.title, "body": notification.body },
+                "sound": "default",
+                "thread-id": notification.tag,
+            },
+        });
+        for (k, v) in &notification.data {
+            payload[k] = serde_json::Value::String(v.clone());
+        }
+
+        let response = self
+            .http
+            .post(format!("{}/3/device/{device_token}", self.host))
+            .bearer_auth(self.provider_token().await?)
+            .header("apns-topic", &self.topic)
+            .header("apns-push-type", "alert")
+            .header("apns-priority", "10")
+            .header("apns-expiration", (unix_now() + TTL_SECS).to_string())
+            .json(&payload)
+            .send()
+            .await?;
+        let status = response.status().as_u16();
+        if response.status().is_success() {
+            return Ok(Delivery::Sent);
+        }
+        let body = response.text().await.unwrap_or_default();
+        if status == 410 || body.contains("BadDeviceToken") || body.contains("Unregistered") {
+            return Ok(Delivery::Expired);
+        }
+        bail!("APNs returned {status}: {body}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Decrypt a single-record `aes128gcm` body as the browser would.
+    fn decrypt(
+        body: &[u8],
+        ua_private: agreement::EphemeralPrivateKey,
+        ua_public: &[u8],
+        auth: &[u8],
+    ) -> Vec<u8> {
+        let salt = &body[..16];
+        assert_eq!(u32::from_be_bytes(body[16..20].try_into().unwrap()), WEB_PUSH_RECORD_SIZE);
+        let id_len = body[20] as usize;
+        let as_public = &body[21..21 + id_len];
+        let peer = agreement::UnparsedPublicKey::new(&agreement::ECDH_P256, as_public);
+        let secret = agreement::agree_ephemeral(ua_private, &peer, |s| s.to_vec()).unwrap();
+        let (cek, nonce) = web_push_keys(&secret, auth, ua_public, as_public, salt).unwrap();
+
+        let key = aead::LessSafeKey::new(aead::UnboundKey::new(&aead::AES_128_GCM, &cek).unwrap());
+        let mut record = body[21 + id_len..].to_vec();
+        key.open_in_place(aead::Nonce::assume_unique_for_key(nonce), aead::Aad::empty(), &mut record)
+            .unwrap()
+            .to_vec()
+    }
+
+    #[test]
+    fn web_push_payload_round_trips() {
+        let rng = ring::rand::SystemRandom::new();
+        let ua_private = agreement::EphemeralPrivateKey::generate(&agreement::ECDH_P256, &rng).unwrap();
+        let ua_public = ua_private.compute_public_key().unwrap().as_ref().to_vec();
+        let auth = [7u8; 16];
+
+        let body = encrypt_web_push(br#"{"title":"hi"}"#, &ua_public, &auth, &rng).unwrap();
+        let plain = decrypt(&body, ua_private, &ua_public, &auth);
+        assert_eq!(plain, b"{\"title\":\"hi\"}\x02");
+    }
+
+    #[test]
+    fn web_push_rejects_invalid_subscription_key() {
+        let rng = ring::rand::SystemRandom::new();
+        assert!(encrypt_web_push(b"x", &[0x04; 65], &[0; 16], &rng).is_err());
+    }
+
+    #[test]
+    fn validates_subscription_keys() {
+        let point = b64(&[[0x04].as_slice(), &[1u8; 64]].concat());
+        let auth = b64(&[2u8; 16]);
+        assert!(valid_web_push_keys(&point, &auth));
+        assert!(valid_web_push_keys(&format!("{point}="), &format!("{auth}==")));
+        assert!(!valid_web_push_keys(&auth, &auth));
+        assert!(!valid_web_push_keys(&point, &b64(&[2u8; 8])));
+        assert!(!valid_web_push_keys("not base64!", &auth));
+    }
+}
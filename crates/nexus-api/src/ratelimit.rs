@@ -0,0 +1,209 @@
+//! REST rate limits — token buckets per caller, across all routes and per route.
+//!
+//! Every request under `/api/v1` takes a token from two buckets: the caller's
+//! global bucket and the bucket for the route it hit, keyed by the route
+//! template and its major parameter (`channel_id`, else `server_id`), so
+//! sending to one channel doesn't use up another's budget. Sending messages
+//! has its own, tighter limit. Callers are identified by user (access
+//! token), bot application (bot token) or, unauthenticated, by IP.
+//!
+//! With Redis the buckets are shared by every API process; without it (lite
+//! mode) each process keeps its own. A Redis error lets the request through
+//! on the in-memory buckets rather than failing it.
+//!
+//! Limits come from [`RateLimitConfig`]; the middleware that applies them is
+//! [`crate::middleware::rate_limit`].
+
+use nexus_common::config::RateLimitConfig;
+use nexus_common::ratelimit::{Limit, Outcome, TokenBucket};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Full in-memory buckets are dropped every this many checks.
+const PRUNE_EVERY: u64 = 4096;
+
+/// Which limit a route falls under.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RouteClass {
+    Default,
+    /// `POST /channels/{channel_id}/messages`
+    MessageSend,
+}
+
+/// Both buckets' outcomes for one request.
+#[derive(Debug, Clone, Copy)]
+pub struct Decision {
+    pub global: Outcome,
+    pub route: Outcome,
+}
+
+impl Decision {
+    pub fn allowed(&self) -> bool {
+        self.global.allowed && self.route.allowed
+    }
+}
+
+/// Buckets shared by the API routes.
+pub struct RateLimiter {
+    config: RateLimitConfig,
+    buckets: Mutex<HashMap<String, (Limit, TokenBucket)>>,
+    checks: AtomicU64,
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self {
+            config,
+            buckets: Mutex::new(HashMap::new()),
+            checks: AtomicU64::new(0),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.config.enabled
+    }
+
+    fn global_limit(&self) -> Limit {
+        Limit::new(self.config.global_limit, self.config.global_window_secs)
+    }
+
+    fn route_limit(&self, class: RouteClass) -> Limit {
+        match class {
+            RouteClass::Default => Limit::new(self.config.route_limit, self.config.route_window_secs),
+            RouteClass::MessageSend => Limit::new(self.config.message_limit, self.config.message_window_secs),
+        }
+    }
+
+    /// Take a token for `caller` from the global bucket and from `bucket`.
+    /// The route bucket is only charged when the global one allowed the request.
+    pub async fn check(
+        &self,
+        redis: Option<&redis::aio::ConnectionManager>,
+        caller: &str,
+        bucket: &str,
+        class: RouteClass,
+    ) -> Decision {
+        let now = now_ms();
+        let global = self.take(redis, &format!("rl:{caller}"), self.global_limit(), now).await;
+        let route_limit = self.route_limit(class);
+        let route = if global.allowed {
+            self.take(redis, &format!("rl:{caller}:{bucket}"), route_limit, now).await
+        } else {
+            route_limit.outcome(false, 0.0)
+        };
+        Decision { global, route }
+    }
+
+    /// Take a token from an ad-hoc bucket `key` with its own `limit`, for
+    /// checks outside the per-route scheme (e.g. registrations per IP).
+    pub async fn take_limit(
+        &self,
+        redis: Option<&redis::aio::ConnectionManager>,
+        key: &str,
+        limit: Limit,
+    ) -> Outcome {
+        self.take(redis, &format!("rl:{key}"), limit, now_ms()).await
+    }
+
+    async fn take(
+        &self,
+        redis: Option<&redis::aio::ConnectionManager>,
+        key: &str,
+        limit: Limit,
+        now: u64,
+    ) -> Outcome {
+        if let Some(redis) = redis {
+            let mut conn = redis.clone();
+            match nexus_db::redis_pool::take_token(&mut conn, key, limit, now).await {
+                Ok((allowed, tokens)) => return limit.outcome(allowed, tokens),
+                Err(e) => tracing::warn!(error = %e, "Rate limit check failed; using local buckets"),
+            }
+        }
+        self.take_local(key, limit, now)
+    }
+
+    fn take_local(&self, key: &str, limit: Limit, now: u64) -> Outcome {
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        if self.checks.fetch_add(1, Ordering::Relaxed) % PRUNE_EVERY == PRUNE_EVERY - 1 {
+            buckets.retain(|_, (limit, bucket)| !bucket.is_full(*limit, now));
+        }
+        let (stored, bucket) = buckets
+            .entry(key.to_owned())
+            .or_insert_with(|| (limit, TokenBucket::new(limit, now)));
+        *stored = limit;
+        bucket.take(limit, now)
+    }
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limiter() -> RateLimiter {
+        RateLimiter::new(RateLimitConfig {
+            enabled: true,
+            global_limit: 5,
+            global_window_secs: 1,
+            route_limit: 3,
+            route_window_secs: 10,
+            message_limit: 2,
+            message_window_secs: 5,
+            gateway_limit: 120,
+            gateway_window_secs: 60,
+            typing_limit: 5,
+            typing_window_secs: 10,
+            presence_limit: 5,
+            presence_window_secs: 60,
+            request_members_limit: 10,
+            request_members_window_secs: 60,
+            live_share_limit: 30,
+            live_share_window_secs: 60,
+        })
+    }
+
+    #[tokio::test]
+    async fn routes_have_separate_buckets() {
+        let l = limiter();
+        for _ in 0..3 {
+            assert!(l.check(None, "user:a", "GET /a", RouteClass::Default).await.allowed());
+        }
+        let refused = l.check(None, "user:a", "GET /a", RouteClass::Default).await;
+        assert!(refused.global.allowed);
+        assert!(!refused.route.allowed);
+        // Another route, and another caller, are unaffected.
+        assert!(l.check(None, "user:a", "GET /b", RouteClass::Default).await.allowed());
+        assert!(l.check(None, "user:b", "GET /a", RouteClass::Default).await.allowed());
+    }
+
+    #[tokio::test]
+    async fn the_global_bucket_spans_routes() {
+        let l = limiter();
+        for i in 0..5 {
+            let bucket = format!("GET /{i}");
+            assert!(l.check(None, "user:a", &bucket, RouteClass::Default).await.allowed());
+        }
+        let refused = l.check(None, "user:a", "GET /5", RouteClass::Default).await;
+        assert!(!refused.global.allowed);
+    }
+
+    #[tokio::test]
+    async fn message_sends_use_the_message_limit() {
+        let l = limiter();
+        let bucket = "POST /channels/{channel_id}/messages:1";
+        for _ in 0..2 {
+            assert!(l.check(None, "user:a", bucket, RouteClass::MessageSend).await.allowed());
+        }
+        let refused = l.check(None, "user:a", bucket, RouteClass::MessageSend).await;
+        assert_eq!(refused.route.limit, 2);
+        assert!(!refused.allowed());
+    }
+}
@@ -0,0 +1,254 @@
+//! Registration abuse protection — who may sign up, and what they must
+//! prove first.
+//!
+//! [`RegistrationConfig::mode`] decides who may register: anyone (`open`),
+//! holders of a single-use token issued by staff (`invite_only`), or anyone,
+//! with the account unusable until staff approve it (`approval`). Staff can
+//! override the mode at runtime; tokens, applications and the override are
+//! managed through [`routes::registration`](crate::routes::registration).
+//! On top of that, [`RegistrationConfig::challenge`] can demand a
+//! proof-of-work or an hCaptcha, and each IP may only attempt `ip_limit`
+//! registrations per `ip_window_secs`.
+//!
+//! Clients learn what is required from `GET /auth/registration`.
+//! Proof-of-work challenges are stateless: `{expires}.{salt}.{mac}`, MAC'd
+//! with the JWT secret. The client finds a `challenge_response` such that
+//! `SHA-256("{challenge}:{username}:{challenge_response}")` starts with
+//! `difficulty` zero bits. Covering the username means one solution can't
+//! register a second account.
+
+use std::net::IpAddr;
+use std::time::Duration;
+
+use hmac::{Hmac, Mac};
+use nexus_common::config::{RegistrationChallenge, RegistrationConfig, RegistrationMode};
+use nexus_common::error::{NexusError, NexusResult};
+use nexus_common::models::user::CreateUserRequest;
+use nexus_common::ratelimit::Limit;
+use nexus_db::repository::instance_settings;
+use rand::Rng;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::AppState;
+
+/// `instance_settings` key of the staff override of `registration.mode`.
+pub const MODE_SETTING: &str = "registration.mode";
+
+/// How long a proof-of-work challenge may be answered.
+pub const POW_TTL_SECS: i64 = 600;
+
+const HCAPTCHA_VERIFY_URL: &str = "https://api.hcaptcha.com/siteverify";
+
+/// What `GET /auth/registration` tells clients.
+#[derive(Debug, Serialize)]
+pub struct RegistrationInfo {
+    pub mode: RegistrationMode,
+    /// The challenge to pass, if any.
+    pub challenge: Option<Challenge>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Challenge {
+    Pow { challenge: String, difficulty: u32 },
+    Hcaptcha { site_key: String },
+}
+
+impl RegistrationInfo {
+    pub fn new(config: &RegistrationConfig, mode: RegistrationMode, secret: &str) -> Self {
+        let challenge = match config.challenge {
+            RegistrationChallenge::None => None,
+            RegistrationChallenge::Pow => Some(Challenge::Pow {
+                challenge: issue_pow_challenge(secret, chrono::Utc::now().timestamp()),
+                difficulty: config.pow_difficulty,
+            }),
+            RegistrationChallenge::Hcaptcha => Some(Challenge::Hcaptcha {
+                site_key: config.hcaptcha_site_key.clone(),
+            }),
+        };
+        Self { mode, challenge }
+    }
+}
+
+/// The mode in effect: staff's override, or else the config's.
+pub async fn current_mode(state: &AppState) -> NexusResult<RegistrationMode> {
+    let configured = nexus_common::config::get().registration.mode;
+    let Some(value) = instance_settings::get(&state.db.pool, MODE_SETTING).await? else {
+        return Ok(configured);
+    };
+    Ok(serde_json::from_value(value).unwrap_or_else(|e| {
+        tracing::warn!(error = %e, "Ignoring unreadable registration mode override");
+        configured
+    }))
+}
+
+/// Take one registration attempt from `ip`'s budget.
+pub async fn check_ip_limit(state: &AppState, config: &RegistrationConfig, ip: Option<IpAddr>) -> NexusResult<()> {
+    if config.ip_limit == 0 {
+        return Ok(());
+    }
+    let key = match ip {
+        Some(ip) => format!("register:{ip}"),
+        None => "register:unknown".to_owned(),
+    };
+    let limit = Limit::new(config.ip_limit, config.ip_window_secs);
+    let outcome = state.rate_limits.take_limit(state.db.redis.as_ref(), &key, limit).await;
+    if !outcome.allowed {
+        return Err(NexusError::RateLimited {
+            retry_after_ms: outcome.retry_after.as_millis() as u64,
+        });
+    }
+    Ok(())
+}
+
+/// Check the configured challenge's answer in `request`.
+pub async fn verify_challenge(
+    config: &RegistrationConfig,
+    secret: &str,
+    request: &CreateUserRequest,
+    ip: Option<IpAddr>,
+) -> NexusResult<()> {
+    let failed = |message: &str| NexusError::Validation { message: message.into() };
+    let response = request.challenge_response.as_deref().unwrap_or("");
+    match config.challenge {
+        RegistrationChallenge::None => Ok(()),
+        RegistrationChallenge::Pow => {
+            let challenge = request.challenge.as_deref().unwrap_or("");
+            let now = chrono::Utc::now().timestamp();
+            if verify_pow(secret, challenge, &request.username, response, config.pow_difficulty, now) {
+                Ok(())
+            } else {
+                Err(failed("Proof-of-work challenge missing, expired or unsolved"))
+            }
+        }
+        RegistrationChallenge::Hcaptcha => {
+            if response.is_empty() {
+                return Err(failed("hCaptcha response required"));
+            }
+            match verify_hcaptcha(&config.hcaptcha_secret, response, ip).await {
+                Ok(true) => Ok(()),
+                Ok(false) => Err(failed("hCaptcha verification failed")),
+                Err(e) => Err(NexusError::Internal(anyhow::anyhow!("hCaptcha siteverify: {e}"))),
+            }
+        }
+    }
+}
+
+// ─── Proof of work ───────────────────────────────────────────────────────────
+
+/// A fresh challenge, valid for [`POW_TTL_SECS`] from `now`.
+pub fn issue_pow_challenge(secret: &str, now: i64) -> String {
+    let salt: [u8; 16] = rand::rng().random();
+    let body = format!("{}.{}", now + POW_TTL_SECS, hex::encode(salt));
+    format!("{body}.{}", challenge_mac(secret, &body))
+}
+
+/// Whether `solution` solves `challenge` for `username`, and the challenge
+/// is ours and unexpired.
+pub fn verify_pow(
+    secret: &str,
+    challenge: &str,
+    username: &str,
+    solution: &str,
+    difficulty: u32,
+    now: i64,
+) -> bool {
+    let Some((body, mac)) = challenge.rsplit_once('.') else { return false };
+    let Some(expires) = body.split_once('.').and_then(|(e, _)| e.parse::<i64>().ok()) else {
+        return false;
+    };
+    let mac_ok = hex::decode(mac).is_ok_and(|mac| {
+        let mut expected = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+        expected.update(body.as_bytes());
+        expected.verify_slice(&mac).is_ok()
+    });
+    mac_ok && now <= expires && pow_bits(challenge, username, solution) >= difficulty
+}
+
+fn challenge_mac(secret: &str, body: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(body.as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
+
+/// Leading zero bits of the solution's hash.
+fn pow_bits(challenge: &str, username: &str, solution: &str) -> u32 {
+    let hash = Sha256::digest(format!("{challenge}:{username}:{solution}").as_bytes());
+    let mut bits = 0;
+    for byte in hash {
+        bits += byte.leading_zeros();
+        if byte != 0 {
+            break;
+        }
+    }
+    bits
+}
+
+// ─── hCaptcha ────────────────────────────────────────────────────────────────
+
+/// Ask hCaptcha whether `response` is a solved captcha for our site.
+async fn verify_hcaptcha(secret: &str, response: &str, ip: Option<IpAddr>) -> Result<bool, reqwest::Error> {
+    let client = reqwest::Client::builder().timeout(Duration::from_secs(10)).build()?;
+    let mut form = vec![("secret", secret.to_owned()), ("response", response.to_owned())];
+    if let Some(ip) = ip {
+        form.push(("remoteip", ip.to_string()));
+    }
+    let body: serde_json::Value = client
+        .post(HCAPTCHA_VERIFY_URL)
+        .form(&form)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    Ok(body.get("success").and_then(serde_json::Value::as_bool) == Some(true))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: &str = "secret";
+    const NOW: i64 = 1_700_000_000;
+
+    fn solve(challenge: &str, username: &str, difficulty: u32) -> String {
+        (0u64..)
+            .map(|n| n.to_string())
+            .find(|n| pow_bits(challenge, username, n) >= difficulty)
+            .unwrap()
+    }
+
+    #[test]
+    fn solved_challenges_verify_for_their_username_only() {
+        let challenge = issue_pow_challenge(SECRET, NOW);
+        let solution = solve(&challenge, "alice", 8);
+        assert!(verify_pow(SECRET, &challenge, "alice", &solution, 8, NOW));
+        assert!(!verify_pow(SECRET, &challenge, "mallory", &solution, 8, NOW));
+        assert!(!verify_pow(SECRET, &challenge, "alice", &solution, 30, NOW));
+    }
+
+    #[test]
+    fn challenges_expire_and_cannot_be_forged() {
+        let challenge = issue_pow_challenge(SECRET, NOW);
+        let solution = solve(&challenge, "alice", 4);
+        assert!(!verify_pow(SECRET, &challenge, "alice", &solution, 4, NOW + POW_TTL_SECS + 1));
+        assert!(!verify_pow("other", &challenge, "alice", &solution, 4, NOW));
+
+        // Pushing the expiry out breaks the MAC.
+        let (_, rest) = challenge.split_once('.').unwrap();
+        let extended = format!("{}.{rest}", NOW + 10 * POW_TTL_SECS);
+        let solution = solve(&extended, "alice", 4);
+        assert!(!verify_pow(SECRET, &extended, "alice", &solution, 4, NOW));
+        assert!(!verify_pow(SECRET, "garbage", "alice", "0", 0, NOW));
+    }
+
+    #[test]
+    fn leading_zero_bits_span_bytes() {
+        let challenge = issue_pow_challenge(SECRET, NOW);
+        let solution = solve(&challenge, "bob", 12);
+        let hash = Sha256::digest(format!("{challenge}:bob:{solution}").as_bytes());
+        assert_eq!(hash[0], 0);
+        assert!(hash[1] < 0x10);
+    }
+}
@@ -0,0 +1,59 @@
+//! Reminders — parsing the relative delays users schedule them with.
+//!
+//! A delay is one or more `<number><unit>` parts, e.g. `45m`, `1h30m` or
+//! `2d`. Units are `s`, `m`, `h`, `d` and `w`.
+
+use chrono::Duration;
+
+/// Parse a relative delay such as `1h30m`. `None` if it is malformed,
+/// zero, or overflows.
+pub fn parse_delay(input: &str) -> Option<Duration> {
+    let input = input.trim();
+    if input.is_empty() {
+        return None;
+    }
+
+    let mut total: i64 = 0;
+    let mut number = String::new();
+    for c in input.chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+        let unit: i64 = match c.to_ascii_lowercase() {
+            's' => 1,
+            'm' => 60,
+            'h' => 60 * 60,
+            'd' => 24 * 60 * 60,
+            'w' => 7 * 24 * 60 * 60,
+            _ => return None,
+        };
+        let n: i64 = number.parse().ok()?;
+        total = total.checked_add(n.checked_mul(unit)?)?;
+        number.clear();
+    }
+    if !number.is_empty() || total == 0 {
+        return None;
+    }
+    Duration::try_seconds(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_compound_delays() {
+        assert_eq!(parse_delay("45m"), Some(Duration::minutes(45)));
+        assert_eq!(parse_delay("1h30m"), Some(Duration::minutes(90)));
+        assert_eq!(parse_delay(" 2D "), Some(Duration::days(2)));
+        assert_eq!(parse_delay("1w1s"), Some(Duration::weeks(1) + Duration::seconds(1)));
+    }
+
+    #[test]
+    fn rejects_malformed_delays() {
+        for bad in ["", "10", "h", "5x", "1h 30m", "0m", "-5m", "99999999999999999999s"] {
+            assert_eq!(parse_delay(bad), None, "{bad:?}");
+        }
+    }
+}
@@ -0,0 +1,207 @@
+//! ActivityPub endpoints — public announcement channels as fediverse actors.
+//!
+//! GET  /.well-known/webfinger?resource=acct:<id>@<server>  — Actor discovery
+//! GET  /ap/channels/:id                                    — Actor document
+//! GET  /ap/channels/:id/outbox                             — Recent posts
+//! GET  /ap/channels/:id/followers                          — Follower count
+//! POST /ap/channels/:id/inbox                              — Follow / Undo (signed)
+//! GET  /ap/channels/:id/notes/:message_id                  — A single post
+//!
+//! Only announcement channels of public servers are exposed, and only when
+//! `activitypub.enabled` is set. Everything else is a 404.
+
+use axum::{
+    body::Bytes,
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, Method, StatusCode, Uri},
+    response::IntoResponse,
+    routing::{get, post},
+    Json, Router,
+};
+use nexus_common::{
+    error::{NexusError, NexusResult},
+    models::{channel::Channel, server::Server},
+};
+use nexus_db::repository::{activitypub, channels, messages, servers};
+use nexus_federation::activitypub::{ChannelActor, ACTIVITY_JSON};
+use nexus_federation::{ActivityPubBridge, OutboundPost};
+use serde::Deserialize;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{bridges, AppState};
+
+/// Posts listed in a channel outbox.
+const OUTBOX_PAGE: i64 = 20;
+
+pub fn router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/.well-known/webfinger", get(webfinger))
+        .route("/ap/channels/{channel_id}", get(actor))
+        .route("/ap/channels/{channel_id}/outbox", get(outbox))
+        .route("/ap/channels/{channel_id}/followers", get(followers))
+        .route("/ap/channels/{channel_id}/inbox", post(inbox))
+        .route("/ap/channels/{channel_id}/notes/{message_id}", get(note))
+}
+
+#[derive(Debug, Deserialize)]
+struct WebfingerQuery {
+    resource: String,
+}
+
+/// GET /.well-known/webfinger
+async fn webfinger(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<WebfingerQuery>,
+) -> NexusResult<impl IntoResponse> {
+    let bridge = bridge(&state)?;
+    let channel_id = bridge.parse_resource(&query.resource).ok_or_else(not_found)?;
+    published_channel(&state, channel_id).await?;
+    Ok((
+        [(header::CONTENT_TYPE, "application/jrd+json")],
+        Json(bridge.webfinger(channel_id)),
+    ))
+}
+
+/// GET /ap/channels/:channel_id
+async fn actor(
+    State(state): State<Arc<AppState>>,
+    Path(channel_id): Path<Uuid>,
+) -> NexusResult<impl IntoResponse> {
+    let bridge = bridge(&state)?;
+    let (channel, server) = published_channel(&state, channel_id).await?;
+    let doc = bridge.actor_document(&ChannelActor {
+        channel_id,
+        channel_name: channel.name.unwrap_or_default(),
+        server_name: server.name,
+        topic: channel.topic,
+        icon_url: server.icon,
+    });
+    Ok(activity_json(doc))
+}
+
+/// GET /ap/channels/:channel_id/outbox
+async fn outbox(
+    State(state): State<Arc<AppState>>,
+    Path(channel_id): Path<Uuid>,
+) -> NexusResult<impl IntoResponse> {
+    let bridge = bridge(&state)?;
+    let (channel, _) = published_channel(&state, channel_id).await?;
+    let recent =
+        messages::list_channel_messages_with_author(&state.db.pool, channel_id, None, None, OUTBOX_PAGE)
+            .await?;
+    let posts: Vec<OutboundPost> = recent
+        .into_iter()
+        .filter(|m| !m.content.is_empty())
+        .map(|m| OutboundPost {
+            message_id: m.id,
+            channel_id,
+            server_id: channel.server_id,
+            author_name: m.author_username,
+            content: m.content,
+            created_at: m.created_at,
+            public_announcement: true,
+        })
+        .collect();
+    Ok(activity_json(bridge.outbox(channel_id, &posts)))
+}
+
+/// GET /ap/channels/:channel_id/followers
+async fn followers(
+    State(state): State<Arc<AppState>>,
+    Path(channel_id): Path<Uuid>,
+) -> NexusResult<impl IntoResponse> {
+    let bridge = bridge(&state)?;
+    published_channel(&state, channel_id).await?;
+    let total = activitypub::count_followers(&state.db.pool, channel_id).await?;
+    Ok(activity_json(bridge.followers(channel_id, total)))
+}
+
+/// POST /ap/channels/:channel_id/inbox
+async fn inbox(
+    State(state): State<Arc<AppState>>,
+    Path(channel_id): Path<Uuid>,
+    method: Method,
+    uri: Uri,
+    headers: HeaderMap,
+    body: Bytes,
+) -> NexusResult<StatusCode> {
+    let bridge = bridge(&state)?;
+    published_channel(&state, channel_id).await?;
+
+    let path = uri.path_and_query().map(|p| p.as_str()).unwrap_or(uri.path());
+    let signer = bridge
+        .verify_request(method.as_str(), path, &headers, &body)
+        .await
+        .map_err(|e| {
+            tracing::debug!(error = %e, "Rejected ActivityPub inbox delivery");
+            NexusError::Forbidden
+        })?;
+    let activity: serde_json::Value = serde_json::from_slice(&body).map_err(|e| NexusError::Validation {
+        message: format!("Invalid activity: {e}"),
+    })?;
+
+    bridge
+        .handle_inbox(channel_id, &signer, &activity)
+        .await
+        .map_err(|e| NexusError::Internal(e.into()))?;
+    Ok(StatusCode::ACCEPTED)
+}
+
+/// GET /ap/channels/:channel_id/notes/:message_id
+async fn note(
+    State(state): State<Arc<AppState>>,
+    Path((channel_id, message_id)): Path<(Uuid, Uuid)>,
+) -> NexusResult<impl IntoResponse> {
+    let bridge = bridge(&state)?;
+    let (channel, _) = published_channel(&state, channel_id).await?;
+    let message = messages::find_by_id(&state.db.pool, message_id)
+        .await?
+        .filter(|m| m.channel_id == channel_id)
+        .ok_or_else(not_found)?;
+
+    let mut note = bridge.note(&OutboundPost {
+        message_id,
+        channel_id,
+        server_id: channel.server_id,
+        author_name: String::new(),
+        content: message.content,
+        created_at: message.created_at,
+        public_announcement: true,
+    });
+    note["@context"] = nexus_federation::activitypub::AS_CONTEXT.into();
+    Ok(activity_json(note))
+}
+
+// ============================================================
+// Helpers
+// ============================================================
+
+fn bridge(state: &AppState) -> NexusResult<&ActivityPubBridge> {
+    state.activitypub.as_deref().ok_or_else(not_found)
+}
+
+/// Load a channel that is published to the fediverse, or 404.
+async fn published_channel(state: &AppState, channel_id: Uuid) -> NexusResult<(Channel, Server)> {
+    let channel = channels::find_by_id(&state.db.pool, channel_id)
+        .await?
+        .ok_or_else(not_found)?;
+    let server_id = channel.server_id.ok_or_else(not_found)?;
+    let server = servers::find_by_id(&state.db.pool, server_id)
+        .await?
+        .ok_or_else(not_found)?;
+    if !bridges::is_public_announcement(&channel, &server) {
+        return Err(not_found());
+    }
+    Ok((channel, server))
+}
+
+fn activity_json(value: serde_json::Value) -> impl IntoResponse {
+    ([(header::CONTENT_TYPE, ACTIVITY_JSON)], Json(value))
+}
+
+fn not_found() -> NexusError {
+    NexusError::NotFound {
+        resource: "Actor".into(),
+    }
+}
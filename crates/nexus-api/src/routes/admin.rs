@@ -0,0 +1,350 @@
+//! Instance admin API — what a web admin panel needs. Staff only: every
+//! route here (and in [`registration`](super::registration)) sits behind
+//! [`staff_middleware`](crate::middleware::staff_middleware).
+//!
+//! GET  /admin/stats                        — Totals and daily message counts
+//! GET  /admin/users?query=&before=&limit=  — Users, newest first
+//! POST /admin/users/{user_id}/deactivate   — Suspend and sign out everywhere
+//! POST /admin/users/{user_id}/reactivate   — Lift the suspension
+//! GET  /admin/servers?query=&before=&limit= — Servers, newest first
+//! GET  /admin/federation/peers?limit=&offset= — Known remote servers
+//! POST /admin/media/purge                  — Delete uploads by uploader, server or age
+//!
+//! Staff are users with the `STAFF` flag, granted in the database (see
+//! docs/self-hosting.md).
+
+use axum::{
+    extract::{Extension, Path, Query, State},
+    routing::{get, post},
+    Json, Router,
+};
+use chrono::{DateTime, Utc};
+use nexus_common::{
+    error::{NexusError, NexusResult},
+    models::user::{user_flags, User},
+};
+use nexus_db::repository::{admin, attachments, sessions, users};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{middleware::AuthContext, AppState};
+
+/// Days of per-day message counts in `/admin/stats`.
+const STATS_DAYS: i64 = 30;
+
+/// The staff-only routes, including [`registration`](super::registration)'s.
+/// The caller adds the staff layer, which needs the state.
+pub fn router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/admin/stats", get(stats))
+        .route("/admin/users", get(list_users))
+        .route("/admin/users/{user_id}/deactivate", post(deactivate_user))
+        .route("/admin/users/{user_id}/reactivate", post(reactivate_user))
+        .route("/admin/servers", get(list_servers))
+        .route("/admin/federation/peers", get(list_federation_peers))
+        .route("/admin/media/purge", post(purge_media))
+        .merge(super::registration::router())
+}
+
+// ============================================================
+// Stats
+// ============================================================
+
+#[derive(Serialize)]
+struct StatsResponse {
+    users: i64,
+    servers: i64,
+    messages: i64,
+    messages_last_24h: i64,
+    attachments: i64,
+    attachment_bytes: i64,
+    federation_peers: i64,
+    /// Messages per UTC day over the last 30 days, oldest first. Days
+    /// without messages are left out.
+    daily_messages: Vec<DailyCount>,
+}
+
+#[derive(Serialize)]
+struct DailyCount {
+    day: String,
+    messages: i64,
+}
+
+/// GET /api/v1/admin/stats
+async fn stats(State(state): State<Arc<AppState>>) -> NexusResult<Json<StatsResponse>> {
+    let totals = admin::stats(&state.db.pool).await?;
+    let daily = admin::daily_message_counts(&state.db.pool, STATS_DAYS).await?;
+    Ok(Json(StatsResponse {
+        users: totals.users,
+        servers: totals.servers,
+        messages: totals.messages,
+        messages_last_24h: totals.messages_last_24h,
+        attachments: totals.attachments,
+        attachment_bytes: totals.attachment_bytes,
+        federation_peers: totals.federation_peers,
+        daily_messages: daily
+            .into_iter()
+            .map(|(day, messages)| DailyCount { day, messages })
+            .collect(),
+    }))
+}
+
+// ============================================================
+// Users
+// ============================================================
+
+#[derive(Debug, Deserialize)]
+struct ListParams {
+    query: Option<String>,
+    before: Option<Uuid>,
+    limit: Option<i64>,
+}
+
+/// A user as staff see them — with email and flags.
+#[derive(Serialize)]
+struct AdminUserResponse {
+    id: Uuid,
+    username: String,
+    display_name: Option<String>,
+    email: Option<String>,
+    flags: i64,
+    staff: bool,
+    deactivated: bool,
+    pending_approval: bool,
+    created_at: DateTime<Utc>,
+}
+
+impl From<User> for AdminUserResponse {
+    fn from(u: User) -> Self {
+        Self {
+            staff: u.flags & user_flags::STAFF != 0,
+            deactivated: u.flags & user_flags::SUSPENDED != 0,
+            pending_approval: u.flags & user_flags::PENDING_APPROVAL != 0,
+            id: u.id,
+            username: u.username,
+            display_name: u.display_name,
+            email: u.email,
+            flags: u.flags,
+            created_at: u.created_at,
+        }
+    }
+}
+
+/// GET /api/v1/admin/users
+async fn list_users(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<ListParams>,
+) -> NexusResult<Json<Vec<AdminUserResponse>>> {
+    let query = params.query.as_deref().map(str::trim).filter(|q| !q.is_empty());
+    let limit = params.limit.unwrap_or(50).clamp(1, 100);
+    let users = admin::list_users(&state.db.pool, query, params.before, limit).await?;
+    Ok(Json(users.into_iter().map(Into::into).collect()))
+}
+
+/// POST /api/v1/admin/users/{user_id}/deactivate
+///
+/// Suspends the account (login and refresh are refused) and revokes all of
+/// its sessions.
+async fn deactivate_user(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path(user_id): Path<Uuid>,
+) -> NexusResult<Json<AdminUserResponse>> {
+    if user_id == auth.user_id {
+        return Err(NexusError::Validation {
+            message: "You can't deactivate your own account".into(),
+        });
+    }
+    let user = users::update_flags(&state.db.pool, user_id, user_flags::SUSPENDED, 0)
+        .await?
+        .ok_or(NexusError::NotFound {
+            resource: "User".into(),
+        })?;
+    let revoked = sessions::delete_others(&state.db.pool, user_id, None).await?;
+    state.live_sessions.remove_user(user_id, None);
+
+    tracing::info!(staff_id = %auth.user_id, %user_id, revoked, "User deactivated");
+    crate::security_events::record(
+        &state,
+        crate::security_events::events::ADMIN_USER_DEACTIVATE,
+        serde_json::json!({ "actor_id": auth.user_id, "user_id": user_id, "username": user.username }),
+    )
+    .await;
+    Ok(Json(user.into()))
+}
+
+/// POST /api/v1/admin/users/{user_id}/reactivate
+async fn reactivate_user(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path(user_id): Path<Uuid>,
+) -> NexusResult<Json<AdminUserResponse>> {
+    let user = users::update_flags(&state.db.pool, user_id, 0, user_flags::SUSPENDED)
+        .await?
+        .ok_or(NexusError::NotFound {
+            resource: "User".into(),
+        })?;
+
+    tracing::info!(staff_id = %auth.user_id, %user_id, "User reactivated");
+    crate::security_events::record(
+        &state,
+        crate::security_events::events::ADMIN_USER_REACTIVATE,
+        serde_json::json!({ "actor_id": auth.user_id, "user_id": user_id, "username": user.username }),
+    )
+    .await;
+    Ok(Json(user.into()))
+}
+
+// ============================================================
+// Servers and federation
+// ============================================================
+
+#[derive(Serialize)]
+struct AdminServerResponse {
+    id: Uuid,
+    name: String,
+    owner_id: Uuid,
+    is_public: bool,
+    member_count: i32,
+    created_at: DateTime<Utc>,
+}
+
+/// GET /api/v1/admin/servers
+async fn list_servers(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<ListParams>,
+) -> NexusResult<Json<Vec<AdminServerResponse>>> {
+    let query = params.query.as_deref().map(str::trim).filter(|q| !q.is_empty());
+    let limit = params.limit.unwrap_or(50).clamp(1, 100);
+    let servers = admin::list_servers(&state.db.pool, query, params.before, limit).await?;
+    Ok(Json(
+        servers
+            .into_iter()
+            .map(|s| AdminServerResponse {
+                id: s.id,
+                name: s.name,
+                owner_id: s.owner_id,
+                is_public: s.is_public,
+                member_count: s.member_count,
+                created_at: s.created_at,
+            })
+            .collect(),
+    ))
+}
+
+#[derive(Debug, Deserialize)]
+struct PageParams {
+    limit: Option<i64>,
+    offset: Option<i64>,
+}
+
+#[derive(Serialize)]
+struct FederationPeerResponse {
+    id: Uuid,
+    server_name: String,
+    server_type: String,
+    server_version: Option<String>,
+    base_url: Option<String>,
+    is_blocked: bool,
+    throttled_until: Option<DateTime<Utc>>,
+    first_seen_at: DateTime<Utc>,
+    last_seen_at: DateTime<Utc>,
+}
+
+/// GET /api/v1/admin/federation/peers — Most recently seen first.
+async fn list_federation_peers(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<PageParams>,
+) -> NexusResult<Json<Vec<FederationPeerResponse>>> {
+    let limit = params.limit.unwrap_or(50).clamp(1, 200);
+    let offset = params.offset.unwrap_or(0).max(0);
+    let peers = admin::list_federation_peers(&state.db.pool, limit, offset).await?;
+    Ok(Json(
+        peers
+            .into_iter()
+            .map(|p| FederationPeerResponse {
+                id: p.id,
+                server_name: p.server_name,
+                server_type: p.server_type,
+                server_version: p.server_version,
+                base_url: p.base_url,
+                is_blocked: p.is_blocked,
+                throttled_until: p.throttled_until,
+                first_seen_at: p.first_seen_at,
+                last_seen_at: p.last_seen_at,
+            })
+            .collect(),
+    ))
+}
+
+// ============================================================
+// Media
+// ============================================================
+
+/// Which uploads to purge; all given filters must match, and at least one
+/// is required.
+#[derive(Debug, Deserialize)]
+struct PurgeMediaRequest {
+    uploader_id: Option<Uuid>,
+    server_id: Option<Uuid>,
+    /// Only uploads older than this.
+    before: Option<DateTime<Utc>>,
+}
+
+#[derive(Serialize)]
+struct PurgeMediaResponse {
+    purged: usize,
+    bytes: i64,
+}
+
+/// POST /api/v1/admin/media/purge
+///
+/// Removes the attachment records at once; the files are deleted from
+/// storage in the background. Messages that used them keep dead links.
+async fn purge_media(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Json(body): Json<PurgeMediaRequest>,
+) -> NexusResult<Json<PurgeMediaResponse>> {
+    if body.uploader_id.is_none() && body.server_id.is_none() && body.before.is_none() {
+        return Err(NexusError::Validation {
+            message: "Give at least one of uploader_id, server_id or before".into(),
+        });
+    }
+    let purged = attachments::purge(&state.db.pool, body.uploader_id, body.server_id, body.before).await?;
+    let bytes = purged.iter().map(|a| a.size).sum();
+
+    let storage = state.storage.clone();
+    let keys: Vec<String> = purged
+        .iter()
+        .flat_map(|a| std::iter::once(a.storage_key.clone()).chain(a.thumbnail_key.clone()))
+        .collect();
+    tokio::spawn(async move {
+        for key in keys {
+            // Best-effort, like a user deleting their own upload
+            if let Err(e) = storage.delete_object(&key).await {
+                tracing::warn!(%key, error = %e, "Failed to delete purged media from storage");
+            }
+        }
+    });
+
+    tracing::info!(staff_id = %auth.user_id, purged = purged.len(), bytes, "Media purged");
+    crate::security_events::record(
+        &state,
+        crate::security_events::events::ADMIN_MEDIA_PURGE,
+        serde_json::json!({
+            "actor_id": auth.user_id,
+            "uploader_id": body.uploader_id,
+            "server_id": body.server_id,
+            "before": body.before,
+            "purged": purged.len(),
+            "bytes": bytes,
+        }),
+    )
+    .await;
+    Ok(Json(PurgeMediaResponse {
+        purged: purged.len(),
+        bytes,
+    }))
+}
@@ -0,0 +1,67 @@
+//! Audit log routes — browse a server's moderation and admin history.
+//!
+//! GET /servers/:id/audit-log?action=&user_id=&target_id=&before=&limit=
+//!
+//! Requires VIEW_AUDIT_LOG. Entries are returned newest first; pass the last
+//! entry's ID as `before` to page back.
+
+use axum::{
+    extract::{Extension, Path, Query, State},
+    middleware,
+    routing::get,
+    Json, Router,
+};
+use nexus_common::{
+    error::{NexusError, NexusResult},
+    permissions::Permissions,
+};
+use nexus_db::repository::{audit_log, servers};
+use serde::Deserialize;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{audit, middleware::AuthContext, AppState};
+
+pub fn router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/servers/{server_id}/audit-log", get(list_entries))
+        .route_layer(middleware::from_fn(crate::middleware::auth_middleware))
+}
+
+#[derive(Debug, Deserialize)]
+struct AuditLogParams {
+    action: Option<String>,
+    /// Filter by the member who performed the action.
+    user_id: Option<Uuid>,
+    target_id: Option<Uuid>,
+    before: Option<Uuid>,
+    limit: Option<i64>,
+}
+
+/// GET /api/v1/servers/:server_id/audit-log
+async fn list_entries(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path(server_id): Path<Uuid>,
+    Query(params): Query<AuditLogParams>,
+) -> NexusResult<Json<Vec<serde_json::Value>>> {
+    let server = servers::find_by_id(&state.db.pool, server_id)
+        .await?
+        .ok_or(NexusError::NotFound {
+            resource: "Server".into(),
+        })?;
+    let permissions = crate::permissions::resolve(&state.db.pool, &server, None, auth.user_id)
+        .await?
+        .ok_or(NexusError::Forbidden)?;
+    crate::permissions::require(permissions, Permissions::VIEW_AUDIT_LOG)?;
+
+    let filter = audit_log::AuditLogFilter {
+        action: params.action.as_deref(),
+        user_id: params.user_id,
+        target_id: params.target_id,
+        before: params.before,
+    };
+    let limit = params.limit.unwrap_or(50).clamp(1, 100);
+    let entries = audit_log::list_entries(&state.db.pool, server_id, &filter, limit).await?;
+    Ok(Json(entries.iter().map(audit::entry_json).collect()))
+}
@@ -2,26 +2,48 @@
 //!
 //! Privacy-first: No phone number. No ID. No age verification.
 //! Just a username and password. Email is optional (only for password reset).
+//!
+//! Every login starts a session (see [`sessions`](super::sessions)). Refresh
+//! tokens are single-use: each refresh returns a new one and retires the
+//! old. Presenting a retired token means it leaked, so the whole session is
+//! revoked and a security event is recorded.
+//!
+//! Who may register, and what they must prove first, is set by
+//! `registration.*` in the config; see [`crate::registration`].
 
-use axum::{extract::State, routing::post, Json, Router};
+use axum::{
+    extract::State,
+    http::{header, HeaderMap, StatusCode},
+    routing::{get, post},
+    Json, Router,
+};
+use chrono::{Duration, Utc};
 use nexus_common::{
+    config::RegistrationMode,
     error::{NexusError, NexusResult},
-    models::user::{CreateUserRequest, LoginRequest, UserResponse},
+    models::user::{user_flags, CreateUserRequest, LoginRequest, User, UserResponse},
     snowflake,
     validation::validate_request,
 };
-use nexus_db::repository::users;
+use nexus_db::repository::{registration, sessions, users};
 use serde::Serialize;
 use std::sync::Arc;
+use uuid::Uuid;
 
 use crate::{
     auth::{self, TokenPair},
+    middleware::ClientIp,
+    registration::RegistrationInfo,
     AppState,
 };
 
+/// Longest user agent kept for the session list.
+const MAX_USER_AGENT_LEN: usize = 256;
+
 /// Auth router.
 pub fn router() -> Router<Arc<AppState>> {
     Router::new()
+        .route("/auth/registration", get(registration_info))
         .route("/auth/register", post(register))
         .route("/auth/login", post(login))
         .route("/auth/refresh", post(refresh_token))
@@ -30,19 +52,42 @@ pub fn router() -> Router<Arc<AppState>> {
 #[derive(Serialize)]
 struct AuthResponse {
     user: UserResponse,
+    /// Absent while the account awaits approval.
     #[serde(flatten)]
-    tokens: TokenPair,
+    tokens: Option<TokenPair>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    approval_pending: bool,
+}
+
+/// GET /api/v1/auth/registration
+///
+/// How registration works here: the mode, and the challenge to solve (a
+/// fresh proof-of-work challenge each call).
+async fn registration_info(State(state): State<Arc<AppState>>) -> NexusResult<Json<RegistrationInfo>> {
+    let config = nexus_common::config::get();
+    let mode = crate::registration::current_mode(&state).await?;
+    Ok(Json(RegistrationInfo::new(&config.registration, mode, &config.auth.jwt_secret)))
 }
 
 /// POST /api/v1/auth/register
 ///
 /// Create a new account. Returns user profile + JWT tokens.
 /// No email required. No phone. No ID. Just pick a username and password.
+///
+/// Invite-only instances also want a `registration_token`. On instances that
+/// approve sign-ups the response is 202 with no tokens; login works once
+/// staff approve.
 async fn register(
     State(state): State<Arc<AppState>>,
+    ClientIp(ip): ClientIp,
+    headers: HeaderMap,
     Json(body): Json<CreateUserRequest>,
-) -> NexusResult<Json<AuthResponse>> {
+) -> NexusResult<(StatusCode, Json<AuthResponse>)> {
+    let config = nexus_common::config::get();
+    crate::registration::check_ip_limit(&state, &config.registration, ip).await?;
     validate_request(&body)?;
+    crate::registration::verify_challenge(&config.registration, &config.auth.jwt_secret, &body, ip).await?;
+    let mode = crate::registration::current_mode(&state).await?;
 
     // Check username availability
     if users::find_by_username(&state.db.pool, &body.username)
@@ -70,33 +115,82 @@ async fn register(
     // Generate user ID
     let user_id = snowflake::generate_id();
 
-    // Create user
-    let user = users::create_user(
-        &state.db.pool,
-        user_id,
-        &body.username,
-        body.email.as_deref(),
-        &password_hash,
-    )
-    .await?;
+    // Spend the invite last, so a rejected request doesn't burn it
+    let invite = match mode {
+        RegistrationMode::InviteOnly => {
+            let token = body.registration_token.as_deref().ok_or_else(|| NexusError::Validation {
+                message: "A registration token is required".into(),
+            })?;
+            let id = registration::claim_token(&state.db.pool, &crate::oauth2::hash_token(token))
+                .await?
+                .ok_or_else(|| NexusError::Validation {
+                    message: "Registration token is invalid, expired or already used".into(),
+                })?;
+            Some(id)
+        }
+        _ => None,
+    };
 
-    // Generate tokens
-    let config = nexus_common::config::get();
-    let tokens = auth::generate_token_pair(
-        user.id,
-        &user.username,
-        &config.auth.jwt_secret,
-        config.auth.access_token_ttl_secs,
-        config.auth.refresh_token_ttl_secs,
-    )
-    .map_err(|e| NexusError::Internal(e.into()))?;
+    // Create user; one awaiting approval is filed along with its application
+    let created = if mode == RegistrationMode::Approval {
+        let ip = ip.map(|ip| ip.to_string());
+        registration::create_pending_user(
+            &state.db.pool,
+            user_id,
+            &body.username,
+            body.email.as_deref(),
+            &password_hash,
+            body.reason.as_deref(),
+            ip.as_deref(),
+        )
+        .await
+    } else {
+        users::create_user(
+            &state.db.pool,
+            user_id,
+            &body.username,
+            body.email.as_deref(),
+            &password_hash,
+        )
+        .await
+    };
+    let user = match created {
+        Ok(user) => user,
+        Err(e) => {
+            if let Some(invite) = invite {
+                registration::finish_claim(&state.db.pool, invite, None).await?;
+            }
+            return Err(e.into());
+        }
+    };
+    if let Some(invite) = invite {
+        registration::finish_claim(&state.db.pool, invite, Some(user.id)).await?;
+    }
+
+    if mode == RegistrationMode::Approval {
+        tracing::info!(user_id = %user.id, username = %user.username, "New user awaiting approval");
+        return Ok((
+            StatusCode::ACCEPTED,
+            Json(AuthResponse {
+                user: user.into(),
+                tokens: None,
+                approval_pending: true,
+            }),
+        ));
+    }
+
+    let tokens = start_session(&state, &user, &headers, ip).await?;
 
     tracing::info!(user_id = %user.id, username = %user.username, "New user registered");
 
-    Ok(Json(AuthResponse {
-        user: user.into(),
-        tokens,
-    }))
+    Ok((
+        StatusCode::OK,
+        Json(AuthResponse {
+            user: user.into(),
+            tokens: Some(tokens),
+            approval_pending: false,
+        }),
+    ))
 }
 
 /// POST /api/v1/auth/login
@@ -104,6 +198,8 @@ async fn register(
 /// Authenticate with username + password. Returns JWT tokens.
 async fn login(
     State(state): State<Arc<AppState>>,
+    ClientIp(ip): ClientIp,
+    headers: HeaderMap,
     Json(body): Json<LoginRequest>,
 ) -> NexusResult<Json<AuthResponse>> {
     validate_request(&body)?;
@@ -122,70 +218,160 @@ async fn login(
     }
 
     // Check if account is disabled/suspended
-    if user.flags & nexus_common::models::user::user_flags::DISABLED != 0 {
+    if user.flags & user_flags::DISABLED != 0 {
         return Err(NexusError::Forbidden);
     }
-    if user.flags & nexus_common::models::user::user_flags::SUSPENDED != 0 {
+    if user.flags & user_flags::SUSPENDED != 0 {
         return Err(NexusError::Forbidden);
     }
+    if user.flags & user_flags::PENDING_APPROVAL != 0 {
+        return Err(NexusError::ApprovalPending);
+    }
+
+    let tokens = start_session(&state, &user, &headers, ip).await?;
+
+    tracing::info!(user_id = %user.id, "User logged in");
+    if user.flags & user_flags::STAFF != 0 {
+        crate::security_events::record(
+            &state,
+            crate::security_events::events::ADMIN_LOGIN,
+            serde_json::json!({ "user_id": user.id, "username": user.username }),
+        )
+        .await;
+    }
+
+    Ok(Json(AuthResponse {
+        user: user.into(),
+        tokens: Some(tokens),
+        approval_pending: false,
+    }))
+}
 
-    // Generate tokens
+/// Start a session for `user` and issue its first token pair.
+async fn start_session(
+    state: &AppState,
+    user: &User,
+    headers: &HeaderMap,
+    ip: Option<std::net::IpAddr>,
+) -> NexusResult<TokenPair> {
     let config = nexus_common::config::get();
+    let session_id = Uuid::new_v4();
     let tokens = auth::generate_token_pair(
         user.id,
         &user.username,
+        session_id,
         &config.auth.jwt_secret,
         config.auth.access_token_ttl_secs,
-        config.auth.refresh_token_ttl_secs,
     )
     .map_err(|e| NexusError::Internal(e.into()))?;
 
-    tracing::info!(user_id = %user.id, "User logged in");
-
-    Ok(Json(AuthResponse {
-        user: user.into(),
-        tokens,
-    }))
+    let user_agent = headers
+        .get(header::USER_AGENT)
+        .and_then(|v| v.to_str().ok())
+        .map(|ua| ua.chars().take(MAX_USER_AGENT_LEN).collect::<String>());
+    sessions::create(
+        &state.db.pool,
+        session_id,
+        user.id,
+        &auth::hash_refresh_token(&tokens.refresh_token),
+        user_agent.as_deref(),
+        ip.map(|ip| ip.to_string()).as_deref(),
+        Utc::now() + Duration::seconds(config.auth.refresh_token_ttl_secs as i64),
+    )
+    .await?;
+    Ok(tokens)
 }
 
 /// POST /api/v1/auth/refresh
 ///
-/// Exchange a refresh token for a new token pair.
+/// Exchange a refresh token for a new token pair. The old refresh token
+/// stops working; presenting it again revokes the session.
 async fn refresh_token(
     State(state): State<Arc<AppState>>,
+    ClientIp(ip): ClientIp,
     Json(body): Json<RefreshRequest>,
 ) -> NexusResult<Json<TokenPair>> {
     let config = nexus_common::config::get();
+    let hash = auth::hash_refresh_token(&body.refresh_token);
 
-    // Validate the refresh token
-    let claims = auth::validate_token(&body.refresh_token, &config.auth.jwt_secret)
-        .map_err(|_| NexusError::InvalidToken)?;
-
-    if claims.token_type != "refresh" {
+    let found = sessions::find_by_refresh_token(&state.db.pool, &hash)
+        .await?
+        .ok_or(NexusError::InvalidToken)?;
+    let session = found.session;
+    if session.expires_at < Utc::now() {
         return Err(NexusError::InvalidToken);
     }
 
-    let user_id: uuid::Uuid = claims
-        .sub
-        .parse()
-        .map_err(|_| NexusError::InvalidToken)?;
+    let new_refresh = auth::generate_refresh_token();
+    let rotated = !found.used
+        && sessions::rotate(
+            &state.db.pool,
+            session.id,
+            &hash,
+            &auth::hash_refresh_token(&new_refresh),
+            ip.map(|ip| ip.to_string()).as_deref(),
+            Utc::now() + Duration::seconds(config.auth.refresh_token_ttl_secs as i64),
+        )
+        .await?;
+    if !rotated {
+        revoke_reused_session(&state, &session, ip).await?;
+        return Err(NexusError::InvalidToken);
+    }
 
     // Verify user still exists and isn't disabled
-    let user = users::find_by_id(&state.db.pool, user_id)
+    let user = users::find_by_id(&state.db.pool, session.user_id)
         .await?
         .ok_or(NexusError::InvalidToken)?;
+    let blocked = user_flags::DISABLED | user_flags::SUSPENDED | user_flags::PENDING_APPROVAL;
+    if user.flags & blocked != 0 {
+        sessions::delete(&state.db.pool, session.id, user.id).await?;
+        state.live_sessions.remove(session.id);
+        return Err(NexusError::Forbidden);
+    }
 
-    // Generate new token pair
-    let tokens = auth::generate_token_pair(
+    let access_token = auth::generate_access_token(
         user.id,
         &user.username,
+        session.id,
         &config.auth.jwt_secret,
         config.auth.access_token_ttl_secs,
-        config.auth.refresh_token_ttl_secs,
     )
     .map_err(|e| NexusError::Internal(e.into()))?;
 
-    Ok(Json(tokens))
+    Ok(Json(TokenPair {
+        access_token,
+        refresh_token: new_refresh,
+        expires_in: config.auth.access_token_ttl_secs,
+        token_type: "Bearer".to_string(),
+    }))
+}
+
+/// A retired refresh token was presented: someone else has a copy of the
+/// session, so end it for everyone.
+async fn revoke_reused_session(
+    state: &AppState,
+    session: &sessions::Session,
+    ip: Option<std::net::IpAddr>,
+) -> NexusResult<()> {
+    sessions::delete(&state.db.pool, session.id, session.user_id).await?;
+    state.live_sessions.remove(session.id);
+    tracing::warn!(
+        user_id = %session.user_id,
+        session_id = %session.id,
+        ip = ?ip,
+        "Refresh token reused; session revoked"
+    );
+    crate::security_events::record(
+        state,
+        crate::security_events::events::REFRESH_TOKEN_REUSE,
+        serde_json::json!({
+            "user_id": session.user_id,
+            "session_id": session.id,
+            "ip": ip,
+        }),
+    )
+    .await;
+    Ok(())
 }
 
 #[derive(serde::Deserialize)]
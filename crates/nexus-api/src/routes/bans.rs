@@ -0,0 +1,235 @@
+//! Ban list routes — export, import, and shared blocklist subscriptions.
+//!
+//! GET    /servers/:id/bans/export                  — Download the server's bans
+//! POST   /servers/:id/bans/import                  — Import from another server or a list
+//! GET    /servers/:id/bans/subscriptions           — List blocklist subscriptions
+//! POST   /servers/:id/bans/subscriptions           — Subscribe to a server or URL
+//! DELETE /servers/:id/bans/subscriptions/:sub_id   — Unsubscribe
+
+use axum::{
+    extract::{Extension, Path, State},
+    middleware,
+    routing::{delete, get, post},
+    Json, Router,
+};
+use nexus_common::{
+    error::{NexusError, NexusResult},
+    models::server::Server,
+    permissions::Permissions,
+};
+use nexus_db::repository::{bans, servers};
+use serde::Deserialize;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{
+    ban_lists::{self, BanList, ImportSummary},
+    middleware::AuthContext,
+    AppState,
+};
+
+pub fn router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/servers/{server_id}/bans/export", get(export_bans))
+        .route("/servers/{server_id}/bans/import", post(import_bans))
+        .route(
+            "/servers/{server_id}/bans/subscriptions",
+            get(list_subscriptions).post(create_subscription),
+        )
+        .route(
+            "/servers/{server_id}/bans/subscriptions/{subscription_id}",
+            delete(delete_subscription),
+        )
+        .route_layer(middleware::from_fn(crate::middleware::auth_middleware))
+}
+
+#[derive(Debug, Deserialize)]
+struct ImportRequest {
+    /// Copy the bans of another server on this instance.
+    source_server_id: Option<Uuid>,
+    /// Or import an uploaded list (e.g. a previous export).
+    list: Option<BanList>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SubscribeRequest {
+    source_server_id: Option<Uuid>,
+    source_url: Option<String>,
+}
+
+/// Load a server and require BAN_MEMBERS in it.
+async fn require_ban_members(state: &AppState, server_id: Uuid, user_id: Uuid) -> NexusResult<Server> {
+    let server = servers::find_by_id(&state.db.pool, server_id)
+        .await?
+        .ok_or(NexusError::NotFound {
+            resource: "Server".into(),
+        })?;
+    let permissions = crate::permissions::resolve(&state.db.pool, &server, None, user_id)
+        .await?
+        .ok_or(NexusError::Forbidden)?;
+    if !permissions.has(Permissions::BAN_MEMBERS) {
+        return Err(NexusError::MissingPermission {
+            permission: "BAN_MEMBERS".into(),
+        });
+    }
+    Ok(server)
+}
+
+fn subscription_json(sub: &bans::BanSubscriptionRow) -> serde_json::Value {
+    serde_json::json!({
+        "id": sub.id,
+        "server_id": sub.server_id,
+        "source_server_id": sub.source_server_id,
+        "source_url": sub.source_url,
+        "created_by": sub.created_by,
+        "last_synced_at": sub.last_synced_at,
+        "last_error": sub.last_error,
+        "disabled_at": sub.disabled_at,
+        "created_at": sub.created_at,
+    })
+}
+
+/// GET /api/v1/servers/:server_id/bans/export
+async fn export_bans(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path(server_id): Path<Uuid>,
+) -> NexusResult<Json<BanList>> {
+    let server = require_ban_members(&state, server_id, auth.user_id).await?;
+    let list = ban_lists::export(&state.db.pool, server_id, &server.name).await?;
+    Ok(Json(list))
+}
+
+/// POST /api/v1/servers/:server_id/bans/import
+///
+/// Copying from another server requires BAN_MEMBERS there too, so bans
+/// can only be shared between communities the caller moderates.
+async fn import_bans(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path(server_id): Path<Uuid>,
+    Json(body): Json<ImportRequest>,
+) -> NexusResult<Json<ImportSummary>> {
+    require_ban_members(&state, server_id, auth.user_id).await?;
+
+    let (list, origin) = match (body.source_server_id, body.list) {
+        (Some(source_id), None) => {
+            if source_id == server_id {
+                return Err(NexusError::Validation {
+                    message: "Cannot import bans from the same server".into(),
+                });
+            }
+            let source = require_ban_members(&state, source_id, auth.user_id).await?;
+            (ban_lists::export(&state.db.pool, source_id, &source.name).await?, source.name)
+        }
+        (None, Some(list)) => {
+            let origin = list.source.clone().unwrap_or_else(|| "import".into());
+            (list, origin)
+        }
+        _ => {
+            return Err(NexusError::Validation {
+                message: "Provide exactly one of source_server_id or list".into(),
+            })
+        }
+    };
+
+    if list.bans.len() > ban_lists::MAX_ENTRIES {
+        return Err(NexusError::Validation {
+            message: format!("Ban lists are limited to {} entries", ban_lists::MAX_ENTRIES),
+        });
+    }
+
+    let actor = ban_lists::Actor::load(&state, server_id, auth.user_id)
+        .await?
+        .ok_or(NexusError::Forbidden)?;
+    let summary = ban_lists::import(&state, &actor, &list.bans, &origin).await?;
+    tracing::info!(
+        server_id = %server_id,
+        imported = summary.imported,
+        skipped = summary.skipped,
+        "Ban list imported"
+    );
+    Ok(Json(summary))
+}
+
+/// GET /api/v1/servers/:server_id/bans/subscriptions
+async fn list_subscriptions(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path(server_id): Path<Uuid>,
+) -> NexusResult<Json<Vec<serde_json::Value>>> {
+    require_ban_members(&state, server_id, auth.user_id).await?;
+    let subs = bans::list_subscriptions(&state.db.pool, server_id).await?;
+    Ok(Json(subs.iter().map(subscription_json).collect()))
+}
+
+/// POST /api/v1/servers/:server_id/bans/subscriptions
+///
+/// The first sync runs immediately in the background; afterwards the
+/// `ban_list_sync` job refreshes every subscription periodically.
+async fn create_subscription(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path(server_id): Path<Uuid>,
+    Json(body): Json<SubscribeRequest>,
+) -> NexusResult<Json<serde_json::Value>> {
+    require_ban_members(&state, server_id, auth.user_id).await?;
+
+    match (body.source_server_id, body.source_url.as_deref()) {
+        (Some(source_id), None) => {
+            if source_id == server_id {
+                return Err(NexusError::Validation {
+                    message: "A server cannot subscribe to its own bans".into(),
+                });
+            }
+            require_ban_members(&state, source_id, auth.user_id).await?;
+        }
+        (None, Some(url)) => {
+            if !url.starts_with("https://") {
+                return Err(NexusError::Validation {
+                    message: "source_url must be an https:// URL".into(),
+                });
+            }
+        }
+        _ => {
+            return Err(NexusError::Validation {
+                message: "Provide exactly one of source_server_id or source_url".into(),
+            })
+        }
+    }
+
+    let sub = bans::create_subscription(
+        &state.db.pool,
+        Uuid::new_v4(),
+        server_id,
+        body.source_server_id,
+        body.source_url.as_deref(),
+        auth.user_id,
+    )
+    .await?;
+
+    let sync_state = state.clone();
+    let sync_sub = sub.clone();
+    tokio::spawn(async move {
+        crate::jobs::ban_list_sync::sync_one(&sync_state, &sync_sub).await;
+    });
+
+    Ok(Json(subscription_json(&sub)))
+}
+
+/// DELETE /api/v1/servers/:server_id/bans/subscriptions/:subscription_id
+///
+/// Bans already imported stay in place.
+async fn delete_subscription(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path((server_id, subscription_id)): Path<(Uuid, Uuid)>,
+) -> NexusResult<Json<serde_json::Value>> {
+    require_ban_members(&state, server_id, auth.user_id).await?;
+    if !bans::delete_subscription(&state.db.pool, server_id, subscription_id).await? {
+        return Err(NexusError::NotFound {
+            resource: "Ban subscription".into(),
+        });
+    }
+    Ok(Json(serde_json::json!({ "deleted": true })))
+}
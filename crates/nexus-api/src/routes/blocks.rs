@@ -0,0 +1,79 @@
+//! Block routes — users whose mentions and messages never notify the caller.
+//!
+//! GET    /users/@me/blocks            — Everyone the caller blocked
+//! PUT    /users/@me/blocks/:user_id   — Block a user
+//! DELETE /users/@me/blocks/:user_id   — Unblock a user
+//!
+//! Blocking is enforced where notifications are produced (see
+//! [`read_states::notifications_suppressed`](nexus_db::repository::read_states::notifications_suppressed)):
+//! a blocked user's mentions add no badge and send no push.
+
+use axum::{
+    extract::{Extension, Path, State},
+    http::StatusCode,
+    middleware,
+    routing::{get, put},
+    Json, Router,
+};
+use nexus_common::error::{NexusError, NexusResult};
+use nexus_db::repository::blocks;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{middleware::AuthContext, AppState};
+
+pub fn router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/users/@me/blocks", get(list_blocks))
+        .route("/users/@me/blocks/{user_id}", put(block_user).delete(unblock_user))
+        .route_layer(middleware::from_fn(crate::middleware::auth_middleware))
+}
+
+/// GET /api/v1/users/@me/blocks
+async fn list_blocks(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+) -> NexusResult<Json<Vec<serde_json::Value>>> {
+    let blocked = blocks::list_blocked(&state.db.pool, auth.user_id).await?;
+    Ok(Json(
+        blocked
+            .iter()
+            .map(|b| serde_json::json!({ "user_id": b.blocked_id, "created_at": b.created_at }))
+            .collect(),
+    ))
+}
+
+/// PUT /api/v1/users/@me/blocks/:user_id — Idempotent.
+async fn block_user(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path(user_id): Path<Uuid>,
+) -> NexusResult<StatusCode> {
+    if user_id == auth.user_id {
+        return Err(NexusError::Validation {
+            message: "You can't block yourself".into(),
+        });
+    }
+    if !blocks::block(&state.db.pool, auth.user_id, user_id).await?
+        && nexus_db::repository::users::find_by_id(&state.db.pool, user_id).await?.is_none()
+    {
+        return Err(NexusError::NotFound {
+            resource: "User".into(),
+        });
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// DELETE /api/v1/users/@me/blocks/:user_id
+async fn unblock_user(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path(user_id): Path<Uuid>,
+) -> NexusResult<StatusCode> {
+    if !blocks::unblock(&state.db.pool, auth.user_id, user_id).await? {
+        return Err(NexusError::NotFound {
+            resource: "Block".into(),
+        });
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -2,27 +2,37 @@
 //!
 //! Bot tokens use the format "Bot <token>" in the Authorization header (same
 //! as Discord's bot token scheme). This file handles the developer-facing REST
-//! API for managing bot applications. Bot authentication is handled by
-//! `bot_auth_middleware` in `middleware.rs`.
+//! API for managing bot applications: token rotation, IP allowlists and
+//! request metrics. Bot authentication is handled by `bot_auth_middleware`
+//! in `middleware.rs`, which enforces the allowlist and records the metrics.
 
 use axum::{
-    extract::{Extension, Path, State},
+    extract::{Extension, Path, Query, State},
     middleware,
     routing::{delete, get, post},
     Json, Router,
 };
 use nexus_common::{
     error::{NexusError, NexusResult},
-    models::bot::{BotApplication, BotServerInstall, BotToken, CreateBotRequest, UpdateBotRequest},
+    models::bot::{
+        application_flags, BotApplication, BotServerInstall, BotToken, CreateBotRequest,
+        OAuth2ClientSecret, UpdateBotRequest,
+    },
     snowflake,
 };
 use nexus_db::repository::bots;
 use rand::distr::Alphanumeric;
 use rand::Rng;
+use serde::Deserialize;
 use std::sync::Arc;
 use uuid::Uuid;
 
-use crate::{middleware::AuthContext, AppState};
+use crate::{auth::hash_bot_token, middleware::AuthContext, AppState};
+
+/// Most IP / CIDR entries an application's allowlist may hold.
+const MAX_ALLOWLIST_ENTRIES: usize = 32;
+/// Longest metrics window the developer portal can request.
+const MAX_METRICS_DAYS: i64 = 90;
 
 /// Bot application routes.
 pub fn router() -> Router<Arc<AppState>> {
@@ -36,6 +46,8 @@ pub fn router() -> Router<Arc<AppState>> {
                 .delete(delete_application),
         )
         .route("/applications/{app_id}/token/reset", post(reset_token))
+        .route("/applications/{app_id}/oauth2/secret/reset", post(reset_client_secret))
+        .route("/applications/{app_id}/metrics", get(get_metrics))
         // Server bot integrations
         .route(
             "/servers/{server_id}/integrations",
@@ -61,12 +73,34 @@ fn generate_bot_token() -> String {
         .collect()
 }
 
-/// Hash a bot token using SHA-256 (stored in the DB).
-fn hash_token(token: &str) -> String {
-    use sha2::{Digest, Sha256};
-    let mut hasher = Sha256::new();
-    hasher.update(token.as_bytes());
-    format!("{:x}", hasher.finalize())
+/// Load an application and require that `user_id` owns it.
+async fn owned_application(
+    state: &AppState,
+    app_id: Uuid,
+    user_id: Uuid,
+) -> NexusResult<BotApplication> {
+    let bot = bots::get_bot(&state.db.pool, app_id)
+        .await?
+        .ok_or(NexusError::NotFound { resource: "application".to_string() })?;
+    if bot.owner_id != user_id {
+        return Err(NexusError::Forbidden);
+    }
+    Ok(bot)
+}
+
+/// Reject allowlist entries that aren't an IP address or CIDR range.
+fn validate_allowlist(entries: &[String]) -> NexusResult<()> {
+    if entries.len() > MAX_ALLOWLIST_ENTRIES {
+        return Err(NexusError::Validation {
+            message: format!("IP allowlist may hold at most {MAX_ALLOWLIST_ENTRIES} entries"),
+        });
+    }
+    if let Some(bad) = entries.iter().find(|e| e.parse::<ipnetwork::IpNetwork>().is_err()) {
+        return Err(NexusError::Validation {
+            message: format!("'{bad}' is not an IP address or CIDR range"),
+        });
+    }
+    Ok(())
 }
 
 /// Generate an Ed25519 public key for webhook signature verification (hex).
@@ -115,7 +149,7 @@ async fn create_application(
 ) -> NexusResult<Json<(BotApplication, BotToken)>> {
     let app_id = snowflake::generate_id();
     let raw_token = generate_bot_token();
-    let token_hash = hash_token(&raw_token);
+    let token_hash = hash_bot_token(&raw_token);
     let public_key = generate_public_key();
 
     let bot = bots::create_bot(
@@ -143,13 +177,18 @@ async fn update_application(
     Path(app_id): Path<Uuid>,
     Json(body): Json<UpdateBotRequest>,
 ) -> NexusResult<Json<BotApplication>> {
-    // Verify ownership
-    let existing = bots::get_bot(&state.db.pool, app_id)
-        .await?
-        .ok_or(NexusError::NotFound { resource: "application".to_string() })?;
-    if existing.owner_id != auth.user_id {
-        return Err(NexusError::Forbidden);
+    let existing = owned_application(&state, app_id, auth.user_id).await?;
+    if let Some(allowlist) = &body.ip_allowlist {
+        validate_allowlist(allowlist)?;
     }
+    // Connected bot sessions keep what they had until they re-identify.
+    let flags = body.message_content.map(|enabled| {
+        if enabled {
+            existing.flags | application_flags::MESSAGE_CONTENT
+        } else {
+            existing.flags & !application_flags::MESSAGE_CONTENT
+        }
+    });
 
     let updated = bots::update_bot(
         &state.db.pool,
@@ -160,6 +199,8 @@ async fn update_application(
         body.is_public,
         body.redirect_uris.as_deref(),
         body.interactions_endpoint_url.as_deref(),
+        body.ip_allowlist.as_deref(),
+        flags,
     )
     .await?
     .ok_or(NexusError::NotFound { resource: "application".to_string() })?;
@@ -185,25 +226,81 @@ async fn delete_application(
 }
 
 /// POST /api/v1/applications/{app_id}/token/reset — Regenerate the bot token.
+///
+/// The previous token is invalidated immediately; the new one is shown once.
 async fn reset_token(
     Extension(auth): Extension<AuthContext>,
     State(state): State<Arc<AppState>>,
     Path(app_id): Path<Uuid>,
 ) -> NexusResult<Json<BotToken>> {
-    let existing = bots::get_bot(&state.db.pool, app_id)
-        .await?
-        .ok_or(NexusError::NotFound { resource: "application".to_string() })?;
-    if existing.owner_id != auth.user_id {
-        return Err(NexusError::Forbidden);
-    }
+    owned_application(&state, app_id, auth.user_id).await?;
 
     let raw_token = generate_bot_token();
-    let token_hash = hash_token(&raw_token);
+    let token_hash = hash_bot_token(&raw_token);
     bots::update_bot_token(&state.db.pool, app_id, &token_hash).await?;
 
+    tracing::info!(%app_id, "Bot token regenerated");
     Ok(Json(BotToken { token: format!("Bot {raw_token}") }))
 }
 
+/// POST /api/v1/applications/{app_id}/oauth2/secret/reset — Generate the
+/// OAuth2 client secret (see [`crate::oauth2`]).
+///
+/// The previous secret is invalidated immediately; the new one is shown once.
+async fn reset_client_secret(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path(app_id): Path<Uuid>,
+) -> NexusResult<Json<OAuth2ClientSecret>> {
+    owned_application(&state, app_id, auth.user_id).await?;
+
+    let secret = crate::oauth2::generate_token();
+    bots::update_client_secret(&state.db.pool, app_id, &crate::oauth2::hash_token(&secret)).await?;
+
+    tracing::info!(%app_id, "OAuth2 client secret regenerated");
+    Ok(Json(OAuth2ClientSecret { client_id: app_id, client_secret: secret }))
+}
+
+#[derive(Deserialize)]
+struct MetricsQuery {
+    /// Days of history, including today (default 7, max 90).
+    days: Option<i64>,
+}
+
+/// GET /api/v1/applications/{app_id}/metrics — Daily bot-token request counts.
+async fn get_metrics(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path(app_id): Path<Uuid>,
+    Query(params): Query<MetricsQuery>,
+) -> NexusResult<Json<serde_json::Value>> {
+    owned_application(&state, app_id, auth.user_id).await?;
+
+    let days = params.days.unwrap_or(7).clamp(1, MAX_METRICS_DAYS);
+    let since = chrono::Utc::now() - chrono::Duration::days(days - 1);
+    let rows = bots::request_metrics(&state.db.pool, app_id, since).await?;
+
+    let total = |f: fn(&bots::BotMetricsDay) -> i64| rows.iter().map(f).sum::<i64>();
+    let requests = total(|r| r.requests);
+    Ok(Json(serde_json::json!({
+        "application_id": app_id,
+        "days": days,
+        "totals": {
+            "requests": requests,
+            "errors": total(|r| r.errors),
+            "rejected": total(|r| r.rejected),
+            "avg_latency_ms": (requests > 0).then(|| total(|r| r.total_latency_ms) / requests),
+        },
+        "daily": rows.iter().map(|r| serde_json::json!({
+            "day": r.day,
+            "requests": r.requests,
+            "errors": r.errors,
+            "rejected": r.rejected,
+            "avg_latency_ms": (r.requests > 0).then(|| r.total_latency_ms / r.requests),
+        })).collect::<Vec<_>>(),
+    })))
+}
+
 // ============================================================================
 // Server Integration Endpoints
 // ============================================================================
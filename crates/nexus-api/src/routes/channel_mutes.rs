@@ -0,0 +1,104 @@
+//! Channel mute routes — channels that never notify the caller, for good or
+//! until a set time.
+//!
+//! GET    /users/@me/channel-mutes     — The caller's mutes in effect
+//! PUT    /channels/:channel_id/mute   — Mute a channel (`{"until": …}` optional)
+//! DELETE /channels/:channel_id/mute   — Unmute it
+//!
+//! A muted channel still counts unread messages but adds no mention badge
+//! and sends no push (see
+//! [`read_states::notifications_suppressed`](nexus_db::repository::read_states::notifications_suppressed)).
+
+use axum::{
+    extract::{Extension, Path, State},
+    http::StatusCode,
+    middleware,
+    routing::{get, put},
+    Json, Router,
+};
+use chrono::{DateTime, Utc};
+use nexus_common::{
+    error::{NexusError, NexusResult},
+    permissions::Permissions,
+};
+use nexus_db::repository::{channel_mutes, channel_mutes::ChannelMuteRow, channels};
+use serde::Deserialize;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{middleware::AuthContext, AppState};
+
+pub fn router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/users/@me/channel-mutes", get(list_mutes))
+        .route("/channels/{channel_id}/mute", put(mute_channel).delete(unmute_channel))
+        .route_layer(middleware::from_fn(crate::middleware::auth_middleware))
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct MuteRequest {
+    /// When the mute lifts; absent mutes until unmuted.
+    #[serde(default)]
+    until: Option<DateTime<Utc>>,
+}
+
+fn mute_json(mute: &ChannelMuteRow) -> serde_json::Value {
+    serde_json::json!({
+        "channel_id": mute.channel_id,
+        "until": mute.muted_until,
+        "created_at": mute.created_at,
+    })
+}
+
+/// GET /api/v1/users/@me/channel-mutes
+async fn list_mutes(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+) -> NexusResult<Json<Vec<serde_json::Value>>> {
+    let mutes = channel_mutes::list_active(&state.db.pool, auth.user_id).await?;
+    Ok(Json(mutes.iter().map(mute_json).collect()))
+}
+
+/// PUT /api/v1/channels/:channel_id/mute — Replaces an earlier mute.
+async fn mute_channel(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path(channel_id): Path<Uuid>,
+    body: Option<Json<MuteRequest>>,
+) -> NexusResult<Json<serde_json::Value>> {
+    let body = body.map(|Json(b)| b).unwrap_or_default();
+    if body.until.is_some_and(|until| until <= Utc::now()) {
+        return Err(NexusError::Validation {
+            message: "until must be in the future".into(),
+        });
+    }
+    require_visible(&state, channel_id, auth.user_id).await?;
+    let mute = channel_mutes::mute(&state.db.pool, auth.user_id, channel_id, body.until).await?;
+    Ok(Json(mute_json(&mute)))
+}
+
+/// DELETE /api/v1/channels/:channel_id/mute
+async fn unmute_channel(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path(channel_id): Path<Uuid>,
+) -> NexusResult<StatusCode> {
+    if !channel_mutes::unmute(&state.db.pool, auth.user_id, channel_id).await? {
+        return Err(NexusError::NotFound {
+            resource: "Channel mute".into(),
+        });
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// A server channel the user can view, or a DM they're in.
+async fn require_visible(state: &AppState, channel_id: Uuid, user_id: Uuid) -> NexusResult<()> {
+    let channel = channels::find_by_id(&state.db.pool, channel_id)
+        .await?
+        .ok_or(NexusError::NotFound { resource: "Channel".into() })?;
+    match crate::permissions::in_channel(&state.db.pool, &channel, user_id).await? {
+        Some(permissions) => crate::permissions::require(permissions, Permissions::VIEW_CHANNEL),
+        None if channels::is_dm_participant(&state.db.pool, channel.id, user_id).await? => Ok(()),
+        None => Err(NexusError::Forbidden),
+    }
+}
@@ -1,22 +1,37 @@
 //! Channel routes — CRUD for channels within a server.
+//!
+//! Archiving is the safer alternative to deletion: an archived channel keeps
+//! its history but is read-only, and is left out of
+//! `GET /servers/:id/channels` unless `?archived=true` is passed.
 
 use axum::{
-    extract::{Extension, Path, State},
+    extract::{Extension, Path, Query, State},
+    http::HeaderMap,
     middleware,
-    routing::get,
+    routing::{get, put},
     Json, Router,
 };
 use nexus_common::{
     error::{NexusError, NexusResult},
-    models::channel::{CreateChannelRequest, UpdateChannelRequest},
+    gateway_event::{event_types, GatewayEvent},
+    models::channel::{Channel, ChannelType, CreateChannelRequest, UpdateChannelRequest},
+    permissions::Permissions,
     snowflake,
     validation::validate_request,
 };
-use nexus_db::repository::{channels, servers};
+use nexus_db::repository::{channels, members, roles, servers, settings_history, voice_chat};
+use serde::Deserialize;
 use std::sync::Arc;
 use uuid::Uuid;
 
-use crate::{middleware::AuthContext, AppState};
+use crate::{
+    audit::{self, actions, Target},
+    middleware::AuthContext,
+    AppState,
+};
+
+/// Channel fields compared for `CHANNEL_UPDATE` audit entries.
+const AUDITED_FIELDS: &[&str] = &["name", "topic", "position", "nsfw", "rate_limit_per_user"];
 
 /// Channel routes.
 pub fn router() -> Router<Arc<AppState>> {
@@ -27,17 +42,31 @@ pub fn router() -> Router<Arc<AppState>> {
             "/channels/{channel_id}",
             get(get_channel).patch(update_channel).delete(delete_channel),
         )
+        .route("/channels/{channel_id}/archive", put(archive_channel).delete(unarchive_channel))
+        .route("/channels/{channel_id}/mention-candidates", get(mention_candidates))
+        .route("/channels/{channel_id}/topic-history", get(topic_history))
         .route_layer(middleware::from_fn(crate::middleware::auth_middleware));
 
     Router::new().merge(authed)
 }
 
+#[derive(Debug, Deserialize)]
+struct ListChannelsParams {
+    /// Include archived channels.
+    #[serde(default)]
+    archived: bool,
+}
+
 /// GET /api/v1/servers/:server_id/channels
 async fn list_channels(
     State(state): State<Arc<AppState>>,
     Path(server_id): Path<Uuid>,
+    Query(params): Query<ListChannelsParams>,
 ) -> NexusResult<Json<Vec<nexus_common::models::channel::Channel>>> {
-    let channel_list = channels::list_server_channels(&state.db.pool, server_id).await?;
+    let mut channel_list = channels::list_server_channels(&state.db.pool, server_id).await?;
+    if !params.archived {
+        channel_list.retain(|c| !c.archived);
+    }
     Ok(Json(channel_list))
 }
 
@@ -90,6 +119,14 @@ async fn create_channel(
         "Channel created"
     );
 
+    // Its text chat is otherwise created when someone first joins the call.
+    if matches!(channel.channel_type, ChannelType::Voice | ChannelType::Stage)
+        && let Err(e) =
+            voice_chat::find_or_create_chat(&state.db.pool, snowflake::generate_id(), &channel).await
+    {
+        tracing::warn!(channel_id = %channel_id, error = %e, "Failed to create voice text chat");
+    }
+
     Ok(Json(channel))
 }
 
@@ -109,14 +146,15 @@ async fn get_channel(
 
 /// PATCH /api/v1/channels/:channel_id
 async fn update_channel(
-    Extension(_auth): Extension<AuthContext>,
+    Extension(auth): Extension<AuthContext>,
     State(state): State<Arc<AppState>>,
     Path(channel_id): Path<Uuid>,
+    headers: HeaderMap,
     Json(body): Json<UpdateChannelRequest>,
 ) -> NexusResult<Json<nexus_common::models::channel::Channel>> {
     validate_request(&body)?;
 
-    let _channel = channels::find_by_id(&state.db.pool, channel_id)
+    let channel = channels::find_by_id(&state.db.pool, channel_id)
         .await?
         .ok_or(NexusError::NotFound {
             resource: "Channel".into(),
@@ -134,19 +172,293 @@ async fn update_channel(
     )
     .await?;
 
+    if updated.topic != channel.topic {
+        let recorded = settings_history::record_topic_change(
+            &state.db.pool,
+            channel_id,
+            updated.topic.as_deref(),
+            auth.user_id,
+        )
+        .await;
+        if let Err(e) = recorded {
+            tracing::warn!(%channel_id, error = %e, "Failed to record topic change");
+        }
+    }
+
+    if let Some(server_id) = channel.server_id {
+        let changes = audit::diff(&channel, &updated, AUDITED_FIELDS);
+        if changes.as_object().is_some_and(|c| !c.is_empty()) {
+            audit::record(
+                &state,
+                server_id,
+                auth.user_id,
+                actions::CHANNEL_UPDATE,
+                Some(Target::Channel(channel_id)),
+                Some(changes),
+                audit::reason(&headers).as_deref(),
+            )
+            .await;
+        }
+    }
+
     Ok(Json(updated))
 }
 
+/// Archived channels are read-only until unarchived. Every path that
+/// writes to a channel checks this.
+pub(crate) fn require_unarchived(channel: &Channel) -> NexusResult<()> {
+    if channel.archived {
+        return Err(NexusError::Validation {
+            message: "This channel is archived".into(),
+        });
+    }
+    Ok(())
+}
+
+/// PUT /api/v1/channels/:channel_id/archive — Make a channel read-only and
+/// hide it from listings. Requires MANAGE_CHANNELS.
+async fn archive_channel(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path(channel_id): Path<Uuid>,
+    headers: HeaderMap,
+) -> NexusResult<Json<Channel>> {
+    set_archived(&state, &auth, channel_id, true, &headers).await.map(Json)
+}
+
+/// DELETE /api/v1/channels/:channel_id/archive — Undo [`archive_channel`].
+async fn unarchive_channel(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path(channel_id): Path<Uuid>,
+    headers: HeaderMap,
+) -> NexusResult<Json<Channel>> {
+    set_archived(&state, &auth, channel_id, false, &headers).await.map(Json)
+}
+
+async fn set_archived(
+    state: &AppState,
+    auth: &AuthContext,
+    channel_id: Uuid,
+    archived: bool,
+    headers: &HeaderMap,
+) -> NexusResult<Channel> {
+    let channel = channels::find_by_id(&state.db.pool, channel_id)
+        .await?
+        .ok_or(NexusError::NotFound {
+            resource: "Channel".into(),
+        })?;
+    let server_id = match channel.server_id {
+        Some(server_id) if channel.channel_type != ChannelType::Category => server_id,
+        _ => {
+            return Err(NexusError::Validation {
+                message: "Only server channels can be archived".into(),
+            })
+        }
+    };
+    let permissions = crate::permissions::in_channel(&state.db.pool, &channel, auth.user_id)
+        .await?
+        .ok_or(NexusError::Forbidden)?;
+    crate::permissions::require(permissions, Permissions::MANAGE_CHANNELS)?;
+    if channel.archived == archived {
+        return Ok(channel);
+    }
+
+    let updated = channels::set_archived(&state.db.pool, channel_id, archived).await?;
+    audit::record(
+        state,
+        server_id,
+        auth.user_id,
+        if archived { actions::CHANNEL_ARCHIVE } else { actions::CHANNEL_UNARCHIVE },
+        Some(Target::Channel(channel_id)),
+        Some(serde_json::json!({ "name": channel.name })),
+        audit::reason(headers).as_deref(),
+    )
+    .await;
+    let _ = state.gateway_tx.send(GatewayEvent {
+        event_type: event_types::CHANNEL_UPDATE.into(),
+        data: serde_json::to_value(&updated).unwrap_or_default(),
+        server_id: Some(server_id),
+        channel_id: Some(channel_id),
+        user_id: None,
+        trace_context: None,
+    });
+
+    tracing::info!(%channel_id, archived, "Channel archive state changed");
+    Ok(updated)
+}
+
+#[derive(Debug, Deserialize)]
+struct TopicHistoryParams {
+    limit: Option<i64>,
+}
+
+/// GET /api/v1/channels/:channel_id/topic-history — Past topics, newest
+/// first. Requires VIEW_AUDIT_LOG.
+async fn topic_history(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path(channel_id): Path<Uuid>,
+    Query(params): Query<TopicHistoryParams>,
+) -> NexusResult<Json<Vec<serde_json::Value>>> {
+    let channel = channels::find_by_id(&state.db.pool, channel_id)
+        .await?
+        .ok_or(NexusError::NotFound {
+            resource: "Channel".into(),
+        })?;
+    let permissions = crate::permissions::in_channel(&state.db.pool, &channel, auth.user_id)
+        .await?
+        .ok_or(NexusError::Forbidden)?;
+    crate::permissions::require(permissions, Permissions::VIEW_AUDIT_LOG)?;
+
+    let limit = params.limit.unwrap_or(50).clamp(1, 100);
+    let changes = settings_history::list_topic_changes(&state.db.pool, channel_id, limit).await?;
+    Ok(Json(
+        changes
+            .into_iter()
+            .map(|c| {
+                serde_json::json!({
+                    "id": c.id,
+                    "topic": c.topic,
+                    "changed_by": c.changed_by,
+                    "created_at": c.created_at,
+                })
+            })
+            .collect(),
+    ))
+}
+
 /// DELETE /api/v1/channels/:channel_id
 async fn delete_channel(
-    Extension(_auth): Extension<AuthContext>,
+    Extension(auth): Extension<AuthContext>,
     State(state): State<Arc<AppState>>,
     Path(channel_id): Path<Uuid>,
+    headers: HeaderMap,
 ) -> NexusResult<Json<serde_json::Value>> {
+    let channel = channels::find_by_id(&state.db.pool, channel_id)
+        .await?
+        .ok_or(NexusError::NotFound {
+            resource: "Channel".into(),
+        })?;
+
     // TODO: proper permission check
     channels::delete_channel(&state.db.pool, channel_id).await?;
 
+    if let Some(server_id) = channel.server_id {
+        audit::record(
+            &state,
+            server_id,
+            auth.user_id,
+            actions::CHANNEL_DELETE,
+            Some(Target::Channel(channel_id)),
+            Some(serde_json::json!({ "name": channel.name, "channel_type": channel.channel_type })),
+            audit::reason(&headers).as_deref(),
+        )
+        .await;
+    }
+
     tracing::info!(channel_id = %channel_id, "Channel deleted");
 
     Ok(Json(serde_json::json!({ "deleted": true })))
 }
+
+#[derive(Debug, Deserialize)]
+struct MentionCandidatesQuery {
+    #[serde(default)]
+    q: String,
+    limit: Option<i64>,
+}
+
+/// GET /api/v1/channels/:channel_id/mention-candidates?q=&limit=
+///
+/// Members who can see the channel, mentionable roles, and visible channels
+/// whose names start with `q`. DM channels have no candidates — clients
+/// already know the participants.
+async fn mention_candidates(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path(channel_id): Path<Uuid>,
+    Query(query): Query<MentionCandidatesQuery>,
+) -> NexusResult<Json<serde_json::Value>> {
+    let pool = &state.db.pool;
+    let limit = query.limit.unwrap_or(25).clamp(1, 100);
+    let prefix = query.q.trim().trim_start_matches(['@', '#']).to_lowercase();
+
+    let channel = channels::find_by_id(pool, channel_id)
+        .await?
+        .ok_or(NexusError::NotFound {
+            resource: "Channel".into(),
+        })?;
+    let Some(server_id) = channel.server_id else {
+        return Ok(Json(serde_json::json!({ "members": [], "roles": [], "channels": [] })));
+    };
+    let server = servers::find_by_id(pool, server_id)
+        .await?
+        .ok_or(NexusError::NotFound {
+            resource: "Server".into(),
+        })?;
+    let requester = members::find_member(pool, auth.user_id, server_id)
+        .await?
+        .ok_or(NexusError::Forbidden)?;
+    let server_roles = roles::list_server_roles(pool, server_id).await?;
+
+    let my_permissions =
+        crate::permissions::for_member(&server, Some(&channel), &requester, &server_roles);
+    if !my_permissions.has(Permissions::VIEW_CHANNEL) {
+        return Err(NexusError::Forbidden);
+    }
+
+    // Over-fetch: some prefix matches won't be able to see this channel.
+    let matches = members::search_by_prefix(pool, server_id, &prefix, limit * 4).await?;
+    let members_json: Vec<serde_json::Value> = matches
+        .iter()
+        .filter(|m| {
+            crate::permissions::for_member(&server, Some(&channel), &m.member, &server_roles)
+                .has(Permissions::VIEW_CHANNEL)
+        })
+        .take(limit as usize)
+        .map(|m| {
+            serde_json::json!({
+                "user_id": m.member.user_id,
+                "username": m.username,
+                "display_name": m.display_name,
+                "nickname": m.member.nickname,
+                "avatar": m.member.avatar.as_ref().or(m.user_avatar.as_ref()),
+                "roles": m.member.roles,
+            })
+        })
+        .collect();
+
+    // Non-mentionable roles can still be pinged by members allowed to @everyone.
+    let can_mention_all = my_permissions.has(Permissions::MENTION_EVERYONE);
+    let roles_json: Vec<serde_json::Value> = server_roles
+        .iter()
+        .filter(|r| !r.is_default && (r.mentionable || can_mention_all))
+        .filter(|r| r.name.to_lowercase().starts_with(&prefix))
+        .take(limit as usize)
+        .map(|r| serde_json::json!({ "id": r.id, "name": r.name, "color": r.color }))
+        .collect();
+
+    let server_channels = channels::list_server_channels(pool, server_id).await?;
+    let channels_json: Vec<serde_json::Value> = server_channels
+        .iter()
+        .filter(|c| c.channel_type != ChannelType::Category)
+        .filter(|c| {
+            c.name
+                .as_deref()
+                .is_some_and(|n| n.to_lowercase().starts_with(&prefix))
+        })
+        .filter(|c| {
+            crate::permissions::for_member(&server, Some(c), &requester, &server_roles)
+                .has(Permissions::VIEW_CHANNEL)
+        })
+        .take(limit as usize)
+        .map(|c| serde_json::json!({ "id": c.id, "name": c.name, "channel_type": c.channel_type }))
+        .collect();
+
+    Ok(Json(serde_json::json!({
+        "members": members_json,
+        "roles": roles_json,
+        "channels": channels_json,
+    })))
+}
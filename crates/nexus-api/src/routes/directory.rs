@@ -171,9 +171,9 @@ async fn list_rooms(
     let limit = q.limit.unwrap_or(20).min(100) as i64;
 
     let rows = sqlx::query(
-        "SELECT room_id, name, topic, member_count, origin_server, join_rule \
+        "SELECT room_id, room_name AS name, room_topic AS topic, member_count, origin_server, join_rule \
          FROM federated_rooms \
-         WHERE join_rule = 'public' \
+         WHERE join_rule = 'public' AND defederated_at IS NULL \
          ORDER BY member_count DESC \
          LIMIT ?",
     )
@@ -218,29 +218,31 @@ async fn search_rooms(
 
     let rows = if let Some(ref server) = server_filter {
         sqlx::query(
-            "SELECT room_id, name, topic, member_count, origin_server, join_rule \
+            "SELECT room_id, room_name AS name, room_topic AS topic, member_count, origin_server, join_rule \
              FROM federated_rooms \
-             WHERE join_rule = 'public' \
+             WHERE join_rule = 'public' AND defederated_at IS NULL \
                AND origin_server = ? \
-               AND (name ILIKE ? OR topic ILIKE ?) \
+               AND (LOWER(room_name) LIKE LOWER(?) OR LOWER(room_topic) LIKE LOWER(?)) \
              ORDER BY member_count DESC \
              LIMIT ?",
         )
         .bind(server)
         .bind(&query_str)
+        .bind(&query_str)
         .bind(limit)
         .fetch_all(&state.db.pool)
         .await
     } else {
         sqlx::query(
-            "SELECT room_id, name, topic, member_count, origin_server, join_rule \
+            "SELECT room_id, room_name AS name, room_topic AS topic, member_count, origin_server, join_rule \
              FROM federated_rooms \
-             WHERE join_rule = 'public' \
-               AND (name ILIKE ? OR topic ILIKE ?) \
+             WHERE join_rule = 'public' AND defederated_at IS NULL \
+               AND (LOWER(room_name) LIKE LOWER(?) OR LOWER(room_topic) LIKE LOWER(?)) \
              ORDER BY member_count DESC \
              LIMIT ?",
         )
         .bind(&query_str)
+        .bind(&query_str)
         .bind(limit)
         .fetch_all(&state.db.pool)
         .await
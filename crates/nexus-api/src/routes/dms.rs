@@ -11,7 +11,6 @@ use axum::{
 };
 use nexus_common::{
     error::{NexusError, NexusResult},
-    models::channel::Channel,
     snowflake,
 };
 use nexus_db::repository::channels;
@@ -50,17 +49,7 @@ async fn list_dm_channels(
     Extension(auth): Extension<AuthContext>,
     State(state): State<Arc<AppState>>,
 ) -> NexusResult<Json<Vec<serde_json::Value>>> {
-    let dms = sqlx::query_as::<_, Channel>(
-        r#"
-        SELECT c.* FROM channels c
-        INNER JOIN dm_participants dp ON dp.channel_id = c.id
-        WHERE dp.user_id = ? AND c.channel_type IN ('dm', 'group_dm')
-        ORDER BY c.updated_at DESC
-        "#,
-    )
-    .bind(auth.user_id.to_string())
-    .fetch_all(&state.db.pool)
-    .await?;
+    let dms = channels::list_dm_channels(&state.db.pool, auth.user_id).await?;
 
     // For each DM, fetch the other participants
     let mut results = Vec::with_capacity(dms.len());
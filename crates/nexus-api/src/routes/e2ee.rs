@@ -5,19 +5,27 @@
 //! GET    /channels/:id/encrypted-messages/:msg_id  — Single message
 //! PUT    /channels/:id/e2ee                        — Enable E2EE on a channel
 //! GET    /channels/:id/e2ee                        — Get channel E2EE config
+//! POST   /e2ee/attachments                         — Upload a client-encrypted file
+//!
+//! Files in E2EE channels are encrypted by the client and uploaded through
+//! `/e2ee/attachments`; the plaintext upload routes refuse those channels
+//! (see [`require_plaintext_channel`]). A message references its uploads in
+//! `attachment_meta.attachments[].id`, and every recipient envelope in
+//! `ciphertext_map` carries the file key under `attachment_keys.<id>`.
 
 use axum::{
-    extract::{Extension, Path, Query, State},
+    extract::{DefaultBodyLimit, Extension, Multipart, Path, Query, State},
     middleware,
-    routing::get,
+    routing::{get, post},
     Json, Router,
 };
 use nexus_common::{
     error::{NexusError, NexusResult},
     models::crypto::{E2eeChannel, EnableE2eeRequest, EncryptedMessage, SendEncryptedMessageRequest},
+    permissions::Permissions,
 };
-use nexus_db::repository::keystore;
-use serde::Deserialize;
+use nexus_db::repository::{channels, keystore};
+use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use uuid::Uuid;
 
@@ -34,9 +42,18 @@ pub fn router() -> Router<Arc<AppState>> {
             "/channels/{channel_id}/e2ee",
             get(get_e2ee_config).put(enable_e2ee),
         )
+        .route(
+            "/e2ee/attachments",
+            post(upload_encrypted_attachment).layer(DefaultBodyLimit::max(super::uploads::upload_body_limit(1))),
+        )
         .route_layer(middleware::from_fn(crate::middleware::auth_middleware))
 }
 
+/// Most encrypted attachments on one message.
+const MAX_ATTACHMENTS_PER_MESSAGE: usize = 10;
+/// Lifetime of the presigned ciphertext URL, matching plaintext uploads.
+const ATTACHMENT_URL_EXPIRY_SECS: u64 = 3600 * 24 * 7;
+
 #[derive(Deserialize)]
 struct MessagesQuery {
     before_sequence: Option<i64>,
@@ -103,6 +120,25 @@ async fn send_encrypted_message(
         message: "No device registered for sender. Register a device before sending E2EE messages.".into(),
     })?;
 
+    let attachment_ids = referenced_attachments(body.attachment_meta.as_ref())?;
+    for &id in &attachment_ids {
+        let attachment = keystore::find_encrypted_attachment(&state.db.pool, id)
+            .await
+            .map_err(NexusError::Internal)?
+            .ok_or(NexusError::NotFound {
+                resource: "Encrypted attachment".into(),
+            })?;
+        if attachment.uploader_id != auth.user_id
+            || attachment.channel_id != channel_id
+            || attachment.message_id.is_some()
+        {
+            return Err(NexusError::Validation {
+                message: format!("Attachment {id} can't be used in this message"),
+            });
+        }
+    }
+    require_attachment_keys(&body.ciphertext_map, &attachment_ids)?;
+
     let msg = keystore::store_encrypted_message(
         &state.db.pool,
         channel_id,
@@ -115,6 +151,12 @@ async fn send_encrypted_message(
     .await
     .map_err(|e| NexusError::Internal(e))?;
 
+    for &id in &attachment_ids {
+        keystore::link_encrypted_attachment(&state.db.pool, id, msg.id)
+            .await
+            .map_err(NexusError::Internal)?;
+    }
+
     // Broadcast to gateway (clients receive the ciphertext_map and decrypt locally)
     let _ = state.gateway_tx.send(GatewayEvent {
         event_type: "ENCRYPTED_MESSAGE_CREATE".into(),
@@ -132,11 +174,208 @@ async fn send_encrypted_message(
         server_id: None,
         channel_id: Some(channel_id),
         user_id: Some(auth.user_id),
+        trace_context: None,
     });
 
     Ok(Json(msg))
 }
 
+/// Upload IDs listed in `attachment_meta.attachments[].id`. The rest of
+/// `attachment_meta` is client-defined and left alone.
+fn referenced_attachments(meta: Option<&serde_json::Value>) -> NexusResult<Vec<Uuid>> {
+    let Some(list) = meta.and_then(|m| m.get("attachments")) else {
+        return Ok(Vec::new());
+    };
+    let invalid = || NexusError::Validation {
+        message: "attachment_meta.attachments must be a list of { \"id\": <upload id> }".into(),
+    };
+    let list = list.as_array().ok_or_else(invalid)?;
+    if list.len() > MAX_ATTACHMENTS_PER_MESSAGE {
+        return Err(NexusError::Validation {
+            message: format!("At most {MAX_ATTACHMENTS_PER_MESSAGE} attachments per message"),
+        });
+    }
+    let mut ids = Vec::with_capacity(list.len());
+    for entry in list {
+        let id = entry
+            .get("id")
+            .and_then(|v| v.as_str())
+            .and_then(|v| Uuid::parse_str(v).ok())
+            .ok_or_else(invalid)?;
+        if !ids.contains(&id) {
+            ids.push(id);
+        }
+    }
+    Ok(ids)
+}
+
+/// Every recipient device must be able to decrypt every attachment, so each
+/// envelope needs a key for each of them.
+fn require_attachment_keys(ciphertext_map: &serde_json::Value, attachment_ids: &[Uuid]) -> NexusResult<()> {
+    if attachment_ids.is_empty() {
+        return Ok(());
+    }
+    let envelopes = ciphertext_map.as_object().into_iter().flatten();
+    for (device_id, envelope) in envelopes {
+        let keys = envelope.get("attachment_keys").and_then(|k| k.as_object());
+        for id in attachment_ids {
+            if !keys.is_some_and(|k| k.get(&id.to_string()).is_some_and(|v| v.is_string())) {
+                return Err(NexusError::Validation {
+                    message: format!("Envelope for device {device_id} is missing attachment_keys.{id}"),
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Reject plaintext uploads into an end-to-end encrypted channel; its files
+/// must go through `POST /e2ee/attachments`.
+pub(crate) async fn require_plaintext_channel(state: &AppState, channel_id: Uuid) -> NexusResult<()> {
+    let e2ee = keystore::get_e2ee_channel(&state.db.pool, channel_id)
+        .await
+        .map_err(NexusError::Internal)?;
+    if e2ee.is_some() {
+        return Err(NexusError::Validation {
+            message: "This channel is end-to-end encrypted; upload files with POST /e2ee/attachments".into(),
+        });
+    }
+    Ok(())
+}
+
+// ============================================================
+// POST /e2ee/attachments
+// ============================================================
+
+#[derive(Serialize)]
+struct EncryptedAttachmentResponse {
+    id: Uuid,
+    /// Download URL for the ciphertext; reveals nothing about the file
+    url: Option<String>,
+    size: i64,
+    sha256: String,
+    /// Name under which each envelope in `ciphertext_map` carries the file
+    /// key: `attachment_keys.<key_slot>`
+    key_slot: String,
+}
+
+/// Upload a client-encrypted file via multipart/form-data.
+///
+/// Form fields:
+/// - `file`       — the ciphertext (required)
+/// - `channel_id` — the E2EE channel it will be sent in (required)
+///
+/// The blob is stored as-is under an opaque key: no content type, filename or
+/// metadata processing.
+async fn upload_encrypted_attachment(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    mut multipart: Multipart,
+) -> NexusResult<Json<EncryptedAttachmentResponse>> {
+    use sha2::{Digest, Sha256};
+
+    let mut data: Option<Vec<u8>> = None;
+    let mut channel_id: Option<Uuid> = None;
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|e| NexusError::Validation {
+            message: format!("Multipart error: {e}"),
+        })?
+    {
+        match field.name() {
+            Some("file") => {
+                let bytes = field.bytes().await.map_err(|e| NexusError::Validation {
+                    message: format!("Failed to read file: {e}"),
+                })?;
+                // Ciphertext can't be sniffed; only its size is checked.
+                let max_bytes = nexus_common::config::get().limits.max_file_size_bytes;
+                if bytes.len() as u64 > max_bytes {
+                    return Err(NexusError::Validation {
+                        message: format!("File too large: {} bytes (max {max_bytes} bytes)", bytes.len()),
+                    });
+                }
+                data = Some(bytes.to_vec());
+            }
+            Some("channel_id") => {
+                let val = field.text().await.unwrap_or_default();
+                channel_id = Uuid::parse_str(val.trim()).ok();
+            }
+            _ => {}
+        }
+    }
+    let data = data.ok_or(NexusError::Validation {
+        message: "No file field in request".into(),
+    })?;
+    let channel_id = channel_id.ok_or(NexusError::Validation {
+        message: "channel_id is required".into(),
+    })?;
+
+    let channel = channels::find_by_id(&state.db.pool, channel_id)
+        .await?
+        .ok_or(NexusError::NotFound {
+            resource: "Channel".into(),
+        })?;
+    match crate::permissions::in_channel(&state.db.pool, &channel, auth.user_id).await? {
+        Some(permissions) => crate::permissions::require(
+            permissions,
+            Permissions::VIEW_CHANNEL | Permissions::ATTACH_FILES,
+        )?,
+        None if channels::is_dm_participant(&state.db.pool, channel.id, auth.user_id).await? => {}
+        None => return Err(NexusError::Forbidden),
+    }
+    if keystore::get_e2ee_channel(&state.db.pool, channel_id)
+        .await
+        .map_err(NexusError::Internal)?
+        .is_none()
+    {
+        return Err(NexusError::Validation {
+            message: "Channel is not end-to-end encrypted".into(),
+        });
+    }
+
+    let id = Uuid::new_v4();
+    let storage_key = format!("e2ee/{channel_id}/{id}");
+    let size = data.len() as i64;
+    let sha256 = hex::encode(Sha256::digest(&data));
+    state
+        .storage
+        .put_object(&storage_key, data, "application/octet-stream")
+        .await
+        .map_err(NexusError::Internal)?;
+
+    let row = match keystore::create_encrypted_attachment(
+        &state.db.pool,
+        id,
+        channel_id,
+        auth.user_id,
+        &storage_key,
+        size,
+        &sha256,
+    )
+    .await
+    {
+        Ok(row) => row,
+        Err(e) => {
+            let _ = state.storage.delete_object(&storage_key).await;
+            return Err(NexusError::Internal(e));
+        }
+    };
+    let url = state
+        .storage
+        .presigned_get_url(&row.storage_key, ATTACHMENT_URL_EXPIRY_SECS)
+        .await
+        .ok();
+
+    Ok(Json(EncryptedAttachmentResponse {
+        id: row.id,
+        url,
+        size: row.size,
+        sha256: row.sha256,
+        key_slot: row.id.to_string(),
+    }))
+}
+
 // ============================================================
 // GET /channels/:channel_id/e2ee
 // ============================================================
@@ -185,6 +424,7 @@ async fn enable_e2ee(
         server_id: None,
         channel_id: Some(channel_id),
         user_id: Some(auth.user_id),
+        trace_context: None,
     });
 
     Ok(Json(config))
@@ -81,6 +81,13 @@ async fn create_emoji(
                         ),
                     });
                 }
+                if !crate::file_types::is_allowed_emoji(&content_type) {
+                    return Err(NexusError::Validation {
+                        message: "Emoji must be PNG, GIF, WebP or JPEG".into(),
+                    });
+                }
+                crate::file_types::check(&content_type, &bytes)
+                    .map_err(|message| NexusError::Validation { message })?;
                 file_data = Some(bytes.to_vec());
             }
             Some("name") => {
@@ -150,6 +157,7 @@ async fn create_emoji(
         server_id: Some(server_id),
         channel_id: None,
         user_id: Some(auth.user_id),
+        trace_context: None,
     });
 
     Ok(Json(se))
@@ -216,6 +224,7 @@ async fn update_emoji(
         server_id: Some(server_id),
         channel_id: None,
         user_id: Some(auth.user_id),
+        trace_context: None,
     });
 
     Ok(Json(se))
@@ -245,6 +254,7 @@ async fn delete_emoji(
         server_id: Some(server_id),
         channel_id: None,
         user_id: Some(auth.user_id),
+        trace_context: None,
     });
 
     Ok(Json(serde_json::json!({ "deleted": true })))
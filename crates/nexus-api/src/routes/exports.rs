@@ -0,0 +1,197 @@
+//! Data export routes — archives of everything a user has put on the
+//! server, built by [`crate::jobs::data_export`].
+//!
+//! POST /users/@me/export               — Request an archive
+//! GET  /users/@me/export/{export_id}   — Its progress, and a download link once done
+//! GET  /exports/{export_id}/download?expires=&signature= — The archive
+//!
+//! The download link is signed rather than authenticated, so it works from a
+//! plain browser tab. It is valid for [`DOWNLOAD_LINK_TTL_SECS`] and only
+//! once: the archive is deleted as it is handed out.
+
+use axum::{
+    body::Body,
+    extract::{Extension, Path, Query, State},
+    http::{header, StatusCode},
+    middleware,
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use nexus_common::error::{NexusError, NexusResult};
+use nexus_db::repository::data_exports::{self, DataExport};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{middleware::AuthContext, AppState};
+
+/// How long a download link works.
+pub const DOWNLOAD_LINK_TTL_SECS: i64 = 15 * 60;
+
+pub fn router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/users/@me/export", post(request_export))
+        .route("/users/@me/export/{export_id}", get(get_export))
+        .route_layer(middleware::from_fn(crate::middleware::auth_middleware))
+        .route("/exports/{export_id}/download", get(download_export))
+}
+
+#[derive(Serialize)]
+struct ExportResponse {
+    id: Uuid,
+    /// `pending`, `processing`, `completed`, `failed`, `downloaded` or `expired`.
+    status: String,
+    /// Percent done.
+    progress: i32,
+    size_bytes: Option<i64>,
+    created_at: DateTime<Utc>,
+    completed_at: Option<DateTime<Utc>>,
+    /// When the archive is deleted if it hasn't been downloaded.
+    expires_at: Option<DateTime<Utc>>,
+    /// One-time link to the archive, while `completed`. Relative to the
+    /// server's origin.
+    download_url: Option<String>,
+}
+
+impl ExportResponse {
+    fn new(export: DataExport, secret: &str) -> Self {
+        let download_url = match export.expires_at {
+            Some(expires_at) if export.status == "completed" => {
+                let expires = (Utc::now().timestamp() + DOWNLOAD_LINK_TTL_SECS).min(expires_at.timestamp());
+                Some(format!(
+                    "/api/v1/exports/{}/download?expires={expires}&signature={}",
+                    export.id,
+                    link_signature(secret, export.id, expires)
+                ))
+            }
+            _ => None,
+        };
+        Self {
+            id: export.id,
+            status: export.status,
+            progress: export.progress,
+            size_bytes: export.size_bytes,
+            created_at: export.created_at,
+            completed_at: export.completed_at,
+            expires_at: export.expires_at,
+            download_url,
+        }
+    }
+}
+
+/// POST /api/v1/users/@me/export — Queue an archive of the caller's data.
+///
+/// Returns the export already under way, if there is one. Otherwise a new
+/// export may be requested once per `exports.cooldown_hours`; failed ones
+/// don't count.
+async fn request_export(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+) -> NexusResult<(StatusCode, Json<ExportResponse>)> {
+    let config = nexus_common::config::get();
+    let secret = &config.auth.jwt_secret;
+
+    if let Some(latest) = data_exports::latest_for_user(&state.db.pool, auth.user_id).await? {
+        if matches!(latest.status.as_str(), "pending" | "processing") {
+            return Ok((StatusCode::OK, Json(ExportResponse::new(latest, secret))));
+        }
+        let next = latest.created_at + chrono::Duration::hours(config.exports.cooldown_hours.into());
+        if latest.status != "failed" && next > Utc::now() {
+            return Err(NexusError::RateLimited {
+                retry_after_ms: (next - Utc::now()).num_milliseconds().max(0) as u64,
+            });
+        }
+    }
+
+    // Random rather than time-ordered: the ID is part of the archive's
+    // storage key.
+    let export = data_exports::create(&state.db.pool, Uuid::new_v4(), auth.user_id).await?;
+    tracing::info!(export_id = %export.id, user_id = %auth.user_id, "Data export requested");
+    Ok((StatusCode::ACCEPTED, Json(ExportResponse::new(export, secret))))
+}
+
+/// GET /api/v1/users/@me/export/:export_id — Poll an export.
+async fn get_export(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path(export_id): Path<Uuid>,
+) -> NexusResult<Json<ExportResponse>> {
+    let export = data_exports::find_by_id(&state.db.pool, export_id)
+        .await?
+        .filter(|e| e.user_id == auth.user_id)
+        .ok_or(NexusError::NotFound {
+            resource: "Export".into(),
+        })?;
+    Ok(Json(ExportResponse::new(
+        export,
+        &nexus_common::config::get().auth.jwt_secret,
+    )))
+}
+
+#[derive(Deserialize)]
+struct DownloadQuery {
+    expires: i64,
+    signature: String,
+}
+
+/// GET /api/v1/exports/:export_id/download — The archive, through a link
+/// from [`get_export`]. Works once.
+async fn download_export(
+    State(state): State<Arc<AppState>>,
+    Path(export_id): Path<Uuid>,
+    Query(query): Query<DownloadQuery>,
+) -> NexusResult<Response> {
+    let secret = &nexus_common::config::get().auth.jwt_secret;
+    if !verify_link(secret, export_id, query.expires, &query.signature, Utc::now().timestamp()) {
+        return Err(NexusError::Forbidden);
+    }
+
+    let not_found = || NexusError::NotFound {
+        resource: "Export".into(),
+    };
+    let export = data_exports::find_by_id(&state.db.pool, export_id)
+        .await?
+        .filter(|e| e.status == "completed")
+        .ok_or_else(not_found)?;
+    let key = export.storage_key.ok_or_else(not_found)?;
+    let data = state.storage.get_object(&key).await?.ok_or_else(not_found)?;
+    // Only the first of concurrent downloads gets the archive
+    if !data_exports::claim_download(&state.db.pool, export_id).await? {
+        return Err(not_found());
+    }
+    if let Err(e) = state.storage.delete_object(&key).await {
+        tracing::warn!(%export_id, error = %e, "Failed to delete downloaded data export");
+    }
+
+    let filename = format!("nexus-export-{}.zip", export.created_at.format("%Y-%m-%d"));
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/zip".to_owned()),
+            (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{filename}\"")),
+            (header::CACHE_CONTROL, "no-store".to_owned()),
+        ],
+        Body::from(data),
+    )
+        .into_response())
+}
+
+fn link_signature(secret: &str, export_id: Uuid, expires: i64) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(format!("export:{export_id}:{expires}").as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
+
+/// Whether `signature` is ours for this export and expiry, and `now` is
+/// before the expiry.
+fn verify_link(secret: &str, export_id: Uuid, expires: i64, signature: &str, now: i64) -> bool {
+    let mac_ok = hex::decode(signature).is_ok_and(|signature| {
+        let mut expected = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+        expected.update(format!("export:{export_id}:{expires}").as_bytes());
+        expected.verify_slice(&signature).is_ok()
+    });
+    mac_ok && now < expires
+}
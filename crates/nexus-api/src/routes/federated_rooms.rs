@@ -0,0 +1,359 @@
+//! Channel federation routes — promote a local channel to a federated room,
+//! or take it back off the federation.
+//!
+//! GET    /channels/:id/federation  — The channel's room, if it has one
+//! PUT    /channels/:id/federation  — Promote the channel (MANAGE_SERVER)
+//! DELETE /channels/:id/federation  — Defederate the channel (MANAGE_SERVER)
+//!
+//! Promoting assigns the room ID `!<channel_id>:<server_name>`, writes the
+//! room's initial state events (create, name, topic, join rule, and the
+//! promoter's join) and — for `public` rooms — lists it in the room
+//! directory. New messages in the channel are then sent to every remote
+//! server with a member in the room, see [`relay_message`].
+//!
+//! Defederating stops all outbound sending and refuses new joins, but keeps
+//! the room, its events and the channel's history: promoting the channel
+//! again resumes the same room.
+
+use axum::{
+    extract::{Extension, Path, State},
+    http::{HeaderMap, StatusCode},
+    middleware,
+    routing::get,
+    Json, Router,
+};
+use base64::Engine as _;
+use nexus_common::{
+    error::{NexusError, NexusResult},
+    gateway_event::{event_types, GatewayEvent},
+    models::channel::{Channel, ChannelType},
+    permissions::Permissions,
+};
+use nexus_db::repository::{channels, federated_rooms, messages::MessageRow};
+use nexus_federation::types::{self, EventHashes, FederationEventType, JoinRule};
+use nexus_federation::{FederationEvent, FederationTransaction};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{
+    audit::{self, actions, Target},
+    middleware::AuthContext,
+    AppState,
+};
+
+pub fn router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route(
+            "/channels/{channel_id}/federation",
+            get(get_federation).put(promote).delete(defederate),
+        )
+        .route_layer(middleware::from_fn(crate::middleware::auth_middleware))
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct PromoteRequest {
+    /// Who may join from other servers; `public` rooms are listed in the
+    /// directory. Defaults to `public`.
+    #[serde(default)]
+    join_rule: JoinRule,
+}
+
+fn join_rule_str(rule: &JoinRule) -> &'static str {
+    match rule {
+        JoinRule::Public => "public",
+        JoinRule::Invite => "invite",
+        JoinRule::Knock => "knock",
+    }
+}
+
+fn room_json(room: &federated_rooms::FederatedRoomRow) -> serde_json::Value {
+    serde_json::json!({
+        "room_id": room.room_id,
+        "channel_id": room.local_channel_id,
+        "origin_server": room.origin_server,
+        "name": room.room_name,
+        "topic": room.room_topic,
+        "join_rule": room.join_rule,
+        "member_count": room.member_count,
+        "federated": room.is_federated(),
+        "listed": room.is_federated() && room.join_rule == "public",
+        "defederated_at": room.defederated_at,
+        "created_at": room.created_at,
+    })
+}
+
+/// Load a server channel and require `required` in it.
+async fn load_channel(
+    state: &AppState,
+    channel_id: Uuid,
+    user_id: Uuid,
+    required: Permissions,
+) -> NexusResult<(Channel, Uuid)> {
+    let channel = channels::find_by_id(&state.db.pool, channel_id)
+        .await?
+        .ok_or(NexusError::NotFound { resource: "Channel".into() })?;
+    let server_id = channel.server_id.ok_or(NexusError::Validation {
+        message: "Only server channels can be federated".into(),
+    })?;
+    let permissions = crate::permissions::in_channel(&state.db.pool, &channel, user_id)
+        .await?
+        .ok_or(NexusError::Forbidden)?;
+    crate::permissions::require(permissions, required)?;
+    Ok((channel, server_id))
+}
+
+// ============================================================
+// Handlers
+// ============================================================
+
+/// GET /api/v1/channels/:channel_id/federation
+async fn get_federation(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path(channel_id): Path<Uuid>,
+) -> NexusResult<Json<serde_json::Value>> {
+    load_channel(&state, channel_id, auth.user_id, Permissions::VIEW_CHANNEL).await?;
+    let room = federated_rooms::find_by_channel(&state.db.pool, channel_id)
+        .await?
+        .ok_or(NexusError::NotFound { resource: "Federated room".into() })?;
+    Ok(Json(room_json(&room)))
+}
+
+/// PUT /api/v1/channels/:channel_id/federation
+async fn promote(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path(channel_id): Path<Uuid>,
+    headers: HeaderMap,
+    body: Option<Json<PromoteRequest>>,
+) -> NexusResult<(StatusCode, Json<serde_json::Value>)> {
+    let body = body.map(|Json(b)| b).unwrap_or_default();
+    let (channel, server_id) =
+        load_channel(&state, channel_id, auth.user_id, Permissions::MANAGE_SERVER).await?;
+    if !matches!(channel.channel_type, ChannelType::Text | ChannelType::Announcement)
+        || channel.voice_channel_id.is_some()
+    {
+        return Err(NexusError::Validation {
+            message: "Only text and announcement channels can be federated".into(),
+        });
+    }
+    if channel.encrypted {
+        return Err(NexusError::Validation {
+            message: "End-to-end encrypted channels can't be federated".into(),
+        });
+    }
+
+    let pool = &state.db.pool;
+    let existing = federated_rooms::find_by_channel(pool, channel_id).await?;
+    if existing.as_ref().is_some_and(|r| r.is_federated()) {
+        return Err(NexusError::Validation {
+            message: "Channel is already federated".into(),
+        });
+    }
+
+    let room_id = types::room_id(&channel_id.to_string(), &state.server_name);
+    let name = channel.name.clone().unwrap_or_else(|| channel_id.to_string());
+    let join_rule = join_rule_str(&body.join_rule);
+    let room = federated_rooms::promote(
+        pool,
+        Uuid::new_v4(),
+        &room_id,
+        channel_id,
+        &state.server_name,
+        &name,
+        channel.topic.as_deref(),
+        join_rule,
+    )
+    .await?;
+
+    // Initial state; a room federated again only restates what may have
+    // changed while it was off the federation.
+    let sender = types::mxid(&auth.username, &state.server_name);
+    let mut initial = Vec::new();
+    if existing.is_none() {
+        initial.push((
+            FederationEventType::RoomState,
+            serde_json::json!({
+                "state_key": "create",
+                "creator": sender,
+                "room_version": "nexus.v1",
+                "channel_id": channel_id,
+                "server_id": server_id,
+            }),
+        ));
+    }
+    initial.push((FederationEventType::RoomState, serde_json::json!({ "state_key": "name", "name": name })));
+    if let Some(topic) = &channel.topic {
+        initial.push((FederationEventType::RoomState, serde_json::json!({ "state_key": "topic", "topic": topic })));
+    }
+    initial.push((
+        FederationEventType::RoomState,
+        serde_json::json!({ "state_key": "join_rule", "join_rule": join_rule }),
+    ));
+    if existing.is_none() {
+        initial.push((FederationEventType::MemberJoin, serde_json::json!({ "membership": "join" })));
+    }
+    for (event_type, content) in initial {
+        create_event(&state, &room_id, event_type, &sender, content).await?;
+    }
+
+    audit::record(
+        &state,
+        server_id,
+        auth.user_id,
+        actions::CHANNEL_FEDERATE,
+        Some(Target::Channel(channel_id)),
+        Some(serde_json::json!({ "room_id": room.room_id, "join_rule": room.join_rule })),
+        audit::reason(&headers).as_deref(),
+    )
+    .await;
+    publish(&state, server_id, channel_id, &room);
+
+    tracing::info!(%channel_id, room_id = %room.room_id, join_rule, "Channel promoted to federated room");
+    let status = if existing.is_none() { StatusCode::CREATED } else { StatusCode::OK };
+    Ok((status, Json(room_json(&room))))
+}
+
+/// DELETE /api/v1/channels/:channel_id/federation
+async fn defederate(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path(channel_id): Path<Uuid>,
+    headers: HeaderMap,
+) -> NexusResult<Json<serde_json::Value>> {
+    let (_, server_id) =
+        load_channel(&state, channel_id, auth.user_id, Permissions::MANAGE_SERVER).await?;
+    let room = federated_rooms::defederate(&state.db.pool, channel_id, chrono::Utc::now())
+        .await?
+        .ok_or(NexusError::NotFound { resource: "Federated room".into() })?;
+
+    audit::record(
+        &state,
+        server_id,
+        auth.user_id,
+        actions::CHANNEL_DEFEDERATE,
+        Some(Target::Channel(channel_id)),
+        Some(serde_json::json!({ "room_id": room.room_id })),
+        audit::reason(&headers).as_deref(),
+    )
+    .await;
+    publish(&state, server_id, channel_id, &room);
+
+    tracing::info!(%channel_id, room_id = %room.room_id, "Channel defederated");
+    Ok(Json(room_json(&room)))
+}
+
+fn publish(state: &AppState, server_id: Uuid, channel_id: Uuid, room: &federated_rooms::FederatedRoomRow) {
+    let _ = state.gateway_tx.send(GatewayEvent {
+        event_type: event_types::CHANNEL_FEDERATION_UPDATE.into(),
+        data: room_json(room),
+        server_id: Some(server_id),
+        channel_id: Some(channel_id),
+        user_id: None,
+        trace_context: None,
+    });
+}
+
+// ============================================================
+// Events
+// ============================================================
+
+/// Build, sign and store an event this server sends in `room_id`.
+async fn create_event(
+    state: &AppState,
+    room_id: &str,
+    event_type: FederationEventType,
+    sender: &str,
+    content: serde_json::Value,
+) -> NexusResult<FederationEvent> {
+    let canonical = nexus_federation::signatures::canonical_json(&content)
+        .map_err(|e| NexusError::Internal(e.into()))?;
+    let event = FederationEvent {
+        event_id: types::new_event_id(&state.server_name),
+        origin: state.server_name.clone(),
+        destination: None,
+        event_type,
+        room_id: room_id.to_owned(),
+        sender: sender.to_owned(),
+        origin_server_ts: chrono::Utc::now().timestamp_millis(),
+        content,
+        prev_events: Vec::new(),
+        signatures: Default::default(),
+        hashes: EventHashes {
+            sha256: base64::engine::general_purpose::URL_SAFE_NO_PAD
+                .encode(Sha256::digest(canonical.as_bytes())),
+        },
+    };
+    let mut signed = serde_json::to_value(&event).map_err(|e| NexusError::Internal(e.into()))?;
+    nexus_federation::sign_event(&state.federation_key, &state.server_name, &mut signed)
+        .map_err(|e| NexusError::Internal(e.into()))?;
+    let event: FederationEvent =
+        serde_json::from_value(signed.clone()).map_err(|e| NexusError::Internal(e.into()))?;
+
+    federated_rooms::insert_event(
+        &state.db.pool,
+        &event.event_id,
+        room_id,
+        signed["type"].as_str().unwrap_or_default(),
+        sender,
+        &state.server_name,
+        event.origin_server_ts,
+        &event.content,
+        &signed["signatures"],
+    )
+    .await?;
+    Ok(event)
+}
+
+/// Send a new message in a federated channel to the room's remote servers.
+///
+/// Does nothing for channels that aren't (or are no longer) federated.
+/// Failures are logged rather than surfaced — federation must never fail
+/// the message itself.
+pub(crate) async fn relay_message(state: &AppState, channel: &Channel, msg: &MessageRow, author_username: &str) {
+    if channel.server_id.is_none() {
+        return;
+    }
+    let room = match federated_rooms::find_by_channel(&state.db.pool, channel.id).await {
+        Ok(Some(room)) if room.is_federated() => room,
+        Ok(_) => return,
+        Err(e) => {
+            tracing::warn!(channel_id = %channel.id, error = %e, "Failed to look up federated room");
+            return;
+        }
+    };
+
+    let sender = types::mxid(author_username, &state.server_name);
+    let content = serde_json::json!({
+        "msgtype": "nexus.text",
+        "body": msg.content,
+        "message_id": msg.id,
+    });
+    let event = match create_event(state, &room.room_id, FederationEventType::MessageCreate, &sender, content).await {
+        Ok(event) => event,
+        Err(e) => {
+            tracing::warn!(message_id = %msg.id, error = %e, "Failed to create federated message event");
+            return;
+        }
+    };
+    let destinations = match federated_rooms::remote_servers(&state.db.pool, &room.room_id, &state.server_name).await {
+        Ok(destinations) => destinations,
+        Err(e) => {
+            tracing::warn!(room_id = %room.room_id, error = %e, "Failed to list room servers");
+            return;
+        }
+    };
+
+    for destination in destinations {
+        let client = state.federation_client.clone();
+        let mut txn = FederationTransaction::new(&state.server_name, &destination);
+        txn.pdus.push(event.clone());
+        tokio::spawn(async move {
+            if let Err(e) = client.send_transaction(&destination, txn).await {
+                tracing::warn!(%destination, error = %e, "Failed to send federated message");
+            }
+        });
+    }
+}
@@ -21,12 +21,12 @@
 
 use axum::{
     extract::{Path, Query, State},
-    http::{HeaderMap, StatusCode},
-    response::IntoResponse,
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
     routing::{get, put},
     Json, Router,
 };
-use nexus_db::repository::users;
+use nexus_db::repository::{federated_rooms, federated_servers, users};
 use serde::Deserialize;
 use serde_json::{json, Value};
 use sqlx::Row as _;
@@ -116,7 +116,15 @@ async fn receive_transaction(
 
     debug!("Received federation transaction {} from {}", txn_id, origin);
 
-    // ── 2. Idempotency: skip already-processed transactions ───────────────────
+    // ── 2. Per-origin rate limits and throttling ──────────────────────────────
+    if state.federation_limits.enabled() {
+        let pdu_count = body.get("pdus").and_then(Value::as_array).map_or(0, Vec::len);
+        if let Some(refused) = check_origin_limits(&state, &origin, pdu_count).await {
+            return refused;
+        }
+    }
+
+    // ── 3. Idempotency: skip already-processed transactions ───────────────────
     match sqlx::query(
         "SELECT 1 FROM federation_txn_log \
          WHERE txn_id = $1 AND origin_server = $2 \
@@ -135,11 +143,20 @@ async fn receive_transaction(
         Err(e) => warn!("Failed to query txn_log for idempotency: {}", e),
     }
 
-    // ── 3. Upsert origin server in federated_servers ──────────────────────────
+    // ── 4. Reject transactions stamped outside the acceptance window ──────────
+    if let Some(ts) = body.get("origin_server_ts").and_then(Value::as_i64) {
+        if let Err(e) = check_origin_ts(ts) {
+            warn!("Rejected federated transaction {} from {}: {}", txn_id, origin, e);
+            return (StatusCode::BAD_REQUEST, Json(json!({ "error": e.to_string() }))).into_response();
+        }
+    }
+
+    // ── 5. Upsert origin server in federated_servers ──────────────────────────
+    // An accepted transaction ends any run of rate-limit strikes.
     if let Err(e) = sqlx::query(
         "INSERT INTO federated_servers (server_name, last_seen_at) \
          VALUES ($1, NOW()) \
-         ON CONFLICT (server_name) DO UPDATE SET last_seen_at = NOW()",
+         ON CONFLICT (server_name) DO UPDATE SET last_seen_at = NOW(), rate_limit_strikes = 0",
     )
     .bind(&origin)
     .execute(&state.db.pool)
@@ -148,10 +165,10 @@ async fn receive_transaction(
         warn!("Failed to upsert federated server {}: {}", origin, e);
     }
 
-    // ── 4. Load verify keys for the origin server ─────────────────────────────
+    // ── 6. Load verify keys for the origin server ─────────────────────────────
     let verify_keys = load_server_verify_keys(&state.db.pool, &origin).await;
 
-    // ── 5. Process each PDU ───────────────────────────────────────────────────
+    // ── 7. Process each PDU ───────────────────────────────────────────────────
     let pdus = body
         .get("pdus")
         .and_then(Value::as_array)
@@ -173,12 +190,16 @@ async fn receive_transaction(
         }
     }
 
+    for edu in body.get("edus").and_then(Value::as_array).into_iter().flatten() {
+        process_edu(&state, &origin, edu).await;
+    }
+
     info!(
         "Federation txn {} from {}: {}/{} PDUs accepted, {} EDUs",
         txn_id, origin, accepted, pdu_count, edu_count
     );
 
-    // ── 6. Log the transaction (idempotent guard for future retries) ──────────
+    // ── 8. Log the transaction (idempotent guard for future retries) ──────────
     if let Err(e) = sqlx::query(
         "INSERT INTO federation_txn_log \
          (txn_id, origin_server, pdu_count, edu_count) \
@@ -198,13 +219,115 @@ async fn receive_transaction(
     (StatusCode::OK, Json(json!({}))).into_response()
 }
 
+/// EDU type announcing that a user's account was deleted on their home
+/// server, with `content.user_id` their MXID.
+pub(crate) const USER_DELETE_EDU: &str = "nexus.user.delete";
+
+/// Act on an EDU. Unknown types are ignored.
+async fn process_edu(state: &AppState, origin: &str, edu: &Value) {
+    if edu.get("edu_type").and_then(Value::as_str) != Some(USER_DELETE_EDU) {
+        return;
+    }
+    let Some(mxid) = edu.pointer("/content/user_id").and_then(Value::as_str) else {
+        return;
+    };
+    // Only a user's own server can say they're gone
+    if mxid_server(mxid) != Some(origin) {
+        warn!("Ignored deletion of {} announced by {}", mxid, origin);
+        return;
+    }
+    match nexus_db::repository::federated_users::tombstone(&state.db.pool, mxid).await {
+        Ok(true) => info!("Remote user {} was deleted by {}", mxid, origin),
+        Ok(false) => {}
+        Err(e) => warn!("Failed to tombstone remote user {}: {}", mxid, e),
+    }
+}
+
+/// The server part of `@localpart:server`.
+fn mxid_server(mxid: &str) -> Option<&str> {
+    mxid.strip_prefix('@')?.split_once(':').map(|(_, server)| server)
+}
+
+/// Tell `destinations` that the local user `mxid` deleted their account.
+/// Fire and forget, like message relaying.
+pub(crate) fn send_user_deletion(state: &AppState, mxid: &str, destinations: Vec<String>) {
+    for destination in destinations {
+        let client = state.federation_client.clone();
+        let mut txn = nexus_federation::FederationTransaction::new(&state.server_name, &destination);
+        txn.edus.push(json!({ "edu_type": USER_DELETE_EDU, "content": { "user_id": mxid } }));
+        tokio::spawn(async move {
+            if let Err(e) = client.send_transaction(&destination, txn).await {
+                warn!("Failed to send user deletion to {}: {}", destination, e);
+            }
+        });
+    }
+}
+
+/// Apply [`crate::federation_limits`] to a transaction from `origin`
+/// carrying `pdu_count` PDUs. Returns the 429 to send if it is refused,
+/// either because the origin is throttled or because it is over its limits;
+/// in the latter case a strike is recorded, which may throttle it.
+async fn check_origin_limits(state: &AppState, origin: &str, pdu_count: usize) -> Option<Response> {
+    let pool = &state.db.pool;
+    let limits = &state.federation_limits;
+    match federated_servers::throttled_until(pool, origin).await {
+        Ok(Some(until)) => {
+            debug!("Refusing transaction from throttled origin {}", origin);
+            let retry_after = (until - chrono::Utc::now()).to_std().unwrap_or_default();
+            return Some(rate_limited(retry_after));
+        }
+        Ok(None) => {}
+        Err(e) => warn!("Failed to load throttle state for {}: {}", origin, e),
+    }
+
+    let outcome = limits.check(origin, u32::try_from(pdu_count).unwrap_or(u32::MAX));
+    if outcome.allowed {
+        return None;
+    }
+    let until = chrono::Utc::now() + limits.throttle_duration();
+    let max_strikes = i32::try_from(limits.throttle_strikes()).unwrap_or(i32::MAX);
+    match federated_servers::record_rate_limit_strike(pool, origin, max_strikes, until).await {
+        Ok(true) => {
+            warn!("Throttling federation origin {} until {}", origin, until);
+            crate::security_events::record(
+                state,
+                crate::security_events::events::FEDERATION_ORIGIN_THROTTLED,
+                json!({ "origin": origin, "until": until }),
+            )
+            .await;
+            return Some(rate_limited(limits.throttle_duration().to_std().unwrap_or_default()));
+        }
+        Ok(false) => debug!("Rate limited federation transaction from {}", origin),
+        Err(e) => warn!("Failed to record rate limit strike for {}: {}", origin, e),
+    }
+    Some(rate_limited(outcome.retry_after))
+}
+
+/// A 429 telling the origin when to retry, as `Retry-After` and `retry_after_ms`.
+fn rate_limited(retry_after: std::time::Duration) -> Response {
+    let mut response = (
+        StatusCode::TOO_MANY_REQUESTS,
+        Json(json!({
+            "error": "Rate limited",
+            "retry_after_ms": retry_after.as_millis() as u64,
+        })),
+    )
+        .into_response();
+    response.headers_mut().insert(
+        header::RETRY_AFTER,
+        HeaderValue::from(retry_after.as_secs_f64().ceil() as u64),
+    );
+    response
+}
+
 // ─── PDU helpers ─────────────────────────────────────────────────────────────
 
 /// Process a single incoming PDU:
 ///
-/// 1. Verify the Ed25519 signature if verify keys are available.
-/// 2. Persist to `federated_events` (idempotent: ON CONFLICT event_id DO NOTHING).
-/// 3. Upsert the sender into `federated_users` if they're from a remote server.
+/// 1. Check `origin_server_ts` against the acceptance window.
+/// 2. Verify the Ed25519 signature if verify keys are available.
+/// 3. Persist to `federated_events` (idempotent: ON CONFLICT event_id DO NOTHING).
+/// 4. Upsert the sender into `federated_users` if they're from a remote server.
 ///
 /// Returns `Ok(true)` if newly persisted, `Ok(false)` if duplicate, `Err` if rejected.
 async fn process_pdu(
@@ -225,7 +348,11 @@ async fn process_pdu(
         .ok_or_else(|| anyhow::anyhow!("PDU missing room_id"))?;
     let event_type = pdu.get("type").and_then(Value::as_str).unwrap_or("nexus.unknown");
     let sender = pdu.get("sender").and_then(Value::as_str).unwrap_or(origin);
-    let origin_server_ts = pdu.get("origin_server_ts").and_then(Value::as_i64).unwrap_or(0);
+    let origin_server_ts = pdu
+        .get("origin_server_ts")
+        .and_then(Value::as_i64)
+        .ok_or_else(|| anyhow::anyhow!("PDU missing origin_server_ts"))?;
+    check_origin_ts(origin_server_ts)?;
     let content = pdu
         .get("content")
         .cloned()
@@ -278,6 +405,27 @@ async fn process_pdu(
     Ok(new_event)
 }
 
+/// Reject an `origin_server_ts` (ms since the epoch) further ahead of our
+/// clock than `federation.max_future_skew_secs`, or older than
+/// `federation.acceptance_window_secs`.
+///
+/// The transaction log is kept for the same window, so a replayed
+/// transaction is either caught by the idempotency check or carries events
+/// too old to be accepted here.
+fn check_origin_ts(origin_server_ts: i64) -> Result<(), anyhow::Error> {
+    let cfg = &nexus_common::config::get().federation;
+    let now = chrono::Utc::now().timestamp_millis();
+    let max_skew_ms = i64::try_from(cfg.max_future_skew_secs.saturating_mul(1000)).unwrap_or(i64::MAX);
+    let window_ms = i64::try_from(cfg.acceptance_window_secs.saturating_mul(1000)).unwrap_or(i64::MAX);
+    if origin_server_ts > now.saturating_add(max_skew_ms) {
+        anyhow::bail!("origin_server_ts {} is in the future", origin_server_ts);
+    }
+    if origin_server_ts < now.saturating_sub(window_ms) {
+        anyhow::bail!("origin_server_ts {} is outside the acceptance window", origin_server_ts);
+    }
+    Ok(())
+}
+
 /// Verify the Ed25519 signature on a PDU against the origin server's verify keys.
 fn verify_pdu_signature(
     pdu: &Value,
@@ -450,6 +598,9 @@ async fn make_join(
     if let Err(e) = extract_federation_origin(&headers) {
         return (StatusCode::UNAUTHORIZED, Json(json!({ "error": e }))).into_response();
     }
+    if let Some(refused) = refuse_defederated(&state, &room_id).await {
+        return refused;
+    }
 
     let server_name = &state.server_name;
 
@@ -485,6 +636,9 @@ async fn send_join(
     };
 
     info!("Processing send_join for room {} event {} from {}", room_id, event_id, origin);
+    if let Some(refused) = refuse_defederated(&state, &room_id).await {
+        return refused;
+    }
 
     let pool = &state.db.pool;
 
@@ -499,57 +653,54 @@ async fn send_join(
         debug!("No cached keys for {} — accepting send_join without sig verify", origin);
     }
 
-    // Upsert room.
+    // Store the join event and count the member together, so a failure
+    // can't leave one without the other.
     let room_name = event
         .get("content")
         .and_then(|c| c.get("room_name"))
         .and_then(Value::as_str)
         .unwrap_or(&room_id)
         .to_owned();
-    let _ = sqlx::query(
-        "INSERT INTO federated_rooms (room_id, origin_server, room_name, join_rule, member_count) \
-         VALUES ($1, $2, $3, 'public', 1) \
-         ON CONFLICT (room_id) DO UPDATE \
-         SET member_count = federated_rooms.member_count + 1, updated_at = NOW()",
-    )
-    .bind(&room_id)
-    .bind(&origin)
-    .bind(&room_name)
-    .execute(pool)
-    .await;
-
-    // Persist join event.
     let event_type = event.get("type").and_then(Value::as_str).unwrap_or("nexus.member.join").to_owned();
     let sender = event.get("sender").and_then(Value::as_str).unwrap_or("").to_owned();
     let ts = event.get("origin_server_ts").and_then(Value::as_i64).unwrap_or(0);
     let content = event.get("content").cloned().unwrap_or(json!({}));
     let sigs = event.get("signatures").cloned().unwrap_or(json!({}));
-    let _ = sqlx::query(
-        "INSERT INTO federated_events \
-         (event_id, room_id, event_type, sender, origin_server, origin_server_ts, content, signatures, txn_id) \
-         VALUES (?, ?, ?, ?, ?, ?, ?, ?, 'send_join') \
-         ON CONFLICT (event_id) DO NOTHING",
-    )
-    .bind(&event_id)
-    .bind(&room_id)
-    .bind(&event_type)
-    .bind(&sender)
-    .bind(&origin)
-    .bind(ts)
-    .bind(serde_json::to_string(&content).unwrap_or_default())
-    .bind(serde_json::to_string(&sigs).unwrap_or_default())
-    .execute(pool)
+    let joined: Result<bool, sqlx::Error> = async {
+        let mut tx = pool.begin().await?;
+        let new = federated_rooms::insert_remote_event(
+            &mut *tx, &event_id, &room_id, &event_type, &sender, &origin, ts, &content, &sigs, "send_join",
+        )
+        .await?;
+        // A retried send_join doesn't count the member twice.
+        if new {
+            federated_rooms::record_remote_join(&mut *tx, &room_id, &origin, &room_name).await?;
+        }
+        tx.commit().await?;
+        Ok(new)
+    }
     .await;
 
-    // Notify gateway of the member join.
-    let gw = nexus_common::gateway_event::GatewayEvent {
-        event_type: "FEDERATED_MEMBER_JOIN".to_owned(),
-        data: json!({ "room_id": room_id, "sender": sender, "origin": origin }),
-        server_id: None,
-        channel_id: None,
-        user_id: None,
-    };
-    let _ = state.gateway_tx.send(gw);
+    match joined {
+        Ok(true) => {
+            // Notify gateway of the member join.
+            let gw = nexus_common::gateway_event::GatewayEvent {
+                event_type: "FEDERATED_MEMBER_JOIN".to_owned(),
+                data: json!({ "room_id": room_id, "sender": sender, "origin": origin }),
+                server_id: None,
+                channel_id: None,
+                user_id: None,
+                trace_context: None,
+            };
+            let _ = state.gateway_tx.send(gw);
+        }
+        Ok(false) => debug!("send_join {} from {} was already processed", event_id, origin),
+        Err(e) => {
+            warn!("Failed to store send_join {} from {}: {}", event_id, origin, e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": "failed to store join" })))
+                .into_response();
+        }
+    }
 
     // Return room state snapshot.
     let state_rows = sqlx::query(
@@ -579,6 +730,17 @@ async fn send_join(
     (StatusCode::OK, Json(json!({ "state": state_pdus, "auth_chain": [] }))).into_response()
 }
 
+/// A 403 for joins to one of our rooms whose channel has been defederated
+/// (see [`super::federated_rooms`]).
+async fn refuse_defederated(state: &AppState, room_id: &str) -> Option<Response> {
+    match federated_rooms::find_by_room_id(&state.db.pool, room_id).await {
+        Ok(Some(room)) if room.local_channel_id.is_some() && !room.is_federated() => Some(
+            (StatusCode::FORBIDDEN, Json(json!({ "error": "room is no longer federated" }))).into_response(),
+        ),
+        _ => None,
+    }
+}
+
 // ─── Backfill ─────────────────────────────────────────────────────────────────
 
 #[derive(Deserialize)]
@@ -655,20 +817,24 @@ async fn backfill(
 /// `PUT /_matrix/app/v1/transactions/{txnId}`
 ///
 /// Matrix homeserver pushes events to this Application Service.
-/// Validates the `access_token` query param (our `hs_token`), then
+/// Validates our `hs_token` (bearer header or `access_token` query param), then
 /// hands off to the bridge for processing and dispatches Nexus gateway events.
 async fn matrix_as_transaction(
     State(state): State<Arc<AppState>>,
     axum::extract::Query(params): axum::extract::Query<std::collections::HashMap<String, String>>,
+    headers: HeaderMap,
     Path(txn_id): Path<String>,
     Json(body): Json<Value>,
 ) -> impl IntoResponse {
-    // Validate homeserver token.
+    // Validate homeserver token (header, or the legacy query parameter).
     let expected_token =
         std::env::var("NEXUS_MATRIX_HS_TOKEN").unwrap_or_default();
-    let provided = params.get("access_token").map(String::as_str).unwrap_or("");
+    let authorization = headers.get(header::AUTHORIZATION).and_then(|v| v.to_str().ok());
+    let provided = params.get("access_token").map(String::as_str);
 
-    if !expected_token.is_empty() && provided != expected_token {
+    if !expected_token.is_empty()
+        && !nexus_federation::matrix_bridge::hs_token_matches(&expected_token, authorization, provided)
+    {
         return (StatusCode::FORBIDDEN, Json(json!({ "error": "Invalid homeserver token" }))).into_response();
     }
 
@@ -713,6 +879,7 @@ async fn matrix_as_transaction(
                         server_id: None,
                         channel_id: None,
                         user_id: None,
+                        trace_context: None,
                     };
                     let _ = state.gateway_tx.send(gw);
                 }
@@ -817,6 +984,8 @@ fn parse_mxid(mxid: &str) -> Option<(String, String)> {
 /// Called after accepting an inbound PDU to keep the remote profile cache
 /// up-to-date. For membership events the display name and avatar in the
 /// event content are used; for other event types only the MXID is stored.
+/// Either way the user is marked active, which keeps their profile on the
+/// refresh schedule (see [`crate::jobs::federated_profiles`]).
 async fn upsert_federated_user(
     pool: &sqlx::AnyPool,
     _local_server_name: &str,
@@ -882,11 +1051,12 @@ async fn upsert_federated_user(
 
     sqlx::query(
         "INSERT INTO federated_users \
-         (mxid, localpart, server_id, display_name, avatar_url) \
-         VALUES (?, ?, ?, ?, ?) \
+         (mxid, localpart, server_id, display_name, avatar_url, last_active_at) \
+         VALUES (?, ?, ?, ?, ?, CURRENT_TIMESTAMP) \
          ON CONFLICT (mxid) DO UPDATE SET \
-         display_name = COALESCE(excluded.display_name, federated_users.display_name), \
-         avatar_url   = COALESCE(excluded.avatar_url, federated_users.avatar_url)",
+         display_name   = COALESCE(excluded.display_name, federated_users.display_name), \
+         avatar_url     = COALESCE(excluded.avatar_url, federated_users.avatar_url), \
+         last_active_at = CURRENT_TIMESTAMP",
     )
     .bind(sender)
     .bind(&localpart)
@@ -3,6 +3,12 @@
 //! `GET /files/*key` — serves uploaded files from the local filesystem when
 //! running without S3/MinIO.  In full mode these are served directly from
 //! MinIO, so this route is a no-op (returns 404 for every request).
+//!
+//! Files are user content; [`security_headers`](crate::middleware::security_headers)
+//! sandboxes them with their own CSP.
+//!
+//! Data export archives are stored alongside but only handed out through
+//! their signed download links ([`super::exports`]), never here.
 
 use axum::{
     body::Body,
@@ -17,13 +23,16 @@ use std::sync::Arc;
 use crate::AppState;
 
 pub fn router() -> Router<Arc<AppState>> {
-    Router::new().route("/files/*key", get(serve_file))
+    Router::new().route("/files/{*key}", get(serve_file))
 }
 
 async fn serve_file(
     State(state): State<Arc<AppState>>,
     Path(key): Path<String>,
 ) -> Response {
+    if key.starts_with("exports/") {
+        return StatusCode::NOT_FOUND.into_response();
+    }
     match state.storage.read_local_file(&key).await {
         Ok(Some((bytes, content_type))) => {
             Response::builder()
@@ -0,0 +1,478 @@
+//! Forum routes — posts, tags and pinned posts in forum channels.
+//!
+//! POST   /channels/:id/posts                  — Start a post (thread + opening message)
+//! GET    /channels/:id/posts?sort=&tag_id=    — List posts, pinned first
+//! PUT    /channels/:id/posts/:post_id/pin     — Pin a post
+//! DELETE /channels/:id/posts/:post_id/pin     — Unpin a post
+//! GET    /channels/:id/tags                   — List tags
+//! POST   /channels/:id/tags                   — Create a tag
+//! PATCH  /channels/:id/tags/:tag_id           — Update a tag
+//! DELETE /channels/:id/tags/:tag_id           — Delete a tag
+//!
+//! Posts are threads under the forum channel, so the thread routes work on
+//! them too. Tag changes need MANAGE_CHANNELS, pinning needs MANAGE_THREADS.
+
+use axum::{
+    extract::{Extension, Path, Query, State},
+    middleware,
+    routing::{get, patch, put},
+    Json, Router,
+};
+use nexus_common::{
+    error::{NexusError, NexusResult},
+    gateway_event::{event_types, GatewayEvent},
+    models::{
+        channel::{Channel, ChannelType},
+        rich::{
+            CreateForumPostRequest, CreateForumTagRequest, ForumSortOrder, ForumTag, Thread,
+            UpdateForumTagRequest,
+        },
+    },
+    permissions::Permissions,
+    snowflake,
+    validation::validate_request,
+};
+use nexus_db::repository::{channels, forums, threads, users};
+use serde::Deserialize;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{middleware::AuthContext, routes::threads::thread_response, AppState};
+
+/// Most tags a single forum may define.
+const MAX_TAGS_PER_FORUM: usize = 20;
+
+pub fn router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route(
+            "/channels/{channel_id}/posts",
+            get(list_posts).post(create_post),
+        )
+        .route(
+            "/channels/{channel_id}/posts/{post_id}/pin",
+            put(pin_post).delete(unpin_post),
+        )
+        .route(
+            "/channels/{channel_id}/tags",
+            get(list_tags).post(create_tag),
+        )
+        .route(
+            "/channels/{channel_id}/tags/{tag_id}",
+            patch(update_tag).delete(delete_tag),
+        )
+        .route_layer(middleware::from_fn(crate::middleware::auth_middleware))
+}
+
+// ============================================================
+// Helpers
+// ============================================================
+
+/// Load a forum channel and check the caller has `required` in it.
+async fn forum_with_permission(
+    state: &AppState,
+    channel_id: Uuid,
+    user_id: Uuid,
+    required: Permissions,
+) -> NexusResult<(Channel, Permissions)> {
+    let channel = channels::find_by_id(&state.db.pool, channel_id)
+        .await?
+        .filter(|c| c.channel_type == ChannelType::Forum)
+        .ok_or(NexusError::NotFound {
+            resource: "Forum".into(),
+        })?;
+    let permissions = crate::permissions::in_channel(&state.db.pool, &channel, user_id)
+        .await?
+        .ok_or(NexusError::Forbidden)?;
+    crate::permissions::require(permissions, required)?;
+    Ok((channel, permissions))
+}
+
+/// Load a post and make sure it belongs to `forum_id`.
+async fn forum_post(state: &AppState, forum_id: Uuid, post_id: Uuid) -> NexusResult<Thread> {
+    threads::find_by_id(&state.db.pool, post_id)
+        .await?
+        .filter(|t| t.parent_channel_id == Some(forum_id))
+        .map(thread_response)
+        .ok_or(NexusError::NotFound {
+            resource: "Post".into(),
+        })
+}
+
+fn broadcast(
+    state: &AppState,
+    event_type: &str,
+    data: serde_json::Value,
+    channel: &Channel,
+    user_id: Uuid,
+) {
+    let _ = state.gateway_tx.send(GatewayEvent {
+        event_type: event_type.into(),
+        data,
+        server_id: channel.server_id,
+        channel_id: Some(channel.id),
+        user_id: Some(user_id),
+        trace_context: None,
+    });
+}
+
+// ============================================================
+// Posts
+// ============================================================
+
+/// POST /api/v1/channels/:channel_id/posts — Start a forum post.
+///
+/// Creates the post's thread and its opening message in one go.
+async fn create_post(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path(channel_id): Path<Uuid>,
+    Json(body): Json<CreateForumPostRequest>,
+) -> NexusResult<Json<serde_json::Value>> {
+    validate_request(&body)?;
+    let (forum, permissions) = forum_with_permission(
+        &state,
+        channel_id,
+        auth.user_id,
+        Permissions::VIEW_CHANNEL | Permissions::SEND_MESSAGES,
+    )
+    .await?;
+    super::channels::require_unarchived(&forum)?;
+
+    let auto_archive = body.auto_archive_minutes.unwrap_or(1440);
+    if ![60, 1440, 4320, 10080].contains(&auto_archive) {
+        return Err(NexusError::Validation {
+            message: "auto_archive_minutes must be 60, 1440, 4320, or 10080".into(),
+        });
+    }
+
+    // Every tag must belong to this forum; moderated ones need MANAGE_THREADS
+    let tag_ids = body.tag_ids.unwrap_or_default();
+    let forum_tags = forums::list_tags(&state.db.pool, channel_id).await?;
+    for tag_id in &tag_ids {
+        let tag =
+            forum_tags
+                .iter()
+                .find(|t| t.id == *tag_id)
+                .ok_or_else(|| NexusError::Validation {
+                    message: format!("Tag {tag_id} does not exist in this forum"),
+                })?;
+        if tag.moderated {
+            crate::permissions::require(permissions, Permissions::MANAGE_THREADS)?;
+        }
+    }
+    let tags: Vec<String> = tag_ids.iter().map(Uuid::to_string).collect();
+
+    let post_channel = channels::create_channel(
+        &state.db.pool,
+        snowflake::generate_id(),
+        forum.server_id,
+        Some(channel_id),
+        "thread",
+        Some(&body.title),
+        None,
+        0,
+    )
+    .await?;
+    let row = threads::create_thread(
+        &state.db.pool,
+        post_channel.id,
+        channel_id,
+        None,
+        auth.user_id,
+        &body.title,
+        auto_archive,
+        &tags,
+    )
+    .await?;
+    let _ = threads::add_member(&state.db.pool, post_channel.id, auth.user_id).await;
+
+    // The opening message lives inside the post's own channel
+    let mut tx = state.db.pool.begin().await?;
+    let msg = super::messages::insert_message(
+        &mut tx,
+        &post_channel,
+        auth.user_id,
+        snowflake::generate_id(),
+        &body.content,
+        (None, None),
+        0,
+        &[],
+        &[],
+        &[],
+    )
+    .await?;
+    tx.commit().await?;
+    let _ = threads::increment_message_count(&state.db.pool, post_channel.id).await;
+
+    let post = thread_response(row);
+    broadcast(
+        &state,
+        event_types::THREAD_CREATE,
+        serde_json::to_value(&post).unwrap_or_default(),
+        &forum,
+        auth.user_id,
+    );
+    let username = users::find_by_id(&state.db.pool, auth.user_id)
+        .await?
+        .map(|u| u.username)
+        .unwrap_or_default();
+    let message = super::messages::publish_message(&state, &post_channel, &msg, &username).await;
+
+    Ok(Json(serde_json::json!({
+        "post": post,
+        "message": message,
+    })))
+}
+
+#[derive(Debug, Deserialize)]
+struct ListPostsParams {
+    #[serde(default)]
+    sort: ForumSortOrder,
+    /// Only posts carrying this tag.
+    tag_id: Option<Uuid>,
+    /// Include archived posts.
+    #[serde(default)]
+    archived: bool,
+    limit: Option<i64>,
+}
+
+/// GET /api/v1/channels/:channel_id/posts — List posts, pinned first.
+async fn list_posts(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path(channel_id): Path<Uuid>,
+    Query(params): Query<ListPostsParams>,
+) -> NexusResult<Json<Vec<Thread>>> {
+    forum_with_permission(&state, channel_id, auth.user_id, Permissions::VIEW_CHANNEL).await?;
+
+    let limit = params.limit.unwrap_or(25).clamp(1, 100);
+    let rows = forums::list_posts(
+        &state.db.pool,
+        channel_id,
+        params.sort,
+        params.tag_id,
+        params.archived,
+        limit,
+    )
+    .await?;
+    Ok(Json(rows.into_iter().map(thread_response).collect()))
+}
+
+/// PUT /api/v1/channels/:channel_id/posts/:post_id/pin
+async fn pin_post(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path((channel_id, post_id)): Path<(Uuid, Uuid)>,
+) -> NexusResult<Json<Thread>> {
+    set_post_pinned(&state, auth.user_id, channel_id, post_id, true).await
+}
+
+/// DELETE /api/v1/channels/:channel_id/posts/:post_id/pin
+async fn unpin_post(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path((channel_id, post_id)): Path<(Uuid, Uuid)>,
+) -> NexusResult<Json<Thread>> {
+    set_post_pinned(&state, auth.user_id, channel_id, post_id, false).await
+}
+
+/// A forum has at most one pinned post; pinning another replaces it.
+async fn set_post_pinned(
+    state: &AppState,
+    user_id: Uuid,
+    channel_id: Uuid,
+    post_id: Uuid,
+    pinned: bool,
+) -> NexusResult<Json<Thread>> {
+    let (forum, _) =
+        forum_with_permission(state, channel_id, user_id, Permissions::MANAGE_THREADS).await?;
+    forum_post(state, channel_id, post_id).await?;
+
+    forums::set_pinned(&state.db.pool, channel_id, post_id, pinned).await?;
+    let post = forum_post(state, channel_id, post_id).await?;
+    broadcast(
+        state,
+        event_types::THREAD_UPDATE,
+        serde_json::to_value(&post).unwrap_or_default(),
+        &forum,
+        user_id,
+    );
+    Ok(Json(post))
+}
+
+// ============================================================
+// Tags
+// ============================================================
+
+/// GET /api/v1/channels/:channel_id/tags
+async fn list_tags(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path(channel_id): Path<Uuid>,
+) -> NexusResult<Json<Vec<ForumTag>>> {
+    forum_with_permission(&state, channel_id, auth.user_id, Permissions::VIEW_CHANNEL).await?;
+    Ok(Json(forums::list_tags(&state.db.pool, channel_id).await?))
+}
+
+/// POST /api/v1/channels/:channel_id/tags
+async fn create_tag(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path(channel_id): Path<Uuid>,
+    Json(body): Json<CreateForumTagRequest>,
+) -> NexusResult<Json<ForumTag>> {
+    validate_request(&body)?;
+    let (forum, _) = forum_with_permission(
+        &state,
+        channel_id,
+        auth.user_id,
+        Permissions::MANAGE_CHANNELS,
+    )
+    .await?;
+
+    let existing = forums::list_tags(&state.db.pool, channel_id).await?;
+    if existing.len() >= MAX_TAGS_PER_FORUM {
+        return Err(NexusError::LimitReached {
+            message: format!("A forum can have at most {MAX_TAGS_PER_FORUM} tags"),
+        });
+    }
+    if existing
+        .iter()
+        .any(|t| t.name.eq_ignore_ascii_case(&body.name))
+    {
+        return Err(NexusError::AlreadyExists {
+            resource: "Tag".into(),
+        });
+    }
+
+    let tag = forums::create_tag(
+        &state.db.pool,
+        snowflake::generate_id(),
+        channel_id,
+        &body.name,
+        body.emoji.as_deref(),
+        body.moderated.unwrap_or(false),
+        body.position.unwrap_or(existing.len() as i32),
+    )
+    .await?;
+    broadcast(
+        &state,
+        event_types::FORUM_TAG_CREATE,
+        serde_json::to_value(&tag).unwrap_or_default(),
+        &forum,
+        auth.user_id,
+    );
+    Ok(Json(tag))
+}
+
+/// PATCH /api/v1/channels/:channel_id/tags/:tag_id
+async fn update_tag(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path((channel_id, tag_id)): Path<(Uuid, Uuid)>,
+    Json(body): Json<UpdateForumTagRequest>,
+) -> NexusResult<Json<ForumTag>> {
+    validate_request(&body)?;
+    let (forum, _) = forum_with_permission(
+        &state,
+        channel_id,
+        auth.user_id,
+        Permissions::MANAGE_CHANNELS,
+    )
+    .await?;
+
+    let existing = forums::list_tags(&state.db.pool, channel_id).await?;
+    if !existing.iter().any(|t| t.id == tag_id) {
+        return Err(NexusError::NotFound {
+            resource: "Tag".into(),
+        });
+    }
+    if let Some(name) = &body.name
+        && existing
+            .iter()
+            .any(|t| t.id != tag_id && t.name.eq_ignore_ascii_case(name))
+    {
+        return Err(NexusError::AlreadyExists {
+            resource: "Tag".into(),
+        });
+    }
+
+    let tag = forums::update_tag(
+        &state.db.pool,
+        tag_id,
+        body.name.as_deref(),
+        body.emoji.as_deref(),
+        body.moderated,
+        body.position,
+    )
+    .await?
+    .ok_or(NexusError::NotFound {
+        resource: "Tag".into(),
+    })?;
+    broadcast(
+        &state,
+        event_types::FORUM_TAG_UPDATE,
+        serde_json::to_value(&tag).unwrap_or_default(),
+        &forum,
+        auth.user_id,
+    );
+    Ok(Json(tag))
+}
+
+/// DELETE /api/v1/channels/:channel_id/tags/:tag_id
+///
+/// The tag is also removed from every post that carried it.
+async fn delete_tag(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path((channel_id, tag_id)): Path<(Uuid, Uuid)>,
+) -> NexusResult<axum::http::StatusCode> {
+    let (forum, _) = forum_with_permission(
+        &state,
+        channel_id,
+        auth.user_id,
+        Permissions::MANAGE_CHANNELS,
+    )
+    .await?;
+    let existing = forums::list_tags(&state.db.pool, channel_id).await?;
+    if !existing.iter().any(|t| t.id == tag_id) {
+        return Err(NexusError::NotFound {
+            resource: "Tag".into(),
+        });
+    }
+
+    forums::delete_tag(&state.db.pool, tag_id).await?;
+
+    let tagged = forums::list_posts(
+        &state.db.pool,
+        channel_id,
+        ForumSortOrder::CreationDate,
+        Some(tag_id),
+        true,
+        i64::MAX,
+    )
+    .await?;
+    let tag = tag_id.to_string();
+    for post in tagged {
+        let remaining: Vec<String> = post.tags.iter().filter(|t| **t != tag).cloned().collect();
+        threads::update_thread(
+            &state.db.pool,
+            post.channel_id,
+            None,
+            None,
+            None,
+            None,
+            Some(&remaining),
+        )
+        .await?;
+    }
+
+    broadcast(
+        &state,
+        event_types::FORUM_TAG_DELETE,
+        serde_json::json!({ "id": tag_id, "channel_id": channel_id }),
+        &forum,
+        auth.user_id,
+    );
+    Ok(axum::http::StatusCode::NO_CONTENT)
+}
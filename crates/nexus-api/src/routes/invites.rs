@@ -0,0 +1,372 @@
+//! Invite routes — create, list, revoke and redeem invite links.
+//!
+//! POST   /servers/:id/invites    — Invite to the server (CREATE_INVITES)
+//! POST   /channels/:id/invites   — Invite landing in a channel (CREATE_INVITES there)
+//! GET    /servers/:id/invites    — Every invite, used up or not (MANAGE_SERVER)
+//! GET    /invites/:code          — Invite info
+//! GET    /invites/:code/preview  — Server preview for invite landing pages (no auth)
+//! DELETE /invites/:code          — Revoke (the inviter, or MANAGE_SERVER)
+//! POST   /invites/:code/join     — Join the server
+//!
+//! Codes avoid look-alike characters and never collide with a server's
+//! vanity code. Creating and revoking an invite dispatch `INVITE_CREATE` /
+//! `INVITE_DELETE` to the server.
+
+use axum::{
+    extract::{Extension, Path, State},
+    http::StatusCode,
+    middleware,
+    routing::{get, post},
+    Json, Router,
+};
+use nexus_common::{
+    error::{NexusError, NexusResult},
+    gateway_event::GatewayEvent,
+    models::{
+        channel::{Channel, ChannelType},
+        server::{CreateInviteRequest, Invite, Server},
+    },
+    permissions::Permissions,
+    validation::validate_request,
+};
+use nexus_db::repository::{bans, channels, members, roles, servers};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{middleware::AuthContext, AppState};
+
+pub fn router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route(
+            "/servers/{server_id}/invites",
+            get(list_invites).post(create_server_invite),
+        )
+        .route("/channels/{channel_id}/invites", post(create_channel_invite))
+        .route("/invites/{code}", get(get_invite).delete(delete_invite))
+        .route("/invites/{code}/join", post(join_via_invite))
+        .route_layer(middleware::from_fn(crate::middleware::auth_middleware))
+        .route("/invites/{code}/preview", get(get_invite_preview))
+}
+
+/// Length of a generated invite code.
+const CODE_LENGTH: usize = 8;
+
+/// Lowercase letters and digits, minus the ones read alike (`0`/`o`,
+/// `1`/`l`/`i`).
+const CODE_ALPHABET: &[u8] = b"23456789abcdefghjkmnpqrstuvwxyz";
+
+/// Give up finding a free code after this many collisions.
+const CODE_ATTEMPTS: usize = 5;
+
+/// Channels listed in an invite preview.
+const PREVIEW_CHANNELS: usize = 5;
+
+fn generate_invite_code() -> String {
+    use rand::Rng;
+    let mut rng = rand::rng();
+    (0..CODE_LENGTH)
+        .map(|_| CODE_ALPHABET[rng.random_range(0..CODE_ALPHABET.len())] as char)
+        .collect()
+}
+
+/// A fresh code that is neither an invite nor a vanity code yet.
+async fn unused_invite_code(state: &AppState) -> NexusResult<String> {
+    for _ in 0..CODE_ATTEMPTS {
+        let code = generate_invite_code();
+        if !servers::invite_code_taken(&state.db.pool, &code).await? {
+            return Ok(code);
+        }
+    }
+    Err(NexusError::Internal(anyhow::anyhow!("No free invite code after {CODE_ATTEMPTS} attempts")))
+}
+
+/// Whether an invite has neither expired nor run out of uses.
+fn invite_usable(invite: &Invite) -> bool {
+    invite.expires_at.is_none_or(|exp| exp >= chrono::Utc::now())
+        && invite.max_uses.is_none_or(|max| invite.uses < max)
+}
+
+fn invite_not_found() -> NexusError {
+    NexusError::NotFound {
+        resource: "Invite".into(),
+    }
+}
+
+async fn find_server(state: &AppState, server_id: Uuid) -> NexusResult<Server> {
+    servers::find_by_id(&state.db.pool, server_id)
+        .await?
+        .ok_or(NexusError::NotFound {
+            resource: "Server".into(),
+        })
+}
+
+/// POST /api/v1/servers/:server_id/invites
+async fn create_server_invite(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path(server_id): Path<Uuid>,
+    Json(body): Json<CreateInviteRequest>,
+) -> NexusResult<Json<Invite>> {
+    validate_request(&body)?;
+    let server = find_server(&state, server_id).await?;
+    let permissions = crate::permissions::resolve(&state.db.pool, &server, None, auth.user_id)
+        .await?
+        .ok_or(NexusError::Forbidden)?;
+    crate::permissions::require(permissions, Permissions::CREATE_INVITES)?;
+
+    create_invite(&state, server.id, None, auth.user_id, &body).await.map(Json)
+}
+
+/// POST /api/v1/channels/:channel_id/invites — An invite that lands the new
+/// member in this channel.
+async fn create_channel_invite(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path(channel_id): Path<Uuid>,
+    Json(body): Json<CreateInviteRequest>,
+) -> NexusResult<Json<Invite>> {
+    validate_request(&body)?;
+    let channel: Channel = channels::find_by_id(&state.db.pool, channel_id)
+        .await?
+        .ok_or(NexusError::NotFound {
+            resource: "Channel".into(),
+        })?;
+    // DMs have no server to invite to
+    let Some(server_id) = channel.server_id else {
+        return Err(NexusError::Validation {
+            message: "Invites can only be created for server channels".into(),
+        });
+    };
+    let permissions = crate::permissions::in_channel(&state.db.pool, &channel, auth.user_id)
+        .await?
+        .ok_or(NexusError::Forbidden)?;
+    crate::permissions::require(permissions, Permissions::VIEW_CHANNEL | Permissions::CREATE_INVITES)?;
+
+    create_invite(&state, server_id, Some(channel.id), auth.user_id, &body)
+        .await
+        .map(Json)
+}
+
+async fn create_invite(
+    state: &AppState,
+    server_id: Uuid,
+    channel_id: Option<Uuid>,
+    inviter_id: Uuid,
+    body: &CreateInviteRequest,
+) -> NexusResult<Invite> {
+    let code = unused_invite_code(state).await?;
+    let expires_at = body
+        .max_age_secs
+        .filter(|&s| s > 0)
+        .map(|s| chrono::Utc::now() + chrono::Duration::seconds(s as i64));
+    let max_uses = body.max_uses.filter(|&u| u > 0);
+
+    let invite = servers::create_invite(
+        &state.db.pool,
+        &code,
+        server_id,
+        channel_id,
+        inviter_id,
+        max_uses,
+        expires_at,
+    )
+    .await?;
+
+    broadcast_invite_event(state, &invite, "INVITE_CREATE", serde_json::to_value(&invite).unwrap_or_default());
+    Ok(invite)
+}
+
+/// GET /api/v1/servers/:server_id/invites
+async fn list_invites(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path(server_id): Path<Uuid>,
+) -> NexusResult<Json<Vec<Invite>>> {
+    let server = find_server(&state, server_id).await?;
+    let permissions = crate::permissions::resolve(&state.db.pool, &server, None, auth.user_id)
+        .await?
+        .ok_or(NexusError::Forbidden)?;
+    crate::permissions::require(permissions, Permissions::MANAGE_SERVER)?;
+
+    Ok(Json(servers::list_invites(&state.db.pool, server_id).await?))
+}
+
+/// GET /api/v1/invites/:code
+async fn get_invite(
+    State(state): State<Arc<AppState>>,
+    Path(code): Path<String>,
+) -> NexusResult<Json<serde_json::Value>> {
+    let invite = servers::find_invite(&state.db.pool, &code)
+        .await?
+        .ok_or_else(invite_not_found)?;
+
+    if !invite_usable(&invite) {
+        return Err(invite_not_found());
+    }
+
+    let server = find_server(&state, invite.server_id).await?;
+
+    Ok(Json(serde_json::json!({
+        "code": invite.code,
+        "server": { "id": server.id, "name": server.name, "member_count": server.member_count },
+        "channel_id": invite.channel_id,
+        "uses": invite.uses,
+        "max_uses": invite.max_uses,
+        "expires_at": invite.expires_at,
+    })))
+}
+
+/// GET /api/v1/invites/:code/preview — What an invite landing page shows
+/// before anyone signs in: the server's name, description, icon and banner,
+/// its approximate member and online counts, and a few channels.
+///
+/// Only channels everyone in the server can see are listed, the invite's
+/// own channel first.
+async fn get_invite_preview(
+    State(state): State<Arc<AppState>>,
+    Path(code): Path<String>,
+) -> NexusResult<Json<serde_json::Value>> {
+    let invite = servers::find_invite(&state.db.pool, &code)
+        .await?
+        .filter(invite_usable)
+        .ok_or_else(invite_not_found)?;
+    let server = find_server(&state, invite.server_id).await?;
+
+    let server_roles = roles::list_server_roles(&state.db.pool, server.id).await?;
+    let mut featured: Vec<Channel> = channels::list_server_channels(&state.db.pool, server.id)
+        .await?
+        .into_iter()
+        .filter(|c| {
+            matches!(
+                c.channel_type,
+                ChannelType::Text | ChannelType::Announcement | ChannelType::Forum
+            )
+        })
+        .filter(|c| {
+            crate::permissions::for_everyone(&server, Some(c), &server_roles)
+                .has(Permissions::VIEW_CHANNEL)
+        })
+        .collect();
+    // Stable, so the rest keep their channel-list order.
+    featured.sort_by_key(|c| Some(c.id) != invite.channel_id);
+    featured.truncate(PREVIEW_CHANNELS);
+
+    let online = members::count_online(&state.db.pool, server.id).await?;
+
+    Ok(Json(serde_json::json!({
+        "code": invite.code,
+        "server": {
+            "id": server.id,
+            "name": server.name,
+            "description": server.description,
+            "icon": server.icon,
+            "banner": server.banner,
+            "approximate_member_count": server.member_count,
+            "approximate_presence_count": online,
+        },
+        "channel_id": invite.channel_id,
+        "featured_channels": featured.iter().map(|c| serde_json::json!({
+            "id": c.id,
+            "name": c.name,
+            "channel_type": c.channel_type,
+            "topic": c.topic,
+        })).collect::<Vec<_>>(),
+        "expires_at": invite.expires_at,
+    })))
+}
+
+/// DELETE /api/v1/invites/:code — Revoke an invite. Allowed for whoever
+/// created it and for members with MANAGE_SERVER.
+async fn delete_invite(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path(code): Path<String>,
+) -> NexusResult<StatusCode> {
+    let invite = servers::find_invite(&state.db.pool, &code)
+        .await?
+        .ok_or_else(invite_not_found)?;
+
+    if invite.inviter_id != auth.user_id {
+        let server = find_server(&state, invite.server_id).await?;
+        let permissions = crate::permissions::resolve(&state.db.pool, &server, None, auth.user_id)
+            .await?
+            .ok_or(NexusError::Forbidden)?;
+        crate::permissions::require(permissions, Permissions::MANAGE_SERVER)?;
+    }
+
+    if !servers::delete_invite(&state.db.pool, &code).await? {
+        return Err(invite_not_found());
+    }
+    broadcast_invite_event(
+        &state,
+        &invite,
+        "INVITE_DELETE",
+        serde_json::json!({
+            "code": invite.code,
+            "server_id": invite.server_id,
+            "channel_id": invite.channel_id,
+        }),
+    );
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// POST /api/v1/invites/:code/join
+///
+/// Members already in the server get the server back without using the
+/// invite up.
+async fn join_via_invite(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path(code): Path<String>,
+) -> NexusResult<Json<serde_json::Value>> {
+    let invite = servers::find_invite(&state.db.pool, &code)
+        .await?
+        .ok_or_else(invite_not_found)?;
+
+    if invite.expires_at.is_some_and(|exp| exp < chrono::Utc::now()) {
+        return Err(NexusError::Validation { message: "Invite has expired.".into() });
+    }
+
+    let server_id = invite.server_id;
+
+    if members::is_member(&state.db.pool, auth.user_id, server_id).await? {
+        let server = find_server(&state, server_id).await?;
+        return Ok(Json(serde_json::json!({
+            "server": { "id": server.id, "name": server.name },
+            "channel_id": invite.channel_id,
+        })));
+    }
+
+    if bans::find_ban(&state.db.pool, server_id, auth.user_id).await?.is_some() {
+        return Err(NexusError::Forbidden);
+    }
+
+    // Taking the use is what enforces max_uses and expiry against other joins.
+    // It is only spent if the membership is created with it.
+    let mut tx = state.db.pool.begin().await?;
+    if !servers::use_invite(&mut *tx, &code).await? {
+        return Err(NexusError::Validation { message: "Invite has reached its maximum uses.".into() });
+    }
+    members::add_member(&mut *tx, auth.user_id, server_id).await?;
+    servers::increment_member_count(&mut *tx, server_id).await?;
+    tx.commit().await?;
+
+    let server = find_server(&state, server_id).await?;
+    super::welcome::on_member_join(&state, &server, auth.user_id, &auth.username).await;
+
+    Ok(Json(serde_json::json!({
+        "server": { "id": server.id, "name": server.name },
+        "channel_id": invite.channel_id,
+    })))
+}
+
+fn broadcast_invite_event(state: &AppState, invite: &Invite, event_type: &str, data: serde_json::Value) {
+    let _ = state.gateway_tx.send(GatewayEvent {
+        event_type: event_type.to_string(),
+        data,
+        server_id: Some(invite.server_id),
+        channel_id: invite.channel_id,
+        user_id: None,
+        trace_context: None,
+    });
+}
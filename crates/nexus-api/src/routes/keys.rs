@@ -7,11 +7,26 @@
 //! POST   /devices/:device_id/signed-pre-key — Rotate signed pre-key
 //! POST   /devices/:device_id/one-time-pre-keys — Upload more OTPks
 //! GET    /devices/:device_id/one-time-pre-keys/count — Remaining OTPk count
+//! GET    /e2ee/devices/:device_id/otk-count  — Same, for clients polling for replenishment
 //! GET    /users/:user_id/key-bundle         — Fetch key bundles for all devices (X3DH initiator)
 //! GET    /users/:user_id/devices/:device_id/key-bundle — Fetch bundle for one device
+//!
+//! Fetching a bundle consumes one of the device's one-time pre-keys. When that
+//! leaves fewer than [`OTPK_LOW_WATERMARK`], the owner is sent
+//! `DEVICE_OTK_LOW` so one of their clients uploads more.
+//!
+//! Key backup (session keys encrypted to a recovery key the server never sees):
+//! POST   /keys/backup                       — Create a new backup version
+//! GET    /keys/backup                       — Current (newest) backup version
+//! GET    /keys/backup/:version              — Get a backup version
+//! DELETE /keys/backup/:version              — Delete a backup version and its keys
+//! PUT    /keys/backup/:version/sessions     — Upload session keys
+//! GET    /keys/backup/:version/sessions     — Fetch session keys (?channel_id=&session_id=)
+//! DELETE /keys/backup/:version/sessions     — Delete session keys (?channel_id=&session_id=)
 
 use axum::{
-    extract::{Extension, Path, State},
+    extract::{Extension, Path, Query, State},
+    http::StatusCode,
     middleware,
     routing::{get, post},
     Json, Router,
@@ -20,16 +35,27 @@ use nexus_common::{
     crypto::{validate_identity_key, validate_signature, validate_x25519_key},
     error::{NexusError, NexusResult},
     models::crypto::{
-        Device, KeyBundle, OtpkCountResponse, RegisterDeviceRequest, RotateSignedPreKeyRequest,
-        UploadOtpkRequest,
+        CreateKeyBackupRequest, Device, KeyBackupEtagResponse, KeyBackupSession, KeyBackupVersion,
+        KeyBundle, OtpkCountResponse, RegisterDeviceRequest, RotateSignedPreKeyRequest,
+        UploadKeyBackupRequest, UploadOtpkRequest, KEY_BACKUP_ALGORITHM, OTPK_LOW_WATERMARK,
     },
+    gateway_event::{event_types, GatewayEvent},
 };
 use nexus_db::repository::keystore;
+use serde::Deserialize;
+use std::collections::HashMap;
 use std::sync::Arc;
 use uuid::Uuid;
 
 use crate::{middleware::AuthContext, AppState};
 
+/// Most sessions in one backup upload.
+const MAX_BACKUP_SESSIONS_PER_UPLOAD: usize = 1000;
+/// Largest serialized `auth_data` / `session_data`, in bytes.
+const MAX_BACKUP_AUTH_DATA_BYTES: usize = 8 * 1024;
+const MAX_BACKUP_SESSION_DATA_BYTES: usize = 16 * 1024;
+const MAX_BACKUP_SESSION_ID_CHARS: usize = 128;
+
 pub fn router() -> Router<Arc<AppState>> {
     Router::new()
         // Own device management
@@ -50,12 +76,25 @@ pub fn router() -> Router<Arc<AppState>> {
             "/devices/{device_id}/one-time-pre-keys/count",
             get(count_one_time_pre_keys),
         )
+        .route("/e2ee/devices/{device_id}/otk-count", get(count_one_time_pre_keys))
         // Key bundles (for X3DH initiators)
         .route("/users/{user_id}/key-bundle", get(get_all_key_bundles))
         .route(
             "/users/{user_id}/devices/{device_id}/key-bundle",
             get(get_device_key_bundle),
         )
+        // Key backup
+        .route("/keys/backup", post(create_key_backup).get(get_current_key_backup))
+        .route(
+            "/keys/backup/{version}",
+            get(get_key_backup).delete(delete_key_backup),
+        )
+        .route(
+            "/keys/backup/{version}/sessions",
+            get(get_backup_sessions)
+                .put(upload_backup_sessions)
+                .delete(delete_backup_sessions),
+        )
         .route_layer(middleware::from_fn(crate::middleware::auth_middleware))
 }
 
@@ -257,11 +296,12 @@ async fn upload_one_time_pre_keys(
         .await
         .map_err(|e| NexusError::Internal(e))?;
 
-    Ok(Json(OtpkCountResponse { device_id, remaining }))
+    Ok(Json(OtpkCountResponse::new(device_id, remaining)))
 }
 
 // ============================================================
 // GET /devices/:device_id/one-time-pre-keys/count
+// GET /e2ee/devices/:device_id/otk-count
 // ============================================================
 
 async fn count_one_time_pre_keys(
@@ -284,7 +324,7 @@ async fn count_one_time_pre_keys(
         .await
         .map_err(|e| NexusError::Internal(e))?;
 
-    Ok(Json(OtpkCountResponse { device_id, remaining }))
+    Ok(Json(OtpkCountResponse::new(device_id, remaining)))
 }
 
 // ============================================================
@@ -299,6 +339,9 @@ async fn get_all_key_bundles(
     let bundles = keystore::get_all_key_bundles(&state.db.pool, user_id)
         .await
         .map_err(|e| NexusError::Internal(e))?;
+    for bundle in &bundles {
+        notify_if_otpks_low(&state, bundle).await;
+    }
     Ok(Json(bundles))
 }
 
@@ -317,5 +360,239 @@ async fn get_device_key_bundle(
         .ok_or(NexusError::NotFound {
             resource: "Device".into(),
         })?;
+    notify_if_otpks_low(&state, &bundle).await;
     Ok(Json(bundle))
 }
+
+/// Send `DEVICE_OTK_LOW` to the owner of the device behind `bundle` if it is
+/// below [`OTPK_LOW_WATERMARK`] one-time pre-keys now that one was consumed.
+/// Repeats on every fetch until the device replenishes, so a client that
+/// missed the first one still hears about it.
+async fn notify_if_otpks_low(state: &AppState, bundle: &KeyBundle) {
+    let remaining = match keystore::count_one_time_pre_keys(&state.db.pool, bundle.device_id).await {
+        Ok(remaining) => remaining,
+        Err(e) => {
+            tracing::warn!(device_id = %bundle.device_id, error = %e, "Failed to count one-time pre-keys");
+            return;
+        }
+    };
+    if remaining >= OTPK_LOW_WATERMARK {
+        return;
+    }
+    let _ = state.gateway_tx.send(GatewayEvent {
+        event_type: event_types::DEVICE_OTK_LOW.into(),
+        data: serde_json::to_value(OtpkCountResponse::new(bundle.device_id, remaining)).unwrap_or_default(),
+        server_id: None,
+        channel_id: None,
+        user_id: Some(bundle.user_id),
+        trace_context: None,
+    });
+}
+
+// ============================================================
+// POST /keys/backup — Create a backup version
+// ============================================================
+
+async fn create_key_backup(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Json(body): Json<CreateKeyBackupRequest>,
+) -> NexusResult<Json<KeyBackupVersion>> {
+    if body.algorithm != KEY_BACKUP_ALGORITHM {
+        return Err(NexusError::Validation {
+            message: format!("Unsupported backup algorithm; expected {KEY_BACKUP_ALGORITHM}"),
+        });
+    }
+    if !body.auth_data.is_object() || body.auth_data.to_string().len() > MAX_BACKUP_AUTH_DATA_BYTES {
+        return Err(NexusError::Validation {
+            message: format!("auth_data must be a JSON object of at most {MAX_BACKUP_AUTH_DATA_BYTES} bytes"),
+        });
+    }
+    // The recovery key's public half; its private half stays with the user.
+    let public_key = body.auth_data.get("public_key").and_then(|k| k.as_str()).unwrap_or("");
+    validate_x25519_key(public_key, "public_key").map_err(|e| NexusError::Validation {
+        message: format!("auth_data.public_key: {e}"),
+    })?;
+
+    let backup =
+        keystore::create_key_backup_version(&state.db.pool, auth.user_id, &body.algorithm, &body.auth_data)
+            .await
+            .map_err(NexusError::Internal)?;
+    Ok(Json(backup))
+}
+
+// ============================================================
+// GET /keys/backup, GET /keys/backup/:version
+// ============================================================
+
+async fn get_current_key_backup(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+) -> NexusResult<Json<KeyBackupVersion>> {
+    Ok(Json(find_backup(&state, auth.user_id, None).await?))
+}
+
+async fn get_key_backup(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path(version): Path<i64>,
+) -> NexusResult<Json<KeyBackupVersion>> {
+    Ok(Json(find_backup(&state, auth.user_id, Some(version)).await?))
+}
+
+async fn find_backup(state: &AppState, user_id: Uuid, version: Option<i64>) -> NexusResult<KeyBackupVersion> {
+    keystore::get_key_backup_version(&state.db.pool, user_id, version)
+        .await
+        .map_err(NexusError::Internal)?
+        .ok_or(NexusError::NotFound {
+            resource: "Key backup".into(),
+        })
+}
+
+// ============================================================
+// DELETE /keys/backup/:version
+// ============================================================
+
+async fn delete_key_backup(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path(version): Path<i64>,
+) -> NexusResult<StatusCode> {
+    let deleted = keystore::delete_key_backup_version(&state.db.pool, auth.user_id, version)
+        .await
+        .map_err(NexusError::Internal)?;
+    if !deleted {
+        return Err(NexusError::NotFound {
+            resource: "Key backup".into(),
+        });
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// ============================================================
+// /keys/backup/:version/sessions
+// ============================================================
+
+#[derive(Deserialize)]
+struct BackupSessionsQuery {
+    channel_id: Option<Uuid>,
+    session_id: Option<String>,
+}
+
+/// PUT — Upload session keys. Only the current version accepts uploads, and
+/// a session already stored is only replaced by a more useful copy (see
+/// [`KeyBackupSession::should_replace`]).
+async fn upload_backup_sessions(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path(version): Path<i64>,
+    Json(body): Json<UploadKeyBackupRequest>,
+) -> NexusResult<Json<KeyBackupEtagResponse>> {
+    let current = find_backup(&state, auth.user_id, None).await?;
+    if current.version != version {
+        return Err(NexusError::Validation {
+            message: format!(
+                "Backup version {version} is not the current version ({})",
+                current.version
+            ),
+        });
+    }
+    if body.sessions.len() > MAX_BACKUP_SESSIONS_PER_UPLOAD {
+        return Err(NexusError::Validation {
+            message: format!("Cannot upload more than {MAX_BACKUP_SESSIONS_PER_UPLOAD} sessions at once"),
+        });
+    }
+    for session in &body.sessions {
+        if session.session_id.is_empty() || session.session_id.chars().count() > MAX_BACKUP_SESSION_ID_CHARS {
+            return Err(NexusError::Validation {
+                message: format!("session_id must be 1-{MAX_BACKUP_SESSION_ID_CHARS} characters"),
+            });
+        }
+        if session.first_message_index < 0 || session.forwarded_count < 0 {
+            return Err(NexusError::Validation {
+                message: "first_message_index and forwarded_count can't be negative".into(),
+            });
+        }
+        if !session.session_data.is_object()
+            || session.session_data.to_string().len() > MAX_BACKUP_SESSION_DATA_BYTES
+        {
+            return Err(NexusError::Validation {
+                message: format!(
+                    "session_data must be a JSON object of at most {MAX_BACKUP_SESSION_DATA_BYTES} bytes"
+                ),
+            });
+        }
+    }
+
+    let existing: HashMap<(Uuid, String), KeyBackupSession> =
+        keystore::list_key_backup_sessions(&state.db.pool, auth.user_id, version, None, None)
+            .await
+            .map_err(NexusError::Internal)?
+            .into_iter()
+            .map(|s| ((s.channel_id, s.session_id.clone()), s))
+            .collect();
+    let mut changed: HashMap<(Uuid, String), KeyBackupSession> = HashMap::new();
+    for session in body.sessions {
+        let key = (session.channel_id, session.session_id.clone());
+        let best = changed.get(&key).or_else(|| existing.get(&key));
+        if best.is_none_or(|b| session.should_replace(b)) {
+            changed.insert(key, session);
+        }
+    }
+
+    if !changed.is_empty() {
+        let sessions: Vec<KeyBackupSession> = changed.into_values().collect();
+        keystore::upsert_key_backup_sessions(&state.db.pool, auth.user_id, version, &sessions)
+            .await
+            .map_err(NexusError::Internal)?;
+    }
+    let backup = find_backup(&state, auth.user_id, Some(version)).await?;
+    Ok(Json(KeyBackupEtagResponse {
+        etag: backup.etag,
+        count: backup.count,
+    }))
+}
+
+/// GET — Fetch session keys, optionally for one channel or session.
+async fn get_backup_sessions(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path(version): Path<i64>,
+    Query(params): Query<BackupSessionsQuery>,
+) -> NexusResult<Json<Vec<KeyBackupSession>>> {
+    find_backup(&state, auth.user_id, Some(version)).await?;
+    let sessions = keystore::list_key_backup_sessions(
+        &state.db.pool,
+        auth.user_id,
+        version,
+        params.channel_id,
+        params.session_id.as_deref(),
+    )
+    .await
+    .map_err(NexusError::Internal)?;
+    Ok(Json(sessions))
+}
+
+/// DELETE — Remove session keys, optionally for one channel or session.
+async fn delete_backup_sessions(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path(version): Path<i64>,
+    Query(params): Query<BackupSessionsQuery>,
+) -> NexusResult<Json<KeyBackupEtagResponse>> {
+    find_backup(&state, auth.user_id, Some(version)).await?;
+    keystore::delete_key_backup_sessions(
+        &state.db.pool,
+        auth.user_id,
+        version,
+        params.channel_id,
+        params.session_id.as_deref(),
+    )
+    .await
+    .map_err(NexusError::Internal)?;
+    let backup = find_backup(&state, auth.user_id, Some(version)).await?;
+    Ok(Json(KeyBackupEtagResponse {
+        etag: backup.etag,
+        count: backup.count,
+    }))
+}
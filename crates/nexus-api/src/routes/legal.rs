@@ -0,0 +1,125 @@
+//! Terms of service and privacy policy routes.
+//!
+//! GET  /legal/terms    — The terms of service (no auth)
+//! GET  /legal/privacy  — The privacy policy (no auth)
+//! GET  /legal/consent  — Whether I have consented to the current version
+//! POST /legal/consent  — Consent to the current version
+//!
+//! Documents are read from the files in `legal.terms_path` and
+//! `legal.privacy_path` on every request, so fixing a typo doesn't need a
+//! restart; publishing a new version is done by changing `legal.version`.
+//! Until a user consents to it, the rest of the API answers them with 451
+//! (see [`crate::legal`]).
+
+use axum::{
+    extract::{Extension, State},
+    middleware,
+    routing::get,
+    Json, Router,
+};
+use nexus_common::{
+    error::{NexusError, NexusResult},
+    models::legal::{ConsentRequest, ConsentStatus, LegalDocument},
+};
+use nexus_db::repository::legal_consents;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{middleware::AuthContext, AppState};
+
+pub fn router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/legal/terms", get(get_terms))
+        .route("/legal/privacy", get(get_privacy))
+        .route(
+            "/legal/consent",
+            get(get_consent)
+                .post(give_consent)
+                .route_layer(middleware::from_fn(crate::middleware::auth_middleware)),
+        )
+}
+
+/// GET /api/v1/legal/terms
+async fn get_terms() -> NexusResult<Json<LegalDocument>> {
+    let legal = &nexus_common::config::get().legal;
+    read_document(&legal.terms_path, "Terms of service").await.map(Json)
+}
+
+/// GET /api/v1/legal/privacy
+async fn get_privacy() -> NexusResult<Json<LegalDocument>> {
+    let legal = &nexus_common::config::get().legal;
+    read_document(&legal.privacy_path, "Privacy policy").await.map(Json)
+}
+
+/// The document at `path`, stamped with the current version. Not found when
+/// the instance doesn't publish one.
+async fn read_document(path: &str, resource: &str) -> NexusResult<LegalDocument> {
+    let not_found = || NexusError::NotFound {
+        resource: resource.into(),
+    };
+    if path.is_empty() {
+        return Err(not_found());
+    }
+    let content = tokio::fs::read_to_string(path).await.map_err(|e| {
+        tracing::warn!(path, error = %e, "Failed to read legal document");
+        not_found()
+    })?;
+    Ok(LegalDocument {
+        version: nexus_common::config::get().legal.version.clone(),
+        content,
+    })
+}
+
+/// GET /api/v1/legal/consent
+async fn get_consent(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+) -> NexusResult<Json<ConsentStatus>> {
+    consent_status(&state, auth.user_id).await.map(Json)
+}
+
+/// POST /api/v1/legal/consent — Consent to the version the client showed.
+///
+/// A stale version is refused, so a user who had the old terms open when a
+/// new version was published is shown the new one first.
+async fn give_consent(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Json(body): Json<ConsentRequest>,
+) -> NexusResult<Json<ConsentStatus>> {
+    let Some(version) = nexus_common::config::get().legal.required_version() else {
+        return Err(NexusError::Validation {
+            message: "This instance does not require consent".into(),
+        });
+    };
+    if body.version != version {
+        return Err(NexusError::Validation {
+            message: format!("The current version of the terms is {version}"),
+        });
+    }
+
+    let ip = auth.ip.map(|ip| ip.to_string());
+    legal_consents::record(&state.db.pool, auth.user_id, version, ip.as_deref()).await?;
+    state.consents.insert(auth.user_id);
+    tracing::info!(user_id = %auth.user_id, version, "User consented to the terms of service");
+
+    consent_status(&state, auth.user_id).await.map(Json)
+}
+
+async fn consent_status(state: &AppState, user_id: Uuid) -> NexusResult<ConsentStatus> {
+    let required = nexus_common::config::get().legal.required_version();
+    let latest = legal_consents::latest(&state.db.pool, user_id).await?;
+    let up_to_date = match required {
+        None => true,
+        Some(version) => {
+            state.consents.contains(user_id)
+                || legal_consents::has_consented(&state.db.pool, user_id, version).await?
+        }
+    };
+    Ok(ConsentStatus {
+        required_version: required.map(str::to_owned),
+        consented_version: latest.as_ref().map(|c| c.version.clone()),
+        consented_at: latest.map(|c| c.consented_at),
+        up_to_date,
+    })
+}
@@ -1,27 +1,55 @@
-//! Message routes — send, edit, delete, history, search, pins, reactions.
+//! Message routes — send, edit, delete, history, edit revisions, previews,
+//! moves, search, pins, reactions.
 //!
 //! This is the core of chat. Every message mutation emits a gateway event
 //! so connected WebSocket clients see changes in real-time.
 
 use axum::{
-    extract::{Extension, Path, Query, State},
+    extract::{DefaultBodyLimit, Extension, FromRequest, Multipart, Path, Query, Request, State},
+    http::{header::CONTENT_TYPE, HeaderMap},
     middleware,
     routing::{delete, get, post, put},
     Json, Router,
 };
 use nexus_common::{
     error::{NexusError, NexusResult},
-    models::message::{CreateMessageRequest, UpdateMessageRequest},
+    models::{
+        channel::{Channel, ChannelType},
+        member::Member,
+        message::{CreateMessageRequest, MessageFlags, UpdateMessageRequest, SPOILER_FILENAME_PREFIX},
+    },
+    permissions::Permissions,
     snowflake,
     validation::validate_request,
 };
-use nexus_db::repository::{channels, members, messages, reactions, read_states};
-use nexus_common::gateway_event::GatewayEvent;
+use nexus_common::models::rich::AttachmentRow;
+use nexus_db::repository::{
+    attachments, audit_log, channels, media_jobs, members, messages, moderation_queue, reactions,
+    read_states, servers,
+};
+use nexus_common::gateway_event::{event_types, GatewayEvent};
 use serde::Deserialize;
 use std::sync::Arc;
 use uuid::Uuid;
 
-use crate::{middleware::AuthContext, AppState};
+use crate::{
+    audit::{self, actions, Target},
+    middleware::AuthContext,
+    routes::channels::require_unarchived,
+    AppState,
+};
+
+/// Longest content snippet in a message link preview, in characters.
+const PREVIEW_SNIPPET_CHARS: usize = 300;
+/// Most attachment thumbnails in a message link preview.
+const PREVIEW_MAX_THUMBNAILS: usize = 4;
+
+/// Most messages one move can carry, counting the replies brought along.
+const MAX_MOVE_MESSAGES: usize = 100;
+
+/// Needed to see a server channel's messages, pins and reactions.
+const READ_HISTORY: Permissions =
+    Permissions::VIEW_CHANNEL.union(Permissions::READ_MESSAGE_HISTORY);
 
 /// Message routes.
 pub fn router() -> Router<Arc<AppState>> {
@@ -29,7 +57,9 @@ pub fn router() -> Router<Arc<AppState>> {
         // Message CRUD
         .route(
             "/channels/{channel_id}/messages",
-            get(get_messages).post(send_message),
+            get(get_messages).post(send_message).layer(DefaultBodyLimit::max(
+                super::uploads::upload_body_limit(nexus_common::config::get().limits.max_attachment_count),
+            )),
         )
         .route(
             "/channels/{channel_id}/messages/{message_id}",
@@ -37,11 +67,27 @@ pub fn router() -> Router<Arc<AppState>> {
                 .patch(edit_message)
                 .delete(delete_message),
         )
+        .route(
+            "/channels/{channel_id}/messages/{message_id}/preview",
+            get(get_message_preview),
+        )
+        .route(
+            "/channels/{channel_id}/messages/{message_id}/revisions",
+            get(get_message_revisions),
+        )
+        .route(
+            "/channels/{channel_id}/messages/{message_id}/move",
+            post(move_message),
+        )
         // Bulk delete
         .route(
             "/channels/{channel_id}/messages/bulk-delete",
             post(bulk_delete_messages),
         )
+        .route(
+            "/channels/{channel_id}/messages/deleted",
+            get(get_deleted_messages),
+        )
         // Pins
         .route(
             "/channels/{channel_id}/pins",
@@ -69,6 +115,8 @@ pub fn router() -> Router<Arc<AppState>> {
             "/channels/{channel_id}/ack/{message_id}",
             post(ack_message),
         )
+        .route("/read-states/ack-bulk", post(ack_bulk))
+        .route("/users/@me/unreads", get(get_unreads))
         // Search
         .route("/channels/{channel_id}/search", get(search_messages))
         // All routes require authentication
@@ -98,18 +146,194 @@ struct BulkDeleteBody {
     messages: Vec<Uuid>,
 }
 
+#[derive(Debug, Deserialize)]
+struct DeletedMessagesParams {
+    limit: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MoveMessageBody {
+    /// Channel to repost into; must be in the same server.
+    channel_id: Uuid,
+    /// Also move every reply chain hanging off the message.
+    #[serde(default)]
+    include_replies: bool,
+    /// Leave the originals in place and link the reposts back to them,
+    /// instead of deleting them.
+    #[serde(default)]
+    keep_original: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct BulkAckBody {
+    read_states: Vec<BulkAckEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BulkAckEntry {
+    channel_id: Uuid,
+    message_id: Uuid,
+}
+
 // ============================================================================
 // Message CRUD
 // ============================================================================
 
+/// A file sent inline with a multipart message create.
+pub(crate) struct PendingFile {
+    pub filename: String,
+    pub content_type: String,
+    pub data: Vec<u8>,
+    /// Marked by a `SPOILER_` filename prefix, as Discord clients do.
+    pub spoiler: bool,
+}
+
+/// Read a message create body: either plain JSON, or `multipart/form-data`
+/// with the JSON in a `payload_json` field and one field per file.
+async fn read_create_body(
+    state: &Arc<AppState>,
+    request: Request,
+) -> NexusResult<(CreateMessageRequest, Vec<PendingFile>)> {
+    let (payload, files) = read_payload_and_files(state, request).await?;
+    // A files-only message has no payload_json at all.
+    let body = payload.unwrap_or(CreateMessageRequest {
+        content: String::new(),
+        reference: None,
+        attachment_ids: None,
+        suppress_embeds: None,
+        silent: None,
+        flags: None,
+        spoiler_attachment_ids: None,
+        encrypted_content: None,
+        encryption_metadata: None,
+    });
+    Ok((body, files))
+}
+
+/// Read a JSON body, or a multipart one with the JSON in `payload_json` and
+/// any files checked against the upload limits. The payload is `None` only
+/// for multipart bodies without `payload_json`.
+pub(crate) async fn read_payload_and_files<T: serde::de::DeserializeOwned>(
+    state: &Arc<AppState>,
+    request: Request,
+) -> NexusResult<(Option<T>, Vec<PendingFile>)> {
+    let is_multipart = request
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|ct| ct.starts_with("multipart/form-data"));
+
+    let limits = &nexus_common::config::get().limits;
+    if !is_multipart {
+        // The route's body limit makes room for files; JSON gets the usual one.
+        let bytes = axum::body::to_bytes(request.into_body(), limits.max_request_body_bytes as usize)
+            .await
+            .map_err(|e| NexusError::Validation {
+                message: format!("Failed to read body: {e}"),
+            })?;
+        let Json(body) = Json::<T>::from_bytes(&bytes)
+            .map_err(|e| NexusError::Validation { message: e.body_text() })?;
+        return Ok((Some(body), Vec::new()));
+    }
+
+    let mut multipart = Multipart::from_request(request, state)
+        .await
+        .map_err(|e| NexusError::Validation { message: e.body_text() })?;
+    let mut payload: Option<T> = None;
+    let mut files = Vec::new();
+
+    while let Some(field) = multipart.next_field().await.map_err(|e| NexusError::Validation {
+        message: format!("Multipart error: {e}"),
+    })? {
+        if field.name() == Some("payload_json") {
+            let text = field.text().await.map_err(|e| NexusError::Validation {
+                message: format!("Failed to read payload_json: {e}"),
+            })?;
+            payload = Some(serde_json::from_str(&text).map_err(|e| NexusError::Validation {
+                message: format!("Invalid payload_json: {e}"),
+            })?);
+            continue;
+        }
+        // Any other field carrying a filename is a file (`file`, `files[0]`, …).
+        let Some(filename) = field.file_name().map(str::to_owned) else {
+            continue;
+        };
+        if files.len() >= limits.max_attachment_count as usize {
+            return Err(NexusError::Validation {
+                message: format!("At most {} attachments per message", limits.max_attachment_count),
+            });
+        }
+        let content_type = field
+            .content_type()
+            .unwrap_or("application/octet-stream")
+            .to_owned();
+        if !crate::file_types::is_allowed(&content_type) {
+            return Err(NexusError::Validation {
+                message: format!("File type '{content_type}' is not allowed"),
+            });
+        }
+        let data = field.bytes().await.map_err(|e| NexusError::Validation {
+            message: format!("Failed to read file: {e}"),
+        })?;
+        if data.len() as u64 > limits.max_file_size_bytes {
+            return Err(NexusError::Validation {
+                message: format!(
+                    "File too large: {} bytes (max {} bytes)",
+                    data.len(),
+                    limits.max_file_size_bytes
+                ),
+            });
+        }
+        crate::file_types::check(&content_type, &data).map_err(|message| NexusError::Validation { message })?;
+        files.push(PendingFile {
+            spoiler: filename.starts_with(SPOILER_FILENAME_PREFIX),
+            filename,
+            content_type,
+            data: data.to_vec(),
+        });
+    }
+
+    Ok((payload, files))
+}
+
+pub(crate) fn attachment_json(row: &AttachmentRow) -> serde_json::Value {
+    serde_json::json!({
+        "id": row.id,
+        "filename": row.filename,
+        "content_type": row.content_type,
+        "size": row.size,
+        "url": row.url,
+        "width": row.width,
+        "height": row.height,
+        "duration_secs": row.duration_secs,
+        "spoiler": row.spoiler,
+        "blurhash": row.blurhash,
+        "thumbnail_url": row.thumbnail_url,
+    })
+}
+
 /// POST /api/v1/channels/:channel_id/messages — Send a message.
+///
+/// Accepts JSON, or multipart/form-data carrying files plus a `payload_json`
+/// field; the message and its attachment rows are created atomically.
+/// In pre-moderated channels the message may instead be held for review,
+/// in which case the pending entry is returned with `"pending": true`.
 async fn send_message(
     Extension(auth): Extension<AuthContext>,
     State(state): State<Arc<AppState>>,
     Path(channel_id): Path<Uuid>,
-    Json(body): Json<CreateMessageRequest>,
+    request: Request,
 ) -> NexusResult<Json<serde_json::Value>> {
-    validate_request(&body)?;
+    let (mut body, files) = read_create_body(&state, request).await?;
+    let flags = body.take_flags();
+    let spoiler_ids = body.spoiler_attachment_ids.clone().unwrap_or_default();
+    let attachment_ids = body.attachment_ids.clone().unwrap_or_default();
+    let has_attachments = !files.is_empty() || !attachment_ids.is_empty();
+
+    // Content may only be empty when the message carries attachments.
+    if !(body.content.is_empty() && has_attachments) {
+        validate_request(&body)?;
+    }
 
     // Verify channel exists
     let channel = channels::find_by_id(&state.db.pool, channel_id)
@@ -117,72 +341,392 @@ async fn send_message(
         .ok_or(NexusError::NotFound {
             resource: "Channel".into(),
         })?;
-
-    // If this is a server channel, verify user is a member
-    if let Some(server_id) = channel.server_id {
-        if !members::is_member(&state.db.pool, auth.user_id, server_id).await? {
-            return Err(NexusError::Forbidden);
-        }
+    let member = require_can_post(&state, &channel, auth.user_id, has_attachments).await?;
+    if has_attachments {
+        super::e2ee::require_plaintext_channel(&state, channel_id).await?;
     }
 
-    // Determine message type: 0 = Default, 1 = Reply (if reference provided)
-    let message_type = if body.reference.is_some() { 1 } else { 0 };
+    // Spam heuristics — a hit drops the message, and times the author out
+    // unless it was only their address that tripped
+    if let Some(verdict) = state.spam.check(auth.user_id, auth.ip, channel_id, &body.content) {
+        handle_spam(&state, &auth, &channel, verdict).await;
+        return Err(NexusError::RateLimited {
+            retry_after_ms: state.spam.timeout().as_millis() as u64,
+        });
+    }
 
     let (ref_msg_id, ref_ch_id) = match &body.reference {
         Some(r) => (Some(r.message_id), Some(r.channel_id)),
         None => (None, None),
     };
 
+    let message_id = snowflake::generate_id();
+
+    // Storage isn't transactional: write the files first, and remove them
+    // again if the database side doesn't commit.
+    let mut stored = Vec::with_capacity(files.len());
+    for file in files {
+        let stored_file = super::uploads::store_message_file(
+            &state,
+            auth.user_id,
+            &file.filename,
+            &file.content_type,
+            file.data,
+        )
+        .await;
+        match stored_file {
+            Ok(f) => stored.push((f, file.spoiler)),
+            Err(e) => {
+                discard_stored_files(&state, &stored).await;
+                return Err(e);
+            }
+        }
+    }
+
+    // Pre-moderation — park the message for review instead of posting it
+    if let Some((member, permissions)) = &member {
+        let held = match hold_reason(&state, &channel, member, *permissions, &body.content).await {
+            Ok(Some(reason)) => {
+                let pending = moderation_queue::PendingMessageRow {
+                    id: message_id,
+                    channel_id,
+                    server_id: member.server_id,
+                    author_id: auth.user_id,
+                    content: body.content.clone(),
+                    reference_message_id: ref_msg_id,
+                    reference_channel_id: ref_ch_id,
+                    flags: flags.bits(),
+                    attachment_ids: attachment_ids.clone(),
+                    spoiler_attachment_ids: spoiler_ids.clone(),
+                    reason: reason.as_str().into(),
+                    created_at: chrono::Utc::now(),
+                };
+                hold_message(&state, &channel, pending, &stored).await.map(Some)
+            }
+            Ok(None) => Ok(None),
+            Err(e) => Err(e),
+        };
+        match held {
+            Ok(Some(pending)) => return Ok(Json(pending)),
+            Ok(None) => {}
+            Err(e) => {
+                discard_stored_files(&state, &stored).await;
+                return Err(e);
+            }
+        }
+    }
+
+    let created: NexusResult<messages::MessageRow> = async {
+        let mut tx = state.db.pool.begin().await?;
+        let msg = insert_message(
+            &mut tx,
+            &channel,
+            auth.user_id,
+            message_id,
+            &body.content,
+            (ref_msg_id, ref_ch_id),
+            flags.bits(),
+            &stored,
+            &attachment_ids,
+            &spoiler_ids,
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(msg)
+    }
+    .await;
+
+    let msg = match created {
+        Ok(msg) => msg,
+        Err(e) => {
+            discard_stored_files(&state, &stored).await;
+            return Err(e);
+        }
+    };
+
+    let response = publish_message(&state, &channel, &msg, &auth.username).await;
+
+    tracing::debug!(
+        message_id = %message_id,
+        channel_id = %channel_id,
+        author = %auth.username,
+        "Message sent"
+    );
+
+    Ok(Json(response))
+}
+
+/// Require that `user_id` may post in `channel`: it isn't archived and, in
+/// a server, they are a screened member who isn't timed out and has
+/// SEND_MESSAGES (and ATTACH_FILES for `has_attachments`) there.
+///
+/// Returns their membership and permissions in server channels.
+pub(crate) async fn require_can_post(
+    state: &AppState,
+    channel: &Channel,
+    user_id: Uuid,
+    has_attachments: bool,
+) -> NexusResult<Option<(Member, Permissions)>> {
+    require_unarchived(channel)?;
+    let Some(server_id) = channel.server_id else {
+        return Ok(None);
+    };
+
+    let member = members::find_member(&state.db.pool, user_id, server_id)
+        .await?
+        .ok_or(NexusError::Forbidden)?;
+    if member.pending {
+        return Err(NexusError::Validation {
+            message: "Accept the server rules before sending messages".into(),
+        });
+    }
+    if let Some(until) = member.communication_disabled_until {
+        let remaining = until - chrono::Utc::now();
+        if remaining > chrono::Duration::zero() {
+            return Err(NexusError::RateLimited {
+                retry_after_ms: remaining.num_milliseconds() as u64,
+            });
+        }
+    }
+    let server = servers::find_by_id(&state.db.pool, server_id)
+        .await?
+        .ok_or(NexusError::NotFound {
+            resource: "Server".into(),
+        })?;
+    // `resolve` applies the voice text chat participation gate
+    let permissions = crate::permissions::resolve(&state.db.pool, &server, Some(channel), user_id)
+        .await?
+        .ok_or(NexusError::Forbidden)?;
+    let mut required = Permissions::VIEW_CHANNEL | Permissions::SEND_MESSAGES;
+    if has_attachments {
+        required |= Permissions::ATTACH_FILES;
+    }
+    crate::permissions::require(permissions, required)?;
+    Ok(Some((member, permissions)))
+}
+
+/// Create a message, link its attachments and bump the mentioned users'
+/// mention counts inside `tx`.
+///
+/// `stored` are files already written for this message; `attachment_ids`
+/// are earlier uploads claimed for it. Mentions and the message type are
+/// derived from the content and reference.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn insert_message(
+    tx: &mut sqlx::Transaction<'_, sqlx::Any>,
+    channel: &Channel,
+    author_id: Uuid,
+    message_id: Uuid,
+    content: &str,
+    (ref_msg_id, ref_ch_id): (Option<Uuid>, Option<Uuid>),
+    flags: i32,
+    stored: &[(super::uploads::StoredFile, bool)],
+    attachment_ids: &[Uuid],
+    spoiler_ids: &[Uuid],
+) -> NexusResult<messages::MessageRow> {
+    // Determine message type: 0 = Default, 1 = Reply (if reference provided)
+    let message_type = if ref_msg_id.is_some() { 1 } else { 0 };
+
     // Parse mentions from content (basic @user_id pattern)
-    let mentions = parse_mentions(&body.content);
-    let mention_everyone = body.content.contains("@everyone");
+    let mentions = parse_mentions(content);
+    let mention_everyone = content.contains("@everyone");
 
-    let message_id = snowflake::generate_id();
     let msg = messages::create_message(
-        &state.db.pool,
+        &mut **tx,
         message_id,
-        channel_id,
-        auth.user_id,
-        &body.content,
+        channel.id,
+        author_id,
+        content,
         message_type,
         ref_msg_id,
         ref_ch_id,
         &mentions,
         &[],
         mention_everyone,
+        flags,
     )
     .await?;
 
-    // Increment mention counts for mentioned users
-    for mentioned_user_id in &mentions {
-        let _ = read_states::increment_mention_count(
-            &state.db.pool,
-            *mentioned_user_id,
-            channel_id,
+    // Silent messages don't notify; blocks and mutes are applied per recipient
+    if !MessageFlags::from_bits_truncate(flags).is_silent() {
+        for &mentioned_user_id in &msg.mentions {
+            read_states::increment_mention_count(&mut **tx, mentioned_user_id, channel.id, author_id).await?;
+        }
+    }
+
+    let mut linked = create_attachments(tx, channel, author_id, message_id, stored).await?;
+    for &attachment_id in attachment_ids {
+        let row = attachments::claim_for_message(
+            &mut **tx,
+            attachment_id,
+            author_id,
+            channel.id,
+            message_id,
+            spoiler_ids.contains(&attachment_id),
         )
-        .await;
+        .await?
+        .ok_or_else(|| NexusError::Validation {
+            message: format!("Attachment {attachment_id} does not exist or is already in use"),
+        })?;
+        linked.push(row);
     }
 
-    let mut response = message_row_to_json(&msg, &[]);
-    response["author_username"] = serde_json::Value::String(auth.username.clone());
+    let msg = if linked.is_empty() {
+        msg
+    } else {
+        let json = serde_json::Value::Array(linked.iter().map(attachment_json).collect());
+        messages::set_attachments(&mut **tx, message_id, &json).await?
+    };
+    Ok(msg)
+}
+
+/// Attachment rows for files stored for `message_id`, inside `tx`. Files
+/// that need it are queued for the media job.
+pub(crate) async fn create_attachments(
+    tx: &mut sqlx::Transaction<'_, sqlx::Any>,
+    channel: &Channel,
+    uploader_id: Uuid,
+    message_id: Uuid,
+    stored: &[(super::uploads::StoredFile, bool)],
+) -> NexusResult<Vec<AttachmentRow>> {
+    let mut rows = Vec::with_capacity(stored.len());
+    for (file, spoiler) in stored {
+        rows.push(
+            attachments::create_for_message(
+                &mut **tx,
+                file.id,
+                uploader_id,
+                channel.server_id,
+                channel.id,
+                Some(message_id),
+                &file.filename,
+                &file.content_type,
+                file.size,
+                &file.storage_key,
+                file.url.as_deref(),
+                file.width,
+                file.height,
+                *spoiler,
+                &file.sha256,
+            )
+            .await?,
+        );
+        if file.needs_processing {
+            media_jobs::enqueue(&mut **tx, file.id).await?;
+        }
+    }
+    Ok(rows)
+}
+
+/// Side effects of a newly created message: the `MESSAGE_CREATE` event,
+/// bridge and federation relays, and link previews. Returns the message JSON.
+pub(crate) async fn publish_message(
+    state: &AppState,
+    channel: &Channel,
+    msg: &messages::MessageRow,
+    author_username: &str,
+) -> serde_json::Value {
+    let mut response = message_row_to_json(msg, &[]);
+    response["author_username"] = serde_json::Value::String(author_username.to_owned());
 
     // Emit MESSAGE_CREATE event to gateway
     let _ = state.gateway_tx.send(GatewayEvent {
         event_type: "MESSAGE_CREATE".into(),
         data: response.clone(),
         server_id: channel.server_id,
-        channel_id: Some(channel_id),
-        user_id: Some(auth.user_id),
+        channel_id: Some(channel.id),
+        user_id: Some(msg.author_id),
+        trace_context: None,
     });
 
+    // Relay to Matrix / ActivityPub / … bridges in the background
+    crate::bridges::relay_message(state, channel, msg, author_username).await;
+    // …and to other Nexus servers, if the channel is a federated room
+    super::federated_rooms::relay_message(state, channel, msg, author_username).await;
+
+    // Link previews follow as a MESSAGE_UPDATE once fetched
+    crate::unfurl::spawn(state, channel, msg);
+
+    response
+}
+
+/// Why `member`'s message should be held, if the channel is pre-moderated.
+/// Members who can manage messages are never held.
+async fn hold_reason(
+    state: &AppState,
+    channel: &Channel,
+    member: &Member,
+    permissions: Permissions,
+    content: &str,
+) -> NexusResult<Option<crate::moderation_queue::HoldReason>> {
+    let pool = &state.db.pool;
+    let Some(settings) = moderation_queue::get_settings(pool, channel.id).await? else {
+        return Ok(None);
+    };
+    if !settings.hold_links && !settings.hold_new_members {
+        return Ok(None);
+    }
+    if permissions.has(Permissions::MANAGE_MESSAGES) {
+        return Ok(None);
+    }
+    Ok(crate::moderation_queue::hold_reason(pool, &settings, member, content).await?)
+}
+
+/// Queue a message for review. Files already written are recorded as
+/// unlinked uploads and claimed like any other attachment on approval.
+async fn hold_message(
+    state: &AppState,
+    channel: &Channel,
+    mut pending: moderation_queue::PendingMessageRow,
+    stored: &[(super::uploads::StoredFile, bool)],
+) -> NexusResult<serde_json::Value> {
+    let mut tx = state.db.pool.begin().await?;
+    for (file, spoiler) in stored {
+        attachments::create_for_message(
+            &mut *tx,
+            file.id,
+            pending.author_id,
+            channel.server_id,
+            channel.id,
+            None,
+            &file.filename,
+            &file.content_type,
+            file.size,
+            &file.storage_key,
+            file.url.as_deref(),
+            file.width,
+            file.height,
+            *spoiler,
+            &file.sha256,
+        )
+        .await?;
+        if file.needs_processing {
+            media_jobs::enqueue(&mut *tx, file.id).await?;
+        }
+        pending.attachment_ids.push(file.id);
+    }
+    tx.commit().await?;
+
+    let pending = moderation_queue::create_pending(&state.db.pool, &pending).await?;
+    super::moderation_queue::notify_queued(state, &pending);
+
     tracing::debug!(
-        message_id = %message_id,
-        channel_id = %channel_id,
-        author = %auth.username,
-        "Message sent"
+        pending_id = %pending.id,
+        channel_id = %channel.id,
+        reason = %pending.reason,
+        "Message held for moderation"
     );
 
-    Ok(Json(response))
+    Ok(super::moderation_queue::pending_json(&pending))
+}
+
+/// Best-effort removal of files written for a message that was never created.
+pub(crate) async fn discard_stored_files(state: &AppState, files: &[(super::uploads::StoredFile, bool)]) {
+    for (file, _) in files {
+        if let Err(e) = state.storage.delete_object(&file.storage_key).await {
+            tracing::warn!(key = %file.storage_key, error = %e, "Failed to delete orphaned upload");
+        }
+    }
 }
 
 /// GET /api/v1/channels/:channel_id/messages — Get message history.
@@ -199,12 +743,8 @@ async fn get_messages(
             resource: "Channel".into(),
         })?;
 
-    // If server channel, verify membership
-    if let Some(server_id) = channel.server_id {
-        if !members::is_member(&state.db.pool, auth.user_id, server_id).await? {
-            return Err(NexusError::Forbidden);
-        }
-    }
+    // If server channel, verify the member can read its history
+    require_in_server_channel(&state, &channel, auth.user_id, READ_HISTORY).await?;
 
     let limit = params.limit.unwrap_or(50).min(100).max(1);
     let rows = messages::list_channel_messages_with_author(
@@ -217,24 +757,40 @@ async fn get_messages(
     .await?;
 
     // Fetch reactions for all messages in batch
-    let mut result = Vec::with_capacity(rows.len());
-    for row in &rows {
-        let reaction_counts = reactions::get_reaction_counts(&state.db.pool, row.id)
-            .await
-            .unwrap_or_default();
-        let my_reactions = get_user_reactions(&state, row.id, auth.user_id, &reaction_counts).await;
-        result.push(message_with_author_to_json(row, &reaction_counts, &my_reactions));
-    }
+    let ids: Vec<Uuid> = rows.iter().map(|r| r.id).collect();
+    let mut reaction_counts = reactions::get_reaction_counts_bulk(&state.db.pool, &ids)
+        .await
+        .unwrap_or_default();
+    let mut my_reactions = reactions::get_user_reactions_bulk(&state.db.pool, &ids, auth.user_id)
+        .await
+        .unwrap_or_default();
+    let result: Vec<serde_json::Value> = rows
+        .iter()
+        .map(|row| {
+            message_with_author_to_json(
+                row,
+                &reaction_counts.remove(&row.id).unwrap_or_default(),
+                &my_reactions.remove(&row.id).unwrap_or_default(),
+            )
+        })
+        .collect();
 
     Ok(Json(result))
 }
 
 /// GET /api/v1/channels/:channel_id/messages/:message_id — Get a single message.
 async fn get_message(
-    Extension(_auth): Extension<AuthContext>,
+    Extension(auth): Extension<AuthContext>,
     State(state): State<Arc<AppState>>,
     Path((channel_id, message_id)): Path<(Uuid, Uuid)>,
 ) -> NexusResult<Json<serde_json::Value>> {
+    let channel = channels::find_by_id(&state.db.pool, channel_id)
+        .await?
+        .ok_or(NexusError::NotFound {
+            resource: "Channel".into(),
+        })?;
+    require_in_server_channel(&state, &channel, auth.user_id, READ_HISTORY).await?;
+
     let msg = messages::find_by_id(&state.db.pool, message_id)
         .await?
         .ok_or(NexusError::NotFound {
@@ -254,6 +810,89 @@ async fn get_message(
     Ok(Json(message_row_to_json(&msg, &reaction_counts)))
 }
 
+/// GET /api/v1/channels/:channel_id/messages/:message_id/preview — Compact
+/// render of a message for inline link previews.
+///
+/// Returns the author, a content snippet and image/video thumbnails. A
+/// viewer who can't read the channel gets the same 404 as for a missing
+/// message, so a pasted link reveals nothing about channels they can't see.
+async fn get_message_preview(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path((channel_id, message_id)): Path<(Uuid, Uuid)>,
+) -> NexusResult<Json<serde_json::Value>> {
+    let pool = &state.db.pool;
+    let not_found = || NexusError::NotFound {
+        resource: "Message".into(),
+    };
+    let channel = channels::find_by_id(pool, channel_id)
+        .await?
+        .ok_or_else(not_found)?;
+    let can_read = match crate::permissions::in_channel(pool, &channel, auth.user_id).await {
+        Ok(Some(permissions)) => permissions.has(READ_HISTORY),
+        Ok(None) => channels::is_dm_participant(pool, channel_id, auth.user_id).await?,
+        Err(NexusError::Forbidden) => false,
+        Err(e) => return Err(e),
+    };
+    if !can_read {
+        return Err(not_found());
+    }
+
+    let msg = messages::find_by_id(pool, message_id)
+        .await?
+        .filter(|m| m.channel_id == channel_id)
+        .ok_or_else(not_found)?;
+
+    let author = match msg.webhook_id {
+        Some(_) => webhook_json(msg.webhook_id, &msg.webhook_username, &msg.webhook_avatar_url),
+        None => match nexus_db::repository::users::find_by_id(pool, msg.author_id).await? {
+            Some(user) => serde_json::json!({
+                "id": user.id,
+                "username": user.username,
+                "display_name": user.display_name,
+                "avatar": user.avatar,
+            }),
+            None => serde_json::json!({ "id": msg.author_id }),
+        },
+    };
+
+    let files = attachments::list_for_message(pool, message_id).await?;
+    let thumbnails: Vec<serde_json::Value> = files
+        .iter()
+        .filter(|a| a.content_type.starts_with("image/") || a.content_type.starts_with("video/"))
+        .take(PREVIEW_MAX_THUMBNAILS)
+        .map(|a| {
+            serde_json::json!({
+                "id": a.id,
+                "content_type": a.content_type,
+                // Spoilers only get their placeholder until clicked through.
+                "url": if a.spoiler { None } else { a.url.as_deref() },
+                "width": a.width,
+                "height": a.height,
+                "blurhash": a.blurhash,
+                "spoiler": a.spoiler,
+            })
+        })
+        .collect();
+
+    let truncated = msg.content.chars().count() > PREVIEW_SNIPPET_CHARS;
+    let snippet: String = msg.content.chars().take(PREVIEW_SNIPPET_CHARS).collect();
+
+    Ok(Json(serde_json::json!({
+        "id": msg.id,
+        "channel_id": msg.channel_id,
+        "server_id": channel.server_id,
+        "channel_name": channel.name,
+        "author": author,
+        "content": snippet,
+        "truncated": truncated,
+        "attachment_count": files.len(),
+        "thumbnails": thumbnails,
+        "edited": msg.edited,
+        "created_at": msg.created_at,
+    })))
+}
+
 /// PATCH /api/v1/channels/:channel_id/messages/:message_id — Edit a message.
 async fn edit_message(
     Extension(auth): Extension<AuthContext>,
@@ -284,11 +923,27 @@ async fn edit_message(
         message: "Content is required".into(),
     })?;
 
-    let updated = messages::update_message(&state.db.pool, message_id, content).await?;
-
     let channel = channels::find_by_id(&state.db.pool, channel_id).await?.ok_or(NexusError::NotFound {
         resource: "Channel".into(),
     })?;
+    require_unarchived(&channel)?;
+
+    // Keep the old content unless the server opted out of edit history
+    let keep_history = msg.content != content
+        && match channel.server_id {
+            Some(server_id) => servers::find_by_id(&state.db.pool, server_id)
+                .await?
+                .is_some_and(|s| s.message_history),
+            None => true,
+        };
+
+    let mut tx = state.db.pool.begin().await?;
+    if keep_history {
+        messages::create_revision(&mut *tx, snowflake::generate_id(), message_id, &msg.content)
+            .await?;
+    }
+    let updated = messages::update_message(&mut *tx, message_id, content).await?;
+    tx.commit().await?;
 
     let response = message_row_to_json(&updated, &[]);
 
@@ -299,16 +954,81 @@ async fn edit_message(
         server_id: channel.server_id,
         channel_id: Some(channel_id),
         user_id: Some(auth.user_id),
+        trace_context: None,
     });
 
+    if msg.content != updated.content {
+        crate::unfurl::spawn(&state, &channel, &updated);
+    }
+
     Ok(Json(response))
 }
 
+/// GET /api/v1/channels/:channel_id/messages/:message_id/revisions — What a
+/// message said before each edit, oldest first.
+///
+/// Authors can always see their own history; anyone else needs
+/// MANAGE_MESSAGES. Servers with edit history turned off return no revisions.
+async fn get_message_revisions(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path((channel_id, message_id)): Path<(Uuid, Uuid)>,
+) -> NexusResult<Json<serde_json::Value>> {
+    let pool = &state.db.pool;
+    let channel = channels::find_by_id(pool, channel_id)
+        .await?
+        .ok_or(NexusError::NotFound {
+            resource: "Channel".into(),
+        })?;
+    let msg = messages::find_by_id(pool, message_id)
+        .await?
+        .filter(|m| m.channel_id == channel_id)
+        .ok_or(NexusError::NotFound {
+            resource: "Message".into(),
+        })?;
+    let required = if msg.author_id == auth.user_id {
+        READ_HISTORY
+    } else {
+        READ_HISTORY | Permissions::MANAGE_MESSAGES
+    };
+    require_in_server_channel(&state, &channel, auth.user_id, required).await?;
+
+    let enabled = match channel.server_id {
+        Some(server_id) => servers::find_by_id(pool, server_id)
+            .await?
+            .is_some_and(|s| s.message_history),
+        None => true,
+    };
+    let revisions = if enabled {
+        messages::list_revisions(pool, message_id).await?
+    } else {
+        Vec::new()
+    };
+
+    Ok(Json(serde_json::json!({
+        "message_id": msg.id,
+        "history_enabled": enabled,
+        "current": {
+            "content": msg.content,
+            "edited_at": msg.edited_at,
+        },
+        "revisions": revisions
+            .iter()
+            .map(|r| serde_json::json!({
+                "id": r.id,
+                "content": r.content,
+                "replaced_at": r.created_at,
+            }))
+            .collect::<Vec<_>>(),
+    })))
+}
+
 /// DELETE /api/v1/channels/:channel_id/messages/:message_id — Delete a message.
 async fn delete_message(
     Extension(auth): Extension<AuthContext>,
     State(state): State<Arc<AppState>>,
     Path((channel_id, message_id)): Path<(Uuid, Uuid)>,
+    headers: HeaderMap,
 ) -> NexusResult<Json<serde_json::Value>> {
     let msg = messages::find_by_id(&state.db.pool, message_id)
         .await?
@@ -328,23 +1048,28 @@ async fn delete_message(
     })?;
 
     if msg.author_id != auth.user_id {
-        // Check if user has MANAGE_MESSAGES permission
-        if let Some(server_id) = channel.server_id {
-            let server = nexus_db::repository::servers::find_by_id(&state.db.pool, server_id)
-                .await?
-                .ok_or(NexusError::NotFound { resource: "Server".into() })?;
-            if server.owner_id != auth.user_id {
-                return Err(NexusError::MissingPermission {
-                    permission: "MANAGE_MESSAGES".into(),
-                });
-            }
-        } else {
-            return Err(NexusError::Forbidden);
-        }
+        let permissions = crate::permissions::in_channel(&state.db.pool, &channel, auth.user_id)
+            .await?
+            .ok_or(NexusError::Forbidden)?;
+        crate::permissions::require(permissions, Permissions::MANAGE_MESSAGES)?;
     }
 
     messages::delete_message(&state.db.pool, message_id).await?;
 
+    // Moderator deletions are audited; authors removing their own are not
+    if let Some(server_id) = channel.server_id.filter(|_| msg.author_id != auth.user_id) {
+        audit::record(
+            &state,
+            server_id,
+            auth.user_id,
+            actions::MESSAGE_DELETE,
+            Some(Target::User(msg.author_id)),
+            Some(serde_json::json!({ "channel_id": channel_id, "message_id": message_id })),
+            audit::reason(&headers).as_deref(),
+        )
+        .await;
+    }
+
     // Emit MESSAGE_DELETE event
     let _ = state.gateway_tx.send(GatewayEvent {
         event_type: "MESSAGE_DELETE".into(),
@@ -356,6 +1081,7 @@ async fn delete_message(
         server_id: channel.server_id,
         channel_id: Some(channel_id),
         user_id: Some(auth.user_id),
+        trace_context: None,
     });
 
     Ok(Json(serde_json::json!({ "deleted": true })))
@@ -366,6 +1092,7 @@ async fn bulk_delete_messages(
     Extension(auth): Extension<AuthContext>,
     State(state): State<Arc<AppState>>,
     Path(channel_id): Path<Uuid>,
+    headers: HeaderMap,
     Json(body): Json<BulkDeleteBody>,
 ) -> NexusResult<Json<serde_json::Value>> {
     if body.messages.is_empty() || body.messages.len() > 100 {
@@ -380,22 +1107,38 @@ async fn bulk_delete_messages(
             resource: "Channel".into(),
         })?;
 
-    // Must be server owner or have MANAGE_MESSAGES
-    if let Some(server_id) = channel.server_id {
-        let server = nexus_db::repository::servers::find_by_id(&state.db.pool, server_id)
-            .await?
-            .ok_or(NexusError::NotFound { resource: "Server".into() })?;
-        if server.owner_id != auth.user_id {
-            return Err(NexusError::MissingPermission {
-                permission: "MANAGE_MESSAGES".into(),
-            });
-        }
-    } else {
-        return Err(NexusError::Forbidden);
-    }
+    // Server channels only, with MANAGE_MESSAGES
+    let permissions = crate::permissions::in_channel(&state.db.pool, &channel, auth.user_id)
+        .await?
+        .ok_or(NexusError::Forbidden)?;
+    crate::permissions::require(permissions, Permissions::MANAGE_MESSAGES)?;
 
     let deleted = messages::bulk_delete_messages(&state.db.pool, &body.messages).await?;
 
+    if let Some(server_id) = channel.server_id {
+        audit::record(
+            &state,
+            server_id,
+            auth.user_id,
+            actions::MESSAGE_BULK_DELETE,
+            Some(Target::Channel(channel_id)),
+            Some(serde_json::json!({ "count": deleted, "message_ids": body.messages })),
+            audit::reason(&headers).as_deref(),
+        )
+        .await;
+    }
+    crate::security_events::record(
+        &state,
+        crate::security_events::events::MESSAGE_BULK_DELETE,
+        serde_json::json!({
+            "actor_id": auth.user_id,
+            "server_id": channel.server_id,
+            "channel_id": channel_id,
+            "count": deleted,
+        }),
+    )
+    .await;
+
     // Emit MESSAGE_BULK_DELETE event
     let _ = state.gateway_tx.send(GatewayEvent {
         event_type: "MESSAGE_BULK_DELETE".into(),
@@ -407,21 +1150,243 @@ async fn bulk_delete_messages(
         server_id: channel.server_id,
         channel_id: Some(channel_id),
         user_id: Some(auth.user_id),
+        trace_context: None,
     });
 
     Ok(Json(serde_json::json!({ "deleted": deleted })))
 }
 
+/// GET /api/v1/channels/:channel_id/messages/deleted — Deleted messages not
+/// yet purged, most recently deleted first. Server channels only, with
+/// MANAGE_MESSAGES.
+async fn get_deleted_messages(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path(channel_id): Path<Uuid>,
+    Query(params): Query<DeletedMessagesParams>,
+) -> NexusResult<Json<Vec<serde_json::Value>>> {
+    let channel = channels::find_by_id(&state.db.pool, channel_id)
+        .await?
+        .ok_or(NexusError::NotFound { resource: "Channel".into() })?;
+    let permissions = crate::permissions::in_channel(&state.db.pool, &channel, auth.user_id)
+        .await?
+        .ok_or(NexusError::Forbidden)?;
+    crate::permissions::require(permissions, Permissions::MANAGE_MESSAGES)?;
+
+    let rows = messages::list_deleted(&state.db.pool, channel_id, params.limit.unwrap_or(50)).await?;
+    let result = rows
+        .iter()
+        .map(|r| {
+            let mut json = message_row_to_json(r, &[]);
+            json["deleted_at"] = serde_json::json!(r.deleted_at);
+            json
+        })
+        .collect();
+    Ok(Json(result))
+}
+
+/// POST /api/v1/channels/:channel_id/messages/:message_id/move — Move a
+/// message, and optionally its replies, to another channel.
+///
+/// The messages are reposted in the target channel as system-attributed
+/// copies that keep their original author, and a notice pointing at the new
+/// location is left behind. Originals are deleted (their attachments follow
+/// the repost) unless `keep_original` is set, in which case the repost
+/// references them instead. Needs MANAGE_MESSAGES in the source channel and
+/// SEND_MESSAGES in the target.
+async fn move_message(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path((channel_id, message_id)): Path<(Uuid, Uuid)>,
+    headers: HeaderMap,
+    Json(body): Json<MoveMessageBody>,
+) -> NexusResult<Json<serde_json::Value>> {
+    let pool = &state.db.pool;
+    if body.channel_id == channel_id {
+        return Err(NexusError::Validation {
+            message: "Message is already in that channel".into(),
+        });
+    }
+    let source = channels::find_by_id(pool, channel_id)
+        .await?
+        .ok_or(NexusError::NotFound {
+            resource: "Channel".into(),
+        })?;
+    let target = channels::find_by_id(pool, body.channel_id)
+        .await?
+        .filter(|c| c.server_id.is_some() && c.server_id == source.server_id)
+        .ok_or(NexusError::NotFound {
+            resource: "Channel".into(),
+        })?;
+    if matches!(target.channel_type, ChannelType::Category | ChannelType::Forum) {
+        return Err(NexusError::Validation {
+            message: "Messages can't be posted in that channel".into(),
+        });
+    }
+    require_unarchived(&source)?;
+    require_unarchived(&target)?;
+
+    // Server channels only: MANAGE_MESSAGES here, SEND_MESSAGES there
+    let permissions = crate::permissions::in_channel(pool, &source, auth.user_id)
+        .await?
+        .ok_or(NexusError::Forbidden)?;
+    crate::permissions::require(permissions, Permissions::MANAGE_MESSAGES)?;
+    let permissions = crate::permissions::in_channel(pool, &target, auth.user_id)
+        .await?
+        .ok_or(NexusError::Forbidden)?;
+    crate::permissions::require(
+        permissions,
+        Permissions::VIEW_CHANNEL | Permissions::SEND_MESSAGES,
+    )?;
+
+    let root = messages::find_by_id(pool, message_id)
+        .await?
+        .filter(|m| m.channel_id == channel_id)
+        .ok_or(NexusError::NotFound {
+            resource: "Message".into(),
+        })?;
+
+    // Gather the reply tree breadth-first; parents always precede replies
+    let mut moving = vec![root];
+    let mut next = 0;
+    while body.include_replies && next < moving.len() {
+        let replies = messages::list_replies(pool, channel_id, moving[next].id).await?;
+        moving.extend(replies);
+        next += 1;
+        if moving.len() > MAX_MOVE_MESSAGES {
+            return Err(NexusError::Validation {
+                message: format!("At most {MAX_MOVE_MESSAGES} messages can be moved at once"),
+            });
+        }
+    }
+    moving.sort_by_key(|m| m.created_at);
+
+    let mut tx = pool.begin().await?;
+    let mut moved: Vec<(Uuid, messages::MessageRow)> = Vec::with_capacity(moving.len());
+    for original in &moving {
+        let reference = if original.id == message_id {
+            if body.keep_original {
+                (Some(original.id), Some(channel_id))
+            } else {
+                (None, None)
+            }
+        } else {
+            // Replies point at their parent's repost
+            let parent = moved
+                .iter()
+                .find(|(old, _)| Some(*old) == original.reference_message_id)
+                .map(|(_, new)| new.id);
+            (parent, parent.map(|_| target.id))
+        };
+        let copy = messages::create_repost(
+            &mut *tx,
+            snowflake::generate_id(),
+            target.id,
+            original.id,
+            reference,
+        )
+        .await?;
+        if !body.keep_original {
+            attachments::move_to_message(&mut *tx, original.id, copy.id, target.id).await?;
+            messages::delete_message(&mut *tx, original.id).await?;
+        }
+        moved.push((original.id, copy));
+    }
+    let new_root = moved[0].1.id;
+    let notice = messages::create_message(
+        &mut *tx,
+        snowflake::generate_id(),
+        channel_id,
+        auth.user_id,
+        &format!("Moved {} message(s) to <#{}>", moved.len(), target.id),
+        2,
+        Some(new_root),
+        Some(target.id),
+        &[],
+        &[],
+        false,
+        MessageFlags::SUPPRESS_NOTIFICATIONS.bits(),
+    )
+    .await?;
+    tx.commit().await?;
+
+    let server_id = source.server_id;
+    let old_ids: Vec<Uuid> = moved.iter().map(|(old, _)| *old).collect();
+    let new_ids: Vec<Uuid> = moved.iter().map(|(_, new)| new.id).collect();
+    for (channel, row) in moved
+        .iter()
+        .map(|(_, new)| (target.id, new))
+        .chain(std::iter::once((channel_id, &notice)))
+    {
+        let _ = state.gateway_tx.send(GatewayEvent {
+            event_type: event_types::MESSAGE_CREATE.into(),
+            data: message_row_to_json(row, &[]),
+            server_id,
+            channel_id: Some(channel),
+            user_id: Some(auth.user_id),
+            trace_context: None,
+        });
+    }
+    if !body.keep_original {
+        let _ = state.gateway_tx.send(GatewayEvent {
+            event_type: "MESSAGE_BULK_DELETE".into(),
+            data: serde_json::json!({
+                "ids": old_ids,
+                "channel_id": channel_id,
+                "server_id": server_id,
+            }),
+            server_id,
+            channel_id: Some(channel_id),
+            user_id: Some(auth.user_id),
+            trace_context: None,
+        });
+    }
+
+    if let Some(server_id) = server_id {
+        audit::record(
+            &state,
+            server_id,
+            auth.user_id,
+            actions::MESSAGE_MOVE,
+            Some(Target::Message(new_root)),
+            Some(serde_json::json!({
+                "from_channel_id": channel_id,
+                "to_channel_id": target.id,
+                "message_ids": old_ids,
+                "new_message_ids": new_ids,
+                "kept_original": body.keep_original,
+            })),
+            audit::reason(&headers).as_deref(),
+        )
+        .await;
+    }
+
+    Ok(Json(serde_json::json!({
+        "channel_id": target.id,
+        "messages": moved
+            .iter()
+            .map(|(old, new)| serde_json::json!({ "original_id": old, "id": new.id }))
+            .collect::<Vec<_>>(),
+        "notice_id": notice.id,
+        "kept_original": body.keep_original,
+    })))
+}
+
 // ============================================================================
 // Pins
 // ============================================================================
 
 /// GET /api/v1/channels/:channel_id/pins
 async fn get_pinned_messages(
-    Extension(_auth): Extension<AuthContext>,
+    Extension(auth): Extension<AuthContext>,
     State(state): State<Arc<AppState>>,
     Path(channel_id): Path<Uuid>,
 ) -> NexusResult<Json<Vec<serde_json::Value>>> {
+    let channel = channels::find_by_id(&state.db.pool, channel_id)
+        .await?
+        .ok_or(NexusError::NotFound { resource: "Channel".into() })?;
+    require_in_server_channel(&state, &channel, auth.user_id, READ_HISTORY).await?;
+
     let rows = messages::get_pinned_messages(&state.db.pool, channel_id).await?;
     let result: Vec<serde_json::Value> = rows
         .iter()
@@ -447,18 +1412,9 @@ async fn pin_message(
     let channel = channels::find_by_id(&state.db.pool, channel_id)
         .await?
         .ok_or(NexusError::NotFound { resource: "Channel".into() })?;
+    require_unarchived(&channel)?;
 
-    // Check permission — for now, any member can pin in DMs, owner in servers
-    if let Some(server_id) = channel.server_id {
-        let server = nexus_db::repository::servers::find_by_id(&state.db.pool, server_id)
-            .await?
-            .ok_or(NexusError::NotFound { resource: "Server".into() })?;
-        if server.owner_id != auth.user_id {
-            return Err(NexusError::MissingPermission {
-                permission: "MANAGE_MESSAGES".into(),
-            });
-        }
-    }
+    require_pin_permission(&state, &channel, auth.user_id).await?;
 
     let pinned = messages::pin_message(&state.db.pool, message_id).await?;
     let response = message_row_to_json(&pinned, &[]);
@@ -472,6 +1428,7 @@ async fn pin_message(
         server_id: channel.server_id,
         channel_id: Some(channel_id),
         user_id: Some(auth.user_id),
+        trace_context: None,
     });
 
     Ok(Json(serde_json::json!({ "pinned": true })))
@@ -491,11 +1448,13 @@ async fn unpin_message(
         return Err(NexusError::NotFound { resource: "Message".into() });
     }
 
-    messages::unpin_message(&state.db.pool, message_id).await?;
-
     let channel = channels::find_by_id(&state.db.pool, channel_id)
         .await?
         .ok_or(NexusError::NotFound { resource: "Channel".into() })?;
+    require_unarchived(&channel)?;
+    require_pin_permission(&state, &channel, auth.user_id).await?;
+
+    messages::unpin_message(&state.db.pool, message_id).await?;
 
     let _ = state.gateway_tx.send(GatewayEvent {
         event_type: "CHANNEL_PINS_UPDATE".into(),
@@ -506,11 +1465,42 @@ async fn unpin_message(
         server_id: channel.server_id,
         channel_id: Some(channel_id),
         user_id: Some(auth.user_id),
+        trace_context: None,
     });
 
     Ok(Json(serde_json::json!({ "unpinned": true })))
 }
 
+/// Require `required` in a server channel. DM participants always pass.
+async fn require_in_server_channel(
+    state: &AppState,
+    channel: &Channel,
+    user_id: Uuid,
+    required: Permissions,
+) -> NexusResult<()> {
+    match crate::permissions::in_channel(&state.db.pool, channel, user_id).await? {
+        Some(permissions) => crate::permissions::require(permissions, required),
+        None => Ok(()),
+    }
+}
+
+/// Anyone in a DM can pin; server channels need PIN_MESSAGES or MANAGE_MESSAGES.
+async fn require_pin_permission(
+    state: &AppState,
+    channel: &Channel,
+    user_id: Uuid,
+) -> NexusResult<()> {
+    let Some(permissions) =
+        crate::permissions::in_channel(&state.db.pool, channel, user_id).await?
+    else {
+        return Ok(());
+    };
+    if permissions.has(Permissions::MANAGE_MESSAGES) {
+        return Ok(());
+    }
+    crate::permissions::require(permissions, Permissions::VIEW_CHANNEL | Permissions::PIN_MESSAGES)
+}
+
 // ============================================================================
 // Reactions
 // ============================================================================
@@ -530,25 +1520,27 @@ async fn add_reaction(
         return Err(NexusError::NotFound { resource: "Message".into() });
     }
 
+    let channel = channels::find_by_id(&state.db.pool, channel_id)
+        .await?
+        .ok_or(NexusError::NotFound { resource: "Channel".into() })?;
+    require_unarchived(&channel)?;
+    let required = READ_HISTORY | Permissions::ADD_REACTIONS;
+    require_in_server_channel(&state, &channel, auth.user_id, required).await?;
+
     let added = reactions::add_reaction(&state.db.pool, message_id, auth.user_id, &emoji).await?;
 
     if added {
-        let channel = channels::find_by_id(&state.db.pool, channel_id)
-            .await?
-            .ok_or(NexusError::NotFound { resource: "Channel".into() })?;
-
-        let _ = state.gateway_tx.send(GatewayEvent {
-            event_type: "MESSAGE_REACTION_ADD".into(),
-            data: serde_json::json!({
-                "message_id": message_id,
-                "channel_id": channel_id,
-                "user_id": auth.user_id,
-                "emoji": emoji,
-            }),
-            server_id: channel.server_id,
-            channel_id: Some(channel_id),
-            user_id: Some(auth.user_id),
-        });
+        send_reaction_events(
+            &state,
+            &channel,
+            message_id,
+            auth.user_id,
+            &emoji,
+            event_types::MESSAGE_REACTION_ADD,
+            event_types::MESSAGE_REACTION_ADD_AGGREGATE,
+        )
+        .await;
+        super::starboard::on_reaction_change(&state, &channel, message_id, &emoji).await;
     }
 
     Ok(Json(serde_json::json!({ "added": added })))
@@ -560,36 +1552,90 @@ async fn remove_reaction(
     State(state): State<Arc<AppState>>,
     Path((channel_id, message_id, emoji)): Path<(Uuid, Uuid, String)>,
 ) -> NexusResult<Json<serde_json::Value>> {
+    // Removing your own reaction only needs access to the channel
+    let channel = channels::find_by_id(&state.db.pool, channel_id)
+        .await?
+        .ok_or(NexusError::NotFound { resource: "Channel".into() })?;
+    require_in_server_channel(&state, &channel, auth.user_id, Permissions::VIEW_CHANNEL).await?;
+
     let removed = reactions::remove_reaction(&state.db.pool, message_id, auth.user_id, &emoji).await?;
 
     if removed {
-        let channel = channels::find_by_id(&state.db.pool, channel_id)
-            .await?
-            .ok_or(NexusError::NotFound { resource: "Channel".into() })?;
-
-        let _ = state.gateway_tx.send(GatewayEvent {
-            event_type: "MESSAGE_REACTION_REMOVE".into(),
-            data: serde_json::json!({
-                "message_id": message_id,
-                "channel_id": channel_id,
-                "user_id": auth.user_id,
-                "emoji": emoji,
-            }),
-            server_id: channel.server_id,
-            channel_id: Some(channel_id),
-            user_id: Some(auth.user_id),
-        });
+        send_reaction_events(
+            &state,
+            &channel,
+            message_id,
+            auth.user_id,
+            &emoji,
+            event_types::MESSAGE_REACTION_REMOVE,
+            event_types::MESSAGE_REACTION_REMOVE_AGGREGATE,
+        )
+        .await;
+        super::starboard::on_reaction_change(&state, &channel, message_id, &emoji).await;
     }
 
     Ok(Json(serde_json::json!({ "removed": removed })))
 }
 
+/// Announce a reaction change: `event_type` with just the change, and
+/// `aggregate_type` with the message's counts for every emoji too, for
+/// sessions with the `REACTION_COUNTS` intent.
+async fn send_reaction_events(
+    state: &AppState,
+    channel: &Channel,
+    message_id: Uuid,
+    user_id: Uuid,
+    emoji: &str,
+    event_type: &str,
+    aggregate_type: &str,
+) {
+    let mut data = serde_json::json!({
+        "message_id": message_id,
+        "channel_id": channel.id,
+        "user_id": user_id,
+        "emoji": emoji,
+    });
+    let _ = state.gateway_tx.send(GatewayEvent {
+        event_type: event_type.into(),
+        data: data.clone(),
+        server_id: channel.server_id,
+        channel_id: Some(channel.id),
+        user_id: Some(user_id),
+        trace_context: None,
+    });
+
+    let counts = match reactions::get_reaction_counts(&state.db.pool, message_id).await {
+        Ok(counts) => counts,
+        Err(e) => {
+            tracing::warn!(%message_id, error = %e, "Failed to count reactions");
+            return;
+        }
+    };
+    data["counts"] = counts
+        .iter()
+        .map(|rc| serde_json::json!({ "emoji": rc.emoji, "count": rc.count }))
+        .collect();
+    let _ = state.gateway_tx.send(GatewayEvent {
+        event_type: aggregate_type.into(),
+        data,
+        server_id: channel.server_id,
+        channel_id: Some(channel.id),
+        user_id: Some(user_id),
+        trace_context: None,
+    });
+}
+
 /// GET /api/v1/channels/:channel_id/messages/:message_id/reactions/:emoji
 async fn get_reactors(
-    Extension(_auth): Extension<AuthContext>,
+    Extension(auth): Extension<AuthContext>,
     State(state): State<Arc<AppState>>,
-    Path((_channel_id, message_id, emoji)): Path<(Uuid, Uuid, String)>,
+    Path((channel_id, message_id, emoji)): Path<(Uuid, Uuid, String)>,
 ) -> NexusResult<Json<Vec<Uuid>>> {
+    let channel = channels::find_by_id(&state.db.pool, channel_id)
+        .await?
+        .ok_or(NexusError::NotFound { resource: "Channel".into() })?;
+    require_in_server_channel(&state, &channel, auth.user_id, READ_HISTORY).await?;
+
     let users = reactions::get_reactors(&state.db.pool, message_id, &emoji, 100).await?;
     Ok(Json(users))
 }
@@ -600,21 +1646,12 @@ async fn remove_all_emoji_reactions(
     State(state): State<Arc<AppState>>,
     Path((channel_id, message_id, emoji)): Path<(Uuid, Uuid, String)>,
 ) -> NexusResult<Json<serde_json::Value>> {
-    // Only server owner / MANAGE_MESSAGES can bulk-remove reactions
+    // Only MANAGE_MESSAGES can bulk-remove reactions
     let channel = channels::find_by_id(&state.db.pool, channel_id)
         .await?
         .ok_or(NexusError::NotFound { resource: "Channel".into() })?;
 
-    if let Some(server_id) = channel.server_id {
-        let server = nexus_db::repository::servers::find_by_id(&state.db.pool, server_id)
-            .await?
-            .ok_or(NexusError::NotFound { resource: "Server".into() })?;
-        if server.owner_id != auth.user_id {
-            return Err(NexusError::MissingPermission {
-                permission: "MANAGE_MESSAGES".into(),
-            });
-        }
-    }
+    require_in_server_channel(&state, &channel, auth.user_id, Permissions::MANAGE_MESSAGES).await?;
 
     let count = reactions::remove_all_reactions_for_emoji(&state.db.pool, message_id, &emoji).await?;
     Ok(Json(serde_json::json!({ "removed": count })))
@@ -630,16 +1667,7 @@ async fn remove_all_reactions(
         .await?
         .ok_or(NexusError::NotFound { resource: "Channel".into() })?;
 
-    if let Some(server_id) = channel.server_id {
-        let server = nexus_db::repository::servers::find_by_id(&state.db.pool, server_id)
-            .await?
-            .ok_or(NexusError::NotFound { resource: "Server".into() })?;
-        if server.owner_id != auth.user_id {
-            return Err(NexusError::MissingPermission {
-                permission: "MANAGE_MESSAGES".into(),
-            });
-        }
-    }
+    require_in_server_channel(&state, &channel, auth.user_id, Permissions::MANAGE_MESSAGES).await?;
 
     let count = reactions::remove_all_reactions(&state.db.pool, message_id).await?;
     Ok(Json(serde_json::json!({ "removed": count })))
@@ -656,11 +1684,83 @@ async fn ack_message(
     Path((channel_id, message_id)): Path<(Uuid, Uuid)>,
 ) -> NexusResult<Json<serde_json::Value>> {
     let rs = read_states::ack_message(&state.db.pool, auth.user_id, channel_id, message_id).await?;
-
-    Ok(Json(serde_json::json!({
+    let response = serde_json::json!({
         "channel_id": rs.channel_id,
         "last_read_message_id": rs.last_read_message_id,
         "mention_count": rs.mention_count,
+    });
+
+    // Sync the ack to the user's other sessions
+    let _ = state.gateway_tx.send(GatewayEvent {
+        event_type: event_types::MESSAGE_ACK.into(),
+        data: response.clone(),
+        server_id: None,
+        channel_id: Some(rs.channel_id),
+        user_id: Some(auth.user_id),
+        trace_context: None,
+    });
+
+    Ok(Json(response))
+}
+
+/// POST /api/v1/read-states/ack-bulk — Acknowledge up to 100 channels in one call.
+async fn ack_bulk(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Json(body): Json<BulkAckBody>,
+) -> NexusResult<Json<serde_json::Value>> {
+    if body.read_states.is_empty() || body.read_states.len() > 100 {
+        return Err(NexusError::Validation {
+            message: "Must acknowledge between 1 and 100 channels".into(),
+        });
+    }
+
+    let acks: Vec<(Uuid, Uuid)> = body
+        .read_states
+        .iter()
+        .map(|e| (e.channel_id, e.message_id))
+        .collect();
+    let rows = read_states::ack_bulk(&state.db.pool, auth.user_id, &acks).await?;
+    let read_states: Vec<serde_json::Value> = rows
+        .iter()
+        .map(|rs| {
+            serde_json::json!({
+                "channel_id": rs.channel_id,
+                "last_read_message_id": rs.last_read_message_id,
+                "mention_count": rs.mention_count,
+            })
+        })
+        .collect();
+
+    for (rs, data) in rows.iter().zip(&read_states) {
+        let _ = state.gateway_tx.send(GatewayEvent {
+            event_type: event_types::MESSAGE_ACK.into(),
+            data: data.clone(),
+            server_id: None,
+            channel_id: Some(rs.channel_id),
+            user_id: Some(auth.user_id),
+            trace_context: None,
+        });
+    }
+
+    Ok(Json(serde_json::json!({ "read_states": read_states })))
+}
+
+/// GET /api/v1/users/@me/unreads — Per-channel unread and mention counts.
+async fn get_unreads(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+) -> NexusResult<Json<serde_json::Value>> {
+    let unreads = read_states::get_unread_channels(&state.db.pool, auth.user_id).await?;
+
+    Ok(Json(serde_json::json!({
+        "unreads": unreads.iter().map(|u| serde_json::json!({
+            "channel_id": u.channel_id,
+            "last_message_id": u.last_message_id,
+            "last_read_message_id": u.last_read_message_id,
+            "unread_count": u.unread_count,
+            "mention_count": u.mention_count,
+        })).collect::<Vec<_>>(),
     })))
 }
 
@@ -707,7 +1807,7 @@ async fn search_messages(
 // ============================================================================
 
 /// Convert a MessageRow to a JSON response.
-fn message_row_to_json(
+pub(crate) fn message_row_to_json(
     row: &messages::MessageRow,
     reaction_counts: &[reactions::ReactionCount],
 ) -> serde_json::Value {
@@ -756,11 +1856,29 @@ fn message_with_author_to_json(
         "mention_everyone": row.mention_everyone,
         "reference": reference,
         "thread_id": row.thread_id,
+        "flags": row.flags,
+        "webhook": webhook_json(row.webhook_id, &row.webhook_username, &row.webhook_avatar_url),
         "reactions": reactions_json,
         "created_at": row.created_at,
     })
 }
 
+/// Display identity of a webhook-authored message, or `null`.
+fn webhook_json(
+    webhook_id: Option<Uuid>,
+    username: &Option<String>,
+    avatar_url: &Option<String>,
+) -> serde_json::Value {
+    match webhook_id {
+        Some(id) => serde_json::json!({
+            "id": id,
+            "username": username,
+            "avatar_url": avatar_url,
+        }),
+        None => serde_json::Value::Null,
+    }
+}
+
 fn message_row_to_json_with_reactions(
     row: &messages::MessageRow,
     reaction_counts: &[reactions::ReactionCount],
@@ -801,11 +1919,76 @@ fn message_row_to_json_with_reactions(
         "mention_everyone": row.mention_everyone,
         "reference": reference,
         "thread_id": row.thread_id,
+        "flags": row.flags,
+        "webhook": webhook_json(row.webhook_id, &row.webhook_username, &row.webhook_avatar_url),
         "reactions": reactions_json,
         "created_at": row.created_at,
     })
 }
 
+/// Apply the automatic timeout for a spam hit and notify moderators via the
+/// audit log and a `SPAM_DETECTED` gateway event.
+async fn handle_spam(
+    state: &AppState,
+    auth: &AuthContext,
+    channel: &Channel,
+    verdict: crate::spam::SpamVerdict,
+) {
+    tracing::warn!(
+        user = %auth.user_id,
+        ip = ?auth.ip,
+        channel_id = %channel.id,
+        kind = verdict.kind.as_str(),
+        "Spam detected"
+    );
+
+    // DMs have no membership to time out, and an address may be shared by
+    // innocent users — the message is simply dropped.
+    if verdict.by_ip {
+        return;
+    }
+    let Some(server_id) = channel.server_id else { return };
+
+    let until = chrono::Utc::now()
+        + chrono::Duration::from_std(state.spam.timeout()).unwrap_or_default();
+    if let Err(e) = members::set_timeout(&state.db.pool, auth.user_id, server_id, Some(until)).await {
+        tracing::error!(error = %e, "Failed to apply spam timeout");
+    }
+
+    let details = serde_json::json!({
+        "kind": verdict.kind.as_str(),
+        "by_ip": verdict.by_ip,
+        "channel_id": channel.id,
+        "timeout_until": until,
+    });
+    let _ = audit_log::create_entry(
+        &state.db.pool,
+        server_id,
+        auth.user_id,
+        event_types::SPAM_DETECTED,
+        Some("user"),
+        Some(auth.user_id),
+        Some(&details),
+        Some("Automatic timeout by spam heuristics"),
+    )
+    .await;
+
+    let _ = state.gateway_tx.send(GatewayEvent {
+        event_type: event_types::SPAM_DETECTED.into(),
+        data: serde_json::json!({
+            "user_id": auth.user_id,
+            "server_id": server_id,
+            "channel_id": channel.id,
+            "kind": verdict.kind.as_str(),
+            "timeout_until": until,
+        }),
+        server_id: Some(server_id),
+        channel_id: Some(channel.id),
+        user_id: Some(auth.user_id),
+        trace_context: None,
+    });
+}
+
 /// Parse @<uuid> mentions from message content.
 fn parse_mentions(content: &str) -> Vec<Uuid> {
     let mut mentions = Vec::new();
@@ -820,22 +2003,3 @@ fn parse_mentions(content: &str) -> Vec<Uuid> {
     }
     mentions
 }
-
-/// Get which emojis the current user has reacted with on a message.
-async fn get_user_reactions(
-    state: &AppState,
-    message_id: Uuid,
-    user_id: Uuid,
-    reaction_counts: &[reactions::ReactionCount],
-) -> Vec<String> {
-    let mut my_reactions = Vec::new();
-    for rc in reaction_counts {
-        if reactions::has_user_reacted(&state.db.pool, message_id, user_id, &rc.emoji)
-            .await
-            .unwrap_or(false)
-        {
-            my_reactions.push(rc.emoji.clone());
-        }
-    }
-    my_reactions
-}
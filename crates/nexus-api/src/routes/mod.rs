@@ -1,25 +1,49 @@
 //! API route modules.
 
+pub mod activitypub;
+pub mod admin;
+pub mod audit_log;
 pub mod auth;
+pub mod bans;
+pub mod blocks;
 pub mod bots;
+pub mod channel_mutes;
 pub mod channels;
 pub mod directory;
 pub mod files;
 pub mod dms;
 pub mod e2ee;
 pub mod emoji;
+pub mod exports;
 pub mod extensibility;
+pub mod federated_rooms;
 pub mod federation;
+pub mod forums;
 pub mod health;
+pub mod invites;
 pub mod keys;
+pub mod legal;
 pub mod messages;
+pub mod moderation;
+pub mod moderation_queue;
+pub mod oauth2;
+pub mod permissions;
 pub mod presence;
+pub mod push;
+pub mod registration;
+pub mod reminders;
+pub mod scheduled_events;
 pub mod search;
 pub mod servers;
+pub mod sessions;
 pub mod slash_commands;
+pub mod starboard;
+pub mod status;
 pub mod threads;
 pub mod uploads;
+pub mod user_settings;
 pub mod users;
 pub mod verification;
 pub mod voice;
 pub mod webhooks;
+pub mod welcome;
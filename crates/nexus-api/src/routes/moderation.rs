@@ -0,0 +1,533 @@
+//! Moderation routes — bans, kicks and timeouts.
+//!
+//! GET    /servers/:id/bans?after=&limit=     — List bans (BAN_MEMBERS)
+//! GET    /servers/:id/bans/:user_id          — Get one ban
+//! PUT    /servers/:id/bans/:user_id          — Ban a user, removing them if a member
+//! DELETE /servers/:id/bans/:user_id          — Lift a ban
+//! DELETE /servers/:id/members/:user_id       — Kick a member (KICK_MEMBERS)
+//! PATCH  /servers/:id/members/:user_id       — Set or clear a timeout (KICK_MEMBERS)
+//! PATCH  /servers/:id/members/bulk           — Change roles / reset nicknames of up to 100 members
+//!
+//! Members can only be moderated by someone whose highest role is above
+//! theirs, and never the server owner. Reasons come from the
+//! `X-Audit-Log-Reason` header (or the ban body) and land in the audit log.
+
+use axum::{
+    extract::{Extension, Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    middleware,
+    routing::{delete, get, patch, put},
+    Json, Router,
+};
+use chrono::{DateTime, Utc};
+use nexus_common::{
+    error::{NexusError, NexusResult},
+    gateway_event::{event_types, GatewayEvent},
+    models::{member::Member, server::Server},
+    permissions::Permissions,
+};
+use nexus_db::repository::{bans, members, roles, servers};
+use serde::{Deserialize, Deserializer};
+use std::collections::HashSet;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{
+    audit::{self, actions, Target},
+    middleware::AuthContext,
+    AppState,
+};
+
+/// Longest timeout a moderator can apply.
+const MAX_TIMEOUT_DAYS: i64 = 28;
+/// Most members one bulk update may touch.
+const MAX_BULK_MEMBERS: usize = 100;
+
+pub fn router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/servers/{server_id}/bans", get(list_bans))
+        .route(
+            "/servers/{server_id}/bans/{user_id}",
+            put(create_ban).get(get_ban).delete(remove_ban),
+        )
+        .route("/servers/{server_id}/members/bulk", patch(bulk_update_members))
+        .route(
+            "/servers/{server_id}/members/{user_id}",
+            delete(kick_member).patch(update_member),
+        )
+        .route_layer(middleware::from_fn(crate::middleware::auth_middleware))
+}
+
+// ============================================================
+// Request types
+// ============================================================
+
+#[derive(Debug, Deserialize)]
+struct BanListParams {
+    /// Return bans for user IDs after this one.
+    after: Option<Uuid>,
+    limit: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BanRequest {
+    reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct UpdateMemberRequest {
+    /// `null` clears the timeout; omitting the field leaves it unchanged.
+    #[serde(default, deserialize_with = "present")]
+    communication_disabled_until: Option<Option<DateTime<Utc>>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BulkUpdateMembersRequest {
+    user_ids: Vec<Uuid>,
+    #[serde(default)]
+    add_roles: Vec<Uuid>,
+    #[serde(default)]
+    remove_roles: Vec<Uuid>,
+    /// Clear every listed member's nickname.
+    #[serde(default)]
+    reset_nickname: bool,
+}
+
+/// Distinguish an explicit `null` (`Some(None)`) from a missing field (`None`).
+fn present<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    T::deserialize(deserializer).map(Some)
+}
+
+// ============================================================
+// Helpers
+// ============================================================
+
+fn ban_json(b: &bans::BanRow) -> serde_json::Value {
+    serde_json::json!({
+        "user_id": b.user_id,
+        "server_id": b.server_id,
+        "reason": b.reason,
+        "banned_by": b.banned_by,
+        "created_at": b.created_at,
+    })
+}
+
+/// Load a server and require `permission` in it.
+async fn require_server_permission(
+    state: &AppState,
+    server_id: Uuid,
+    user_id: Uuid,
+    permission: Permissions,
+) -> NexusResult<Server> {
+    let server = servers::find_by_id(&state.db.pool, server_id)
+        .await?
+        .ok_or(NexusError::NotFound {
+            resource: "Server".into(),
+        })?;
+    let permissions = crate::permissions::resolve(&state.db.pool, &server, None, user_id)
+        .await?
+        .ok_or(NexusError::Forbidden)?;
+    crate::permissions::require(permissions, permission)?;
+    Ok(server)
+}
+
+/// Require that `actor_id` may moderate `target`. Moderating yourself is
+/// refused, as is anyone at or above your highest role.
+async fn require_outranks(
+    state: &AppState,
+    server: &Server,
+    actor_id: Uuid,
+    target: &Member,
+) -> NexusResult<()> {
+    if target.user_id == actor_id {
+        return Err(NexusError::Validation {
+            message: "You cannot moderate yourself".into(),
+        });
+    }
+    let actor = members::find_member(&state.db.pool, actor_id, server.id)
+        .await?
+        .ok_or(NexusError::Forbidden)?;
+    let server_roles = roles::list_server_roles(&state.db.pool, server.id).await?;
+    if !crate::permissions::outranks(server, &actor, target, &server_roles) {
+        return Err(NexusError::Forbidden);
+    }
+    Ok(())
+}
+
+/// Remove a member and tell the server (including the removed user).
+async fn remove_from_server(state: &AppState, server_id: Uuid, user_id: Uuid) -> NexusResult<()> {
+    members::remove_member(&state.db.pool, user_id, server_id).await?;
+    servers::decrement_member_count(&state.db.pool, server_id).await?;
+    let _ = state.gateway_tx.send(GatewayEvent {
+        event_type: event_types::SERVER_MEMBER_REMOVE.into(),
+        data: serde_json::json!({ "server_id": server_id, "user_id": user_id }),
+        server_id: Some(server_id),
+        channel_id: None,
+        user_id: Some(user_id),
+        trace_context: None,
+    });
+    Ok(())
+}
+
+// ============================================================
+// Bans
+// ============================================================
+
+/// GET /api/v1/servers/:server_id/bans
+async fn list_bans(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path(server_id): Path<Uuid>,
+    Query(params): Query<BanListParams>,
+) -> NexusResult<Json<Vec<serde_json::Value>>> {
+    require_server_permission(&state, server_id, auth.user_id, Permissions::BAN_MEMBERS).await?;
+    let limit = params.limit.unwrap_or(100).clamp(1, 1000);
+    let rows = bans::list_bans_page(&state.db.pool, server_id, params.after, limit).await?;
+    Ok(Json(rows.iter().map(ban_json).collect()))
+}
+
+/// GET /api/v1/servers/:server_id/bans/:user_id
+async fn get_ban(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path((server_id, user_id)): Path<(Uuid, Uuid)>,
+) -> NexusResult<Json<serde_json::Value>> {
+    require_server_permission(&state, server_id, auth.user_id, Permissions::BAN_MEMBERS).await?;
+    let ban = bans::find_ban(&state.db.pool, server_id, user_id)
+        .await?
+        .ok_or(NexusError::NotFound {
+            resource: "Ban".into(),
+        })?;
+    Ok(Json(ban_json(&ban)))
+}
+
+/// PUT /api/v1/servers/:server_id/bans/:user_id
+///
+/// Users who aren't members can be banned pre-emptively. Banning an
+/// already-banned user is a no-op.
+async fn create_ban(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path((server_id, user_id)): Path<(Uuid, Uuid)>,
+    headers: HeaderMap,
+    body: Option<Json<BanRequest>>,
+) -> NexusResult<StatusCode> {
+    let server =
+        require_server_permission(&state, server_id, auth.user_id, Permissions::BAN_MEMBERS).await?;
+    if user_id == server.owner_id || user_id == auth.user_id {
+        return Err(NexusError::Validation {
+            message: "This user cannot be banned".into(),
+        });
+    }
+    let target = members::find_member(&state.db.pool, user_id, server_id).await?;
+    if let Some(target) = &target {
+        require_outranks(&state, &server, auth.user_id, target).await?;
+    }
+
+    let reason = body
+        .and_then(|Json(b)| b.reason)
+        .and_then(|r| audit::clean_reason(&r))
+        .or_else(|| audit::reason(&headers));
+
+    if !bans::create_ban(&state.db.pool, server_id, user_id, reason.as_deref(), auth.user_id).await? {
+        if bans::find_ban(&state.db.pool, server_id, user_id).await?.is_some() {
+            return Ok(StatusCode::NO_CONTENT);
+        }
+        return Err(NexusError::NotFound {
+            resource: "User".into(),
+        });
+    }
+    if target.is_some() {
+        remove_from_server(&state, server_id, user_id).await?;
+    }
+
+    let _ = state.gateway_tx.send(GatewayEvent {
+        event_type: event_types::SERVER_BAN_ADD.into(),
+        data: serde_json::json!({ "server_id": server_id, "user_id": user_id, "reason": reason }),
+        server_id: Some(server_id),
+        channel_id: None,
+        user_id: Some(user_id),
+        trace_context: None,
+    });
+    audit::record(
+        &state,
+        server_id,
+        auth.user_id,
+        actions::MEMBER_BAN_ADD,
+        Some(Target::User(user_id)),
+        None,
+        reason.as_deref(),
+    )
+    .await;
+
+    tracing::info!(%server_id, %user_id, by = %auth.user_id, "Member banned");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// DELETE /api/v1/servers/:server_id/bans/:user_id
+async fn remove_ban(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path((server_id, user_id)): Path<(Uuid, Uuid)>,
+    headers: HeaderMap,
+) -> NexusResult<StatusCode> {
+    require_server_permission(&state, server_id, auth.user_id, Permissions::BAN_MEMBERS).await?;
+    if !bans::delete_ban(&state.db.pool, server_id, user_id).await? {
+        return Err(NexusError::NotFound {
+            resource: "Ban".into(),
+        });
+    }
+
+    let _ = state.gateway_tx.send(GatewayEvent {
+        event_type: event_types::SERVER_BAN_REMOVE.into(),
+        data: serde_json::json!({ "server_id": server_id, "user_id": user_id }),
+        server_id: Some(server_id),
+        channel_id: None,
+        user_id: Some(user_id),
+        trace_context: None,
+    });
+    audit::record(
+        &state,
+        server_id,
+        auth.user_id,
+        actions::MEMBER_BAN_REMOVE,
+        Some(Target::User(user_id)),
+        None,
+        audit::reason(&headers).as_deref(),
+    )
+    .await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// ============================================================
+// Members
+// ============================================================
+
+/// DELETE /api/v1/servers/:server_id/members/:user_id — Kick a member.
+async fn kick_member(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path((server_id, user_id)): Path<(Uuid, Uuid)>,
+    headers: HeaderMap,
+) -> NexusResult<StatusCode> {
+    let server =
+        require_server_permission(&state, server_id, auth.user_id, Permissions::KICK_MEMBERS).await?;
+    let target = members::find_member(&state.db.pool, user_id, server_id)
+        .await?
+        .ok_or(NexusError::NotFound {
+            resource: "Member".into(),
+        })?;
+    require_outranks(&state, &server, auth.user_id, &target).await?;
+
+    remove_from_server(&state, server_id, user_id).await?;
+    audit::record(
+        &state,
+        server_id,
+        auth.user_id,
+        actions::MEMBER_KICK,
+        Some(Target::User(user_id)),
+        None,
+        audit::reason(&headers).as_deref(),
+    )
+    .await;
+
+    tracing::info!(%server_id, %user_id, by = %auth.user_id, "Member kicked");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// PATCH /api/v1/servers/:server_id/members/:user_id
+///
+/// Currently only `communication_disabled_until` (timeouts, up to 28 days).
+/// Timed-out members keep reading but can't send messages.
+async fn update_member(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path((server_id, user_id)): Path<(Uuid, Uuid)>,
+    headers: HeaderMap,
+    Json(body): Json<UpdateMemberRequest>,
+) -> NexusResult<Json<Member>> {
+    let server =
+        require_server_permission(&state, server_id, auth.user_id, Permissions::KICK_MEMBERS).await?;
+    let target = members::find_member(&state.db.pool, user_id, server_id)
+        .await?
+        .ok_or(NexusError::NotFound {
+            resource: "Member".into(),
+        })?;
+
+    let Some(until) = body.communication_disabled_until else {
+        return Ok(Json(target));
+    };
+    require_outranks(&state, &server, auth.user_id, &target).await?;
+    if let Some(until) = until {
+        let now = Utc::now();
+        if until <= now || until > now + chrono::Duration::days(MAX_TIMEOUT_DAYS) {
+            return Err(NexusError::Validation {
+                message: format!(
+                    "communication_disabled_until must be in the future and at most {MAX_TIMEOUT_DAYS} days away"
+                ),
+            });
+        }
+    }
+
+    members::set_timeout(&state.db.pool, user_id, server_id, until).await?;
+    let updated = members::find_member(&state.db.pool, user_id, server_id)
+        .await?
+        .ok_or(NexusError::NotFound {
+            resource: "Member".into(),
+        })?;
+
+    let _ = state.gateway_tx.send(GatewayEvent {
+        event_type: event_types::SERVER_MEMBER_UPDATE.into(),
+        data: serde_json::to_value(&updated).unwrap_or_default(),
+        server_id: Some(server_id),
+        channel_id: None,
+        user_id: Some(user_id),
+        trace_context: None,
+    });
+    audit::record(
+        &state,
+        server_id,
+        auth.user_id,
+        actions::MEMBER_UPDATE,
+        Some(Target::User(user_id)),
+        Some(audit::diff(&target, &updated, &["communication_disabled_until"])),
+        audit::reason(&headers).as_deref(),
+    )
+    .await;
+
+    Ok(Json(updated))
+}
+
+/// PATCH /api/v1/servers/:server_id/members/bulk
+///
+/// Adds and removes roles (MANAGE_ROLES) and resets nicknames
+/// (MANAGE_NICKNAMES) for up to 100 members at once. Every member must be
+/// one the caller outranks and every role one below the caller's highest,
+/// or nothing changes. The changes are made in one transaction and
+/// announced with a single `SERVER_MEMBERS_BULK_UPDATE` event.
+async fn bulk_update_members(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path(server_id): Path<Uuid>,
+    headers: HeaderMap,
+    Json(body): Json<BulkUpdateMembersRequest>,
+) -> NexusResult<Json<Vec<Member>>> {
+    let mut user_ids = body.user_ids;
+    let mut seen = HashSet::new();
+    user_ids.retain(|id| seen.insert(*id));
+    if user_ids.is_empty() || user_ids.len() > MAX_BULK_MEMBERS {
+        return Err(NexusError::Validation {
+            message: format!("Must update between 1 and {MAX_BULK_MEMBERS} members"),
+        });
+    }
+    let changes_roles = !body.add_roles.is_empty() || !body.remove_roles.is_empty();
+    if !changes_roles && !body.reset_nickname {
+        return Err(NexusError::Validation {
+            message: "Nothing to update".into(),
+        });
+    }
+    if body.add_roles.iter().any(|r| body.remove_roles.contains(r)) {
+        return Err(NexusError::Validation {
+            message: "A role cannot be both added and removed".into(),
+        });
+    }
+
+    let mut required = Permissions::empty();
+    if changes_roles {
+        required |= Permissions::MANAGE_ROLES;
+    }
+    if body.reset_nickname {
+        required |= Permissions::MANAGE_NICKNAMES;
+    }
+    let server = require_server_permission(&state, server_id, auth.user_id, required).await?;
+
+    let actor = members::find_member(&state.db.pool, auth.user_id, server_id)
+        .await?
+        .ok_or(NexusError::Forbidden)?;
+    let server_roles = roles::list_server_roles(&state.db.pool, server_id).await?;
+    let actor_top = crate::permissions::top_role_position(&actor, &server_roles);
+    for role_id in body.add_roles.iter().chain(&body.remove_roles) {
+        let role = server_roles
+            .iter()
+            .find(|r| r.id == *role_id)
+            .ok_or(NexusError::NotFound {
+                resource: "Role".into(),
+            })?;
+        if role.is_default {
+            return Err(NexusError::Validation {
+                message: "The @everyone role cannot be assigned".into(),
+            });
+        }
+        if actor.user_id != server.owner_id && role.position >= actor_top {
+            return Err(NexusError::Forbidden);
+        }
+    }
+    for &user_id in &user_ids {
+        let target = members::find_member(&state.db.pool, user_id, server_id)
+            .await?
+            .ok_or(NexusError::NotFound {
+                resource: "Member".into(),
+            })?;
+        if target.user_id == actor.user_id {
+            return Err(NexusError::Validation {
+                message: "You cannot moderate yourself".into(),
+            });
+        }
+        if !crate::permissions::outranks(&server, &actor, &target, &server_roles) {
+            return Err(NexusError::Forbidden);
+        }
+    }
+
+    let mut tx = state.db.pool.begin().await?;
+    let mut updated = Vec::with_capacity(user_ids.len());
+    for &user_id in &user_ids {
+        for &role_id in &body.remove_roles {
+            members::remove_role(&mut *tx, user_id, server_id, role_id).await?;
+        }
+        for &role_id in &body.add_roles {
+            members::add_role(&mut *tx, user_id, server_id, role_id).await?;
+        }
+        if body.reset_nickname {
+            members::update_nickname(&mut *tx, user_id, server_id, None).await?;
+        }
+        let member = members::find_member(&mut *tx, user_id, server_id)
+            .await?
+            .ok_or(NexusError::NotFound {
+                resource: "Member".into(),
+            })?;
+        updated.push(member);
+    }
+    tx.commit().await?;
+
+    let _ = state.gateway_tx.send(GatewayEvent {
+        event_type: event_types::SERVER_MEMBERS_BULK_UPDATE.into(),
+        data: serde_json::json!({ "server_id": server_id, "members": updated }),
+        server_id: Some(server_id),
+        channel_id: None,
+        user_id: None,
+        trace_context: None,
+    });
+    audit::record(
+        &state,
+        server_id,
+        auth.user_id,
+        actions::MEMBER_BULK_UPDATE,
+        None,
+        Some(serde_json::json!({
+            "user_ids": user_ids,
+            "add_roles": body.add_roles,
+            "remove_roles": body.remove_roles,
+            "reset_nickname": body.reset_nickname,
+        })),
+        audit::reason(&headers).as_deref(),
+    )
+    .await;
+
+    tracing::info!(%server_id, count = updated.len(), by = %auth.user_id, "Members updated in bulk");
+    Ok(Json(updated))
+}
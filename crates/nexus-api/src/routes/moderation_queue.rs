@@ -0,0 +1,396 @@
+//! Moderation queue routes — per-channel pre-moderation and held messages.
+//!
+//! GET  /channels/:id/moderation                        — Get pre-moderation settings
+//! PUT  /channels/:id/moderation                        — Replace pre-moderation settings
+//! GET  /servers/:id/moderation-queue                   — List held messages
+//! POST /servers/:id/moderation-queue/:pending_id/approve — Post a held message
+//! POST /servers/:id/moderation-queue/:pending_id/reject  — Discard a held message
+//!
+//! Queue events (`MODERATION_QUEUE_ADD` / `_REMOVE`) carry IDs only, never
+//! the held content; moderators fetch it from the queue endpoint.
+
+use axum::{
+    extract::{Extension, Path, Query, State},
+    middleware,
+    routing::{get, post},
+    Json, Router,
+};
+use nexus_common::{
+    error::{NexusError, NexusResult},
+    gateway_event::{event_types, GatewayEvent},
+    models::channel::Channel,
+    permissions::Permissions,
+};
+use nexus_db::repository::{attachments, channels, moderation_queue, servers, users};
+use serde::Deserialize;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{middleware::AuthContext, AppState};
+
+pub fn router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route(
+            "/channels/{channel_id}/moderation",
+            get(get_settings).put(put_settings),
+        )
+        .route("/servers/{server_id}/moderation-queue", get(list_queue))
+        .route(
+            "/servers/{server_id}/moderation-queue/{pending_id}/approve",
+            post(approve),
+        )
+        .route(
+            "/servers/{server_id}/moderation-queue/{pending_id}/reject",
+            post(reject),
+        )
+        .route_layer(middleware::from_fn(crate::middleware::auth_middleware))
+}
+
+// ============================================================
+// Request / response types
+// ============================================================
+
+#[derive(Debug, Deserialize)]
+struct SettingsRequest {
+    #[serde(default)]
+    hold_new_members: bool,
+    new_member_minutes: Option<i32>,
+    #[serde(default)]
+    hold_links: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct QueueParams {
+    channel_id: Option<Uuid>,
+    limit: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RejectRequest {
+    reason: Option<String>,
+}
+
+/// Members count as new for a day unless configured otherwise.
+const DEFAULT_NEW_MEMBER_MINUTES: i32 = 24 * 60;
+/// Upper bound on `new_member_minutes` (30 days).
+const MAX_NEW_MEMBER_MINUTES: i32 = 30 * 24 * 60;
+
+fn settings_json(s: &moderation_queue::ModerationSettingsRow) -> serde_json::Value {
+    serde_json::json!({
+        "channel_id": s.channel_id,
+        "hold_new_members": s.hold_new_members,
+        "new_member_minutes": s.new_member_minutes,
+        "hold_links": s.hold_links,
+    })
+}
+
+pub(crate) fn pending_json(p: &moderation_queue::PendingMessageRow) -> serde_json::Value {
+    serde_json::json!({
+        "id": p.id,
+        "pending": true,
+        "channel_id": p.channel_id,
+        "server_id": p.server_id,
+        "author_id": p.author_id,
+        "content": p.content,
+        "reference_message_id": p.reference_message_id,
+        "reference_channel_id": p.reference_channel_id,
+        "flags": p.flags,
+        "attachment_ids": p.attachment_ids,
+        "reason": p.reason,
+        "created_at": p.created_at,
+    })
+}
+
+/// Tell the server's clients a message entered the queue.
+pub(crate) fn notify_queued(state: &AppState, p: &moderation_queue::PendingMessageRow) {
+    let _ = state.gateway_tx.send(GatewayEvent {
+        event_type: event_types::MODERATION_QUEUE_ADD.into(),
+        data: serde_json::json!({
+            "id": p.id,
+            "server_id": p.server_id,
+            "channel_id": p.channel_id,
+            "author_id": p.author_id,
+            "reason": p.reason,
+        }),
+        server_id: Some(p.server_id),
+        channel_id: Some(p.channel_id),
+        user_id: Some(p.author_id),
+        trace_context: None,
+    });
+}
+
+fn notify_resolved(
+    state: &AppState,
+    p: &moderation_queue::PendingMessageRow,
+    status: &str,
+    moderator_id: Uuid,
+    reason: Option<&str>,
+) {
+    let _ = state.gateway_tx.send(GatewayEvent {
+        event_type: event_types::MODERATION_QUEUE_REMOVE.into(),
+        data: serde_json::json!({
+            "id": p.id,
+            "server_id": p.server_id,
+            "channel_id": p.channel_id,
+            "author_id": p.author_id,
+            "status": status,
+            "moderator_id": moderator_id,
+            "reason": reason,
+        }),
+        server_id: Some(p.server_id),
+        channel_id: Some(p.channel_id),
+        user_id: Some(p.author_id),
+        trace_context: None,
+    });
+}
+
+/// Delete the attachments held with a rejected message, rows and stored
+/// files. Ones already linked to a message or uploaded by someone else
+/// are left alone. Best-effort: failures are logged.
+async fn discard_held_attachments(state: &AppState, p: &moderation_queue::PendingMessageRow) {
+    for &id in &p.attachment_ids {
+        let row = match attachments::find_by_id(&state.db.pool, id).await {
+            Ok(Some(row)) if row.uploader_id == p.author_id && row.message_id.is_none() => row,
+            Ok(_) => continue,
+            Err(e) => {
+                tracing::warn!(attachment_id = %id, error = %e, "Failed to look up held attachment");
+                continue;
+            }
+        };
+        if let Err(e) = attachments::delete_attachment(&state.db.pool, id, p.author_id).await {
+            tracing::warn!(attachment_id = %id, error = %e, "Failed to delete held attachment");
+            continue;
+        }
+        for key in std::iter::once(&row.storage_key).chain(&row.thumbnail_key) {
+            if let Err(e) = state.storage.delete_object(key).await {
+                tracing::warn!(key = %key, error = %e, "Failed to delete held upload");
+            }
+        }
+    }
+}
+
+/// Require `permission` in `server_id` (and `channel`, if given).
+async fn require_permission(
+    state: &AppState,
+    server_id: Uuid,
+    channel: Option<&Channel>,
+    user_id: Uuid,
+    permission: Permissions,
+    name: &str,
+) -> NexusResult<()> {
+    let server = servers::find_by_id(&state.db.pool, server_id)
+        .await?
+        .ok_or(NexusError::NotFound {
+            resource: "Server".into(),
+        })?;
+    let permissions = crate::permissions::resolve(&state.db.pool, &server, channel, user_id)
+        .await?
+        .ok_or(NexusError::Forbidden)?;
+    if !permissions.has(permission) {
+        return Err(NexusError::MissingPermission {
+            permission: name.into(),
+        });
+    }
+    Ok(())
+}
+
+/// Load a server channel and require MANAGE_CHANNELS on it.
+async fn managed_channel(state: &AppState, channel_id: Uuid, user_id: Uuid) -> NexusResult<Channel> {
+    let channel = channels::find_by_id(&state.db.pool, channel_id)
+        .await?
+        .ok_or(NexusError::NotFound {
+            resource: "Channel".into(),
+        })?;
+    let server_id = channel.server_id.ok_or(NexusError::Validation {
+        message: "Pre-moderation is only available in server channels".into(),
+    })?;
+    require_permission(
+        state,
+        server_id,
+        Some(&channel),
+        user_id,
+        Permissions::MANAGE_CHANNELS,
+        "MANAGE_CHANNELS",
+    )
+    .await?;
+    Ok(channel)
+}
+
+// ============================================================
+// Settings
+// ============================================================
+
+/// GET /api/v1/channels/:channel_id/moderation
+async fn get_settings(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path(channel_id): Path<Uuid>,
+) -> NexusResult<Json<serde_json::Value>> {
+    let channel = managed_channel(&state, channel_id, auth.user_id).await?;
+    let settings = moderation_queue::get_settings(&state.db.pool, channel.id)
+        .await?
+        .unwrap_or(moderation_queue::ModerationSettingsRow {
+            channel_id: channel.id,
+            hold_new_members: false,
+            new_member_minutes: DEFAULT_NEW_MEMBER_MINUTES,
+            hold_links: false,
+            updated_at: chrono::Utc::now(),
+        });
+    Ok(Json(settings_json(&settings)))
+}
+
+/// PUT /api/v1/channels/:channel_id/moderation
+async fn put_settings(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path(channel_id): Path<Uuid>,
+    Json(body): Json<SettingsRequest>,
+) -> NexusResult<Json<serde_json::Value>> {
+    let channel = managed_channel(&state, channel_id, auth.user_id).await?;
+
+    let new_member_minutes = body.new_member_minutes.unwrap_or(DEFAULT_NEW_MEMBER_MINUTES);
+    if !(0..=MAX_NEW_MEMBER_MINUTES).contains(&new_member_minutes) {
+        return Err(NexusError::Validation {
+            message: format!("new_member_minutes must be between 0 and {MAX_NEW_MEMBER_MINUTES}"),
+        });
+    }
+
+    let saved = moderation_queue::upsert_settings(
+        &state.db.pool,
+        &moderation_queue::ModerationSettingsRow {
+            channel_id: channel.id,
+            hold_new_members: body.hold_new_members,
+            new_member_minutes,
+            hold_links: body.hold_links,
+            updated_at: chrono::Utc::now(),
+        },
+    )
+    .await?;
+
+    Ok(Json(settings_json(&saved)))
+}
+
+// ============================================================
+// Queue
+// ============================================================
+
+/// GET /api/v1/servers/:server_id/moderation-queue?channel_id=&limit=
+async fn list_queue(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path(server_id): Path<Uuid>,
+    Query(params): Query<QueueParams>,
+) -> NexusResult<Json<Vec<serde_json::Value>>> {
+    require_permission(
+        &state,
+        server_id,
+        None,
+        auth.user_id,
+        Permissions::MANAGE_MESSAGES,
+        "MANAGE_MESSAGES",
+    )
+    .await?;
+    let limit = params.limit.unwrap_or(50).clamp(1, 100);
+    let pending =
+        moderation_queue::list_pending(&state.db.pool, server_id, params.channel_id, limit).await?;
+    Ok(Json(pending.iter().map(pending_json).collect()))
+}
+
+/// POST /api/v1/servers/:server_id/moderation-queue/:pending_id/approve
+///
+/// Posts the held message as its author. The message keeps the ID it was
+/// queued under.
+async fn approve(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path((server_id, pending_id)): Path<(Uuid, Uuid)>,
+) -> NexusResult<Json<serde_json::Value>> {
+    require_permission(
+        &state,
+        server_id,
+        None,
+        auth.user_id,
+        Permissions::MANAGE_MESSAGES,
+        "MANAGE_MESSAGES",
+    )
+    .await?;
+
+    let mut tx = state.db.pool.begin().await?;
+    let pending = moderation_queue::take_pending(&mut *tx, server_id, pending_id)
+        .await?
+        .ok_or(NexusError::NotFound {
+            resource: "Pending message".into(),
+        })?;
+    let channel = channels::find_by_id(&state.db.pool, pending.channel_id)
+        .await?
+        .ok_or(NexusError::NotFound {
+            resource: "Channel".into(),
+        })?;
+    let msg = super::messages::insert_message(
+        &mut tx,
+        &channel,
+        pending.author_id,
+        pending.id,
+        &pending.content,
+        (pending.reference_message_id, pending.reference_channel_id),
+        pending.flags,
+        &[],
+        &pending.attachment_ids,
+        &pending.spoiler_attachment_ids,
+    )
+    .await?;
+    tx.commit().await?;
+
+    notify_resolved(&state, &pending, "approved", auth.user_id, None);
+
+    let author_username = users::find_by_id(&state.db.pool, pending.author_id)
+        .await?
+        .map(|u| u.username)
+        .unwrap_or_default();
+    let response = super::messages::publish_message(&state, &channel, &msg, &author_username).await;
+
+    tracing::info!(
+        pending_id = %pending.id,
+        moderator = %auth.user_id,
+        "Held message approved"
+    );
+
+    Ok(Json(response))
+}
+
+/// POST /api/v1/servers/:server_id/moderation-queue/:pending_id/reject
+async fn reject(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path((server_id, pending_id)): Path<(Uuid, Uuid)>,
+    body: Option<Json<RejectRequest>>,
+) -> NexusResult<Json<serde_json::Value>> {
+    require_permission(
+        &state,
+        server_id,
+        None,
+        auth.user_id,
+        Permissions::MANAGE_MESSAGES,
+        "MANAGE_MESSAGES",
+    )
+    .await?;
+
+    let pending = moderation_queue::take_pending(&state.db.pool, server_id, pending_id)
+        .await?
+        .ok_or(NexusError::NotFound {
+            resource: "Pending message".into(),
+        })?;
+
+    discard_held_attachments(&state, &pending).await;
+
+    let reason = body.and_then(|Json(b)| b.reason);
+    notify_resolved(&state, &pending, "rejected", auth.user_id, reason.as_deref());
+
+    tracing::info!(
+        pending_id = %pending.id,
+        moderator = %auth.user_id,
+        "Held message rejected"
+    );
+
+    Ok(Json(serde_json::json!({ "rejected": true })))
+}
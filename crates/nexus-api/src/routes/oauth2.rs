@@ -0,0 +1,493 @@
+//! OAuth2 provider routes (see [`crate::oauth2`]).
+//!
+//! GET  /oauth2/authorize       — What the consent page shows (user auth)
+//! POST /oauth2/authorize       — Grant consent; returns the redirect (user auth)
+//! POST /oauth2/token           — Exchange a code or refresh token (client auth)
+//! POST /oauth2/token/revoke    — Revoke a token pair (client auth)
+//! GET  /oauth2/@me             — The application and user a token is for (access token)
+//! GET  /oauth2/@me/guilds      — The user's servers (access token, `guilds`)
+//!
+//! The token routes take form-encoded bodies and authenticate the client
+//! with HTTP Basic or `client_id` / `client_secret` fields. Their errors
+//! use the RFC 6749 `{"error", "error_description"}` shape.
+
+use axum::{
+    extract::{Extension, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    middleware,
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Form, Json, Router,
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use nexus_common::{
+    error::{NexusError, NexusResult},
+    models::{bot::BotApplication, user::UserResponse},
+    permissions::Permissions,
+};
+use nexus_db::repository::{bots, oauth2 as grants, servers, users};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{
+    middleware::{AuthContext, OAuth2Context},
+    oauth2::{self, scopes},
+    AppState,
+};
+
+/// Consent and token routes.
+pub fn router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/oauth2/authorize", get(authorize_info).post(authorize))
+        .route_layer(middleware::from_fn(crate::middleware::auth_middleware))
+        .route("/oauth2/token", post(token))
+        .route("/oauth2/token/revoke", post(revoke))
+}
+
+/// Routes called with an access token; the caller adds
+/// `oauth2_auth_middleware`.
+pub fn resource_router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/oauth2/@me", get(current_authorization))
+        .route("/oauth2/@me/guilds", get(authorized_guilds))
+}
+
+// ============================================================================
+// Authorization
+// ============================================================================
+
+#[derive(Deserialize)]
+struct AuthorizeQuery {
+    client_id: Uuid,
+    redirect_uri: String,
+    scope: String,
+    response_type: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct AuthorizeBody {
+    client_id: Uuid,
+    redirect_uri: String,
+    scope: String,
+    state: Option<String>,
+    code_challenge: Option<String>,
+    code_challenge_method: Option<String>,
+    /// Where to add the bot, with the `bot` scope.
+    server_id: Option<Uuid>,
+    /// Bot permissions; defaults to what the application asks for.
+    permissions: Option<i64>,
+}
+
+#[derive(Serialize)]
+struct AuthorizeResponse {
+    /// The client's `redirect_uri` with `code` (for user scopes) and `state`.
+    location: String,
+}
+
+/// Check an authorization request: the application exists, the redirect
+/// URI is one it registered, and the user may grant the scopes.
+async fn check_request(
+    state: &AppState,
+    user_id: Uuid,
+    client_id: Uuid,
+    redirect_uri: &str,
+    scope: &str,
+) -> NexusResult<(BotApplication, Vec<String>)> {
+    let app = bots::get_bot(&state.db.pool, client_id)
+        .await?
+        .ok_or(NexusError::NotFound { resource: "application".to_string() })?;
+    if !app.redirect_uris.iter().any(|uri| uri == redirect_uri) {
+        return Err(NexusError::Validation {
+            message: "redirect_uri is not registered for this application".into(),
+        });
+    }
+    let scopes = oauth2::parse_scopes(scope)?;
+    if scopes.iter().any(|s| s == scopes::BOT) && !app.is_public && app.owner_id != user_id {
+        return Err(NexusError::Forbidden);
+    }
+    Ok((app, scopes))
+}
+
+/// GET /api/v1/oauth2/authorize — The application and scopes to show on
+/// the consent page.
+async fn authorize_info(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<AuthorizeQuery>,
+) -> NexusResult<Json<serde_json::Value>> {
+    if params.response_type.as_deref().is_some_and(|t| t != "code") {
+        return Err(NexusError::Validation {
+            message: "response_type must be 'code'".into(),
+        });
+    }
+    let (app, scopes) =
+        check_request(&state, auth.user_id, params.client_id, &params.redirect_uri, &params.scope)
+            .await?;
+    Ok(Json(serde_json::json!({
+        "application": application_summary(&app),
+        "scopes": scopes,
+        "redirect_uri": params.redirect_uri,
+        "bot_permissions": app.permissions,
+    })))
+}
+
+/// POST /api/v1/oauth2/authorize — The user consents. With the `bot`
+/// scope the bot is added to `server_id` (MANAGE_SERVER there); with user
+/// scopes a code is issued.
+async fn authorize(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Json(body): Json<AuthorizeBody>,
+) -> NexusResult<Json<AuthorizeResponse>> {
+    let (app, scopes) =
+        check_request(&state, auth.user_id, body.client_id, &body.redirect_uri, &body.scope)
+            .await?;
+    if body.code_challenge.is_some() && body.code_challenge_method.as_deref() != Some("S256") {
+        return Err(NexusError::Validation {
+            message: "code_challenge_method must be 'S256'".into(),
+        });
+    }
+
+    if scopes.iter().any(|s| s == scopes::BOT) {
+        let server_id = body.server_id.ok_or(NexusError::Validation {
+            message: "server_id is required with the bot scope".into(),
+        })?;
+        let server = servers::find_by_id(&state.db.pool, server_id)
+            .await?
+            .ok_or(NexusError::NotFound { resource: "Server".into() })?;
+        let permissions = crate::permissions::resolve(&state.db.pool, &server, None, auth.user_id)
+            .await?
+            .ok_or(NexusError::Forbidden)?;
+        crate::permissions::require(permissions, Permissions::MANAGE_SERVER)?;
+        bots::install_bot_to_server(
+            &state.db.pool,
+            app.id,
+            server.id,
+            auth.user_id,
+            &scopes,
+            body.permissions.unwrap_or(app.permissions),
+        )
+        .await?;
+    }
+
+    let code = if oauth2::has_user_scopes(&scopes) {
+        let code = oauth2::generate_token();
+        grants::create_code(
+            &state.db.pool,
+            &oauth2::hash_token(&code),
+            app.id,
+            auth.user_id,
+            &body.redirect_uri,
+            &scopes,
+            body.code_challenge.as_deref(),
+            chrono::Utc::now() + chrono::Duration::seconds(oauth2::CODE_TTL_SECS),
+        )
+        .await?;
+        Some(code)
+    } else {
+        None
+    };
+
+    let location =
+        oauth2::redirect_location(&body.redirect_uri, code.as_deref(), body.state.as_deref())?;
+    Ok(Json(AuthorizeResponse { location }))
+}
+
+// ============================================================================
+// Token endpoint
+// ============================================================================
+
+/// A token endpoint error (RFC 6749 §5.2).
+struct TokenError {
+    status: StatusCode,
+    error: &'static str,
+    description: &'static str,
+}
+
+impl TokenError {
+    fn invalid_request(description: &'static str) -> Self {
+        Self { status: StatusCode::BAD_REQUEST, error: "invalid_request", description }
+    }
+
+    fn invalid_client() -> Self {
+        Self {
+            status: StatusCode::UNAUTHORIZED,
+            error: "invalid_client",
+            description: "Client authentication failed",
+        }
+    }
+
+    fn invalid_grant(description: &'static str) -> Self {
+        Self { status: StatusCode::BAD_REQUEST, error: "invalid_grant", description }
+    }
+}
+
+impl From<NexusError> for TokenError {
+    fn from(e: NexusError) -> Self {
+        tracing::error!(error = %e, "OAuth2 token request failed");
+        Self {
+            status: StatusCode::INTERNAL_SERVER_ERROR,
+            error: "server_error",
+            description: "An internal error occurred",
+        }
+    }
+}
+
+impl From<sqlx::Error> for TokenError {
+    fn from(e: sqlx::Error) -> Self {
+        NexusError::from(e).into()
+    }
+}
+
+impl IntoResponse for TokenError {
+    fn into_response(self) -> Response {
+        let body = Json(serde_json::json!({
+            "error": self.error,
+            "error_description": self.description,
+        }));
+        (self.status, [(header::CACHE_CONTROL, "no-store")], body).into_response()
+    }
+}
+
+#[derive(Deserialize)]
+struct TokenRequest {
+    grant_type: String,
+    code: Option<String>,
+    redirect_uri: Option<String>,
+    code_verifier: Option<String>,
+    refresh_token: Option<String>,
+    client_id: Option<String>,
+    client_secret: Option<String>,
+}
+
+#[derive(Serialize)]
+struct TokenResponse {
+    access_token: String,
+    token_type: &'static str,
+    expires_in: i64,
+    refresh_token: String,
+    scope: String,
+}
+
+#[derive(Deserialize)]
+struct RevokeRequest {
+    token: String,
+    client_id: Option<String>,
+    client_secret: Option<String>,
+}
+
+/// The client authenticating a token request, from HTTP Basic or the body.
+async fn authenticate_client(
+    state: &AppState,
+    headers: &HeaderMap,
+    client_id: Option<&str>,
+    client_secret: Option<&str>,
+) -> Result<Uuid, TokenError> {
+    let basic = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Basic "))
+        .and_then(|v| STANDARD.decode(v).ok())
+        .and_then(|v| String::from_utf8(v).ok());
+    let (id, secret) = match &basic {
+        Some(credentials) => credentials.split_once(':').ok_or_else(TokenError::invalid_client)?,
+        None => (
+            client_id.ok_or_else(TokenError::invalid_client)?,
+            client_secret.ok_or_else(TokenError::invalid_client)?,
+        ),
+    };
+    let id: Uuid = id.parse().map_err(|_| TokenError::invalid_client())?;
+
+    let stored = bots::get_client_secret_hash(&state.db.pool, id).await.map_err(NexusError::from)?;
+    if stored.as_deref() != Some(oauth2::hash_token(secret).as_str()) {
+        return Err(TokenError::invalid_client());
+    }
+    Ok(id)
+}
+
+/// Issue a new token pair for `user_id`.
+async fn issue(
+    state: &AppState,
+    application_id: Uuid,
+    user_id: Uuid,
+    scopes: &[String],
+) -> Result<TokenResponse, TokenError> {
+    let access_token = oauth2::generate_token();
+    let refresh_token = oauth2::generate_token();
+    grants::create_token(
+        &state.db.pool,
+        Uuid::new_v4(),
+        application_id,
+        user_id,
+        &oauth2::hash_token(&access_token),
+        &oauth2::hash_token(&refresh_token),
+        scopes,
+        chrono::Utc::now() + chrono::Duration::seconds(oauth2::ACCESS_TOKEN_TTL_SECS),
+    )
+    .await?;
+    Ok(TokenResponse {
+        access_token,
+        token_type: "Bearer",
+        expires_in: oauth2::ACCESS_TOKEN_TTL_SECS,
+        refresh_token,
+        scope: scopes.join(" "),
+    })
+}
+
+/// POST /api/v1/oauth2/token — `authorization_code` and `refresh_token`
+/// grants. Refreshing replaces both tokens.
+async fn token(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Form(body): Form<TokenRequest>,
+) -> Result<Response, TokenError> {
+    let client_id = authenticate_client(
+        &state,
+        &headers,
+        body.client_id.as_deref(),
+        body.client_secret.as_deref(),
+    )
+    .await?;
+
+    let response = match body.grant_type.as_str() {
+        "authorization_code" => {
+            let code = body.code.as_deref().ok_or(TokenError::invalid_request("code is required"))?;
+            let grant = grants::take_code(&state.db.pool, &oauth2::hash_token(code))
+                .await?
+                .filter(|grant| grant.application_id == client_id)
+                .filter(|grant| grant.expires_at > chrono::Utc::now())
+                .ok_or(TokenError::invalid_grant("Invalid or expired code"))?;
+            if body.redirect_uri.as_deref() != Some(grant.redirect_uri.as_str()) {
+                return Err(TokenError::invalid_grant("redirect_uri does not match"));
+            }
+            if let Some(challenge) = &grant.code_challenge {
+                let verifier = body.code_verifier.as_deref().unwrap_or_default();
+                if !oauth2::verify_pkce(challenge, verifier) {
+                    return Err(TokenError::invalid_grant("code_verifier does not match"));
+                }
+            }
+            issue(&state, client_id, grant.user_id, &grant.scopes).await?
+        }
+        "refresh_token" => {
+            let refresh_token = body
+                .refresh_token
+                .as_deref()
+                .ok_or(TokenError::invalid_request("refresh_token is required"))?;
+            let access_token = oauth2::generate_token();
+            let new_refresh_token = oauth2::generate_token();
+            let grant = grants::rotate(
+                &state.db.pool,
+                client_id,
+                &oauth2::hash_token(refresh_token),
+                &oauth2::hash_token(&access_token),
+                &oauth2::hash_token(&new_refresh_token),
+                chrono::Utc::now() + chrono::Duration::seconds(oauth2::ACCESS_TOKEN_TTL_SECS),
+            )
+            .await?
+            .ok_or(TokenError::invalid_grant("Invalid refresh token"))?;
+            TokenResponse {
+                access_token,
+                token_type: "Bearer",
+                expires_in: oauth2::ACCESS_TOKEN_TTL_SECS,
+                refresh_token: new_refresh_token,
+                scope: grant.scopes.join(" "),
+            }
+        }
+        _ => {
+            return Err(TokenError {
+                status: StatusCode::BAD_REQUEST,
+                error: "unsupported_grant_type",
+                description: "grant_type must be authorization_code or refresh_token",
+            });
+        }
+    };
+
+    Ok(([(header::CACHE_CONTROL, "no-store")], Json(response)).into_response())
+}
+
+/// POST /api/v1/oauth2/token/revoke — Revoke the pair an access or refresh
+/// token belongs to. Unknown tokens succeed too (RFC 7009 §2.2).
+async fn revoke(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Form(body): Form<RevokeRequest>,
+) -> Result<StatusCode, TokenError> {
+    let client_id = authenticate_client(
+        &state,
+        &headers,
+        body.client_id.as_deref(),
+        body.client_secret.as_deref(),
+    )
+    .await?;
+    grants::revoke(&state.db.pool, client_id, &oauth2::hash_token(&body.token)).await?;
+    Ok(StatusCode::OK)
+}
+
+// ============================================================================
+// Resources
+// ============================================================================
+
+fn application_summary(app: &BotApplication) -> serde_json::Value {
+    serde_json::json!({
+        "id": app.id,
+        "name": app.name,
+        "description": app.description,
+        "avatar": app.avatar,
+        "verified": app.verified,
+    })
+}
+
+/// GET /api/v1/oauth2/@me — The application, granted scopes and, with
+/// `identify`, the user (email only with `email`).
+async fn current_authorization(
+    Extension(grant): Extension<OAuth2Context>,
+    State(state): State<Arc<AppState>>,
+) -> NexusResult<Json<serde_json::Value>> {
+    let app = bots::get_bot(&state.db.pool, grant.application_id)
+        .await?
+        .ok_or(NexusError::NotFound { resource: "application".to_string() })?;
+
+    let user = if grant.require(scopes::IDENTIFY).is_ok() {
+        let user = users::find_by_id(&state.db.pool, grant.user_id)
+            .await?
+            .ok_or(NexusError::NotFound { resource: "User".into() })?;
+        let email = user.email.clone().filter(|_| grant.require(scopes::EMAIL).is_ok());
+        let mut profile = serde_json::to_value(UserResponse::from(user))
+            .map_err(|e| NexusError::Internal(e.into()))?;
+        if let Some(email) = email {
+            profile["email"] = email.into();
+        }
+        Some(profile)
+    } else {
+        None
+    };
+
+    Ok(Json(serde_json::json!({
+        "application": application_summary(&app),
+        "scopes": grant.scopes,
+        "user": user,
+    })))
+}
+
+/// GET /api/v1/oauth2/@me/guilds — The servers the user is in (`guilds`).
+async fn authorized_guilds(
+    Extension(grant): Extension<OAuth2Context>,
+    State(state): State<Arc<AppState>>,
+) -> NexusResult<Json<Vec<serde_json::Value>>> {
+    grant.require(scopes::GUILDS)?;
+    let servers = servers::list_user_servers(&state.db.pool, grant.user_id).await?;
+    Ok(Json(
+        servers
+            .iter()
+            .map(|server| {
+                serde_json::json!({
+                    "id": server.id,
+                    "name": server.name,
+                    "icon": server.icon,
+                    "owner": server.owner_id == grant.user_id,
+                })
+            })
+            .collect(),
+    ))
+}
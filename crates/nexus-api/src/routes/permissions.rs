@@ -0,0 +1,107 @@
+//! Permission routes — the permission catalog and a per-member breakdown.
+//!
+//! GET /permissions                                              — Every permission name and bit
+//! GET /servers/:id/members/:user_id/permissions?channel_id=    — Explain a member's permissions
+//!
+//! The breakdown lists each bit with the role or channel overwrite that
+//! granted or denied it. Members can inspect themselves; inspecting someone
+//! else needs MANAGE_ROLES.
+
+use axum::{
+    extract::{Extension, Path, Query, State},
+    middleware,
+    routing::get,
+    Json, Router,
+};
+use nexus_common::{
+    error::{NexusError, NexusResult},
+    permissions::Permissions,
+};
+use nexus_db::repository::{channels, members, roles, servers};
+use serde::Deserialize;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{middleware::AuthContext, AppState};
+
+pub fn router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/permissions", get(catalog))
+        .route(
+            "/servers/{server_id}/members/{user_id}/permissions",
+            get(explain_member),
+        )
+        .route_layer(middleware::from_fn(crate::middleware::auth_middleware))
+}
+
+#[derive(Debug, Deserialize)]
+struct ExplainParams {
+    /// Include this channel's overwrites.
+    channel_id: Option<Uuid>,
+}
+
+/// GET /api/v1/permissions
+async fn catalog() -> Json<serde_json::Value> {
+    let permissions: Vec<serde_json::Value> = Permissions::catalog()
+        .into_iter()
+        .map(|(name, bit)| serde_json::json!({ "name": name, "value": bit }))
+        .collect();
+    Json(serde_json::json!({
+        "permissions": permissions,
+        "default_everyone": Permissions::default_everyone().bits(),
+    }))
+}
+
+/// GET /api/v1/servers/:server_id/members/:user_id/permissions
+async fn explain_member(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path((server_id, user_id)): Path<(Uuid, Uuid)>,
+    Query(params): Query<ExplainParams>,
+) -> NexusResult<Json<serde_json::Value>> {
+    let pool = &state.db.pool;
+    let server = servers::find_by_id(pool, server_id)
+        .await?
+        .ok_or(NexusError::NotFound {
+            resource: "Server".into(),
+        })?;
+
+    if user_id != auth.user_id {
+        let permissions = crate::permissions::resolve(pool, &server, None, auth.user_id)
+            .await?
+            .ok_or(NexusError::Forbidden)?;
+        crate::permissions::require(permissions, Permissions::MANAGE_ROLES)?;
+    }
+
+    let member = members::find_member(pool, user_id, server_id)
+        .await?
+        .ok_or(NexusError::NotFound {
+            resource: "Member".into(),
+        })?;
+    let channel = match params.channel_id {
+        Some(channel_id) => Some(
+            channels::find_by_id(pool, channel_id)
+                .await?
+                .filter(|c| c.server_id == Some(server_id))
+                .ok_or(NexusError::NotFound {
+                    resource: "Channel".into(),
+                })?,
+        ),
+        None => None,
+    };
+
+    let server_roles = roles::list_server_roles(pool, server_id).await?;
+    let effective =
+        crate::permissions::for_member(&server, channel.as_ref(), &member, &server_roles);
+    let bits =
+        crate::permissions::explain_for_member(&server, channel.as_ref(), &member, &server_roles);
+
+    Ok(Json(serde_json::json!({
+        "server_id": server_id,
+        "user_id": user_id,
+        "channel_id": params.channel_id,
+        "permissions": effective.bits(),
+        "names": effective.names(),
+        "bits": bits,
+    })))
+}
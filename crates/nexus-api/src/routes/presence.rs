@@ -1,7 +1,17 @@
 //! Enhanced presence routes — rich status, activity, custom emoji.
 //!
-//! POST /users/@me/presence — Update presence, custom status, and activity
-//! GET  /users/:id/presence — Get a user's public presence
+//! POST /users/@me/presence      — Update presence, custom status, and activity
+//! GET  /users/:id/presence      — Get a user's public presence
+//!
+//! Presence itself is aggregated across the user's gateway sessions by
+//! [`nexus_db::presence`]; setting it here applies to all of them.
+//! POST /users/:id/activity/join — Join a user's game party
+//!
+//! Joining: a game reports a join secret with its activity. Another user
+//! who shares a server asks to join, and receives the secret in an
+//! `ACTIVITY_JOIN` event; their desktop client hands it to the game over
+//! local RPC. The host gets an `ACTIVITY_JOIN_REQUEST` so their game can
+//! show who is coming. Secrets never appear in presence payloads.
 
 use axum::{
     extract::{Extension, Path, State},
@@ -11,11 +21,11 @@ use axum::{
 };
 use nexus_common::{
     error::{NexusError, NexusResult},
-    models::rich::UpdatePresenceRequest,
+    models::rich::{ActivityParty, UpdatePresenceRequest, MAX_ACTIVITY_SECRET_LEN},
     validation::validate_request,
 };
-use nexus_db::repository::users;
-use nexus_common::gateway_event::GatewayEvent;
+use nexus_db::repository::{members, users};
+use nexus_common::gateway_event::{event_types, GatewayEvent};
 use serde::Serialize;
 use std::sync::Arc;
 use uuid::Uuid;
@@ -35,12 +45,30 @@ struct UserActivityRow {
     url: Option<String>,
     large_image: Option<String>,
     small_image: Option<String>,
+    party_id: Option<String>,
+    party_size: Option<i32>,
+    party_max: Option<i32>,
+    join_secret: Option<String>,
+}
+
+impl UserActivityRow {
+    fn party(&self) -> Option<ActivityParty> {
+        if self.party_id.is_none() && self.party_size.is_none() && self.party_max.is_none() {
+            return None;
+        }
+        Some(ActivityParty {
+            id: self.party_id.clone(),
+            size: self.party_size,
+            max: self.party_max,
+        })
+    }
 }
 
 pub fn router() -> Router<Arc<AppState>> {
     Router::new()
         .route("/users/@me/presence", post(update_presence))
         .route("/users/{user_id}/presence", get(get_user_presence))
+        .route("/users/{user_id}/activity/join", post(join_activity))
         .route_layer(middleware::from_fn(crate::middleware::auth_middleware))
 }
 
@@ -66,6 +94,9 @@ struct ActivityResponse {
     url: Option<String>,
     large_image: Option<String>,
     small_image: Option<String>,
+    party: Option<ActivityParty>,
+    /// Whether others can join through `/users/:id/activity/join`.
+    joinable: bool,
 }
 
 // ============================================================
@@ -78,25 +109,38 @@ async fn update_presence(
     Json(body): Json<UpdatePresenceRequest>,
 ) -> NexusResult<Json<PresenceResponse>> {
     validate_request(&body)?;
+    let join_secret = body
+        .activity
+        .as_ref()
+        .and_then(|a| a.secrets.as_ref())
+        .and_then(|s| s.join.as_deref());
+    if join_secret.is_some_and(|s| s.is_empty() || s.len() > MAX_ACTIVITY_SECRET_LEN) {
+        return Err(NexusError::Validation {
+            message: format!("Join secret must be 1-{MAX_ACTIVITY_SECRET_LEN} characters"),
+        });
+    }
+
+    // Presence applies to every connected session; the stored copy
+    // follows the aggregate.
+    if let Some(presence) = body.presence
+        && let Some(aggregate) = state.presence.set_all(auth.user_id, presence).await
+    {
+        users::update_presence(&state.db.pool, auth.user_id, aggregate.as_str()).await?;
+    }
 
-    // Update presence + status in the users table
-    if body.presence.is_some() || body.status.is_some() || body.custom_status_emoji.is_some() {
-        // Use string-based presence cast to avoid compile-time type checking
-        let presence_str = body.presence.map(|p| format!("{:?}", p).to_lowercase());
-        let presence_str = presence_str.as_deref();
+    // Update status in the users table
+    if body.status.is_some() || body.custom_status_emoji.is_some() {
         sqlx::query(
             r#"
             UPDATE users
             SET
-                presence = COALESCE(CAST($2 AS user_presence), presence),
-                status = COALESCE($3, status),
-                custom_status_emoji = COALESCE($4, custom_status_emoji),
+                status = COALESCE($2, status),
+                custom_status_emoji = COALESCE($3, custom_status_emoji),
                 updated_at = NOW()
             WHERE id = $1
             "#,
         )
         .bind(auth.user_id.to_string())
-        .bind(presence_str)
         .bind(body.status.as_deref())
         .bind(body.custom_status_emoji.as_deref())
         .execute(&state.db.pool)
@@ -110,9 +154,10 @@ async fn update_presence(
             INSERT INTO user_activities (
                 user_id, activity_type, name, details,
                 state, url, large_image, small_image,
+                party_id, party_size, party_max, join_secret,
                 started_at, updated_at
             )
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, NOW(), NOW())
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, NOW(), NOW())
             ON CONFLICT (user_id) DO UPDATE SET
                 activity_type = EXCLUDED.activity_type,
                 name = EXCLUDED.name,
@@ -121,6 +166,10 @@ async fn update_presence(
                 url = EXCLUDED.url,
                 large_image = EXCLUDED.large_image,
                 small_image = EXCLUDED.small_image,
+                party_id = EXCLUDED.party_id,
+                party_size = EXCLUDED.party_size,
+                party_max = EXCLUDED.party_max,
+                join_secret = EXCLUDED.join_secret,
                 updated_at = NOW()
             "#,
         )
@@ -132,6 +181,10 @@ async fn update_presence(
         .bind(act.url.as_deref())
         .bind(act.large_image.as_deref())
         .bind(act.small_image.as_deref())
+        .bind(act.party.as_ref().and_then(|p| p.id.as_deref()))
+        .bind(act.party.as_ref().and_then(|p| p.size))
+        .bind(act.party.as_ref().and_then(|p| p.max))
+        .bind(join_secret)
         .execute(&state.db.pool)
         .await?;
 
@@ -143,6 +196,8 @@ async fn update_presence(
             url: act.url.clone(),
             large_image: act.large_image.clone(),
             small_image: act.small_image.clone(),
+            party: act.party.clone(),
+            joinable: join_secret.is_some(),
         })
     } else {
         None
@@ -162,13 +217,14 @@ async fn update_presence(
     .fetch_optional(&state.db.pool)
     .await?
     .and_then(|r| r.custom_status_emoji);
+    let presence = state.presence.get(auth.user_id).await;
 
     // Broadcast presence update to gateway
     let _ = state.gateway_tx.send(GatewayEvent {
         event_type: "PRESENCE_UPDATE".into(),
         data: serde_json::json!({
             "user_id": auth.user_id,
-            "presence": user.presence,
+            "presence": presence,
             "status": user.status,
             "custom_status_emoji": custom_emoji,
             "activity": activity_resp.as_ref().map(|a| serde_json::json!({
@@ -176,16 +232,19 @@ async fn update_presence(
                 "name": a.name,
                 "details": a.details,
                 "state": a.state,
+                "party": a.party,
+                "joinable": a.joinable,
             }))
         }),
         server_id: None,
         channel_id: None,
         user_id: Some(auth.user_id),
+        trace_context: None,
     });
 
     Ok(Json(PresenceResponse {
         user_id: auth.user_id,
-        presence: user.presence,
+        presence,
         status: user.status,
         custom_status_emoji: custom_emoji,
         activity: activity_resp,
@@ -201,13 +260,14 @@ async fn get_user_presence(
     State(state): State<Arc<AppState>>,
     Path(user_id): Path<Uuid>,
 ) -> NexusResult<Json<PresenceResponse>> {
-    let _ = auth;
-
     let user = users::find_by_id(&state.db.pool, user_id)
         .await?
         .ok_or(NexusError::NotFound {
             resource: "User".into(),
         })?;
+    // Live across all sessions; invisible shows as offline to others.
+    let presence = state.presence.get(user_id).await;
+    let presence = if user_id == auth.user_id { presence } else { presence.public() };
 
     let custom_emoji = sqlx::query_as::<_, UserCustomEmojiRow>(
         "SELECT custom_status_emoji FROM users WHERE id = $1",
@@ -219,7 +279,8 @@ async fn get_user_presence(
 
     let activity_resp = sqlx::query_as::<_, UserActivityRow>(
         r#"
-        SELECT activity_type, name, details, state, url, large_image, small_image
+        SELECT activity_type, name, details, state, url, large_image, small_image,
+               party_id, party_size, party_max, join_secret
         FROM user_activities
         WHERE user_id = $1
         "#,
@@ -228,6 +289,8 @@ async fn get_user_presence(
     .fetch_optional(&state.db.pool)
     .await?
     .map(|r| ActivityResponse {
+        party: r.party(),
+        joinable: r.join_secret.is_some(),
         activity_type: r.activity_type,
         name: r.name,
         details: r.details,
@@ -239,9 +302,84 @@ async fn get_user_presence(
 
     Ok(Json(PresenceResponse {
         user_id: user.id,
-        presence: user.presence,
+        presence,
         status: user.status,
         custom_status_emoji: custom_emoji,
         activity: activity_resp,
     }))
 }
+
+// ============================================================
+// POST /users/:user_id/activity/join
+// ============================================================
+
+async fn join_activity(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path(user_id): Path<Uuid>,
+) -> NexusResult<Json<serde_json::Value>> {
+    if user_id == auth.user_id {
+        return Err(NexusError::Validation {
+            message: "You cannot join your own activity".into(),
+        });
+    }
+    // Only people who could already see the host's presence may join.
+    if !members::share_server(&state.db.pool, auth.user_id, user_id).await? {
+        return Err(NexusError::Forbidden);
+    }
+
+    let activity = sqlx::query_as::<_, UserActivityRow>(
+        r#"
+        SELECT activity_type, name, details, state, url, large_image, small_image,
+               party_id, party_size, party_max, join_secret
+        FROM user_activities
+        WHERE user_id = $1
+        "#,
+    )
+    .bind(user_id.to_string())
+    .fetch_optional(&state.db.pool)
+    .await?
+    .ok_or(NexusError::NotFound {
+        resource: "Activity".into(),
+    })?;
+    let Some(secret) = activity.join_secret.clone() else {
+        return Err(NexusError::Validation {
+            message: "This activity cannot be joined".into(),
+        });
+    };
+    let party = activity.party();
+    if party.as_ref().is_some_and(ActivityParty::is_full) {
+        return Err(NexusError::Validation {
+            message: "This party is full".into(),
+        });
+    }
+
+    // Host first, so their game can make room before the joiner arrives.
+    let _ = state.gateway_tx.send(GatewayEvent {
+        event_type: event_types::ACTIVITY_JOIN_REQUEST.into(),
+        data: serde_json::json!({
+            "user_id": auth.user_id,
+            "username": auth.username,
+            "party_id": activity.party_id,
+        }),
+        server_id: None,
+        channel_id: None,
+        user_id: Some(user_id),
+        trace_context: None,
+    });
+    let _ = state.gateway_tx.send(GatewayEvent {
+        event_type: event_types::ACTIVITY_JOIN.into(),
+        data: serde_json::json!({
+            "host_id": user_id,
+            "name": activity.name,
+            "party": party,
+            "secret": secret,
+        }),
+        server_id: None,
+        channel_id: None,
+        user_id: Some(auth.user_id),
+        trace_context: None,
+    });
+
+    Ok(Json(serde_json::json!({ "requested": true })))
+}
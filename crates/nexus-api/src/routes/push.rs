@@ -0,0 +1,165 @@
+//! Push notification routes — device registration and preferences.
+//!
+//! GET    /push/vapid-key                      — VAPID public key for browser subscriptions
+//! GET    /users/@me/push-tokens               — List registered devices
+//! POST   /users/@me/push-tokens               — Register a device
+//! DELETE /users/@me/push-tokens/:token_id     — Unregister a device
+//! GET    /users/@me/push-preferences          — Get push preferences
+//! PATCH  /users/@me/push-preferences          — Update push preferences
+//!
+//! Delivery happens in the push job (see [`crate::jobs::push`]).
+
+use axum::{
+    extract::{Extension, Path, State},
+    http::StatusCode,
+    middleware,
+    routing::{delete, get},
+    Json, Router,
+};
+use nexus_common::{
+    error::{NexusError, NexusResult},
+    models::push::{
+        PushPlatform, PushPreferences, PushToken, RegisterPushTokenRequest,
+        UpdatePushPreferencesRequest,
+    },
+    snowflake,
+    validation::validate_request,
+};
+use nexus_db::repository::push;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{middleware::AuthContext, AppState};
+
+/// Most devices one user may register.
+const MAX_TOKENS_PER_USER: usize = 25;
+
+pub fn router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/push/vapid-key", get(get_vapid_key))
+        .route(
+            "/users/@me/push-tokens",
+            get(list_push_tokens).post(register_push_token),
+        )
+        .route("/users/@me/push-tokens/{token_id}", delete(delete_push_token))
+        .route(
+            "/users/@me/push-preferences",
+            get(get_push_preferences).patch(update_push_preferences),
+        )
+        .route_layer(middleware::from_fn(crate::middleware::auth_middleware))
+}
+
+/// GET /api/v1/push/vapid-key — `applicationServerKey` for
+/// `PushManager.subscribe()`; `null` when WebPush isn't configured.
+async fn get_vapid_key() -> Json<serde_json::Value> {
+    let cfg = &nexus_common::config::get().push;
+    let key = (cfg.enabled && !cfg.vapid_public_key.is_empty()).then_some(&cfg.vapid_public_key);
+    Json(serde_json::json!({ "public_key": key }))
+}
+
+/// GET /api/v1/users/@me/push-tokens
+async fn list_push_tokens(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+) -> NexusResult<Json<Vec<PushToken>>> {
+    Ok(Json(push::list_tokens(&state.db.pool, auth.user_id).await?))
+}
+
+/// POST /api/v1/users/@me/push-tokens — Register (or refresh) a device.
+async fn register_push_token(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Json(body): Json<RegisterPushTokenRequest>,
+) -> NexusResult<Json<PushToken>> {
+    validate_request(&body)?;
+
+    let (p256dh, key_auth) = match (body.platform, &body.keys) {
+        (PushPlatform::WebPush, Some(keys)) => {
+            if !body.token.starts_with("https://") {
+                return Err(NexusError::Validation {
+                    message: "WebPush token must be the subscription's https endpoint".into(),
+                });
+            }
+            if !crate::push::valid_web_push_keys(&keys.p256dh, &keys.auth) {
+                return Err(NexusError::Validation {
+                    message: "Invalid WebPush subscription keys".into(),
+                });
+            }
+            (Some(keys.p256dh.as_str()), Some(keys.auth.as_str()))
+        }
+        (PushPlatform::WebPush, None) => {
+            return Err(NexusError::Validation {
+                message: "WebPush subscriptions require keys.p256dh and keys.auth".into(),
+            });
+        }
+        _ => (None, None),
+    };
+
+    let existing = push::list_tokens(&state.db.pool, auth.user_id).await?;
+    let refreshing = existing
+        .iter()
+        .any(|t| t.platform == body.platform && t.token == body.token);
+    if !refreshing && existing.len() >= MAX_TOKENS_PER_USER {
+        return Err(NexusError::LimitReached {
+            message: format!("At most {MAX_TOKENS_PER_USER} devices can receive push notifications"),
+        });
+    }
+
+    let token = push::upsert_token(
+        &state.db.pool,
+        snowflake::generate_id(),
+        auth.user_id,
+        body.platform,
+        &body.token,
+        p256dh,
+        key_auth,
+        body.device_name.as_deref(),
+    )
+    .await?;
+    Ok(Json(token))
+}
+
+/// DELETE /api/v1/users/@me/push-tokens/:token_id
+async fn delete_push_token(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path(token_id): Path<Uuid>,
+) -> NexusResult<StatusCode> {
+    if !push::delete_token(&state.db.pool, auth.user_id, token_id).await? {
+        return Err(NexusError::NotFound {
+            resource: "Push token".into(),
+        });
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// GET /api/v1/users/@me/push-preferences
+async fn get_push_preferences(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+) -> NexusResult<Json<PushPreferences>> {
+    Ok(Json(push::get_preferences(&state.db.pool, auth.user_id).await?))
+}
+
+/// PATCH /api/v1/users/@me/push-preferences
+async fn update_push_preferences(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Json(body): Json<UpdatePushPreferencesRequest>,
+) -> NexusResult<Json<PushPreferences>> {
+    let mut prefs = push::get_preferences(&state.db.pool, auth.user_id).await?;
+    if let Some(v) = body.enabled {
+        prefs.enabled = v;
+    }
+    if let Some(v) = body.direct_messages {
+        prefs.direct_messages = v;
+    }
+    if let Some(v) = body.mentions {
+        prefs.mentions = v;
+    }
+    if let Some(v) = body.show_content {
+        prefs.show_content = v;
+    }
+    push::set_preferences(&state.db.pool, auth.user_id, &prefs).await?;
+    Ok(Json(prefs))
+}
@@ -0,0 +1,259 @@
+//! Registration management — mode, invite tokens and the approval queue.
+//! Staff only (see [`admin`](super::admin)).
+//!
+//! GET    /admin/registration                                 — Current mode
+//! PUT    /admin/registration                                 — Change the mode
+//! GET    /admin/registration/tokens                          — All tokens
+//! POST   /admin/registration/tokens                          — Issue a token
+//! DELETE /admin/registration/tokens/{token_id}               — Revoke a token
+//! GET    /admin/registration/applications                    — Pending accounts
+//! POST   /admin/registration/applications/{user_id}/approve  — Let one in
+//! DELETE /admin/registration/applications/{user_id}          — Reject (deletes the account)
+//!
+//! Which of these matter depends on the mode; see [`crate::registration`].
+//! A mode set here overrides `registration.mode` until it is set back to
+//! `null`, and survives restarts.
+
+use axum::{
+    extract::{Extension, Path, State},
+    http::StatusCode,
+    routing::{delete, get, post},
+    Json, Router,
+};
+use chrono::{DateTime, Duration, Utc};
+use nexus_common::{
+    config::RegistrationMode,
+    error::{NexusError, NexusResult},
+};
+use nexus_db::repository::{instance_settings, registration};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{middleware::AuthContext, AppState};
+
+/// Longest `expires_in_secs` accepted for a token.
+const MAX_TOKEN_LIFETIME_SECS: u64 = 365 * 24 * 3600;
+
+/// Routes for the staff-only group built in [`admin::router`](super::admin::router).
+pub fn router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/admin/registration", get(get_mode).put(set_mode))
+        .route("/admin/registration/tokens", get(list_tokens).post(create_token))
+        .route("/admin/registration/tokens/{token_id}", delete(delete_token))
+        .route("/admin/registration/applications", get(list_applications))
+        .route(
+            "/admin/registration/applications/{user_id}/approve",
+            post(approve_application),
+        )
+        .route("/admin/registration/applications/{user_id}", delete(reject_application))
+}
+
+#[derive(Serialize)]
+struct ModeResponse {
+    /// The mode in effect.
+    mode: RegistrationMode,
+    /// `registration.mode` from the config.
+    configured_mode: RegistrationMode,
+    /// Whether staff have overridden the config.
+    overridden: bool,
+}
+
+#[derive(Deserialize)]
+struct SetModeRequest {
+    /// `null` goes back to the configured mode.
+    mode: Option<RegistrationMode>,
+}
+
+async fn mode_response(state: &AppState) -> NexusResult<ModeResponse> {
+    let configured_mode = nexus_common::config::get().registration.mode;
+    let mode = crate::registration::current_mode(state).await?;
+    let overridden = instance_settings::get(&state.db.pool, crate::registration::MODE_SETTING)
+        .await?
+        .is_some();
+    Ok(ModeResponse {
+        mode,
+        configured_mode,
+        overridden,
+    })
+}
+
+/// GET /api/v1/admin/registration
+async fn get_mode(State(state): State<Arc<AppState>>) -> NexusResult<Json<ModeResponse>> {
+    Ok(Json(mode_response(&state).await?))
+}
+
+/// PUT /api/v1/admin/registration — e.g. `{"mode": "invite_only"}` to close
+/// sign-ups during a spam wave.
+async fn set_mode(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Json(body): Json<SetModeRequest>,
+) -> NexusResult<Json<ModeResponse>> {
+    let key = crate::registration::MODE_SETTING;
+    match body.mode {
+        Some(mode) => {
+            let value = serde_json::to_value(mode).map_err(|e| NexusError::Internal(e.into()))?;
+            instance_settings::set(&state.db.pool, key, &value, auth.user_id).await?;
+        }
+        None => instance_settings::clear(&state.db.pool, key).await?,
+    }
+    tracing::info!(staff_id = %auth.user_id, mode = ?body.mode, "Registration mode changed");
+    Ok(Json(mode_response(&state).await?))
+}
+
+#[derive(Serialize)]
+struct TokenResponse {
+    id: Uuid,
+    /// Only returned when the token is created.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    token: Option<String>,
+    created_by: Option<Uuid>,
+    note: Option<String>,
+    created_at: DateTime<Utc>,
+    expires_at: Option<DateTime<Utc>>,
+    used_by: Option<Uuid>,
+    used_at: Option<DateTime<Utc>>,
+}
+
+impl From<registration::RegistrationToken> for TokenResponse {
+    fn from(t: registration::RegistrationToken) -> Self {
+        Self {
+            id: t.id,
+            token: None,
+            created_by: t.created_by,
+            note: t.note,
+            created_at: t.created_at,
+            expires_at: t.expires_at,
+            used_by: t.used_by,
+            used_at: t.used_at,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct CreateTokenRequest {
+    note: Option<String>,
+    /// Omit for a token that never expires.
+    expires_in_secs: Option<u64>,
+}
+
+/// GET /api/v1/admin/registration/tokens — Newest first.
+async fn list_tokens(
+    State(state): State<Arc<AppState>>,
+) -> NexusResult<Json<Vec<TokenResponse>>> {
+    let tokens = registration::list_tokens(&state.db.pool).await?;
+    Ok(Json(tokens.into_iter().map(Into::into).collect()))
+}
+
+/// POST /api/v1/admin/registration/tokens
+///
+/// The token is in the response and nowhere else; it can't be shown again.
+async fn create_token(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Json(body): Json<CreateTokenRequest>,
+) -> NexusResult<(StatusCode, Json<TokenResponse>)> {
+    if body.note.as_ref().is_some_and(|n| n.chars().count() > 200) {
+        return Err(NexusError::Validation {
+            message: "Note must be at most 200 characters".into(),
+        });
+    }
+    if body.expires_in_secs.is_some_and(|secs| secs > MAX_TOKEN_LIFETIME_SECS) {
+        return Err(NexusError::Validation {
+            message: "Tokens can last at most a year; omit expires_in_secs for no expiry".into(),
+        });
+    }
+    let expires_at = body
+        .expires_in_secs
+        .map(|secs| Utc::now() + Duration::seconds(secs as i64));
+
+    let token = crate::oauth2::generate_token();
+    let created = registration::create_token(
+        &state.db.pool,
+        Uuid::new_v4(),
+        &crate::oauth2::hash_token(&token),
+        auth.user_id,
+        body.note.as_deref(),
+        expires_at,
+    )
+    .await?;
+    tracing::info!(user_id = %auth.user_id, token_id = %created.id, "Registration token issued");
+
+    let mut response = TokenResponse::from(created);
+    response.token = Some(token);
+    Ok((StatusCode::CREATED, Json(response)))
+}
+
+/// DELETE /api/v1/admin/registration/tokens/{token_id}
+async fn delete_token(
+    State(state): State<Arc<AppState>>,
+    Path(token_id): Path<Uuid>,
+) -> NexusResult<StatusCode> {
+    if !registration::delete_token(&state.db.pool, token_id).await? {
+        return Err(NexusError::NotFound {
+            resource: "Registration token".into(),
+        });
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Serialize)]
+struct ApplicationResponse {
+    user_id: Uuid,
+    username: String,
+    email: Option<String>,
+    reason: Option<String>,
+    ip: Option<String>,
+    created_at: DateTime<Utc>,
+}
+
+/// GET /api/v1/admin/registration/applications — Oldest first.
+async fn list_applications(
+    State(state): State<Arc<AppState>>,
+) -> NexusResult<Json<Vec<ApplicationResponse>>> {
+    let applications = registration::list_applications(&state.db.pool).await?;
+    Ok(Json(
+        applications
+            .into_iter()
+            .map(|a| ApplicationResponse {
+                user_id: a.user_id,
+                username: a.username,
+                email: a.email,
+                reason: a.reason,
+                ip: a.ip,
+                created_at: a.created_at,
+            })
+            .collect(),
+    ))
+}
+
+/// POST /api/v1/admin/registration/applications/{user_id}/approve
+async fn approve_application(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path(user_id): Path<Uuid>,
+) -> NexusResult<StatusCode> {
+    if !registration::approve(&state.db.pool, user_id).await? {
+        return Err(NexusError::NotFound {
+            resource: "Application".into(),
+        });
+    }
+    tracing::info!(staff_id = %auth.user_id, %user_id, "Registration approved");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// DELETE /api/v1/admin/registration/applications/{user_id}
+async fn reject_application(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path(user_id): Path<Uuid>,
+) -> NexusResult<StatusCode> {
+    if !registration::reject(&state.db.pool, user_id).await? {
+        return Err(NexusError::NotFound {
+            resource: "Application".into(),
+        });
+    }
+    tracing::info!(staff_id = %auth.user_id, %user_id, "Registration rejected");
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,190 @@
+//! Reminder routes — notes users schedule for themselves.
+//!
+//! GET    /users/@me/reminders                — List pending reminders
+//! POST   /users/@me/reminders                — Schedule a reminder
+//! GET    /users/@me/reminders/due            — List delivered, undismissed reminders
+//! DELETE /users/@me/reminders/:reminder_id   — Cancel a pending reminder, or dismiss a due one
+//!
+//! A reminder is due at `remind_at`, or after a relative delay given as
+//! `in` (e.g. `"1h30m"`, see [`crate::reminders::parse_delay`]). Without a
+//! `channel_id` it is delivered privately to the user's sessions as
+//! `REMINDER_DUE`, and listed under `due` until dismissed so clients that
+//! were offline still see it; with one it is posted there, mentioning the
+//! user. Delivery happens in the reminders job (see
+//! [`crate::jobs::reminders`]).
+
+use axum::{
+    extract::{Extension, Path, State},
+    http::StatusCode,
+    middleware,
+    routing::{delete, get},
+    Json, Router,
+};
+use chrono::{DateTime, Utc};
+use nexus_common::{
+    error::{NexusError, NexusResult},
+    permissions::Permissions,
+    snowflake,
+};
+use nexus_db::repository::{channels, messages, reminders};
+use serde::Deserialize;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{middleware::AuthContext, AppState};
+
+/// Most reminders one user may have pending.
+const MAX_PENDING_PER_USER: i64 = 25;
+/// Longest a reminder may be scheduled ahead.
+const MAX_DELAY_DAYS: i64 = 365;
+/// Longest reminder text, in characters.
+const MAX_CONTENT_LENGTH: usize = 1000;
+
+pub fn router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/users/@me/reminders", get(list_reminders).post(create_reminder))
+        .route("/users/@me/reminders/due", get(list_due_reminders))
+        .route("/users/@me/reminders/{reminder_id}", delete(cancel_reminder))
+        .route_layer(middleware::from_fn(crate::middleware::auth_middleware))
+}
+
+#[derive(Debug, Deserialize)]
+struct CreateReminderRequest {
+    content: String,
+    remind_at: Option<DateTime<Utc>>,
+    #[serde(rename = "in")]
+    delay: Option<String>,
+    channel_id: Option<Uuid>,
+    message_id: Option<Uuid>,
+}
+
+pub(crate) fn reminder_json(r: &reminders::ReminderRow) -> serde_json::Value {
+    serde_json::json!({
+        "id": r.id,
+        "content": r.content,
+        "remind_at": r.remind_at,
+        "delivered_at": r.delivered_at,
+        "channel_id": r.channel_id,
+        "message_id": r.message_id,
+        "created_at": r.created_at,
+    })
+}
+
+/// GET /api/v1/users/@me/reminders
+async fn list_reminders(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+) -> NexusResult<Json<Vec<serde_json::Value>>> {
+    let pending = reminders::list_pending(&state.db.pool, auth.user_id).await?;
+    Ok(Json(pending.iter().map(reminder_json).collect()))
+}
+
+/// GET /api/v1/users/@me/reminders/due
+async fn list_due_reminders(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+) -> NexusResult<Json<Vec<serde_json::Value>>> {
+    let due = reminders::list_due(&state.db.pool, auth.user_id).await?;
+    Ok(Json(due.iter().map(reminder_json).collect()))
+}
+
+/// POST /api/v1/users/@me/reminders
+async fn create_reminder(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Json(body): Json<CreateReminderRequest>,
+) -> NexusResult<(StatusCode, Json<serde_json::Value>)> {
+    let content = body.content.trim();
+    if content.is_empty() || content.chars().count() > MAX_CONTENT_LENGTH {
+        return Err(NexusError::Validation {
+            message: format!("content must be between 1 and {MAX_CONTENT_LENGTH} characters"),
+        });
+    }
+
+    let now = Utc::now();
+    let remind_at = match (body.remind_at, body.delay.as_deref()) {
+        (Some(at), None) => at,
+        (None, Some(delay)) => {
+            now + crate::reminders::parse_delay(delay).ok_or(NexusError::Validation {
+                message: "in must be a delay such as 45m, 1h30m or 2d".into(),
+            })?
+        }
+        _ => {
+            return Err(NexusError::Validation {
+                message: "Exactly one of remind_at or in is required".into(),
+            })
+        }
+    };
+    if remind_at <= now || remind_at > now + chrono::Duration::days(MAX_DELAY_DAYS) {
+        return Err(NexusError::Validation {
+            message: format!("Reminders must be due within the next {MAX_DELAY_DAYS} days"),
+        });
+    }
+
+    if let Some(channel_id) = body.channel_id {
+        let required = Permissions::VIEW_CHANNEL | Permissions::SEND_MESSAGES;
+        require_channel_access(&state, channel_id, auth.user_id, required).await?;
+    }
+    if let Some(message_id) = body.message_id {
+        let message = messages::find_by_id(&state.db.pool, message_id)
+            .await?
+            .ok_or(NexusError::NotFound { resource: "Message".into() })?;
+        if body.channel_id.is_some_and(|c| c != message.channel_id) {
+            return Err(NexusError::Validation {
+                message: "message_id must be in channel_id".into(),
+            });
+        }
+        let required = Permissions::VIEW_CHANNEL | Permissions::READ_MESSAGE_HISTORY;
+        require_channel_access(&state, message.channel_id, auth.user_id, required).await?;
+    }
+
+    if reminders::count_pending(&state.db.pool, auth.user_id).await? >= MAX_PENDING_PER_USER {
+        return Err(NexusError::Validation {
+            message: format!("You can have at most {MAX_PENDING_PER_USER} pending reminders"),
+        });
+    }
+
+    let reminder = reminders::create(
+        &state.db.pool,
+        snowflake::generate_id(),
+        auth.user_id,
+        body.channel_id,
+        body.message_id,
+        content,
+        remind_at,
+    )
+    .await?;
+    Ok((StatusCode::CREATED, Json(reminder_json(&reminder))))
+}
+
+/// DELETE /api/v1/users/@me/reminders/:reminder_id
+async fn cancel_reminder(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path(reminder_id): Path<Uuid>,
+) -> NexusResult<StatusCode> {
+    let pool = &state.db.pool;
+    if !reminders::cancel(pool, auth.user_id, reminder_id).await?
+        && !reminders::acknowledge(pool, auth.user_id, reminder_id).await?
+    {
+        return Err(NexusError::NotFound { resource: "Reminder".into() });
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Require `required` in a server channel, or participation in a DM.
+async fn require_channel_access(
+    state: &AppState,
+    channel_id: Uuid,
+    user_id: Uuid,
+    required: Permissions,
+) -> NexusResult<()> {
+    let channel = channels::find_by_id(&state.db.pool, channel_id)
+        .await?
+        .ok_or(NexusError::NotFound { resource: "Channel".into() })?;
+    match crate::permissions::in_channel(&state.db.pool, &channel, user_id).await? {
+        Some(permissions) => crate::permissions::require(permissions, required),
+        None if channels::is_dm_participant(&state.db.pool, channel.id, user_id).await? => Ok(()),
+        None => Err(NexusError::Forbidden),
+    }
+}
@@ -0,0 +1,436 @@
+//! Scheduled events and calendar subscriptions.
+//!
+//! GET    /servers/:id/events                 — Upcoming events (members)
+//! POST   /servers/:id/events                 — Create an event (MANAGE_EVENTS)
+//! GET    /servers/:id/events/:event_id       — Get an event (members)
+//! PATCH  /servers/:id/events/:event_id       — Update an event (MANAGE_EVENTS)
+//! DELETE /servers/:id/events/:event_id       — Delete an event (MANAGE_EVENTS)
+//! GET    /servers/:id/events/:event_id/ics   — Single-event `.ics` download
+//! GET    /servers/:id/events/feed            — Caller's calendar feed URL
+//! POST   /servers/:id/events/feed/reset      — Rotate the caller's feed URL
+//! GET    /calendar/:token/events.ics         — ICS feed (token in path, no auth)
+//!
+//! Every event response carries an `add_to_calendar` object with Google /
+//! Outlook deep links and the single-event `.ics` path.
+
+use axum::{
+    extract::{Extension, Path, State},
+    http::header,
+    middleware,
+    response::IntoResponse,
+    routing::{get, post},
+    Json, Router,
+};
+use chrono::{DateTime, Utc};
+use nexus_common::{
+    error::{NexusError, NexusResult},
+    gateway_event::{event_types, GatewayEvent},
+    permissions::Permissions,
+    snowflake,
+    validation::validate_request,
+};
+use nexus_db::repository::{channels, members, scheduled_events, servers};
+use nexus_db::repository::scheduled_events::ScheduledEventRow;
+use rand::distr::Alphanumeric;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{ics, middleware::AuthContext, AppState};
+
+/// Events returned by the list endpoint and the feed.
+const MAX_EVENTS: i64 = 200;
+/// How far back the feed reaches, so recently finished events don't vanish
+/// from subscribers' calendars.
+const FEED_LOOKBACK_DAYS: i64 = 30;
+
+pub fn router() -> Router<Arc<AppState>> {
+    let authed = Router::new()
+        .route("/servers/{server_id}/events", get(list_events).post(create_event))
+        .route("/servers/{server_id}/events/feed", get(get_feed))
+        .route("/servers/{server_id}/events/feed/reset", post(reset_feed))
+        .route(
+            "/servers/{server_id}/events/{event_id}",
+            get(get_event).patch(update_event).delete(delete_event),
+        )
+        .route("/servers/{server_id}/events/{event_id}/ics", get(get_event_ics))
+        .route_layer(middleware::from_fn(crate::middleware::auth_middleware));
+
+    // Calendar apps can't send a bearer token — the feed token authenticates.
+    authed.route("/calendar/{token}/events.ics", get(get_calendar_feed))
+}
+
+// ============================================================
+// Request / response types
+// ============================================================
+
+#[derive(Debug, Deserialize, Validate)]
+struct CreateEventRequest {
+    #[validate(length(min = 1, max = 100))]
+    name: String,
+    #[validate(length(max = 1000))]
+    description: Option<String>,
+    #[validate(length(max = 200))]
+    location: Option<String>,
+    channel_id: Option<Uuid>,
+    start_time: DateTime<Utc>,
+    end_time: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+struct UpdateEventRequest {
+    #[validate(length(min = 1, max = 100))]
+    name: Option<String>,
+    #[validate(length(max = 1000))]
+    description: Option<String>,
+    #[validate(length(max = 200))]
+    location: Option<String>,
+    channel_id: Option<Uuid>,
+    start_time: Option<DateTime<Utc>>,
+    end_time: Option<DateTime<Utc>>,
+    status: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct AddToCalendar {
+    google: String,
+    outlook: String,
+    /// Relative to the API root; requires the usual bearer token.
+    ics: String,
+}
+
+#[derive(Debug, Serialize)]
+struct EventResponse {
+    id: Uuid,
+    server_id: Uuid,
+    channel_id: Option<Uuid>,
+    creator_id: Option<Uuid>,
+    name: String,
+    description: Option<String>,
+    location: Option<String>,
+    start_time: DateTime<Utc>,
+    end_time: Option<DateTime<Utc>>,
+    status: String,
+    created_at: DateTime<Utc>,
+    add_to_calendar: AddToCalendar,
+}
+
+impl From<ScheduledEventRow> for EventResponse {
+    fn from(e: ScheduledEventRow) -> Self {
+        let add_to_calendar = AddToCalendar {
+            google: ics::google_calendar_url(&e),
+            outlook: ics::outlook_calendar_url(&e),
+            ics: format!("/api/v1/servers/{}/events/{}/ics", e.server_id, e.id),
+        };
+        Self {
+            id: e.id,
+            server_id: e.server_id,
+            channel_id: e.channel_id,
+            creator_id: e.creator_id,
+            name: e.name,
+            description: e.description,
+            location: e.location,
+            start_time: e.start_time,
+            end_time: e.end_time,
+            status: e.status,
+            created_at: e.created_at,
+            add_to_calendar,
+        }
+    }
+}
+
+// ============================================================
+// Events
+// ============================================================
+
+/// GET /api/v1/servers/:server_id/events
+async fn list_events(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path(server_id): Path<Uuid>,
+) -> NexusResult<Json<Vec<EventResponse>>> {
+    require_member(&state, server_id, auth.user_id).await?;
+    let events =
+        scheduled_events::list_for_server(&state.db.pool, server_id, Utc::now(), MAX_EVENTS).await?;
+    Ok(Json(events.into_iter().map(Into::into).collect()))
+}
+
+/// POST /api/v1/servers/:server_id/events
+async fn create_event(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path(server_id): Path<Uuid>,
+    Json(body): Json<CreateEventRequest>,
+) -> NexusResult<Json<EventResponse>> {
+    validate_request(&body)?;
+    require_manage_events(&state, server_id, auth.user_id).await?;
+    validate_times(body.start_time, body.end_time)?;
+    if let Some(channel_id) = body.channel_id {
+        require_server_channel(&state, server_id, channel_id).await?;
+    }
+
+    let event = scheduled_events::create_event(
+        &state.db.pool,
+        snowflake::generate_id(),
+        server_id,
+        body.channel_id,
+        auth.user_id,
+        body.name.trim(),
+        body.description.as_deref(),
+        body.location.as_deref(),
+        body.start_time,
+        body.end_time,
+    )
+    .await?;
+
+    let response = EventResponse::from(event);
+    broadcast(&state, event_types::SCHEDULED_EVENT_CREATE, server_id, &response);
+    Ok(Json(response))
+}
+
+/// GET /api/v1/servers/:server_id/events/:event_id
+async fn get_event(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path((server_id, event_id)): Path<(Uuid, Uuid)>,
+) -> NexusResult<Json<EventResponse>> {
+    require_member(&state, server_id, auth.user_id).await?;
+    let event = find_event(&state, server_id, event_id).await?;
+    Ok(Json(event.into()))
+}
+
+/// PATCH /api/v1/servers/:server_id/events/:event_id
+async fn update_event(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path((server_id, event_id)): Path<(Uuid, Uuid)>,
+    Json(body): Json<UpdateEventRequest>,
+) -> NexusResult<Json<EventResponse>> {
+    validate_request(&body)?;
+    require_manage_events(&state, server_id, auth.user_id).await?;
+    let event = find_event(&state, server_id, event_id).await?;
+
+    let status = body.status.unwrap_or(event.status);
+    if ![
+        scheduled_events::STATUS_SCHEDULED,
+        scheduled_events::STATUS_ACTIVE,
+        scheduled_events::STATUS_COMPLETED,
+        scheduled_events::STATUS_CANCELLED,
+    ]
+    .contains(&status.as_str())
+    {
+        return Err(NexusError::Validation {
+            message: "status must be one of scheduled, active, completed, cancelled".into(),
+        });
+    }
+    let start_time = body.start_time.unwrap_or(event.start_time);
+    let end_time = body.end_time.or(event.end_time);
+    validate_times(start_time, end_time)?;
+    let channel_id = body.channel_id.or(event.channel_id);
+    if let Some(channel_id) = body.channel_id {
+        require_server_channel(&state, server_id, channel_id).await?;
+    }
+
+    let updated = scheduled_events::update_event(
+        &state.db.pool,
+        event.id,
+        channel_id,
+        body.name.as_deref().map(str::trim).unwrap_or(&event.name),
+        body.description.as_deref().or(event.description.as_deref()),
+        body.location.as_deref().or(event.location.as_deref()),
+        start_time,
+        end_time,
+        &status,
+    )
+    .await?;
+
+    let response = EventResponse::from(updated);
+    broadcast(&state, event_types::SCHEDULED_EVENT_UPDATE, server_id, &response);
+    Ok(Json(response))
+}
+
+/// DELETE /api/v1/servers/:server_id/events/:event_id
+async fn delete_event(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path((server_id, event_id)): Path<(Uuid, Uuid)>,
+) -> NexusResult<Json<serde_json::Value>> {
+    require_manage_events(&state, server_id, auth.user_id).await?;
+    let event = find_event(&state, server_id, event_id).await?;
+    scheduled_events::delete_event(&state.db.pool, event.id).await?;
+
+    broadcast(
+        &state,
+        event_types::SCHEDULED_EVENT_DELETE,
+        server_id,
+        &serde_json::json!({ "id": event.id, "server_id": server_id }),
+    );
+    Ok(Json(serde_json::json!({ "deleted": true })))
+}
+
+/// GET /api/v1/servers/:server_id/events/:event_id/ics
+async fn get_event_ics(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path((server_id, event_id)): Path<(Uuid, Uuid)>,
+) -> NexusResult<impl IntoResponse> {
+    require_member(&state, server_id, auth.user_id).await?;
+    let event = find_event(&state, server_id, event_id).await?;
+    let body = ics::render_calendar(&state.server_name, &event.name, std::slice::from_ref(&event));
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/calendar; charset=utf-8".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"event-{}.ics\"", event.id),
+            ),
+        ],
+        body,
+    ))
+}
+
+// ============================================================
+// Calendar feed
+// ============================================================
+
+/// GET /api/v1/servers/:server_id/events/feed — issue-on-first-use.
+async fn get_feed(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path(server_id): Path<Uuid>,
+) -> NexusResult<Json<serde_json::Value>> {
+    require_member(&state, server_id, auth.user_id).await?;
+    let token = match scheduled_events::get_feed_token(&state.db.pool, server_id, auth.user_id).await? {
+        Some(token) => token,
+        None => issue_feed_token(&state, server_id, auth.user_id).await?,
+    };
+    Ok(Json(feed_json(&state, &token)))
+}
+
+/// POST /api/v1/servers/:server_id/events/feed/reset
+async fn reset_feed(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path(server_id): Path<Uuid>,
+) -> NexusResult<Json<serde_json::Value>> {
+    require_member(&state, server_id, auth.user_id).await?;
+    let token = issue_feed_token(&state, server_id, auth.user_id).await?;
+    Ok(Json(feed_json(&state, &token)))
+}
+
+/// GET /api/v1/calendar/:token/events.ics
+///
+/// The token only works while its owner is still a member of the server.
+async fn get_calendar_feed(
+    State(state): State<Arc<AppState>>,
+    Path(token): Path<String>,
+) -> NexusResult<impl IntoResponse> {
+    let not_found = || NexusError::NotFound {
+        resource: "Calendar feed".into(),
+    };
+    let (server_id, user_id) = scheduled_events::resolve_feed_token(&state.db.pool, &token)
+        .await?
+        .ok_or_else(not_found)?;
+    if !members::is_member(&state.db.pool, user_id, server_id).await? {
+        return Err(not_found());
+    }
+    let server = servers::find_by_id(&state.db.pool, server_id)
+        .await?
+        .ok_or_else(not_found)?;
+
+    let since = Utc::now() - chrono::Duration::days(FEED_LOOKBACK_DAYS);
+    let events =
+        scheduled_events::list_for_server(&state.db.pool, server_id, since, MAX_EVENTS).await?;
+    let body = ics::render_calendar(&state.server_name, &server.name, &events);
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/calendar; charset=utf-8"),
+            (header::CACHE_CONTROL, "private, max-age=300"),
+        ],
+        body,
+    ))
+}
+
+async fn issue_feed_token(state: &AppState, server_id: Uuid, user_id: Uuid) -> NexusResult<String> {
+    let token: String = rand::rng()
+        .sample_iter(Alphanumeric)
+        .take(48)
+        .map(char::from)
+        .collect();
+    scheduled_events::set_feed_token(&state.db.pool, server_id, user_id, &token).await?;
+    Ok(token)
+}
+
+fn feed_json(state: &AppState, token: &str) -> serde_json::Value {
+    let path = format!("/api/v1/calendar/{token}/events.ics");
+    serde_json::json!({
+        "path": path,
+        "url": format!("https://{}{}", state.server_name, path),
+        "webcal_url": format!("webcal://{}{}", state.server_name, path),
+    })
+}
+
+// ============================================================
+// Helpers
+// ============================================================
+
+async fn require_member(state: &AppState, server_id: Uuid, user_id: Uuid) -> NexusResult<()> {
+    if !members::is_member(&state.db.pool, user_id, server_id).await? {
+        return Err(NexusError::Forbidden);
+    }
+    Ok(())
+}
+
+async fn require_manage_events(state: &AppState, server_id: Uuid, user_id: Uuid) -> NexusResult<()> {
+    let server = servers::find_by_id(&state.db.pool, server_id)
+        .await?
+        .ok_or(NexusError::NotFound {
+            resource: "Server".into(),
+        })?;
+    let permissions = crate::permissions::resolve(&state.db.pool, &server, None, user_id)
+        .await?
+        .ok_or(NexusError::Forbidden)?;
+    crate::permissions::require(permissions, Permissions::MANAGE_EVENTS)
+}
+
+async fn require_server_channel(state: &AppState, server_id: Uuid, channel_id: Uuid) -> NexusResult<()> {
+    channels::find_by_id(&state.db.pool, channel_id)
+        .await?
+        .filter(|c| c.server_id == Some(server_id))
+        .ok_or(NexusError::Validation {
+            message: "Event channel must belong to this server".into(),
+        })?;
+    Ok(())
+}
+
+async fn find_event(state: &AppState, server_id: Uuid, event_id: Uuid) -> NexusResult<ScheduledEventRow> {
+    scheduled_events::find_by_id(&state.db.pool, event_id)
+        .await?
+        .filter(|e| e.server_id == server_id)
+        .ok_or(NexusError::NotFound {
+            resource: "Event".into(),
+        })
+}
+
+fn validate_times(start: DateTime<Utc>, end: Option<DateTime<Utc>>) -> NexusResult<()> {
+    if end.is_some_and(|end| end <= start) {
+        return Err(NexusError::Validation {
+            message: "end_time must be after start_time".into(),
+        });
+    }
+    Ok(())
+}
+
+fn broadcast(state: &AppState, event_type: &str, server_id: Uuid, data: &impl Serialize) {
+    let _ = state.gateway_tx.send(GatewayEvent {
+        event_type: event_type.into(),
+        data: serde_json::to_value(data).unwrap_or_default(),
+        server_id: Some(server_id),
+        channel_id: None,
+        user_id: None,
+        trace_context: None,
+    });
+}
@@ -1,23 +1,34 @@
 //! Server (guild) routes — create, join, leave, manage.
 
 use axum::{
-    extract::{Extension, Path, State},
+    extract::{Extension, Path, Query, State},
+    http::HeaderMap,
     middleware,
     routing::{get, post},
     Json, Router,
 };
 use nexus_common::{
     error::{NexusError, NexusResult},
-    models::server::{CreateServerRequest, ServerResponse, UpdateServerRequest},
+    models::server::{
+        CreateServerRequest, Server, ServerResponse, ServerSettings, UpdateServerRequest,
+    },
     permissions::Permissions,
     snowflake,
     validation::validate_request,
 };
-use nexus_db::repository::{channels, members, roles, servers};
+use nexus_db::repository::{
+    bans, channels, members, messages, roles, servers,
+    settings_history::{self, SettingsVersionRow},
+};
+use serde::Deserialize;
 use std::sync::Arc;
 use uuid::Uuid;
 
-use crate::{middleware::AuthContext, AppState};
+use crate::{
+    audit::{self, actions},
+    middleware::AuthContext,
+    AppState,
+};
 
 /// Server routes.
 pub fn router() -> Router<Arc<AppState>> {
@@ -27,24 +38,14 @@ pub fn router() -> Router<Arc<AppState>> {
         .route("/servers/{server_id}/members", get(list_members))
         .route("/servers/{server_id}/join", post(join_server))
         .route("/servers/{server_id}/leave", post(leave_server))
-        .route("/servers/{server_id}/invites", post(create_invite_route))
-        .route("/invites/{code}", get(get_invite_route))
-        .route("/invites/{code}/join", post(join_via_invite_route))
+        .route("/servers/{server_id}/settings/versions", get(list_settings_versions))
+        .route(
+            "/servers/{server_id}/settings/rollback/{version}",
+            post(rollback_settings),
+        )
         .route_layer(middleware::from_fn(crate::middleware::auth_middleware))
 }
 
-/// Generate a short random alphanumeric invite code.
-fn generate_invite_code() -> String {
-    use rand::Rng;
-    let mut rng = rand::rng();
-    (0..8)
-        .map(|_| {
-            let idx = rng.gen_range(0..36u8);
-            (if idx < 10 { b'0' + idx } else { b'a' + idx - 10 }) as char
-        })
-        .collect()
-}
-
 /// GET /api/v1/servers — List servers the authenticated user is a member of.
 async fn list_my_servers(
     Extension(auth): Extension<AuthContext>,
@@ -78,15 +79,15 @@ async fn create_server(
     let server_id = snowflake::generate_id();
     let is_public = body.is_public.unwrap_or(false);
 
-    // Create the server
-    let server =
-        servers::create_server(&state.db.pool, server_id, &body.name, auth.user_id, is_public)
-            .await?;
+    // The server, its @everyone role, default channels and the owner's
+    // membership are created together or not at all.
+    let mut tx = state.db.pool.begin().await?;
+    let server = servers::create_server(&mut *tx, server_id, &body.name, auth.user_id, is_public).await?;
 
     // Create @everyone role with default permissions
     let everyone_role_id = snowflake::generate_id();
     roles::create_role(
-        &state.db.pool,
+        &mut *tx,
         everyone_role_id,
         server_id,
         "@everyone",
@@ -100,7 +101,7 @@ async fn create_server(
     // Create default channels
     let general_id = snowflake::generate_id();
     channels::create_channel(
-        &state.db.pool,
+        &mut *tx,
         general_id,
         Some(server_id),
         None,
@@ -113,7 +114,7 @@ async fn create_server(
 
     let voice_id = snowflake::generate_id();
     channels::create_channel(
-        &state.db.pool,
+        &mut *tx,
         voice_id,
         Some(server_id),
         None,
@@ -125,7 +126,8 @@ async fn create_server(
     .await?;
 
     // Add creator as member
-    members::add_member(&state.db.pool, auth.user_id, server_id).await?;
+    members::add_member(&mut *tx, auth.user_id, server_id).await?;
+    tx.commit().await?;
 
     tracing::info!(
         server_id = %server_id,
@@ -177,12 +179,165 @@ async fn update_server(
         body.name.as_deref(),
         body.description.as_deref(),
         body.is_public,
+        body.message_history,
+        body.preferred_locale.as_deref(),
+        body.message_retention_days,
     )
     .await?;
 
+    apply_settings_side_effects(&state, &server, &updated).await?;
+    record_settings_version(&state, &server, &updated, auth.user_id, None).await;
+
     Ok(Json(updated.into()))
 }
 
+/// Follow-up work when a server's settings change.
+async fn apply_settings_side_effects(
+    state: &AppState,
+    before: &Server,
+    after: &Server,
+) -> NexusResult<()> {
+    // Turning edit history off also forgets what was already kept
+    if before.message_history && !after.message_history {
+        let purged = messages::purge_server_revisions(&state.db.pool, after.id).await?;
+        tracing::info!(server_id = %after.id, purged, "Message edit history disabled");
+    }
+    Ok(())
+}
+
+/// Store a new settings version if anything changed between `before` and
+/// `after`.
+///
+/// The first change also records `before` as version 1, so even the
+/// original settings can be restored. A failure here is logged rather than
+/// returned — the change itself has already been made.
+async fn record_settings_version(
+    state: &AppState,
+    before: &Server,
+    after: &Server,
+    actor_id: Uuid,
+    rollback_of: Option<i32>,
+) -> Option<SettingsVersionRow> {
+    let (old, new) = (ServerSettings::from(before), ServerSettings::from(after));
+    if old == new {
+        return None;
+    }
+    let pool = &state.db.pool;
+    let snapshot = |s: &ServerSettings| serde_json::to_value(s).unwrap_or_default();
+    let recorded = async {
+        if !settings_history::has_versions(pool, after.id).await? {
+            settings_history::create_version(pool, after.id, &snapshot(&old), None, None).await?;
+        }
+        settings_history::create_version(pool, after.id, &snapshot(&new), Some(actor_id), rollback_of)
+            .await
+    }
+    .await;
+    match recorded {
+        Ok(version) => Some(version),
+        Err(e) => {
+            tracing::warn!(server_id = %after.id, error = %e, "Failed to record settings version");
+            None
+        }
+    }
+}
+
+fn settings_version_json(v: &SettingsVersionRow) -> serde_json::Value {
+    serde_json::json!({
+        "version": v.version,
+        "settings": v.settings,
+        "changed_by": v.changed_by,
+        "rollback_of": v.rollback_of,
+        "created_at": v.created_at,
+    })
+}
+
+/// The server, and the caller's permissions in it.
+async fn server_with_permission(
+    state: &AppState,
+    server_id: Uuid,
+    user_id: Uuid,
+    required: Permissions,
+) -> NexusResult<Server> {
+    let server = servers::find_by_id(&state.db.pool, server_id)
+        .await?
+        .ok_or(NexusError::NotFound {
+            resource: "Server".into(),
+        })?;
+    let permissions = crate::permissions::resolve(&state.db.pool, &server, None, user_id)
+        .await?
+        .ok_or(NexusError::Forbidden)?;
+    crate::permissions::require(permissions, required)?;
+    Ok(server)
+}
+
+#[derive(Debug, Deserialize)]
+struct ListVersionsParams {
+    limit: Option<i64>,
+}
+
+/// GET /api/v1/servers/:server_id/settings/versions — Settings history,
+/// newest first. Requires VIEW_AUDIT_LOG.
+async fn list_settings_versions(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path(server_id): Path<Uuid>,
+    Query(params): Query<ListVersionsParams>,
+) -> NexusResult<Json<Vec<serde_json::Value>>> {
+    server_with_permission(&state, server_id, auth.user_id, Permissions::VIEW_AUDIT_LOG).await?;
+
+    let limit = params.limit.unwrap_or(50).clamp(1, 100);
+    let versions = settings_history::list_versions(&state.db.pool, server_id, limit).await?;
+    Ok(Json(versions.iter().map(settings_version_json).collect()))
+}
+
+/// POST /api/v1/servers/:server_id/settings/rollback/:version — Restore the
+/// settings recorded in `version`. Requires MANAGE_SERVER.
+///
+/// The rollback is itself recorded as a new version.
+async fn rollback_settings(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path((server_id, version)): Path<(Uuid, i32)>,
+    headers: HeaderMap,
+) -> NexusResult<Json<serde_json::Value>> {
+    let server =
+        server_with_permission(&state, server_id, auth.user_id, Permissions::MANAGE_SERVER)
+            .await?;
+
+    let target = settings_history::find_version(&state.db.pool, server_id, version)
+        .await?
+        .ok_or(NexusError::NotFound {
+            resource: "Settings version".into(),
+        })?;
+    let settings: ServerSettings =
+        serde_json::from_value(target.settings.clone()).map_err(|e| NexusError::Internal(e.into()))?;
+
+    let updated = servers::restore_settings(&state.db.pool, server_id, &settings).await?;
+    apply_settings_side_effects(&state, &server, &updated).await?;
+    let recorded =
+        record_settings_version(&state, &server, &updated, auth.user_id, Some(version)).await;
+
+    audit::record(
+        &state,
+        server_id,
+        auth.user_id,
+        actions::SERVER_SETTINGS_ROLLBACK,
+        None,
+        Some(audit::diff(
+            &ServerSettings::from(&server),
+            &settings,
+            &["name", "description", "is_public", "message_history", "preferred_locale", "message_retention_days"],
+        )),
+        audit::reason(&headers).as_deref(),
+    )
+    .await;
+
+    Ok(Json(serde_json::json!({
+        "server": ServerResponse::from(updated),
+        "version": recorded.as_ref().map(settings_version_json),
+    })))
+}
+
 /// DELETE /api/v1/servers/:server_id
 async fn delete_server(
     Extension(auth): Extension<AuthContext>,
@@ -202,6 +357,16 @@ async fn delete_server(
     servers::delete_server(&state.db.pool, server_id).await?;
 
     tracing::info!(server_id = %server_id, "Server deleted");
+    crate::security_events::record(
+        &state,
+        crate::security_events::events::SERVER_DELETE,
+        serde_json::json!({
+            "actor_id": auth.user_id,
+            "server_id": server_id,
+            "name": server.name,
+        }),
+    )
+    .await;
 
     Ok(Json(serde_json::json!({ "deleted": true })))
 }
@@ -232,6 +397,10 @@ async fn join_server(
         return Err(NexusError::Forbidden);
     }
 
+    if bans::find_ban(&state.db.pool, server_id, auth.user_id).await?.is_some() {
+        return Err(NexusError::Forbidden);
+    }
+
     // Check if already a member
     if members::is_member(&state.db.pool, auth.user_id, server_id).await? {
         return Err(NexusError::AlreadyExists {
@@ -242,6 +411,11 @@ async fn join_server(
     members::add_member(&state.db.pool, auth.user_id, server_id).await?;
     servers::increment_member_count(&state.db.pool, server_id).await?;
 
+    // Re-read so `{member_count}` in welcome templates includes the new member
+    if let Some(server) = servers::find_by_id(&state.db.pool, server_id).await? {
+        super::welcome::on_member_join(&state, &server, auth.user_id, &auth.username).await;
+    }
+
     Ok(Json(serde_json::json!({ "joined": true })))
 }
 
@@ -269,112 +443,3 @@ async fn leave_server(
 
     Ok(Json(serde_json::json!({ "left": true })))
 }
-/// POST /api/v1/servers/:server_id/invites
-async fn create_invite_route(
-    Extension(auth): Extension<AuthContext>,
-    State(state): State<Arc<AppState>>,
-    Path(server_id): Path<Uuid>,
-    Json(body): Json<nexus_common::models::server::CreateInviteRequest>,
-) -> NexusResult<Json<serde_json::Value>> {
-    // Must be a member to create an invite
-    if !members::is_member(&state.db.pool, auth.user_id, server_id).await? {
-        return Err(NexusError::Forbidden);
-    }
-
-    let code = generate_invite_code();
-    let expires_at = body.max_age_secs.filter(|&s| s > 0).map(|s| {
-        chrono::Utc::now() + chrono::Duration::seconds(s as i64)
-    });
-    let max_uses = body.max_uses.filter(|&u| u > 0);
-
-    let invite = servers::create_invite(
-        &state.db.pool,
-        &code,
-        server_id,
-        None,
-        auth.user_id,
-        max_uses,
-        expires_at,
-    )
-    .await?;
-
-    Ok(Json(serde_json::json!({ "code": invite.code })))
-}
-
-/// GET /api/v1/invites/:code — public, no auth required
-async fn get_invite_route(
-    State(state): State<Arc<AppState>>,
-    Path(code): Path<String>,
-) -> NexusResult<Json<serde_json::Value>> {
-    let invite = servers::find_invite(&state.db.pool, &code)
-        .await?
-        .ok_or(NexusError::NotFound { resource: "Invite".into() })?;
-
-    if let Some(exp) = invite.expires_at {
-        if exp < chrono::Utc::now() {
-            return Err(NexusError::NotFound { resource: "Invite".into() });
-        }
-    }
-    if let Some(max) = invite.max_uses {
-        if invite.uses >= max {
-            return Err(NexusError::NotFound { resource: "Invite".into() });
-        }
-    }
-
-    let server = servers::find_by_id(&state.db.pool, invite.server_id)
-        .await?
-        .ok_or(NexusError::NotFound { resource: "Server".into() })?;
-
-    Ok(Json(serde_json::json!({
-        "code": invite.code,
-        "server": { "id": server.id, "name": server.name, "member_count": server.member_count },
-        "uses": invite.uses,
-        "max_uses": invite.max_uses,
-    })))
-}
-
-/// POST /api/v1/invites/:code/join
-async fn join_via_invite_route(
-    Extension(auth): Extension<AuthContext>,
-    State(state): State<Arc<AppState>>,
-    Path(code): Path<String>,
-) -> NexusResult<Json<serde_json::Value>> {
-    let invite = servers::find_invite(&state.db.pool, &code)
-        .await?
-        .ok_or(NexusError::NotFound { resource: "Invite".into() })?;
-
-    if let Some(exp) = invite.expires_at {
-        if exp < chrono::Utc::now() {
-            return Err(NexusError::Validation { message: "Invite has expired.".into() });
-        }
-    }
-    if let Some(max) = invite.max_uses {
-        if invite.uses >= max {
-            return Err(NexusError::Validation { message: "Invite has reached its maximum uses.".into() });
-        }
-    }
-
-    let server_id = invite.server_id;
-
-    // Already a member — just return the server info
-    if members::is_member(&state.db.pool, auth.user_id, server_id).await? {
-        let server = servers::find_by_id(&state.db.pool, server_id)
-            .await?
-            .ok_or(NexusError::NotFound { resource: "Server".into() })?;
-        return Ok(Json(serde_json::json!({
-            "server": { "id": server.id, "name": server.name }
-        })));
-    }
-
-    members::add_member(&state.db.pool, auth.user_id, server_id).await?;
-    servers::use_invite(&state.db.pool, &code).await?;
-    servers::increment_member_count(&state.db.pool, server_id).await?;
-
-    let server = servers::find_by_id(&state.db.pool, server_id)
-        .await?
-        .ok_or(NexusError::NotFound { resource: "Server".into() })?;
-
-    Ok(Json(serde_json::json!({
-        "server": { "id": server.id, "name": server.name }
-    })))
-}
\ No newline at end of file
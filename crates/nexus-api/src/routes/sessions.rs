@@ -0,0 +1,94 @@
+//! Login sessions — see and revoke the devices signed in to an account.
+//!
+//! GET    /users/@me/sessions               — Active sessions
+//! DELETE /users/@me/sessions               — Revoke every session but this one
+//! DELETE /users/@me/sessions/{session_id}  — Revoke one session
+//!
+//! Revoking a session kills its refresh token and the access tokens issued
+//! for it (see [`crate::session_cache`]).
+
+use axum::{
+    extract::{Extension, Path, State},
+    http::StatusCode,
+    middleware,
+    routing::{delete, get},
+    Json, Router,
+};
+use chrono::{DateTime, Utc};
+use nexus_common::error::{NexusError, NexusResult};
+use nexus_db::repository::sessions;
+use serde::Serialize;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{middleware::AuthContext, AppState};
+
+pub fn router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/users/@me/sessions", get(list_sessions).delete(revoke_other_sessions))
+        .route("/users/@me/sessions/{session_id}", delete(revoke_session))
+        .route_layer(middleware::from_fn(crate::middleware::auth_middleware))
+}
+
+#[derive(Serialize)]
+struct SessionResponse {
+    id: Uuid,
+    user_agent: Option<String>,
+    ip: Option<String>,
+    created_at: DateTime<Utc>,
+    last_used_at: DateTime<Utc>,
+    expires_at: DateTime<Utc>,
+    /// Whether this is the session making the request.
+    current: bool,
+}
+
+/// GET /api/v1/users/@me/sessions — Most recently used first.
+async fn list_sessions(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+) -> NexusResult<Json<Vec<SessionResponse>>> {
+    let sessions = sessions::list_for_user(&state.db.pool, auth.user_id).await?;
+    Ok(Json(
+        sessions
+            .into_iter()
+            .map(|s| SessionResponse {
+                current: auth.session_id == Some(s.id),
+                id: s.id,
+                user_agent: s.user_agent,
+                ip: s.ip,
+                created_at: s.created_at,
+                last_used_at: s.last_used_at,
+                expires_at: s.expires_at,
+            })
+            .collect(),
+    ))
+}
+
+/// DELETE /api/v1/users/@me/sessions/{session_id}
+///
+/// Revoking the current session is allowed and amounts to logging out.
+async fn revoke_session(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path(session_id): Path<Uuid>,
+) -> NexusResult<StatusCode> {
+    if !sessions::delete(&state.db.pool, session_id, auth.user_id).await? {
+        return Err(NexusError::NotFound {
+            resource: "Session".into(),
+        });
+    }
+    state.live_sessions.remove(session_id);
+    tracing::info!(user_id = %auth.user_id, %session_id, "Session revoked");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// DELETE /api/v1/users/@me/sessions — Sign out everywhere else.
+async fn revoke_other_sessions(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+) -> NexusResult<StatusCode> {
+    let revoked = sessions::delete_others(&state.db.pool, auth.user_id, auth.session_id).await?;
+    state.live_sessions.remove_user(auth.user_id, auth.session_id);
+    tracing::info!(user_id = %auth.user_id, revoked, "Other sessions revoked");
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -1,11 +1,13 @@
 //! Slash command routes — register, list, delete application commands.
 //!
 //! Both user-authenticated (developer portal) and bot-authenticated flows are
-//! supported. The bot middleware sets a `BotContext` extension for bot-token
-//! requests. For user requests, the `AuthContext` extension is set.
+//! supported on [`application_router`]: `bot_auth_middleware` sets an
+//! [`AppCaller`] for either kind of request. Client-facing routes in
+//! [`router`] take user auth only.
 
 use axum::{
     extract::{Extension, Path, Query, State},
+    http::{header::ACCEPT_LANGUAGE, HeaderMap},
     middleware,
     routing::{get, post},
     Json, Router,
@@ -13,24 +15,41 @@ use axum::{
 use nexus_common::{
     error::{NexusError, NexusResult},
     gateway_event::GatewayEvent,
+    locale,
     models::slash_command::{
         CreateInteractionRequest, Interaction, InteractionResponse, SlashCommand,
         UpsertCommandRequest,
     },
     snowflake,
+    validation::validate_request,
 };
-use nexus_db::repository::{bots, slash_commands};
+use nexus_db::repository::{bots, servers, slash_commands, users};
 use rand::Rng;
 use serde::Deserialize;
 use std::sync::Arc;
 use uuid::Uuid;
 
-use crate::{middleware::AuthContext, AppState};
+use crate::{
+    middleware::{AppCaller, AuthContext},
+    AppState,
+};
 
-/// Slash command routes.
+/// Client-facing slash command routes (user auth).
 pub fn router() -> Router<Arc<AppState>> {
     Router::new()
-        // Global commands (developer portal or bot token)
+        // Client-facing: get commands available in a server (for the slash menu)
+        .route("/servers/{server_id}/commands", get(list_available_commands))
+        // Interactions (client → server → bot pipeline)
+        .route("/interactions", post(create_interaction))
+        .route_layer(middleware::from_fn(crate::middleware::auth_middleware))
+}
+
+/// Application-scoped routes, callable with the developer's user token or
+/// the bot's own token. The caller layers `bot_auth_middleware` (it needs
+/// the app state to look tokens up).
+pub fn application_router() -> Router<Arc<AppState>> {
+    Router::new()
+        // Global commands
         .route(
             "/applications/{app_id}/commands",
             get(get_global_commands).put(bulk_overwrite_global_commands),
@@ -55,15 +74,11 @@ pub fn router() -> Router<Arc<AppState>> {
                 .patch(edit_server_command)
                 .delete(delete_server_command),
         )
-        // Client-facing: get commands available in a server (for the slash menu)
-        .route("/servers/{server_id}/commands", get(list_available_commands))
-        // Interactions (client → server → bot pipeline)
-        .route("/interactions", post(create_interaction))
+        // Bot responses to interactions
         .route(
             "/interactions/{interaction_id}/callback",
             post(interaction_callback),
         )
-        .route_layer(middleware::from_fn(crate::middleware::auth_middleware))
 }
 
 // ============================================================================
@@ -72,22 +87,22 @@ pub fn router() -> Router<Arc<AppState>> {
 
 /// GET /api/v1/applications/{app_id}/commands
 async fn get_global_commands(
-    Extension(auth): Extension<AuthContext>,
+    Extension(caller): Extension<AppCaller>,
     State(state): State<Arc<AppState>>,
     Path(app_id): Path<Uuid>,
 ) -> NexusResult<Json<Vec<SlashCommand>>> {
-    verify_app_access(&state, app_id, auth.user_id).await?;
+    verify_app_access(&state, app_id, &caller).await?;
     let cmds = slash_commands::get_global_commands(&state.db.pool, app_id).await?;
     Ok(Json(cmds))
 }
 
 /// GET /api/v1/applications/{app_id}/commands/{command_id}
 async fn get_global_command(
-    Extension(auth): Extension<AuthContext>,
+    Extension(caller): Extension<AppCaller>,
     State(state): State<Arc<AppState>>,
     Path((app_id, command_id)): Path<(Uuid, Uuid)>,
 ) -> NexusResult<Json<SlashCommand>> {
-    verify_app_access(&state, app_id, auth.user_id).await?;
+    verify_app_access(&state, app_id, &caller).await?;
     let cmd = slash_commands::get_command(&state.db.pool, command_id)
         .await?
         .ok_or(NexusError::NotFound { resource: "command".to_string() })?;
@@ -96,12 +111,13 @@ async fn get_global_command(
 
 /// POST /api/v1/applications/{app_id}/commands — Create a global command.
 async fn create_global_command(
-    Extension(auth): Extension<AuthContext>,
+    Extension(caller): Extension<AppCaller>,
     State(state): State<Arc<AppState>>,
     Path(app_id): Path<Uuid>,
     Json(body): Json<UpsertCommandRequest>,
 ) -> NexusResult<Json<SlashCommand>> {
-    verify_app_access(&state, app_id, auth.user_id).await?;
+    validate_request(&body)?;
+    verify_app_access(&state, app_id, &caller).await?;
     let id = snowflake::generate_id();
     let cmd = slash_commands::upsert_command(
         &state.db.pool,
@@ -114,6 +130,8 @@ async fn create_global_command(
         body.command_type.unwrap_or(1),
         body.default_member_permissions.as_deref(),
         body.dm_permission.unwrap_or(true),
+        body.name_localizations.as_ref(),
+        body.description_localizations.as_ref(),
     )
     .await?;
 
@@ -123,12 +141,13 @@ async fn create_global_command(
 
 /// PATCH /api/v1/applications/{app_id}/commands/{command_id}
 async fn edit_global_command(
-    Extension(auth): Extension<AuthContext>,
+    Extension(caller): Extension<AppCaller>,
     State(state): State<Arc<AppState>>,
     Path((app_id, command_id)): Path<(Uuid, Uuid)>,
     Json(body): Json<UpsertCommandRequest>,
 ) -> NexusResult<Json<SlashCommand>> {
-    verify_app_access(&state, app_id, auth.user_id).await?;
+    validate_request(&body)?;
+    verify_app_access(&state, app_id, &caller).await?;
     let existing = slash_commands::get_command(&state.db.pool, command_id)
         .await?
         .ok_or(NexusError::NotFound { resource: "command".to_string() })?;
@@ -146,6 +165,10 @@ async fn edit_global_command(
             .as_deref()
             .or(existing.default_member_permissions.as_deref()),
         body.dm_permission.unwrap_or(existing.dm_permission),
+        body.name_localizations.as_ref().or(existing.name_localizations.as_ref()),
+        body.description_localizations
+            .as_ref()
+            .or(existing.description_localizations.as_ref()),
     )
     .await?;
 
@@ -155,62 +178,77 @@ async fn edit_global_command(
 
 /// DELETE /api/v1/applications/{app_id}/commands/{command_id}
 async fn delete_global_command(
-    Extension(auth): Extension<AuthContext>,
+    Extension(caller): Extension<AppCaller>,
     State(state): State<Arc<AppState>>,
     Path((app_id, command_id)): Path<(Uuid, Uuid)>,
 ) -> NexusResult<axum::http::StatusCode> {
-    verify_app_access(&state, app_id, auth.user_id).await?;
+    verify_app_access(&state, app_id, &caller).await?;
     slash_commands::delete_command(&state.db.pool, command_id).await?;
     Ok(axum::http::StatusCode::NO_CONTENT)
 }
 
 /// PUT /api/v1/applications/{app_id}/commands — Bulk overwrite global commands.
 async fn bulk_overwrite_global_commands(
-    Extension(auth): Extension<AuthContext>,
+    Extension(caller): Extension<AppCaller>,
     State(state): State<Arc<AppState>>,
     Path(app_id): Path<Uuid>,
     Json(body): Json<Vec<UpsertCommandRequest>>,
 ) -> NexusResult<Json<Vec<SlashCommand>>> {
-    verify_app_access(&state, app_id, auth.user_id).await?;
-
-    // Build the commands tuple array expected by the repo
-    let commands: Vec<(Uuid, String, String, serde_json::Value, i32)> = body
-        .iter()
-        .map(|req| {
-            let id = snowflake::generate_id();
-            let opts =
-                serde_json::to_value(req.options.as_deref().unwrap_or(&[])).unwrap_or_default();
-            (id, req.name.clone(), req.description.clone(), opts, req.command_type.unwrap_or(1))
-        })
-        .collect();
+    verify_app_access(&state, app_id, &caller).await?;
+    let commands = new_commands(body)?;
 
     let cmds =
         slash_commands::bulk_overwrite_global_commands(&state.db.pool, app_id, &commands).await?;
     Ok(Json(cmds))
 }
 
+/// Validate a bulk overwrite and build the rows for it. Validation messages
+/// are prefixed with the command's index, e.g. `[2]: name: ...`.
+fn new_commands(body: Vec<UpsertCommandRequest>) -> NexusResult<Vec<slash_commands::NewCommand>> {
+    for (index, req) in body.iter().enumerate() {
+        validate_request(req).map_err(|e| match e {
+            NexusError::Validation { message } => NexusError::Validation {
+                message: format!("[{index}]: {message}"),
+            },
+            other => other,
+        })?;
+    }
+    Ok(body
+        .into_iter()
+        .map(|req| slash_commands::NewCommand {
+            id: snowflake::generate_id(),
+            name: req.name,
+            description: req.description,
+            options: req.options.unwrap_or_default(),
+            command_type: req.command_type.unwrap_or(1),
+            name_localizations: req.name_localizations,
+            description_localizations: req.description_localizations,
+        })
+        .collect())
+}
+
 // ============================================================================
 // Server-Scoped Commands
 // ============================================================================
 
 /// GET /api/v1/applications/{app_id}/guilds/{server_id}/commands
 async fn get_server_commands(
-    Extension(auth): Extension<AuthContext>,
+    Extension(caller): Extension<AppCaller>,
     State(state): State<Arc<AppState>>,
     Path((app_id, server_id)): Path<(Uuid, Uuid)>,
 ) -> NexusResult<Json<Vec<SlashCommand>>> {
-    verify_app_access(&state, app_id, auth.user_id).await?;
+    verify_app_access(&state, app_id, &caller).await?;
     let cmds = slash_commands::get_server_commands(&state.db.pool, app_id, server_id).await?;
     Ok(Json(cmds))
 }
 
 /// GET /api/v1/applications/{app_id}/guilds/{server_id}/commands/{command_id}
 async fn get_server_command(
-    Extension(auth): Extension<AuthContext>,
+    Extension(caller): Extension<AppCaller>,
     State(state): State<Arc<AppState>>,
     Path((app_id, _server_id, command_id)): Path<(Uuid, Uuid, Uuid)>,
 ) -> NexusResult<Json<SlashCommand>> {
-    verify_app_access(&state, app_id, auth.user_id).await?;
+    verify_app_access(&state, app_id, &caller).await?;
     let cmd = slash_commands::get_command(&state.db.pool, command_id)
         .await?
         .ok_or(NexusError::NotFound { resource: "command".to_string() })?;
@@ -219,12 +257,13 @@ async fn get_server_command(
 
 /// POST /api/v1/applications/{app_id}/guilds/{server_id}/commands
 async fn create_server_command(
-    Extension(auth): Extension<AuthContext>,
+    Extension(caller): Extension<AppCaller>,
     State(state): State<Arc<AppState>>,
     Path((app_id, server_id)): Path<(Uuid, Uuid)>,
     Json(body): Json<UpsertCommandRequest>,
 ) -> NexusResult<Json<SlashCommand>> {
-    verify_app_access(&state, app_id, auth.user_id).await?;
+    validate_request(&body)?;
+    verify_app_access(&state, app_id, &caller).await?;
     let id = snowflake::generate_id();
     let cmd = slash_commands::upsert_command(
         &state.db.pool,
@@ -237,6 +276,8 @@ async fn create_server_command(
         body.command_type.unwrap_or(1),
         body.default_member_permissions.as_deref(),
         body.dm_permission.unwrap_or(true),
+        body.name_localizations.as_ref(),
+        body.description_localizations.as_ref(),
     )
     .await?;
     broadcast_command_event(&state, &cmd, "APPLICATION_COMMAND_CREATE");
@@ -245,12 +286,13 @@ async fn create_server_command(
 
 /// PATCH /api/v1/applications/{app_id}/guilds/{server_id}/commands/{command_id}
 async fn edit_server_command(
-    Extension(auth): Extension<AuthContext>,
+    Extension(caller): Extension<AppCaller>,
     State(state): State<Arc<AppState>>,
     Path((app_id, _server_id, command_id)): Path<(Uuid, Uuid, Uuid)>,
     Json(body): Json<UpsertCommandRequest>,
 ) -> NexusResult<Json<SlashCommand>> {
-    verify_app_access(&state, app_id, auth.user_id).await?;
+    validate_request(&body)?;
+    verify_app_access(&state, app_id, &caller).await?;
     let existing = slash_commands::get_command(&state.db.pool, command_id)
         .await?
         .ok_or(NexusError::NotFound { resource: "command".to_string() })?;
@@ -267,6 +309,10 @@ async fn edit_server_command(
             .as_deref()
             .or(existing.default_member_permissions.as_deref()),
         body.dm_permission.unwrap_or(existing.dm_permission),
+        body.name_localizations.as_ref().or(existing.name_localizations.as_ref()),
+        body.description_localizations
+            .as_ref()
+            .or(existing.description_localizations.as_ref()),
     )
     .await?;
     broadcast_command_event(&state, &cmd, "APPLICATION_COMMAND_UPDATE");
@@ -275,34 +321,24 @@ async fn edit_server_command(
 
 /// DELETE /api/v1/applications/{app_id}/guilds/{server_id}/commands/{command_id}
 async fn delete_server_command(
-    Extension(auth): Extension<AuthContext>,
+    Extension(caller): Extension<AppCaller>,
     State(state): State<Arc<AppState>>,
     Path((app_id, _server_id, command_id)): Path<(Uuid, Uuid, Uuid)>,
 ) -> NexusResult<axum::http::StatusCode> {
-    verify_app_access(&state, app_id, auth.user_id).await?;
+    verify_app_access(&state, app_id, &caller).await?;
     slash_commands::delete_command(&state.db.pool, command_id).await?;
     Ok(axum::http::StatusCode::NO_CONTENT)
 }
 
 /// PUT /api/v1/applications/{app_id}/guilds/{server_id}/commands — Bulk overwrite.
 async fn bulk_overwrite_server_commands(
-    Extension(auth): Extension<AuthContext>,
+    Extension(caller): Extension<AppCaller>,
     State(state): State<Arc<AppState>>,
     Path((app_id, server_id)): Path<(Uuid, Uuid)>,
     Json(body): Json<Vec<UpsertCommandRequest>>,
 ) -> NexusResult<Json<Vec<SlashCommand>>> {
-    verify_app_access(&state, app_id, auth.user_id).await?;
-
-    // Build the commands tuple array expected by the repo
-    let commands: Vec<(Uuid, String, String, serde_json::Value, i32)> = body
-        .iter()
-        .map(|req| {
-            let id = snowflake::generate_id();
-            let opts =
-                serde_json::to_value(req.options.as_deref().unwrap_or(&[])).unwrap_or_default();
-            (id, req.name.clone(), req.description.clone(), opts, req.command_type.unwrap_or(1))
-        })
-        .collect();
+    verify_app_access(&state, app_id, &caller).await?;
+    let commands = new_commands(body)?;
 
     let cmds = slash_commands::bulk_overwrite_server_commands(
         &state.db.pool,
@@ -324,17 +360,38 @@ struct AvailableCommandsQuery {
 }
 
 /// GET /api/v1/servers/{server_id}/commands — List all commands the user can invoke.
+///
+/// Names and descriptions are resolved for the `Accept-Language` header —
+/// or without one, the user's locale then the server's preferred language —
+/// into `name_localized` / `description_localized`; the full maps are kept.
 async fn list_available_commands(
-    Extension(_auth): Extension<AuthContext>,
+    Extension(auth): Extension<AuthContext>,
     State(state): State<Arc<AppState>>,
     Path(server_id): Path<Uuid>,
     Query(q): Query<AvailableCommandsQuery>,
+    headers: HeaderMap,
 ) -> NexusResult<Json<Vec<SlashCommand>>> {
-    let cmds = if let Some(app_id) = q.application_id {
+    let mut cmds = if let Some(app_id) = q.application_id {
         slash_commands::get_server_commands(&state.db.pool, app_id, server_id).await?
     } else {
         slash_commands::get_all_server_commands(&state.db.pool, server_id).await?
     };
+    let mut preferred = headers
+        .get(ACCEPT_LANGUAGE)
+        .and_then(|v| v.to_str().ok())
+        .map(locale::parse_accept_language)
+        .unwrap_or_default();
+    if preferred.is_empty() && !cmds.is_empty() {
+        if let Some(user) = users::find_by_id(&state.db.pool, auth.user_id).await? {
+            preferred.push(user.locale);
+        }
+        if let Some(server) = servers::find_by_id(&state.db.pool, server_id).await? {
+            preferred.push(server.preferred_locale);
+        }
+    }
+    for cmd in &mut cmds {
+        cmd.localize(&preferred);
+    }
     Ok(Json(cmds))
 }
 
@@ -389,6 +446,7 @@ async fn create_interaction(
         server_id: interaction.server_id,
         channel_id: interaction.channel_id,
         user_id: Some(auth.user_id),
+        trace_context: None,
     });
 
     Ok(Json(interaction))
@@ -396,7 +454,7 @@ async fn create_interaction(
 
 /// POST /api/v1/interactions/{interaction_id}/callback — Bot responds to interaction.
 async fn interaction_callback(
-    Extension(_auth): Extension<AuthContext>,
+    Extension(_caller): Extension<AppCaller>,
     State(state): State<Arc<AppState>>,
     Path(_interaction_id): Path<Uuid>,
     Json(body): Json<InteractionResponse>,
@@ -413,6 +471,7 @@ async fn interaction_callback(
                 server_id: None,
                 channel_id: None,
                 user_id: None,
+                trace_context: None,
             });
         }
     }
@@ -424,15 +483,16 @@ async fn interaction_callback(
 // Helpers
 // ============================================================================
 
+/// The application's owner, or the bot itself, may manage its commands.
 async fn verify_app_access(
     state: &AppState,
     app_id: Uuid,
-    user_id: Uuid,
+    caller: &AppCaller,
 ) -> NexusResult<()> {
     let app = bots::get_bot(&state.db.pool, app_id)
         .await?
         .ok_or(NexusError::NotFound { resource: "application".to_string() })?;
-    if app.owner_id != user_id {
+    if !caller.can_manage(app.id, app.owner_id) {
         return Err(NexusError::Forbidden);
     }
     Ok(())
@@ -445,5 +505,6 @@ fn broadcast_command_event(state: &AppState, cmd: &SlashCommand, event_type: &st
         server_id: cmd.server_id,
         channel_id: None,
         user_id: None,
+        trace_context: None,
     });
 }
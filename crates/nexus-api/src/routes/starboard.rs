@@ -0,0 +1,306 @@
+//! Starboard routes — per-server highlights configuration.
+//!
+//! GET /servers/:id/starboard          — Get starboard settings (MANAGE_SERVER)
+//! PUT /servers/:id/starboard          — Replace starboard settings (MANAGE_SERVER)
+//! GET /servers/:id/starboard/entries  — List starred messages (members)
+//!
+//! Reposting itself happens in [`on_reaction_change`], called by the
+//! reaction routes after a reaction is added or removed.
+
+use axum::{
+    extract::{Extension, Path, Query, State},
+    middleware,
+    routing::get,
+    Json, Router,
+};
+use nexus_common::{
+    error::{NexusError, NexusResult},
+    gateway_event::{event_types, GatewayEvent},
+    models::channel::Channel,
+    permissions::Permissions,
+    snowflake,
+};
+use nexus_db::repository::{channels, members, messages, reactions, servers, starboard};
+use serde::Deserialize;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{middleware::AuthContext, routes::messages::message_row_to_json, AppState};
+
+pub fn router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/servers/{server_id}/starboard", get(get_settings).put(put_settings))
+        .route("/servers/{server_id}/starboard/entries", get(list_entries))
+        .route_layer(middleware::from_fn(crate::middleware::auth_middleware))
+}
+
+// ============================================================
+// Request / response types
+// ============================================================
+
+#[derive(Debug, Deserialize)]
+struct SettingsRequest {
+    #[serde(default)]
+    enabled: bool,
+    channel_id: Option<Uuid>,
+    emoji: Option<String>,
+    threshold: Option<i32>,
+}
+
+#[derive(Debug, Deserialize)]
+struct EntriesParams {
+    limit: Option<i64>,
+}
+
+const DEFAULT_EMOJI: &str = "⭐";
+const DEFAULT_THRESHOLD: i32 = 3;
+/// Upper bound on `threshold`.
+const MAX_THRESHOLD: i32 = 1000;
+
+fn settings_json(s: &starboard::StarboardSettingsRow) -> serde_json::Value {
+    serde_json::json!({
+        "server_id": s.server_id,
+        "enabled": s.enabled,
+        "channel_id": s.channel_id,
+        "emoji": s.emoji,
+        "threshold": s.threshold,
+    })
+}
+
+fn entry_json(e: &starboard::StarboardEntryRow) -> serde_json::Value {
+    serde_json::json!({
+        "message_id": e.message_id,
+        "server_id": e.server_id,
+        "starboard_message_id": e.starboard_message_id,
+        "star_count": e.star_count,
+        "created_at": e.created_at,
+    })
+}
+
+/// Require MANAGE_SERVER in `server_id`.
+async fn require_manage_server(state: &AppState, server_id: Uuid, user_id: Uuid) -> NexusResult<()> {
+    let server = servers::find_by_id(&state.db.pool, server_id)
+        .await?
+        .ok_or(NexusError::NotFound {
+            resource: "Server".into(),
+        })?;
+    let permissions = crate::permissions::resolve(&state.db.pool, &server, None, user_id)
+        .await?
+        .ok_or(NexusError::Forbidden)?;
+    crate::permissions::require(permissions, Permissions::MANAGE_SERVER)
+}
+
+// ============================================================
+// Settings
+// ============================================================
+
+/// GET /api/v1/servers/:server_id/starboard
+async fn get_settings(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path(server_id): Path<Uuid>,
+) -> NexusResult<Json<serde_json::Value>> {
+    require_manage_server(&state, server_id, auth.user_id).await?;
+    let settings = starboard::get_settings(&state.db.pool, server_id)
+        .await?
+        .unwrap_or(starboard::StarboardSettingsRow {
+            server_id,
+            enabled: false,
+            channel_id: None,
+            emoji: DEFAULT_EMOJI.into(),
+            threshold: DEFAULT_THRESHOLD,
+            updated_at: chrono::Utc::now(),
+        });
+    Ok(Json(settings_json(&settings)))
+}
+
+/// PUT /api/v1/servers/:server_id/starboard
+async fn put_settings(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path(server_id): Path<Uuid>,
+    Json(body): Json<SettingsRequest>,
+) -> NexusResult<Json<serde_json::Value>> {
+    require_manage_server(&state, server_id, auth.user_id).await?;
+
+    let threshold = body.threshold.unwrap_or(DEFAULT_THRESHOLD);
+    if !(1..=MAX_THRESHOLD).contains(&threshold) {
+        return Err(NexusError::Validation {
+            message: format!("threshold must be between 1 and {MAX_THRESHOLD}"),
+        });
+    }
+    let emoji = body.emoji.as_deref().map(str::trim).unwrap_or(DEFAULT_EMOJI);
+    if emoji.is_empty() || emoji.len() > 64 {
+        return Err(NexusError::Validation {
+            message: "emoji must be between 1 and 64 bytes".into(),
+        });
+    }
+
+    if body.enabled {
+        let channel_id = body.channel_id.ok_or(NexusError::Validation {
+            message: "channel_id is required when the starboard is enabled".into(),
+        })?;
+        channels::find_by_id(&state.db.pool, channel_id)
+            .await?
+            .filter(|c| c.server_id == Some(server_id))
+            .ok_or(NexusError::Validation {
+                message: "Starboard channel must belong to this server".into(),
+            })?;
+    }
+
+    let saved = starboard::upsert_settings(
+        &state.db.pool,
+        &starboard::StarboardSettingsRow {
+            server_id,
+            enabled: body.enabled,
+            channel_id: body.channel_id,
+            emoji: emoji.to_string(),
+            threshold,
+            updated_at: chrono::Utc::now(),
+        },
+    )
+    .await?;
+
+    Ok(Json(settings_json(&saved)))
+}
+
+// ============================================================
+// Entries
+// ============================================================
+
+/// GET /api/v1/servers/:server_id/starboard/entries?limit=
+async fn list_entries(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path(server_id): Path<Uuid>,
+    Query(params): Query<EntriesParams>,
+) -> NexusResult<Json<Vec<serde_json::Value>>> {
+    members::find_member(&state.db.pool, auth.user_id, server_id)
+        .await?
+        .ok_or(NexusError::Forbidden)?;
+    let limit = params.limit.unwrap_or(50).clamp(1, 100);
+    let entries = starboard::list_entries(&state.db.pool, server_id, limit).await?;
+    Ok(Json(entries.iter().map(entry_json).collect()))
+}
+
+// ============================================================
+// Reaction hook
+// ============================================================
+
+/// Repost or update a message after its `emoji` reactions changed.
+///
+/// Failures are logged rather than surfaced — a broken starboard must never
+/// fail the reaction itself.
+pub(crate) async fn on_reaction_change(
+    state: &AppState,
+    channel: &Channel,
+    message_id: Uuid,
+    emoji: &str,
+) {
+    let Some(server_id) = channel.server_id else {
+        return;
+    };
+    if let Err(e) = sync_entry(state, server_id, channel, message_id, emoji).await {
+        tracing::warn!(%server_id, %message_id, error = %e, "Failed to update starboard");
+    }
+}
+
+async fn sync_entry(
+    state: &AppState,
+    server_id: Uuid,
+    channel: &Channel,
+    message_id: Uuid,
+    emoji: &str,
+) -> Result<(), sqlx::Error> {
+    let pool = &state.db.pool;
+    let Some(settings) = starboard::get_settings(pool, server_id).await? else {
+        return Ok(());
+    };
+    if !settings.enabled || settings.emoji != emoji {
+        return Ok(());
+    }
+
+    let count = reactions::get_reaction_counts(pool, message_id)
+        .await?
+        .into_iter()
+        .find(|rc| rc.emoji == emoji)
+        .map_or(0, |rc| rc.count);
+    let max_len = nexus_common::config::get().limits.max_message_length as usize;
+
+    if let Some(entry) = starboard::find_entry(pool, message_id).await? {
+        if entry.star_count as i64 == count {
+            return Ok(());
+        }
+        starboard::set_star_count(pool, message_id, count as i32).await?;
+        let (Some(repost_id), Some(source)) = (
+            entry.starboard_message_id,
+            messages::find_by_id(pool, message_id).await?,
+        ) else {
+            return Ok(());
+        };
+        let content = crate::starboard::render(
+            emoji,
+            count,
+            channel.id,
+            source.author_id,
+            &source.content,
+            max_len,
+        );
+        // The repost may have been removed by a moderator
+        let Some(repost) = messages::find_by_id(pool, repost_id).await? else {
+            return Ok(());
+        };
+        let updated = messages::update_message(pool, repost.id, &content).await?;
+        publish(state, event_types::MESSAGE_UPDATE, server_id, &updated);
+        return Ok(());
+    }
+
+    let Some(target) = crate::starboard::target_channel(&settings, channel.id, count) else {
+        return Ok(());
+    };
+    if !starboard::claim_entry(pool, message_id, server_id, count as i32).await? {
+        return Ok(());
+    }
+    let Some(source) = messages::find_by_id(pool, message_id).await? else {
+        return starboard::delete_entry(pool, message_id).await;
+    };
+
+    let content =
+        crate::starboard::render(emoji, count, channel.id, source.author_id, &source.content, max_len);
+    let repost = match messages::create_message(
+        pool,
+        snowflake::generate_id(),
+        target,
+        source.author_id,
+        &content,
+        0,
+        Some(source.id),
+        Some(channel.id),
+        &[],
+        &[],
+        false,
+        0,
+    )
+    .await
+    {
+        Ok(repost) => repost,
+        Err(e) => {
+            starboard::delete_entry(pool, message_id).await?;
+            return Err(e);
+        }
+    };
+    starboard::set_starboard_message(pool, message_id, repost.id).await?;
+    publish(state, event_types::MESSAGE_CREATE, server_id, &repost);
+    Ok(())
+}
+
+fn publish(state: &AppState, event_type: &str, server_id: Uuid, msg: &messages::MessageRow) {
+    let _ = state.gateway_tx.send(GatewayEvent {
+        event_type: event_type.into(),
+        data: message_row_to_json(msg, &[]),
+        server_id: Some(server_id),
+        channel_id: Some(msg.channel_id),
+        user_id: Some(msg.author_id),
+        trace_context: None,
+    });
+}
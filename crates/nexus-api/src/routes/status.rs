@@ -0,0 +1,143 @@
+//! Public status page — component health and uptime history.
+//!
+//! Unauthenticated so communities can embed a status widget. Values come from
+//! the self-check job (see [`crate::jobs::status_check`]); this module only
+//! reads and aggregates them.
+
+use axum::{
+    extract::{Query, State},
+    routing::get,
+    Json, Router,
+};
+use nexus_common::error::NexusResult;
+use nexus_db::repository::status::{self, UptimeBucket};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use crate::{jobs::status_check::ComponentStatus, AppState};
+
+/// Status routes (public).
+pub fn router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/status", get(get_status))
+        .route("/status/history", get(get_history))
+}
+
+#[derive(Serialize)]
+struct StatusResponse {
+    status: &'static str,
+    version: &'static str,
+    uptime_secs: u64,
+    components: Vec<ComponentResponse>,
+}
+
+#[derive(Serialize)]
+struct ComponentResponse {
+    name: String,
+    status: String,
+    latency_ms: Option<i64>,
+    detail: Option<String>,
+    checked_at: chrono::DateTime<chrono::Utc>,
+    /// Percentage of operational checks over the last 7 / 30 / 90 days.
+    uptime_7d: Option<f64>,
+    uptime_30d: Option<f64>,
+    uptime_90d: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct HistoryParams {
+    component: Option<String>,
+    /// Number of days to return (default 30, max 90).
+    days: Option<i64>,
+}
+
+#[derive(Serialize)]
+struct HistoryDay {
+    component: String,
+    day: String,
+    total_checks: i64,
+    uptime_percent: f64,
+}
+
+/// GET /api/v1/status — current health of every component.
+async fn get_status(State(state): State<Arc<AppState>>) -> NexusResult<Json<StatusResponse>> {
+    let latest = status::latest_checks(&state.db.pool).await?;
+    let since = chrono::Utc::now() - chrono::Duration::days(90);
+    let buckets = status::uptime_buckets(&state.db.pool, None, since).await?;
+
+    let overall = latest
+        .iter()
+        .map(|c| ComponentStatus::parse(&c.status))
+        .max()
+        .unwrap_or(ComponentStatus::Operational);
+
+    let components = latest
+        .into_iter()
+        .map(|c| {
+            let mine: Vec<&UptimeBucket> =
+                buckets.iter().filter(|b| b.component == c.component).collect();
+            ComponentResponse {
+                uptime_7d: uptime_over(&mine, 7),
+                uptime_30d: uptime_over(&mine, 30),
+                uptime_90d: uptime_over(&mine, 90),
+                name: c.component,
+                status: c.status,
+                latency_ms: c.latency_ms,
+                detail: c.detail,
+                checked_at: c.checked_at,
+            }
+        })
+        .collect();
+
+    let uptime_secs = (chrono::Utc::now() - state.started_at).num_seconds().max(0) as u64;
+
+    Ok(Json(StatusResponse {
+        status: overall.as_str(),
+        version: env!("CARGO_PKG_VERSION"),
+        uptime_secs,
+        components,
+    }))
+}
+
+/// GET /api/v1/status/history?component=&days= — daily uptime buckets.
+async fn get_history(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<HistoryParams>,
+) -> NexusResult<Json<Vec<HistoryDay>>> {
+    let days = params.days.unwrap_or(30).clamp(1, 90);
+    let since = chrono::Utc::now() - chrono::Duration::days(days);
+    let buckets =
+        status::uptime_buckets(&state.db.pool, params.component.as_deref(), since).await?;
+
+    Ok(Json(
+        buckets
+            .into_iter()
+            .map(|b| HistoryDay {
+                uptime_percent: percent(b.operational_checks, b.total_checks),
+                component: b.component,
+                day: b.day,
+                total_checks: b.total_checks,
+            })
+            .collect(),
+    ))
+}
+
+/// Uptime percentage across the most recent `days` daily buckets.
+fn uptime_over(buckets: &[&UptimeBucket], days: i64) -> Option<f64> {
+    let cutoff = (chrono::Utc::now() - chrono::Duration::days(days))
+        .format("%Y-%m-%d")
+        .to_string();
+    let (ok, total) = buckets
+        .iter()
+        .filter(|b| b.day >= cutoff)
+        .fold((0, 0), |(ok, total), b| (ok + b.operational_checks, total + b.total_checks));
+    (total > 0).then(|| percent(ok, total))
+}
+
+fn percent(ok: i64, total: i64) -> f64 {
+    if total == 0 {
+        return 100.0;
+    }
+    // Two decimal places is plenty for a status widget.
+    ((ok as f64 / total as f64) * 10_000.0).round() / 100.0
+}
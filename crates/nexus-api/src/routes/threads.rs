@@ -59,7 +59,7 @@ pub fn router() -> Router<Arc<AppState>> {
 // Response helpers
 // ============================================================
 
-fn thread_response(row: ThreadRow) -> Thread {
+pub(crate) fn thread_response(row: ThreadRow) -> Thread {
     Thread {
         id: row.channel_id,
         parent_channel_id: row.parent_channel_id.unwrap_or(row.channel_id),
@@ -72,6 +72,7 @@ fn thread_response(row: ThreadRow) -> Thread {
         archived: row.archived,
         archived_at: row.archived_at,
         locked: row.locked,
+        pinned: row.pinned,
         tags: row.tags,
         created_at: row.created_at,
         updated_at: row.updated_at,
@@ -91,11 +92,12 @@ async fn create_thread(
     validate_request(&body)?;
 
     // Verify parent channel exists
-    let _channel = channels::find_by_id(&state.db.pool, channel_id)
+    let channel = channels::find_by_id(&state.db.pool, channel_id)
         .await?
         .ok_or(NexusError::NotFound {
             resource: "Channel".into(),
         })?;
+    super::channels::require_unarchived(&channel)?;
 
     // Verify the user is a member of the server / channel
     // (simplified: just check the channel row exists — full permission
@@ -151,6 +153,7 @@ async fn create_thread(
         server_id: None,
         channel_id: Some(thread.id),
         user_id: Some(auth.user_id),
+        trace_context: None,
     });
 
     Ok(Json(thread))
@@ -266,6 +269,7 @@ async fn update_thread(
         server_id: None,
         channel_id: Some(thread.id),
         user_id: Some(auth.user_id),
+        trace_context: None,
     });
 
     Ok(Json(thread))
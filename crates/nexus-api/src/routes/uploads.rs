@@ -3,15 +3,16 @@
 //! POST  /api/v1/attachments/upload          — Upload a file (multipart/form-data)
 //! GET   /api/v1/attachments/:id             — Get attachment metadata + presigned URL
 //! DELETE /api/v1/attachments/:id            — Delete own attachment
+//! GET   /api/v1/attachments/:id/transcript  — Speech-to-text transcript (audio only)
 
 use axum::{
-    extract::{Multipart, Path, State},
+    extract::{DefaultBodyLimit, Multipart, Path, State},
     middleware,
     routing::{get, post},
     Json, Router,
 };
 use nexus_common::error::{NexusError, NexusResult};
-use nexus_db::repository::attachments;
+use nexus_db::repository::{attachments, media_jobs, transcripts};
 use serde::Serialize;
 use std::sync::Arc;
 use uuid::Uuid;
@@ -19,36 +20,29 @@ use uuid::Uuid;
 use crate::{middleware::AuthContext, AppState};
 use axum::extract::Extension;
 
-// ============================================================
-// Maximum upload size: 100 MiB
-// ============================================================
-const MAX_UPLOAD_BYTES: usize = 100 * 1024 * 1024;
-
-/// Allowed content-type categories. Reject executables server-side.
-fn is_allowed_content_type(ct: &str) -> bool {
-    matches!(
-        ct,
-        // Images
-        | "image/jpeg" | "image/png" | "image/gif" | "image/webp"
-        | "image/svg+xml" | "image/avif" | "image/bmp" | "image/tiff"
-        // Video
-        | "video/mp4" | "video/webm" | "video/ogg" | "video/quicktime"
-        // Audio
-        | "audio/mpeg" | "audio/ogg" | "audio/wav" | "audio/flac"
-        | "audio/aac" | "audio/opus" | "audio/webm"
-        // Documents
-        | "application/pdf" | "text/plain" | "text/markdown"
-        | "application/zip" | "application/x-tar"
-    )
+/// Room for multipart boundaries, part headers and small form fields on top
+/// of the files in an upload request.
+const MULTIPART_OVERHEAD_BYTES: usize = 1024 * 1024;
+
+/// Body limit for a route taking up to `files` uploads of
+/// `limits.max_file_size_bytes` each. Everything else gets
+/// `limits.max_request_body_bytes`.
+pub(crate) fn upload_body_limit(files: u32) -> usize {
+    let max_file = nexus_common::config::get().limits.max_file_size_bytes as usize;
+    max_file.saturating_mul(files.max(1) as usize).saturating_add(MULTIPART_OVERHEAD_BYTES)
 }
 
 pub fn router() -> Router<Arc<AppState>> {
     Router::new()
-        .route("/attachments/upload", post(upload_file))
+        .route(
+            "/attachments/upload",
+            post(upload_file).layer(DefaultBodyLimit::max(upload_body_limit(1))),
+        )
         .route(
             "/attachments/{id}",
             get(get_attachment).delete(delete_attachment),
         )
+        .route("/attachments/{id}/transcript", get(get_transcript))
         .route_layer(middleware::from_fn(crate::middleware::auth_middleware))
 }
 
@@ -67,6 +61,8 @@ struct AttachmentResponse {
     height: Option<i32>,
     duration_secs: Option<f64>,
     spoiler: bool,
+    blurhash: Option<String>,
+    thumbnail_url: Option<String>,
     status: String,
 }
 
@@ -109,7 +105,7 @@ async fn upload_file(
                 }
 
                 // Validate content-type early
-                if !is_allowed_content_type(&content_type) {
+                if !crate::file_types::is_allowed(&content_type) {
                     return Err(NexusError::Validation {
                         message: format!("File type '{content_type}' is not allowed"),
                     });
@@ -122,15 +118,14 @@ async fn upload_file(
                         message: format!("Failed to read file: {e}"),
                     })?;
 
-                if bytes.len() > MAX_UPLOAD_BYTES {
+                let max_bytes = nexus_common::config::get().limits.max_file_size_bytes;
+                if bytes.len() as u64 > max_bytes {
                     return Err(NexusError::Validation {
-                        message: format!(
-                            "File too large: {} bytes (max {} bytes)",
-                            bytes.len(),
-                            MAX_UPLOAD_BYTES
-                        ),
+                        message: format!("File too large: {} bytes (max {max_bytes} bytes)", bytes.len()),
                     });
                 }
+                crate::file_types::check(&content_type, &bytes)
+                    .map_err(|message| NexusError::Validation { message })?;
 
                 file_data = Some(bytes.to_vec());
             }
@@ -149,7 +144,13 @@ async fn upload_file(
     let data = file_data.ok_or(NexusError::Validation {
         message: "No file field in request".into(),
     })?;
+    if let Some(channel_id) = channel_id {
+        super::e2ee::require_plaintext_channel(&state, channel_id).await?;
+    }
 
+    // Drop EXIF/XMP before anything is stored; the size comes from the header.
+    let data = crate::media::strip_metadata(&content_type, data);
+    let (width, height) = image_size(&content_type, &data);
     let size = data.len() as i64;
 
     // Sanitize filename
@@ -195,22 +196,38 @@ async fn upload_file(
         &content_type,
         size,
         &storage_key,
-        None, // width
-        None, // height
+        width,
+        height,
         None, // duration
         spoiler,
         Some(&hash_hex),
     )
     .await?;
 
-    // Mark ready immediately (no async processing for now)
-    let row = attachments::mark_ready(
-        &state.db.pool,
-        row.id,
-        url.as_deref().unwrap_or(""),
-        None, // blurhash — would need async image processing
-    )
-    .await?;
+    // The file is usable right away; thumbnail and blurhash follow from the media job.
+    let row = attachments::mark_ready(&state.db.pool, row.id, url.as_deref().unwrap_or(""), None).await?;
+    if state.media.is_some()
+        && crate::media::is_processable(&row.content_type)
+        && let Err(e) = media_jobs::enqueue(&state.db.pool, row.id).await
+    {
+        tracing::warn!(attachment_id = %row.id, error = %e, "Failed to queue media processing");
+    }
+
+    // Voice messages are transcribed in the background when STT is enabled.
+    if state.transcription.is_some() && crate::transcription::is_transcribable(&row.content_type) {
+        if let Err(e) = transcripts::enqueue(
+            &state.db.pool,
+            transcripts::SOURCE_ATTACHMENT,
+            row.id,
+            &row.storage_key,
+            &row.content_type,
+            row.channel_id,
+        )
+        .await
+        {
+            tracing::warn!(attachment_id = %row.id, error = %e, "Failed to queue transcription");
+        }
+    }
 
     Ok(Json(AttachmentResponse {
         id: row.id,
@@ -222,10 +239,77 @@ async fn upload_file(
         height: row.height,
         duration_secs: row.duration_secs,
         spoiler: row.spoiler,
+        blurhash: row.blurhash,
+        thumbnail_url: row.thumbnail_url,
         status: row.status,
     }))
 }
 
+// ============================================================
+// Files sent inline with a message
+// ============================================================
+
+/// A file from a multipart message create, already written to storage but
+/// not yet recorded in the database.
+pub(crate) struct StoredFile {
+    pub id: Uuid,
+    pub filename: String,
+    pub content_type: String,
+    pub size: i64,
+    pub storage_key: String,
+    pub url: Option<String>,
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+    pub sha256: String,
+    /// Queue the file for the media job once its row exists.
+    pub needs_processing: bool,
+}
+
+/// Write a message file to storage. The caller inserts the attachment row
+/// (in the message's transaction) and deletes the object if that fails.
+pub(crate) async fn store_message_file(
+    state: &AppState,
+    uploader_id: Uuid,
+    filename: &str,
+    content_type: &str,
+    data: Vec<u8>,
+) -> NexusResult<StoredFile> {
+    use sha2::{Digest, Sha256};
+
+    let filename = sanitize_filename(filename);
+    let ext = filename.rsplit('.').next().unwrap_or("bin").to_lowercase();
+    let id = Uuid::new_v4();
+    let storage_key = format!("uploads/{uploader_id}/{id}.{ext}");
+    let data = crate::media::strip_metadata(content_type, data);
+    let (width, height) = image_size(content_type, &data);
+    let size = data.len() as i64;
+    let sha256 = hex::encode(Sha256::digest(&data));
+
+    state
+        .storage
+        .put_object(&storage_key, data, content_type)
+        .await
+        .map_err(NexusError::Internal)?;
+    let url = state
+        .storage
+        .presigned_get_url(&storage_key, 3600 * 24 * 7)
+        .await
+        .ok();
+
+    Ok(StoredFile {
+        id,
+        filename,
+        content_type: content_type.to_owned(),
+        size,
+        storage_key,
+        url,
+        width,
+        height,
+        sha256,
+        needs_processing: state.media.is_some() && crate::media::is_processable(content_type),
+    })
+}
+
 // ============================================================
 // GET /attachments/:id
 // ============================================================
@@ -264,10 +348,48 @@ async fn get_attachment(
         height: row.height,
         duration_secs: row.duration_secs,
         spoiler: row.spoiler,
+        blurhash: row.blurhash,
+        thumbnail_url: row.thumbnail_url,
         status: row.status,
     }))
 }
 
+// ============================================================
+// GET /attachments/:id/transcript
+// ============================================================
+
+#[derive(Serialize)]
+struct TranscriptResponse {
+    attachment_id: Uuid,
+    /// `pending` | `processing` | `completed` | `failed`
+    status: String,
+    language: Option<String>,
+    text: Option<String>,
+    segments: serde_json::Value,
+    completed_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+async fn get_transcript(
+    Extension(_auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+) -> NexusResult<Json<TranscriptResponse>> {
+    let row = transcripts::find_for_source(&state.db.pool, transcripts::SOURCE_ATTACHMENT, id)
+        .await?
+        .ok_or(NexusError::NotFound {
+            resource: "Transcript".into(),
+        })?;
+
+    Ok(Json(TranscriptResponse {
+        attachment_id: id,
+        status: row.status,
+        language: row.language,
+        text: row.text,
+        segments: row.segments,
+        completed_at: row.completed_at,
+    }))
+}
+
 // ============================================================
 // DELETE /attachments/:id
 // ============================================================
@@ -294,6 +416,9 @@ async fn delete_attachment(
 
     // Delete from object storage (best-effort — don't fail if already gone)
     let _ = state.storage.delete_object(&row.storage_key).await;
+    if let Some(key) = &row.thumbnail_key {
+        let _ = state.storage.delete_object(key).await;
+    }
 
     Ok(Json(serde_json::json!({ "deleted": true })))
 }
@@ -303,9 +428,20 @@ async fn delete_attachment(
 // ============================================================
 
 /// Strip path separators and null bytes from filenames.
-fn sanitize_filename(name: &str) -> String {
+pub(crate) fn sanitize_filename(name: &str) -> String {
     name.chars()
         .filter(|c| !matches!(c, '/' | '\\' | '\0'))
         .take(255)
         .collect()
 }
+
+/// Header-reported size of an image upload, as stored on the attachment.
+fn image_size(content_type: &str, data: &[u8]) -> (Option<i32>, Option<i32>) {
+    if !content_type.starts_with("image/") {
+        return (None, None);
+    }
+    match crate::media::dimensions(data) {
+        Some((w, h)) => (i32::try_from(w).ok(), i32::try_from(h).ok()),
+        None => (None, None),
+    }
+}
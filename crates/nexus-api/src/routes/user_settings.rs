@@ -0,0 +1,133 @@
+//! User settings sync — settings that follow a user across clients.
+//!
+//! GET   /users/@me/settings                 — All synced settings
+//! PUT   /users/@me/settings/server-layout   — Replace the server list layout
+//! PUT   /users/@me/settings/user-volumes    — Replace per-user voice volumes
+//! PATCH /users/@me/settings/user-volumes    — Set or clear (`null`) some of them
+//!
+//! Changes are pushed to the user's other sessions as `SERVER_LAYOUT_UPDATE`
+//! and `USER_VOLUMES_UPDATE`, so the sidebar looks the same and others sound
+//! the same on every device. The settings also arrive in READY.
+
+use axum::{
+    extract::{Extension, State},
+    middleware,
+    routing::{get, put},
+    Json, Router,
+};
+use nexus_common::{
+    error::NexusResult,
+    gateway_event::{event_types, GatewayEvent},
+    models::user_settings::{ServerLayout, UserSettings, UserVolumes, UserVolumesPatch},
+    validation::validate_request,
+};
+use nexus_db::repository::{servers, user_settings};
+use std::collections::HashSet;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{middleware::AuthContext, AppState};
+
+pub fn router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/users/@me/settings", get(get_settings))
+        .route("/users/@me/settings/server-layout", put(update_server_layout))
+        .route(
+            "/users/@me/settings/user-volumes",
+            put(replace_user_volumes).patch(patch_user_volumes),
+        )
+        .route_layer(middleware::from_fn(crate::middleware::auth_middleware))
+}
+
+/// Servers the user currently belongs to.
+async fn member_servers(state: &AppState, user_id: Uuid) -> NexusResult<HashSet<Uuid>> {
+    let servers = servers::list_user_servers(&state.db.pool, user_id).await?;
+    Ok(servers.into_iter().map(|s| s.id).collect())
+}
+
+/// GET /api/v1/users/@me/settings
+async fn get_settings(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+) -> NexusResult<Json<UserSettings>> {
+    let mut settings = user_settings::get(&state.db.pool, auth.user_id).await?;
+    // Servers left since the layout was saved are no longer shown.
+    let member_of = member_servers(&state, auth.user_id).await?;
+    settings.server_layout.retain_servers(&member_of);
+    Ok(Json(settings))
+}
+
+/// PUT /api/v1/users/@me/settings/server-layout — Replace the whole layout.
+///
+/// Servers the user isn't a member of are dropped rather than rejected, so a
+/// client racing a leave or kick doesn't fail its save.
+async fn update_server_layout(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Json(mut layout): Json<ServerLayout>,
+) -> NexusResult<Json<UserSettings>> {
+    validate_request(&layout)?;
+    let member_of = member_servers(&state, auth.user_id).await?;
+    layout.retain_servers(&member_of);
+
+    let settings = user_settings::set_server_layout(&state.db.pool, auth.user_id, &layout).await?;
+
+    let _ = state.gateway_tx.send(GatewayEvent {
+        event_type: event_types::SERVER_LAYOUT_UPDATE.into(),
+        data: serde_json::json!({
+            "server_layout": settings.server_layout,
+            "version": settings.version,
+        }),
+        server_id: None,
+        channel_id: None,
+        user_id: Some(auth.user_id),
+        trace_context: None,
+    });
+
+    Ok(Json(settings))
+}
+
+/// PUT /api/v1/users/@me/settings/user-volumes — Replace every saved volume.
+async fn replace_user_volumes(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Json(volumes): Json<UserVolumes>,
+) -> NexusResult<Json<UserSettings>> {
+    validate_request(&volumes)?;
+    save_user_volumes(&state, auth.user_id, &volumes).await
+}
+
+/// PATCH /api/v1/users/@me/settings/user-volumes — Change some volumes,
+/// leaving the rest as they are.
+async fn patch_user_volumes(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Json(patch): Json<UserVolumesPatch>,
+) -> NexusResult<Json<UserSettings>> {
+    let mut volumes = user_settings::get(&state.db.pool, auth.user_id).await?.user_volumes;
+    volumes.apply(patch);
+    validate_request(&volumes)?;
+    save_user_volumes(&state, auth.user_id, &volumes).await
+}
+
+async fn save_user_volumes(
+    state: &AppState,
+    user_id: Uuid,
+    volumes: &UserVolumes,
+) -> NexusResult<Json<UserSettings>> {
+    let settings = user_settings::set_user_volumes(&state.db.pool, user_id, volumes).await?;
+
+    let _ = state.gateway_tx.send(GatewayEvent {
+        event_type: event_types::USER_VOLUMES_UPDATE.into(),
+        data: serde_json::json!({
+            "user_volumes": settings.user_volumes,
+            "version": settings.version,
+        }),
+        server_id: None,
+        channel_id: None,
+        user_id: Some(user_id),
+        trace_context: None,
+    });
+
+    Ok(Json(settings))
+}
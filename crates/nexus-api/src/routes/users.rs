@@ -1,7 +1,14 @@
-//! User routes — profile management, user lookup.
+//! User routes — profile management, user lookup, account deletion.
+//!
+//! Deleting an account keeps the user row as a tombstone, so messages keep
+//! an author: it is renamed `deleted-user-…` and cleared of everything
+//! identifying (see [`users::tombstone`]). The user leaves every server,
+//! loses their sessions, bot applications and data exports, and the remote
+//! servers of their federated rooms are told with a `nexus.user.delete` EDU.
 
 use axum::{
     extract::{Extension, Path, State},
+    http::StatusCode,
     middleware,
     routing::get,
     Json, Router,
@@ -11,16 +18,21 @@ use nexus_common::{
     models::user::{UpdateUserRequest, UserResponse},
     validation::validate_request,
 };
-use nexus_db::repository::users;
+use nexus_db::repository::{bots, data_exports, federated_rooms, members, servers, sessions, users};
+use nexus_federation::types;
+use serde::Deserialize;
 use std::sync::Arc;
 use uuid::Uuid;
 
-use crate::{middleware::AuthContext, AppState};
+use crate::{auth, middleware::AuthContext, AppState};
 
 /// User routes (all require authentication).
 pub fn router() -> Router<Arc<AppState>> {
     Router::new()
-        .route("/users/@me", get(get_current_user).patch(update_current_user))
+        .route(
+            "/users/@me",
+            get(get_current_user).patch(update_current_user).delete(delete_current_user),
+        )
         .route("/users/{user_id}", get(get_user))
         .route_layer(middleware::from_fn(
             crate::middleware::auth_middleware,
@@ -38,7 +50,7 @@ async fn get_current_user(
             resource: "User".into(),
         })?;
 
-    Ok(Json(user.into()))
+    Ok(Json(UserResponse::own(user)))
 }
 
 /// PATCH /api/v1/users/@me — Update the authenticated user's profile.
@@ -67,10 +79,78 @@ async fn update_current_user(
         body.display_name.as_deref(),
         body.bio.as_deref(),
         body.status.as_deref(),
+        body.locale.as_deref(),
     )
     .await?;
 
-    Ok(Json(user.into()))
+    Ok(Json(UserResponse::own(user)))
+}
+
+#[derive(Deserialize)]
+struct DeleteAccountRequest {
+    password: String,
+}
+
+/// DELETE /api/v1/users/@me — Delete the authenticated user's account.
+///
+/// Needs the password. Servers the user owns must be transferred or
+/// deleted first.
+async fn delete_current_user(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Json(body): Json<DeleteAccountRequest>,
+) -> NexusResult<StatusCode> {
+    let pool = &state.db.pool;
+    let user = users::find_by_id(pool, auth.user_id)
+        .await?
+        .ok_or(NexusError::NotFound {
+            resource: "User".into(),
+        })?;
+    let valid =
+        auth::verify_password(&body.password, &user.password_hash).map_err(|_| NexusError::InvalidCredentials)?;
+    if !valid {
+        return Err(NexusError::InvalidCredentials);
+    }
+
+    let joined = servers::list_user_servers(pool, user.id).await?;
+    if joined.iter().any(|server| server.owner_id == user.id) {
+        return Err(NexusError::Validation {
+            message: "Transfer ownership of or delete the servers you own first".into(),
+        });
+    }
+    // Before the memberships that say who has seen the user are gone
+    let peers = federated_rooms::remote_servers_for_user(pool, user.id, &state.server_name).await?;
+    let mxid = types::mxid(&user.username, &state.server_name);
+
+    // The account is gone all at once or not at all; storage and remote
+    // servers are only touched once it has committed.
+    let mut tx = pool.begin().await?;
+    for server in &joined {
+        members::remove_member(&mut *tx, user.id, server.id).await?;
+        servers::decrement_member_count(&mut *tx, server.id).await?;
+    }
+    users::tombstone(&mut *tx, user.id, &format!("deleted-user-{}", &user.id.simple().to_string()[16..])).await?;
+    let revoked = sessions::delete_others(&mut *tx, user.id, None).await?;
+    let removed_bots = bots::delete_by_owner(&mut *tx, user.id).await?;
+    let export_keys = data_exports::delete_for_user(&mut *tx, user.id).await?;
+    tx.commit().await?;
+    state.live_sessions.remove_user(user.id, None);
+
+    for key in export_keys {
+        if let Err(e) = state.storage.delete_object(&key).await {
+            tracing::warn!(user_id = %user.id, key, error = %e, "Failed to delete data export of deleted account");
+        }
+    }
+
+    tracing::info!(user_id = %user.id, revoked, bots = removed_bots, peers = peers.len(), "Account deleted");
+    crate::security_events::record(
+        &state,
+        crate::security_events::events::ACCOUNT_DELETE,
+        serde_json::json!({ "user_id": user.id, "username": user.username }),
+    )
+    .await;
+    super::federation::send_user_deletion(&state, &mxid, peers);
+    Ok(StatusCode::NO_CONTENT)
 }
 
 /// GET /api/v1/users/:user_id — Get a user's public profile.
@@ -8,6 +8,7 @@
 //! - GET  /voice/channels/{channel_id}        — Get voice channel state (who's connected)
 //! - POST /voice/channels/{channel_id}/join    — Join a voice channel (pre-flight)
 //! - POST /voice/channels/{channel_id}/leave   — Leave a voice channel
+//! - GET  /voice/channels/{channel_id}/chat    — The channel's linked text chat
 //! - PATCH /voice/state                        — Update own voice state
 //! - POST /voice/channels/{channel_id}/mute    — Server mute/deaf a user (mod action)
 //! - GET  /voice/stats                         — Voice server statistics
@@ -21,7 +22,11 @@ use axum::{
 use nexus_common::{
     error::{NexusError, NexusResult},
     gateway_event::GatewayEvent,
+    models::channel::{Channel, ChannelType},
+    permissions::Permissions,
+    snowflake,
 };
+use nexus_db::repository::{channels, voice_chat};
 use nexus_voice::state::{VoiceGlobalStats, VoiceModAction, VoiceState, VoiceStateUpdate};
 use serde::Serialize;
 use std::sync::Arc;
@@ -47,6 +52,11 @@ pub fn router() -> Router<Arc<AppState>> {
             "/voice/channels/{channel_id}/leave",
             post(voice_leave),
         )
+        // Linked text chat
+        .route(
+            "/voice/channels/{channel_id}/chat",
+            get(get_voice_text_chat),
+        )
         // Update own voice state
         .route("/voice/state", patch(update_voice_state))
         // Server mute/deaf (moderation)
@@ -189,6 +199,7 @@ async fn voice_leave(
 
     // Remove from voice state
     state.voice_state.leave(auth.user_id).await;
+    voice_chat::record_leave(&state.db.pool, channel_id, auth.user_id).await?;
 
     // Broadcast leave event
     let _ = state.gateway_tx.send(GatewayEvent {
@@ -202,11 +213,41 @@ async fn voice_leave(
         server_id: voice_state.server_id,
         channel_id: Some(channel_id),
         user_id: Some(auth.user_id),
+        trace_context: None,
     });
 
     Ok(Json(serde_json::json!({ "success": true })))
 }
 
+/// GET /voice/channels/{channel_id}/chat — The voice channel's text chat.
+///
+/// Created on first request for voice channels that predate text chat.
+/// Reading and posting in it is gated separately, on having recently been
+/// in the call (see [`crate::permissions::for_voice_chat`]).
+async fn get_voice_text_chat(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(channel_id): Path<Uuid>,
+) -> NexusResult<Json<Channel>> {
+    let channel = channels::find_by_id(&state.db.pool, channel_id)
+        .await?
+        .ok_or_else(|| NexusError::NotFound { resource: "Channel".into() })?;
+    if !matches!(channel.channel_type, ChannelType::Voice | ChannelType::Stage) {
+        return Err(NexusError::Validation {
+            message: "Channel is not a voice channel".into(),
+        });
+    }
+    if let Some(permissions) =
+        crate::permissions::in_channel(&state.db.pool, &channel, auth.user_id).await?
+    {
+        crate::permissions::require(permissions, Permissions::VIEW_CHANNEL)?;
+    }
+
+    let chat =
+        voice_chat::find_or_create_chat(&state.db.pool, snowflake::generate_id(), &channel).await?;
+    Ok(Json(chat))
+}
+
 /// PATCH /voice/state — Update own voice state (mute/deaf/video/stream).
 async fn update_voice_state(
     State(state): State<Arc<AppState>>,
@@ -226,6 +267,7 @@ async fn update_voice_state(
         server_id: new_state.server_id,
         channel_id: Some(new_state.channel_id),
         user_id: Some(auth.user_id),
+        trace_context: None,
     });
 
     Ok(Json(new_state))
@@ -283,6 +325,7 @@ async fn server_mute(
         server_id: Some(server_id),
         channel_id: Some(channel_id),
         user_id: Some(action.target_user_id),
+        trace_context: None,
     });
 
     Ok(Json(new_state))
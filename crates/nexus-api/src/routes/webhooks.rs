@@ -1,17 +1,28 @@
 //! Webhook routes — create, manage, and execute webhooks.
 //!
-//! Incoming webhook execution URL: POST /webhooks/{id}/{token}
+//! Incoming webhook execution URL: POST /webhooks/{id}/{token}[?wait=true]
+//! Slack-compatible execution:      POST /webhooks/{id}/{token}/slack
+//! GitHub event ingestion:          POST /webhooks/{id}/{token}/github
 //! (No auth required — token in URL path authenticates the request.)
+//!
+//! Native execution takes the Discord payload, as JSON or as
+//! `multipart/form-data` with the JSON in `payload_json` and files in the
+//! other fields, so existing Discord tooling can post unchanged.
 
 use axum::{
-    extract::{Extension, Path, State},
+    body::Bytes,
+    extract::{DefaultBodyLimit, Extension, Path, Query, Request, State},
+    http::{header, HeaderMap, StatusCode},
     middleware,
+    response::{IntoResponse, Response},
     routing::{get, post},
     Json, Router,
 };
 use nexus_common::{
     error::{NexusError, NexusResult},
     gateway_event::GatewayEvent,
+    models::message::Embed,
+    models::user::SYSTEM_USER_ID,
     models::webhook::{
         CreateIncomingWebhookRequest, CreateOutgoingWebhookRequest, ExecuteWebhookRequest,
         ModifyWebhookRequest, Webhook,
@@ -21,10 +32,24 @@ use nexus_common::{
 use nexus_db::repository::{channels, messages, webhooks};
 use rand::distr::Alphanumeric;
 use rand::Rng;
+use serde::Deserialize;
 use std::sync::Arc;
 use uuid::Uuid;
 
-use crate::{middleware::AuthContext, AppState};
+use crate::{
+    audit::{self, actions, Target},
+    middleware::AuthContext,
+    routes::messages::{
+        attachment_json, create_attachments, discard_stored_files, message_row_to_json,
+        read_payload_and_files, PendingFile,
+    },
+    routes::uploads::{store_message_file, upload_body_limit},
+    webhook_formats::{github, slack, WebhookMessage, MAX_EMBEDS},
+    AppState,
+};
+
+/// Webhook fields compared for `WEBHOOK_UPDATE` audit entries (never the token).
+const AUDITED_FIELDS: &[&str] = &["name", "avatar", "channel_id", "url", "events", "active"];
 
 /// Webhook routes — authenticated management + unauthenticated execution.
 pub fn router() -> Router<Arc<AppState>> {
@@ -51,8 +76,14 @@ pub fn router() -> Router<Arc<AppState>> {
         // Public execution URL — token in path, no Bearer required
         .route(
             "/webhooks/{webhook_id}/{token}",
-            post(execute_webhook).get(get_webhook_public),
+            post(execute_webhook)
+                .layer(DefaultBodyLimit::max(upload_body_limit(
+                    nexus_common::config::get().limits.max_attachment_count,
+                )))
+                .get(get_webhook_public),
         )
+        .route("/webhooks/{webhook_id}/{token}/slack", post(execute_slack_webhook))
+        .route("/webhooks/{webhook_id}/{token}/github", post(execute_github_webhook))
 }
 
 /// Generate a random webhook token.
@@ -83,6 +114,7 @@ async fn create_incoming_webhook(
     Extension(auth): Extension<AuthContext>,
     State(state): State<Arc<AppState>>,
     Path(channel_id): Path<Uuid>,
+    headers: HeaderMap,
     Json(body): Json<CreateIncomingWebhookRequest>,
 ) -> NexusResult<Json<Webhook>> {
     // Look up the channel to get server_id
@@ -108,6 +140,7 @@ async fn create_incoming_webhook(
     )
     .await?;
 
+    record_create(&state, &wh, auth.user_id, &headers).await;
     Ok(Json(wh))
 }
 
@@ -116,6 +149,7 @@ async fn create_outgoing_webhook(
     Extension(auth): Extension<AuthContext>,
     State(state): State<Arc<AppState>>,
     Path(server_id): Path<Uuid>,
+    headers: HeaderMap,
     Json(body): Json<CreateOutgoingWebhookRequest>,
 ) -> NexusResult<Json<Webhook>> {
     let id = snowflake::generate_id();
@@ -132,6 +166,7 @@ async fn create_outgoing_webhook(
     )
     .await?;
 
+    record_create(&state, &wh, auth.user_id, &headers).await;
     Ok(Json(wh))
 }
 
@@ -158,6 +193,7 @@ async fn modify_webhook(
     Extension(auth): Extension<AuthContext>,
     State(state): State<Arc<AppState>>,
     Path(webhook_id): Path<Uuid>,
+    headers: HeaderMap,
     Json(body): Json<ModifyWebhookRequest>,
 ) -> NexusResult<Json<Webhook>> {
     let existing = webhooks::get_webhook(&state.db.pool, webhook_id)
@@ -180,6 +216,22 @@ async fn modify_webhook(
     .await?
     .ok_or(NexusError::NotFound { resource: "webhook".to_string() })?;
 
+    if let Some(server_id) = updated.server_id {
+        let changes = audit::diff(&existing, &updated, AUDITED_FIELDS);
+        if changes.as_object().is_some_and(|c| !c.is_empty()) {
+            audit::record(
+                &state,
+                server_id,
+                auth.user_id,
+                actions::WEBHOOK_UPDATE,
+                Some(Target::Webhook(webhook_id)),
+                Some(changes),
+                audit::reason(&headers).as_deref(),
+            )
+            .await;
+        }
+    }
+
     Ok(Json(updated))
 }
 
@@ -188,6 +240,7 @@ async fn delete_webhook(
     Extension(auth): Extension<AuthContext>,
     State(state): State<Arc<AppState>>,
     Path(webhook_id): Path<Uuid>,
+    headers: HeaderMap,
 ) -> NexusResult<axum::http::StatusCode> {
     let existing = webhooks::get_webhook(&state.db.pool, webhook_id)
         .await?
@@ -197,9 +250,41 @@ async fn delete_webhook(
     }
 
     webhooks::delete_webhook(&state.db.pool, webhook_id).await?;
+
+    if let Some(server_id) = existing.server_id {
+        audit::record(
+            &state,
+            server_id,
+            auth.user_id,
+            actions::WEBHOOK_DELETE,
+            Some(Target::Webhook(webhook_id)),
+            Some(serde_json::json!({ "name": existing.name, "channel_id": existing.channel_id })),
+            audit::reason(&headers).as_deref(),
+        )
+        .await;
+    }
     Ok(axum::http::StatusCode::NO_CONTENT)
 }
 
+/// Audit a newly created webhook.
+async fn record_create(state: &AppState, wh: &Webhook, actor_id: Uuid, headers: &HeaderMap) {
+    let Some(server_id) = wh.server_id else { return };
+    audit::record(
+        state,
+        server_id,
+        actor_id,
+        actions::WEBHOOK_CREATE,
+        Some(Target::Webhook(wh.id)),
+        Some(serde_json::json!({
+            "name": wh.name,
+            "type": wh.webhook_type,
+            "channel_id": wh.channel_id,
+        })),
+        audit::reason(headers).as_deref(),
+    )
+    .await;
+}
+
 // ============================================================================
 // Public Execution Endpoints (no user auth — token in URL)
 // ============================================================================
@@ -215,65 +300,228 @@ async fn get_webhook_public(
     Ok(Json(wh))
 }
 
+#[derive(Debug, Deserialize)]
+struct ExecuteParams {
+    /// Respond with the created message instead of 204.
+    #[serde(default)]
+    wait: bool,
+}
+
 /// POST /api/v1/webhooks/{webhook_id}/{token} — Execute a webhook (post a message).
+///
+/// With `?wait=true` the created message is returned, as Discord does.
 async fn execute_webhook(
     State(state): State<Arc<AppState>>,
     Path((webhook_id, token)): Path<(Uuid, String)>,
-    Json(body): Json<ExecuteWebhookRequest>,
+    Query(params): Query<ExecuteParams>,
+    request: Request,
+) -> NexusResult<Response> {
+    let (body, files) = read_payload_and_files::<ExecuteWebhookRequest>(&state, request).await?;
+    // A files-only execution has no payload_json at all.
+    let body = body.unwrap_or_default();
+    let embeds = body
+        .embeds
+        .unwrap_or_default()
+        .into_iter()
+        .map(serde_json::from_value)
+        .collect::<Result<Vec<Embed>, _>>()
+        .map_err(|e| NexusError::Validation {
+            message: format!("Invalid embed: {e}"),
+        })?;
+
+    let message = WebhookMessage {
+        content: body.content.unwrap_or_default(),
+        username: body.username,
+        avatar_url: body.avatar_url,
+        embeds,
+    };
+    let created = deliver(&state, webhook_id, &token, message, files).await?;
+    if params.wait {
+        return Ok(Json(created).into_response());
+    }
+    Ok(StatusCode::NO_CONTENT.into_response())
+}
+
+/// POST /api/v1/webhooks/{webhook_id}/{token}/slack — Execute with a Slack payload.
+///
+/// Responds with Slack's plain-text `ok` so integrations that check for it
+/// keep working.
+async fn execute_slack_webhook(
+    State(state): State<Arc<AppState>>,
+    Path((webhook_id, token)): Path<(Uuid, String)>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> NexusResult<&'static str> {
+    let form_encoded = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|ct| ct.starts_with("application/x-www-form-urlencoded"));
+    let payload = slack::parse_body(&body, form_encoded)
+        .map_err(|message| NexusError::Validation { message })?;
+
+    deliver(&state, webhook_id, &token, slack::translate(payload), Vec::new()).await?;
+    Ok("ok")
+}
+
+/// POST /api/v1/webhooks/{webhook_id}/{token}/github — Ingest a GitHub event.
+///
+/// Point a repository webhook (content type `application/json`) here. If a
+/// secret is configured on the GitHub side it must be the webhook token;
+/// deliveries carrying `X-Hub-Signature-256` are then verified against it.
+/// Events that are not rendered (including `ping`) are acknowledged with 204.
+async fn execute_github_webhook(
+    State(state): State<Arc<AppState>>,
+    Path((webhook_id, token)): Path<(Uuid, String)>,
+    headers: HeaderMap,
+    body: Bytes,
 ) -> NexusResult<axum::http::StatusCode> {
-    // Validate token
     let wh = webhooks::get_webhook_by_token(&state.db.pool, webhook_id, &token)
         .await?
         .ok_or(NexusError::NotFound { resource: "webhook".to_string() })?;
+    if !wh.active {
+        return Err(NexusError::Forbidden);
+    }
+
+    if let Some(signature) = headers.get("x-hub-signature-256") {
+        let signature = signature.to_str().unwrap_or_default();
+        if !github::verify_signature(&token, &body, signature) {
+            return Err(NexusError::Forbidden);
+        }
+    }
+
+    let event = headers
+        .get("x-github-event")
+        .and_then(|v| v.to_str().ok())
+        .ok_or(NexusError::Validation {
+            message: "Missing X-GitHub-Event header".into(),
+        })?;
+    let Some(message) =
+        github::translate(event, &body).map_err(|message| NexusError::Validation { message })?
+    else {
+        return Ok(axum::http::StatusCode::NO_CONTENT);
+    };
+
+    deliver(&state, webhook_id, &token, message, Vec::new()).await?;
+    Ok(axum::http::StatusCode::NO_CONTENT)
+}
+
+/// Validate the webhook token and post `message`, with `files` attached, to
+/// the webhook's channel. Returns the message as `MESSAGE_CREATE` carries it.
+async fn deliver(
+    state: &AppState,
+    webhook_id: Uuid,
+    token: &str,
+    message: WebhookMessage,
+    files: Vec<PendingFile>,
+) -> NexusResult<serde_json::Value> {
+    // Validate token
+    let wh = webhooks::get_webhook_by_token(&state.db.pool, webhook_id, token)
+        .await?
+        .ok_or(NexusError::NotFound { resource: "webhook".to_string() })?;
+    if !wh.active {
+        return Err(NexusError::Forbidden);
+    }
 
     let channel_id = wh.channel_id.ok_or(NexusError::Validation {
         message: "Webhook has no target channel".into(),
     })?;
+    let channel = channels::find_by_id(&state.db.pool, channel_id)
+        .await?
+        .ok_or(NexusError::NotFound { resource: "channel".to_string() })?;
+    if channel.archived {
+        return Err(NexusError::Validation {
+            message: "The webhook's channel is archived".into(),
+        });
+    }
 
-    let content = body.content.unwrap_or_default();
-    if content.is_empty() && body.embeds.as_ref().map_or(true, |e| e.is_empty()) {
+    if message.content.trim().is_empty() && message.embeds.is_empty() && files.is_empty() {
+        return Err(NexusError::Validation {
+            message: "content, embeds or files must be provided".into(),
+        });
+    }
+    if !files.is_empty() {
+        super::e2ee::require_plaintext_channel(state, channel_id).await?;
+    }
+    let max_len = nexus_common::config::get().limits.max_message_length as usize;
+    if message.content.chars().count() > max_len {
+        return Err(NexusError::Validation {
+            message: format!("content must be at most {max_len} characters"),
+        });
+    }
+    if message.embeds.len() > MAX_EMBEDS {
         return Err(NexusError::Validation {
-            message: "content or embeds must be provided".into(),
+            message: format!("At most {MAX_EMBEDS} embeds are allowed"),
         });
     }
 
-    // Create the message as a "webhook" author
-    let msg_id = snowflake::generate_id();
-    let display_name = body.username.as_deref().unwrap_or(&wh.name);
+    // messages.author_id must be a real user; webhook posts belong to the
+    // system account and are told apart by webhook_id.
+    let author_id = SYSTEM_USER_ID;
 
-    // Build a stub message record — in production this would go through the
-    // full message creation pipeline including thread resolution, embeds, etc.
-    let message = messages::create_message(
-        &state.db.pool,
-        msg_id,
-        channel_id,
-        // Use webhook UUID as pseudo user_id
-        webhook_id,
-        &content,
-        0,    // message_type: normal
-        None, // reference_message_id
-        None, // reference_channel_id
-        &[],  // mentions
-        &[],  // mention_roles
-        false, // mention_everyone
-    )
-    .await?;
+    // Per-message overrides win over the webhook's configured identity.
+    let username = message.username.as_deref().unwrap_or(&wh.name);
+    let avatar_url = message.avatar_url.as_deref().or(wh.avatar.as_deref());
+    let embeds = serde_json::to_value(&message.embeds)
+        .map_err(|e| NexusError::Internal(e.into()))?;
+
+    // Storage isn't transactional: write the files first, and remove them
+    // again if the database side doesn't commit.
+    let mut stored = Vec::with_capacity(files.len());
+    for file in files {
+        match store_message_file(state, author_id, &file.filename, &file.content_type, file.data).await {
+            Ok(f) => stored.push((f, file.spoiler)),
+            Err(e) => {
+                discard_stored_files(state, &stored).await;
+                return Err(e);
+            }
+        }
+    }
+
+    let message_id = snowflake::generate_id();
+    let created: NexusResult<messages::MessageRow> = async {
+        let mut tx = state.db.pool.begin().await?;
+        let row = messages::create_webhook_message(
+            &mut *tx,
+            message_id,
+            channel_id,
+            author_id,
+            webhook_id,
+            &message.content,
+            &embeds,
+            username,
+            avatar_url,
+        )
+        .await?;
+        let linked = create_attachments(&mut tx, &channel, author_id, message_id, &stored).await?;
+        let row = if linked.is_empty() {
+            row
+        } else {
+            let json = serde_json::Value::Array(linked.iter().map(attachment_json).collect());
+            messages::set_attachments(&mut *tx, message_id, &json).await?
+        };
+        tx.commit().await?;
+        Ok(row)
+    }
+    .await;
+    let row = match created {
+        Ok(row) => row,
+        Err(e) => {
+            discard_stored_files(state, &stored).await;
+            return Err(e);
+        }
+    };
 
     // Broadcast MESSAGE_CREATE via the gateway
+    let mut data = message_row_to_json(&row, &[]);
+    data["author_username"] = serde_json::Value::String(username.to_string());
     let _ = state.gateway_tx.send(GatewayEvent {
         event_type: nexus_common::gateway_event::event_types::MESSAGE_CREATE.to_string(),
-        data: serde_json::json!({
-            "message_id": message.id,
-            "channel_id": channel_id,
-            "webhook_id": webhook_id,
-            "username": display_name,
-            "avatar_url": body.avatar_url,
-            "content": &content,
-        }),
+        data: data.clone(),
         server_id: wh.server_id,
         channel_id: Some(channel_id),
         user_id: None,
+        trace_context: None,
     });
 
-    Ok(axum::http::StatusCode::NO_CONTENT)
+    Ok(data)
 }
@@ -0,0 +1,399 @@
+//! Server join actions — welcome posts, welcome DMs, auto-roles and
+//! membership screening.
+//!
+//! GET  /servers/:id/welcome            — Get join settings (MANAGE_SERVER)
+//! PUT  /servers/:id/welcome            — Replace join settings (MANAGE_SERVER)
+//! GET  /servers/:id/screening          — Screening rules shown to new members
+//! POST /servers/:id/screening/accept   — Accept the rules and finish joining
+//!
+//! Template placeholders: `{user}` (mention), `{username}`, `{server}`,
+//! `{member_count}`. A welcome post without a template uses the default
+//! join message in the server's preferred language.
+
+use axum::{
+    extract::{Extension, Path, State},
+    middleware,
+    routing::{get, post},
+    Json, Router,
+};
+use nexus_common::{
+    error::{NexusError, NexusResult},
+    gateway_event::GatewayEvent,
+    locale,
+    models::{server::Server, user::SYSTEM_USER_ID},
+    permissions::Permissions,
+    snowflake,
+    validation::validate_request,
+};
+use nexus_db::repository::{channels, members, messages, roles, servers, users, welcome};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{middleware::AuthContext, routes::messages::message_row_to_json, AppState};
+
+/// `MessageType::MemberJoin` as stored in `messages.message_type`.
+const MESSAGE_TYPE_MEMBER_JOIN: i32 = 6;
+
+pub fn router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/servers/{server_id}/welcome", get(get_settings).put(put_settings))
+        .route("/servers/{server_id}/screening", get(get_screening))
+        .route("/servers/{server_id}/screening/accept", post(accept_screening))
+        .route_layer(middleware::from_fn(crate::middleware::auth_middleware))
+}
+
+// ============================================================
+// Request / response types
+// ============================================================
+
+#[derive(Debug, Deserialize, Validate)]
+struct WelcomeSettingsRequest {
+    #[serde(default)]
+    channel_enabled: bool,
+    channel_id: Option<Uuid>,
+    #[validate(length(max = 2000))]
+    channel_message: Option<String>,
+    #[serde(default)]
+    dm_enabled: bool,
+    #[validate(length(max = 2000))]
+    dm_message: Option<String>,
+    #[serde(default)]
+    #[validate(length(max = 25))]
+    auto_role_ids: Vec<Uuid>,
+    #[serde(default)]
+    screening_enabled: bool,
+    #[validate(length(max = 4000))]
+    screening_rules: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct WelcomeSettingsResponse {
+    server_id: Uuid,
+    channel_enabled: bool,
+    channel_id: Option<Uuid>,
+    channel_message: Option<String>,
+    dm_enabled: bool,
+    dm_message: Option<String>,
+    auto_role_ids: Vec<Uuid>,
+    screening_enabled: bool,
+    screening_rules: Option<String>,
+}
+
+impl From<welcome::WelcomeSettingsRow> for WelcomeSettingsResponse {
+    fn from(r: welcome::WelcomeSettingsRow) -> Self {
+        Self {
+            server_id: r.server_id,
+            channel_enabled: r.channel_enabled,
+            channel_id: r.channel_id,
+            channel_message: r.channel_message,
+            dm_enabled: r.dm_enabled,
+            dm_message: r.dm_message,
+            auto_role_ids: r.auto_role_ids,
+            screening_enabled: r.screening_enabled,
+            screening_rules: r.screening_rules,
+        }
+    }
+}
+
+// ============================================================
+// Settings
+// ============================================================
+
+/// GET /api/v1/servers/:server_id/welcome
+async fn get_settings(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path(server_id): Path<Uuid>,
+) -> NexusResult<Json<WelcomeSettingsResponse>> {
+    let server = managed_server(&state, server_id, auth.user_id).await?;
+    let settings = welcome::get_settings(&state.db.pool, server.id)
+        .await?
+        .unwrap_or_else(|| empty_settings(server.id));
+    Ok(Json(settings.into()))
+}
+
+/// PUT /api/v1/servers/:server_id/welcome
+async fn put_settings(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path(server_id): Path<Uuid>,
+    Json(body): Json<WelcomeSettingsRequest>,
+) -> NexusResult<Json<WelcomeSettingsResponse>> {
+    validate_request(&body)?;
+    let server = managed_server(&state, server_id, auth.user_id).await?;
+
+    if body.channel_enabled {
+        let channel_id = body.channel_id.ok_or(NexusError::Validation {
+            message: "channel_id is required when the channel welcome is enabled".into(),
+        })?;
+        channels::find_by_id(&state.db.pool, channel_id)
+            .await?
+            .filter(|c| c.server_id == Some(server_id))
+            .ok_or(NexusError::Validation {
+                message: "Welcome channel must belong to this server".into(),
+            })?;
+    }
+    if body.dm_enabled && body.dm_message.as_deref().is_none_or(|m| m.trim().is_empty()) {
+        return Err(NexusError::Validation {
+            message: "dm_message is required when the welcome DM is enabled".into(),
+        });
+    }
+
+    let everyone = roles::get_everyone_role(&state.db.pool, server_id).await?;
+    for role_id in &body.auto_role_ids {
+        let role = roles::find_by_id(&state.db.pool, *role_id)
+            .await?
+            .filter(|r| r.server_id == server_id)
+            .ok_or(NexusError::Validation {
+                message: format!("Role {role_id} does not belong to this server"),
+            })?;
+        if everyone.as_ref().is_some_and(|e| e.id == role.id) {
+            return Err(NexusError::Validation {
+                message: "The @everyone role cannot be an auto-role".into(),
+            });
+        }
+    }
+
+    let mut auto_role_ids = body.auto_role_ids;
+    auto_role_ids.sort();
+    auto_role_ids.dedup();
+
+    let saved = welcome::upsert_settings(
+        &state.db.pool,
+        &welcome::WelcomeSettingsRow {
+            server_id: server.id,
+            channel_enabled: body.channel_enabled,
+            channel_id: body.channel_id,
+            channel_message: body.channel_message,
+            dm_enabled: body.dm_enabled,
+            dm_message: body.dm_message,
+            auto_role_ids,
+            screening_enabled: body.screening_enabled,
+            screening_rules: body.screening_rules,
+            updated_at: chrono::Utc::now(),
+        },
+    )
+    .await?;
+
+    Ok(Json(saved.into()))
+}
+
+// ============================================================
+// Screening
+// ============================================================
+
+/// GET /api/v1/servers/:server_id/screening
+async fn get_screening(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path(server_id): Path<Uuid>,
+) -> NexusResult<Json<serde_json::Value>> {
+    let member = members::find_member(&state.db.pool, auth.user_id, server_id)
+        .await?
+        .ok_or(NexusError::Forbidden)?;
+    let settings = welcome::get_settings(&state.db.pool, server_id).await?;
+
+    Ok(Json(serde_json::json!({
+        "enabled": settings.as_ref().is_some_and(|s| s.screening_enabled),
+        "rules": settings.and_then(|s| s.screening_rules),
+        "pending": member.pending,
+    })))
+}
+
+/// POST /api/v1/servers/:server_id/screening/accept
+async fn accept_screening(
+    Extension(auth): Extension<AuthContext>,
+    State(state): State<Arc<AppState>>,
+    Path(server_id): Path<Uuid>,
+) -> NexusResult<Json<serde_json::Value>> {
+    let member = members::find_member(&state.db.pool, auth.user_id, server_id)
+        .await?
+        .ok_or(NexusError::Forbidden)?;
+    if !member.pending {
+        return Ok(Json(serde_json::json!({ "pending": false })));
+    }
+
+    let server = servers::find_by_id(&state.db.pool, server_id)
+        .await?
+        .ok_or(NexusError::NotFound {
+            resource: "Server".into(),
+        })?;
+
+    members::set_pending(&state.db.pool, auth.user_id, server_id, false).await?;
+    if let Some(settings) = welcome::get_settings(&state.db.pool, server_id).await? {
+        run_join_actions(&state, &server, &settings, auth.user_id, &auth.username).await;
+    }
+
+    Ok(Json(serde_json::json!({ "pending": false })))
+}
+
+// ============================================================
+// Join hook
+// ============================================================
+
+/// Run a server's join actions for a freshly added member.
+///
+/// With screening enabled the member is parked as `pending` and the actions
+/// are deferred until they accept the rules. Failures are logged rather than
+/// surfaced — a broken welcome template must never block a join.
+pub(crate) async fn on_member_join(state: &AppState, server: &Server, user_id: Uuid, username: &str) {
+    let settings = match welcome::get_settings(&state.db.pool, server.id).await {
+        Ok(Some(s)) => s,
+        Ok(None) => return,
+        Err(e) => {
+            tracing::warn!(server_id = %server.id, error = %e, "Failed to load welcome settings");
+            return;
+        }
+    };
+
+    if settings.screening_enabled {
+        if let Err(e) = members::set_pending(&state.db.pool, user_id, server.id, true).await {
+            tracing::warn!(server_id = %server.id, error = %e, "Failed to mark member pending");
+        }
+        return;
+    }
+
+    run_join_actions(state, server, &settings, user_id, username).await;
+}
+
+async fn run_join_actions(
+    state: &AppState,
+    server: &Server,
+    settings: &welcome::WelcomeSettingsRow,
+    user_id: Uuid,
+    username: &str,
+) {
+    let pool = &state.db.pool;
+
+    for role_id in &settings.auto_role_ids {
+        if let Err(e) = members::add_role(pool, user_id, server.id, *role_id).await {
+            tracing::warn!(server_id = %server.id, role_id = %role_id, error = %e, "Failed to apply auto-role");
+        }
+    }
+
+    if settings.channel_enabled {
+        let template = settings
+            .channel_message
+            .as_deref()
+            .filter(|t| !t.trim().is_empty())
+            .or_else(|| locale::system_text("member_join", &server.preferred_locale));
+        if let (Some(channel_id), Some(template)) = (settings.channel_id, template) {
+            let content = render_template(template, server, user_id, username);
+            post_message(
+                state,
+                Some(server.id),
+                channel_id,
+                (user_id, username),
+                &content,
+                MESSAGE_TYPE_MEMBER_JOIN,
+                &[user_id],
+            )
+            .await;
+        }
+    }
+
+    if settings.dm_enabled {
+        if let Some(template) = &settings.dm_message {
+            let content = render_template(template, server, user_id, username);
+            let system_name = users::find_by_id(pool, SYSTEM_USER_ID)
+                .await
+                .ok()
+                .flatten()
+                .map(|u| u.username)
+                .unwrap_or_default();
+            match channels::find_or_create_dm(pool, snowflake::generate_id(), SYSTEM_USER_ID, user_id).await {
+                Ok(dm) => {
+                    post_message(state, None, dm.id, (SYSTEM_USER_ID, &system_name), &content, 0, &[]).await;
+                }
+                Err(e) => {
+                    tracing::warn!(server_id = %server.id, error = %e, "Failed to open welcome DM")
+                }
+            }
+        }
+    }
+}
+
+/// Create a message as `author` and broadcast `MESSAGE_CREATE`.
+async fn post_message(
+    state: &AppState,
+    server_id: Option<Uuid>,
+    channel_id: Uuid,
+    (author_id, author_username): (Uuid, &str),
+    content: &str,
+    message_type: i32,
+    mentions: &[Uuid],
+) {
+    let msg = match messages::create_message(
+        &state.db.pool,
+        snowflake::generate_id(),
+        channel_id,
+        author_id,
+        content,
+        message_type,
+        None,
+        None,
+        mentions,
+        &[],
+        false,
+        0,
+    )
+    .await
+    {
+        Ok(msg) => msg,
+        Err(e) => {
+            tracing::warn!(channel_id = %channel_id, error = %e, "Failed to post welcome message");
+            return;
+        }
+    };
+
+    let mut data = message_row_to_json(&msg, &[]);
+    data["author_username"] = serde_json::Value::String(author_username.to_string());
+    let _ = state.gateway_tx.send(GatewayEvent {
+        event_type: "MESSAGE_CREATE".into(),
+        data,
+        server_id,
+        channel_id: Some(channel_id),
+        user_id: Some(author_id),
+        trace_context: None,
+    });
+}
+
+/// Substitute template placeholders.
+fn render_template(template: &str, server: &Server, user_id: Uuid, username: &str) -> String {
+    template
+        .replace("{user}", &format!("<@{user_id}>"))
+        .replace("{username}", username)
+        .replace("{server}", &server.name)
+        .replace("{member_count}", &server.member_count.to_string())
+}
+
+/// Load a server and require MANAGE_SERVER in it.
+async fn managed_server(state: &AppState, server_id: Uuid, user_id: Uuid) -> NexusResult<Server> {
+    let server = servers::find_by_id(&state.db.pool, server_id)
+        .await?
+        .ok_or(NexusError::NotFound {
+            resource: "Server".into(),
+        })?;
+    let permissions = crate::permissions::resolve(&state.db.pool, &server, None, user_id)
+        .await?
+        .ok_or(NexusError::Forbidden)?;
+    crate::permissions::require(permissions, Permissions::MANAGE_SERVER)?;
+    Ok(server)
+}
+
+fn empty_settings(server_id: Uuid) -> welcome::WelcomeSettingsRow {
+    welcome::WelcomeSettingsRow {
+        server_id,
+        channel_enabled: false,
+        channel_id: None,
+        channel_message: None,
+        dm_enabled: false,
+        dm_message: None,
+        auto_role_ids: Vec::new(),
+        screening_enabled: false,
+        screening_rules: None,
+        updated_at: chrono::Utc::now(),
+    }
+}
@@ -0,0 +1,151 @@
+//! Internal gRPC service — the API tier's side of `nexus-rpc`.
+//!
+//! Gateway and voice nodes use it for session lookups, permission checks,
+//! and publishing / subscribing to gateway events without going through the
+//! public REST API.
+
+use std::pin::Pin;
+use std::sync::Arc;
+
+use futures_util::Stream;
+use nexus_common::gateway_event::GatewayEvent;
+use nexus_common::permissions::Permissions;
+use nexus_db::repository::{channels, servers};
+use nexus_rpc::pb;
+use tokio::sync::broadcast::error::RecvError;
+use tonic::{Request, Response, Status};
+use uuid::Uuid;
+
+use crate::AppState;
+
+/// `nexus.internal.v1.Internal` backed by the shared [`AppState`].
+pub struct InternalService {
+    state: Arc<AppState>,
+}
+
+impl InternalService {
+    pub fn new(state: Arc<AppState>) -> Self {
+        Self { state }
+    }
+
+    /// Wrap in a tonic server that requires the shared internal `token`.
+    pub fn into_server(
+        self,
+        token: &str,
+    ) -> tonic::service::interceptor::InterceptedService<
+        nexus_rpc::InternalServer<Self>,
+        nexus_rpc::RequireToken,
+    > {
+        nexus_rpc::InternalServer::with_interceptor(self, nexus_rpc::RequireToken::new(token))
+    }
+}
+
+type EventStream = Pin<Box<dyn Stream<Item = Result<pb::Event, Status>> + Send>>;
+
+#[tonic::async_trait]
+impl nexus_rpc::Internal for InternalService {
+    async fn lookup_session(
+        &self,
+        request: Request<pb::LookupSessionRequest>,
+    ) -> Result<Response<pb::LookupSessionResponse>, Status> {
+        let token = request.into_inner().access_token;
+        let secret = &nexus_common::config::get().auth.jwt_secret;
+        let response = match crate::auth::validate_token(&token, secret) {
+            Ok(claims) if claims.token_type == "access" => pb::LookupSessionResponse {
+                valid: true,
+                user_id: claims.sub,
+                username: claims.username,
+                expires_at: claims.exp,
+            },
+            _ => pb::LookupSessionResponse::default(),
+        };
+        Ok(Response::new(response))
+    }
+
+    async fn check_permission(
+        &self,
+        request: Request<pb::CheckPermissionRequest>,
+    ) -> Result<Response<pb::CheckPermissionResponse>, Status> {
+        let req = request.into_inner();
+        let user_id = parse_uuid("user_id", &req.user_id)?;
+        let server_id = parse_uuid("server_id", &req.server_id)?;
+        let pool = &self.state.db.pool;
+
+        let server = servers::find_by_id(pool, server_id)
+            .await
+            .map_err(internal)?
+            .ok_or_else(|| Status::not_found("server not found"))?;
+        let channel = match req.channel_id.as_deref() {
+            Some(id) => Some(
+                channels::find_by_id(pool, parse_uuid("channel_id", id)?)
+                    .await
+                    .map_err(internal)?
+                    .filter(|c| c.server_id == Some(server_id))
+                    .ok_or_else(|| Status::not_found("channel not found"))?,
+            ),
+            None => None,
+        };
+
+        let permissions = crate::permissions::resolve(pool, &server, channel.as_ref(), user_id)
+            .await
+            .map_err(internal)?;
+        let required = Permissions::from_bits_truncate(req.required);
+        Ok(Response::new(pb::CheckPermissionResponse {
+            allowed: permissions.is_some_and(|p| p.has(required)),
+            member: permissions.is_some(),
+            permissions: permissions.map(|p| p.bits()).unwrap_or(0),
+        }))
+    }
+
+    async fn publish_event(
+        &self,
+        request: Request<pb::PublishEventRequest>,
+    ) -> Result<Response<pb::PublishEventResponse>, Status> {
+        let event = request
+            .into_inner()
+            .event
+            .ok_or_else(|| Status::invalid_argument("event is required"))?;
+        let event = GatewayEvent::try_from(event)?;
+        // No receivers just means no gateway is connected right now.
+        let _ = self.state.gateway_tx.send(event);
+        Ok(Response::new(pb::PublishEventResponse {}))
+    }
+
+    type SubscribeEventsStream = EventStream;
+
+    async fn subscribe_events(
+        &self,
+        request: Request<pb::SubscribeEventsRequest>,
+    ) -> Result<Response<Self::SubscribeEventsStream>, Status> {
+        let filter = request.into_inner().event_types;
+        let rx = self.state.gateway_tx.subscribe();
+
+        let stream = futures_util::stream::unfold((rx, filter), |(mut rx, filter)| async move {
+            loop {
+                match rx.recv().await {
+                    Ok(event) if filter.is_empty() || filter.contains(&event.event_type) => {
+                        return Some((Ok(pb::Event::from(&event)), (rx, filter)));
+                    }
+                    Ok(_) => continue,
+                    // A slow subscriber must resync rather than silently miss events.
+                    Err(RecvError::Lagged(n)) => {
+                        return Some((
+                            Err(Status::data_loss(format!("subscriber lagged by {n} events"))),
+                            (rx, filter),
+                        ));
+                    }
+                    Err(RecvError::Closed) => return None,
+                }
+            }
+        });
+        Ok(Response::new(Box::pin(stream)))
+    }
+}
+
+fn parse_uuid(field: &str, value: &str) -> Result<Uuid, Status> {
+    Uuid::parse_str(value).map_err(|_| Status::invalid_argument(format!("{field} is not a UUID")))
+}
+
+fn internal(e: impl std::fmt::Display) -> Status {
+    Status::internal(e.to_string())
+}
@@ -0,0 +1,98 @@
+//! Security events — instance-level webhooks for the operator's SIEM or
+//! alerting stack.
+//!
+//! Routes call [`record`] when something security-relevant happens (a staff
+//! account logs in, a refresh token is replayed, a federation origin is
+//! throttled, messages or a whole server are deleted in bulk, staff
+//! deactivate a user or purge media). One delivery per URL in
+//! `security_webhooks.urls` is queued and sent by
+//! [`jobs::security_webhooks`](crate::jobs::security_webhooks), which
+//! retries failures with backoff.
+//!
+//! Each delivery is a JSON `POST` of `{"id", "event", "occurred_at", "data"}`
+//! with headers:
+//!
+//! - `X-Nexus-Event` — the event name, one of [`events`]
+//! - `X-Nexus-Timestamp` — Unix seconds when the request was signed
+//! - `X-Nexus-Signature` — `sha256=<hex>`, the HMAC-SHA256 of
+//!   `"{timestamp}.{body}"` under `security_webhooks.secret`
+//!
+//! `id` stays the same across retries, so receivers can de-duplicate.
+//! Recording never fails the action itself: a queue error is logged.
+
+use hmac::{Hmac, Mac};
+use nexus_db::repository::security_events;
+use sha2::Sha256;
+use uuid::Uuid;
+
+use crate::AppState;
+
+pub const EVENT_HEADER: &str = "x-nexus-event";
+pub const TIMESTAMP_HEADER: &str = "x-nexus-timestamp";
+pub const SIGNATURE_HEADER: &str = "x-nexus-signature";
+
+/// Event names, as sent in `X-Nexus-Event`.
+pub mod events {
+    /// A user with the staff flag logged in.
+    pub const ADMIN_LOGIN: &str = "admin.login";
+    /// Staff deactivated or reactivated a user through the admin API.
+    pub const ADMIN_USER_DEACTIVATE: &str = "admin.user_deactivate";
+    pub const ADMIN_USER_REACTIVATE: &str = "admin.user_reactivate";
+    /// Staff purged uploaded media through the admin API.
+    pub const ADMIN_MEDIA_PURGE: &str = "admin.media_purge";
+    /// An already-used refresh token was presented; its session was revoked.
+    pub const REFRESH_TOKEN_REUSE: &str = "auth.refresh_token_reuse";
+    /// A federation origin was throttled after repeated rate limit strikes.
+    pub const FEDERATION_ORIGIN_THROTTLED: &str = "federation.origin_throttled";
+    /// A user deleted their own account.
+    pub const ACCOUNT_DELETE: &str = "account.delete";
+    pub const MESSAGE_BULK_DELETE: &str = "message.bulk_delete";
+    pub const SERVER_DELETE: &str = "server.delete";
+}
+
+/// Queue `event` for every configured URL.
+pub async fn record(state: &AppState, event: &str, data: serde_json::Value) {
+    let urls = nexus_common::config::get().security_webhooks.url_list();
+    if urls.is_empty() {
+        return;
+    }
+    let id = Uuid::new_v4();
+    let payload = serde_json::json!({
+        "id": id,
+        "event": event,
+        "occurred_at": chrono::Utc::now(),
+        "data": data,
+    });
+    for url in urls {
+        if let Err(e) =
+            security_events::enqueue(&state.db.pool, Uuid::new_v4(), &url, event, &payload).await
+        {
+            tracing::warn!(%event, %url, error = %e, "Failed to queue security event");
+        }
+    }
+}
+
+/// The `X-Nexus-Signature` value for `body` sent at `timestamp`.
+pub fn sign(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signature_covers_timestamp_and_body() {
+        let signature = sign("secret", 1_700_000_000, br#"{"event":"admin.login"}"#);
+        assert!(signature.starts_with("sha256="));
+        assert_eq!(signature.len(), "sha256=".len() + 64);
+        assert_eq!(signature, sign("secret", 1_700_000_000, br#"{"event":"admin.login"}"#));
+        assert_ne!(signature, sign("secret", 1_700_000_001, br#"{"event":"admin.login"}"#));
+        assert_ne!(signature, sign("other", 1_700_000_000, br#"{"event":"admin.login"}"#));
+    }
+}
@@ -0,0 +1,84 @@
+//! Which login sessions still exist, for authenticating access tokens.
+//!
+//! An access token names its session (`sid`), and stops working once the
+//! session is revoked. Live sessions are remembered for
+//! [`LIVE_SESSION_TTL`] so most requests skip the database. Revocations on
+//! this instance take effect at once; ones made elsewhere (another
+//! instance, `nexus admin`) within the TTL.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+/// How long a session seen in the database is trusted without looking again.
+pub const LIVE_SESSION_TTL: Duration = Duration::from_secs(30);
+
+/// Sessions known to exist, with their user and when they were checked.
+#[derive(Default)]
+pub struct SessionCache {
+    live: Mutex<HashMap<Uuid, (Uuid, Instant)>>,
+}
+
+impl SessionCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether `session_id` was seen in the database within the TTL.
+    pub fn contains(&self, session_id: Uuid) -> bool {
+        let mut live = self.live.lock().unwrap_or_else(|e| e.into_inner());
+        match live.get(&session_id) {
+            Some((_, checked)) if checked.elapsed() < LIVE_SESSION_TTL => true,
+            Some(_) => {
+                live.remove(&session_id);
+                false
+            }
+            None => false,
+        }
+    }
+
+    /// Note that `session_id`, belonging to `user_id`, exists.
+    pub fn insert(&self, session_id: Uuid, user_id: Uuid) {
+        self.live
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(session_id, (user_id, Instant::now()));
+    }
+
+    /// Forget a revoked session.
+    pub fn remove(&self, session_id: Uuid) {
+        self.live.lock().unwrap_or_else(|e| e.into_inner()).remove(&session_id);
+    }
+
+    /// Forget all of `user_id`'s sessions except `keep`.
+    pub fn remove_user(&self, user_id: Uuid, keep: Option<Uuid>) {
+        self.live
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .retain(|id, (owner, _)| *owner != user_id || Some(*id) == keep);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn revoked_sessions_are_forgotten() {
+        let cache = SessionCache::new();
+        let (alice, bob) = (Uuid::new_v4(), Uuid::new_v4());
+        let (phone, laptop, other) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        cache.insert(phone, alice);
+        cache.insert(laptop, alice);
+        cache.insert(other, bob);
+
+        cache.remove_user(alice, Some(laptop));
+        assert!(!cache.contains(phone));
+        assert!(cache.contains(laptop));
+        assert!(cache.contains(other));
+
+        cache.remove(laptop);
+        assert!(!cache.contains(laptop));
+    }
+}
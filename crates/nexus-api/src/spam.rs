@@ -0,0 +1,304 @@
+//! Message spam heuristics — duplicate bursts, cross-channel link spam and
+//! invite spam.
+//!
+//! Recent message activity is tracked in memory per user *and* per client IP,
+//! so a spammer rotating between throwaway accounts from one address is still
+//! caught. Thresholds come from [`SpamConfig`]; the message route turns a
+//! verdict into an automatic timeout, an audit log entry and a
+//! `SPAM_DETECTED` gateway event. A shared address can't single out one
+//! account, so per-IP verdicts only drop the message.
+//!
+//! Entries expire in the order they were recorded, so each check only
+//! drops the ones that aged out since the last, not every tracked subject.
+
+use nexus_common::config::SpamConfig;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::hash::{Hash, Hasher};
+use std::net::IpAddr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+/// Substrings that mark a message as carrying a server invite.
+const INVITE_MARKERS: &[&str] = &["/invite/", "/invites/", "discord.gg/", "nexus.gg/"];
+
+/// Which heuristic fired.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpamKind {
+    DuplicateBurst,
+    LinkSpam,
+    InviteSpam,
+}
+
+impl SpamKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::DuplicateBurst => "duplicate_burst",
+            Self::LinkSpam => "link_spam",
+            Self::InviteSpam => "invite_spam",
+        }
+    }
+}
+
+/// Result of a positive spam check.
+#[derive(Debug, Clone, Copy)]
+pub struct SpamVerdict {
+    pub kind: SpamKind,
+    /// Whether the per-IP tracker (rather than the per-user one) tripped.
+    pub by_ip: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Subject {
+    User(Uuid),
+    Ip(IpAddr),
+}
+
+#[derive(Debug)]
+struct Recent {
+    at: Instant,
+    fingerprint: u64,
+    channel_id: Uuid,
+    has_link: bool,
+    has_invite: bool,
+}
+
+#[derive(Debug, Default)]
+struct Tracked {
+    by_subject: HashMap<Subject, VecDeque<Recent>>,
+    /// Every entry in `by_subject`, oldest first.
+    order: VecDeque<(Instant, Subject)>,
+}
+
+impl Tracked {
+    /// Drop entries older than `horizon`, and subjects left with none.
+    fn expire(&mut self, now: Instant, horizon: Duration) {
+        while let Some(&(at, subject)) = self.order.front() {
+            if now.duration_since(at) <= horizon {
+                break;
+            }
+            self.order.pop_front();
+            // A subject's entries were recorded in the same order, so this
+            // is the front of its queue.
+            if let Some(queue) = self.by_subject.get_mut(&subject) {
+                queue.pop_front();
+                if queue.is_empty() {
+                    self.by_subject.remove(&subject);
+                }
+            }
+        }
+    }
+}
+
+/// In-memory sliding-window spam detector shared by all API handlers.
+pub struct SpamDetector {
+    config: SpamConfig,
+    recent: Mutex<Tracked>,
+}
+
+impl SpamDetector {
+    pub fn new(config: SpamConfig) -> Self {
+        Self {
+            config,
+            recent: Mutex::new(Tracked::default()),
+        }
+    }
+
+    /// Length of the automatic timeout applied when spam is detected.
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(self.config.timeout_secs)
+    }
+
+    /// Record a message and report whether it pushes the author (or their IP)
+    /// over any threshold. Messages without text (attachments only) are
+    /// neither recorded nor judged.
+    pub fn check(
+        &self,
+        user_id: Uuid,
+        ip: Option<IpAddr>,
+        channel_id: Uuid,
+        content: &str,
+    ) -> Option<SpamVerdict> {
+        if !self.config.enabled || content.trim().is_empty() {
+            return None;
+        }
+
+        let now = Instant::now();
+        let lower = content.to_lowercase();
+        let has_link = has_link(&lower);
+        let has_invite = INVITE_MARKERS.iter().any(|m| lower.contains(m));
+        let fingerprint = fingerprint(&lower);
+
+        let mut recent = self.recent.lock().unwrap_or_else(|e| e.into_inner());
+        recent.expire(now, self.longest_window());
+
+        let mut subjects = vec![(Subject::User(user_id), false)];
+        if let Some(ip) = ip {
+            subjects.push((Subject::Ip(ip), true));
+        }
+
+        let mut verdict = None;
+        for (subject, by_ip) in subjects {
+            recent.order.push_back((now, subject));
+            let queue = recent.by_subject.entry(subject).or_default();
+            queue.push_back(Recent {
+                at: now,
+                fingerprint,
+                channel_id,
+                has_link,
+                has_invite,
+            });
+            if verdict.is_none() {
+                verdict = self.evaluate(queue, now).map(|kind| SpamVerdict { kind, by_ip });
+            }
+        }
+        verdict
+    }
+
+    fn evaluate(&self, queue: &VecDeque<Recent>, now: Instant) -> Option<SpamKind> {
+        let cfg = &self.config;
+        let within = |r: &&Recent, secs: u64| now.duration_since(r.at) <= Duration::from_secs(secs);
+        let latest = queue.back()?;
+
+        let duplicates = queue
+            .iter()
+            .filter(|r| within(r, cfg.duplicate_window_secs) && r.fingerprint == latest.fingerprint)
+            .count();
+        if duplicates >= cfg.duplicate_threshold as usize {
+            return Some(SpamKind::DuplicateBurst);
+        }
+
+        if latest.has_invite {
+            let invites = queue
+                .iter()
+                .filter(|r| within(r, cfg.invite_window_secs) && r.has_invite)
+                .count();
+            if invites >= cfg.invite_threshold as usize {
+                return Some(SpamKind::InviteSpam);
+            }
+        }
+
+        if latest.has_link {
+            let channels: HashSet<Uuid> = queue
+                .iter()
+                .filter(|r| within(r, cfg.link_window_secs) && r.has_link)
+                .map(|r| r.channel_id)
+                .collect();
+            if channels.len() >= cfg.link_channel_threshold as usize {
+                return Some(SpamKind::LinkSpam);
+            }
+        }
+
+        None
+    }
+
+    fn longest_window(&self) -> Duration {
+        let cfg = &self.config;
+        Duration::from_secs(
+            cfg.duplicate_window_secs
+                .max(cfg.link_window_secs)
+                .max(cfg.invite_window_secs),
+        )
+    }
+}
+
+/// Whether already-lowercased content contains something link-like.
+pub(crate) fn has_link(lower: &str) -> bool {
+    lower.contains("http://") || lower.contains("https://") || lower.contains("www.")
+}
+
+/// Whitespace-insensitive content fingerprint so trivially padded copies
+/// still count as duplicates.
+fn fingerprint(lower: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    for word in lower.split_whitespace() {
+        word.hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detector() -> SpamDetector {
+        SpamDetector::new(SpamConfig {
+            enabled: true,
+            duplicate_threshold: 3,
+            duplicate_window_secs: 10,
+            link_channel_threshold: 3,
+            link_window_secs: 60,
+            invite_threshold: 2,
+            invite_window_secs: 300,
+            timeout_secs: 600,
+        })
+    }
+
+    #[test]
+    fn test_duplicate_burst() {
+        let d = detector();
+        let (user, channel) = (Uuid::now_v7(), Uuid::now_v7());
+        assert!(d.check(user, None, channel, "buy now").is_none());
+        assert!(d.check(user, None, channel, "buy   now").is_none());
+        let v = d.check(user, None, channel, "BUY NOW").expect("third copy should trip");
+        assert_eq!(v.kind, SpamKind::DuplicateBurst);
+        assert!(!v.by_ip);
+    }
+
+    #[test]
+    fn test_link_spam_across_channels() {
+        let d = detector();
+        let user = Uuid::now_v7();
+        assert!(d.check(user, None, Uuid::now_v7(), "see https://a.example").is_none());
+        assert!(d.check(user, None, Uuid::now_v7(), "see https://b.example").is_none());
+        let v = d.check(user, None, Uuid::now_v7(), "see https://c.example").unwrap();
+        assert_eq!(v.kind, SpamKind::LinkSpam);
+    }
+
+    #[test]
+    fn test_invite_spam_caught_per_ip() {
+        let d = detector();
+        let ip: IpAddr = "203.0.113.7".parse().unwrap();
+        let channel = Uuid::now_v7();
+        assert!(d.check(Uuid::now_v7(), Some(ip), channel, "join discord.gg/abc").is_none());
+        let v = d
+            .check(Uuid::now_v7(), Some(ip), channel, "come to discord.gg/xyz")
+            .expect("second account on same IP should trip");
+        assert_eq!(v.kind, SpamKind::InviteSpam);
+        assert!(v.by_ip);
+    }
+
+    #[test]
+    fn test_empty_content_ignored() {
+        let d = detector();
+        let (user, channel) = (Uuid::now_v7(), Uuid::now_v7());
+        for content in ["", "  ", "", "\n"] {
+            assert!(d.check(user, None, channel, content).is_none());
+        }
+        assert!(d.recent.lock().unwrap().by_subject.is_empty());
+    }
+
+    #[test]
+    fn test_expired_entries_dropped() {
+        let d = detector();
+        let user = Uuid::now_v7();
+        let ip: IpAddr = "203.0.113.7".parse().unwrap();
+        d.check(user, Some(ip), Uuid::now_v7(), "hello");
+        let mut recent = d.recent.lock().unwrap();
+        assert_eq!(recent.order.len(), 2);
+        recent.expire(Instant::now() + Duration::from_secs(301), d.longest_window());
+        assert!(recent.order.is_empty());
+        assert!(recent.by_subject.is_empty());
+    }
+
+    #[test]
+    fn test_disabled() {
+        let mut d = detector();
+        d.config.enabled = false;
+        let (user, channel) = (Uuid::now_v7(), Uuid::now_v7());
+        for _ in 0..10 {
+            assert!(d.check(user, None, channel, "same").is_none());
+        }
+    }
+}
@@ -0,0 +1,91 @@
+//! Starboard — reposts messages that collect enough of a server's chosen
+//! reaction into its highlights channel.
+//!
+//! Each source message gets at most one repost, tracked in
+//! `starboard_entries`. The repost quotes the original, mentions its
+//! channel and author, and references the source message so clients can
+//! jump back to it. Later reactions only update the count in the header.
+
+use nexus_db::repository::starboard::StarboardSettingsRow;
+use uuid::Uuid;
+
+/// The channel a message with `count` reactions should be reposted to,
+/// or `None` if it does not qualify. Messages already in the starboard
+/// channel never qualify.
+pub fn target_channel(
+    settings: &StarboardSettingsRow,
+    source_channel_id: Uuid,
+    count: i64,
+) -> Option<Uuid> {
+    let channel_id = settings.channel_id.filter(|_| settings.enabled)?;
+    (channel_id != source_channel_id && count >= settings.threshold.max(1) as i64)
+        .then_some(channel_id)
+}
+
+/// Render a repost: a header with the reaction count and origin, then the
+/// original content as a quote, cut to fit within `max_len` characters.
+pub fn render(
+    emoji: &str,
+    count: i64,
+    source_channel_id: Uuid,
+    author_id: Uuid,
+    content: &str,
+    max_len: usize,
+) -> String {
+    let mut out = format!("{emoji} **{count}** in <#{source_channel_id}> from <@{author_id}>");
+    for line in content.lines() {
+        let quoted = format!("\n> {line}");
+        let room = max_len.saturating_sub(out.chars().count());
+        if quoted.chars().count() > room {
+            if room > 1 {
+                out.extend(quoted.chars().take(room - 1));
+                out.push('…');
+            }
+            break;
+        }
+        out.push_str(&quoted);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(enabled: bool, channel_id: Option<Uuid>, threshold: i32) -> StarboardSettingsRow {
+        StarboardSettingsRow {
+            server_id: Uuid::new_v4(),
+            enabled,
+            channel_id,
+            emoji: "⭐".into(),
+            threshold,
+            updated_at: chrono::Utc::now(),
+        }
+    }
+
+    #[test]
+    fn qualifies_at_threshold() {
+        let board = Uuid::new_v4();
+        let source = Uuid::new_v4();
+        let s = settings(true, Some(board), 3);
+        assert_eq!(target_channel(&s, source, 2), None);
+        assert_eq!(target_channel(&s, source, 3), Some(board));
+        assert_eq!(target_channel(&s, board, 10), None);
+        assert_eq!(target_channel(&settings(false, Some(board), 3), source, 10), None);
+        assert_eq!(target_channel(&settings(true, None, 3), source, 10), None);
+    }
+
+    #[test]
+    fn render_quotes_and_truncates() {
+        let channel = Uuid::nil();
+        let author = Uuid::nil();
+        let full = render("⭐", 4, channel, author, "hello\nworld", 4000);
+        assert!(full.starts_with("⭐ **4** in <#"));
+        assert!(full.ends_with("\n> hello\n> world"));
+
+        let header = render("⭐", 4, channel, author, "", 4000).chars().count();
+        let cut = render("⭐", 4, channel, author, "abcdefghij", header + 6);
+        assert_eq!(cut.chars().count(), header + 6);
+        assert!(cut.ends_with("\n> ab…"));
+    }
+}
@@ -0,0 +1,354 @@
+//! In-memory harness for route-level tests.
+//!
+//! [`TestApp::new`] builds the full router over an [`AppState`] with a fresh
+//! in-memory SQLite database (lite migrations applied), local file storage
+//! in a temporary directory, search disabled, no Redis, and the gateway
+//! channel captured so tests can assert on the events a route broadcast.
+//! Requests go straight to the router; nothing listens on a port.
+//!
+//! ```ignore
+//! let app = TestApp::new().await;
+//! let alice = app.user("alice").await;
+//! let me = app.get("/api/v1/users/@me").auth(&alice).send().await.expect(StatusCode::OK);
+//! assert_eq!(me["username"], "alice");
+//! ```
+//!
+//! All apps share one global config (see [`TEST_CONFIG`]): rate limits,
+//! the per-IP registration limit, spam heuristics, link unfurling and media
+//! processing are off, so tests don't trip over them or reach the network.
+//!
+//! Built for this crate's unit tests and, with the `test-support` feature,
+//! for its integration tests in `tests/`.
+
+use std::path::PathBuf;
+use std::sync::{Arc, OnceLock};
+
+use axum::{
+    body::Body,
+    http::{header, HeaderMap, Method, Request, StatusCode},
+    Router,
+};
+use chrono::{Duration, Utc};
+use nexus_common::{
+    config::AppConfig,
+    gateway_event::{EventBus, GatewayEvent},
+    models::user::{user_flags, User},
+    snowflake,
+};
+use nexus_db::{
+    repository::{sessions, users},
+    search::SearchClient,
+    storage::StorageClient,
+    Database, DbBackend,
+};
+use nexus_federation::{client::FederationClient, BridgeRegistry, ServerKeyPair};
+use serde::Serialize;
+use serde_json::Value;
+use tokio::sync::broadcast::{self, error::TryRecvError};
+use tower::ServiceExt;
+use uuid::Uuid;
+
+use crate::{auth, AppState};
+
+/// Config keys every test app runs with, on top of the defaults.
+pub const TEST_CONFIG: &[(&str, &str)] = &[
+    ("server.name", "nexus.test"),
+    ("database.url", "sqlite::memory:"),
+    ("auth.jwt_secret", "nexus-test-secret"),
+    ("rate_limit.enabled", "false"),
+    ("registration.ip_limit", "0"),
+    ("spam.enabled", "false"),
+    ("unfurl.enabled", "false"),
+    ("media.enabled", "false"),
+];
+
+/// Password of every user made by [`TestApp::user`].
+pub const TEST_PASSWORD: &str = "correct horse battery staple";
+
+/// The global config, initialized with [`TEST_CONFIG`] on first use.
+pub fn config() -> &'static AppConfig {
+    nexus_common::config::init_with(TEST_CONFIG).expect("test config")
+}
+
+/// The API with its own empty database.
+pub struct TestApp {
+    pub state: Arc<AppState>,
+    pub router: Router,
+    events: broadcast::Receiver<GatewayEvent>,
+    storage_dir: PathBuf,
+}
+
+/// A user made by [`TestApp::user`], with a session and its tokens.
+#[derive(Debug, Clone)]
+pub struct TestUser {
+    pub id: Uuid,
+    pub username: String,
+    pub session_id: Uuid,
+    /// Access token for `Authorization: Bearer`.
+    pub token: String,
+    pub refresh_token: String,
+}
+
+impl TestApp {
+    pub async fn new() -> Self {
+        let config = config();
+
+        sqlx::any::install_default_drivers();
+        // Every connection to `sqlite::memory:` is a separate database, so
+        // keep exactly one open for the app's lifetime.
+        let pool = sqlx::any::AnyPoolOptions::new()
+            .max_connections(1)
+            .min_connections(1)
+            .idle_timeout(None)
+            .max_lifetime(None)
+            .connect(&config.database.url)
+            .await
+            .expect("in-memory SQLite");
+        let db = Database {
+            pool,
+            redis: None,
+            backend: DbBackend::Sqlite,
+        };
+        db.migrate().await.expect("lite migrations");
+
+        let storage_dir = std::env::temp_dir().join(format!("nexus-test-{}", Uuid::new_v4().simple()));
+        let storage = StorageClient::new_local(&storage_dir, "http://nexus.test/files").expect("local storage");
+
+        let gateway_tx = EventBus::new(1024);
+        let events = gateway_tx.subscribe();
+        let federation_key = Arc::new(ServerKeyPair::generate());
+        let state = AppState {
+            db: db.clone(),
+            gateway_tx,
+            voice_state: nexus_voice::state::VoiceStateManager::new(),
+            storage,
+            search: SearchClient::disabled(),
+            server_name: config.server.name.clone(),
+            federation_client: Arc::new(FederationClient::new(&config.server.name, federation_key.clone())),
+            federation_key,
+            bridges: BridgeRegistry::new(),
+            activitypub: None,
+            rate_limits: Arc::new(crate::ratelimit::RateLimiter::new(config.rate_limit.clone())),
+            federation_limits: Arc::new(crate::federation_limits::OriginLimiter::new(config.federation.clone())),
+            consents: Arc::new(crate::legal::ConsentCache::new()),
+            live_sessions: Arc::new(crate::session_cache::SessionCache::new()),
+            started_at: Utc::now(),
+            spam: Arc::new(crate::spam::SpamDetector::new(config.spam.clone())),
+            transcription: None,
+            media: None,
+            presence: Arc::new(nexus_db::presence::PresenceService::new(None)),
+        };
+
+        Self {
+            router: crate::build_router(state.clone()),
+            state: Arc::new(state),
+            events,
+            storage_dir,
+        }
+    }
+
+    pub fn pool(&self) -> &sqlx::AnyPool {
+        &self.state.db.pool
+    }
+
+    // ── Users and tokens ──────────────────────────────────────────────────────
+
+    /// Create a user with password [`TEST_PASSWORD`] and log them in.
+    pub async fn user(&self, username: &str) -> TestUser {
+        let user = users::create_user(self.pool(), snowflake::generate_id(), username, None, password_hash())
+            .await
+            .expect("create user");
+        self.login(&user).await
+    }
+
+    /// Like [`user`](Self::user), with the `STAFF` flag.
+    pub async fn staff(&self, username: &str) -> TestUser {
+        let user = self.user(username).await;
+        self.set_flags(user.id, user_flags::STAFF).await;
+        user
+    }
+
+    /// Set flags on a user directly.
+    pub async fn set_flags(&self, user_id: Uuid, flags: i64) -> User {
+        users::update_flags(self.pool(), user_id, flags, 0)
+            .await
+            .expect("update flags")
+            .expect("user exists")
+    }
+
+    /// Start a session for an existing user, as logging in would.
+    pub async fn login(&self, user: &User) -> TestUser {
+        let session_id = Uuid::new_v4();
+        let refresh_token = auth::generate_refresh_token();
+        sessions::create(
+            self.pool(),
+            session_id,
+            user.id,
+            &auth::hash_refresh_token(&refresh_token),
+            Some("nexus-api tests"),
+            None,
+            Utc::now() + Duration::days(1),
+        )
+        .await
+        .expect("create session");
+        TestUser {
+            id: user.id,
+            username: user.username.clone(),
+            session_id,
+            token: access_token(user.id, &user.username, session_id),
+            refresh_token,
+        }
+    }
+
+    // ── Requests ──────────────────────────────────────────────────────────────
+
+    pub fn get(&self, path: &str) -> TestRequest<'_> {
+        self.request(Method::GET, path)
+    }
+
+    pub fn post(&self, path: &str) -> TestRequest<'_> {
+        self.request(Method::POST, path)
+    }
+
+    pub fn put(&self, path: &str) -> TestRequest<'_> {
+        self.request(Method::PUT, path)
+    }
+
+    pub fn patch(&self, path: &str) -> TestRequest<'_> {
+        self.request(Method::PATCH, path)
+    }
+
+    pub fn delete(&self, path: &str) -> TestRequest<'_> {
+        self.request(Method::DELETE, path)
+    }
+
+    pub fn request(&self, method: Method, path: &str) -> TestRequest<'_> {
+        TestRequest {
+            app: self,
+            builder: Request::builder().method(method).uri(path),
+            body: Body::empty(),
+        }
+    }
+
+    // ── Gateway ───────────────────────────────────────────────────────────────
+
+    /// Gateway events broadcast since the last call, oldest first.
+    pub fn events(&mut self) -> Vec<GatewayEvent> {
+        let mut events = Vec::new();
+        loop {
+            match self.events.try_recv() {
+                Ok(event) => events.push(event),
+                Err(TryRecvError::Lagged(_)) => continue,
+                Err(TryRecvError::Empty | TryRecvError::Closed) => return events,
+            }
+        }
+    }
+
+    /// Gateway events of one type broadcast since the last [`events`](Self::events)
+    /// call. Events of other types are discarded.
+    pub fn events_of(&mut self, event_type: &str) -> Vec<GatewayEvent> {
+        self.events().into_iter().filter(|e| e.event_type == event_type).collect()
+    }
+}
+
+impl Drop for TestApp {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.storage_dir);
+    }
+}
+
+/// An access token for `user_id`, signed with the test secret.
+pub fn access_token(user_id: Uuid, username: &str, session_id: Uuid) -> String {
+    let config = config();
+    auth::generate_access_token(
+        user_id,
+        username,
+        session_id,
+        &config.auth.jwt_secret,
+        config.auth.access_token_ttl_secs,
+    )
+    .expect("sign access token")
+}
+
+/// [`TEST_PASSWORD`] hashed once: Argon2 is slow in debug builds.
+fn password_hash() -> &'static str {
+    static HASH: OnceLock<String> = OnceLock::new();
+    HASH.get_or_init(|| auth::hash_password(TEST_PASSWORD).expect("hash password"))
+}
+
+/// A request being built; [`send`](Self::send) runs it.
+pub struct TestRequest<'a> {
+    app: &'a TestApp,
+    builder: axum::http::request::Builder,
+    body: Body,
+}
+
+impl TestRequest<'_> {
+    /// Authenticate as `user`.
+    pub fn auth(self, user: &TestUser) -> Self {
+        self.bearer(&user.token)
+    }
+
+    pub fn bearer(self, token: &str) -> Self {
+        self.header(header::AUTHORIZATION.as_str(), &format!("Bearer {token}"))
+    }
+
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.builder = self.builder.header(name, value);
+        self
+    }
+
+    pub fn json(mut self, body: &impl Serialize) -> Self {
+        self.builder = self.builder.header(header::CONTENT_TYPE, "application/json");
+        self.body = Body::from(serde_json::to_vec(body).expect("serialize body"));
+        self
+    }
+
+    /// A raw body, e.g. a hand-built multipart form.
+    pub fn body(mut self, content_type: &str, body: impl Into<Body>) -> Self {
+        self.builder = self.builder.header(header::CONTENT_TYPE, content_type);
+        self.body = body.into();
+        self
+    }
+
+    pub async fn send(self) -> TestResponse {
+        let request = self.builder.body(self.body).expect("request");
+        let response = self.app.router.clone().oneshot(request).await.expect("infallible");
+        let status = response.status();
+        let headers = response.headers().clone();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("response body");
+        let body = if bytes.is_empty() {
+            Value::Null
+        } else {
+            serde_json::from_slice(&bytes).unwrap_or_else(|_| Value::String(String::from_utf8_lossy(&bytes).into()))
+        };
+        TestResponse { status, headers, body }
+    }
+}
+
+/// A response with its body read; non-JSON bodies become a JSON string.
+#[derive(Debug)]
+pub struct TestResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: Value,
+}
+
+impl TestResponse {
+    /// The body, after checking the status. Panics with the body otherwise.
+    #[track_caller]
+    pub fn expect(self, status: StatusCode) -> Value {
+        assert_eq!(self.status, status, "unexpected status; body: {}", self.body);
+        self.body
+    }
+
+    /// The error `code` name of an error response (e.g. `"FORBIDDEN"`),
+    /// after checking the status.
+    #[track_caller]
+    pub fn expect_error(self, status: StatusCode) -> String {
+        let body = self.expect(status);
+        body["error"].as_str().unwrap_or_default().to_owned()
+    }
+}
@@ -0,0 +1,319 @@
+//! Speech-to-text providers for voice messages and voice-channel recordings.
+//!
+//! Two backends are supported, selected by `transcription.provider`:
+//! - `whisper_cpp` — runs a local whisper.cpp binary; nothing leaves the host.
+//! - `http`        — any OpenAI-compatible `/audio/transcriptions` endpoint.
+//!
+//! The client is only used from the transcription background job
+//! (see [`crate::jobs::transcription`]); handlers just queue work. Voice
+//! messages are queued on upload. Recordings are queued as
+//! [`transcripts::SOURCE_RECORDING`](nexus_db::repository::transcripts::SOURCE_RECORDING)
+//! by whatever stores them; the voice server doesn't record calls yet.
+//!
+//! Every provider call is bounded by `transcription.timeout_secs`.
+
+use anyhow::{bail, Context, Result};
+use nexus_common::config::TranscriptionConfig;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+/// Output of a single transcription.
+#[derive(Debug, Clone)]
+pub struct Transcript {
+    pub text: String,
+    pub language: Option<String>,
+    /// Timed segments as `[{ "start": secs, "end": secs, "text": "..." }]`.
+    pub segments: serde_json::Value,
+}
+
+enum Backend {
+    WhisperCpp {
+        binary: String,
+        model: String,
+        ffmpeg: String,
+    },
+    Http {
+        client: reqwest::Client,
+        url: String,
+        api_key: String,
+        model: String,
+    },
+}
+
+/// Speech-to-text client — local whisper.cpp or a remote API.
+#[derive(Clone)]
+pub struct TranscriptionClient {
+    inner: Arc<Backend>,
+    language: Option<String>,
+    timeout: Duration,
+}
+
+impl TranscriptionClient {
+    /// Build a client from config. Returns `Ok(None)` when transcription is disabled.
+    pub fn from_config(cfg: &TranscriptionConfig) -> Result<Option<Self>> {
+        let timeout = Duration::from_secs(cfg.timeout_secs);
+        let backend = match cfg.provider.as_str() {
+            "" | "none" => return Ok(None),
+            "whisper_cpp" => Backend::WhisperCpp {
+                binary: cfg.whisper_binary.clone(),
+                model: cfg.whisper_model.clone(),
+                ffmpeg: cfg.ffmpeg_binary.clone(),
+            },
+            "http" => {
+                if cfg.api_url.is_empty() {
+                    bail!("transcription.api_url must be set for the http provider");
+                }
+                Backend::Http {
+                    client: reqwest::Client::builder()
+                        .timeout(timeout)
+                        .build()
+                        .context("Failed to build transcription HTTP client")?,
+                    url: cfg.api_url.clone(),
+                    api_key: cfg.api_key.clone(),
+                    model: cfg.api_model.clone(),
+                }
+            }
+            other => bail!("Unknown transcription provider '{other}'"),
+        };
+        Ok(Some(Self {
+            inner: Arc::new(backend),
+            language: cfg.language.clone().filter(|l| !l.is_empty()),
+            timeout,
+        }))
+    }
+
+    /// Short provider name stored alongside each transcript.
+    pub fn provider_name(&self) -> &'static str {
+        match self.inner.as_ref() {
+            Backend::WhisperCpp { .. } => "whisper_cpp",
+            Backend::Http { .. } => "http",
+        }
+    }
+
+    /// Transcribe an audio blob.
+    pub async fn transcribe(&self, audio: Vec<u8>, content_type: &str) -> Result<Transcript> {
+        match self.inner.as_ref() {
+            Backend::WhisperCpp { binary, model, ffmpeg } => {
+                self.transcribe_local(binary, model, ffmpeg, audio, content_type).await
+            }
+            Backend::Http { client, url, api_key, model } => {
+                self.transcribe_http(client, url, api_key, model, audio, content_type).await
+            }
+        }
+    }
+
+    // ── whisper.cpp ───────────────────────────────────────────────────────────
+
+    async fn transcribe_local(
+        &self,
+        binary: &str,
+        model: &str,
+        ffmpeg: &str,
+        audio: Vec<u8>,
+        content_type: &str,
+    ) -> Result<Transcript> {
+        let work = ScratchDir::new("nexus-stt").await?;
+        let input = work.path().join(format!("input.{}", extension_for(content_type)));
+        tokio::fs::write(&input, &audio).await.context("Failed to write audio scratch file")?;
+
+        // whisper.cpp wants 16 kHz mono PCM WAV.
+        let wav = work.path().join("audio.wav");
+        let convert = run(ffmpeg, &[
+            "-nostdin", "-loglevel", "error", "-y",
+            "-i", &input.to_string_lossy(),
+            "-ar", "16000", "-ac", "1", "-c:a", "pcm_s16le",
+            &wav.to_string_lossy(),
+        ]);
+        // `run` kills the child when its future is dropped.
+        tokio::time::timeout(self.timeout, convert)
+            .await
+            .context("ffmpeg conversion timed out")?
+            .context("ffmpeg conversion failed")?;
+
+        let out_base = work.path().join("transcript");
+        let wav = wav.to_string_lossy();
+        let out_arg = out_base.to_string_lossy();
+        let language = self.language.as_deref().unwrap_or("auto");
+        let whisper = run(binary, &["-m", model, "-f", &wav, "-l", language, "-oj", "-of", &out_arg, "-np"]);
+        tokio::time::timeout(self.timeout, whisper)
+            .await
+            .context("whisper.cpp timed out")?
+            .context("whisper.cpp failed")?;
+
+        let json = tokio::fs::read(out_base.with_extension("json"))
+            .await
+            .context("whisper.cpp produced no JSON output")?;
+        let out: WhisperCppOutput =
+            serde_json::from_slice(&json).context("Invalid whisper.cpp JSON output")?;
+
+        let segments: Vec<serde_json::Value> = out
+            .transcription
+            .iter()
+            .map(|s| {
+                serde_json::json!({
+                    "start": s.offsets.from as f64 / 1000.0,
+                    "end": s.offsets.to as f64 / 1000.0,
+                    "text": s.text.trim(),
+                })
+            })
+            .collect();
+        let text = out
+            .transcription
+            .iter()
+            .map(|s| s.text.trim())
+            .filter(|t| !t.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        Ok(Transcript {
+            text,
+            language: out.result.and_then(|r| r.language).or_else(|| self.language.clone()),
+            segments: serde_json::Value::Array(segments),
+        })
+    }
+
+    // ── OpenAI-compatible HTTP ────────────────────────────────────────────────
+
+    async fn transcribe_http(
+        &self,
+        client: &reqwest::Client,
+        url: &str,
+        api_key: &str,
+        model: &str,
+        audio: Vec<u8>,
+        content_type: &str,
+    ) -> Result<Transcript> {
+        let file = reqwest::multipart::Part::bytes(audio)
+            .file_name(format!("audio.{}", extension_for(content_type)))
+            .mime_str(content_type)
+            .context("Invalid audio content type")?;
+        let mut form = reqwest::multipart::Form::new()
+            .part("file", file)
+            .text("model", model.to_string())
+            .text("response_format", "verbose_json");
+        if let Some(lang) = &self.language {
+            form = form.text("language", lang.clone());
+        }
+
+        let mut req = client.post(url).multipart(form);
+        if !api_key.is_empty() {
+            req = req.bearer_auth(api_key);
+        }
+        let resp = req.send().await.context("Transcription request failed")?;
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let body = resp.text().await.unwrap_or_default();
+            bail!("Transcription API returned {status}: {body}");
+        }
+        let out: HttpOutput = resp.json().await.context("Invalid transcription response")?;
+
+        Ok(Transcript {
+            text: out.text.trim().to_string(),
+            language: out.language.or_else(|| self.language.clone()),
+            segments: serde_json::to_value(out.segments.unwrap_or_default())?,
+        })
+    }
+}
+
+// ── Provider response shapes ─────────────────────────────────────────────────
+
+#[derive(Deserialize)]
+struct WhisperCppOutput {
+    result: Option<WhisperCppResult>,
+    #[serde(default)]
+    transcription: Vec<WhisperCppSegment>,
+}
+
+#[derive(Deserialize)]
+struct WhisperCppResult {
+    language: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct WhisperCppSegment {
+    offsets: WhisperCppOffsets,
+    text: String,
+}
+
+#[derive(Deserialize)]
+struct WhisperCppOffsets {
+    /// Milliseconds.
+    from: i64,
+    to: i64,
+}
+
+#[derive(Deserialize)]
+struct HttpOutput {
+    text: String,
+    language: Option<String>,
+    segments: Option<Vec<HttpSegment>>,
+}
+
+#[derive(Deserialize, serde::Serialize)]
+struct HttpSegment {
+    start: f64,
+    end: f64,
+    text: String,
+}
+
+// ── Helpers ──────────────────────────────────────────────────────────────────
+
+/// Whether an uploaded file should be queued for transcription.
+pub fn is_transcribable(content_type: &str) -> bool {
+    content_type.starts_with("audio/")
+}
+
+fn extension_for(content_type: &str) -> &'static str {
+    match content_type.split(';').next().unwrap_or("").trim() {
+        "audio/ogg" | "audio/opus" => "ogg",
+        "audio/webm" => "webm",
+        "audio/mpeg" | "audio/mp3" => "mp3",
+        "audio/mp4" | "audio/m4a" | "audio/x-m4a" | "audio/aac" => "m4a",
+        "audio/wav" | "audio/x-wav" | "audio/wave" => "wav",
+        "audio/flac" => "flac",
+        _ => "bin",
+    }
+}
+
+pub(crate) async fn run(program: &str, args: &[&str]) -> Result<()> {
+    let output = tokio::process::Command::new(program)
+        .args(args)
+        .kill_on_drop(true)
+        .output()
+        .await
+        .with_context(|| format!("Failed to spawn {program}"))?;
+    if !output.status.success() {
+        bail!(
+            "{program} exited with {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
+
+/// Per-job scratch directory, removed on drop.
+pub(crate) struct ScratchDir(PathBuf);
+
+impl ScratchDir {
+    pub(crate) async fn new(prefix: &str) -> Result<Self> {
+        let dir = std::env::temp_dir().join(format!("{prefix}-{}", Uuid::new_v4()));
+        tokio::fs::create_dir_all(&dir)
+            .await
+            .context("Failed to create scratch dir")?;
+        Ok(Self(dir))
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for ScratchDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
@@ -0,0 +1,513 @@
+//! Link previews — turn the URLs in a message into embeds.
+//!
+//! [`spawn`] runs after a message is posted or edited. In the background it
+//! fetches up to `unfurl.max_links` of the message's links, reads their
+//! OpenGraph / Twitter card `<meta>` tags, stores the previews as the
+//! message's `embeds` and emits `MESSAGE_UPDATE`.
+//!
+//! Only public addresses are fetched: every host, including each redirect
+//! target, is resolved first and skipped if any address it resolves to is
+//! loopback, private, link-local or otherwise not globally routable. The
+//! connection is then pinned to the checked address. Domains listed in
+//! `unfurl.blocked_domains` (and their subdomains) are never fetched. Links
+//! wrapped in `<…>` are not previewed, nor are messages flagged
+//! `SUPPRESS_EMBEDS`.
+
+use std::net::{IpAddr, SocketAddr};
+use std::time::Duration;
+
+use nexus_common::config::UnfurlConfig;
+use nexus_common::net::is_public;
+use nexus_common::gateway_event::{event_types, EventBus, GatewayEvent};
+use nexus_common::models::channel::Channel;
+use nexus_common::models::message::{Embed, EmbedAuthor, EmbedMedia, MessageFlags};
+use nexus_db::repository::messages::{self, MessageRow};
+use url::{Host, Url};
+use uuid::Uuid;
+
+use crate::{routes::messages::message_row_to_json, AppState};
+
+/// Redirects followed per link.
+pub(crate) const MAX_REDIRECTS: usize = 3;
+/// Longest preview title, in characters.
+const MAX_TITLE_CHARS: usize = 256;
+/// Longest preview description, in characters.
+const MAX_DESCRIPTION_CHARS: usize = 350;
+
+const USER_AGENT: &str = concat!("Mozilla/5.0 (compatible; NexusBot/", env!("CARGO_PKG_VERSION"), "; link preview)");
+
+/// Generate link previews for `msg` in the background, if previews are
+/// enabled and it has links — or had previews that an edit has made stale.
+pub(crate) fn spawn(state: &AppState, channel: &Channel, msg: &MessageRow) {
+    let cfg = &nexus_common::config::get().unfurl;
+    if !cfg.enabled || MessageFlags::from_bits_truncate(msg.flags).suppresses_embeds() {
+        return;
+    }
+    let urls = extract_urls(&msg.content, cfg.max_links as usize);
+    let had_previews = msg.embeds.as_array().is_some_and(|e| !e.is_empty());
+    if urls.is_empty() && !had_previews {
+        return;
+    }
+
+    let pool = state.db.pool.clone();
+    let gateway_tx = state.gateway_tx.clone();
+    let server_id = channel.server_id;
+    let (message_id, content, current) = (msg.id, msg.content.clone(), msg.embeds.clone());
+    tokio::spawn(async move {
+        let mut embeds = Vec::new();
+        for url in urls {
+            if let Some(embed) = fetch_embed(&url, cfg).await {
+                embeds.push(embed);
+            }
+        }
+        let embeds = serde_json::to_value(&embeds).unwrap_or_else(|_| serde_json::json!([]));
+        if embeds == current {
+            return;
+        }
+        match messages::set_embeds(&pool, message_id, &content, &embeds).await {
+            Ok(Some(updated)) => publish(&gateway_tx, server_id, &updated),
+            Ok(None) => {}
+            Err(e) => tracing::warn!(%message_id, error = %e, "Failed to store link previews"),
+        }
+    });
+}
+
+fn publish(gateway_tx: &EventBus, server_id: Option<Uuid>, msg: &MessageRow) {
+    let _ = gateway_tx.send(GatewayEvent {
+        event_type: event_types::MESSAGE_UPDATE.into(),
+        data: message_row_to_json(msg, &[]),
+        server_id,
+        channel_id: Some(msg.channel_id),
+        user_id: Some(msg.author_id),
+        trace_context: None,
+    });
+}
+
+// ============================================================================
+// Link extraction and address policy
+// ============================================================================
+
+/// The distinct `http(s)` links in `content`, in order, at most `max`.
+/// Links wrapped in `<…>` are left out.
+pub fn extract_urls(content: &str, max: usize) -> Vec<Url> {
+    let mut urls: Vec<Url> = Vec::new();
+    for word in content.split_whitespace() {
+        if urls.len() >= max {
+            break;
+        }
+        let lower = word.to_ascii_lowercase();
+        let Some(start) = lower.find("https://").or_else(|| lower.find("http://")) else {
+            continue;
+        };
+        if word[..start].ends_with('<') {
+            continue;
+        }
+        let candidate = word[start..].trim_end_matches(['.', ',', ';', ':', '!', '?', ')', ']', '>', '"', '\'', '*', '_', '~', '|']);
+        let Ok(url) = Url::parse(candidate) else {
+            continue;
+        };
+        if url.host().is_some() && !urls.contains(&url) {
+            urls.push(url);
+        }
+    }
+    urls
+}
+
+/// Whether `host` is, or is a subdomain of, a domain in the comma-separated
+/// `blocklist`.
+pub fn is_blocked(host: &str, blocklist: &str) -> bool {
+    let host = host.trim_end_matches('.').to_ascii_lowercase();
+    blocklist
+        .split(',')
+        .map(|d| d.trim().trim_start_matches("*.").trim_end_matches('.').to_ascii_lowercase())
+        .filter(|d| !d.is_empty())
+        .any(|d| host == d || host.strip_suffix(&d).is_some_and(|rest| rest.ends_with('.')))
+}
+
+/// The address `url` may be fetched from, or `None` if it is not an
+/// `http(s)` URL on a public host outside the comma-separated
+/// `blocked_domains`.
+pub(crate) async fn resolve(url: &Url, blocked_domains: &str) -> Option<(String, SocketAddr)> {
+    if !matches!(url.scheme(), "http" | "https") {
+        return None;
+    }
+    let port = url.port_or_known_default()?;
+    match url.host()? {
+        Host::Ipv4(v4) => is_public(IpAddr::V4(v4)).then(|| (v4.to_string(), SocketAddr::new(v4.into(), port))),
+        Host::Ipv6(v6) => is_public(IpAddr::V6(v6)).then(|| (v6.to_string(), SocketAddr::new(v6.into(), port))),
+        Host::Domain(domain) => {
+            if is_blocked(domain, blocked_domains) {
+                return None;
+            }
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((domain, port)).await.ok()?.collect();
+            // One private answer is enough to refuse: the client might pick it
+            if addrs.is_empty() || addrs.iter().any(|a| !is_public(a.ip())) {
+                return None;
+            }
+            Some((domain.to_owned(), addrs[0]))
+        }
+    }
+}
+
+// ============================================================================
+// Fetching
+// ============================================================================
+
+/// Fetch `url` and build its preview, or `None` if it is not allowed, not
+/// reachable within `unfurl.timeout_secs`, or has nothing to show.
+pub async fn fetch_embed(url: &Url, cfg: &UnfurlConfig) -> Option<Embed> {
+    tokio::time::timeout(Duration::from_secs(cfg.timeout_secs), fetch_embed_inner(url, cfg))
+        .await
+        .ok()
+        .flatten()
+}
+
+async fn fetch_embed_inner(url: &Url, cfg: &UnfurlConfig) -> Option<Embed> {
+    let mut url = url.clone();
+    for _ in 0..=MAX_REDIRECTS {
+        let (host, addr) = resolve(&url, &cfg.blocked_domains).await?;
+        // Pin the connection to the address just checked, and follow
+        // redirects by hand so each hop is checked too
+        let client = reqwest::Client::builder()
+            .redirect(reqwest::redirect::Policy::none())
+            .resolve(&host, addr)
+            .user_agent(USER_AGENT)
+            .build()
+            .ok()?;
+        let mut response = client
+            .get(url.clone())
+            .header(reqwest::header::ACCEPT, "text/html,application/xhtml+xml,image/*;q=0.8")
+            .send()
+            .await
+            .ok()?;
+
+        if response.status().is_redirection() {
+            let location = response.headers().get(reqwest::header::LOCATION)?.to_str().ok()?;
+            url = url.join(location).ok()?;
+            continue;
+        }
+        if !response.status().is_success() {
+            return None;
+        }
+
+        let content_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        if content_type.starts_with("image/") {
+            return Some(Embed {
+                url: Some(url.to_string()),
+                image: Some(EmbedMedia { url: url.to_string(), width: None, height: None }),
+                ..Default::default()
+            });
+        }
+        if !content_type.starts_with("text/html") && !content_type.starts_with("application/xhtml") {
+            return None;
+        }
+
+        let mut body = Vec::new();
+        while let Ok(Some(chunk)) = response.chunk().await {
+            let room = (cfg.max_bytes as usize).saturating_sub(body.len());
+            body.extend_from_slice(&chunk[..chunk.len().min(room)]);
+            if body.len() >= cfg.max_bytes as usize {
+                break;
+            }
+        }
+        return parse_embed(&String::from_utf8_lossy(&body), &url);
+    }
+    None
+}
+
+// ============================================================================
+// Metadata parsing
+// ============================================================================
+
+/// Build a preview from a page's OpenGraph / Twitter card `<meta>` tags,
+/// falling back to `<title>` and `<meta name="description">`. `None` if the
+/// page has neither a title nor a description.
+pub fn parse_embed(html: &str, page_url: &Url) -> Option<Embed> {
+    let meta = meta_tags(html);
+    let get = |keys: &[&str]| {
+        keys.iter()
+            .find_map(|k| meta.iter().find(|(key, _)| key == k).map(|(_, v)| v.as_str()))
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+    };
+
+    let title = get(&["og:title", "twitter:title"])
+        .map(str::to_owned)
+        .or_else(|| title_tag(html))
+        .map(|t| truncate(&t, MAX_TITLE_CHARS));
+    let description = get(&["og:description", "twitter:description", "description"])
+        .map(|d| truncate(d, MAX_DESCRIPTION_CHARS));
+    if title.is_none() && description.is_none() {
+        return None;
+    }
+
+    let absolute = |v: &str| {
+        page_url.join(v).ok().filter(|u| matches!(u.scheme(), "http" | "https")).map(String::from)
+    };
+    let dimension = |keys: &[&str]| get(keys).and_then(|v| v.parse::<u32>().ok());
+    let media = get(&["og:image:secure_url", "og:image:url", "og:image", "twitter:image", "twitter:image:src"])
+        .and_then(absolute)
+        .map(|url| EmbedMedia {
+            url,
+            width: dimension(&["og:image:width"]),
+            height: dimension(&["og:image:height"]),
+        });
+    // Small "summary" cards show their image beside the text, not under it
+    let (image, thumbnail) = match get(&["twitter:card"]) {
+        Some("summary") => (None, media),
+        _ => (media, None),
+    };
+
+    Some(Embed {
+        title,
+        description,
+        url: Some(get(&["og:url"]).and_then(absolute).unwrap_or_else(|| page_url.to_string())),
+        color: get(&["theme-color"]).and_then(parse_color),
+        image,
+        thumbnail,
+        author: get(&["og:site_name"]).map(|name| EmbedAuthor {
+            name: truncate(name, MAX_TITLE_CHARS),
+            url: Some(page_url.origin().ascii_serialization()),
+            icon_url: None,
+        }),
+        ..Default::default()
+    })
+}
+
+/// `(property or name, content)` of every `<meta>` tag, keys lowercased.
+fn meta_tags(html: &str) -> Vec<(String, String)> {
+    let lower = html.to_ascii_lowercase();
+    let mut tags = Vec::new();
+    let mut rest = 0;
+    while let Some(offset) = lower[rest..].find("<meta") {
+        let start = rest + offset + "<meta".len();
+        let Some(len) = lower[start..].find('>') else {
+            break;
+        };
+        let attrs = attributes(&html[start..start + len]);
+        rest = start + len;
+        let key = attrs.iter().find(|(k, _)| k == "property" || k == "name").map(|(_, v)| v.to_ascii_lowercase());
+        let content = attrs.iter().find(|(k, _)| k == "content").map(|(_, v)| v.clone());
+        if let (Some(key), Some(content)) = (key, content) {
+            tags.push((key, content));
+        }
+    }
+    tags
+}
+
+/// Attributes of a tag body such as ` property="og:title" content='Hi'`,
+/// names lowercased and values entity-decoded.
+fn attributes(tag: &str) -> Vec<(String, String)> {
+    let mut attrs = Vec::new();
+    let mut chars = tag.char_indices().peekable();
+    while let Some(&(start, c)) = chars.peek() {
+        if c.is_whitespace() || c == '/' {
+            chars.next();
+            continue;
+        }
+        let mut end = start;
+        while let Some(&(i, c)) = chars.peek() {
+            if c == '=' || c.is_whitespace() || c == '/' {
+                break;
+            }
+            end = i + c.len_utf8();
+            chars.next();
+        }
+        let name = tag[start..end].to_ascii_lowercase();
+        if name.is_empty() {
+            chars.next();
+            continue;
+        }
+        while chars.next_if(|&(_, c)| c.is_whitespace()).is_some() {}
+        if chars.next_if(|&(_, c)| c == '=').is_none() {
+            attrs.push((name, String::new()));
+            continue;
+        }
+        while chars.next_if(|&(_, c)| c.is_whitespace()).is_some() {}
+        let value = match chars.peek() {
+            Some(&(i, quote @ ('"' | '\''))) => {
+                chars.next();
+                let close = tag[i + 1..].find(quote).map_or(tag.len(), |n| i + 1 + n);
+                while chars.next_if(|&(j, _)| j <= close).is_some() {}
+                &tag[i + 1..close]
+            }
+            Some(&(i, _)) => {
+                let mut end = i;
+                while let Some((j, c)) = chars.next_if(|&(_, c)| !c.is_whitespace()) {
+                    end = j + c.len_utf8();
+                }
+                &tag[i..end]
+            }
+            None => "",
+        };
+        attrs.push((name, decode_entities(value)));
+    }
+    attrs
+}
+
+fn title_tag(html: &str) -> Option<String> {
+    let lower = html.to_ascii_lowercase();
+    let open = lower.find("<title")?;
+    let start = open + lower[open..].find('>')? + 1;
+    let end = start + lower[start..].find("</title")?;
+    let title = decode_entities(html[start..end].trim());
+    (!title.is_empty()).then_some(title)
+}
+
+/// Decode the named entities pages commonly use in metadata, plus numeric
+/// character references.
+fn decode_entities(s: &str) -> String {
+    if !s.contains('&') {
+        return s.to_owned();
+    }
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let decoded = rest[1..].find(';').filter(|&n| n <= 10).and_then(|n| {
+            let entity = &rest[1..n + 1];
+            let c = match entity {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                "nbsp" => Some('\u{a0}'),
+                _ => entity
+                    .strip_prefix("#x")
+                    .or_else(|| entity.strip_prefix("#X"))
+                    .map(|hex| u32::from_str_radix(hex, 16))
+                    .or_else(|| entity.strip_prefix('#').map(str::parse))
+                    .and_then(Result::ok)
+                    .and_then(char::from_u32),
+            };
+            c.map(|c| (c, n + 2))
+        });
+        match decoded {
+            Some((c, len)) => {
+                out.push(c);
+                rest = &rest[len..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// `#rrggbb` (or `#rgb`) as an embed colour.
+fn parse_color(value: &str) -> Option<u32> {
+    let hex = value.strip_prefix('#')?;
+    match hex.len() {
+        6 => u32::from_str_radix(hex, 16).ok(),
+        3 => {
+            let n = u32::from_str_radix(hex, 16).ok()?;
+            let (r, g, b) = ((n >> 8) & 0xf, (n >> 4) & 0xf, n & 0xf);
+            Some(((r * 0x11) << 16) | ((g * 0x11) << 8) | (b * 0x11))
+        }
+        _ => None,
+    }
+}
+
+fn truncate(s: &str, max_chars: usize) -> String {
+    match s.char_indices().nth(max_chars) {
+        Some((i, _)) => format!("{}…", s[..i].trim_end()),
+        None => s.to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn url(s: &str) -> Url {
+        Url::parse(s).unwrap()
+    }
+
+    #[test]
+    fn extracts_distinct_unsuppressed_links() {
+        let content = "see https://a.example/x, and (http://b.example/) <https://c.example> \
+                       again https://a.example/x https://d.example https://e.example";
+        let urls: Vec<_> = extract_urls(content, 3).into_iter().map(String::from).collect();
+        assert_eq!(urls, ["https://a.example/x", "http://b.example/", "https://d.example/"]);
+        assert!(extract_urls("no links, ftp://x.example www.example.com", 3).is_empty());
+    }
+
+    #[test]
+    fn blocks_domains_and_subdomains() {
+        let list = "evil.example, *.tracker.example.";
+        assert!(is_blocked("evil.example", list));
+        assert!(is_blocked("CDN.Evil.Example.", list));
+        assert!(is_blocked("a.tracker.example", list));
+        assert!(!is_blocked("notevil.example", list));
+        assert!(!is_blocked("evil.example.org", list));
+        assert!(!is_blocked("evil.example", ""));
+    }
+
+    #[test]
+    fn parses_open_graph_and_twitter_cards() {
+        let html = r##"<html><head>
+            <title>Fallback</title>
+            <meta property="og:title" content="Tom &amp; Jerry&#39;s">
+            <meta name=description content='Plain description'>
+            <meta property="og:image" content="/img/cover.png" />
+            <meta property="og:image:width" content="1200">
+            <meta property="og:site_name" content="Example">
+            <meta name="twitter:card" content="summary">
+            <meta name="theme-color" content="#ff8800">
+        </head></html>"##;
+        let embed = parse_embed(html, &url("https://www.example.com/post/1")).unwrap();
+        assert_eq!(embed.title.as_deref(), Some("Tom & Jerry's"));
+        assert_eq!(embed.description.as_deref(), Some("Plain description"));
+        assert_eq!(embed.url.as_deref(), Some("https://www.example.com/post/1"));
+        assert_eq!(embed.color, Some(0xff8800));
+        assert!(embed.image.is_none());
+        let thumb = embed.thumbnail.unwrap();
+        assert_eq!(thumb.url, "https://www.example.com/img/cover.png");
+        assert_eq!(thumb.width, Some(1200));
+        assert_eq!(embed.author.unwrap().name, "Example");
+    }
+
+    #[test]
+    fn falls_back_to_title_tag_and_skips_empty_pages() {
+        let page = url("https://example.com/");
+        let embed = parse_embed("<TITLE> Just a title </TITLE>", &page).unwrap();
+        assert_eq!(embed.title.as_deref(), Some("Just a title"));
+        assert!(embed.image.is_none() && embed.author.is_none());
+        assert!(parse_embed("<html><body>hi</body></html>", &page).is_none());
+
+        let long = format!(r#"<meta property="og:description" content="{}">"#, "x".repeat(500));
+        let description = parse_embed(&long, &page).unwrap().description.unwrap();
+        assert_eq!(description.chars().count(), MAX_DESCRIPTION_CHARS + 1);
+    }
+
+    #[tokio::test]
+    async fn refuses_private_hosts_before_connecting() {
+        let cfg = UnfurlConfig {
+            enabled: true,
+            blocked_domains: "blocked.example".into(),
+            timeout_secs: 5,
+            max_bytes: 1024,
+            max_links: 3,
+        };
+        for target in [
+            "http://127.0.0.1:1/",
+            "http://[::1]/",
+            "http://localhost/",
+            "http://169.254.169.254/latest/meta-data/",
+            "https://blocked.example/",
+            "file:///etc/passwd",
+        ] {
+            assert!(fetch_embed(&url(target), &cfg).await.is_none(), "{target}");
+        }
+    }
+}
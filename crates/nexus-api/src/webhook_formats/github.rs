@@ -0,0 +1,363 @@
+//! GitHub webhook ingestion.
+//!
+//! Renders the events repositories most often notify chat about — `push`,
+//! `pull_request`, `issues` and `release` — into embeds. Other events (and
+//! noisy actions such as PR `synchronize`) are accepted but produce no
+//! message, so a repo can send "everything" without flooding the channel.
+
+use nexus_common::models::message::{Embed, EmbedAuthor, EmbedFooter};
+use serde::Deserialize;
+
+use super::{empty_embed, WebhookMessage};
+
+const COLOR_PUSH: u32 = 0x7289DA;
+const COLOR_OPENED: u32 = 0x2EA043;
+const COLOR_CLOSED: u32 = 0xCF222E;
+const COLOR_MERGED: u32 = 0x8250DF;
+const COLOR_RELEASE: u32 = 0xBF8700;
+
+/// Commits listed in a push embed before the rest are summarised.
+const MAX_COMMITS: usize = 10;
+/// Issue / PR / release bodies are clipped to this many characters.
+const MAX_BODY_CHARS: usize = 500;
+
+#[derive(Debug, Deserialize)]
+struct Repository {
+    full_name: String,
+    html_url: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Sender {
+    login: String,
+    html_url: Option<String>,
+    avatar_url: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PushEvent {
+    #[serde(rename = "ref")]
+    git_ref: String,
+    #[serde(default)]
+    created: bool,
+    #[serde(default)]
+    deleted: bool,
+    #[serde(default)]
+    forced: bool,
+    compare: Option<String>,
+    #[serde(default)]
+    commits: Vec<Commit>,
+    repository: Repository,
+    sender: Sender,
+}
+
+#[derive(Debug, Deserialize)]
+struct Commit {
+    id: String,
+    message: String,
+    url: String,
+    author: CommitAuthor,
+}
+
+#[derive(Debug, Deserialize)]
+struct CommitAuthor {
+    name: String,
+    username: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PullRequestEvent {
+    action: String,
+    pull_request: Issue,
+    repository: Repository,
+    sender: Sender,
+}
+
+#[derive(Debug, Deserialize)]
+struct IssuesEvent {
+    action: String,
+    issue: Issue,
+    repository: Repository,
+    sender: Sender,
+}
+
+/// Fields shared by issues and pull requests.
+#[derive(Debug, Deserialize)]
+struct Issue {
+    number: u64,
+    title: String,
+    html_url: String,
+    body: Option<String>,
+    #[serde(default)]
+    merged: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReleaseEvent {
+    action: String,
+    release: Release,
+    repository: Repository,
+    sender: Sender,
+}
+
+#[derive(Debug, Deserialize)]
+struct Release {
+    tag_name: String,
+    name: Option<String>,
+    html_url: String,
+    body: Option<String>,
+    #[serde(default)]
+    prerelease: bool,
+}
+
+/// Translate a GitHub delivery (`X-GitHub-Event` + JSON body).
+///
+/// Returns `Ok(None)` for events that are understood but deliberately not
+/// posted (including `ping`).
+pub fn translate(event: &str, body: &[u8]) -> Result<Option<WebhookMessage>, String> {
+    let parse_err = |e: serde_json::Error| format!("invalid GitHub '{event}' payload: {e}");
+    let message = match event {
+        "push" => render_push(serde_json::from_slice(body).map_err(parse_err)?),
+        "pull_request" => render_pull_request(serde_json::from_slice(body).map_err(parse_err)?),
+        "issues" => render_issue(serde_json::from_slice(body).map_err(parse_err)?),
+        "release" => render_release(serde_json::from_slice(body).map_err(parse_err)?),
+        _ => None,
+    };
+    Ok(message)
+}
+
+/// Verify an `X-Hub-Signature-256` header (`sha256=<hex>`) against `secret`.
+pub fn verify_signature(secret: &str, body: &[u8], header: &str) -> bool {
+    use hmac::{Hmac, Mac};
+    use sha2::Sha256;
+
+    let Some(expected) = header.strip_prefix("sha256=").and_then(|h| hex::decode(h).ok()) else {
+        return false;
+    };
+    let Ok(mut mac) = Hmac::<Sha256>::new_from_slice(secret.as_bytes()) else {
+        return false;
+    };
+    mac.update(body);
+    mac.verify_slice(&expected).is_ok()
+}
+
+fn render_push(e: PushEvent) -> Option<WebhookMessage> {
+    let (kind, name) = match e.git_ref.strip_prefix("refs/tags/") {
+        Some(tag) => ("tag", tag),
+        None => ("branch", e.git_ref.strip_prefix("refs/heads/").unwrap_or(&e.git_ref)),
+    };
+    let repo = &e.repository.full_name;
+
+    let mut embed = empty_embed();
+    embed.color = Some(COLOR_PUSH);
+    embed.author = Some(author(&e.sender));
+
+    if e.deleted {
+        embed.title = Some(format!("[{repo}] {kind} deleted: {name}"));
+        embed.url = Some(e.repository.html_url);
+    } else if e.commits.is_empty() {
+        if !e.created {
+            return None;
+        }
+        embed.title = Some(format!("[{repo}] new {kind} created: {name}"));
+        embed.url = Some(format!("{}/tree/{name}", e.repository.html_url));
+    } else {
+        let count = e.commits.len();
+        let plural = if count == 1 { "" } else { "s" };
+        let forced = if e.forced { " (force-pushed)" } else { "" };
+        embed.title = Some(format!("[{repo}:{name}] {count} new commit{plural}{forced}"));
+        embed.url = e.compare;
+
+        let mut lines: Vec<String> = e
+            .commits
+            .iter()
+            .take(MAX_COMMITS)
+            .map(|c| {
+                let short = &c.id[..c.id.len().min(7)];
+                let summary = c.message.lines().next().unwrap_or_default();
+                let who = c.author.username.as_deref().unwrap_or(&c.author.name);
+                format!("[`{short}`]({}) {} — {who}", c.url, clip(summary, 72))
+            })
+            .collect();
+        if count > MAX_COMMITS {
+            lines.push(format!("…and {} more", count - MAX_COMMITS));
+        }
+        embed.description = Some(lines.join("\n"));
+    }
+
+    Some(message(embed))
+}
+
+fn render_pull_request(e: PullRequestEvent) -> Option<WebhookMessage> {
+    let pr = e.pull_request;
+    let (verb, color, show_body) = match e.action.as_str() {
+        "opened" => ("opened", COLOR_OPENED, true),
+        "reopened" => ("reopened", COLOR_OPENED, false),
+        "ready_for_review" => ("ready for review", COLOR_OPENED, false),
+        "closed" if pr.merged => ("merged", COLOR_MERGED, false),
+        "closed" => ("closed", COLOR_CLOSED, false),
+        _ => return None,
+    };
+
+    let mut embed = empty_embed();
+    embed.title = Some(format!(
+        "[{}] Pull request {verb}: #{} {}",
+        e.repository.full_name, pr.number, pr.title
+    ));
+    embed.url = Some(pr.html_url);
+    embed.color = Some(color);
+    embed.author = Some(author(&e.sender));
+    if show_body {
+        embed.description = pr.body.as_deref().map(|b| clip(b, MAX_BODY_CHARS)).filter(|b| !b.is_empty());
+    }
+    Some(message(embed))
+}
+
+fn render_issue(e: IssuesEvent) -> Option<WebhookMessage> {
+    let issue = e.issue;
+    let (verb, color, show_body) = match e.action.as_str() {
+        "opened" => ("opened", COLOR_OPENED, true),
+        "reopened" => ("reopened", COLOR_OPENED, false),
+        "closed" => ("closed", COLOR_CLOSED, false),
+        _ => return None,
+    };
+
+    let mut embed = empty_embed();
+    embed.title = Some(format!(
+        "[{}] Issue {verb}: #{} {}",
+        e.repository.full_name, issue.number, issue.title
+    ));
+    embed.url = Some(issue.html_url);
+    embed.color = Some(color);
+    embed.author = Some(author(&e.sender));
+    if show_body {
+        embed.description = issue.body.as_deref().map(|b| clip(b, MAX_BODY_CHARS)).filter(|b| !b.is_empty());
+    }
+    Some(message(embed))
+}
+
+fn render_release(e: ReleaseEvent) -> Option<WebhookMessage> {
+    if e.action != "published" {
+        return None;
+    }
+    let r = e.release;
+    let label = r.name.as_deref().filter(|n| !n.is_empty()).unwrap_or(&r.tag_name);
+    let kind = if r.prerelease { "pre-release" } else { "release" };
+
+    let mut embed = empty_embed();
+    embed.title = Some(format!("[{}] New {kind} published: {label}", e.repository.full_name));
+    embed.url = Some(r.html_url);
+    embed.color = Some(COLOR_RELEASE);
+    embed.author = Some(author(&e.sender));
+    embed.description = r.body.as_deref().map(|b| clip(b, MAX_BODY_CHARS)).filter(|b| !b.is_empty());
+    embed.footer = Some(EmbedFooter {
+        text: r.tag_name.clone(),
+        icon_url: None,
+    });
+    Some(message(embed))
+}
+
+fn author(sender: &Sender) -> EmbedAuthor {
+    EmbedAuthor {
+        name: sender.login.clone(),
+        url: sender.html_url.clone(),
+        icon_url: sender.avatar_url.clone(),
+    }
+}
+
+/// Posts keep the webhook's own name and avatar — no per-message override.
+fn message(embed: Embed) -> WebhookMessage {
+    WebhookMessage {
+        embeds: vec![embed],
+        ..Default::default()
+    }
+}
+
+/// Clip to `max` characters on a char boundary, marking the cut with `…`.
+fn clip(s: &str, max: usize) -> String {
+    let s = s.trim();
+    if s.chars().count() <= max {
+        return s.to_string();
+    }
+    let mut out: String = s.chars().take(max.saturating_sub(1)).collect();
+    out.push('…');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn repo() -> serde_json::Value {
+        serde_json::json!({ "full_name": "acme/app", "html_url": "https://github.com/acme/app" })
+    }
+
+    fn sender() -> serde_json::Value {
+        serde_json::json!({ "login": "octocat", "avatar_url": "https://avatars.example/1" })
+    }
+
+    #[test]
+    fn test_push_lists_commits() {
+        let body = serde_json::json!({
+            "ref": "refs/heads/main",
+            "compare": "https://github.com/acme/app/compare/a...b",
+            "commits": [
+                { "id": "0123456789abcdef", "message": "Fix login\n\nlong body",
+                  "url": "https://github.com/acme/app/commit/0123456",
+                  "author": { "name": "Octo Cat", "username": "octocat" } }
+            ],
+            "repository": repo(),
+            "sender": sender(),
+        });
+        let msg = translate("push", body.to_string().as_bytes()).unwrap().unwrap();
+        let embed = &msg.embeds[0];
+        assert_eq!(embed.title.as_deref(), Some("[acme/app:main] 1 new commit"));
+        assert_eq!(
+            embed.description.as_deref(),
+            Some("[`0123456`](https://github.com/acme/app/commit/0123456) Fix login — octocat")
+        );
+        assert!(msg.username.is_none());
+    }
+
+    #[test]
+    fn test_merged_pull_request() {
+        let body = serde_json::json!({
+            "action": "closed",
+            "pull_request": { "number": 7, "title": "Add cache", "html_url": "https://github.com/acme/app/pull/7", "merged": true },
+            "repository": repo(),
+            "sender": sender(),
+        });
+        let msg = translate("pull_request", body.to_string().as_bytes()).unwrap().unwrap();
+        assert_eq!(
+            msg.embeds[0].title.as_deref(),
+            Some("[acme/app] Pull request merged: #7 Add cache")
+        );
+        assert_eq!(msg.embeds[0].color, Some(COLOR_MERGED));
+    }
+
+    #[test]
+    fn test_noisy_and_unknown_events_are_ignored() {
+        let body = serde_json::json!({
+            "action": "synchronize",
+            "pull_request": { "number": 7, "title": "x", "html_url": "https://github.com/acme/app/pull/7" },
+            "repository": repo(),
+            "sender": sender(),
+        });
+        assert!(translate("pull_request", body.to_string().as_bytes()).unwrap().is_none());
+        assert!(translate("ping", b"{}").unwrap().is_none());
+        assert!(translate("watch", b"{}").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_verify_signature() {
+        let valid = {
+            use hmac::{Hmac, Mac};
+            let mut mac = Hmac::<sha2::Sha256>::new_from_slice(b"secret").unwrap();
+            mac.update(b"{}");
+            format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+        };
+        assert!(verify_signature("secret", b"{}", &valid));
+        assert!(!verify_signature("other", b"{}", &valid));
+        assert!(!verify_signature("secret", b"{}", "sha1=abc"));
+    }
+}
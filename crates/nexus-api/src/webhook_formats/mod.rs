@@ -0,0 +1,49 @@
+//! Third-party webhook payload translators.
+//!
+//! Each submodule turns a foreign payload format into a [`WebhookMessage`],
+//! which the webhook routes then deliver exactly like a native execution.
+
+pub mod github;
+pub mod slack;
+
+use nexus_common::models::message::Embed;
+
+/// Maximum embeds accepted on a single webhook message.
+pub const MAX_EMBEDS: usize = 10;
+
+/// A translated webhook message, ready for delivery.
+#[derive(Debug, Default)]
+pub struct WebhookMessage {
+    pub content: String,
+    /// Per-message display-name override.
+    pub username: Option<String>,
+    /// Per-message avatar override.
+    pub avatar_url: Option<String>,
+    pub embeds: Vec<Embed>,
+}
+
+/// Parse a `#rrggbb` / `rrggbb` colour string.
+pub(crate) fn parse_hex_color(s: &str) -> Option<u32> {
+    let hex = s.trim().trim_start_matches('#');
+    if hex.len() != 6 {
+        return None;
+    }
+    u32::from_str_radix(hex, 16).ok()
+}
+
+/// Convenience constructor — `Embed` has no `Default`.
+pub(crate) fn empty_embed() -> Embed {
+    Embed {
+        title: None,
+        description: None,
+        url: None,
+        color: None,
+        timestamp: None,
+        footer: None,
+        image: None,
+        thumbnail: None,
+        video: None,
+        author: None,
+        fields: Vec::new(),
+    }
+}
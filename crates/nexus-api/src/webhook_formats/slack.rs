@@ -0,0 +1,410 @@
+//! Slack incoming-webhook compatibility.
+//!
+//! Accepts the payload Slack's incoming webhooks take — `text`, legacy
+//! `attachments` and the common Block Kit blocks — and rewrites Slack
+//! `mrkdwn` into Nexus Markdown, so CI and monitoring tools that only speak
+//! Slack can post without changes.
+
+use chrono::{TimeZone, Utc};
+use nexus_common::models::message::{Embed, EmbedAuthor, EmbedField, EmbedFooter, EmbedMedia};
+use serde::Deserialize;
+
+use super::{empty_embed, parse_hex_color, WebhookMessage, MAX_EMBEDS};
+
+/// Slack's named attachment colours.
+const COLOR_GOOD: u32 = 0x2EB67D;
+const COLOR_WARNING: u32 = 0xECB22E;
+const COLOR_DANGER: u32 = 0xE01E5A;
+
+#[derive(Debug, Deserialize)]
+pub struct SlackPayload {
+    #[serde(default)]
+    pub text: Option<String>,
+    pub username: Option<String>,
+    pub icon_url: Option<String>,
+    /// Whether `text` is mrkdwn (Slack's default) or plain text.
+    #[serde(default = "default_true")]
+    pub mrkdwn: bool,
+    #[serde(default)]
+    pub attachments: Vec<SlackAttachment>,
+    #[serde(default)]
+    pub blocks: Vec<serde_json::Value>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct SlackAttachment {
+    pub fallback: Option<String>,
+    pub color: Option<String>,
+    pub pretext: Option<String>,
+    pub author_name: Option<String>,
+    pub author_link: Option<String>,
+    pub author_icon: Option<String>,
+    pub title: Option<String>,
+    pub title_link: Option<String>,
+    pub text: Option<String>,
+    #[serde(default)]
+    pub fields: Vec<SlackField>,
+    pub image_url: Option<String>,
+    pub thumb_url: Option<String>,
+    pub footer: Option<String>,
+    pub footer_icon: Option<String>,
+    /// Unix timestamp — Slack accepts both numbers and numeric strings.
+    pub ts: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SlackField {
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub value: String,
+    #[serde(default)]
+    pub short: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Parse a request body. Legacy integrations post `payload=<json>` as
+/// `application/x-www-form-urlencoded`; everything else sends raw JSON.
+pub fn parse_body(body: &[u8], form_encoded: bool) -> Result<SlackPayload, String> {
+    if form_encoded {
+        let json = url::form_urlencoded::parse(body)
+            .find(|(k, _)| k == "payload")
+            .map(|(_, v)| v.into_owned())
+            .ok_or_else(|| "missing 'payload' form field".to_string())?;
+        serde_json::from_str(&json).map_err(|e| format!("invalid Slack payload: {e}"))
+    } else {
+        serde_json::from_slice(body).map_err(|e| format!("invalid Slack payload: {e}"))
+    }
+}
+
+/// Translate a Slack payload into a Nexus webhook message.
+pub fn translate(payload: SlackPayload) -> WebhookMessage {
+    let mut lines: Vec<String> = Vec::new();
+
+    // With blocks present Slack only uses `text` as the notification fallback.
+    if !payload.blocks.is_empty() {
+        lines.extend(payload.blocks.iter().filter_map(render_block));
+    } else if let Some(text) = payload.text.as_deref().filter(|t| !t.is_empty()) {
+        lines.push(if payload.mrkdwn { mrkdwn_to_markdown(text) } else { unescape(text) });
+    }
+
+    let mut embeds = Vec::new();
+    for attachment in payload.attachments.into_iter().take(MAX_EMBEDS) {
+        if let Some(pretext) = attachment.pretext.as_deref().filter(|p| !p.is_empty()) {
+            lines.push(mrkdwn_to_markdown(pretext));
+        }
+        embeds.push(attachment_to_embed(attachment));
+    }
+
+    WebhookMessage {
+        content: lines.join("\n"),
+        username: payload.username,
+        avatar_url: payload.icon_url,
+        embeds,
+    }
+}
+
+fn attachment_to_embed(a: SlackAttachment) -> Embed {
+    let mut embed = empty_embed();
+    embed.color = a.color.as_deref().and_then(|c| match c {
+        "good" => Some(COLOR_GOOD),
+        "warning" => Some(COLOR_WARNING),
+        "danger" => Some(COLOR_DANGER),
+        other => parse_hex_color(other),
+    });
+    embed.author = a.author_name.map(|name| EmbedAuthor {
+        name,
+        url: a.author_link,
+        icon_url: a.author_icon,
+    });
+    embed.title = a.title.map(|t| unescape(&t));
+    embed.url = a.title_link;
+    embed.description = a.text.as_deref().map(mrkdwn_to_markdown);
+    embed.fields = a
+        .fields
+        .into_iter()
+        .map(|f| EmbedField {
+            name: unescape(&f.title),
+            value: mrkdwn_to_markdown(&f.value),
+            inline: f.short,
+        })
+        .collect();
+    embed.image = a.image_url.map(media);
+    embed.thumbnail = a.thumb_url.map(media);
+    embed.footer = a.footer.map(|text| EmbedFooter {
+        text: unescape(&text),
+        icon_url: a.footer_icon,
+    });
+    embed.timestamp = a.ts.as_ref().and_then(parse_ts);
+
+    // An attachment with nothing renderable still carries its fallback text.
+    if embed.title.is_none() && embed.description.is_none() && embed.fields.is_empty() {
+        embed.description = a.fallback.map(|f| unescape(&f));
+    }
+    embed
+}
+
+fn media(url: String) -> EmbedMedia {
+    EmbedMedia { url, width: None, height: None }
+}
+
+fn parse_ts(ts: &serde_json::Value) -> Option<chrono::DateTime<Utc>> {
+    let secs = match ts {
+        serde_json::Value::Number(n) => n.as_f64()?,
+        serde_json::Value::String(s) => s.parse::<f64>().ok()?,
+        _ => return None,
+    };
+    Utc.timestamp_opt(secs as i64, 0).single()
+}
+
+/// Render the Block Kit blocks that map onto plain Markdown.
+fn render_block(block: &serde_json::Value) -> Option<String> {
+    let text_of = |v: &serde_json::Value| -> Option<String> {
+        let text = v.get("text")?.as_str()?;
+        Some(match v.get("type").and_then(|t| t.as_str()) {
+            Some("mrkdwn") => mrkdwn_to_markdown(text),
+            _ => unescape(text),
+        })
+    };
+
+    match block.get("type")?.as_str()? {
+        "header" => text_of(block.get("text")?).map(|t| format!("**{t}**")),
+        "section" => {
+            let mut parts: Vec<String> = block.get("text").and_then(text_of).into_iter().collect();
+            if let Some(fields) = block.get("fields").and_then(|f| f.as_array()) {
+                parts.extend(fields.iter().filter_map(text_of));
+            }
+            (!parts.is_empty()).then(|| parts.join("\n"))
+        }
+        "context" => {
+            let parts: Vec<String> = block
+                .get("elements")?
+                .as_array()?
+                .iter()
+                .filter_map(text_of)
+                .collect();
+            (!parts.is_empty()).then(|| parts.join(" · "))
+        }
+        "divider" => Some("---".into()),
+        _ => None,
+    }
+}
+
+// ============================================================================
+// mrkdwn → Markdown
+// ============================================================================
+
+/// Marker used to protect already-converted spans during emphasis rewriting.
+const HOLE: char = '\u{1}';
+
+/// Convert Slack `mrkdwn` to the Markdown dialect Nexus clients render.
+///
+/// - `*bold*` → `**bold**`, `_italic_` → `*italic*`, `~strike~` → `~~strike~~`
+/// - `<url|label>` → `[label](url)`, `<url>` → `url`
+/// - `<@U123|name>` → `@name`, `<#C123|name>` → `#name`
+/// - `<!here>` → `@here`, `<!channel>` / `<!everyone>` → `@everyone`
+/// - `&amp;` / `&lt;` / `&gt;` are unescaped
+///
+/// Code spans and fenced blocks are left untouched.
+pub fn mrkdwn_to_markdown(input: &str) -> String {
+    let mut protected: Vec<String> = Vec::new();
+    let mut out = String::with_capacity(input.len());
+    let mut rest = input;
+
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix("```") {
+            if let Some(end) = after.find("```") {
+                protected.push(unescape(&rest[..end + 6]));
+                out.push(HOLE);
+                rest = &after[end + 3..];
+                continue;
+            }
+        } else if let Some(after) = rest.strip_prefix('`') {
+            if let Some(end) = after.find('`') {
+                protected.push(unescape(&rest[..end + 2]));
+                out.push(HOLE);
+                rest = &after[end + 1..];
+                continue;
+            }
+        } else if let Some(after) = rest.strip_prefix('<') {
+            if let Some(end) = after.find('>') {
+                protected.push(convert_token(&after[..end]));
+                out.push(HOLE);
+                rest = &after[end + 1..];
+                continue;
+            }
+        }
+        let ch = rest.chars().next().unwrap_or_default();
+        out.push(ch);
+        rest = &rest[ch.len_utf8()..];
+    }
+
+    let out = convert_emphasis(&out, '*', "**");
+    let out = convert_emphasis(&out, '_', "*");
+    let out = convert_emphasis(&out, '~', "~~");
+    let out = unescape(&out);
+
+    let mut pieces = protected.into_iter();
+    out.chars()
+        .fold(String::with_capacity(out.len()), |mut acc, c| {
+            if c == HOLE {
+                acc.push_str(&pieces.next().unwrap_or_default());
+            } else {
+                acc.push(c);
+            }
+            acc
+        })
+}
+
+/// Convert the inside of a `<...>` token.
+fn convert_token(token: &str) -> String {
+    let (target, label) = match token.split_once('|') {
+        Some((t, l)) => (t, Some(unescape(l))),
+        None => (token, None),
+    };
+    match target.chars().next() {
+        Some('@') => format!("@{}", label.unwrap_or_else(|| target[1..].to_string())),
+        Some('#') => format!("#{}", label.unwrap_or_else(|| target[1..].to_string())),
+        Some('!') => match &target[1..] {
+            "here" => "@here".into(),
+            "channel" | "everyone" => "@everyone".into(),
+            _ => label.unwrap_or_default(),
+        },
+        _ => {
+            let url = unescape(target);
+            match label {
+                Some(label) if label != url => format!("[{label}]({url})"),
+                _ => url,
+            }
+        }
+    }
+}
+
+/// Rewrite `marker…marker` spans into `replacement…replacement` when the
+/// markers sit on word boundaries, mirroring how Slack decides emphasis.
+fn convert_emphasis(input: &str, marker: char, replacement: &str) -> String {
+    let chars: Vec<char> = input.chars().collect();
+    let mut out = String::with_capacity(input.len());
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let opens = c == marker
+            && (i == 0 || !chars[i - 1].is_alphanumeric())
+            && chars.get(i + 1).is_some_and(|n| !n.is_whitespace() && *n != marker);
+        if opens {
+            let close = (i + 2..chars.len()).find(|&j| {
+                chars[j] == marker
+                    && !chars[j - 1].is_whitespace()
+                    && chars.get(j + 1).is_none_or(|n| !n.is_alphanumeric())
+            });
+            let same_line = close.is_some_and(|j| !chars[i + 1..j].contains(&'\n'));
+            if let (Some(j), true) = (close, same_line) {
+                out.push_str(replacement);
+                out.extend(&chars[i + 1..j]);
+                out.push_str(replacement);
+                i = j + 1;
+                continue;
+            }
+        }
+        out.push(c);
+        i += 1;
+    }
+    out
+}
+
+/// Undo Slack's mandatory HTML-entity escaping.
+fn unescape(s: &str) -> String {
+    s.replace("&lt;", "<").replace("&gt;", ">").replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mrkdwn_emphasis() {
+        assert_eq!(
+            mrkdwn_to_markdown("*build* _passed_ ~flaky~"),
+            "**build** *passed* ~~flaky~~"
+        );
+        // Intra-word markers are not emphasis.
+        assert_eq!(mrkdwn_to_markdown("snake_case_name 2*3*4"), "snake_case_name 2*3*4");
+    }
+
+    #[test]
+    fn test_mrkdwn_links_and_mentions() {
+        assert_eq!(
+            mrkdwn_to_markdown("<https://ci.example/run/1|Run #1> by <@U123|alice> <!here>"),
+            "[Run #1](https://ci.example/run/1) by @alice @here"
+        );
+        assert_eq!(
+            mrkdwn_to_markdown("see <https://x.example/a_b_c>"),
+            "see https://x.example/a_b_c"
+        );
+    }
+
+    #[test]
+    fn test_mrkdwn_code_untouched_and_unescaped() {
+        assert_eq!(
+            mrkdwn_to_markdown("`*not bold*` &lt;tag&gt; &amp;"),
+            "`*not bold*` <tag> &"
+        );
+    }
+
+    #[test]
+    fn test_attachment_becomes_embed() {
+        let payload: SlackPayload = serde_json::from_value(serde_json::json!({
+            "text": "Deploy finished",
+            "username": "ci-bot",
+            "icon_url": "https://ci.example/icon.png",
+            "attachments": [{
+                "color": "danger",
+                "pretext": "Heads up",
+                "title": "Build #42",
+                "title_link": "https://ci.example/42",
+                "text": "*failed* on `main`",
+                "fields": [{ "title": "Branch", "value": "main", "short": true }],
+                "footer": "CI",
+                "ts": 1700000000
+            }]
+        }))
+        .unwrap();
+
+        let msg = translate(payload);
+        assert_eq!(msg.content, "Deploy finished\nHeads up");
+        assert_eq!(msg.username.as_deref(), Some("ci-bot"));
+        assert_eq!(msg.avatar_url.as_deref(), Some("https://ci.example/icon.png"));
+
+        let embed = &msg.embeds[0];
+        assert_eq!(embed.color, Some(COLOR_DANGER));
+        assert_eq!(embed.title.as_deref(), Some("Build #42"));
+        assert_eq!(embed.description.as_deref(), Some("**failed** on `main`"));
+        assert_eq!(embed.fields[0].name, "Branch");
+        assert!(embed.fields[0].inline);
+        assert_eq!(embed.timestamp.unwrap().timestamp(), 1_700_000_000);
+    }
+
+    #[test]
+    fn test_blocks_take_precedence_over_text() {
+        let payload: SlackPayload = serde_json::from_value(serde_json::json!({
+            "text": "fallback only",
+            "blocks": [
+                { "type": "header", "text": { "type": "plain_text", "text": "Alert" } },
+                { "type": "section", "text": { "type": "mrkdwn", "text": "CPU at *95%*" } }
+            ]
+        }))
+        .unwrap();
+        assert_eq!(translate(payload).content, "**Alert**\nCPU at **95%**");
+    }
+
+    #[test]
+    fn test_form_encoded_body() {
+        let body = b"payload=%7B%22text%22%3A%22hi%22%7D";
+        let payload = parse_body(body, true).unwrap();
+        assert_eq!(payload.text.as_deref(), Some("hi"));
+    }
+}
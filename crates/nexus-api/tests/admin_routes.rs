@@ -0,0 +1,65 @@
+//! Route tests for the staff-only admin API, on the in-memory harness
+//! (`nexus_api::test_support`).
+
+use axum::http::StatusCode;
+use nexus_api::test_support::TestApp;
+use serde_json::json;
+
+#[tokio::test]
+async fn admin_routes_are_staff_only() {
+    let app = TestApp::new().await;
+    let alice = app.user("alice").await;
+    let forbidden = app
+        .get("/api/v1/admin/stats")
+        .auth(&alice)
+        .send()
+        .await
+        .expect_error(StatusCode::FORBIDDEN);
+    assert_eq!(forbidden, "FORBIDDEN");
+    app.get("/api/v1/admin/stats").send().await.expect(StatusCode::UNAUTHORIZED);
+
+    let admin = app.staff("admin").await;
+    let stats = app
+        .get("/api/v1/admin/stats")
+        .auth(&admin)
+        .send()
+        .await
+        .expect(StatusCode::OK);
+    assert_eq!(stats["users"], 2);
+    assert_eq!(stats["messages"], 0);
+}
+
+#[tokio::test]
+async fn deactivated_users_lose_their_sessions() {
+    let app = TestApp::new().await;
+    let admin = app.staff("admin").await;
+    let bob = app.user("bob").await;
+
+    let deactivated = app
+        .post(&format!("/api/v1/admin/users/{}/deactivate", bob.id))
+        .auth(&admin)
+        .send()
+        .await
+        .expect(StatusCode::OK);
+    assert_eq!(deactivated["deactivated"], true);
+    app.post("/api/v1/auth/refresh")
+        .json(&json!({ "refresh_token": bob.refresh_token }))
+        .send()
+        .await
+        .expect(StatusCode::UNAUTHORIZED);
+
+    app.post(&format!("/api/v1/admin/users/{}/deactivate", admin.id))
+        .auth(&admin)
+        .send()
+        .await
+        .expect(StatusCode::BAD_REQUEST);
+
+    let found = app
+        .get("/api/v1/admin/users?query=bo")
+        .auth(&admin)
+        .send()
+        .await
+        .expect(StatusCode::OK);
+    assert_eq!(found.as_array().unwrap().len(), 1);
+    assert_eq!(found[0]["username"], "bob");
+}
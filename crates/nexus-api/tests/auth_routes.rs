@@ -0,0 +1,191 @@
+//! Route tests for registration, login and token refresh, on the in-memory
+//! harness (`nexus_api::test_support`).
+
+use axum::http::StatusCode;
+use nexus_api::test_support::{TestApp, TEST_PASSWORD};
+use serde_json::json;
+
+#[tokio::test]
+async fn registering_returns_working_tokens() {
+    let app = TestApp::new().await;
+    let body = app
+        .post("/api/v1/auth/register")
+        .json(&json!({ "username": "alice", "password": "hunter2hunter2" }))
+        .send()
+        .await
+        .expect(StatusCode::OK);
+    assert_eq!(body["user"]["username"], "alice");
+
+    let token = body["access_token"].as_str().expect("access token");
+    let me = app
+        .get("/api/v1/users/@me")
+        .bearer(token)
+        .send()
+        .await
+        .expect(StatusCode::OK);
+    assert_eq!(me["id"], body["user"]["id"]);
+
+    let taken = app
+        .post("/api/v1/auth/register")
+        .json(&json!({ "username": "ALICE", "password": "hunter2hunter2" }))
+        .send()
+        .await
+        .expect_error(StatusCode::CONFLICT);
+    assert_eq!(taken, "ALREADY_EXISTS");
+}
+
+#[tokio::test]
+async fn routes_need_a_valid_token() {
+    let app = TestApp::new().await;
+    app.get("/api/v1/users/@me").send().await.expect(StatusCode::UNAUTHORIZED);
+    app.get("/api/v1/users/@me")
+        .bearer("not-a-jwt")
+        .send()
+        .await
+        .expect(StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn reused_refresh_token_revokes_the_session() {
+    let app = TestApp::new().await;
+    let alice = app.user("alice").await;
+
+    let rotated = app
+        .post("/api/v1/auth/refresh")
+        .json(&json!({ "refresh_token": alice.refresh_token }))
+        .send()
+        .await
+        .expect(StatusCode::OK);
+    let new_refresh = rotated["refresh_token"].as_str().unwrap().to_owned();
+    assert_ne!(new_refresh, alice.refresh_token);
+
+    // Replaying the old token kills the session, new token included.
+    let reused = app
+        .post("/api/v1/auth/refresh")
+        .json(&json!({ "refresh_token": alice.refresh_token }))
+        .send()
+        .await
+        .expect_error(StatusCode::UNAUTHORIZED);
+    assert_eq!(reused, "INVALID_TOKEN");
+    app.post("/api/v1/auth/refresh")
+        .json(&json!({ "refresh_token": new_refresh }))
+        .send()
+        .await
+        .expect(StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn revoked_sessions_lose_their_access_tokens() {
+    let app = TestApp::new().await;
+    let alice = app.user("alice").await;
+    app.get("/api/v1/users/@me").auth(&alice).send().await.expect(StatusCode::OK);
+
+    let login = app
+        .post("/api/v1/auth/login")
+        .json(&json!({ "username": "alice", "password": TEST_PASSWORD }))
+        .send()
+        .await
+        .expect(StatusCode::OK);
+    let other = login["access_token"].as_str().unwrap().to_owned();
+
+    // Signing out everywhere else ends the first session at once
+    app.delete("/api/v1/users/@me/sessions")
+        .bearer(&other)
+        .send()
+        .await
+        .expect(StatusCode::NO_CONTENT);
+    let revoked = app
+        .get("/api/v1/users/@me")
+        .auth(&alice)
+        .send()
+        .await
+        .expect_error(StatusCode::UNAUTHORIZED);
+    assert_eq!(revoked, "INVALID_TOKEN");
+    app.get("/api/v1/users/@me").bearer(&other).send().await.expect(StatusCode::OK);
+}
+
+#[tokio::test]
+async fn approval_mode_holds_new_accounts_until_approved() {
+    let app = TestApp::new().await;
+    let admin = app.staff("admin").await;
+    app.put("/api/v1/admin/registration")
+        .auth(&admin)
+        .json(&json!({ "mode": "approval" }))
+        .send()
+        .await
+        .expect(StatusCode::OK);
+
+    let applied = app
+        .post("/api/v1/auth/register")
+        .json(&json!({ "username": "bob", "password": TEST_PASSWORD, "reason": "friend of alice" }))
+        .send()
+        .await
+        .expect(StatusCode::ACCEPTED);
+    assert_eq!(applied["approval_pending"], true);
+    assert!(applied.get("access_token").is_none());
+
+    let login = json!({ "username": "bob", "password": TEST_PASSWORD });
+    let pending = app
+        .post("/api/v1/auth/login")
+        .json(&login)
+        .send()
+        .await
+        .expect_error(StatusCode::FORBIDDEN);
+    assert_eq!(pending, "APPROVAL_PENDING");
+
+    let queue = app
+        .get("/api/v1/admin/registration/applications")
+        .auth(&admin)
+        .send()
+        .await
+        .expect(StatusCode::OK);
+    assert_eq!(queue[0]["username"], "bob");
+    assert_eq!(queue[0]["reason"], "friend of alice");
+
+    let bob_id = applied["user"]["id"].as_str().unwrap();
+    app.post(&format!("/api/v1/admin/registration/applications/{bob_id}/approve"))
+        .auth(&admin)
+        .send()
+        .await
+        .expect(StatusCode::NO_CONTENT);
+    app.post("/api/v1/auth/login").json(&login).send().await.expect(StatusCode::OK);
+}
+
+#[tokio::test]
+async fn invite_only_mode_takes_single_use_tokens() {
+    let app = TestApp::new().await;
+    let admin = app.staff("admin").await;
+    app.put("/api/v1/admin/registration")
+        .auth(&admin)
+        .json(&json!({ "mode": "invite_only" }))
+        .send()
+        .await
+        .expect(StatusCode::OK);
+    let issued = app
+        .post("/api/v1/admin/registration/tokens")
+        .auth(&admin)
+        .json(&json!({ "note": "for carol" }))
+        .send()
+        .await
+        .expect(StatusCode::CREATED);
+    let token = issued["token"].as_str().unwrap();
+
+    let register = |username: &str, token: Option<&str>| {
+        json!({ "username": username, "password": TEST_PASSWORD, "registration_token": token })
+    };
+    app.post("/api/v1/auth/register")
+        .json(&register("carol", None))
+        .send()
+        .await
+        .expect(StatusCode::BAD_REQUEST);
+    app.post("/api/v1/auth/register")
+        .json(&register("carol", Some(token)))
+        .send()
+        .await
+        .expect(StatusCode::OK);
+    app.post("/api/v1/auth/register")
+        .json(&register("dave", Some(token)))
+        .send()
+        .await
+        .expect(StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,28 @@
+//! Blocking users, on the in-memory harness (`nexus_api::test_support`).
+
+use axum::http::StatusCode;
+use nexus_api::test_support::TestApp;
+
+#[tokio::test]
+async fn blocks_are_listed_and_removed() {
+    let app = TestApp::new().await;
+    let alice = app.user("alice").await;
+    let bob = app.user("bob").await;
+    let path = format!("/api/v1/users/@me/blocks/{}", bob.id);
+
+    app.put(&format!("/api/v1/users/@me/blocks/{}", alice.id))
+        .auth(&alice)
+        .send()
+        .await
+        .expect(StatusCode::BAD_REQUEST);
+    app.put(&path).auth(&alice).send().await.expect(StatusCode::NO_CONTENT);
+    // Blocking again is fine
+    app.put(&path).auth(&alice).send().await.expect(StatusCode::NO_CONTENT);
+
+    let blocks = app.get("/api/v1/users/@me/blocks").auth(&alice).send().await.expect(StatusCode::OK);
+    assert_eq!(blocks.as_array().unwrap().len(), 1);
+    assert_eq!(blocks[0]["user_id"], bob.id.to_string());
+
+    app.delete(&path).auth(&alice).send().await.expect(StatusCode::NO_CONTENT);
+    app.delete(&path).auth(&alice).send().await.expect(StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,103 @@
+//! Archiving channels, on the in-memory harness (`nexus_api::test_support`).
+
+use axum::http::StatusCode;
+use nexus_api::test_support::TestApp;
+use serde_json::json;
+
+#[tokio::test]
+async fn archived_channels_are_hidden_and_read_only() {
+    let app = TestApp::new().await;
+    let owner = app.user("alice").await;
+    let server = app
+        .post("/api/v1/servers")
+        .auth(&owner)
+        .json(&json!({ "name": "Archive" }))
+        .send()
+        .await
+        .expect(StatusCode::OK);
+    let channels_path = format!("/api/v1/servers/{}/channels", server["id"].as_str().unwrap());
+    let channel = app
+        .post(&channels_path)
+        .auth(&owner)
+        .json(&json!({ "name": "old-news", "channel_type": "text" }))
+        .send()
+        .await
+        .expect(StatusCode::OK);
+    let channel_id = channel["id"].as_str().unwrap();
+    let archive = format!("/api/v1/channels/{channel_id}/archive");
+    let messages = format!("/api/v1/channels/{channel_id}/messages");
+
+    let archived = app.put(&archive).auth(&owner).send().await.expect(StatusCode::OK);
+    assert_eq!(archived["archived"], true);
+
+    let listed = app.get(&channels_path).auth(&owner).send().await.expect(StatusCode::OK);
+    assert!(listed.as_array().unwrap().iter().all(|c| c["id"] != channel_id));
+    let listed = app
+        .get(&format!("{channels_path}?archived=true"))
+        .auth(&owner)
+        .send()
+        .await
+        .expect(StatusCode::OK);
+    assert!(listed.as_array().unwrap().iter().any(|c| c["id"] == channel_id));
+
+    app.post(&messages)
+        .auth(&owner)
+        .json(&json!({ "content": "anyone here?" }))
+        .send()
+        .await
+        .expect(StatusCode::BAD_REQUEST);
+
+    let unarchived = app.delete(&archive).auth(&owner).send().await.expect(StatusCode::OK);
+    assert_eq!(unarchived["archived"], false);
+    app.post(&messages)
+        .auth(&owner)
+        .json(&json!({ "content": "back again" }))
+        .send()
+        .await
+        .expect(StatusCode::OK);
+}
+
+#[tokio::test]
+async fn archived_channels_refuse_threads_and_pins() {
+    let app = TestApp::new().await;
+    let owner = app.user("alice").await;
+    let server = app
+        .post("/api/v1/servers")
+        .auth(&owner)
+        .json(&json!({ "name": "Archive" }))
+        .send()
+        .await
+        .expect(StatusCode::OK);
+    let channel = app
+        .post(&format!("/api/v1/servers/{}/channels", server["id"].as_str().unwrap()))
+        .auth(&owner)
+        .json(&json!({ "name": "old-news", "channel_type": "text" }))
+        .send()
+        .await
+        .expect(StatusCode::OK);
+    let channel_id = channel["id"].as_str().unwrap();
+    let message = app
+        .post(&format!("/api/v1/channels/{channel_id}/messages"))
+        .auth(&owner)
+        .json(&json!({ "content": "pin me" }))
+        .send()
+        .await
+        .expect(StatusCode::OK);
+    let pin = format!("/api/v1/channels/{channel_id}/pins/{}", message["id"].as_str().unwrap());
+    app.put(&pin).auth(&owner).send().await.expect(StatusCode::OK);
+
+    app.put(&format!("/api/v1/channels/{channel_id}/archive"))
+        .auth(&owner)
+        .send()
+        .await
+        .expect(StatusCode::OK);
+
+    app.post(&format!("/api/v1/channels/{channel_id}/threads"))
+        .auth(&owner)
+        .json(&json!({ "title": "follow-up", "message_id": message["id"] }))
+        .send()
+        .await
+        .expect(StatusCode::BAD_REQUEST);
+    app.delete(&pin).auth(&owner).send().await.expect(StatusCode::BAD_REQUEST);
+    app.put(&pin).auth(&owner).send().await.expect(StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,97 @@
+//! Data exports and account deletion, on the in-memory harness
+//! (`nexus_api::test_support`).
+
+use axum::http::{header, StatusCode};
+use nexus_api::test_support::{TestApp, TEST_PASSWORD};
+use serde_json::json;
+
+#[tokio::test]
+async fn exports_are_built_and_downloaded_once() {
+    let app = TestApp::new().await;
+    let alice = app.user("alice").await;
+
+    let export = app
+        .post("/api/v1/users/@me/export")
+        .auth(&alice)
+        .send()
+        .await
+        .expect(StatusCode::ACCEPTED);
+    assert_eq!(export["status"], "pending");
+    let path = format!("/api/v1/users/@me/export/{}", export["id"].as_str().unwrap());
+    // Asking again returns the export under way
+    let again = app.post("/api/v1/users/@me/export").auth(&alice).send().await.expect(StatusCode::OK);
+    assert_eq!(again["id"], export["id"]);
+
+    nexus_api::jobs::data_export::run_once(&app.state).await;
+    let done = app.get(&path).auth(&alice).send().await.expect(StatusCode::OK);
+    assert_eq!(done["status"], "completed");
+    assert_eq!(done["progress"], 100);
+    let url = done["download_url"].as_str().unwrap().to_owned();
+
+    // Other users can't see it, and the link can't be altered
+    let bob = app.user("bob").await;
+    app.get(&path).auth(&bob).send().await.expect(StatusCode::NOT_FOUND);
+    let forged = url.replace("signature=", "signature=00");
+    app.get(&forged).send().await.expect(StatusCode::FORBIDDEN);
+
+    let download = app.get(&url).send().await;
+    assert_eq!(download.status, StatusCode::OK);
+    assert_eq!(download.headers[header::CONTENT_TYPE], "application/zip");
+    app.get(&url).send().await.expect(StatusCode::NOT_FOUND);
+
+    // One export per cooldown
+    app.post("/api/v1/users/@me/export")
+        .auth(&alice)
+        .send()
+        .await
+        .expect(StatusCode::TOO_MANY_REQUESTS);
+}
+
+#[tokio::test]
+async fn deleted_accounts_become_tombstones() {
+    let app = TestApp::new().await;
+    let alice = app.user("alice").await;
+    let bob = app.user("bob").await;
+    let created = app
+        .post("/api/v1/applications")
+        .auth(&alice)
+        .json(&json!({ "name": "alice's bot" }))
+        .send()
+        .await
+        .expect(StatusCode::OK);
+    let application = format!("/api/v1/applications/{}", created[0]["id"].as_str().unwrap());
+    app.get(&application).auth(&bob).send().await.expect(StatusCode::FORBIDDEN);
+
+    app.delete("/api/v1/users/@me")
+        .auth(&alice)
+        .json(&json!({ "password": "not the password" }))
+        .send()
+        .await
+        .expect(StatusCode::UNAUTHORIZED);
+    app.delete("/api/v1/users/@me")
+        .auth(&alice)
+        .json(&json!({ "password": TEST_PASSWORD }))
+        .send()
+        .await
+        .expect(StatusCode::NO_CONTENT);
+
+    let profile = app
+        .get(&format!("/api/v1/users/{}", alice.id))
+        .auth(&bob)
+        .send()
+        .await
+        .expect(StatusCode::OK);
+    assert!(profile["username"].as_str().unwrap().starts_with("deleted-user-"));
+    app.post("/api/v1/auth/login")
+        .json(&json!({ "username": "alice", "password": TEST_PASSWORD }))
+        .send()
+        .await
+        .expect(StatusCode::UNAUTHORIZED);
+    app.post("/api/v1/auth/refresh")
+        .json(&json!({ "refresh_token": alice.refresh_token }))
+        .send()
+        .await
+        .expect(StatusCode::UNAUTHORIZED);
+    // Their bot applications, and with them the bot tokens, are gone
+    app.get(&application).auth(&bob).send().await.expect(StatusCode::NOT_FOUND);
+}
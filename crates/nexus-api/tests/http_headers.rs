@@ -0,0 +1,46 @@
+//! CORS and security headers, on the in-memory harness
+//! (`nexus_api::test_support`).
+
+use axum::http::{header, Method, StatusCode};
+use nexus_api::test_support::TestApp;
+
+#[tokio::test]
+async fn api_responses_carry_security_headers() {
+    let app = TestApp::new().await;
+    let response = app.get("/api/v1/auth/registration").send().await;
+    assert_eq!(response.status, StatusCode::OK);
+
+    let h = &response.headers;
+    assert_eq!(h[header::X_CONTENT_TYPE_OPTIONS], "nosniff");
+    assert_eq!(h[header::X_FRAME_OPTIONS], "DENY");
+    assert_eq!(h[header::STRICT_TRANSPORT_SECURITY], "max-age=63072000; includeSubDomains");
+    assert_eq!(h[header::CONTENT_SECURITY_POLICY], "default-src 'none'; frame-ancestors 'none'");
+}
+
+#[tokio::test]
+async fn uploaded_files_are_sandboxed() {
+    let app = TestApp::new().await;
+    let response = app.get("/files/attachments/missing.html").send().await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+    let csp = response.headers[header::CONTENT_SECURITY_POLICY].to_str().unwrap();
+    assert!(csp.contains("sandbox"), "{csp}");
+}
+
+#[tokio::test]
+async fn preflight_allows_any_origin_by_default() {
+    let app = TestApp::new().await;
+    let response = app
+        .request(Method::OPTIONS, "/api/v1/users/@me")
+        .header("origin", "https://client.example")
+        .header("access-control-request-method", "PATCH")
+        .header("access-control-request-headers", "authorization,content-type")
+        .send()
+        .await;
+    assert_eq!(response.status, StatusCode::OK);
+
+    let h = &response.headers;
+    assert_eq!(h[header::ACCESS_CONTROL_ALLOW_ORIGIN], "*");
+    assert!(h[header::ACCESS_CONTROL_ALLOW_METHODS].to_str().unwrap().contains("PATCH"));
+    assert_eq!(h[header::ACCESS_CONTROL_ALLOW_HEADERS], "authorization,content-type");
+    assert!(h.get(header::ACCESS_CONTROL_ALLOW_CREDENTIALS).is_none());
+}
@@ -0,0 +1,69 @@
+//! Upload validation and request body limits, on the in-memory harness
+//! (`nexus_api::test_support`).
+
+use axum::http::StatusCode;
+use nexus_api::test_support::{TestApp, TestUser};
+use serde_json::json;
+
+const BOUNDARY: &str = "nexus-test-boundary";
+
+/// A multipart form with one `file` field.
+fn form(filename: &str, content_type: &str, data: &[u8]) -> Vec<u8> {
+    let mut body = format!(
+        "--{BOUNDARY}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"{filename}\"\r\n\
+         Content-Type: {content_type}\r\n\r\n"
+    )
+    .into_bytes();
+    body.extend_from_slice(data);
+    body.extend_from_slice(format!("\r\n--{BOUNDARY}--\r\n").as_bytes());
+    body
+}
+
+async fn upload(app: &TestApp, user: &TestUser, filename: &str, content_type: &str, data: &[u8]) -> StatusCode {
+    app.post("/api/v1/attachments/upload")
+        .auth(user)
+        .body(&format!("multipart/form-data; boundary={BOUNDARY}"), form(filename, content_type, data))
+        .send()
+        .await
+        .status
+}
+
+#[tokio::test]
+async fn files_matching_their_type_are_stored() {
+    let app = TestApp::new().await;
+    let alice = app.user("alice").await;
+
+    let png = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR\0\0\0\x01\0\0\0\x01\x08\x06\0\0\0";
+    assert_eq!(upload(&app, &alice, "dot.png", "image/png", png).await, StatusCode::OK);
+    assert_eq!(upload(&app, &alice, "notes.md", "text/markdown", b"# Notes").await, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn mismatched_and_markup_files_are_refused() {
+    let app = TestApp::new().await;
+    let alice = app.user("alice").await;
+
+    let svg = b"<svg xmlns=\"http://www.w3.org/2000/svg\" onload=\"alert(1)\"/>";
+    assert_eq!(upload(&app, &alice, "x.svg", "image/svg+xml", svg).await, StatusCode::BAD_REQUEST);
+    assert_eq!(upload(&app, &alice, "x.txt", "text/plain", svg).await, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        upload(&app, &alice, "x.png", "image/png", b"<html><script>alert(1)</script>").await,
+        StatusCode::BAD_REQUEST
+    );
+    assert_eq!(upload(&app, &alice, "x.pdf", "application/pdf", b"MZ\x90\0").await, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn oversized_json_bodies_are_refused() {
+    let app = TestApp::new().await;
+    let alice = app.user("alice").await;
+
+    let limit = nexus_api::test_support::config().limits.max_request_body_bytes as usize;
+    let response = app
+        .patch("/api/v1/users/@me")
+        .auth(&alice)
+        .json(&json!({ "bio": "x".repeat(limit) }))
+        .send()
+        .await;
+    assert_eq!(response.status, StatusCode::PAYLOAD_TOO_LARGE);
+}
@@ -0,0 +1,83 @@
+//! Webhook execution, on the in-memory harness (`nexus_api::test_support`).
+
+use axum::http::StatusCode;
+use nexus_api::test_support::TestApp;
+use serde_json::json;
+
+const BOUNDARY: &str = "nexus-test-boundary";
+
+/// An incoming webhook's execution path, in a fresh server channel.
+async fn webhook(app: &TestApp) -> String {
+    let owner = app.user("alice").await;
+    let server = app
+        .post("/api/v1/servers")
+        .auth(&owner)
+        .json(&json!({ "name": "Hooks" }))
+        .send()
+        .await
+        .expect(StatusCode::OK);
+    let channel = app
+        .post(&format!("/api/v1/servers/{}/channels", server["id"].as_str().unwrap()))
+        .auth(&owner)
+        .json(&json!({ "name": "deploys", "channel_type": "text" }))
+        .send()
+        .await
+        .expect(StatusCode::OK);
+    let hook = app
+        .post(&format!("/api/v1/channels/{}/webhooks", channel["id"].as_str().unwrap()))
+        .auth(&owner)
+        .json(&json!({ "name": "CI" }))
+        .send()
+        .await
+        .expect(StatusCode::OK);
+    format!("/api/v1/webhooks/{}/{}", hook["id"].as_str().unwrap(), hook["token"].as_str().unwrap())
+}
+
+#[tokio::test]
+async fn wait_returns_the_created_message() {
+    let app = TestApp::new().await;
+    let path = webhook(&app).await;
+
+    app.post(&path)
+        .json(&json!({ "content": "queued" }))
+        .send()
+        .await
+        .expect(StatusCode::NO_CONTENT);
+    let message = app
+        .post(&format!("{path}?wait=true"))
+        .json(&json!({ "username": "Deploy bot", "embeds": [{ "title": "v1.2.0 released" }] }))
+        .send()
+        .await
+        .expect(StatusCode::OK);
+    assert_eq!(message["author_username"], "Deploy bot");
+    // Attributed to the system account, not the webhook's creator
+    assert_eq!(message["author_id"], "00000000-0000-0000-0000-000000000001");
+    assert_eq!(message["embeds"][0]["title"], "v1.2.0 released");
+
+    app.post(&path).json(&json!({})).send().await.expect(StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn multipart_executions_attach_files() {
+    let app = TestApp::new().await;
+    let path = webhook(&app).await;
+
+    let mut body = format!(
+        "--{BOUNDARY}\r\nContent-Disposition: form-data; name=\"payload_json\"\r\n\r\n\
+         {{\"content\":\"build log\"}}\r\n\
+         --{BOUNDARY}\r\nContent-Disposition: form-data; name=\"files[0]\"; filename=\"build.md\"\r\n\
+         Content-Type: text/markdown\r\n\r\n"
+    )
+    .into_bytes();
+    body.extend_from_slice(b"# All green");
+    body.extend_from_slice(format!("\r\n--{BOUNDARY}--\r\n").as_bytes());
+
+    let message = app
+        .post(&format!("{path}?wait=true"))
+        .body(&format!("multipart/form-data; boundary={BOUNDARY}"), body)
+        .send()
+        .await
+        .expect(StatusCode::OK);
+    assert_eq!(message["content"], "build log");
+    assert_eq!(message["attachments"][0]["filename"], "build.md");
+}
@@ -0,0 +1,257 @@
+//! # Nexus Bench
+//!
+//! Load-testing harness for the gateway fan-out path. Connects many
+//! simulated users to the gateway, has some of them send messages into one
+//! channel at a fixed rate through the REST API, and reports how long each
+//! `MESSAGE_CREATE` took to reach every connected client and how many never
+//! arrived.
+//!
+//! The target needs a public server with a text channel; the simulated
+//! users join it. Run against a disposable instance:
+//!
+//! ```text
+//! nexus-bench --server-id <uuid> --channel-id <uuid> --register \
+//!     --clients 2000 --senders 20 --rate 2 --duration 120
+//! ```
+//!
+//! Latency is measured from just before the REST send to the dispatch, so
+//! it includes the API's write path, not only fan-out.
+
+mod stats;
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Context;
+use clap::Parser;
+use futures_util::stream::{self, StreamExt};
+use nexus_client::models::user::CreateUserRequest;
+use nexus_client::{ClientError, Event, GatewayClient, RestClient};
+use tokio::time::{timeout, Instant};
+use uuid::Uuid;
+
+use crate::stats::Recorder;
+
+/// Prefix of benchmark message content; the rest is the message's id.
+const CONTENT_PREFIX: &str = "nexus-bench ";
+
+#[derive(Parser)]
+#[command(
+    name = "nexus-bench",
+    about = "Load-test Nexus gateway fan-out and message throughput",
+    version = env!("CARGO_PKG_VERSION"),
+)]
+struct Cli {
+    /// REST API base URL.
+    #[arg(long, env = "NEXUS_API_URL", default_value = "http://localhost:3000/api/v1")]
+    api_url: String,
+
+    /// WebSocket gateway URL.
+    #[arg(long, env = "NEXUS_GATEWAY_URL", default_value = "ws://localhost:3001/gateway")]
+    gateway_url: String,
+
+    /// Public server the simulated users join.
+    #[arg(long)]
+    server_id: Uuid,
+
+    /// Text channel in that server to send into.
+    #[arg(long)]
+    channel_id: Uuid,
+
+    /// Gateway clients to connect.
+    #[arg(long, default_value_t = 100)]
+    clients: usize,
+
+    /// How many of the clients also send messages.
+    #[arg(long, default_value_t = 10)]
+    senders: usize,
+
+    /// Messages per second per sender.
+    #[arg(long, default_value_t = 1.0)]
+    rate: f64,
+
+    /// How long to send for, in seconds.
+    #[arg(long, default_value_t = 60)]
+    duration: u64,
+
+    /// How long to wait for outstanding dispatches after sending stops.
+    #[arg(long, default_value_t = 5)]
+    grace: u64,
+
+    /// Usernames are `<prefix><n>`.
+    #[arg(long, default_value = "bench")]
+    user_prefix: String,
+
+    #[arg(long, env = "NEXUS_BENCH_PASSWORD", default_value = "bench-password", hide_env_values = true)]
+    password: String,
+
+    /// Create the users if they don't exist yet.
+    #[arg(long)]
+    register: bool,
+
+    /// Users set up at once while connecting.
+    #[arg(long, default_value_t = 50)]
+    concurrency: usize,
+}
+
+struct Client {
+    rest: RestClient,
+    gateway: GatewayClient,
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            tracing_subscriber::EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| "warn".into()),
+        )
+        .init();
+
+    let cli = Arc::new(Cli::parse());
+    anyhow::ensure!(cli.senders <= cli.clients, "--senders can't exceed --clients");
+    anyhow::ensure!(cli.rate > 0.0, "--rate must be positive");
+    let recorder = Arc::new(Recorder::default());
+
+    println!("Connecting {} clients…", cli.clients);
+    let started = Instant::now();
+    let results: Vec<_> = stream::iter(0..cli.clients)
+        .map(|n| {
+            let cli = Arc::clone(&cli);
+            let recorder = Arc::clone(&recorder);
+            async move { (n, connect(&cli, n, recorder).await) }
+        })
+        .buffer_unordered(cli.concurrency.max(1))
+        .collect()
+        .await;
+    let mut clients = Vec::new();
+    for (n, result) in results {
+        match result {
+            Ok(client) => clients.push(client),
+            Err(e) => tracing::warn!(client = n, "Setup failed: {e:#}"),
+        }
+    }
+    println!(
+        "{} of {} clients ready in {:.1}s",
+        clients.len(),
+        cli.clients,
+        started.elapsed().as_secs_f64()
+    );
+    anyhow::ensure!(!clients.is_empty(), "No clients connected");
+
+    println!(
+        "Sending {:.1} msg/s from {} senders for {}s…",
+        cli.rate * cli.senders as f64,
+        cli.senders.min(clients.len()),
+        cli.duration
+    );
+    let next_id = Arc::new(AtomicU64::new(0));
+    let deadline = Instant::now() + Duration::from_secs(cli.duration);
+    let senders: Vec<_> = clients
+        .iter()
+        .take(cli.senders)
+        .map(|client| {
+            let rest = client.rest.clone();
+            let recorder = Arc::clone(&recorder);
+            let next_id = Arc::clone(&next_id);
+            let channel_id = cli.channel_id;
+            let period = Duration::from_secs_f64(1.0 / cli.rate);
+            tokio::spawn(async move {
+                let mut ticks = tokio::time::interval(period);
+                while Instant::now() < deadline {
+                    ticks.tick().await;
+                    let id = next_id.fetch_add(1, Ordering::Relaxed);
+                    recorder.sending(id);
+                    if let Err(e) = rest.say(channel_id, &format!("{CONTENT_PREFIX}{id}")).await {
+                        tracing::debug!("Send failed: {e}");
+                        recorder.send_failed(id);
+                    }
+                }
+            })
+        })
+        .collect();
+    for sender in senders {
+        sender.await?;
+    }
+    tokio::time::sleep(Duration::from_secs(cli.grace)).await;
+
+    println!("\n{}", recorder.report(clients.len()));
+    for client in clients {
+        client.gateway.close();
+    }
+    Ok(())
+}
+
+/// Log in (or register) user `n`, join the server, and connect to the
+/// gateway, recording benchmark messages it receives.
+async fn connect(cli: &Cli, n: usize, recorder: Arc<Recorder>) -> anyhow::Result<Client> {
+    let username = format!("{}{n}", cli.user_prefix);
+    let rest = RestClient::new(Some(&cli.api_url))?;
+    if cli.register {
+        let request = CreateUserRequest {
+            username: username.clone(),
+            password: cli.password.clone(),
+            email: None,
+            invite_code: None,
+            registration_token: None,
+            reason: None,
+            challenge: None,
+            challenge_response: None,
+        };
+        match rest.register(&request).await {
+            Ok(_) => {}
+            Err(ClientError::Api { status: 409, .. }) => {
+                rest.login(&username, &cli.password).await?;
+            }
+            Err(e) => return Err(e).context("register"),
+        }
+    } else {
+        rest.login(&username, &cli.password).await.context("login")?;
+    }
+    match rest.join_server(cli.server_id).await {
+        Ok(_) | Err(ClientError::Api { status: 409, .. }) => {}
+        Err(e) => return Err(e).context("join server"),
+    }
+
+    let token = rest.access_token().context("no access token")?;
+    let gateway = GatewayClient::connect(Some(&cli.gateway_url), &token, 0);
+    let mut events = gateway.subscribe();
+    timeout(Duration::from_secs(30), async {
+        loop {
+            match events.recv().await {
+                Ok(Event::Ready { .. }) => return Ok(()),
+                Ok(_) => continue,
+                Err(e) => return Err(e),
+            }
+        }
+    })
+    .await
+    .context("timed out waiting for Ready")?
+    .context("gateway closed before Ready")?;
+
+    let channel_id = cli.channel_id;
+    tokio::spawn(async move {
+        loop {
+            match events.recv().await {
+                Ok(Event::MessageCreate(message)) if message.channel_id == channel_id => {
+                    if let Some(id) = message
+                        .content
+                        .strip_prefix(CONTENT_PREFIX)
+                        .and_then(|id| id.parse().ok())
+                    {
+                        recorder.received(id);
+                    }
+                }
+                Ok(_) => {}
+                // A slow consumer; the missed dispatches count as dropped.
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
+                    tracing::debug!(skipped, "Listener lagged");
+                }
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => return,
+            }
+        }
+    });
+
+    Ok(Client { rest, gateway })
+}
@@ -0,0 +1,133 @@
+//! Latency and delivery accounting shared by all simulated clients.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Send times of in-flight messages and the latencies clients observed.
+#[derive(Default)]
+pub struct Recorder {
+    sent: Mutex<HashMap<u64, Instant>>,
+    /// Dispatch latencies in microseconds.
+    latencies: Mutex<Vec<u64>>,
+    send_errors: Mutex<u64>,
+}
+
+impl Recorder {
+    /// Note that message `id` is about to be sent.
+    pub fn sending(&self, id: u64) {
+        self.sent.lock().unwrap().insert(id, Instant::now());
+    }
+
+    /// The send of message `id` failed; it won't be delivered.
+    pub fn send_failed(&self, id: u64) {
+        self.sent.lock().unwrap().remove(&id);
+        *self.send_errors.lock().unwrap() += 1;
+    }
+
+    /// A client received message `id` over the gateway.
+    pub fn received(&self, id: u64) {
+        let Some(sent_at) = self.sent.lock().unwrap().get(&id).copied() else { return };
+        let micros = sent_at.elapsed().as_micros() as u64;
+        self.latencies.lock().unwrap().push(micros);
+    }
+
+    pub fn report(&self, listeners: usize) -> Report {
+        let mut latencies = self.latencies.lock().unwrap().clone();
+        latencies.sort_unstable();
+        let sent = self.sent.lock().unwrap().len() as u64;
+        Report {
+            sent,
+            send_errors: *self.send_errors.lock().unwrap(),
+            expected: sent * listeners as u64,
+            received: latencies.len() as u64,
+            p50: percentile(&latencies, 50.0),
+            p90: percentile(&latencies, 90.0),
+            p99: percentile(&latencies, 99.0),
+            max: latencies.last().copied().map(Duration::from_micros),
+        }
+    }
+}
+
+/// Results of a run.
+#[derive(Debug)]
+pub struct Report {
+    /// Messages the API accepted.
+    pub sent: u64,
+    pub send_errors: u64,
+    /// Dispatches there should have been: each sent message to each listener.
+    pub expected: u64,
+    pub received: u64,
+    pub p50: Option<Duration>,
+    pub p90: Option<Duration>,
+    pub p99: Option<Duration>,
+    pub max: Option<Duration>,
+}
+
+impl Report {
+    /// Share of expected dispatches that never arrived.
+    pub fn drop_rate(&self) -> f64 {
+        if self.expected == 0 {
+            return 0.0;
+        }
+        1.0 - (self.received.min(self.expected) as f64 / self.expected as f64)
+    }
+}
+
+impl std::fmt::Display for Report {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let ms = |d: Option<Duration>| {
+            d.map_or("-".to_owned(), |d| format!("{:.1} ms", d.as_secs_f64() * 1000.0))
+        };
+        writeln!(f, "messages sent:       {} ({} failed)", self.sent, self.send_errors)?;
+        writeln!(f, "dispatches received: {} of {}", self.received, self.expected)?;
+        writeln!(f, "drop rate:           {:.3}%", self.drop_rate() * 100.0)?;
+        writeln!(f, "latency p50:         {}", ms(self.p50))?;
+        writeln!(f, "latency p90:         {}", ms(self.p90))?;
+        writeln!(f, "latency p99:         {}", ms(self.p99))?;
+        write!(f, "latency max:         {}", ms(self.max))
+    }
+}
+
+/// Nearest-rank percentile of sorted microsecond samples.
+fn percentile(sorted: &[u64], p: f64) -> Option<Duration> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let rank = ((p / 100.0) * sorted.len() as f64).ceil() as usize;
+    let index = rank.clamp(1, sorted.len()) - 1;
+    Some(Duration::from_micros(sorted[index]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nearest_rank_percentiles() {
+        let samples: Vec<u64> = (1..=100).collect();
+        assert_eq!(percentile(&samples, 50.0), Some(Duration::from_micros(50)));
+        assert_eq!(percentile(&samples, 99.0), Some(Duration::from_micros(99)));
+        assert_eq!(percentile(&samples, 100.0), Some(Duration::from_micros(100)));
+        assert_eq!(percentile(&[7], 1.0), Some(Duration::from_micros(7)));
+        assert_eq!(percentile(&[], 50.0), None);
+    }
+
+    #[test]
+    fn drop_rate_counts_missing_dispatches() {
+        let recorder = Recorder::default();
+        for id in 0..4 {
+            recorder.sending(id);
+        }
+        recorder.send_failed(3);
+        for id in 0..3 {
+            recorder.received(id);
+            recorder.received(id);
+        }
+        recorder.received(99);
+        let report = recorder.report(4);
+        assert_eq!((report.sent, report.send_errors), (3, 1));
+        assert_eq!((report.expected, report.received), (12, 6));
+        assert!((report.drop_rate() - 0.5).abs() < f64::EPSILON);
+    }
+}
@@ -20,12 +20,17 @@ use uuid::Uuid;
 
 use crate::models::{
     channel::{Channel, ChannelType},
-    crypto::{Device, DeviceType, DeviceVerification, E2eeChannel, E2eeSession, EncryptedMessage, OneTimePreKey, VerificationMethod},
+    crypto::{
+        Device, DeviceType, DeviceVerification, E2eeChannel, E2eeSession, EncryptedAttachment,
+        EncryptedMessage, KeyBackupSession, KeyBackupVersion, OneTimePreKey, VerificationMethod,
+    },
     member::Member,
-    rich::{AttachmentRow, ServerEmojiRow, ThreadRow},
+    push::{PushPlatform, PushPreferences, PushToken},
+    rich::{AttachmentRow, ForumTag, ServerEmojiRow, ThreadRow},
     role::Role,
     server::{Invite, Server},
     user::{User, UserPresence},
+    user_settings::UserSettings,
 };
 
 // ── Internal helpers ──────────────────────────────────────────────────────────
@@ -138,6 +143,7 @@ impl<'r> sqlx::FromRow<'r, AnyRow> for User {
                 _ => Some(UserPresence::Offline),
             })?,
             flags: row.try_get("flags")?,
+            locale: row.try_get("locale")?,
             created_at: dt(row, "created_at")?,
             updated_at: dt(row, "updated_at")?,
         })
@@ -162,6 +168,9 @@ impl<'r> sqlx::FromRow<'r, AnyRow> for Server {
             vanity_code: row.try_get("vanity_code")?,
             member_count: row.try_get("member_count")?,
             max_file_size: row.try_get("max_file_size")?,
+            message_history: row.try_get("message_history")?,
+            preferred_locale: row.try_get("preferred_locale")?,
+            message_retention_days: row.try_get("message_retention_days")?,
             created_at: dt(row, "created_at")?,
             updated_at: dt(row, "updated_at")?,
         })
@@ -201,6 +210,7 @@ impl<'r> sqlx::FromRow<'r, AnyRow> for Channel {
             auto_archive_duration: row.try_get("auto_archive_duration")?,
             archived: row.try_get("archived")?,
             locked: row.try_get("locked")?,
+            voice_channel_id: opt_uuid(row, "voice_channel_id")?,
             created_at: dt(row, "created_at")?,
             updated_at: dt(row, "updated_at")?,
         })
@@ -221,6 +231,7 @@ impl<'r> sqlx::FromRow<'r, AnyRow> for Member {
             deafened: row.try_get("deafened")?,
             joined_at: dt(row, "joined_at")?,
             communication_disabled_until: opt_dt(row, "communication_disabled_until")?,
+            pending: row.try_get("pending")?,
         })
     }
 }
@@ -318,6 +329,7 @@ impl<'r> sqlx::FromRow<'r, AnyRow> for ThreadRow {
             archived: row.try_get("archived")?,
             archived_at: opt_dt(row, "archived_at")?,
             locked: row.try_get("locked")?,
+            pinned: row.try_get("pinned")?,
             tags: str_vec(row, "tags")?,
             created_at: dt(row, "created_at")?,
             updated_at: dt(row, "updated_at")?,
@@ -326,6 +338,22 @@ impl<'r> sqlx::FromRow<'r, AnyRow> for ThreadRow {
     }
 }
 
+// ── ForumTag ──────────────────────────────────────────────────────────────────
+
+impl<'r> sqlx::FromRow<'r, AnyRow> for ForumTag {
+    fn from_row(row: &'r AnyRow) -> Result<Self, sqlx::Error> {
+        Ok(ForumTag {
+            id: uuid(row, "id")?,
+            channel_id: uuid(row, "channel_id")?,
+            name: row.try_get("name")?,
+            emoji: row.try_get("emoji")?,
+            moderated: row.try_get("moderated")?,
+            position: row.try_get("position")?,
+            created_at: dt(row, "created_at")?,
+        })
+    }
+}
+
 // ── ServerEmojiRow ────────────────────────────────────────────────────────────
 
 impl<'r> sqlx::FromRow<'r, AnyRow> for ServerEmojiRow {
@@ -367,6 +395,8 @@ impl<'r> sqlx::FromRow<'r, AnyRow> for AttachmentRow {
             blurhash: row.try_get("blurhash")?,
             sha256: row.try_get("sha256")?,
             status: row.try_get("status")?,
+            thumbnail_key: row.try_get("thumbnail_key")?,
+            thumbnail_url: row.try_get("thumbnail_url")?,
             created_at: dt(row, "created_at")?,
             updated_at: dt(row, "updated_at")?,
         })
@@ -406,6 +436,52 @@ impl<'r> sqlx::FromRow<'r, AnyRow> for E2eeChannel {
     }
 }
 
+// ── EncryptedAttachment ───────────────────────────────────────────────────────
+
+impl<'r> sqlx::FromRow<'r, AnyRow> for EncryptedAttachment {
+    fn from_row(row: &'r AnyRow) -> Result<Self, sqlx::Error> {
+        Ok(EncryptedAttachment {
+            id: uuid(row, "id")?,
+            channel_id: uuid(row, "channel_id")?,
+            uploader_id: uuid(row, "uploader_id")?,
+            message_id: opt_uuid(row, "message_id")?,
+            storage_key: row.try_get("storage_key")?,
+            size: row.try_get("size")?,
+            sha256: row.try_get("sha256")?,
+            created_at: dt(row, "created_at")?,
+        })
+    }
+}
+
+// ── Key backup ────────────────────────────────────────────────────────────────
+
+/// Expects a computed `count` column alongside the version row.
+impl<'r> sqlx::FromRow<'r, AnyRow> for KeyBackupVersion {
+    fn from_row(row: &'r AnyRow) -> Result<Self, sqlx::Error> {
+        Ok(KeyBackupVersion {
+            version: row.try_get("version")?,
+            algorithm: row.try_get("algorithm")?,
+            auth_data: json(row, "auth_data")?,
+            etag: row.try_get("etag")?,
+            count: row.try_get("count")?,
+            created_at: dt(row, "created_at")?,
+        })
+    }
+}
+
+impl<'r> sqlx::FromRow<'r, AnyRow> for KeyBackupSession {
+    fn from_row(row: &'r AnyRow) -> Result<Self, sqlx::Error> {
+        Ok(KeyBackupSession {
+            channel_id: uuid(row, "channel_id")?,
+            session_id: row.try_get("session_id")?,
+            first_message_index: row.try_get("first_message_index")?,
+            forwarded_count: row.try_get("forwarded_count")?,
+            is_verified: row.try_get("is_verified")?,
+            session_data: json(row, "session_data")?,
+        })
+    }
+}
+
 // ── EncryptedMessage ──────────────────────────────────────────────────────────
 
 impl<'r> sqlx::FromRow<'r, AnyRow> for EncryptedMessage {
@@ -448,3 +524,51 @@ impl<'r> sqlx::FromRow<'r, AnyRow> for Invite {
         })
     }
 }
+
+// ── PushToken ─────────────────────────────────────────────────────────────────
+
+impl<'r> sqlx::FromRow<'r, AnyRow> for PushToken {
+    fn from_row(row: &'r AnyRow) -> Result<Self, sqlx::Error> {
+        Ok(PushToken {
+            id: uuid(row, "id")?,
+            user_id: uuid(row, "user_id")?,
+            platform: parse_enum(row, "platform", PushPlatform::parse)?,
+            token: row.try_get("token")?,
+            p256dh: row.try_get("p256dh")?,
+            auth: row.try_get("auth")?,
+            device_name: row.try_get("device_name")?,
+            created_at: dt(row, "created_at")?,
+            last_used_at: opt_dt(row, "last_used_at")?,
+        })
+    }
+}
+
+// ── PushPreferences ───────────────────────────────────────────────────────────
+
+impl<'r> sqlx::FromRow<'r, AnyRow> for PushPreferences {
+    fn from_row(row: &'r AnyRow) -> Result<Self, sqlx::Error> {
+        Ok(PushPreferences {
+            enabled: row.try_get("enabled")?,
+            direct_messages: row.try_get("direct_messages")?,
+            mentions: row.try_get("mentions")?,
+            show_content: row.try_get("show_content")?,
+        })
+    }
+}
+
+// ── UserSettings ──────────────────────────────────────────────────────────────
+
+impl<'r> sqlx::FromRow<'r, AnyRow> for UserSettings {
+    fn from_row(row: &'r AnyRow) -> Result<Self, sqlx::Error> {
+        let layout = json(row, "server_layout")?;
+        let volumes = json(row, "user_volumes")?;
+        Ok(UserSettings {
+            server_layout: serde_json::from_value(layout)
+                .map_err(|e| sqlx::Error::Decode(Box::new(e) as _))?,
+            user_volumes: serde_json::from_value(volumes)
+                .map_err(|e| sqlx::Error::Decode(Box::new(e) as _))?,
+            version: row.try_get("version")?,
+            updated_at: Some(dt(row, "updated_at")?),
+        })
+    }
+}
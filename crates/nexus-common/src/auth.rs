@@ -1,13 +1,16 @@
 //! Shared JWT authentication utilities.
 //!
 //! Claims and token validation live here so both nexus-api and nexus-gateway
-//! can use them without circular dependencies. Password hashing and token
-//! generation stay in nexus-api since they're API-specific.
+//! can use them without circular dependencies, as does bot token hashing.
+//! [`TokenPair`] is shared with clients.
+//! Password hashing and token generation stay in nexus-api since they're
+//! API-specific.
 
 use jsonwebtoken::{decode, DecodingKey, Validation};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 
-/// JWT claims embedded in access and refresh tokens.
+/// JWT claims embedded in access tokens.
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Claims {
     /// Subject (user ID as string)
@@ -20,6 +23,20 @@ pub struct Claims {
     pub exp: i64,
     /// Token type ("access" or "refresh")
     pub token_type: String,
+    /// Login session the token was issued for. Refresh tokens are opaque
+    /// and tied to the session server-side; tokens minted before sessions
+    /// existed have none.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sid: Option<String>,
+}
+
+/// Token pair returned on login/register and refresh.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenPair {
+    pub access_token: String,
+    pub refresh_token: String,
+    pub expires_in: u64,
+    pub token_type: String,
 }
 
 /// Validate and decode a JWT token.
@@ -31,3 +48,9 @@ pub fn validate_token(token: &str, secret: &str) -> Result<Claims, jsonwebtoken:
     )?;
     Ok(token_data.claims)
 }
+
+/// Hash a bot token for storage and lookup (SHA-256, hex). Bot tokens are
+/// long random strings, so a fast unsalted hash is enough.
+pub fn hash_bot_token(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
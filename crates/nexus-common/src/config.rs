@@ -23,18 +23,63 @@ pub fn init() -> Result<&'static AppConfig, config::ConfigError> {
     // Load .env file if present (development)
     let _ = dotenvy::dotenv();
 
-    let cfg = config::Config::builder()
-        // Defaults
+    let cfg = defaults()?
+        // Optional config file
+        .add_source(config::File::with_name("config").required(false))
+        // Environment variables (NEXUS_SERVER__HOST, NEXUS_DATABASE__URL, etc.)
+        .add_source(
+            config::Environment::with_prefix("NEXUS")
+                .separator("__")
+                .try_parsing(true),
+        )
+        .build()?;
+
+    let app_config: AppConfig = cfg.try_deserialize()?;
+    Ok(CONFIG.get_or_init(|| app_config))
+}
+
+/// Initialize the global configuration from the defaults and `overrides`
+/// (`("auth.jwt_secret", "…")` pairs) alone — no config file, no
+/// environment. For tests, which need the same configuration wherever they
+/// run. Like [`init`], only the first call takes effect.
+pub fn init_with(overrides: &[(&str, &str)]) -> Result<&'static AppConfig, config::ConfigError> {
+    let mut builder = defaults()?;
+    for (key, value) in overrides {
+        builder = builder.set_override(*key, *value)?;
+    }
+    let app_config: AppConfig = builder.build()?.try_deserialize()?;
+    Ok(CONFIG.get_or_init(|| app_config))
+}
+
+type Builder = config::ConfigBuilder<config::builder::DefaultState>;
+
+fn defaults() -> Result<Builder, config::ConfigError> {
+    config::Config::builder()
         .set_default("server.host", "0.0.0.0")?
+        .set_default("server.gateway_host", "")?
+        .set_default("server.voice_host", "")?
         .set_default("server.port", 8080)?
         .set_default("server.gateway_port", 8081)?
         .set_default("server.voice_port", 8082)?
         .set_default("server.federation_port", 8448)?
+        .set_default("server.shutdown_timeout_secs", 30)?
         .set_default("server.name", "localhost")?
+        .set_default("http.cors_origins", "*")?
+        .set_default("http.cors_allow_credentials", false)?
+        .set_default("http.cors_max_age_secs", 3600)? // 1 hour
+        .set_default("http.hsts_max_age_secs", 63_072_000)? // 2 years
+        .set_default("http.trusted_proxies", "127.0.0.0/8,::1")?
         .set_default("database.max_connections", 20)?
         .set_default("database.min_connections", 5)?
         .set_default("auth.access_token_ttl_secs", 900)? // 15 min
         .set_default("auth.refresh_token_ttl_secs", 2_592_000)? // 30 days
+        .set_default("registration.mode", "open")?
+        .set_default("registration.challenge", "none")?
+        .set_default("registration.pow_difficulty", 20)?
+        .set_default("registration.hcaptcha_site_key", "")?
+        .set_default("registration.hcaptcha_secret", "")?
+        .set_default("registration.ip_limit", 5)?
+        .set_default("registration.ip_window_secs", 3600)? // 1 hour
         .set_default("storage.endpoint", "")?
         .set_default("storage.bucket", "nexus")?
         .set_default("storage.access_key", "")?
@@ -50,32 +95,145 @@ pub fn init() -> Result<&'static AppConfig, config::ConfigError> {
         .set_default("limits.max_message_length", 4000)?
         .set_default("limits.max_file_size_bytes", 104_857_600)? // 100MB default
         .set_default("limits.max_attachment_count", 10)?
+        .set_default("limits.max_request_body_bytes", 1_048_576)? // 1MB
+        .set_default("limits.deleted_message_retention_days", 30)?
+        .set_default("spam.enabled", true)?
+        .set_default("spam.duplicate_threshold", 4)?
+        .set_default("spam.duplicate_window_secs", 15)?
+        .set_default("spam.link_channel_threshold", 3)?
+        .set_default("spam.link_window_secs", 60)?
+        .set_default("spam.invite_threshold", 3)?
+        .set_default("spam.invite_window_secs", 300)?
+        .set_default("spam.timeout_secs", 600)? // 10 min
+        .set_default("rate_limit.enabled", true)?
+        .set_default("rate_limit.global_limit", 50)?
+        .set_default("rate_limit.global_window_secs", 1)?
+        .set_default("rate_limit.route_limit", 30)?
+        .set_default("rate_limit.route_window_secs", 10)?
+        .set_default("rate_limit.message_limit", 5)?
+        .set_default("rate_limit.message_window_secs", 5)?
+        .set_default("rate_limit.gateway_limit", 120)?
+        .set_default("rate_limit.gateway_window_secs", 60)?
+        .set_default("rate_limit.typing_limit", 5)?
+        .set_default("rate_limit.typing_window_secs", 10)?
+        .set_default("rate_limit.presence_limit", 5)?
+        .set_default("rate_limit.presence_window_secs", 60)?
+        .set_default("rate_limit.request_members_limit", 10)?
+        .set_default("rate_limit.request_members_window_secs", 60)?
+        .set_default("rate_limit.live_share_limit", 30)?
+        .set_default("rate_limit.live_share_window_secs", 60)?
+        .set_default("gateway.event_bus_capacity", 10_000)?
+        .set_default("gateway.overflow", "spill")?
+        .set_default("transcription.provider", "none")?
+        .set_default("transcription.whisper_binary", "whisper-cli")?
+        .set_default("transcription.whisper_model", "./models/ggml-base.en.bin")?
+        .set_default("transcription.ffmpeg_binary", "ffmpeg")?
+        .set_default("transcription.api_url", "https://api.openai.com/v1/audio/transcriptions")?
+        .set_default("transcription.api_key", "")?
+        .set_default("transcription.api_model", "whisper-1")?
+        .set_default("transcription.max_attempts", 3)?
+        .set_default("transcription.timeout_secs", 600)?
+        .set_default("media.enabled", true)?
+        .set_default("media.ffmpeg_binary", "ffmpeg")?
+        .set_default("media.thumbnail_size", 400)?
+        .set_default("media.max_attempts", 3)?
+        .set_default("unfurl.enabled", true)?
+        .set_default("unfurl.blocked_domains", "")?
+        .set_default("unfurl.timeout_secs", 5)?
+        .set_default("unfurl.max_bytes", 1_048_576)? // 1 MiB
+        .set_default("unfurl.max_links", 3)?
+        .set_default("federation.max_future_skew_secs", 300)? // 5 min
+        .set_default("federation.acceptance_window_secs", 604_800)? // 7 days
+        .set_default("federation.txn_log_max_rows", 100_000)?
+        .set_default("federation.rate_limit_enabled", true)?
+        .set_default("federation.txn_limit", 60)?
+        .set_default("federation.pdu_limit", 1000)?
+        .set_default("federation.throttle_strikes", 10)?
+        .set_default("federation.throttle_secs", 600)? // 10 min
+        .set_default("legal.version", "")?
+        .set_default("legal.terms_path", "")?
+        .set_default("legal.privacy_path", "")?
+        .set_default("activitypub.enabled", false)?
+        .set_default("activitypub.max_delivery_attempts", 8)?
+        .set_default("security_webhooks.urls", "")?
+        .set_default("security_webhooks.secret", "")?
+        .set_default("security_webhooks.max_delivery_attempts", 10)?
+        .set_default("telemetry.otlp_endpoint", "")?
+        .set_default("telemetry.service_name", "nexus")?
+        .set_default("telemetry.sample_ratio", 1.0)?
+        .set_default("metrics.enabled", false)?
+        .set_default("metrics.host", "127.0.0.1")?
+        .set_default("metrics.port", 9464)?
+        .set_default("rpc.enabled", false)?
+        .set_default("rpc.host", "")?
+        .set_default("rpc.port", 50051)?
+        .set_default("rpc.token", "")?
+        .set_default("voice.node_id", "")?
+        .set_default("voice.public_url", "")?
+        .set_default("voice.heartbeat_secs", 10)?
+        .set_default("voice.turn_urls", "")?
+        .set_default("voice.turn_secret", "")?
+        .set_default("voice.turn_ttl_secs", 86400)?
+        .set_default("voice.rtc_host", "0.0.0.0")?
+        .set_default("voice.public_ips", "")?
+        .set_default("voice.rtc_port_min", 0)?
+        .set_default("voice.rtc_port_max", 0)?
+        .set_default("voice.rtc_single_port", 0)?
+        .set_default("push.enabled", false)?
+        .set_default("push.vapid_subject", "")?
+        .set_default("push.vapid_public_key", "")?
+        .set_default("push.vapid_private_key", "")?
+        .set_default("push.fcm_service_account", "")?
+        .set_default("push.apns_key_path", "")?
+        .set_default("push.apns_key_id", "")?
+        .set_default("push.apns_team_id", "")?
+        .set_default("push.apns_topic", "")?
+        .set_default("push.apns_sandbox", false)?
+        .set_default("snowflake.role", "server")?
+        .set_default("snowflake.instance", 0)?
+        .set_default("tls.enabled", false)?
+        .set_default("tls.domains", "")?
+        .set_default("tls.acme_email", "")?
+        .set_default("tls.acme_directory", "https://acme-v02.api.letsencrypt.org/directory")?
+        .set_default("tls.cache_dir", "data/acme")?
+        .set_default("tls.http_port", 80)?
+        .set_default("tls.renew_days", 30)?
+        .set_default("exports.retention_hours", 168)? // 7 days
+        .set_default("exports.cooldown_hours", 24)?
         .set_default("scylla.nodes", "127.0.0.1:9042")?
-        .set_default("scylla.keyspace", "nexus")?
-        // Optional config file
-        .add_source(config::File::with_name("config").required(false))
-        // Environment variables (NEXUS_SERVER__HOST, NEXUS_DATABASE__URL, etc.)
-        .add_source(
-            config::Environment::with_prefix("NEXUS")
-                .separator("__")
-                .try_parsing(true),
-        )
-        .build()?;
-
-    let app_config: AppConfig = cfg.try_deserialize()?;
-    Ok(CONFIG.get_or_init(|| app_config))
+        .set_default("scylla.keyspace", "nexus")
 }
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct AppConfig {
     pub server: ServerConfig,
+    pub http: HttpConfig,
     pub database: DatabaseConfig,
     pub redis: RedisConfig,
     pub scylla: ScyllaConfig,
     pub auth: AuthConfig,
+    pub registration: RegistrationConfig,
     pub storage: StorageConfig,
     pub search: SearchConfig,
     pub limits: LimitsConfig,
+    pub spam: SpamConfig,
+    pub rate_limit: RateLimitConfig,
+    pub gateway: GatewayConfig,
+    pub transcription: TranscriptionConfig,
+    pub media: MediaConfig,
+    pub unfurl: UnfurlConfig,
+    pub federation: FederationConfig,
+    pub legal: LegalConfig,
+    pub activitypub: ActivityPubConfig,
+    pub security_webhooks: SecurityWebhooksConfig,
+    pub telemetry: TelemetryConfig,
+    pub metrics: MetricsConfig,
+    pub rpc: RpcConfig,
+    pub voice: VoiceConfig,
+    pub push: PushConfig,
+    pub snowflake: SnowflakeConfig,
+    pub tls: TlsConfig,
+    pub exports: ExportsConfig,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -83,12 +241,68 @@ pub struct ServerConfig {
     /// Public server name used for federation (e.g. "nexus.example.com").
     /// Maps to the `NEXUS__SERVER__NAME` env var or `server.name` in config.toml.
     pub name: String,
+    /// Address the API binds. "::" listens on IPv6 and IPv4 alike.
     pub host: String,
+    /// Address the gateway binds; empty for `host`.
+    pub gateway_host: String,
+    /// Address voice signaling binds; empty for `host`.
+    pub voice_host: String,
     pub port: u16,
     pub gateway_port: u16,
     pub voice_port: u16,
     /// Port used for server-to-server federation (default 8448).
     pub federation_port: u16,
+    /// How long shutdown waits for requests to finish, gateway clients to be
+    /// told to reconnect and queued federation deliveries to go out before
+    /// the process exits anyway.
+    pub shutdown_timeout_secs: u64,
+}
+
+impl ServerConfig {
+    /// A listener's own host setting, or `host` when it's empty.
+    pub fn host_or<'a>(&'a self, host: &'a str) -> &'a str {
+        if host.trim().is_empty() {
+            &self.host
+        } else {
+            host
+        }
+    }
+}
+
+/// CORS and security headers on the HTTP API.
+#[derive(Debug, Deserialize, Clone)]
+pub struct HttpConfig {
+    /// Comma-separated origins browsers may call the API from (e.g.
+    /// "https://app.example.com,tauri://localhost"), or "*" for any. Empty
+    /// sends no CORS headers, so only same-origin pages can use the API.
+    pub cors_origins: String,
+    /// Let browsers send cookies and HTTP auth cross-origin. Only honoured
+    /// with an explicit origin list, never with "*".
+    pub cors_allow_credentials: bool,
+    /// How long browsers may cache a preflight response.
+    pub cors_max_age_secs: u64,
+    /// `Strict-Transport-Security` max-age. 0 leaves the header out, for
+    /// instances served over plain HTTP.
+    pub hsts_max_age_secs: u64,
+    /// Comma-separated IPs and CIDR ranges of reverse proxies whose
+    /// `X-Forwarded-For` / `X-Real-IP` are believed. Requests from anywhere
+    /// else are attributed to the socket address.
+    pub trusted_proxies: String,
+}
+
+impl HttpConfig {
+    /// `cors_origins`, split and trimmed. `None` means any origin.
+    pub fn cors_origin_list(&self) -> Option<Vec<String>> {
+        if self.cors_origins.trim() == "*" {
+            return None;
+        }
+        Some(self.cors_origins.split(',').map(str::trim).filter(|o| !o.is_empty()).map(str::to_owned).collect())
+    }
+
+    /// `trusted_proxies`, parsed. Malformed entries are skipped.
+    pub fn trusted_proxy_list(&self) -> Vec<ipnetwork::IpNetwork> {
+        crate::net::parse_networks(&self.trusted_proxies)
+    }
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -123,6 +337,44 @@ pub struct AuthConfig {
     pub refresh_token_ttl_secs: u64,
 }
 
+/// Who may create accounts, and what they must prove first.
+#[derive(Debug, Deserialize, Clone)]
+pub struct RegistrationConfig {
+    pub mode: RegistrationMode,
+    /// Challenge every registration must pass.
+    pub challenge: RegistrationChallenge,
+    /// Leading zero bits the `pow` challenge demands. Each extra bit doubles
+    /// the expected work; 20 takes a browser around a second.
+    pub pow_difficulty: u32,
+    /// hCaptcha keys for the `hcaptcha` challenge.
+    pub hcaptcha_site_key: String,
+    pub hcaptcha_secret: String,
+    /// Registrations one IP may attempt per window. 0 disables the limit.
+    pub ip_limit: u32,
+    pub ip_window_secs: u64,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RegistrationMode {
+    /// Anyone may register.
+    Open,
+    /// Only holders of a single-use registration token issued by staff.
+    InviteOnly,
+    /// Anyone may apply; the account can't log in until staff approve it.
+    Approval,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RegistrationChallenge {
+    None,
+    /// A SHA-256 proof-of-work puzzle, solved by the client.
+    Pow,
+    /// An hCaptcha, verified with hCaptcha's `siteverify` API.
+    Hcaptcha,
+}
+
 #[derive(Debug, Deserialize, Clone)]
 pub struct StorageConfig {
     /// S3 endpoint URL (e.g., http://localhost:9000 for MinIO).
@@ -153,4 +405,366 @@ pub struct LimitsConfig {
     pub max_message_length: u32,
     pub max_file_size_bytes: u64,
     pub max_attachment_count: u32,
+    /// Largest request body on routes that don't take file uploads. Upload
+    /// routes allow `max_file_size_bytes` per file instead.
+    pub max_request_body_bytes: u64,
+    /// Days a deleted message stays available to moderators before it is
+    /// purged. 0 purges deleted messages on the next retention run.
+    pub deleted_message_retention_days: u32,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct SpamConfig {
+    /// Master switch for the message spam heuristics.
+    pub enabled: bool,
+    /// Identical messages from one user/IP within the window that trigger a timeout.
+    pub duplicate_threshold: u32,
+    pub duplicate_window_secs: u64,
+    /// Distinct channels a user/IP may post links into within the window.
+    pub link_channel_threshold: u32,
+    pub link_window_secs: u64,
+    /// Messages containing invite links allowed within the window.
+    pub invite_threshold: u32,
+    pub invite_window_secs: u64,
+    /// Length of the automatic communication timeout applied on detection.
+    pub timeout_secs: u64,
+}
+
+/// Token bucket limits (see [`crate::ratelimit`]) on client traffic. A limit
+/// of N per W seconds allows a burst of N, refilling at N/W per second.
+#[derive(Debug, Deserialize, Clone)]
+pub struct RateLimitConfig {
+    /// Master switch for REST and gateway rate limits.
+    pub enabled: bool,
+    /// Requests one user (or IP, unauthenticated) may make across all routes.
+    pub global_limit: u32,
+    pub global_window_secs: u64,
+    /// Requests per route, per user and channel/server.
+    pub route_limit: u32,
+    pub route_window_secs: u64,
+    /// Messages one user may send to one channel.
+    pub message_limit: u32,
+    pub message_window_secs: u64,
+    /// Gateway frames one connection may send; over it the socket is closed.
+    pub gateway_limit: u32,
+    pub gateway_window_secs: u64,
+    /// `TypingStart` ops per connection; extra ones are dropped.
+    pub typing_limit: u32,
+    pub typing_window_secs: u64,
+    /// `PresenceUpdate` ops per connection; extra ones are dropped.
+    pub presence_limit: u32,
+    pub presence_window_secs: u64,
+    /// `RequestMembers` ops per connection; extra ones are dropped.
+    pub request_members_limit: u32,
+    pub request_members_window_secs: u64,
+    /// `LiveShare` ops per connection; extra ones are dropped.
+    pub live_share_limit: u32,
+    pub live_share_window_secs: u64,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct GatewayConfig {
+    /// Events buffered on the in-process event bus, and frames buffered for
+    /// each gateway connection, before the slowest reader starts missing
+    /// them.
+    pub event_bus_capacity: usize,
+    /// What a connection does when it falls that far behind.
+    pub overflow: GatewayOverflow,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum GatewayOverflow {
+    /// Queue what the connection missed from the recent-frame log and send
+    /// it before anything newer; falls back to `resume` once the log no
+    /// longer has it.
+    Spill,
+    /// Ask the client to reconnect and resume, replaying what it missed.
+    Resume,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct TranscriptionConfig {
+    /// Speech-to-text backend: `none` (disabled), `whisper_cpp` (local binary)
+    /// or `http` (OpenAI-compatible `/audio/transcriptions` endpoint).
+    pub provider: String,
+    /// Path to the whisper.cpp CLI binary.
+    pub whisper_binary: String,
+    /// Path to the ggml model file passed to whisper.cpp.
+    pub whisper_model: String,
+    /// ffmpeg binary used to resample non-WAV audio to 16 kHz mono for whisper.cpp.
+    pub ffmpeg_binary: String,
+    /// Transcription endpoint for the `http` provider.
+    pub api_url: String,
+    pub api_key: String,
+    pub api_model: String,
+    /// Force a spoken language (ISO 639-1); auto-detected when unset.
+    pub language: Option<String>,
+    /// Attempts per transcript before it is marked failed.
+    pub max_attempts: u32,
+    /// Longest one transcription may take: the API request, or each of the
+    /// ffmpeg and whisper.cpp runs, which are killed when it runs out.
+    pub timeout_secs: u64,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct MediaConfig {
+    /// Generate thumbnails and blurhash placeholders for image uploads.
+    /// EXIF stripping and dimension probing happen on upload regardless.
+    pub enabled: bool,
+    /// ffmpeg binary used to decode and scale images.
+    pub ffmpeg_binary: String,
+    /// Longest edge of generated thumbnails, in pixels.
+    pub thumbnail_size: u32,
+    /// Attempts per image before its job is marked failed.
+    pub max_attempts: u32,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct UnfurlConfig {
+    /// Fetch OpenGraph / Twitter card metadata for links in new messages.
+    pub enabled: bool,
+    /// Comma-separated domains never fetched; subdomains are blocked too.
+    pub blocked_domains: String,
+    /// Per-request timeout, covering redirects.
+    pub timeout_secs: u64,
+    /// Largest page body read while looking for metadata.
+    pub max_bytes: u64,
+    /// Links previewed per message.
+    pub max_links: u32,
+}
+
+/// Checks on transactions received from remote servers.
+#[derive(Debug, Deserialize, Clone)]
+pub struct FederationConfig {
+    /// How far ahead of our clock an `origin_server_ts` may be.
+    pub max_future_skew_secs: u64,
+    /// How old an `origin_server_ts` may be. Transaction IDs are remembered
+    /// for this long, so a replay is either recognised or too old to accept.
+    pub acceptance_window_secs: u64,
+    /// Transaction IDs kept for de-duplication; the oldest are pruned first.
+    pub txn_log_max_rows: u64,
+    /// Master switch for the per-origin limits below.
+    pub rate_limit_enabled: bool,
+    /// Transactions one origin server may send per minute.
+    pub txn_limit: u32,
+    /// PDUs one origin server may send per minute, across its transactions.
+    pub pdu_limit: u32,
+    /// Consecutive rate-limited transactions after which an origin is
+    /// throttled outright.
+    pub throttle_strikes: u32,
+    /// How long a throttled origin has every transaction refused.
+    pub throttle_secs: u64,
+}
+
+/// The instance's terms of service and privacy policy.
+#[derive(Debug, Deserialize, Clone)]
+pub struct LegalConfig {
+    /// Version of the published documents, e.g. "2026-10-01". Users must
+    /// consent to it before using the API; changing it asks everyone again.
+    /// Empty disables consent tracking.
+    pub version: String,
+    /// Markdown files served as the documents. Empty serves nothing.
+    pub terms_path: String,
+    pub privacy_path: String,
+}
+
+impl LegalConfig {
+    /// The version users must have consented to, if consent is required.
+    pub fn required_version(&self) -> Option<&str> {
+        (!self.version.is_empty()).then_some(self.version.as_str())
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct ActivityPubConfig {
+    /// Publish announcement channels of public servers to the fediverse.
+    pub enabled: bool,
+    /// Delivery attempts per inbox before an activity is dropped.
+    pub max_delivery_attempts: u32,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct SecurityWebhooksConfig {
+    /// Comma-separated URLs every security event is POSTed to (e.g. a SIEM
+    /// HTTP collector). Empty sends nothing.
+    pub urls: String,
+    /// HMAC-SHA256 key for the `X-Nexus-Signature` header. Events are only
+    /// sent when this is set.
+    pub secret: String,
+    /// Delivery attempts per URL before an event is dropped.
+    pub max_delivery_attempts: u32,
+}
+
+impl SecurityWebhooksConfig {
+    /// `urls`, split and trimmed; empty unless `secret` is set too.
+    pub fn url_list(&self) -> Vec<String> {
+        if self.secret.is_empty() {
+            return Vec::new();
+        }
+        self.urls.split(',').map(str::trim).filter(|u| !u.is_empty()).map(str::to_owned).collect()
+    }
+}
+
+/// OpenTelemetry tracing.
+#[derive(Debug, Deserialize, Clone)]
+pub struct TelemetryConfig {
+    /// OTLP/gRPC collector spans are exported to (e.g. "http://tempo:4317"
+    /// or Jaeger's "http://jaeger:4317"). Empty exports nothing.
+    pub otlp_endpoint: String,
+    /// `service.name` of the exported spans; give each node its own when
+    /// running split roles.
+    pub service_name: String,
+    /// Share of new traces recorded, 0.0 to 1.0. Traces started by a caller
+    /// that sent a `traceparent` follow the caller's decision.
+    pub sample_ratio: f64,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct MetricsConfig {
+    /// Serve Prometheus metrics at `/metrics` on their own listener.
+    pub enabled: bool,
+    /// Address the listener binds. Loopback by default: the metrics aren't
+    /// authenticated, so expose them only to the network Prometheus is on.
+    pub host: String,
+    pub port: u16,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct RpcConfig {
+    /// Serve the internal gRPC API for gateway / voice nodes.
+    pub enabled: bool,
+    /// Address the gRPC listener binds; empty for `server.host`.
+    pub host: String,
+    pub port: u16,
+    /// Shared secret every internal call must present. Required when enabled.
+    pub token: String,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct VoiceConfig {
+    /// Stable identifier of this voice node in the cluster. Defaults to the
+    /// public URL when empty.
+    pub node_id: String,
+    /// Signaling URL clients are redirected to when this node owns a room
+    /// (e.g. "wss://voice-1.example.com/voice"). Clustering is enabled only
+    /// when this and `redis.url` are both set.
+    pub public_url: String,
+    /// How often the node refreshes its liveness and room ownership in Redis.
+    pub heartbeat_secs: u64,
+    /// Comma-separated TURN URLs handed to clients that can't reach the SFU
+    /// directly (e.g. "turn:turn.example.com:3478,turns:turn.example.com:5349").
+    pub turn_urls: String,
+    /// Shared secret of the TURN server's REST API auth (coturn's
+    /// `static-auth-secret`). TURN is offered only when this and
+    /// `turn_urls` are both set.
+    pub turn_secret: String,
+    /// How long the TURN credentials given to a client stay valid.
+    pub turn_ttl_secs: u64,
+    /// Address the SFU's media sockets bind. "::" takes IPv6 and IPv4.
+    pub rtc_host: String,
+    /// Comma-separated addresses clients send media to, e.g. the public
+    /// IPv4 and IPv6 of a host behind 1:1 NAT. Empty advertises `rtc_host`,
+    /// or the host's outbound address when that is unspecified.
+    pub public_ips: String,
+    /// UDP port range for media sockets, one per participant, for
+    /// firewalls. 0 and 0 uses any free port.
+    pub rtc_port_min: u16,
+    pub rtc_port_max: u16,
+    /// One UDP port for every participant's media, for firewalls that
+    /// should have a single port open. 0 gives each participant its own.
+    pub rtc_single_port: u16,
+}
+
+impl VoiceConfig {
+    /// `public_ips`, split and trimmed.
+    pub fn public_ip_list(&self) -> Vec<String> {
+        self.public_ips.split(',').map(str::trim).filter(|ip| !ip.is_empty()).map(str::to_owned).collect()
+    }
+
+    /// `turn_urls`, split and trimmed; empty unless `turn_secret` is set too.
+    pub fn turn_url_list(&self) -> Vec<String> {
+        if self.turn_secret.is_empty() {
+            return Vec::new();
+        }
+        self.turn_urls.split(',').map(str::trim).filter(|u| !u.is_empty()).map(str::to_owned).collect()
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct PushConfig {
+    /// Deliver push notifications to offline users. Each service below is
+    /// used only when its credentials are set.
+    pub enabled: bool,
+    /// VAPID contact, e.g. "mailto:admin@example.com".
+    pub vapid_subject: String,
+    /// VAPID key pair (base64url, as printed by `web-push generate-vapid-keys`).
+    /// Browsers subscribe with the public key.
+    pub vapid_public_key: String,
+    pub vapid_private_key: String,
+    /// Path to a Firebase service account JSON file.
+    pub fcm_service_account: String,
+    /// Path to an APNs auth key (`AuthKey_XXXXXXXXXX.p8`).
+    pub apns_key_path: String,
+    pub apns_key_id: String,
+    pub apns_team_id: String,
+    /// App bundle ID notifications are addressed to.
+    pub apns_topic: String,
+    /// Use the APNs development environment.
+    pub apns_sandbox: bool,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct SnowflakeConfig {
+    /// What this node runs ("server", "api", "gateway", "voice", ...). Forms
+    /// the high bits of the worker ID stamped into generated IDs.
+    pub role: String,
+    /// Index of this node among others with the same role (0–127). Each node
+    /// of a role needs its own.
+    pub instance: u16,
+}
+
+/// Built-in TLS with certificates from an ACME CA (Let's Encrypt by default).
+#[derive(Debug, Deserialize, Clone)]
+pub struct TlsConfig {
+    /// Serve the API, gateway and voice signaling over HTTPS. Enabling this
+    /// agrees to the CA's terms of service.
+    pub enabled: bool,
+    /// Comma-separated names the certificate covers; each must resolve to
+    /// this host.
+    pub domains: String,
+    /// Contact address given to the CA, for expiry warnings. Optional.
+    pub acme_email: String,
+    /// ACME directory URL. Point at a staging directory while testing.
+    pub acme_directory: String,
+    /// Where the ACME account and certificate are kept between restarts.
+    pub cache_dir: String,
+    /// Port of the plain-HTTP listener that answers HTTP-01 challenges and
+    /// redirects everything else to HTTPS. The CA only connects to 80.
+    pub http_port: u16,
+    /// Renew a certificate this many days before it expires.
+    pub renew_days: u64,
+}
+
+impl TlsConfig {
+    /// `domains`, split and trimmed.
+    pub fn domain_list(&self) -> Vec<String> {
+        self.domains.split(',').map(str::trim).filter(|d| !d.is_empty()).map(str::to_owned).collect()
+    }
+}
+
+/// Archives of their data users request with `POST /users/@me/export`.
+#[derive(Debug, Deserialize, Clone)]
+pub struct ExportsConfig {
+    /// How long a finished archive can be downloaded before it is deleted.
+    pub retention_hours: u32,
+    /// Minimum time between two exports by the same user.
+    pub cooldown_hours: u32,
+}
+
+impl SnowflakeConfig {
+    /// Worker ID for this node, or `None` if the role or instance is invalid.
+    pub fn worker_id(&self) -> Option<u16> {
+        crate::snowflake::worker_id(&self.role, self.instance)
+    }
 }
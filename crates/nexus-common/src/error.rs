@@ -2,6 +2,15 @@
 //!
 //! Uses `thiserror` for ergonomic error definitions and provides HTTP-friendly
 //! error variants that can be directly converted to API responses.
+//!
+//! Every error response carries a stable [`ErrorCode`] — both the number and
+//! its name — so clients and SDKs can branch on it instead of parsing
+//! messages:
+//!
+//! ```json
+//! { "code": 50013, "error": "MISSING_PERMISSION", "status": 403,
+//!   "message": "Missing permission: BAN_MEMBERS" }
+//! ```
 
 use axum::http::StatusCode;
 use axum::response::{IntoResponse, Response};
@@ -23,6 +32,15 @@ pub enum NexusError {
     #[error("Unauthorized")]
     Unauthorized,
 
+    /// The instance's terms changed (or were never accepted); the user must
+    /// consent to `version` before doing anything else.
+    #[error("Consent to the terms of service version {version} is required")]
+    ConsentRequired { version: String },
+
+    /// Registration needs approval and staff haven't approved the account yet.
+    #[error("This account is waiting for approval")]
+    ApprovalPending,
+
     // === Resource errors ===
     #[error("{resource} not found")]
     NotFound { resource: String },
@@ -34,6 +52,10 @@ pub enum NexusError {
     #[error("Validation failed: {message}")]
     Validation { message: String },
 
+    /// Request body validation with per-field details.
+    #[error("Validation failed: {}", summarize_fields(errors))]
+    InvalidFields { errors: Vec<FieldError> },
+
     // === Permission errors ===
     #[error("Missing permission: {permission}")]
     MissingPermission { permission: String },
@@ -60,13 +82,131 @@ pub enum NexusError {
     Internal(#[from] anyhow::Error),
 }
 
+/// Stable error codes.
+///
+/// Numbers are grouped by kind (1xxxx missing resources, 3xxxx limits,
+/// 4xxxx authentication and request problems, 5xxxx permissions and
+/// validation, 9xxxx server faults). Released codes never change meaning;
+/// new ones are only ever added.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorCode {
+    NotFound,
+    LimitReached,
+    Unauthorized,
+    InvalidCredentials,
+    InvalidToken,
+    TokenExpired,
+    AlreadyExists,
+    RateLimited,
+    ConsentRequired,
+    ApprovalPending,
+    Forbidden,
+    MissingPermission,
+    ValidationError,
+    InternalError,
+    DatabaseError,
+    CacheError,
+}
+
+impl ErrorCode {
+    /// Every code, in catalog order.
+    pub const ALL: &'static [ErrorCode] = &[
+        Self::NotFound,
+        Self::LimitReached,
+        Self::Unauthorized,
+        Self::InvalidCredentials,
+        Self::InvalidToken,
+        Self::TokenExpired,
+        Self::AlreadyExists,
+        Self::RateLimited,
+        Self::ConsentRequired,
+        Self::ApprovalPending,
+        Self::Forbidden,
+        Self::MissingPermission,
+        Self::ValidationError,
+        Self::InternalError,
+        Self::DatabaseError,
+        Self::CacheError,
+    ];
+
+    /// Numeric code sent as `code` in error responses.
+    pub const fn number(self) -> u32 {
+        match self {
+            Self::NotFound => 10001,
+            Self::LimitReached => 30001,
+            Self::Unauthorized => 40001,
+            Self::InvalidCredentials => 40002,
+            Self::InvalidToken => 40003,
+            Self::TokenExpired => 40004,
+            Self::AlreadyExists => 40009,
+            Self::RateLimited => 40029,
+            Self::ConsentRequired => 40051,
+            Self::ApprovalPending => 40052,
+            Self::Forbidden => 50001,
+            Self::MissingPermission => 50013,
+            Self::ValidationError => 50035,
+            Self::InternalError => 90001,
+            Self::DatabaseError => 90002,
+            Self::CacheError => 90003,
+        }
+    }
+
+    /// Name sent as `error` in error responses.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::NotFound => "NOT_FOUND",
+            Self::LimitReached => "LIMIT_REACHED",
+            Self::Unauthorized => "UNAUTHORIZED",
+            Self::InvalidCredentials => "INVALID_CREDENTIALS",
+            Self::InvalidToken => "INVALID_TOKEN",
+            Self::TokenExpired => "TOKEN_EXPIRED",
+            Self::AlreadyExists => "ALREADY_EXISTS",
+            Self::RateLimited => "RATE_LIMITED",
+            Self::ConsentRequired => "CONSENT_REQUIRED",
+            Self::ApprovalPending => "APPROVAL_PENDING",
+            Self::Forbidden => "FORBIDDEN",
+            Self::MissingPermission => "MISSING_PERMISSION",
+            Self::ValidationError => "VALIDATION_ERROR",
+            Self::InternalError => "INTERNAL_ERROR",
+            Self::DatabaseError => "DATABASE_ERROR",
+            Self::CacheError => "CACHE_ERROR",
+        }
+    }
+}
+
+/// A problem with one field of a request body.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldError {
+    pub field: String,
+    /// Validator rule that failed, e.g. `length` or `email`.
+    pub code: String,
+    /// The rule's limits, e.g. `{ "min": 1, "max": 100 }`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub constraint: Option<serde_json::Value>,
+    pub message: String,
+}
+
+fn summarize_fields(errors: &[FieldError]) -> String {
+    errors
+        .iter()
+        .map(|e| e.message.as_str())
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
 /// JSON error response body sent to clients.
 #[derive(Serialize)]
 struct ErrorResponse {
-    code: u16,
-    error: String,
+    /// Numeric [`ErrorCode`].
+    code: u32,
+    /// [`ErrorCode`] name.
+    error: &'static str,
+    /// HTTP status.
+    status: u16,
     message: String,
     #[serde(skip_serializing_if = "Option::is_none")]
+    errors: Option<Vec<FieldError>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     retry_after_ms: Option<u64>,
 }
 
@@ -77,9 +217,11 @@ impl NexusError {
             Self::InvalidCredentials | Self::InvalidToken => StatusCode::UNAUTHORIZED,
             Self::TokenExpired => StatusCode::UNAUTHORIZED,
             Self::Unauthorized => StatusCode::UNAUTHORIZED,
+            Self::ConsentRequired { .. } => StatusCode::UNAVAILABLE_FOR_LEGAL_REASONS,
+            Self::ApprovalPending => StatusCode::FORBIDDEN,
             Self::NotFound { .. } => StatusCode::NOT_FOUND,
             Self::AlreadyExists { .. } => StatusCode::CONFLICT,
-            Self::Validation { .. } => StatusCode::BAD_REQUEST,
+            Self::Validation { .. } | Self::InvalidFields { .. } => StatusCode::BAD_REQUEST,
             Self::MissingPermission { .. } | Self::Forbidden => StatusCode::FORBIDDEN,
             Self::RateLimited { .. } => StatusCode::TOO_MANY_REQUESTS,
             Self::LimitReached { .. } => StatusCode::FORBIDDEN,
@@ -89,25 +231,32 @@ impl NexusError {
         }
     }
 
-    /// Error code string for programmatic handling by clients.
-    pub fn error_code(&self) -> &str {
+    /// Catalog code for programmatic handling by clients.
+    pub fn code(&self) -> ErrorCode {
         match self {
-            Self::InvalidCredentials => "INVALID_CREDENTIALS",
-            Self::TokenExpired => "TOKEN_EXPIRED",
-            Self::InvalidToken => "INVALID_TOKEN",
-            Self::Unauthorized => "UNAUTHORIZED",
-            Self::NotFound { .. } => "NOT_FOUND",
-            Self::AlreadyExists { .. } => "ALREADY_EXISTS",
-            Self::Validation { .. } => "VALIDATION_ERROR",
-            Self::MissingPermission { .. } => "MISSING_PERMISSION",
-            Self::Forbidden => "FORBIDDEN",
-            Self::RateLimited { .. } => "RATE_LIMITED",
-            Self::LimitReached { .. } => "LIMIT_REACHED",
-            Self::Database(_) => "DATABASE_ERROR",
-            Self::Redis(_) => "CACHE_ERROR",
-            Self::Internal(_) => "INTERNAL_ERROR",
+            Self::InvalidCredentials => ErrorCode::InvalidCredentials,
+            Self::TokenExpired => ErrorCode::TokenExpired,
+            Self::InvalidToken => ErrorCode::InvalidToken,
+            Self::Unauthorized => ErrorCode::Unauthorized,
+            Self::ConsentRequired { .. } => ErrorCode::ConsentRequired,
+            Self::ApprovalPending => ErrorCode::ApprovalPending,
+            Self::NotFound { .. } => ErrorCode::NotFound,
+            Self::AlreadyExists { .. } => ErrorCode::AlreadyExists,
+            Self::Validation { .. } | Self::InvalidFields { .. } => ErrorCode::ValidationError,
+            Self::MissingPermission { .. } => ErrorCode::MissingPermission,
+            Self::Forbidden => ErrorCode::Forbidden,
+            Self::RateLimited { .. } => ErrorCode::RateLimited,
+            Self::LimitReached { .. } => ErrorCode::LimitReached,
+            Self::Database(_) => ErrorCode::DatabaseError,
+            Self::Redis(_) => ErrorCode::CacheError,
+            Self::Internal(_) => ErrorCode::InternalError,
         }
     }
+
+    /// Error code string for programmatic handling by clients.
+    pub fn error_code(&self) -> &str {
+        self.code().name()
+    }
 }
 
 impl IntoResponse for NexusError {
@@ -137,10 +286,18 @@ impl IntoResponse for NexusError {
             None
         };
 
+        let errors = match &self {
+            NexusError::InvalidFields { errors } => Some(errors.clone()),
+            _ => None,
+        };
+
+        let code = self.code();
         let body = ErrorResponse {
-            code: status.as_u16(),
-            error: self.error_code().to_string(),
+            code: code.number(),
+            error: code.name(),
+            status: status.as_u16(),
             message,
+            errors,
             retry_after_ms,
         };
 
@@ -150,3 +307,37 @@ impl IntoResponse for NexusError {
 
 /// Convenience type alias for Results using NexusError.
 pub type NexusResult<T> = Result<T, NexusError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn catalog_codes_are_unique() {
+        let numbers: HashSet<u32> = ErrorCode::ALL.iter().map(|c| c.number()).collect();
+        let names: HashSet<&str> = ErrorCode::ALL.iter().map(|c| c.name()).collect();
+        assert_eq!(numbers.len(), ErrorCode::ALL.len());
+        assert_eq!(names.len(), ErrorCode::ALL.len());
+    }
+
+    #[test]
+    fn errors_map_to_catalog_codes() {
+        let err = NexusError::MissingPermission {
+            permission: "BAN_MEMBERS".into(),
+        };
+        assert_eq!(err.code().number(), 50013);
+        assert_eq!(err.error_code(), "MISSING_PERMISSION");
+
+        let err = NexusError::InvalidFields {
+            errors: vec![FieldError {
+                field: "name".into(),
+                code: "length".into(),
+                constraint: None,
+                message: "Name must be 1-100 characters".into(),
+            }],
+        };
+        assert_eq!(err.code(), ErrorCode::ValidationError);
+        assert_eq!(err.to_string(), "Validation failed: Name must be 1-100 characters");
+    }
+}
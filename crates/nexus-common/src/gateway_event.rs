@@ -5,6 +5,8 @@
 //! This module lives in `nexus-common` so both crates can use it without circular deps.
 
 use serde::{Deserialize, Serialize};
+use std::ops::Deref;
+use tokio::sync::broadcast;
 use uuid::Uuid;
 
 /// Well-known event type constants (v0.7 extensibility additions).
@@ -13,6 +15,8 @@ pub mod event_types {
     pub const MESSAGE_CREATE: &str = "MESSAGE_CREATE";
     pub const MESSAGE_UPDATE: &str = "MESSAGE_UPDATE";
     pub const MESSAGE_DELETE: &str = "MESSAGE_DELETE";
+    pub const MESSAGE_REACTION_ADD: &str = "MESSAGE_REACTION_ADD";
+    pub const MESSAGE_REACTION_REMOVE: &str = "MESSAGE_REACTION_REMOVE";
     pub const TYPING_START: &str = "TYPING_START";
     pub const PRESENCE_UPDATE: &str = "PRESENCE_UPDATE";
     pub const VOICE_STATE_UPDATE: &str = "VOICE_STATE_UPDATE";
@@ -22,12 +26,265 @@ pub mod event_types {
     pub const SERVER_MEMBER_ADD: &str = "SERVER_MEMBER_ADD";
     pub const SERVER_MEMBER_REMOVE: &str = "SERVER_MEMBER_REMOVE";
     pub const SERVER_MEMBER_UPDATE: &str = "SERVER_MEMBER_UPDATE";
+    pub const SERVER_MEMBERS_BULK_UPDATE: &str = "SERVER_MEMBERS_BULK_UPDATE";
+    // Member list pages asked for with RequestMembers — sent only to the
+    // requesting connection
+    pub const GUILD_MEMBERS_CHUNK: &str = "GUILD_MEMBERS_CHUNK";
+    // Ephemeral live shares in DMs — sent to each participant
+    pub const LIVE_SHARE_UPDATE: &str = "LIVE_SHARE_UPDATE";
+    pub const LIVE_SHARE_STOP: &str = "LIVE_SHARE_STOP";
+    pub const SERVER_BAN_ADD: &str = "SERVER_BAN_ADD";
+    pub const SERVER_BAN_REMOVE: &str = "SERVER_BAN_REMOVE";
     // v0.7 — Extensibility
     pub const INTERACTION_CREATE: &str = "INTERACTION_CREATE";
     pub const WEBHOOK_EXECUTE: &str = "WEBHOOK_EXECUTE";
     pub const APPLICATION_COMMAND_CREATE: &str = "APPLICATION_COMMAND_CREATE";
     pub const APPLICATION_COMMAND_UPDATE: &str = "APPLICATION_COMMAND_UPDATE";
     pub const APPLICATION_COMMAND_DELETE: &str = "APPLICATION_COMMAND_DELETE";
+    // Scheduled events
+    pub const SCHEDULED_EVENT_CREATE: &str = "SCHEDULED_EVENT_CREATE";
+    pub const SCHEDULED_EVENT_UPDATE: &str = "SCHEDULED_EVENT_UPDATE";
+    pub const SCHEDULED_EVENT_DELETE: &str = "SCHEDULED_EVENT_DELETE";
+    // Moderation
+    pub const SPAM_DETECTED: &str = "SPAM_DETECTED";
+    pub const MODERATION_QUEUE_ADD: &str = "MODERATION_QUEUE_ADD";
+    pub const MODERATION_QUEUE_REMOVE: &str = "MODERATION_QUEUE_REMOVE";
+    pub const AUDIT_LOG_ENTRY_CREATE: &str = "AUDIT_LOG_ENTRY_CREATE";
+    // Threads and forums
+    pub const THREAD_CREATE: &str = "THREAD_CREATE";
+    pub const THREAD_UPDATE: &str = "THREAD_UPDATE";
+    pub const FORUM_TAG_CREATE: &str = "FORUM_TAG_CREATE";
+    pub const FORUM_TAG_UPDATE: &str = "FORUM_TAG_UPDATE";
+    pub const FORUM_TAG_DELETE: &str = "FORUM_TAG_DELETE";
+    // Rich presence
+    pub const ACTIVITY_JOIN: &str = "ACTIVITY_JOIN";
+    pub const ACTIVITY_JOIN_REQUEST: &str = "ACTIVITY_JOIN_REQUEST";
+    // Read states — sent only to the acknowledging user
+    pub const MESSAGE_ACK: &str = "MESSAGE_ACK";
+    // Synced settings — sent only to the user's own sessions
+    pub const SERVER_LAYOUT_UPDATE: &str = "SERVER_LAYOUT_UPDATE";
+    pub const USER_VOLUMES_UPDATE: &str = "USER_VOLUMES_UPDATE";
+    // Accessibility
+    pub const TRANSCRIPT_READY: &str = "TRANSCRIPT_READY";
+    // Attachments — thumbnail and blurhash ready
+    pub const ATTACHMENT_UPDATE: &str = "ATTACHMENT_UPDATE";
+    // Federation — a remote user's cached profile changed
+    pub const PROFILE_UPDATE: &str = "PROFILE_UPDATE";
+    // Federation — a channel was promoted to a federated room or defederated
+    pub const CHANNEL_FEDERATION_UPDATE: &str = "CHANNEL_FEDERATION_UPDATE";
+    // Reminders — a scheduled reminder fell due; sent only to its owner
+    pub const REMINDER_DUE: &str = "REMINDER_DUE";
+    // E2EE — a device is running out of one-time pre-keys; sent only to its owner
+    pub const DEVICE_OTK_LOW: &str = "DEVICE_OTK_LOW";
+    // Reactions with the message's full counts — sent instead of
+    // MESSAGE_REACTION_ADD/REMOVE to sessions with `intents::REACTION_COUNTS`
+    pub const MESSAGE_REACTION_ADD_AGGREGATE: &str = "MESSAGE_REACTION_ADD_AGGREGATE";
+    pub const MESSAGE_REACTION_REMOVE_AGGREGATE: &str = "MESSAGE_REACTION_REMOVE_AGGREGATE";
+}
+
+/// Opt-in event variants a gateway client asks for with `intents` in
+/// Identify, plus [`intents::MESSAGE_CONTENT`], which the gateway decides.
+/// Sessions without an intent get the plain events.
+pub mod intents {
+    use super::event_types;
+
+    /// Reaction events carry the message's counts for every emoji, so bots
+    /// keeping tallies (starboards, polls) needn't refetch the message.
+    pub const REACTION_COUNTS: u64 = 1 << 0;
+
+    /// Message bodies in MESSAGE_CREATE and MESSAGE_UPDATE. Not something a
+    /// client asks for: user sessions always have it, and bot sessions have
+    /// it exactly when their application holds the message content
+    /// capability. Without it those events arrive with [`redact`] applied.
+    pub const MESSAGE_CONTENT: u64 = 1 << 1;
+
+    /// Whether `event_type` carries a message body gated by MESSAGE_CONTENT.
+    pub fn gates_content(event_type: &str) -> bool {
+        matches!(event_type, event_types::MESSAGE_CREATE | event_types::MESSAGE_UPDATE)
+    }
+
+    /// Empty the content, embeds and attachments of a message payload,
+    /// leaving ids, author and mentions so bots can still react to them.
+    pub fn redact(data: &mut serde_json::Value) {
+        let Some(message) = data.as_object_mut() else {
+            return;
+        };
+        if let Some(content) = message.get_mut("content") {
+            *content = serde_json::Value::String(String::new());
+        }
+        for key in ["embeds", "attachments"] {
+            if let Some(list) = message.get_mut(key) {
+                *list = serde_json::Value::Array(Vec::new());
+            }
+        }
+    }
+
+    /// Whether a session with `intents` receives events of `event_type`.
+    /// An event with an opt-in variant goes either as the variant or
+    /// plain, never both.
+    pub fn wants(event_type: &str, intents: u64) -> bool {
+        match event_type {
+            event_types::MESSAGE_REACTION_ADD_AGGREGATE
+            | event_types::MESSAGE_REACTION_REMOVE_AGGREGATE => intents & REACTION_COUNTS != 0,
+            event_types::MESSAGE_REACTION_ADD | event_types::MESSAGE_REACTION_REMOVE => {
+                intents & REACTION_COUNTS == 0
+            }
+            _ => true,
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn reaction_events_go_plain_or_aggregate() {
+            assert!(wants(event_types::MESSAGE_REACTION_ADD, 0));
+            assert!(!wants(event_types::MESSAGE_REACTION_ADD_AGGREGATE, 0));
+            assert!(!wants(event_types::MESSAGE_REACTION_REMOVE, REACTION_COUNTS));
+            assert!(wants(event_types::MESSAGE_REACTION_REMOVE_AGGREGATE, REACTION_COUNTS));
+            assert!(wants(event_types::MESSAGE_CREATE, REACTION_COUNTS));
+        }
+
+        #[test]
+        fn redact_empties_message_body() {
+            let mut data = serde_json::json!({
+                "id": "1",
+                "content": "secret",
+                "embeds": [{"title": "x"}],
+                "attachments": [{"url": "y"}],
+                "mentions": ["2"],
+            });
+            redact(&mut data);
+            assert_eq!(
+                data,
+                serde_json::json!({
+                    "id": "1",
+                    "content": "",
+                    "embeds": [],
+                    "attachments": [],
+                    "mentions": ["2"],
+                })
+            );
+            assert!(gates_content(event_types::MESSAGE_UPDATE));
+            assert!(!gates_content(event_types::MESSAGE_DELETE));
+        }
+    }
+}
+
+/// Gateway opcodes — what the client and server send to each other.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", content = "d")]
+pub enum GatewayMessage {
+    /// Server → Client: Sent on connect, before Identify. Send a Heartbeat
+    /// every `heartbeat_interval` milliseconds.
+    Hello { heartbeat_interval: u64 },
+
+    /// Client → Server: Authenticate with an access token or `Bot <token>`.
+    /// `intents` opts in to event variants (see [`intents`]); bots get
+    /// message bodies only if their application has the message content
+    /// capability.
+    Identify {
+        token: String,
+        #[serde(default)]
+        intents: u64,
+    },
+
+    /// Server → Client: Connection accepted, here's your session info
+    Ready {
+        session_id: String,
+        user: serde_json::Value,
+        servers: Vec<serde_json::Value>,
+    },
+
+    /// Bidirectional: Keepalive ping/pong
+    Heartbeat { timestamp: i64 },
+
+    /// Server → Client: Heartbeat acknowledged
+    HeartbeatAck { timestamp: i64 },
+
+    /// Client → Server: Resume a disconnected session. `sequence` is the
+    /// last Dispatch the client received.
+    Resume {
+        session_id: String,
+        token: String,
+        sequence: u64,
+    },
+
+    /// Server → Client: Resume accepted; the missed Dispatches were sent
+    /// just before this. `complete` is false when the session was resumed
+    /// on another gateway node, which can't replay what was missed; the
+    /// client should refetch any state it caches.
+    Resumed {
+        session_id: String,
+        replayed: usize,
+        #[serde(default = "resumed_complete")]
+        complete: bool,
+    },
+
+    /// Server → Client: An event occurred
+    Dispatch {
+        event: String,
+        data: serde_json::Value,
+        sequence: u64,
+    },
+
+    /// Server → Client: Reconnect requested (server restarting, etc.)
+    Reconnect,
+
+    /// Server → Client: Session invalidated, must re-identify
+    InvalidSession,
+
+    /// Server → Client: An op was dropped for exceeding its rate limit
+    /// (see `nexus_gateway::ratelimit`); it may be sent again after `retry_after_ms`.
+    RateLimited { op: String, retry_after_ms: u64 },
+
+    /// Client → Server: Request presence update
+    PresenceUpdate {
+        status: String,
+        custom_status: Option<String>,
+    },
+
+    /// Client → Server: Typing indicator
+    TypingStart { channel_id: String },
+
+    /// Client → Server: Page through a server's members, optionally those
+    /// whose username, display name or nickname starts with `query`.
+    /// Answered with `GUILD_MEMBERS_CHUNK` dispatches echoing `nonce`.
+    RequestMembers {
+        server_id: String,
+        #[serde(default)]
+        query: Option<String>,
+        #[serde(default)]
+        limit: Option<u32>,
+        #[serde(default)]
+        nonce: Option<String>,
+    },
+
+    /// Client → Server: Start or update a live share in a DM (see
+    /// `nexus_gateway::live_share`). Participants get `LIVE_SHARE_UPDATE`.
+    LiveShare {
+        channel_id: String,
+        data: serde_json::Value,
+        #[serde(default)]
+        expires_in_secs: Option<u64>,
+    },
+
+    /// Client → Server: Stop a live share. Participants get `LIVE_SHARE_STOP`.
+    LiveShareStop { channel_id: String },
+
+    /// Client → Server: Join voice channel
+    VoiceStateUpdate {
+        server_id: Option<String>,
+        channel_id: Option<String>,
+        self_mute: bool,
+        self_deaf: bool,
+    },
+}
+
+/// Servers that predate `Resumed.complete` always replayed everything.
+fn resumed_complete() -> bool {
+    true
 }
 
 /// Events broadcast through the gateway to connected clients.
@@ -46,4 +303,112 @@ pub struct GatewayEvent {
     pub channel_id: Option<Uuid>,
     /// Which user triggered this event
     pub user_id: Option<Uuid>,
+    /// W3C `traceparent` of the span that published the event, so the
+    /// gateway's work joins the same trace. Filled in by [`EventBus::send`];
+    /// publishers leave it `None`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trace_context: Option<String>,
+}
+
+/// The in-process event bus: a broadcast channel of [`GatewayEvent`]s that
+/// stamps each one with the sender's trace context. Everything else about
+/// the channel (`subscribe`, `receiver_count`, ...) is the plain sender's.
+///
+/// With several nodes, a relay (`nexus_db::event_relay`) passes the events
+/// published here to the other nodes and [`deliver`](Self::deliver)s theirs,
+/// so subscribers see every event in the cluster. Work that must happen once
+/// per event rather than once per node subscribes with
+/// [`subscribe_local`](Self::subscribe_local) instead.
+#[derive(Debug, Clone)]
+pub struct EventBus {
+    /// Every event, published here or relayed from another node.
+    all: broadcast::Sender<GatewayEvent>,
+    /// Events published on this node.
+    local: broadcast::Sender<GatewayEvent>,
+}
+
+impl EventBus {
+    /// A bus holding up to `capacity` events per receiver
+    /// (`gateway.event_bus_capacity`).
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            all: broadcast::channel(capacity).0,
+            local: broadcast::channel(capacity).0,
+        }
+    }
+
+    /// Publish `event`, as [`broadcast::Sender::send`]. Receivers of both
+    /// [`subscribe`](broadcast::Sender::subscribe) and
+    /// [`subscribe_local`](Self::subscribe_local) are counted.
+    pub fn send(&self, mut event: GatewayEvent) -> Result<usize, broadcast::error::SendError<GatewayEvent>> {
+        if event.trace_context.is_none() {
+            event.trace_context = crate::telemetry::current_traceparent();
+        }
+        let local = if self.local.receiver_count() > 0 {
+            self.local.send(event.clone()).unwrap_or(0)
+        } else {
+            0
+        };
+        match self.all.send(event) {
+            Ok(n) => Ok(n + local),
+            Err(_) if local > 0 => Ok(local),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Hand subscribers an event published on another node.
+    pub fn deliver(&self, event: GatewayEvent) -> Result<usize, broadcast::error::SendError<GatewayEvent>> {
+        self.all.send(event)
+    }
+
+    /// Events published on this node only.
+    pub fn subscribe_local(&self) -> broadcast::Receiver<GatewayEvent> {
+        self.local.subscribe()
+    }
+}
+
+impl Deref for EventBus {
+    type Target = broadcast::Sender<GatewayEvent>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.all
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(event_type: &str) -> GatewayEvent {
+        GatewayEvent {
+            event_type: event_type.into(),
+            data: serde_json::Value::Null,
+            server_id: None,
+            channel_id: None,
+            user_id: None,
+            trace_context: None,
+        }
+    }
+
+    #[test]
+    fn relayed_events_skip_local_subscribers() {
+        let bus = EventBus::new(8);
+        let mut all = bus.subscribe();
+        let mut local = bus.subscribe_local();
+
+        bus.send(event("MESSAGE_CREATE")).unwrap();
+        bus.deliver(event("TYPING_START")).unwrap();
+
+        assert_eq!(all.try_recv().unwrap().event_type, "MESSAGE_CREATE");
+        assert_eq!(all.try_recv().unwrap().event_type, "TYPING_START");
+        assert_eq!(local.try_recv().unwrap().event_type, "MESSAGE_CREATE");
+        assert!(local.try_recv().is_err());
+    }
+
+    #[test]
+    fn send_reaches_a_relay_without_other_subscribers() {
+        let bus = EventBus::new(8);
+        let _relay = bus.subscribe_local();
+        assert_eq!(bus.send(event("MESSAGE_CREATE")).unwrap(), 1);
+    }
 }
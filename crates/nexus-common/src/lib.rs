@@ -8,9 +8,16 @@ pub mod config;
 pub mod crypto;
 pub mod error;
 pub mod gateway_event;
+pub mod locale;
 pub mod models;
+pub mod net;
 pub mod permissions;
+pub mod ratelimit;
+pub mod shutdown;
 pub mod snowflake;
+pub mod telemetry;
+pub mod timestamps;
 pub mod validation;
+pub mod voice;
 /// Manual `sqlx::FromRow<'_, AnyRow>` impls for all model types (AnyPool compat).
 pub mod any_row;
@@ -0,0 +1,164 @@
+//! Locales — the language tags clients may send, the lookup of per-locale
+//! strings (e.g. slash command `name_localizations`), and the text of
+//! server-generated messages.
+//!
+//! Tags follow Discord's list so bots can reuse their localization maps
+//! unchanged. Matching is case-insensitive; a regional tag falls back to
+//! another region of the same language (`es-MX` → `es-ES`), so a client
+//! asking for `pt-PT` still gets the `pt-BR` strings rather than none.
+//!
+//! Users pick a locale (`users.locale`) and servers a preferred language
+//! (`servers.preferred_locale`); both default to [`DEFAULT_LOCALE`].
+
+use validator::ValidationError;
+
+/// Every locale a localization map may be keyed by.
+pub const SUPPORTED_LOCALES: &[&str] = &[
+    "id", "da", "de", "en-GB", "en-US", "es-ES", "es-419", "fr", "hr", "it", "lt", "hu", "nl",
+    "no", "pl", "pt-BR", "ro", "fi", "sv-SE", "vi", "tr", "cs", "el", "bg", "ru", "uk", "hi",
+    "th", "zh-CN", "ja", "zh-TW", "ko",
+];
+
+/// Locale of users and servers that haven't chosen one.
+pub const DEFAULT_LOCALE: &str = "en-US";
+
+/// Most tags taken from one `Accept-Language` header.
+const MAX_PREFERENCES: usize = 16;
+
+/// Whether `tag` is one of [`SUPPORTED_LOCALES`], exactly as listed.
+pub fn is_supported(tag: &str) -> bool {
+    SUPPORTED_LOCALES.contains(&tag)
+}
+
+/// `validator` hook for locale fields.
+pub fn validate_locale(tag: &str) -> Result<(), ValidationError> {
+    if is_supported(tag) {
+        Ok(())
+    } else {
+        Err(ValidationError::new("unknown_locale").with_message(format!("'{tag}' is not a supported locale").into()))
+    }
+}
+
+/// The language tags of an `Accept-Language` header, most preferred first.
+///
+/// Tags with `q=0` and the `*` wildcard are dropped; equal weights keep
+/// header order.
+pub fn parse_accept_language(header: &str) -> Vec<String> {
+    let mut tags: Vec<(String, f32)> = header
+        .split(',')
+        .filter_map(|part| {
+            let mut pieces = part.split(';');
+            let tag = pieces.next()?.trim();
+            if tag.is_empty() || tag == "*" {
+                return None;
+            }
+            let q = pieces
+                .filter_map(|p| p.trim().strip_prefix("q="))
+                .find_map(|q| q.trim().parse::<f32>().ok())
+                .unwrap_or(1.0);
+            (q > 0.0).then(|| (tag.to_owned(), q))
+        })
+        .take(MAX_PREFERENCES)
+        .collect();
+    tags.sort_by(|a, b| b.1.total_cmp(&a.1));
+    tags.into_iter().map(|(tag, _)| tag).collect()
+}
+
+fn language(tag: &str) -> &str {
+    tag.split(['-', '_']).next().unwrap_or(tag)
+}
+
+/// The string in `map` (a JSON object keyed by locale) for the first of
+/// `preferred` it has, or `None` to use the default string.
+///
+/// Each preference is tried exactly, then by language alone, before moving
+/// on to the next one.
+pub fn resolve<'a>(map: Option<&'a serde_json::Value>, preferred: &[String]) -> Option<&'a str> {
+    let map = map?.as_object()?;
+    preferred.iter().find_map(|want| {
+        let exact = map
+            .iter()
+            .find(|(tag, _)| tag.eq_ignore_ascii_case(want))
+            .and_then(|(_, v)| v.as_str());
+        exact.or_else(|| {
+            map.iter()
+                .find(|(tag, _)| language(tag).eq_ignore_ascii_case(language(want)))
+                .and_then(|(_, v)| v.as_str())
+        })
+    })
+}
+
+/// Messages the server writes itself, by key, then locale. Placeholders are
+/// filled in by the caller (see `routes::welcome` for `member_join`).
+const SYSTEM_TEXT: &[(&str, &[(&str, &str)])] = &[(
+    "member_join",
+    &[
+        ("en-US", "Welcome {user} to {server}!"),
+        ("de", "Willkommen auf {server}, {user}!"),
+        ("fr", "Bienvenue sur {server}, {user} !"),
+        ("es-ES", "¡Te damos la bienvenida a {server}, {user}!"),
+        ("pt-BR", "Boas-vindas ao {server}, {user}!"),
+        ("nl", "Welkom op {server}, {user}!"),
+        ("ja", "{user} さん、{server} へようこそ！"),
+    ],
+)];
+
+/// The server-generated text `key` in `locale`, falling back by language
+/// and then to [`DEFAULT_LOCALE`]. `None` for an unknown key.
+pub fn system_text(key: &str, locale: &str) -> Option<&'static str> {
+    let (_, texts) = SYSTEM_TEXT.iter().find(|(k, _)| *k == key)?;
+    let find = |want: &str| {
+        texts
+            .iter()
+            .find(|(tag, _)| tag.eq_ignore_ascii_case(want))
+            .or_else(|| texts.iter().find(|(tag, _)| language(tag).eq_ignore_ascii_case(language(want))))
+            .map(|(_, text)| *text)
+    };
+    find(locale).or_else(|| find(DEFAULT_LOCALE))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn accept_language_is_ordered_by_weight() {
+        assert_eq!(
+            parse_accept_language("fr;q=0.5, de-CH, *;q=0.1, en;q=0.8, ja;q=0"),
+            ["de-CH", "en", "fr"]
+        );
+        assert!(parse_accept_language("").is_empty());
+    }
+
+    #[test]
+    fn resolves_exact_then_by_language() {
+        let map = json!({ "de": "hallo", "pt-BR": "olá", "es-ES": "hola" });
+        let prefs = |tags: &[&str]| tags.iter().map(|t| t.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(resolve(Some(&map), &prefs(&["de"])), Some("hallo"));
+        assert_eq!(resolve(Some(&map), &prefs(&["DE-at"])), Some("hallo"));
+        assert_eq!(resolve(Some(&map), &prefs(&["pt-PT"])), Some("olá"));
+        assert_eq!(resolve(Some(&map), &prefs(&["ko", "es-419"])), Some("hola"));
+        assert_eq!(resolve(Some(&map), &prefs(&["ko"])), None);
+        assert_eq!(resolve(None, &prefs(&["de"])), None);
+    }
+
+    #[test]
+    fn supported_locales_are_exact() {
+        assert!(is_supported("en-US"));
+        assert!(is_supported("es-419"));
+        assert!(!is_supported("en"));
+        assert!(!is_supported("xx"));
+    }
+
+    #[test]
+    fn system_text_falls_back_to_the_default_locale() {
+        assert_eq!(system_text("member_join", "de"), Some("Willkommen auf {server}, {user}!"));
+        assert_eq!(system_text("member_join", "es-419"), Some("¡Te damos la bienvenida a {server}, {user}!"));
+        assert_eq!(system_text("member_join", "ko"), Some("Welcome {user} to {server}!"));
+        assert_eq!(system_text("no_such_key", "en-US"), None);
+        assert!(validate_locale("ja").is_ok());
+        assert!(validate_locale("japanese").is_err());
+    }
+}
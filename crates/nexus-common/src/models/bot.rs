@@ -18,11 +18,30 @@ pub struct BotApplication {
     pub verified: bool,
     pub is_public: bool,
     pub interactions_endpoint_url: Option<String>,
+    /// [`application_flags`] bits.
     pub flags: i64,
+    /// IPs / CIDR ranges allowed to use the bot token. Empty allows any.
+    pub ip_allowlist: Vec<String>,
+    /// When the token was last regenerated.
+    pub token_reset_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+impl BotApplication {
+    /// Whether the application's bot is sent message bodies.
+    pub fn has_message_content(&self) -> bool {
+        self.flags & application_flags::MESSAGE_CONTENT != 0
+    }
+}
+
+/// Capabilities granted to a bot application, stored in its `flags`.
+pub mod application_flags {
+    /// Message content, embeds and attachments in message events. Without
+    /// it the bot still sees that messages were sent, not what they say.
+    pub const MESSAGE_CONTENT: i64 = 1 << 0;
+}
+
 /// Create a new bot application.
 #[derive(Debug, Deserialize)]
 pub struct CreateBotRequest {
@@ -42,14 +61,26 @@ pub struct UpdateBotRequest {
     pub is_public: Option<bool>,
     pub redirect_uris: Option<Vec<String>>,
     pub interactions_endpoint_url: Option<String>,
+    /// Replace the IP allowlist; an empty list removes the restriction.
+    pub ip_allowlist: Option<Vec<String>>,
+    /// Grant or revoke [`application_flags::MESSAGE_CONTENT`].
+    pub message_content: Option<bool>,
 }
 
-/// Returned when a bot token is regenerated (shown once).
+/// Returned when a bot token is created or regenerated (shown once).
 #[derive(Debug, Serialize)]
 pub struct BotToken {
     pub token: String,
 }
 
+/// Returned when an application's OAuth2 client secret is generated (shown
+/// once).
+#[derive(Debug, Serialize)]
+pub struct OAuth2ClientSecret {
+    pub client_id: Uuid,
+    pub client_secret: String,
+}
+
 /// A bot installed in a server.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BotServerInstall {
@@ -58,12 +58,16 @@ pub struct Channel {
     /// Auto-archive duration for threads (minutes)
     pub auto_archive_duration: Option<i32>,
 
-    /// Whether the thread is archived
+    /// Whether the channel is archived: read-only, and left out of channel
+    /// listings unless asked for
     pub archived: bool,
 
     /// Whether the thread is locked (no new messages)
     pub locked: bool,
 
+    /// For a voice channel's text chat: the voice channel it belongs to
+    pub voice_channel_id: Option<Uuid>,
+
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -104,14 +108,19 @@ pub struct CreateChannelRequest {
 
     pub parent_id: Option<Uuid>,
 
+    #[validate(range(min = 0))]
     pub position: Option<i32>,
 
     pub nsfw: Option<bool>,
 
+    #[validate(range(min = 8000, max = 384000))]
     pub bitrate: Option<i32>,
 
+    #[validate(range(min = 0, max = 99))]
     pub user_limit: Option<i32>,
 
+    /// Slowmode in seconds, up to 6 hours
+    #[validate(range(min = 0, max = 21600))]
     pub rate_limit_per_user: Option<i32>,
 
     /// Enable E2E encryption for this channel
@@ -126,14 +135,19 @@ pub struct UpdateChannelRequest {
     #[validate(length(max = 1024))]
     pub topic: Option<String>,
 
+    #[validate(range(min = 0))]
     pub position: Option<i32>,
 
     pub nsfw: Option<bool>,
 
+    /// Slowmode in seconds, up to 6 hours
+    #[validate(range(min = 0, max = 21600))]
     pub rate_limit_per_user: Option<i32>,
 
+    #[validate(range(min = 8000, max = 384000))]
     pub bitrate: Option<i32>,
 
+    #[validate(range(min = 0, max = 99))]
     pub user_limit: Option<i32>,
 
     pub parent_id: Option<Uuid>,
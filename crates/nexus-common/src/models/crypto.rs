@@ -155,6 +155,87 @@ pub struct E2eeChannel {
     pub last_rotated_at: DateTime<Utc>,
 }
 
+// ============================================================
+// Encrypted Attachments
+// ============================================================
+
+/// A client-encrypted file in an E2EE channel.
+///
+/// The server holds only the ciphertext, under an opaque storage key. The
+/// file key, name and type travel encrypted: the name and type in the
+/// message's `attachment_meta`, the key in each recipient device's envelope
+/// under `attachment_keys.<id>`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EncryptedAttachment {
+    pub id: Uuid,
+    pub channel_id: Uuid,
+    pub uploader_id: Uuid,
+    /// Set once an encrypted message references the upload
+    pub message_id: Option<Uuid>,
+    #[serde(skip_serializing)]
+    pub storage_key: String,
+    /// Ciphertext size in bytes
+    pub size: i64,
+    /// Hex SHA-256 of the ciphertext, so recipients can check the download
+    pub sha256: String,
+    pub created_at: DateTime<Utc>,
+}
+
+// ============================================================
+// Key Backup (secure secret storage)
+// ============================================================
+
+/// The only supported backup algorithm: session keys encrypted to a
+/// Curve25519 public key, with AES-256-CTR + HMAC-SHA-256 per session.
+pub const KEY_BACKUP_ALGORITHM: &str = "nexus.key_backup.v1.curve25519-aes-sha2";
+
+/// A versioned key backup container.
+///
+/// `auth_data` holds the public half of the user's recovery key (and any
+/// signatures over it); the private half never leaves the user's devices.
+/// Only the newest version accepts uploads.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyBackupVersion {
+    pub version: i64,
+    pub algorithm: String,
+    pub auth_data: serde_json::Value,
+    /// Changes whenever the stored sessions change
+    pub etag: i64,
+    /// Number of sessions stored
+    pub count: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+/// One backed-up session key. `session_data` is ciphertext the server
+/// can't read; the other fields let it keep the most useful copy.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyBackupSession {
+    pub channel_id: Uuid,
+    pub session_id: String,
+    /// First message the key can decrypt; lower is more useful
+    pub first_message_index: i32,
+    /// Times the key was forwarded between devices before backup
+    pub forwarded_count: i32,
+    /// Whether the key came from a verified device
+    pub is_verified: bool,
+    pub session_data: serde_json::Value,
+}
+
+impl KeyBackupSession {
+    /// Whether this copy should overwrite `existing`: verified beats
+    /// unverified, then the lower first message index wins, then the fewer
+    /// forwards. Ties keep the existing copy.
+    pub fn should_replace(&self, existing: &KeyBackupSession) -> bool {
+        if self.is_verified != existing.is_verified {
+            return self.is_verified;
+        }
+        if self.first_message_index != existing.first_message_index {
+            return self.first_message_index < existing.first_message_index;
+        }
+        self.forwarded_count < existing.forwarded_count
+    }
+}
+
 // ============================================================
 // Device Verification
 // ============================================================
@@ -244,11 +325,49 @@ pub struct VerifyDeviceRequest {
     pub method: VerificationMethod,
 }
 
+/// Create a new key backup version.
+#[derive(Debug, Deserialize)]
+pub struct CreateKeyBackupRequest {
+    pub algorithm: String,
+    /// Must contain `public_key`: the recovery key's Curve25519 public key
+    pub auth_data: serde_json::Value,
+}
+
+/// Upload session keys to a backup version.
+#[derive(Debug, Deserialize)]
+pub struct UploadKeyBackupRequest {
+    pub sessions: Vec<KeyBackupSession>,
+}
+
+/// Response: a backup's state after its sessions changed.
+#[derive(Debug, Serialize)]
+pub struct KeyBackupEtagResponse {
+    pub etag: i64,
+    pub count: i64,
+}
+
+/// Below this many unconsumed one-time pre-keys the device's owner is sent
+/// `DEVICE_OTK_LOW` and should upload more. Once they run out, new sessions
+/// fall back to X3DH without a one-time pre-key.
+pub const OTPK_LOW_WATERMARK: i64 = 10;
+
 /// Response: how many one-time pre-keys remain for a device.
 #[derive(Debug, Serialize)]
 pub struct OtpkCountResponse {
     pub device_id: Uuid,
     pub remaining: i64,
+    /// [`OTPK_LOW_WATERMARK`]; clients replenish when `remaining` is below it.
+    pub low_watermark: i64,
+}
+
+impl OtpkCountResponse {
+    pub fn new(device_id: Uuid, remaining: i64) -> Self {
+        Self {
+            device_id,
+            remaining,
+            low_watermark: OTPK_LOW_WATERMARK,
+        }
+    }
 }
 
 /// Safety number — a human-verifiable fingerprint of two identity keys.
@@ -260,3 +379,37 @@ pub struct SafetyNumberResponse {
     /// Pre-computed hex fingerprint (SHA-512 of sorted concat of both keys, truncated to 60 digits)
     pub fingerprint: String,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(is_verified: bool, first_message_index: i32, forwarded_count: i32) -> KeyBackupSession {
+        KeyBackupSession {
+            channel_id: Uuid::nil(),
+            session_id: "s".into(),
+            first_message_index,
+            forwarded_count,
+            is_verified,
+            session_data: serde_json::json!({}),
+        }
+    }
+
+    #[test]
+    fn verified_copy_wins_over_earlier_index() {
+        assert!(session(true, 10, 3).should_replace(&session(false, 0, 0)));
+        assert!(!session(false, 0, 0).should_replace(&session(true, 10, 3)));
+    }
+
+    #[test]
+    fn lower_index_then_fewer_forwards_wins() {
+        assert!(session(false, 0, 5).should_replace(&session(false, 4, 0)));
+        assert!(session(false, 4, 0).should_replace(&session(false, 4, 2)));
+        assert!(!session(false, 4, 2).should_replace(&session(false, 4, 0)));
+    }
+
+    #[test]
+    fn ties_keep_existing_copy() {
+        assert!(!session(true, 4, 1).should_replace(&session(true, 4, 1)));
+    }
+}
@@ -0,0 +1,36 @@
+//! Terms of service and privacy policy — the documents an instance publishes
+//! and each user's consent to them.
+//!
+//! Documents carry the version set in `legal.version`. Consent is recorded
+//! per user and version, so publishing a new version asks everyone again
+//! while keeping a record of what each user agreed to before.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// A published document.
+#[derive(Debug, Clone, Serialize)]
+pub struct LegalDocument {
+    pub version: String,
+    /// Markdown.
+    pub content: String,
+}
+
+/// Where a user stands with the current terms.
+#[derive(Debug, Clone, Serialize)]
+pub struct ConsentStatus {
+    /// Version consent is required for; `None` when the instance requires none.
+    pub required_version: Option<String>,
+    /// Newest version the user consented to.
+    pub consented_version: Option<String>,
+    pub consented_at: Option<DateTime<Utc>>,
+    /// Whether the user may use the API without consenting again.
+    pub up_to_date: bool,
+}
+
+/// Body of `POST /legal/consent`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ConsentRequest {
+    /// The version the user was shown; must be the current one.
+    pub version: String,
+}
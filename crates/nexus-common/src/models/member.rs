@@ -30,6 +30,10 @@ pub struct Member {
 
     /// Communication timeout (mute until this time)
     pub communication_disabled_until: Option<DateTime<Utc>>,
+
+    /// Still awaiting membership screening (rules not yet accepted)
+    #[serde(default)]
+    pub pending: bool,
 }
 
 #[derive(Debug, Serialize)]
@@ -40,6 +44,7 @@ pub struct MemberResponse {
     pub avatar: Option<String>,
     pub roles: Vec<Uuid>,
     pub joined_at: DateTime<Utc>,
+    pub pending: bool,
 }
 
 impl From<Member> for MemberResponse {
@@ -51,6 +56,7 @@ impl From<Member> for MemberResponse {
             avatar: m.avatar,
             roles: m.roles,
             joined_at: m.joined_at,
+            pending: m.pending,
         }
     }
 }
@@ -4,6 +4,7 @@
 //! and replicated to MeiliSearch for full-text search.
 //! Messages support rich formatting, embeds, attachments, reactions, and threads.
 
+use bitflags::bitflags;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
@@ -20,7 +21,8 @@ pub struct Message {
     /// Author user ID
     pub author_id: Uuid,
 
-    /// Message content (Markdown-flavored, up to configurable limit)
+    /// Message content (Markdown-flavored, up to configurable limit).
+    /// May embed `<t:UNIX:STYLE>` timestamps; see [`crate::timestamps`].
     pub content: String,
 
     /// Message type
@@ -89,8 +91,43 @@ pub enum MessageType {
     Boost,
 }
 
+bitflags! {
+    /// Per-message behaviour flags, stored in `messages.flags`.
+    ///
+    /// Bit positions match Discord's so bridged and bot clients can pass
+    /// them through unchanged.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+    pub struct MessageFlags: i32 {
+        /// Don't generate link previews for this message.
+        const SUPPRESS_EMBEDS        = 1 << 2;
+        /// Silent message: no mention counts, no push notifications.
+        const SUPPRESS_NOTIFICATIONS = 1 << 12;
+    }
+}
+
+impl MessageFlags {
+    /// Flags a client may set when creating a message.
+    pub const CLIENT_SETTABLE: Self = Self::SUPPRESS_EMBEDS.union(Self::SUPPRESS_NOTIFICATIONS);
+
+    /// Whether mention counts and push notifications should be skipped.
+    pub fn is_silent(self) -> bool {
+        self.contains(Self::SUPPRESS_NOTIFICATIONS)
+    }
+
+    /// Whether link previews should be skipped.
+    pub fn suppresses_embeds(self) -> bool {
+        self.contains(Self::SUPPRESS_EMBEDS)
+    }
+}
+
+/// Content prefix that marks a message as silent, e.g. `@silent brb`.
+pub const SILENT_PREFIX: &str = "@silent";
+
+/// Filename prefix that marks an uploaded file as a spoiler.
+pub const SPOILER_FILENAME_PREFIX: &str = "SPOILER_";
+
 /// Rich embed — for link previews, bot embeds, etc.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Embed {
     pub title: Option<String>,
     pub description: Option<String>,
@@ -102,6 +139,7 @@ pub struct Embed {
     pub thumbnail: Option<EmbedMedia>,
     pub video: Option<EmbedMedia>,
     pub author: Option<EmbedAuthor>,
+    #[serde(default)]
     pub fields: Vec<EmbedField>,
 }
 
@@ -171,9 +209,11 @@ pub struct MessageReference {
 }
 
 /// Create message request.
-#[derive(Debug, Deserialize, Validate)]
+#[derive(Debug, Default, Serialize, Deserialize, Validate)]
 pub struct CreateMessageRequest {
+    /// Timestamp tokens (`<t:UNIX:STYLE>`) must be well-formed
     #[validate(length(min = 1, max = 4000, message = "Message must be 1-4000 characters"))]
+    #[validate(custom(function = "crate::timestamps::validate_tokens"))]
     pub content: String,
 
     /// Reply to a message
@@ -185,14 +225,46 @@ pub struct CreateMessageRequest {
     /// Whether to suppress embeds
     pub suppress_embeds: Option<bool>,
 
+    /// Send without notifying anyone (same as an `@silent` prefix)
+    pub silent: Option<bool>,
+
+    /// Raw [`MessageFlags`] bits; only [`MessageFlags::CLIENT_SETTABLE`] are kept
+    pub flags: Option<i32>,
+
+    /// Already-uploaded attachments (from `attachment_ids`) to mark as spoilers
+    pub spoiler_attachment_ids: Option<Vec<Uuid>>,
+
     /// If channel is E2EE, encrypted content bytes
     pub encrypted_content: Option<Vec<u8>>,
     pub encryption_metadata: Option<serde_json::Value>,
 }
 
+impl CreateMessageRequest {
+    /// Resolve the flags for the new message and strip an `@silent` prefix
+    /// from the content.
+    pub fn take_flags(&mut self) -> MessageFlags {
+        let mut flags = MessageFlags::from_bits_truncate(self.flags.unwrap_or(0))
+            & MessageFlags::CLIENT_SETTABLE;
+        if self.suppress_embeds == Some(true) {
+            flags |= MessageFlags::SUPPRESS_EMBEDS;
+        }
+        if self.silent == Some(true) {
+            flags |= MessageFlags::SUPPRESS_NOTIFICATIONS;
+        }
+        if let Some(rest) = self.content.strip_prefix(SILENT_PREFIX) {
+            if rest.is_empty() || rest.starts_with(char::is_whitespace) {
+                self.content = rest.trim_start().to_owned();
+                flags |= MessageFlags::SUPPRESS_NOTIFICATIONS;
+            }
+        }
+        flags
+    }
+}
+
 #[derive(Debug, Deserialize, Validate)]
 pub struct UpdateMessageRequest {
     #[validate(length(min = 1, max = 4000))]
+    #[validate(custom(function = "crate::timestamps::validate_tokens"))]
     pub content: Option<String>,
 }
 
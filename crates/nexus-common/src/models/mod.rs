@@ -6,26 +6,32 @@
 pub mod bot;
 pub mod channel;
 pub mod crypto;
+pub mod legal;
 pub mod member;
 pub mod message;
 pub mod plugin;
+pub mod push;
 pub mod rich;
 pub mod role;
 pub mod server;
 pub mod slash_command;
 pub mod user;
+pub mod user_settings;
 pub mod webhook;
 
 /// Re-export all model types for convenience.
 pub use bot::*;
 pub use channel::*;
 pub use crypto::*;
+pub use legal::*;
 pub use member::*;
 pub use message::*;
 pub use plugin::*;
+pub use push::*;
 pub use rich::*;
 pub use role::*;
 pub use server::*;
 pub use slash_command::*;
 pub use user::*;
+pub use user_settings::*;
 pub use webhook::*;
@@ -0,0 +1,128 @@
+//! Push notification models — device tokens and per-user preferences.
+//!
+//! Users who are offline (no live gateway session) get DMs and direct
+//! mentions delivered through WebPush, Firebase Cloud Messaging or APNs,
+//! depending on how each device registered.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+/// Delivery service a push token belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PushPlatform {
+    /// Browser push subscription (RFC 8030); the token is the endpoint URL.
+    WebPush,
+    /// Firebase Cloud Messaging registration token (Android, web).
+    Fcm,
+    /// Apple Push Notification service device token (iOS, macOS).
+    Apns,
+}
+
+impl PushPlatform {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::WebPush => "web_push",
+            Self::Fcm => "fcm",
+            Self::Apns => "apns",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "web_push" => Some(Self::WebPush),
+            "fcm" => Some(Self::Fcm),
+            "apns" => Some(Self::Apns),
+            _ => None,
+        }
+    }
+}
+
+/// A device registered for push delivery.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PushToken {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub platform: PushPlatform,
+
+    /// FCM/APNs device token, or the WebPush endpoint URL
+    pub token: String,
+
+    /// WebPush subscription keys (base64url); never returned to clients
+    #[serde(skip_serializing)]
+    pub p256dh: Option<String>,
+    #[serde(skip_serializing)]
+    pub auth: Option<String>,
+
+    /// Client-supplied label, e.g. "Pixel 8" or "Firefox on laptop"
+    pub device_name: Option<String>,
+
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+/// WebPush subscription keys, as found in `PushSubscription.toJSON().keys`.
+#[derive(Debug, Deserialize, Validate)]
+pub struct WebPushKeys {
+    /// Client's P-256 public key (base64url, uncompressed point)
+    #[validate(length(min = 80, max = 100))]
+    pub p256dh: String,
+
+    /// 16-byte authentication secret (base64url)
+    #[validate(length(min = 16, max = 32))]
+    pub auth: String,
+}
+
+/// Register push token request.
+#[derive(Debug, Deserialize, Validate)]
+pub struct RegisterPushTokenRequest {
+    pub platform: PushPlatform,
+
+    #[validate(length(min = 1, max = 2048))]
+    pub token: String,
+
+    /// Required for `web_push`
+    #[validate(nested)]
+    pub keys: Option<WebPushKeys>,
+
+    #[validate(length(max = 100))]
+    pub device_name: Option<String>,
+}
+
+/// What a user wants pushed to their devices while offline.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PushPreferences {
+    /// Master switch
+    pub enabled: bool,
+
+    /// Messages in DMs and group DMs
+    pub direct_messages: bool,
+
+    /// Messages in server channels that @mention the user
+    pub mentions: bool,
+
+    /// Include the message text; when off, notifications only say who wrote
+    pub show_content: bool,
+}
+
+impl Default for PushPreferences {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            direct_messages: true,
+            mentions: true,
+            show_content: true,
+        }
+    }
+}
+
+/// Update push preferences request.
+#[derive(Debug, Deserialize)]
+pub struct UpdatePushPreferencesRequest {
+    pub enabled: Option<bool>,
+    pub direct_messages: Option<bool>,
+    pub mentions: Option<bool>,
+    pub show_content: Option<bool>,
+}
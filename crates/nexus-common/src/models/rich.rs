@@ -39,6 +39,9 @@ pub struct Thread {
     pub archived_at: Option<DateTime<Utc>>,
     pub locked: bool,
 
+    /// Pinned forum posts are listed first
+    pub pinned: bool,
+
     /// Optional forum-style tags
     pub tags: Vec<String>,
 
@@ -59,6 +62,7 @@ pub struct ThreadRow {
     pub archived: bool,
     pub archived_at: Option<DateTime<Utc>>,
     pub locked: bool,
+    pub pinned: bool,
     pub tags: Vec<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
@@ -93,6 +97,86 @@ pub struct UpdateThreadRequest {
     pub tags: Option<Vec<String>>,
 }
 
+// ============================================================
+// Forums
+// ============================================================
+
+/// A tag that can be applied to posts in a forum channel.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ForumTag {
+    pub id: Uuid,
+    pub channel_id: Uuid,
+    pub name: String,
+
+    /// Unicode emoji or custom emoji ID shown next to the name
+    pub emoji: Option<String>,
+
+    /// Only members with MANAGE_THREADS can apply moderated tags
+    pub moderated: bool,
+
+    pub position: i32,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Create forum tag request.
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateForumTagRequest {
+    #[validate(length(min = 1, max = 20, message = "Tag name must be 1-20 characters"))]
+    pub name: String,
+
+    #[validate(length(max = 64))]
+    pub emoji: Option<String>,
+
+    pub moderated: Option<bool>,
+    pub position: Option<i32>,
+}
+
+/// Update forum tag request.
+#[derive(Debug, Deserialize, Validate)]
+pub struct UpdateForumTagRequest {
+    #[validate(length(min = 1, max = 20, message = "Tag name must be 1-20 characters"))]
+    pub name: Option<String>,
+
+    #[validate(length(max = 64))]
+    pub emoji: Option<String>,
+
+    pub moderated: Option<bool>,
+    pub position: Option<i32>,
+}
+
+/// Create forum post request — a thread plus its opening message.
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateForumPostRequest {
+    #[validate(length(min = 1, max = 100, message = "Post title must be 1-100 characters"))]
+    pub title: String,
+
+    #[validate(length(
+        min = 1,
+        max = 4000,
+        message = "Post content must be 1-4000 characters"
+    ))]
+    #[validate(custom(function = "crate::timestamps::validate_tokens"))]
+    pub content: String,
+
+    /// Forum tag IDs to apply (at most 5)
+    #[validate(length(max = 5, message = "At most 5 tags can be applied"))]
+    pub tag_ids: Option<Vec<Uuid>>,
+
+    /// Auto-archive threshold in minutes (60, 1440, 4320, or 10080)
+    pub auto_archive_minutes: Option<i32>,
+}
+
+/// How forum posts are ordered. Pinned posts always come first.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ForumSortOrder {
+    /// Most recently active first
+    #[default]
+    LatestActivity,
+    /// Newest post first
+    CreationDate,
+}
+
 // ============================================================
 // Custom Emoji
 // ============================================================
@@ -191,6 +275,9 @@ pub struct AttachmentRow {
     pub blurhash: Option<String>,
     pub sha256: Option<String>,
     pub status: String,
+    /// Storage key and URL of the generated thumbnail (images only)
+    pub thumbnail_key: Option<String>,
+    pub thumbnail_url: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -238,8 +325,39 @@ pub struct ActivityUpdate {
     pub url: Option<String>,
     pub large_image: Option<String>,
     pub small_image: Option<String>,
+    /// The game party the user is in, if any.
+    pub party: Option<ActivityParty>,
+    /// Secrets handed to other users through the activity-join flow.
+    pub secrets: Option<ActivitySecrets>,
+}
+
+/// A game party shown on a rich presence activity.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActivityParty {
+    pub id: Option<String>,
+    /// Current party size.
+    pub size: Option<i32>,
+    /// Maximum party size.
+    pub max: Option<i32>,
 }
 
+impl ActivityParty {
+    /// Whether the party has no room left.
+    pub fn is_full(&self) -> bool {
+        matches!((self.size, self.max), (Some(size), Some(max)) if size >= max)
+    }
+}
+
+/// Game-defined secrets for an activity. Never returned in presence.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ActivitySecrets {
+    /// Passed to the game of a user who joins this activity.
+    pub join: Option<String>,
+}
+
+/// Longest accepted activity secret.
+pub const MAX_ACTIVITY_SECRET_LEN: usize = 128;
+
 use std::sync::LazyLock;
 static EMOJI_NAME_REGEX: LazyLock<regex::Regex> =
     LazyLock::new(|| regex::Regex::new(r"^[a-zA-Z0-9_]+$").unwrap());
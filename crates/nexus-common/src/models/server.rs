@@ -49,6 +49,15 @@ pub struct Server {
     /// Max file upload size override (server admins can set this)
     pub max_file_size: Option<i64>,
 
+    /// Keep the previous content of edited messages (off for privacy)
+    pub message_history: bool,
+
+    /// Language of server-generated messages (see [`crate::locale`])
+    pub preferred_locale: String,
+
+    /// Purge messages older than this many days; 0 keeps them forever
+    pub message_retention_days: i32,
+
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -64,6 +73,7 @@ pub struct CreateServerRequest {
     pub is_public: Option<bool>,
 
     /// Template to clone from (pre-built channel structures)
+    #[validate(length(max = 64))]
     pub template: Option<String>,
 }
 
@@ -77,10 +87,48 @@ pub struct UpdateServerRequest {
 
     pub is_public: Option<bool>,
 
+    #[validate(length(max = 64))]
     pub region: Option<String>,
+
+    /// Keep edit history for messages in this server
+    pub message_history: Option<bool>,
+
+    #[validate(custom(function = "crate::locale::validate_locale"))]
+    pub preferred_locale: Option<String>,
+
+    /// Purge messages after this many days (0 = never)
+    #[validate(range(min = 0, max = 3650))]
+    pub message_retention_days: Option<i32>,
 }
 
-#[derive(Debug, Serialize)]
+/// The editable server settings, as snapshotted in each settings version.
+///
+/// Rolling back to a version writes every field back, including clearing
+/// the description if it was empty at the time.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ServerSettings {
+    pub name: String,
+    pub description: Option<String>,
+    pub is_public: bool,
+    pub message_history: bool,
+    pub preferred_locale: String,
+    pub message_retention_days: i32,
+}
+
+impl From<&Server> for ServerSettings {
+    fn from(s: &Server) -> Self {
+        Self {
+            name: s.name.clone(),
+            description: s.description.clone(),
+            is_public: s.is_public,
+            message_history: s.message_history,
+            preferred_locale: s.preferred_locale.clone(),
+            message_retention_days: s.message_retention_days,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ServerResponse {
     pub id: Uuid,
     pub name: String,
@@ -92,6 +140,9 @@ pub struct ServerResponse {
     pub is_public: bool,
     pub vanity_code: Option<String>,
     pub member_count: i32,
+    pub message_history: bool,
+    pub preferred_locale: String,
+    pub message_retention_days: i32,
     pub created_at: DateTime<Utc>,
 }
 
@@ -108,6 +159,9 @@ impl From<Server> for ServerResponse {
             is_public: s.is_public,
             vanity_code: s.vanity_code,
             member_count: s.member_count,
+            message_history: s.message_history,
+            preferred_locale: s.preferred_locale,
+            message_retention_days: s.message_retention_days,
             created_at: s.created_at,
         }
     }
@@ -129,10 +183,12 @@ pub struct Invite {
     pub created_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Validate)]
 pub struct CreateInviteRequest {
     /// Max number of uses (0 = unlimited)
+    #[validate(range(min = 0, max = 100))]
     pub max_uses: Option<i32>,
-    /// Duration in seconds (0 = never expires)
+    /// Duration in seconds (0 = never expires), at most 7 days
+    #[validate(range(max = 604_800))]
     pub max_age_secs: Option<u64>,
 }
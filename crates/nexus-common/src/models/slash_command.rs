@@ -3,6 +3,9 @@
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
+use validator::{Validate, ValidationError};
+
+use crate::locale;
 
 /// Application command type.
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
@@ -30,21 +33,40 @@ pub enum OptionType {
 }
 
 /// A choice for a String/Integer/Number option.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
 pub struct CommandChoice {
     pub name: String,
+    /// `{locale: name}`, see [`locale::SUPPORTED_LOCALES`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[validate(custom(function = "validate_description_localizations"))]
+    pub name_localizations: Option<serde_json::Value>,
+    /// `name` in the caller's locale, set when listing for a client
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name_localized: Option<String>,
     pub value: serde_json::Value,
 }
 
 /// A command option (parameter or subcommand).
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
 pub struct CommandOption {
     #[serde(rename = "type")]
     pub option_type: OptionType,
     pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[validate(custom(function = "validate_name_localizations"))]
+    pub name_localizations: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name_localized: Option<String>,
     pub description: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[validate(custom(function = "validate_description_localizations"))]
+    pub description_localizations: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description_localized: Option<String>,
     pub required: Option<bool>,
+    #[validate(nested)]
     pub choices: Option<Vec<CommandChoice>>,
+    #[validate(nested)]
     pub options: Option<Vec<CommandOption>>,
     pub min_value: Option<serde_json::Value>,
     pub max_value: Option<serde_json::Value>,
@@ -59,8 +81,13 @@ pub struct SlashCommand {
     pub server_id: Option<Uuid>,
     pub name: String,
     pub name_localizations: Option<serde_json::Value>,
+    /// `name` in the caller's locale, set when listing for a client
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name_localized: Option<String>,
     pub description: String,
     pub description_localizations: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description_localized: Option<String>,
     pub options: Vec<CommandOption>,
     pub default_member_permissions: Option<String>,
     pub dm_permission: bool,
@@ -71,19 +98,87 @@ pub struct SlashCommand {
     pub updated_at: DateTime<Utc>,
 }
 
+impl SlashCommand {
+    /// Fill in the `*_localized` fields, here and in every option and
+    /// choice, from the first of `preferred` locales each map has.
+    pub fn localize(&mut self, preferred: &[String]) {
+        self.name_localized = locale::resolve(self.name_localizations.as_ref(), preferred).map(str::to_owned);
+        self.description_localized =
+            locale::resolve(self.description_localizations.as_ref(), preferred).map(str::to_owned);
+        localize_options(&mut self.options, preferred);
+    }
+}
+
+fn localize_options(options: &mut [CommandOption], preferred: &[String]) {
+    for option in options {
+        option.name_localized = locale::resolve(option.name_localizations.as_ref(), preferred).map(str::to_owned);
+        option.description_localized =
+            locale::resolve(option.description_localizations.as_ref(), preferred).map(str::to_owned);
+        for choice in option.choices.iter_mut().flatten() {
+            choice.name_localized = locale::resolve(choice.name_localizations.as_ref(), preferred).map(str::to_owned);
+        }
+        if let Some(nested) = option.options.as_mut() {
+            localize_options(nested, preferred);
+        }
+    }
+}
+
 /// Register or update a slash command.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Validate)]
 pub struct UpsertCommandRequest {
     pub name: String,
     pub description: String,
+    #[validate(nested)]
     pub options: Option<Vec<CommandOption>>,
     pub default_member_permissions: Option<String>,
     pub dm_permission: Option<bool>,
     pub command_type: Option<i32>,
+    /// `{locale: name}`, keys from [`locale::SUPPORTED_LOCALES`]
+    #[validate(custom(function = "validate_name_localizations"))]
     pub name_localizations: Option<serde_json::Value>,
+    #[validate(custom(function = "validate_description_localizations"))]
     pub description_localizations: Option<serde_json::Value>,
 }
 
+/// Longest localized command or option name, in characters.
+pub const MAX_NAME_CHARS: usize = 32;
+/// Longest localized description or choice name, in characters.
+pub const MAX_DESCRIPTION_CHARS: usize = 100;
+
+fn validate_name_localizations(map: &serde_json::Value) -> Result<(), ValidationError> {
+    validate_localizations(map, MAX_NAME_CHARS)
+}
+
+fn validate_description_localizations(map: &serde_json::Value) -> Result<(), ValidationError> {
+    validate_localizations(map, MAX_DESCRIPTION_CHARS)
+}
+
+/// An object keyed by supported locales, each value a non-empty string of
+/// at most `max_chars`.
+fn validate_localizations(map: &serde_json::Value, max_chars: usize) -> Result<(), ValidationError> {
+    let fail = |code: &'static str, message: String| Err(ValidationError::new(code).with_message(message.into()));
+
+    if map.is_null() {
+        return Ok(());
+    }
+    let Some(map) = map.as_object() else {
+        return fail("localizations", "Localizations must be an object keyed by locale".into());
+    };
+    for (tag, value) in map {
+        if !locale::is_supported(tag) {
+            return fail("unknown_locale", format!("'{tag}' is not a supported locale"));
+        }
+        let Some(text) = value.as_str() else {
+            return fail("localizations", format!("The '{tag}' localization must be a string"));
+        };
+        let chars = text.chars().count();
+        if chars == 0 || chars > max_chars {
+            return fail("length", format!("The '{tag}' localization must be 1-{max_chars} characters"));
+        }
+    }
+    Ok(())
+}
+
 /// Interaction data sent from client to bot via the interactions endpoint.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Interaction {
@@ -125,3 +220,78 @@ pub struct InteractionResponse {
     pub response_type: i32,
     pub data: Option<serde_json::Value>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn request(body: serde_json::Value) -> UpsertCommandRequest {
+        serde_json::from_value(body).unwrap()
+    }
+
+    #[test]
+    fn localization_keys_must_be_supported() {
+        let ok = request(json!({
+            "name": "ping", "description": "Ping",
+            "name_localizations": { "de": "pingen", "pt-BR": "pingar" },
+        }));
+        assert!(ok.validate().is_ok());
+
+        let bad = request(json!({
+            "name": "ping", "description": "Ping",
+            "name_localizations": { "klingon": "pIng" },
+        }));
+        let errors = crate::validation::field_errors(&bad.validate().unwrap_err());
+        assert_eq!(errors[0].field, "name_localizations");
+        assert_eq!(errors[0].code, "unknown_locale");
+    }
+
+    #[test]
+    fn nested_localizations_are_validated() {
+        let bad = request(json!({
+            "name": "roll", "description": "Roll dice",
+            "options": [{
+                "type": "Integer", "name": "sides", "description": "Sides",
+                "name_localizations": { "fr": "x".repeat(33) },
+            }],
+        }));
+        let errors = crate::validation::field_errors(&bad.validate().unwrap_err());
+        assert_eq!(errors[0].field, "options[0].name_localizations");
+        assert_eq!(errors[0].code, "length");
+    }
+
+    #[test]
+    fn localize_fills_every_level() {
+        let option: CommandOption = serde_json::from_value(json!({
+            "type": "String", "name": "color", "description": "Color",
+            "description_localizations": { "de": "Farbe" },
+            "choices": [{ "name": "red", "name_localizations": { "de": "rot" }, "value": "red" }],
+        }))
+        .unwrap();
+        let mut cmd = SlashCommand {
+            id: Uuid::nil(),
+            application_id: Uuid::nil(),
+            server_id: None,
+            name: "paint".into(),
+            name_localizations: Some(json!({ "de": "malen" })),
+            name_localized: None,
+            description: "Paint".into(),
+            description_localizations: None,
+            description_localized: None,
+            options: vec![option],
+            default_member_permissions: None,
+            dm_permission: true,
+            command_type: 1,
+            version: Uuid::nil(),
+            enabled: true,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+        cmd.localize(&["de-AT".to_owned()]);
+        assert_eq!(cmd.name_localized.as_deref(), Some("malen"));
+        assert_eq!(cmd.description_localized, None);
+        assert_eq!(cmd.options[0].description_localized.as_deref(), Some("Farbe"));
+        assert_eq!(cmd.options[0].choices.as_ref().unwrap()[0].name_localized.as_deref(), Some("rot"));
+    }
+}
@@ -46,6 +46,10 @@ pub struct User {
     /// User flags (bitfield: staff, verified, bot, etc.)
     pub flags: i64,
 
+    /// Language for server-generated text and localized commands
+    /// (see [`crate::locale`])
+    pub locale: String,
+
     /// Account creation timestamp
     pub created_at: DateTime<Utc>,
 
@@ -70,6 +74,52 @@ pub enum UserPresence {
     Offline,
 }
 
+impl UserPresence {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            UserPresence::Online => "online",
+            UserPresence::Idle => "idle",
+            UserPresence::DoNotDisturb => "do_not_disturb",
+            UserPresence::Invisible => "invisible",
+            UserPresence::Offline => "offline",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        Some(match s {
+            "online" => UserPresence::Online,
+            "idle" => UserPresence::Idle,
+            "do_not_disturb" => UserPresence::DoNotDisturb,
+            "invisible" => UserPresence::Invisible,
+            "offline" => UserPresence::Offline,
+            _ => return None,
+        })
+    }
+
+    /// A user's presence given the status of each of their sessions.
+    /// Choices made on any device (invisible, do not disturb) beat automatic
+    /// states, and an active device beats an idle one; no sessions is
+    /// `Offline`.
+    pub fn aggregate(sessions: impl IntoIterator<Item = UserPresence>) -> UserPresence {
+        let rank = |p: &UserPresence| match p {
+            UserPresence::Invisible => 0,
+            UserPresence::DoNotDisturb => 1,
+            UserPresence::Online => 2,
+            UserPresence::Idle => 3,
+            UserPresence::Offline => 4,
+        };
+        sessions.into_iter().min_by_key(rank).unwrap_or(UserPresence::Offline)
+    }
+
+    /// What other users see: invisible users appear offline.
+    pub fn public(self) -> UserPresence {
+        match self {
+            UserPresence::Invisible => UserPresence::Offline,
+            other => other,
+        }
+    }
+}
+
 /// Bitflags for user account flags.
 pub mod user_flags {
     /// Nexus team member
@@ -86,10 +136,18 @@ pub mod user_flags {
     pub const DISABLED: i64 = 1 << 5;
     /// Account suspended by moderation
     pub const SUSPENDED: i64 = 1 << 6;
+    /// Registered while registration needs approval, and not yet approved
+    pub const PENDING_APPROVAL: i64 = 1 << 7;
+    /// The built-in system account (see [`super::SYSTEM_USER_ID`])
+    pub const SYSTEM: i64 = 1 << 8;
 }
 
+/// The built-in account that authors messages no person sent, such as
+/// welcome DMs. It is created by a migration and can't log in.
+pub const SYSTEM_USER_ID: Uuid = Uuid::from_u128(1);
+
 /// Registration request — minimal by design. No ID, no phone, no nonsense.
-#[derive(Debug, Deserialize, Validate)]
+#[derive(Debug, Serialize, Deserialize, Validate)]
 pub struct CreateUserRequest {
     #[validate(length(min = 3, max = 32, message = "Username must be 3-32 characters"))]
     #[validate(regex(
@@ -107,10 +165,23 @@ pub struct CreateUserRequest {
 
     /// Optional invite code
     pub invite_code: Option<String>,
+
+    /// Single-use token from staff; required when registration is invite-only
+    pub registration_token: Option<String>,
+
+    /// Why the user wants to join, shown to staff when registration needs approval
+    #[validate(length(max = 1000, message = "Reason must be at most 1000 characters"))]
+    pub reason: Option<String>,
+
+    /// Proof-of-work challenge being answered, as issued by `GET /auth/registration`
+    pub challenge: Option<String>,
+
+    /// The proof-of-work solution, or the hCaptcha response token
+    pub challenge_response: Option<String>,
 }
 
 /// Login request
-#[derive(Debug, Deserialize, Validate)]
+#[derive(Debug, Serialize, Deserialize, Validate)]
 pub struct LoginRequest {
     #[validate(length(min = 3, max = 32))]
     pub username: String,
@@ -120,7 +191,7 @@ pub struct LoginRequest {
 }
 
 /// Safe user representation for API responses (no sensitive fields)
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UserResponse {
     pub id: Uuid,
     pub username: String,
@@ -131,9 +202,23 @@ pub struct UserResponse {
     pub status: Option<String>,
     pub presence: UserPresence,
     pub flags: i64,
+    /// Only shown to the user themselves
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
     pub created_at: DateTime<Utc>,
 }
 
+impl UserResponse {
+    /// The profile as the user themselves sees it, locale included.
+    pub fn own(user: User) -> Self {
+        let locale = user.locale.clone();
+        Self {
+            locale: Some(locale),
+            ..user.into()
+        }
+    }
+}
+
 impl From<User> for UserResponse {
     fn from(u: User) -> Self {
         Self {
@@ -146,6 +231,7 @@ impl From<User> for UserResponse {
             status: u.status,
             presence: u.presence,
             flags: u.flags,
+            locale: None,
             created_at: u.created_at,
         }
     }
@@ -154,7 +240,11 @@ impl From<User> for UserResponse {
 /// Update profile request
 #[derive(Debug, Deserialize, Validate)]
 pub struct UpdateUserRequest {
-    #[validate(length(min = 3, max = 32))]
+    #[validate(length(min = 3, max = 32, message = "Username must be 3-32 characters"))]
+    #[validate(regex(
+        path = *USERNAME_REGEX,
+        message = "Username can only contain letters, numbers, underscores, and hyphens"
+    ))]
     pub username: Option<String>,
 
     #[validate(length(max = 64))]
@@ -167,8 +257,27 @@ pub struct UpdateUserRequest {
     pub status: Option<String>,
 
     pub presence: Option<UserPresence>,
+
+    #[validate(custom(function = "crate::locale::validate_locale"))]
+    pub locale: Option<String>,
 }
 
 use std::sync::LazyLock;
 static USERNAME_REGEX: LazyLock<regex::Regex> =
     LazyLock::new(|| regex::Regex::new(r"^[a-zA-Z0-9_-]+$").unwrap());
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn presence_aggregates_across_sessions() {
+        use UserPresence::*;
+        assert_eq!(UserPresence::aggregate([]), Offline);
+        assert_eq!(UserPresence::aggregate([Idle, Online]), Online);
+        assert_eq!(UserPresence::aggregate([Online, DoNotDisturb, Idle]), DoNotDisturb);
+        assert_eq!(UserPresence::aggregate([DoNotDisturb, Invisible]), Invisible);
+        assert_eq!(Invisible.public(), Offline);
+        assert_eq!(UserPresence::parse(DoNotDisturb.as_str()), Some(DoNotDisturb));
+    }
+}
@@ -0,0 +1,285 @@
+//! Synced user settings — client preferences that follow a user across
+//! desktop, web and mobile.
+//!
+//! These are the server list layout — the order of servers in the sidebar
+//! and the folders (name, color) they are grouped into — and the volume
+//! and local mute the user set for other people in voice.
+
+use std::collections::{BTreeMap, HashSet};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::{Validate, ValidationError};
+
+/// Most folders in a server list.
+pub const MAX_SERVER_FOLDERS: usize = 100;
+/// Longest folder name, in characters.
+pub const MAX_FOLDER_NAME_CHARS: usize = 32;
+
+/// One entry of the server sidebar, top to bottom.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerListItem {
+    /// A server outside any folder
+    Server { id: Uuid },
+    Folder(ServerFolder),
+}
+
+/// A named group of servers in the sidebar.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ServerFolder {
+    /// Client-generated, stable across renames
+    pub id: Uuid,
+    pub name: Option<String>,
+    /// RGB color as `0xRRGGBB`
+    pub color: Option<u32>,
+    /// Servers in the folder, in display order
+    pub server_ids: Vec<Uuid>,
+}
+
+/// How a user's server list is ordered and grouped.
+///
+/// Servers the user is in but that the layout doesn't mention (e.g. joined
+/// from another client that hasn't synced yet) are shown after it.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, Validate)]
+pub struct ServerLayout {
+    /// Top-level entries, at most 500
+    #[validate(length(max = 500), custom(function = "validate_items"))]
+    pub items: Vec<ServerListItem>,
+}
+
+impl ServerLayout {
+    /// Drop servers the user is no longer a member of, and folders that are
+    /// left empty by it.
+    pub fn retain_servers(&mut self, member_of: &HashSet<Uuid>) {
+        self.items.retain_mut(|item| match item {
+            ServerListItem::Server { id } => member_of.contains(id),
+            ServerListItem::Folder(folder) => {
+                folder.server_ids.retain(|id| member_of.contains(id));
+                !folder.server_ids.is_empty()
+            }
+        });
+    }
+
+    /// Every server the layout places, in display order.
+    pub fn server_ids(&self) -> impl Iterator<Item = Uuid> + '_ {
+        self.items.iter().flat_map(|item| match item {
+            ServerListItem::Server { id } => std::slice::from_ref(id),
+            ServerListItem::Folder(folder) => folder.server_ids.as_slice(),
+        })
+        .copied()
+    }
+}
+
+/// Each server appears once, folder ids are unique, and folder names and
+/// colors are in range.
+fn validate_items(items: &[ServerListItem]) -> Result<(), ValidationError> {
+    let fail = |code: &'static str, message: &'static str| {
+        let mut e = ValidationError::new(code);
+        e.message = Some(message.into());
+        Err(e)
+    };
+
+    let mut servers = HashSet::new();
+    let mut folders = HashSet::new();
+    for item in items {
+        match item {
+            ServerListItem::Server { id } => {
+                if !servers.insert(*id) {
+                    return fail("duplicate_server", "A server can only appear once in the layout");
+                }
+            }
+            ServerListItem::Folder(folder) => {
+                if !folders.insert(folder.id) {
+                    return fail("duplicate_folder", "Folder ids must be unique");
+                }
+                if folder
+                    .name
+                    .as_ref()
+                    .is_some_and(|n| n.chars().count() > MAX_FOLDER_NAME_CHARS)
+                {
+                    return fail("folder_name", "Folder names can be at most 32 characters");
+                }
+                if folder.color.is_some_and(|c| c > 0xFF_FFFF) {
+                    return fail("folder_color", "Folder colors must be 0xRRGGBB");
+                }
+                for id in &folder.server_ids {
+                    if !servers.insert(*id) {
+                        return fail("duplicate_server", "A server can only appear once in the layout");
+                    }
+                }
+            }
+        }
+    }
+    if folders.len() > MAX_SERVER_FOLDERS {
+        return fail("too_many_folders", "A server list can have at most 100 folders");
+    }
+    Ok(())
+}
+
+/// Most users a volume can be saved for.
+pub const MAX_USER_VOLUMES: usize = 1000;
+
+/// How loud another user is played back locally. Nothing changes for
+/// anyone else in the call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Validate)]
+pub struct UserVolume {
+    /// Percent of normal volume, 0–200
+    #[validate(range(max = 200))]
+    pub volume: u16,
+    /// Muted for this user only
+    #[serde(default)]
+    pub muted: bool,
+}
+
+/// Per-user playback levels, keyed by the other user's ID.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, Validate)]
+#[serde(transparent)]
+pub struct UserVolumes {
+    #[validate(length(max = 1000), nested)]
+    pub volumes: BTreeMap<Uuid, UserVolume>,
+}
+
+/// Changes to [`UserVolumes`]: each entry is set, or cleared when `null`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(transparent)]
+pub struct UserVolumesPatch {
+    pub volumes: BTreeMap<Uuid, Option<UserVolume>>,
+}
+
+impl UserVolumes {
+    /// Apply `patch`. Validate the result, not the patch.
+    pub fn apply(&mut self, patch: UserVolumesPatch) {
+        for (user_id, volume) in patch.volumes {
+            match volume {
+                Some(volume) => self.volumes.insert(user_id, volume),
+                None => self.volumes.remove(&user_id),
+            };
+        }
+    }
+}
+
+/// A user's synced settings.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UserSettings {
+    pub server_layout: ServerLayout,
+
+    #[serde(default)]
+    pub user_volumes: UserVolumes,
+
+    /// Bumped on every change; `0` until the user first saves settings
+    pub version: i64,
+
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn folder(name: &str, server_ids: &[Uuid]) -> ServerListItem {
+        ServerListItem::Folder(ServerFolder {
+            id: Uuid::new_v4(),
+            name: Some(name.into()),
+            color: Some(0x5865F2),
+            server_ids: server_ids.to_vec(),
+        })
+    }
+
+    #[test]
+    fn accepts_servers_and_folders() {
+        let (a, b, c) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let layout = ServerLayout {
+            items: vec![ServerListItem::Server { id: a }, folder("Games", &[b, c])],
+        };
+        assert!(layout.validate().is_ok());
+        assert_eq!(layout.server_ids().collect::<Vec<_>>(), vec![a, b, c]);
+    }
+
+    #[test]
+    fn rejects_a_server_placed_twice() {
+        let a = Uuid::new_v4();
+        let layout = ServerLayout {
+            items: vec![ServerListItem::Server { id: a }, folder("Games", &[a])],
+        };
+        assert!(layout.validate().is_err());
+    }
+
+    #[test]
+    fn rejects_out_of_range_folders() {
+        let long_name = ServerLayout {
+            items: vec![folder(&"x".repeat(MAX_FOLDER_NAME_CHARS + 1), &[Uuid::new_v4()])],
+        };
+        assert!(long_name.validate().is_err());
+
+        let mut bad_color = folder("Work", &[Uuid::new_v4()]);
+        if let ServerListItem::Folder(f) = &mut bad_color {
+            f.color = Some(0x1_000000);
+        }
+        assert!(ServerLayout { items: vec![bad_color] }.validate().is_err());
+    }
+
+    #[test]
+    fn retain_drops_left_servers_and_empty_folders() {
+        let (a, b, c) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let mut layout = ServerLayout {
+            items: vec![
+                ServerListItem::Server { id: a },
+                folder("Old", &[b]),
+                folder("Mixed", &[b, c]),
+            ],
+        };
+        layout.retain_servers(&HashSet::from([a, c]));
+        assert_eq!(layout.items.len(), 2);
+        assert_eq!(layout.server_ids().collect::<Vec<_>>(), vec![a, c]);
+    }
+
+    #[test]
+    fn user_volumes_validate_and_patch() {
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        let loud = UserVolume { volume: 150, muted: false };
+        let mut volumes = UserVolumes { volumes: BTreeMap::from([(a, loud)]) };
+        assert!(volumes.validate().is_ok());
+
+        let too_loud = UserVolumes {
+            volumes: BTreeMap::from([(a, UserVolume { volume: 201, muted: false })]),
+        };
+        assert!(too_loud.validate().is_err());
+
+        let quiet = UserVolume { volume: 0, muted: true };
+        let patch = UserVolumesPatch { volumes: BTreeMap::from([(a, None), (b, Some(quiet))]) };
+        volumes.apply(patch);
+        assert_eq!(volumes.volumes, BTreeMap::from([(b, quiet)]));
+
+        let patch = UserVolumesPatch {
+            volumes: BTreeMap::from([(a, Some(UserVolume { volume: 999, muted: false }))]),
+        };
+        volumes.apply(patch);
+        assert!(volumes.validate().is_err());
+    }
+
+    #[test]
+    fn user_volumes_serialize_as_a_map() {
+        let id = Uuid::nil();
+        let volumes = UserVolumes {
+            volumes: BTreeMap::from([(id, UserVolume { volume: 80, muted: false })]),
+        };
+        let json = serde_json::to_value(&volumes).unwrap();
+        assert_eq!(json, serde_json::json!({ id.to_string(): { "volume": 80, "muted": false } }));
+        let parsed: UserVolumes =
+            serde_json::from_value(serde_json::json!({ id.to_string(): { "volume": 80 } })).unwrap();
+        assert_eq!(parsed, volumes);
+    }
+
+    #[test]
+    fn serializes_with_item_type_tags() {
+        let id = Uuid::nil();
+        let layout = ServerLayout {
+            items: vec![ServerListItem::Server { id }],
+        };
+        let json = serde_json::to_value(&layout).unwrap();
+        assert_eq!(json["items"][0]["type"], "server");
+        assert_eq!(json["items"][0]["id"], id.to_string());
+    }
+}
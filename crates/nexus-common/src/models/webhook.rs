@@ -72,7 +72,7 @@ pub struct ModifyWebhookRequest {
 }
 
 /// Execute an incoming webhook — post a message to the channel.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Default, Deserialize)]
 pub struct ExecuteWebhookRequest {
     pub content: Option<String>,
     pub username: Option<String>,
@@ -0,0 +1,185 @@
+//! Address policy.
+//!
+//! - Outbound requests to URLs that users or remote servers supply (link
+//!   previews, ban list subscriptions, ActivityPub actors) connect only to
+//!   public addresses, so they can't reach services on the server's own
+//!   network.
+//! - Inbound requests are attributed to a client address that only the
+//!   configured `http.trusted_proxies` can vouch for.
+
+use std::net::{IpAddr, Ipv4Addr};
+
+use axum::http::HeaderMap;
+use ipnetwork::IpNetwork;
+
+/// Whether `ip` is a globally routable unicast address — one that outbound
+/// fetches of user-supplied URLs may connect to.
+pub fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => is_public_v4(v4),
+        IpAddr::V6(v6) => {
+            if let Some(v4) = v6.to_ipv4_mapped() {
+                return is_public_v4(v4);
+            }
+            let first = v6.segments()[0];
+            !(v6.is_loopback()
+                || v6.is_unspecified()
+                || v6.is_multicast()
+                || (first & 0xfe00) == 0xfc00 // unique local
+                || (first & 0xffc0) == 0xfe80 // link-local
+                || (first & 0xffc0) == 0xfec0 // site-local
+                || (first == 0x2001 && v6.segments()[1] == 0x0db8) // documentation
+                || (first == 0x0064 && v6.segments()[1] == 0xff9b) // NAT64
+                || v6.segments()[..6] == [0; 6]) // IPv4-compatible
+        }
+    }
+}
+
+fn is_public_v4(v4: Ipv4Addr) -> bool {
+    let [a, b, c, _] = v4.octets();
+    !(v4.is_loopback()
+        || v4.is_private()
+        || v4.is_link_local()
+        || v4.is_broadcast()
+        || v4.is_documentation()
+        || v4.is_unspecified()
+        || v4.is_multicast()
+        || a == 0
+        || (a == 100 && (64..128).contains(&b)) // carrier-grade NAT
+        || (a == 192 && b == 0 && c == 0) // IETF protocol assignments
+        || (a == 198 && (18..20).contains(&b)) // benchmarking
+        || a >= 240) // reserved
+}
+
+/// Parse a comma-separated list of IPs and CIDR ranges. Malformed entries
+/// are skipped.
+pub fn parse_networks(list: &str) -> Vec<IpNetwork> {
+    list.split(',')
+        .map(str::trim)
+        .filter_map(|entry| entry.parse().ok())
+        .collect()
+}
+
+/// The client address of a request received from `peer`.
+///
+/// Forwarding headers are only read when `peer` is one of the `trusted`
+/// proxies. `X-Forwarded-For` is then walked from the right — the hops the
+/// proxies appended — and the first address that isn't a trusted proxy
+/// itself is the client; anything further left was written by the client
+/// and is ignored. `X-Real-IP` is used when a trusted proxy sends no
+/// `X-Forwarded-For`.
+pub fn client_ip(peer: Option<IpAddr>, headers: &HeaderMap, trusted: &[IpNetwork]) -> Option<IpAddr> {
+    let is_trusted = |ip: IpAddr| trusted.iter().any(|network| network.contains(ip));
+    let peer = peer?;
+    if !is_trusted(peer) {
+        return Some(peer);
+    }
+
+    let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+    if let Some(forwarded) = header("x-forwarded-for") {
+        let mut client = peer;
+        for hop in forwarded.rsplit(',') {
+            let Ok(ip) = hop.trim().parse::<IpAddr>() else {
+                break;
+            };
+            client = ip;
+            if !is_trusted(ip) {
+                break;
+            }
+        }
+        return Some(client);
+    }
+    header("x-real-ip")
+        .and_then(|v| v.trim().parse().ok())
+        .or(Some(peer))
+}
+
+/// Whether `ip` may use a token restricted to `allowlist` (IPs or CIDR
+/// ranges). An empty list allows everyone; an unknown client IP is refused
+/// by a non-empty list. Malformed entries match nothing.
+pub fn ip_allowed(allowlist: &[String], ip: Option<IpAddr>) -> bool {
+    if allowlist.is_empty() {
+        return true;
+    }
+    let Some(ip) = ip else {
+        return false;
+    };
+    allowlist
+        .iter()
+        .filter_map(|entry| entry.parse::<IpNetwork>().ok())
+        .any(|network| network.contains(ip))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_public_addresses_are_allowed() {
+        for ip in ["93.184.216.34", "2606:2800:220:1:248:1893:25c8:1946", "1.1.1.1"] {
+            assert!(is_public(ip.parse().unwrap()), "{ip}");
+        }
+        for ip in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "255.255.255.255",
+            "::1",
+            "::",
+            "fd00::1",
+            "fe80::1",
+            "::ffff:127.0.0.1",
+            "::ffff:10.0.0.1",
+            "64:ff9b::a00:1",
+        ] {
+            assert!(!is_public(ip.parse().unwrap()), "{ip}");
+        }
+    }
+
+    fn forwarded(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-forwarded-for", value.parse().unwrap());
+        headers
+    }
+
+    #[test]
+    fn forwarding_headers_are_read_only_from_trusted_proxies() {
+        let trusted = parse_networks("127.0.0.0/8, 10.0.0.0/8, not-a-network");
+        assert_eq!(trusted.len(), 2);
+        let ip = |s: &str| s.parse::<IpAddr>().ok();
+
+        // A direct client can't claim another address
+        assert_eq!(client_ip(ip("203.0.113.7"), &forwarded("198.51.100.1"), &trusted), ip("203.0.113.7"));
+        // Behind proxies the rightmost untrusted hop wins, not the spoofable leftmost
+        assert_eq!(
+            client_ip(ip("127.0.0.1"), &forwarded("198.51.100.1, 203.0.113.7, 10.0.0.2"), &trusted),
+            ip("203.0.113.7")
+        );
+        assert_eq!(client_ip(ip("127.0.0.1"), &HeaderMap::new(), &trusted), ip("127.0.0.1"));
+        assert_eq!(client_ip(None, &forwarded("203.0.113.7"), &trusted), None);
+    }
+
+    #[test]
+    fn empty_allowlist_allows_everyone() {
+        assert!(ip_allowed(&[], None));
+        assert!(ip_allowed(&[], "203.0.113.7".parse().ok()));
+    }
+
+    #[test]
+    fn allowlist_matches_addresses_and_ranges() {
+        let allowlist = vec![
+            "203.0.113.7".to_owned(),
+            "198.51.100.0/24".to_owned(),
+            "2001:db8::/32".to_owned(),
+        ];
+        assert!(ip_allowed(&allowlist, "203.0.113.7".parse().ok()));
+        assert!(ip_allowed(&allowlist, "198.51.100.200".parse().ok()));
+        assert!(ip_allowed(&allowlist, "2001:db8::1".parse().ok()));
+        assert!(!ip_allowed(&allowlist, "203.0.113.8".parse().ok()));
+        assert!(!ip_allowed(&allowlist, None));
+    }
+}
@@ -138,6 +138,43 @@ impl Permissions {
     pub fn has(&self, required: Permissions) -> bool {
         self.is_admin() || self.contains(required)
     }
+
+    /// Canonical names of the set bits, in bit order (e.g. `["VIEW_CHANNEL"]`).
+    pub fn names(&self) -> Vec<&'static str> {
+        self.iter_names().map(|(name, _)| name).collect()
+    }
+
+    /// Parse canonical names back into a bitfield. Fails on the first
+    /// unknown name.
+    pub fn from_names<'a>(names: impl IntoIterator<Item = &'a str>) -> Result<Self, String> {
+        names.into_iter().try_fold(Self::empty(), |acc, name| {
+            Self::from_name(name)
+                .map(|p| acc | p)
+                .ok_or_else(|| format!("unknown permission '{name}'"))
+        })
+    }
+
+    /// Every permission as `(name, bit value)`, in bit order — the catalog
+    /// clients mirror.
+    pub fn catalog() -> Vec<(&'static str, i64)> {
+        Self::all().iter_names().map(|(name, p)| (name, p.bits())).collect()
+    }
+}
+
+/// Serialize [`Permissions`] as a list of names rather than an integer:
+/// `#[serde(with = "nexus_common::permissions::as_names")]`.
+pub mod as_names {
+    use super::Permissions;
+    use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(perms: &Permissions, serializer: S) -> Result<S::Ok, S::Error> {
+        perms.names().serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Permissions, D::Error> {
+        let names = Vec::<String>::deserialize(deserializer)?;
+        Permissions::from_names(names.iter().map(String::as_str)).map_err(D::Error::custom)
+    }
 }
 
 /// Channel-level permission override.
@@ -220,3 +257,175 @@ pub fn compute_permissions(
 
     perms
 }
+
+/// Where a member's permission bit came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PermissionSource {
+    /// The server owner has every permission.
+    Owner,
+    /// ADMINISTRATOR, granted by this role (`None` = @everyone).
+    Administrator { role_id: Option<uuid::Uuid> },
+    /// The @everyone role.
+    Everyone,
+    /// A role the member holds.
+    Role { role_id: uuid::Uuid },
+    /// A channel overwrite allowed the bit.
+    OverwriteAllow { target_type: OverwriteType, target_id: uuid::Uuid },
+    /// A channel overwrite denied the bit.
+    OverwriteDeny { target_type: OverwriteType, target_id: uuid::Uuid },
+    /// Nothing granted it.
+    Unset,
+}
+
+/// One permission bit in an [`explain_permissions`] report.
+#[derive(Debug, Clone, Serialize)]
+pub struct PermissionExplanation {
+    pub permission: &'static str,
+    pub granted: bool,
+    /// The rule that decided the outcome (the last one applied).
+    pub source: PermissionSource,
+}
+
+/// Same algorithm as [`compute_permissions`], but reports for every bit
+/// whether it is granted and which role or overwrite decided it.
+///
+/// `roles` are the member's roles as `(role_id, permissions)`, excluding
+/// @everyone.
+pub fn explain_permissions(
+    base_permissions: Permissions,
+    roles: &[(uuid::Uuid, Permissions)],
+    channel_overwrites: &[PermissionOverwrite],
+    member_id: uuid::Uuid,
+    everyone_role_id: uuid::Uuid,
+) -> Vec<PermissionExplanation> {
+    let explain = |decide: &dyn Fn(Permissions) -> (bool, PermissionSource)| {
+        Permissions::all()
+            .iter_names()
+            .map(|(name, bit)| {
+                let (granted, source) = decide(bit);
+                PermissionExplanation { permission: name, granted, source }
+            })
+            .collect::<Vec<_>>()
+    };
+
+    if base_permissions.is_admin() {
+        return explain(&|_| (true, PermissionSource::Administrator { role_id: None }));
+    }
+    if let Some(&(role_id, _)) = roles.iter().find(|(_, p)| p.is_admin()) {
+        return explain(&|_| (true, PermissionSource::Administrator { role_id: Some(role_id) }));
+    }
+
+    explain(&|bit| {
+        let mut decision = if base_permissions.contains(bit) {
+            (true, PermissionSource::Everyone)
+        } else if let Some(&(role_id, _)) = roles.iter().find(|(_, p)| p.contains(bit)) {
+            (true, PermissionSource::Role { role_id })
+        } else {
+            (false, PermissionSource::Unset)
+        };
+
+        // @everyone and role overwrites are merged; an allow beats a deny.
+        let applies = |ow: &&PermissionOverwrite| {
+            ow.target_type == OverwriteType::Role
+                && (ow.target_id == everyone_role_id || roles.iter().any(|(id, _)| *id == ow.target_id))
+        };
+        let role_overwrites: Vec<&PermissionOverwrite> =
+            channel_overwrites.iter().filter(applies).collect();
+        let source = |ow: &PermissionOverwrite, allow: bool| {
+            let (target_type, target_id) = (ow.target_type, ow.target_id);
+            if allow {
+                PermissionSource::OverwriteAllow { target_type, target_id }
+            } else {
+                PermissionSource::OverwriteDeny { target_type, target_id }
+            }
+        };
+        if let Some(ow) = role_overwrites.iter().find(|ow| ow.deny & bit.bits() != 0) {
+            decision = (false, source(ow, false));
+        }
+        if let Some(ow) = role_overwrites.iter().find(|ow| ow.allow & bit.bits() != 0) {
+            decision = (true, source(ow, true));
+        }
+
+        // User-specific overwrites (highest priority)
+        for ow in channel_overwrites {
+            if ow.target_type == OverwriteType::User && ow.target_id == member_id {
+                if ow.deny & bit.bits() != 0 {
+                    decision = (false, source(ow, false));
+                }
+                if ow.allow & bit.bits() != 0 {
+                    decision = (true, source(ow, true));
+                }
+            }
+        }
+        decision
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    #[test]
+    fn names_round_trip() {
+        let perms = Permissions::SEND_MESSAGES | Permissions::VIEW_CHANNEL;
+        assert_eq!(perms.names(), ["VIEW_CHANNEL", "SEND_MESSAGES"]);
+        assert_eq!(Permissions::from_names(perms.names()), Ok(perms));
+        assert!(Permissions::from_names(["SEND_MESSAGES", "FLY"]).is_err());
+        assert_eq!(Permissions::catalog().len(), Permissions::all().iter().count());
+    }
+
+    #[test]
+    fn explanation_matches_computed_permissions() {
+        let (everyone, moderator, member) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let base = Permissions::default_everyone();
+        let roles = [(moderator, Permissions::MANAGE_MESSAGES)];
+        let overwrites = vec![
+            PermissionOverwrite {
+                target_id: everyone,
+                target_type: OverwriteType::Role,
+                allow: 0,
+                deny: (Permissions::SEND_MESSAGES | Permissions::ADD_REACTIONS).bits(),
+            },
+            PermissionOverwrite {
+                target_id: moderator,
+                target_type: OverwriteType::Role,
+                allow: Permissions::SEND_MESSAGES.bits(),
+                deny: 0,
+            },
+            PermissionOverwrite {
+                target_id: member,
+                target_type: OverwriteType::User,
+                allow: 0,
+                deny: Permissions::ATTACH_FILES.bits(),
+            },
+        ];
+
+        let report = explain_permissions(base, &roles, &overwrites, member, everyone);
+        let computed =
+            compute_permissions(base, &[roles[0].1], &overwrites, &[moderator], member, everyone);
+        let granted = report
+            .iter()
+            .filter(|e| e.granted)
+            .fold(Permissions::empty(), |acc, e| acc | Permissions::from_name(e.permission).unwrap());
+        assert_eq!(granted, computed);
+
+        let source = |name: &str| report.iter().find(|e| e.permission == name).unwrap().source;
+        assert_eq!(source("VIEW_CHANNEL"), PermissionSource::Everyone);
+        assert_eq!(source("MANAGE_MESSAGES"), PermissionSource::Role { role_id: moderator });
+        assert_eq!(
+            source("SEND_MESSAGES"),
+            PermissionSource::OverwriteAllow { target_type: OverwriteType::Role, target_id: moderator }
+        );
+        assert_eq!(
+            source("ADD_REACTIONS"),
+            PermissionSource::OverwriteDeny { target_type: OverwriteType::Role, target_id: everyone }
+        );
+        assert_eq!(
+            source("ATTACH_FILES"),
+            PermissionSource::OverwriteDeny { target_type: OverwriteType::User, target_id: member }
+        );
+        assert_eq!(source("BAN_MEMBERS"), PermissionSource::Unset);
+    }
+}
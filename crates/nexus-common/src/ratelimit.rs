@@ -0,0 +1,169 @@
+//! Token buckets — the rate limit arithmetic shared by the REST API and the
+//! gateway.
+//!
+//! A bucket holds up to `capacity` tokens and refills continuously, so that
+//! a full bucket is `capacity` requests per `window`. Each request takes one
+//! token; a request finding less than one is refused until enough has
+//! refilled. The state is just a token count and a timestamp, which lets the
+//! API keep buckets in Redis (see `nexus_db::redis_pool::take_token`) as
+//! well as in memory.
+
+use std::time::Duration;
+
+/// A bucket's size and refill period.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Limit {
+    pub capacity: u32,
+    pub window: Duration,
+}
+
+impl Limit {
+    pub fn new(capacity: u32, window_secs: u64) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            window: Duration::from_secs(window_secs.max(1)),
+        }
+    }
+
+    /// Tokens refilled per millisecond.
+    pub fn rate_per_ms(&self) -> f64 {
+        self.capacity as f64 / self.window.as_millis() as f64
+    }
+
+    /// The result of a take that left `tokens` in the bucket.
+    pub fn outcome(&self, allowed: bool, tokens: f64) -> Outcome {
+        self.outcome_for(allowed, tokens, 1)
+    }
+
+    /// The result of a take of `cost` tokens that left `tokens` in the bucket.
+    pub fn outcome_for(&self, allowed: bool, tokens: f64, cost: u32) -> Outcome {
+        let rate = self.rate_per_ms();
+        let capacity = self.capacity as f64;
+        Outcome {
+            allowed,
+            limit: self.capacity,
+            remaining: tokens.max(0.0).floor() as u32,
+            reset_after: Duration::from_millis(((capacity - tokens).max(0.0) / rate).ceil() as u64),
+            retry_after: if allowed {
+                Duration::ZERO
+            } else {
+                Duration::from_millis(((cost as f64 - tokens).max(0.0) / rate).ceil() as u64)
+            },
+        }
+    }
+}
+
+/// What a caller is told about a bucket after one request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Outcome {
+    pub allowed: bool,
+    pub limit: u32,
+    /// Whole requests left right now.
+    pub remaining: u32,
+    /// Until the bucket is full again.
+    pub reset_after: Duration,
+    /// Until the next request would be allowed; zero when this one was.
+    pub retry_after: Duration,
+}
+
+/// An in-memory bucket.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TokenBucket {
+    tokens: f64,
+    /// Milliseconds on the caller's clock when `tokens` was last updated.
+    updated_ms: u64,
+}
+
+impl TokenBucket {
+    /// A full bucket.
+    pub fn new(limit: Limit, now_ms: u64) -> Self {
+        Self {
+            tokens: limit.capacity as f64,
+            updated_ms: now_ms,
+        }
+    }
+
+    /// Refill for the time since the last take, then take one token if there is one.
+    pub fn take(&mut self, limit: Limit, now_ms: u64) -> Outcome {
+        self.take_n(limit, 1, now_ms)
+    }
+
+    /// Like [`take`](Self::take), but all-or-nothing for `cost` tokens. A
+    /// cost above the capacity is charged as a full bucket.
+    pub fn take_n(&mut self, limit: Limit, cost: u32, now_ms: u64) -> Outcome {
+        let cost = cost.min(limit.capacity);
+        let elapsed = now_ms.saturating_sub(self.updated_ms) as f64;
+        self.tokens = (self.tokens + elapsed * limit.rate_per_ms()).min(limit.capacity as f64);
+        self.updated_ms = self.updated_ms.max(now_ms);
+        let allowed = self.tokens >= cost as f64;
+        if allowed {
+            self.tokens -= cost as f64;
+        }
+        limit.outcome_for(allowed, self.tokens, cost)
+    }
+
+    /// Whether the bucket has refilled completely by `now_ms`, so dropping
+    /// it loses nothing.
+    pub fn is_full(&self, limit: Limit, now_ms: u64) -> bool {
+        let elapsed = now_ms.saturating_sub(self.updated_ms) as f64;
+        self.tokens + elapsed * limit.rate_per_ms() >= limit.capacity as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn allows_a_burst_then_refuses() {
+        let limit = Limit::new(3, 3);
+        let mut bucket = TokenBucket::new(limit, 0);
+        for remaining in [2, 1, 0] {
+            let outcome = bucket.take(limit, 0);
+            assert!(outcome.allowed);
+            assert_eq!(outcome.remaining, remaining);
+        }
+        let refused = bucket.take(limit, 0);
+        assert!(!refused.allowed);
+        // One token per second.
+        assert_eq!(refused.retry_after, Duration::from_secs(1));
+        assert_eq!(refused.reset_after, Duration::from_secs(3));
+    }
+
+    #[test]
+    fn refills_continuously_up_to_capacity() {
+        let limit = Limit::new(2, 10);
+        let mut bucket = TokenBucket::new(limit, 0);
+        bucket.take(limit, 0);
+        bucket.take(limit, 0);
+        assert!(!bucket.take(limit, 4_000).allowed);
+        assert!(bucket.take(limit, 5_000).allowed);
+        assert!(!bucket.is_full(limit, 5_000));
+        assert!(bucket.is_full(limit, 60_000));
+        // A long idle period doesn't bank more than a full bucket.
+        let outcome = bucket.take(limit, 60_000);
+        assert_eq!(outcome.remaining, 1);
+    }
+
+    #[test]
+    fn takes_several_tokens_at_once_or_none() {
+        let limit = Limit::new(10, 10);
+        let mut bucket = TokenBucket::new(limit, 0);
+        assert_eq!(bucket.take_n(limit, 8, 0).remaining, 2);
+        let refused = bucket.take_n(limit, 4, 0);
+        assert!(!refused.allowed);
+        assert_eq!(refused.remaining, 2);
+        // Two more tokens are needed, at one per second.
+        assert_eq!(refused.retry_after, Duration::from_secs(2));
+        // More than a full bucket costs a full bucket.
+        assert!(bucket.take_n(limit, 50, 10_000).allowed);
+    }
+
+    #[test]
+    fn a_clock_going_backwards_refills_nothing() {
+        let limit = Limit::new(1, 1);
+        let mut bucket = TokenBucket::new(limit, 10_000);
+        assert!(bucket.take(limit, 10_000).allowed);
+        assert!(!bucket.take(limit, 5_000).allowed);
+    }
+}
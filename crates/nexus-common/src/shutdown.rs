@@ -0,0 +1,67 @@
+//! Process-wide shutdown signal.
+//!
+//! The server triggers it on SIGTERM or Ctrl-C. Listeners stop accepting
+//! connections, long-lived connections (gateway, voice) say goodbye to their
+//! clients, and workers that hold queued work finish it before the process
+//! exits.
+
+use std::future::Future;
+use std::sync::Arc;
+
+use tokio::sync::watch;
+
+/// A cloneable handle; every clone sees the same signal.
+#[derive(Clone)]
+pub struct Shutdown {
+    tx: Arc<watch::Sender<bool>>,
+}
+
+impl Shutdown {
+    pub fn new() -> Self {
+        Self {
+            tx: Arc::new(watch::channel(false).0),
+        }
+    }
+
+    /// Start shutting down. Idempotent.
+    pub fn trigger(&self) {
+        self.tx.send_replace(true);
+    }
+
+    pub fn is_triggered(&self) -> bool {
+        *self.tx.borrow()
+    }
+
+    /// Resolves once [`trigger`](Self::trigger) has been called, including
+    /// before this was. Owns what it needs, so it can be handed to
+    /// `axum::serve(..).with_graceful_shutdown`.
+    pub fn triggered(&self) -> impl Future<Output = ()> + Send + 'static {
+        let mut rx = self.tx.subscribe();
+        async move {
+            let _ = rx.wait_for(|&triggered| triggered).await;
+        }
+    }
+}
+
+impl Default for Shutdown {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn waiters_before_and_after_the_trigger_resolve() {
+        let shutdown = Shutdown::new();
+        let early = tokio::spawn(shutdown.triggered());
+        assert!(!shutdown.is_triggered());
+
+        shutdown.clone().trigger();
+        early.await.unwrap();
+        shutdown.triggered().await;
+        assert!(shutdown.is_triggered());
+    }
+}
@@ -3,32 +3,191 @@
 //! Like Discord, Nexus uses Snowflake IDs — globally unique, time-sortable,
 //! generated without coordination. We use UUID v7 which provides the same
 //! properties with broader ecosystem support.
+//!
+//! Within the v7 layout the bits after the timestamp are not left fully
+//! random. Nexus packs them as:
+//!
+//! | bits | field                                            |
+//! |------|--------------------------------------------------|
+//! | 48   | Unix timestamp, milliseconds                     |
+//! | 4    | version (`0b0111`)                               |
+//! | 12   | per-millisecond sequence                         |
+//! | 2    | variant (`0b10`)                                 |
+//! | 10   | worker ID (3 bits role, 7 bits instance)         |
+//! | 52   | random                                           |
+//!
+//! The sequence keeps IDs from one process strictly increasing, even when
+//! the wall clock steps backwards; the worker ID tells apart IDs minted by
+//! different nodes in the same millisecond and records where they came from.
+
+use std::sync::Mutex;
 
+use chrono::{DateTime, Utc};
 use uuid::Uuid;
 
+/// Highest per-millisecond sequence number (12 bits).
+const MAX_SEQUENCE: u16 = 0x0FFF;
+/// Highest worker ID (10 bits).
+pub const MAX_WORKER_ID: u16 = 0x03FF;
+/// Highest instance number within a role (7 bits).
+pub const MAX_INSTANCE: u16 = 0x7F;
+/// A clock rollback larger than this is logged as a warning.
+const DRIFT_WARN_MS: u64 = 1_000;
+
+/// Node roles, in worker-ID order. The index is the high 3 bits of the ID.
+pub const ROLES: [&str; 8] = [
+    "server",
+    "api",
+    "gateway",
+    "voice",
+    "federation",
+    "rpc",
+    "worker",
+    "desktop",
+];
+
+/// Generator state shared by every thread in the process.
+struct Generator {
+    worker_id: u16,
+    /// Millisecond of the last ID handed out. Never moves backwards.
+    last_ms: u64,
+    sequence: u16,
+}
+
+static GENERATOR: Mutex<Generator> = Mutex::new(Generator {
+    worker_id: 0,
+    last_ms: 0,
+    sequence: 0,
+});
+
+/// The parts of a Nexus ID.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Snowflake {
+    pub timestamp: DateTime<Utc>,
+    pub worker_id: u16,
+    pub sequence: u16,
+}
+
+impl Snowflake {
+    /// Role name encoded in the worker ID.
+    pub fn role(&self) -> &'static str {
+        ROLES[usize::from(self.worker_id >> 7)]
+    }
+
+    /// Instance number within the role.
+    pub fn instance(&self) -> u16 {
+        self.worker_id & MAX_INSTANCE
+    }
+}
+
+/// Worker ID for a node role and instance number, e.g. `("gateway", 3)`.
+///
+/// Returns `None` for an unknown role or an instance above [`MAX_INSTANCE`].
+pub fn worker_id(role: &str, instance: u16) -> Option<u16> {
+    let role = ROLES.iter().position(|r| r.eq_ignore_ascii_case(role))? as u16;
+    (instance <= MAX_INSTANCE).then_some((role << 7) | instance)
+}
+
+/// Set the worker ID stamped into every ID this process generates.
+///
+/// Call once at startup, before any IDs are minted. IDs from a process that
+/// never calls this carry worker 0.
+pub fn init(worker_id: u16) {
+    let mut generator = GENERATOR.lock().unwrap_or_else(|e| e.into_inner());
+    generator.worker_id = worker_id & MAX_WORKER_ID;
+}
+
 /// Generate a new Snowflake-style ID using UUID v7.
 ///
 /// UUID v7 provides:
-/// - Monotonically increasing (time-sortable)
+/// - Monotonically increasing (time-sortable), strictly so within a process
 /// - 48 bits of Unix timestamp (millisecond precision)
-/// - 74 bits of randomness (guaranteed unique across nodes)
+/// - 12 bits of sequence, 10 bits of worker ID and 52 bits of randomness
 /// - Compatible with all UUID infrastructure (Postgres, etc.)
 pub fn generate_id() -> Uuid {
-    Uuid::now_v7()
+    let (ms, worker_id, sequence) = {
+        let mut generator = GENERATOR.lock().unwrap_or_else(|e| e.into_inner());
+        generator.next(now_ms())
+    };
+    build(ms, worker_id, sequence, *Uuid::new_v4().as_bytes())
 }
 
-/// Extract the approximate creation timestamp from a UUID v7.
-pub fn extract_timestamp(id: Uuid) -> Option<chrono::DateTime<chrono::Utc>> {
+impl Generator {
+    /// Claim the next (millisecond, sequence) slot at wall-clock time `now`.
+    fn next(&mut self, now: u64) -> (u64, u16, u16) {
+        if now > self.last_ms {
+            self.last_ms = now;
+            self.sequence = 0;
+        } else {
+            if self.last_ms - now > DRIFT_WARN_MS {
+                tracing::warn!(
+                    drift_ms = self.last_ms - now,
+                    "System clock moved backwards; holding snowflake timestamps"
+                );
+            }
+            // Same millisecond or the clock went backwards: stay on the last
+            // timestamp and bump the sequence, borrowing the next millisecond
+            // once it runs out.
+            if self.sequence == MAX_SEQUENCE {
+                self.last_ms += 1;
+                self.sequence = 0;
+            } else {
+                self.sequence += 1;
+            }
+        }
+        (self.last_ms, self.worker_id, self.sequence)
+    }
+}
+
+fn now_ms() -> u64 {
+    Utc::now().timestamp_millis().max(0) as u64
+}
+
+/// Lay out an ID from its parts, taking the random tail from `random`.
+fn build(ms: u64, worker_id: u16, sequence: u16, random: [u8; 16]) -> Uuid {
+    let mut bytes = random;
+    bytes[..6].copy_from_slice(&ms.to_be_bytes()[2..]);
+    bytes[6] = 0x70 | (sequence >> 8) as u8;
+    bytes[7] = sequence as u8;
+    bytes[8] = 0x80 | (worker_id >> 4) as u8;
+    bytes[9] = ((worker_id & 0x0F) as u8) << 4 | (bytes[9] & 0x0F);
+    Uuid::from_bytes(bytes)
+}
+
+/// Split an ID into its timestamp, worker ID and sequence.
+///
+/// Returns `None` for anything that isn't a UUID v7. IDs minted before
+/// worker IDs existed decode with arbitrary worker and sequence values, but
+/// their timestamp is still exact.
+pub fn decompose(id: Uuid) -> Option<Snowflake> {
+    if id.get_version_num() != 7 {
+        return None;
+    }
     let bytes = id.as_bytes();
-    // UUID v7: first 48 bits are millisecond timestamp
-    let ms = ((bytes[0] as u64) << 40)
-        | ((bytes[1] as u64) << 32)
-        | ((bytes[2] as u64) << 24)
-        | ((bytes[3] as u64) << 16)
-        | ((bytes[4] as u64) << 8)
-        | (bytes[5] as u64);
-
-    chrono::DateTime::from_timestamp_millis(ms as i64)
+    let mut ms = [0u8; 8];
+    ms[2..].copy_from_slice(&bytes[..6]);
+    Some(Snowflake {
+        timestamp: DateTime::from_timestamp_millis(u64::from_be_bytes(ms) as i64)?,
+        worker_id: (u16::from(bytes[8] & 0x3F) << 4) | u16::from(bytes[9] >> 4),
+        sequence: (u16::from(bytes[6] & 0x0F) << 8) | u16::from(bytes[7]),
+    })
+}
+
+/// Extract the approximate creation timestamp from a UUID v7.
+pub fn extract_timestamp(id: Uuid) -> Option<DateTime<Utc>> {
+    decompose(id).map(|s| s.timestamp)
+}
+
+/// The smallest ID that could have been minted at `ts`.
+///
+/// Every ID generated at or after `ts` sorts above it, so it works as a
+/// pagination cursor or retention cutoff over ID-ordered tables.
+pub fn lower_bound(ts: DateTime<Utc>) -> Uuid {
+    let mut bytes = [0u8; 16];
+    bytes[..6].copy_from_slice(&(ts.timestamp_millis().max(0) as u64).to_be_bytes()[2..]);
+    bytes[6] = 0x70;
+    bytes[8] = 0x80;
+    Uuid::from_bytes(bytes)
 }
 
 #[cfg(test)]
@@ -62,4 +221,62 @@ mod tests {
         assert!(extracted >= before - chrono::Duration::milliseconds(1));
         assert!(extracted <= after + chrono::Duration::milliseconds(1));
     }
+
+    #[test]
+    fn test_ids_from_one_process_strictly_increase() {
+        let ids: Vec<Uuid> = (0..10_000).map(|_| generate_id()).collect();
+        assert!(ids.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn test_sequence_survives_clock_rollback() {
+        let mut generator = Generator {
+            worker_id: 5,
+            last_ms: 0,
+            sequence: 0,
+        };
+        let a = generator.next(10_000);
+        let b = generator.next(4_000); // clock stepped back six seconds
+        let c = generator.next(10_000);
+        assert_eq!(a, (10_000, 5, 0));
+        assert_eq!(b, (10_000, 5, 1));
+        assert_eq!(c, (10_000, 5, 2));
+
+        generator.sequence = MAX_SEQUENCE;
+        assert_eq!(generator.next(10_000), (10_001, 5, 0));
+        assert_eq!(generator.next(10_002), (10_002, 5, 0));
+    }
+
+    #[test]
+    fn test_decompose_round_trips() {
+        let worker = worker_id("gateway", 3).unwrap();
+        let id = build(1_767_225_600_123, worker, 0xABC, [0xFF; 16]);
+        assert_eq!(id.get_version_num(), 7);
+        assert_eq!(id.get_variant(), uuid::Variant::RFC4122);
+
+        let parts = decompose(id).unwrap();
+        assert_eq!(parts.timestamp.timestamp_millis(), 1_767_225_600_123);
+        assert_eq!(parts.worker_id, worker);
+        assert_eq!(parts.sequence, 0xABC);
+        assert_eq!(parts.role(), "gateway");
+        assert_eq!(parts.instance(), 3);
+        assert!(decompose(Uuid::new_v4()).is_none());
+    }
+
+    #[test]
+    fn test_worker_id_bounds() {
+        assert_eq!(worker_id("server", 0), Some(0));
+        assert_eq!(worker_id("Desktop", MAX_INSTANCE), Some(MAX_WORKER_ID));
+        assert_eq!(worker_id("api", MAX_INSTANCE + 1), None);
+        assert_eq!(worker_id("scheduler", 0), None);
+    }
+
+    #[test]
+    fn test_lower_bound_sorts_before_ids_at_that_time() {
+        let ts = DateTime::from_timestamp_millis(1_767_225_600_000).unwrap();
+        let floor = lower_bound(ts);
+        assert_eq!(extract_timestamp(floor), Some(ts));
+        assert!(floor <= build(1_767_225_600_000, 0, 0, [0; 16]));
+        assert!(floor > build(1_767_225_599_999, MAX_WORKER_ID, MAX_SEQUENCE, [0xFF; 16]));
+    }
 }
@@ -0,0 +1,31 @@
+//! Trace context propagation between processes, as W3C `traceparent`
+//! values: into gateway events, outbound federation requests and internal
+//! RPC, and back out of incoming requests.
+//!
+//! `nexus-server` exports spans over OTLP when `telemetry.otlp_endpoint` is
+//! set, and installs the propagator used here. Until then these are no-ops:
+//! nothing is injected and extracted contexts are empty.
+
+use std::collections::HashMap;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+/// Header (and metadata key) carrying the context.
+pub const TRACEPARENT: &str = "traceparent";
+
+/// The current span's context as a `traceparent`, if it is being traced.
+pub fn current_traceparent() -> Option<String> {
+    let context = tracing::Span::current().context();
+    let mut carrier = HashMap::new();
+    opentelemetry::global::get_text_map_propagator(|propagator| {
+        propagator.inject_context(&context, &mut carrier)
+    });
+    carrier.remove(TRACEPARENT)
+}
+
+/// Make `span` a child of the remote span `traceparent` names. Invalid
+/// values are ignored and `span` stays a root.
+pub fn set_parent(span: &tracing::Span, traceparent: &str) {
+    let carrier = HashMap::from([(TRACEPARENT.to_owned(), traceparent.to_owned())]);
+    let context = opentelemetry::global::get_text_map_propagator(|propagator| propagator.extract(&carrier));
+    span.set_parent(context);
+}
@@ -0,0 +1,366 @@
+//! Timestamp markup tokens.
+//!
+//! Message content may embed `<t:UNIX>` or `<t:UNIX:STYLE>`, where `UNIX` is
+//! a Unix timestamp in seconds. Clients replace each token with the time in
+//! the viewer's own timezone and clock format, so "the raid starts at
+//! <t:1767225600:t>" reads correctly for everyone in the channel.
+//!
+//! | style | example (en, 12-hour)               |
+//! |-------|-------------------------------------|
+//! | `t`   | 4:05 PM                             |
+//! | `T`   | 4:05:09 PM                          |
+//! | `d`   | 10/15/2026                          |
+//! | `D`   | October 15, 2026                    |
+//! | `f`   | October 15, 2026 4:05 PM (default)  |
+//! | `F`   | Thursday, October 15, 2026 4:05 PM  |
+//! | `R`   | in 3 hours / 2 days ago             |
+//!
+//! The server rejects malformed tokens on write (see [`validate_tokens`]);
+//! [`render`] is the reference renderer shared by the SDK and clients.
+
+use std::sync::LazyLock;
+
+use chrono::{DateTime, FixedOffset, Utc};
+use regex::Regex;
+use validator::ValidationError;
+
+/// Largest timestamp magnitude accepted, in seconds (roughly the year
+/// 261,000) — inside what both chrono and a JavaScript `Date` can represent,
+/// so every client can render every valid token.
+pub const MAX_UNIX_SECONDS: i64 = 8_200_000_000_000;
+
+/// Anything shaped like a token; the body is checked separately so that
+/// near-misses such as `<t:soon>` are reported instead of ignored.
+static TOKEN: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"<t:([^<>\s]*)>").unwrap());
+
+/// How a timestamp token is displayed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TimestampStyle {
+    /// `t` — hours and minutes
+    ShortTime,
+    /// `T` — hours, minutes and seconds
+    LongTime,
+    /// `d` — numeric date
+    ShortDate,
+    /// `D` — date with the month spelled out
+    LongDate,
+    /// `f` — long date and short time
+    #[default]
+    ShortDateTime,
+    /// `F` — weekday, long date and short time
+    LongDateTime,
+    /// `R` — relative to now
+    Relative,
+}
+
+impl TimestampStyle {
+    pub fn from_char(c: char) -> Option<Self> {
+        Some(match c {
+            't' => Self::ShortTime,
+            'T' => Self::LongTime,
+            'd' => Self::ShortDate,
+            'D' => Self::LongDate,
+            'f' => Self::ShortDateTime,
+            'F' => Self::LongDateTime,
+            'R' => Self::Relative,
+            _ => return None,
+        })
+    }
+
+    pub fn as_char(self) -> char {
+        match self {
+            Self::ShortTime => 't',
+            Self::LongTime => 'T',
+            Self::ShortDate => 'd',
+            Self::LongDate => 'D',
+            Self::ShortDateTime => 'f',
+            Self::LongDateTime => 'F',
+            Self::Relative => 'R',
+        }
+    }
+}
+
+/// A well-formed timestamp token found in message content.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimestampToken {
+    /// Byte range of the whole token in the content.
+    pub start: usize,
+    pub end: usize,
+    pub unix: i64,
+    pub style: TimestampStyle,
+}
+
+impl TimestampToken {
+    pub fn datetime(&self) -> DateTime<Utc> {
+        // In range by construction: parse_body rejects anything past MAX_UNIX_SECONDS.
+        DateTime::from_timestamp(self.unix, 0).unwrap_or_default()
+    }
+}
+
+/// Why a token was rejected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TokenError {
+    /// Not a whole number of seconds.
+    InvalidTimestamp(String),
+    /// Outside ±[`MAX_UNIX_SECONDS`].
+    OutOfRange(i64),
+    /// Style is not one of `tTdDfFR`.
+    UnknownStyle(String),
+}
+
+impl std::fmt::Display for TokenError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidTimestamp(s) => write!(f, "'{s}' is not a Unix timestamp in seconds"),
+            Self::OutOfRange(n) => write!(f, "timestamp {n} is out of range"),
+            Self::UnknownStyle(s) => {
+                write!(f, "unknown timestamp style '{s}' (expected one of t, T, d, D, f, F, R)")
+            }
+        }
+    }
+}
+
+fn parse_body(body: &str) -> Result<(i64, TimestampStyle), TokenError> {
+    let (unix, style) = match body.split_once(':') {
+        Some((unix, style)) => (unix, Some(style)),
+        None => (body, None),
+    };
+    let unix: i64 = unix
+        .parse()
+        .map_err(|_| TokenError::InvalidTimestamp(unix.to_owned()))?;
+    if unix.abs() > MAX_UNIX_SECONDS {
+        return Err(TokenError::OutOfRange(unix));
+    }
+    let style = match style {
+        None => TimestampStyle::default(),
+        Some(s) => {
+            let mut chars = s.chars();
+            match (chars.next().and_then(TimestampStyle::from_char), chars.next()) {
+                (Some(style), None) => style,
+                _ => return Err(TokenError::UnknownStyle(s.to_owned())),
+            }
+        }
+    };
+    Ok((unix, style))
+}
+
+/// Every token in `content`, well-formed or not, in order.
+pub fn scan(content: &str) -> Vec<Result<TimestampToken, TokenError>> {
+    TOKEN
+        .captures_iter(content)
+        .map(|caps| {
+            let whole = caps.get(0).expect("match has group 0");
+            let (unix, style) = parse_body(&caps[1])?;
+            Ok(TimestampToken {
+                start: whole.start(),
+                end: whole.end(),
+                unix,
+                style,
+            })
+        })
+        .collect()
+}
+
+/// The well-formed tokens in `content`. Malformed ones are skipped and
+/// should be shown as literal text.
+pub fn parse(content: &str) -> Vec<TimestampToken> {
+    scan(content).into_iter().filter_map(Result::ok).collect()
+}
+
+/// `validator` hook for content fields: fails on the first malformed token.
+pub fn validate_tokens(content: &str) -> Result<(), ValidationError> {
+    match scan(content).into_iter().find_map(Result::err) {
+        None => Ok(()),
+        Some(e) => Err(ValidationError::new("timestamp_token").with_message(e.to_string().into())),
+    }
+}
+
+/// The viewer's display preferences.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ViewerClock {
+    /// Offset from UTC in minutes (e.g. -300 for UTC−5).
+    pub utc_offset_minutes: i32,
+    /// 12-hour clock with AM/PM instead of 24-hour.
+    pub hour12: bool,
+    /// Day before month in numeric dates (15/10/2026 rather than 10/15/2026).
+    pub day_first: bool,
+}
+
+impl Default for ViewerClock {
+    fn default() -> Self {
+        Self {
+            utc_offset_minutes: 0,
+            hour12: true,
+            day_first: false,
+        }
+    }
+}
+
+impl ViewerClock {
+    fn offset(&self) -> FixedOffset {
+        FixedOffset::east_opt(self.utc_offset_minutes * 60)
+            .unwrap_or_else(|| FixedOffset::east_opt(0).expect("zero offset"))
+    }
+}
+
+/// Format one timestamp for `clock`; `now` anchors [`TimestampStyle::Relative`].
+pub fn format(
+    at: DateTime<Utc>,
+    style: TimestampStyle,
+    clock: &ViewerClock,
+    now: DateTime<Utc>,
+) -> String {
+    let local = at.with_timezone(&clock.offset());
+    let (short_time, long_time) = if clock.hour12 {
+        ("%-I:%M %p", "%-I:%M:%S %p")
+    } else {
+        ("%H:%M", "%H:%M:%S")
+    };
+    let (short_date, long_date) = if clock.day_first {
+        ("%d/%m/%Y", "%-d %B %Y")
+    } else {
+        ("%m/%d/%Y", "%B %-d, %Y")
+    };
+    let pattern = match style {
+        TimestampStyle::ShortTime => short_time.to_owned(),
+        TimestampStyle::LongTime => long_time.to_owned(),
+        TimestampStyle::ShortDate => short_date.to_owned(),
+        TimestampStyle::LongDate => long_date.to_owned(),
+        TimestampStyle::ShortDateTime => format!("{long_date} {short_time}"),
+        TimestampStyle::LongDateTime => format!("%A, {long_date} {short_time}"),
+        TimestampStyle::Relative => return relative(at - now),
+    };
+    local.format(&pattern).to_string()
+}
+
+/// "in 3 hours" / "2 days ago", using the largest whole unit.
+fn relative(delta: chrono::TimeDelta) -> String {
+    let secs = delta.num_seconds();
+    let abs = secs.unsigned_abs();
+    let (n, unit) = match abs {
+        0..60 => (abs, "second"),
+        60..3_600 => (abs / 60, "minute"),
+        3_600..86_400 => (abs / 3_600, "hour"),
+        86_400..2_592_000 => (abs / 86_400, "day"),
+        2_592_000..31_536_000 => (abs / 2_592_000, "month"),
+        _ => (abs / 31_536_000, "year"),
+    };
+    let amount = format!("{n} {unit}{}", if n == 1 { "" } else { "s" });
+    if secs < 0 {
+        format!("{amount} ago")
+    } else {
+        format!("in {amount}")
+    }
+}
+
+/// Replace every well-formed token in `content` with its formatted time.
+pub fn render(content: &str, clock: &ViewerClock, now: DateTime<Utc>) -> String {
+    let mut out = String::with_capacity(content.len());
+    let mut last = 0;
+    for token in parse(content) {
+        out.push_str(&content[last..token.start]);
+        out.push_str(&format(token.datetime(), token.style, clock, now));
+        last = token.end;
+    }
+    out.push_str(&content[last..]);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Thursday 2026-10-15 16:05:09 UTC
+    const AT: i64 = 1_792_080_309;
+
+    fn at() -> DateTime<Utc> {
+        DateTime::from_timestamp(AT, 0).unwrap()
+    }
+
+    #[test]
+    fn parses_tokens_with_and_without_style() {
+        let content = format!("starts <t:{AT}:R>, i.e. <t:{AT}> or <t:-60:d>");
+        let tokens = parse(&content);
+        assert_eq!(tokens.len(), 3);
+        assert_eq!(tokens[0].style, TimestampStyle::Relative);
+        assert_eq!(tokens[1].style, TimestampStyle::ShortDateTime);
+        assert_eq!(tokens[2].unix, -60);
+        assert_eq!(&content[tokens[0].start..tokens[0].end], format!("<t:{AT}:R>"));
+    }
+
+    #[test]
+    fn rejects_malformed_tokens() {
+        assert!(validate_tokens("plain text, <b>not a token</b>").is_ok());
+        assert_eq!(
+            scan("<t:soon>")[0],
+            Err(TokenError::InvalidTimestamp("soon".into()))
+        );
+        assert_eq!(
+            scan("<t:1:x>")[0],
+            Err(TokenError::UnknownStyle("x".into()))
+        );
+        assert_eq!(
+            scan("<t:1:ff>")[0],
+            Err(TokenError::UnknownStyle("ff".into()))
+        );
+        assert_eq!(
+            scan("<t:99999999999999:f>")[0],
+            Err(TokenError::OutOfRange(99_999_999_999_999))
+        );
+        let err = validate_tokens("ok <t:1:R> then <t:1.5>").unwrap_err();
+        assert_eq!(err.code, "timestamp_token");
+    }
+
+    #[test]
+    fn formats_every_style_in_viewer_timezone() {
+        let us = ViewerClock {
+            utc_offset_minutes: -240,
+            ..Default::default()
+        };
+        let eu = ViewerClock {
+            utc_offset_minutes: 120,
+            hour12: false,
+            day_first: true,
+        };
+        let f = |style, clock: &ViewerClock| format(at(), style, clock, at());
+        assert_eq!(f(TimestampStyle::ShortTime, &us), "12:05 PM");
+        assert_eq!(f(TimestampStyle::LongTime, &us), "12:05:09 PM");
+        assert_eq!(f(TimestampStyle::ShortDate, &us), "10/15/2026");
+        assert_eq!(f(TimestampStyle::LongDate, &us), "October 15, 2026");
+        assert_eq!(f(TimestampStyle::ShortDateTime, &us), "October 15, 2026 12:05 PM");
+        assert_eq!(
+            f(TimestampStyle::LongDateTime, &us),
+            "Thursday, October 15, 2026 12:05 PM"
+        );
+        assert_eq!(f(TimestampStyle::ShortTime, &eu), "18:05");
+        assert_eq!(f(TimestampStyle::ShortDate, &eu), "15/10/2026");
+        assert_eq!(f(TimestampStyle::LongDateTime, &eu), "Thursday, 15 October 2026 18:05");
+    }
+
+    #[test]
+    fn formats_relative_times() {
+        let clock = ViewerClock::default();
+        let rel = |secs: i64| {
+            format(
+                at() + chrono::TimeDelta::seconds(secs),
+                TimestampStyle::Relative,
+                &clock,
+                at(),
+            )
+        };
+        assert_eq!(rel(0), "in 0 seconds");
+        assert_eq!(rel(-1), "1 second ago");
+        assert_eq!(rel(3 * 3_600), "in 3 hours");
+        assert_eq!(rel(-2 * 86_400 - 5), "2 days ago");
+        assert_eq!(rel(400 * 86_400), "in 1 year");
+    }
+
+    #[test]
+    fn renders_tokens_and_leaves_malformed_ones() {
+        let clock = ViewerClock::default();
+        assert_eq!(
+            render(&format!("at <t:{AT}:t>, not <t:later>"), &clock, at()),
+            "at 4:05 PM, not <t:later>"
+        );
+    }
+}
@@ -2,34 +2,88 @@
 //!
 //! Centralized validation helpers used across API routes.
 
-use validator::Validate;
+use validator::{Validate, ValidationError, ValidationErrors, ValidationErrorsKind};
 
-use crate::error::NexusError;
+use crate::error::{FieldError, NexusError};
 
-/// Validate a request body, returning a NexusError::Validation on failure.
+/// Validate a request body, returning a NexusError::InvalidFields on failure.
 pub fn validate_request<T: Validate>(body: &T) -> Result<(), NexusError> {
-    body.validate().map_err(|e| NexusError::Validation {
-        message: format_validation_errors(e),
+    body.validate().map_err(|e| NexusError::InvalidFields {
+        errors: field_errors(&e),
     })
 }
 
-/// Format validation errors into a human-readable string.
-fn format_validation_errors(errors: validator::ValidationErrors) -> String {
-    errors
-        .field_errors()
+/// Flatten validator errors into per-field details, sorted by field path.
+///
+/// Nested structs and lists get dotted / indexed paths, e.g.
+/// `embeds[0].title`.
+pub fn field_errors(errors: &ValidationErrors) -> Vec<FieldError> {
+    let mut fields = Vec::new();
+    collect_field_errors(errors, "", &mut fields);
+    fields.sort_by(|a, b| a.field.cmp(&b.field));
+    fields
+}
+
+fn collect_field_errors(errors: &ValidationErrors, prefix: &str, out: &mut Vec<FieldError>) {
+    for (field, kind) in errors.errors() {
+        let path = if prefix.is_empty() {
+            field.to_string()
+        } else {
+            format!("{prefix}.{field}")
+        };
+        match kind {
+            ValidationErrorsKind::Field(errs) => {
+                out.extend(errs.iter().map(|e| field_error(&path, e)));
+            }
+            ValidationErrorsKind::Struct(nested) => collect_field_errors(nested, &path, out),
+            ValidationErrorsKind::List(items) => {
+                for (index, nested) in items {
+                    collect_field_errors(nested, &format!("{path}[{index}]"), out);
+                }
+            }
+        }
+    }
+}
+
+fn field_error(field: &str, e: &ValidationError) -> FieldError {
+    // `value` echoes the rejected input (possibly a password) — never send it back.
+    let constraint: serde_json::Map<String, serde_json::Value> = e
+        .params
         .iter()
-        .flat_map(|(field, errs)| {
-            errs.iter().map(move |e| {
-                let msg = e
-                    .message
-                    .as_ref()
-                    .map(|m| m.to_string())
-                    .unwrap_or_else(|| format!("Invalid value for '{field}'"));
-                msg
-            })
-        })
-        .collect::<Vec<_>>()
-        .join("; ")
+        .filter(|(k, _)| *k != "value")
+        .map(|(k, v)| (k.to_string(), v.clone()))
+        .collect();
+    let message = e
+        .message
+        .as_ref()
+        .map(|m| m.to_string())
+        .unwrap_or_else(|| default_message(field, &e.code, &constraint));
+    FieldError {
+        field: field.to_owned(),
+        code: e.code.to_string(),
+        constraint: (!constraint.is_empty()).then_some(serde_json::Value::Object(constraint)),
+        message,
+    }
+}
+
+/// Message for rules declared without one.
+fn default_message(
+    field: &str,
+    code: &str,
+    constraint: &serde_json::Map<String, serde_json::Value>,
+) -> String {
+    let (min, max) = (constraint.get("min"), constraint.get("max"));
+    match (code, min, max) {
+        ("length", Some(min), Some(max)) => format!("'{field}' must be {min}-{max} characters"),
+        ("length", None, Some(max)) => format!("'{field}' must be at most {max} characters"),
+        ("length", Some(min), None) => format!("'{field}' must be at least {min} characters"),
+        ("range", Some(min), Some(max)) => format!("'{field}' must be between {min} and {max}"),
+        ("range", None, Some(max)) => format!("'{field}' must be at most {max}"),
+        ("range", Some(min), None) => format!("'{field}' must be at least {min}"),
+        ("email", ..) => format!("'{field}' must be a valid email address"),
+        ("url", ..) => format!("'{field}' must be a valid URL"),
+        _ => format!("Invalid value for '{field}'"),
+    }
 }
 
 /// Validate that a string is a safe channel/server name (no special chars that break routing).
@@ -54,3 +108,47 @@ pub fn validate_name(name: &str) -> Result<(), NexusError> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Validate)]
+    struct Item {
+        #[validate(length(max = 4))]
+        label: String,
+    }
+
+    #[derive(Validate)]
+    struct Body {
+        #[validate(length(min = 1, max = 10, message = "Name must be 1-10 characters"))]
+        name: String,
+        #[validate(range(min = 0, max = 60))]
+        delay: i32,
+        #[validate(nested)]
+        items: Vec<Item>,
+    }
+
+    #[test]
+    fn reports_each_field_with_its_constraint() {
+        let body = Body {
+            name: String::new(),
+            delay: 90,
+            items: vec![
+                Item { label: "ok".into() },
+                Item { label: "too long".into() },
+            ],
+        };
+        let errors = field_errors(&body.validate().unwrap_err());
+        let fields: Vec<&str> = errors.iter().map(|e| e.field.as_str()).collect();
+        assert_eq!(fields, ["delay", "items[1].label", "name"]);
+
+        assert_eq!(errors[0].code, "range");
+        let constraint = errors[0].constraint.as_ref().unwrap();
+        assert_eq!(constraint["max"].as_f64(), Some(60.0));
+        assert!(constraint.get("value").is_none());
+        assert!(errors[0].message.starts_with("'delay' must be between 0"));
+        assert_eq!(errors[1].message, "'items[1].label' must be at most 4 characters");
+        assert_eq!(errors[2].message, "Name must be 1-10 characters");
+    }
+}
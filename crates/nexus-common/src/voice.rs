@@ -0,0 +1,147 @@
+//! Voice signaling protocol — the messages exchanged over the voice
+//! WebSocket while negotiating a WebRTC connection with the SFU.
+//!
+//! The server side lives in `nexus-voice`; this module lives in
+//! `nexus-common` so clients can speak the same protocol.
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Voice signaling messages (client ↔ server).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", content = "d")]
+pub enum VoiceSignal {
+    // === Client → Server ===
+    /// Authenticate with JWT token.
+    Identify {
+        token: String,
+    },
+
+    /// Join a voice channel.
+    Join {
+        channel_id: Uuid,
+        server_id: Option<Uuid>,
+    },
+
+    /// Send SDP offer to establish WebRTC connection.
+    Offer {
+        sdp: String,
+    },
+
+    /// Answer to a `ServerOffer`.
+    ClientAnswer {
+        sdp: String,
+    },
+
+    /// Send ICE candidate.
+    IceCandidate {
+        candidate: String,
+        sdp_mid: Option<String>,
+        sdp_m_line_index: Option<u32>,
+    },
+
+    /// Update self mute/deaf/video state.
+    StateUpdate {
+        self_mute: Option<bool>,
+        self_deaf: Option<bool>,
+        self_video: Option<bool>,
+        self_stream: Option<bool>,
+    },
+
+    /// Set speaking state (voice activity detection result).
+    Speaking {
+        speaking: bool,
+    },
+
+    /// Leave voice channel.
+    Leave,
+
+    // === Server → Client ===
+    /// Authentication successful.
+    Ready {
+        session_id: String,
+    },
+
+    /// Joined voice channel — here's the current state.
+    Joined {
+        channel_id: Uuid,
+        voice_states: Vec<serde_json::Value>,
+        ice_servers: Vec<IceServerConfig>,
+        /// The channel's linked text chat.
+        text_channel_id: Option<Uuid>,
+    },
+
+    /// SDP answer from the SFU.
+    Answer {
+        sdp: String,
+    },
+
+    /// SDP offer from the SFU adding or removing the tracks forwarded from
+    /// other participants; reply with `ClientAnswer`. Each track's stream id
+    /// is the publishing user's id.
+    ServerOffer {
+        sdp: String,
+    },
+
+    /// ICE candidate from the server.
+    ServerIceCandidate {
+        candidate: String,
+        sdp_mid: Option<String>,
+        sdp_m_line_index: Option<u32>,
+    },
+
+    /// Another user's voice state changed (joined, left, mute, etc.).
+    VoiceStateUpdate {
+        state: serde_json::Value,
+    },
+
+    /// Speaking state changed for a user.
+    SpeakingUpdate {
+        user_id: Uuid,
+        speaking: bool,
+    },
+
+    /// The room is hosted on another voice node — reconnect to `url`,
+    /// identify again, and repeat the Join there.
+    Redirect {
+        channel_id: Uuid,
+        url: String,
+    },
+
+    /// Error occurred.
+    Error {
+        code: u32,
+        message: String,
+    },
+}
+
+/// ICE server configuration sent to clients.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IceServerConfig {
+    pub urls: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub credential: Option<String>,
+}
+
+impl IceServerConfig {
+    /// Default STUN servers (free, public).
+    pub fn defaults() -> Vec<Self> {
+        vec![
+            Self {
+                urls: vec![
+                    "stun:stun.l.google.com:19302".into(),
+                    "stun:stun1.l.google.com:19302".into(),
+                ],
+                username: None,
+                credential: None,
+            },
+            Self {
+                urls: vec!["stun:stun.cloudflare.com:3478".into()],
+                username: None,
+                credential: None,
+            },
+        ]
+    }
+}
@@ -0,0 +1,89 @@
+//! Criterion benchmarks for nexus-db repository writes, against an in-memory
+//! SQLite database.
+//!
+//! Run with:
+//!   cargo bench -p nexus-db
+//!
+//! HTML reports are written to `target/criterion/`.
+
+use criterion::{BenchmarkId, Criterion, black_box, criterion_group, criterion_main};
+use nexus_db::repository::keystore;
+use sqlx::any::AnyPoolOptions;
+use tokio::runtime::Runtime;
+use uuid::Uuid;
+
+/// `one_time_pre_keys` as in the lite schema, without the `devices` foreign key.
+const SCHEMA: &str = r#"
+CREATE TABLE one_time_pre_keys (
+    id          TEXT PRIMARY KEY,
+    device_id   TEXT NOT NULL,
+    key_id      INTEGER NOT NULL,
+    public_key  TEXT NOT NULL,
+    used        INTEGER NOT NULL DEFAULT 0,
+    created_at  TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (device_id, key_id)
+)
+"#;
+
+async fn pool() -> sqlx::AnyPool {
+    sqlx::any::install_default_drivers();
+    let pool = AnyPoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .unwrap();
+    sqlx::query(SCHEMA).execute(&pool).await.unwrap();
+    pool
+}
+
+fn keys(count: i32) -> Vec<(i32, String)> {
+    (0..count)
+        .map(|key_id| (key_id, format!("otpk-public-key-{key_id:0>32}")))
+        .collect()
+}
+
+/// One INSERT per key — how uploads were stored before the bulk insert.
+async fn insert_one_by_one(pool: &sqlx::AnyPool, device_id: Uuid, keys: &[(i32, String)]) {
+    for (key_id, public_key) in keys {
+        sqlx::query(
+            "INSERT INTO one_time_pre_keys (device_id, key_id, public_key) VALUES (?, ?, ?) \
+             ON CONFLICT (device_id, key_id) DO NOTHING",
+        )
+        .bind(device_id.to_string())
+        .bind(key_id)
+        .bind(public_key)
+        .execute(pool)
+        .await
+        .unwrap();
+    }
+}
+
+// ── One-time pre-key uploads ──────────────────────────────────────────────────
+
+/// Store an upload of N one-time pre-keys for a new device, row by row and in bulk.
+fn bench_otpk_upload(c: &mut Criterion) {
+    let rt = Runtime::new().unwrap();
+    let pool = rt.block_on(pool());
+    let mut group = c.benchmark_group("keystore/otpk_upload");
+
+    for count in [10i32, 100, 1000] {
+        let upload = keys(count);
+        group.bench_with_input(BenchmarkId::new("one_by_one", count), &upload, |b, upload| {
+            b.iter(|| rt.block_on(insert_one_by_one(&pool, Uuid::now_v7(), black_box(upload))))
+        });
+        group.bench_with_input(BenchmarkId::new("bulk", count), &upload, |b, upload| {
+            b.iter(|| {
+                rt.block_on(keystore::insert_one_time_pre_keys(&pool, Uuid::now_v7(), black_box(upload)))
+                    .unwrap()
+            })
+        });
+    }
+
+    group.finish();
+}
+
+// ── criterion entrypoints ─────────────────────────────────────────────────────
+
+criterion_group!(keystore_writes, bench_otpk_upload);
+
+criterion_main!(keystore_writes);
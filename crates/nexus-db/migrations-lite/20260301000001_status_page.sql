@@ -0,0 +1,31 @@
+-- Status page — component self-checks and uptime history (lite mode)
+
+-- ============================================================
+-- Component self-checks
+-- ============================================================
+CREATE TABLE IF NOT EXISTS status_checks (
+    id          TEXT PRIMARY KEY,
+    component   TEXT NOT NULL,
+    status      TEXT NOT NULL CHECK (status IN ('operational', 'degraded', 'down')),
+    latency_ms  INTEGER,
+    detail      TEXT,
+    checked_at  TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_status_checks_component ON status_checks (component, checked_at DESC);
+
+-- ============================================================
+-- Federation outbox
+-- ============================================================
+CREATE TABLE IF NOT EXISTS federation_outbox (
+    id              TEXT PRIMARY KEY,
+    destination     TEXT NOT NULL,
+    txn_id          TEXT NOT NULL,
+    payload         TEXT NOT NULL DEFAULT '{}',
+    attempts        INTEGER NOT NULL DEFAULT 0,
+    last_error      TEXT,
+    created_at      TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    delivered_at    TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_federation_outbox_pending ON federation_outbox (delivered_at, created_at);
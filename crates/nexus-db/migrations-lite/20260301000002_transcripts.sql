@@ -0,0 +1,22 @@
+-- Speech-to-text transcripts for voice messages and recordings (lite mode)
+
+CREATE TABLE IF NOT EXISTS transcripts (
+    id              TEXT PRIMARY KEY,
+    source_type     TEXT NOT NULL CHECK (source_type IN ('attachment', 'recording')),
+    source_id       TEXT NOT NULL,
+    storage_key     TEXT NOT NULL,
+    content_type    TEXT NOT NULL,
+    channel_id      TEXT REFERENCES channels(id) ON DELETE CASCADE,
+    status          TEXT NOT NULL DEFAULT 'pending',
+    language        TEXT,
+    text            TEXT,
+    segments        TEXT NOT NULL DEFAULT '[]',
+    provider        TEXT,
+    attempts        INTEGER NOT NULL DEFAULT 0,
+    last_error      TEXT,
+    created_at      TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    completed_at    TEXT,
+    UNIQUE (source_type, source_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_transcripts_status ON transcripts (status, created_at);
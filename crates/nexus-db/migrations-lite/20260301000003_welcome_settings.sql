@@ -0,0 +1,17 @@
+-- Server join actions — welcome messages, DM greetings, auto-roles and
+-- membership screening (lite mode)
+
+CREATE TABLE IF NOT EXISTS server_welcome_settings (
+    server_id           TEXT PRIMARY KEY REFERENCES servers(id) ON DELETE CASCADE,
+    channel_enabled     INTEGER NOT NULL DEFAULT 0,
+    channel_id          TEXT REFERENCES channels(id) ON DELETE SET NULL,
+    channel_message     TEXT,
+    dm_enabled          INTEGER NOT NULL DEFAULT 0,
+    dm_message          TEXT,
+    auto_role_ids       TEXT NOT NULL DEFAULT '[]',
+    screening_enabled   INTEGER NOT NULL DEFAULT 0,
+    screening_rules     TEXT,
+    updated_at          TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+ALTER TABLE members ADD COLUMN pending INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,7 @@
+-- Webhook-authored messages (lite mode)
+
+ALTER TABLE messages ADD COLUMN webhook_id TEXT REFERENCES webhooks(id) ON DELETE SET NULL;
+ALTER TABLE messages ADD COLUMN webhook_username TEXT;
+ALTER TABLE messages ADD COLUMN webhook_avatar_url TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_messages_webhook ON messages (webhook_id);
@@ -0,0 +1,26 @@
+-- Scheduled server events and per-member calendar (ICS) feeds (lite mode)
+
+CREATE TABLE IF NOT EXISTS scheduled_events (
+    id              TEXT PRIMARY KEY,
+    server_id       TEXT NOT NULL REFERENCES servers(id) ON DELETE CASCADE,
+    channel_id      TEXT REFERENCES channels(id) ON DELETE SET NULL,
+    creator_id      TEXT REFERENCES users(id) ON DELETE SET NULL,
+    name            TEXT NOT NULL,
+    description     TEXT,
+    location        TEXT,
+    start_time      TEXT NOT NULL,
+    end_time        TEXT,
+    status          TEXT NOT NULL DEFAULT 'scheduled',
+    created_at      TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at      TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_scheduled_events_server ON scheduled_events(server_id, start_time);
+
+CREATE TABLE IF NOT EXISTS event_feed_tokens (
+    token           TEXT PRIMARY KEY,
+    server_id       TEXT NOT NULL REFERENCES servers(id) ON DELETE CASCADE,
+    user_id         TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_at      TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (server_id, user_id)
+);
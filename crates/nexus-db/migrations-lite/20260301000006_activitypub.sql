@@ -0,0 +1,22 @@
+-- ActivityPub outbound bridge — actor key and channel followers (lite mode)
+
+CREATE TABLE IF NOT EXISTS activitypub_keys (
+    id                  TEXT PRIMARY KEY,
+    private_key_pem     TEXT NOT NULL,
+    public_key_pem      TEXT NOT NULL,
+    created_at          TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS activitypub_followers (
+    id                  TEXT PRIMARY KEY,
+    channel_id          TEXT NOT NULL REFERENCES channels(id) ON DELETE CASCADE,
+    actor_uri           TEXT NOT NULL,
+    inbox_url           TEXT NOT NULL,
+    shared_inbox_url    TEXT,
+    created_at          TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (channel_id, actor_uri)
+);
+
+CREATE INDEX IF NOT EXISTS idx_activitypub_followers_channel ON activitypub_followers (channel_id);
+
+ALTER TABLE federation_outbox ADD COLUMN next_attempt_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP;
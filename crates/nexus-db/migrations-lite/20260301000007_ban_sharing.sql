@@ -0,0 +1,14 @@
+-- Ban list subscriptions (lite mode)
+
+CREATE TABLE IF NOT EXISTS ban_list_subscriptions (
+    id                  TEXT PRIMARY KEY,
+    server_id           TEXT NOT NULL REFERENCES servers(id) ON DELETE CASCADE,
+    source_server_id    TEXT REFERENCES servers(id) ON DELETE CASCADE,
+    source_url          TEXT,
+    created_by          TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    last_synced_at      TEXT,
+    last_error          TEXT,
+    created_at          TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_ban_list_subscriptions_server ON ban_list_subscriptions(server_id);
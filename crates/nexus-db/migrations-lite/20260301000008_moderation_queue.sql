@@ -0,0 +1,27 @@
+-- Pre-moderation queue (lite mode)
+
+CREATE TABLE IF NOT EXISTS channel_moderation_settings (
+    channel_id          TEXT PRIMARY KEY REFERENCES channels(id) ON DELETE CASCADE,
+    hold_new_members    INTEGER NOT NULL DEFAULT 0,
+    new_member_minutes  INTEGER NOT NULL DEFAULT 1440,
+    hold_links          INTEGER NOT NULL DEFAULT 0,
+    updated_at          TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS pending_messages (
+    id                      TEXT PRIMARY KEY,
+    channel_id              TEXT NOT NULL REFERENCES channels(id) ON DELETE CASCADE,
+    server_id               TEXT NOT NULL REFERENCES servers(id) ON DELETE CASCADE,
+    author_id               TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    content                 TEXT NOT NULL,
+    reference_message_id    TEXT,
+    reference_channel_id    TEXT,
+    flags                   INTEGER NOT NULL DEFAULT 0,
+    attachment_ids          TEXT NOT NULL DEFAULT '[]',
+    spoiler_attachment_ids  TEXT NOT NULL DEFAULT '[]',
+    reason                  TEXT NOT NULL,
+    created_at              TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_pending_messages_channel ON pending_messages(channel_id, created_at);
+CREATE INDEX IF NOT EXISTS idx_pending_messages_server ON pending_messages(server_id, created_at);
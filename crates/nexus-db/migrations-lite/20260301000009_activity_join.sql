@@ -0,0 +1,20 @@
+-- Rich presence activities with joinable parties (lite mode)
+
+CREATE TABLE IF NOT EXISTS user_activities (
+    user_id         TEXT PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    activity_type   TEXT,
+    name            TEXT,
+    details         TEXT,
+    state           TEXT,
+    large_image     TEXT,
+    small_image     TEXT,
+    url             TEXT,
+    application_id  TEXT,
+    started_at      TEXT,
+    ends_at         TEXT,
+    party_id        TEXT,
+    party_size      INTEGER,
+    party_max       INTEGER,
+    join_secret     TEXT,
+    updated_at      TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
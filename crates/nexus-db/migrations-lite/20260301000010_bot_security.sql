@@ -0,0 +1,14 @@
+-- Bot token rotation, IP allowlists and request metrics (lite mode)
+
+ALTER TABLE bots ADD COLUMN token_reset_at TEXT;
+ALTER TABLE bots ADD COLUMN ip_allowlist TEXT NOT NULL DEFAULT '[]';
+
+CREATE TABLE IF NOT EXISTS bot_request_metrics (
+    bot_id          TEXT NOT NULL REFERENCES bots(id) ON DELETE CASCADE,
+    day             TEXT NOT NULL,
+    requests        INTEGER NOT NULL DEFAULT 0,
+    errors          INTEGER NOT NULL DEFAULT 0,
+    rejected        INTEGER NOT NULL DEFAULT 0,
+    total_latency_ms INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (bot_id, day)
+);
@@ -0,0 +1,6 @@
+-- Migration: Indexes backing the audit log endpoint's action, actor and
+-- target filters.
+
+CREATE INDEX IF NOT EXISTS idx_audit_log_action ON audit_log (server_id, action, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_audit_log_user ON audit_log (user_id);
+CREATE INDEX IF NOT EXISTS idx_audit_log_target ON audit_log (target_id);
@@ -0,0 +1,12 @@
+-- Message edit history (lite mode)
+
+CREATE TABLE IF NOT EXISTS message_revisions (
+    id          TEXT PRIMARY KEY,
+    message_id  TEXT NOT NULL REFERENCES messages(id) ON DELETE CASCADE,
+    content     TEXT NOT NULL,
+    created_at  TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_message_revisions_message ON message_revisions (message_id, created_at);
+
+ALTER TABLE servers ADD COLUMN message_history INTEGER NOT NULL DEFAULT 1;
@@ -0,0 +1,15 @@
+-- Forum channels (lite mode)
+
+CREATE TABLE IF NOT EXISTS forum_tags (
+    id          TEXT PRIMARY KEY,
+    channel_id  TEXT NOT NULL REFERENCES channels(id) ON DELETE CASCADE,
+    name        TEXT NOT NULL,
+    emoji       TEXT,
+    moderated   INTEGER NOT NULL DEFAULT 0,
+    position    INTEGER NOT NULL DEFAULT 0,
+    created_at  TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_forum_tags_channel ON forum_tags (channel_id, position);
+
+ALTER TABLE threads ADD COLUMN pinned INTEGER NOT NULL DEFAULT 0;
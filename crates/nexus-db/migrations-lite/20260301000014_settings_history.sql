@@ -0,0 +1,22 @@
+-- Channel topic history and versioned server settings (lite mode)
+
+CREATE TABLE IF NOT EXISTS channel_topic_history (
+    id          TEXT PRIMARY KEY,
+    channel_id  TEXT NOT NULL REFERENCES channels(id) ON DELETE CASCADE,
+    topic       TEXT,
+    changed_by  TEXT REFERENCES users(id) ON DELETE SET NULL,
+    created_at  TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_channel_topic_history_channel ON channel_topic_history (channel_id, created_at DESC);
+
+CREATE TABLE IF NOT EXISTS server_settings_versions (
+    id          TEXT PRIMARY KEY,
+    server_id   TEXT NOT NULL REFERENCES servers(id) ON DELETE CASCADE,
+    version     INTEGER NOT NULL,
+    settings    TEXT NOT NULL,
+    changed_by  TEXT REFERENCES users(id) ON DELETE SET NULL,
+    rollback_of INTEGER,
+    created_at  TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (server_id, version)
+);
@@ -0,0 +1,25 @@
+-- Push notification tokens and preferences (lite mode)
+
+CREATE TABLE IF NOT EXISTS push_tokens (
+    id            TEXT PRIMARY KEY,
+    user_id       TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    platform      TEXT NOT NULL,
+    token         TEXT NOT NULL,
+    p256dh        TEXT,
+    auth          TEXT,
+    device_name   TEXT,
+    created_at    TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    last_used_at  TEXT,
+    UNIQUE (platform, token)
+);
+
+CREATE INDEX IF NOT EXISTS idx_push_tokens_user ON push_tokens (user_id);
+
+CREATE TABLE IF NOT EXISTS push_preferences (
+    user_id          TEXT PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    enabled          INTEGER NOT NULL DEFAULT 1,
+    direct_messages  INTEGER NOT NULL DEFAULT 1,
+    mentions         INTEGER NOT NULL DEFAULT 1,
+    show_content     INTEGER NOT NULL DEFAULT 1,
+    updated_at       TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
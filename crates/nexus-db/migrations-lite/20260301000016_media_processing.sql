@@ -0,0 +1,16 @@
+-- Attachment media processing queue (lite mode)
+
+ALTER TABLE attachments ADD COLUMN thumbnail_key TEXT;
+ALTER TABLE attachments ADD COLUMN thumbnail_url TEXT;
+
+CREATE TABLE IF NOT EXISTS media_jobs (
+    id              TEXT PRIMARY KEY,
+    attachment_id   TEXT NOT NULL UNIQUE REFERENCES attachments(id) ON DELETE CASCADE,
+    status          TEXT NOT NULL DEFAULT 'pending',
+    attempts        INTEGER NOT NULL DEFAULT 0,
+    last_error      TEXT,
+    created_at      TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    completed_at    TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_media_jobs_status ON media_jobs (status, created_at);
@@ -0,0 +1,8 @@
+-- Synced user settings (lite mode)
+
+CREATE TABLE IF NOT EXISTS user_settings (
+    user_id         TEXT PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    user_volumes    TEXT NOT NULL DEFAULT '{}',
+    version         INTEGER NOT NULL DEFAULT 1,
+    updated_at      TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
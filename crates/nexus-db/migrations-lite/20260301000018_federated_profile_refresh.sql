@@ -0,0 +1,7 @@
+-- Federated profile refresh (lite mode)
+
+ALTER TABLE federated_users ADD COLUMN last_active_at TEXT NOT NULL DEFAULT '1970-01-01 00:00:00';
+ALTER TABLE federated_users ADD COLUMN next_refresh_at TEXT;
+ALTER TABLE federated_users ADD COLUMN refresh_failures INTEGER NOT NULL DEFAULT 0;
+
+CREATE INDEX IF NOT EXISTS idx_federated_users_refresh ON federated_users (next_refresh_at, last_active_at);
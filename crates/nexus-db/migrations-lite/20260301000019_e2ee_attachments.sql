@@ -0,0 +1,23 @@
+-- End-to-end encrypted attachments (lite mode)
+
+-- Plaintext uploads check this table, so lite mode needs it too.
+CREATE TABLE IF NOT EXISTS e2ee_channels (
+    channel_id              TEXT PRIMARY KEY REFERENCES channels(id) ON DELETE CASCADE,
+    enabled_by              TEXT NOT NULL REFERENCES users(id),
+    enabled_at              TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    rotation_interval_secs  INTEGER NOT NULL DEFAULT 604800,
+    last_rotated_at         TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS e2ee_attachments (
+    id              TEXT PRIMARY KEY,
+    channel_id      TEXT NOT NULL REFERENCES channels(id) ON DELETE CASCADE,
+    uploader_id     TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    message_id      TEXT REFERENCES encrypted_messages(id) ON DELETE CASCADE,
+    storage_key     TEXT NOT NULL,
+    size            INTEGER NOT NULL,
+    sha256          TEXT NOT NULL,
+    created_at      TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_e2ee_attachments_message ON e2ee_attachments (message_id);
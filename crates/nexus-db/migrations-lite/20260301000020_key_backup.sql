@@ -0,0 +1,26 @@
+-- E2EE key backup (lite mode)
+
+CREATE TABLE IF NOT EXISTS key_backup_versions (
+    user_id         TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    version         INTEGER NOT NULL,
+    algorithm       TEXT NOT NULL,
+    auth_data       TEXT NOT NULL,
+    etag            INTEGER NOT NULL DEFAULT 0,
+    deleted         INTEGER NOT NULL DEFAULT 0,
+    created_at      TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (user_id, version)
+);
+
+CREATE TABLE IF NOT EXISTS key_backup_sessions (
+    user_id             TEXT NOT NULL,
+    version             INTEGER NOT NULL,
+    channel_id          TEXT NOT NULL,
+    session_id          TEXT NOT NULL,
+    first_message_index INTEGER NOT NULL,
+    forwarded_count     INTEGER NOT NULL,
+    is_verified         INTEGER NOT NULL,
+    session_data        TEXT NOT NULL,
+    updated_at          TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (user_id, version, channel_id, session_id),
+    FOREIGN KEY (user_id, version) REFERENCES key_backup_versions (user_id, version) ON DELETE CASCADE
+);
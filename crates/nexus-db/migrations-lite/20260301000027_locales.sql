@@ -0,0 +1,4 @@
+-- User locale and server preferred language (lite mode)
+
+ALTER TABLE users ADD COLUMN locale TEXT NOT NULL DEFAULT 'en-US';
+ALTER TABLE servers ADD COLUMN preferred_locale TEXT NOT NULL DEFAULT 'en-US';
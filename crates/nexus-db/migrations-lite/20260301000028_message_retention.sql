@@ -0,0 +1,6 @@
+-- Soft-deleted messages and per-server message retention (lite mode)
+
+ALTER TABLE messages ADD COLUMN deleted_at TEXT;
+CREATE INDEX idx_messages_deleted_at ON messages (deleted_at) WHERE deleted_at IS NOT NULL;
+
+ALTER TABLE servers ADD COLUMN message_retention_days INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,21 @@
+-- Starboard — messages that collect enough of a chosen reaction are reposted
+-- into a highlights channel (lite mode)
+
+CREATE TABLE IF NOT EXISTS server_starboard_settings (
+    server_id       TEXT PRIMARY KEY REFERENCES servers(id) ON DELETE CASCADE,
+    enabled         INTEGER NOT NULL DEFAULT 0,
+    channel_id      TEXT REFERENCES channels(id) ON DELETE SET NULL,
+    emoji           TEXT NOT NULL DEFAULT '⭐',
+    threshold       INTEGER NOT NULL DEFAULT 3,
+    updated_at      TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS starboard_entries (
+    message_id              TEXT PRIMARY KEY REFERENCES messages(id) ON DELETE CASCADE,
+    server_id               TEXT NOT NULL REFERENCES servers(id) ON DELETE CASCADE,
+    starboard_message_id    TEXT REFERENCES messages(id) ON DELETE SET NULL,
+    star_count              INTEGER NOT NULL DEFAULT 0,
+    created_at              TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_starboard_entries_server ON starboard_entries (server_id, created_at DESC);
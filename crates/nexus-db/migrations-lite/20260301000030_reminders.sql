@@ -0,0 +1,16 @@
+-- Reminders — users schedule a note to themselves, delivered privately or as
+-- a mention in a channel once it falls due (lite mode)
+
+CREATE TABLE IF NOT EXISTS reminders (
+    id              TEXT PRIMARY KEY,
+    user_id         TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    channel_id      TEXT REFERENCES channels(id) ON DELETE CASCADE,
+    message_id      TEXT REFERENCES messages(id) ON DELETE SET NULL,
+    content         TEXT NOT NULL,
+    remind_at       TEXT NOT NULL,
+    delivered_at    TEXT,
+    created_at      TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_reminders_user ON reminders (user_id, remind_at);
+CREATE INDEX IF NOT EXISTS idx_reminders_due ON reminders (remind_at) WHERE delivered_at IS NULL;
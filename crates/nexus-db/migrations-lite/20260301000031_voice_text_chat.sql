@@ -0,0 +1,13 @@
+-- Text chat for voice channels — each voice or stage channel gets a linked
+-- text channel, open to those who have recently been in the call (lite mode)
+
+ALTER TABLE channels ADD COLUMN voice_channel_id TEXT REFERENCES channels(id) ON DELETE CASCADE;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_channels_voice_channel ON channels (voice_channel_id) WHERE voice_channel_id IS NOT NULL;
+
+CREATE TABLE IF NOT EXISTS voice_participants (
+    channel_id      TEXT NOT NULL REFERENCES channels(id) ON DELETE CASCADE,
+    user_id         TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    joined_at       TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    left_at         TEXT,
+    PRIMARY KEY (channel_id, user_id)
+);
@@ -0,0 +1,3 @@
+-- Synced server list layout (lite mode)
+
+ALTER TABLE user_settings ADD COLUMN server_layout TEXT NOT NULL DEFAULT '{"items": []}';
@@ -0,0 +1,39 @@
+-- Promote local channels to federated rooms, and back (lite mode). Lite
+-- databases never had the room tables, so they are created here.
+
+CREATE TABLE IF NOT EXISTS federated_rooms (
+    id              TEXT PRIMARY KEY,
+    room_id         TEXT NOT NULL UNIQUE,
+    local_channel_id TEXT REFERENCES channels(id) ON DELETE SET NULL,
+    origin_server   TEXT NOT NULL,
+    room_name       TEXT,
+    room_topic      TEXT,
+    join_rule       TEXT NOT NULL DEFAULT 'public',
+    member_count    INTEGER NOT NULL DEFAULT 0,
+    participating_servers TEXT NOT NULL DEFAULT '[]',
+    created_at      TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at      TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    defederated_at  TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_federated_rooms_origin ON federated_rooms (origin_server);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_federated_rooms_local_channel
+    ON federated_rooms (local_channel_id) WHERE local_channel_id IS NOT NULL;
+
+CREATE TABLE IF NOT EXISTS federated_events (
+    id              TEXT PRIMARY KEY DEFAULT (lower(hex(randomblob(16)))),
+    event_id        TEXT NOT NULL UNIQUE,
+    room_id         TEXT NOT NULL,
+    event_type      TEXT NOT NULL,
+    sender          TEXT NOT NULL,
+    origin_server   TEXT NOT NULL,
+    origin_server_ts INTEGER NOT NULL,
+    content         TEXT NOT NULL DEFAULT '{}',
+    signatures      TEXT NOT NULL DEFAULT '{}',
+    content_hash    TEXT,
+    txn_id          TEXT,
+    received_at     TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    is_redacted     INTEGER NOT NULL DEFAULT 0
+);
+
+CREATE INDEX IF NOT EXISTS idx_federated_events_room ON federated_events (room_id, origin_server_ts DESC);
@@ -0,0 +1,15 @@
+-- Inbound federation transaction log (lite mode). Lite databases never had
+-- the table, so it is created here along with the pruning index.
+
+CREATE TABLE IF NOT EXISTS federation_txn_log (
+    id              TEXT PRIMARY KEY DEFAULT (lower(hex(randomblob(16)))),
+    txn_id          TEXT NOT NULL,
+    origin_server   TEXT NOT NULL,
+    pdu_count       INTEGER NOT NULL DEFAULT 0,
+    edu_count       INTEGER NOT NULL DEFAULT 0,
+    received_at     TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (txn_id, origin_server)
+);
+
+CREATE INDEX IF NOT EXISTS idx_federation_txn_log_received
+    ON federation_txn_log (received_at);
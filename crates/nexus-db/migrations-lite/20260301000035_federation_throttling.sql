@@ -0,0 +1,5 @@
+-- Throttle origin servers that keep exceeding the inbound federation rate
+-- limits (lite mode).
+
+ALTER TABLE federated_servers ADD COLUMN rate_limit_strikes INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE federated_servers ADD COLUMN throttled_until TEXT;
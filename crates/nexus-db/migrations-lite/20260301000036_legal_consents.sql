@@ -0,0 +1,12 @@
+-- Terms of service consent (lite mode)
+
+CREATE TABLE IF NOT EXISTS legal_consents (
+    id              TEXT PRIMARY KEY,
+    user_id         TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    version         TEXT NOT NULL,
+    ip              TEXT,
+    consented_at    TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (user_id, version)
+);
+
+CREATE INDEX IF NOT EXISTS idx_legal_consents_user ON legal_consents (user_id, consented_at DESC);
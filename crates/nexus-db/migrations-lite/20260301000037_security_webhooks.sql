@@ -0,0 +1,16 @@
+-- Security webhooks (lite mode)
+
+CREATE TABLE IF NOT EXISTS security_event_deliveries (
+    id              TEXT PRIMARY KEY,
+    url             TEXT NOT NULL,
+    event           TEXT NOT NULL,
+    payload         TEXT NOT NULL DEFAULT '{}',
+    attempts        INTEGER NOT NULL DEFAULT 0,
+    last_error      TEXT,
+    created_at      TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    next_attempt_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    delivered_at    TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_security_event_deliveries_pending
+    ON security_event_deliveries (delivered_at, next_attempt_at);
@@ -0,0 +1,27 @@
+-- OAuth2 provider (lite mode)
+
+ALTER TABLE bots ADD COLUMN client_secret_hash TEXT;
+
+CREATE TABLE IF NOT EXISTS oauth2_authorization_codes (
+    code_hash       TEXT PRIMARY KEY,
+    application_id  TEXT NOT NULL REFERENCES bots(id) ON DELETE CASCADE,
+    user_id         TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    redirect_uri    TEXT NOT NULL,
+    scopes          TEXT NOT NULL DEFAULT '[]',
+    code_challenge  TEXT,
+    expires_at      TEXT NOT NULL,
+    created_at      TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS oauth2_tokens (
+    id                  TEXT PRIMARY KEY,
+    application_id      TEXT NOT NULL REFERENCES bots(id) ON DELETE CASCADE,
+    user_id             TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    access_token_hash   TEXT NOT NULL UNIQUE,
+    refresh_token_hash  TEXT NOT NULL UNIQUE,
+    scopes              TEXT NOT NULL DEFAULT '[]',
+    expires_at          TEXT NOT NULL,
+    created_at          TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_oauth2_tokens_user ON oauth2_tokens (user_id, application_id);
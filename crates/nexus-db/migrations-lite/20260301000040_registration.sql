@@ -0,0 +1,19 @@
+-- Registration tokens and the approval queue (lite mode)
+
+CREATE TABLE IF NOT EXISTS registration_tokens (
+    id              TEXT PRIMARY KEY,
+    token_hash      TEXT NOT NULL UNIQUE,
+    created_by      TEXT REFERENCES users(id) ON DELETE SET NULL,
+    note            TEXT,
+    created_at      TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    expires_at      TEXT,
+    used_by         TEXT REFERENCES users(id) ON DELETE SET NULL,
+    used_at         TEXT
+);
+
+CREATE TABLE IF NOT EXISTS registration_applications (
+    user_id         TEXT PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    reason          TEXT,
+    ip              TEXT,
+    created_at      TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
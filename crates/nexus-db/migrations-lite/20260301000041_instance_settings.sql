@@ -0,0 +1,8 @@
+-- Instance settings changed at runtime (lite mode)
+
+CREATE TABLE IF NOT EXISTS instance_settings (
+    key             TEXT PRIMARY KEY,
+    value           TEXT NOT NULL,
+    updated_by      TEXT REFERENCES users(id) ON DELETE SET NULL,
+    updated_at      TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
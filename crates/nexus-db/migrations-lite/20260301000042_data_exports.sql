@@ -0,0 +1,21 @@
+-- Data exports and account deletion (lite mode)
+
+CREATE TABLE IF NOT EXISTS data_exports (
+    id              TEXT PRIMARY KEY,
+    user_id         TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    status          TEXT NOT NULL DEFAULT 'pending',
+    progress        INTEGER NOT NULL DEFAULT 0,
+    storage_key     TEXT,
+    size_bytes      INTEGER,
+    last_error      TEXT,
+    created_at      TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    completed_at    TEXT,
+    expires_at      TEXT,
+    downloaded_at   TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_data_exports_user ON data_exports (user_id, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_data_exports_status ON data_exports (status, created_at);
+
+ALTER TABLE users ADD COLUMN deleted_at TEXT;
+ALTER TABLE federated_users ADD COLUMN deleted_at TEXT;
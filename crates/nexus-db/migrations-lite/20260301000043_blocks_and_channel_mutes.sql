@@ -0,0 +1,16 @@
+-- User blocks and channel mutes (lite mode)
+
+CREATE TABLE IF NOT EXISTS user_blocks (
+    user_id         TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    blocked_id      TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_at      TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (user_id, blocked_id)
+);
+
+CREATE TABLE IF NOT EXISTS channel_mutes (
+    user_id         TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    channel_id      TEXT NOT NULL REFERENCES channels(id) ON DELETE CASCADE,
+    muted_until     TEXT,
+    created_at      TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (user_id, channel_id)
+);
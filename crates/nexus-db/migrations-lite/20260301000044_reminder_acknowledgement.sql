@@ -0,0 +1,8 @@
+-- Keep privately delivered reminders until the user dismisses them (lite
+-- mode)
+
+ALTER TABLE reminders ADD COLUMN acknowledged_at TEXT;
+UPDATE reminders SET acknowledged_at = delivered_at WHERE delivered_at IS NOT NULL;
+
+CREATE INDEX IF NOT EXISTS idx_reminders_unacknowledged ON reminders (user_id, remind_at)
+    WHERE delivered_at IS NOT NULL AND acknowledged_at IS NULL;
@@ -0,0 +1,5 @@
+-- Built-in system account (lite mode)
+
+INSERT INTO users (id, username, password_hash, flags)
+VALUES ('00000000-0000-0000-0000-000000000001', 'nexus.system', '!', 256)
+ON CONFLICT (id) DO NOTHING;
@@ -0,0 +1,4 @@
+-- Attribute webhook messages to the system account (lite mode)
+
+UPDATE messages SET author_id = '00000000-0000-0000-0000-000000000001'
+WHERE webhook_id IS NOT NULL OR webhook_username IS NOT NULL;
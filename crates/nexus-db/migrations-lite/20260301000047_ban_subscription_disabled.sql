@@ -0,0 +1,4 @@
+-- Disable ban list subscriptions whose creator lost the right to ban (lite
+-- mode)
+
+ALTER TABLE ban_list_subscriptions ADD COLUMN disabled_at TEXT;
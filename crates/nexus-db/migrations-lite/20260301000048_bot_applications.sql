@@ -0,0 +1,13 @@
+-- Bot applications (lite mode): rename `bots` to the table the repository
+-- uses and add the columns it reads. Tables referencing `bots(id)` follow
+-- the rename.
+
+ALTER TABLE bots RENAME TO bot_applications;
+ALTER TABLE bot_applications RENAME COLUMN is_verified TO verified;
+ALTER TABLE bot_applications ADD COLUMN public_key TEXT NOT NULL DEFAULT '';
+ALTER TABLE bot_applications ADD COLUMN redirect_uris TEXT NOT NULL DEFAULT '[]';
+ALTER TABLE bot_applications ADD COLUMN interactions_endpoint_url TEXT;
+ALTER TABLE bot_applications ADD COLUMN flags INTEGER NOT NULL DEFAULT 0;
+
+DROP INDEX IF EXISTS idx_bots_owner;
+CREATE INDEX IF NOT EXISTS idx_bot_applications_owner ON bot_applications (owner_id);
@@ -0,0 +1,40 @@
+-- Migration: Status page — component self-checks and uptime history
+-- Populated by the self-check background job; read by GET /api/v1/status.
+
+-- ============================================================================
+-- Component self-checks
+-- ============================================================================
+
+CREATE TABLE status_checks (
+    id              UUID PRIMARY KEY,
+    -- 'api' | 'database' | 'gateway' | 'voice' | 'search' | 'federation'
+    component       VARCHAR(32) NOT NULL,
+    -- 'operational' | 'degraded' | 'down'
+    status          VARCHAR(16) NOT NULL,
+    -- Probe round-trip time in milliseconds (NULL when the probe failed outright)
+    latency_ms      BIGINT,
+    -- Free-form detail, e.g. "outbox lag 42s" or the probe error
+    detail          TEXT,
+    checked_at      TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_status_checks_component ON status_checks (component, checked_at DESC);
+
+-- ============================================================================
+-- Federation outbox
+-- ============================================================================
+
+-- Outbound server-to-server transactions awaiting delivery. The status page
+-- reports the age of the oldest undelivered row as the federation lag.
+CREATE TABLE federation_outbox (
+    id              UUID PRIMARY KEY,
+    destination     TEXT NOT NULL,
+    txn_id          TEXT NOT NULL,
+    payload         JSONB NOT NULL DEFAULT '{}',
+    attempts        INTEGER NOT NULL DEFAULT 0,
+    last_error      TEXT,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    delivered_at    TIMESTAMPTZ
+);
+
+CREATE INDEX idx_federation_outbox_pending ON federation_outbox (created_at) WHERE delivered_at IS NULL;
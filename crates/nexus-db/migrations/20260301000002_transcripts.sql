@@ -0,0 +1,28 @@
+-- Migration: Speech-to-text transcripts for voice messages and recordings
+-- Rows are queued when audio is uploaded and filled in by the transcription job.
+
+CREATE TABLE transcripts (
+    id              UUID PRIMARY KEY,
+    -- 'attachment' (voice message) | 'recording' (voice-channel recording)
+    source_type     VARCHAR(16) NOT NULL,
+    source_id       UUID NOT NULL,
+    storage_key     TEXT NOT NULL,
+    content_type    VARCHAR(128) NOT NULL,
+    channel_id      UUID REFERENCES channels(id) ON DELETE CASCADE,
+    -- 'pending' | 'processing' | 'completed' | 'failed'
+    status          VARCHAR(16) NOT NULL DEFAULT 'pending',
+    -- BCP-47 language reported by the provider (or forced via config)
+    language        VARCHAR(16),
+    text            TEXT,
+    -- Provider-specific timed segments: [{ "start": 0.0, "end": 1.2, "text": "..." }]
+    segments        JSONB NOT NULL DEFAULT '[]',
+    provider        VARCHAR(32),
+    attempts        INTEGER NOT NULL DEFAULT 0,
+    last_error      TEXT,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    completed_at    TIMESTAMPTZ,
+    UNIQUE (source_type, source_id)
+);
+
+CREATE INDEX idx_transcripts_pending ON transcripts (created_at) WHERE status = 'pending';
+CREATE INDEX idx_transcripts_fts ON transcripts USING GIN (to_tsvector('english', COALESCE(text, '')));
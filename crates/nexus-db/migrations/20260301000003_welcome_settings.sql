@@ -0,0 +1,29 @@
+-- Migration: Server join actions — welcome messages, DM greetings, auto-roles
+-- and membership screening.
+
+-- ============================================================================
+-- Per-server join configuration
+-- ============================================================================
+
+CREATE TABLE server_welcome_settings (
+    server_id           UUID PRIMARY KEY REFERENCES servers(id) ON DELETE CASCADE,
+    -- Public welcome posted to a channel. Placeholders: {user} {username} {server} {member_count}
+    channel_enabled     BOOLEAN NOT NULL DEFAULT FALSE,
+    channel_id          UUID REFERENCES channels(id) ON DELETE SET NULL,
+    channel_message     TEXT,
+    -- Private welcome DM'd to the new member from the server owner
+    dm_enabled          BOOLEAN NOT NULL DEFAULT FALSE,
+    dm_message          TEXT,
+    -- Role IDs granted on join (after screening, if enabled)
+    auto_role_ids       JSONB NOT NULL DEFAULT '[]',
+    -- When enabled, new members are `pending` until they accept the rules
+    screening_enabled   BOOLEAN NOT NULL DEFAULT FALSE,
+    screening_rules     TEXT,
+    updated_at          TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- ============================================================================
+-- Membership screening state
+-- ============================================================================
+
+ALTER TABLE members ADD COLUMN pending BOOLEAN NOT NULL DEFAULT FALSE;
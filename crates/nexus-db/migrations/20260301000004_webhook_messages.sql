@@ -0,0 +1,11 @@
+-- Migration: Webhook-authored messages
+-- Webhook messages are stored under the webhook creator's user ID (author_id
+-- must reference users), with the webhook and any per-message display
+-- overrides recorded alongside.
+
+ALTER TABLE messages ADD COLUMN webhook_id UUID REFERENCES webhooks(id) ON DELETE SET NULL;
+-- Per-message display name / avatar override supplied at execution time
+ALTER TABLE messages ADD COLUMN webhook_username VARCHAR(80);
+ALTER TABLE messages ADD COLUMN webhook_avatar_url TEXT;
+
+CREATE INDEX idx_messages_webhook ON messages (webhook_id) WHERE webhook_id IS NOT NULL;
@@ -0,0 +1,37 @@
+-- Migration: Scheduled server events and per-member calendar (ICS) feeds.
+
+-- ============================================================================
+-- Scheduled events
+-- ============================================================================
+
+CREATE TABLE scheduled_events (
+    id              UUID PRIMARY KEY,
+    server_id       UUID NOT NULL REFERENCES servers(id) ON DELETE CASCADE,
+    -- Optional voice / stage / text channel the event takes place in
+    channel_id      UUID REFERENCES channels(id) ON DELETE SET NULL,
+    creator_id      UUID REFERENCES users(id) ON DELETE SET NULL,
+    name            VARCHAR(100) NOT NULL,
+    description     TEXT,
+    -- Free-form location for events held outside Nexus
+    location        VARCHAR(200),
+    start_time      TIMESTAMPTZ NOT NULL,
+    end_time        TIMESTAMPTZ,
+    -- 'scheduled' | 'active' | 'completed' | 'cancelled'
+    status          VARCHAR(16) NOT NULL DEFAULT 'scheduled',
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at      TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_scheduled_events_server ON scheduled_events(server_id, start_time);
+
+-- ============================================================================
+-- Calendar feed tokens — one secret URL per member per server
+-- ============================================================================
+
+CREATE TABLE event_feed_tokens (
+    token           VARCHAR(64) PRIMARY KEY,
+    server_id       UUID NOT NULL REFERENCES servers(id) ON DELETE CASCADE,
+    user_id         UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (server_id, user_id)
+);
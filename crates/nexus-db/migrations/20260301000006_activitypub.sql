@@ -0,0 +1,37 @@
+-- Migration: ActivityPub outbound bridge — actor key and channel followers.
+
+-- ============================================================================
+-- Actor signing key
+-- ============================================================================
+
+-- RSA key shared by every channel actor. Fediverse servers verify HTTP
+-- Signatures with RSA, so the Ed25519 federation key can't be reused.
+CREATE TABLE activitypub_keys (
+    id                  UUID PRIMARY KEY,
+    private_key_pem     TEXT NOT NULL,
+    public_key_pem      TEXT NOT NULL,
+    created_at          TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- ============================================================================
+-- Followers of public announcement channels
+-- ============================================================================
+
+CREATE TABLE activitypub_followers (
+    id                  UUID PRIMARY KEY,
+    channel_id          UUID NOT NULL REFERENCES channels(id) ON DELETE CASCADE,
+    actor_uri           TEXT NOT NULL,
+    inbox_url           TEXT NOT NULL,
+    -- Preferred delivery target when the remote server advertises one
+    shared_inbox_url    TEXT,
+    created_at          TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (channel_id, actor_uri)
+);
+
+CREATE INDEX idx_activitypub_followers_channel ON activitypub_followers (channel_id);
+
+-- ============================================================================
+-- Outbox retry scheduling
+-- ============================================================================
+
+ALTER TABLE federation_outbox ADD COLUMN next_attempt_at TIMESTAMPTZ NOT NULL DEFAULT NOW();
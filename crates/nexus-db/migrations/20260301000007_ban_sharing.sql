@@ -0,0 +1,18 @@
+-- Migration: Ban list subscriptions — periodically import another server's
+-- bans, or a shared blocklist published at a URL.
+
+CREATE TABLE ban_list_subscriptions (
+    id                  UUID PRIMARY KEY,
+    server_id           UUID NOT NULL REFERENCES servers(id) ON DELETE CASCADE,
+    -- Exactly one source: a server on this instance, or a blocklist URL
+    source_server_id    UUID REFERENCES servers(id) ON DELETE CASCADE,
+    source_url          TEXT,
+    -- Imported bans are attributed to the member who subscribed
+    created_by          UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    last_synced_at      TIMESTAMPTZ,
+    last_error          TEXT,
+    created_at          TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK ((source_server_id IS NULL) <> (source_url IS NULL))
+);
+
+CREATE INDEX idx_ban_list_subscriptions_server ON ban_list_subscriptions(server_id);
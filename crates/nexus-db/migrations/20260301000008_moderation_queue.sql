@@ -0,0 +1,42 @@
+-- Migration: Pre-moderation queue — hold messages from new members and
+-- messages containing links until a moderator approves them.
+
+-- ============================================================================
+-- Per-channel pre-moderation settings
+-- ============================================================================
+
+CREATE TABLE channel_moderation_settings (
+    channel_id          UUID PRIMARY KEY REFERENCES channels(id) ON DELETE CASCADE,
+    -- Hold messages from members who joined recently or have never posted
+    hold_new_members    BOOLEAN NOT NULL DEFAULT FALSE,
+    -- A member counts as new for this many minutes after joining
+    new_member_minutes  INTEGER NOT NULL DEFAULT 1440,
+    -- Hold any message containing a link, regardless of author
+    hold_links          BOOLEAN NOT NULL DEFAULT FALSE,
+    updated_at          TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- ============================================================================
+-- Held messages
+-- ============================================================================
+
+CREATE TABLE pending_messages (
+    -- Becomes the message ID on approval
+    id                      UUID PRIMARY KEY,
+    channel_id              UUID NOT NULL REFERENCES channels(id) ON DELETE CASCADE,
+    server_id               UUID NOT NULL REFERENCES servers(id) ON DELETE CASCADE,
+    author_id               UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    content                 TEXT NOT NULL,
+    reference_message_id    UUID,
+    reference_channel_id    UUID,
+    flags                   INTEGER NOT NULL DEFAULT 0,
+    -- Uploaded, not yet linked attachments claimed on approval
+    attachment_ids          JSONB NOT NULL DEFAULT '[]',
+    spoiler_attachment_ids  JSONB NOT NULL DEFAULT '[]',
+    -- new_member | first_message | link
+    reason                  TEXT NOT NULL,
+    created_at              TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_pending_messages_channel ON pending_messages (channel_id, created_at);
+CREATE INDEX idx_pending_messages_server  ON pending_messages (server_id, created_at);
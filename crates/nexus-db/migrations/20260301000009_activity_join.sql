@@ -0,0 +1,8 @@
+-- Migration: Rich presence parties — joinable game activities.
+
+-- Party the user is in, shown to others as "In a party (2 of 4)"
+ALTER TABLE user_activities ADD COLUMN party_id   TEXT;
+ALTER TABLE user_activities ADD COLUMN party_size INTEGER;
+ALTER TABLE user_activities ADD COLUMN party_max  INTEGER;
+-- Opaque game-defined secret handed to users who join; never exposed in presence
+ALTER TABLE user_activities ADD COLUMN join_secret TEXT;
@@ -0,0 +1,24 @@
+-- Migration: Bot credential hygiene — token rotation bookkeeping, IP
+-- allowlists, and per-application request metrics for the developer portal.
+
+-- When the token was last regenerated (NULL = never since creation)
+ALTER TABLE bot_applications ADD COLUMN token_reset_at TIMESTAMPTZ;
+-- JSON array of IPs / CIDR ranges allowed to use the bot token; empty = any
+ALTER TABLE bot_applications ADD COLUMN ip_allowlist JSONB NOT NULL DEFAULT '[]';
+
+-- ============================================================================
+-- Daily request counters per application
+-- ============================================================================
+
+CREATE TABLE bot_request_metrics (
+    bot_id          UUID NOT NULL REFERENCES bot_applications(id) ON DELETE CASCADE,
+    -- UTC calendar day, YYYY-MM-DD (text so SQLite and PostgreSQL compare alike)
+    day             TEXT NOT NULL,
+    requests        BIGINT NOT NULL DEFAULT 0,
+    -- Responses with a 4xx/5xx status
+    errors          BIGINT NOT NULL DEFAULT 0,
+    -- Requests refused by the IP allowlist (not counted in `requests`)
+    rejected        BIGINT NOT NULL DEFAULT 0,
+    total_latency_ms BIGINT NOT NULL DEFAULT 0,
+    PRIMARY KEY (bot_id, day)
+);
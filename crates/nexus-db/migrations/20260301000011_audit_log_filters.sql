@@ -0,0 +1,4 @@
+-- Migration: Indexes backing the audit log endpoint's action and target filters.
+
+CREATE INDEX IF NOT EXISTS idx_audit_log_action ON audit_log (server_id, action, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_audit_log_target ON audit_log (target_id);
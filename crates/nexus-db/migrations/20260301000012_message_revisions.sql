@@ -0,0 +1,15 @@
+-- Migration: Message edit history — the content a message had before each edit.
+
+CREATE TABLE message_revisions (
+    id          UUID PRIMARY KEY,
+    message_id  UUID NOT NULL REFERENCES messages(id) ON DELETE CASCADE,
+    -- Content as it read before the edit
+    content     TEXT NOT NULL,
+    -- When the edit replaced this content
+    created_at  TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_message_revisions_message ON message_revisions (message_id, created_at);
+
+-- Servers can turn history off for privacy; edits then overwrite as before.
+ALTER TABLE servers ADD COLUMN message_history BOOLEAN NOT NULL DEFAULT TRUE;
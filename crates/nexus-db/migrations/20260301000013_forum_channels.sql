@@ -0,0 +1,21 @@
+-- Migration: Forum channels — post tags and pinned posts.
+--
+-- Forum posts are threads whose parent is a forum channel; a post's
+-- `threads.tags` holds the IDs of the forum tags applied to it.
+
+CREATE TABLE forum_tags (
+    id          UUID PRIMARY KEY,
+    channel_id  UUID NOT NULL REFERENCES channels(id) ON DELETE CASCADE,
+    name        VARCHAR(20) NOT NULL,
+    -- Unicode emoji or custom emoji ID shown next to the name
+    emoji       VARCHAR(64),
+    -- Only members with MANAGE_THREADS can apply moderated tags
+    moderated   BOOLEAN NOT NULL DEFAULT FALSE,
+    position    INTEGER NOT NULL DEFAULT 0,
+    created_at  TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_forum_tags_channel ON forum_tags (channel_id, position);
+
+-- Pinned posts are listed ahead of the rest of the forum
+ALTER TABLE threads ADD COLUMN pinned BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,27 @@
+-- Migration: Channel topic history and versioned server settings.
+
+CREATE TABLE channel_topic_history (
+    id          UUID PRIMARY KEY,
+    channel_id  UUID NOT NULL REFERENCES channels(id) ON DELETE CASCADE,
+    -- Topic after the change (NULL when it was cleared)
+    topic       TEXT,
+    changed_by  UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at  TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_channel_topic_history_channel ON channel_topic_history (channel_id, created_at DESC);
+
+-- One row per settings change; version 1 is the state before the first
+-- recorded change, so every change can be rolled back.
+CREATE TABLE server_settings_versions (
+    id          UUID PRIMARY KEY,
+    server_id   UUID NOT NULL REFERENCES servers(id) ON DELETE CASCADE,
+    version     INTEGER NOT NULL,
+    -- Snapshot of the settings after the change
+    settings    JSONB NOT NULL,
+    changed_by  UUID REFERENCES users(id) ON DELETE SET NULL,
+    -- Set when this version was created by rolling back to an older one
+    rollback_of INTEGER,
+    created_at  TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (server_id, version)
+);
@@ -0,0 +1,29 @@
+-- Migration: Push notification tokens and per-user preferences.
+
+CREATE TABLE push_tokens (
+    id            UUID PRIMARY KEY,
+    user_id       UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    -- 'web_push', 'fcm' or 'apns'
+    platform      TEXT NOT NULL,
+    -- FCM/APNs device token, or the WebPush endpoint URL
+    token         TEXT NOT NULL,
+    -- WebPush subscription keys (base64url)
+    p256dh        TEXT,
+    auth          TEXT,
+    device_name   TEXT,
+    created_at    TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_used_at  TIMESTAMPTZ,
+    UNIQUE (platform, token)
+);
+
+CREATE INDEX idx_push_tokens_user ON push_tokens (user_id);
+
+-- A missing row means the defaults (everything on).
+CREATE TABLE push_preferences (
+    user_id          UUID PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    enabled          BOOLEAN NOT NULL DEFAULT TRUE,
+    direct_messages  BOOLEAN NOT NULL DEFAULT TRUE,
+    mentions         BOOLEAN NOT NULL DEFAULT TRUE,
+    show_content     BOOLEAN NOT NULL DEFAULT TRUE,
+    updated_at       TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
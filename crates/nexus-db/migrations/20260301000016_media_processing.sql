@@ -0,0 +1,20 @@
+-- Migration: Attachment media processing
+-- Image uploads are queued for the media job, which stores a thumbnail and
+-- fills in dimensions and blurhash on the attachment.
+
+ALTER TABLE attachments
+    ADD COLUMN thumbnail_key  TEXT,
+    ADD COLUMN thumbnail_url  TEXT;
+
+CREATE TABLE media_jobs (
+    id              UUID PRIMARY KEY,
+    attachment_id   UUID NOT NULL UNIQUE REFERENCES attachments(id) ON DELETE CASCADE,
+    -- 'pending' | 'processing' | 'completed' | 'failed'
+    status          VARCHAR(16) NOT NULL DEFAULT 'pending',
+    attempts        INTEGER NOT NULL DEFAULT 0,
+    last_error      TEXT,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    completed_at    TIMESTAMPTZ
+);
+
+CREATE INDEX idx_media_jobs_pending ON media_jobs (created_at) WHERE status = 'pending';
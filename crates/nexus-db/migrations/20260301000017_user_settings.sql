@@ -0,0 +1,11 @@
+-- Migration: Synced user settings
+-- Per-user client settings shared across devices, starting with the
+-- playback volume and local mute a user sets for others in voice.
+
+CREATE TABLE user_settings (
+    user_id         UUID PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    -- { "<user id>": { "volume": 0-200, "muted": bool } }
+    user_volumes    JSONB NOT NULL DEFAULT '{}',
+    version         BIGINT NOT NULL DEFAULT 1,
+    updated_at      TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
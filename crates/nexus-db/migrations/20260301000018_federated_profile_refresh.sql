@@ -0,0 +1,14 @@
+-- Migration: Federated profile refresh
+-- Remote profiles were only written when a PDU from the user arrived, so
+-- display names and avatars went stale. A background job now re-fetches
+-- recently active users' profiles from their home server.
+
+ALTER TABLE federated_users
+    -- Last PDU received from this user; only active users are refreshed
+    ADD COLUMN last_active_at   TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    -- NULL = due now
+    ADD COLUMN next_refresh_at  TIMESTAMPTZ,
+    -- Consecutive failed fetches, for backoff
+    ADD COLUMN refresh_failures INTEGER     NOT NULL DEFAULT 0;
+
+CREATE INDEX idx_federated_users_refresh ON federated_users (next_refresh_at, last_active_at);
@@ -0,0 +1,19 @@
+-- Migration: End-to-end encrypted attachments
+-- Ciphertext blobs uploaded by clients for E2EE channels. The server never
+-- sees the file key, name or type; those ride inside the message.
+
+CREATE TABLE e2ee_attachments (
+    id              UUID PRIMARY KEY,
+    channel_id      UUID NOT NULL REFERENCES channels(id) ON DELETE CASCADE,
+    uploader_id     UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    -- NULL until an encrypted message references the upload
+    message_id      UUID REFERENCES encrypted_messages(id) ON DELETE CASCADE,
+    -- Opaque object key: e2ee/{channel_id}/{id}
+    storage_key     TEXT NOT NULL,
+    size            BIGINT NOT NULL,
+    -- Hex SHA-256 of the ciphertext
+    sha256          TEXT NOT NULL,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_e2ee_attachments_message ON e2ee_attachments (message_id);
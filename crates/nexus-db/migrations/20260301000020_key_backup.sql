@@ -0,0 +1,32 @@
+-- Migration: E2EE key backup
+-- Server-side backup of session keys, encrypted to a recovery key the server
+-- never sees, so history survives the loss of every device.
+
+CREATE TABLE key_backup_versions (
+    user_id         UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    version         BIGINT NOT NULL,
+    algorithm       TEXT NOT NULL,
+    -- Public half of the recovery key, plus signatures over it
+    auth_data       JSONB NOT NULL,
+    -- Bumped whenever the version's sessions change
+    etag            BIGINT NOT NULL DEFAULT 0,
+    -- Deleted versions keep their number so it is never reused
+    deleted         BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_id, version)
+);
+
+CREATE TABLE key_backup_sessions (
+    user_id             UUID NOT NULL,
+    version             BIGINT NOT NULL,
+    channel_id          UUID NOT NULL,
+    session_id          TEXT NOT NULL,
+    first_message_index INTEGER NOT NULL,
+    forwarded_count     INTEGER NOT NULL,
+    is_verified         BOOLEAN NOT NULL,
+    -- Ciphertext: { "ephemeral", "ciphertext", "mac" }
+    session_data        JSONB NOT NULL,
+    updated_at          TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_id, version, channel_id, session_id),
+    FOREIGN KEY (user_id, version) REFERENCES key_backup_versions (user_id, version) ON DELETE CASCADE
+);
@@ -0,0 +1,6 @@
+-- Migration: User locale and server preferred language.
+-- Both drive server-generated text (join messages) and the fallback for
+-- slash command localizations when a request has no Accept-Language.
+
+ALTER TABLE users ADD COLUMN locale VARCHAR(16) NOT NULL DEFAULT 'en-US';
+ALTER TABLE servers ADD COLUMN preferred_locale VARCHAR(16) NOT NULL DEFAULT 'en-US';
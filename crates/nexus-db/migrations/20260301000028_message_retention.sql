@@ -0,0 +1,10 @@
+-- Migration: Soft-deleted messages and per-server message retention.
+-- Deleting a message stamps deleted_at; the row stays for moderator review
+-- until the retention job purges it (limits.deleted_message_retention_days).
+-- Servers may also purge every message older than message_retention_days
+-- (0 keeps history forever).
+
+ALTER TABLE messages ADD COLUMN deleted_at TIMESTAMPTZ;
+CREATE INDEX idx_messages_deleted_at ON messages (deleted_at) WHERE deleted_at IS NOT NULL;
+
+ALTER TABLE servers ADD COLUMN message_retention_days INTEGER NOT NULL DEFAULT 0;
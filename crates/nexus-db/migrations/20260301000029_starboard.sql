@@ -0,0 +1,31 @@
+-- Migration: Starboard — messages that collect enough of a chosen reaction
+-- are reposted into a highlights channel.
+
+-- ============================================================================
+-- Per-server starboard configuration
+-- ============================================================================
+
+CREATE TABLE server_starboard_settings (
+    server_id       UUID PRIMARY KEY REFERENCES servers(id) ON DELETE CASCADE,
+    enabled         BOOLEAN NOT NULL DEFAULT FALSE,
+    channel_id      UUID REFERENCES channels(id) ON DELETE SET NULL,
+    -- Reaction that counts towards the threshold (unicode or custom emoji id)
+    emoji           TEXT NOT NULL DEFAULT '⭐',
+    threshold       INTEGER NOT NULL DEFAULT 3,
+    updated_at      TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- ============================================================================
+-- Starred messages — one row per source message, so it is reposted once
+-- ============================================================================
+
+CREATE TABLE starboard_entries (
+    message_id              UUID PRIMARY KEY REFERENCES messages(id) ON DELETE CASCADE,
+    server_id               UUID NOT NULL REFERENCES servers(id) ON DELETE CASCADE,
+    -- The repost in the starboard channel; NULL while it is being posted
+    starboard_message_id    UUID REFERENCES messages(id) ON DELETE SET NULL,
+    star_count              INTEGER NOT NULL DEFAULT 0,
+    created_at              TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_starboard_entries_server ON starboard_entries (server_id, created_at DESC);
@@ -0,0 +1,18 @@
+-- Migration: Reminders — users schedule a note to themselves, delivered
+-- privately or as a mention in a channel once it falls due.
+
+CREATE TABLE reminders (
+    id              UUID PRIMARY KEY,
+    user_id         UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    -- Channel to post the reminder in; NULL delivers it privately
+    channel_id      UUID REFERENCES channels(id) ON DELETE CASCADE,
+    -- Message the reminder is about, if any
+    message_id      UUID REFERENCES messages(id) ON DELETE SET NULL,
+    content         TEXT NOT NULL,
+    remind_at       TIMESTAMPTZ NOT NULL,
+    delivered_at    TIMESTAMPTZ,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_reminders_user ON reminders (user_id, remind_at);
+CREATE INDEX idx_reminders_due ON reminders (remind_at) WHERE delivered_at IS NULL;
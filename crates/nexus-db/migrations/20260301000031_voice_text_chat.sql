@@ -0,0 +1,21 @@
+-- Migration: Text chat for voice channels — each voice or stage channel
+-- gets a linked text channel, open to those who have recently been in the
+-- call.
+
+-- Set on a voice channel's text chat; such channels are left out of the
+-- regular channel list and follow the voice channel's permissions.
+ALTER TABLE channels ADD COLUMN voice_channel_id UUID REFERENCES channels(id) ON DELETE CASCADE;
+CREATE UNIQUE INDEX idx_channels_voice_channel ON channels (voice_channel_id) WHERE voice_channel_id IS NOT NULL;
+
+-- ============================================================================
+-- Voice participation history — one row per user and voice channel
+-- ============================================================================
+
+CREATE TABLE voice_participants (
+    channel_id      UUID NOT NULL REFERENCES channels(id) ON DELETE CASCADE,
+    user_id         UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    joined_at       TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    -- NULL while connected
+    left_at         TIMESTAMPTZ,
+    PRIMARY KEY (channel_id, user_id)
+);
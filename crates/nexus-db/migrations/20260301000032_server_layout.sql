@@ -0,0 +1,6 @@
+-- Migration: Synced server list layout
+-- The order of servers in the sidebar and the folders (name, color) they
+-- are grouped into, shared across the user's devices.
+
+-- { "items": [{ "type": "server", "id": ... } | { "type": "folder", ... }] }
+ALTER TABLE user_settings ADD COLUMN server_layout JSONB NOT NULL DEFAULT '{"items": []}';
@@ -0,0 +1,12 @@
+-- Migration: Promote local channels to federated rooms, and back.
+--
+-- A channel promoted to a room keeps its federated_rooms row when it is
+-- defederated: the room ID and the events already exchanged stay, but
+-- nothing more is sent and the room leaves the directory until it is
+-- promoted again.
+
+ALTER TABLE federated_rooms ADD COLUMN defederated_at TIMESTAMPTZ;
+
+-- At most one room per local channel
+CREATE UNIQUE INDEX idx_federated_rooms_local_channel
+    ON federated_rooms (local_channel_id) WHERE local_channel_id IS NOT NULL;
@@ -0,0 +1,7 @@
+-- Migration: Prune the inbound federation transaction log.
+--
+-- Transactions are remembered for the federation acceptance window and up to
+-- a row cap; the pruning job deletes the oldest by received_at.
+
+CREATE INDEX IF NOT EXISTS idx_federation_txn_log_received
+    ON federation_txn_log (received_at);
@@ -0,0 +1,9 @@
+-- Migration: Throttle origin servers that keep exceeding the inbound
+-- federation rate limits.
+--
+-- rate_limit_strikes counts consecutive rate-limited transactions and is
+-- reset by the next accepted one; reaching the configured number sets
+-- throttled_until, before which every transaction from the origin is refused.
+
+ALTER TABLE federated_servers ADD COLUMN rate_limit_strikes INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE federated_servers ADD COLUMN throttled_until TIMESTAMPTZ;
@@ -0,0 +1,17 @@
+-- Migration: Terms of service consent
+--
+-- One row per user and document version they consented to. Rows are never
+-- updated, so the history of what each user agreed to is kept when the
+-- operator publishes a new version.
+
+CREATE TABLE legal_consents (
+    id              UUID PRIMARY KEY,
+    user_id         UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    version         TEXT NOT NULL,
+    -- Client IP the consent was given from, when known
+    ip              TEXT,
+    consented_at    TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (user_id, version)
+);
+
+CREATE INDEX idx_legal_consents_user ON legal_consents (user_id, consented_at DESC);
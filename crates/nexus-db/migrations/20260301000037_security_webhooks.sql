@@ -0,0 +1,20 @@
+-- Migration: Security webhooks
+--
+-- Outbound deliveries of instance-level security events (admin logins,
+-- federation throttling, mass deletions) to the operator's configured URLs.
+-- One row per event and URL; retried with backoff until delivered.
+
+CREATE TABLE security_event_deliveries (
+    id              UUID PRIMARY KEY,
+    url             TEXT NOT NULL,
+    event           TEXT NOT NULL,
+    payload         JSONB NOT NULL DEFAULT '{}',
+    attempts        INTEGER NOT NULL DEFAULT 0,
+    last_error      TEXT,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    next_attempt_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    delivered_at    TIMESTAMPTZ
+);
+
+CREATE INDEX idx_security_event_deliveries_pending
+    ON security_event_deliveries (next_attempt_at) WHERE delivered_at IS NULL;
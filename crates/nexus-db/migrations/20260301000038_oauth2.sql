@@ -0,0 +1,35 @@
+-- Migration: OAuth2 provider
+--
+-- Bot applications double as OAuth2 clients: client_id is the application
+-- id, and the client secret is hashed here like the bot token. Users
+-- authorize an application with the authorization-code flow and it gets
+-- access tokens limited to the scopes they granted.
+
+ALTER TABLE bot_applications ADD COLUMN client_secret_hash TEXT;
+
+-- Single-use codes from /oauth2/authorize, exchanged at /oauth2/token
+CREATE TABLE oauth2_authorization_codes (
+    code_hash       TEXT PRIMARY KEY,
+    application_id  UUID NOT NULL REFERENCES bot_applications(id) ON DELETE CASCADE,
+    user_id         UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    redirect_uri    TEXT NOT NULL,
+    -- JSON array of granted scopes
+    scopes          JSONB NOT NULL DEFAULT '[]',
+    -- PKCE (S256) challenge, when the client sent one
+    code_challenge  TEXT,
+    expires_at      TIMESTAMPTZ NOT NULL,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE oauth2_tokens (
+    id                  UUID PRIMARY KEY,
+    application_id      UUID NOT NULL REFERENCES bot_applications(id) ON DELETE CASCADE,
+    user_id             UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    access_token_hash   TEXT NOT NULL UNIQUE,
+    refresh_token_hash  TEXT NOT NULL UNIQUE,
+    scopes              JSONB NOT NULL DEFAULT '[]',
+    expires_at          TIMESTAMPTZ NOT NULL,
+    created_at          TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_oauth2_tokens_user ON oauth2_tokens (user_id, application_id);
@@ -0,0 +1,31 @@
+-- Migration: Login sessions with rotating refresh tokens
+--
+-- Each login is a session. Refresh tokens are opaque and single-use: a
+-- refresh marks the presented token used and issues the next one in the
+-- same session. Presenting a used token means it was copied, so the whole
+-- session is revoked.
+
+CREATE TABLE sessions (
+    id              UUID PRIMARY KEY,
+    user_id         UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    -- What the client said it was at login, for the session list
+    user_agent      TEXT,
+    -- Address of the last login or refresh
+    ip              TEXT,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_used_at    TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    -- Pushed forward on each refresh
+    expires_at      TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX idx_sessions_user ON sessions (user_id);
+
+CREATE TABLE session_refresh_tokens (
+    token_hash      TEXT PRIMARY KEY,
+    session_id      UUID NOT NULL REFERENCES sessions(id) ON DELETE CASCADE,
+    -- Set when exchanged; a used token must never be presented again
+    used_at         TIMESTAMPTZ,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_session_refresh_tokens_session ON session_refresh_tokens (session_id);
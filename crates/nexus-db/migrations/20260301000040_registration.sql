@@ -0,0 +1,25 @@
+-- Migration: Registration tokens and the approval queue
+--
+-- Invite-only instances hand out single-use registration tokens (stored
+-- hashed). Instances that approve sign-ups keep each pending account's
+-- application here until staff approve or reject it.
+
+CREATE TABLE registration_tokens (
+    id              UUID PRIMARY KEY,
+    token_hash      TEXT NOT NULL UNIQUE,
+    created_by      UUID REFERENCES users(id) ON DELETE SET NULL,
+    -- Who it was meant for, as a reminder to staff
+    note            TEXT,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    -- NULL never expires
+    expires_at      TIMESTAMPTZ,
+    used_by         UUID REFERENCES users(id) ON DELETE SET NULL,
+    used_at         TIMESTAMPTZ
+);
+
+CREATE TABLE registration_applications (
+    user_id         UUID PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    reason          TEXT,
+    ip              TEXT,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,11 @@
+-- Migration: Instance settings changed at runtime
+--
+-- Settings staff change through the admin API, overriding the config file
+-- until cleared. Values are JSON-encoded text.
+
+CREATE TABLE instance_settings (
+    key             TEXT PRIMARY KEY,
+    value           TEXT NOT NULL,
+    updated_by      UUID REFERENCES users(id) ON DELETE SET NULL,
+    updated_at      TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,32 @@
+-- Migration: Data exports and account deletion
+--
+-- A user can ask for an archive of their data. The request is queued as
+-- `pending`, built by the data export job (`processing`, with `progress` in
+-- percent) and stored in object storage once `completed`. It can be
+-- downloaded once, through a signed link; after that, or once it expires,
+-- the archive is deleted.
+
+CREATE TABLE data_exports (
+    id              UUID PRIMARY KEY,
+    user_id         UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    status          TEXT NOT NULL DEFAULT 'pending',
+    progress        INTEGER NOT NULL DEFAULT 0,
+    storage_key     TEXT,
+    size_bytes      BIGINT,
+    last_error      TEXT,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    completed_at    TIMESTAMPTZ,
+    expires_at      TIMESTAMPTZ,
+    downloaded_at   TIMESTAMPTZ
+);
+
+CREATE INDEX idx_data_exports_user ON data_exports (user_id, created_at DESC);
+CREATE INDEX idx_data_exports_status ON data_exports (status, created_at);
+
+-- Deleted accounts are kept as tombstones, so their messages still have an
+-- author; everything identifying is cleared.
+ALTER TABLE users ADD COLUMN deleted_at TIMESTAMPTZ;
+
+-- Remote users whose home server told us they were deleted. Their profiles
+-- are cleared and no longer refreshed.
+ALTER TABLE federated_users ADD COLUMN deleted_at TIMESTAMPTZ;
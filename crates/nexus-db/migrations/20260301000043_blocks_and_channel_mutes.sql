@@ -0,0 +1,21 @@
+-- Migration: User blocks and channel mutes
+--
+-- Both silence notifications. A mention by someone the user blocked, or any
+-- message in a channel they muted, adds nothing to their mention counts and
+-- sends them no push notification.
+
+CREATE TABLE user_blocks (
+    user_id         UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    blocked_id      UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_id, blocked_id)
+);
+
+CREATE TABLE channel_mutes (
+    user_id         UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    channel_id      UUID NOT NULL REFERENCES channels(id) ON DELETE CASCADE,
+    -- NULL mutes until unmuted
+    muted_until     TIMESTAMPTZ,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_id, channel_id)
+);
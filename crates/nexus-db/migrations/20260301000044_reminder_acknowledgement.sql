@@ -0,0 +1,10 @@
+-- Migration: Keep privately delivered reminders until the user dismisses
+-- them, so one that falls due while they are offline is not lost.
+--
+-- Reminders posted in a channel are acknowledged as they are posted.
+
+ALTER TABLE reminders ADD COLUMN acknowledged_at TIMESTAMPTZ;
+UPDATE reminders SET acknowledged_at = delivered_at WHERE delivered_at IS NOT NULL;
+
+CREATE INDEX idx_reminders_unacknowledged ON reminders (user_id, remind_at)
+    WHERE delivered_at IS NOT NULL AND acknowledged_at IS NULL;
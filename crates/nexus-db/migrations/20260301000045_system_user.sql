@@ -0,0 +1,7 @@
+-- Migration: Built-in system account
+-- Authors messages no person sent (welcome DMs). The username contains a
+-- character registration rejects, and the password hash matches no password.
+
+INSERT INTO users (id, username, password_hash, flags)
+VALUES ('00000000-0000-0000-0000-000000000001', 'nexus.system', '!', 256)
+ON CONFLICT (id) DO NOTHING;
@@ -0,0 +1,6 @@
+-- Migration: Attribute webhook messages to the system account
+-- They were stored under the webhook creator's user ID, which made them
+-- count as that user's messages (search, exports, deletion).
+
+UPDATE messages SET author_id = '00000000-0000-0000-0000-000000000001'
+WHERE webhook_id IS NOT NULL OR webhook_username IS NOT NULL;
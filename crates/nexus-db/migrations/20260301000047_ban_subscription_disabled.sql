@@ -0,0 +1,5 @@
+-- Migration: Disable ban list subscriptions whose creator lost the right to
+-- ban in the subscribing or the source server. Disabled subscriptions are
+-- no longer synced; moderators delete them and subscribe again.
+
+ALTER TABLE ban_list_subscriptions ADD COLUMN disabled_at TIMESTAMPTZ;
@@ -0,0 +1,97 @@
+//! Event relay — the gateway event bus across nodes.
+//!
+//! Every event published on this node's [`EventBus`] goes out on the Redis
+//! pub/sub channel [`CHANNEL`], tagged with this node's id, and every event
+//! another node puts there is [`EventBus::deliver`]ed here. That's what lets
+//! API, gateway and voice run as separate processes: a message sent through
+//! one API node reaches clients on every gateway node.
+//!
+//! Pub/sub is fire-and-forget: events published while a node is
+//! disconnected from Redis are lost to it, as with a lagging receiver. The
+//! gateway's resume logic already copes with that.
+
+use futures_util::StreamExt;
+use nexus_common::gateway_event::{EventBus, GatewayEvent};
+use redis::aio::ConnectionManager;
+use redis::AsyncCommands;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tokio::sync::broadcast::{self, error::RecvError};
+use uuid::Uuid;
+
+/// Redis pub/sub channel events travel on.
+pub const CHANNEL: &str = "nexus:events";
+
+/// How long to wait before resubscribing after losing Redis.
+const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+
+#[derive(Serialize, Deserialize)]
+struct Envelope {
+    /// Publishing node; a node ignores its own events.
+    node: Uuid,
+    event: GatewayEvent,
+}
+
+/// Start relaying `bus` through Redis at `url`. `conn` publishes; pub/sub
+/// needs a connection of its own, which is opened from `url`.
+pub fn spawn(url: &str, conn: ConnectionManager, bus: EventBus) -> redis::RedisResult<tokio::task::JoinHandle<()>> {
+    let client = redis::Client::open(url)?;
+    let node = Uuid::new_v4();
+    tracing::info!(%node, "Relaying gateway events through Redis");
+
+    tokio::spawn(publish(node, conn, bus.subscribe_local()));
+    Ok(tokio::spawn(async move {
+        loop {
+            if let Err(e) = receive(node, &client, &bus).await {
+                tracing::warn!(error = %e, "Event relay lost Redis; resubscribing");
+            }
+            tokio::time::sleep(RECONNECT_DELAY).await;
+        }
+    }))
+}
+
+/// Publish this node's events until the bus closes.
+async fn publish(node: Uuid, mut conn: ConnectionManager, mut events: broadcast::Receiver<GatewayEvent>) {
+    loop {
+        let event = match events.recv().await {
+            Ok(event) => event,
+            Err(RecvError::Lagged(skipped)) => {
+                tracing::warn!(skipped, "Event relay fell behind; other nodes missed events");
+                continue;
+            }
+            Err(RecvError::Closed) => break,
+        };
+        let payload = match serde_json::to_string(&Envelope { node, event }) {
+            Ok(payload) => payload,
+            Err(e) => {
+                tracing::error!(error = %e, "Failed to encode event for relay");
+                continue;
+            }
+        };
+        if let Err(e) = conn.publish::<_, _, ()>(CHANNEL, payload).await {
+            tracing::warn!(error = %e, "Failed to relay event");
+        }
+    }
+}
+
+/// Deliver other nodes' events until the subscription drops.
+async fn receive(node: Uuid, client: &redis::Client, bus: &EventBus) -> redis::RedisResult<()> {
+    let mut pubsub = client.get_async_pubsub().await?;
+    pubsub.subscribe(CHANNEL).await?;
+    let mut messages = pubsub.on_message();
+    while let Some(message) = messages.next().await {
+        let envelope = message
+            .get_payload::<String>()
+            .ok()
+            .and_then(|payload| serde_json::from_str::<Envelope>(&payload).ok());
+        match envelope {
+            Some(envelope) if envelope.node != node => {
+                // No subscribers just means nothing here wants events.
+                let _ = bus.deliver(envelope.event);
+            }
+            Some(_) => {}
+            None => tracing::warn!("Ignoring malformed relayed event"),
+        }
+    }
+    Ok(())
+}
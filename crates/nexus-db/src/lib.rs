@@ -8,7 +8,9 @@
 //! * **Lite mode** (`sqlite://…`) — embedded SQLite, no external services required.
 
 pub mod any_compat;
+pub mod event_relay;
 pub mod postgres;
+pub mod presence;
 pub mod redis_pool;
 pub mod repository;
 pub mod search;
@@ -33,6 +35,15 @@ impl DbBackend {
     }
 }
 
+/// Migrations against the current database, as `(version, description)`.
+#[derive(Debug, Default)]
+pub struct MigrationStatus {
+    /// Not applied yet.
+    pub pending: Vec<(i64, String)>,
+    /// Applied, but the file has changed since; migrating will fail.
+    pub modified: Vec<(i64, String)>,
+}
+
 /// Shared database state passed through Axum extractors.
 #[derive(Clone)]
 pub struct Database {
@@ -96,15 +107,57 @@ impl Database {
     /// Run migrations appropriate for the active backend.
     pub async fn migrate(&self) -> Result<()> {
         tracing::info!("Running database migrations…");
-        match self.backend {
-            DbBackend::Postgres => {
-                sqlx::migrate!("./migrations").run(&self.pool).await?;
+        self.migrator().run(&self.pool).await?;
+        tracing::info!("Migrations complete");
+        Ok(())
+    }
+
+    /// What [`migrate`](Self::migrate) would do, without doing it.
+    pub async fn migration_status(&self) -> Result<MigrationStatus> {
+        use sqlx::migrate::Migrate;
+
+        // Looked for first: listing applied migrations needs the table, and
+        // a dry run shouldn't create it.
+        let table_query = match self.backend {
+            DbBackend::Postgres => "SELECT COUNT(*) FROM information_schema.tables WHERE table_name = '_sqlx_migrations'",
+            DbBackend::Sqlite => "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = '_sqlx_migrations'",
+        };
+        let has_table: i64 = sqlx::query_scalar(table_query).fetch_one(&self.pool).await?;
+        let applied = if has_table > 0 {
+            self.pool.acquire().await?.list_applied_migrations().await?
+        } else {
+            Vec::new()
+        };
+
+        let mut status = MigrationStatus::default();
+        for migration in self.migrator().iter() {
+            if migration.migration_type.is_down_migration() {
+                continue;
             }
-            DbBackend::Sqlite => {
-                sqlx::migrate!("./migrations-lite").run(&self.pool).await?;
+            let entry = (migration.version, migration.description.to_string());
+            match applied.iter().find(|a| a.version == migration.version) {
+                None => status.pending.push(entry),
+                Some(a) if a.checksum != migration.checksum => status.modified.push(entry),
+                Some(_) => {}
             }
         }
-        tracing::info!("Migrations complete");
-        Ok(())
+        Ok(status)
+    }
+
+    fn migrator(&self) -> sqlx::migrate::Migrator {
+        match self.backend {
+            DbBackend::Postgres => sqlx::migrate!("./migrations"),
+            DbBackend::Sqlite => sqlx::migrate!("./migrations-lite"),
+        }
+    }
+
+    /// Set the `nexus_db_pool_*` gauges from the pool's current state. Called
+    /// when metrics are scraped, so they're never stale.
+    pub fn record_pool_metrics(&self) {
+        let size = self.pool.size();
+        let idle = self.pool.num_idle() as u32;
+        metrics::gauge!("nexus_db_pool_connections", "state" => "idle").set(idle);
+        metrics::gauge!("nexus_db_pool_connections", "state" => "in_use").set(size.saturating_sub(idle));
+        metrics::gauge!("nexus_db_pool_max_connections").set(self.pool.options().get_max_connections());
     }
 }
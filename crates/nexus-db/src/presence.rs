@@ -0,0 +1,201 @@
+//! Presence — a user's status across every gateway session, on every node.
+//!
+//! Each session (one per device or tab) reports its own status, and the
+//! user's presence is [`UserPresence::aggregate`] of the sessions still
+//! alive: `Offline` only once the last one closes anywhere in the cluster.
+//!
+//! With Redis, a user's sessions live in the hash `presence:{user_id}`,
+//! session id → status and expiry. Sessions are refreshed by heartbeats and
+//! drop out [`SESSION_TTL_SECS`] after the last one, so a crashed node
+//! doesn't leave its users online. Without Redis the same state is kept in
+//! process, which is enough for a single node.
+
+use nexus_common::models::user::UserPresence;
+use redis::aio::ConnectionManager;
+use redis::AsyncCommands;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use uuid::Uuid;
+
+/// How long a session counts without a heartbeat. Clients beat every 45s.
+pub const SESSION_TTL_SECS: i64 = 120;
+
+fn presence_key(user_id: Uuid) -> String {
+    format!("presence:{user_id}")
+}
+
+/// One session's status.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+struct Entry {
+    status: UserPresence,
+    /// Unix milliseconds.
+    expires_at: i64,
+}
+
+type Sessions = HashMap<String, Entry>;
+
+/// Aggregated presence, shared by the API and the gateway.
+pub struct PresenceService {
+    redis: Option<ConnectionManager>,
+    /// user_id → session_id → entry, when there's no Redis.
+    local: Mutex<HashMap<Uuid, Sessions>>,
+}
+
+impl PresenceService {
+    pub fn new(redis: Option<ConnectionManager>) -> Self {
+        Self {
+            redis,
+            local: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// The user's aggregate presence.
+    pub async fn get(&self, user_id: Uuid) -> UserPresence {
+        aggregate(&self.load(user_id).await)
+    }
+
+    /// Whether the user has a live session anywhere.
+    pub async fn is_online(&self, user_id: Uuid) -> bool {
+        !self.load(user_id).await.is_empty()
+    }
+
+    /// Set one session's status, adding the session if it's new. Returns
+    /// the user's new presence if that changed.
+    ///
+    /// A connected session can't be offline, so asking for `Offline` sets
+    /// `Invisible`, here and in [`set_all`](Self::set_all).
+    pub async fn set(&self, user_id: Uuid, session_id: &str, status: UserPresence) -> Option<UserPresence> {
+        let status = connected(status);
+        let expires_at = expiry();
+        self.update(user_id, |sessions| {
+            sessions.insert(session_id.to_owned(), Entry { status, expires_at });
+        })
+        .await
+    }
+
+    /// Set the status of all the user's live sessions, e.g. from the REST
+    /// API. A user with no sessions stays offline.
+    pub async fn set_all(&self, user_id: Uuid, status: UserPresence) -> Option<UserPresence> {
+        let status = connected(status);
+        self.update(user_id, |sessions| {
+            for entry in sessions.values_mut() {
+                entry.status = status;
+            }
+        })
+        .await
+    }
+
+    /// Keep a session alive for another [`SESSION_TTL_SECS`].
+    pub async fn refresh(&self, user_id: Uuid, session_id: &str) -> Option<UserPresence> {
+        let expires_at = expiry();
+        self.update(user_id, |sessions| {
+            if let Some(entry) = sessions.get_mut(session_id) {
+                entry.expires_at = expires_at;
+            }
+        })
+        .await
+    }
+
+    /// Drop a closed session. Returns the user's new presence if that
+    /// changed — `Offline` when it was their last.
+    pub async fn remove(&self, user_id: Uuid, session_id: &str) -> Option<UserPresence> {
+        self.update(user_id, |sessions| {
+            sessions.remove(session_id);
+        })
+        .await
+    }
+
+    /// The user's live sessions. Expired ones are left for the next update
+    /// to clear.
+    async fn load(&self, user_id: Uuid) -> Sessions {
+        let now = chrono::Utc::now().timestamp_millis();
+        let mut sessions = match &self.redis {
+            Some(redis) => load_redis(&mut redis.clone(), user_id).await.unwrap_or_else(|e| {
+                tracing::warn!(%user_id, error = %e, "Failed to load presence");
+                Sessions::new()
+            }),
+            None => self.local.lock().unwrap().get(&user_id).cloned().unwrap_or_default(),
+        };
+        sessions.retain(|_, entry| entry.expires_at > now);
+        sessions
+    }
+
+    /// Apply `change` to the user's sessions and store the fields it
+    /// touched. Only changed fields are written, so nodes updating
+    /// different sessions of one user don't overwrite each other.
+    async fn update(&self, user_id: Uuid, change: impl FnOnce(&mut Sessions)) -> Option<UserPresence> {
+        let now = chrono::Utc::now().timestamp_millis();
+        let Some(redis) = &self.redis else {
+            let mut local = self.local.lock().unwrap();
+            let sessions = local.entry(user_id).or_default();
+            sessions.retain(|_, entry| entry.expires_at > now);
+            let before = aggregate(sessions);
+            change(sessions);
+            let after = aggregate(sessions);
+            if sessions.is_empty() {
+                local.remove(&user_id);
+            }
+            return (after != before).then_some(after);
+        };
+
+        let mut conn = redis.clone();
+        let stored = match load_redis(&mut conn, user_id).await {
+            Ok(stored) => stored,
+            Err(e) => {
+                tracing::warn!(%user_id, error = %e, "Failed to load presence");
+                return None;
+            }
+        };
+        let mut sessions = stored.clone();
+        sessions.retain(|_, entry| entry.expires_at > now);
+        let before = aggregate(&sessions);
+        change(&mut sessions);
+        let after = aggregate(&sessions);
+
+        let removed: Vec<&String> = stored.keys().filter(|id| !sessions.contains_key(*id)).collect();
+        let written: Vec<(&String, String)> = sessions
+            .iter()
+            .filter(|(id, entry)| stored.get(*id) != Some(*entry))
+            .filter_map(|(id, entry)| Some((id, serde_json::to_string(entry).ok()?)))
+            .collect();
+        let key = presence_key(user_id);
+        let mut pipe = redis::pipe();
+        pipe.atomic();
+        if !removed.is_empty() {
+            pipe.hdel(&key, removed).ignore();
+        }
+        if !written.is_empty() {
+            pipe.hset_multiple(&key, &written).ignore();
+            pipe.expire(&key, SESSION_TTL_SECS).ignore();
+        }
+        if let Err(e) = pipe.query_async::<()>(&mut conn).await {
+            tracing::warn!(%user_id, error = %e, "Failed to store presence");
+            return None;
+        }
+        (after != before).then_some(after)
+    }
+}
+
+fn connected(status: UserPresence) -> UserPresence {
+    match status {
+        UserPresence::Offline => UserPresence::Invisible,
+        status => status,
+    }
+}
+
+fn expiry() -> i64 {
+    chrono::Utc::now().timestamp_millis() + SESSION_TTL_SECS * 1000
+}
+
+fn aggregate(sessions: &Sessions) -> UserPresence {
+    UserPresence::aggregate(sessions.values().map(|entry| entry.status))
+}
+
+async fn load_redis(conn: &mut ConnectionManager, user_id: Uuid) -> Result<Sessions, redis::RedisError> {
+    let raw: HashMap<String, String> = conn.hgetall(presence_key(user_id)).await?;
+    Ok(raw
+        .into_iter()
+        .filter_map(|(id, entry)| Some((id, serde_json::from_str(&entry).ok()?)))
+        .collect())
+}
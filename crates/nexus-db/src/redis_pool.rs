@@ -49,3 +49,43 @@ pub async fn incr_expire(
     }
     Ok(count)
 }
+
+/// Take one token from the bucket at `key` (see `nexus_common::ratelimit`),
+/// refilling it for the time since it was last used. Returns whether a token
+/// was taken and the tokens left. Atomic across every process sharing Redis.
+pub async fn take_token(
+    conn: &mut ConnectionManager,
+    key: &str,
+    limit: nexus_common::ratelimit::Limit,
+    now_ms: u64,
+) -> Result<(bool, f64), redis::RedisError> {
+    static SCRIPT: std::sync::LazyLock<redis::Script> = std::sync::LazyLock::new(|| {
+        redis::Script::new(
+            r#"
+            local capacity = tonumber(ARGV[1])
+            local rate = tonumber(ARGV[2])
+            local now = tonumber(ARGV[3])
+            local state = redis.call('HMGET', KEYS[1], 'tokens', 'ts')
+            local tokens = tonumber(state[1]) or capacity
+            local ts = tonumber(state[2]) or now
+            tokens = math.min(capacity, tokens + math.max(0, now - ts) * rate)
+            local allowed = 0
+            if tokens >= 1 then
+                tokens = tokens - 1
+                allowed = 1
+            end
+            redis.call('HSET', KEYS[1], 'tokens', tostring(tokens), 'ts', math.max(now, ts))
+            redis.call('PEXPIRE', KEYS[1], math.ceil((capacity - tokens) / rate) + 1000)
+            return {allowed, tostring(tokens)}
+            "#,
+        )
+    });
+    let (allowed, tokens): (i64, String) = SCRIPT
+        .key(key)
+        .arg(limit.capacity)
+        .arg(limit.rate_per_ms())
+        .arg(now_ms)
+        .invoke_async(conn)
+        .await?;
+    Ok((allowed == 1, tokens.parse().unwrap_or(0.0)))
+}
@@ -0,0 +1,139 @@
+//! ActivityPub repository — actor signing key and channel followers.
+
+use chrono::{DateTime, Utc};
+use sqlx::Row;
+use uuid::Uuid;
+
+#[derive(Debug, Clone)]
+pub struct FollowerRow {
+    pub id: Uuid,
+    pub channel_id: Uuid,
+    pub actor_uri: String,
+    pub inbox_url: String,
+    pub shared_inbox_url: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl<'r> sqlx::FromRow<'r, sqlx::any::AnyRow> for FollowerRow {
+    fn from_row(row: &'r sqlx::any::AnyRow) -> Result<Self, sqlx::Error> {
+        use crate::any_compat::*;
+        Ok(FollowerRow {
+            id: get_uuid(row, "id")?,
+            channel_id: get_uuid(row, "channel_id")?,
+            actor_uri: row.try_get("actor_uri")?,
+            inbox_url: row.try_get("inbox_url")?,
+            shared_inbox_url: row.try_get("shared_inbox_url")?,
+            created_at: get_datetime(row, "created_at")?,
+        })
+    }
+}
+
+// ============================================================
+// Actor key
+// ============================================================
+
+/// Load the newest actor key as `(private_key_pem, public_key_pem)`.
+#[tracing::instrument(skip_all)]
+pub async fn load_key(pool: &sqlx::AnyPool) -> Result<Option<(String, String)>, sqlx::Error> {
+    let row = sqlx::query(
+        "SELECT private_key_pem, public_key_pem FROM activitypub_keys ORDER BY created_at DESC LIMIT 1",
+    )
+    .fetch_optional(pool)
+    .await?;
+    row.map(|r| Ok((r.try_get("private_key_pem")?, r.try_get("public_key_pem")?)))
+        .transpose()
+}
+
+/// Persist a freshly generated actor key.
+#[tracing::instrument(skip_all)]
+pub async fn store_key(
+    pool: &sqlx::AnyPool,
+    id: Uuid,
+    private_key_pem: &str,
+    public_key_pem: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("INSERT INTO activitypub_keys (id, private_key_pem, public_key_pem) VALUES (?, ?, ?)")
+        .bind(id.to_string())
+        .bind(private_key_pem)
+        .bind(public_key_pem)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+// ============================================================
+// Followers
+// ============================================================
+
+/// Record (or refresh) a remote follower of a channel.
+#[tracing::instrument(skip_all)]
+pub async fn add_follower(
+    pool: &sqlx::AnyPool,
+    id: Uuid,
+    channel_id: Uuid,
+    actor_uri: &str,
+    inbox_url: &str,
+    shared_inbox_url: Option<&str>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO activitypub_followers (id, channel_id, actor_uri, inbox_url, shared_inbox_url)
+        VALUES (?, ?, ?, ?, ?)
+        ON CONFLICT (channel_id, actor_uri) DO UPDATE SET
+            inbox_url = excluded.inbox_url,
+            shared_inbox_url = excluded.shared_inbox_url
+        "#,
+    )
+    .bind(id.to_string())
+    .bind(channel_id.to_string())
+    .bind(actor_uri)
+    .bind(inbox_url)
+    .bind(shared_inbox_url)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Remove a follower. Returns whether a row was deleted.
+#[tracing::instrument(skip_all)]
+pub async fn remove_follower(
+    pool: &sqlx::AnyPool,
+    channel_id: Uuid,
+    actor_uri: &str,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM activitypub_followers WHERE channel_id = ? AND actor_uri = ?")
+        .bind(channel_id.to_string())
+        .bind(actor_uri)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Distinct delivery targets for a channel, preferring shared inboxes.
+#[tracing::instrument(skip_all)]
+pub async fn list_delivery_inboxes(
+    pool: &sqlx::AnyPool,
+    channel_id: Uuid,
+) -> Result<Vec<String>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT DISTINCT COALESCE(shared_inbox_url, inbox_url) AS inbox
+        FROM activitypub_followers
+        WHERE channel_id = ?
+        "#,
+    )
+    .bind(channel_id.to_string())
+    .fetch_all(pool)
+    .await?;
+    rows.iter().map(|r| r.try_get("inbox")).collect()
+}
+
+/// Number of followers of a channel.
+#[tracing::instrument(skip_all)]
+pub async fn count_followers(pool: &sqlx::AnyPool, channel_id: Uuid) -> Result<i64, sqlx::Error> {
+    let row = sqlx::query("SELECT COUNT(*) AS n FROM activitypub_followers WHERE channel_id = ?")
+        .bind(channel_id.to_string())
+        .fetch_one(pool)
+        .await?;
+    row.try_get("n")
+}
@@ -0,0 +1,185 @@
+//! Instance-wide queries for the admin API — across every user and server,
+//! where the other repositories work within one.
+
+use chrono::{DateTime, Duration, Utc};
+use nexus_common::models::{server::Server, user::User};
+use sqlx::Row;
+use uuid::Uuid;
+
+/// Headline numbers for the admin dashboard.
+#[derive(Debug, Clone)]
+pub struct InstanceStats {
+    pub users: i64,
+    pub servers: i64,
+    /// Messages not deleted.
+    pub messages: i64,
+    pub messages_last_24h: i64,
+    pub attachments: i64,
+    pub attachment_bytes: i64,
+    pub federation_peers: i64,
+}
+
+/// A remote server this instance has federated with.
+#[derive(Debug, Clone)]
+pub struct FederationPeer {
+    pub id: Uuid,
+    pub server_name: String,
+    pub server_type: String,
+    pub server_version: Option<String>,
+    pub base_url: Option<String>,
+    pub is_blocked: bool,
+    pub throttled_until: Option<DateTime<Utc>>,
+    pub first_seen_at: DateTime<Utc>,
+    pub last_seen_at: DateTime<Utc>,
+}
+
+impl<'r> sqlx::FromRow<'r, sqlx::any::AnyRow> for FederationPeer {
+    fn from_row(row: &'r sqlx::any::AnyRow) -> Result<Self, sqlx::Error> {
+        use crate::any_compat::*;
+        Ok(FederationPeer {
+            id: get_uuid(row, "id")?,
+            server_name: row.try_get("server_name")?,
+            server_type: row.try_get("server_type")?,
+            server_version: row.try_get("server_version")?,
+            base_url: row.try_get("base_url")?,
+            is_blocked: row.try_get("is_blocked")?,
+            throttled_until: get_opt_datetime(row, "throttled_until")?,
+            first_seen_at: get_datetime(row, "first_seen_at")?,
+            last_seen_at: get_datetime(row, "last_seen_at")?,
+        })
+    }
+}
+
+fn sql_timestamp(ts: DateTime<Utc>) -> String {
+    ts.format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+async fn count(pool: &sqlx::AnyPool, sql: &str) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar(sql).fetch_one(pool).await
+}
+
+#[tracing::instrument(skip_all)]
+pub async fn stats(pool: &sqlx::AnyPool) -> Result<InstanceStats, sqlx::Error> {
+    let messages_last_24h: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM messages WHERE deleted_at IS NULL AND created_at > ?")
+            .bind(sql_timestamp(Utc::now() - Duration::hours(24)))
+            .fetch_one(pool)
+            .await?;
+    Ok(InstanceStats {
+        users: count(pool, "SELECT COUNT(*) FROM users").await?,
+        servers: count(pool, "SELECT COUNT(*) FROM servers").await?,
+        messages: count(pool, "SELECT COUNT(*) FROM messages WHERE deleted_at IS NULL").await?,
+        messages_last_24h,
+        attachments: count(pool, "SELECT COUNT(*) FROM attachments").await?,
+        // SUM(BIGINT) is NUMERIC on PostgreSQL
+        attachment_bytes: count(pool, "SELECT CAST(COALESCE(SUM(size), 0) AS BIGINT) FROM attachments")
+            .await?,
+        federation_peers: count(pool, "SELECT COUNT(*) FROM federated_servers").await?,
+    })
+}
+
+/// Messages posted per UTC day over the last `days` days, oldest first, as
+/// `(YYYY-MM-DD, count)`. Days without messages are left out.
+#[tracing::instrument(skip_all)]
+pub async fn daily_message_counts(pool: &sqlx::AnyPool, days: i64) -> Result<Vec<(String, i64)>, sqlx::Error> {
+    let since = (Utc::now() - Duration::days(days - 1)).date_naive();
+    sqlx::query_as(
+        r#"
+        SELECT CAST(DATE(created_at) AS TEXT) AS day, COUNT(*) AS messages
+        FROM messages
+        WHERE created_at >= ?
+        GROUP BY DATE(created_at)
+        ORDER BY DATE(created_at)
+        "#,
+    )
+    .bind(since.format("%Y-%m-%d 00:00:00").to_string())
+    .fetch_all(pool)
+    .await
+}
+
+/// Users, newest first; `query` matches username, display name or email.
+/// Pass the last user's ID as `before` to page back.
+#[tracing::instrument(skip_all)]
+pub async fn list_users(
+    pool: &sqlx::AnyPool,
+    query: Option<&str>,
+    before: Option<Uuid>,
+    limit: i64,
+) -> Result<Vec<User>, sqlx::Error> {
+    let mut conditions = String::from("1 = 1");
+    if query.is_some() {
+        conditions.push_str(
+            " AND (LOWER(username) LIKE ? OR LOWER(display_name) LIKE ? OR LOWER(email) LIKE ?)",
+        );
+    }
+    if before.is_some() {
+        conditions.push_str(" AND id < ?");
+    }
+
+    let sql = format!("SELECT * FROM users WHERE {conditions} ORDER BY id DESC LIMIT ?");
+    let mut q = sqlx::query_as::<_, User>(&sql);
+    if let Some(query) = query {
+        let pattern = like_pattern(query);
+        q = q.bind(pattern.clone()).bind(pattern.clone()).bind(pattern);
+    }
+    if let Some(before) = before {
+        q = q.bind(before.to_string());
+    }
+    q.bind(limit).fetch_all(pool).await
+}
+
+/// Servers, newest first; `query` matches the name. Pass the last server's
+/// ID as `before` to page back.
+#[tracing::instrument(skip_all)]
+pub async fn list_servers(
+    pool: &sqlx::AnyPool,
+    query: Option<&str>,
+    before: Option<Uuid>,
+    limit: i64,
+) -> Result<Vec<Server>, sqlx::Error> {
+    let mut conditions = String::from("1 = 1");
+    if query.is_some() {
+        conditions.push_str(" AND LOWER(name) LIKE ?");
+    }
+    if before.is_some() {
+        conditions.push_str(" AND id < ?");
+    }
+
+    let sql = format!("SELECT * FROM servers WHERE {conditions} ORDER BY id DESC LIMIT ?");
+    let mut q = sqlx::query_as::<_, Server>(&sql);
+    if let Some(query) = query {
+        q = q.bind(like_pattern(query));
+    }
+    if let Some(before) = before {
+        q = q.bind(before.to_string());
+    }
+    q.bind(limit).fetch_all(pool).await
+}
+
+/// Every known remote server, most recently seen first.
+#[tracing::instrument(skip_all)]
+pub async fn list_federation_peers(
+    pool: &sqlx::AnyPool,
+    limit: i64,
+    offset: i64,
+) -> Result<Vec<FederationPeer>, sqlx::Error> {
+    sqlx::query_as::<_, FederationPeer>(
+        r#"
+        SELECT id, server_name, server_type, server_version, base_url, is_blocked,
+               throttled_until, first_seen_at, last_seen_at
+        FROM federated_servers
+        ORDER BY last_seen_at DESC
+        LIMIT ? OFFSET ?
+        "#,
+    )
+    .bind(limit)
+    .bind(offset)
+    .fetch_all(pool)
+    .await
+}
+
+/// `%query%`, lowercased. A `%` in `query` is dropped; `_` is left to match
+/// itself (or any one character), as usernames contain it.
+fn like_pattern(query: &str) -> String {
+    format!("%{}%", query.to_lowercase().replace('%', ""))
+}
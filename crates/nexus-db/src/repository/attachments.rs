@@ -13,6 +13,7 @@ use uuid::Uuid;
 
 /// Insert a new pending attachment record.
 #[allow(clippy::too_many_arguments)]
+#[tracing::instrument(skip_all)]
 pub async fn create_attachment(
     pool: &sqlx::AnyPool,
     id: Uuid,
@@ -65,11 +66,75 @@ pub async fn create_attachment(
     .await
 }
 
+/// Insert a ready attachment for a multipart message create.
+///
+/// `message_id` is the message being created in the same transaction, or
+/// `None` when the message is held for moderation and linked on approval.
+#[allow(clippy::too_many_arguments)]
+#[tracing::instrument(skip_all)]
+pub async fn create_for_message<'e, E>(
+    executor: E,
+    id: Uuid,
+    uploader_id: Uuid,
+    server_id: Option<Uuid>,
+    channel_id: Uuid,
+    message_id: Option<Uuid>,
+    filename: &str,
+    content_type: &str,
+    size: i64,
+    storage_key: &str,
+    url: Option<&str>,
+    width: Option<i32>,
+    height: Option<i32>,
+    spoiler: bool,
+    sha256: &str,
+) -> Result<AttachmentRow, sqlx::Error>
+where
+    E: sqlx::Executor<'e, Database = sqlx::Any>,
+{
+    sqlx::query_as::<_, AttachmentRow>(
+        r#"
+        INSERT INTO attachments (
+            id, uploader_id, server_id, channel_id, message_id,
+            filename, content_type, size, storage_key, url,
+            width, height,
+            spoiler, sha256, status,
+            created_at, updated_at
+        )
+        VALUES (
+            ?, ?, ?, ?, ?,
+            ?, ?, ?, ?, ?,
+            ?, ?,
+            ?, ?, 'ready',
+            CURRENT_TIMESTAMP, CURRENT_TIMESTAMP
+        )
+        RETURNING *
+        "#,
+    )
+    .bind(id.to_string())
+    .bind(uploader_id.to_string())
+    .bind(server_id.map(|u| u.to_string()))
+    .bind(channel_id.to_string())
+    .bind(message_id.map(|u| u.to_string()))
+    .bind(filename)
+    .bind(content_type)
+    .bind(size)
+    .bind(storage_key)
+    .bind(url)
+    .bind(width)
+    .bind(height)
+    .bind(spoiler)
+    .bind(sha256)
+    .fetch_one(executor)
+    .await
+}
+
 // ============================================================
 // Read
 // ============================================================
 
 /// Find an attachment by ID.
+#[tracing::instrument(skip_all)]
 pub async fn find_by_id(pool: &sqlx::AnyPool, id: Uuid) -> Result<Option<AttachmentRow>, sqlx::Error> {
     sqlx::query_as::<_, AttachmentRow>("SELECT * FROM attachments WHERE id = ?")
         .bind(id.to_string())
@@ -78,6 +143,7 @@ pub async fn find_by_id(pool: &sqlx::AnyPool, id: Uuid) -> Result<Option<Attachm
 }
 
 /// Find all attachments for a message.
+#[tracing::instrument(skip_all)]
 pub async fn list_for_message(
     pool: &sqlx::AnyPool,
     message_id: Uuid,
@@ -91,6 +157,7 @@ pub async fn list_for_message(
 }
 
 /// Find all attachments uploaded by a user (paginated).
+#[tracing::instrument(skip_all)]
 pub async fn list_for_uploader(
     pool: &sqlx::AnyPool,
     uploader_id: Uuid,
@@ -133,6 +200,7 @@ pub async fn list_for_uploader(
 // ============================================================
 
 /// Mark an attachment as ready and set its public URL.
+#[tracing::instrument(skip_all)]
 pub async fn mark_ready(
     pool: &sqlx::AnyPool,
     id: Uuid,
@@ -147,14 +215,46 @@ pub async fn mark_ready(
         RETURNING *
         "#,
     )
-    .bind(id.to_string())
     .bind(url)
     .bind(blurhash)
+    .bind(id.to_string())
+    .fetch_one(pool)
+    .await
+}
+
+/// Store what the media job derived from an image: its display
+/// dimensions, blurhash placeholder and thumbnail.
+#[tracing::instrument(skip_all)]
+pub async fn set_media_metadata(
+    pool: &sqlx::AnyPool,
+    id: Uuid,
+    width: i32,
+    height: i32,
+    blurhash: Option<&str>,
+    thumbnail_key: Option<&str>,
+    thumbnail_url: Option<&str>,
+) -> Result<AttachmentRow, sqlx::Error> {
+    sqlx::query_as::<_, AttachmentRow>(
+        r#"
+        UPDATE attachments
+        SET width = ?, height = ?, blurhash = ?, thumbnail_key = ?, thumbnail_url = ?,
+            updated_at = CURRENT_TIMESTAMP
+        WHERE id = ?
+        RETURNING *
+        "#,
+    )
+    .bind(width)
+    .bind(height)
+    .bind(blurhash)
+    .bind(thumbnail_key)
+    .bind(thumbnail_url)
+    .bind(id.to_string())
     .fetch_one(pool)
     .await
 }
 
 /// Link an attachment to a message after the message is created.
+#[tracing::instrument(skip_all)]
 pub async fn attach_to_message(
     pool: &sqlx::AnyPool,
     attachment_id: Uuid,
@@ -163,14 +263,77 @@ pub async fn attach_to_message(
     sqlx::query(
         "UPDATE attachments SET message_id = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?",
     )
-    .bind(attachment_id.to_string())
     .bind(message_id.to_string())
+    .bind(attachment_id.to_string())
     .execute(pool)
     .await?;
     Ok(())
 }
 
+/// Claim a previously uploaded attachment for a new message.
+///
+/// Only the uploader's own, still-unlinked, ready attachments can be
+/// claimed; returns `None` otherwise. `spoiler` can only turn the spoiler
+/// mark on, never off.
+#[tracing::instrument(skip_all)]
+pub async fn claim_for_message<'e, E>(
+    executor: E,
+    attachment_id: Uuid,
+    uploader_id: Uuid,
+    channel_id: Uuid,
+    message_id: Uuid,
+    spoiler: bool,
+) -> Result<Option<AttachmentRow>, sqlx::Error>
+where
+    E: sqlx::Executor<'e, Database = sqlx::Any>,
+{
+    sqlx::query_as::<_, AttachmentRow>(
+        r#"
+        UPDATE attachments
+        SET message_id = ?, channel_id = ?, spoiler = (spoiler OR ?),
+            updated_at = CURRENT_TIMESTAMP
+        WHERE id = ? AND uploader_id = ? AND message_id IS NULL AND status = 'ready'
+        RETURNING *
+        "#,
+    )
+    .bind(message_id.to_string())
+    .bind(channel_id.to_string())
+    .bind(spoiler)
+    .bind(attachment_id.to_string())
+    .bind(uploader_id.to_string())
+    .fetch_optional(executor)
+    .await
+}
+
+/// Re-home every attachment of `from_message` onto `to_message` in
+/// `channel_id`. Returns the number of attachments moved.
+#[tracing::instrument(skip_all)]
+pub async fn move_to_message<'e, E>(
+    executor: E,
+    from_message: Uuid,
+    to_message: Uuid,
+    channel_id: Uuid,
+) -> Result<u64, sqlx::Error>
+where
+    E: sqlx::Executor<'e, Database = sqlx::Any>,
+{
+    let result = sqlx::query(
+        r#"
+        UPDATE attachments
+        SET message_id = ?, channel_id = ?, updated_at = CURRENT_TIMESTAMP
+        WHERE message_id = ?
+        "#,
+    )
+    .bind(to_message.to_string())
+    .bind(channel_id.to_string())
+    .bind(from_message.to_string())
+    .execute(executor)
+    .await?;
+    Ok(result.rows_affected())
+}
+
 /// Mark an attachment as failed.
+#[tracing::instrument(skip_all)]
 pub async fn mark_failed(pool: &sqlx::AnyPool, id: Uuid) -> Result<(), sqlx::Error> {
     sqlx::query(
         "UPDATE attachments SET status = 'failed', updated_at = CURRENT_TIMESTAMP WHERE id = ?",
@@ -186,6 +349,7 @@ pub async fn mark_failed(pool: &sqlx::AnyPool, id: Uuid) -> Result<(), sqlx::Err
 // ============================================================
 
 /// Delete an attachment record. Caller is responsible for deleting from storage.
+#[tracing::instrument(skip_all)]
 pub async fn delete_attachment(
     pool: &sqlx::AnyPool,
     id: Uuid,
@@ -200,3 +364,38 @@ pub async fn delete_attachment(
     .await?;
     Ok(result.rows_affected() > 0)
 }
+
+/// Delete every attachment matching all the given filters, returning the
+/// rows so the caller can remove the files from storage. Admin use; at
+/// least one filter should be set.
+#[tracing::instrument(skip_all)]
+pub async fn purge(
+    pool: &sqlx::AnyPool,
+    uploader_id: Option<Uuid>,
+    server_id: Option<Uuid>,
+    created_before: Option<chrono::DateTime<chrono::Utc>>,
+) -> Result<Vec<AttachmentRow>, sqlx::Error> {
+    let mut conditions = String::from("1 = 1");
+    if uploader_id.is_some() {
+        conditions.push_str(" AND uploader_id = ?");
+    }
+    if server_id.is_some() {
+        conditions.push_str(" AND server_id = ?");
+    }
+    if created_before.is_some() {
+        conditions.push_str(" AND created_at < ?");
+    }
+
+    let sql = format!("DELETE FROM attachments WHERE {conditions} RETURNING *");
+    let mut query = sqlx::query_as::<_, AttachmentRow>(&sql);
+    if let Some(uploader_id) = uploader_id {
+        query = query.bind(uploader_id.to_string());
+    }
+    if let Some(server_id) = server_id {
+        query = query.bind(server_id.to_string());
+    }
+    if let Some(before) = created_before {
+        query = query.bind(before.format("%Y-%m-%d %H:%M:%S").to_string());
+    }
+    query.fetch_all(pool).await
+}
@@ -0,0 +1,127 @@
+//! Audit log repository — append-only record of moderation and admin actions.
+
+use chrono::{DateTime, Utc};
+use sqlx::Row;
+use uuid::Uuid;
+
+#[derive(Debug, Clone)]
+pub struct AuditLogRow {
+    pub id: Uuid,
+    pub server_id: Uuid,
+    /// The member who performed the action.
+    pub user_id: Uuid,
+    pub action: String,
+    pub target_type: Option<String>,
+    pub target_id: Option<Uuid>,
+    pub changes: Option<serde_json::Value>,
+    pub reason: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl<'r> sqlx::FromRow<'r, sqlx::any::AnyRow> for AuditLogRow {
+    fn from_row(row: &'r sqlx::any::AnyRow) -> Result<Self, sqlx::Error> {
+        use crate::any_compat::*;
+        Ok(AuditLogRow {
+            id: get_uuid(row, "id")?,
+            server_id: get_uuid(row, "server_id")?,
+            user_id: get_uuid(row, "user_id")?,
+            action: row.try_get("action")?,
+            target_type: row.try_get("target_type")?,
+            target_id: get_opt_uuid(row, "target_id")?,
+            changes: get_opt_json_value(row, "changes")?,
+            reason: row.try_get("reason")?,
+            created_at: get_datetime(row, "created_at")?,
+        })
+    }
+}
+
+/// Filters for [`list_entries`]. Unset fields match everything.
+#[derive(Debug, Clone, Default)]
+pub struct AuditLogFilter<'a> {
+    pub action: Option<&'a str>,
+    pub user_id: Option<Uuid>,
+    pub target_id: Option<Uuid>,
+    /// Only entries older than this entry (IDs are time-ordered).
+    pub before: Option<Uuid>,
+}
+
+/// Append an entry to a server's audit log.
+#[allow(clippy::too_many_arguments)]
+#[tracing::instrument(skip_all)]
+pub async fn create_entry(
+    pool: &sqlx::AnyPool,
+    server_id: Uuid,
+    user_id: Uuid,
+    action: &str,
+    target_type: Option<&str>,
+    target_id: Option<Uuid>,
+    changes: Option<&serde_json::Value>,
+    reason: Option<&str>,
+) -> Result<Uuid, sqlx::Error> {
+    let id = Uuid::now_v7();
+    sqlx::query(
+        r#"
+        INSERT INTO audit_log (id, server_id, user_id, action, target_type, target_id, changes, reason, created_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, CURRENT_TIMESTAMP)
+        "#,
+    )
+    .bind(id.to_string())
+    .bind(server_id.to_string())
+    .bind(user_id.to_string())
+    .bind(action)
+    .bind(target_type)
+    .bind(target_id.map(|u| u.to_string()))
+    .bind(changes.map(|c| c.to_string()))
+    .bind(reason)
+    .execute(pool)
+    .await?;
+    Ok(id)
+}
+
+/// Fetch a single entry (used to broadcast it after creation).
+#[tracing::instrument(skip_all)]
+pub async fn get_entry(pool: &sqlx::AnyPool, id: Uuid) -> Result<Option<AuditLogRow>, sqlx::Error> {
+    sqlx::query_as::<_, AuditLogRow>("SELECT * FROM audit_log WHERE id = ?")
+        .bind(id.to_string())
+        .fetch_optional(pool)
+        .await
+}
+
+/// List a server's audit log, newest first.
+#[tracing::instrument(skip_all)]
+pub async fn list_entries(
+    pool: &sqlx::AnyPool,
+    server_id: Uuid,
+    filter: &AuditLogFilter<'_>,
+    limit: i64,
+) -> Result<Vec<AuditLogRow>, sqlx::Error> {
+    let mut conditions = String::from("server_id = ?");
+    if filter.action.is_some() {
+        conditions.push_str(" AND action = ?");
+    }
+    if filter.user_id.is_some() {
+        conditions.push_str(" AND user_id = ?");
+    }
+    if filter.target_id.is_some() {
+        conditions.push_str(" AND target_id = ?");
+    }
+    if filter.before.is_some() {
+        conditions.push_str(" AND id < ?");
+    }
+
+    let sql = format!("SELECT * FROM audit_log WHERE {conditions} ORDER BY id DESC LIMIT ?");
+    let mut query = sqlx::query_as::<_, AuditLogRow>(&sql).bind(server_id.to_string());
+    if let Some(action) = filter.action {
+        query = query.bind(action);
+    }
+    if let Some(user_id) = filter.user_id {
+        query = query.bind(user_id.to_string());
+    }
+    if let Some(target_id) = filter.target_id {
+        query = query.bind(target_id.to_string());
+    }
+    if let Some(before) = filter.before {
+        query = query.bind(before.to_string());
+    }
+    query.bind(limit).fetch_all(pool).await
+}
@@ -0,0 +1,263 @@
+//! Ban repository — server bans and ban list subscriptions.
+
+use chrono::{DateTime, Utc};
+use sqlx::Row;
+use uuid::Uuid;
+
+#[derive(Debug, Clone)]
+pub struct BanRow {
+    pub user_id: Uuid,
+    pub server_id: Uuid,
+    pub reason: Option<String>,
+    pub banned_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl<'r> sqlx::FromRow<'r, sqlx::any::AnyRow> for BanRow {
+    fn from_row(row: &'r sqlx::any::AnyRow) -> Result<Self, sqlx::Error> {
+        use crate::any_compat::*;
+        Ok(BanRow {
+            user_id: get_uuid(row, "user_id")?,
+            server_id: get_uuid(row, "server_id")?,
+            reason: row.try_get("reason")?,
+            banned_by: get_opt_uuid(row, "banned_by")?,
+            created_at: get_datetime(row, "created_at")?,
+        })
+    }
+}
+
+/// A server's subscription to another ban list.
+#[derive(Debug, Clone)]
+pub struct BanSubscriptionRow {
+    pub id: Uuid,
+    pub server_id: Uuid,
+    pub source_server_id: Option<Uuid>,
+    pub source_url: Option<String>,
+    pub created_by: Uuid,
+    pub last_synced_at: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+    /// Set once the subscription stops syncing; see [`disable_subscription`].
+    pub disabled_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl<'r> sqlx::FromRow<'r, sqlx::any::AnyRow> for BanSubscriptionRow {
+    fn from_row(row: &'r sqlx::any::AnyRow) -> Result<Self, sqlx::Error> {
+        use crate::any_compat::*;
+        Ok(BanSubscriptionRow {
+            id: get_uuid(row, "id")?,
+            server_id: get_uuid(row, "server_id")?,
+            source_server_id: get_opt_uuid(row, "source_server_id")?,
+            source_url: row.try_get("source_url")?,
+            created_by: get_uuid(row, "created_by")?,
+            last_synced_at: get_opt_datetime(row, "last_synced_at")?,
+            last_error: row.try_get("last_error")?,
+            disabled_at: get_opt_datetime(row, "disabled_at")?,
+            created_at: get_datetime(row, "created_at")?,
+        })
+    }
+}
+
+// ============================================================
+// Bans
+// ============================================================
+
+/// Ban a user from a server.
+///
+/// Returns `false` when the user is already banned or doesn't exist on this
+/// instance (imported lists may name users we've never seen).
+#[tracing::instrument(skip_all)]
+pub async fn create_ban(
+    pool: &sqlx::AnyPool,
+    server_id: Uuid,
+    user_id: Uuid,
+    reason: Option<&str>,
+    banned_by: Uuid,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        INSERT INTO bans (user_id, server_id, reason, banned_by, created_at)
+        SELECT ?, ?, ?, ?, CURRENT_TIMESTAMP
+        WHERE EXISTS (SELECT 1 FROM users WHERE id = ?)
+        ON CONFLICT (user_id, server_id) DO NOTHING
+        "#,
+    )
+    .bind(user_id.to_string())
+    .bind(server_id.to_string())
+    .bind(reason)
+    .bind(banned_by.to_string())
+    .bind(user_id.to_string())
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Get a single ban.
+#[tracing::instrument(skip_all)]
+pub async fn find_ban(
+    pool: &sqlx::AnyPool,
+    server_id: Uuid,
+    user_id: Uuid,
+) -> Result<Option<BanRow>, sqlx::Error> {
+    sqlx::query_as::<_, BanRow>("SELECT * FROM bans WHERE server_id = ? AND user_id = ?")
+        .bind(server_id.to_string())
+        .bind(user_id.to_string())
+        .fetch_optional(pool)
+        .await
+}
+
+/// All bans in a server, oldest first.
+#[tracing::instrument(skip_all)]
+pub async fn list_bans(pool: &sqlx::AnyPool, server_id: Uuid) -> Result<Vec<BanRow>, sqlx::Error> {
+    sqlx::query_as::<_, BanRow>("SELECT * FROM bans WHERE server_id = ? ORDER BY created_at")
+        .bind(server_id.to_string())
+        .fetch_all(pool)
+        .await
+}
+
+/// A page of a server's bans ordered by user ID, starting after `after`.
+#[tracing::instrument(skip_all)]
+pub async fn list_bans_page(
+    pool: &sqlx::AnyPool,
+    server_id: Uuid,
+    after: Option<Uuid>,
+    limit: i64,
+) -> Result<Vec<BanRow>, sqlx::Error> {
+    match after {
+        Some(after) => {
+            sqlx::query_as::<_, BanRow>(
+                "SELECT * FROM bans WHERE server_id = ? AND user_id > ? ORDER BY user_id LIMIT ?",
+            )
+            .bind(server_id.to_string())
+            .bind(after.to_string())
+            .bind(limit)
+            .fetch_all(pool)
+            .await
+        }
+        None => {
+            sqlx::query_as::<_, BanRow>(
+                "SELECT * FROM bans WHERE server_id = ? ORDER BY user_id LIMIT ?",
+            )
+            .bind(server_id.to_string())
+            .bind(limit)
+            .fetch_all(pool)
+            .await
+        }
+    }
+}
+
+/// Lift a ban. Returns `false` if the user wasn't banned.
+#[tracing::instrument(skip_all)]
+pub async fn delete_ban(
+    pool: &sqlx::AnyPool,
+    server_id: Uuid,
+    user_id: Uuid,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM bans WHERE server_id = ? AND user_id = ?")
+        .bind(server_id.to_string())
+        .bind(user_id.to_string())
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+// ============================================================
+// Ban list subscriptions
+// ============================================================
+
+/// Subscribe a server to another ban list. Exactly one of
+/// `source_server_id` / `source_url` must be set.
+#[tracing::instrument(skip_all)]
+pub async fn create_subscription(
+    pool: &sqlx::AnyPool,
+    id: Uuid,
+    server_id: Uuid,
+    source_server_id: Option<Uuid>,
+    source_url: Option<&str>,
+    created_by: Uuid,
+) -> Result<BanSubscriptionRow, sqlx::Error> {
+    sqlx::query_as::<_, BanSubscriptionRow>(
+        r#"
+        INSERT INTO ban_list_subscriptions (id, server_id, source_server_id, source_url, created_by, created_at)
+        VALUES (?, ?, ?, ?, ?, CURRENT_TIMESTAMP)
+        RETURNING *
+        "#,
+    )
+    .bind(id.to_string())
+    .bind(server_id.to_string())
+    .bind(source_server_id.map(|u| u.to_string()))
+    .bind(source_url)
+    .bind(created_by.to_string())
+    .fetch_one(pool)
+    .await
+}
+
+#[tracing::instrument(skip_all)]
+pub async fn list_subscriptions(
+    pool: &sqlx::AnyPool,
+    server_id: Uuid,
+) -> Result<Vec<BanSubscriptionRow>, sqlx::Error> {
+    sqlx::query_as::<_, BanSubscriptionRow>(
+        "SELECT * FROM ban_list_subscriptions WHERE server_id = ? ORDER BY created_at",
+    )
+    .bind(server_id.to_string())
+    .fetch_all(pool)
+    .await
+}
+
+/// Every enabled subscription on the instance, for the sync job.
+#[tracing::instrument(skip_all)]
+pub async fn list_all_subscriptions(
+    pool: &sqlx::AnyPool,
+) -> Result<Vec<BanSubscriptionRow>, sqlx::Error> {
+    sqlx::query_as::<_, BanSubscriptionRow>(
+        "SELECT * FROM ban_list_subscriptions WHERE disabled_at IS NULL ORDER BY created_at",
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Delete a subscription. Returns `false` if it didn't belong to `server_id`.
+#[tracing::instrument(skip_all)]
+pub async fn delete_subscription(
+    pool: &sqlx::AnyPool,
+    server_id: Uuid,
+    id: Uuid,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM ban_list_subscriptions WHERE id = ? AND server_id = ?")
+        .bind(id.to_string())
+        .bind(server_id.to_string())
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Record the outcome of a sync; `error` is `None` on success.
+#[tracing::instrument(skip_all)]
+pub async fn record_sync(
+    pool: &sqlx::AnyPool,
+    id: Uuid,
+    error: Option<&str>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "UPDATE ban_list_subscriptions SET last_synced_at = CURRENT_TIMESTAMP, last_error = ? WHERE id = ?",
+    )
+    .bind(error)
+    .bind(id.to_string())
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Stop syncing a subscription for good, recording why.
+#[tracing::instrument(skip_all)]
+pub async fn disable_subscription(pool: &sqlx::AnyPool, id: Uuid, reason: &str) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "UPDATE ban_list_subscriptions SET disabled_at = CURRENT_TIMESTAMP, last_error = ? WHERE id = ?",
+    )
+    .bind(reason)
+    .bind(id.to_string())
+    .execute(pool)
+    .await?;
+    Ok(())
+}
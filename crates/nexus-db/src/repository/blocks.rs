@@ -0,0 +1,66 @@
+//! Block repository — users a user has blocked.
+//!
+//! Blocking only silences notifications for now: see
+//! [`read_states::notifications_suppressed`](super::read_states::notifications_suppressed).
+
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+/// A blocked user and when they were blocked.
+#[derive(Debug, Clone)]
+pub struct BlockRow {
+    pub blocked_id: Uuid,
+    pub created_at: DateTime<Utc>,
+}
+
+impl<'r> sqlx::FromRow<'r, sqlx::any::AnyRow> for BlockRow {
+    fn from_row(row: &'r sqlx::any::AnyRow) -> Result<Self, sqlx::Error> {
+        use crate::any_compat::*;
+        Ok(BlockRow {
+            blocked_id: get_uuid(row, "blocked_id")?,
+            created_at: get_datetime(row, "created_at")?,
+        })
+    }
+}
+
+/// Block `blocked_id` for `user_id`. Returns `false` if they already were,
+/// or `blocked_id` doesn't exist.
+#[tracing::instrument(skip_all)]
+pub async fn block(pool: &sqlx::AnyPool, user_id: Uuid, blocked_id: Uuid) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        INSERT INTO user_blocks (user_id, blocked_id, created_at)
+        SELECT ?, ?, CURRENT_TIMESTAMP
+        WHERE EXISTS (SELECT 1 FROM users WHERE id = ?)
+        ON CONFLICT (user_id, blocked_id) DO NOTHING
+        "#,
+    )
+    .bind(user_id.to_string())
+    .bind(blocked_id.to_string())
+    .bind(blocked_id.to_string())
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Returns whether `blocked_id` was blocked.
+#[tracing::instrument(skip_all)]
+pub async fn unblock(pool: &sqlx::AnyPool, user_id: Uuid, blocked_id: Uuid) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM user_blocks WHERE user_id = ? AND blocked_id = ?")
+        .bind(user_id.to_string())
+        .bind(blocked_id.to_string())
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Everyone `user_id` blocked, most recent first.
+#[tracing::instrument(skip_all)]
+pub async fn list_blocked(pool: &sqlx::AnyPool, user_id: Uuid) -> Result<Vec<BlockRow>, sqlx::Error> {
+    sqlx::query_as::<_, BlockRow>(
+        "SELECT blocked_id, created_at FROM user_blocks WHERE user_id = ? ORDER BY created_at DESC",
+    )
+    .bind(user_id.to_string())
+    .fetch_all(pool)
+    .await
+}
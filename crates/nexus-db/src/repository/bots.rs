@@ -1,6 +1,8 @@
-//! Bot application repository — CRUD for bot_applications and bot_server_installs.
+//! Bot application repository — CRUD for bot_applications and bot_server_installs,
+//! plus per-application request metrics.
 
 use anyhow::Result;
+use chrono::{DateTime, Utc};
 use sqlx::Row;
 use uuid::Uuid;
 
@@ -22,6 +24,10 @@ fn row_to_bot(row: &sqlx::any::AnyRow) -> BotApplication {
         is_public: row.try_get("is_public").unwrap_or(true),
         interactions_endpoint_url: row.try_get("interactions_endpoint_url").unwrap_or(None),
         flags: row.try_get("flags").unwrap_or(0),
+        ip_allowlist: row.try_get::<Option<String>, _>("ip_allowlist").unwrap_or(None)
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default(),
+        token_reset_at: crate::any_compat::get_opt_datetime(row, "token_reset_at").unwrap_or(None),
         created_at: crate::any_compat::get_datetime(row, "created_at").unwrap_or_default(),
         updated_at: crate::any_compat::get_datetime(row, "updated_at").unwrap_or_default(),
     }
@@ -45,6 +51,7 @@ fn row_to_server_install(row: &sqlx::any::AnyRow) -> BotServerInstall {
 // Bot Applications
 // ============================================================================
 
+#[tracing::instrument(skip_all)]
 pub async fn get_bot(pool: &sqlx::AnyPool, bot_id: Uuid) -> Result<Option<BotApplication>> {
     let row = sqlx::query("SELECT * FROM bot_applications WHERE id = ?")
         .bind(bot_id.to_string())
@@ -53,6 +60,7 @@ pub async fn get_bot(pool: &sqlx::AnyPool, bot_id: Uuid) -> Result<Option<BotApp
     Ok(row.as_ref().map(row_to_bot))
 }
 
+#[tracing::instrument(skip_all)]
 pub async fn get_bots_by_owner(pool: &sqlx::AnyPool, owner_id: Uuid) -> Result<Vec<BotApplication>> {
     let rows = sqlx::query(
         "SELECT * FROM bot_applications WHERE owner_id = ? ORDER BY created_at DESC",
@@ -63,6 +71,7 @@ pub async fn get_bots_by_owner(pool: &sqlx::AnyPool, owner_id: Uuid) -> Result<V
     Ok(rows.iter().map(row_to_bot).collect())
 }
 
+#[tracing::instrument(skip_all)]
 pub async fn get_bot_by_token_hash(
     pool: &sqlx::AnyPool,
     token_hash: &str,
@@ -74,6 +83,7 @@ pub async fn get_bot_by_token_hash(
     Ok(row.as_ref().map(row_to_bot))
 }
 
+#[tracing::instrument(skip_all)]
 pub async fn create_bot(
     pool: &sqlx::AnyPool,
     id: Uuid,
@@ -108,6 +118,7 @@ pub async fn create_bot(
     Ok(row_to_bot(&row))
 }
 
+#[tracing::instrument(skip_all)]
 pub async fn update_bot(
     pool: &sqlx::AnyPool,
     bot_id: Uuid,
@@ -117,8 +128,11 @@ pub async fn update_bot(
     is_public: Option<bool>,
     redirect_uris: Option<&[String]>,
     interactions_endpoint_url: Option<&str>,
+    ip_allowlist: Option<&[String]>,
+    flags: Option<i64>,
 ) -> Result<Option<BotApplication>> {
     let uris = redirect_uris.map(|r| serde_json::to_string(r)).transpose()?;
+    let allowlist = ip_allowlist.map(|a| serde_json::to_string(a)).transpose()?;
     let row = sqlx::query(
         r#"UPDATE bot_applications SET
                name        = COALESCE(?, name),
@@ -127,6 +141,8 @@ pub async fn update_bot(
                is_public   = COALESCE(?, is_public),
                redirect_uris = COALESCE(?, redirect_uris),
                interactions_endpoint_url = COALESCE(?, interactions_endpoint_url),
+               ip_allowlist = COALESCE(?, ip_allowlist),
+               flags       = COALESCE(?, flags),
                updated_at  = CURRENT_TIMESTAMP
            WHERE id = ?
            RETURNING *"#,
@@ -137,15 +153,21 @@ pub async fn update_bot(
     .bind(is_public)
     .bind(uris)
     .bind(interactions_endpoint_url)
+    .bind(allowlist)
+    .bind(flags)
     .bind(bot_id.to_string())
     .fetch_optional(pool)
     .await?;
     Ok(row.as_ref().map(row_to_bot))
 }
 
+/// Replace the token hash. The old token stops authenticating immediately.
+#[tracing::instrument(skip_all)]
 pub async fn update_bot_token(pool: &sqlx::AnyPool, bot_id: Uuid, new_token_hash: &str) -> Result<bool> {
     let result = sqlx::query(
-        "UPDATE bot_applications SET token_hash = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?",
+        r#"UPDATE bot_applications
+           SET token_hash = ?, token_reset_at = CURRENT_TIMESTAMP, updated_at = CURRENT_TIMESTAMP
+           WHERE id = ?"#,
     )
     .bind(new_token_hash)
     .bind(bot_id.to_string())
@@ -154,6 +176,33 @@ pub async fn update_bot_token(pool: &sqlx::AnyPool, bot_id: Uuid, new_token_hash
     Ok(result.rows_affected() > 0)
 }
 
+/// Replace the OAuth2 client secret hash. The old secret stops working
+/// immediately.
+#[tracing::instrument(skip_all)]
+pub async fn update_client_secret(pool: &sqlx::AnyPool, bot_id: Uuid, secret_hash: &str) -> Result<bool> {
+    let result = sqlx::query(
+        "UPDATE bot_applications SET client_secret_hash = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?",
+    )
+    .bind(secret_hash)
+    .bind(bot_id.to_string())
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// The application's OAuth2 client secret hash; `None` until one is
+/// generated.
+#[tracing::instrument(skip_all)]
+pub async fn get_client_secret_hash(pool: &sqlx::AnyPool, bot_id: Uuid) -> Result<Option<String>> {
+    let hash: Option<Option<String>> =
+        sqlx::query_scalar("SELECT client_secret_hash FROM bot_applications WHERE id = ?")
+            .bind(bot_id.to_string())
+            .fetch_optional(pool)
+            .await?;
+    Ok(hash.flatten())
+}
+
+#[tracing::instrument(skip_all)]
 pub async fn delete_bot(pool: &sqlx::AnyPool, bot_id: Uuid) -> Result<bool> {
     let result = sqlx::query("DELETE FROM bot_applications WHERE id = ?")
         .bind(bot_id.to_string())
@@ -162,10 +211,25 @@ pub async fn delete_bot(pool: &sqlx::AnyPool, bot_id: Uuid) -> Result<bool> {
     Ok(result.rows_affected() > 0)
 }
 
+/// Delete every application `owner_id` owns, returning how many went. Their
+/// tokens go with them; installs, commands and OAuth2 grants cascade.
+#[tracing::instrument(skip_all)]
+pub async fn delete_by_owner<'e, E>(executor: E, owner_id: Uuid) -> Result<u64, sqlx::Error>
+where
+    E: sqlx::Executor<'e, Database = sqlx::Any>,
+{
+    let result = sqlx::query("DELETE FROM bot_applications WHERE owner_id = ?")
+        .bind(owner_id.to_string())
+        .execute(executor)
+        .await?;
+    Ok(result.rows_affected())
+}
+
 // ============================================================================
 // Bot Server Installs
 // ============================================================================
 
+#[tracing::instrument(skip_all)]
 pub async fn install_bot_to_server(
     pool: &sqlx::AnyPool,
     bot_id: Uuid,
@@ -193,6 +257,7 @@ pub async fn install_bot_to_server(
     Ok(row_to_server_install(&row))
 }
 
+#[tracing::instrument(skip_all)]
 pub async fn get_server_bots(pool: &sqlx::AnyPool, server_id: Uuid) -> Result<Vec<BotServerInstall>> {
     let rows = sqlx::query(
         "SELECT * FROM bot_server_installs WHERE server_id = ? ORDER BY installed_at DESC",
@@ -203,6 +268,19 @@ pub async fn get_server_bots(pool: &sqlx::AnyPool, server_id: Uuid) -> Result<Ve
     Ok(rows.iter().map(row_to_server_install).collect())
 }
 
+/// Servers the bot is installed in.
+#[tracing::instrument(skip_all)]
+pub async fn get_bot_servers(pool: &sqlx::AnyPool, bot_id: Uuid) -> Result<Vec<Uuid>> {
+    let ids: Vec<String> = sqlx::query_scalar(
+        "SELECT server_id FROM bot_server_installs WHERE bot_id = ?",
+    )
+    .bind(bot_id.to_string())
+    .fetch_all(pool)
+    .await?;
+    Ok(ids.iter().filter_map(|id| id.parse().ok()).collect())
+}
+
+#[tracing::instrument(skip_all)]
 pub async fn uninstall_bot_from_server(
     pool: &sqlx::AnyPool,
     bot_id: Uuid,
@@ -218,6 +296,7 @@ pub async fn uninstall_bot_from_server(
     Ok(result.rows_affected() > 0)
 }
 
+#[tracing::instrument(skip_all)]
 pub async fn is_bot_in_server(pool: &sqlx::AnyPool, bot_id: Uuid, server_id: Uuid) -> Result<bool> {
     let count: i64 = sqlx::query_scalar(
         "SELECT COUNT(*) FROM bot_server_installs WHERE bot_id = ? AND server_id = ?",
@@ -228,3 +307,85 @@ pub async fn is_bot_in_server(pool: &sqlx::AnyPool, bot_id: Uuid, server_id: Uui
     .await?;
     Ok(count > 0)
 }
+
+// ============================================================================
+// Request Metrics
+// ============================================================================
+
+/// One application's request counters for a UTC day.
+#[derive(Debug)]
+pub struct BotMetricsDay {
+    /// Calendar day in `YYYY-MM-DD` form (UTC).
+    pub day: String,
+    pub requests: i64,
+    pub errors: i64,
+    pub rejected: i64,
+    pub total_latency_ms: i64,
+}
+
+impl<'r> sqlx::FromRow<'r, sqlx::any::AnyRow> for BotMetricsDay {
+    fn from_row(row: &'r sqlx::any::AnyRow) -> Result<Self, sqlx::Error> {
+        Ok(BotMetricsDay {
+            day: row.try_get("day")?,
+            requests: row.try_get("requests")?,
+            errors: row.try_get("errors")?,
+            rejected: row.try_get("rejected")?,
+            total_latency_ms: row.try_get("total_latency_ms")?,
+        })
+    }
+}
+
+fn metrics_day(ts: DateTime<Utc>) -> String {
+    ts.format("%Y-%m-%d").to_string()
+}
+
+/// Count a bot-token request in today's bucket. Requests refused by the IP
+/// allowlist are counted as `rejected` only.
+#[tracing::instrument(skip_all)]
+pub async fn record_request(
+    pool: &sqlx::AnyPool,
+    bot_id: Uuid,
+    rejected: bool,
+    error: bool,
+    latency_ms: i64,
+) -> Result<()> {
+    let (requests, rejected) = if rejected { (0i64, 1i64) } else { (1, 0) };
+    sqlx::query(
+        r#"INSERT INTO bot_request_metrics (bot_id, day, requests, errors, rejected, total_latency_ms)
+           VALUES (?, ?, ?, ?, ?, ?)
+           ON CONFLICT (bot_id, day) DO UPDATE SET
+               requests         = bot_request_metrics.requests + EXCLUDED.requests,
+               errors           = bot_request_metrics.errors + EXCLUDED.errors,
+               rejected         = bot_request_metrics.rejected + EXCLUDED.rejected,
+               total_latency_ms = bot_request_metrics.total_latency_ms + EXCLUDED.total_latency_ms"#,
+    )
+    .bind(bot_id.to_string())
+    .bind(metrics_day(Utc::now()))
+    .bind(requests)
+    .bind(i64::from(error))
+    .bind(rejected)
+    .bind(latency_ms)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Daily counters for `bot_id` from `since` onwards, oldest first.
+#[tracing::instrument(skip_all)]
+pub async fn request_metrics(
+    pool: &sqlx::AnyPool,
+    bot_id: Uuid,
+    since: DateTime<Utc>,
+) -> Result<Vec<BotMetricsDay>> {
+    let rows = sqlx::query_as::<_, BotMetricsDay>(
+        r#"SELECT day, requests, errors, rejected, total_latency_ms
+           FROM bot_request_metrics
+           WHERE bot_id = ? AND day >= ?
+           ORDER BY day ASC"#,
+    )
+    .bind(bot_id.to_string())
+    .bind(metrics_day(since))
+    .fetch_all(pool)
+    .await?;
+    Ok(rows)
+}
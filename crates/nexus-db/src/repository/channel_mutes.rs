@@ -0,0 +1,83 @@
+//! Channel mute repository — channels a user silenced, for good or until a
+//! set time.
+//!
+//! A mute only silences notifications (see
+//! [`read_states::notifications_suppressed`](super::read_states::notifications_suppressed));
+//! the channel still shows as unread.
+
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+#[derive(Debug, Clone)]
+pub struct ChannelMuteRow {
+    pub channel_id: Uuid,
+    /// `None` mutes until unmuted.
+    pub muted_until: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl<'r> sqlx::FromRow<'r, sqlx::any::AnyRow> for ChannelMuteRow {
+    fn from_row(row: &'r sqlx::any::AnyRow) -> Result<Self, sqlx::Error> {
+        use crate::any_compat::*;
+        Ok(ChannelMuteRow {
+            channel_id: get_uuid(row, "channel_id")?,
+            muted_until: get_opt_datetime(row, "muted_until")?,
+            created_at: get_datetime(row, "created_at")?,
+        })
+    }
+}
+
+fn sql_timestamp(ts: DateTime<Utc>) -> String {
+    ts.format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+/// Mute `channel_id` for `user_id` until `muted_until`, or indefinitely.
+/// Replaces an earlier mute.
+#[tracing::instrument(skip_all)]
+pub async fn mute(
+    pool: &sqlx::AnyPool,
+    user_id: Uuid,
+    channel_id: Uuid,
+    muted_until: Option<DateTime<Utc>>,
+) -> Result<ChannelMuteRow, sqlx::Error> {
+    sqlx::query_as::<_, ChannelMuteRow>(
+        r#"
+        INSERT INTO channel_mutes (user_id, channel_id, muted_until, created_at)
+        VALUES (?, ?, ?, CURRENT_TIMESTAMP)
+        ON CONFLICT (user_id, channel_id) DO UPDATE SET
+            muted_until = excluded.muted_until
+        RETURNING channel_id, muted_until, created_at
+        "#,
+    )
+    .bind(user_id.to_string())
+    .bind(channel_id.to_string())
+    .bind(muted_until.map(sql_timestamp))
+    .fetch_one(pool)
+    .await
+}
+
+/// Returns whether the channel was muted.
+#[tracing::instrument(skip_all)]
+pub async fn unmute(pool: &sqlx::AnyPool, user_id: Uuid, channel_id: Uuid) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM channel_mutes WHERE user_id = ? AND channel_id = ?")
+        .bind(user_id.to_string())
+        .bind(channel_id.to_string())
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// The user's mutes still in effect.
+#[tracing::instrument(skip_all)]
+pub async fn list_active(pool: &sqlx::AnyPool, user_id: Uuid) -> Result<Vec<ChannelMuteRow>, sqlx::Error> {
+    sqlx::query_as::<_, ChannelMuteRow>(
+        r#"
+        SELECT channel_id, muted_until, created_at FROM channel_mutes
+        WHERE user_id = ? AND (muted_until IS NULL OR muted_until > ?)
+        "#,
+    )
+    .bind(user_id.to_string())
+    .bind(sql_timestamp(Utc::now()))
+    .fetch_all(pool)
+    .await
+}
@@ -5,8 +5,9 @@ use nexus_common::models::channel::Channel;
 use uuid::Uuid;
 
 /// Create a new channel.
-pub async fn create_channel(
-    pool: &sqlx::AnyPool,
+#[tracing::instrument(skip_all)]
+pub async fn create_channel<'e, E>(
+    executor: E,
     id: Uuid,
     server_id: Option<Uuid>,
     parent_id: Option<Uuid>,
@@ -14,7 +15,10 @@ pub async fn create_channel(
     name: Option<&str>,
     topic: Option<&str>,
     position: i32,
-) -> Result<Channel, sqlx::Error> {
+) -> Result<Channel, sqlx::Error>
+where
+    E: sqlx::Executor<'e, Database = sqlx::Any>,
+{
     sqlx::query_as::<_, Channel>(
         r#"
         INSERT INTO channels (
@@ -33,24 +37,48 @@ pub async fn create_channel(
     .bind(name)
     .bind(topic)
     .bind(position)
-    .fetch_one(pool)
+    .fetch_one(executor)
     .await
 }
 
-/// List channels in a server.
+/// List channels in a server. Voice channels' text chats are left out
+/// (see [`crate::repository::voice_chat`]).
+#[tracing::instrument(skip_all)]
 pub async fn list_server_channels(
     pool: &sqlx::AnyPool,
     server_id: Uuid,
 ) -> Result<Vec<Channel>, sqlx::Error> {
     sqlx::query_as::<_, Channel>(
-        "SELECT * FROM channels WHERE server_id = ? ORDER BY position, created_at",
+        "SELECT * FROM channels WHERE server_id = ? AND voice_channel_id IS NULL ORDER BY position, created_at",
     )
     .bind(server_id.to_string())
     .fetch_all(pool)
     .await
 }
 
+/// Every channel in several servers in one query, voice channels' text
+/// chats included, ordered by server and then as [`list_server_channels`].
+#[tracing::instrument(skip_all)]
+pub async fn list_channels_for_servers(
+    pool: &sqlx::AnyPool,
+    server_ids: &[Uuid],
+) -> Result<Vec<Channel>, sqlx::Error> {
+    if server_ids.is_empty() {
+        return Ok(Vec::new());
+    }
+    let sql = format!(
+        "SELECT * FROM channels WHERE server_id IN ({}) ORDER BY server_id, position, created_at",
+        super::placeholders(server_ids.len())
+    );
+    let mut query = sqlx::query_as::<_, Channel>(&sql);
+    for id in server_ids {
+        query = query.bind(id.to_string());
+    }
+    query.fetch_all(pool).await
+}
+
 /// Find a channel by ID.
+#[tracing::instrument(skip_all)]
 pub async fn find_by_id(pool: &sqlx::AnyPool, id: Uuid) -> Result<Option<Channel>, sqlx::Error> {
     sqlx::query_as::<_, Channel>("SELECT * FROM channels WHERE id = ?")
         .bind(id.to_string())
@@ -59,6 +87,7 @@ pub async fn find_by_id(pool: &sqlx::AnyPool, id: Uuid) -> Result<Option<Channel
 }
 
 /// Update a channel.
+#[tracing::instrument(skip_all)]
 pub async fn update_channel(
     pool: &sqlx::AnyPool,
     id: Uuid,
@@ -91,7 +120,20 @@ pub async fn update_channel(
     .await
 }
 
+/// Archive or unarchive a channel.
+#[tracing::instrument(skip_all)]
+pub async fn set_archived(pool: &sqlx::AnyPool, id: Uuid, archived: bool) -> Result<Channel, sqlx::Error> {
+    sqlx::query_as::<_, Channel>(
+        "UPDATE channels SET archived = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ? RETURNING *",
+    )
+    .bind(archived)
+    .bind(id.to_string())
+    .fetch_one(pool)
+    .await
+}
+
 /// Delete a channel.
+#[tracing::instrument(skip_all)]
 pub async fn delete_channel(pool: &sqlx::AnyPool, id: Uuid) -> Result<(), sqlx::Error> {
     sqlx::query("DELETE FROM channels WHERE id = ?")
         .bind(id.to_string())
@@ -101,6 +143,7 @@ pub async fn delete_channel(pool: &sqlx::AnyPool, id: Uuid) -> Result<(), sqlx::
 }
 
 /// Create a DM channel between two users.
+#[tracing::instrument(skip_all)]
 pub async fn find_or_create_dm(
     pool: &sqlx::AnyPool,
     id: Uuid,
@@ -140,3 +183,49 @@ pub async fn find_or_create_dm(
 
     Ok(channel)
 }
+
+/// Whether `user_id` is a participant in the DM or group DM `channel_id`.
+#[tracing::instrument(skip_all)]
+pub async fn is_dm_participant(
+    pool: &sqlx::AnyPool,
+    channel_id: Uuid,
+    user_id: Uuid,
+) -> Result<bool, sqlx::Error> {
+    let row = sqlx::query("SELECT 1 FROM dm_participants WHERE channel_id = ? AND user_id = ?")
+        .bind(channel_id.to_string())
+        .bind(user_id.to_string())
+        .fetch_optional(pool)
+        .await?;
+    Ok(row.is_some())
+}
+
+/// Every participant of a DM or group DM.
+#[tracing::instrument(skip_all)]
+pub async fn list_dm_participants(
+    pool: &sqlx::AnyPool,
+    channel_id: Uuid,
+) -> Result<Vec<Uuid>, sqlx::Error> {
+    let rows = sqlx::query("SELECT user_id FROM dm_participants WHERE channel_id = ?")
+        .bind(channel_id.to_string())
+        .fetch_all(pool)
+        .await?;
+    rows.iter()
+        .map(|row| crate::any_compat::get_uuid(row, "user_id"))
+        .collect()
+}
+
+/// The DMs and group DMs `user_id` takes part in, most recently active first.
+#[tracing::instrument(skip_all)]
+pub async fn list_dm_channels(pool: &sqlx::AnyPool, user_id: Uuid) -> Result<Vec<Channel>, sqlx::Error> {
+    sqlx::query_as::<_, Channel>(
+        r#"
+        SELECT c.* FROM channels c
+        INNER JOIN dm_participants dp ON dp.channel_id = c.id
+        WHERE dp.user_id = ? AND c.channel_type IN ('dm', 'group_dm')
+        ORDER BY c.updated_at DESC
+        "#,
+    )
+    .bind(user_id.to_string())
+    .fetch_all(pool)
+    .await
+}
@@ -0,0 +1,210 @@
+//! Data export repository — users' requests for an archive of their data.
+//!
+//! An export is queued as `pending`, claimed by the data export job
+//! (`processing`), and ends up `completed` with its archive in storage, or
+//! `failed`. A completed archive is `downloaded` once, or `expired`; either
+//! way it is deleted from storage.
+
+use chrono::{DateTime, Utc};
+use sqlx::Row;
+use uuid::Uuid;
+
+#[derive(Debug, Clone)]
+pub struct DataExport {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub status: String,
+    /// Percent done while `processing`.
+    pub progress: i32,
+    pub storage_key: Option<String>,
+    pub size_bytes: Option<i64>,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub downloaded_at: Option<DateTime<Utc>>,
+}
+
+impl<'r> sqlx::FromRow<'r, sqlx::any::AnyRow> for DataExport {
+    fn from_row(row: &'r sqlx::any::AnyRow) -> Result<Self, sqlx::Error> {
+        use crate::any_compat::*;
+        Ok(DataExport {
+            id: get_uuid(row, "id")?,
+            user_id: get_uuid(row, "user_id")?,
+            status: row.try_get("status")?,
+            progress: row.try_get("progress")?,
+            storage_key: row.try_get("storage_key")?,
+            size_bytes: row.try_get("size_bytes")?,
+            last_error: row.try_get("last_error")?,
+            created_at: get_datetime(row, "created_at")?,
+            completed_at: get_opt_datetime(row, "completed_at")?,
+            expires_at: get_opt_datetime(row, "expires_at")?,
+            downloaded_at: get_opt_datetime(row, "downloaded_at")?,
+        })
+    }
+}
+
+fn sql_timestamp(ts: DateTime<Utc>) -> String {
+    ts.format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+/// Queue an export for `user_id`.
+#[tracing::instrument(skip_all)]
+pub async fn create(pool: &sqlx::AnyPool, id: Uuid, user_id: Uuid) -> Result<DataExport, sqlx::Error> {
+    sqlx::query_as::<_, DataExport>(
+        "INSERT INTO data_exports (id, user_id, status, created_at) VALUES (?, ?, 'pending', CURRENT_TIMESTAMP) RETURNING *",
+    )
+    .bind(id.to_string())
+    .bind(user_id.to_string())
+    .fetch_one(pool)
+    .await
+}
+
+#[tracing::instrument(skip_all)]
+pub async fn find_by_id(pool: &sqlx::AnyPool, id: Uuid) -> Result<Option<DataExport>, sqlx::Error> {
+    sqlx::query_as::<_, DataExport>("SELECT * FROM data_exports WHERE id = ?")
+        .bind(id.to_string())
+        .fetch_optional(pool)
+        .await
+}
+
+/// The user's most recent export.
+#[tracing::instrument(skip_all)]
+pub async fn latest_for_user(pool: &sqlx::AnyPool, user_id: Uuid) -> Result<Option<DataExport>, sqlx::Error> {
+    sqlx::query_as::<_, DataExport>("SELECT * FROM data_exports WHERE user_id = ? ORDER BY created_at DESC LIMIT 1")
+        .bind(user_id.to_string())
+        .fetch_optional(pool)
+        .await
+}
+
+/// Claim up to `limit` pending exports, oldest first. Rows are flipped to
+/// `processing` with a guarded update, so two workers never both win one.
+#[tracing::instrument(skip_all)]
+pub async fn claim_pending(pool: &sqlx::AnyPool, limit: i64) -> Result<Vec<DataExport>, sqlx::Error> {
+    let candidates = sqlx::query_as::<_, DataExport>(
+        "SELECT * FROM data_exports WHERE status = 'pending' ORDER BY created_at LIMIT ?",
+    )
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    let mut claimed = Vec::with_capacity(candidates.len());
+    for mut row in candidates {
+        let result = sqlx::query("UPDATE data_exports SET status = 'processing' WHERE id = ? AND status = 'pending'")
+            .bind(row.id.to_string())
+            .execute(pool)
+            .await?;
+        if result.rows_affected() == 1 {
+            row.status = "processing".into();
+            claimed.push(row);
+        }
+    }
+    Ok(claimed)
+}
+
+#[tracing::instrument(skip_all)]
+pub async fn set_progress(pool: &sqlx::AnyPool, id: Uuid, progress: i32) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE data_exports SET progress = ? WHERE id = ?")
+        .bind(progress)
+        .bind(id.to_string())
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Record the finished archive, downloadable until `expires_at`. Returns
+/// `false` if the export was deleted meanwhile, with its account.
+#[tracing::instrument(skip_all)]
+pub async fn complete(
+    pool: &sqlx::AnyPool,
+    id: Uuid,
+    storage_key: &str,
+    size_bytes: i64,
+    expires_at: DateTime<Utc>,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        UPDATE data_exports
+        SET status = 'completed', progress = 100, storage_key = ?, size_bytes = ?,
+            last_error = NULL, completed_at = CURRENT_TIMESTAMP, expires_at = ?
+        WHERE id = ?
+        "#,
+    )
+    .bind(storage_key)
+    .bind(size_bytes)
+    .bind(sql_timestamp(expires_at))
+    .bind(id.to_string())
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() == 1)
+}
+
+#[tracing::instrument(skip_all)]
+pub async fn fail(pool: &sqlx::AnyPool, id: Uuid, error: &str) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE data_exports SET status = 'failed', last_error = ? WHERE id = ?")
+        .bind(error)
+        .bind(id.to_string())
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Mark a completed, unexpired export downloaded. Returns whether this call
+/// did it; only one download of an archive may succeed.
+#[tracing::instrument(skip_all)]
+pub async fn claim_download(pool: &sqlx::AnyPool, id: Uuid) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        UPDATE data_exports
+        SET status = 'downloaded', downloaded_at = CURRENT_TIMESTAMP
+        WHERE id = ? AND status = 'completed' AND expires_at > ?
+        "#,
+    )
+    .bind(id.to_string())
+    .bind(sql_timestamp(Utc::now()))
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() == 1)
+}
+
+/// Mark completed exports past their expiry `expired`, returning them so
+/// their archives can be deleted.
+#[tracing::instrument(skip_all)]
+pub async fn expire(pool: &sqlx::AnyPool) -> Result<Vec<DataExport>, sqlx::Error> {
+    sqlx::query_as::<_, DataExport>(
+        "UPDATE data_exports SET status = 'expired' WHERE status = 'completed' AND expires_at <= ? RETURNING *",
+    )
+    .bind(sql_timestamp(Utc::now()))
+    .fetch_all(pool)
+    .await
+}
+
+/// Put rows left in `processing` by a crashed worker back in the queue.
+#[tracing::instrument(skip_all)]
+pub async fn requeue_stale(pool: &sqlx::AnyPool) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query("UPDATE data_exports SET status = 'pending', progress = 0 WHERE status = 'processing'")
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected())
+}
+
+/// Delete every export of a user, returning the storage keys of archives
+/// still in storage.
+#[tracing::instrument(skip_all)]
+pub async fn delete_for_user<'e, E>(executor: E, user_id: Uuid) -> Result<Vec<String>, sqlx::Error>
+where
+    E: sqlx::Executor<'e, Database = sqlx::Any>,
+{
+    let rows = sqlx::query("DELETE FROM data_exports WHERE user_id = ? RETURNING status, storage_key")
+        .bind(user_id.to_string())
+        .fetch_all(executor)
+        .await?;
+    let mut keys = Vec::new();
+    for row in &rows {
+        if row.try_get::<String, _>("status")? != "completed" {
+            continue;
+        }
+        keys.extend(row.try_get::<Option<String>, _>("storage_key")?);
+    }
+    Ok(keys)
+}
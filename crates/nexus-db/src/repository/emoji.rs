@@ -16,6 +16,7 @@ struct CountRow { count: i64 }
 // ============================================================
 
 /// Insert a new custom emoji for a server.
+#[tracing::instrument(skip_all)]
 pub async fn create_emoji(
     pool: &sqlx::AnyPool,
     id: Uuid,
@@ -53,6 +54,7 @@ pub async fn create_emoji(
 // ============================================================
 
 /// Get all emoji for a server.
+#[tracing::instrument(skip_all)]
 pub async fn list_for_server(
     pool: &sqlx::AnyPool,
     server_id: Uuid,
@@ -66,6 +68,7 @@ pub async fn list_for_server(
 }
 
 /// Get a single emoji by ID.
+#[tracing::instrument(skip_all)]
 pub async fn find_by_id(
     pool: &sqlx::AnyPool,
     id: Uuid,
@@ -77,6 +80,7 @@ pub async fn find_by_id(
 }
 
 /// Get a single emoji by server + name.
+#[tracing::instrument(skip_all)]
 pub async fn find_by_name(
     pool: &sqlx::AnyPool,
     server_id: Uuid,
@@ -96,6 +100,7 @@ pub async fn find_by_name(
 // ============================================================
 
 /// Rename an emoji.
+#[tracing::instrument(skip_all)]
 pub async fn update_emoji(
     pool: &sqlx::AnyPool,
     id: Uuid,
@@ -118,6 +123,7 @@ pub async fn update_emoji(
 }
 
 /// Set an emoji's public URL after upload.
+#[tracing::instrument(skip_all)]
 pub async fn set_url(pool: &sqlx::AnyPool, id: Uuid, url: &str) -> Result<(), sqlx::Error> {
     sqlx::query("UPDATE server_emoji SET url = ? WHERE id = ?")
         .bind(id.to_string())
@@ -132,6 +138,7 @@ pub async fn set_url(pool: &sqlx::AnyPool, id: Uuid, url: &str) -> Result<(), sq
 // ============================================================
 
 /// Delete an emoji. Returns the storage_key so the caller can clean up storage.
+#[tracing::instrument(skip_all)]
 pub async fn delete_emoji(
     pool: &sqlx::AnyPool,
     id: Uuid,
@@ -148,6 +155,7 @@ pub async fn delete_emoji(
 }
 
 /// Count emoji for a server (for limit enforcement).
+#[tracing::instrument(skip_all)]
 pub async fn count_for_server(pool: &sqlx::AnyPool, server_id: Uuid) -> Result<i64, sqlx::Error> {
     let row = sqlx::query_as::<_, CountRow>(
         "SELECT COUNT(*) AS count FROM server_emoji WHERE server_id = ?",
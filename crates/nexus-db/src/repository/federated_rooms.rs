@@ -0,0 +1,308 @@
+//! Federated rooms repository — local channels promoted to federated rooms,
+//! and the events stored for them.
+//!
+//! A room owned by this server has `local_channel_id` set. Defederating it
+//! sets `defederated_at` and keeps everything else, so promoting the channel
+//! again resumes the same room.
+
+use chrono::{DateTime, Utc};
+use sqlx::Row;
+use uuid::Uuid;
+
+#[derive(Debug, Clone)]
+pub struct FederatedRoomRow {
+    pub id: Uuid,
+    pub room_id: String,
+    pub local_channel_id: Option<Uuid>,
+    pub origin_server: String,
+    pub room_name: Option<String>,
+    pub room_topic: Option<String>,
+    pub join_rule: String,
+    pub member_count: i32,
+    pub defederated_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl<'r> sqlx::FromRow<'r, sqlx::any::AnyRow> for FederatedRoomRow {
+    fn from_row(row: &'r sqlx::any::AnyRow) -> Result<Self, sqlx::Error> {
+        use crate::any_compat::*;
+        Ok(FederatedRoomRow {
+            id: get_uuid(row, "id")?,
+            room_id: row.try_get("room_id")?,
+            local_channel_id: get_opt_uuid(row, "local_channel_id")?,
+            origin_server: row.try_get("origin_server")?,
+            room_name: row.try_get("room_name")?,
+            room_topic: row.try_get("room_topic")?,
+            join_rule: row.try_get("join_rule")?,
+            member_count: row.try_get("member_count")?,
+            defederated_at: get_opt_datetime(row, "defederated_at")?,
+            created_at: get_datetime(row, "created_at")?,
+            updated_at: get_datetime(row, "updated_at")?,
+        })
+    }
+}
+
+impl FederatedRoomRow {
+    /// Whether events are currently exchanged for this room.
+    pub fn is_federated(&self) -> bool {
+        self.defederated_at.is_none()
+    }
+}
+
+/// Columns read into [`FederatedRoomRow`] (`participating_servers` is a
+/// Postgres array, which the Any driver can't decode).
+const ROOM_COLUMNS: &str = "id, room_id, local_channel_id, origin_server, room_name, room_topic, \
+                            join_rule, member_count, defederated_at, created_at, updated_at";
+
+fn sql_timestamp(ts: DateTime<Utc>) -> String {
+    ts.format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+/// The room a local channel was promoted to, federated or not.
+#[tracing::instrument(skip_all)]
+pub async fn find_by_channel(
+    pool: &sqlx::AnyPool,
+    channel_id: Uuid,
+) -> Result<Option<FederatedRoomRow>, sqlx::Error> {
+    sqlx::query_as::<_, FederatedRoomRow>(&format!(
+        "SELECT {ROOM_COLUMNS} FROM federated_rooms WHERE local_channel_id = ?"
+    ))
+    .bind(channel_id.to_string())
+    .fetch_optional(pool)
+    .await
+}
+
+#[tracing::instrument(skip_all)]
+pub async fn find_by_room_id(
+    pool: &sqlx::AnyPool,
+    room_id: &str,
+) -> Result<Option<FederatedRoomRow>, sqlx::Error> {
+    sqlx::query_as::<_, FederatedRoomRow>(&format!(
+        "SELECT {ROOM_COLUMNS} FROM federated_rooms WHERE room_id = ?"
+    ))
+    .bind(room_id)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Create the room for a local channel, or federate it again with fresh
+/// name, topic and join rule if it was defederated.
+#[allow(clippy::too_many_arguments)]
+#[tracing::instrument(skip_all)]
+pub async fn promote(
+    pool: &sqlx::AnyPool,
+    id: Uuid,
+    room_id: &str,
+    channel_id: Uuid,
+    origin_server: &str,
+    room_name: &str,
+    room_topic: Option<&str>,
+    join_rule: &str,
+) -> Result<FederatedRoomRow, sqlx::Error> {
+    sqlx::query_as::<_, FederatedRoomRow>(&format!(
+        r#"
+        INSERT INTO federated_rooms (
+            id, room_id, local_channel_id, origin_server, room_name, room_topic,
+            join_rule, member_count, created_at, updated_at
+        )
+        VALUES (?, ?, ?, ?, ?, ?, ?, 0, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP)
+        ON CONFLICT (room_id) DO UPDATE SET
+            local_channel_id = excluded.local_channel_id,
+            room_name = excluded.room_name,
+            room_topic = excluded.room_topic,
+            join_rule = excluded.join_rule,
+            defederated_at = NULL,
+            updated_at = CURRENT_TIMESTAMP
+        RETURNING {ROOM_COLUMNS}
+        "#
+    ))
+    .bind(id.to_string())
+    .bind(room_id)
+    .bind(channel_id.to_string())
+    .bind(origin_server)
+    .bind(room_name)
+    .bind(room_topic)
+    .bind(join_rule)
+    .fetch_one(pool)
+    .await
+}
+
+/// Stop federating a channel's room. `None` if it has no federated room.
+#[tracing::instrument(skip_all)]
+pub async fn defederate(
+    pool: &sqlx::AnyPool,
+    channel_id: Uuid,
+    now: DateTime<Utc>,
+) -> Result<Option<FederatedRoomRow>, sqlx::Error> {
+    sqlx::query_as::<_, FederatedRoomRow>(&format!(
+        r#"
+        UPDATE federated_rooms SET defederated_at = ?, updated_at = CURRENT_TIMESTAMP
+        WHERE local_channel_id = ? AND defederated_at IS NULL
+        RETURNING {ROOM_COLUMNS}
+        "#
+    ))
+    .bind(sql_timestamp(now))
+    .bind(channel_id.to_string())
+    .fetch_optional(pool)
+    .await
+}
+
+/// Store an event created on this server for `room_id`.
+#[allow(clippy::too_many_arguments)]
+#[tracing::instrument(skip_all)]
+pub async fn insert_event(
+    pool: &sqlx::AnyPool,
+    event_id: &str,
+    room_id: &str,
+    event_type: &str,
+    sender: &str,
+    origin_server: &str,
+    origin_server_ts: i64,
+    content: &serde_json::Value,
+    signatures: &serde_json::Value,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO federated_events (
+            id, event_id, room_id, event_type, sender, origin_server,
+            origin_server_ts, content, signatures
+        )
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(Uuid::new_v4().to_string())
+    .bind(event_id)
+    .bind(room_id)
+    .bind(event_type)
+    .bind(sender)
+    .bind(origin_server)
+    .bind(origin_server_ts)
+    .bind(content.to_string())
+    .bind(signatures.to_string())
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Store an event `origin_server` sent for `room_id`, unless it is already
+/// stored. Returns whether it was new.
+#[allow(clippy::too_many_arguments)]
+#[tracing::instrument(skip_all)]
+pub async fn insert_remote_event<'e, E>(
+    executor: E,
+    event_id: &str,
+    room_id: &str,
+    event_type: &str,
+    sender: &str,
+    origin_server: &str,
+    origin_server_ts: i64,
+    content: &serde_json::Value,
+    signatures: &serde_json::Value,
+    txn_id: &str,
+) -> Result<bool, sqlx::Error>
+where
+    E: sqlx::Executor<'e, Database = sqlx::Any>,
+{
+    let result = sqlx::query(
+        r#"
+        INSERT INTO federated_events (
+            id, event_id, room_id, event_type, sender, origin_server,
+            origin_server_ts, content, signatures, txn_id
+        )
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        ON CONFLICT (event_id) DO NOTHING
+        "#,
+    )
+    .bind(Uuid::new_v4().to_string())
+    .bind(event_id)
+    .bind(room_id)
+    .bind(event_type)
+    .bind(sender)
+    .bind(origin_server)
+    .bind(origin_server_ts)
+    .bind(content.to_string())
+    .bind(signatures.to_string())
+    .bind(txn_id)
+    .execute(executor)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Count a member joining `room_id` from `origin_server`, creating the room
+/// with `room_name` if this is the first we hear of it.
+#[tracing::instrument(skip_all)]
+pub async fn record_remote_join<'e, E>(
+    executor: E,
+    room_id: &str,
+    origin_server: &str,
+    room_name: &str,
+) -> Result<(), sqlx::Error>
+where
+    E: sqlx::Executor<'e, Database = sqlx::Any>,
+{
+    sqlx::query(
+        r#"
+        INSERT INTO federated_rooms (
+            id, room_id, origin_server, room_name, join_rule, member_count,
+            created_at, updated_at
+        )
+        VALUES (?, ?, ?, ?, 'public', 1, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP)
+        ON CONFLICT (room_id) DO UPDATE SET
+            member_count = federated_rooms.member_count + 1,
+            updated_at = CURRENT_TIMESTAMP
+        "#,
+    )
+    .bind(Uuid::new_v4().to_string())
+    .bind(room_id)
+    .bind(origin_server)
+    .bind(room_name)
+    .execute(executor)
+    .await?;
+    Ok(())
+}
+
+/// Remote servers with a member in `room_id` — everyone a new event must be
+/// sent to.
+#[tracing::instrument(skip_all)]
+pub async fn remote_servers(
+    pool: &sqlx::AnyPool,
+    room_id: &str,
+    local_server: &str,
+) -> Result<Vec<String>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT DISTINCT origin_server FROM federated_events
+        WHERE room_id = ? AND event_type = 'nexus.member.join' AND origin_server <> ?
+        "#,
+    )
+    .bind(room_id)
+    .bind(local_server)
+    .fetch_all(pool)
+    .await?;
+    rows.iter().map(|r| r.try_get("origin_server")).collect()
+}
+
+/// Remote servers with a member in any federated room whose channel is in a
+/// server `user_id` belongs to — everyone who may have seen the user.
+#[tracing::instrument(skip_all)]
+pub async fn remote_servers_for_user(
+    pool: &sqlx::AnyPool,
+    user_id: Uuid,
+    local_server: &str,
+) -> Result<Vec<String>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT DISTINCT e.origin_server FROM federated_events e
+        JOIN federated_rooms r ON r.room_id = e.room_id
+        JOIN channels c ON c.id = r.local_channel_id
+        JOIN members m ON m.server_id = c.server_id
+        WHERE m.user_id = ? AND e.event_type = 'nexus.member.join' AND e.origin_server <> ?
+        "#,
+    )
+    .bind(user_id.to_string())
+    .bind(local_server)
+    .fetch_all(pool)
+    .await?;
+    rows.iter().map(|r| r.try_get("origin_server")).collect()
+}
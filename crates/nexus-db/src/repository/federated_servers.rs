@@ -0,0 +1,65 @@
+//! Federated servers repository — the inbound rate limit state of remote
+//! servers.
+//!
+//! Rows are created and their keys cached by the federation routes; this
+//! module only covers strikes and throttling (see
+//! `nexus_api::federation_limits`).
+
+use chrono::{DateTime, Utc};
+
+fn sql_timestamp(ts: DateTime<Utc>) -> String {
+    ts.format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+/// When `server_name`'s throttle ends, if it is throttled as of now.
+#[tracing::instrument(skip_all)]
+pub async fn throttled_until(
+    pool: &sqlx::AnyPool,
+    server_name: &str,
+) -> Result<Option<DateTime<Utc>>, sqlx::Error> {
+    let row = sqlx::query(
+        "SELECT throttled_until FROM federated_servers WHERE server_name = ? AND throttled_until > ?",
+    )
+    .bind(server_name)
+    .bind(sql_timestamp(Utc::now()))
+    .fetch_optional(pool)
+    .await?;
+    match row {
+        Some(row) => crate::any_compat::get_opt_datetime(&row, "throttled_until"),
+        None => Ok(None),
+    }
+}
+
+/// Count a rate-limited transaction against `server_name`. On reaching
+/// `max_strikes` the server is throttled until `throttle_until` and its
+/// strikes start over; returns whether that happened.
+#[tracing::instrument(skip_all)]
+pub async fn record_rate_limit_strike(
+    pool: &sqlx::AnyPool,
+    server_name: &str,
+    max_strikes: i32,
+    throttle_until: DateTime<Utc>,
+) -> Result<bool, sqlx::Error> {
+    let strikes: Option<i32> = sqlx::query_scalar(
+        r#"
+        UPDATE federated_servers SET rate_limit_strikes = rate_limit_strikes + 1
+        WHERE server_name = ?
+        RETURNING rate_limit_strikes
+        "#,
+    )
+    .bind(server_name)
+    .fetch_optional(pool)
+    .await?;
+    if strikes.is_none_or(|s| s < max_strikes) {
+        return Ok(false);
+    }
+
+    sqlx::query(
+        "UPDATE federated_servers SET rate_limit_strikes = 0, throttled_until = ? WHERE server_name = ?",
+    )
+    .bind(sql_timestamp(throttle_until))
+    .bind(server_name)
+    .execute(pool)
+    .await?;
+    Ok(true)
+}
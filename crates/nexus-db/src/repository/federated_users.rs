@@ -0,0 +1,147 @@
+//! Federated users repository — cached profiles of remote users, and the
+//! schedule on which they are re-fetched from their home servers.
+//!
+//! Rows are created by inbound PDUs (see the federation routes); this module
+//! only covers the profile refresh side.
+
+use chrono::{DateTime, Utc};
+use sqlx::Row;
+use uuid::Uuid;
+
+#[derive(Debug, Clone)]
+pub struct FederatedUserRow {
+    pub id: Uuid,
+    /// `@localpart:server.tld`
+    pub mxid: String,
+    pub display_name: Option<String>,
+    pub avatar_url: Option<String>,
+    pub last_active_at: DateTime<Utc>,
+    pub next_refresh_at: Option<DateTime<Utc>>,
+    pub refresh_failures: i32,
+}
+
+impl<'r> sqlx::FromRow<'r, sqlx::any::AnyRow> for FederatedUserRow {
+    fn from_row(row: &'r sqlx::any::AnyRow) -> Result<Self, sqlx::Error> {
+        use crate::any_compat::*;
+        Ok(FederatedUserRow {
+            id: get_uuid(row, "id")?,
+            mxid: row.try_get("mxid")?,
+            display_name: row.try_get("display_name")?,
+            avatar_url: row.try_get("avatar_url")?,
+            last_active_at: get_datetime(row, "last_active_at")?,
+            next_refresh_at: get_opt_datetime(row, "next_refresh_at")?,
+            refresh_failures: row.try_get("refresh_failures")?,
+        })
+    }
+}
+
+fn sql_timestamp(ts: DateTime<Utc>) -> String {
+    ts.format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+/// Users active since `active_since` whose profile refresh is due, most
+/// recently active first.
+#[tracing::instrument(skip_all)]
+pub async fn list_due_for_refresh(
+    pool: &sqlx::AnyPool,
+    active_since: DateTime<Utc>,
+    limit: i64,
+) -> Result<Vec<FederatedUserRow>, sqlx::Error> {
+    sqlx::query_as::<_, FederatedUserRow>(
+        r#"
+        SELECT id, mxid, display_name, avatar_url, last_active_at, next_refresh_at, refresh_failures
+        FROM federated_users
+        WHERE last_active_at >= ? AND deleted_at IS NULL
+          AND (next_refresh_at IS NULL OR next_refresh_at <= ?)
+        ORDER BY last_active_at DESC
+        LIMIT ?
+        "#,
+    )
+    .bind(sql_timestamp(active_since))
+    .bind(sql_timestamp(Utc::now()))
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+}
+
+/// Store a freshly fetched profile and schedule the next refresh.
+#[tracing::instrument(skip_all)]
+pub async fn record_refresh(
+    pool: &sqlx::AnyPool,
+    id: Uuid,
+    display_name: Option<&str>,
+    avatar_url: Option<&str>,
+    next_refresh_at: DateTime<Utc>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        UPDATE federated_users
+        SET display_name = ?, avatar_url = ?, next_refresh_at = ?, refresh_failures = 0,
+            updated_at = CURRENT_TIMESTAMP
+        WHERE id = ?
+        "#,
+    )
+    .bind(display_name)
+    .bind(avatar_url)
+    .bind(sql_timestamp(next_refresh_at))
+    .bind(id.to_string())
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Record a failed fetch and schedule the retry.
+#[tracing::instrument(skip_all)]
+pub async fn record_refresh_failure(
+    pool: &sqlx::AnyPool,
+    id: Uuid,
+    next_refresh_at: DateTime<Utc>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "UPDATE federated_users SET refresh_failures = refresh_failures + 1, next_refresh_at = ? WHERE id = ?",
+    )
+    .bind(sql_timestamp(next_refresh_at))
+    .bind(id.to_string())
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Forget the profile of a user their home server says was deleted. The
+/// row stays for the user's events; it is no longer refreshed.
+#[tracing::instrument(skip_all)]
+pub async fn tombstone(pool: &sqlx::AnyPool, mxid: &str) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        UPDATE federated_users
+        SET display_name = NULL, avatar_url = NULL, next_refresh_at = NULL,
+            deleted_at = CURRENT_TIMESTAMP, updated_at = CURRENT_TIMESTAMP
+        WHERE mxid = ? AND deleted_at IS NULL
+        "#,
+    )
+    .bind(mxid)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Local servers with a federated channel the user has posted in — where
+/// their profile is shown.
+#[tracing::instrument(skip_all)]
+pub async fn list_local_servers(pool: &sqlx::AnyPool, mxid: &str) -> Result<Vec<Uuid>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT DISTINCT c.server_id
+        FROM federated_events e
+        JOIN federated_rooms r ON r.room_id = e.room_id
+        JOIN channels c ON c.id = r.local_channel_id
+        WHERE e.sender = ? AND c.server_id IS NOT NULL
+        "#,
+    )
+    .bind(mxid)
+    .fetch_all(pool)
+    .await?;
+    rows.iter()
+        .map(|row| crate::any_compat::get_uuid(row, "server_id"))
+        .collect()
+}
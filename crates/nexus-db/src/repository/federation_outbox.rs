@@ -0,0 +1,142 @@
+//! Federation outbox repository — outbound server-to-server deliveries
+//! awaiting (re)delivery.
+//!
+//! `destination` is whatever the sending bridge needs to address the remote
+//! (a server name for Nexus S2S, an inbox URL for ActivityPub); `txn_id` is
+//! the transaction / activity ID used for de-duplication on the remote side.
+
+use chrono::{DateTime, Utc};
+use sqlx::Row;
+use uuid::Uuid;
+
+#[derive(Debug, Clone)]
+pub struct OutboxRow {
+    pub id: Uuid,
+    pub destination: String,
+    pub txn_id: String,
+    pub payload: serde_json::Value,
+    pub attempts: i32,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub delivered_at: Option<DateTime<Utc>>,
+    pub next_attempt_at: DateTime<Utc>,
+}
+
+impl<'r> sqlx::FromRow<'r, sqlx::any::AnyRow> for OutboxRow {
+    fn from_row(row: &'r sqlx::any::AnyRow) -> Result<Self, sqlx::Error> {
+        use crate::any_compat::*;
+        Ok(OutboxRow {
+            id: get_uuid(row, "id")?,
+            destination: row.try_get("destination")?,
+            txn_id: row.try_get("txn_id")?,
+            payload: get_json_value(row, "payload")?,
+            attempts: row.try_get("attempts")?,
+            last_error: row.try_get("last_error")?,
+            created_at: get_datetime(row, "created_at")?,
+            delivered_at: get_opt_datetime(row, "delivered_at")?,
+            next_attempt_at: get_datetime(row, "next_attempt_at")?,
+        })
+    }
+}
+
+fn sql_timestamp(ts: DateTime<Utc>) -> String {
+    ts.format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+/// Queue a delivery for immediate sending.
+#[tracing::instrument(skip_all)]
+pub async fn enqueue(
+    pool: &sqlx::AnyPool,
+    id: Uuid,
+    destination: &str,
+    txn_id: &str,
+    payload: &serde_json::Value,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO federation_outbox (id, destination, txn_id, payload, next_attempt_at)
+        VALUES (?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(id.to_string())
+    .bind(destination)
+    .bind(txn_id)
+    .bind(payload.to_string())
+    .bind(sql_timestamp(Utc::now()))
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Fetch undelivered rows that are due, oldest first.
+#[tracing::instrument(skip_all)]
+pub async fn list_due(pool: &sqlx::AnyPool, limit: i64) -> Result<Vec<OutboxRow>, sqlx::Error> {
+    sqlx::query_as::<_, OutboxRow>(
+        r#"
+        SELECT * FROM federation_outbox
+        WHERE delivered_at IS NULL AND next_attempt_at <= ?
+        ORDER BY created_at
+        LIMIT ?
+        "#,
+    )
+    .bind(sql_timestamp(Utc::now()))
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+}
+
+/// Mark a row delivered.
+#[tracing::instrument(skip_all)]
+pub async fn mark_delivered(pool: &sqlx::AnyPool, id: Uuid) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "UPDATE federation_outbox SET delivered_at = CURRENT_TIMESTAMP, attempts = attempts + 1 WHERE id = ?",
+    )
+    .bind(id.to_string())
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Record a failed attempt and schedule the next one.
+#[tracing::instrument(skip_all)]
+pub async fn mark_failed(
+    pool: &sqlx::AnyPool,
+    id: Uuid,
+    error: &str,
+    next_attempt_at: DateTime<Utc>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        UPDATE federation_outbox
+        SET attempts = attempts + 1, last_error = ?, next_attempt_at = ?
+        WHERE id = ?
+        "#,
+    )
+    .bind(error)
+    .bind(sql_timestamp(next_attempt_at))
+    .bind(id.to_string())
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Drop a row that will never be delivered (e.g. out of attempts), so it
+/// stops counting towards the status page's federation lag.
+#[tracing::instrument(skip_all)]
+pub async fn discard(pool: &sqlx::AnyPool, id: Uuid) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM federation_outbox WHERE id = ?")
+        .bind(id.to_string())
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Delete delivered rows older than `before`. Returns the number removed.
+#[tracing::instrument(skip_all)]
+pub async fn prune_delivered(pool: &sqlx::AnyPool, before: DateTime<Utc>) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM federation_outbox WHERE delivered_at IS NOT NULL AND delivered_at < ?")
+        .bind(sql_timestamp(before))
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected())
+}
@@ -0,0 +1,46 @@
+//! Federation transaction log repository — IDs of inbound transactions
+//! already processed, kept so retries from remote servers are answered
+//! without re-applying their events.
+//!
+//! Lookups and inserts happen inline in the federation routes; this module
+//! covers pruning, which keeps the table bounded.
+
+use chrono::{DateTime, Utc};
+
+fn sql_timestamp(ts: DateTime<Utc>) -> String {
+    ts.format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+/// Remove transactions received before `received_before`, then the oldest
+/// beyond the newest `max_rows`. Returns the number of rows removed.
+#[tracing::instrument(skip_all)]
+pub async fn prune(
+    pool: &sqlx::AnyPool,
+    received_before: DateTime<Utc>,
+    max_rows: i64,
+) -> Result<u64, sqlx::Error> {
+    let expired = sqlx::query("DELETE FROM federation_txn_log WHERE received_at < ?")
+        .bind(sql_timestamp(received_before))
+        .execute(pool)
+        .await?
+        .rows_affected();
+
+    // No row at the offset means the table is within the cap, and the
+    // comparison against NULL deletes nothing.
+    let excess = sqlx::query(
+        r#"
+        DELETE FROM federation_txn_log
+        WHERE received_at <= (
+            SELECT received_at FROM federation_txn_log
+            ORDER BY received_at DESC
+            LIMIT 1 OFFSET ?
+        )
+        "#,
+    )
+    .bind(max_rows.max(0))
+    .execute(pool)
+    .await?
+    .rows_affected();
+
+    Ok(expired + excess)
+}
@@ -0,0 +1,168 @@
+//! Forum repository — tags and post listings for forum channels.
+//!
+//! Forum posts are ordinary threads (see [`super::threads`]) whose parent is
+//! a forum channel; this module adds the `forum_tags` table and the
+//! forum-specific views over `threads`.
+
+use nexus_common::models::rich::{ForumSortOrder, ForumTag, ThreadRow};
+use uuid::Uuid;
+
+// ============================================================
+// Tags
+// ============================================================
+
+/// Create a forum tag.
+#[tracing::instrument(skip_all)]
+pub async fn create_tag(
+    pool: &sqlx::AnyPool,
+    id: Uuid,
+    channel_id: Uuid,
+    name: &str,
+    emoji: Option<&str>,
+    moderated: bool,
+    position: i32,
+) -> Result<ForumTag, sqlx::Error> {
+    sqlx::query_as::<_, ForumTag>(
+        r#"
+        INSERT INTO forum_tags (id, channel_id, name, emoji, moderated, position, created_at)
+        VALUES (?, ?, ?, ?, ?, ?, CURRENT_TIMESTAMP)
+        RETURNING *
+        "#,
+    )
+    .bind(id.to_string())
+    .bind(channel_id.to_string())
+    .bind(name)
+    .bind(emoji)
+    .bind(moderated)
+    .bind(position)
+    .fetch_one(pool)
+    .await
+}
+
+/// List a forum's tags in display order.
+#[tracing::instrument(skip_all)]
+pub async fn list_tags(
+    pool: &sqlx::AnyPool,
+    channel_id: Uuid,
+) -> Result<Vec<ForumTag>, sqlx::Error> {
+    sqlx::query_as::<_, ForumTag>(
+        "SELECT * FROM forum_tags WHERE channel_id = ? ORDER BY position, created_at",
+    )
+    .bind(channel_id.to_string())
+    .fetch_all(pool)
+    .await
+}
+
+/// Update a forum tag. `None` leaves a field unchanged.
+#[tracing::instrument(skip_all)]
+pub async fn update_tag(
+    pool: &sqlx::AnyPool,
+    id: Uuid,
+    name: Option<&str>,
+    emoji: Option<&str>,
+    moderated: Option<bool>,
+    position: Option<i32>,
+) -> Result<Option<ForumTag>, sqlx::Error> {
+    sqlx::query_as::<_, ForumTag>(
+        r#"
+        UPDATE forum_tags SET
+            name = COALESCE(?, name),
+            emoji = COALESCE(?, emoji),
+            moderated = COALESCE(?, moderated),
+            position = COALESCE(?, position)
+        WHERE id = ?
+        RETURNING *
+        "#,
+    )
+    .bind(name)
+    .bind(emoji)
+    .bind(moderated)
+    .bind(position)
+    .bind(id.to_string())
+    .fetch_optional(pool)
+    .await
+}
+
+/// Delete a forum tag. Returns whether it existed.
+#[tracing::instrument(skip_all)]
+pub async fn delete_tag(pool: &sqlx::AnyPool, id: Uuid) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM forum_tags WHERE id = ?")
+        .bind(id.to_string())
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+// ============================================================
+// Posts
+// ============================================================
+
+/// List posts in a forum: pinned first, then by `sort`.
+///
+/// `tag` keeps only posts carrying that tag; archived posts are included
+/// only when `archived` is set.
+#[tracing::instrument(skip_all)]
+pub async fn list_posts(
+    pool: &sqlx::AnyPool,
+    forum_id: Uuid,
+    sort: ForumSortOrder,
+    tag: Option<Uuid>,
+    archived: bool,
+    limit: i64,
+) -> Result<Vec<ThreadRow>, sqlx::Error> {
+    let order = match sort {
+        ForumSortOrder::LatestActivity => "t.updated_at DESC",
+        ForumSortOrder::CreationDate => "t.created_at DESC",
+    };
+    let mut sql = String::from(
+        r#"
+        SELECT t.*, c.parent_id AS parent_channel_id
+        FROM threads t
+        JOIN channels c ON c.id = t.channel_id
+        WHERE c.parent_id = ?
+        "#,
+    );
+    if !archived {
+        sql.push_str(" AND t.archived = false");
+    }
+    if tag.is_some() {
+        // Tags are stored as a JSON array of IDs
+        sql.push_str(" AND t.tags LIKE ?");
+    }
+    sql.push_str(&format!(" ORDER BY t.pinned DESC, {order} LIMIT ?"));
+
+    let mut query = sqlx::query_as::<_, ThreadRow>(&sql).bind(forum_id.to_string());
+    if let Some(tag) = tag {
+        query = query.bind(format!("%\"{tag}\"%"));
+    }
+    query.bind(limit).fetch_all(pool).await
+}
+
+/// Pin or unpin a post. Pinning one post unpins any other in the forum.
+#[tracing::instrument(skip_all)]
+pub async fn set_pinned(
+    pool: &sqlx::AnyPool,
+    forum_id: Uuid,
+    post_id: Uuid,
+    pinned: bool,
+) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    if pinned {
+        sqlx::query(
+            r#"
+            UPDATE threads SET pinned = false
+            WHERE pinned = true
+              AND channel_id IN (SELECT id FROM channels WHERE parent_id = ?)
+            "#,
+        )
+        .bind(forum_id.to_string())
+        .execute(&mut *tx)
+        .await?;
+    }
+    sqlx::query("UPDATE threads SET pinned = ? WHERE channel_id = ?")
+        .bind(pinned)
+        .bind(post_id.to_string())
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await
+}
@@ -0,0 +1,52 @@
+//! Instance settings staff change at runtime, overriding the config file.
+//! Values are stored JSON-encoded; an absent key means "use the config".
+
+use uuid::Uuid;
+
+/// The stored value of `key`, if staff have set one.
+#[tracing::instrument(skip_all)]
+pub async fn get(pool: &sqlx::AnyPool, key: &str) -> Result<Option<serde_json::Value>, sqlx::Error> {
+    let value: Option<String> = sqlx::query_scalar("SELECT value FROM instance_settings WHERE key = ?")
+        .bind(key)
+        .fetch_optional(pool)
+        .await?;
+    value
+        .map(|v| serde_json::from_str(&v).map_err(|e| sqlx::Error::Decode(Box::new(e))))
+        .transpose()
+}
+
+/// Store `value` for `key`.
+#[tracing::instrument(skip_all)]
+pub async fn set(
+    pool: &sqlx::AnyPool,
+    key: &str,
+    value: &serde_json::Value,
+    updated_by: Uuid,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO instance_settings (key, value, updated_by, updated_at)
+        VALUES (?, ?, ?, CURRENT_TIMESTAMP)
+        ON CONFLICT (key) DO UPDATE SET
+            value = excluded.value,
+            updated_by = excluded.updated_by,
+            updated_at = excluded.updated_at
+        "#,
+    )
+    .bind(key)
+    .bind(value.to_string())
+    .bind(updated_by.to_string())
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Drop the override for `key`, going back to the config file.
+#[tracing::instrument(skip_all)]
+pub async fn clear(pool: &sqlx::AnyPool, key: &str) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM instance_settings WHERE key = ?")
+        .bind(key)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
@@ -6,8 +6,8 @@
 
 use anyhow::Result;
 use nexus_common::models::crypto::{
-    Device, DeviceVerification, E2eeChannel, E2eeSession, EncryptedMessage, KeyBundle, OneTimePreKey,
-    OtpkPublic,
+    Device, DeviceVerification, E2eeChannel, E2eeSession, EncryptedAttachment, EncryptedMessage,
+    KeyBackupSession, KeyBackupVersion, KeyBundle, OneTimePreKey, OtpkPublic,
 };
 
 use uuid::Uuid;
@@ -18,6 +18,7 @@ use uuid::Uuid;
 
 /// Register a new device for a user.
 #[allow(clippy::too_many_arguments)]
+#[tracing::instrument(skip_all)]
 pub async fn create_device(
     pool: &sqlx::AnyPool,
     user_id: Uuid,
@@ -50,6 +51,7 @@ pub async fn create_device(
 }
 
 /// List all devices for a user (public info only — no secret material stored server-side).
+#[tracing::instrument(skip_all)]
 pub async fn list_devices(pool: &sqlx::AnyPool, user_id: Uuid) -> Result<Vec<Device>> {
     let rows = sqlx::query_as::<_, Device>(
         "SELECT * FROM devices WHERE user_id = ? ORDER BY created_at ASC",
@@ -61,6 +63,7 @@ pub async fn list_devices(pool: &sqlx::AnyPool, user_id: Uuid) -> Result<Vec<Dev
 }
 
 /// Find a single device by ID.
+#[tracing::instrument(skip_all)]
 pub async fn find_device(pool: &sqlx::AnyPool, device_id: Uuid) -> Result<Option<Device>> {
     let row = sqlx::query_as::<_, Device>("SELECT * FROM devices WHERE id = ?")
         .bind(device_id.to_string())
@@ -70,6 +73,7 @@ pub async fn find_device(pool: &sqlx::AnyPool, device_id: Uuid) -> Result<Option
 }
 
 /// Update the signed pre-key (rotation).
+#[tracing::instrument(skip_all)]
 pub async fn rotate_signed_pre_key(
     pool: &sqlx::AnyPool,
     device_id: Uuid,
@@ -97,6 +101,7 @@ pub async fn rotate_signed_pre_key(
 }
 
 /// Touch last_seen_at for a device.
+#[tracing::instrument(skip_all)]
 pub async fn touch_device(pool: &sqlx::AnyPool, device_id: Uuid) -> Result<()> {
     sqlx::query("UPDATE devices SET last_seen_at = CURRENT_TIMESTAMP WHERE id = ?")
         .bind(device_id.to_string())
@@ -106,6 +111,7 @@ pub async fn touch_device(pool: &sqlx::AnyPool, device_id: Uuid) -> Result<()> {
 }
 
 /// Delete a device and all associated key material.
+#[tracing::instrument(skip_all)]
 pub async fn delete_device(pool: &sqlx::AnyPool, device_id: Uuid) -> Result<()> {
     sqlx::query("DELETE FROM devices WHERE id = ?")
         .bind(device_id.to_string())
@@ -118,33 +124,62 @@ pub async fn delete_device(pool: &sqlx::AnyPool, device_id: Uuid) -> Result<()>
 // One-Time Pre-Keys
 // ============================================================
 
-/// Bulk-insert one-time pre-keys for a device.
+/// Bind parameters per statement when bulk inserting. SQLite before 3.32
+/// refuses more than 999; Postgres allows 65535.
+const MAX_BIND_PARAMS: usize = 999;
+
+/// Bulk-insert one-time pre-keys for a device, skipping key IDs it already
+/// has. Keys go in as multi-row INSERTs in one transaction, so an upload is
+/// a handful of round trips rather than one per key. Returns how many were
+/// new.
+#[tracing::instrument(skip_all)]
 pub async fn insert_one_time_pre_keys(
     pool: &sqlx::AnyPool,
     device_id: Uuid,
     keys: &[(i32, String)],
 ) -> Result<usize> {
+    if keys.is_empty() {
+        return Ok(0);
+    }
+    let device_id = device_id.to_string();
+    let mut tx = pool.begin().await?;
+    let postgres = tx.backend_name() == "PostgreSQL";
     let mut inserted = 0usize;
-    for (key_id, public_key) in keys {
-        sqlx::query(
-            r#"
-            INSERT INTO one_time_pre_keys (device_id, key_id, public_key)
-            VALUES (?, ?, ?)
-            ON CONFLICT (device_id, key_id) DO NOTHING
-            "#,
-        )
-        .bind(device_id.to_string())
-        .bind(key_id)
-        .bind(public_key)
-        .execute(pool)
-        .await?;
-        inserted += 1;
+    for chunk in keys.chunks(MAX_BIND_PARAMS / 3) {
+        let sql = one_time_pre_keys_insert_sql(chunk.len(), postgres);
+        let mut query = sqlx::query(&sql);
+        for (key_id, public_key) in chunk {
+            query = query.bind(&device_id).bind(key_id).bind(public_key);
+        }
+        inserted += query.execute(&mut *tx).await?.rows_affected() as usize;
     }
+    tx.commit().await?;
     Ok(inserted)
 }
 
+/// `INSERT` of `rows` one-time pre-keys, with `$n` placeholders for Postgres
+/// and `?` for SQLite.
+fn one_time_pre_keys_insert_sql(rows: usize, postgres: bool) -> String {
+    let values: Vec<String> = (0..rows)
+        .map(|row| {
+            if postgres {
+                let n = row * 3;
+                format!("(${}, ${}, ${})", n + 1, n + 2, n + 3)
+            } else {
+                "(?, ?, ?)".to_owned()
+            }
+        })
+        .collect();
+    format!(
+        "INSERT INTO one_time_pre_keys (device_id, key_id, public_key) VALUES {} \
+         ON CONFLICT (device_id, key_id) DO NOTHING",
+        values.join(", ")
+    )
+}
+
 /// Consume one one-time pre-key for a device (atomically marks it used and returns it).
 /// Returns `None` if the device has run out of one-time pre-keys.
+#[tracing::instrument(skip_all)]
 pub async fn consume_one_time_pre_key(
     pool: &sqlx::AnyPool,
     device_id: Uuid,
@@ -179,6 +214,7 @@ pub async fn consume_one_time_pre_key(
 }
 
 /// Count remaining (unconsumed) one-time pre-keys for a device.
+#[tracing::instrument(skip_all)]
 pub async fn count_one_time_pre_keys(pool: &sqlx::AnyPool, device_id: Uuid) -> Result<i64> {
     #[derive(sqlx::FromRow)]
     struct CountRow {
@@ -199,6 +235,7 @@ pub async fn count_one_time_pre_keys(pool: &sqlx::AnyPool, device_id: Uuid) -> R
 
 /// Fetch a full key bundle for a device (for X3DH initiators).
 /// Atomically consumes one OTPk.
+#[tracing::instrument(skip_all)]
 pub async fn get_key_bundle(pool: &sqlx::AnyPool, device_id: Uuid) -> Result<Option<KeyBundle>> {
     let device = match find_device(pool, device_id).await? {
         Some(d) => d,
@@ -217,6 +254,7 @@ pub async fn get_key_bundle(pool: &sqlx::AnyPool, device_id: Uuid) -> Result<Opt
 }
 
 /// Fetch key bundles for ALL devices of a user (multi-device send).
+#[tracing::instrument(skip_all)]
 pub async fn get_all_key_bundles(pool: &sqlx::AnyPool, user_id: Uuid) -> Result<Vec<KeyBundle>> {
     let devices = list_devices(pool, user_id).await?;
     let mut bundles = Vec::with_capacity(devices.len());
@@ -240,6 +278,7 @@ pub async fn get_all_key_bundles(pool: &sqlx::AnyPool, user_id: Uuid) -> Result<
 // ============================================================
 
 /// Upsert a session state blob (client ratchets and re-uploads).
+#[tracing::instrument(skip_all)]
 pub async fn upsert_session(
     pool: &sqlx::AnyPool,
     owner_device_id: Uuid,
@@ -269,6 +308,7 @@ pub async fn upsert_session(
 }
 
 /// Fetch session state for a device pair.
+#[tracing::instrument(skip_all)]
 pub async fn get_session(
     pool: &sqlx::AnyPool,
     owner_device_id: Uuid,
@@ -289,6 +329,7 @@ pub async fn get_session(
 // ============================================================
 
 /// Store an encrypted message.
+#[tracing::instrument(skip_all)]
 pub async fn store_encrypted_message(
     pool: &sqlx::AnyPool,
     channel_id: Uuid,
@@ -327,6 +368,7 @@ pub async fn store_encrypted_message(
 }
 
 /// List encrypted messages for a channel, paginated, newest-first.
+#[tracing::instrument(skip_all)]
 pub async fn list_encrypted_messages(
     pool: &sqlx::AnyPool,
     channel_id: Uuid,
@@ -364,11 +406,269 @@ pub async fn list_encrypted_messages(
     Ok(rows)
 }
 
+// ============================================================
+// Encrypted Attachments
+// ============================================================
+
+/// Record an uploaded ciphertext blob.
+#[tracing::instrument(skip_all)]
+pub async fn create_encrypted_attachment(
+    pool: &sqlx::AnyPool,
+    id: Uuid,
+    channel_id: Uuid,
+    uploader_id: Uuid,
+    storage_key: &str,
+    size: i64,
+    sha256: &str,
+) -> Result<EncryptedAttachment> {
+    let row = sqlx::query_as::<_, EncryptedAttachment>(
+        r#"
+        INSERT INTO e2ee_attachments (id, channel_id, uploader_id, storage_key, size, sha256)
+        VALUES (?, ?, ?, ?, ?, ?)
+        RETURNING *
+        "#,
+    )
+    .bind(id.to_string())
+    .bind(channel_id.to_string())
+    .bind(uploader_id.to_string())
+    .bind(storage_key)
+    .bind(size)
+    .bind(sha256)
+    .fetch_one(pool)
+    .await?;
+    Ok(row)
+}
+
+/// Get an encrypted attachment by ID.
+#[tracing::instrument(skip_all)]
+pub async fn find_encrypted_attachment(pool: &sqlx::AnyPool, id: Uuid) -> Result<Option<EncryptedAttachment>> {
+    let row = sqlx::query_as::<_, EncryptedAttachment>("SELECT * FROM e2ee_attachments WHERE id = ?")
+        .bind(id.to_string())
+        .fetch_optional(pool)
+        .await?;
+    Ok(row)
+}
+
+/// Attach an upload to the encrypted message that references it.
+#[tracing::instrument(skip_all)]
+pub async fn link_encrypted_attachment(pool: &sqlx::AnyPool, id: Uuid, message_id: Uuid) -> Result<()> {
+    sqlx::query("UPDATE e2ee_attachments SET message_id = ? WHERE id = ? AND message_id IS NULL")
+        .bind(message_id.to_string())
+        .bind(id.to_string())
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+// ============================================================
+// Key Backup
+// ============================================================
+
+/// Create the next backup version for a user. Version numbers are never
+/// reused, even after deletion.
+#[tracing::instrument(skip_all)]
+pub async fn create_key_backup_version(
+    pool: &sqlx::AnyPool,
+    user_id: Uuid,
+    algorithm: &str,
+    auth_data: &serde_json::Value,
+) -> Result<KeyBackupVersion> {
+    let version: i64 = sqlx::query_scalar(
+        r#"
+        INSERT INTO key_backup_versions (user_id, version, algorithm, auth_data)
+        VALUES (
+            ?,
+            COALESCE((SELECT MAX(version) FROM key_backup_versions WHERE user_id = ?), 0) + 1,
+            ?, ?
+        )
+        RETURNING version
+        "#,
+    )
+    .bind(user_id.to_string())
+    .bind(user_id.to_string())
+    .bind(algorithm)
+    .bind(auth_data.to_string())
+    .fetch_one(pool)
+    .await?;
+    get_key_backup_version(pool, user_id, Some(version))
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("key backup version {version} vanished after insert"))
+}
+
+/// Get a backup version, or the newest one when `version` is `None`.
+/// Deleted versions are never returned.
+#[tracing::instrument(skip_all)]
+pub async fn get_key_backup_version(
+    pool: &sqlx::AnyPool,
+    user_id: Uuid,
+    version: Option<i64>,
+) -> Result<Option<KeyBackupVersion>> {
+    let sql = format!(
+        r#"
+        SELECT v.*,
+               (SELECT COUNT(*) FROM key_backup_sessions s
+                WHERE s.user_id = v.user_id AND s.version = v.version) AS count
+        FROM key_backup_versions v
+        WHERE v.user_id = ? AND v.deleted = false {}
+        ORDER BY v.version DESC
+        LIMIT 1
+        "#,
+        if version.is_some() { "AND v.version = ?" } else { "" }
+    );
+    let mut query = sqlx::query_as::<_, KeyBackupVersion>(&sql).bind(user_id.to_string());
+    if let Some(version) = version {
+        query = query.bind(version);
+    }
+    Ok(query.fetch_optional(pool).await?)
+}
+
+/// Delete a backup version and every session stored in it. Returns whether
+/// it existed.
+#[tracing::instrument(skip_all)]
+pub async fn delete_key_backup_version(pool: &sqlx::AnyPool, user_id: Uuid, version: i64) -> Result<bool> {
+    let mut tx = pool.begin().await?;
+    let updated = sqlx::query(
+        "UPDATE key_backup_versions SET deleted = true WHERE user_id = ? AND version = ? AND deleted = false",
+    )
+    .bind(user_id.to_string())
+    .bind(version)
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query("DELETE FROM key_backup_sessions WHERE user_id = ? AND version = ?")
+        .bind(user_id.to_string())
+        .bind(version)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+    Ok(updated.rows_affected() > 0)
+}
+
+/// Sessions in a backup, optionally narrowed to a channel or a single session.
+#[tracing::instrument(skip_all)]
+pub async fn list_key_backup_sessions(
+    pool: &sqlx::AnyPool,
+    user_id: Uuid,
+    version: i64,
+    channel_id: Option<Uuid>,
+    session_id: Option<&str>,
+) -> Result<Vec<KeyBackupSession>> {
+    let mut sql = String::from("SELECT * FROM key_backup_sessions WHERE user_id = ? AND version = ?");
+    sql.push_str(&session_filter(channel_id, session_id));
+    sql.push_str(" ORDER BY channel_id, session_id");
+
+    let mut query = sqlx::query_as::<_, KeyBackupSession>(&sql)
+        .bind(user_id.to_string())
+        .bind(version);
+    if let Some(channel_id) = channel_id {
+        query = query.bind(channel_id.to_string());
+    }
+    if let Some(session_id) = session_id {
+        query = query.bind(session_id);
+    }
+    Ok(query.fetch_all(pool).await?)
+}
+
+/// `AND` clauses for the optional channel / session filters, in bind order.
+fn session_filter(channel_id: Option<Uuid>, session_id: Option<&str>) -> String {
+    let mut sql = String::new();
+    if channel_id.is_some() {
+        sql.push_str(" AND channel_id = ?");
+    }
+    if session_id.is_some() {
+        sql.push_str(" AND session_id = ?");
+    }
+    sql
+}
+
+/// Store sessions in a backup, overwriting any with the same ID, and bump
+/// its etag. The caller decides which copies are worth keeping.
+#[tracing::instrument(skip_all)]
+pub async fn upsert_key_backup_sessions(
+    pool: &sqlx::AnyPool,
+    user_id: Uuid,
+    version: i64,
+    sessions: &[KeyBackupSession],
+) -> Result<()> {
+    let mut tx = pool.begin().await?;
+    for session in sessions {
+        sqlx::query(
+            r#"
+            INSERT INTO key_backup_sessions
+                (user_id, version, channel_id, session_id, first_message_index,
+                 forwarded_count, is_verified, session_data)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT (user_id, version, channel_id, session_id) DO UPDATE SET
+                first_message_index = EXCLUDED.first_message_index,
+                forwarded_count     = EXCLUDED.forwarded_count,
+                is_verified         = EXCLUDED.is_verified,
+                session_data        = EXCLUDED.session_data,
+                updated_at          = CURRENT_TIMESTAMP
+            "#,
+        )
+        .bind(user_id.to_string())
+        .bind(version)
+        .bind(session.channel_id.to_string())
+        .bind(&session.session_id)
+        .bind(session.first_message_index)
+        .bind(session.forwarded_count)
+        .bind(session.is_verified)
+        .bind(session.session_data.to_string())
+        .execute(&mut *tx)
+        .await?;
+    }
+    bump_key_backup_etag(&mut tx, user_id, version).await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Remove sessions from a backup, optionally narrowed to a channel or a
+/// single session, and bump its etag. Returns the number removed.
+#[tracing::instrument(skip_all)]
+pub async fn delete_key_backup_sessions(
+    pool: &sqlx::AnyPool,
+    user_id: Uuid,
+    version: i64,
+    channel_id: Option<Uuid>,
+    session_id: Option<&str>,
+) -> Result<u64> {
+    let mut sql = String::from("DELETE FROM key_backup_sessions WHERE user_id = ? AND version = ?");
+    sql.push_str(&session_filter(channel_id, session_id));
+
+    let mut tx = pool.begin().await?;
+    let mut query = sqlx::query(&sql).bind(user_id.to_string()).bind(version);
+    if let Some(channel_id) = channel_id {
+        query = query.bind(channel_id.to_string());
+    }
+    if let Some(session_id) = session_id {
+        query = query.bind(session_id);
+    }
+    let deleted = query.execute(&mut *tx).await?.rows_affected();
+    if deleted > 0 {
+        bump_key_backup_etag(&mut tx, user_id, version).await?;
+    }
+    tx.commit().await?;
+    Ok(deleted)
+}
+
+async fn bump_key_backup_etag(
+    tx: &mut sqlx::Transaction<'_, sqlx::Any>,
+    user_id: Uuid,
+    version: i64,
+) -> Result<()> {
+    sqlx::query("UPDATE key_backup_versions SET etag = etag + 1 WHERE user_id = ? AND version = ?")
+        .bind(user_id.to_string())
+        .bind(version)
+        .execute(&mut **tx)
+        .await?;
+    Ok(())
+}
+
 // ============================================================
 // E2EE Channels
 // ============================================================
 
 /// Mark a channel as E2EE.
+#[tracing::instrument(skip_all)]
 pub async fn enable_e2ee_channel(
     pool: &sqlx::AnyPool,
     channel_id: Uuid,
@@ -394,6 +694,7 @@ pub async fn enable_e2ee_channel(
 }
 
 /// Get E2EE config for a channel (returns None if not E2EE).
+#[tracing::instrument(skip_all)]
 pub async fn get_e2ee_channel(pool: &sqlx::AnyPool, channel_id: Uuid) -> Result<Option<E2eeChannel>> {
     let row = sqlx::query_as::<_, E2eeChannel>(
         "SELECT * FROM e2ee_channels WHERE channel_id = ?",
@@ -405,6 +706,7 @@ pub async fn get_e2ee_channel(pool: &sqlx::AnyPool, channel_id: Uuid) -> Result<
 }
 
 /// Record a key rotation event.
+#[tracing::instrument(skip_all)]
 pub async fn record_key_rotation(pool: &sqlx::AnyPool, channel_id: Uuid) -> Result<()> {
     sqlx::query(
         "UPDATE e2ee_channels SET last_rotated_at = CURRENT_TIMESTAMP WHERE channel_id = ?",
@@ -420,6 +722,7 @@ pub async fn record_key_rotation(pool: &sqlx::AnyPool, channel_id: Uuid) -> Resu
 // ============================================================
 
 /// Record that a user has verified a device.
+#[tracing::instrument(skip_all)]
 pub async fn verify_device(
     pool: &sqlx::AnyPool,
     verifier_id: Uuid,
@@ -452,6 +755,7 @@ pub async fn verify_device(
 }
 
 /// Check whether a verifier has verified a target device.
+#[tracing::instrument(skip_all)]
 pub async fn is_device_verified(
     pool: &sqlx::AnyPool,
     verifier_id: Uuid,
@@ -477,6 +781,7 @@ pub async fn is_device_verified(
 }
 
 /// List all verifications made by a user.
+#[tracing::instrument(skip_all)]
 pub async fn list_verifications(
     pool: &sqlx::AnyPool,
     verifier_id: Uuid,
@@ -491,6 +796,7 @@ pub async fn list_verifications(
 }
 
 /// Fetch the one-time pre-key for a device by key_id (for debugging / admin purposes).
+#[tracing::instrument(skip_all)]
 pub async fn get_one_time_pre_key(
     pool: &sqlx::AnyPool,
     device_id: Uuid,
@@ -0,0 +1,75 @@
+//! Legal consents repository — which versions of the instance's terms each
+//! user consented to, and when.
+
+use chrono::{DateTime, Utc};
+use sqlx::Row;
+use uuid::Uuid;
+
+#[derive(Debug, Clone)]
+pub struct ConsentRow {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub version: String,
+    pub ip: Option<String>,
+    pub consented_at: DateTime<Utc>,
+}
+
+impl<'r> sqlx::FromRow<'r, sqlx::any::AnyRow> for ConsentRow {
+    fn from_row(row: &'r sqlx::any::AnyRow) -> Result<Self, sqlx::Error> {
+        use crate::any_compat::*;
+        Ok(ConsentRow {
+            id: get_uuid(row, "id")?,
+            user_id: get_uuid(row, "user_id")?,
+            version: row.try_get("version")?,
+            ip: row.try_get("ip")?,
+            consented_at: get_datetime(row, "consented_at")?,
+        })
+    }
+}
+
+/// Record that `user_id` consented to `version`. Consenting again to the same
+/// version keeps the original record.
+#[tracing::instrument(skip_all)]
+pub async fn record(
+    pool: &sqlx::AnyPool,
+    user_id: Uuid,
+    version: &str,
+    ip: Option<&str>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO legal_consents (id, user_id, version, ip, consented_at)
+        VALUES (?, ?, ?, ?, CURRENT_TIMESTAMP)
+        ON CONFLICT (user_id, version) DO NOTHING
+        "#,
+    )
+    .bind(Uuid::now_v7().to_string())
+    .bind(user_id.to_string())
+    .bind(version)
+    .bind(ip)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Whether `user_id` has consented to `version`.
+#[tracing::instrument(skip_all)]
+pub async fn has_consented(pool: &sqlx::AnyPool, user_id: Uuid, version: &str) -> Result<bool, sqlx::Error> {
+    let row = sqlx::query("SELECT 1 FROM legal_consents WHERE user_id = ? AND version = ?")
+        .bind(user_id.to_string())
+        .bind(version)
+        .fetch_optional(pool)
+        .await?;
+    Ok(row.is_some())
+}
+
+/// The user's most recent consent, to any version.
+#[tracing::instrument(skip_all)]
+pub async fn latest(pool: &sqlx::AnyPool, user_id: Uuid) -> Result<Option<ConsentRow>, sqlx::Error> {
+    sqlx::query_as::<_, ConsentRow>(
+        "SELECT * FROM legal_consents WHERE user_id = ? ORDER BY consented_at DESC LIMIT 1",
+    )
+    .bind(user_id.to_string())
+    .fetch_optional(pool)
+    .await
+}
@@ -0,0 +1,139 @@
+//! Media job repository — the queue of image attachments waiting for
+//! thumbnailing and blurhash extraction.
+//!
+//! Jobs are queued as `pending` when an image upload lands in storage,
+//! claimed by the media job, and finally marked `completed` or `failed`.
+
+use chrono::{DateTime, Utc};
+use sqlx::Row;
+use uuid::Uuid;
+
+#[derive(Debug)]
+pub struct MediaJobRow {
+    pub id: Uuid,
+    pub attachment_id: Uuid,
+    pub status: String,
+    pub attempts: i32,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+impl<'r> sqlx::FromRow<'r, sqlx::any::AnyRow> for MediaJobRow {
+    fn from_row(row: &'r sqlx::any::AnyRow) -> Result<Self, sqlx::Error> {
+        use crate::any_compat::*;
+        Ok(MediaJobRow {
+            id: get_uuid(row, "id")?,
+            attachment_id: get_uuid(row, "attachment_id")?,
+            status: row.try_get("status")?,
+            attempts: row.try_get("attempts")?,
+            last_error: row.try_get("last_error")?,
+            created_at: get_datetime(row, "created_at")?,
+            completed_at: get_opt_datetime(row, "completed_at")?,
+        })
+    }
+}
+
+/// Queue an attachment for processing. Re-queuing is a no-op.
+#[tracing::instrument(skip_all)]
+pub async fn enqueue<'e, E>(executor: E, attachment_id: Uuid) -> Result<(), sqlx::Error>
+where
+    E: sqlx::Executor<'e, Database = sqlx::Any>,
+{
+    sqlx::query(
+        r#"
+        INSERT INTO media_jobs (id, attachment_id, status, created_at)
+        VALUES (?, ?, 'pending', CURRENT_TIMESTAMP)
+        ON CONFLICT (attachment_id) DO NOTHING
+        "#,
+    )
+    .bind(Uuid::now_v7().to_string())
+    .bind(attachment_id.to_string())
+    .execute(executor)
+    .await?;
+    Ok(())
+}
+
+/// Claim up to `limit` pending jobs, oldest first.
+///
+/// Each row is flipped to `processing` with a guarded update, so two workers
+/// racing on the same row never both win it.
+#[tracing::instrument(skip_all)]
+pub async fn claim_pending(pool: &sqlx::AnyPool, limit: i64) -> Result<Vec<MediaJobRow>, sqlx::Error> {
+    let candidates = sqlx::query_as::<_, MediaJobRow>(
+        "SELECT * FROM media_jobs WHERE status = 'pending' ORDER BY created_at LIMIT ?",
+    )
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    let mut claimed = Vec::with_capacity(candidates.len());
+    for mut row in candidates {
+        let result = sqlx::query(
+            r#"
+            UPDATE media_jobs
+            SET status = 'processing', attempts = attempts + 1
+            WHERE id = ? AND status = 'pending'
+            "#,
+        )
+        .bind(row.id.to_string())
+        .execute(pool)
+        .await?;
+        if result.rows_affected() == 1 {
+            row.status = "processing".into();
+            row.attempts += 1;
+            claimed.push(row);
+        }
+    }
+    Ok(claimed)
+}
+
+/// Mark a job as done.
+#[tracing::instrument(skip_all)]
+pub async fn complete(pool: &sqlx::AnyPool, id: Uuid) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        UPDATE media_jobs
+        SET status = 'completed', last_error = NULL, completed_at = CURRENT_TIMESTAMP
+        WHERE id = ?
+        "#,
+    )
+    .bind(id.to_string())
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Record a failed attempt. The row goes back to `pending` until
+/// `max_attempts` is reached, after which it is left as `failed`.
+#[tracing::instrument(skip_all)]
+pub async fn fail(
+    pool: &sqlx::AnyPool,
+    id: Uuid,
+    error: &str,
+    max_attempts: i32,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        UPDATE media_jobs
+        SET status = CASE WHEN attempts >= ? THEN 'failed' ELSE 'pending' END,
+            last_error = ?
+        WHERE id = ?
+        "#,
+    )
+    .bind(max_attempts)
+    .bind(error)
+    .bind(id.to_string())
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Put rows left in `processing` by a crashed worker back in the queue.
+#[tracing::instrument(skip_all)]
+pub async fn requeue_stale(pool: &sqlx::AnyPool) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query("UPDATE media_jobs SET status = 'pending' WHERE status = 'processing'")
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected())
+}
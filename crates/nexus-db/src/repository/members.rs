@@ -1,15 +1,20 @@
 //! Member repository — server membership management.
 
 use nexus_common::models::member::Member;
+use sqlx::FromRow;
 
 use uuid::Uuid;
 
 /// Add a user as a member of a server.
-pub async fn add_member(
-    pool: &sqlx::AnyPool,
+#[tracing::instrument(skip_all)]
+pub async fn add_member<'e, E>(
+    executor: E,
     user_id: Uuid,
     server_id: Uuid,
-) -> Result<Member, sqlx::Error> {
+) -> Result<Member, sqlx::Error>
+where
+    E: sqlx::Executor<'e, Database = sqlx::Any>,
+{
     sqlx::query_as::<_, Member>(
         r#"
         INSERT INTO members (user_id, server_id, roles, muted, deafened, joined_at)
@@ -19,40 +24,58 @@ pub async fn add_member(
     )
     .bind(user_id.to_string())
     .bind(server_id.to_string())
-    .fetch_one(pool)
+    .fetch_one(executor)
     .await
 }
 
 /// Remove a member from a server.
-pub async fn remove_member(
-    pool: &sqlx::AnyPool,
+#[tracing::instrument(skip_all)]
+pub async fn remove_member<'e, E>(
+    executor: E,
     user_id: Uuid,
     server_id: Uuid,
-) -> Result<(), sqlx::Error> {
+) -> Result<(), sqlx::Error>
+where
+    E: sqlx::Executor<'e, Database = sqlx::Any>,
+{
     sqlx::query("DELETE FROM members WHERE user_id = ? AND server_id = ?")
         .bind(user_id.to_string())
         .bind(server_id.to_string())
-        .execute(pool)
+        .execute(executor)
         .await?;
     Ok(())
 }
 
 /// Get a member by user ID and server ID.
-pub async fn find_member(
-    pool: &sqlx::AnyPool,
+#[tracing::instrument(skip_all)]
+pub async fn find_member<'e, E>(
+    executor: E,
     user_id: Uuid,
     server_id: Uuid,
-) -> Result<Option<Member>, sqlx::Error> {
+) -> Result<Option<Member>, sqlx::Error>
+where
+    E: sqlx::Executor<'e, Database = sqlx::Any>,
+{
     sqlx::query_as::<_, Member>(
         "SELECT * FROM members WHERE user_id = ? AND server_id = ?",
     )
     .bind(user_id.to_string())
     .bind(server_id.to_string())
-    .fetch_optional(pool)
+    .fetch_optional(executor)
     .await
 }
 
+/// Every membership of a user, one per server they're in.
+#[tracing::instrument(skip_all)]
+pub async fn list_for_user(pool: &sqlx::AnyPool, user_id: Uuid) -> Result<Vec<Member>, sqlx::Error> {
+    sqlx::query_as::<_, Member>("SELECT * FROM members WHERE user_id = ?")
+        .bind(user_id.to_string())
+        .fetch_all(pool)
+        .await
+}
+
 /// List members of a server with pagination.
+#[tracing::instrument(skip_all)]
 pub async fn list_members(
     pool: &sqlx::AnyPool,
     server_id: Uuid,
@@ -75,58 +98,108 @@ pub async fn list_members(
 }
 
 /// Update member nickname.
-pub async fn update_nickname(
-    pool: &sqlx::AnyPool,
+#[tracing::instrument(skip_all)]
+pub async fn update_nickname<'e, E>(
+    executor: E,
     user_id: Uuid,
     server_id: Uuid,
     nickname: Option<&str>,
-) -> Result<(), sqlx::Error> {
+) -> Result<(), sqlx::Error>
+where
+    E: sqlx::Executor<'e, Database = sqlx::Any>,
+{
     sqlx::query("UPDATE members SET nickname = ? WHERE user_id = ? AND server_id = ?")
+        .bind(nickname)
         .bind(user_id.to_string())
         .bind(server_id.to_string())
-        .bind(nickname)
-        .execute(pool)
+        .execute(executor)
         .await?;
     Ok(())
 }
 
 /// Add a role to a member.
-pub async fn add_role(
-    pool: &sqlx::AnyPool,
+#[tracing::instrument(skip_all)]
+pub async fn add_role<'e, E>(
+    executor: E,
     user_id: Uuid,
     server_id: Uuid,
     role_id: Uuid,
-) -> Result<(), sqlx::Error> {
+) -> Result<(), sqlx::Error>
+where
+    E: sqlx::Executor<'e, Database = sqlx::Any>,
+{
     sqlx::query(
         "UPDATE members SET roles = array_append(roles, ?) WHERE user_id = ? AND server_id = ? AND NOT (? = ANY(roles))",
     )
+    .bind(role_id.to_string())
     .bind(user_id.to_string())
     .bind(server_id.to_string())
     .bind(role_id.to_string())
-    .execute(pool)
+    .execute(executor)
     .await?;
     Ok(())
 }
 
 /// Remove a role from a member.
-pub async fn remove_role(
-    pool: &sqlx::AnyPool,
+#[tracing::instrument(skip_all)]
+pub async fn remove_role<'e, E>(
+    executor: E,
     user_id: Uuid,
     server_id: Uuid,
     role_id: Uuid,
-) -> Result<(), sqlx::Error> {
+) -> Result<(), sqlx::Error>
+where
+    E: sqlx::Executor<'e, Database = sqlx::Any>,
+{
     sqlx::query(
         "UPDATE members SET roles = array_remove(roles, ?) WHERE user_id = ? AND server_id = ?",
     )
+    .bind(role_id.to_string())
+    .bind(user_id.to_string())
+    .bind(server_id.to_string())
+    .execute(executor)
+    .await?;
+    Ok(())
+}
+
+/// Set or clear a member's communication timeout.
+#[tracing::instrument(skip_all)]
+pub async fn set_timeout(
+    pool: &sqlx::AnyPool,
+    user_id: Uuid,
+    server_id: Uuid,
+    until: Option<chrono::DateTime<chrono::Utc>>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "UPDATE members SET communication_disabled_until = ? WHERE user_id = ? AND server_id = ?",
+    )
+    .bind(until.map(|t| t.to_rfc3339()))
     .bind(user_id.to_string())
     .bind(server_id.to_string())
-    .bind(role_id.to_string())
     .execute(pool)
     .await?;
     Ok(())
 }
 
+/// Mark a member as pending (awaiting membership screening) or fully joined.
+#[tracing::instrument(skip_all)]
+pub async fn set_pending(
+    pool: &sqlx::AnyPool,
+    user_id: Uuid,
+    server_id: Uuid,
+    pending: bool,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE members SET pending = ? WHERE user_id = ? AND server_id = ?")
+        .bind(pending)
+        .bind(user_id.to_string())
+        .bind(server_id.to_string())
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
 /// Check if a user is a member of a server.
+#[tracing::instrument(skip_all)]
 pub async fn is_member(
     pool: &sqlx::AnyPool,
     user_id: Uuid,
@@ -141,3 +214,104 @@ pub async fn is_member(
     .await?;
     Ok(result.0)
 }
+
+/// Members of a server who show as online (invisible ones don't).
+#[tracing::instrument(skip_all)]
+pub async fn count_online(pool: &sqlx::AnyPool, server_id: Uuid) -> Result<i64, sqlx::Error> {
+    let row: (i64,) = sqlx::query_as(
+        r#"
+        SELECT COUNT(*) FROM members m
+        INNER JOIN users u ON u.id = m.user_id
+        WHERE m.server_id = ? AND u.presence IN ('online', 'idle', 'do_not_disturb')
+        "#,
+    )
+    .bind(server_id.to_string())
+    .fetch_one(pool)
+    .await?;
+    Ok(row.0)
+}
+
+/// Whether two users are both members of at least one server.
+#[tracing::instrument(skip_all)]
+pub async fn share_server(
+    pool: &sqlx::AnyPool,
+    user_a: Uuid,
+    user_b: Uuid,
+) -> Result<bool, sqlx::Error> {
+    let result: (bool,) = sqlx::query_as(
+        r#"
+        SELECT EXISTS(
+            SELECT 1 FROM members a
+            JOIN members b ON b.server_id = a.server_id
+            WHERE a.user_id = ? AND b.user_id = ?
+        )
+        "#,
+    )
+    .bind(user_a.to_string())
+    .bind(user_b.to_string())
+    .fetch_one(pool)
+    .await?;
+    Ok(result.0)
+}
+
+/// A member matched by [`search_by_prefix`], with the user fields a
+/// mention picker needs.
+#[derive(Debug)]
+pub struct MemberMatch {
+    pub member: Member,
+    pub username: String,
+    pub display_name: Option<String>,
+    pub user_avatar: Option<String>,
+}
+
+impl<'r> sqlx::FromRow<'r, sqlx::any::AnyRow> for MemberMatch {
+    fn from_row(row: &'r sqlx::any::AnyRow) -> Result<Self, sqlx::Error> {
+        use sqlx::Row;
+        Ok(MemberMatch {
+            member: Member::from_row(row)?,
+            username: row.try_get("username")?,
+            display_name: row.try_get("display_name")?,
+            user_avatar: row.try_get("user_avatar")?,
+        })
+    }
+}
+
+/// Members of a server whose username, display name, or nickname starts
+/// with `prefix` (case-insensitive), ordered by username.
+#[tracing::instrument(skip_all)]
+pub async fn search_by_prefix(
+    pool: &sqlx::AnyPool,
+    server_id: Uuid,
+    prefix: &str,
+    limit: i64,
+) -> Result<Vec<MemberMatch>, sqlx::Error> {
+    let escaped = prefix
+        .to_lowercase()
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    let pattern = format!("{escaped}%");
+
+    sqlx::query_as::<_, MemberMatch>(
+        r#"
+        SELECT m.*, u.username, u.display_name, u.avatar AS user_avatar
+        FROM members m
+        INNER JOIN users u ON u.id = m.user_id
+        WHERE m.server_id = ?
+        AND (
+            LOWER(u.username) LIKE ? ESCAPE '\'
+            OR LOWER(COALESCE(u.display_name, '')) LIKE ? ESCAPE '\'
+            OR LOWER(COALESCE(m.nickname, '')) LIKE ? ESCAPE '\'
+        )
+        ORDER BY u.username
+        LIMIT ?
+        "#,
+    )
+    .bind(server_id.to_string())
+    .bind(&pattern)
+    .bind(&pattern)
+    .bind(&pattern)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+}
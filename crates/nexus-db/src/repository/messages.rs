@@ -2,6 +2,10 @@
 //!
 //! Supports both PostgreSQL (production) and SQLite (lite mode) via AnyPool.
 //! All UUID/DateTime/Vec<Uuid>/JSON fields are encoded as strings for AnyPool compatibility.
+//!
+//! Deleting a message only sets `deleted_at`; every read here skips such
+//! rows except [`list_deleted`], which moderators review them with. The
+//! retention job removes them for good ([`purge_deleted`]).
 
 use chrono::{DateTime, Utc};
 use sqlx::Row;
@@ -28,6 +32,12 @@ pub struct MessageRow {
     pub reference_channel_id: Option<Uuid>,
     pub thread_id: Option<Uuid>,
     pub flags: i32,
+    /// Set when the message was posted through an incoming webhook.
+    pub webhook_id: Option<Uuid>,
+    pub webhook_username: Option<String>,
+    pub webhook_avatar_url: Option<String>,
+    /// When the message was deleted; it is purged later.
+    pub deleted_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -53,15 +63,28 @@ impl<'r> sqlx::FromRow<'r, sqlx::any::AnyRow> for MessageRow {
             reference_channel_id: get_opt_uuid(row, "reference_channel_id")?,
             thread_id: get_opt_uuid(row, "thread_id")?,
             flags: row.try_get("flags")?,
+            webhook_id: get_opt_uuid(row, "webhook_id")?,
+            webhook_username: row.try_get("webhook_username")?,
+            webhook_avatar_url: row.try_get("webhook_avatar_url")?,
+            deleted_at: get_opt_datetime(row, "deleted_at")?,
             created_at: get_datetime(row, "created_at")?,
             updated_at: get_datetime(row, "updated_at")?,
         })
     }
 }
 
+fn sql_timestamp(ts: DateTime<Utc>) -> String {
+    ts.format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
 /// Create a new message.
-pub async fn create_message(
-    pool: &sqlx::AnyPool,
+///
+/// Takes any executor so it can run inside a transaction alongside the
+/// message's attachment rows.
+#[allow(clippy::too_many_arguments)]
+#[tracing::instrument(skip_all)]
+pub async fn create_message<'e, E>(
+    executor: E,
     id: Uuid,
     channel_id: Uuid,
     author_id: Uuid,
@@ -72,7 +95,11 @@ pub async fn create_message(
     mentions: &[Uuid],
     mention_roles: &[Uuid],
     mention_everyone: bool,
-) -> Result<MessageRow, sqlx::Error> {
+    flags: i32,
+) -> Result<MessageRow, sqlx::Error>
+where
+    E: sqlx::Executor<'e, Database = sqlx::Any>,
+{
     let mentions_json = serde_json::to_string(
         &mentions.iter().map(|x| x.to_string()).collect::<Vec<_>>(),
     )
@@ -96,7 +123,7 @@ pub async fn create_message(
             false, false, '[]', '[]',
             ?, ?, ?,
             ?, ?,
-            0, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP
+            ?, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP
         )
         RETURNING *
         "#,
@@ -111,13 +138,148 @@ pub async fn create_message(
     .bind(mention_everyone)
     .bind(reference_message_id.map(|x| x.to_string()))
     .bind(reference_channel_id.map(|x| x.to_string()))
-    .fetch_one(pool)
+    .bind(flags)
+    .fetch_one(executor)
+    .await
+}
+
+/// Replace a message's denormalized attachment list.
+#[tracing::instrument(skip_all)]
+pub async fn set_attachments<'e, E>(
+    executor: E,
+    id: Uuid,
+    attachments: &serde_json::Value,
+) -> Result<MessageRow, sqlx::Error>
+where
+    E: sqlx::Executor<'e, Database = sqlx::Any>,
+{
+    sqlx::query_as::<_, MessageRow>(
+        "UPDATE messages SET attachments = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ? RETURNING *",
+    )
+    .bind(attachments.to_string())
+    .bind(id.to_string())
+    .fetch_one(executor)
+    .await
+}
+
+/// Create a message posted through an incoming webhook.
+///
+/// `author_id` must be a real user because `messages.author_id` references
+/// `users`; callers pass the system account, and clients render the
+/// webhook's display name and avatar instead.
+#[allow(clippy::too_many_arguments)]
+#[tracing::instrument(skip_all)]
+pub async fn create_webhook_message<'e, E>(
+    executor: E,
+    id: Uuid,
+    channel_id: Uuid,
+    author_id: Uuid,
+    webhook_id: Uuid,
+    content: &str,
+    embeds: &serde_json::Value,
+    username: &str,
+    avatar_url: Option<&str>,
+) -> Result<MessageRow, sqlx::Error>
+where
+    E: sqlx::Executor<'e, Database = sqlx::Any>,
+{
+    sqlx::query_as::<_, MessageRow>(
+        r#"
+        INSERT INTO messages (
+            id, channel_id, author_id, content, message_type,
+            edited, pinned, embeds, attachments,
+            mentions, mention_roles, mention_everyone,
+            flags, webhook_id, webhook_username, webhook_avatar_url,
+            created_at, updated_at
+        )
+        VALUES (
+            ?, ?, ?, ?, 3,
+            false, false, ?, '[]',
+            '[]', '[]', false,
+            0, ?, ?, ?,
+            CURRENT_TIMESTAMP, CURRENT_TIMESTAMP
+        )
+        RETURNING *
+        "#,
+    )
+    .bind(id.to_string())
+    .bind(channel_id.to_string())
+    .bind(author_id.to_string())
+    .bind(content)
+    .bind(embeds.to_string())
+    .bind(webhook_id.to_string())
+    .bind(username)
+    .bind(avatar_url)
+    .fetch_one(executor)
+    .await
+}
+
+/// Copy `source_id` into `channel_id` as a system-attributed repost.
+///
+/// The copy keeps the original author, content, embeds, attachment list
+/// and flags, is marked as a system message (`message_type` 2) and starts
+/// unpinned and with no reactions.
+#[tracing::instrument(skip_all)]
+pub async fn create_repost<'e, E>(
+    executor: E,
+    id: Uuid,
+    channel_id: Uuid,
+    source_id: Uuid,
+    (reference_message_id, reference_channel_id): (Option<Uuid>, Option<Uuid>),
+) -> Result<MessageRow, sqlx::Error>
+where
+    E: sqlx::Executor<'e, Database = sqlx::Any>,
+{
+    sqlx::query_as::<_, MessageRow>(
+        r#"
+        INSERT INTO messages (
+            id, channel_id, author_id, content, message_type,
+            edited, edited_at, pinned, embeds, attachments,
+            mentions, mention_roles, mention_everyone,
+            reference_message_id, reference_channel_id,
+            flags, webhook_id, webhook_username, webhook_avatar_url,
+            created_at, updated_at
+        )
+        SELECT
+            ?, ?, author_id, content, 2,
+            edited, edited_at, false, embeds, attachments,
+            mentions, mention_roles, mention_everyone,
+            ?, ?,
+            flags, webhook_id, webhook_username, webhook_avatar_url,
+            CURRENT_TIMESTAMP, CURRENT_TIMESTAMP
+        FROM messages WHERE id = ?
+        RETURNING *
+        "#,
+    )
+    .bind(id.to_string())
+    .bind(channel_id.to_string())
+    .bind(reference_message_id.map(|x| x.to_string()))
+    .bind(reference_channel_id.map(|x| x.to_string()))
+    .bind(source_id.to_string())
+    .fetch_one(executor)
+    .await
+}
+
+/// Direct replies to `parent_id` within `channel_id`, oldest first.
+#[tracing::instrument(skip_all)]
+pub async fn list_replies(
+    pool: &sqlx::AnyPool,
+    channel_id: Uuid,
+    parent_id: Uuid,
+) -> Result<Vec<MessageRow>, sqlx::Error> {
+    sqlx::query_as::<_, MessageRow>(
+        "SELECT * FROM messages WHERE channel_id = ? AND reference_message_id = ? AND deleted_at IS NULL ORDER BY created_at",
+    )
+    .bind(channel_id.to_string())
+    .bind(parent_id.to_string())
+    .fetch_all(pool)
     .await
 }
 
 /// Find a message by ID.
+#[tracing::instrument(skip_all)]
 pub async fn find_by_id(pool: &sqlx::AnyPool, id: Uuid) -> Result<Option<MessageRow>, sqlx::Error> {
-    sqlx::query_as::<_, MessageRow>("SELECT * FROM messages WHERE id = ?")
+    sqlx::query_as::<_, MessageRow>("SELECT * FROM messages WHERE id = ? AND deleted_at IS NULL")
         .bind(id.to_string())
         .fetch_optional(pool)
         .await
@@ -130,6 +292,7 @@ pub async fn find_by_id(pool: &sqlx::AnyPool, id: Uuid) -> Result<Option<Message
 /// - `limit`: Max messages to return (default 50, max 100)
 ///
 /// Returns messages in reverse chronological order (newest first).
+#[tracing::instrument(skip_all)]
 pub async fn list_channel_messages(
     pool: &sqlx::AnyPool,
     channel_id: Uuid,
@@ -143,7 +306,7 @@ pub async fn list_channel_messages(
         sqlx::query_as::<_, MessageRow>(
             r#"
             SELECT m.* FROM messages m
-            WHERE m.channel_id = ?
+            WHERE m.channel_id = ? AND m.deleted_at IS NULL
               AND m.created_at < (SELECT created_at FROM messages WHERE id = ?)
             ORDER BY m.created_at DESC
             LIMIT ?
@@ -159,7 +322,7 @@ pub async fn list_channel_messages(
             r#"
             SELECT * FROM (
                 SELECT m.* FROM messages m
-                WHERE m.channel_id = ?
+                WHERE m.channel_id = ? AND m.deleted_at IS NULL
                   AND m.created_at > (SELECT created_at FROM messages WHERE id = ?)
                 ORDER BY m.created_at ASC
                 LIMIT ?
@@ -175,7 +338,7 @@ pub async fn list_channel_messages(
         sqlx::query_as::<_, MessageRow>(
             r#"
             SELECT * FROM messages
-            WHERE channel_id = ?
+            WHERE channel_id = ? AND deleted_at IS NULL
             ORDER BY created_at DESC
             LIMIT ?
             "#,
@@ -187,6 +350,48 @@ pub async fn list_channel_messages(
     }
 }
 
+/// A user's messages across every channel, newest first, paged with
+/// `before` like [`list_channel_messages`].
+#[tracing::instrument(skip_all)]
+pub async fn list_by_author(
+    pool: &sqlx::AnyPool,
+    author_id: Uuid,
+    before: Option<Uuid>,
+    limit: i64,
+) -> Result<Vec<MessageRow>, sqlx::Error> {
+    let limit = limit.min(100).max(1);
+
+    if let Some(before_id) = before {
+        sqlx::query_as::<_, MessageRow>(
+            r#"
+            SELECT m.* FROM messages m
+            WHERE m.author_id = ? AND m.deleted_at IS NULL
+              AND m.created_at < (SELECT created_at FROM messages WHERE id = ?)
+            ORDER BY m.created_at DESC
+            LIMIT ?
+            "#,
+        )
+        .bind(author_id.to_string())
+        .bind(before_id.to_string())
+        .bind(limit)
+        .fetch_all(pool)
+        .await
+    } else {
+        sqlx::query_as::<_, MessageRow>(
+            r#"
+            SELECT * FROM messages
+            WHERE author_id = ? AND deleted_at IS NULL
+            ORDER BY created_at DESC
+            LIMIT ?
+            "#,
+        )
+        .bind(author_id.to_string())
+        .bind(limit)
+        .fetch_all(pool)
+        .await
+    }
+}
+
 /// Message row with author username (via JOIN with users table).
 #[derive(Debug)]
 pub struct MessageWithAuthor {
@@ -208,6 +413,10 @@ pub struct MessageWithAuthor {
     pub reference_channel_id: Option<Uuid>,
     pub thread_id: Option<Uuid>,
     pub flags: i32,
+    /// Set when the message was posted through an incoming webhook.
+    pub webhook_id: Option<Uuid>,
+    pub webhook_username: Option<String>,
+    pub webhook_avatar_url: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -234,6 +443,9 @@ impl<'r> sqlx::FromRow<'r, sqlx::any::AnyRow> for MessageWithAuthor {
             reference_channel_id: get_opt_uuid(row, "reference_channel_id")?,
             thread_id: get_opt_uuid(row, "thread_id")?,
             flags: row.try_get("flags")?,
+            webhook_id: get_opt_uuid(row, "webhook_id")?,
+            webhook_username: row.try_get("webhook_username")?,
+            webhook_avatar_url: row.try_get("webhook_avatar_url")?,
             created_at: get_datetime(row, "created_at")?,
             updated_at: get_datetime(row, "updated_at")?,
         })
@@ -241,6 +453,7 @@ impl<'r> sqlx::FromRow<'r, sqlx::any::AnyRow> for MessageWithAuthor {
 }
 
 /// List messages in a channel with author usernames (JOIN users), cursor-based pagination.
+#[tracing::instrument(skip_all)]
 pub async fn list_channel_messages_with_author(
     pool: &sqlx::AnyPool,
     channel_id: Uuid,
@@ -255,7 +468,7 @@ pub async fn list_channel_messages_with_author(
             SELECT m.*, u.username AS author_username
             FROM messages m
             JOIN users u ON u.id = m.author_id
-            WHERE m.channel_id = ?
+            WHERE m.channel_id = ? AND m.deleted_at IS NULL
               AND m.created_at < (SELECT created_at FROM messages WHERE id = ?)
             ORDER BY m.created_at DESC
             LIMIT ?
@@ -273,7 +486,7 @@ pub async fn list_channel_messages_with_author(
                 SELECT m.*, u.username AS author_username
                 FROM messages m
                 JOIN users u ON u.id = m.author_id
-                WHERE m.channel_id = ?
+                WHERE m.channel_id = ? AND m.deleted_at IS NULL
                   AND m.created_at > (SELECT created_at FROM messages WHERE id = ?)
                 ORDER BY m.created_at ASC
                 LIMIT ?
@@ -291,7 +504,7 @@ pub async fn list_channel_messages_with_author(
             SELECT m.*, u.username AS author_username
             FROM messages m
             JOIN users u ON u.id = m.author_id
-            WHERE m.channel_id = ?
+            WHERE m.channel_id = ? AND m.deleted_at IS NULL
             ORDER BY m.created_at DESC
             LIMIT ?
             "#,
@@ -304,11 +517,15 @@ pub async fn list_channel_messages_with_author(
 }
 
 /// Update a message's content (edit).
-pub async fn update_message(
-    pool: &sqlx::AnyPool,
+#[tracing::instrument(skip_all)]
+pub async fn update_message<'e, E>(
+    executor: E,
     id: Uuid,
     content: &str,
-) -> Result<MessageRow, sqlx::Error> {
+) -> Result<MessageRow, sqlx::Error>
+where
+    E: sqlx::Executor<'e, Database = sqlx::Any>,
+{
     sqlx::query_as::<_, MessageRow>(
         r#"
         UPDATE messages SET
@@ -316,42 +533,205 @@ pub async fn update_message(
             edited = true,
             edited_at = CURRENT_TIMESTAMP,
             updated_at = CURRENT_TIMESTAMP
-        WHERE id = ?
+        WHERE id = ? AND deleted_at IS NULL
         RETURNING *
         "#,
     )
     .bind(content)
     .bind(id.to_string())
-    .fetch_one(pool)
+    .fetch_one(executor)
     .await
 }
 
-/// Delete a single message.
-pub async fn delete_message(pool: &sqlx::AnyPool, id: Uuid) -> Result<bool, sqlx::Error> {
-    let result = sqlx::query("DELETE FROM messages WHERE id = ?")
-        .bind(id.to_string())
-        .execute(pool)
-        .await?;
+/// Replace a message's embeds, e.g. once its link previews are ready.
+/// `None` if the message has since been deleted or no longer reads
+/// `content` — the previews were for text that has been edited away.
+#[tracing::instrument(skip_all)]
+pub async fn set_embeds(
+    pool: &sqlx::AnyPool,
+    id: Uuid,
+    content: &str,
+    embeds: &serde_json::Value,
+) -> Result<Option<MessageRow>, sqlx::Error> {
+    sqlx::query_as::<_, MessageRow>(
+        r#"
+        UPDATE messages SET embeds = ?, updated_at = CURRENT_TIMESTAMP
+        WHERE id = ? AND content = ? AND deleted_at IS NULL
+        RETURNING *
+        "#,
+    )
+    .bind(embeds.to_string())
+    .bind(id.to_string())
+    .bind(content)
+    .fetch_optional(pool)
+    .await
+}
+
+/// A message's content before one of its edits.
+#[derive(Debug, Clone)]
+pub struct MessageRevisionRow {
+    pub id: Uuid,
+    pub message_id: Uuid,
+    pub content: String,
+    /// When the edit replaced this content.
+    pub created_at: DateTime<Utc>,
+}
+
+impl<'r> sqlx::FromRow<'r, sqlx::any::AnyRow> for MessageRevisionRow {
+    fn from_row(row: &'r sqlx::any::AnyRow) -> Result<Self, sqlx::Error> {
+        use crate::any_compat::*;
+        Ok(MessageRevisionRow {
+            id: get_uuid(row, "id")?,
+            message_id: get_uuid(row, "message_id")?,
+            content: row.try_get("content")?,
+            created_at: get_datetime(row, "created_at")?,
+        })
+    }
+}
+
+/// Keep `content` as a revision of `message_id` before it is overwritten.
+#[tracing::instrument(skip_all)]
+pub async fn create_revision<'e, E>(
+    executor: E,
+    id: Uuid,
+    message_id: Uuid,
+    content: &str,
+) -> Result<(), sqlx::Error>
+where
+    E: sqlx::Executor<'e, Database = sqlx::Any>,
+{
+    sqlx::query(
+        "INSERT INTO message_revisions (id, message_id, content, created_at) VALUES (?, ?, ?, CURRENT_TIMESTAMP)",
+    )
+    .bind(id.to_string())
+    .bind(message_id.to_string())
+    .bind(content)
+    .execute(executor)
+    .await?;
+    Ok(())
+}
+
+/// A message's earlier contents, oldest first.
+#[tracing::instrument(skip_all)]
+pub async fn list_revisions(
+    pool: &sqlx::AnyPool,
+    message_id: Uuid,
+) -> Result<Vec<MessageRevisionRow>, sqlx::Error> {
+    sqlx::query_as::<_, MessageRevisionRow>(
+        "SELECT * FROM message_revisions WHERE message_id = ? ORDER BY created_at, id",
+    )
+    .bind(message_id.to_string())
+    .fetch_all(pool)
+    .await
+}
+
+/// Drop the edit history of every message in a server's channels.
+#[tracing::instrument(skip_all)]
+pub async fn purge_server_revisions(
+    pool: &sqlx::AnyPool,
+    server_id: Uuid,
+) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        DELETE FROM message_revisions WHERE message_id IN (
+            SELECT m.id FROM messages m
+            JOIN channels c ON c.id = m.channel_id
+            WHERE c.server_id = ?
+        )
+        "#,
+    )
+    .bind(server_id.to_string())
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+/// Delete a single message (soft: it stays for review until purged).
+#[tracing::instrument(skip_all)]
+pub async fn delete_message<'e, E>(executor: E, id: Uuid) -> Result<bool, sqlx::Error>
+where
+    E: sqlx::Executor<'e, Database = sqlx::Any>,
+{
+    let result = sqlx::query(
+        "UPDATE messages SET deleted_at = CURRENT_TIMESTAMP, updated_at = CURRENT_TIMESTAMP WHERE id = ? AND deleted_at IS NULL",
+    )
+    .bind(id.to_string())
+    .execute(executor)
+    .await?;
     Ok(result.rows_affected() > 0)
 }
 
 /// Bulk delete messages (for moderation). Returns count deleted.
+#[tracing::instrument(skip_all)]
 pub async fn bulk_delete_messages(pool: &sqlx::AnyPool, ids: &[Uuid]) -> Result<u64, sqlx::Error> {
     let mut total: u64 = 0;
     for id in ids {
-        let result = sqlx::query("DELETE FROM messages WHERE id = ?")
-            .bind(id.to_string())
-            .execute(pool)
-            .await?;
-        total += result.rows_affected();
+        if delete_message(pool, *id).await? {
+            total += 1;
+        }
     }
     Ok(total)
 }
 
+/// A channel's deleted messages that haven't been purged, most recently
+/// deleted first, for moderator review.
+#[tracing::instrument(skip_all)]
+pub async fn list_deleted(
+    pool: &sqlx::AnyPool,
+    channel_id: Uuid,
+    limit: i64,
+) -> Result<Vec<MessageRow>, sqlx::Error> {
+    sqlx::query_as::<_, MessageRow>(
+        r#"
+        SELECT * FROM messages
+        WHERE channel_id = ? AND deleted_at IS NOT NULL
+        ORDER BY deleted_at DESC, id DESC
+        LIMIT ?
+        "#,
+    )
+    .bind(channel_id.to_string())
+    .bind(limit.clamp(1, 100))
+    .fetch_all(pool)
+    .await
+}
+
+/// Permanently remove messages deleted before `deleted_before`.
+#[tracing::instrument(skip_all)]
+pub async fn purge_deleted(pool: &sqlx::AnyPool, deleted_before: DateTime<Utc>) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM messages WHERE deleted_at IS NOT NULL AND deleted_at < ?")
+        .bind(sql_timestamp(deleted_before))
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected())
+}
+
+/// Permanently remove every message, deleted or not, sent in a server's
+/// channels before `created_before`.
+#[tracing::instrument(skip_all)]
+pub async fn purge_server_messages(
+    pool: &sqlx::AnyPool,
+    server_id: Uuid,
+    created_before: DateTime<Utc>,
+) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        DELETE FROM messages
+        WHERE created_at < ?
+          AND channel_id IN (SELECT id FROM channels WHERE server_id = ?)
+        "#,
+    )
+    .bind(sql_timestamp(created_before))
+    .bind(server_id.to_string())
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
+
 /// Pin a message.
+#[tracing::instrument(skip_all)]
 pub async fn pin_message(pool: &sqlx::AnyPool, id: Uuid) -> Result<MessageRow, sqlx::Error> {
     sqlx::query_as::<_, MessageRow>(
-        "UPDATE messages SET pinned = true, updated_at = CURRENT_TIMESTAMP WHERE id = ? RETURNING *",
+        "UPDATE messages SET pinned = true, updated_at = CURRENT_TIMESTAMP WHERE id = ? AND deleted_at IS NULL RETURNING *",
     )
     .bind(id.to_string())
     .fetch_one(pool)
@@ -359,9 +739,10 @@ pub async fn pin_message(pool: &sqlx::AnyPool, id: Uuid) -> Result<MessageRow, s
 }
 
 /// Unpin a message.
+#[tracing::instrument(skip_all)]
 pub async fn unpin_message(pool: &sqlx::AnyPool, id: Uuid) -> Result<MessageRow, sqlx::Error> {
     sqlx::query_as::<_, MessageRow>(
-        "UPDATE messages SET pinned = false, updated_at = CURRENT_TIMESTAMP WHERE id = ? RETURNING *",
+        "UPDATE messages SET pinned = false, updated_at = CURRENT_TIMESTAMP WHERE id = ? AND deleted_at IS NULL RETURNING *",
     )
     .bind(id.to_string())
     .fetch_one(pool)
@@ -369,12 +750,13 @@ pub async fn unpin_message(pool: &sqlx::AnyPool, id: Uuid) -> Result<MessageRow,
 }
 
 /// Get pinned messages in a channel.
+#[tracing::instrument(skip_all)]
 pub async fn get_pinned_messages(
     pool: &sqlx::AnyPool,
     channel_id: Uuid,
 ) -> Result<Vec<MessageRow>, sqlx::Error> {
     sqlx::query_as::<_, MessageRow>(
-        "SELECT * FROM messages WHERE channel_id = ? AND pinned = true ORDER BY created_at DESC",
+        "SELECT * FROM messages WHERE channel_id = ? AND pinned = true AND deleted_at IS NULL ORDER BY created_at DESC",
     )
     .bind(channel_id.to_string())
     .fetch_all(pool)
@@ -382,6 +764,7 @@ pub async fn get_pinned_messages(
 }
 
 /// Search messages using full-text search (PostgreSQL only; returns empty for SQLite).
+#[tracing::instrument(skip_all)]
 pub async fn search_messages(
     pool: &sqlx::AnyPool,
     channel_id: Option<Uuid>,
@@ -395,7 +778,7 @@ pub async fn search_messages(
         sqlx::query_as::<_, MessageRow>(
             r#"
             SELECT * FROM messages
-            WHERE channel_id = ?
+            WHERE channel_id = ? AND deleted_at IS NULL
               AND search_vector @@ plainto_tsquery('english', ?)
             ORDER BY ts_rank(search_vector, plainto_tsquery('english', ?)) DESC, created_at DESC
             LIMIT ? OFFSET ?
@@ -412,7 +795,8 @@ pub async fn search_messages(
         sqlx::query_as::<_, MessageRow>(
             r#"
             SELECT * FROM messages
-            WHERE search_vector @@ plainto_tsquery('english', ?)
+            WHERE deleted_at IS NULL
+              AND search_vector @@ plainto_tsquery('english', ?)
             ORDER BY ts_rank(search_vector, plainto_tsquery('english', ?)) DESC, created_at DESC
             LIMIT ? OFFSET ?
             "#,
@@ -427,13 +811,36 @@ pub async fn search_messages(
 }
 
 /// Count messages in a channel (for stats).
+#[tracing::instrument(skip_all)]
 pub async fn count_channel_messages(
     pool: &sqlx::AnyPool,
     channel_id: Uuid,
 ) -> Result<i64, sqlx::Error> {
-    let row: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM messages WHERE channel_id = ?")
+    let row: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM messages WHERE channel_id = ? AND deleted_at IS NULL")
         .bind(channel_id.to_string())
         .fetch_one(pool)
         .await?;
     Ok(row.0)
 }
+
+/// Whether a user has ever posted in any channel of a server.
+#[tracing::instrument(skip_all)]
+pub async fn has_posted_in_server(
+    pool: &sqlx::AnyPool,
+    author_id: Uuid,
+    server_id: Uuid,
+) -> Result<bool, sqlx::Error> {
+    let row = sqlx::query(
+        r#"
+        SELECT 1 FROM messages m
+        JOIN channels c ON c.id = m.channel_id
+        WHERE m.author_id = ? AND c.server_id = ?
+        LIMIT 1
+        "#,
+    )
+    .bind(author_id.to_string())
+    .bind(server_id.to_string())
+    .fetch_optional(pool)
+    .await?;
+    Ok(row.is_some())
+}
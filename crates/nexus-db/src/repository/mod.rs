@@ -1,18 +1,58 @@
 //! Repository layer — query functions organized by domain.
+//!
+//! Every public query function runs in a span of its own
+//! (`#[tracing::instrument(skip_all)]`), so slow queries stand out in traces.
 
+pub mod activitypub;
+pub mod admin;
 pub mod attachments;
+pub mod audit_log;
+pub mod bans;
+pub mod blocks;
 pub mod bots;
+pub mod channel_mutes;
 pub mod channels;
+pub mod data_exports;
 pub mod emoji;
+pub mod federated_rooms;
+pub mod federated_servers;
+pub mod federated_users;
+pub mod federation_outbox;
+pub mod federation_txn_log;
+pub mod forums;
+pub mod instance_settings;
 pub mod keystore;
+pub mod legal_consents;
 pub mod members;
+pub mod media_jobs;
 pub mod messages;
+pub mod moderation_queue;
+pub mod oauth2;
 pub mod plugins;
+pub mod push;
 pub mod reactions;
+pub mod reminders;
 pub mod read_states;
+pub mod registration;
 pub mod roles;
+pub mod scheduled_events;
+pub mod security_events;
 pub mod servers;
+pub mod sessions;
+pub mod settings_history;
 pub mod slash_commands;
+pub mod starboard;
+pub mod status;
 pub mod threads;
+pub mod transcripts;
+pub mod user_settings;
 pub mod users;
+pub mod voice_chat;
 pub mod webhooks;
+pub mod welcome;
+
+/// `?, ?, ?` — placeholders for an `IN (...)` list of `n` values. Callers
+/// keep lists to a few hundred values, well under any bind limit.
+pub(crate) fn placeholders(n: usize) -> String {
+    vec!["?"; n].join(", ")
+}
@@ -0,0 +1,210 @@
+//! Moderation queue repository — per-channel pre-moderation settings and
+//! messages held for review.
+
+use chrono::{DateTime, Utc};
+use sqlx::Row;
+use uuid::Uuid;
+
+#[derive(Debug, Clone)]
+pub struct ModerationSettingsRow {
+    pub channel_id: Uuid,
+    pub hold_new_members: bool,
+    pub new_member_minutes: i32,
+    pub hold_links: bool,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl<'r> sqlx::FromRow<'r, sqlx::any::AnyRow> for ModerationSettingsRow {
+    fn from_row(row: &'r sqlx::any::AnyRow) -> Result<Self, sqlx::Error> {
+        use crate::any_compat::*;
+        Ok(ModerationSettingsRow {
+            channel_id: get_uuid(row, "channel_id")?,
+            hold_new_members: row.try_get("hold_new_members")?,
+            new_member_minutes: row.try_get("new_member_minutes")?,
+            hold_links: row.try_get("hold_links")?,
+            updated_at: get_datetime(row, "updated_at")?,
+        })
+    }
+}
+
+/// A message waiting for moderator approval.
+#[derive(Debug, Clone)]
+pub struct PendingMessageRow {
+    pub id: Uuid,
+    pub channel_id: Uuid,
+    pub server_id: Uuid,
+    pub author_id: Uuid,
+    pub content: String,
+    pub reference_message_id: Option<Uuid>,
+    pub reference_channel_id: Option<Uuid>,
+    pub flags: i32,
+    pub attachment_ids: Vec<Uuid>,
+    pub spoiler_attachment_ids: Vec<Uuid>,
+    pub reason: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl<'r> sqlx::FromRow<'r, sqlx::any::AnyRow> for PendingMessageRow {
+    fn from_row(row: &'r sqlx::any::AnyRow) -> Result<Self, sqlx::Error> {
+        use crate::any_compat::*;
+        Ok(PendingMessageRow {
+            id: get_uuid(row, "id")?,
+            channel_id: get_uuid(row, "channel_id")?,
+            server_id: get_uuid(row, "server_id")?,
+            author_id: get_uuid(row, "author_id")?,
+            content: row.try_get("content")?,
+            reference_message_id: get_opt_uuid(row, "reference_message_id")?,
+            reference_channel_id: get_opt_uuid(row, "reference_channel_id")?,
+            flags: row.try_get("flags")?,
+            attachment_ids: get_uuid_vec(row, "attachment_ids")?,
+            spoiler_attachment_ids: get_uuid_vec(row, "spoiler_attachment_ids")?,
+            reason: row.try_get("reason")?,
+            created_at: get_datetime(row, "created_at")?,
+        })
+    }
+}
+
+// ============================================================
+// Settings
+// ============================================================
+
+/// Fetch a channel's pre-moderation settings. `None` means it's off.
+#[tracing::instrument(skip_all)]
+pub async fn get_settings(
+    pool: &sqlx::AnyPool,
+    channel_id: Uuid,
+) -> Result<Option<ModerationSettingsRow>, sqlx::Error> {
+    sqlx::query_as::<_, ModerationSettingsRow>(
+        "SELECT * FROM channel_moderation_settings WHERE channel_id = ?",
+    )
+    .bind(channel_id.to_string())
+    .fetch_optional(pool)
+    .await
+}
+
+/// Insert or replace a channel's pre-moderation settings.
+#[tracing::instrument(skip_all)]
+pub async fn upsert_settings(
+    pool: &sqlx::AnyPool,
+    settings: &ModerationSettingsRow,
+) -> Result<ModerationSettingsRow, sqlx::Error> {
+    sqlx::query_as::<_, ModerationSettingsRow>(
+        r#"
+        INSERT INTO channel_moderation_settings (
+            channel_id, hold_new_members, new_member_minutes, hold_links, updated_at
+        )
+        VALUES (?, ?, ?, ?, CURRENT_TIMESTAMP)
+        ON CONFLICT (channel_id) DO UPDATE SET
+            hold_new_members = excluded.hold_new_members,
+            new_member_minutes = excluded.new_member_minutes,
+            hold_links = excluded.hold_links,
+            updated_at = CURRENT_TIMESTAMP
+        RETURNING *
+        "#,
+    )
+    .bind(settings.channel_id.to_string())
+    .bind(settings.hold_new_members)
+    .bind(settings.new_member_minutes)
+    .bind(settings.hold_links)
+    .fetch_one(pool)
+    .await
+}
+
+// ============================================================
+// Pending messages
+// ============================================================
+
+/// Hold a message for review.
+#[tracing::instrument(skip_all)]
+pub async fn create_pending(
+    pool: &sqlx::AnyPool,
+    pending: &PendingMessageRow,
+) -> Result<PendingMessageRow, sqlx::Error> {
+    let id_list = |ids: &[Uuid]| {
+        serde_json::to_string(&ids.iter().map(|a| a.to_string()).collect::<Vec<_>>())
+            .unwrap_or_else(|_| "[]".to_string())
+    };
+
+    sqlx::query_as::<_, PendingMessageRow>(
+        r#"
+        INSERT INTO pending_messages (
+            id, channel_id, server_id, author_id, content,
+            reference_message_id, reference_channel_id, flags,
+            attachment_ids, spoiler_attachment_ids, reason, created_at
+        )
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, CURRENT_TIMESTAMP)
+        RETURNING *
+        "#,
+    )
+    .bind(pending.id.to_string())
+    .bind(pending.channel_id.to_string())
+    .bind(pending.server_id.to_string())
+    .bind(pending.author_id.to_string())
+    .bind(&pending.content)
+    .bind(pending.reference_message_id.map(|u| u.to_string()))
+    .bind(pending.reference_channel_id.map(|u| u.to_string()))
+    .bind(pending.flags)
+    .bind(id_list(&pending.attachment_ids))
+    .bind(id_list(&pending.spoiler_attachment_ids))
+    .bind(&pending.reason)
+    .fetch_one(pool)
+    .await
+}
+
+/// Held messages in a server, oldest first, optionally for one channel.
+#[tracing::instrument(skip_all)]
+pub async fn list_pending(
+    pool: &sqlx::AnyPool,
+    server_id: Uuid,
+    channel_id: Option<Uuid>,
+    limit: i64,
+) -> Result<Vec<PendingMessageRow>, sqlx::Error> {
+    match channel_id {
+        Some(channel_id) => {
+            sqlx::query_as::<_, PendingMessageRow>(
+                r#"
+                SELECT * FROM pending_messages
+                WHERE server_id = ? AND channel_id = ?
+                ORDER BY created_at
+                LIMIT ?
+                "#,
+            )
+            .bind(server_id.to_string())
+            .bind(channel_id.to_string())
+            .bind(limit)
+            .fetch_all(pool)
+            .await
+        }
+        None => {
+            sqlx::query_as::<_, PendingMessageRow>(
+                "SELECT * FROM pending_messages WHERE server_id = ? ORDER BY created_at LIMIT ?",
+            )
+            .bind(server_id.to_string())
+            .bind(limit)
+            .fetch_all(pool)
+            .await
+        }
+    }
+}
+
+/// Remove a held message from the queue and return it.
+///
+/// Returns `None` if it was already resolved, so concurrent approve/reject
+/// calls can't both succeed.
+#[tracing::instrument(skip_all)]
+pub async fn take_pending<'e, E>(
+    executor: E,
+    server_id: Uuid,
+    id: Uuid,
+) -> Result<Option<PendingMessageRow>, sqlx::Error>
+where
+    E: sqlx::Executor<'e, Database = sqlx::Any>,
+{
+    sqlx::query_as::<_, PendingMessageRow>(
+        "DELETE FROM pending_messages WHERE id = ? AND server_id = ? RETURNING *",
+    )
+    .bind(id.to_string())
+    .bind(server_id.to_string())
+    .fetch_optional(executor)
+    .await
+}
@@ -0,0 +1,218 @@
+//! OAuth2 provider — authorization codes and the access / refresh token
+//! pairs issued for them. Codes and tokens are stored as SHA-256 hashes.
+
+use chrono::{DateTime, Utc};
+use sqlx::Row;
+use uuid::Uuid;
+
+/// A code from `/oauth2/authorize`, not yet exchanged.
+#[derive(Debug, Clone)]
+pub struct AuthorizationCode {
+    pub application_id: Uuid,
+    pub user_id: Uuid,
+    pub redirect_uri: String,
+    pub scopes: Vec<String>,
+    pub code_challenge: Option<String>,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl<'r> sqlx::FromRow<'r, sqlx::any::AnyRow> for AuthorizationCode {
+    fn from_row(row: &'r sqlx::any::AnyRow) -> Result<Self, sqlx::Error> {
+        use crate::any_compat::*;
+        Ok(AuthorizationCode {
+            application_id: get_uuid(row, "application_id")?,
+            user_id: get_uuid(row, "user_id")?,
+            redirect_uri: row.try_get("redirect_uri")?,
+            scopes: get_string_vec(row, "scopes")?,
+            code_challenge: row.try_get("code_challenge")?,
+            expires_at: get_datetime(row, "expires_at")?,
+        })
+    }
+}
+
+/// An issued token pair.
+#[derive(Debug, Clone)]
+pub struct OAuth2Token {
+    pub id: Uuid,
+    pub application_id: Uuid,
+    pub user_id: Uuid,
+    pub scopes: Vec<String>,
+    /// When the access token expires; the refresh token doesn't.
+    pub expires_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl<'r> sqlx::FromRow<'r, sqlx::any::AnyRow> for OAuth2Token {
+    fn from_row(row: &'r sqlx::any::AnyRow) -> Result<Self, sqlx::Error> {
+        use crate::any_compat::*;
+        Ok(OAuth2Token {
+            id: get_uuid(row, "id")?,
+            application_id: get_uuid(row, "application_id")?,
+            user_id: get_uuid(row, "user_id")?,
+            scopes: get_string_vec(row, "scopes")?,
+            expires_at: get_datetime(row, "expires_at")?,
+            created_at: get_datetime(row, "created_at")?,
+        })
+    }
+}
+
+fn sql_timestamp(ts: DateTime<Utc>) -> String {
+    ts.format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+fn scopes_json(scopes: &[String]) -> String {
+    serde_json::to_string(scopes).unwrap_or_else(|_| "[]".into())
+}
+
+// ============================================================================
+// Authorization codes
+// ============================================================================
+
+/// Store a new code, clearing out expired ones.
+#[tracing::instrument(skip_all)]
+pub async fn create_code(
+    pool: &sqlx::AnyPool,
+    code_hash: &str,
+    application_id: Uuid,
+    user_id: Uuid,
+    redirect_uri: &str,
+    scopes: &[String],
+    code_challenge: Option<&str>,
+    expires_at: DateTime<Utc>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM oauth2_authorization_codes WHERE expires_at < ?")
+        .bind(sql_timestamp(Utc::now()))
+        .execute(pool)
+        .await?;
+    sqlx::query(
+        r#"
+        INSERT INTO oauth2_authorization_codes
+            (code_hash, application_id, user_id, redirect_uri, scopes, code_challenge, expires_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(code_hash)
+    .bind(application_id.to_string())
+    .bind(user_id.to_string())
+    .bind(redirect_uri)
+    .bind(scopes_json(scopes))
+    .bind(code_challenge)
+    .bind(sql_timestamp(expires_at))
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Remove and return a code, so it can only be exchanged once. Expiry is
+/// left to the caller.
+#[tracing::instrument(skip_all)]
+pub async fn take_code(
+    pool: &sqlx::AnyPool,
+    code_hash: &str,
+) -> Result<Option<AuthorizationCode>, sqlx::Error> {
+    sqlx::query_as::<_, AuthorizationCode>(
+        "DELETE FROM oauth2_authorization_codes WHERE code_hash = ? RETURNING *",
+    )
+    .bind(code_hash)
+    .fetch_optional(pool)
+    .await
+}
+
+// ============================================================================
+// Tokens
+// ============================================================================
+
+#[tracing::instrument(skip_all)]
+pub async fn create_token(
+    pool: &sqlx::AnyPool,
+    id: Uuid,
+    application_id: Uuid,
+    user_id: Uuid,
+    access_token_hash: &str,
+    refresh_token_hash: &str,
+    scopes: &[String],
+    expires_at: DateTime<Utc>,
+) -> Result<OAuth2Token, sqlx::Error> {
+    sqlx::query_as::<_, OAuth2Token>(
+        r#"
+        INSERT INTO oauth2_tokens
+            (id, application_id, user_id, access_token_hash, refresh_token_hash, scopes, expires_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?)
+        RETURNING *
+        "#,
+    )
+    .bind(id.to_string())
+    .bind(application_id.to_string())
+    .bind(user_id.to_string())
+    .bind(access_token_hash)
+    .bind(refresh_token_hash)
+    .bind(scopes_json(scopes))
+    .bind(sql_timestamp(expires_at))
+    .fetch_one(pool)
+    .await
+}
+
+/// The token pair an unexpired access token belongs to.
+#[tracing::instrument(skip_all)]
+pub async fn find_by_access_token(
+    pool: &sqlx::AnyPool,
+    access_token_hash: &str,
+) -> Result<Option<OAuth2Token>, sqlx::Error> {
+    sqlx::query_as::<_, OAuth2Token>(
+        "SELECT * FROM oauth2_tokens WHERE access_token_hash = ? AND expires_at > ?",
+    )
+    .bind(access_token_hash)
+    .bind(sql_timestamp(Utc::now()))
+    .fetch_optional(pool)
+    .await
+}
+
+/// Replace both tokens of `application_id`'s pair with refresh token
+/// `refresh_token_hash`. The old tokens stop working at once; `None` if
+/// there was no such pair.
+#[tracing::instrument(skip_all)]
+pub async fn rotate(
+    pool: &sqlx::AnyPool,
+    application_id: Uuid,
+    refresh_token_hash: &str,
+    new_access_token_hash: &str,
+    new_refresh_token_hash: &str,
+    expires_at: DateTime<Utc>,
+) -> Result<Option<OAuth2Token>, sqlx::Error> {
+    sqlx::query_as::<_, OAuth2Token>(
+        r#"
+        UPDATE oauth2_tokens
+        SET access_token_hash = ?, refresh_token_hash = ?, expires_at = ?
+        WHERE refresh_token_hash = ? AND application_id = ?
+        RETURNING *
+        "#,
+    )
+    .bind(new_access_token_hash)
+    .bind(new_refresh_token_hash)
+    .bind(sql_timestamp(expires_at))
+    .bind(refresh_token_hash)
+    .bind(application_id.to_string())
+    .fetch_optional(pool)
+    .await
+}
+
+/// Revoke the pair `token_hash` (access or refresh) belongs to.
+#[tracing::instrument(skip_all)]
+pub async fn revoke(
+    pool: &sqlx::AnyPool,
+    application_id: Uuid,
+    token_hash: &str,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        DELETE FROM oauth2_tokens
+        WHERE application_id = ? AND (access_token_hash = ? OR refresh_token_hash = ?)
+        "#,
+    )
+    .bind(application_id.to_string())
+    .bind(token_hash)
+    .bind(token_hash)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
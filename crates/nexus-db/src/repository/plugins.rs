@@ -79,6 +79,7 @@ fn row_to_user_theme(row: &sqlx::any::AnyRow) -> UserThemeInstall {
 // Plugins
 // ============================================================================
 
+#[tracing::instrument(skip_all)]
 pub async fn list_plugins(pool: &sqlx::AnyPool, limit: i64, offset: i64) -> Result<Vec<ClientPlugin>> {
     let rows = sqlx::query(
         "SELECT * FROM client_plugins WHERE verified = true ORDER BY install_count DESC LIMIT ? OFFSET ?",
@@ -90,6 +91,7 @@ pub async fn list_plugins(pool: &sqlx::AnyPool, limit: i64, offset: i64) -> Resu
     Ok(rows.iter().map(row_to_plugin).collect())
 }
 
+#[tracing::instrument(skip_all)]
 pub async fn get_plugin_by_id(pool: &sqlx::AnyPool, plugin_id: Uuid) -> Result<Option<ClientPlugin>> {
     let row = sqlx::query("SELECT * FROM client_plugins WHERE id = ?")
         .bind(plugin_id.to_string())
@@ -98,6 +100,7 @@ pub async fn get_plugin_by_id(pool: &sqlx::AnyPool, plugin_id: Uuid) -> Result<O
     Ok(row.as_ref().map(row_to_plugin))
 }
 
+#[tracing::instrument(skip_all)]
 pub async fn get_plugin_by_slug(pool: &sqlx::AnyPool, slug: &str) -> Result<Option<ClientPlugin>> {
     let row = sqlx::query("SELECT * FROM client_plugins WHERE slug = ?")
         .bind(slug)
@@ -106,6 +109,7 @@ pub async fn get_plugin_by_slug(pool: &sqlx::AnyPool, slug: &str) -> Result<Opti
     Ok(row.as_ref().map(row_to_plugin))
 }
 
+#[tracing::instrument(skip_all)]
 pub async fn create_plugin(
     pool: &sqlx::AnyPool,
     id: Uuid,
@@ -143,6 +147,7 @@ pub async fn create_plugin(
 // User Plugin Installs
 // ============================================================================
 
+#[tracing::instrument(skip_all)]
 pub async fn get_user_plugins(pool: &sqlx::AnyPool, user_id: Uuid) -> Result<Vec<UserPluginInstall>> {
     let rows = sqlx::query(
         "SELECT * FROM user_plugin_installs WHERE user_id = ? ORDER BY installed_at DESC",
@@ -153,6 +158,7 @@ pub async fn get_user_plugins(pool: &sqlx::AnyPool, user_id: Uuid) -> Result<Vec
     Ok(rows.iter().map(|r| row_to_user_plugin(r).0).collect())
 }
 
+#[tracing::instrument(skip_all)]
 pub async fn install_plugin(
     pool: &sqlx::AnyPool,
     user_id: Uuid,
@@ -175,6 +181,7 @@ pub async fn install_plugin(
     Ok(row_to_user_plugin(&row).0)
 }
 
+#[tracing::instrument(skip_all)]
 pub async fn update_plugin_install(
     pool: &sqlx::AnyPool,
     user_id: Uuid,
@@ -199,6 +206,7 @@ pub async fn update_plugin_install(
     Ok(row.as_ref().map(|r| row_to_user_plugin(r).0))
 }
 
+#[tracing::instrument(skip_all)]
 pub async fn uninstall_plugin(pool: &sqlx::AnyPool, user_id: Uuid, plugin_id: Uuid) -> Result<bool> {
     let result = sqlx::query(
         "DELETE FROM user_plugin_installs WHERE user_id = ? AND plugin_id = ?",
@@ -214,6 +222,7 @@ pub async fn uninstall_plugin(pool: &sqlx::AnyPool, user_id: Uuid, plugin_id: Uu
 // Themes
 // ============================================================================
 
+#[tracing::instrument(skip_all)]
 pub async fn list_themes(pool: &sqlx::AnyPool, limit: i64, offset: i64) -> Result<Vec<Theme>> {
     let rows = sqlx::query(
         "SELECT * FROM themes WHERE verified = true ORDER BY install_count DESC LIMIT ? OFFSET ?",
@@ -225,6 +234,7 @@ pub async fn list_themes(pool: &sqlx::AnyPool, limit: i64, offset: i64) -> Resul
     Ok(rows.iter().map(row_to_theme).collect())
 }
 
+#[tracing::instrument(skip_all)]
 pub async fn get_theme_by_id(pool: &sqlx::AnyPool, theme_id: Uuid) -> Result<Option<Theme>> {
     let row = sqlx::query("SELECT * FROM themes WHERE id = ?")
         .bind(theme_id.to_string())
@@ -233,6 +243,7 @@ pub async fn get_theme_by_id(pool: &sqlx::AnyPool, theme_id: Uuid) -> Result<Opt
     Ok(row.as_ref().map(row_to_theme))
 }
 
+#[tracing::instrument(skip_all)]
 pub async fn get_theme_by_slug(pool: &sqlx::AnyPool, slug: &str) -> Result<Option<Theme>> {
     let row = sqlx::query("SELECT * FROM themes WHERE slug = ?")
         .bind(slug)
@@ -241,6 +252,7 @@ pub async fn get_theme_by_slug(pool: &sqlx::AnyPool, slug: &str) -> Result<Optio
     Ok(row.as_ref().map(row_to_theme))
 }
 
+#[tracing::instrument(skip_all)]
 pub async fn create_theme(
     pool: &sqlx::AnyPool,
     id: Uuid,
@@ -277,6 +289,7 @@ pub async fn create_theme(
 // User Theme Installs
 // ============================================================================
 
+#[tracing::instrument(skip_all)]
 pub async fn get_user_themes(pool: &sqlx::AnyPool, user_id: Uuid) -> Result<Vec<UserThemeInstall>> {
     let rows = sqlx::query(
         "SELECT * FROM user_theme_installs WHERE user_id = ? ORDER BY installed_at DESC",
@@ -287,6 +300,7 @@ pub async fn get_user_themes(pool: &sqlx::AnyPool, user_id: Uuid) -> Result<Vec<
     Ok(rows.iter().map(row_to_user_theme).collect())
 }
 
+#[tracing::instrument(skip_all)]
 pub async fn install_theme(
     pool: &sqlx::AnyPool,
     user_id: Uuid,
@@ -309,6 +323,7 @@ pub async fn install_theme(
     Ok(row_to_user_theme(&row))
 }
 
+#[tracing::instrument(skip_all)]
 pub async fn activate_theme(pool: &sqlx::AnyPool, user_id: Uuid, theme_id: Uuid) -> Result<bool> {
     sqlx::query(
         "UPDATE user_theme_installs SET active = false WHERE user_id = ?",
@@ -326,6 +341,7 @@ pub async fn activate_theme(pool: &sqlx::AnyPool, user_id: Uuid, theme_id: Uuid)
     Ok(result.rows_affected() > 0)
 }
 
+#[tracing::instrument(skip_all)]
 pub async fn uninstall_theme(pool: &sqlx::AnyPool, user_id: Uuid, theme_id: Uuid) -> Result<bool> {
     let result =
         sqlx::query("DELETE FROM user_theme_installs WHERE user_id = ? AND theme_id = ?")
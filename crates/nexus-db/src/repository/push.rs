@@ -0,0 +1,124 @@
+//! Push repository — device push tokens and notification preferences.
+
+use nexus_common::models::push::{PushPlatform, PushPreferences, PushToken};
+use uuid::Uuid;
+
+/// Register a device, or re-register it for `user_id` if the same token was
+/// already known (e.g. after logging into another account on that device).
+#[allow(clippy::too_many_arguments)]
+#[tracing::instrument(skip_all)]
+pub async fn upsert_token(
+    pool: &sqlx::AnyPool,
+    id: Uuid,
+    user_id: Uuid,
+    platform: PushPlatform,
+    token: &str,
+    p256dh: Option<&str>,
+    auth: Option<&str>,
+    device_name: Option<&str>,
+) -> Result<PushToken, sqlx::Error> {
+    sqlx::query_as::<_, PushToken>(
+        r#"
+        INSERT INTO push_tokens (id, user_id, platform, token, p256dh, auth, device_name, created_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?, CURRENT_TIMESTAMP)
+        ON CONFLICT (platform, token) DO UPDATE SET
+            user_id = excluded.user_id,
+            p256dh = excluded.p256dh,
+            auth = excluded.auth,
+            device_name = excluded.device_name
+        RETURNING *
+        "#,
+    )
+    .bind(id.to_string())
+    .bind(user_id.to_string())
+    .bind(platform.as_str())
+    .bind(token)
+    .bind(p256dh)
+    .bind(auth)
+    .bind(device_name)
+    .fetch_one(pool)
+    .await
+}
+
+/// All devices a user has registered.
+#[tracing::instrument(skip_all)]
+pub async fn list_tokens(pool: &sqlx::AnyPool, user_id: Uuid) -> Result<Vec<PushToken>, sqlx::Error> {
+    sqlx::query_as::<_, PushToken>(
+        "SELECT * FROM push_tokens WHERE user_id = ? ORDER BY created_at",
+    )
+    .bind(user_id.to_string())
+    .fetch_all(pool)
+    .await
+}
+
+/// Remove one of a user's devices. Returns whether it existed.
+#[tracing::instrument(skip_all)]
+pub async fn delete_token(pool: &sqlx::AnyPool, user_id: Uuid, id: Uuid) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM push_tokens WHERE id = ? AND user_id = ?")
+        .bind(id.to_string())
+        .bind(user_id.to_string())
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Drop a token the push service reported as expired or unregistered.
+#[tracing::instrument(skip_all)]
+pub async fn delete_token_by_id(pool: &sqlx::AnyPool, id: Uuid) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM push_tokens WHERE id = ?")
+        .bind(id.to_string())
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Record a successful delivery to a device.
+#[tracing::instrument(skip_all)]
+pub async fn touch_token(pool: &sqlx::AnyPool, id: Uuid) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE push_tokens SET last_used_at = CURRENT_TIMESTAMP WHERE id = ?")
+        .bind(id.to_string())
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// A user's push preferences, or the defaults if they never changed them.
+#[tracing::instrument(skip_all)]
+pub async fn get_preferences(pool: &sqlx::AnyPool, user_id: Uuid) -> Result<PushPreferences, sqlx::Error> {
+    let prefs = sqlx::query_as::<_, PushPreferences>(
+        "SELECT * FROM push_preferences WHERE user_id = ?",
+    )
+    .bind(user_id.to_string())
+    .fetch_optional(pool)
+    .await?;
+    Ok(prefs.unwrap_or_default())
+}
+
+/// Store a user's push preferences.
+#[tracing::instrument(skip_all)]
+pub async fn set_preferences(
+    pool: &sqlx::AnyPool,
+    user_id: Uuid,
+    prefs: &PushPreferences,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO push_preferences (user_id, enabled, direct_messages, mentions, show_content, updated_at)
+        VALUES (?, ?, ?, ?, ?, CURRENT_TIMESTAMP)
+        ON CONFLICT (user_id) DO UPDATE SET
+            enabled = excluded.enabled,
+            direct_messages = excluded.direct_messages,
+            mentions = excluded.mentions,
+            show_content = excluded.show_content,
+            updated_at = CURRENT_TIMESTAMP
+        "#,
+    )
+    .bind(user_id.to_string())
+    .bind(prefs.enabled)
+    .bind(prefs.direct_messages)
+    .bind(prefs.mentions)
+    .bind(prefs.show_content)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
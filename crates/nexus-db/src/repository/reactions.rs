@@ -2,8 +2,11 @@
 
 use chrono::{DateTime, Utc};
 use sqlx::Row;
+use std::collections::HashMap;
 use uuid::Uuid;
 
+use super::placeholders;
+
 /// A reaction row from the database.
 #[derive(Debug)]
 pub struct ReactionRow {
@@ -33,6 +36,7 @@ pub struct ReactionCount {
 }
 
 /// Add a reaction to a message. Returns true if newly added, false if already exists.
+#[tracing::instrument(skip_all)]
 pub async fn add_reaction(
     pool: &sqlx::AnyPool,
     message_id: Uuid,
@@ -55,6 +59,7 @@ pub async fn add_reaction(
 }
 
 /// Remove a reaction from a message.
+#[tracing::instrument(skip_all)]
 pub async fn remove_reaction(
     pool: &sqlx::AnyPool,
     message_id: Uuid,
@@ -73,6 +78,7 @@ pub async fn remove_reaction(
 }
 
 /// Remove all reactions of a specific emoji from a message (moderation).
+#[tracing::instrument(skip_all)]
 pub async fn remove_all_reactions_for_emoji(
     pool: &sqlx::AnyPool,
     message_id: Uuid,
@@ -89,6 +95,7 @@ pub async fn remove_all_reactions_for_emoji(
 }
 
 /// Remove ALL reactions from a message.
+#[tracing::instrument(skip_all)]
 pub async fn remove_all_reactions(
     pool: &sqlx::AnyPool,
     message_id: Uuid,
@@ -101,6 +108,7 @@ pub async fn remove_all_reactions(
 }
 
 /// Get reaction counts for a message, grouped by emoji.
+#[tracing::instrument(skip_all)]
 pub async fn get_reaction_counts(
     pool: &sqlx::AnyPool,
     message_id: Uuid,
@@ -119,7 +127,72 @@ pub async fn get_reaction_counts(
     .await
 }
 
+/// Reaction counts for several messages in one query, keyed by message.
+/// Messages without reactions are left out.
+#[tracing::instrument(skip_all)]
+pub async fn get_reaction_counts_bulk(
+    pool: &sqlx::AnyPool,
+    message_ids: &[Uuid],
+) -> Result<HashMap<Uuid, Vec<ReactionCount>>, sqlx::Error> {
+    let mut counts: HashMap<Uuid, Vec<ReactionCount>> = HashMap::new();
+    if message_ids.is_empty() {
+        return Ok(counts);
+    }
+    let sql = format!(
+        r#"
+        SELECT message_id, emoji, COUNT(*) as count
+        FROM reactions
+        WHERE message_id IN ({})
+        GROUP BY message_id, emoji
+        ORDER BY message_id, MIN(created_at) ASC
+        "#,
+        placeholders(message_ids.len())
+    );
+    let mut query = sqlx::query_as::<_, (String, String, i64)>(&sql);
+    for id in message_ids {
+        query = query.bind(id.to_string());
+    }
+    for (message_id, emoji, count) in query.fetch_all(pool).await? {
+        if let Ok(message_id) = message_id.parse() {
+            counts
+                .entry(message_id)
+                .or_default()
+                .push(ReactionCount { emoji, count });
+        }
+    }
+    Ok(counts)
+}
+
+/// The emojis `user_id` has reacted with on each of several messages, in
+/// one query. Messages they haven't reacted to are left out.
+#[tracing::instrument(skip_all)]
+pub async fn get_user_reactions_bulk(
+    pool: &sqlx::AnyPool,
+    message_ids: &[Uuid],
+    user_id: Uuid,
+) -> Result<HashMap<Uuid, Vec<String>>, sqlx::Error> {
+    let mut reacted: HashMap<Uuid, Vec<String>> = HashMap::new();
+    if message_ids.is_empty() {
+        return Ok(reacted);
+    }
+    let sql = format!(
+        "SELECT message_id, emoji FROM reactions WHERE user_id = ? AND message_id IN ({})",
+        placeholders(message_ids.len())
+    );
+    let mut query = sqlx::query_as::<_, (String, String)>(&sql).bind(user_id.to_string());
+    for id in message_ids {
+        query = query.bind(id.to_string());
+    }
+    for (message_id, emoji) in query.fetch_all(pool).await? {
+        if let Ok(message_id) = message_id.parse() {
+            reacted.entry(message_id).or_default().push(emoji);
+        }
+    }
+    Ok(reacted)
+}
+
 /// Check if a specific user has reacted with a specific emoji.
+#[tracing::instrument(skip_all)]
 pub async fn has_user_reacted(
     pool: &sqlx::AnyPool,
     message_id: Uuid,
@@ -138,6 +211,7 @@ pub async fn has_user_reacted(
 }
 
 /// Get users who reacted with a specific emoji on a message.
+#[tracing::instrument(skip_all)]
 pub async fn get_reactors(
     pool: &sqlx::AnyPool,
     message_id: Uuid,
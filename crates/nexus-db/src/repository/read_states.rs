@@ -29,6 +29,7 @@ impl<'r> sqlx::FromRow<'r, sqlx::any::AnyRow> for ReadStateRow {
 
 /// Acknowledge reading a channel up to a specific message.
 /// Resets mention count to 0.
+#[tracing::instrument(skip_all)]
 pub async fn ack_message(
     pool: &sqlx::AnyPool,
     user_id: Uuid,
@@ -54,28 +55,91 @@ pub async fn ack_message(
     .await
 }
 
-/// Increment mention count for a user in a channel (called when a message mentions them).
-pub async fn increment_mention_count(
+/// Acknowledge several channels at once. Each entry is
+/// `(channel_id, message_id)`; see [`ack_message`].
+#[tracing::instrument(skip_all)]
+pub async fn ack_bulk(
     pool: &sqlx::AnyPool,
     user_id: Uuid,
+    acks: &[(Uuid, Uuid)],
+) -> Result<Vec<ReadStateRow>, sqlx::Error> {
+    let mut rows = Vec::with_capacity(acks.len());
+    for &(channel_id, message_id) in acks {
+        rows.push(ack_message(pool, user_id, channel_id, message_id).await?);
+    }
+    Ok(rows)
+}
+
+/// Whether the recipient blocked the author or has the channel muted.
+/// Binds: recipient, author, recipient, channel, now.
+const SUPPRESSED: &str = r#"(
+    EXISTS (SELECT 1 FROM user_blocks WHERE user_id = ? AND blocked_id = ?)
+    OR EXISTS (
+        SELECT 1 FROM channel_mutes
+        WHERE user_id = ? AND channel_id = ? AND (muted_until IS NULL OR muted_until > ?)
+    )
+)"#;
+
+fn sql_timestamp(ts: DateTime<Utc>) -> String {
+    ts.format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+/// Whether `user_id` should hear nothing of `author_id`'s message in
+/// `channel_id` — no mention badge, no push — because they blocked the
+/// author or muted the channel. Every notification path asks this.
+#[tracing::instrument(skip_all)]
+pub async fn notifications_suppressed(
+    pool: &sqlx::AnyPool,
+    user_id: Uuid,
+    author_id: Uuid,
     channel_id: Uuid,
-) -> Result<(), sqlx::Error> {
-    sqlx::query(
+) -> Result<bool, sqlx::Error> {
+    let row = sqlx::query(&format!("SELECT 1 AS suppressed WHERE {SUPPRESSED}"))
+        .bind(user_id.to_string())
+        .bind(author_id.to_string())
+        .bind(user_id.to_string())
+        .bind(channel_id.to_string())
+        .bind(sql_timestamp(Utc::now()))
+        .fetch_optional(pool)
+        .await?;
+    Ok(row.is_some())
+}
+
+/// Increment mention count for a user in a channel (called when a message
+/// mentions them), unless [`notifications_suppressed`] says otherwise.
+#[tracing::instrument(skip_all)]
+pub async fn increment_mention_count<'e, E>(
+    executor: E,
+    user_id: Uuid,
+    channel_id: Uuid,
+    author_id: Uuid,
+) -> Result<(), sqlx::Error>
+where
+    E: sqlx::Executor<'e, Database = sqlx::Any>,
+{
+    sqlx::query(&format!(
         r#"
         INSERT INTO read_states (user_id, channel_id, mention_count, last_read_at)
-        VALUES (?, ?, 1, CURRENT_TIMESTAMP)
+        SELECT ?, ?, 1, CURRENT_TIMESTAMP
+        WHERE NOT {SUPPRESSED}
         ON CONFLICT (user_id, channel_id) DO UPDATE SET
             mention_count = read_states.mention_count + 1
-        "#,
-    )
+        "#
+    ))
     .bind(user_id.to_string())
     .bind(channel_id.to_string())
-    .execute(pool)
+    .bind(user_id.to_string())
+    .bind(author_id.to_string())
+    .bind(user_id.to_string())
+    .bind(channel_id.to_string())
+    .bind(sql_timestamp(Utc::now()))
+    .execute(executor)
     .await?;
     Ok(())
 }
 
 /// Get a user's read state for a specific channel.
+#[tracing::instrument(skip_all)]
 pub async fn get_read_state(
     pool: &sqlx::AnyPool,
     user_id: Uuid,
@@ -91,6 +155,7 @@ pub async fn get_read_state(
 }
 
 /// Get all read states for a user (for READY payload).
+#[tracing::instrument(skip_all)]
 pub async fn get_all_read_states(
     pool: &sqlx::AnyPool,
     user_id: Uuid,
@@ -103,7 +168,9 @@ pub async fn get_all_read_states(
     .await
 }
 
-/// Get unread channel IDs for a user (channels where last_read_message_id < channel.last_message_id).
+/// Get unread channels for a user (channels where last_read_message_id < channel.last_message_id),
+/// with the number of messages from other users since the last ack.
+#[tracing::instrument(skip_all)]
 pub async fn get_unread_channels(
     pool: &sqlx::AnyPool,
     user_id: Uuid,
@@ -114,7 +181,14 @@ pub async fn get_unread_channels(
             c.id as channel_id,
             c.last_message_id,
             rs.last_read_message_id,
-            COALESCE(rs.mention_count, 0) as mention_count
+            COALESCE(rs.mention_count, 0) as mention_count,
+            (
+                SELECT COUNT(*) FROM messages m
+                WHERE m.channel_id = c.id
+                AND m.author_id <> ?
+                AND m.deleted_at IS NULL
+                AND (rs.last_read_message_id IS NULL OR m.id > rs.last_read_message_id)
+            ) as unread_count
         FROM channels c
         LEFT JOIN read_states rs ON rs.channel_id = c.id AND rs.user_id = ?
         WHERE (
@@ -130,6 +204,7 @@ pub async fn get_unread_channels(
     .bind(user_id.to_string())
     .bind(user_id.to_string())
     .bind(user_id.to_string())
+    .bind(user_id.to_string())
     .fetch_all(pool)
     .await
 }
@@ -141,6 +216,8 @@ pub struct UnreadChannel {
     pub last_message_id: Option<Uuid>,
     pub last_read_message_id: Option<Uuid>,
     pub mention_count: i32,
+    /// Messages by other users after `last_read_message_id`.
+    pub unread_count: i64,
 }
 
 impl<'r> sqlx::FromRow<'r, sqlx::any::AnyRow> for UnreadChannel {
@@ -151,11 +228,13 @@ impl<'r> sqlx::FromRow<'r, sqlx::any::AnyRow> for UnreadChannel {
             last_message_id: get_opt_uuid(row, "last_message_id")?,
             last_read_message_id: get_opt_uuid(row, "last_read_message_id")?,
             mention_count: row.try_get("mention_count")?,
+            unread_count: row.try_get("unread_count")?,
         })
     }
 }
 
 /// Delete all read states for a user in a specific server's channels (on leave).
+#[tracing::instrument(skip_all)]
 pub async fn delete_server_read_states(
     pool: &sqlx::AnyPool,
     user_id: Uuid,
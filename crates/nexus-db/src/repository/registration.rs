@@ -0,0 +1,234 @@
+//! Registration tokens (invite-only instances) and applications (instances
+//! that approve sign-ups). Tokens are stored as SHA-256 hashes.
+
+use chrono::{DateTime, Utc};
+use nexus_common::models::user::{user_flags, User};
+use sqlx::Row;
+use uuid::Uuid;
+
+/// A single-use registration token. The token itself is only shown once,
+/// when it is created.
+#[derive(Debug, Clone)]
+pub struct RegistrationToken {
+    pub id: Uuid,
+    pub created_by: Option<Uuid>,
+    pub note: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub used_by: Option<Uuid>,
+    pub used_at: Option<DateTime<Utc>>,
+}
+
+impl<'r> sqlx::FromRow<'r, sqlx::any::AnyRow> for RegistrationToken {
+    fn from_row(row: &'r sqlx::any::AnyRow) -> Result<Self, sqlx::Error> {
+        use crate::any_compat::*;
+        Ok(RegistrationToken {
+            id: get_uuid(row, "id")?,
+            created_by: get_opt_uuid(row, "created_by")?,
+            note: row.try_get("note")?,
+            created_at: get_datetime(row, "created_at")?,
+            expires_at: get_opt_datetime(row, "expires_at")?,
+            used_by: get_opt_uuid(row, "used_by")?,
+            used_at: get_opt_datetime(row, "used_at")?,
+        })
+    }
+}
+
+/// A pending account waiting for staff.
+#[derive(Debug, Clone)]
+pub struct Application {
+    pub user_id: Uuid,
+    pub username: String,
+    pub email: Option<String>,
+    pub reason: Option<String>,
+    pub ip: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl<'r> sqlx::FromRow<'r, sqlx::any::AnyRow> for Application {
+    fn from_row(row: &'r sqlx::any::AnyRow) -> Result<Self, sqlx::Error> {
+        use crate::any_compat::*;
+        Ok(Application {
+            user_id: get_uuid(row, "user_id")?,
+            username: row.try_get("username")?,
+            email: row.try_get("email")?,
+            reason: row.try_get("reason")?,
+            ip: row.try_get("ip")?,
+            created_at: get_datetime(row, "created_at")?,
+        })
+    }
+}
+
+fn sql_timestamp(ts: DateTime<Utc>) -> String {
+    ts.format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+// ============================================================================
+// Tokens
+// ============================================================================
+
+#[tracing::instrument(skip_all)]
+pub async fn create_token(
+    pool: &sqlx::AnyPool,
+    id: Uuid,
+    token_hash: &str,
+    created_by: Uuid,
+    note: Option<&str>,
+    expires_at: Option<DateTime<Utc>>,
+) -> Result<RegistrationToken, sqlx::Error> {
+    sqlx::query_as::<_, RegistrationToken>(
+        r#"
+        INSERT INTO registration_tokens (id, token_hash, created_by, note, expires_at)
+        VALUES (?, ?, ?, ?, ?)
+        RETURNING *
+        "#,
+    )
+    .bind(id.to_string())
+    .bind(token_hash)
+    .bind(created_by.to_string())
+    .bind(note)
+    .bind(expires_at.map(sql_timestamp))
+    .fetch_one(pool)
+    .await
+}
+
+/// All tokens, newest first.
+#[tracing::instrument(skip_all)]
+pub async fn list_tokens(pool: &sqlx::AnyPool) -> Result<Vec<RegistrationToken>, sqlx::Error> {
+    sqlx::query_as::<_, RegistrationToken>("SELECT * FROM registration_tokens ORDER BY created_at DESC")
+        .fetch_all(pool)
+        .await
+}
+
+/// Revoke a token. Returns whether it existed.
+#[tracing::instrument(skip_all)]
+pub async fn delete_token(pool: &sqlx::AnyPool, id: Uuid) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM registration_tokens WHERE id = ?")
+        .bind(id.to_string())
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Mark an unused, unexpired token used, so concurrent sign-ups can't share
+/// it. Returns its ID, or `None` if it can't be used.
+#[tracing::instrument(skip_all)]
+pub async fn claim_token(pool: &sqlx::AnyPool, token_hash: &str) -> Result<Option<Uuid>, sqlx::Error> {
+    let now = sql_timestamp(Utc::now());
+    let row = sqlx::query(
+        r#"
+        UPDATE registration_tokens SET used_at = ?
+        WHERE token_hash = ? AND used_at IS NULL AND (expires_at IS NULL OR expires_at > ?)
+        RETURNING id
+        "#,
+    )
+    .bind(&now)
+    .bind(token_hash)
+    .bind(&now)
+    .fetch_optional(pool)
+    .await?;
+    row.map(|row| crate::any_compat::get_uuid(&row, "id")).transpose()
+}
+
+/// Record who a claimed token created, or hand it back (`None`) if the
+/// account couldn't be created after all.
+#[tracing::instrument(skip_all)]
+pub async fn finish_claim(pool: &sqlx::AnyPool, id: Uuid, used_by: Option<Uuid>) -> Result<(), sqlx::Error> {
+    let query = match used_by {
+        Some(user_id) => sqlx::query("UPDATE registration_tokens SET used_by = ? WHERE id = ?")
+            .bind(user_id.to_string()),
+        None => sqlx::query("UPDATE registration_tokens SET used_at = NULL WHERE id = ?"),
+    };
+    query.bind(id.to_string()).execute(pool).await?;
+    Ok(())
+}
+
+// ============================================================================
+// Applications
+// ============================================================================
+
+/// Create an account held for approval, filing its application in the
+/// same transaction so it is never left pending without one.
+#[allow(clippy::too_many_arguments)]
+#[tracing::instrument(skip_all)]
+pub async fn create_pending_user(
+    pool: &sqlx::AnyPool,
+    id: Uuid,
+    username: &str,
+    email: Option<&str>,
+    password_hash: &str,
+    reason: Option<&str>,
+    ip: Option<&str>,
+) -> Result<User, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let user = sqlx::query_as::<_, User>(
+        r#"
+        INSERT INTO users (id, username, email, password_hash, presence, flags, created_at, updated_at)
+        VALUES (?, ?, ?, ?, 'offline', ?, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP)
+        RETURNING *
+        "#,
+    )
+    .bind(id.to_string())
+    .bind(username)
+    .bind(email)
+    .bind(password_hash)
+    .bind(user_flags::PENDING_APPROVAL)
+    .fetch_one(&mut *tx)
+    .await?;
+    sqlx::query("INSERT INTO registration_applications (user_id, reason, ip) VALUES (?, ?, ?)")
+        .bind(id.to_string())
+        .bind(reason)
+        .bind(ip)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+    Ok(user)
+}
+
+/// Pending applications, oldest first.
+#[tracing::instrument(skip_all)]
+pub async fn list_applications(pool: &sqlx::AnyPool) -> Result<Vec<Application>, sqlx::Error> {
+    sqlx::query_as::<_, Application>(
+        r#"
+        SELECT a.user_id, u.username, u.email, a.reason, a.ip, a.created_at
+        FROM registration_applications a
+        JOIN users u ON u.id = a.user_id
+        ORDER BY a.created_at
+        "#,
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Let a pending account in. Returns whether there was an application.
+#[tracing::instrument(skip_all)]
+pub async fn approve(pool: &sqlx::AnyPool, user_id: Uuid) -> Result<bool, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let filed = sqlx::query("DELETE FROM registration_applications WHERE user_id = ?")
+        .bind(user_id.to_string())
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+    if filed == 0 {
+        return Ok(false);
+    }
+    sqlx::query("UPDATE users SET flags = flags & ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?")
+        .bind(!user_flags::PENDING_APPROVAL)
+        .bind(user_id.to_string())
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+    Ok(true)
+}
+
+/// Turn a pending account away, deleting it (it never held any data).
+/// Returns whether there was one.
+#[tracing::instrument(skip_all)]
+pub async fn reject(pool: &sqlx::AnyPool, user_id: Uuid) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM users WHERE id = ? AND (flags & ?) != 0")
+        .bind(user_id.to_string())
+        .bind(user_flags::PENDING_APPROVAL)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
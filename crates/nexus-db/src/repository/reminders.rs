@@ -0,0 +1,167 @@
+//! Reminders repository — notes users schedule for themselves.
+//!
+//! A reminder is pending until the reminders job claims it at `remind_at`,
+//! which stamps `delivered_at`. Cancelling deletes a pending reminder.
+//! A delivered reminder stays due until `acknowledged_at` is stamped: when
+//! it is posted in a channel, or when the user dismisses it.
+
+use chrono::{DateTime, Utc};
+use sqlx::Row;
+use uuid::Uuid;
+
+#[derive(Debug, Clone)]
+pub struct ReminderRow {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub channel_id: Option<Uuid>,
+    pub message_id: Option<Uuid>,
+    pub content: String,
+    pub remind_at: DateTime<Utc>,
+    pub delivered_at: Option<DateTime<Utc>>,
+    pub acknowledged_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl<'r> sqlx::FromRow<'r, sqlx::any::AnyRow> for ReminderRow {
+    fn from_row(row: &'r sqlx::any::AnyRow) -> Result<Self, sqlx::Error> {
+        use crate::any_compat::*;
+        Ok(ReminderRow {
+            id: get_uuid(row, "id")?,
+            user_id: get_uuid(row, "user_id")?,
+            channel_id: get_opt_uuid(row, "channel_id")?,
+            message_id: get_opt_uuid(row, "message_id")?,
+            content: row.try_get("content")?,
+            remind_at: get_datetime(row, "remind_at")?,
+            delivered_at: get_opt_datetime(row, "delivered_at")?,
+            acknowledged_at: get_opt_datetime(row, "acknowledged_at")?,
+            created_at: get_datetime(row, "created_at")?,
+        })
+    }
+}
+
+fn sql_timestamp(ts: DateTime<Utc>) -> String {
+    ts.format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+/// Schedule a reminder.
+#[tracing::instrument(skip_all)]
+pub async fn create(
+    pool: &sqlx::AnyPool,
+    id: Uuid,
+    user_id: Uuid,
+    channel_id: Option<Uuid>,
+    message_id: Option<Uuid>,
+    content: &str,
+    remind_at: DateTime<Utc>,
+) -> Result<ReminderRow, sqlx::Error> {
+    sqlx::query_as::<_, ReminderRow>(
+        r#"
+        INSERT INTO reminders (id, user_id, channel_id, message_id, content, remind_at, created_at)
+        VALUES (?, ?, ?, ?, ?, ?, CURRENT_TIMESTAMP)
+        RETURNING *
+        "#,
+    )
+    .bind(id.to_string())
+    .bind(user_id.to_string())
+    .bind(channel_id.map(|c| c.to_string()))
+    .bind(message_id.map(|m| m.to_string()))
+    .bind(content)
+    .bind(sql_timestamp(remind_at))
+    .fetch_one(pool)
+    .await
+}
+
+/// A user's pending reminders, soonest first.
+#[tracing::instrument(skip_all)]
+pub async fn list_pending(pool: &sqlx::AnyPool, user_id: Uuid) -> Result<Vec<ReminderRow>, sqlx::Error> {
+    sqlx::query_as::<_, ReminderRow>(
+        "SELECT * FROM reminders WHERE user_id = ? AND delivered_at IS NULL ORDER BY remind_at",
+    )
+    .bind(user_id.to_string())
+    .fetch_all(pool)
+    .await
+}
+
+/// A user's delivered reminders they haven't acknowledged, oldest first.
+#[tracing::instrument(skip_all)]
+pub async fn list_due(pool: &sqlx::AnyPool, user_id: Uuid) -> Result<Vec<ReminderRow>, sqlx::Error> {
+    sqlx::query_as::<_, ReminderRow>(
+        "SELECT * FROM reminders WHERE user_id = ? AND delivered_at IS NOT NULL AND acknowledged_at IS NULL ORDER BY remind_at",
+    )
+    .bind(user_id.to_string())
+    .fetch_all(pool)
+    .await
+}
+
+/// How many reminders a user has pending.
+#[tracing::instrument(skip_all)]
+pub async fn count_pending(pool: &sqlx::AnyPool, user_id: Uuid) -> Result<i64, sqlx::Error> {
+    let row: (i64,) =
+        sqlx::query_as("SELECT COUNT(*) FROM reminders WHERE user_id = ? AND delivered_at IS NULL")
+            .bind(user_id.to_string())
+            .fetch_one(pool)
+            .await?;
+    Ok(row.0)
+}
+
+/// Cancel one of a user's pending reminders. Returns false if there was
+/// none (or it has already been delivered).
+#[tracing::instrument(skip_all)]
+pub async fn cancel(pool: &sqlx::AnyPool, user_id: Uuid, id: Uuid) -> Result<bool, sqlx::Error> {
+    let result =
+        sqlx::query("DELETE FROM reminders WHERE id = ? AND user_id = ? AND delivered_at IS NULL")
+            .bind(id.to_string())
+            .bind(user_id.to_string())
+            .execute(pool)
+            .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Acknowledge one of a user's delivered reminders. Returns false if there
+/// was none (or it was already acknowledged).
+#[tracing::instrument(skip_all)]
+pub async fn acknowledge(pool: &sqlx::AnyPool, user_id: Uuid, id: Uuid) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        "UPDATE reminders SET acknowledged_at = CURRENT_TIMESTAMP \
+         WHERE id = ? AND user_id = ? AND delivered_at IS NOT NULL AND acknowledged_at IS NULL",
+    )
+    .bind(id.to_string())
+    .bind(user_id.to_string())
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Claim up to `limit` reminders due at `now`, oldest first.
+///
+/// Each row is stamped delivered with a guarded update, so two workers
+/// racing on the same row never both deliver it.
+#[tracing::instrument(skip_all)]
+pub async fn claim_due(
+    pool: &sqlx::AnyPool,
+    now: DateTime<Utc>,
+    limit: i64,
+) -> Result<Vec<ReminderRow>, sqlx::Error> {
+    let now = sql_timestamp(now);
+    let candidates = sqlx::query_as::<_, ReminderRow>(
+        "SELECT * FROM reminders WHERE delivered_at IS NULL AND remind_at <= ? ORDER BY remind_at LIMIT ?",
+    )
+    .bind(&now)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    let mut claimed = Vec::with_capacity(candidates.len());
+    for row in candidates {
+        let result =
+            sqlx::query("UPDATE reminders SET delivered_at = ? WHERE id = ? AND delivered_at IS NULL")
+                .bind(&now)
+                .bind(row.id.to_string())
+                .execute(pool)
+                .await?;
+        if result.rows_affected() == 1 {
+            claimed.push(row);
+        }
+    }
+    Ok(claimed)
+}
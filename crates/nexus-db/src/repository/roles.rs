@@ -5,8 +5,9 @@ use nexus_common::models::role::Role;
 use uuid::Uuid;
 
 /// Create a new role.
-pub async fn create_role(
-    pool: &sqlx::AnyPool,
+#[tracing::instrument(skip_all)]
+pub async fn create_role<'e, E>(
+    executor: E,
     id: Uuid,
     server_id: Uuid,
     name: &str,
@@ -14,7 +15,10 @@ pub async fn create_role(
     permissions: i64,
     position: i32,
     is_default: bool,
-) -> Result<Role, sqlx::Error> {
+) -> Result<Role, sqlx::Error>
+where
+    E: sqlx::Executor<'e, Database = sqlx::Any>,
+{
     sqlx::query_as::<_, Role>(
         r#"
         INSERT INTO roles (id, server_id, name, color, hoist, position, permissions, mentionable, is_default, created_at, updated_at)
@@ -29,11 +33,12 @@ pub async fn create_role(
     .bind(position)
     .bind(permissions)
     .bind(is_default)
-    .fetch_one(pool)
+    .fetch_one(executor)
     .await
 }
 
 /// List all roles in a server.
+#[tracing::instrument(skip_all)]
 pub async fn list_server_roles(
     pool: &sqlx::AnyPool,
     server_id: Uuid,
@@ -47,6 +52,7 @@ pub async fn list_server_roles(
 }
 
 /// Find a role by ID.
+#[tracing::instrument(skip_all)]
 pub async fn find_by_id(pool: &sqlx::AnyPool, id: Uuid) -> Result<Option<Role>, sqlx::Error> {
     sqlx::query_as::<_, Role>("SELECT * FROM roles WHERE id = ?")
         .bind(id.to_string())
@@ -55,6 +61,7 @@ pub async fn find_by_id(pool: &sqlx::AnyPool, id: Uuid) -> Result<Option<Role>,
 }
 
 /// Update a role.
+#[tracing::instrument(skip_all)]
 pub async fn update_role(
     pool: &sqlx::AnyPool,
     id: Uuid,
@@ -91,6 +98,7 @@ pub async fn update_role(
 }
 
 /// Delete a role.
+#[tracing::instrument(skip_all)]
 pub async fn delete_role(pool: &sqlx::AnyPool, id: Uuid) -> Result<(), sqlx::Error> {
     sqlx::query("DELETE FROM roles WHERE id = ? AND is_default = false")
         .bind(id.to_string())
@@ -100,6 +108,7 @@ pub async fn delete_role(pool: &sqlx::AnyPool, id: Uuid) -> Result<(), sqlx::Err
 }
 
 /// Get the @everyone role for a server.
+#[tracing::instrument(skip_all)]
 pub async fn get_everyone_role(
     pool: &sqlx::AnyPool,
     server_id: Uuid,
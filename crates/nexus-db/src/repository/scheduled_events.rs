@@ -0,0 +1,222 @@
+//! Scheduled events repository — server events and calendar feed tokens.
+
+use chrono::{DateTime, Utc};
+use sqlx::Row;
+use uuid::Uuid;
+
+pub const STATUS_SCHEDULED: &str = "scheduled";
+pub const STATUS_ACTIVE: &str = "active";
+pub const STATUS_COMPLETED: &str = "completed";
+pub const STATUS_CANCELLED: &str = "cancelled";
+
+#[derive(Debug, Clone)]
+pub struct ScheduledEventRow {
+    pub id: Uuid,
+    pub server_id: Uuid,
+    pub channel_id: Option<Uuid>,
+    pub creator_id: Option<Uuid>,
+    pub name: String,
+    pub description: Option<String>,
+    pub location: Option<String>,
+    pub start_time: DateTime<Utc>,
+    pub end_time: Option<DateTime<Utc>>,
+    pub status: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl<'r> sqlx::FromRow<'r, sqlx::any::AnyRow> for ScheduledEventRow {
+    fn from_row(row: &'r sqlx::any::AnyRow) -> Result<Self, sqlx::Error> {
+        use crate::any_compat::*;
+        Ok(ScheduledEventRow {
+            id: get_uuid(row, "id")?,
+            server_id: get_uuid(row, "server_id")?,
+            channel_id: get_opt_uuid(row, "channel_id")?,
+            creator_id: get_opt_uuid(row, "creator_id")?,
+            name: row.try_get("name")?,
+            description: row.try_get("description")?,
+            location: row.try_get("location")?,
+            start_time: get_datetime(row, "start_time")?,
+            end_time: get_opt_datetime(row, "end_time")?,
+            status: row.try_get("status")?,
+            created_at: get_datetime(row, "created_at")?,
+            updated_at: get_datetime(row, "updated_at")?,
+        })
+    }
+}
+
+/// Insert a new event.
+#[allow(clippy::too_many_arguments)]
+#[tracing::instrument(skip_all)]
+pub async fn create_event(
+    pool: &sqlx::AnyPool,
+    id: Uuid,
+    server_id: Uuid,
+    channel_id: Option<Uuid>,
+    creator_id: Uuid,
+    name: &str,
+    description: Option<&str>,
+    location: Option<&str>,
+    start_time: DateTime<Utc>,
+    end_time: Option<DateTime<Utc>>,
+) -> Result<ScheduledEventRow, sqlx::Error> {
+    sqlx::query_as::<_, ScheduledEventRow>(
+        r#"
+        INSERT INTO scheduled_events
+            (id, server_id, channel_id, creator_id, name, description, location, start_time, end_time)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+        RETURNING *
+        "#,
+    )
+    .bind(id.to_string())
+    .bind(server_id.to_string())
+    .bind(channel_id.map(|c| c.to_string()))
+    .bind(creator_id.to_string())
+    .bind(name)
+    .bind(description)
+    .bind(location)
+    .bind(start_time.to_rfc3339())
+    .bind(end_time.map(|t| t.to_rfc3339()))
+    .fetch_one(pool)
+    .await
+}
+
+/// Find an event by ID.
+#[tracing::instrument(skip_all)]
+pub async fn find_by_id(
+    pool: &sqlx::AnyPool,
+    id: Uuid,
+) -> Result<Option<ScheduledEventRow>, sqlx::Error> {
+    sqlx::query_as::<_, ScheduledEventRow>("SELECT * FROM scheduled_events WHERE id = ?")
+        .bind(id.to_string())
+        .fetch_optional(pool)
+        .await
+}
+
+/// List a server's events that end (or, without an end, start) after `since`,
+/// soonest first.
+#[tracing::instrument(skip_all)]
+pub async fn list_for_server(
+    pool: &sqlx::AnyPool,
+    server_id: Uuid,
+    since: DateTime<Utc>,
+    limit: i64,
+) -> Result<Vec<ScheduledEventRow>, sqlx::Error> {
+    sqlx::query_as::<_, ScheduledEventRow>(
+        r#"
+        SELECT * FROM scheduled_events
+        WHERE server_id = ? AND COALESCE(end_time, start_time) >= ?
+        ORDER BY start_time ASC
+        LIMIT ?
+        "#,
+    )
+    .bind(server_id.to_string())
+    .bind(since.to_rfc3339())
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+}
+
+/// Replace an event's editable fields.
+#[allow(clippy::too_many_arguments)]
+#[tracing::instrument(skip_all)]
+pub async fn update_event(
+    pool: &sqlx::AnyPool,
+    id: Uuid,
+    channel_id: Option<Uuid>,
+    name: &str,
+    description: Option<&str>,
+    location: Option<&str>,
+    start_time: DateTime<Utc>,
+    end_time: Option<DateTime<Utc>>,
+    status: &str,
+) -> Result<ScheduledEventRow, sqlx::Error> {
+    sqlx::query_as::<_, ScheduledEventRow>(
+        r#"
+        UPDATE scheduled_events SET
+            channel_id = ?, name = ?, description = ?, location = ?,
+            start_time = ?, end_time = ?, status = ?, updated_at = CURRENT_TIMESTAMP
+        WHERE id = ?
+        RETURNING *
+        "#,
+    )
+    .bind(channel_id.map(|c| c.to_string()))
+    .bind(name)
+    .bind(description)
+    .bind(location)
+    .bind(start_time.to_rfc3339())
+    .bind(end_time.map(|t| t.to_rfc3339()))
+    .bind(status)
+    .bind(id.to_string())
+    .fetch_one(pool)
+    .await
+}
+
+/// Delete an event.
+#[tracing::instrument(skip_all)]
+pub async fn delete_event(pool: &sqlx::AnyPool, id: Uuid) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM scheduled_events WHERE id = ?")
+        .bind(id.to_string())
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+// ============================================================
+// Calendar feed tokens
+// ============================================================
+
+/// Get a member's feed token for a server, if one has been issued.
+#[tracing::instrument(skip_all)]
+pub async fn get_feed_token(
+    pool: &sqlx::AnyPool,
+    server_id: Uuid,
+    user_id: Uuid,
+) -> Result<Option<String>, sqlx::Error> {
+    let row = sqlx::query("SELECT token FROM event_feed_tokens WHERE server_id = ? AND user_id = ?")
+        .bind(server_id.to_string())
+        .bind(user_id.to_string())
+        .fetch_optional(pool)
+        .await?;
+    row.map(|r| r.try_get("token")).transpose()
+}
+
+/// Issue (or replace) a member's feed token. Replacing invalidates the old URL.
+#[tracing::instrument(skip_all)]
+pub async fn set_feed_token(
+    pool: &sqlx::AnyPool,
+    server_id: Uuid,
+    user_id: Uuid,
+    token: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO event_feed_tokens (token, server_id, user_id)
+        VALUES (?, ?, ?)
+        ON CONFLICT (server_id, user_id) DO UPDATE SET
+            token = excluded.token,
+            created_at = CURRENT_TIMESTAMP
+        "#,
+    )
+    .bind(token)
+    .bind(server_id.to_string())
+    .bind(user_id.to_string())
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Resolve a feed token to its `(server_id, user_id)`.
+#[tracing::instrument(skip_all)]
+pub async fn resolve_feed_token(
+    pool: &sqlx::AnyPool,
+    token: &str,
+) -> Result<Option<(Uuid, Uuid)>, sqlx::Error> {
+    use crate::any_compat::get_uuid;
+    let row = sqlx::query("SELECT server_id, user_id FROM event_feed_tokens WHERE token = ?")
+        .bind(token)
+        .fetch_optional(pool)
+        .await?;
+    row.map(|r| Ok((get_uuid(&r, "server_id")?, get_uuid(&r, "user_id")?)))
+        .transpose()
+}
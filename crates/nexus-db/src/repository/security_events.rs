@@ -0,0 +1,139 @@
+//! Security event deliveries — instance-level security events queued for
+//! the operator's webhook URLs (see `nexus_api::security_events`).
+
+use chrono::{DateTime, Utc};
+use sqlx::Row;
+use uuid::Uuid;
+
+#[derive(Debug, Clone)]
+pub struct DeliveryRow {
+    pub id: Uuid,
+    pub url: String,
+    pub event: String,
+    pub payload: serde_json::Value,
+    pub attempts: i32,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub next_attempt_at: DateTime<Utc>,
+    pub delivered_at: Option<DateTime<Utc>>,
+}
+
+impl<'r> sqlx::FromRow<'r, sqlx::any::AnyRow> for DeliveryRow {
+    fn from_row(row: &'r sqlx::any::AnyRow) -> Result<Self, sqlx::Error> {
+        use crate::any_compat::*;
+        Ok(DeliveryRow {
+            id: get_uuid(row, "id")?,
+            url: row.try_get("url")?,
+            event: row.try_get("event")?,
+            payload: get_json_value(row, "payload")?,
+            attempts: row.try_get("attempts")?,
+            last_error: row.try_get("last_error")?,
+            created_at: get_datetime(row, "created_at")?,
+            next_attempt_at: get_datetime(row, "next_attempt_at")?,
+            delivered_at: get_opt_datetime(row, "delivered_at")?,
+        })
+    }
+}
+
+fn sql_timestamp(ts: DateTime<Utc>) -> String {
+    ts.format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+/// Queue `payload` for immediate delivery to `url`.
+#[tracing::instrument(skip_all)]
+pub async fn enqueue(
+    pool: &sqlx::AnyPool,
+    id: Uuid,
+    url: &str,
+    event: &str,
+    payload: &serde_json::Value,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO security_event_deliveries (id, url, event, payload, next_attempt_at)
+        VALUES (?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(id.to_string())
+    .bind(url)
+    .bind(event)
+    .bind(payload.to_string())
+    .bind(sql_timestamp(Utc::now()))
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Undelivered rows that are due, oldest first.
+#[tracing::instrument(skip_all)]
+pub async fn list_due(pool: &sqlx::AnyPool, limit: i64) -> Result<Vec<DeliveryRow>, sqlx::Error> {
+    sqlx::query_as::<_, DeliveryRow>(
+        r#"
+        SELECT * FROM security_event_deliveries
+        WHERE delivered_at IS NULL AND next_attempt_at <= ?
+        ORDER BY created_at
+        LIMIT ?
+        "#,
+    )
+    .bind(sql_timestamp(Utc::now()))
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+}
+
+/// Mark a row delivered.
+#[tracing::instrument(skip_all)]
+pub async fn mark_delivered(pool: &sqlx::AnyPool, id: Uuid) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "UPDATE security_event_deliveries SET delivered_at = CURRENT_TIMESTAMP, attempts = attempts + 1 WHERE id = ?",
+    )
+    .bind(id.to_string())
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Record a failed attempt and schedule the next one.
+#[tracing::instrument(skip_all)]
+pub async fn mark_failed(
+    pool: &sqlx::AnyPool,
+    id: Uuid,
+    error: &str,
+    next_attempt_at: DateTime<Utc>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        UPDATE security_event_deliveries
+        SET attempts = attempts + 1, last_error = ?, next_attempt_at = ?
+        WHERE id = ?
+        "#,
+    )
+    .bind(error)
+    .bind(sql_timestamp(next_attempt_at))
+    .bind(id.to_string())
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Drop a row that ran out of attempts.
+#[tracing::instrument(skip_all)]
+pub async fn discard(pool: &sqlx::AnyPool, id: Uuid) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM security_event_deliveries WHERE id = ?")
+        .bind(id.to_string())
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Delete delivered rows older than `before`. Returns the number removed.
+#[tracing::instrument(skip_all)]
+pub async fn prune_delivered(pool: &sqlx::AnyPool, before: DateTime<Utc>) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        "DELETE FROM security_event_deliveries WHERE delivered_at IS NOT NULL AND delivered_at < ?",
+    )
+    .bind(sql_timestamp(before))
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
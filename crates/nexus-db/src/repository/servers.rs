@@ -1,17 +1,21 @@
 //! Server repository — CRUD operations for servers (guilds).
 
-use nexus_common::models::server::{Invite, Server};
-
+use nexus_common::models::server::{Invite, Server, ServerSettings};
+use sqlx::Row;
 use uuid::Uuid;
 
 /// Create a new server.
-pub async fn create_server(
-    pool: &sqlx::AnyPool,
+#[tracing::instrument(skip_all)]
+pub async fn create_server<'e, E>(
+    executor: E,
     id: Uuid,
     name: &str,
     owner_id: Uuid,
     is_public: bool,
-) -> Result<Server, sqlx::Error> {
+) -> Result<Server, sqlx::Error>
+where
+    E: sqlx::Executor<'e, Database = sqlx::Any>,
+{
     sqlx::query_as::<_, Server>(
         r#"
         INSERT INTO servers (id, name, owner_id, is_public, features, settings, member_count, created_at, updated_at)
@@ -23,11 +27,12 @@ pub async fn create_server(
     .bind(name)
     .bind(owner_id.to_string())
     .bind(is_public)
-    .fetch_one(pool)
+    .fetch_one(executor)
     .await
 }
 
 /// Find a server by ID.
+#[tracing::instrument(skip_all)]
 pub async fn find_by_id(pool: &sqlx::AnyPool, id: Uuid) -> Result<Option<Server>, sqlx::Error> {
     sqlx::query_as::<_, Server>("SELECT * FROM servers WHERE id = ?")
         .bind(id.to_string())
@@ -36,6 +41,7 @@ pub async fn find_by_id(pool: &sqlx::AnyPool, id: Uuid) -> Result<Option<Server>
 }
 
 /// List servers a user is a member of.
+#[tracing::instrument(skip_all)]
 pub async fn list_user_servers(pool: &sqlx::AnyPool, user_id: Uuid) -> Result<Vec<Server>, sqlx::Error> {
     sqlx::query_as::<_, Server>(
         r#"
@@ -51,12 +57,16 @@ pub async fn list_user_servers(pool: &sqlx::AnyPool, user_id: Uuid) -> Result<Ve
 }
 
 /// Update server details.
+#[tracing::instrument(skip_all)]
 pub async fn update_server(
     pool: &sqlx::AnyPool,
     id: Uuid,
     name: Option<&str>,
     description: Option<&str>,
     is_public: Option<bool>,
+    message_history: Option<bool>,
+    preferred_locale: Option<&str>,
+    message_retention_days: Option<i32>,
 ) -> Result<Server, sqlx::Error> {
     sqlx::query_as::<_, Server>(
         r#"
@@ -64,20 +74,59 @@ pub async fn update_server(
             name = COALESCE(?, name),
             description = COALESCE(?, description),
             is_public = COALESCE(?, is_public),
+            message_history = COALESCE(?, message_history),
+            preferred_locale = COALESCE(?, preferred_locale),
+            message_retention_days = COALESCE(?, message_retention_days),
             updated_at = CURRENT_TIMESTAMP
         WHERE id = ?
         RETURNING *
         "#,
     )
-    .bind(id.to_string())
     .bind(name)
     .bind(description)
     .bind(is_public)
+    .bind(message_history)
+    .bind(preferred_locale)
+    .bind(message_retention_days)
+    .bind(id.to_string())
+    .fetch_one(pool)
+    .await
+}
+
+/// Overwrite every editable setting with a snapshot (used for rollback).
+#[tracing::instrument(skip_all)]
+pub async fn restore_settings(
+    pool: &sqlx::AnyPool,
+    id: Uuid,
+    settings: &ServerSettings,
+) -> Result<Server, sqlx::Error> {
+    sqlx::query_as::<_, Server>(
+        r#"
+        UPDATE servers SET
+            name = ?,
+            description = ?,
+            is_public = ?,
+            message_history = ?,
+            preferred_locale = ?,
+            message_retention_days = ?,
+            updated_at = CURRENT_TIMESTAMP
+        WHERE id = ?
+        RETURNING *
+        "#,
+    )
+    .bind(&settings.name)
+    .bind(settings.description.as_deref())
+    .bind(settings.is_public)
+    .bind(settings.message_history)
+    .bind(&settings.preferred_locale)
+    .bind(settings.message_retention_days)
+    .bind(id.to_string())
     .fetch_one(pool)
     .await
 }
 
 /// Delete a server and all associated data.
+#[tracing::instrument(skip_all)]
 pub async fn delete_server(pool: &sqlx::AnyPool, id: Uuid) -> Result<(), sqlx::Error> {
     // Cascading deletes handled by foreign keys
     sqlx::query("DELETE FROM servers WHERE id = ?")
@@ -87,25 +136,45 @@ pub async fn delete_server(pool: &sqlx::AnyPool, id: Uuid) -> Result<(), sqlx::E
     Ok(())
 }
 
+/// Servers with a message retention period, and its length in days.
+#[tracing::instrument(skip_all)]
+pub async fn list_retention_policies(pool: &sqlx::AnyPool) -> Result<Vec<(Uuid, i32)>, sqlx::Error> {
+    let rows = sqlx::query("SELECT id, message_retention_days FROM servers WHERE message_retention_days > 0")
+        .fetch_all(pool)
+        .await?;
+    rows.iter()
+        .map(|row| Ok((crate::any_compat::get_uuid(row, "id")?, row.try_get("message_retention_days")?)))
+        .collect()
+}
+
 /// Increment server member count.
-pub async fn increment_member_count(pool: &sqlx::AnyPool, server_id: Uuid) -> Result<(), sqlx::Error> {
+#[tracing::instrument(skip_all)]
+pub async fn increment_member_count<'e, E>(executor: E, server_id: Uuid) -> Result<(), sqlx::Error>
+where
+    E: sqlx::Executor<'e, Database = sqlx::Any>,
+{
     sqlx::query("UPDATE servers SET member_count = member_count + 1 WHERE id = ?")
         .bind(server_id.to_string())
-        .execute(pool)
+        .execute(executor)
         .await?;
     Ok(())
 }
 
 /// Decrement server member count.
-pub async fn decrement_member_count(pool: &sqlx::AnyPool, server_id: Uuid) -> Result<(), sqlx::Error> {
+#[tracing::instrument(skip_all)]
+pub async fn decrement_member_count<'e, E>(executor: E, server_id: Uuid) -> Result<(), sqlx::Error>
+where
+    E: sqlx::Executor<'e, Database = sqlx::Any>,
+{
     sqlx::query("UPDATE servers SET member_count = max(member_count - 1, 0) WHERE id = ?")
         .bind(server_id.to_string())
-        .execute(pool)
+        .execute(executor)
         .await?;
     Ok(())
 }
 
 /// Create an invite link.
+#[tracing::instrument(skip_all)]
 pub async fn create_invite(
     pool: &sqlx::AnyPool,
     code: &str,
@@ -127,12 +196,50 @@ pub async fn create_invite(
     .bind(channel_id.map(|u| u.to_string()))
     .bind(inviter_id.to_string())
     .bind(max_uses)
-    .bind(expires_at.map(|x| x.to_rfc3339()))
+    .bind(expires_at.map(sql_timestamp))
     .fetch_one(pool)
     .await
 }
 
+/// Whether `code` is already an invite code or a server's vanity code, so a
+/// generated code never shadows a vanity link.
+#[tracing::instrument(skip_all)]
+pub async fn invite_code_taken(pool: &sqlx::AnyPool, code: &str) -> Result<bool, sqlx::Error> {
+    let row = sqlx::query(
+        r#"
+        SELECT (SELECT COUNT(*) FROM invites WHERE code = ?)
+             + (SELECT COUNT(*) FROM servers WHERE LOWER(vanity_code) = LOWER(?)) AS n
+        "#,
+    )
+    .bind(code)
+    .bind(code)
+    .fetch_one(pool)
+    .await?;
+    Ok(row.try_get::<i64, _>("n")? > 0)
+}
+
+/// A server's invites, newest first — including expired and used-up ones,
+/// so moderators can see and revoke them.
+#[tracing::instrument(skip_all)]
+pub async fn list_invites(pool: &sqlx::AnyPool, server_id: Uuid) -> Result<Vec<Invite>, sqlx::Error> {
+    sqlx::query_as::<_, Invite>("SELECT * FROM invites WHERE server_id = ? ORDER BY created_at DESC")
+        .bind(server_id.to_string())
+        .fetch_all(pool)
+        .await
+}
+
+/// Delete an invite. Returns `false` if there was none.
+#[tracing::instrument(skip_all)]
+pub async fn delete_invite(pool: &sqlx::AnyPool, code: &str) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM invites WHERE code = ?")
+        .bind(code)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
 /// Find an invite by code.
+#[tracing::instrument(skip_all)]
 pub async fn find_invite(pool: &sqlx::AnyPool, code: &str) -> Result<Option<Invite>, sqlx::Error> {
     sqlx::query_as::<_, Invite>("SELECT * FROM invites WHERE code = ?")
         .bind(code)
@@ -140,16 +247,35 @@ pub async fn find_invite(pool: &sqlx::AnyPool, code: &str) -> Result<Option<Invi
         .await
 }
 
-/// Consume an invite (increment use count).
-pub async fn use_invite(pool: &sqlx::AnyPool, code: &str) -> Result<(), sqlx::Error> {
-    sqlx::query("UPDATE invites SET uses = uses + 1 WHERE code = ?")
-        .bind(code)
-        .execute(pool)
-        .await?;
-    Ok(())
+/// Spend one use of an invite. Returns `false`, using nothing, if it has
+/// expired or run out of uses — checked in the update itself, so two
+/// concurrent joins can't both take an invite's last use.
+#[tracing::instrument(skip_all)]
+pub async fn use_invite<'e, E>(executor: E, code: &str) -> Result<bool, sqlx::Error>
+where
+    E: sqlx::Executor<'e, Database = sqlx::Any>,
+{
+    let result = sqlx::query(
+        r#"
+        UPDATE invites SET uses = uses + 1
+        WHERE code = ?
+          AND (max_uses IS NULL OR uses < max_uses)
+          AND (expires_at IS NULL OR expires_at > ?)
+        "#,
+    )
+    .bind(code)
+    .bind(sql_timestamp(chrono::Utc::now()))
+    .execute(executor)
+    .await?;
+    Ok(result.rows_affected() == 1)
+}
+
+fn sql_timestamp(ts: chrono::DateTime<chrono::Utc>) -> String {
+    ts.format("%Y-%m-%d %H:%M:%S").to_string()
 }
 
 /// List public/discoverable servers.
+#[tracing::instrument(skip_all)]
 pub async fn list_public_servers(
     pool: &sqlx::AnyPool,
     limit: i64,
@@ -0,0 +1,223 @@
+//! Login sessions and their rotating refresh tokens. Refresh tokens are
+//! stored as SHA-256 hashes and kept after use, so a replayed token can be
+//! recognised.
+
+use chrono::{DateTime, Utc};
+use sqlx::Row;
+use uuid::Uuid;
+
+/// One login on one device.
+#[derive(Debug, Clone)]
+pub struct Session {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub user_agent: Option<String>,
+    pub ip: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl<'r> sqlx::FromRow<'r, sqlx::any::AnyRow> for Session {
+    fn from_row(row: &'r sqlx::any::AnyRow) -> Result<Self, sqlx::Error> {
+        use crate::any_compat::*;
+        Ok(Session {
+            id: get_uuid(row, "id")?,
+            user_id: get_uuid(row, "user_id")?,
+            user_agent: row.try_get("user_agent")?,
+            ip: row.try_get("ip")?,
+            created_at: get_datetime(row, "created_at")?,
+            last_used_at: get_datetime(row, "last_used_at")?,
+            expires_at: get_datetime(row, "expires_at")?,
+        })
+    }
+}
+
+/// The session a refresh token belongs to, and whether the token was
+/// already exchanged.
+#[derive(Debug, Clone)]
+pub struct RefreshTokenLookup {
+    pub session: Session,
+    pub used: bool,
+}
+
+fn sql_timestamp(ts: DateTime<Utc>) -> String {
+    ts.format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+/// Start a session with its first refresh token, clearing out the user's
+/// expired sessions.
+#[tracing::instrument(skip_all)]
+pub async fn create(
+    pool: &sqlx::AnyPool,
+    id: Uuid,
+    user_id: Uuid,
+    refresh_token_hash: &str,
+    user_agent: Option<&str>,
+    ip: Option<&str>,
+    expires_at: DateTime<Utc>,
+) -> Result<Session, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    sqlx::query("DELETE FROM sessions WHERE user_id = ? AND expires_at < ?")
+        .bind(user_id.to_string())
+        .bind(sql_timestamp(Utc::now()))
+        .execute(&mut *tx)
+        .await?;
+    let session = sqlx::query_as::<_, Session>(
+        r#"
+        INSERT INTO sessions (id, user_id, user_agent, ip, expires_at)
+        VALUES (?, ?, ?, ?, ?)
+        RETURNING *
+        "#,
+    )
+    .bind(id.to_string())
+    .bind(user_id.to_string())
+    .bind(user_agent)
+    .bind(ip)
+    .bind(sql_timestamp(expires_at))
+    .fetch_one(&mut *tx)
+    .await?;
+    sqlx::query("INSERT INTO session_refresh_tokens (token_hash, session_id) VALUES (?, ?)")
+        .bind(refresh_token_hash)
+        .bind(id.to_string())
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+    Ok(session)
+}
+
+/// Look up a refresh token. Expiry is left to the caller.
+#[tracing::instrument(skip_all)]
+pub async fn find_by_refresh_token(
+    pool: &sqlx::AnyPool,
+    token_hash: &str,
+) -> Result<Option<RefreshTokenLookup>, sqlx::Error> {
+    let row = sqlx::query(
+        r#"
+        SELECT s.*, t.used_at
+        FROM session_refresh_tokens t
+        JOIN sessions s ON s.id = t.session_id
+        WHERE t.token_hash = ?
+        "#,
+    )
+    .bind(token_hash)
+    .fetch_optional(pool)
+    .await?;
+    row.map(|row| {
+        use sqlx::FromRow;
+        Ok(RefreshTokenLookup {
+            session: Session::from_row(&row)?,
+            used: crate::any_compat::get_opt_datetime(&row, "used_at")?.is_some(),
+        })
+    })
+    .transpose()
+}
+
+/// Exchange `old_hash` for `new_hash` and extend the session.
+///
+/// Returns `false` without changing anything if `old_hash` was already
+/// used — a concurrent refresh got there first, and the caller should treat
+/// it as reuse.
+#[tracing::instrument(skip_all)]
+pub async fn rotate(
+    pool: &sqlx::AnyPool,
+    session_id: Uuid,
+    old_hash: &str,
+    new_hash: &str,
+    ip: Option<&str>,
+    expires_at: DateTime<Utc>,
+) -> Result<bool, sqlx::Error> {
+    let now = sql_timestamp(Utc::now());
+    let mut tx = pool.begin().await?;
+    let claimed = sqlx::query(
+        "UPDATE session_refresh_tokens SET used_at = ? WHERE token_hash = ? AND used_at IS NULL",
+    )
+    .bind(&now)
+    .bind(old_hash)
+    .execute(&mut *tx)
+    .await?
+    .rows_affected();
+    if claimed == 0 {
+        return Ok(false);
+    }
+    sqlx::query("INSERT INTO session_refresh_tokens (token_hash, session_id) VALUES (?, ?)")
+        .bind(new_hash)
+        .bind(session_id.to_string())
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query(
+        "UPDATE sessions SET last_used_at = ?, ip = COALESCE(?, ip), expires_at = ? WHERE id = ?",
+    )
+    .bind(&now)
+    .bind(ip)
+    .bind(sql_timestamp(expires_at))
+    .bind(session_id.to_string())
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+    Ok(true)
+}
+
+/// The user's unexpired sessions, most recently used first.
+#[tracing::instrument(skip_all)]
+pub async fn list_for_user(
+    pool: &sqlx::AnyPool,
+    user_id: Uuid,
+) -> Result<Vec<Session>, sqlx::Error> {
+    sqlx::query_as::<_, Session>(
+        "SELECT * FROM sessions WHERE user_id = ? AND expires_at > ? ORDER BY last_used_at DESC",
+    )
+    .bind(user_id.to_string())
+    .bind(sql_timestamp(Utc::now()))
+    .fetch_all(pool)
+    .await
+}
+
+/// Whether the session still exists, i.e. hasn't been revoked or cleared out.
+#[tracing::instrument(skip_all)]
+pub async fn exists(pool: &sqlx::AnyPool, id: Uuid) -> Result<bool, sqlx::Error> {
+    let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM sessions WHERE id = ?")
+        .bind(id.to_string())
+        .fetch_one(pool)
+        .await?;
+    Ok(count > 0)
+}
+
+/// Revoke one of the user's sessions. Returns whether it existed.
+#[tracing::instrument(skip_all)]
+pub async fn delete(pool: &sqlx::AnyPool, id: Uuid, user_id: Uuid) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM sessions WHERE id = ? AND user_id = ?")
+        .bind(id.to_string())
+        .bind(user_id.to_string())
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Revoke all of the user's sessions except `keep`. Returns how many went.
+#[tracing::instrument(skip_all)]
+pub async fn delete_others<'e, E>(
+    executor: E,
+    user_id: Uuid,
+    keep: Option<Uuid>,
+) -> Result<u64, sqlx::Error>
+where
+    E: sqlx::Executor<'e, Database = sqlx::Any>,
+{
+    let result = match keep {
+        Some(keep) => {
+            sqlx::query("DELETE FROM sessions WHERE user_id = ? AND id != ?")
+                .bind(user_id.to_string())
+                .bind(keep.to_string())
+                .execute(executor)
+                .await?
+        }
+        None => {
+            sqlx::query("DELETE FROM sessions WHERE user_id = ?")
+                .bind(user_id.to_string())
+                .execute(executor)
+                .await?
+        }
+    };
+    Ok(result.rows_affected())
+}
@@ -0,0 +1,178 @@
+//! Settings history repository — channel topic changes and versioned server
+//! settings.
+//!
+//! Both tables are append-only. Server settings versions are numbered per
+//! server starting at 1; rolling back adds a new version rather than
+//! rewriting history.
+
+use chrono::{DateTime, Utc};
+use sqlx::Row;
+use uuid::Uuid;
+
+#[derive(Debug, Clone)]
+pub struct TopicChangeRow {
+    pub id: Uuid,
+    pub channel_id: Uuid,
+    /// Topic after the change; `None` when it was cleared.
+    pub topic: Option<String>,
+    pub changed_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl<'r> sqlx::FromRow<'r, sqlx::any::AnyRow> for TopicChangeRow {
+    fn from_row(row: &'r sqlx::any::AnyRow) -> Result<Self, sqlx::Error> {
+        use crate::any_compat::*;
+        Ok(TopicChangeRow {
+            id: get_uuid(row, "id")?,
+            channel_id: get_uuid(row, "channel_id")?,
+            topic: row.try_get("topic")?,
+            changed_by: get_opt_uuid(row, "changed_by")?,
+            created_at: get_datetime(row, "created_at")?,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SettingsVersionRow {
+    pub id: Uuid,
+    pub server_id: Uuid,
+    pub version: i32,
+    /// A serialized `ServerSettings` snapshot.
+    pub settings: serde_json::Value,
+    /// `None` for the baseline version and for users since deleted.
+    pub changed_by: Option<Uuid>,
+    /// The version this one restored, if it was a rollback.
+    pub rollback_of: Option<i32>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl<'r> sqlx::FromRow<'r, sqlx::any::AnyRow> for SettingsVersionRow {
+    fn from_row(row: &'r sqlx::any::AnyRow) -> Result<Self, sqlx::Error> {
+        use crate::any_compat::*;
+        Ok(SettingsVersionRow {
+            id: get_uuid(row, "id")?,
+            server_id: get_uuid(row, "server_id")?,
+            version: row.try_get("version")?,
+            settings: get_json_value(row, "settings")?,
+            changed_by: get_opt_uuid(row, "changed_by")?,
+            rollback_of: row.try_get("rollback_of")?,
+            created_at: get_datetime(row, "created_at")?,
+        })
+    }
+}
+
+// ============================================================
+// Channel topics
+// ============================================================
+
+/// Record a channel's new topic.
+#[tracing::instrument(skip_all)]
+pub async fn record_topic_change(
+    pool: &sqlx::AnyPool,
+    channel_id: Uuid,
+    topic: Option<&str>,
+    changed_by: Uuid,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO channel_topic_history (id, channel_id, topic, changed_by, created_at)
+        VALUES (?, ?, ?, ?, CURRENT_TIMESTAMP)
+        "#,
+    )
+    .bind(Uuid::now_v7().to_string())
+    .bind(channel_id.to_string())
+    .bind(topic)
+    .bind(changed_by.to_string())
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// A channel's topic changes, newest first.
+#[tracing::instrument(skip_all)]
+pub async fn list_topic_changes(
+    pool: &sqlx::AnyPool,
+    channel_id: Uuid,
+    limit: i64,
+) -> Result<Vec<TopicChangeRow>, sqlx::Error> {
+    sqlx::query_as::<_, TopicChangeRow>(
+        "SELECT * FROM channel_topic_history WHERE channel_id = ? ORDER BY id DESC LIMIT ?",
+    )
+    .bind(channel_id.to_string())
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+}
+
+// ============================================================
+// Server settings
+// ============================================================
+
+/// Append a settings snapshot as the server's next version.
+#[tracing::instrument(skip_all)]
+pub async fn create_version(
+    pool: &sqlx::AnyPool,
+    server_id: Uuid,
+    settings: &serde_json::Value,
+    changed_by: Option<Uuid>,
+    rollback_of: Option<i32>,
+) -> Result<SettingsVersionRow, sqlx::Error> {
+    sqlx::query_as::<_, SettingsVersionRow>(
+        r#"
+        INSERT INTO server_settings_versions (id, server_id, version, settings, changed_by, rollback_of, created_at)
+        SELECT ?, ?, COALESCE(MAX(version), 0) + 1, ?, ?, ?, CURRENT_TIMESTAMP
+        FROM server_settings_versions WHERE server_id = ?
+        RETURNING *
+        "#,
+    )
+    .bind(Uuid::now_v7().to_string())
+    .bind(server_id.to_string())
+    .bind(settings.to_string())
+    .bind(changed_by.map(|u| u.to_string()))
+    .bind(rollback_of)
+    .bind(server_id.to_string())
+    .fetch_one(pool)
+    .await
+}
+
+/// Whether any settings version has been recorded for a server.
+#[tracing::instrument(skip_all)]
+pub async fn has_versions(pool: &sqlx::AnyPool, server_id: Uuid) -> Result<bool, sqlx::Error> {
+    let row = sqlx::query("SELECT COUNT(*) AS n FROM server_settings_versions WHERE server_id = ?")
+        .bind(server_id.to_string())
+        .fetch_one(pool)
+        .await?;
+    Ok(row.try_get::<i64, _>("n")? > 0)
+}
+
+/// A server's settings versions, newest first.
+#[tracing::instrument(skip_all)]
+pub async fn list_versions(
+    pool: &sqlx::AnyPool,
+    server_id: Uuid,
+    limit: i64,
+) -> Result<Vec<SettingsVersionRow>, sqlx::Error> {
+    sqlx::query_as::<_, SettingsVersionRow>(
+        "SELECT * FROM server_settings_versions WHERE server_id = ? ORDER BY version DESC LIMIT ?",
+    )
+    .bind(server_id.to_string())
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+}
+
+/// One settings version of a server.
+#[tracing::instrument(skip_all)]
+pub async fn find_version(
+    pool: &sqlx::AnyPool,
+    server_id: Uuid,
+    version: i32,
+) -> Result<Option<SettingsVersionRow>, sqlx::Error> {
+    sqlx::query_as::<_, SettingsVersionRow>(
+        "SELECT * FROM server_settings_versions WHERE server_id = ? AND version = ?",
+    )
+    .bind(server_id.to_string())
+    .bind(version)
+    .fetch_optional(pool)
+    .await
+}
@@ -17,9 +17,11 @@ fn row_to_command(row: &sqlx::any::AnyRow) -> SlashCommand {
         name: row.try_get("name").unwrap_or_default(),
         name_localizations: row.try_get::<Option<String>, _>("name_localizations").unwrap_or(None)
             .and_then(|s| serde_json::from_str(&s).ok()),
+        name_localized: None,
         description: row.try_get("description").unwrap_or_default(),
         description_localizations: row.try_get::<Option<String>, _>("description_localizations").unwrap_or(None)
             .and_then(|s| serde_json::from_str(&s).ok()),
+        description_localized: None,
         options,
         default_member_permissions: row.try_get("default_member_permissions").unwrap_or(None),
         dm_permission: row.try_get("dm_permission").unwrap_or(true),
@@ -51,6 +53,18 @@ fn row_to_interaction(row: &sqlx::any::AnyRow) -> Interaction {
 // Slash Commands
 // ============================================================================
 
+/// One command of a bulk overwrite.
+pub struct NewCommand {
+    pub id: Uuid,
+    pub name: String,
+    pub description: String,
+    pub options: Vec<CommandOption>,
+    pub command_type: i32,
+    pub name_localizations: Option<serde_json::Value>,
+    pub description_localizations: Option<serde_json::Value>,
+}
+
+#[tracing::instrument(skip_all)]
 pub async fn get_command(pool: &sqlx::AnyPool, command_id: Uuid) -> Result<Option<SlashCommand>> {
     let row = sqlx::query("SELECT * FROM slash_commands WHERE id = ?")
         .bind(command_id.to_string())
@@ -59,6 +73,7 @@ pub async fn get_command(pool: &sqlx::AnyPool, command_id: Uuid) -> Result<Optio
     Ok(row.as_ref().map(row_to_command))
 }
 
+#[tracing::instrument(skip_all)]
 pub async fn get_global_commands(pool: &sqlx::AnyPool, application_id: Uuid) -> Result<Vec<SlashCommand>> {
     let rows = sqlx::query(
         "SELECT * FROM slash_commands WHERE application_id = ? AND server_id IS NULL AND enabled = true ORDER BY name",
@@ -69,6 +84,7 @@ pub async fn get_global_commands(pool: &sqlx::AnyPool, application_id: Uuid) ->
     Ok(rows.iter().map(row_to_command).collect())
 }
 
+#[tracing::instrument(skip_all)]
 pub async fn get_server_commands(
     pool: &sqlx::AnyPool,
     application_id: Uuid,
@@ -84,6 +100,7 @@ pub async fn get_server_commands(
     Ok(rows.iter().map(row_to_command).collect())
 }
 
+#[tracing::instrument(skip_all)]
 pub async fn get_all_server_commands(pool: &sqlx::AnyPool, server_id: Uuid) -> Result<Vec<SlashCommand>> {
     let rows = sqlx::query(
         "SELECT * FROM slash_commands WHERE server_id = ? AND enabled = true ORDER BY name",
@@ -94,6 +111,7 @@ pub async fn get_all_server_commands(pool: &sqlx::AnyPool, server_id: Uuid) -> R
     Ok(rows.iter().map(row_to_command).collect())
 }
 
+#[tracing::instrument(skip_all)]
 pub async fn upsert_command(
     pool: &sqlx::AnyPool,
     id: Uuid,
@@ -105,17 +123,22 @@ pub async fn upsert_command(
     command_type: i32,
     default_member_permissions: Option<&str>,
     dm_permission: bool,
+    name_localizations: Option<&serde_json::Value>,
+    description_localizations: Option<&serde_json::Value>,
 ) -> Result<SlashCommand> {
     let opts = serde_json::to_string(options)?;
     let row = sqlx::query(
         r#"INSERT INTO slash_commands
                (id, application_id, server_id, name, description, options,
-                command_type, default_member_permissions, dm_permission)
-           VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+                command_type, default_member_permissions, dm_permission,
+                name_localizations, description_localizations)
+           VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
            ON CONFLICT (application_id, name)
            DO UPDATE SET
                description = EXCLUDED.description,
                options     = EXCLUDED.options,
+               name_localizations = EXCLUDED.name_localizations,
+               description_localizations = EXCLUDED.description_localizations,
                command_type = EXCLUDED.command_type,
                default_member_permissions = EXCLUDED.default_member_permissions,
                dm_permission = EXCLUDED.dm_permission,
@@ -131,11 +154,14 @@ pub async fn upsert_command(
     .bind(command_type)
     .bind(default_member_permissions)
     .bind(dm_permission)
+    .bind(name_localizations.map(|v| v.to_string()))
+    .bind(description_localizations.map(|v| v.to_string()))
     .fetch_one(pool)
     .await?;
     Ok(row_to_command(&row))
 }
 
+#[tracing::instrument(skip_all)]
 pub async fn delete_command(pool: &sqlx::AnyPool, command_id: Uuid) -> Result<bool> {
     let result = sqlx::query("DELETE FROM slash_commands WHERE id = ?")
         .bind(command_id.to_string())
@@ -144,10 +170,11 @@ pub async fn delete_command(pool: &sqlx::AnyPool, command_id: Uuid) -> Result<bo
     Ok(result.rows_affected() > 0)
 }
 
+#[tracing::instrument(skip_all)]
 pub async fn bulk_overwrite_global_commands(
     pool: &sqlx::AnyPool,
     application_id: Uuid,
-    commands: &[(Uuid, String, String, serde_json::Value, i32)],
+    commands: &[NewCommand],
 ) -> Result<Vec<SlashCommand>> {
     sqlx::query("DELETE FROM slash_commands WHERE application_id = ? AND server_id IS NULL")
         .bind(application_id.to_string())
@@ -155,19 +182,22 @@ pub async fn bulk_overwrite_global_commands(
         .await?;
 
     let mut result = Vec::new();
-    for (id, name, description, options, cmd_type) in commands {
+    for cmd in commands {
         let row = sqlx::query(
             r#"INSERT INTO slash_commands
-                   (id, application_id, name, description, options, command_type)
-               VALUES (?, ?, ?, ?, ?, ?)
+                   (id, application_id, name, description, options, command_type,
+                    name_localizations, description_localizations)
+               VALUES (?, ?, ?, ?, ?, ?, ?, ?)
                RETURNING *"#,
         )
-        .bind(id.to_string())
+        .bind(cmd.id.to_string())
         .bind(application_id.to_string())
-        .bind(name)
-        .bind(description)
-        .bind(serde_json::to_string(options).unwrap_or_default())
-        .bind(cmd_type)
+        .bind(&cmd.name)
+        .bind(&cmd.description)
+        .bind(serde_json::to_string(&cmd.options).unwrap_or_default())
+        .bind(cmd.command_type)
+        .bind(cmd.name_localizations.as_ref().map(|v| v.to_string()))
+        .bind(cmd.description_localizations.as_ref().map(|v| v.to_string()))
         .fetch_one(pool)
         .await?;
         result.push(row_to_command(&row));
@@ -179,6 +209,7 @@ pub async fn bulk_overwrite_global_commands(
 // Interactions
 // ============================================================================
 
+#[tracing::instrument(skip_all)]
 pub async fn create_interaction(
     pool: &sqlx::AnyPool,
     id: Uuid,
@@ -209,6 +240,7 @@ pub async fn create_interaction(
     Ok(row_to_interaction(&row))
 }
 
+#[tracing::instrument(skip_all)]
 pub async fn get_interaction(pool: &sqlx::AnyPool, interaction_id: Uuid) -> Result<Option<Interaction>> {
     let row = sqlx::query("SELECT * FROM interactions WHERE id = ?")
         .bind(interaction_id.to_string())
@@ -219,11 +251,12 @@ pub async fn get_interaction(pool: &sqlx::AnyPool, interaction_id: Uuid) -> Resu
 
 /// Bulk overwrite all commands for a given application in a specific server.
 /// Deletes existing server commands for that app, then inserts the new set.
+#[tracing::instrument(skip_all)]
 pub async fn bulk_overwrite_server_commands(
     pool: &sqlx::AnyPool,
     application_id: Uuid,
     server_id: Uuid,
-    commands: &[(Uuid, String, String, serde_json::Value, i32)],
+    commands: &[NewCommand],
 ) -> Result<Vec<SlashCommand>> {
     sqlx::query(
         "DELETE FROM slash_commands WHERE application_id = ? AND server_id = ?",
@@ -234,20 +267,23 @@ pub async fn bulk_overwrite_server_commands(
     .await?;
 
     let mut result = Vec::new();
-    for (id, name, description, options, cmd_type) in commands {
+    for cmd in commands {
         let row = sqlx::query(
             r#"INSERT INTO slash_commands
-                   (id, application_id, server_id, name, description, options, command_type)
-               VALUES (?, ?, ?, ?, ?, ?, ?)
+                   (id, application_id, server_id, name, description, options, command_type,
+                    name_localizations, description_localizations)
+               VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
                RETURNING *"#,
         )
-        .bind(id.to_string())
+        .bind(cmd.id.to_string())
         .bind(application_id.to_string())
         .bind(server_id.to_string())
-        .bind(name)
-        .bind(description)
-        .bind(serde_json::to_string(options).unwrap_or_default())
-        .bind(cmd_type)
+        .bind(&cmd.name)
+        .bind(&cmd.description)
+        .bind(serde_json::to_string(&cmd.options).unwrap_or_default())
+        .bind(cmd.command_type)
+        .bind(cmd.name_localizations.as_ref().map(|v| v.to_string()))
+        .bind(cmd.description_localizations.as_ref().map(|v| v.to_string()))
         .fetch_one(pool)
         .await?;
         result.push(row_to_command(&row));
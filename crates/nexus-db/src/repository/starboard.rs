@@ -0,0 +1,196 @@
+//! Starboard repository — per-server starboard configuration and the
+//! messages that have been reposted to the starboard channel.
+
+use chrono::{DateTime, Utc};
+use sqlx::Row;
+use uuid::Uuid;
+
+#[derive(Debug, Clone)]
+pub struct StarboardSettingsRow {
+    pub server_id: Uuid,
+    pub enabled: bool,
+    pub channel_id: Option<Uuid>,
+    pub emoji: String,
+    pub threshold: i32,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl<'r> sqlx::FromRow<'r, sqlx::any::AnyRow> for StarboardSettingsRow {
+    fn from_row(row: &'r sqlx::any::AnyRow) -> Result<Self, sqlx::Error> {
+        use crate::any_compat::*;
+        Ok(StarboardSettingsRow {
+            server_id: get_uuid(row, "server_id")?,
+            enabled: row.try_get("enabled")?,
+            channel_id: get_opt_uuid(row, "channel_id")?,
+            emoji: row.try_get("emoji")?,
+            threshold: row.try_get("threshold")?,
+            updated_at: get_datetime(row, "updated_at")?,
+        })
+    }
+}
+
+/// A message that crossed the threshold. `starboard_message_id` is `None`
+/// between claiming the entry and posting the repost.
+#[derive(Debug, Clone)]
+pub struct StarboardEntryRow {
+    pub message_id: Uuid,
+    pub server_id: Uuid,
+    pub starboard_message_id: Option<Uuid>,
+    pub star_count: i32,
+    pub created_at: DateTime<Utc>,
+}
+
+impl<'r> sqlx::FromRow<'r, sqlx::any::AnyRow> for StarboardEntryRow {
+    fn from_row(row: &'r sqlx::any::AnyRow) -> Result<Self, sqlx::Error> {
+        use crate::any_compat::*;
+        Ok(StarboardEntryRow {
+            message_id: get_uuid(row, "message_id")?,
+            server_id: get_uuid(row, "server_id")?,
+            starboard_message_id: get_opt_uuid(row, "starboard_message_id")?,
+            star_count: row.try_get("star_count")?,
+            created_at: get_datetime(row, "created_at")?,
+        })
+    }
+}
+
+/// Fetch a server's starboard settings. `None` means nothing is configured.
+#[tracing::instrument(skip_all)]
+pub async fn get_settings(
+    pool: &sqlx::AnyPool,
+    server_id: Uuid,
+) -> Result<Option<StarboardSettingsRow>, sqlx::Error> {
+    sqlx::query_as::<_, StarboardSettingsRow>(
+        "SELECT * FROM server_starboard_settings WHERE server_id = ?",
+    )
+    .bind(server_id.to_string())
+    .fetch_optional(pool)
+    .await
+}
+
+/// Insert or replace a server's starboard settings.
+#[tracing::instrument(skip_all)]
+pub async fn upsert_settings(
+    pool: &sqlx::AnyPool,
+    settings: &StarboardSettingsRow,
+) -> Result<StarboardSettingsRow, sqlx::Error> {
+    sqlx::query_as::<_, StarboardSettingsRow>(
+        r#"
+        INSERT INTO server_starboard_settings (
+            server_id, enabled, channel_id, emoji, threshold, updated_at
+        )
+        VALUES (?, ?, ?, ?, ?, CURRENT_TIMESTAMP)
+        ON CONFLICT (server_id) DO UPDATE SET
+            enabled = excluded.enabled,
+            channel_id = excluded.channel_id,
+            emoji = excluded.emoji,
+            threshold = excluded.threshold,
+            updated_at = CURRENT_TIMESTAMP
+        RETURNING *
+        "#,
+    )
+    .bind(settings.server_id.to_string())
+    .bind(settings.enabled)
+    .bind(settings.channel_id.map(|u| u.to_string()))
+    .bind(&settings.emoji)
+    .bind(settings.threshold)
+    .fetch_one(pool)
+    .await
+}
+
+/// Look up the starboard entry for a source message.
+#[tracing::instrument(skip_all)]
+pub async fn find_entry(
+    pool: &sqlx::AnyPool,
+    message_id: Uuid,
+) -> Result<Option<StarboardEntryRow>, sqlx::Error> {
+    sqlx::query_as::<_, StarboardEntryRow>("SELECT * FROM starboard_entries WHERE message_id = ?")
+        .bind(message_id.to_string())
+        .fetch_optional(pool)
+        .await
+}
+
+/// Claim a source message for the starboard. Returns false when it already
+/// has an entry, so concurrent reactions repost it only once.
+#[tracing::instrument(skip_all)]
+pub async fn claim_entry(
+    pool: &sqlx::AnyPool,
+    message_id: Uuid,
+    server_id: Uuid,
+    star_count: i32,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        INSERT INTO starboard_entries (message_id, server_id, star_count, created_at)
+        VALUES (?, ?, ?, CURRENT_TIMESTAMP)
+        ON CONFLICT (message_id) DO NOTHING
+        "#,
+    )
+    .bind(message_id.to_string())
+    .bind(server_id.to_string())
+    .bind(star_count)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Record the repost created for a claimed entry.
+#[tracing::instrument(skip_all)]
+pub async fn set_starboard_message(
+    pool: &sqlx::AnyPool,
+    message_id: Uuid,
+    starboard_message_id: Uuid,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE starboard_entries SET starboard_message_id = ? WHERE message_id = ?")
+        .bind(starboard_message_id.to_string())
+        .bind(message_id.to_string())
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Update the reaction count shown on an entry's repost.
+#[tracing::instrument(skip_all)]
+pub async fn set_star_count(
+    pool: &sqlx::AnyPool,
+    message_id: Uuid,
+    star_count: i32,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE starboard_entries SET star_count = ? WHERE message_id = ?")
+        .bind(star_count)
+        .bind(message_id.to_string())
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Drop an entry whose repost could not be created, so a later reaction
+/// can try again.
+#[tracing::instrument(skip_all)]
+pub async fn delete_entry(pool: &sqlx::AnyPool, message_id: Uuid) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM starboard_entries WHERE message_id = ?")
+        .bind(message_id.to_string())
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// A server's starred messages, newest first.
+#[tracing::instrument(skip_all)]
+pub async fn list_entries(
+    pool: &sqlx::AnyPool,
+    server_id: Uuid,
+    limit: i64,
+) -> Result<Vec<StarboardEntryRow>, sqlx::Error> {
+    sqlx::query_as::<_, StarboardEntryRow>(
+        r#"
+        SELECT * FROM starboard_entries
+        WHERE server_id = ? AND starboard_message_id IS NOT NULL
+        ORDER BY created_at DESC
+        LIMIT ?
+        "#,
+    )
+    .bind(server_id.to_string())
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+}
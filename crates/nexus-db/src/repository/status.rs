@@ -0,0 +1,152 @@
+//! Status repository — component self-check results and uptime history.
+//!
+//! Rows are written by the self-check background job and read by the public
+//! status page. Timestamps are bound as `YYYY-MM-DD HH:MM:SS` strings so the
+//! range comparisons behave identically on PostgreSQL and SQLite.
+
+use chrono::{DateTime, Utc};
+use sqlx::Row;
+use uuid::Uuid;
+
+/// A single self-check result.
+#[derive(Debug)]
+pub struct StatusCheckRow {
+    pub id: Uuid,
+    pub component: String,
+    pub status: String,
+    pub latency_ms: Option<i64>,
+    pub detail: Option<String>,
+    pub checked_at: DateTime<Utc>,
+}
+
+impl<'r> sqlx::FromRow<'r, sqlx::any::AnyRow> for StatusCheckRow {
+    fn from_row(row: &'r sqlx::any::AnyRow) -> Result<Self, sqlx::Error> {
+        use crate::any_compat::*;
+        Ok(StatusCheckRow {
+            id: get_uuid(row, "id")?,
+            component: row.try_get("component")?,
+            status: row.try_get("status")?,
+            latency_ms: row.try_get("latency_ms")?,
+            detail: row.try_get("detail")?,
+            checked_at: get_datetime(row, "checked_at")?,
+        })
+    }
+}
+
+/// Per-component, per-day uptime bucket.
+#[derive(Debug)]
+pub struct UptimeBucket {
+    pub component: String,
+    /// Calendar day in `YYYY-MM-DD` form (UTC).
+    pub day: String,
+    pub total_checks: i64,
+    pub operational_checks: i64,
+}
+
+impl<'r> sqlx::FromRow<'r, sqlx::any::AnyRow> for UptimeBucket {
+    fn from_row(row: &'r sqlx::any::AnyRow) -> Result<Self, sqlx::Error> {
+        Ok(UptimeBucket {
+            component: row.try_get("component")?,
+            day: row.try_get("day")?,
+            total_checks: row.try_get("total_checks")?,
+            operational_checks: row.try_get("operational_checks")?,
+        })
+    }
+}
+
+fn sql_timestamp(ts: DateTime<Utc>) -> String {
+    ts.format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+/// Record the outcome of a component probe.
+#[tracing::instrument(skip_all)]
+pub async fn record_check(
+    pool: &sqlx::AnyPool,
+    component: &str,
+    status: &str,
+    latency_ms: Option<i64>,
+    detail: Option<&str>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO status_checks (id, component, status, latency_ms, detail, checked_at)
+        VALUES (?, ?, ?, ?, ?, CURRENT_TIMESTAMP)
+        "#,
+    )
+    .bind(Uuid::now_v7().to_string())
+    .bind(component)
+    .bind(status)
+    .bind(latency_ms)
+    .bind(detail)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Latest check result for every component that has ever been probed.
+#[tracing::instrument(skip_all)]
+pub async fn latest_checks(pool: &sqlx::AnyPool) -> Result<Vec<StatusCheckRow>, sqlx::Error> {
+    sqlx::query_as::<_, StatusCheckRow>(
+        r#"
+        SELECT sc.* FROM status_checks sc
+        INNER JOIN (
+            SELECT component, MAX(checked_at) AS checked_at
+            FROM status_checks
+            GROUP BY component
+        ) latest ON latest.component = sc.component AND latest.checked_at = sc.checked_at
+        ORDER BY sc.component
+        "#,
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Daily uptime buckets since `since`, optionally restricted to one component.
+#[tracing::instrument(skip_all)]
+pub async fn uptime_buckets(
+    pool: &sqlx::AnyPool,
+    component: Option<&str>,
+    since: DateTime<Utc>,
+) -> Result<Vec<UptimeBucket>, sqlx::Error> {
+    sqlx::query_as::<_, UptimeBucket>(
+        r#"
+        SELECT
+            component,
+            SUBSTR(CAST(checked_at AS TEXT), 1, 10) AS day,
+            COUNT(*) AS total_checks,
+            SUM(CASE WHEN status = 'operational' THEN 1 ELSE 0 END) AS operational_checks
+        FROM status_checks
+        WHERE checked_at >= ?
+          AND (? IS NULL OR component = ?)
+        GROUP BY component, SUBSTR(CAST(checked_at AS TEXT), 1, 10)
+        ORDER BY component, day
+        "#,
+    )
+    .bind(sql_timestamp(since))
+    .bind(component)
+    .bind(component)
+    .fetch_all(pool)
+    .await
+}
+
+/// Delete check rows older than `before`. Returns the number of rows removed.
+#[tracing::instrument(skip_all)]
+pub async fn prune_checks(pool: &sqlx::AnyPool, before: DateTime<Utc>) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM status_checks WHERE checked_at < ?")
+        .bind(sql_timestamp(before))
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected())
+}
+
+/// Age of the oldest undelivered federation outbox entry, if any.
+#[tracing::instrument(skip_all)]
+pub async fn federation_outbox_lag(pool: &sqlx::AnyPool) -> Result<Option<chrono::Duration>, sqlx::Error> {
+    let row = sqlx::query(
+        "SELECT MIN(created_at) AS oldest FROM federation_outbox WHERE delivered_at IS NULL",
+    )
+    .fetch_one(pool)
+    .await?;
+    let oldest = crate::any_compat::get_opt_datetime(&row, "oldest")?;
+    Ok(oldest.map(|ts| Utc::now() - ts))
+}
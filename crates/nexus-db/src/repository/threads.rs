@@ -18,6 +18,7 @@ struct ThreadMemberRow {
 
 /// Create a thread record. Assumes the corresponding channel row already exists.
 #[allow(clippy::too_many_arguments)]
+#[tracing::instrument(skip_all)]
 pub async fn create_thread(
     pool: &sqlx::AnyPool,
     channel_id: Uuid,
@@ -57,6 +58,7 @@ pub async fn create_thread(
 // ============================================================
 
 /// Get a thread by its channel ID, including parent_channel_id.
+#[tracing::instrument(skip_all)]
 pub async fn find_by_id(pool: &sqlx::AnyPool, channel_id: Uuid) -> Result<Option<ThreadRow>, sqlx::Error> {
     sqlx::query_as::<_, ThreadRow>(
         r#"
@@ -72,6 +74,7 @@ pub async fn find_by_id(pool: &sqlx::AnyPool, channel_id: Uuid) -> Result<Option
 }
 
 /// List active (non-archived) threads in a channel.
+#[tracing::instrument(skip_all)]
 pub async fn list_active(
     pool: &sqlx::AnyPool,
     parent_channel_id: Uuid,
@@ -96,6 +99,7 @@ pub async fn list_active(
 }
 
 /// List archived threads in a channel.
+#[tracing::instrument(skip_all)]
 pub async fn list_archived(
     pool: &sqlx::AnyPool,
     parent_channel_id: Uuid,
@@ -144,6 +148,7 @@ pub async fn list_archived(
 // ============================================================
 
 /// Update thread metadata.
+#[tracing::instrument(skip_all)]
 pub async fn update_thread(
     pool: &sqlx::AnyPool,
     channel_id: Uuid,
@@ -193,6 +198,7 @@ pub async fn update_thread(
 }
 
 /// Increment the thread message count.
+#[tracing::instrument(skip_all)]
 pub async fn increment_message_count(pool: &sqlx::AnyPool, channel_id: Uuid) -> Result<(), sqlx::Error> {
     sqlx::query(
         "UPDATE threads SET message_count = message_count + 1, updated_at = CURRENT_TIMESTAMP WHERE channel_id = ?",
@@ -208,6 +214,7 @@ pub async fn increment_message_count(pool: &sqlx::AnyPool, channel_id: Uuid) ->
 // ============================================================
 
 /// Add a user as a thread member.
+#[tracing::instrument(skip_all)]
 pub async fn add_member(pool: &sqlx::AnyPool, thread_id: Uuid, user_id: Uuid) -> Result<(), sqlx::Error> {
     sqlx::query(
         r#"
@@ -239,6 +246,7 @@ pub async fn add_member(pool: &sqlx::AnyPool, thread_id: Uuid, user_id: Uuid) ->
 }
 
 /// Remove a user from a thread.
+#[tracing::instrument(skip_all)]
 pub async fn remove_member(
     pool: &sqlx::AnyPool,
     thread_id: Uuid,
@@ -271,6 +279,7 @@ pub async fn remove_member(
 }
 
 /// Check if a user is a member of a thread.
+#[tracing::instrument(skip_all)]
 pub async fn is_member(pool: &sqlx::AnyPool, thread_id: Uuid, user_id: Uuid) -> Result<bool, sqlx::Error> {
     let row = sqlx::query(
         "SELECT 1 FROM thread_members WHERE thread_id = ? AND user_id = ?",
@@ -283,6 +292,7 @@ pub async fn is_member(pool: &sqlx::AnyPool, thread_id: Uuid, user_id: Uuid) ->
 }
 
 /// List all members of a thread.
+#[tracing::instrument(skip_all)]
 pub async fn list_members(
     pool: &sqlx::AnyPool,
     thread_id: Uuid,
@@ -0,0 +1,196 @@
+//! Transcript repository — speech-to-text results for voice messages and
+//! voice-channel recordings.
+//!
+//! Rows are queued as `pending` when audio lands in storage, claimed by the
+//! transcription job, and finally marked `completed` or `failed`.
+
+use chrono::{DateTime, Utc};
+use sqlx::Row;
+use uuid::Uuid;
+
+/// Source kind for a voice-message attachment.
+pub const SOURCE_ATTACHMENT: &str = "attachment";
+/// Source kind for a voice-channel recording.
+pub const SOURCE_RECORDING: &str = "recording";
+
+#[derive(Debug)]
+pub struct TranscriptRow {
+    pub id: Uuid,
+    pub source_type: String,
+    pub source_id: Uuid,
+    pub storage_key: String,
+    pub content_type: String,
+    pub channel_id: Option<Uuid>,
+    pub status: String,
+    pub language: Option<String>,
+    pub text: Option<String>,
+    pub segments: serde_json::Value,
+    pub provider: Option<String>,
+    pub attempts: i32,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+impl<'r> sqlx::FromRow<'r, sqlx::any::AnyRow> for TranscriptRow {
+    fn from_row(row: &'r sqlx::any::AnyRow) -> Result<Self, sqlx::Error> {
+        use crate::any_compat::*;
+        Ok(TranscriptRow {
+            id: get_uuid(row, "id")?,
+            source_type: row.try_get("source_type")?,
+            source_id: get_uuid(row, "source_id")?,
+            storage_key: row.try_get("storage_key")?,
+            content_type: row.try_get("content_type")?,
+            channel_id: get_opt_uuid(row, "channel_id")?,
+            status: row.try_get("status")?,
+            language: row.try_get("language")?,
+            text: row.try_get("text")?,
+            segments: get_json_value(row, "segments")?,
+            provider: row.try_get("provider")?,
+            attempts: row.try_get("attempts")?,
+            last_error: row.try_get("last_error")?,
+            created_at: get_datetime(row, "created_at")?,
+            completed_at: get_opt_datetime(row, "completed_at")?,
+        })
+    }
+}
+
+/// Queue a source for transcription. Re-queuing an existing source is a no-op.
+#[tracing::instrument(skip_all)]
+pub async fn enqueue(
+    pool: &sqlx::AnyPool,
+    source_type: &str,
+    source_id: Uuid,
+    storage_key: &str,
+    content_type: &str,
+    channel_id: Option<Uuid>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO transcripts (id, source_type, source_id, storage_key, content_type, channel_id, status, created_at)
+        VALUES (?, ?, ?, ?, ?, ?, 'pending', CURRENT_TIMESTAMP)
+        ON CONFLICT (source_type, source_id) DO NOTHING
+        "#,
+    )
+    .bind(Uuid::now_v7().to_string())
+    .bind(source_type)
+    .bind(source_id.to_string())
+    .bind(storage_key)
+    .bind(content_type)
+    .bind(channel_id.map(|u| u.to_string()))
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Find the transcript for a given source.
+#[tracing::instrument(skip_all)]
+pub async fn find_for_source(
+    pool: &sqlx::AnyPool,
+    source_type: &str,
+    source_id: Uuid,
+) -> Result<Option<TranscriptRow>, sqlx::Error> {
+    sqlx::query_as::<_, TranscriptRow>(
+        "SELECT * FROM transcripts WHERE source_type = ? AND source_id = ?",
+    )
+    .bind(source_type)
+    .bind(source_id.to_string())
+    .fetch_optional(pool)
+    .await
+}
+
+/// Claim up to `limit` pending transcripts, oldest first.
+///
+/// Each row is flipped to `processing` with a guarded update, so two workers
+/// racing on the same row never both win it.
+#[tracing::instrument(skip_all)]
+pub async fn claim_pending(pool: &sqlx::AnyPool, limit: i64) -> Result<Vec<TranscriptRow>, sqlx::Error> {
+    let candidates = sqlx::query_as::<_, TranscriptRow>(
+        "SELECT * FROM transcripts WHERE status = 'pending' ORDER BY created_at LIMIT ?",
+    )
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    let mut claimed = Vec::with_capacity(candidates.len());
+    for mut row in candidates {
+        let result = sqlx::query(
+            r#"
+            UPDATE transcripts
+            SET status = 'processing', attempts = attempts + 1
+            WHERE id = ? AND status = 'pending'
+            "#,
+        )
+        .bind(row.id.to_string())
+        .execute(pool)
+        .await?;
+        if result.rows_affected() == 1 {
+            row.status = "processing".into();
+            row.attempts += 1;
+            claimed.push(row);
+        }
+    }
+    Ok(claimed)
+}
+
+/// Store a finished transcript.
+#[tracing::instrument(skip_all)]
+pub async fn complete(
+    pool: &sqlx::AnyPool,
+    id: Uuid,
+    text: &str,
+    language: Option<&str>,
+    segments: &serde_json::Value,
+    provider: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        UPDATE transcripts
+        SET status = 'completed', text = ?, language = ?, segments = ?, provider = ?,
+            last_error = NULL, completed_at = CURRENT_TIMESTAMP
+        WHERE id = ?
+        "#,
+    )
+    .bind(text)
+    .bind(language)
+    .bind(segments.to_string())
+    .bind(provider)
+    .bind(id.to_string())
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Record a failed attempt. The row goes back to `pending` until
+/// `max_attempts` is reached, after which it is left as `failed`.
+#[tracing::instrument(skip_all)]
+pub async fn fail(
+    pool: &sqlx::AnyPool,
+    id: Uuid,
+    error: &str,
+    max_attempts: i32,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        UPDATE transcripts
+        SET status = CASE WHEN attempts >= ? THEN 'failed' ELSE 'pending' END,
+            last_error = ?
+        WHERE id = ?
+        "#,
+    )
+    .bind(max_attempts)
+    .bind(error)
+    .bind(id.to_string())
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Put rows left in `processing` by a crashed worker back in the queue.
+#[tracing::instrument(skip_all)]
+pub async fn requeue_stale(pool: &sqlx::AnyPool) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query("UPDATE transcripts SET status = 'pending' WHERE status = 'processing'")
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected())
+}
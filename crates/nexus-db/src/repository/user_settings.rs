@@ -0,0 +1,65 @@
+//! User settings repository — per-user settings synced across clients.
+
+use nexus_common::models::user_settings::{ServerLayout, UserSettings, UserVolumes};
+use uuid::Uuid;
+
+/// A user's synced settings, or the defaults (version 0) if they never
+/// saved any.
+#[tracing::instrument(skip_all)]
+pub async fn get(pool: &sqlx::AnyPool, user_id: Uuid) -> Result<UserSettings, sqlx::Error> {
+    let settings = sqlx::query_as::<_, UserSettings>("SELECT * FROM user_settings WHERE user_id = ?")
+        .bind(user_id.to_string())
+        .fetch_optional(pool)
+        .await?;
+    Ok(settings.unwrap_or_default())
+}
+
+/// Replace a user's server list layout, bumping the settings version.
+#[tracing::instrument(skip_all)]
+pub async fn set_server_layout(
+    pool: &sqlx::AnyPool,
+    user_id: Uuid,
+    layout: &ServerLayout,
+) -> Result<UserSettings, sqlx::Error> {
+    let layout = serde_json::to_string(layout).map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
+    sqlx::query_as::<_, UserSettings>(
+        r#"
+        INSERT INTO user_settings (user_id, server_layout, version, updated_at)
+        VALUES (?, ?, 1, CURRENT_TIMESTAMP)
+        ON CONFLICT (user_id) DO UPDATE SET
+            server_layout = excluded.server_layout,
+            version = user_settings.version + 1,
+            updated_at = CURRENT_TIMESTAMP
+        RETURNING *
+        "#,
+    )
+    .bind(user_id.to_string())
+    .bind(layout)
+    .fetch_one(pool)
+    .await
+}
+
+/// Replace a user's per-user voice volumes, bumping the settings version.
+#[tracing::instrument(skip_all)]
+pub async fn set_user_volumes(
+    pool: &sqlx::AnyPool,
+    user_id: Uuid,
+    volumes: &UserVolumes,
+) -> Result<UserSettings, sqlx::Error> {
+    let volumes = serde_json::to_string(volumes).map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
+    sqlx::query_as::<_, UserSettings>(
+        r#"
+        INSERT INTO user_settings (user_id, user_volumes, version, updated_at)
+        VALUES (?, ?, 1, CURRENT_TIMESTAMP)
+        ON CONFLICT (user_id) DO UPDATE SET
+            user_volumes = excluded.user_volumes,
+            version = user_settings.version + 1,
+            updated_at = CURRENT_TIMESTAMP
+        RETURNING *
+        "#,
+    )
+    .bind(user_id.to_string())
+    .bind(volumes)
+    .fetch_one(pool)
+    .await
+}
@@ -5,6 +5,7 @@ use nexus_common::models::user::User;
 use uuid::Uuid;
 
 /// Create a new user account.
+#[tracing::instrument(skip_all)]
 pub async fn create_user(
     pool: &sqlx::AnyPool,
     id: Uuid,
@@ -28,6 +29,7 @@ pub async fn create_user(
 }
 
 /// Find a user by their unique ID.
+#[tracing::instrument(skip_all)]
 pub async fn find_by_id(pool: &sqlx::AnyPool, id: Uuid) -> Result<Option<User>, sqlx::Error> {
     sqlx::query_as::<_, User>("SELECT * FROM users WHERE id = ?")
         .bind(id.to_string())
@@ -36,6 +38,7 @@ pub async fn find_by_id(pool: &sqlx::AnyPool, id: Uuid) -> Result<Option<User>,
 }
 
 /// Find a user by username (case-insensitive).
+#[tracing::instrument(skip_all)]
 pub async fn find_by_username(pool: &sqlx::AnyPool, username: &str) -> Result<Option<User>, sqlx::Error> {
     sqlx::query_as::<_, User>("SELECT * FROM users WHERE LOWER(username) = LOWER(?)")
         .bind(username)
@@ -44,6 +47,7 @@ pub async fn find_by_username(pool: &sqlx::AnyPool, username: &str) -> Result<Op
 }
 
 /// Find a user by email.
+#[tracing::instrument(skip_all)]
 pub async fn find_by_email(pool: &sqlx::AnyPool, email: &str) -> Result<Option<User>, sqlx::Error> {
     sqlx::query_as::<_, User>("SELECT * FROM users WHERE LOWER(email) = LOWER(?)")
         .bind(email)
@@ -52,6 +56,7 @@ pub async fn find_by_email(pool: &sqlx::AnyPool, email: &str) -> Result<Option<U
 }
 
 /// Update user profile fields.
+#[tracing::instrument(skip_all)]
 pub async fn update_user(
     pool: &sqlx::AnyPool,
     id: Uuid,
@@ -59,6 +64,7 @@ pub async fn update_user(
     display_name: Option<&str>,
     bio: Option<&str>,
     status: Option<&str>,
+    locale: Option<&str>,
 ) -> Result<User, sqlx::Error> {
     sqlx::query_as::<_, User>(
         r#"
@@ -67,35 +73,69 @@ pub async fn update_user(
             display_name = COALESCE(?, display_name),
             bio = COALESCE(?, bio),
             status = COALESCE(?, status),
+            locale = COALESCE(?, locale),
             updated_at = CURRENT_TIMESTAMP
         WHERE id = ?
         RETURNING *
         "#,
     )
-    .bind(id.to_string())
     .bind(username)
     .bind(display_name)
     .bind(bio)
     .bind(status)
+    .bind(locale)
+    .bind(id.to_string())
     .fetch_one(pool)
     .await
 }
 
-/// Update user presence state.
+/// Store a user's aggregate presence (see `crate::presence`).
+#[tracing::instrument(skip_all)]
 pub async fn update_presence(
     pool: &sqlx::AnyPool,
     id: Uuid,
     presence: &str,
 ) -> Result<(), sqlx::Error> {
     sqlx::query("UPDATE users SET presence = ?::user_presence WHERE id = ?")
-        .bind(id.to_string())
         .bind(presence)
+        .bind(id.to_string())
         .execute(pool)
         .await?;
     Ok(())
 }
 
+/// Replace a user's password hash. Returns whether the user exists.
+#[tracing::instrument(skip_all)]
+pub async fn update_password(pool: &sqlx::AnyPool, id: Uuid, password_hash: &str) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("UPDATE users SET password_hash = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?")
+        .bind(password_hash)
+        .bind(id.to_string())
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Set the `add` flags and clear the `remove` ones. Returns the updated
+/// user, or `None` if there is no such user.
+#[tracing::instrument(skip_all)]
+pub async fn update_flags(
+    pool: &sqlx::AnyPool,
+    id: Uuid,
+    add: i64,
+    remove: i64,
+) -> Result<Option<User>, sqlx::Error> {
+    sqlx::query_as::<_, User>(
+        "UPDATE users SET flags = (flags | ?) & ?, updated_at = CURRENT_TIMESTAMP WHERE id = ? RETURNING *",
+    )
+    .bind(add)
+    .bind(!remove)
+    .bind(id.to_string())
+    .fetch_optional(pool)
+    .await
+}
+
 /// Delete a user account (soft delete — sets DISABLED flag).
+#[tracing::instrument(skip_all)]
 pub async fn soft_delete_user(pool: &sqlx::AnyPool, id: Uuid) -> Result<(), sqlx::Error> {
     sqlx::query(
         r#"
@@ -112,7 +152,41 @@ pub async fn soft_delete_user(pool: &sqlx::AnyPool, id: Uuid) -> Result<(), sqlx
     Ok(())
 }
 
+/// Replace a deleted account with a tombstone: the row stays, so the
+/// user's messages keep an author, but it is renamed to `username`, stripped
+/// of everything identifying and can't sign in again.
+#[tracing::instrument(skip_all)]
+pub async fn tombstone<'e, E>(executor: E, id: Uuid, username: &str) -> Result<(), sqlx::Error>
+where
+    E: sqlx::Executor<'e, Database = sqlx::Any>,
+{
+    sqlx::query(
+        r#"
+        UPDATE users SET
+            username = ?,
+            display_name = NULL,
+            email = NULL,
+            password_hash = '',
+            avatar = NULL,
+            banner = NULL,
+            bio = NULL,
+            status = NULL,
+            presence = 'offline',
+            flags = flags | (1 << 5),
+            deleted_at = CURRENT_TIMESTAMP,
+            updated_at = CURRENT_TIMESTAMP
+        WHERE id = ?
+        "#,
+    )
+    .bind(username)
+    .bind(id.to_string())
+    .execute(executor)
+    .await?;
+    Ok(())
+}
+
 /// Count total users (for admin dashboard).
+#[tracing::instrument(skip_all)]
 pub async fn count_users(pool: &sqlx::AnyPool) -> Result<i64, sqlx::Error> {
     let row: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM users WHERE (flags & (1 << 5)) = 0")
         .fetch_one(pool)
@@ -0,0 +1,148 @@
+//! Voice chat repository — the text channels linked to voice channels, and
+//! who has been in each voice channel.
+//!
+//! A voice channel's text chat is an ordinary `text` channel with
+//! `voice_channel_id` set. It is created with the voice channel, or on
+//! first use for voice channels that predate the feature.
+
+use chrono::{DateTime, Utc};
+use nexus_common::models::channel::Channel;
+use uuid::Uuid;
+
+/// How long after leaving a voice channel its text chat stays visible.
+pub const HISTORY_HOURS: i64 = 24;
+
+fn sql_timestamp(ts: DateTime<Utc>) -> String {
+    ts.format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+/// The text chat linked to `voice_channel_id`, if it has one yet.
+#[tracing::instrument(skip_all)]
+pub async fn find_chat(
+    pool: &sqlx::AnyPool,
+    voice_channel_id: Uuid,
+) -> Result<Option<Channel>, sqlx::Error> {
+    sqlx::query_as::<_, Channel>("SELECT * FROM channels WHERE voice_channel_id = ?")
+        .bind(voice_channel_id.to_string())
+        .fetch_optional(pool)
+        .await
+}
+
+/// The text chat linked to `voice`, creating it if needed. The chat takes
+/// the voice channel's name and category.
+#[tracing::instrument(skip_all)]
+pub async fn find_or_create_chat(
+    pool: &sqlx::AnyPool,
+    id: Uuid,
+    voice: &Channel,
+) -> Result<Channel, sqlx::Error> {
+    if let Some(chat) = find_chat(pool, voice.id).await? {
+        return Ok(chat);
+    }
+
+    let created = sqlx::query_as::<_, Channel>(
+        r#"
+        INSERT INTO channels (
+            id, server_id, parent_id, channel_type, name, position,
+            nsfw, rate_limit_per_user, encrypted, permission_overwrites,
+            archived, locked, voice_channel_id, created_at, updated_at
+        )
+        VALUES (?, ?, ?, 'text', ?, 0, ?, 0, false, '[]', false, false, ?, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP)
+        RETURNING *
+        "#,
+    )
+    .bind(id.to_string())
+    .bind(voice.server_id.map(|u| u.to_string()))
+    .bind(voice.parent_id.map(|u| u.to_string()))
+    .bind(voice.name.as_deref())
+    .bind(voice.nsfw)
+    .bind(voice.id.to_string())
+    .fetch_one(pool)
+    .await;
+
+    match created {
+        Ok(chat) => Ok(chat),
+        // Lost a race with another creator: the unique index kept theirs.
+        Err(e) => find_chat(pool, voice.id).await?.ok_or(e),
+    }
+}
+
+/// `(voice channel, text chat)` pairs for every voice chat in a server.
+#[tracing::instrument(skip_all)]
+pub async fn list_server_chats(
+    pool: &sqlx::AnyPool,
+    server_id: Uuid,
+) -> Result<Vec<(Uuid, Uuid)>, sqlx::Error> {
+    let rows: Vec<(String, String)> = sqlx::query_as(
+        "SELECT voice_channel_id, id FROM channels WHERE server_id = ? AND voice_channel_id IS NOT NULL",
+    )
+    .bind(server_id.to_string())
+    .fetch_all(pool)
+    .await?;
+    Ok(rows
+        .into_iter()
+        .filter_map(|(voice, chat)| Some((voice.parse().ok()?, chat.parse().ok()?)))
+        .collect())
+}
+
+/// Record that `user_id` connected to a voice channel.
+#[tracing::instrument(skip_all)]
+pub async fn record_join(
+    pool: &sqlx::AnyPool,
+    channel_id: Uuid,
+    user_id: Uuid,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO voice_participants (channel_id, user_id, joined_at, left_at)
+        VALUES (?, ?, CURRENT_TIMESTAMP, NULL)
+        ON CONFLICT (channel_id, user_id) DO UPDATE SET
+            joined_at = CURRENT_TIMESTAMP,
+            left_at = NULL
+        "#,
+    )
+    .bind(channel_id.to_string())
+    .bind(user_id.to_string())
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Record that `user_id` disconnected from a voice channel.
+#[tracing::instrument(skip_all)]
+pub async fn record_leave(
+    pool: &sqlx::AnyPool,
+    channel_id: Uuid,
+    user_id: Uuid,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "UPDATE voice_participants SET left_at = CURRENT_TIMESTAMP WHERE channel_id = ? AND user_id = ?",
+    )
+    .bind(channel_id.to_string())
+    .bind(user_id.to_string())
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Whether `user_id` is in the voice channel now, or left it after `since`.
+#[tracing::instrument(skip_all)]
+pub async fn has_participated(
+    pool: &sqlx::AnyPool,
+    channel_id: Uuid,
+    user_id: Uuid,
+    since: DateTime<Utc>,
+) -> Result<bool, sqlx::Error> {
+    let row: (i64,) = sqlx::query_as(
+        r#"
+        SELECT COUNT(*) FROM voice_participants
+        WHERE channel_id = ? AND user_id = ? AND (left_at IS NULL OR left_at >= ?)
+        "#,
+    )
+    .bind(channel_id.to_string())
+    .bind(user_id.to_string())
+    .bind(sql_timestamp(since))
+    .fetch_one(pool)
+    .await?;
+    Ok(row.0 > 0)
+}
@@ -32,6 +32,7 @@ fn row_to_webhook(row: &sqlx::any::AnyRow) -> Webhook {
     }
 }
 
+#[tracing::instrument(skip_all)]
 pub async fn get_webhook(pool: &sqlx::AnyPool, webhook_id: Uuid) -> Result<Option<Webhook>> {
     let row = sqlx::query("SELECT * FROM webhooks WHERE id = ?")
         .bind(webhook_id.to_string())
@@ -40,6 +41,7 @@ pub async fn get_webhook(pool: &sqlx::AnyPool, webhook_id: Uuid) -> Result<Optio
     Ok(row.as_ref().map(row_to_webhook))
 }
 
+#[tracing::instrument(skip_all)]
 pub async fn get_webhook_by_token(
     pool: &sqlx::AnyPool,
     webhook_id: Uuid,
@@ -53,6 +55,7 @@ pub async fn get_webhook_by_token(
     Ok(row.as_ref().map(row_to_webhook))
 }
 
+#[tracing::instrument(skip_all)]
 pub async fn get_channel_webhooks(pool: &sqlx::AnyPool, channel_id: Uuid) -> Result<Vec<Webhook>> {
     let rows = sqlx::query(
         "SELECT * FROM webhooks WHERE channel_id = ? ORDER BY created_at DESC",
@@ -63,6 +66,7 @@ pub async fn get_channel_webhooks(pool: &sqlx::AnyPool, channel_id: Uuid) -> Res
     Ok(rows.iter().map(row_to_webhook).collect())
 }
 
+#[tracing::instrument(skip_all)]
 pub async fn get_server_webhooks(pool: &sqlx::AnyPool, server_id: Uuid) -> Result<Vec<Webhook>> {
     let rows = sqlx::query(
         "SELECT * FROM webhooks WHERE server_id = ? ORDER BY created_at DESC",
@@ -73,6 +77,7 @@ pub async fn get_server_webhooks(pool: &sqlx::AnyPool, server_id: Uuid) -> Resul
     Ok(rows.iter().map(row_to_webhook).collect())
 }
 
+#[tracing::instrument(skip_all)]
 pub async fn create_incoming_webhook(
     pool: &sqlx::AnyPool,
     id: Uuid,
@@ -101,6 +106,7 @@ pub async fn create_incoming_webhook(
     Ok(row_to_webhook(&row))
 }
 
+#[tracing::instrument(skip_all)]
 pub async fn create_outgoing_webhook(
     pool: &sqlx::AnyPool,
     id: Uuid,
@@ -130,6 +136,7 @@ pub async fn create_outgoing_webhook(
     Ok(row_to_webhook(&row))
 }
 
+#[tracing::instrument(skip_all)]
 pub async fn update_webhook(
     pool: &sqlx::AnyPool,
     webhook_id: Uuid,
@@ -165,6 +172,7 @@ pub async fn update_webhook(
     Ok(row.as_ref().map(row_to_webhook))
 }
 
+#[tracing::instrument(skip_all)]
 pub async fn delete_webhook(pool: &sqlx::AnyPool, webhook_id: Uuid) -> Result<bool> {
     let result = sqlx::query("DELETE FROM webhooks WHERE id = ?")
         .bind(webhook_id.to_string())
@@ -0,0 +1,97 @@
+//! Welcome repository — per-server join actions (welcome posts, welcome DMs,
+//! auto-roles) and membership screening configuration.
+
+use chrono::{DateTime, Utc};
+use sqlx::Row;
+use uuid::Uuid;
+
+#[derive(Debug, Clone)]
+pub struct WelcomeSettingsRow {
+    pub server_id: Uuid,
+    pub channel_enabled: bool,
+    pub channel_id: Option<Uuid>,
+    pub channel_message: Option<String>,
+    pub dm_enabled: bool,
+    pub dm_message: Option<String>,
+    pub auto_role_ids: Vec<Uuid>,
+    pub screening_enabled: bool,
+    pub screening_rules: Option<String>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl<'r> sqlx::FromRow<'r, sqlx::any::AnyRow> for WelcomeSettingsRow {
+    fn from_row(row: &'r sqlx::any::AnyRow) -> Result<Self, sqlx::Error> {
+        use crate::any_compat::*;
+        Ok(WelcomeSettingsRow {
+            server_id: get_uuid(row, "server_id")?,
+            channel_enabled: row.try_get("channel_enabled")?,
+            channel_id: get_opt_uuid(row, "channel_id")?,
+            channel_message: row.try_get("channel_message")?,
+            dm_enabled: row.try_get("dm_enabled")?,
+            dm_message: row.try_get("dm_message")?,
+            auto_role_ids: get_uuid_vec(row, "auto_role_ids")?,
+            screening_enabled: row.try_get("screening_enabled")?,
+            screening_rules: row.try_get("screening_rules")?,
+            updated_at: get_datetime(row, "updated_at")?,
+        })
+    }
+}
+
+/// Fetch a server's join settings. `None` means nothing is configured.
+#[tracing::instrument(skip_all)]
+pub async fn get_settings(
+    pool: &sqlx::AnyPool,
+    server_id: Uuid,
+) -> Result<Option<WelcomeSettingsRow>, sqlx::Error> {
+    sqlx::query_as::<_, WelcomeSettingsRow>(
+        "SELECT * FROM server_welcome_settings WHERE server_id = ?",
+    )
+    .bind(server_id.to_string())
+    .fetch_optional(pool)
+    .await
+}
+
+/// Insert or replace a server's join settings.
+#[tracing::instrument(skip_all)]
+pub async fn upsert_settings(
+    pool: &sqlx::AnyPool,
+    settings: &WelcomeSettingsRow,
+) -> Result<WelcomeSettingsRow, sqlx::Error> {
+    let role_ids = serde_json::to_string(
+        &settings.auto_role_ids.iter().map(|r| r.to_string()).collect::<Vec<_>>(),
+    )
+    .unwrap_or_else(|_| "[]".to_string());
+
+    sqlx::query_as::<_, WelcomeSettingsRow>(
+        r#"
+        INSERT INTO server_welcome_settings (
+            server_id, channel_enabled, channel_id, channel_message,
+            dm_enabled, dm_message, auto_role_ids,
+            screening_enabled, screening_rules, updated_at
+        )
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, CURRENT_TIMESTAMP)
+        ON CONFLICT (server_id) DO UPDATE SET
+            channel_enabled = excluded.channel_enabled,
+            channel_id = excluded.channel_id,
+            channel_message = excluded.channel_message,
+            dm_enabled = excluded.dm_enabled,
+            dm_message = excluded.dm_message,
+            auto_role_ids = excluded.auto_role_ids,
+            screening_enabled = excluded.screening_enabled,
+            screening_rules = excluded.screening_rules,
+            updated_at = CURRENT_TIMESTAMP
+        RETURNING *
+        "#,
+    )
+    .bind(settings.server_id.to_string())
+    .bind(settings.channel_enabled)
+    .bind(settings.channel_id.map(|u| u.to_string()))
+    .bind(settings.channel_message.as_deref())
+    .bind(settings.dm_enabled)
+    .bind(settings.dm_message.as_deref())
+    .bind(role_ids)
+    .bind(settings.screening_enabled)
+    .bind(settings.screening_rules.as_deref())
+    .fetch_one(pool)
+    .await
+}
@@ -26,6 +26,9 @@ pub struct MessageDocument {
     pub content: String,
     pub has_attachments: bool,
     pub has_embeds: bool,
+    /// Speech-to-text transcript of any voice-message attachments.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transcript: Option<String>,
     /// Unix timestamp for range-filter support
     pub created_at: i64,
 }
@@ -74,6 +77,14 @@ impl SearchClient {
         self.inner.is_some()
     }
 
+    /// Ping MeiliSearch's `/health` endpoint. Always `false` when disabled.
+    pub async fn health(&self) -> bool {
+        match &self.inner {
+            Some(c) => c.is_healthy().await,
+            None => false,
+        }
+    }
+
     // ------------------------------------------------------------------
     // Index bootstrapping
     // ------------------------------------------------------------------
@@ -103,7 +114,7 @@ impl SearchClient {
 
         // Configure searchable attributes
         index
-            .set_searchable_attributes(["content", "author_username"])
+            .set_searchable_attributes(["content", "transcript", "author_username"])
             .await
             .context("Failed to set searchable attributes for messages index")?;
 
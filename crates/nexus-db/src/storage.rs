@@ -197,6 +197,28 @@ impl StorageClient {
         }
     }
 
+    /// Read an object's bytes from either backend. Returns `Ok(None)` if the
+    /// key does not exist.
+    pub async fn get_object(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        match self.inner.as_ref() {
+            StorageBackend::S3(client, bucket, _) => {
+                let resp = match client.get_object().bucket(bucket).key(key).send().await {
+                    Ok(resp) => resp,
+                    Err(e) if e.as_service_error().is_some_and(|s| s.is_no_such_key()) => {
+                        return Ok(None);
+                    }
+                    Err(e) => return Err(e).with_context(|| format!("S3: failed to read {key}")),
+                };
+                let body = resp.body.collect().await
+                    .with_context(|| format!("S3: failed to stream {key}"))?;
+                Ok(Some(body.into_bytes().to_vec()))
+            }
+            StorageBackend::Local(_, _) => {
+                Ok(self.read_local_file(key).await?.map(|(bytes, _)| bytes))
+            }
+        }
+    }
+
     pub async fn upload_file(&self, key: &str, path: &std::path::Path, content_type: &str) -> Result<String> {
         let data = tokio::fs::read(path).await
             .with_context(|| format!("Failed to read {}", path.display()))?;
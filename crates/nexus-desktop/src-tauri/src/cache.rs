@@ -0,0 +1,53 @@
+//! Offline cache — servers, channels and recent messages seen this session.
+//!
+//! Commands write through as they fetch from the API, so features like the
+//! quick switcher can answer without a round-trip to the server.
+
+use std::collections::{HashMap, VecDeque};
+use uuid::Uuid;
+
+use crate::commands::{channels::ChannelClient, messages::MessageClient, servers::ServerClient};
+
+/// Recent messages kept for search, across all channels.
+pub const MAX_CACHED_MESSAGES: usize = 2_000;
+
+#[derive(Debug, Default)]
+pub struct LocalCache {
+    pub servers: HashMap<Uuid, ServerClient>,
+    pub channels: HashMap<Uuid, ChannelClient>,
+    /// Oldest first.
+    pub messages: VecDeque<MessageClient>,
+}
+
+impl LocalCache {
+    pub fn put_servers<'a>(&mut self, servers: impl IntoIterator<Item = &'a ServerClient>) {
+        for server in servers {
+            self.servers.insert(server.id, server.clone());
+        }
+    }
+
+    pub fn put_channels<'a>(&mut self, channels: impl IntoIterator<Item = &'a ChannelClient>) {
+        for channel in channels {
+            self.channels.insert(channel.id, channel.clone());
+        }
+    }
+
+    /// Add messages, replacing any already cached with the same ID.
+    pub fn put_messages<'a>(&mut self, messages: impl IntoIterator<Item = &'a MessageClient>) {
+        for message in messages {
+            if let Some(existing) = self.messages.iter_mut().find(|m| m.id == message.id) {
+                *existing = message.clone();
+                continue;
+            }
+            if self.messages.len() >= MAX_CACHED_MESSAGES {
+                self.messages.pop_front();
+            }
+            self.messages.push_back(message.clone());
+        }
+    }
+
+    /// Forget everything, e.g. on logout.
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
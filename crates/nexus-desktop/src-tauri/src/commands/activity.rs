@@ -0,0 +1,60 @@
+//! Rich presence join commands.
+//!
+//! `request_activity_join` asks the server to join another user's game
+//! party. The server answers over the gateway with `ACTIVITY_JOIN`; the
+//! frontend passes that payload to `relay_activity_join`, which queues the
+//! secret for the game and emits `activity-join` for the local RPC server
+//! to deliver.
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, State};
+use uuid::Uuid;
+
+use crate::state::AppState;
+use super::api_client;
+
+/// Queued joins older than this many entries are dropped.
+const MAX_PENDING_JOINS: usize = 16;
+
+/// An `ACTIVITY_JOIN` gateway payload, as handed to the game.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ActivityJoin {
+    pub host_id: Uuid,
+    pub name: Option<String>,
+    pub party: Option<serde_json::Value>,
+    pub secret: String,
+}
+
+#[tauri::command]
+pub async fn request_activity_join(
+    state: State<'_, AppState>,
+    user_id: Uuid,
+) -> Result<(), String> {
+    let session = state.session_snapshot();
+    let (client, base) = api_client(&session).map_err(|e| e.to_string())?;
+    let resp = client
+        .post(format!("{base}/api/v1/users/{user_id}/activity/join"))
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    if !resp.status().is_success() {
+        return Err(resp.text().await.unwrap_or_else(|e| e.to_string()));
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn relay_activity_join(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    join: ActivityJoin,
+) -> Result<(), String> {
+    {
+        let mut pending = state.activity_joins.lock().unwrap();
+        if pending.len() >= MAX_PENDING_JOINS {
+            pending.pop_front();
+        }
+        pending.push_back(join.clone());
+    }
+    app.emit("activity-join", &join).map_err(|e| e.to_string())
+}
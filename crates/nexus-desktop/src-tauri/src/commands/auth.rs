@@ -68,7 +68,11 @@ pub async fn register(
         return Err(format!("Registration failed ({status}): {body}"));
     }
 
-    let auth: AuthResponse = resp.json().await.map_err(|e| e.to_string())?;
+    let body: serde_json::Value = resp.json().await.map_err(|e| e.to_string())?;
+    if body["approval_pending"] == true {
+        return Err("Account created. You can log in once staff approve it.".into());
+    }
+    let auth: AuthResponse = serde_json::from_value(body).map_err(|e| e.to_string())?;
 
     {
         let mut session = state.session.lock().unwrap();
@@ -126,6 +130,7 @@ pub async fn logout(state: State<'_, AppState>) -> Result<(), String> {
         server_url: session.server_url.clone(),
         ..Default::default()
     };
+    state.cache.lock().unwrap().clear();
     Ok(())
 }
 
@@ -159,8 +164,15 @@ pub async fn refresh_token(
         .as_str()
         .ok_or("Missing access_token in response")?
         .to_owned();
+    // Refresh tokens are single-use; the old one is now dead.
+    let new_refresh = body["refresh_token"]
+        .as_str()
+        .ok_or("Missing refresh_token in response")?
+        .to_owned();
 
-    state.session.lock().unwrap().access_token = Some(new_token.clone());
+    let mut session = state.session.lock().unwrap();
+    session.access_token = Some(new_token.clone());
+    session.refresh_token = Some(new_refresh);
     Ok(new_token)
 }
 
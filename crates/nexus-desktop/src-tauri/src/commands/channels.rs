@@ -70,7 +70,9 @@ pub async fn create_channel(
         return Err(format!("Server error {status}: {body}"));
     }
     let raw: RawChannel = resp.json().await.map_err(|e| e.to_string())?;
-    Ok(ChannelClient::from(raw))
+    let channel = ChannelClient::from(raw);
+    state.cache.lock().unwrap().put_channels([&channel]);
+    Ok(channel)
 }
 
 #[tauri::command]
@@ -86,7 +88,9 @@ pub async fn list_channels(
         .await
         .map_err(|e| e.to_string())?;
     let raw: Vec<RawChannel> = resp.json().await.map_err(|e| e.to_string())?;
-    Ok(raw.into_iter().map(ChannelClient::from).collect())
+    let channels: Vec<ChannelClient> = raw.into_iter().map(ChannelClient::from).collect();
+    state.cache.lock().unwrap().put_channels(&channels);
+    Ok(channels)
 }
 
 #[tauri::command]
@@ -102,5 +106,7 @@ pub async fn get_channel(
         .await
         .map_err(|e| e.to_string())?;
     let raw: RawChannel = resp.json().await.map_err(|e| e.to_string())?;
-    Ok(ChannelClient::from(raw))
+    let channel = ChannelClient::from(raw);
+    state.cache.lock().unwrap().put_channels([&channel]);
+    Ok(channel)
 }
@@ -0,0 +1,284 @@
+//! Media commands — audio devices, input sensitivity, output volume, and
+//! hardware acceleration.
+//!
+//! Preferences are persisted to the settings store and broadcast to the
+//! frontend as `media-settings` so the WebRTC layer can switch devices and
+//! gate the microphone without a reload.
+//!
+//! Hardware video decode is a webview flag: WebView2 (Windows) only reads it
+//! when the main window is created, so it applies at the next launch there;
+//! WebKitGTK (Linux) switches immediately. macOS always uses the system
+//! decoder.
+
+use cpal::traits::{DeviceTrait, HostTrait};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, State, WebviewWindow, WebviewWindowBuilder};
+use tauri_plugin_store::StoreExt;
+
+use crate::state::AppState;
+
+const STORE_FILE: &str = "settings.json";
+const MEDIA_KEY: &str = "media";
+
+/// Quietest and loudest configurable voice activity thresholds, in dBFS.
+const MIN_SENSITIVITY_DB: f32 = -100.0;
+const MAX_SENSITIVITY_DB: f32 = 0.0;
+
+/// Loudest output volume, in percent of the stream's own level.
+const MAX_OUTPUT_VOLUME: u16 = 200;
+
+/// WebView2 arguments when hardware acceleration is off. Tauri's own
+/// defaults are repeated because setting arguments replaces them.
+#[cfg(windows)]
+const NO_GPU_BROWSER_ARGS: &str = "--disable-features=msWebOOUI,msPdfOOUI,msSmartScreenProtection \
+     --disable-accelerated-video-decode --disable-gpu-compositing";
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DeviceKind {
+    Input,
+    Output,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AudioDevice {
+    /// Stable identifier to store as a preference. cpal exposes no device
+    /// IDs, so this is the device name.
+    pub id: String,
+    pub name: String,
+    pub kind: DeviceKind,
+    pub is_default: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct MediaSettings {
+    /// Preferred microphone; `None` follows the system default.
+    pub input_device: Option<String>,
+    /// Preferred speakers/headphones; `None` follows the system default.
+    pub output_device: Option<String>,
+    /// Voice activity threshold in dBFS. Ignored when `auto_sensitivity`.
+    pub input_sensitivity_db: f32,
+    /// Let the voice activity detector pick its own threshold.
+    pub auto_sensitivity: bool,
+    /// Nexus's own playback volume in percent, applied on top of the system
+    /// mixer (100 = unchanged).
+    pub output_volume: u16,
+    /// Hardware video decode in the webview.
+    pub hardware_acceleration: bool,
+}
+
+impl Default for MediaSettings {
+    fn default() -> Self {
+        Self {
+            input_device: None,
+            output_device: None,
+            input_sensitivity_db: -50.0,
+            auto_sensitivity: true,
+            output_volume: 100,
+            hardware_acceleration: true,
+        }
+    }
+}
+
+/// The persisted media settings, or the defaults.
+pub fn load(app: &AppHandle) -> MediaSettings {
+    app.store(STORE_FILE)
+        .ok()
+        .and_then(|store| store.get(MEDIA_KEY))
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default()
+}
+
+/// Persist `settings`, update `AppState`, and notify the frontend.
+pub(crate) fn save(app: &AppHandle, settings: MediaSettings) -> Result<MediaSettings, String> {
+    let store = app.store(STORE_FILE).map_err(|e| e.to_string())?;
+    store.set(MEDIA_KEY, serde_json::json!(settings));
+    store.save().map_err(|e| e.to_string())?;
+
+    *app.state::<AppState>().media.lock().unwrap() = settings.clone();
+    let _ = app.emit("media-settings", &settings);
+    Ok(settings)
+}
+
+/// Build the main window (declared with `create: false` in tauri.conf.json)
+/// so its webview honours the hardware acceleration setting.
+pub fn create_main_window(app: &tauri::App) -> tauri::Result<()> {
+    let settings = load(app.handle());
+    *app.state::<AppState>().media.lock().unwrap() = settings.clone();
+
+    let config = app
+        .config()
+        .app
+        .windows
+        .iter()
+        .find(|w| w.label == "main")
+        .cloned()
+        .ok_or(tauri::Error::WindowNotFound)?;
+    #[allow(unused_mut)]
+    let mut builder = WebviewWindowBuilder::from_config(app.handle(), &config)?;
+    #[cfg(windows)]
+    if !settings.hardware_acceleration {
+        builder = builder.additional_browser_args(NO_GPU_BROWSER_ARGS);
+    }
+    let window = builder.build()?;
+    apply_hardware_acceleration(&window, settings.hardware_acceleration);
+    Ok(())
+}
+
+/// Switch hardware acceleration on a live webview, where supported.
+#[allow(unused_variables)]
+fn apply_hardware_acceleration(window: &WebviewWindow, enabled: bool) {
+    #[cfg(target_os = "linux")]
+    let _ = window.with_webview(move |webview| {
+        use webkit2gtk::{HardwareAccelerationPolicy, SettingsExt, WebViewExt};
+        if let Some(settings) = WebViewExt::settings(&webview.inner()) {
+            settings.set_hardware_acceleration_policy(if enabled {
+                HardwareAccelerationPolicy::OnDemand
+            } else {
+                HardwareAccelerationPolicy::Never
+            });
+        }
+    });
+}
+
+fn to_devices<I: Iterator<Item = cpal::Device>>(
+    devices: I,
+    kind: DeviceKind,
+    default: Option<&str>,
+) -> Vec<AudioDevice> {
+    devices
+        .filter_map(|d| d.name().ok())
+        .map(|name| AudioDevice {
+            id: name.clone(),
+            is_default: default == Some(name.as_str()),
+            name,
+            kind,
+        })
+        .collect()
+}
+
+/// Enumerate devices on the default audio host. Blocking; some backends
+/// probe hardware.
+fn enumerate() -> Result<Vec<AudioDevice>, String> {
+    let host = cpal::default_host();
+    let default_input = host.default_input_device().and_then(|d| d.name().ok());
+    let default_output = host.default_output_device().and_then(|d| d.name().ok());
+
+    let mut devices = to_devices(
+        host.input_devices().map_err(|e| e.to_string())?,
+        DeviceKind::Input,
+        default_input.as_deref(),
+    );
+    devices.extend(to_devices(
+        host.output_devices().map_err(|e| e.to_string())?,
+        DeviceKind::Output,
+        default_output.as_deref(),
+    ));
+    Ok(devices)
+}
+
+/// List audio input and output devices.
+#[tauri::command]
+pub async fn list_audio_devices() -> Result<Vec<AudioDevice>, String> {
+    tauri::async_runtime::spawn_blocking(enumerate)
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub fn get_media_settings(state: State<'_, AppState>) -> MediaSettings {
+    state.media.lock().unwrap().clone()
+}
+
+/// Set preferred devices. `None` (or an empty string) means system default.
+#[tauri::command]
+pub fn set_audio_devices(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    input_device: Option<String>,
+    output_device: Option<String>,
+) -> Result<MediaSettings, String> {
+    let mut settings = state.media.lock().unwrap().clone();
+    settings.input_device = input_device.filter(|d| !d.is_empty());
+    settings.output_device = output_device.filter(|d| !d.is_empty());
+    save(&app, settings)
+}
+
+/// Configure the voice activity threshold.
+#[tauri::command]
+pub fn set_input_sensitivity(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    threshold_db: f32,
+    automatic: bool,
+) -> Result<MediaSettings, String> {
+    if !(MIN_SENSITIVITY_DB..=MAX_SENSITIVITY_DB).contains(&threshold_db) {
+        return Err(format!(
+            "Sensitivity must be between {MIN_SENSITIVITY_DB} and {MAX_SENSITIVITY_DB} dB"
+        ));
+    }
+    let mut settings = state.media.lock().unwrap().clone();
+    settings.input_sensitivity_db = threshold_db;
+    settings.auto_sensitivity = automatic;
+    save(&app, settings)
+}
+
+/// Clamp an output volume to `0..=MAX_OUTPUT_VOLUME` percent.
+pub(crate) fn clamp_output_volume(percent: i32) -> u16 {
+    percent.clamp(0, MAX_OUTPUT_VOLUME as i32) as u16
+}
+
+/// Set Nexus's playback volume, in percent.
+#[tauri::command]
+pub fn set_output_volume(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    percent: u16,
+) -> Result<MediaSettings, String> {
+    if percent > MAX_OUTPUT_VOLUME {
+        return Err(format!("Output volume must be at most {MAX_OUTPUT_VOLUME}%"));
+    }
+    let mut settings = state.media.lock().unwrap().clone();
+    settings.output_volume = percent;
+    save(&app, settings)
+}
+
+/// Toggle hardware video decode. On Windows this takes effect after a
+/// restart.
+#[tauri::command]
+pub fn set_hardware_acceleration(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    enabled: bool,
+) -> Result<MediaSettings, String> {
+    let mut settings = state.media.lock().unwrap().clone();
+    settings.hardware_acceleration = enabled;
+    if let Some(window) = app.get_webview_window("main") {
+        apply_hardware_acceleration(&window, enabled);
+    }
+    save(&app, settings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn settings_default_missing_fields() {
+        let settings: MediaSettings =
+            serde_json::from_value(serde_json::json!({ "inputDevice": "USB Mic" })).unwrap();
+        assert_eq!(settings.input_device.as_deref(), Some("USB Mic"));
+        assert!(settings.auto_sensitivity);
+        assert!(settings.hardware_acceleration);
+        assert_eq!(settings.output_volume, 100);
+    }
+
+    #[test]
+    fn output_volume_is_clamped() {
+        assert_eq!(clamp_output_volume(-10), 0);
+        assert_eq!(clamp_output_volume(150), 150);
+        assert_eq!(clamp_output_volume(210), MAX_OUTPUT_VOLUME);
+    }
+}
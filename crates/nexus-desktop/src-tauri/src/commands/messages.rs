@@ -79,7 +79,9 @@ pub async fn send_message(
         return Err(format!("Server error {status}: {body}"));
     }
     let raw: RawMessage = resp.json().await.map_err(|e| e.to_string())?;
-    Ok(MessageClient::from(raw))
+    let message = MessageClient::from(raw);
+    state.cache.lock().unwrap().put_messages([&message]);
+    Ok(message)
 }
 
 #[tauri::command]
@@ -105,5 +107,7 @@ pub async fn fetch_history(
         return Err(format!("Server error {status}: {body}"));
     }
     let raw: Vec<RawMessage> = resp.json().await.map_err(|e| e.to_string())?;
-    Ok(raw.into_iter().map(MessageClient::from).collect())
+    let messages: Vec<MessageClient> = raw.into_iter().map(MessageClient::from).collect();
+    state.cache.lock().unwrap().put_messages(&messages);
+    Ok(messages)
 }
@@ -3,13 +3,16 @@
 //! Each sub-module groups commands by domain, mirroring the server-side route structure.
 //! All HTTP calls use the `reqwest` client with the base URL from [`AppState::session`].
 
+pub mod activity;
 pub mod auth;
 pub mod channels;
 pub mod e2ee;
+pub mod media;
 pub mod messages;
 pub mod presence;
 pub mod servers;
 pub mod settings;
+pub mod switcher;
 pub mod voice;
 
 use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION, CONTENT_TYPE};
@@ -54,7 +54,9 @@ pub async fn list_servers(state: State<'_, AppState>) -> Result<Vec<ServerClient
         .await
         .map_err(|e| e.to_string())?;
     let raw: Vec<RawServer> = resp.json().await.map_err(|e| e.to_string())?;
-    Ok(raw.into_iter().map(ServerClient::from).collect())
+    let servers: Vec<ServerClient> = raw.into_iter().map(ServerClient::from).collect();
+    state.cache.lock().unwrap().put_servers(&servers);
+    Ok(servers)
 }
 
 #[tauri::command]
@@ -70,7 +72,9 @@ pub async fn get_server(
         .await
         .map_err(|e| e.to_string())?;
     let raw: RawServer = resp.json().await.map_err(|e| e.to_string())?;
-    Ok(ServerClient::from(raw))
+    let server = ServerClient::from(raw);
+    state.cache.lock().unwrap().put_servers([&server]);
+    Ok(server)
 }
 
 #[derive(Deserialize)]
@@ -99,5 +103,7 @@ pub async fn create_server(
         return Err(text);
     }
     let raw: RawServer = resp.json().await.map_err(|e| e.to_string())?;
-    Ok(ServerClient::from(raw))
+    let server = ServerClient::from(raw);
+    state.cache.lock().unwrap().put_servers([&server]);
+    Ok(server)
 }
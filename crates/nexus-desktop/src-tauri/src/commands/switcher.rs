@@ -0,0 +1,253 @@
+//! Quick switcher — Ctrl+K fuzzy search over the local cache.
+//!
+//! Servers, channels and DMs are matched fuzzily by name; cached messages
+//! are matched by substring so long bodies don't match every query. Nothing
+//! here touches the network.
+
+use serde::Serialize;
+use tauri::State;
+use uuid::Uuid;
+
+use crate::{cache::LocalCache, state::AppState};
+
+/// Results returned when the caller doesn't ask for a limit.
+const DEFAULT_LIMIT: usize = 20;
+const MAX_LIMIT: usize = 100;
+/// Characters of message content shown as a result title.
+const SNIPPET_LEN: usize = 80;
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SwitcherKind {
+    Server,
+    Channel,
+    Dm,
+    Message,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SwitcherResult {
+    pub kind: SwitcherKind,
+    pub id: Uuid,
+    pub title: String,
+    pub subtitle: Option<String>,
+    /// Where to navigate: the server and/or channel to open.
+    pub server_id: Option<Uuid>,
+    pub channel_id: Option<Uuid>,
+    pub score: i64,
+}
+
+/// Score `candidate` against `query`, case-insensitively.
+///
+/// Every non-space query character must appear in order. Matches at the
+/// start, at word boundaries and in consecutive runs score higher; gaps and
+/// long candidates cost a little. Returns `None` if the query doesn't match.
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<i64> {
+    let query: Vec<char> = query
+        .to_lowercase()
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect();
+    if query.is_empty() {
+        return Some(0);
+    }
+    let candidate: Vec<char> = candidate.to_lowercase().chars().collect();
+
+    let mut score = 0i64;
+    let mut next = 0;
+    let mut prev: Option<usize> = None;
+    for (i, &ch) in candidate.iter().enumerate() {
+        if next == query.len() {
+            break;
+        }
+        if ch != query[next] {
+            continue;
+        }
+        score += 10;
+        if i == 0 {
+            score += 15;
+        } else if !candidate[i - 1].is_alphanumeric() {
+            score += 10;
+        }
+        match prev {
+            Some(p) if p + 1 == i => score += 8,
+            Some(p) => score -= (i - p - 1).min(5) as i64,
+            None => {}
+        }
+        prev = Some(i);
+        next += 1;
+    }
+    if next < query.len() {
+        return None;
+    }
+    Some(score - (candidate.len() - query.len()) as i64 / 4)
+}
+
+fn snippet(content: &str) -> String {
+    let mut chars = content.chars();
+    let mut out: String = chars.by_ref().take(SNIPPET_LEN).collect();
+    if chars.next().is_some() {
+        out.push('…');
+    }
+    out
+}
+
+/// Rank everything in `cache` against `query`, best first.
+///
+/// An empty query lists channels then servers alphabetically, so the
+/// switcher has something to show before the user types.
+pub fn rank(cache: &LocalCache, query: &str, limit: usize) -> Vec<SwitcherResult> {
+    let query = query.trim();
+    let mut results = Vec::new();
+
+    for server in cache.servers.values() {
+        if let Some(score) = fuzzy_score(query, &server.name) {
+            results.push(SwitcherResult {
+                kind: SwitcherKind::Server,
+                id: server.id,
+                title: server.name.clone(),
+                subtitle: None,
+                server_id: Some(server.id),
+                channel_id: None,
+                score,
+            });
+        }
+    }
+
+    for channel in cache.channels.values() {
+        let Some(score) = fuzzy_score(query, &channel.name) else {
+            continue;
+        };
+        let (kind, subtitle) = match channel.server_id {
+            Some(server_id) => (
+                SwitcherKind::Channel,
+                cache.servers.get(&server_id).map(|s| s.name.clone()),
+            ),
+            None => (SwitcherKind::Dm, None),
+        };
+        results.push(SwitcherResult {
+            kind,
+            id: channel.id,
+            title: channel.name.clone(),
+            subtitle,
+            server_id: channel.server_id,
+            channel_id: Some(channel.id),
+            // Channels are the usual destination; nudge them above servers.
+            score: score + 5,
+        });
+    }
+
+    if !query.is_empty() {
+        let needle = query.to_lowercase();
+        // Newest first so recency breaks ties.
+        for (age, message) in cache.messages.iter().rev().enumerate() {
+            if !message.content.to_lowercase().contains(&needle) {
+                continue;
+            }
+            let channel = cache.channels.get(&message.channel_id);
+            let subtitle = match channel {
+                Some(c) => format!("#{} — {}", c.name, message.author_username),
+                None => message.author_username.clone(),
+            };
+            results.push(SwitcherResult {
+                kind: SwitcherKind::Message,
+                id: message.id,
+                title: snippet(&message.content),
+                subtitle: Some(subtitle),
+                server_id: channel.and_then(|c| c.server_id),
+                channel_id: Some(message.channel_id),
+                score: needle.chars().count() as i64 * 5 - (age as i64 / 100),
+            });
+        }
+    }
+
+    results.sort_by(|a, b| {
+        b.score
+            .cmp(&a.score)
+            .then_with(|| a.title.to_lowercase().cmp(&b.title.to_lowercase()))
+    });
+    results.truncate(limit);
+    results
+}
+
+/// Search cached servers, channels, DMs and recent messages.
+#[tauri::command]
+pub async fn quick_switch(
+    state: State<'_, AppState>,
+    query: String,
+    limit: Option<usize>,
+) -> Result<Vec<SwitcherResult>, String> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+    let cache = state.cache.lock().unwrap();
+    Ok(rank(&cache, &query, limit))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::{channels::ChannelClient, messages::MessageClient, servers::ServerClient};
+
+    fn channel(name: &str, server_id: Option<Uuid>) -> ChannelClient {
+        ChannelClient {
+            id: Uuid::new_v4(),
+            server_id,
+            name: name.into(),
+            kind: "text".into(),
+            is_e2ee: false,
+        }
+    }
+
+    #[test]
+    fn fuzzy_requires_ordered_subsequence() {
+        assert!(fuzzy_score("gnrl", "general").is_some());
+        assert!(fuzzy_score("GEN", "general").is_some());
+        assert!(fuzzy_score("lrng", "general").is_none());
+        assert!(fuzzy_score("xyz", "general").is_none());
+    }
+
+    #[test]
+    fn fuzzy_prefers_prefix_and_word_starts() {
+        let prefix = fuzzy_score("dev", "dev-chat").unwrap();
+        let boundary = fuzzy_score("dev", "game-dev").unwrap();
+        let scattered = fuzzy_score("dev", "divide-ever").unwrap();
+        assert!(prefix > boundary);
+        assert!(boundary > scattered);
+    }
+
+    #[test]
+    fn ranks_channels_and_messages() {
+        let server = ServerClient {
+            id: Uuid::new_v4(),
+            name: "Rustaceans".into(),
+            icon: None,
+            member_count: None,
+            owner_id: Uuid::new_v4(),
+        };
+        let general = channel("general", Some(server.id));
+        let dm = channel("alice", None);
+        let mut cache = LocalCache::default();
+        cache.put_servers([&server]);
+        cache.put_channels([&general, &dm]);
+        cache.put_messages([&MessageClient {
+            id: Uuid::new_v4(),
+            channel_id: general.id,
+            author_id: Uuid::new_v4(),
+            author_username: "bob".into(),
+            content: "general kenobi".into(),
+            created_at: String::new(),
+            edited_at: None,
+        }]);
+
+        let results = rank(&cache, "general", 10);
+        assert_eq!(results[0].kind, SwitcherKind::Channel);
+        assert_eq!(results[0].subtitle.as_deref(), Some("Rustaceans"));
+        assert!(results.iter().any(|r| r.kind == SwitcherKind::Message));
+
+        let results = rank(&cache, "ali", 10);
+        assert_eq!(results[0].kind, SwitcherKind::Dm);
+
+        // Empty query lists everything except messages.
+        assert_eq!(rank(&cache, "", 10).len(), 3);
+    }
+}
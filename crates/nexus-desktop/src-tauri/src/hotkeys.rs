@@ -1,71 +1,250 @@
 //! Global hotkeys — push-to-talk (PTT) and other system-wide shortcuts.
 //!
-//! PTT default: `CapsLock` (user-configurable via Settings → Voice → PTT key).
+//! PTT default: `CapsLock` (user-configurable via Settings → Keybinds).
+//! Push-to-mute, deafen and output volume have no default binding.
 //!
-//! When the PTT key is pressed:
-//!   1. Backend emits `ptt-start` event → frontend enables mic capture.
-//! When released:
-//!   2. Backend emits `ptt-stop` event → frontend disables mic capture.
+//! | Action         | Pressed                              | Released                 |
+//! |----------------|--------------------------------------|--------------------------|
+//! | Push-to-talk   | `ptt-start` → mic capture on         | `ptt-stop`               |
+//! | Push-to-mute   | mute, `voice-settings`               | unmute (unless deafened) |
+//! | Toggle deafen  | flip deafen, `voice-settings`        | —                        |
+//! | Volume up/down | step output volume, `media-settings` | —                        |
+//!
+//! Bound keys are registered with the OS hotkey API (`RegisterHotKey` on
+//! Windows, Carbon hot keys on macOS, `XGrabKey` on X11), which consumes the
+//! key: it is suppressed for every other application while bound. Wayland has
+//! no global hotkey protocol, so registration fails there and the binding is
+//! reported as unregistered rather than half-working.
 
-use tauri::{App, AppHandle, Emitter, Manager, Runtime};
+use serde::{Deserialize, Serialize};
+use tauri::{App, AppHandle, Emitter, Manager, State};
 use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+use tauri_plugin_store::StoreExt;
 
 use crate::state::AppState;
 
 const DEFAULT_PTT_SHORTCUT: &str = "CapsLock";
 
-/// Register the default push-to-talk shortcut on application startup.
-pub fn register_defaults<R: Runtime>(app: &mut App<R>) -> tauri::Result<()> {
+const STORE_FILE: &str = "settings.json";
+const KEYBINDS_KEY: &str = "keybinds";
+
+/// Output volume change per press of a volume binding, in percent.
+const VOLUME_STEP: i32 = 10;
+
+/// Shortcut strings for each action (e.g. `"CapsLock"`, `"Ctrl+Shift+M"`).
+/// An empty string leaves the action unbound.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Keybinds {
+    pub push_to_talk: String,
+    pub push_to_mute: String,
+    pub toggle_deafen: String,
+    pub volume_up: String,
+    pub volume_down: String,
+}
+
+impl Default for Keybinds {
+    fn default() -> Self {
+        Self {
+            push_to_talk: DEFAULT_PTT_SHORTCUT.to_owned(),
+            push_to_mute: String::new(),
+            toggle_deafen: String::new(),
+            volume_up: String::new(),
+            volume_down: String::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Action {
+    PushToTalk,
+    PushToMute,
+    ToggleDeafen,
+    VolumeUp,
+    VolumeDown,
+}
+
+impl Keybinds {
+    fn bound(&self) -> Vec<(Action, &str)> {
+        [
+            (Action::PushToTalk, self.push_to_talk.as_str()),
+            (Action::PushToMute, self.push_to_mute.as_str()),
+            (Action::ToggleDeafen, self.toggle_deafen.as_str()),
+            (Action::VolumeUp, self.volume_up.as_str()),
+            (Action::VolumeDown, self.volume_down.as_str()),
+        ]
+        .into_iter()
+        .filter(|(_, s)| !s.is_empty())
+        .collect()
+    }
+
+    /// Parse every bound shortcut, rejecting one key bound to two actions.
+    fn parse(&self) -> Result<Vec<(Action, Shortcut)>, String> {
+        let mut parsed: Vec<(Action, Shortcut)> = Vec::new();
+        for (action, s) in self.bound() {
+            let shortcut: Shortcut = s
+                .parse()
+                .map_err(|e| format!("Invalid shortcut '{s}': {e}"))?;
+            if parsed.iter().any(|(_, other)| other.id() == shortcut.id()) {
+                return Err(format!("'{s}' is bound to more than one action"));
+            }
+            parsed.push((action, shortcut));
+        }
+        Ok(parsed)
+    }
+}
+
+/// Which bound actions the OS accepted.
+#[derive(Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct KeybindStatus {
+    pub keybinds: Keybinds,
+    /// Bound actions whose shortcut could not be registered, by field name.
+    pub unregistered: Vec<String>,
+}
+
+fn field_name(action: Action) -> &'static str {
+    match action {
+        Action::PushToTalk => "pushToTalk",
+        Action::PushToMute => "pushToMute",
+        Action::ToggleDeafen => "toggleDeafen",
+        Action::VolumeUp => "volumeUp",
+        Action::VolumeDown => "volumeDown",
+    }
+}
+
+/// The persisted keybinds, or the defaults.
+fn load(app: &AppHandle) -> Keybinds {
+    app.store(STORE_FILE)
+        .ok()
+        .and_then(|store| store.get(KEYBINDS_KEY))
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default()
+}
+
+fn save(app: &AppHandle, keybinds: &Keybinds) -> Result<(), String> {
+    let store = app.store(STORE_FILE).map_err(|e| e.to_string())?;
+    store.set(KEYBINDS_KEY, serde_json::json!(keybinds));
+    store.save().map_err(|e| e.to_string())
+}
+
+/// Register the saved shortcuts (default: PTT on CapsLock) on application startup.
+pub fn register_defaults(app: &mut App) -> tauri::Result<()> {
     let handle = app.handle().clone();
     // Unregister anything left over from a previous session before registering.
     // This prevents a panic when the OS still holds the hotkey from a stale process.
     let _ = handle.global_shortcut().unregister_all();
-    if let Err(e) = register_ptt_shortcut(&handle, DEFAULT_PTT_SHORTCUT) {
-        // Log and continue — PTT simply won't work until the user changes it in Settings.
-        tracing::warn!("Could not register PTT shortcut '{}': {}. PTT disabled until reassigned in Settings > Voice.", DEFAULT_PTT_SHORTCUT, e);
+    let keybinds = load(&handle);
+    match register_keybinds(&handle, &keybinds) {
+        // Log and continue — the action simply won't work until the user changes it in Settings.
+        Ok(status) => {
+            for action in status.unregistered {
+                tracing::warn!("Could not register the {action} shortcut. Disabled until reassigned in Settings > Keybinds.");
+            }
+        }
+        Err(e) => tracing::warn!("Invalid saved keybinds: {e}. Hotkeys disabled until reassigned in Settings > Keybinds."),
     }
     Ok(())
 }
 
-/// Register a specific shortcut string as the PTT key.
-pub fn register_ptt_shortcut<R: Runtime>(app: &AppHandle<R>, shortcut_str: &str) -> tauri::Result<()> {
-    let handle = app.clone();
-    let shortcut: Shortcut = shortcut_str.parse().map_err(|e| {
-        tauri::Error::Anyhow(anyhow::anyhow!("Invalid shortcut '{}': {}", shortcut_str, e))
-    })?;
+/// Replace every registered shortcut with `keybinds`.
+///
+/// Fails without touching the current bindings if a shortcut doesn't parse or
+/// is used twice. Shortcuts the OS refuses (already taken by another app, or
+/// no global hotkeys at all) are skipped and listed in the result.
+pub fn register_keybinds(app: &AppHandle, keybinds: &Keybinds) -> Result<KeybindStatus, String> {
+    let parsed = keybinds.parse()?;
 
     // Always clear our own shortcuts first — safe to call even if nothing is registered.
     let _ = app.global_shortcut().unregister_all();
 
-    app.global_shortcut().on_shortcut(shortcut, move |_app, _shortcut, event| {
-        match event.state {
-            ShortcutState::Pressed => {
-                if let Some(state) = handle.try_state::<AppState>() {
-                    let mut ptt = state.ptt.lock().unwrap();
-                    if !ptt.transmitting {
-                        ptt.transmitting = true;
-                        let _ = handle.emit("ptt-start", ());
-                        tracing::debug!("PTT start");
-                    }
-                }
-            }
-            ShortcutState::Released => {
-                if let Some(state) = handle.try_state::<AppState>() {
-                    let mut ptt = state.ptt.lock().unwrap();
-                    ptt.transmitting = false;
-                    let _ = handle.emit("ptt-stop", ());
-                    tracing::debug!("PTT stop");
-                }
-            }
+    let mut unregistered = Vec::new();
+    for (action, shortcut) in parsed {
+        let handle = app.clone();
+        let registered = app
+            .global_shortcut()
+            .on_shortcut(shortcut, move |_app, _shortcut, event| on_action(&handle, action, event.state));
+        if let Err(e) = registered {
+            tracing::warn!("GlobalShortcut error for {}: {e}", field_name(action));
+            unregistered.push(field_name(action).to_owned());
         }
-    }).map_err(|e| tauri::Error::Anyhow(anyhow::anyhow!("GlobalShortcut error: {}", e)))?;
+    }
 
-    // Store the new shortcut string
+    let status = KeybindStatus {
+        keybinds: keybinds.clone(),
+        unregistered,
+    };
     if let Some(state) = app.try_state::<AppState>() {
-        state.ptt.lock().unwrap().shortcut = shortcut_str.to_owned();
+        state.ptt.lock().unwrap().shortcut = keybinds.push_to_talk.clone();
+        *state.keybinds.lock().unwrap() = status.clone();
     }
+    Ok(status)
+}
 
-    Ok(())
+fn on_action(handle: &AppHandle, action: Action, pressed: ShortcutState) {
+    let Some(state) = handle.try_state::<AppState>() else {
+        return;
+    };
+    match (action, pressed) {
+        (Action::PushToTalk, ShortcutState::Pressed) => {
+            let mut ptt = state.ptt.lock().unwrap();
+            if !ptt.transmitting {
+                ptt.transmitting = true;
+                let _ = handle.emit("ptt-start", ());
+                tracing::debug!("PTT start");
+            }
+        }
+        (Action::PushToTalk, ShortcutState::Released) => {
+            state.ptt.lock().unwrap().transmitting = false;
+            let _ = handle.emit("ptt-stop", ());
+            tracing::debug!("PTT stop");
+        }
+        (Action::PushToMute, pressed) => {
+            let settings = {
+                let mut voice = state.voice.lock().unwrap();
+                // A deafened user stays muted when the key comes back up.
+                voice.muted = pressed == ShortcutState::Pressed || voice.deafened;
+                serde_json::json!({ "mute": voice.muted, "deaf": voice.deafened })
+            };
+            let _ = handle.emit("voice-settings", settings);
+        }
+        (Action::ToggleDeafen, ShortcutState::Pressed) => {
+            let settings = {
+                let mut voice = state.voice.lock().unwrap();
+                voice.deafened = !voice.deafened;
+                // Deafening implies muting, as in the main client.
+                voice.muted = voice.deafened;
+                serde_json::json!({ "mute": voice.muted, "deaf": voice.deafened })
+            };
+            let _ = handle.emit("voice-settings", settings);
+        }
+        (Action::VolumeUp, ShortcutState::Pressed) => step_volume(handle, &state, VOLUME_STEP),
+        (Action::VolumeDown, ShortcutState::Pressed) => step_volume(handle, &state, -VOLUME_STEP),
+        _ => {}
+    }
+}
+
+fn step_volume(handle: &AppHandle, state: &AppState, delta: i32) {
+    let mut settings = state.media.lock().unwrap().clone();
+    settings.output_volume = crate::commands::media::clamp_output_volume(settings.output_volume as i32 + delta);
+    if let Err(e) = crate::commands::media::save(handle, settings) {
+        tracing::warn!("Could not save output volume: {e}");
+    }
+}
+
+/// Tauri command: the current keybinds and any the OS refused.
+#[tauri::command]
+pub fn get_keybinds(state: State<'_, AppState>) -> KeybindStatus {
+    state.keybinds.lock().unwrap().clone()
+}
+
+/// Tauri command: rebind every action at runtime and persist the bindings.
+#[tauri::command]
+pub fn set_keybinds(app: AppHandle, keybinds: Keybinds) -> Result<KeybindStatus, String> {
+    let status = register_keybinds(&app, &keybinds)?;
+    save(&app, &keybinds)?;
+    tracing::info!("Keybinds changed");
+    Ok(status)
 }
 
 /// Tauri command: change the PTT shortcut at runtime.
@@ -74,13 +253,18 @@ pub fn register_ptt_shortcut<R: Runtime>(app: &AppHandle<R>, shortcut_str: &str)
 #[tauri::command]
 pub fn set_ptt_shortcut(
     app: AppHandle,
+    state: State<'_, AppState>,
     shortcut: String,
 ) -> Result<(), String> {
-    // Unregister all existing shortcuts managed by us
-    app.global_shortcut().unregister_all().map_err(|e| e.to_string())?;
-
-    // Register the new one
-    register_ptt_shortcut(&app, &shortcut).map_err(|e| e.to_string())?;
+    let keybinds = Keybinds {
+        push_to_talk: shortcut.clone(),
+        ..state.keybinds.lock().unwrap().keybinds.clone()
+    };
+    let status = register_keybinds(&app, &keybinds)?;
+    if !status.unregistered.is_empty() {
+        return Err(format!("Could not register '{shortcut}'"));
+    }
+    save(&app, &keybinds)?;
 
     tracing::info!("PTT shortcut changed to '{shortcut}'");
     Ok(())
@@ -101,3 +285,39 @@ pub fn get_ptt_shortcut(app: AppHandle) -> String {
         shortcut
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keybinds_default_missing_fields() {
+        let keybinds: Keybinds =
+            serde_json::from_value(serde_json::json!({ "pushToMute": "Ctrl+M" })).unwrap();
+        assert_eq!(keybinds.push_to_talk, DEFAULT_PTT_SHORTCUT);
+        assert_eq!(keybinds.push_to_mute, "Ctrl+M");
+        assert!(keybinds.volume_up.is_empty());
+    }
+
+    #[test]
+    fn unbound_actions_are_skipped() {
+        let parsed = Keybinds::default().parse().unwrap();
+        assert_eq!(parsed.len(), 1);
+        assert_eq!(parsed[0].0, Action::PushToTalk);
+    }
+
+    #[test]
+    fn one_key_cannot_serve_two_actions() {
+        let keybinds = Keybinds {
+            push_to_mute: "CapsLock".into(),
+            ..Keybinds::default()
+        };
+        assert!(keybinds.parse().is_err());
+
+        let keybinds = Keybinds {
+            volume_up: "Ctrl+Nope".into(),
+            ..Keybinds::default()
+        };
+        assert!(keybinds.parse().is_err());
+    }
+}
@@ -6,12 +6,15 @@
 //! - Maintain a persistent WebSocket connection to the gateway
 //! - Expose Tauri commands consumed by the React frontend
 //! - System tray with presence/quick-action menu
-//! - Push-to-talk global hotkey
+//! - Push-to-talk, push-to-mute, deafen and volume global hotkeys
 //! - Gaming overlay window
 //! - Auto-update checks
+//! - Local RPC server for game SDKs and streaming tools
 
+pub mod cache;
 pub mod commands;
 pub mod hotkeys;
+pub mod local_rpc;
 pub mod notifications;
 pub mod overlay;
 pub mod state;
@@ -40,12 +43,18 @@ pub fn run() {
         .manage(state::AppState::default())
         // ── Setup hook ───────────────────────────────────────────────────────
         .setup(|app| {
+            // Main window (built here so webview flags follow media settings)
+            commands::media::create_main_window(app)?;
+
             // System tray
             tray::setup_tray(app)?;
 
-            // Register push-to-talk shortcut (default: CapsLock, user-configurable)
+            // Register saved voice shortcuts (PTT default: CapsLock, user-configurable)
             hotkeys::register_defaults(app)?;
 
+            // Local RPC for games (rich presence, voice, mute)
+            local_rpc::start(app.handle().clone());
+
             // Start background update check (every 4 hours)
             updater::schedule_check(app.handle().clone());
 
@@ -70,6 +79,8 @@ pub fn run() {
             // Messages
             commands::messages::send_message,
             commands::messages::fetch_history,
+            // Quick switcher
+            commands::switcher::quick_switch,
             // Encrypted messaging
             commands::e2ee::send_encrypted_message,
             commands::e2ee::fetch_encrypted_history,
@@ -77,7 +88,14 @@ pub fn run() {
             commands::e2ee::get_key_bundle,
             // Presence & voice
             commands::presence::update_presence,
+            commands::activity::request_activity_join,
+            commands::activity::relay_activity_join,
             commands::voice::get_voice_state,
+            commands::media::list_audio_devices,
+            commands::media::get_media_settings,
+            commands::media::set_audio_devices,
+            commands::media::set_input_sensitivity,
+            commands::media::set_hardware_acceleration,
             // Settings & window management
             commands::settings::get_settings,
             commands::settings::set_setting,
@@ -91,6 +109,18 @@ pub fn run() {
             // Hotkeys
             hotkeys::set_ptt_shortcut,
             hotkeys::get_ptt_shortcut,
+            hotkeys::get_keybinds,
+            hotkeys::set_keybinds,
+            commands::media::set_output_volume,
+            // Updates
+            updater::get_update_channel,
+            updater::set_update_channel,
+            updater::check_for_updates,
+            updater::get_release_notes,
+            updater::install_update,
+            updater::restart_to_update,
+            // Tray
+            tray::set_tray_unread,
         ])
         .on_window_event(|window, event| {
             // Intercept close on main window → minimise to tray instead
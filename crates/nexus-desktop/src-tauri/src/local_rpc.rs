@@ -0,0 +1,456 @@
+//! Local RPC server — the integration point for games and streaming tools.
+//!
+//! Listens on a Unix socket (`$XDG_RUNTIME_DIR/nexus-ipc-N`, falling back
+//! to the temp dir) or, on Windows, the named pipe `\\.\pipe\nexus-ipc-N`,
+//! taking the first free N in `0..10`. The wire format follows Discord RPC
+//! so existing game SDKs work with little change: every frame is an 8-byte
+//! header (little-endian opcode and payload length) and a JSON payload.
+//!
+//! After a `HANDSHAKE` (`{"v":1,"client_id":"…"}`) the client sends `FRAME`
+//! commands and gets a reply carrying the same `nonce`:
+//!
+//! - `SET_ACTIVITY`         — set or clear the user's rich presence
+//! - `SELECT_VOICE_CHANNEL` — join a voice channel (`null` leaves)
+//! - `GET_VOICE_SETTINGS` / `SET_VOICE_SETTINGS` — read or toggle mute/deafen
+//! - `SUBSCRIBE`            — `ACTIVITY_JOIN` delivers join secrets
+//!
+//! Voice changes are forwarded to the frontend as `rpc-voice-join` and
+//! `rpc-voice-settings` events; it owns the actual voice connection.
+
+use serde_json::{json, Value};
+use tauri::{AppHandle, Emitter, Listener, Manager, Runtime};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+use crate::commands::activity::ActivityJoin;
+use crate::commands::api_client;
+use crate::state::AppState;
+
+/// Protocol version spoken in the handshake.
+const RPC_VERSION: u64 = 1;
+/// Socket / pipe suffixes tried in order.
+const MAX_INSTANCES: u32 = 10;
+/// Largest accepted frame payload.
+const MAX_FRAME_LEN: usize = 64 * 1024;
+
+// Error codes sent in `ERROR` replies.
+const ERR_UNKNOWN_COMMAND: u32 = 1000;
+const ERR_INVALID_PAYLOAD: u32 = 1001;
+const ERR_REQUEST_FAILED: u32 = 1002;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u32)]
+pub enum Opcode {
+    Handshake = 0,
+    Frame = 1,
+    Close = 2,
+    Ping = 3,
+    Pong = 4,
+}
+
+impl Opcode {
+    fn from_u32(op: u32) -> Option<Self> {
+        Some(match op {
+            0 => Self::Handshake,
+            1 => Self::Frame,
+            2 => Self::Close,
+            3 => Self::Ping,
+            4 => Self::Pong,
+            _ => return None,
+        })
+    }
+}
+
+/// Encode one frame.
+pub fn encode(op: Opcode, payload: &Value) -> Vec<u8> {
+    let body = payload.to_string();
+    let mut out = Vec::with_capacity(8 + body.len());
+    out.extend_from_slice(&(op as u32).to_le_bytes());
+    out.extend_from_slice(&(body.len() as u32).to_le_bytes());
+    out.extend_from_slice(body.as_bytes());
+    out
+}
+
+/// Read one frame. `Ok(None)` on a clean disconnect.
+async fn read_frame<R: AsyncRead + Unpin>(reader: &mut R) -> std::io::Result<Option<(Opcode, Value)>> {
+    let mut header = [0u8; 8];
+    match reader.read_exact(&mut header).await {
+        Ok(_) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e),
+    }
+    let op = u32::from_le_bytes(header[..4].try_into().unwrap());
+    let len = u32::from_le_bytes(header[4..].try_into().unwrap()) as usize;
+    let invalid = |msg: String| std::io::Error::new(std::io::ErrorKind::InvalidData, msg);
+    let op = Opcode::from_u32(op).ok_or_else(|| invalid(format!("unknown opcode {op}")))?;
+    if len > MAX_FRAME_LEN {
+        return Err(invalid(format!("frame of {len} bytes exceeds limit")));
+    }
+    let mut body = vec![0u8; len];
+    reader.read_exact(&mut body).await?;
+    let payload = serde_json::from_slice(&body).map_err(|e| invalid(e.to_string()))?;
+    Ok(Some((op, payload)))
+}
+
+/// Translate a Discord-RPC style activity into the Nexus presence format.
+///
+/// Party sizes arrive as `[current, max]`; `name` falls back to the client
+/// ID given in the handshake.
+pub fn to_presence_activity(activity: &Value, client_id: &str) -> Value {
+    let party = activity.get("party").map(|p| {
+        let size = p.get("size").and_then(Value::as_array);
+        json!({
+            "id": p.get("id"),
+            "size": size.and_then(|s| s.first()).and_then(Value::as_i64),
+            "max": size.and_then(|s| s.get(1)).and_then(Value::as_i64),
+        })
+    });
+    let assets = activity.get("assets");
+    json!({
+        "activity_type": activity.get("type").and_then(Value::as_str).unwrap_or("playing"),
+        "name": activity.get("name").and_then(Value::as_str).unwrap_or(client_id),
+        "details": activity.get("details"),
+        "state": activity.get("state"),
+        "large_image": assets.and_then(|a| a.get("large_image")),
+        "small_image": assets.and_then(|a| a.get("small_image")),
+        "party": party,
+        "secrets": activity.get("secrets").map(|s| json!({ "join": s.get("join") })),
+    })
+}
+
+/// Start the server on the Tauri runtime. Failure to bind is logged, not
+/// fatal — the app works without it.
+pub fn start<R: Runtime>(app: AppHandle<R>) {
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = serve(app).await {
+            tracing::warn!(error = %e, "Local RPC server unavailable");
+        }
+    });
+}
+
+#[cfg(unix)]
+async fn serve<R: Runtime>(app: AppHandle<R>) -> anyhow::Result<()> {
+    use tokio::net::{UnixListener, UnixStream};
+
+    let dir = std::env::var_os("XDG_RUNTIME_DIR")
+        .map(std::path::PathBuf::from)
+        .unwrap_or_else(std::env::temp_dir);
+
+    let mut bound = None;
+    for n in 0..MAX_INSTANCES {
+        let path = dir.join(format!("nexus-ipc-{n}"));
+        if path.exists() {
+            // Another live instance owns it; a stale file from a crash doesn't.
+            if UnixStream::connect(&path).await.is_ok() {
+                continue;
+            }
+            let _ = std::fs::remove_file(&path);
+        }
+        if let Ok(listener) = UnixListener::bind(&path) {
+            bound = Some((listener, path));
+            break;
+        }
+    }
+    let (listener, path) = bound.ok_or_else(|| anyhow::anyhow!("no free nexus-ipc socket"))?;
+    tracing::info!(path = %path.display(), "Local RPC server listening");
+
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = handle_connection(app, stream).await {
+                tracing::debug!(error = %e, "Local RPC connection closed");
+            }
+        });
+    }
+}
+
+#[cfg(windows)]
+async fn serve<R: Runtime>(app: AppHandle<R>) -> anyhow::Result<()> {
+    use tokio::net::windows::named_pipe::ServerOptions;
+
+    let mut bound = None;
+    for n in 0..MAX_INSTANCES {
+        let name = format!(r"\\.\pipe\nexus-ipc-{n}");
+        if let Ok(server) = ServerOptions::new().first_pipe_instance(true).create(&name) {
+            bound = Some((server, name));
+            break;
+        }
+    }
+    let (mut server, name) = bound.ok_or_else(|| anyhow::anyhow!("no free nexus-ipc pipe"))?;
+    tracing::info!(pipe = %name, "Local RPC server listening");
+
+    loop {
+        server.connect().await?;
+        // Open the next instance before handing this one off, so clients
+        // never find the pipe missing.
+        let connected = std::mem::replace(&mut server, ServerOptions::new().create(&name)?);
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = handle_connection(app, connected).await {
+                tracing::debug!(error = %e, "Local RPC connection closed");
+            }
+        });
+    }
+}
+
+/// Per-connection state.
+struct Connection<R: Runtime> {
+    app: AppHandle<R>,
+    client_id: String,
+    /// Listener feeding `ACTIVITY_JOIN` events, once subscribed.
+    join_listener: Option<tauri::EventId>,
+    join_tx: mpsc::UnboundedSender<ActivityJoin>,
+}
+
+async fn handle_connection<R: Runtime, S>(app: AppHandle<R>, stream: S) -> anyhow::Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    let (mut reader, mut writer) = tokio::io::split(stream);
+
+    // The first frame must be the handshake.
+    let Some((Opcode::Handshake, handshake)) = read_frame(&mut reader).await? else {
+        anyhow::bail!("expected handshake");
+    };
+    if handshake.get("v").and_then(Value::as_u64) != Some(RPC_VERSION) {
+        let close = json!({ "code": 4000, "message": "Unsupported RPC version" });
+        writer.write_all(&encode(Opcode::Close, &close)).await?;
+        return Ok(());
+    }
+    let client_id = handshake
+        .get("client_id")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_owned();
+
+    let session = app.state::<AppState>().session_snapshot();
+    let ready = json!({
+        "cmd": "DISPATCH",
+        "evt": "READY",
+        "data": {
+            "v": RPC_VERSION,
+            "user": { "id": session.user_id, "username": session.username },
+        },
+    });
+    writer.write_all(&encode(Opcode::Frame, &ready)).await?;
+    tracing::info!(client_id = %client_id, "Local RPC client connected");
+
+    // Reads run on their own task: a frame read cancelled half-way by
+    // `select!` would desynchronise the stream.
+    let (frame_tx, mut frame_rx) = mpsc::channel(16);
+    let reader_task = tauri::async_runtime::spawn(async move {
+        loop {
+            let frame = read_frame(&mut reader).await;
+            let done = !matches!(frame, Ok(Some(_)));
+            if frame_tx.send(frame).await.is_err() || done {
+                break;
+            }
+        }
+    });
+
+    let (join_tx, mut join_rx) = mpsc::unbounded_channel();
+    let mut conn = Connection {
+        app,
+        client_id,
+        join_listener: None,
+        join_tx,
+    };
+
+    let result = async {
+        loop {
+            tokio::select! {
+                Some(frame) = frame_rx.recv() => {
+                    let Some((op, payload)) = frame? else { break };
+                    match op {
+                        Opcode::Frame => {
+                            let reply = conn.dispatch(&payload).await;
+                            writer.write_all(&encode(Opcode::Frame, &reply)).await?;
+                        }
+                        Opcode::Ping => writer.write_all(&encode(Opcode::Pong, &payload)).await?,
+                        Opcode::Close => break,
+                        Opcode::Handshake | Opcode::Pong => {}
+                    }
+                }
+                Some(join) = join_rx.recv() => {
+                    conn.app
+                        .state::<AppState>()
+                        .activity_joins
+                        .lock()
+                        .unwrap()
+                        .retain(|j| j.secret != join.secret);
+                    let event = json!({
+                        "cmd": "DISPATCH",
+                        "evt": "ACTIVITY_JOIN",
+                        "data": { "secret": join.secret },
+                    });
+                    writer.write_all(&encode(Opcode::Frame, &event)).await?;
+                }
+                else => break,
+            }
+        }
+        anyhow::Ok(())
+    }
+    .await;
+
+    reader_task.abort();
+    if let Some(id) = conn.join_listener.take() {
+        conn.app.unlisten(id);
+    }
+    result
+}
+
+impl<R: Runtime> Connection<R> {
+    /// Run one command and build its reply.
+    async fn dispatch(&mut self, payload: &Value) -> Value {
+        let cmd = payload.get("cmd").and_then(Value::as_str).unwrap_or_default().to_owned();
+        let nonce = payload.get("nonce").cloned().unwrap_or(Value::Null);
+        let args = payload.get("args").cloned().unwrap_or(Value::Null);
+
+        let result = match cmd.as_str() {
+            "SET_ACTIVITY" => self.set_activity(&args).await,
+            "SELECT_VOICE_CHANNEL" => self.select_voice_channel(&args),
+            "GET_VOICE_SETTINGS" => Ok(self.voice_settings()),
+            "SET_VOICE_SETTINGS" => self.set_voice_settings(&args),
+            "SUBSCRIBE" => self.subscribe(payload),
+            _ => Err((ERR_UNKNOWN_COMMAND, format!("Unknown command {cmd:?}"))),
+        };
+
+        match result {
+            Ok(data) => json!({ "cmd": cmd, "nonce": nonce, "evt": null, "data": data }),
+            Err((code, message)) => json!({
+                "cmd": cmd,
+                "nonce": nonce,
+                "evt": "ERROR",
+                "data": { "code": code, "message": message },
+            }),
+        }
+    }
+
+    async fn set_activity(&self, args: &Value) -> Result<Value, (u32, String)> {
+        let activity = match args.get("activity") {
+            Some(a) if !a.is_null() => to_presence_activity(a, &self.client_id),
+            // An empty activity clears the stored one.
+            _ => json!({}),
+        };
+        let session = self.app.state::<AppState>().session_snapshot();
+        let (client, base) = api_client(&session).map_err(|e| (ERR_REQUEST_FAILED, e.to_string()))?;
+        let resp = client
+            .post(format!("{base}/api/v1/users/@me/presence"))
+            .json(&json!({ "activity": activity }))
+            .send()
+            .await
+            .map_err(|e| (ERR_REQUEST_FAILED, e.to_string()))?;
+        if !resp.status().is_success() {
+            let status = resp.status();
+            return Err((ERR_REQUEST_FAILED, format!("Presence update failed: {status}")));
+        }
+        Ok(args.get("activity").cloned().unwrap_or(Value::Null))
+    }
+
+    fn select_voice_channel(&self, args: &Value) -> Result<Value, (u32, String)> {
+        let channel_id = match args.get("channel_id") {
+            None | Some(Value::Null) => None,
+            Some(v) => Some(
+                v.as_str()
+                    .and_then(|s| Uuid::parse_str(s).ok())
+                    .ok_or((ERR_INVALID_PAYLOAD, "channel_id must be a UUID".to_owned()))?,
+            ),
+        };
+        self.app.state::<AppState>().voice.lock().unwrap().channel_id = channel_id;
+        let _ = self.app.emit("rpc-voice-join", channel_id);
+        Ok(json!({ "id": channel_id }))
+    }
+
+    fn voice_settings(&self) -> Value {
+        let voice = self.app.state::<AppState>().voice.lock().unwrap().clone();
+        json!({ "mute": voice.muted, "deaf": voice.deafened })
+    }
+
+    fn set_voice_settings(&self, args: &Value) -> Result<Value, (u32, String)> {
+        {
+            let state = self.app.state::<AppState>();
+            let mut voice = state.voice.lock().unwrap();
+            if let Some(mute) = args.get("mute").and_then(Value::as_bool) {
+                voice.muted = mute;
+            }
+            if let Some(deaf) = args.get("deaf").and_then(Value::as_bool) {
+                voice.deafened = deaf;
+                // Deafening implies muting, as in the main client.
+                if deaf {
+                    voice.muted = true;
+                }
+            }
+        }
+        let settings = self.voice_settings();
+        let _ = self.app.emit("rpc-voice-settings", &settings);
+        Ok(settings)
+    }
+
+    fn subscribe(&mut self, payload: &Value) -> Result<Value, (u32, String)> {
+        let evt = payload.get("evt").and_then(Value::as_str).unwrap_or_default();
+        if evt != "ACTIVITY_JOIN" {
+            return Err((ERR_INVALID_PAYLOAD, format!("Cannot subscribe to {evt:?}")));
+        }
+        if self.join_listener.is_none() {
+            let tx = self.join_tx.clone();
+            self.join_listener = Some(self.app.listen("activity-join", move |event| {
+                if let Ok(join) = serde_json::from_str::<ActivityJoin>(event.payload()) {
+                    let _ = tx.send(join);
+                }
+            }));
+            // Joins accepted before the game connected are delivered now.
+            let pending: Vec<_> = self
+                .app
+                .state::<AppState>()
+                .activity_joins
+                .lock()
+                .unwrap()
+                .drain(..)
+                .collect();
+            for join in pending {
+                let _ = self.join_tx.send(join);
+            }
+        }
+        Ok(json!({ "evt": evt }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn frames_round_trip() {
+        let payload = json!({ "cmd": "SET_ACTIVITY", "nonce": "1" });
+        let bytes = encode(Opcode::Frame, &payload);
+        let (op, decoded) = read_frame(&mut bytes.as_slice()).await.unwrap().unwrap();
+        assert_eq!(op, Opcode::Frame);
+        assert_eq!(decoded, payload);
+    }
+
+    #[tokio::test]
+    async fn rejects_oversized_frames() {
+        let mut bytes = (Opcode::Frame as u32).to_le_bytes().to_vec();
+        bytes.extend_from_slice(&((MAX_FRAME_LEN + 1) as u32).to_le_bytes());
+        assert!(read_frame(&mut bytes.as_slice()).await.is_err());
+    }
+
+    #[test]
+    fn maps_discord_activity() {
+        let activity = json!({
+            "state": "In a group",
+            "details": "Ranked",
+            "assets": { "large_image": "map" },
+            "party": { "id": "abc", "size": [2, 4] },
+            "secrets": { "join": "s3cret" },
+        });
+        let mapped = to_presence_activity(&activity, "my-game");
+        assert_eq!(mapped["name"], "my-game");
+        assert_eq!(mapped["activity_type"], "playing");
+        assert_eq!(mapped["large_image"], "map");
+        assert_eq!(mapped["party"], json!({ "id": "abc", "size": 2, "max": 4 }));
+        assert_eq!(mapped["secrets"]["join"], "s3cret");
+    }
+}
@@ -1,5 +1,6 @@
 //! Application state — shared across all Tauri commands via `State<AppState>`.
 
+use std::collections::VecDeque;
 use std::sync::Mutex;
 use uuid::Uuid;
 
@@ -22,12 +23,31 @@ pub struct PttState {
     pub shortcut: String, // e.g. "CapsLock"
 }
 
+/// The user's own voice state, as driven by local RPC clients.
+#[derive(Debug, Default, Clone)]
+pub struct LocalVoiceState {
+    pub channel_id: Option<Uuid>,
+    pub muted: bool,
+    pub deafened: bool,
+}
+
 /// Shared application state injected via `tauri::Manager::manage`.
 #[derive(Debug, Default)]
 pub struct AppState {
     pub session: Mutex<Session>,
     pub ptt: Mutex<PttState>,
+    /// Bound shortcuts, as last registered.
+    pub keybinds: Mutex<crate::hotkeys::KeybindStatus>,
     pub overlay_visible: Mutex<bool>,
+    pub voice: Mutex<LocalVoiceState>,
+    /// Activity joins waiting to be handed to the game over local RPC.
+    pub activity_joins: Mutex<VecDeque<crate::commands::activity::ActivityJoin>>,
+    /// Servers, channels and messages fetched this session.
+    pub cache: Mutex<crate::cache::LocalCache>,
+    pub media: Mutex<crate::commands::media::MediaSettings>,
+    pub update_channel: Mutex<crate::updater::UpdateChannel>,
+    /// Update found by the last check, if any.
+    pub pending_update: Mutex<Option<crate::updater::PendingUpdate>>,
 }
 
 impl AppState {
@@ -1,56 +1,113 @@
-//! System tray — presence menu, quick actions, show/hide main window.
+//! System tray — presence menu, quick actions, show/hide main window,
+//! and unread mention badges.
 
+use serde::Deserialize;
 use tauri::{
-    menu::{Menu, MenuItem, PredefinedMenuItem},
+    image::Image,
+    menu::{Menu, MenuItem, PredefinedMenuItem, Submenu},
     tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
-    App, Emitter, Manager, Runtime,
+    App, AppHandle, Emitter, Manager, Runtime,
 };
+use uuid::Uuid;
 
-/// Build and attach the system tray icon + context menu.
-pub fn setup_tray<R: Runtime>(app: &mut App<R>) -> tauri::Result<()> {
-    let handle = app.handle();
+const TRAY_ID: &str = "main-tray";
+/// Menu ID prefix for unread entries: `unread:<channel_id>:<server_id|dm>`.
+const UNREAD_PREFIX: &str = "unread:";
 
-    // ── Menu items ──────────────────────────────────────────────────────────
-    let show = MenuItem::with_id(handle, "show", "Show Nexus", true, None::<&str>)?;
-    let separator = PredefinedMenuItem::separator(handle)?;
+/// A server (or the DM list) with unread mentions, as reported by the frontend.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UnreadServer {
+    /// `None` for direct messages.
+    pub server_id: Option<Uuid>,
+    pub name: String,
+    /// Channel to open when the entry is clicked.
+    pub channel_id: Uuid,
+    pub mentions: u32,
+}
 
-    let online = MenuItem::with_id(handle, "presence_online", "● Online", true, None::<&str>)?;
-    let idle = MenuItem::with_id(handle, "presence_idle", "◑ Idle", true, None::<&str>)?;
-    let dnd = MenuItem::with_id(handle, "presence_dnd", "⊘ Do Not Disturb", true, None::<&str>)?;
-    let invisible =
-        MenuItem::with_id(handle, "presence_invisible", "○ Invisible", true, None::<&str>)?;
+fn unread_item_id(entry: &UnreadServer) -> String {
+    let server = entry.server_id.map_or_else(|| "dm".to_owned(), |id| id.to_string());
+    format!("{UNREAD_PREFIX}{}:{server}", entry.channel_id)
+}
 
-    let separator2 = PredefinedMenuItem::separator(handle)?;
-    let quit = MenuItem::with_id(handle, "quit", "Quit Nexus", true, None::<&str>)?;
+/// Parse an unread menu ID back into `(server_id, channel_id)`.
+fn parse_unread_item_id(id: &str) -> Option<(Option<Uuid>, Uuid)> {
+    let (channel, server) = id.strip_prefix(UNREAD_PREFIX)?.split_once(':')?;
+    let channel_id = channel.parse().ok()?;
+    let server_id = match server {
+        "dm" => None,
+        other => Some(other.parse().ok()?),
+    };
+    Some((server_id, channel_id))
+}
 
-    let menu = Menu::with_items(
+/// Build the tray context menu, with an "Unread Mentions" submenu when
+/// `unread` is non-empty.
+fn build_menu<R: Runtime>(handle: &AppHandle<R>, unread: &[UnreadServer]) -> tauri::Result<Menu<R>> {
+    let menu = Menu::new(handle)?;
+    menu.append(&MenuItem::with_id(handle, "show", "Show Nexus", true, None::<&str>)?)?;
+
+    if !unread.is_empty() {
+        let submenu = Submenu::with_id(handle, "unread", "Unread Mentions", true)?;
+        for entry in unread {
+            let label = format!("{} ({})", entry.name, entry.mentions);
+            submenu.append(&MenuItem::with_id(
+                handle,
+                unread_item_id(entry),
+                label,
+                true,
+                None::<&str>,
+            )?)?;
+        }
+        menu.append(&submenu)?;
+    }
+
+    menu.append(&PredefinedMenuItem::separator(handle)?)?;
+    menu.append(&MenuItem::with_id(handle, "presence_online", "● Online", true, None::<&str>)?)?;
+    menu.append(&MenuItem::with_id(handle, "presence_idle", "◑ Idle", true, None::<&str>)?)?;
+    menu.append(&MenuItem::with_id(
         handle,
-        &[
-            &show,
-            &separator,
-            &online,
-            &idle,
-            &dnd,
-            &invisible,
-            &separator2,
-            &quit,
-        ],
-    )?;
+        "presence_dnd",
+        "⊘ Do Not Disturb",
+        true,
+        None::<&str>,
+    )?)?;
+    menu.append(&MenuItem::with_id(
+        handle,
+        "presence_invisible",
+        "○ Invisible",
+        true,
+        None::<&str>,
+    )?)?;
+
+    menu.append(&PredefinedMenuItem::separator(handle)?)?;
+    menu.append(&MenuItem::with_id(handle, "quit", "Quit Nexus", true, None::<&str>)?)?;
+    Ok(menu)
+}
+
+fn show_main<R: Runtime>(handle: &AppHandle<R>) {
+    if let Some(window) = handle.get_webview_window("main") {
+        let _ = window.show();
+        let _ = window.unminimize();
+        let _ = window.set_focus();
+    }
+}
+
+/// Build and attach the system tray icon + context menu.
+pub fn setup_tray<R: Runtime>(app: &mut App<R>) -> tauri::Result<()> {
+    let handle = app.handle();
+    let menu = build_menu(handle, &[])?;
 
     // ── Tray icon ───────────────────────────────────────────────────────────
-    TrayIconBuilder::with_id("main-tray")
+    TrayIconBuilder::with_id(TRAY_ID)
         .tooltip("Nexus")
         .menu(&menu)
         .show_menu_on_left_click(false)
         .on_menu_event(move |app, event| {
             let handle = app.clone();
             match event.id.as_ref() {
-                "show" => {
-                    if let Some(window) = handle.get_webview_window("main") {
-                        let _ = window.show();
-                        let _ = window.set_focus();
-                    }
-                }
+                "show" => show_main(&handle),
                 "quit" => {
                     std::process::exit(0);
                 }
@@ -59,7 +116,15 @@ pub fn setup_tray<R: Runtime>(app: &mut App<R>) -> tauri::Result<()> {
                     // Emit to frontend so it can call the API
                     let _ = handle.emit("tray-presence-change", presence.to_owned());
                 }
-                _ => {}
+                id => {
+                    if let Some((server_id, channel_id)) = parse_unread_item_id(id) {
+                        show_main(&handle);
+                        let _ = handle.emit(
+                            "tray-open-channel",
+                            serde_json::json!({ "serverId": server_id, "channelId": channel_id }),
+                        );
+                    }
+                }
             }
         })
         .on_tray_icon_event(|tray, event| {
@@ -91,8 +156,89 @@ pub fn update_tray_tooltip<R: Runtime>(
     app: &tauri::AppHandle<R>,
     text: &str,
 ) -> tauri::Result<()> {
-    if let Some(tray) = app.tray_by_id("main-tray") {
+    if let Some(tray) = app.tray_by_id(TRAY_ID) {
         tray.set_tooltip(Some(text))?;
     }
     Ok(())
 }
+
+/// A red dot for the Windows taskbar overlay icon.
+#[cfg_attr(not(windows), allow(dead_code))]
+fn mention_dot() -> Image<'static> {
+    const SIZE: u32 = 16;
+    let center = (SIZE as f32 - 1.0) / 2.0;
+    let radius = SIZE as f32 / 2.0;
+    let mut rgba = Vec::with_capacity((SIZE * SIZE * 4) as usize);
+    for y in 0..SIZE {
+        for x in 0..SIZE {
+            let distance = ((x as f32 - center).powi(2) + (y as f32 - center).powi(2)).sqrt();
+            // One pixel of anti-aliasing at the edge
+            let alpha = (radius - distance).clamp(0.0, 1.0);
+            rgba.extend_from_slice(&[0xED, 0x42, 0x45, (alpha * 255.0) as u8]);
+        }
+    }
+    Image::new_owned(rgba, SIZE, SIZE)
+}
+
+/// Show unread mentions in the tray: a submenu entry per server, a tooltip
+/// total, and a badge (dock/launcher) or overlay icon (Windows taskbar) on
+/// the main window. An empty list clears all three.
+#[tauri::command]
+pub fn set_tray_unread(app: AppHandle, servers: Vec<UnreadServer>) -> Result<(), String> {
+    let servers: Vec<UnreadServer> = servers.into_iter().filter(|s| s.mentions > 0).collect();
+    let total: u32 = servers.iter().map(|s| s.mentions).sum();
+
+    if let Some(tray) = app.tray_by_id(TRAY_ID) {
+        let menu = build_menu(&app, &servers).map_err(|e| e.to_string())?;
+        tray.set_menu(Some(menu)).map_err(|e| e.to_string())?;
+        let tooltip = match total {
+            0 => "Nexus".to_owned(),
+            1 => "Nexus — 1 unread mention".to_owned(),
+            n => format!("Nexus — {n} unread mentions"),
+        };
+        tray.set_tooltip(Some(tooltip)).map_err(|e| e.to_string())?;
+    }
+
+    if let Some(window) = app.get_webview_window("main") {
+        #[cfg(windows)]
+        window
+            .set_overlay_icon((total > 0).then(mention_dot))
+            .map_err(|e| e.to_string())?;
+        // Not every Linux desktop supports launcher badges; ignore failures.
+        #[cfg(not(windows))]
+        let _ = window.set_badge_count((total > 0).then_some(i64::from(total)));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(server_id: Option<Uuid>) -> UnreadServer {
+        UnreadServer {
+            server_id,
+            name: "Rustaceans".into(),
+            channel_id: Uuid::new_v4(),
+            mentions: 2,
+        }
+    }
+
+    #[test]
+    fn unread_item_id_round_trips() {
+        for server_id in [Some(Uuid::new_v4()), None] {
+            let entry = entry(server_id);
+            assert_eq!(
+                parse_unread_item_id(&unread_item_id(&entry)),
+                Some((server_id, entry.channel_id))
+            );
+        }
+    }
+
+    #[test]
+    fn other_menu_ids_are_not_unread_entries() {
+        assert_eq!(parse_unread_item_id("show"), None);
+        assert_eq!(parse_unread_item_id("presence_idle"), None);
+        assert_eq!(parse_unread_item_id("unread:not-a-uuid:dm"), None);
+    }
+}
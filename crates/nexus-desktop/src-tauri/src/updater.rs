@@ -1,14 +1,110 @@
 //! Auto-update — background check using tauri-plugin-updater.
 //!
-//! Checks for a new release every 4 hours by default.
+//! Checks for a new release every 4 hours by default, on the update channel
+//! chosen in Settings (stable, beta or nightly).
 //! On finding a pending update, emits `update-available` to the frontend
 //! (which renders a non-intrusive banner) instead of updating silently.
-//! The user explicitly triggers download + install.
+//! The user explicitly triggers download + install, then restarts via
+//! `restart_to_update` once `update-ready` fires.
+//!
+//! Every platform downloads the full signed bundle; there are no delta
+//! updates. tauri-plugin-updater verifies the signature over the bytes it
+//! downloads and installs exactly those, so a patch would have to be applied
+//! to a copy of the installed artifact, outside the plugin:
+//!
+//! - Windows: the artifact is an NSIS/MSI installer. The installed files are
+//!   not the installer, so there is nothing to patch.
+//! - macOS: the artifact is an `.app.tar.gz` extracted over the bundle. The
+//!   tarball isn't kept and can't be rebuilt byte for byte from the signed
+//!   `.app`.
+//! - Linux: the installed AppImage is the artifact, so a binary patch could
+//!   apply, but the result would then be installed without the plugin ever
+//!   checking its signature. Doing that safely means patching and verifying
+//!   here, which isn't implemented.
+//!
+//! See `docs/desktop-updates.md`.
 
-use tauri::{AppHandle, Emitter};
-use tauri_plugin_updater::UpdaterExt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, State, Url};
+use tauri_plugin_store::StoreExt;
+use tauri_plugin_updater::{Update, UpdaterExt};
 use tokio::time::{interval, Duration};
 
+use crate::state::AppState;
+
+const RELEASES_BASE: &str = "https://releases.nexus.chat";
+/// Placeholders filled in by tauri-plugin-updater.
+const MANIFEST_PATH: &str = "{{target}}/{{arch}}/{{current_version}}";
+const STORE_FILE: &str = "settings.json";
+const CHANNEL_KEY: &str = "update_channel";
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum UpdateChannel {
+    #[default]
+    Stable,
+    Beta,
+    Nightly,
+}
+
+impl UpdateChannel {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Stable => "stable",
+            Self::Beta => "beta",
+            Self::Nightly => "nightly",
+        }
+    }
+
+    /// Manifest endpoint for this channel. Stable keeps the original
+    /// unprefixed path so existing installs keep updating.
+    pub fn endpoint(self) -> String {
+        match self {
+            Self::Stable => format!("{RELEASES_BASE}/{MANIFEST_PATH}"),
+            other => format!("{RELEASES_BASE}/{}/{MANIFEST_PATH}", other.as_str()),
+        }
+    }
+
+    /// Markdown release notes for `version` on this channel.
+    pub fn notes_url(self, version: &str) -> String {
+        format!("{RELEASES_BASE}/notes/{}/{version}.md", self.as_str())
+    }
+}
+
+impl FromStr for UpdateChannel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "stable" => Ok(Self::Stable),
+            "beta" => Ok(Self::Beta),
+            "nightly" => Ok(Self::Nightly),
+            other => Err(format!("Unknown update channel '{other}'")),
+        }
+    }
+}
+
+/// Payload of `update-available`, also returned by `check_for_updates`.
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateInfo {
+    pub current_version: String,
+    pub version: String,
+    /// Release notes from the manifest, if any.
+    pub body: Option<String>,
+    pub date: Option<String>,
+    pub channel: UpdateChannel,
+}
+
+/// An update found by the last check, waiting for the user.
+#[derive(Debug)]
+pub struct PendingUpdate {
+    update: Update,
+    /// Installed and waiting for a restart.
+    installed: bool,
+}
 
 /// Spawn a background task that polls for updates on a fixed interval.
 ///
@@ -17,50 +113,234 @@ use tokio::time::{interval, Duration};
 /// updater::schedule_check(app.handle().clone());
 /// ```
 pub fn schedule_check(app: AppHandle) {
+    *app.state::<AppState>().update_channel.lock().unwrap() = load_channel(&app);
+
     tauri::async_runtime::spawn(async move {
         // First check after 30 seconds (let the app settle)
         tokio::time::sleep(Duration::from_secs(30)).await;
-        check_once(&app).await;
+        log_check(&app).await;
 
         // Then every 4 hours
         let mut ticker = interval(Duration::from_secs(4 * 60 * 60));
         loop {
             ticker.tick().await;
-            check_once(&app).await;
+            log_check(&app).await;
         }
     });
 }
 
-/// Perform a single update check; emit an event if an update is available.
-async fn check_once(app: &AppHandle) {
-    tracing::debug!("Checking for updates...");
-    match app.updater() {
-        Ok(updater) => match updater.check().await {
-            Ok(Some(update)) => {
-                tracing::info!(
-                    "Update available: {} → {}",
-                    update.current_version,
-                    update.version
-                );
-                let _ = app.emit(
-                    "update-available",
-                    serde_json::json!({
-                        "current_version": update.current_version,
-                        "new_version": update.version,
-                        "body": update.body,
-                        "date": update.date,
-                    }),
-                );
-            }
-            Ok(None) => {
-                tracing::debug!("No update available");
-            }
-            Err(e) => {
-                tracing::warn!("Update check failed: {e}");
-            }
-        },
+/// The persisted update channel, defaulting to stable.
+fn load_channel(app: &AppHandle) -> UpdateChannel {
+    app.store(STORE_FILE)
+        .ok()
+        .and_then(|store| store.get(CHANNEL_KEY))
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default()
+}
+
+async fn log_check(app: &AppHandle) {
+    match check_once(app).await {
+        Ok(Some(info)) => {
+            tracing::info!(
+                "Update available on {}: {} → {}",
+                info.channel.as_str(),
+                info.current_version,
+                info.version
+            );
+        }
+        Ok(None) => {
+            tracing::debug!("No update available");
+        }
         Err(e) => {
-            tracing::warn!("Updater not available: {e}");
+            tracing::warn!("Update check failed: {e}");
         }
     }
 }
+
+/// Perform a single update check; emit an event if an update is available.
+async fn check_once(app: &AppHandle) -> Result<Option<UpdateInfo>, String> {
+    let state = app.state::<AppState>();
+    if state
+        .pending_update
+        .lock()
+        .unwrap()
+        .as_ref()
+        .is_some_and(|p| p.installed)
+    {
+        // Already installed; nothing to do until the user restarts.
+        return Ok(None);
+    }
+
+    let channel = *state.update_channel.lock().unwrap();
+    tracing::debug!("Checking for updates on {}...", channel.as_str());
+    let endpoint = Url::parse(&channel.endpoint()).map_err(|e| e.to_string())?;
+    let updater = app
+        .updater_builder()
+        .endpoints(vec![endpoint])
+        .and_then(|builder| builder.build())
+        .map_err(|e| format!("Updater not available: {e}"))?;
+
+    let Some(update) = updater.check().await.map_err(|e| e.to_string())? else {
+        *state.pending_update.lock().unwrap() = None;
+        return Ok(None);
+    };
+
+    let info = UpdateInfo {
+        current_version: update.current_version.clone(),
+        version: update.version.clone(),
+        body: update.body.clone(),
+        date: update.date.map(|d| d.to_string()),
+        channel,
+    };
+    *state.pending_update.lock().unwrap() = Some(PendingUpdate {
+        update,
+        installed: false,
+    });
+    let _ = app.emit("update-available", &info);
+    Ok(Some(info))
+}
+
+/// Download and install `update`, emitting `update-progress` as it goes.
+async fn download(app: &AppHandle, update: &Update) -> Result<(), String> {
+    let progress = app.clone();
+    let mut downloaded: u64 = 0;
+    update
+        .download_and_install(
+            move |chunk, total| {
+                downloaded += chunk as u64;
+                let _ = progress.emit(
+                    "update-progress",
+                    serde_json::json!({ "downloaded": downloaded, "total": total }),
+                );
+            },
+            || {},
+        )
+        .await
+        .map_err(|e| e.to_string())
+}
+
+// ── Commands ─────────────────────────────────────────────────────────────────
+
+/// Tauri command: the channel updates are fetched from.
+#[tauri::command]
+pub fn get_update_channel(state: State<'_, AppState>) -> UpdateChannel {
+    *state.update_channel.lock().unwrap()
+}
+
+/// Tauri command: switch update channel, persist it, and check right away.
+///
+/// Moving to a more stable channel never downgrades; the new channel takes
+/// over once it ships a version newer than the one installed.
+#[tauri::command]
+pub async fn set_update_channel(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    channel: String,
+) -> Result<Option<UpdateInfo>, String> {
+    let channel: UpdateChannel = channel.parse()?;
+    *state.update_channel.lock().unwrap() = channel;
+
+    let store = app.store(STORE_FILE).map_err(|e| e.to_string())?;
+    store.set(CHANNEL_KEY, serde_json::json!(channel));
+    store.save().map_err(|e| e.to_string())?;
+
+    tracing::info!("Update channel changed to {}", channel.as_str());
+    check_once(&app).await
+}
+
+/// Tauri command: check for an update now instead of waiting for the timer.
+#[tauri::command]
+pub async fn check_for_updates(app: AppHandle) -> Result<Option<UpdateInfo>, String> {
+    check_once(&app).await
+}
+
+/// Tauri command: release notes as markdown.
+///
+/// Defaults to the pending update's notes, falling back to the running
+/// version's ("what's new"). Returns an empty string when none are published.
+#[tauri::command]
+pub async fn get_release_notes(
+    state: State<'_, AppState>,
+    version: Option<String>,
+) -> Result<String, String> {
+    let pending = state
+        .pending_update
+        .lock()
+        .unwrap()
+        .as_ref()
+        .map(|p| (p.update.version.clone(), p.update.body.clone()));
+    let version = match (version, pending) {
+        (Some(v), _) => v,
+        (None, Some((_, Some(body)))) => return Ok(body),
+        (None, Some((v, None))) => v,
+        (None, None) => env!("CARGO_PKG_VERSION").to_owned(),
+    };
+
+    let channel = *state.update_channel.lock().unwrap();
+    let resp = reqwest::get(channel.notes_url(&version))
+        .await
+        .map_err(|e| e.to_string())?;
+    if resp.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(String::new());
+    }
+    if !resp.status().is_success() {
+        return Err(format!("Release notes unavailable ({})", resp.status()));
+    }
+    resp.text().await.map_err(|e| e.to_string())
+}
+
+/// Tauri command: download and install the pending update.
+///
+/// Emits `update-ready` when done; the update applies on the next start
+/// (see `restart_to_update`).
+#[tauri::command]
+pub async fn install_update(app: AppHandle, state: State<'_, AppState>) -> Result<(), String> {
+    let update = match state.pending_update.lock().unwrap().as_ref() {
+        Some(p) if p.installed => return Ok(()),
+        Some(p) => p.update.clone(),
+        None => return Err("No update available".into()),
+    };
+
+    download(&app, &update).await?;
+
+    if let Some(pending) = state.pending_update.lock().unwrap().as_mut() {
+        pending.installed = true;
+    }
+    tracing::info!("Update {} installed; waiting for restart", update.version);
+    let _ = app.emit("update-ready", &update.version);
+    Ok(())
+}
+
+/// Tauri command: restart into the installed update.
+#[tauri::command]
+pub fn restart_to_update(app: AppHandle, state: State<'_, AppState>) -> Result<(), String> {
+    let installed = state
+        .pending_update
+        .lock()
+        .unwrap()
+        .as_ref()
+        .is_some_and(|p| p.installed);
+    if !installed {
+        return Err("No update has been installed".into());
+    }
+    app.restart()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn channel_endpoints() {
+        assert_eq!(
+            UpdateChannel::Stable.endpoint(),
+            "https://releases.nexus.chat/{{target}}/{{arch}}/{{current_version}}"
+        );
+        assert_eq!(
+            UpdateChannel::Nightly.endpoint(),
+            "https://releases.nexus.chat/nightly/{{target}}/{{arch}}/{{current_version}}"
+        );
+        assert_eq!("beta".parse::<UpdateChannel>(), Ok(UpdateChannel::Beta));
+        assert!("canary".parse::<UpdateChannel>().is_err());
+    }
+}
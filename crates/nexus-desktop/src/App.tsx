@@ -1,7 +1,7 @@
 import { Routes, Route, Navigate } from "react-router-dom";
 import { useEffect } from "react";
 import { listen } from "@tauri-apps/api/event";
-import { useStore } from "./store";
+import { useStore, type UpdateInfo } from "./store";
 import { isTauri } from "./invoke";
 import LoginPage from "./pages/Login";
 import RegisterPage from "./pages/Register";
@@ -17,7 +17,7 @@ export default function App() {
   // Listen for update-available event from the Tauri updater plugin (Tauri only)
   useEffect(() => {
     if (!isTauri()) return;
-    const unlisten = listen<{ version: string; body: string }>(
+    const unlisten = listen<UpdateInfo>(
       "update-available",
       (e) => {
         setUpdateAvailable(e.payload);
@@ -1,32 +1,73 @@
-import { invoke } from "../invoke";
+import { useEffect, useState } from "react";
+import { listen } from "@tauri-apps/api/event";
+import { invoke, isTauri } from "../invoke";
 import { useStore } from "../store";
 
+type Phase = "available" | "downloading" | "ready";
+
 export default function UpdateBanner() {
   const { updateAvailable, setUpdateAvailable } = useStore();
+  const [phase, setPhase] = useState<Phase>("available");
+  const [progress, setProgress] = useState<number | null>(null);
+
+  useEffect(() => {
+    if (!isTauri()) return;
+    const unlistenProgress = listen<{ downloaded: number; total: number | null }>(
+      "update-progress",
+      (e) => {
+        const { downloaded, total } = e.payload;
+        setProgress(total ? Math.round((downloaded / total) * 100) : null);
+      }
+    );
+    const unlistenReady = listen<string>("update-ready", () => setPhase("ready"));
+    return () => {
+      unlistenProgress.then((fn) => fn());
+      unlistenReady.then((fn) => fn());
+    };
+  }, []);
 
   if (!updateAvailable) return null;
 
   const handleInstall = async () => {
+    setPhase("downloading");
     try {
       await invoke("install_update");
     } catch (e) {
       console.error("install_update error", e);
+      setPhase("available");
+    }
+  };
+
+  const handleRestart = async () => {
+    try {
+      await invoke("restart_to_update");
+    } catch (e) {
+      console.error("restart_to_update error", e);
     }
   };
 
   return (
     <div className="flex items-center justify-between px-4 py-2 bg-accent-500/20 border-b border-accent-500/30 text-sm shrink-0 no-select">
       <p className="text-white">
-        <span className="font-semibold">Nexus {updateAvailable.version}</span> is available
-        {updateAvailable.body ? ` — ${updateAvailable.body}` : "."}
+        <span className="font-semibold">Nexus {updateAvailable.version}</span>
+        {phase === "ready" ? " is ready — restart to finish updating." : " is available"}
+        {phase === "available" && (updateAvailable.body ? ` — ${updateAvailable.body}` : ".")}
+        {phase === "downloading" && ` — downloading${progress !== null ? ` ${progress}%` : "…"}`}
       </p>
       <div className="flex gap-2">
-        <button
-          onClick={handleInstall}
-          className="btn-primary text-xs px-3 py-1"
-        >
-          Install &amp; Restart
-        </button>
+        {phase === "ready" ? (
+          <button onClick={handleRestart} className="btn-primary text-xs px-3 py-1">
+            Restart to Update
+          </button>
+        ) : (
+          <button
+            onClick={handleInstall}
+            disabled={phase === "downloading"}
+            className="btn-primary text-xs px-3 py-1 disabled:opacity-50"
+          >
+            Download
+          </button>
+        )}
         <button
           onClick={() => setUpdateAvailable(null)}
           className="btn-ghost text-xs"
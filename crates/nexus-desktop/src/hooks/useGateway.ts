@@ -11,15 +11,33 @@
  */
 import { useEffect, useRef } from "react";
 import { useStore, Message, VoiceParticipant } from "../store";
+import { invoke, isTauri } from "../invoke";
 
 interface WireMessage {
   op: string;
   d: unknown;
 }
 
+interface ReadyData {
+  servers?: { id: string; channels?: { id: string }[] }[];
+  dm_channels?: { id: string }[];
+  read_states?: { channel_id: string; mention_count: number }[];
+}
+
+/** MessageFlags::SUPPRESS_NOTIFICATIONS — silent messages don't count as mentions. */
+const SILENT_FLAG = 1 << 12;
+
 export function useGateway() {
-  const { session, appendMessage, setVoiceParticipants, setPttActive, setTyping } =
-    useStore();
+  const {
+    session,
+    appendMessage,
+    setVoiceParticipants,
+    setPttActive,
+    setTyping,
+    setReadStates,
+    setMentionCount,
+    addMention,
+  } = useStore();
   const wsRef = useRef<WebSocket | null>(null);
   const reconnectTimer = useRef<ReturnType<typeof setTimeout> | null>(null);
   const heartbeatTimer = useRef<ReturnType<typeof setInterval> | null>(null);
@@ -75,9 +93,21 @@ export function useGateway() {
             break;
           }
 
-          case "Ready":
+          case "Ready": {
             console.log("[gateway] READY received");
+            const ready = wire.d as ReadyData;
+            const channelServers: Record<string, string | null> = {};
+            for (const server of ready.servers ?? []) {
+              for (const ch of server.channels ?? []) channelServers[ch.id] = server.id;
+            }
+            for (const dm of ready.dm_channels ?? []) channelServers[dm.id] = null;
+            const mentionCounts: Record<string, number> = {};
+            for (const rs of ready.read_states ?? []) {
+              if (rs.mention_count > 0) mentionCounts[rs.channel_id] = rs.mention_count;
+            }
+            setReadStates(mentionCounts, channelServers);
             break;
+          }
 
           case "Dispatch": {
             const dispatch = wire.d as { event: string; data: unknown };
@@ -118,6 +148,8 @@ export function useGateway() {
             content: string;
             created_at: string;
             edited_at?: string;
+            mentions?: string[];
+            flags?: number;
           };
 
           // Dedup: skip if this message was already added optimistically
@@ -135,6 +167,18 @@ export function useGateway() {
             editedAt: raw.edited_at,
           };
           appendMessage(msg.channelId, msg);
+
+          const silent = ((raw.flags ?? 0) & SILENT_FLAG) !== 0;
+          if (!silent && raw.author_id !== session.userId && raw.mentions?.includes(session.userId)) {
+            addMention(raw.channel_id);
+          }
+          break;
+        }
+
+        case "MESSAGE_ACK": {
+          // Read on this or another device
+          const raw = data as { channel_id: string; mention_count: number };
+          setMentionCount(raw.channel_id, raw.mention_count);
           break;
         }
 
@@ -154,6 +198,16 @@ export function useGateway() {
           break;
         }
 
+        case "ACTIVITY_JOIN": {
+          // Hand the join secret to the game via the desktop's local RPC server
+          if (isTauri()) {
+            invoke("relay_activity_join", { join: data }).catch((e) =>
+              console.error("[gateway] failed to relay activity join", e)
+            );
+          }
+          break;
+        }
+
         case "PTT_START":
           setPttActive(true);
           break;
@@ -174,5 +228,14 @@ export function useGateway() {
       if (heartbeatTimer.current) clearInterval(heartbeatTimer.current);
       wsRef.current?.close();
     };
-  }, [session, appendMessage, setVoiceParticipants, setPttActive, setTyping]);
+  }, [
+    session,
+    appendMessage,
+    setVoiceParticipants,
+    setPttActive,
+    setTyping,
+    setReadStates,
+    setMentionCount,
+    addMention,
+  ]);
 }
@@ -0,0 +1,36 @@
+/**
+ * Apply voice commands from games connected to the desktop's local RPC
+ * server (SELECT_VOICE_CHANNEL, SET_VOICE_SETTINGS).
+ * No-ops when running in a plain browser (Tauri not present).
+ */
+import { useEffect } from "react";
+import { listen } from "@tauri-apps/api/event";
+import { useNavigate } from "react-router-dom";
+import { useStore } from "../store";
+import { isTauri } from "../invoke";
+
+export function useLocalRpc() {
+  const { setActiveChannel, setJoinedVoiceChannel, setSelfVoice } = useStore();
+  const navigate = useNavigate();
+
+  useEffect(() => {
+    if (!isTauri()) return;
+    const unlistenJoin = listen<string | null>("rpc-voice-join", (ev) => {
+      const channelId = ev.payload;
+      setJoinedVoiceChannel(channelId);
+      if (channelId) {
+        setActiveChannel(channelId);
+        navigate(`/voice/${channelId}`);
+      }
+    });
+    const unlistenSettings = listen<{ mute: boolean; deaf: boolean }>(
+      "rpc-voice-settings",
+      (ev) => setSelfVoice(ev.payload.mute, ev.payload.deaf)
+    );
+
+    return () => {
+      unlistenJoin.then((fn) => fn());
+      unlistenSettings.then((fn) => fn());
+    };
+  }, [navigate, setActiveChannel, setJoinedVoiceChannel, setSelfVoice]);
+}
@@ -1,5 +1,6 @@
 /**
- * Listen for PTT start/stop events emitted by the Tauri hotkeys module.
+ * Listen for PTT start/stop and push-to-mute/deafen events emitted by the
+ * Tauri hotkeys module.
  * No-ops when running in a plain browser (Tauri not present).
  */
 import { useEffect } from "react";
@@ -8,16 +9,21 @@ import { useStore } from "../store";
 import { isTauri } from "../invoke";
 
 export function usePtt() {
-  const { setPttActive } = useStore();
+  const { setPttActive, setSelfVoice } = useStore();
 
   useEffect(() => {
     if (!isTauri()) return;
     const unlistenStart = listen("ptt-start", () => setPttActive(true));
     const unlistenStop = listen("ptt-stop", () => setPttActive(false));
+    const unlistenVoice = listen<{ mute: boolean; deaf: boolean }>(
+      "voice-settings",
+      (ev) => setSelfVoice(ev.payload.mute, ev.payload.deaf)
+    );
 
     return () => {
       unlistenStart.then((fn) => fn());
       unlistenStop.then((fn) => fn());
+      unlistenVoice.then((fn) => fn());
     };
-  }, [setPttActive]);
+  }, [setPttActive, setSelfVoice]);
 }
@@ -0,0 +1,63 @@
+/**
+ * Mirror unread mentions into the system tray (badge + per-server submenu)
+ * and open the channel picked from that submenu.
+ * No-ops when running in a plain browser (Tauri not present).
+ */
+import { useEffect } from "react";
+import { listen } from "@tauri-apps/api/event";
+import { useNavigate } from "react-router-dom";
+import { useStore } from "../store";
+import { invoke, isTauri } from "../invoke";
+
+interface UnreadServer {
+  serverId: string | null;
+  name: string;
+  channelId: string;
+  mentions: number;
+}
+
+export function useTrayUnread() {
+  const { mentionCounts, channelServers, servers, setActiveServer, setActiveChannel } =
+    useStore();
+  const navigate = useNavigate();
+
+  useEffect(() => {
+    if (!isTauri()) return;
+    // Group by server; clicking opens the channel with the most mentions
+    const byServer = new Map<string | null, UnreadServer>();
+    for (const [channelId, count] of Object.entries(mentionCounts)) {
+      const serverId = channelServers[channelId] ?? null;
+      const entry = byServer.get(serverId);
+      if (!entry) {
+        const name = serverId
+          ? servers.find((s) => s.id === serverId)?.name ?? "Unknown server"
+          : "Direct Messages";
+        byServer.set(serverId, { serverId, name, channelId, mentions: count });
+      } else {
+        if (count > (mentionCounts[entry.channelId] ?? 0)) entry.channelId = channelId;
+        entry.mentions += count;
+      }
+    }
+    const unread = [...byServer.values()].sort((a, b) => b.mentions - a.mentions);
+    invoke("set_tray_unread", { servers: unread }).catch((e) =>
+      console.error("[tray] failed to update unread mentions", e)
+    );
+  }, [mentionCounts, channelServers, servers]);
+
+  useEffect(() => {
+    if (!isTauri()) return;
+    const unlisten = listen<{ serverId: string | null; channelId: string }>(
+      "tray-open-channel",
+      (ev) => {
+        const { serverId, channelId } = ev.payload;
+        // setActiveServer clears the active channel, so it goes first
+        if (serverId !== useStore.getState().activeServerId) setActiveServer(serverId);
+        setActiveChannel(channelId);
+        navigate(`/channel/${channelId}`);
+      }
+    );
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [navigate, setActiveServer, setActiveChannel]);
+}
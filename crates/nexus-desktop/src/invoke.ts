@@ -195,10 +195,62 @@ async function browserInvoke<T>(cmd: string, args: Raw = {}): Promise<T> {
       });
     }
 
+    // ── Rich presence ─────────────────────────────────────────────────────
+    case "request_activity_join": {
+      return apiFetch<T>("POST", `/api/v1/users/${args.userId}/activity/join`);
+    }
+
     // ── Desktop-only commands (no-ops in browser) ─────────────────────────
     case "install_update":
       console.info("[browser] install_update is a no-op in the browser");
       return undefined as unknown as T;
+    // ── Media (browser falls back to mediaDevices + localStorage) ─────────
+    case "list_audio_devices": {
+      const devices = await navigator.mediaDevices.enumerateDevices();
+      return devices
+        .filter((d) => d.kind === "audioinput" || d.kind === "audiooutput")
+        .map((d) => ({
+          id: d.deviceId,
+          name: d.label || d.deviceId,
+          kind: d.kind === "audioinput" ? "input" : "output",
+          isDefault: d.deviceId === "default",
+        })) as unknown as T;
+    }
+    case "get_media_settings":
+    case "set_audio_devices":
+    case "set_input_sensitivity":
+    case "set_hardware_acceleration": {
+      const saved = {
+        inputDevice: null,
+        outputDevice: null,
+        inputSensitivityDb: -50,
+        autoSensitivity: true,
+        hardwareAcceleration: true,
+        ...JSON.parse(localStorage.getItem("nexus:dev:media") ?? "{}"),
+      };
+      if (cmd === "set_audio_devices") {
+        saved.inputDevice = (args.inputDevice as string) || null;
+        saved.outputDevice = (args.outputDevice as string) || null;
+      } else if (cmd === "set_input_sensitivity") {
+        saved.inputSensitivityDb = args.thresholdDb;
+        saved.autoSensitivity = args.automatic;
+      } else if (cmd === "set_hardware_acceleration") {
+        saved.hardwareAcceleration = args.enabled;
+      }
+      localStorage.setItem("nexus:dev:media", JSON.stringify(saved));
+      return saved as unknown as T;
+    }
+
+    case "get_update_channel":
+      return "stable" as unknown as T;
+    case "get_release_notes":
+      return "" as unknown as T;
+    case "set_update_channel":
+    case "check_for_updates":
+      return null as unknown as T;
+    case "quick_switch":
+      // The quick switcher searches the desktop's local cache; none in browser.
+      return [] as unknown as T;
 
     default:
       throw new Error(`[browser] Unhandled invoke command: "${cmd}"`);
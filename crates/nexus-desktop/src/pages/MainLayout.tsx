@@ -3,6 +3,8 @@ import { Routes, Route } from "react-router-dom";
 import { useStore } from "../store";
 import { useGateway } from "../hooks/useGateway";
 import { usePtt } from "../hooks/usePtt";
+import { useLocalRpc } from "../hooks/useLocalRpc";
+import { useTrayUnread } from "../hooks/useTrayUnread";
 import ServerList from "../components/ServerList";
 import ChannelList from "../components/ChannelList";
 import ChatView from "../components/ChatView";
@@ -16,6 +18,10 @@ export default function MainLayout() {
   useGateway();
   // Listen for PTT events from Tauri
   usePtt();
+  // Voice commands from games over local RPC
+  useLocalRpc();
+  // Unread mention badge and tray submenu
+  useTrayUnread();
 
   useEffect(() => {
     loadServers();
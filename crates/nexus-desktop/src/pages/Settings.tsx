@@ -1,11 +1,20 @@
-import { useState } from "react";
-import { useStore } from "../store";
-import { invoke } from "../invoke";
+import { useEffect, useState } from "react";
+import {
+  useStore,
+  type AudioDevice,
+  type KeybindStatus,
+  type Keybinds,
+  type MediaSettings,
+  type UpdateChannel,
+  type UpdateInfo,
+} from "../store";
+import { listen } from "@tauri-apps/api/event";
+import { invoke, isTauri } from "../invoke";
 import ThemeSwitcher from "../themes/ThemeSwitcher";
 import type { PluginManifest } from "../plugins/types";
 
 export default function SettingsPage() {
-  const { plugins, enabledPlugins, installPlugin, uninstallPlugin, togglePlugin, session, setSession } = useStore();
+  const { plugins, enabledPlugins, installPlugin, uninstallPlugin, togglePlugin, session, setSession, setUpdateAvailable } = useStore();
   const [urlInput, setUrlInput] = useState("");
   const [error, setError] = useState<string | null>(null);
   const [loading, setLoading] = useState(false);
@@ -16,6 +25,102 @@ export default function SettingsPage() {
   const [profileSaving, setProfileSaving] = useState(false);
   const [profileMsg, setProfileMsg] = useState<string | null>(null);
 
+  // Voice & video section state
+  const [devices, setDevices] = useState<AudioDevice[]>([]);
+  const [media, setMedia] = useState<MediaSettings | null>(null);
+  const [mediaMsg, setMediaMsg] = useState<string | null>(null);
+
+  useEffect(() => {
+    invoke<AudioDevice[]>("list_audio_devices").then(setDevices).catch(() => {});
+    invoke<MediaSettings>("get_media_settings").then(setMedia).catch(() => {});
+  }, []);
+
+  // Volume keybinds change the output volume from outside this page.
+  useEffect(() => {
+    if (!isTauri()) return;
+    const unlisten = listen<MediaSettings>("media-settings", (ev) => setMedia(ev.payload));
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  // Keybinds section state
+  const [keybinds, setKeybinds] = useState<Keybinds | null>(null);
+  const [keybindMsg, setKeybindMsg] = useState<string | null>(null);
+
+  useEffect(() => {
+    if (!isTauri()) return;
+    invoke<KeybindStatus>("get_keybinds").then((s) => setKeybinds(s.keybinds)).catch(() => {});
+  }, []);
+
+  const saveKeybinds = async () => {
+    if (!keybinds) return;
+    setKeybindMsg(null);
+    try {
+      const status = await invoke<KeybindStatus>("set_keybinds", { keybinds });
+      setKeybinds(status.keybinds);
+      setKeybindMsg(
+        status.unregistered.length
+          ? `Error: could not register ${status.unregistered.join(", ")} — the key may be taken by another app.`
+          : "Saved!"
+      );
+    } catch (e) {
+      setKeybindMsg(`Error: ${String(e)}`);
+    }
+  };
+
+  const updateMedia = async (cmd: string, args: Record<string, unknown>) => {
+    setMediaMsg(null);
+    try {
+      setMedia(await invoke<MediaSettings>(cmd, args));
+    } catch (e) {
+      setMediaMsg(`Error: ${String(e)}`);
+    }
+  };
+
+  // Updates section state
+  const [updateChannel, setUpdateChannel] = useState<UpdateChannel>("stable");
+  const [updateMsg, setUpdateMsg] = useState<string | null>(null);
+  const [releaseNotes, setReleaseNotes] = useState<string | null>(null);
+
+  useEffect(() => {
+    if (!isTauri()) return;
+    invoke<UpdateChannel>("get_update_channel").then(setUpdateChannel).catch(() => {});
+  }, []);
+
+  const applyCheck = (info: UpdateInfo | null) => {
+    setUpdateAvailable(info);
+    setUpdateMsg(info ? `Nexus ${info.version} is available.` : "You're up to date.");
+  };
+
+  const changeChannel = async (channel: UpdateChannel) => {
+    setUpdateChannel(channel);
+    setUpdateMsg("Checking…");
+    try {
+      applyCheck(await invoke<UpdateInfo | null>("set_update_channel", { channel }));
+    } catch (e) {
+      setUpdateMsg(`Error: ${String(e)}`);
+    }
+  };
+
+  const checkNow = async () => {
+    setUpdateMsg("Checking…");
+    try {
+      applyCheck(await invoke<UpdateInfo | null>("check_for_updates"));
+    } catch (e) {
+      setUpdateMsg(`Error: ${String(e)}`);
+    }
+  };
+
+  const showReleaseNotes = async () => {
+    try {
+      const notes = await invoke<string>("get_release_notes");
+      setReleaseNotes(notes || "No release notes published.");
+    } catch (e) {
+      setReleaseNotes(`Error: ${String(e)}`);
+    }
+  };
+
   const saveProfile = async () => {
     if (!session) return;
     setProfileSaving(true);
@@ -127,6 +232,175 @@ export default function SettingsPage() {
         </div>
       </section>
 
+      {/* ── Voice & Video ────────────────────────────── */}
+      {media && (
+        <section className="mb-10">
+          <h2 className="text-base font-semibold mb-4 border-b border-bg-600 pb-2">Voice &amp; Video</h2>
+          <div className="flex flex-col gap-3 max-w-lg">
+            {(["input", "output"] as const).map((kind) => {
+              const key = kind === "input" ? "inputDevice" : "outputDevice";
+              return (
+                <div key={kind}>
+                  <label className="block text-xs font-semibold text-muted uppercase tracking-wide mb-1">
+                    {kind === "input" ? "Input Device" : "Output Device"}
+                  </label>
+                  <select
+                    className="input w-full"
+                    value={media[key] ?? ""}
+                    onChange={(e) =>
+                      updateMedia("set_audio_devices", {
+                        inputDevice: media.inputDevice,
+                        outputDevice: media.outputDevice,
+                        [key]: e.target.value || null,
+                      })
+                    }
+                  >
+                    <option value="">System default</option>
+                    {devices
+                      .filter((d) => d.kind === kind)
+                      .map((d) => (
+                        <option key={d.id} value={d.id}>
+                          {d.name}
+                          {d.isDefault ? " (default)" : ""}
+                        </option>
+                      ))}
+                  </select>
+                </div>
+              );
+            })}
+            <div>
+              <label className="block text-xs font-semibold text-muted uppercase tracking-wide mb-1">
+                Input Sensitivity {media.autoSensitivity ? "(automatic)" : `(${media.inputSensitivityDb} dB)`}
+              </label>
+              <label className="flex items-center gap-2 mb-2">
+                <input
+                  type="checkbox"
+                  checked={media.autoSensitivity}
+                  onChange={(e) =>
+                    updateMedia("set_input_sensitivity", {
+                      thresholdDb: media.inputSensitivityDb,
+                      automatic: e.target.checked,
+                    })
+                  }
+                />
+                Automatically determine input sensitivity
+              </label>
+              <input
+                type="range"
+                min={-100}
+                max={0}
+                className="w-full"
+                disabled={media.autoSensitivity}
+                value={media.inputSensitivityDb}
+                onChange={(e) => setMedia({ ...media, inputSensitivityDb: Number(e.target.value) })}
+                onMouseUp={() =>
+                  updateMedia("set_input_sensitivity", {
+                    thresholdDb: media.inputSensitivityDb,
+                    automatic: false,
+                  })
+                }
+              />
+            </div>
+            <div>
+              <label className="block text-xs font-semibold text-muted uppercase tracking-wide mb-1">
+                Output Volume ({media.outputVolume}%)
+              </label>
+              <input
+                type="range"
+                min={0}
+                max={200}
+                step={5}
+                className="w-full"
+                value={media.outputVolume}
+                onChange={(e) => setMedia({ ...media, outputVolume: Number(e.target.value) })}
+                onMouseUp={() => updateMedia("set_output_volume", { percent: media.outputVolume })}
+              />
+            </div>
+            <label className="flex items-center gap-2">
+              <input
+                type="checkbox"
+                checked={media.hardwareAcceleration}
+                onChange={(e) => updateMedia("set_hardware_acceleration", { enabled: e.target.checked })}
+              />
+              Hardware acceleration (may require a restart)
+            </label>
+            {mediaMsg && <p className="text-dnd text-xs">{mediaMsg}</p>}
+          </div>
+        </section>
+      )}
+
+      {/* ── Keybinds ─────────────────────────────────── */}
+      {keybinds && (
+        <section className="mb-10">
+          <h2 className="text-base font-semibold mb-4 border-b border-bg-600 pb-2">Keybinds</h2>
+          <div className="flex flex-col gap-3 max-w-lg">
+            <p className="text-muted text-xs">
+              Work in every app; a bound key no longer reaches other apps. Leave a field empty to unbind it.
+            </p>
+            {(
+              [
+                ["pushToTalk", "Push to Talk"],
+                ["pushToMute", "Push to Mute"],
+                ["toggleDeafen", "Toggle Deafen"],
+                ["volumeUp", "Output Volume Up"],
+                ["volumeDown", "Output Volume Down"],
+              ] as const
+            ).map(([key, label]) => (
+              <div key={key}>
+                <label className="block text-xs font-semibold text-muted uppercase tracking-wide mb-1">{label}</label>
+                <input
+                  className="input w-full"
+                  placeholder="e.g. Ctrl+Shift+M"
+                  value={keybinds[key]}
+                  onChange={(e) => setKeybinds({ ...keybinds, [key]: e.target.value })}
+                />
+              </div>
+            ))}
+            <div className="flex items-center gap-3">
+              <button onClick={saveKeybinds} className="btn-primary">Save Keybinds</button>
+              {keybindMsg && (
+                <span className={keybindMsg.startsWith("Error") ? "text-dnd text-xs" : "text-muted text-xs"}>
+                  {keybindMsg}
+                </span>
+              )}
+            </div>
+          </div>
+        </section>
+      )}
+
+      {/* ── Updates ──────────────────────────────────── */}
+      {isTauri() && (
+        <section className="mb-10">
+          <h2 className="text-base font-semibold mb-4 border-b border-bg-600 pb-2">Updates</h2>
+          <div className="flex flex-col gap-3 max-w-lg">
+            <div>
+              <label className="block text-xs font-semibold text-muted uppercase tracking-wide mb-1">Update Channel</label>
+              <select
+                className="input w-full"
+                value={updateChannel}
+                onChange={(e) => changeChannel(e.target.value as UpdateChannel)}
+              >
+                <option value="stable">Stable</option>
+                <option value="beta">Beta — early access, mostly stable</option>
+                <option value="nightly">Nightly — latest builds, may break</option>
+              </select>
+            </div>
+            <div className="flex items-center gap-3">
+              <button onClick={checkNow} className="btn-primary">Check for Updates</button>
+              <button onClick={showReleaseNotes} className="btn-ghost text-xs">Release Notes</button>
+              {updateMsg && (
+                <span className={updateMsg.startsWith("Error") ? "text-dnd text-xs" : "text-muted text-xs"}>
+                  {updateMsg}
+                </span>
+              )}
+            </div>
+            {releaseNotes && (
+              <pre className="whitespace-pre-wrap bg-bg-800 rounded-lg p-4 text-xs">{releaseNotes}</pre>
+            )}
+          </div>
+        </section>
+      )}
+
       {/* ── Plugins ──────────────────────────────────── */}
       <section>
         <h2 className="text-base font-semibold mb-4 border-b border-bg-600 pb-2">Plugins</h2>
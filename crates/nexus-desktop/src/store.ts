@@ -56,9 +56,45 @@ export interface VoiceParticipant {
   avatar?: string;
 }
 
+export interface AudioDevice {
+  id: string;
+  name: string;
+  kind: "input" | "output";
+  isDefault: boolean;
+}
+
+export interface MediaSettings {
+  inputDevice: string | null;
+  outputDevice: string | null;
+  inputSensitivityDb: number;
+  autoSensitivity: boolean;
+  /** Playback volume in percent, 0–200. */
+  outputVolume: number;
+  hardwareAcceleration: boolean;
+}
+
+/** Global shortcut per voice action; "" leaves it unbound. */
+export interface Keybinds {
+  pushToTalk: string;
+  pushToMute: string;
+  toggleDeafen: string;
+  volumeUp: string;
+  volumeDown: string;
+}
+
+export interface KeybindStatus {
+  keybinds: Keybinds;
+  /** Bound actions the OS refused (key taken, or no global hotkeys). */
+  unregistered: (keyof Keybinds)[];
+}
+
+export type UpdateChannel = "stable" | "beta" | "nightly";
+
 export interface UpdateInfo {
+  currentVersion?: string;
   version: string;
-  body: string;
+  body: string | null;
+  channel?: UpdateChannel;
 }
 
 interface StoreState {
@@ -91,13 +127,24 @@ interface StoreState {
   // Unread — channelIds that have received messages since last viewed
   unreadChannels: Record<string, boolean>;
 
+  // Mentions — unread mention count per channelId, and the server each
+  // channel belongs to (null for DMs)
+  mentionCounts: Record<string, number>;
+  channelServers: Record<string, string | null>;
+  setReadStates: (mentionCounts: Record<string, number>, channelServers: Record<string, string | null>) => void;
+  setMentionCount: (channelId: string, count: number) => void;
+  addMention: (channelId: string) => void;
+
   // Voice
   voiceParticipants: VoiceParticipant[];
   joinedVoiceChannelId: string | null;
   pttActive: boolean;
+  selfMuted: boolean;
+  selfDeafened: boolean;
   setVoiceParticipants: (participants: VoiceParticipant[]) => void;
   setJoinedVoiceChannel: (id: string | null) => void;
   setPttActive: (active: boolean) => void;
+  setSelfVoice: (muted: boolean, deafened: boolean) => void;
 
   // UI
   updateAvailable: UpdateInfo | null;
@@ -144,8 +191,12 @@ export const useStore = create<StoreState>((set, get) => ({
   setChannels: (channels) => set({ channels }),
   setActiveChannel: (id) => set((s) => {
     const unreadChannels = { ...s.unreadChannels };
-    if (id) delete unreadChannels[id];
-    return { activeChannelId: id, unreadChannels };
+    const mentionCounts = { ...s.mentionCounts };
+    if (id) {
+      delete unreadChannels[id];
+      delete mentionCounts[id];
+    }
+    return { activeChannelId: id, unreadChannels, mentionCounts };
   }),
 
   // ─── Messages ─────────────────────────────────────────────────────────
@@ -206,13 +257,35 @@ export const useStore = create<StoreState>((set, get) => ({
   // ─── Unread ───────────────────────────────────────────────────────────────
   unreadChannels: {},
 
+  // ─── Mentions ─────────────────────────────────────────────────────────────
+  mentionCounts: {},
+  channelServers: {},
+  setReadStates: (mentionCounts, channelServers) => set({ mentionCounts, channelServers }),
+  setMentionCount: (channelId, count) =>
+    set((s) => {
+      const mentionCounts = { ...s.mentionCounts };
+      if (count > 0) mentionCounts[channelId] = count;
+      else delete mentionCounts[channelId];
+      return { mentionCounts };
+    }),
+  addMention: (channelId) =>
+    set((s) =>
+      // Already reading it — the mention is seen as it arrives
+      s.activeChannelId === channelId
+        ? {}
+        : { mentionCounts: { ...s.mentionCounts, [channelId]: (s.mentionCounts[channelId] ?? 0) + 1 } }
+    ),
+
   // ─── Voice ────────────────────────────────────────────────────────────
   voiceParticipants: [],
   joinedVoiceChannelId: null,
   pttActive: false,
+  selfMuted: false,
+  selfDeafened: false,
   setVoiceParticipants: (participants) => set({ voiceParticipants: participants }),
   setJoinedVoiceChannel: (id) => set({ joinedVoiceChannelId: id }),
   setPttActive: (active) => set({ pttActive: active }),
+  setSelfVoice: (muted, deafened) => set({ selfMuted: muted, selfDeafened: deafened }),
 
   // ─── UI ───────────────────────────────────────────────────────────────
   updateAvailable: null,
@@ -277,6 +350,8 @@ export const useStore = create<StoreState>((set, get) => ({
       messages: {},
       typingUsers: {},
       unreadChannels: {},
+      mentionCounts: {},
+      channelServers: {},
       activeServerId: null,
       activeChannelId: null,
     });
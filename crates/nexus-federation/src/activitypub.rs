@@ -0,0 +1,716 @@
+//! ActivityPub outbound bridge.
+//!
+//! Every announcement channel of a public server is exposed as an
+//! ActivityPub `Service` actor, so fediverse accounts (Mastodon, Misskey,
+//! …) can follow it. New posts become `Create` / `Note` activities that are
+//! queued in `federation_outbox` and delivered to each follower's inbox with
+//! an HTTP Signature (`rsa-sha256`, draft-cavage-http-signatures-12).
+//!
+//! The bridge is publish-only: the only inbound activities handled are
+//! `Follow` and `Undo(Follow)`, which are answered with `Accept`.
+//!
+//! ```text
+//!  acct:<channel-id>@nexus.example.com     (WebFinger)
+//!    └─► https://nexus.example.com/ap/channels/<id>            (actor)
+//!          ├── /inbox      ◄── Follow / Undo
+//!          ├── /outbox     recent Create activities
+//!          ├── /followers  follower count
+//!          └── /notes/<message-id>
+//! ```
+
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use base64::{engine::general_purpose::STANDARD as B64, Engine as _};
+use chrono::Utc;
+use reqwest::header::HeaderMap;
+use rsa::pkcs1v15::{Signature, SigningKey, VerifyingKey};
+use rsa::pkcs8::{DecodePrivateKey, DecodePublicKey, EncodePrivateKey, EncodePublicKey, LineEnding};
+use rsa::signature::{SignatureEncoding, Signer, Verifier};
+use rsa::{RsaPrivateKey, RsaPublicKey};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use tracing::{debug, info};
+use uuid::Uuid;
+
+use nexus_common::net::is_public;
+use nexus_db::repository::{activitypub, federation_outbox};
+
+use crate::bridge::{BridgeError, BridgeFuture, OutboundPost, ProtocolBridge};
+
+/// ActivityStreams JSON-LD context.
+pub const AS_CONTEXT: &str = "https://www.w3.org/ns/activitystreams";
+/// Addressing constant for public posts.
+pub const AS_PUBLIC: &str = "https://www.w3.org/ns/activitystreams#Public";
+/// Media type for ActivityPub requests and responses.
+pub const ACTIVITY_JSON: &str = "application/activity+json";
+
+const KEY_BITS: usize = 2048;
+/// Inbound signatures older (or newer) than this are rejected.
+const MAX_DATE_SKEW: chrono::Duration = chrono::Duration::hours(1);
+
+// ─── Actor key ───────────────────────────────────────────────────────────────
+
+/// RSA key shared by every channel actor on this server.
+pub struct ActorKey {
+    private: RsaPrivateKey,
+    public_pem: String,
+}
+
+impl ActorKey {
+    /// Generate a fresh key pair.
+    pub fn generate() -> Result<Self, BridgeError> {
+        let private = RsaPrivateKey::new(&mut rand_core::OsRng, KEY_BITS)
+            .map_err(|e| BridgeError::Signing(e.to_string()))?;
+        Self::from_private(private)
+    }
+
+    /// Reconstruct from a PKCS#8 PEM private key.
+    pub fn from_pem(private_pem: &str) -> Result<Self, BridgeError> {
+        let private = RsaPrivateKey::from_pkcs8_pem(private_pem)
+            .map_err(|e| BridgeError::Signing(e.to_string()))?;
+        Self::from_private(private)
+    }
+
+    fn from_private(private: RsaPrivateKey) -> Result<Self, BridgeError> {
+        let public_pem = RsaPublicKey::from(&private)
+            .to_public_key_pem(LineEnding::LF)
+            .map_err(|e| BridgeError::Signing(e.to_string()))?;
+        Ok(Self { private, public_pem })
+    }
+
+    /// Load the persisted key, generating and storing one on first run.
+    pub async fn load_or_generate(pool: &sqlx::AnyPool) -> Result<Self, BridgeError> {
+        if let Some((private_pem, _)) = activitypub::load_key(pool).await? {
+            return Self::from_pem(&private_pem);
+        }
+        let key = Self::generate()?;
+        let private_pem = key
+            .private
+            .to_pkcs8_pem(LineEnding::LF)
+            .map_err(|e| BridgeError::Signing(e.to_string()))?;
+        activitypub::store_key(pool, Uuid::new_v4(), &private_pem, &key.public_pem).await?;
+        info!("ActivityPub: generated and persisted new actor key");
+        Ok(key)
+    }
+
+    pub fn public_key_pem(&self) -> &str {
+        &self.public_pem
+    }
+
+    fn sign(&self, data: &str) -> String {
+        let signature = SigningKey::<Sha256>::new(self.private.clone()).sign(data.as_bytes());
+        B64.encode(signature.to_bytes())
+    }
+}
+
+// ─── Actor metadata ──────────────────────────────────────────────────────────
+
+/// What the API layer knows about a channel when rendering its actor.
+#[derive(Debug, Clone)]
+pub struct ChannelActor {
+    pub channel_id: Uuid,
+    pub channel_name: String,
+    pub server_name: String,
+    pub topic: Option<String>,
+    pub icon_url: Option<String>,
+}
+
+/// The parts of a remote actor document the bridge needs.
+#[derive(Debug, Clone)]
+pub struct RemoteActor {
+    pub id: String,
+    pub inbox: String,
+    pub shared_inbox: Option<String>,
+    pub public_key_id: Option<String>,
+    pub public_key_pem: Option<String>,
+}
+
+// ─── Bridge ──────────────────────────────────────────────────────────────────
+
+/// Publishes public announcement channels to the fediverse.
+pub struct ActivityPubBridge {
+    domain: String,
+    base_url: String,
+    key: ActorKey,
+    pool: sqlx::AnyPool,
+    http: reqwest::Client,
+}
+
+impl ActivityPubBridge {
+    /// Create a bridge for `domain` (the public server name).
+    pub fn new(domain: impl Into<String>, key: ActorKey, pool: sqlx::AnyPool) -> Self {
+        let domain = domain.into();
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(20))
+            .user_agent(concat!("Nexus-ActivityPub/", env!("CARGO_PKG_VERSION")))
+            .build()
+            .expect("failed to build ActivityPub http client");
+        Self { base_url: format!("https://{domain}"), domain, key, pool, http }
+    }
+
+    pub fn domain(&self) -> &str {
+        &self.domain
+    }
+
+    // ── Identifiers ─────────────────────────────────────────────────────────
+
+    /// WebFinger username for a channel (`<uuid-simple>`).
+    pub fn username(channel_id: Uuid) -> String {
+        channel_id.simple().to_string()
+    }
+
+    pub fn actor_id(&self, channel_id: Uuid) -> String {
+        format!("{}/ap/channels/{}", self.base_url, channel_id)
+    }
+
+    pub fn note_id(&self, channel_id: Uuid, message_id: Uuid) -> String {
+        format!("{}/notes/{}", self.actor_id(channel_id), message_id)
+    }
+
+    /// Resolve a WebFinger `resource` (`acct:user@domain`) to a channel ID.
+    pub fn parse_resource(&self, resource: &str) -> Option<Uuid> {
+        let acct = resource.strip_prefix("acct:").unwrap_or(resource);
+        let (user, domain) = acct.rsplit_once('@')?;
+        if !domain.eq_ignore_ascii_case(&self.domain) {
+            return None;
+        }
+        Uuid::parse_str(user).ok()
+    }
+
+    // ── Documents ───────────────────────────────────────────────────────────
+
+    /// WebFinger JRD for a channel actor.
+    pub fn webfinger(&self, channel_id: Uuid) -> Value {
+        json!({
+            "subject": format!("acct:{}@{}", Self::username(channel_id), self.domain),
+            "aliases": [self.actor_id(channel_id)],
+            "links": [{
+                "rel": "self",
+                "type": ACTIVITY_JSON,
+                "href": self.actor_id(channel_id),
+            }],
+        })
+    }
+
+    /// The actor document for a channel.
+    pub fn actor_document(&self, channel: &ChannelActor) -> Value {
+        let id = self.actor_id(channel.channel_id);
+        let mut actor = json!({
+            "@context": [AS_CONTEXT, "https://w3id.org/security/v1"],
+            "id": id,
+            "type": "Service",
+            "preferredUsername": Self::username(channel.channel_id),
+            "name": format!("{} #{}", channel.server_name, channel.channel_name),
+            "summary": channel.topic.as_deref().map(render_html).unwrap_or_default(),
+            "url": id,
+            "inbox": format!("{id}/inbox"),
+            "outbox": format!("{id}/outbox"),
+            "followers": format!("{id}/followers"),
+            "manuallyApprovesFollowers": false,
+            "discoverable": true,
+            "publicKey": {
+                "id": format!("{id}#main-key"),
+                "owner": id,
+                "publicKeyPem": self.key.public_key_pem(),
+            },
+        });
+        if let Some(icon) = &channel.icon_url {
+            actor["icon"] = json!({ "type": "Image", "url": icon });
+        }
+        actor
+    }
+
+    /// A post rendered as a `Note`.
+    pub fn note(&self, post: &OutboundPost) -> Value {
+        let actor = self.actor_id(post.channel_id);
+        json!({
+            "id": self.note_id(post.channel_id, post.message_id),
+            "type": "Note",
+            "attributedTo": actor,
+            "content": render_html(&post.content),
+            "published": post.created_at.to_rfc3339(),
+            "url": self.note_id(post.channel_id, post.message_id),
+            "to": [AS_PUBLIC],
+            "cc": [format!("{actor}/followers")],
+        })
+    }
+
+    /// The `Create` activity wrapping [`Self::note`].
+    pub fn create_activity(&self, post: &OutboundPost) -> Value {
+        let note = self.note(post);
+        json!({
+            "@context": AS_CONTEXT,
+            "id": format!("{}/activity", note["id"].as_str().unwrap_or_default()),
+            "type": "Create",
+            "actor": self.actor_id(post.channel_id),
+            "published": note["published"],
+            "to": note["to"],
+            "cc": note["cc"],
+            "object": note,
+        })
+    }
+
+    /// `OrderedCollection` of recent activities for a channel outbox.
+    pub fn outbox(&self, channel_id: Uuid, posts: &[OutboundPost]) -> Value {
+        let items: Vec<Value> = posts.iter().map(|p| self.create_activity(p)).collect();
+        json!({
+            "@context": AS_CONTEXT,
+            "id": format!("{}/outbox", self.actor_id(channel_id)),
+            "type": "OrderedCollection",
+            "totalItems": items.len(),
+            "orderedItems": items,
+        })
+    }
+
+    /// `OrderedCollection` exposing only the follower count.
+    pub fn followers(&self, channel_id: Uuid, total: i64) -> Value {
+        json!({
+            "@context": AS_CONTEXT,
+            "id": format!("{}/followers", self.actor_id(channel_id)),
+            "type": "OrderedCollection",
+            "totalItems": total,
+        })
+    }
+
+    // ── Inbox ───────────────────────────────────────────────────────────────
+
+    /// Handle a verified inbound activity addressed to a channel actor.
+    ///
+    /// `signer` is the actor whose key signed the request; activities whose
+    /// `actor` differs are rejected.
+    pub async fn handle_inbox(
+        &self,
+        channel_id: Uuid,
+        signer: &RemoteActor,
+        activity: &Value,
+    ) -> Result<(), BridgeError> {
+        let actor = activity["actor"].as_str().unwrap_or_default();
+        if actor != signer.id {
+            return Err(BridgeError::Signing("activity actor does not match signer".into()));
+        }
+        let target = self.actor_id(channel_id);
+
+        match activity["type"].as_str() {
+            Some("Follow") if object_id(&activity["object"]) == Some(target.as_str()) => {
+                activitypub::add_follower(
+                    &self.pool,
+                    Uuid::new_v4(),
+                    channel_id,
+                    &signer.id,
+                    &signer.inbox,
+                    signer.shared_inbox.as_deref(),
+                )
+                .await?;
+                let accept = json!({
+                    "@context": AS_CONTEXT,
+                    "id": format!("{target}#accepts/{}", Uuid::new_v4()),
+                    "type": "Accept",
+                    "actor": target,
+                    "object": activity,
+                });
+                self.enqueue(&signer.inbox, &accept).await?;
+                info!("ActivityPub: {} followed channel {}", signer.id, channel_id);
+            }
+            Some("Undo") if activity["object"]["type"] == "Follow" => {
+                if activitypub::remove_follower(&self.pool, channel_id, &signer.id).await? {
+                    info!("ActivityPub: {} unfollowed channel {}", signer.id, channel_id);
+                }
+            }
+            other => debug!("ActivityPub: ignoring inbound activity {:?}", other),
+        }
+        Ok(())
+    }
+
+    /// Verify the HTTP Signature on an inbound request and return the signer.
+    ///
+    /// `path` is the request path (and query) as received.
+    pub async fn verify_request(
+        &self,
+        method: &str,
+        path: &str,
+        headers: &HeaderMap,
+        body: &[u8],
+    ) -> Result<RemoteActor, BridgeError> {
+        let header = header_str(headers, "signature")
+            .ok_or_else(|| BridgeError::Signing("missing Signature header".into()))?;
+        let params = parse_signature_header(header)
+            .ok_or_else(|| BridgeError::Signing("malformed Signature header".into()))?;
+
+        check_signed_headers(&params.headers, method)?;
+        let signed = params.headers.iter().map(String::as_str).collect::<Vec<_>>();
+        let date = header_str(headers, "date")
+            .and_then(|d| chrono::DateTime::parse_from_rfc2822(d).ok())
+            .ok_or_else(|| BridgeError::Signing("missing or invalid Date header".into()))?;
+        if (Utc::now() - date.with_timezone(&Utc)).abs() > MAX_DATE_SKEW {
+            return Err(BridgeError::Signing("Date header outside the allowed window".into()));
+        }
+        if signed.contains(&"digest") && header_str(headers, "digest") != Some(digest_header(body).as_str()) {
+            return Err(BridgeError::Signing("Digest does not match body".into()));
+        }
+
+        let signing_string = build_signing_string(&params.headers, method, path, |name| {
+            header_str(headers, name).map(str::to_owned)
+        })
+        .ok_or_else(|| BridgeError::Signing("signed header missing from request".into()))?;
+
+        // The key must be published by the actor document it names, which
+        // `fetch_actor` checks is served from its own ID
+        let actor_uri = params.key_id.split('#').next().unwrap_or_default();
+        let actor = self.fetch_actor(actor_uri).await?;
+        if actor.public_key_id.as_deref() != Some(params.key_id.as_str()) || !same_origin(&actor.id, &params.key_id) {
+            return Err(BridgeError::Signing("keyId does not belong to actor".into()));
+        }
+        let pem = actor
+            .public_key_pem
+            .as_deref()
+            .ok_or_else(|| BridgeError::Signing("actor has no public key".into()))?;
+        verify_signature(pem, &signing_string, &params.signature)?;
+        Ok(actor)
+    }
+
+    /// Fetch and parse a remote actor document.
+    ///
+    /// Only public hosts are contacted, redirects are not followed, and the
+    /// document's `id` must be `uri` itself — otherwise any server could
+    /// serve a document claiming to be someone else's actor.
+    pub async fn fetch_actor(&self, uri: &str) -> Result<RemoteActor, BridgeError> {
+        let url = url::Url::parse(uri).map_err(|e| BridgeError::Http(e.to_string()))?;
+        if url.scheme() != "https" {
+            return Err(BridgeError::Http(format!("refusing non-https actor '{uri}'")));
+        }
+        let (host, addr) = public_addr(&url).await?;
+        // Pin the connection to the address just checked
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(20))
+            .redirect(reqwest::redirect::Policy::none())
+            .resolve(&host, addr)
+            .user_agent(concat!("Nexus-ActivityPub/", env!("CARGO_PKG_VERSION")))
+            .build()
+            .map_err(|e| BridgeError::Http(e.to_string()))?;
+        let resp = client
+            .get(url)
+            .header("Accept", ACTIVITY_JSON)
+            .send()
+            .await
+            .map_err(|e| BridgeError::Http(e.to_string()))?;
+        if !resp.status().is_success() {
+            let status = resp.status().as_u16();
+            return Err(BridgeError::Remote(uri.to_owned(), status, resp.text().await.unwrap_or_default()));
+        }
+        let doc: Value = resp.json().await.map_err(|e| BridgeError::Http(e.to_string()))?;
+        let actor = parse_remote_actor(&doc)
+            .ok_or_else(|| BridgeError::Remote(uri.to_owned(), 200, "invalid actor document".into()))?;
+        if actor.id != uri {
+            return Err(BridgeError::Signing(format!("actor document at '{uri}' has id '{}'", actor.id)));
+        }
+        Ok(actor)
+    }
+
+    // ── Delivery ────────────────────────────────────────────────────────────
+
+    /// Queue `activity` for delivery to `inbox` via `federation_outbox`.
+    pub async fn enqueue(&self, inbox: &str, activity: &Value) -> Result<(), BridgeError> {
+        let txn_id = activity["id"].as_str().unwrap_or_default();
+        federation_outbox::enqueue(&self.pool, Uuid::new_v4(), inbox, txn_id, activity).await?;
+        Ok(())
+    }
+
+    /// POST a signed activity to a remote inbox. Called by the outbox worker.
+    pub async fn deliver(&self, inbox: &str, activity: &Value) -> Result<(), BridgeError> {
+        let url = url::Url::parse(inbox).map_err(|e| BridgeError::Http(e.to_string()))?;
+        let host = url
+            .host_str()
+            .ok_or_else(|| BridgeError::Http(format!("inbox '{inbox}' has no host")))?;
+        let host = match url.port() {
+            Some(port) => format!("{host}:{port}"),
+            None => host.to_owned(),
+        };
+        let path = match url.query() {
+            Some(q) => format!("{}?{q}", url.path()),
+            None => url.path().to_owned(),
+        };
+
+        let body = serde_json::to_vec(activity).map_err(|e| BridgeError::Http(e.to_string()))?;
+        let date = Utc::now().format("%a, %d %b %Y %H:%M:%S GMT").to_string();
+        let digest = digest_header(&body);
+        let headers = ["(request-target)", "host", "date", "digest"].map(String::from);
+        let signing_string = build_signing_string(&headers, "post", &path, |name| match name {
+            "host" => Some(host.clone()),
+            "date" => Some(date.clone()),
+            "digest" => Some(digest.clone()),
+            _ => None,
+        })
+        .expect("all signed headers are provided");
+
+        let key_id = format!("{}#main-key", activity["actor"].as_str().unwrap_or_default());
+        let signature = format!(
+            r#"keyId="{key_id}",algorithm="rsa-sha256",headers="{}",signature="{}""#,
+            headers.join(" "),
+            self.key.sign(&signing_string)
+        );
+
+        let resp = self
+            .http
+            .post(url)
+            .header("Host", host)
+            .header("Date", date)
+            .header("Digest", digest)
+            .header("Signature", signature)
+            .header("Content-Type", ACTIVITY_JSON)
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| BridgeError::Http(e.to_string()))?;
+
+        if !resp.status().is_success() {
+            let status = resp.status().as_u16();
+            return Err(BridgeError::Remote(inbox.to_owned(), status, resp.text().await.unwrap_or_default()));
+        }
+        debug!("ActivityPub: delivered {} to {}", activity["id"], inbox);
+        Ok(())
+    }
+}
+
+impl ProtocolBridge for ActivityPubBridge {
+    fn protocol(&self) -> &'static str {
+        "activitypub"
+    }
+
+    fn accepts(&self, post: &OutboundPost) -> bool {
+        post.public_announcement
+    }
+
+    fn publish<'a>(&'a self, post: &'a OutboundPost) -> BridgeFuture<'a> {
+        Box::pin(async move {
+            let inboxes = activitypub::list_delivery_inboxes(&self.pool, post.channel_id).await?;
+            if inboxes.is_empty() {
+                return Ok(());
+            }
+            let activity = self.create_activity(post);
+            for inbox in &inboxes {
+                self.enqueue(inbox, &activity).await?;
+            }
+            debug!("ActivityPub: queued {} for {} inboxes", post.message_id, inboxes.len());
+            Ok(())
+        })
+    }
+}
+
+// ─── HTTP Signatures ─────────────────────────────────────────────────────────
+
+/// Parsed `Signature` header parameters.
+#[derive(Debug, PartialEq)]
+struct SignatureParams {
+    key_id: String,
+    headers: Vec<String>,
+    signature: String,
+}
+
+/// Inbound signatures must cover the request target, host and date, and a
+/// POST's body digest, so they can't be replayed against another request.
+fn check_signed_headers(signed: &[String], method: &str) -> Result<(), BridgeError> {
+    let covers = |name: &str| signed.iter().any(|h| h == name);
+    let mut required = vec!["(request-target)", "host", "date"];
+    if method.eq_ignore_ascii_case("post") {
+        required.push("digest");
+    }
+    match required.into_iter().find(|name| !covers(name)) {
+        Some(missing) => Err(BridgeError::Signing(format!("signature must cover {missing}"))),
+        None => Ok(()),
+    }
+}
+
+fn parse_signature_header(value: &str) -> Option<SignatureParams> {
+    let mut key_id = None;
+    let mut headers = None;
+    let mut signature = None;
+    for part in value.split(',') {
+        let (k, v) = part.trim().split_once('=')?;
+        let v = v.trim_matches('"');
+        match k {
+            "keyId" => key_id = Some(v.to_owned()),
+            "headers" => headers = Some(v.split_whitespace().map(|h| h.to_ascii_lowercase()).collect()),
+            "signature" => signature = Some(v.to_owned()),
+            _ => {}
+        }
+    }
+    Some(SignatureParams {
+        key_id: key_id?,
+        // The spec default when `headers` is omitted.
+        headers: headers.unwrap_or_else(|| vec!["date".to_owned()]),
+        signature: signature?,
+    })
+}
+
+/// Build the string to sign from the listed headers. Returns `None` if a
+/// listed header is unavailable.
+fn build_signing_string(
+    headers: &[String],
+    method: &str,
+    path: &str,
+    value_of: impl Fn(&str) -> Option<String>,
+) -> Option<String> {
+    headers
+        .iter()
+        .map(|name| match name.as_str() {
+            "(request-target)" => Some(format!("(request-target): {} {}", method.to_ascii_lowercase(), path)),
+            other => value_of(other).map(|v| format!("{other}: {v}")),
+        })
+        .collect::<Option<Vec<_>>>()
+        .map(|lines| lines.join("\n"))
+}
+
+/// `Digest` header value for a body.
+fn digest_header(body: &[u8]) -> String {
+    format!("SHA-256={}", B64.encode(Sha256::digest(body)))
+}
+
+fn verify_signature(public_key_pem: &str, signing_string: &str, signature_b64: &str) -> Result<(), BridgeError> {
+    let key = RsaPublicKey::from_public_key_pem(public_key_pem)
+        .map_err(|e| BridgeError::Signing(e.to_string()))?;
+    let bytes = B64
+        .decode(signature_b64)
+        .map_err(|e| BridgeError::Signing(e.to_string()))?;
+    let signature = Signature::try_from(bytes.as_slice()).map_err(|e| BridgeError::Signing(e.to_string()))?;
+    VerifyingKey::<Sha256>::new(key)
+        .verify(signing_string.as_bytes(), &signature)
+        .map_err(|_| BridgeError::Signing("signature verification failed".into()))
+}
+
+fn header_str<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers.get(name).and_then(|v| v.to_str().ok())
+}
+
+// ─── Helpers ─────────────────────────────────────────────────────────────────
+
+fn parse_remote_actor(doc: &Value) -> Option<RemoteActor> {
+    Some(RemoteActor {
+        id: doc["id"].as_str()?.to_owned(),
+        inbox: doc["inbox"].as_str()?.to_owned(),
+        shared_inbox: doc["endpoints"]["sharedInbox"].as_str().map(str::to_owned),
+        public_key_id: doc["publicKey"]["id"].as_str().map(str::to_owned),
+        public_key_pem: doc["publicKey"]["publicKeyPem"].as_str().map(str::to_owned),
+    })
+}
+
+/// Whether two URLs share scheme, host and port.
+fn same_origin(a: &str, b: &str) -> bool {
+    match (url::Url::parse(a), url::Url::parse(b)) {
+        (Ok(a), Ok(b)) => a.origin() == b.origin(),
+        _ => false,
+    }
+}
+
+/// The address to fetch `url` from. Hosts resolving to loopback, private or
+/// otherwise non-public addresses are refused.
+async fn public_addr(url: &url::Url) -> Result<(String, SocketAddr), BridgeError> {
+    let refuse = || BridgeError::Http(format!("refusing non-public host in '{url}'"));
+    let port = url.port_or_known_default().ok_or_else(refuse)?;
+    let (host, addrs): (String, Vec<SocketAddr>) = match url.host().ok_or_else(refuse)? {
+        url::Host::Ipv4(v4) => (v4.to_string(), vec![SocketAddr::new(v4.into(), port)]),
+        url::Host::Ipv6(v6) => (v6.to_string(), vec![SocketAddr::new(v6.into(), port)]),
+        url::Host::Domain(domain) => {
+            let addrs = tokio::net::lookup_host((domain, port))
+                .await
+                .map_err(|e| BridgeError::Http(e.to_string()))?
+                .collect();
+            (domain.to_owned(), addrs)
+        }
+    };
+    // One private answer is enough to refuse: the client might pick it
+    if addrs.is_empty() || addrs.iter().any(|a| !is_public(a.ip())) {
+        return Err(refuse());
+    }
+    Ok((host, addrs[0]))
+}
+
+/// An activity `object` may be an ID string or an embedded object.
+fn object_id(object: &Value) -> Option<&str> {
+    object.as_str().or_else(|| object["id"].as_str())
+}
+
+/// Render a plain / Markdown message body as minimal HTML: blank lines
+/// separate paragraphs, single newlines become `<br>`.
+fn render_html(text: &str) -> String {
+    text.split("\n\n")
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+        .map(|p| format!("<p>{}</p>", html_escape(p).replace('\n', "<br>")))
+        .collect()
+}
+
+fn html_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_signature_header() {
+        let header = r#"keyId="https://a.example/users/x#main-key",algorithm="rsa-sha256",headers="(request-target) host date digest",signature="c2ln""#;
+        let params = parse_signature_header(header).unwrap();
+        assert_eq!(params.key_id, "https://a.example/users/x#main-key");
+        assert_eq!(params.headers, vec!["(request-target)", "host", "date", "digest"]);
+        assert_eq!(params.signature, "c2ln");
+        assert!(parse_signature_header("garbage").is_none());
+    }
+
+    #[test]
+    fn test_signed_headers_required() {
+        let headers = |list: &str| list.split(' ').map(str::to_owned).collect::<Vec<_>>();
+        assert!(check_signed_headers(&headers("(request-target) host date digest"), "POST").is_ok());
+        assert!(check_signed_headers(&headers("(request-target) host date"), "GET").is_ok());
+        assert!(check_signed_headers(&headers("(request-target) host date"), "POST").is_err());
+        assert!(check_signed_headers(&headers("host date digest"), "POST").is_err());
+        assert!(check_signed_headers(&headers("(request-target) date digest"), "POST").is_err());
+    }
+
+    #[test]
+    fn test_same_origin() {
+        assert!(same_origin("https://a.example/users/x", "https://a.example/users/x#main-key"));
+        assert!(!same_origin("https://a.example/users/x", "https://b.example/users/x#main-key"));
+        assert!(!same_origin("https://a.example/users/x", "not a url"));
+    }
+
+    #[test]
+    fn test_signing_string() {
+        let headers = ["(request-target)", "host", "date"].map(String::from);
+        let s = build_signing_string(&headers, "POST", "/inbox", |name| match name {
+            "host" => Some("b.example".into()),
+            "date" => Some("Sun, 01 Mar 2026 00:00:00 GMT".into()),
+            _ => None,
+        })
+        .unwrap();
+        assert_eq!(
+            s,
+            "(request-target): post /inbox\nhost: b.example\ndate: Sun, 01 Mar 2026 00:00:00 GMT"
+        );
+        let missing = ["digest".to_string()];
+        assert!(build_signing_string(&missing, "POST", "/inbox", |_| None).is_none());
+    }
+
+    #[test]
+    fn test_sign_and_verify_roundtrip() {
+        let key = ActorKey::from_private(RsaPrivateKey::new(&mut rand_core::OsRng, 1024).unwrap()).unwrap();
+        let sig = key.sign("hello");
+        assert!(verify_signature(key.public_key_pem(), "hello", &sig).is_ok());
+        assert!(verify_signature(key.public_key_pem(), "tampered", &sig).is_err());
+    }
+
+    #[test]
+    fn test_render_html() {
+        assert_eq!(render_html("a <b>\nline\n\nnext"), "<p>a &lt;b&gt;<br>line</p><p>next</p>");
+    }
+
+    #[test]
+    fn test_digest_header() {
+        // sha256("") = e3b0c442…
+        assert_eq!(digest_header(b""), "SHA-256=47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuFU=");
+    }
+}
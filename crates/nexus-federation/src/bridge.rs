@@ -0,0 +1,106 @@
+//! Generic outbound protocol bridges.
+//!
+//! A [`ProtocolBridge`] relays Nexus posts to a foreign network (Matrix,
+//! ActivityPub, XMPP, …). The API layer builds one [`OutboundPost`] per
+//! message and hands it to the [`BridgeRegistry`], which fans it out to every
+//! bridge that wants it. Bridges must never block message creation — the
+//! registry runs them on a background task and only logs failures.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+/// Boxed future returned by [`ProtocolBridge::publish`] (keeps the trait
+/// object-safe without pulling in `async-trait`).
+pub type BridgeFuture<'a> = Pin<Box<dyn Future<Output = Result<(), BridgeError>> + Send + 'a>>;
+
+/// A message posted on Nexus, normalised for relaying.
+#[derive(Debug, Clone)]
+pub struct OutboundPost {
+    pub message_id: Uuid,
+    pub channel_id: Uuid,
+    pub server_id: Option<Uuid>,
+    /// Display name of the author, for attribution on the remote side.
+    pub author_name: String,
+    /// Markdown message body.
+    pub content: String,
+    pub created_at: DateTime<Utc>,
+    /// The post was made in an announcement channel of a public server, so
+    /// it may be published to open networks.
+    pub public_announcement: bool,
+}
+
+/// An outbound bridge to another protocol.
+pub trait ProtocolBridge: Send + Sync {
+    /// Short protocol name used in logs (e.g. `"matrix"`, `"activitypub"`).
+    fn protocol(&self) -> &'static str;
+
+    /// Whether this bridge relays `post` at all. Checked before `publish`.
+    fn accepts(&self, post: &OutboundPost) -> bool;
+
+    /// Relay `post` to the remote network.
+    fn publish<'a>(&'a self, post: &'a OutboundPost) -> BridgeFuture<'a>;
+}
+
+/// The set of bridges enabled on this server.
+#[derive(Default, Clone)]
+pub struct BridgeRegistry {
+    bridges: Vec<Arc<dyn ProtocolBridge>>,
+}
+
+impl BridgeRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a bridge.
+    pub fn register(&mut self, bridge: Arc<dyn ProtocolBridge>) {
+        tracing::info!(protocol = bridge.protocol(), "Protocol bridge registered");
+        self.bridges.push(bridge);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bridges.is_empty()
+    }
+
+    /// Relay `post` through every interested bridge on a background task.
+    pub fn dispatch(&self, post: OutboundPost) {
+        let bridges: Vec<_> = self.bridges.iter().filter(|b| b.accepts(&post)).cloned().collect();
+        if bridges.is_empty() {
+            return;
+        }
+        tokio::spawn(async move {
+            for bridge in bridges {
+                if let Err(e) = bridge.publish(&post).await {
+                    tracing::warn!(
+                        protocol = bridge.protocol(),
+                        message_id = %post.message_id,
+                        error = %e,
+                        "Bridge relay failed"
+                    );
+                }
+            }
+        });
+    }
+}
+
+// ─── Bridge error ─────────────────────────────────────────────────────────────
+
+#[derive(Debug, thiserror::Error)]
+pub enum BridgeError {
+    #[error("HTTP error: {0}")]
+    Http(String),
+    #[error("Matrix homeserver returned {0}: {1}")]
+    HomeserverError(u16, String),
+    #[error("Room not found for channel '{0}'")]
+    RoomNotFound(String),
+    #[error("Remote '{0}' returned {1}: {2}")]
+    Remote(String, u16, String),
+    #[error("Signing error: {0}")]
+    Signing(String),
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+}
@@ -2,7 +2,8 @@
 //!
 //! The [`FederationClient`] handles all outbound communication to remote
 //! Nexus servers. Every request is signed with this server's key pair before
-//! being sent.
+//! being sent, and carries the caller's trace context (`traceparent`) so the
+//! remote server's handling can join the trace.
 //!
 //! # Usage
 //!
@@ -24,7 +25,7 @@
 use std::sync::Arc;
 use std::time::Duration;
 
-use reqwest::Client;
+use reqwest::{Client, RequestBuilder};
 use serde::de::DeserializeOwned;
 use serde_json::Value;
 use tracing::debug;
@@ -36,7 +37,7 @@ use crate::{
     signatures::sign_request,
     types::{
         DirectoryListingResponse, FederationEvent, FederationTransaction, MakeJoinResponse,
-        SendJoinResponse, ServerInfo,
+        RemoteProfile, SendJoinResponse, ServerInfo,
     },
 };
 
@@ -191,19 +192,33 @@ impl FederationClient {
         self.signed_get(destination, &base_url, &uri).await
     }
 
+    // ── Profiles ─────────────────────────────────────────────────────────────
+
+    /// Fetch a remote user's public profile from their home server.
+    ///
+    /// `GET /_nexus/federation/v1/user/{userId}`
+    pub async fn get_user_profile(
+        &self,
+        destination: &str,
+        user_id: &str,
+    ) -> Result<RemoteProfile, FederationError> {
+        let uri = format!("/_nexus/federation/v1/user/{}", urlencoded(user_id));
+        let base_url = self.discovery.resolve(destination).await?;
+        self.signed_get(destination, &base_url, &uri).await
+    }
+
     // ── Server keys ──────────────────────────────────────────────────────────
 
     /// Fetch the key document from a remote server.
     ///
     /// `GET /_nexus/key/v2/server`
+    #[tracing::instrument(skip(self))]
     pub async fn fetch_server_keys(&self, destination: &str) -> Result<ServerInfo, FederationError> {
         let base_url = self.discovery.resolve(destination).await?;
         // Key fetch is unauthenticated (like Matrix).
         let url = format!("{}{}", base_url, "/_nexus/key/v2/server");
         debug!("Fetching server keys from {}", url);
-        let resp = self
-            .http
-            .get(&url)
+        let resp = traced(self.http.get(&url))
             .send()
             .await?
             .error_for_status()
@@ -213,6 +228,7 @@ impl FederationClient {
 
     // ── Signed request helpers ───────────────────────────────────────────────
 
+    #[tracing::instrument(skip(self, base_url))]
     async fn signed_get<T: DeserializeOwned>(
         &self,
         destination: &str,
@@ -222,9 +238,7 @@ impl FederationClient {
         let auth = sign_request(&self.key_pair, &self.server_name, destination, "GET", uri, None);
         let url = format!("{}{}", base_url, uri);
         debug!("Federation GET {}", url);
-        let resp = self
-            .http
-            .get(&url)
+        let resp = traced(self.http.get(&url))
             .header("Authorization", auth.to_header())
             .send()
             .await?
@@ -233,6 +247,7 @@ impl FederationClient {
         Ok(resp.json().await?)
     }
 
+    #[tracing::instrument(skip(self, base_url, body))]
     async fn signed_put<T: DeserializeOwned>(
         &self,
         destination: &str,
@@ -244,9 +259,7 @@ impl FederationClient {
             sign_request(&self.key_pair, &self.server_name, destination, "PUT", uri, Some(body));
         let url = format!("{}{}", base_url, uri);
         debug!("Federation PUT {}", url);
-        let resp = self
-            .http
-            .put(&url)
+        let resp = traced(self.http.put(&url))
             .header("Authorization", auth.to_header())
             .json(body)
             .send()
@@ -259,6 +272,14 @@ impl FederationClient {
 
 // ─── Helpers ─────────────────────────────────────────────────────────────────
 
+/// Attach the current span's `traceparent`, if it is being traced.
+fn traced(request: RequestBuilder) -> RequestBuilder {
+    match nexus_common::telemetry::current_traceparent() {
+        Some(traceparent) => request.header(nexus_common::telemetry::TRACEPARENT, traceparent),
+        None => request,
+    }
+}
+
 fn urlencoded(s: &str) -> String {
     url::form_urlencoded::byte_serialize(s.as_bytes()).collect()
 }
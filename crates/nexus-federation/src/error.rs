@@ -51,6 +51,9 @@ pub enum FederationError {
 
     // ── General ─────────────────────────────────────────────────────────────
 
+    #[error("Not representable as canonical JSON: {0}")]
+    NotCanonical(String),
+
     #[error("Serialisation error: {0}")]
     Serialisation(#[from] serde_json::Error),
 
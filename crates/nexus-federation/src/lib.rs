@@ -25,13 +25,21 @@
 //! - **Signed requests** (`signatures.rs`): all S2S HTTP requests are signed with
 //!   the originating server's private key using the Nexus Request Authorization scheme
 //!   (modelled on Matrix's `X-Matrix` auth).
+//! - **Test vectors** (`test_vectors.rs`): golden canonical JSON and signature
+//!   vectors, including the Matrix specification's, for interop checks.
 //! - **Federation client** (`client.rs`): async HTTP client for sending events to
 //!   remote servers and resolving remote room state.
 //! - **Discovery** (`discovery.rs`): resolves `server.tld` → actual S2S endpoint via
 //!   `/.well-known/nexus/server`, SRV DNS, or direct HTTPS fallback.
+//! - **Protocol bridges** (`bridge.rs`): the [`ProtocolBridge`] trait and
+//!   [`BridgeRegistry`] that fan Nexus posts out to foreign networks.
 //! - **Matrix bridge** (`matrix_bridge.rs`): Matrix Application Service (AS) bridge
 //!   for relaying messages to/from Matrix homeservers.
+//! - **ActivityPub bridge** (`activitypub.rs`): publishes public announcement
+//!   channels as fediverse actors that Mastodon & co. can follow.
 
+pub mod activitypub;
+pub mod bridge;
 pub mod client;
 pub mod discovery;
 pub mod error;
@@ -39,12 +47,15 @@ pub mod key_manager;
 pub mod keys;
 pub mod matrix_bridge;
 pub mod signatures;
+pub mod test_vectors;
 pub mod types;
 
+pub use activitypub::{ActivityPubBridge, ActorKey};
+pub use bridge::{BridgeError, BridgeRegistry, OutboundPost, ProtocolBridge};
 pub use client::FederationClient;
 pub use error::FederationError;
 pub use key_manager::KeyManager;
 pub use keys::ServerKeyPair;
 pub use matrix_bridge::{BridgeConfig, BridgedEvent, MatrixBridge, MatrixTransaction};
-pub use signatures::sign_event;
-pub use types::{FederationEvent, FederationTransaction, ServerInfo};
+pub use signatures::{sign_event, verify_event};
+pub use types::{FederationEvent, FederationTransaction, RemoteProfile, ServerInfo};
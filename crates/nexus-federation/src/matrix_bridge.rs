@@ -13,7 +13,9 @@
 //!
 //! A `registration.yaml` file (not this crate) must be provided to the Matrix
 //! homeserver that registers this AS with the correct `hs_token`, `as_token`,
-//! and `url` fields.
+//! and `url` fields. `tests/synapse/` has a working registration and
+//! homeserver config, used by the Synapse interop suite
+//! (`tests/synapse_interop.rs`, behind the `synapse-interop` feature).
 //!
 //! # Status: stub implementation
 //!
@@ -24,6 +26,9 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use tracing::{debug, info};
 
+pub use crate::bridge::BridgeError;
+use crate::bridge::{BridgeFuture, OutboundPost, ProtocolBridge};
+
 // ─── Types ───────────────────────────────────────────────────────────────────
 
 /// A Matrix homeserver transaction pushed to the AS.
@@ -103,6 +108,15 @@ impl MatrixBridge {
 
     // ── Inbound (Matrix → Nexus) ────────────────────────────────────────────
 
+    /// Whether an inbound homeserver request carries our `hs_token`.
+    ///
+    /// Homeservers send it as `Authorization: Bearer …` since Matrix v1.4;
+    /// older ones (and Synapse with `use_appservice_legacy_authorization`)
+    /// use the `access_token` query parameter.
+    pub fn verify_hs_token(&self, authorization: Option<&str>, access_token: Option<&str>) -> bool {
+        hs_token_matches(&self.config.hs_token, authorization, access_token)
+    }
+
     /// Handle an inbound transaction from the Matrix homeserver.
     ///
     /// Called from the `PUT /_matrix/app/v1/transactions/{txnId}` route.
@@ -110,6 +124,10 @@ impl MatrixBridge {
     pub async fn handle_transaction(&self, txn: MatrixTransaction) -> Vec<BridgedEvent> {
         let mut out = Vec::new();
         for ev in txn.events {
+            // Our own relays come back to us like any other room event.
+            if ev.sender == self.config.bot_mxid {
+                continue;
+            }
             match ev.event_type.as_str() {
                 "m.room.message" => {
                     if let Some(bridged) = self.convert_matrix_message(&ev) {
@@ -212,6 +230,26 @@ impl MatrixBridge {
     }
 }
 
+impl ProtocolBridge for MatrixBridge {
+    fn protocol(&self) -> &'static str {
+        "matrix"
+    }
+
+    fn accepts(&self, post: &OutboundPost) -> bool {
+        self.matrix_room_for_channel(&post.channel_id.to_string()).is_some()
+    }
+
+    fn publish<'a>(&'a self, post: &'a OutboundPost) -> BridgeFuture<'a> {
+        Box::pin(async move {
+            let channel_id = post.channel_id.to_string();
+            let room_id = self
+                .matrix_room_for_channel(&channel_id)
+                .ok_or(BridgeError::RoomNotFound(channel_id.clone()))?;
+            self.send_to_matrix(room_id, &post.author_name, &post.content).await
+        })
+    }
+}
+
 // ─── Bridged event ────────────────────────────────────────────────────────────
 
 /// A normalised event produced by the bridge for Nexus to consume.
@@ -233,20 +271,18 @@ pub enum BridgedEvent {
     },
 }
 
-// ─── Bridge error ─────────────────────────────────────────────────────────────
+// ─── Helpers ─────────────────────────────────────────────────────────────────
 
-#[derive(Debug, thiserror::Error)]
-pub enum BridgeError {
-    #[error("HTTP error: {0}")]
-    Http(String),
-    #[error("Matrix homeserver returned {0}: {1}")]
-    HomeserverError(u16, String),
-    #[error("Room not found for channel '{0}'")]
-    RoomNotFound(String),
+/// [`MatrixBridge::verify_hs_token`] without a bridge, for handlers that
+/// check the token before building one.
+pub fn hs_token_matches(expected: &str, authorization: Option<&str>, access_token: Option<&str>) -> bool {
+    let provided = authorization
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .or(access_token)
+        .unwrap_or("");
+    !expected.is_empty() && provided == expected
 }
 
-// ─── Helpers ─────────────────────────────────────────────────────────────────
-
 fn urlencoded(s: &str) -> String {
     url::form_urlencoded::byte_serialize(s.as_bytes()).collect()
 }
@@ -257,3 +293,17 @@ fn html_escape(s: &str) -> String {
         .replace('>', "&gt;")
         .replace('"', "&quot;")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hs_token_from_header_or_query() {
+        assert!(hs_token_matches("hs", Some("Bearer hs"), None));
+        assert!(hs_token_matches("hs", None, Some("hs")));
+        assert!(!hs_token_matches("hs", Some("Bearer nope"), Some("hs")));
+        assert!(!hs_token_matches("hs", Some("hs"), None));
+        assert!(!hs_token_matches("", None, None));
+    }
+}
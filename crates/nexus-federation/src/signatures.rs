@@ -23,19 +23,26 @@
 //! The object is serialised as canonical JSON (sorted keys, no extra whitespace)
 //! before signing.
 
-use std::collections::BTreeMap;
-
+use std::collections::{BTreeMap, HashMap};
 
 use serde_json::Value;
 
 use crate::{
     error::FederationError,
     keys::{verify_signature, ServerKeyPair},
+    types::VerifyKey,
 };
 
 // Maximum allowed clock skew between servers (30 seconds).
 const MAX_SKEW_SECS: i64 = 30;
 
+/// Largest magnitude an integer may have in canonical JSON (2^53 - 1), as
+/// in Matrix.
+pub const MAX_SAFE_INTEGER: i64 = (1 << 53) - 1;
+
+/// Event fields that are not covered by the event signature.
+const UNSIGNED_EVENT_FIELDS: &[&str] = &["signatures", "hashes"];
+
 // ─── Signing ─────────────────────────────────────────────────────────────────
 
 /// A signed federation request authorization, ready to be serialised into
@@ -110,13 +117,7 @@ pub fn sign_event(
     server_name: &str,
     event_json: &mut Value,
 ) -> Result<(), FederationError> {
-    // Remove existing signatures and hashes before signing (they aren't part of the payload).
-    let mut signing_obj = event_json.clone();
-    signing_obj.as_object_mut().map(|obj| {
-        obj.remove("signatures");
-        obj.remove("hashes");
-    });
-    let canonical = canonical_json(&signing_obj)?;
+    let canonical = canonical_json(&signed_content(event_json))?;
     let sig = kp.sign_json(&canonical);
 
     // Attach signature.
@@ -135,6 +136,49 @@ pub fn sign_event(
     Ok(())
 }
 
+/// Verify `server_name`'s signatures on an event signed with [`sign_event`].
+///
+/// `verify_keys` maps key IDs to public keys, as in the server's
+/// [`ServerKeyDocument`](crate::keys::ServerKeyDocument). Signatures by
+/// keys not in it are skipped, but at least one must be checked and every
+/// checked one must be valid. Returns the key IDs that verified.
+pub fn verify_event(
+    event_json: &Value,
+    server_name: &str,
+    verify_keys: &HashMap<String, VerifyKey>,
+) -> Result<Vec<String>, FederationError> {
+    let sigs = event_json
+        .get("signatures")
+        .and_then(|s| s.get(server_name))
+        .and_then(Value::as_object)
+        .ok_or(FederationError::InvalidSignature)?;
+    let canonical = canonical_json(&signed_content(event_json))?;
+
+    let mut verified = Vec::new();
+    for (key_id, sig) in sigs {
+        let Some(key) = verify_keys.get(key_id) else { continue };
+        let sig = sig.as_str().ok_or(FederationError::InvalidSignature)?;
+        verify_signature(&key.key, sig, canonical.as_bytes())?;
+        verified.push(key_id.clone());
+    }
+    if verified.is_empty() {
+        let key_ids: Vec<_> = sigs.keys().map(String::as_str).collect();
+        return Err(FederationError::KeyNotFound(key_ids.join(", ")));
+    }
+    Ok(verified)
+}
+
+/// The part of an event its signature covers.
+pub fn signed_content(event_json: &Value) -> Value {
+    let mut content = event_json.clone();
+    if let Some(obj) = content.as_object_mut() {
+        for field in UNSIGNED_EVENT_FIELDS {
+            obj.remove(*field);
+        }
+    }
+    content
+}
+
 // ─── Internals ───────────────────────────────────────────────────────────────
 
 /// Build the canonical JSON object that is signed for an HTTP request.
@@ -194,23 +238,38 @@ struct ParsedAuth {
 /// Produce canonical JSON (sorted keys, no extra whitespace).
 ///
 /// Nexus canonical JSON is a subset of RFC 7159 following the Matrix canonical
-/// JSON spec: keys sorted lexicographically, no trailing spaces/newlines.
+/// JSON spec: keys sorted lexicographically, no trailing spaces/newlines, and
+/// numbers written as integers. Whole-valued floats such as `1e10` become
+/// integers; fractions and integers beyond ±[`MAX_SAFE_INTEGER`] are rejected.
+/// Known-good vectors are in [`test_vectors`](crate::test_vectors).
 pub fn canonical_json(value: &Value) -> Result<String, FederationError> {
-    Ok(sort_keys(value).to_string())
+    Ok(canonicalize(value)?.to_string())
 }
 
-fn sort_keys(value: &Value) -> Value {
-    match value {
+fn canonicalize(value: &Value) -> Result<Value, FederationError> {
+    Ok(match value {
         Value::Object(map) => {
-            let sorted: serde_json::Map<String, Value> = map
+            let sorted = map
                 .iter()
-                .map(|(k, v)| (k.clone(), sort_keys(v)))
-                .collect::<BTreeMap<_, _>>()
-                .into_iter()
-                .collect();
-            Value::Object(sorted)
+                .map(|(k, v)| Ok((k.clone(), canonicalize(v)?)))
+                .collect::<Result<BTreeMap<_, _>, FederationError>>()?;
+            Value::Object(sorted.into_iter().collect())
         }
-        Value::Array(arr) => Value::Array(arr.iter().map(sort_keys).collect()),
+        Value::Array(arr) => Value::Array(arr.iter().map(canonicalize).collect::<Result<_, _>>()?),
+        Value::Number(n) => Value::from(canonical_integer(n)?),
         other => other.clone(),
-    }
+    })
+}
+
+fn canonical_integer(n: &serde_json::Number) -> Result<i64, FederationError> {
+    let range = -MAX_SAFE_INTEGER..=MAX_SAFE_INTEGER;
+    let int = match n.as_i64() {
+        Some(i) => Some(i),
+        None => n
+            .as_f64()
+            .filter(|f| f.fract() == 0.0 && f.abs() <= MAX_SAFE_INTEGER as f64)
+            .map(|f| f as i64),
+    };
+    int.filter(|i| range.contains(i))
+        .ok_or_else(|| FederationError::NotCanonical(format!("{n} is not an integer within ±(2^53 - 1)")))
 }
@@ -0,0 +1,244 @@
+//! Known-good vectors for canonical JSON and event signatures.
+//!
+//! Federation only works if every implementation produces the same bytes
+//! for the same JSON, so these are published for other implementations to
+//! check themselves against. The Matrix vectors come from the Matrix
+//! specification's canonical JSON and signing appendices; Matrix encodes
+//! signatures as standard unpadded base64, so they appear here re-encoded
+//! as the base64url Nexus uses.
+//!
+//! Event signatures cover the canonical JSON of the event without its
+//! `signatures` and `hashes` fields.
+
+/// `(input, canonical)` pairs from the Matrix specification.
+pub const MATRIX_CANONICAL_JSON: &[(&str, &str)] = &[
+    ("{}", "{}"),
+    (r#"{"one": 1, "two": "Two"}"#, r#"{"one":1,"two":"Two"}"#),
+    (r#"{"b": "2", "a": "1"}"#, r#"{"a":"1","b":"2"}"#),
+    (r#"{"b":"2","a":"1"}"#, r#"{"a":"1","b":"2"}"#),
+    (
+        r#"{
+            "auth": {
+                "success": true,
+                "mxid": "@john.doe:example.com",
+                "profile": {
+                    "display_name": "John Doe",
+                    "three_pids": [
+                        {"medium": "email", "address": "john.doe@example.org"},
+                        {"medium": "msisdn", "address": "123456789"}
+                    ]
+                }
+            }
+        }"#,
+        r#"{"auth":{"mxid":"@john.doe:example.com","profile":{"display_name":"John Doe","three_pids":[{"address":"john.doe@example.org","medium":"email"},{"address":"123456789","medium":"msisdn"}]},"success":true}}"#,
+    ),
+    (r#"{"a": "日本語"}"#, r#"{"a":"日本語"}"#),
+    (r#"{"本": 2, "日": 1}"#, r#"{"日":1,"本":2}"#),
+    (r#"{"a": "\u65E5"}"#, r#"{"a":"日"}"#),
+    (r#"{"a": null}"#, r#"{"a":null}"#),
+    (r#"{"a": -0, "b": 1e10}"#, r#"{"a":0,"b":10000000000}"#),
+];
+
+/// `(input, canonical)` pairs for edge cases Nexus relies on.
+pub const NEXUS_CANONICAL_JSON: &[(&str, &str)] = &[
+    // Arrays keep their order; only object keys are sorted.
+    (r#"{"z": [3, 1, 2], "a": [{"y": 1, "x": 2}]}"#, r#"{"a":[{"x":2,"y":1}],"z":[3,1,2]}"#),
+    // Keys sort by code point, so uppercase before lowercase.
+    (r#"{"b": 1, "B": 2, "a": 3, "A": 4}"#, r#"{"A":4,"B":2,"a":3,"b":1}"#),
+    // Control characters are escaped in lowercase hex; `/` and `<` are not.
+    (r#"{"a": "\u0001\n\t</b>"}"#, r#"{"a":"\u0001\n\t</b>"}"#),
+    (r#"{"a": "\"\\"}"#, r#"{"a":"\"\\"}"#),
+    (r#"{"max": 9007199254740991, "min": -9007199254740991}"#, r#"{"max":9007199254740991,"min":-9007199254740991}"#),
+];
+
+/// Inputs that have no canonical form.
+pub const NON_CANONICAL_JSON: &[&str] = &[
+    r#"{"a": 1.5}"#,
+    r#"{"a": 9007199254740992}"#,
+    r#"{"a": -9007199254740992}"#,
+    r#"{"a": [1e300]}"#,
+];
+
+/// Signing key seed from the Matrix specification (standard base64).
+pub const MATRIX_SIGNING_SEED: &str = "YJDBA9Xnr2sVqXD9Vj7XVUnmFZcZrlw8Md7kMW+3XA1";
+
+/// Key ID Nexus derives for [`MATRIX_SIGNING_SEED`].
+pub const MATRIX_SIGNING_KEY_ID: &str = "ed25519:5c65f42514b6";
+
+/// Public key for [`MATRIX_SIGNING_SEED`], base64url.
+pub const MATRIX_SIGNING_PUBLIC_KEY: &str = "XGX0JRS2Af3be3knz2fBiRbApjm2Dh61gXDJA8kcJNI";
+
+/// `(event, signature)` pairs signed with [`MATRIX_SIGNING_SEED`]. The first
+/// two are the Matrix specification's signing examples.
+pub const SIGNED_EVENTS: &[(&str, &str)] = &[
+    (
+        "{}",
+        "K8280_U9SSy9IVtjBuVeLr-HpOB4BQFWbg-UZaADMtTdGYI7Geitb76LTrr5QV_7Xg4ahLwYGYZzuHGZKM5ZAQ",
+    ),
+    (
+        r#"{"one": 1, "two": "Two"}"#,
+        "KqmLSbO39_Bzb0QIYE82zqLwsA-PDzYIpIRA2sRQ4sL53-sN6_fpNSoqE7BP7vBZhG6kYdD13EIMJpvhJI-6Bw",
+    ),
+    (
+        r#"{
+            "type": "nexus.message",
+            "event_id": "$1:a.example",
+            "room_id": "!r:a.example",
+            "sender": "@alice:a.example",
+            "origin": "a.example",
+            "origin_server_ts": 1700000000000,
+            "content": {"mentions": [2, 1], "body": "héllo <b>"},
+            "hashes": {"sha256": "not signed"}
+        }"#,
+        "pOzyb4SbhHwFsSxww_Exo92CGOTh8UPirbX7KHMbiiOC0zUch_FlgWMI9JkjB_VHE8kQI4R4fjlI4Dg_Vru1CQ",
+    ),
+];
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use base64::Engine as _;
+    use proptest::prelude::*;
+    use serde_json::Value;
+
+    use super::*;
+    use crate::keys::ServerKeyPair;
+    use crate::signatures::{canonical_json, sign_event, verify_event, MAX_SAFE_INTEGER};
+    use crate::types::VerifyKey;
+
+    const SERVER: &str = "a.example";
+
+    fn matrix_key() -> ServerKeyPair {
+        let seed = base64::engine::general_purpose::STANDARD_NO_PAD
+            .decode(MATRIX_SIGNING_SEED)
+            .unwrap();
+        ServerKeyPair::from_seed(&seed).unwrap()
+    }
+
+    fn verify_keys(kp: &ServerKeyPair) -> HashMap<String, VerifyKey> {
+        HashMap::from([(kp.key_id.clone(), VerifyKey { key: kp.public_key_base64() })])
+    }
+
+    #[test]
+    fn canonical_json_matches_golden_vectors() {
+        for (input, expected) in MATRIX_CANONICAL_JSON.iter().chain(NEXUS_CANONICAL_JSON) {
+            let value: Value = serde_json::from_str(input).unwrap();
+            assert_eq!(canonical_json(&value).unwrap(), *expected, "input: {input}");
+        }
+    }
+
+    #[test]
+    fn non_canonical_numbers_are_rejected() {
+        for input in NON_CANONICAL_JSON {
+            let value: Value = serde_json::from_str(input).unwrap();
+            assert!(canonical_json(&value).is_err(), "input: {input}");
+        }
+    }
+
+    #[test]
+    fn signatures_match_golden_vectors() {
+        let kp = matrix_key();
+        assert_eq!(kp.key_id, MATRIX_SIGNING_KEY_ID);
+        assert_eq!(kp.public_key_base64(), MATRIX_SIGNING_PUBLIC_KEY);
+
+        for (event, expected) in SIGNED_EVENTS {
+            let mut event: Value = serde_json::from_str(event).unwrap();
+            sign_event(&kp, SERVER, &mut event).unwrap();
+            assert_eq!(event["signatures"][SERVER][&kp.key_id], *expected);
+            assert_eq!(verify_event(&event, SERVER, &verify_keys(&kp)).unwrap(), [kp.key_id.clone()]);
+        }
+    }
+
+    #[test]
+    fn verify_event_needs_a_known_key() {
+        let kp = matrix_key();
+        let mut event: Value = serde_json::from_str(SIGNED_EVENTS[2].0).unwrap();
+        sign_event(&kp, SERVER, &mut event).unwrap();
+        assert!(verify_event(&event, SERVER, &HashMap::new()).is_err());
+        assert!(verify_event(&event, "b.example", &verify_keys(&kp)).is_err());
+
+        // `hashes` isn't covered by the signature; everything else is.
+        event["hashes"]["sha256"] = "changed".into();
+        assert!(verify_event(&event, SERVER, &verify_keys(&kp)).is_ok());
+        event["content"]["body"] = "changed".into();
+        assert!(verify_event(&event, SERVER, &verify_keys(&kp)).is_err());
+    }
+
+    /// Arbitrary JSON within canonical JSON's domain.
+    fn json_value() -> impl Strategy<Value = Value> {
+        let leaf = prop_oneof![
+            Just(Value::Null),
+            any::<bool>().prop_map(Value::Bool),
+            (-MAX_SAFE_INTEGER..=MAX_SAFE_INTEGER).prop_map(Value::from),
+            ".*".prop_map(Value::String),
+        ];
+        leaf.prop_recursive(4, 64, 8, |inner| {
+            prop_oneof![
+                prop::collection::vec(inner.clone(), 0..8).prop_map(Value::Array),
+                prop::collection::vec((".*", inner), 0..8)
+                    .prop_map(|entries| Value::Object(entries.into_iter().collect())),
+            ]
+        })
+    }
+
+    /// `value` with every object's keys inserted in reverse order.
+    fn reverse_keys(value: &Value) -> Value {
+        match value {
+            Value::Object(map) => {
+                let mut reversed = serde_json::Map::new();
+                for (k, v) in map.iter().rev() {
+                    reversed.insert(k.clone(), reverse_keys(v));
+                }
+                Value::Object(reversed)
+            }
+            Value::Array(arr) => Value::Array(arr.iter().map(reverse_keys).collect()),
+            other => other.clone(),
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn canonical_json_is_a_fixed_point(value in json_value()) {
+            let canonical = canonical_json(&value).unwrap();
+            let reparsed: Value = serde_json::from_str(&canonical).unwrap();
+            prop_assert_eq!(&reparsed, &value);
+            prop_assert_eq!(canonical_json(&reparsed).unwrap(), canonical);
+        }
+
+        #[test]
+        fn canonical_json_ignores_key_order(value in json_value()) {
+            prop_assert_eq!(
+                canonical_json(&reverse_keys(&value)).unwrap(),
+                canonical_json(&value).unwrap()
+            );
+        }
+
+        #[test]
+        fn canonical_json_has_no_insignificant_whitespace(value in json_value()) {
+            let canonical = canonical_json(&value).unwrap();
+            let compact = serde_json::to_string(&serde_json::from_str::<Value>(&canonical).unwrap()).unwrap();
+            prop_assert_eq!(canonical, compact);
+        }
+
+        #[test]
+        fn signed_events_verify_until_tampered(
+            content in json_value(),
+            ts in 0..MAX_SAFE_INTEGER,
+            tamper in ".+",
+        ) {
+            let kp = matrix_key();
+            let mut event = serde_json::json!({
+                "type": "nexus.message",
+                "origin": SERVER,
+                "origin_server_ts": ts,
+                "content": content,
+            });
+            sign_event(&kp, SERVER, &mut event).unwrap();
+            prop_assert!(verify_event(&reverse_keys(&event), SERVER, &verify_keys(&kp)).is_ok());
+
+            event["tampered"] = Value::String(tamper);
+            prop_assert!(verify_event(&event, SERVER, &verify_keys(&kp)).is_err());
+        }
+    }
+}
@@ -210,6 +210,19 @@ pub struct DirectoryListingResponse {
     pub next_batch: Option<String>,
 }
 
+// ─── User profiles ───────────────────────────────────────────────────────────
+
+/// A user's public profile, as served by `GET /_nexus/federation/v1/user/{userId}`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RemoteProfile {
+    /// Fully-qualified user ID (`@user:server_name`).
+    pub user_id: String,
+    pub displayname: Option<String>,
+    pub avatar_url: Option<String>,
+    #[serde(default)]
+    pub bio: Option<String>,
+}
+
 // ─── Well-known response ──────────────────────────────────────────────────────
 
 /// Response shape for `/.well-known/nexus/server`.
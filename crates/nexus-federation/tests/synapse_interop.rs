@@ -0,0 +1,328 @@
+//! Federation interop suite against a real Synapse homeserver.
+//!
+//! Checks that the "Matrix-compatible" parts of this crate work with the
+//! reference homeserver: Synapse's signed key document verifies with our
+//! canonical JSON and Ed25519 code, and the application service bridge
+//! relays messages both ways.
+//!
+//! Needs Docker. `scripts/synapse-interop.sh` starts Synapse from
+//! `tests/synapse/`, runs this suite and tears it down. Against an already
+//! running homeserver:
+//!
+//! ```text
+//! docker compose -f crates/nexus-federation/tests/synapse/docker-compose.yml up -d --wait
+//! cargo test -p nexus-federation --features synapse-interop --test synapse_interop
+//! ```
+//!
+//! `NEXUS_SYNAPSE_URL` overrides the homeserver URL (default
+//! `http://localhost:8008`). The bridge listens on port 9009, where
+//! `tests/synapse/nexus-registration.yaml` points Synapse.
+
+#![cfg(feature = "synapse-interop")]
+
+use std::collections::HashMap;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use axum::{
+    extract::{Query, State},
+    http::{header, HeaderMap, StatusCode},
+    routing::put,
+    Json, Router,
+};
+use base64::Engine as _;
+use nexus_federation::types::VerifyKey;
+use nexus_federation::{verify_event, BridgeConfig, BridgedEvent, MatrixBridge, MatrixTransaction};
+use reqwest::Method;
+use serde_json::{json, Value};
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+const SERVER_NAME: &str = "synapse.test";
+const AS_TOKEN: &str = "nexus-interop-as-token";
+const HS_TOKEN: &str = "nexus-interop-hs-token";
+const BOT_MXID: &str = "@nexus_bot:synapse.test";
+const AS_LISTEN: &str = "0.0.0.0:9009";
+
+/// How long to wait for Synapse to push an event to the bridge.
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(30);
+
+fn homeserver_url() -> String {
+    std::env::var("NEXUS_SYNAPSE_URL").unwrap_or_else(|_| "http://localhost:8008".to_owned())
+}
+
+fn bridge() -> MatrixBridge {
+    MatrixBridge::new(BridgeConfig {
+        homeserver_url: homeserver_url(),
+        as_token: AS_TOKEN.to_owned(),
+        hs_token: HS_TOKEN.to_owned(),
+        bot_mxid: BOT_MXID.to_owned(),
+    })
+}
+
+/// Matrix uses standard unpadded base64 for keys and signatures; Nexus
+/// uses base64url.
+fn to_base64url(matrix: &str) -> String {
+    let bytes = base64::engine::general_purpose::STANDARD_NO_PAD
+        .decode(matrix)
+        .expect("Matrix base64");
+    base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(bytes)
+}
+
+// ─── Client-server API ───────────────────────────────────────────────────────
+
+/// Just enough of the Matrix client-server API to drive the homeserver.
+struct Matrix {
+    http: reqwest::Client,
+    base: String,
+}
+
+impl Matrix {
+    fn new() -> Self {
+        Self { http: reqwest::Client::new(), base: homeserver_url() }
+    }
+
+    async fn call(&self, method: Method, path: &str, token: Option<&str>, body: Option<Value>) -> Value {
+        let mut request = self.http.request(method.clone(), format!("{}{path}", self.base));
+        if let Some(token) = token {
+            request = request.bearer_auth(token);
+        }
+        if let Some(body) = body {
+            request = request.json(&body);
+        }
+        let response = request.send().await.unwrap_or_else(|e| panic!("{method} {path}: {e}"));
+        let status = response.status();
+        let body: Value = response.json().await.unwrap_or(Value::Null);
+        assert!(status.is_success(), "{method} {path}: {status} {body}");
+        body
+    }
+
+    /// Register a fresh local user, returning `(user_id, access_token)`.
+    async fn register(&self, prefix: &str) -> (String, String) {
+        let username = format!("{prefix}_{}", Uuid::new_v4().simple());
+        let body = self
+            .call(
+                Method::POST,
+                "/_matrix/client/v3/register",
+                None,
+                Some(json!({
+                    "username": username,
+                    "password": Uuid::new_v4().to_string(),
+                    "auth": { "type": "m.login.dummy" },
+                })),
+            )
+            .await;
+        (
+            body["user_id"].as_str().unwrap().to_owned(),
+            body["access_token"].as_str().unwrap().to_owned(),
+        )
+    }
+
+    async fn create_room(&self, token: &str) -> String {
+        let body = self
+            .call(
+                Method::POST,
+                "/_matrix/client/v3/createRoom",
+                Some(token),
+                Some(json!({ "preset": "public_chat" })),
+            )
+            .await;
+        body["room_id"].as_str().unwrap().to_owned()
+    }
+
+    async fn join(&self, token: &str, room_id: &str) {
+        let path = format!("/_matrix/client/v3/join/{}", encode(room_id));
+        self.call(Method::POST, &path, Some(token), Some(json!({}))).await;
+    }
+
+    async fn send(&self, token: &str, room_id: &str, content: Value) {
+        let path = format!(
+            "/_matrix/client/v3/rooms/{}/send/m.room.message/{}",
+            encode(room_id),
+            Uuid::new_v4().simple()
+        );
+        self.call(Method::PUT, &path, Some(token), Some(content)).await;
+    }
+
+    /// Recent `m.room.message` events in a room, oldest first.
+    async fn messages(&self, token: &str, room_id: &str) -> Vec<Value> {
+        let path = format!("/_matrix/client/v3/rooms/{}/messages?dir=b&limit=50", encode(room_id));
+        let body = self.call(Method::GET, &path, Some(token), None).await;
+        let mut events: Vec<Value> = body["chunk"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter(|ev| ev["type"] == "m.room.message")
+            .cloned()
+            .collect();
+        events.reverse();
+        events
+    }
+}
+
+fn encode(s: &str) -> String {
+    url::form_urlencoded::byte_serialize(s.as_bytes()).collect()
+}
+
+// ─── Application service listener ────────────────────────────────────────────
+
+/// Start the bridge's AS endpoint (once per test binary) and subscribe to
+/// what it bridges.
+///
+/// It runs on its own thread and runtime so it outlives individual tests:
+/// Synapse backs off from an application service that stops answering,
+/// which would delay delivery to later tests.
+fn appservice() -> broadcast::Receiver<BridgedEvent> {
+    static EVENTS: OnceLock<broadcast::Sender<BridgedEvent>> = OnceLock::new();
+    EVENTS
+        .get_or_init(|| {
+            let (tx, _) = broadcast::channel(256);
+            let listener = std::net::TcpListener::bind(AS_LISTEN)
+                .unwrap_or_else(|e| panic!("bind {AS_LISTEN}: {e}"));
+            listener.set_nonblocking(true).unwrap();
+            let events = tx.clone();
+            std::thread::spawn(move || {
+                tokio::runtime::Runtime::new().unwrap().block_on(async move {
+                    let app = Router::new()
+                        .route("/_matrix/app/v1/transactions/{txn_id}", put(transaction))
+                        .with_state(events);
+                    let listener = tokio::net::TcpListener::from_std(listener).unwrap();
+                    axum::serve(listener, app).await.unwrap();
+                })
+            });
+            tx
+        })
+        .subscribe()
+}
+
+async fn transaction(
+    State(events): State<broadcast::Sender<BridgedEvent>>,
+    Query(params): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+    Json(txn): Json<MatrixTransaction>,
+) -> (StatusCode, Json<Value>) {
+    let bridge = bridge();
+    let authorization = headers.get(header::AUTHORIZATION).and_then(|v| v.to_str().ok());
+    if !bridge.verify_hs_token(authorization, params.get("access_token").map(String::as_str)) {
+        return (StatusCode::FORBIDDEN, Json(json!({ "errcode": "M_FORBIDDEN" })));
+    }
+    for event in bridge.handle_transaction(txn).await {
+        let _ = events.send(event);
+    }
+    (StatusCode::OK, Json(json!({})))
+}
+
+/// The next message the bridge produced for `room_id`.
+async fn next_message(events: &mut broadcast::Receiver<BridgedEvent>, room_id: &str) -> (String, String) {
+    tokio::time::timeout(DELIVERY_TIMEOUT, async {
+        loop {
+            match events.recv().await.expect("appservice listener stopped") {
+                BridgedEvent::MessageCreate { matrix_room_id, sender_mxid, body, .. }
+                    if matrix_room_id == room_id =>
+                {
+                    return (sender_mxid, body);
+                }
+                _ => continue,
+            }
+        }
+    })
+    .await
+    .unwrap_or_else(|_| panic!("Synapse didn't deliver a message in {room_id} to the bridge"))
+}
+
+// ─── Key exchange ────────────────────────────────────────────────────────────
+
+#[tokio::test]
+async fn synapse_key_document_verifies() {
+    let mut doc = Matrix::new().call(Method::GET, "/_matrix/key/v2/server", None, None).await;
+    assert_eq!(doc["server_name"], SERVER_NAME);
+
+    let keys: HashMap<String, VerifyKey> = doc["verify_keys"]
+        .as_object()
+        .expect("verify_keys")
+        .iter()
+        .map(|(key_id, key)| {
+            (key_id.clone(), VerifyKey { key: to_base64url(key["key"].as_str().unwrap()) })
+        })
+        .collect();
+    assert!(!keys.is_empty(), "Synapse advertised no keys: {doc}");
+    for sig in doc["signatures"][SERVER_NAME].as_object_mut().expect("self-signature").values_mut() {
+        *sig = to_base64url(sig.as_str().unwrap()).into();
+    }
+
+    // Same canonical JSON and Ed25519 as Synapse, or this fails.
+    let mut verified = verify_event(&doc, SERVER_NAME, &keys).expect("Synapse key document signature");
+    verified.sort();
+    let mut expected: Vec<_> = keys.keys().cloned().collect();
+    expected.sort();
+    assert_eq!(verified, expected);
+
+    doc["valid_until_ts"] = (doc["valid_until_ts"].as_i64().unwrap() + 1).into();
+    assert!(verify_event(&doc, SERVER_NAME, &keys).is_err(), "tampered document verified");
+}
+
+// ─── Application service bridge ──────────────────────────────────────────────
+
+#[tokio::test]
+async fn bridge_relays_nexus_messages_to_matrix() {
+    let mut events = appservice();
+    let matrix = Matrix::new();
+    let (alice, alice_token) = matrix.register("alice").await;
+    let room_id = matrix.create_room(AS_TOKEN).await;
+    matrix.join(&alice_token, &room_id).await;
+
+    bridge()
+        .send_to_matrix(&room_id, "Nexus Tester", "hello from <nexus> & co")
+        .await
+        .expect("relay to Matrix");
+
+    let relayed = matrix
+        .messages(&alice_token, &room_id)
+        .await
+        .into_iter()
+        .find(|ev| ev["sender"] == BOT_MXID)
+        .expect("relayed message in the room");
+    assert_eq!(relayed["content"]["msgtype"], "m.text");
+    assert_eq!(relayed["content"]["body"], "Nexus Tester: hello from <nexus> & co");
+    assert_eq!(relayed["content"]["format"], "org.matrix.custom.html");
+    assert_eq!(
+        relayed["content"]["formatted_body"],
+        "<b>Nexus Tester</b>: hello from &lt;nexus&gt; &amp; co"
+    );
+
+    // The relay comes back through the AS too; it must not bounce into
+    // Nexus. Alice's reply is the first message the bridge should produce.
+    matrix
+        .send(&alice_token, &room_id, json!({ "msgtype": "m.text", "body": "got it" }))
+        .await;
+    assert_eq!(next_message(&mut events, &room_id).await, (alice, "got it".to_owned()));
+}
+
+#[tokio::test]
+async fn bridge_receives_matrix_messages() {
+    let mut events = appservice();
+    let matrix = Matrix::new();
+    let (bob, bob_token) = matrix.register("bob").await;
+    let room_id = matrix.create_room(&bob_token).await;
+
+    // Attachments aren't bridged yet; text and notices are.
+    matrix
+        .send(
+            &bob_token,
+            &room_id,
+            json!({ "msgtype": "m.image", "body": "cat.png", "url": "mxc://synapse.test/cat" }),
+        )
+        .await;
+    matrix
+        .send(&bob_token, &room_id, json!({ "msgtype": "m.text", "body": "hello from matrix 日本語" }))
+        .await;
+    matrix
+        .send(&bob_token, &room_id, json!({ "msgtype": "m.notice", "body": "a notice" }))
+        .await;
+
+    assert_eq!(
+        next_message(&mut events, &room_id).await,
+        (bob.clone(), "hello from matrix 日本語".to_owned())
+    );
+    assert_eq!(next_message(&mut events, &room_id).await, (bob, "a notice".to_owned()));
+}
@@ -0,0 +1,330 @@
+//! Event fan-out — serialize each event once, not once per connection.
+//!
+//! A single task receives from the shared [`GatewayEvent`] bus, renders the
+//! event body to JSON, and rebroadcasts it as a [`DispatchFrame`]. Each
+//! connection then only filters on the routing ids and splices in its own
+//! sequence number, which is a string concatenation rather than a full
+//! `serde_json` pass over the payload.
+//!
+//! Every frame gets a node-local bus index and is kept in a [`FrameLog`],
+//! so a session that reconnects can be sent what it missed while away.
+//!
+//! Both channels are bounded (`gateway.event_bus_capacity`). Events the
+//! fan-out task falls behind on are lost outright; frames a connection
+//! falls behind on are still in the log, and `gateway.overflow` decides how
+//! it gets them. Either way it is counted in [`BusStats`] and logged.
+//!
+//! Both also feed the `nexus_gateway_*` Prometheus metrics, alongside a
+//! count of every event fanned out.
+
+use nexus_common::gateway_event::{intents, GatewayEvent};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast::{self, error::RecvError};
+use uuid::Uuid;
+
+/// Frames kept for session resume. Older ones are evicted; a session that
+/// missed any of those can't resume.
+pub const FRAME_LOG_LEN: usize = 10_000;
+
+/// A pre-serialized event, shared by every connection that receives it.
+#[derive(Debug)]
+pub struct DispatchFrame {
+    /// Position on this node's event bus, starting at 1.
+    pub index: u64,
+    pub event_type: String,
+    pub server_id: Option<Uuid>,
+    pub channel_id: Option<Uuid>,
+    pub user_id: Option<Uuid>,
+    /// `"event":…,"data":…` — the Dispatch body without braces or sequence.
+    body: Arc<str>,
+    /// The body with the message redacted, for events gated by
+    /// [`intents::MESSAGE_CONTENT`].
+    redacted: Option<Arc<str>>,
+}
+
+impl DispatchFrame {
+    pub fn new(event: &GatewayEvent, index: u64) -> Self {
+        let event_type = serde_json::to_string(&event.event_type).unwrap_or_default();
+        let body = |data: &serde_json::Value| -> Arc<str> {
+            let data = serde_json::to_string(data).unwrap_or_else(|_| "null".into());
+            format!(r#""event":{event_type},"data":{data}"#).into()
+        };
+        let redacted = intents::gates_content(&event.event_type).then(|| {
+            let mut data = event.data.clone();
+            intents::redact(&mut data);
+            body(&data)
+        });
+        Self {
+            index,
+            event_type: event.event_type.clone(),
+            server_id: event.server_id,
+            channel_id: event.channel_id,
+            user_id: event.user_id,
+            body: body(&event.data),
+            redacted,
+        }
+    }
+
+    /// The full wire message for one session with `session_intents`.
+    pub fn render(&self, sequence: u64, session_intents: u64) -> String {
+        let body = match &self.redacted {
+            Some(redacted) if session_intents & intents::MESSAGE_CONTENT == 0 => redacted,
+            _ => &self.body,
+        };
+        let mut out = String::with_capacity(body.len() + 48);
+        out.push_str(r#"{"op":"Dispatch","d":{"sequence":"#);
+        out.push_str(&sequence.to_string());
+        out.push(',');
+        out.push_str(body);
+        out.push_str("}}");
+        out
+    }
+}
+
+/// The most recent frames, oldest first.
+#[derive(Debug, Default)]
+pub struct FrameLog {
+    inner: Mutex<LogInner>,
+}
+
+#[derive(Debug, Default)]
+struct LogInner {
+    frames: VecDeque<Arc<DispatchFrame>>,
+    /// Index of the newest frame; 0 before the first.
+    head: u64,
+}
+
+impl FrameLog {
+    fn push(&self, frame: Arc<DispatchFrame>) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.head = frame.index;
+        inner.frames.push_back(frame);
+        if inner.frames.len() > FRAME_LOG_LEN {
+            inner.frames.pop_front();
+        }
+    }
+
+    /// Index of the newest frame.
+    pub fn head(&self) -> u64 {
+        self.inner.lock().unwrap().head
+    }
+
+    /// Frames after `index`, oldest first, or `None` if some of them have
+    /// already been evicted.
+    pub fn since(&self, index: u64) -> Option<Vec<Arc<DispatchFrame>>> {
+        let inner = self.inner.lock().unwrap();
+        if index >= inner.head {
+            return Some(Vec::new());
+        }
+        match inner.frames.front() {
+            Some(oldest) if oldest.index <= index + 1 => Some(
+                inner
+                    .frames
+                    .iter()
+                    .filter(|f| f.index > index)
+                    .cloned()
+                    .collect(),
+            ),
+            _ => None,
+        }
+    }
+}
+
+/// Counts of events the gateway fell behind on, since startup.
+#[derive(Debug, Default)]
+pub struct BusStats {
+    /// Events the fan-out task missed; nobody received these.
+    fanout_dropped: AtomicU64,
+    /// Frames connections missed on their channel.
+    connection_lagged: AtomicU64,
+    /// Of those, frames sent anyway from the log.
+    spilled: AtomicU64,
+    /// Connections asked to reconnect and resume after falling behind.
+    forced_resumes: AtomicU64,
+}
+
+/// A point-in-time copy of [`BusStats`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize)]
+pub struct BusStatsSnapshot {
+    pub fanout_dropped: u64,
+    pub connection_lagged: u64,
+    pub spilled: u64,
+    pub forced_resumes: u64,
+}
+
+impl BusStats {
+    /// Record `n` events lost by the fan-out task; returns the new total.
+    pub fn record_fanout_dropped(&self, n: u64) -> u64 {
+        metrics::counter!("nexus_gateway_fanout_dropped_total").increment(n);
+        self.fanout_dropped.fetch_add(n, Ordering::Relaxed) + n
+    }
+
+    /// Record `n` frames a connection fell behind on; returns the new total.
+    pub fn record_connection_lagged(&self, n: u64) -> u64 {
+        metrics::counter!("nexus_gateway_connection_lagged_total").increment(n);
+        self.connection_lagged.fetch_add(n, Ordering::Relaxed) + n
+    }
+
+    pub fn record_spilled(&self, n: u64) {
+        metrics::counter!("nexus_gateway_spilled_total").increment(n);
+        self.spilled.fetch_add(n, Ordering::Relaxed);
+    }
+
+    pub fn record_forced_resume(&self) {
+        metrics::counter!("nexus_gateway_forced_resumes_total").increment(1);
+        self.forced_resumes.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self) -> BusStatsSnapshot {
+        BusStatsSnapshot {
+            fanout_dropped: self.fanout_dropped.load(Ordering::Relaxed),
+            connection_lagged: self.connection_lagged.load(Ordering::Relaxed),
+            spilled: self.spilled.load(Ordering::Relaxed),
+            forced_resumes: self.forced_resumes.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Start the fan-out task and return the frame channel connections
+/// subscribe to, holding up to `capacity` frames, plus the log of recent
+/// frames. The task ends when `events` closes.
+pub fn spawn(
+    events: &broadcast::Sender<GatewayEvent>,
+    capacity: usize,
+    stats: Arc<BusStats>,
+) -> (broadcast::Sender<Arc<DispatchFrame>>, Arc<FrameLog>) {
+    let (frames, _) = broadcast::channel(capacity);
+    let log = Arc::new(FrameLog::default());
+    let mut rx = events.subscribe();
+    let tx = frames.clone();
+    let task_log = log.clone();
+    tokio::spawn(async move {
+        let mut index = 0;
+        loop {
+            match rx.recv().await {
+                Ok(event) => {
+                    // Logged even with nobody connected: a disconnected
+                    // session may still resume and need it.
+                    index += 1;
+                    metrics::counter!("nexus_gateway_events_total").increment(1);
+                    // Continues the trace of whatever published the event.
+                    let span = tracing::info_span!("gateway.fanout", event = %event.event_type, index);
+                    if let Some(traceparent) = &event.trace_context {
+                        nexus_common::telemetry::set_parent(&span, traceparent);
+                    }
+                    let _entered = span.enter();
+                    let frame = Arc::new(DispatchFrame::new(&event, index));
+                    task_log.push(frame.clone());
+                    let _ = tx.send(frame);
+                }
+                Err(RecvError::Lagged(n)) => {
+                    let total = stats.record_fanout_dropped(n);
+                    tracing::warn!(
+                        skipped = n,
+                        total,
+                        "Gateway fan-out lagged behind the event bus; events were dropped \
+                         (consider raising gateway.event_bus_capacity)"
+                    );
+                }
+                Err(RecvError::Closed) => break,
+            }
+        }
+    });
+    (frames, log)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_matches_serde_output() {
+        let event = GatewayEvent {
+            event_type: "MESSAGE_CREATE".into(),
+            data: serde_json::json!({"content": "hi \"there\"", "n": [1, 2]}),
+            server_id: Some(Uuid::nil()),
+            channel_id: None,
+            user_id: None,
+            trace_context: None,
+        };
+        let frame = DispatchFrame::new(&event, 1);
+        let rendered: serde_json::Value =
+            serde_json::from_str(&frame.render(42, intents::MESSAGE_CONTENT)).unwrap();
+        assert_eq!(
+            rendered,
+            serde_json::json!({
+                "op": "Dispatch",
+                "d": {
+                    "sequence": 42,
+                    "event": "MESSAGE_CREATE",
+                    "data": {"content": "hi \"there\"", "n": [1, 2]},
+                }
+            })
+        );
+    }
+    #[test]
+    fn message_body_needs_message_content() {
+        let event = GatewayEvent {
+            event_type: "MESSAGE_CREATE".into(),
+            data: serde_json::json!({"id": "1", "content": "hi"}),
+            server_id: Some(Uuid::nil()),
+            channel_id: None,
+            user_id: None,
+            trace_context: None,
+        };
+        let frame = DispatchFrame::new(&event, 1);
+        let content = |session_intents| {
+            let rendered: serde_json::Value =
+                serde_json::from_str(&frame.render(1, session_intents)).unwrap();
+            rendered["d"]["data"]["content"].clone()
+        };
+        assert_eq!(content(intents::MESSAGE_CONTENT), "hi");
+        assert_eq!(content(0), "");
+    }
+
+    #[test]
+    fn log_replays_from_index_until_evicted() {
+        let event = GatewayEvent {
+            event_type: "TYPING_START".into(),
+            data: serde_json::Value::Null,
+            server_id: None,
+            channel_id: None,
+            user_id: None,
+            trace_context: None,
+        };
+        let log = FrameLog::default();
+        for index in 1..=(FRAME_LOG_LEN as u64 + 5) {
+            log.push(Arc::new(DispatchFrame::new(&event, index)));
+        }
+        let head = log.head();
+        assert_eq!(head, FRAME_LOG_LEN as u64 + 5);
+
+        let tail = log.since(head - 3).unwrap();
+        assert_eq!(tail.iter().map(|f| f.index).collect::<Vec<_>>(), [head - 2, head - 1, head]);
+        assert!(log.since(head).unwrap().is_empty());
+        // Frames 1..=5 are gone, so a session last at index 2 can't catch up.
+        assert!(log.since(5).is_some());
+        assert!(log.since(2).is_none());
+    }
+
+    #[test]
+    fn stats_accumulate() {
+        let stats = BusStats::default();
+        assert_eq!(stats.record_fanout_dropped(3), 3);
+        assert_eq!(stats.record_connection_lagged(5), 5);
+        assert_eq!(stats.record_connection_lagged(2), 7);
+        stats.record_spilled(7);
+        stats.record_forced_resume();
+        assert_eq!(
+            stats.snapshot(),
+            BusStatsSnapshot {
+                fanout_dropped: 3,
+                connection_lagged: 7,
+                spilled: 7,
+                forced_resumes: 1,
+            }
+        );
+    }
+}
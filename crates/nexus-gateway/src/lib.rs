@@ -9,118 +9,149 @@
 //! Protocol inspired by Discord's Gateway but cleaner:
 //! - Opcodes are named, not numbered
 //! - Events are typed and documented
-//! - No hidden rate limits
+//! - No hidden rate limits: refused ops are answered with `RateLimited`
 
 pub mod events;
+pub mod fanout;
+pub mod live_share;
+pub mod ratelimit;
 pub mod session;
+pub mod voice_chat;
 
 use axum::{
     extract::{
-        ws::{Message, WebSocket},
-        State, WebSocketUpgrade,
+        ws::{CloseFrame, Message, WebSocket},
+        ConnectInfo, State, WebSocketUpgrade,
     },
+    http::HeaderMap,
     response::Response,
+    Extension,
     routing::get,
     Router,
 };
+use fanout::{BusStats, DispatchFrame, FrameLog};
 use futures_util::{SinkExt, StreamExt};
-use nexus_common::gateway_event::GatewayEvent;
-use nexus_db::repository::{channels, members, read_states, servers};
-use serde::{Deserialize, Serialize};
-use session::SessionManager;
-use std::sync::Arc;
-use tokio::sync::{broadcast, RwLock};
+use live_share::LiveShares;
+use nexus_common::config::GatewayOverflow;
+use nexus_common::gateway_event::{event_types, intents, EventBus, GatewayEvent};
+pub use nexus_common::gateway_event::GatewayMessage;
+use nexus_common::models::{bot::BotApplication, user::UserPresence};
+use nexus_common::shutdown::Shutdown;
+use nexus_db::presence::PresenceService;
+use nexus_db::repository::{bots, channels, members, read_states, servers, user_settings};
+use session::{ReplayBuffer, SessionManager};
+use std::collections::{HashMap, HashSet};
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::sync::watch;
+use voice_chat::{VoiceChatAccess, VoiceChats};
+
+/// Most members one `RequestMembers` returns.
+const REQUEST_MEMBERS_MAX: u32 = 1_000;
+/// Members returned when `RequestMembers` doesn't say.
+const REQUEST_MEMBERS_DEFAULT: u32 = 100;
+/// Members per `GUILD_MEMBERS_CHUNK` dispatch.
+const MEMBERS_CHUNK_SIZE: usize = 100;
+
+/// How long a connection being closed may take to flush its last op.
+const CLOSE_FLUSH: Duration = Duration::from_secs(2);
+/// How often clients are asked to heartbeat.
+const HEARTBEAT_INTERVAL_MS: u64 = 45_000;
+/// Close code for a connection told to resume after falling behind.
+pub const CLOSE_LAGGED: u16 = 4009;
+/// Close code for connections closed because the server is shutting down
+/// (WebSocket "Service Restart"); clients should resume on another node or
+/// once it is back.
+pub const CLOSE_RESTARTING: u16 = 1012;
 
 /// Gateway state.
 #[derive(Clone)]
 pub struct GatewayState {
-    /// Broadcast channel for dispatching events to all connected clients.
-    /// In production, this would use Redis pub/sub for multi-node support.
-    pub broadcast: broadcast::Sender<GatewayEvent>,
+    /// Broadcast channel for events that fan out to many clients. With
+    /// Redis, it carries events published on every node.
+    ///
+    /// Replies to a single connection (Ready, HeartbeatAck, InvalidSession,
+    /// Resumed) never go through here; they use that connection's
+    /// [`Outbound`] channel.
+    pub broadcast: EventBus,
+    /// Pre-serialized copies of `broadcast` events that connections read from.
+    pub frames: broadcast::Sender<Arc<DispatchFrame>>,
+    /// Recent frames, for replaying to resumed sessions.
+    pub log: Arc<FrameLog>,
+    /// Events and frames readers fell behind on.
+    pub stats: Arc<BusStats>,
     pub db: nexus_db::Database,
     pub sessions: Arc<SessionManager>,
+    /// Users' presence across sessions, shared with the API.
+    pub presence: Arc<PresenceService>,
+    /// Live shares in DMs on this node. Never persisted.
+    pub live_shares: Arc<LiveShares>,
+    /// Which channels are voice channels' text chats.
+    pub voice_chats: Arc<VoiceChats>,
+    /// Once triggered, every connection is sent `Reconnect` and closed.
+    pub shutdown: Shutdown,
+    /// Open connections, for [`drained`](Self::drained).
+    connections: Arc<watch::Sender<usize>>,
 }
 
 impl GatewayState {
     pub fn new(db: nexus_db::Database) -> Self {
-        let (broadcast, _) = broadcast::channel(10_000);
-        Self {
-            broadcast,
-            db,
-            sessions: Arc::new(SessionManager::new()),
-        }
+        let broadcast = EventBus::new(nexus_common::config::get().gateway.event_bus_capacity);
+        let presence = Arc::new(PresenceService::new(db.redis.clone()));
+        Self::with_broadcast(db, broadcast, presence)
     }
 
-    /// Create a GatewayState using an externally-created broadcast sender.
-    /// This allows the API server to share the same broadcast channel.
+    /// Create a GatewayState using an externally-created broadcast sender
+    /// and presence service. This allows the API server to share both.
     pub fn with_broadcast(
         db: nexus_db::Database,
-        broadcast: broadcast::Sender<GatewayEvent>,
+        broadcast: EventBus,
+        presence: Arc<PresenceService>,
     ) -> Self {
+        let stats = Arc::new(BusStats::default());
+        let capacity = nexus_common::config::get().gateway.event_bus_capacity;
+        let (frames, log) = fanout::spawn(&broadcast, capacity, stats.clone());
         Self {
+            frames,
+            log,
+            stats,
             broadcast,
+            sessions: Arc::new(session_manager(&db)),
+            presence,
+            live_shares: Arc::new(LiveShares::default()),
+            voice_chats: Arc::new(VoiceChats::default()),
+            shutdown: Shutdown::new(),
+            connections: Arc::new(watch::channel(0).0),
             db,
-            sessions: Arc::new(SessionManager::new()),
         }
     }
-}
-
-/// Gateway opcodes — what the client and server send to each other.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(tag = "op", content = "d")]
-pub enum GatewayMessage {
-    /// Client → Server: Authenticate with access token
-    Identify { token: String },
-
-    /// Server → Client: Connection accepted, here's your session info
-    Ready {
-        session_id: String,
-        user: serde_json::Value,
-        servers: Vec<serde_json::Value>,
-    },
-
-    /// Bidirectional: Keepalive ping/pong
-    Heartbeat { timestamp: i64 },
-
-    /// Server → Client: Heartbeat acknowledged
-    HeartbeatAck { timestamp: i64 },
 
-    /// Client → Server: Resume a disconnected session
-    Resume {
-        session_id: String,
-        token: String,
-        sequence: u64,
-    },
-
-    /// Server → Client: An event occurred
-    Dispatch {
-        event: String,
-        data: serde_json::Value,
-        sequence: u64,
-    },
-
-    /// Server → Client: Reconnect requested (server restarting, etc.)
-    Reconnect,
-
-    /// Server → Client: Session invalidated, must re-identify
-    InvalidSession,
-
-    /// Client → Server: Request presence update
-    PresenceUpdate {
-        status: String,
-        custom_status: Option<String>,
-    },
+    /// Close connections when `shutdown` is triggered, rather than when the
+    /// gateway's own (never triggered) signal is.
+    pub fn with_shutdown(mut self, shutdown: Shutdown) -> Self {
+        self.shutdown = shutdown;
+        self
+    }
 
-    /// Client → Server: Typing indicator
-    TypingStart { channel_id: String },
+    /// Resolves once no connections are open — after shutdown, once every
+    /// client has been told to reconnect and its session checkpointed.
+    pub fn drained(&self) -> impl std::future::Future<Output = ()> + Send + 'static {
+        let mut rx = self.connections.subscribe();
+        async move {
+            let _ = rx.wait_for(|&open| open == 0).await;
+        }
+    }
+}
 
-    /// Client → Server: Join voice channel
-    VoiceStateUpdate {
-        server_id: Option<String>,
-        channel_id: Option<String>,
-        self_mute: bool,
-        self_deaf: bool,
-    },
+/// Sessions are mirrored to Redis when it's configured, so they can be
+/// resumed on another node after failover.
+fn session_manager(db: &nexus_db::Database) -> SessionManager {
+    match db.redis.clone() {
+        Some(redis) => SessionManager::with_redis(redis),
+        None => SessionManager::new(),
+    }
 }
 
 // GatewayEvent is imported at the top of the file — re-export it here
@@ -137,28 +168,124 @@ pub fn build_router(state: GatewayState) -> Router {
 async fn ws_handler(
     ws: WebSocketUpgrade,
     State(state): State<Arc<GatewayState>>,
+    peer: Option<Extension<ConnectInfo<SocketAddr>>>,
+    headers: HeaderMap,
 ) -> Response {
-    ws.on_upgrade(move |socket| handle_connection(socket, state))
+    let peer = peer.map(|Extension(ConnectInfo(addr))| addr.ip());
+    let trusted = nexus_common::config::get().http.trusted_proxy_list();
+    let client_ip = nexus_common::net::client_ip(peer, &headers, &trusted);
+    ws.on_upgrade(move |socket| handle_connection(socket, state, client_ip))
+}
+
+/// What a connection's send task should do next. Each connection has its
+/// own channel of these, so replies reach only the client they're for.
+enum Outbound {
+    /// Send an op (Ready, HeartbeatAck, ...) straight to this client.
+    Op(serde_json::Value),
+    /// Dispatch an event to this connection only. It is numbered in the
+    /// attached session's sequence, so a resume replays it like any other.
+    Dispatch(GatewayEvent),
+    /// Start dispatching for a session, first sending `backlog` in order.
+    Attach {
+        session: Attached,
+        backlog: Vec<String>,
+    },
+    /// Send an op, then close the socket with the given code and reason.
+    Close(serde_json::Value, u16, &'static str),
+}
+
+/// The session a connection is dispatching for.
+struct Attached {
+    connection_id: uuid::Uuid,
+    user_id: uuid::Uuid,
+    subscribed: Vec<uuid::Uuid>,
+    intents: u64,
+    replay: Arc<Mutex<ReplayBuffer>>,
+    voice_chats: VoiceChatAccess,
+}
+
+/// Whether a session for `user_id` subscribed to `servers`, with
+/// `session_intents`, receives `frame`. `hidden` holds the voice chats
+/// among the frames being looked at that the user may not read.
+fn should_forward(
+    frame: &DispatchFrame,
+    user_id: uuid::Uuid,
+    servers: &[uuid::Uuid],
+    session_intents: u64,
+    hidden: &HashSet<uuid::Uuid>,
+) -> bool {
+    if !intents::wants(&frame.event_type, session_intents) {
+        return false;
+    }
+    match frame.server_id {
+        Some(sid) => {
+            servers.contains(&sid) && !frame.channel_id.is_some_and(|c| hidden.contains(&c))
+        }
+        // DM / targeted events — forward if addressed to this user
+        None => frame.user_id == Some(user_id),
+    }
+}
+
+/// Everything a resuming client missed: dispatches it never acknowledged,
+/// then bus `frames` from while it was away, numbered on from its
+/// sequence. `None` if the dispatches are no longer kept.
+fn resume_backlog(
+    replay: &mut ReplayBuffer,
+    frames: &[Arc<DispatchFrame>],
+    sequence: u64,
+    user_id: uuid::Uuid,
+    servers: &[uuid::Uuid],
+    session_intents: u64,
+    hidden: &HashSet<uuid::Uuid>,
+) -> Option<Vec<String>> {
+    let mut backlog: Vec<String> = replay
+        .since(sequence)?
+        .iter()
+        .map(|(seq, frame)| frame.render(*seq, session_intents))
+        .collect();
+    backlog.extend(missed_frames(replay, frames, user_id, servers, session_intents, hidden));
+    Some(backlog)
+}
+
+/// Those of `frames`, taken from the frame log, that come after the last
+/// one this session looked at, numbered and rendered. Frames logged since
+/// `frames` was taken still arrive on the connection's receiver.
+fn missed_frames(
+    replay: &mut ReplayBuffer,
+    frames: &[Arc<DispatchFrame>],
+    user_id: uuid::Uuid,
+    servers: &[uuid::Uuid],
+    session_intents: u64,
+    hidden: &HashSet<uuid::Uuid>,
+) -> Vec<String> {
+    let mut missed = Vec::new();
+    for frame in frames.iter().filter(|f| f.index > replay.bus_index) {
+        if should_forward(frame, user_id, servers, session_intents, hidden) {
+            let seq = replay.push(frame.clone());
+            missed.push(frame.render(seq, session_intents));
+        } else {
+            replay.skip(frame.index);
+        }
+    }
+    missed
 }
 
 /// Handle a single WebSocket connection.
-async fn handle_connection(socket: WebSocket, state: Arc<GatewayState>) {
+async fn handle_connection(socket: WebSocket, state: Arc<GatewayState>, client_ip: Option<IpAddr>) {
     let (mut sender, mut receiver) = socket.split();
 
-    let session_id = uuid::Uuid::new_v4().to_string();
+    let connection_id = uuid::Uuid::new_v4();
+    // Replaced by the resumed session's ID on a successful Resume.
+    let mut session_id = connection_id.to_string();
 
     // Direct-send channel: receive loop → sender task (for Ready, HeartbeatAck, etc.)
-    let (direct_tx, mut direct_rx) = tokio::sync::mpsc::channel::<serde_json::Value>(64);
-
-    // Shared mutable state accessed by both the sender task and the receive loop
-    let subscribed: Arc<RwLock<Vec<uuid::Uuid>>> = Arc::new(RwLock::new(Vec::new()));
-    let authed_user_id: Arc<RwLock<Option<uuid::Uuid>>> = Arc::new(RwLock::new(None));
+    let (direct_tx, mut direct_rx) = tokio::sync::mpsc::channel::<Outbound>(64);
 
     // Subscribe to broadcast BEFORE spawning tasks so we don't miss events
-    let mut broadcast_rx = state.broadcast.subscribe();
+    let mut frame_rx = state.frames.subscribe();
 
     // Send Hello immediately to prompt the client to Identify
-    let hello = serde_json::json!({"op": "Hello", "d": {"heartbeat_interval": 45000}});
+    let hello = GatewayMessage::Hello { heartbeat_interval: HEARTBEAT_INTERVAL_MS };
     if sender
         .send(Message::Text(serde_json::to_string(&hello).unwrap().into()))
         .await
@@ -166,55 +293,174 @@ async fn handle_connection(socket: WebSocket, state: Arc<GatewayState>) {
     {
         return;
     }
+    metrics::gauge!("nexus_gateway_connections").increment(1);
+    state.connections.send_modify(|open| *open += 1);
 
     // ── Sender task ──────────────────────────────────────────────────────────
     // Merges broadcast events (filtered to this user's servers) and direct
     // messages (Ready, HeartbeatAck) onto the single WebSocket sender.
-    let subscribed_clone = subscribed.clone();
-    let uid_clone = authed_user_id.clone();
-
-    let send_task = tokio::spawn(async move {
+    // Frames queue in `frame_rx` until a session is attached, so nothing
+    // between Identify/Resume and the first dispatch is lost.
+    //
+    // A connection that falls behind on `frame_rx` gets what it missed per
+    // `gateway.overflow` rather than silently skipping ahead.
+    let task_log = state.log.clone();
+    let task_stats = state.stats.clone();
+    let task_voice_chats = state.voice_chats.clone();
+    let task_pool = state.db.pool.clone();
+    let overflow = nexus_common::config::get().gateway.overflow;
+    let stopping = state.shutdown.triggered();
+    let mut send_task = tokio::spawn(async move {
+        let mut attached: Option<Attached> = None;
+        tokio::pin!(stopping);
         loop {
             tokio::select! {
-                Ok(event) = broadcast_rx.recv() => {
-                    // Only forward events after the client has identified
-                    let uid = *uid_clone.read().await;
-                    let Some(uid) = uid else { continue };
-
-                    let subs = subscribed_clone.read().await;
-                    let forward = match event.server_id {
-                        Some(sid) => subs.contains(&sid),
-                        None => {
-                            // DM / targeted events — forward if addressed to this user
-                            event.user_id.map_or(false, |eid| eid == uid)
+                () = &mut stopping => {
+                    // With Redis the session outlives this process and the
+                    // client resumes; otherwise it has to identify again.
+                    let op = serde_json::to_string(&GatewayMessage::Reconnect).unwrap();
+                    if sender.send(Message::Text(op.into())).await.is_ok() {
+                        let _ = sender
+                            .send(Message::Close(Some(CloseFrame {
+                                code: CLOSE_RESTARTING,
+                                reason: "Server restarting".into(),
+                            })))
+                            .await;
+                    }
+                    break;
+                }
+                received = frame_rx.recv(), if attached.is_some() => {
+                    let Some(session) = attached.as_mut() else { continue };
+                    let frame = match received {
+                        Ok(frame) => frame,
+                        Err(RecvError::Lagged(n)) => {
+                            let total = task_stats.record_connection_lagged(n);
+                            let spilled = match overflow {
+                                GatewayOverflow::Spill => {
+                                    let bus_index = session.replay.lock().unwrap().bus_index;
+                                    match task_log.since(bus_index) {
+                                        Some(frames) => {
+                                            let hidden = session
+                                                .voice_chats
+                                                .hidden_among(&task_voice_chats, &task_pool, &frames)
+                                                .await;
+                                            let mut replay = session.replay.lock().unwrap();
+                                            if replay.owner != session.connection_id {
+                                                break;
+                                            }
+                                            Some(missed_frames(
+                                                &mut replay,
+                                                &frames,
+                                                session.user_id,
+                                                &session.subscribed,
+                                                session.intents,
+                                                &hidden,
+                                            ))
+                                        }
+                                        None => None,
+                                    }
+                                }
+                                GatewayOverflow::Resume => None,
+                            };
+                            let Some(spilled) = spilled else {
+                                // Hand the client to Resume, which replays
+                                // what it can or tells it to re-identify.
+                                task_stats.record_forced_resume();
+                                tracing::warn!(
+                                    user = %session.user_id,
+                                    skipped = n,
+                                    total,
+                                    "Gateway connection fell behind; asking it to resume"
+                                );
+                                let op = serde_json::to_string(&GatewayMessage::Reconnect).unwrap();
+                                if sender.send(Message::Text(op.into())).await.is_ok() {
+                                    let _ = sender
+                                        .send(Message::Close(Some(CloseFrame {
+                                            code: CLOSE_LAGGED,
+                                            reason: "Fell behind".into(),
+                                        })))
+                                        .await;
+                                }
+                                break;
+                            };
+                            task_stats.record_spilled(spilled.len() as u64);
+                            tracing::warn!(
+                                user = %session.user_id,
+                                skipped = n,
+                                total,
+                                spilled = spilled.len(),
+                                "Gateway connection fell behind; catching up from the frame log"
+                            );
+                            for text in spilled {
+                                if sender.send(Message::Text(text.into())).await.is_err() {
+                                    return;
+                                }
+                            }
+                            continue;
                         }
+                        Err(RecvError::Closed) => break,
                     };
-                    drop(subs);
-
-                    if !forward { continue; }
-
-                    let wire = serde_json::json!({
-                        "op": "Dispatch",
-                        "d": {
-                            "event": event.event_type,
-                            "data": event.data,
+                    let hidden = session
+                        .voice_chats
+                        .hidden_among(&task_voice_chats, &task_pool, std::slice::from_ref(&frame))
+                        .await;
+                    let text = {
+                        let mut replay = session.replay.lock().unwrap();
+                        if replay.owner != session.connection_id {
+                            // The session was resumed on another connection.
+                            break;
                         }
-                    });
-                    if sender
-                        .send(Message::Text(serde_json::to_string(&wire).unwrap().into()))
-                        .await
-                        .is_err()
-                    {
+                        if frame.index <= replay.bus_index {
+                            // Already replayed, or from before READY.
+                            continue;
+                        }
+                        if !should_forward(&frame, session.user_id, &session.subscribed, session.intents, &hidden) {
+                            replay.skip(frame.index);
+                            continue;
+                        }
+                        let seq = replay.push(frame.clone());
+                        frame.render(seq, session.intents)
+                    };
+                    if sender.send(Message::Text(text.into())).await.is_err() {
                         break;
                     }
                 }
                 Some(direct) = direct_rx.recv() => {
-                    if sender
-                        .send(Message::Text(serde_json::to_string(&direct).unwrap().into()))
-                        .await
-                        .is_err()
-                    {
-                        break;
+                    let texts = match direct {
+                        Outbound::Op(op) => vec![serde_json::to_string(&op).unwrap()],
+                        Outbound::Close(op, code, reason) => {
+                            let text = serde_json::to_string(&op).unwrap();
+                            if sender.send(Message::Text(text.into())).await.is_ok() {
+                                let _ = sender
+                                    .send(Message::Close(Some(CloseFrame {
+                                        code,
+                                        reason: reason.into(),
+                                    })))
+                                    .await;
+                            }
+                            return;
+                        }
+                        Outbound::Attach { session, backlog } => {
+                            attached = Some(session);
+                            backlog
+                        }
+                        Outbound::Dispatch(event) => {
+                            let Some(session) = attached.as_ref() else { continue };
+                            let mut replay = session.replay.lock().unwrap();
+                            if replay.owner != session.connection_id {
+                                break;
+                            }
+                            // Sits at the current bus position: it doesn't
+                            // move the session past any broadcast frame.
+                            let frame = Arc::new(DispatchFrame::new(&event, replay.bus_index));
+                            let seq = replay.push(frame.clone());
+                            vec![frame.render(seq, session.intents)]
+                        }
+                    };
+                    for text in texts {
+                        if sender.send(Message::Text(text.into())).await.is_err() {
+                            return;
+                        }
                     }
                 }
                 else => break,
@@ -225,75 +471,230 @@ async fn handle_connection(socket: WebSocket, state: Arc<GatewayState>) {
     // ── Receive loop ─────────────────────────────────────────────────────────
     let mut authenticated = false;
     let mut user_id: Option<uuid::Uuid> = None;
+    // Shared with the sender task once a session is attached.
+    let mut replay: Option<Arc<Mutex<ReplayBuffer>>> = None;
+    let mut limits = ratelimit::ConnectionLimits::new(&nexus_common::config::get().rate_limit);
 
-    while let Some(Ok(msg)) = receiver.next().await {
+    loop {
+        let msg = tokio::select! {
+            msg = receiver.next() => msg,
+            // The socket is gone or the session moved to another connection.
+            _ = &mut send_task => break,
+        };
+        let Some(Ok(msg)) = msg else { break };
         match msg {
             Message::Text(text) => {
+                if !limits.frame() {
+                    tracing::info!(session = %session_id, "Closing gateway connection over its rate limit");
+                    let op = serde_json::to_value(GatewayMessage::InvalidSession).unwrap();
+                    if direct_tx
+                        .send(Outbound::Close(op, ratelimit::CLOSE_RATE_LIMITED, "Rate limited"))
+                        .await
+                        .is_ok()
+                    {
+                        let _ = tokio::time::timeout(CLOSE_FLUSH, &mut send_task).await;
+                    }
+                    break;
+                }
                 let Ok(gateway_msg) = serde_json::from_str::<GatewayMessage>(&text) else {
                     continue;
                 };
+                if let Err(retry_after) = limits.op(&gateway_msg) {
+                    let limited = GatewayMessage::RateLimited {
+                        op: ratelimit::op_name(&gateway_msg),
+                        retry_after_ms: retry_after.as_millis() as u64,
+                    };
+                    let _ = direct_tx.send(Outbound::Op(serde_json::to_value(limited).unwrap())).await;
+                    continue;
+                }
                 match gateway_msg {
-                    GatewayMessage::Identify { token } => {
-                        let config = nexus_common::config::get();
-                        match nexus_common::auth::validate_token(&token, &config.auth.jwt_secret) {
-                            Ok(claims) => {
-                                let Ok(uid) = claims.sub.parse::<uuid::Uuid>() else {
-                                    continue;
-                                };
-                                authenticated = true;
-                                user_id = Some(uid);
-
-                                // Update shared uid so sender task can start forwarding
-                                *authed_user_id.write().await = Some(uid);
-
-                                // Build READY payload (servers + channels + read states)
-                                let ready_data = build_ready_payload(
-                                    &state, uid, &session_id, &claims.username,
-                                ).await;
-
-                                // Populate subscribed server list BEFORE sender task
-                                // processes any further broadcast events
-                                let server_ids: Vec<uuid::Uuid> = ready_data["servers"]
-                                    .as_array()
-                                    .unwrap_or(&vec![])
-                                    .iter()
-                                    .filter_map(|s| s["id"].as_str()?.parse().ok())
-                                    .collect();
-
-                                *subscribed.write().await = server_ids.clone();
-
-                                state.sessions.register(
-                                    session_id.clone(),
-                                    uid,
-                                    server_ids,
-                                ).await;
-
-                                // Send READY directly (not via broadcast)
-                                let _ = direct_tx.send(serde_json::json!({
-                                    "op": "Ready",
-                                    "d": ready_data,
-                                })).await;
-
-                                tracing::info!(
-                                    session = %session_id,
-                                    user = %claims.username,
-                                    "Gateway READY sent"
-                                );
+                    GatewayMessage::Identify { token, intents: requested } => {
+                        let Some(principal) = authenticate(&state, &token, client_ip).await else {
+                            let _ = direct_tx.send(Outbound::Op(serde_json::json!({
+                                "op": "InvalidSession",
+                                "d": null,
+                            }))).await;
+                            continue;
+                        };
+                        let intents = principal.intents(requested);
+                        authenticated = true;
+
+                        // Events up to here are covered by the READY snapshot.
+                        let bus_index = state.log.head();
+
+                        // Build READY payload (servers + channels + read states)
+                        let ready_data = match &principal {
+                            Principal::User { id, username } => {
+                                user_id = Some(*id);
+                                build_ready_payload(&state, *id, &session_id, username).await
                             }
-                            Err(_) => {
-                                let _ = direct_tx.send(serde_json::json!({
-                                    "op": "InvalidSession",
-                                    "d": null,
-                                })).await;
+                            Principal::Bot(app) => {
+                                build_bot_ready_payload(&state, app, &session_id).await
                             }
+                        };
+
+                        let server_ids: Vec<uuid::Uuid> = ready_data["servers"]
+                            .as_array()
+                            .unwrap_or(&vec![])
+                            .iter()
+                            .filter_map(|s| s["id"].as_str()?.parse().ok())
+                            .collect();
+
+                        let session_replay = state.sessions.register(
+                            session_id.clone(),
+                            principal.id(),
+                            server_ids.clone(),
+                            intents,
+                            connection_id,
+                            bus_index,
+                        ).await;
+                        replay = Some(session_replay.clone());
+
+                        // READY goes first, then dispatches from `bus_index` on.
+                        let _ = direct_tx.send(Outbound::Op(serde_json::json!({
+                            "op": "Ready",
+                            "d": ready_data,
+                        }))).await;
+                        let _ = direct_tx.send(Outbound::Attach {
+                            session: Attached {
+                                connection_id,
+                                user_id: principal.id(),
+                                subscribed: server_ids,
+                                intents,
+                                replay: session_replay,
+                                voice_chats: VoiceChatAccess::new(principal.id()),
+                            },
+                            backlog: Vec::new(),
+                        }).await;
+
+                        if let Some(uid) = user_id {
+                            set_session_presence(&state, uid, &session_id, UserPresence::Online).await;
                         }
+
+                        tracing::info!(
+                            session = %session_id,
+                            user = %principal.name(),
+                            "Gateway READY sent"
+                        );
+                    }
+
+                    GatewayMessage::Resume { session_id: resume_id, token, sequence } => {
+                        if authenticated {
+                            continue;
+                        }
+                        let principal = authenticate(&state, &token, client_ip).await;
+                        let uid = principal.as_ref().map(Principal::id);
+
+                        // Served here: replay from the session's buffer.
+                        // Served by another node: adopt it from Redis, with
+                        // nothing to replay.
+                        let resumed = match uid {
+                            Some(uid) => match state.sessions.resume(&resume_id, uid, connection_id).await {
+                                Ok(r) => Some((uid, r, true)),
+                                Err(_) => state
+                                    .sessions
+                                    .adopt(&resume_id, uid, sequence, connection_id, state.log.head())
+                                    .await
+                                    .ok()
+                                    .map(|r| (uid, r, false)),
+                            },
+                            None => None,
+                        };
+                        // `resume` and `adopt` only find sessions `uid` owns;
+                        // naming someone else's session must not end it.
+                        let owned = resumed.is_some();
+                        let attached = match (resumed, principal.as_ref()) {
+                            (Some((uid, r, complete)), Some(principal)) => {
+                                // A bot's message content capability may have
+                                // changed while it was away.
+                                let intents = principal.intents(r.intents);
+                                let mut voice_chats = VoiceChatAccess::new(uid);
+                                let bus_index = r.replay.lock().unwrap().bus_index;
+                                match state.log.since(bus_index) {
+                                    Some(frames) => {
+                                        let hidden = voice_chats
+                                            .hidden_among(&state.voice_chats, &state.db.pool, &frames)
+                                            .await;
+                                        resume_backlog(
+                                            &mut r.replay.lock().unwrap(),
+                                            &frames,
+                                            sequence,
+                                            uid,
+                                            &r.subscribed_servers,
+                                            intents,
+                                            &hidden,
+                                        )
+                                        .map(|backlog| (uid, r, complete, intents, voice_chats, backlog))
+                                    }
+                                    None => None,
+                                }
+                            }
+                            _ => None,
+                        };
+
+                        let Some((uid, resumed, complete, intents, voice_chats, mut backlog)) = attached else {
+                            if owned {
+                                // Resumable no longer; make the client start over.
+                                state.sessions.forget(&resume_id).await;
+                            }
+                            let _ = direct_tx.send(Outbound::Op(serde_json::json!({
+                                "op": "InvalidSession",
+                                "d": null,
+                            }))).await;
+                            continue;
+                        };
+
+                        authenticated = true;
+                        if let Some(Principal::User { .. }) = principal {
+                            user_id = Some(uid);
+                            set_session_presence(&state, uid, &resume_id, UserPresence::Online).await;
+                        }
+                        session_id = resume_id;
+                        replay = Some(resumed.replay.clone());
+
+                        let replayed = backlog.len();
+                        backlog.push(
+                            serde_json::json!({
+                                "op": "Resumed",
+                                "d": { "session_id": session_id, "replayed": replayed, "complete": complete },
+                            })
+                            .to_string(),
+                        );
+                        let _ = direct_tx.send(Outbound::Attach {
+                            session: Attached {
+                                connection_id,
+                                user_id: uid,
+                                subscribed: resumed.subscribed_servers,
+                                intents,
+                                replay: resumed.replay,
+                                voice_chats,
+                            },
+                            backlog,
+                        }).await;
+
+                        tracing::info!(
+                            session = %session_id,
+                            user = %uid,
+                            replayed,
+                            complete,
+                            "Gateway session resumed"
+                        );
                     }
 
                     GatewayMessage::Heartbeat { .. } => {
-                        let _ = direct_tx.send(serde_json::json!({
+                        if let Some(replay) = &replay {
+                            let sequence = replay.lock().unwrap().sequence;
+                            state.sessions.checkpoint(&session_id, sequence).await;
+                        }
+                        if let Some(uid) = user_id
+                            && let Some(presence) = state.presence.refresh(uid, &session_id).await
+                        {
+                            publish_presence(&state, uid, presence, None).await;
+                        }
+                        let _ = direct_tx.send(Outbound::Op(serde_json::json!({
                             "op": "HeartbeatAck",
                             "d": { "timestamp": chrono::Utc::now().timestamp_millis() },
-                        })).await;
+                        }))).await;
                     }
 
                     GatewayMessage::TypingStart { channel_id } => {
@@ -308,26 +709,49 @@ async fn handle_connection(socket: WebSocket, state: Arc<GatewayState>) {
                                 server_id: None,
                                 channel_id: channel_id.parse().ok(),
                                 user_id,
+                                trace_context: None,
                             });
                         }
                     }
 
+                    GatewayMessage::RequestMembers { server_id, query, limit, nonce } => {
+                        let Some(uid) = user_id else { continue };
+                        let Ok(server_id) = server_id.parse::<uuid::Uuid>() else { continue };
+                        send_member_chunks(
+                            &state,
+                            &direct_tx,
+                            uid,
+                            server_id,
+                            query.as_deref().unwrap_or(""),
+                            limit.unwrap_or(REQUEST_MEMBERS_DEFAULT).clamp(1, REQUEST_MEMBERS_MAX),
+                            nonce,
+                        )
+                        .await;
+                    }
+
+                    GatewayMessage::LiveShare { channel_id, data, expires_in_secs } => {
+                        let Some(uid) = user_id else { continue };
+                        let Ok(channel_id) = channel_id.parse::<uuid::Uuid>() else { continue };
+                        live_share::update(&state, uid, connection_id, channel_id, data, expires_in_secs)
+                            .await;
+                    }
+
+                    GatewayMessage::LiveShareStop { channel_id } => {
+                        let Some(uid) = user_id else { continue };
+                        let Ok(channel_id) = channel_id.parse::<uuid::Uuid>() else { continue };
+                        live_share::stop(&state, uid, channel_id);
+                    }
+
                     GatewayMessage::PresenceUpdate { status, custom_status } => {
-                        if let Some(uid) = user_id {
-                            let _ = nexus_db::repository::users::update_presence(
-                                &state.db.pool, uid, &status,
-                            ).await;
-                            let _ = state.broadcast.send(GatewayEvent {
-                                event_type: "PRESENCE_UPDATE".into(),
-                                data: serde_json::json!({
-                                    "user_id": uid,
-                                    "status": status,
-                                    "custom_status": custom_status,
-                                }),
-                                server_id: None,
-                                channel_id: None,
-                                user_id: Some(uid),
-                            });
+                        let Some(uid) = user_id else { continue };
+                        let Some(status) = UserPresence::parse(&status) else { continue };
+                        let changed = state.presence.set(uid, &session_id, status).await;
+                        if changed.is_some() || custom_status.is_some() {
+                            let presence = match changed {
+                                Some(presence) => presence,
+                                None => state.presence.get(uid).await,
+                            };
+                            publish_presence(&state, uid, presence, custom_status).await;
                         }
                     }
 
@@ -356,6 +780,7 @@ async fn handle_connection(socket: WebSocket, state: Arc<GatewayState>) {
                                 server_id: server_uuid,
                                 channel_id: channel_uuid,
                                 user_id: Some(uid),
+                                trace_context: None,
                             });
                         }
                     }
@@ -369,27 +794,237 @@ async fn handle_connection(socket: WebSocket, state: Arc<GatewayState>) {
     }
 
     // ── Cleanup ───────────────────────────────────────────────────────────────
-    state.sessions.remove(&session_id).await;
-    if let Some(uid) = user_id {
-        if !state.sessions.is_online(uid).await {
-            let _ = nexus_db::repository::users::update_presence(
-                &state.db.pool, uid, "offline",
-            ).await;
-            let _ = state.broadcast.send(GatewayEvent {
-                event_type: "PRESENCE_UPDATE".into(),
-                data: serde_json::json!({"user_id": uid, "status": "offline"}),
-                server_id: None,
-                channel_id: None,
-                user_id: Some(uid),
-            });
-        }
+    // Keep the session resumable; the client may reconnect with Resume.
+    // Its presence goes now, unless another connection already resumed it.
+    let mut owned = false;
+    if let Some(replay) = &replay {
+        let sequence = {
+            let replay = replay.lock().unwrap();
+            owned = replay.owner == connection_id;
+            replay.sequence
+        };
+        state.sessions.checkpoint(&session_id, sequence).await;
+        state.sessions.detach(&session_id, connection_id).await;
     }
+    if let Some(uid) = user_id.filter(|_| owned)
+        && let Some(presence) = state.presence.remove(uid, &session_id).await
+    {
+        publish_presence(&state, uid, presence, None).await;
+    }
+
+    live_share::stop_connection(&state, connection_id);
 
     send_task.abort();
+    metrics::gauge!("nexus_gateway_connections").decrement(1);
+    state.connections.send_modify(|open| *open -= 1);
     tracing::info!(session = %session_id, "Client disconnected from gateway");
 }
+
+/// Set one session's status, publishing the user's presence if that
+/// changed it.
+async fn set_session_presence(
+    state: &GatewayState,
+    uid: uuid::Uuid,
+    session_id: &str,
+    status: UserPresence,
+) {
+    if let Some(presence) = state.presence.set(uid, session_id, status).await {
+        publish_presence(state, uid, presence, None).await;
+    }
+}
+
+/// Store a user's aggregate presence and send it to their sessions.
+async fn publish_presence(
+    state: &GatewayState,
+    uid: uuid::Uuid,
+    presence: UserPresence,
+    custom_status: Option<String>,
+) {
+    if let Err(e) = nexus_db::repository::users::update_presence(
+        &state.db.pool, uid, presence.as_str(),
+    ).await {
+        tracing::warn!(user = %uid, error = %e, "Failed to store presence");
+    }
+    let _ = state.broadcast.send(GatewayEvent {
+        event_type: "PRESENCE_UPDATE".into(),
+        data: serde_json::json!({
+            "user_id": uid,
+            "status": presence,
+            "custom_status": custom_status,
+        }),
+        server_id: None,
+        channel_id: None,
+        user_id: Some(uid),
+        trace_context: None,
+    });
+}
+
+/// Answer `RequestMembers`: up to `limit` members of `server_id` matching
+/// `query`, in `GUILD_MEMBERS_CHUNK` dispatches of [`MEMBERS_CHUNK_SIZE`].
+///
+/// Chunks go only to the connection that asked, through `direct_tx`; the
+/// user's other sessions never see them. Non-members get nothing, as do
+/// requests the database fails on.
+async fn send_member_chunks(
+    state: &GatewayState,
+    direct_tx: &tokio::sync::mpsc::Sender<Outbound>,
+    uid: uuid::Uuid,
+    server_id: uuid::Uuid,
+    query: &str,
+    limit: u32,
+    nonce: Option<String>,
+) {
+    match members::is_member(&state.db.pool, uid, server_id).await {
+        Ok(true) => {}
+        Ok(false) => return,
+        Err(e) => {
+            tracing::warn!(%server_id, error = %e, "RequestMembers membership check failed");
+            return;
+        }
+    }
+    let matches = match members::search_by_prefix(&state.db.pool, server_id, query, limit as i64).await {
+        Ok(matches) => matches,
+        Err(e) => {
+            tracing::warn!(%server_id, error = %e, "RequestMembers lookup failed");
+            return;
+        }
+    };
+
+    let chunks: Vec<_> = matches.chunks(MEMBERS_CHUNK_SIZE).collect();
+    // An empty result still gets one (empty) chunk, so the client knows.
+    let chunk_count = chunks.len().max(1);
+    for chunk_index in 0..chunk_count {
+        let members_json: Vec<_> = chunks
+            .get(chunk_index)
+            .copied()
+            .unwrap_or_default()
+            .iter()
+            .map(|m| serde_json::json!({
+                "user": {
+                    "id": m.member.user_id,
+                    "username": m.username,
+                    "display_name": m.display_name,
+                    "avatar": m.user_avatar,
+                },
+                "nickname": m.member.nickname,
+                "avatar": m.member.avatar,
+                "roles": m.member.roles,
+                "joined_at": m.member.joined_at,
+                "pending": m.member.pending,
+            }))
+            .collect();
+        let chunk = GatewayEvent {
+            event_type: event_types::GUILD_MEMBERS_CHUNK.into(),
+            data: serde_json::json!({
+                "server_id": server_id,
+                "members": members_json,
+                "chunk_index": chunk_index,
+                "chunk_count": chunk_count,
+                "nonce": nonce,
+            }),
+            server_id: None,
+            channel_id: None,
+            user_id: Some(uid),
+            trace_context: None,
+        };
+        if direct_tx.send(Outbound::Dispatch(chunk)).await.is_err() {
+            return;
+        }
+    }
+}
+
+/// Who an Identify or Resume token belongs to.
+enum Principal {
+    /// An access token's user.
+    User { id: uuid::Uuid, username: String },
+    /// The application behind a `Bot <token>`.
+    Bot(BotApplication),
+}
+
+impl Principal {
+    /// The id sessions are registered under.
+    fn id(&self) -> uuid::Uuid {
+        match self {
+            Principal::User { id, .. } => *id,
+            Principal::Bot(app) => app.id,
+        }
+    }
+
+    fn name(&self) -> &str {
+        match self {
+            Principal::User { username, .. } => username,
+            Principal::Bot(app) => &app.name,
+        }
+    }
+
+    /// `requested` intents, with [`intents::MESSAGE_CONTENT`] set exactly
+    /// when this principal may read message bodies.
+    fn intents(&self, requested: u64) -> u64 {
+        let content = match self {
+            Principal::User { .. } => true,
+            Principal::Bot(app) => app.has_message_content(),
+        };
+        if content {
+            requested | intents::MESSAGE_CONTENT
+        } else {
+            requested & !intents::MESSAGE_CONTENT
+        }
+    }
+}
+
+/// Resolve an access token or `Bot <token>`. Bots with an IP allowlist are
+/// refused unless `client_ip` is on it, as in the REST middleware.
+async fn authenticate(state: &GatewayState, token: &str, client_ip: Option<IpAddr>) -> Option<Principal> {
+    let Some(bot_token) = token.strip_prefix("Bot ") else {
+        let config = nexus_common::config::get();
+        let claims = nexus_common::auth::validate_token(token, &config.auth.jwt_secret).ok()?;
+        return Some(Principal::User {
+            id: claims.sub.parse().ok()?,
+            username: claims.username,
+        });
+    };
+    let hash = nexus_common::auth::hash_bot_token(bot_token);
+    let app = match bots::get_bot_by_token_hash(&state.db.pool, &hash).await {
+        Ok(app) => app?,
+        Err(e) => {
+            tracing::warn!(error = %e, "Bot token lookup failed");
+            return None;
+        }
+    };
+    if !nexus_common::net::ip_allowed(&app.ip_allowlist, client_ip) {
+        tracing::info!(bot_id = %app.id, ip = ?client_ip, "Gateway refused bot by IP allowlist");
+        return None;
+    }
+    Some(Principal::Bot(app))
+}
+
+/// Build the READY payload for a bot: its application and the servers it
+/// is installed in.
+async fn build_bot_ready_payload(
+    state: &GatewayState,
+    app: &BotApplication,
+    session_id: &str,
+) -> serde_json::Value {
+    let server_ids = bots::get_bot_servers(&state.db.pool, app.id)
+        .await
+        .unwrap_or_default();
+    serde_json::json!({
+        "session_id": session_id,
+        "application": {
+            "id": app.id,
+            "name": app.name,
+            "message_content": app.has_message_content(),
+        },
+        "servers": server_ids
+            .iter()
+            .map(|id| serde_json::json!({ "id": id }))
+            .collect::<Vec<_>>(),
+    })
+}
+
 /// Build the READY payload for a newly authenticated user.
-/// Contains: user profile, server list with channels, read states.
+/// Contains: user profile, server list with channels, read states, synced
+/// settings.
 async fn build_ready_payload(
     state: &GatewayState,
     uid: uuid::Uuid,
@@ -407,17 +1042,37 @@ async fn build_ready_payload(
         .await
         .unwrap_or_default();
 
-    // For each server, fetch channels
+    // Channels and memberships for every server at once, grouped by server.
+    let server_ids: Vec<uuid::Uuid> = user_servers.iter().map(|s| s.id).collect();
+    let mut channels_by_server: HashMap<uuid::Uuid, Vec<_>> = HashMap::new();
+    // Voice channels' text chats ride along on their voice channel.
+    let mut voice_chats: HashMap<uuid::Uuid, uuid::Uuid> = HashMap::new();
+    for channel in channels::list_channels_for_servers(&state.db.pool, &server_ids)
+        .await
+        .unwrap_or_default()
+    {
+        match channel.voice_channel_id {
+            Some(voice_channel_id) => {
+                voice_chats.insert(voice_channel_id, channel.id);
+            }
+            None => {
+                if let Some(server_id) = channel.server_id {
+                    channels_by_server.entry(server_id).or_default().push(channel);
+                }
+            }
+        }
+    }
+    let mut memberships: HashMap<uuid::Uuid, _> = members::list_for_user(&state.db.pool, uid)
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .map(|m| (m.server_id, m))
+        .collect();
+
     let mut server_payloads = Vec::new();
     for server in &user_servers {
-        let server_channels = channels::list_server_channels(&state.db.pool, server.id)
-            .await
-            .unwrap_or_default();
-
-        let member = members::find_member(&state.db.pool, uid, server.id)
-            .await
-            .ok()
-            .flatten();
+        let server_channels = channels_by_server.remove(&server.id).unwrap_or_default();
+        let member = memberships.remove(&server.id);
 
         server_payloads.push(serde_json::json!({
             "id": server.id,
@@ -425,6 +1080,7 @@ async fn build_ready_payload(
             "icon": server.icon,
             "owner_id": server.owner_id,
             "member_count": server.member_count,
+            "preferred_locale": server.preferred_locale,
             "channels": server_channels.iter().map(|c| serde_json::json!({
                 "id": c.id,
                 "name": c.name,
@@ -434,6 +1090,7 @@ async fn build_ready_payload(
                 "last_message_id": c.last_message_id,
                 "topic": c.topic,
                 "nsfw": c.nsfw,
+                "text_channel_id": voice_chats.get(&c.id),
             })).collect::<Vec<_>>(),
             "member": member.map(|m| serde_json::json!({
                 "nickname": m.nickname,
@@ -462,6 +1119,11 @@ async fn build_ready_payload(
         .await
         .unwrap_or_default();
 
+    // Synced settings, with servers left since the layout was saved dropped
+    let mut settings = user_settings::get(&state.db.pool, uid).await.unwrap_or_default();
+    let member_of = user_servers.iter().map(|s| s.id).collect();
+    settings.server_layout.retain_servers(&member_of);
+
     serde_json::json!({
         "session_id": session_id,
         "user": user.map(|u| serde_json::json!({
@@ -473,6 +1135,7 @@ async fn build_ready_payload(
             "status": u.status,
             "presence": u.presence,
             "flags": u.flags,
+            "locale": u.locale,
         })),
         "servers": server_payloads,
         "dm_channels": dm_channels.iter().map(|c| serde_json::json!({
@@ -485,5 +1148,6 @@ async fn build_ready_payload(
             "last_read_message_id": rs.last_read_message_id,
             "mention_count": rs.mention_count,
         })).collect::<Vec<_>>(),
+        "settings": settings,
     })
 }
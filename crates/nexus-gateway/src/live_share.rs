@@ -0,0 +1,240 @@
+//! Live sharing in DMs — "on my way", live location and the like.
+//!
+//! A participant of a DM or group DM shares a small payload with the
+//! channel through `LiveShare`, updating it as often as its rate limit
+//! allows, until it sends `LiveShareStop`, its connection closes, or the
+//! share expires. Participants get `LIVE_SHARE_UPDATE` and
+//! `LIVE_SHARE_STOP` dispatches.
+//!
+//! Nothing is persisted: shares live only in this node's memory. In E2EE
+//! channels the payload must be `{"ciphertext_map": {...}}`, encrypted per
+//! recipient device like messages, so the server never sees it.
+
+use chrono::{DateTime, Utc};
+use nexus_common::gateway_event::{event_types, GatewayEvent};
+use nexus_common::models::channel::ChannelType;
+use nexus_db::repository::channels;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::GatewayState;
+
+/// How long a share lasts when `expires_in_secs` isn't given.
+pub const DEFAULT_SECS: u64 = 15 * 60;
+/// Longest a share may last.
+pub const MAX_SECS: u64 = 8 * 60 * 60;
+/// Largest payload, as serialized JSON.
+pub const MAX_DATA_BYTES: usize = 16 * 1024;
+
+/// Why a share ended, as sent in `LIVE_SHARE_STOP`.
+#[derive(Debug, Clone, Copy)]
+pub enum StopReason {
+    Stopped,
+    Expired,
+    Disconnected,
+}
+
+impl StopReason {
+    fn as_str(self) -> &'static str {
+        match self {
+            StopReason::Stopped => "stopped",
+            StopReason::Expired => "expired",
+            StopReason::Disconnected => "disconnected",
+        }
+    }
+}
+
+struct Share {
+    /// Identifies the expiry timer that may end this share.
+    token: Uuid,
+    connection_id: Uuid,
+    participants: Vec<Uuid>,
+    expires_at: DateTime<Utc>,
+}
+
+/// Active shares on this node, by (user, channel).
+#[derive(Default)]
+pub struct LiveShares {
+    shares: Mutex<HashMap<(Uuid, Uuid), Share>>,
+}
+
+/// Start or update `user_id`'s share in `channel_id`. An update keeps the
+/// share's expiry unless `expires_in_secs` is given. Invalid requests are
+/// dropped.
+pub async fn update(
+    state: &Arc<GatewayState>,
+    user_id: Uuid,
+    connection_id: Uuid,
+    channel_id: Uuid,
+    data: serde_json::Value,
+    expires_in_secs: Option<u64>,
+) {
+    let participants = match participants(state, user_id, channel_id, &data).await {
+        Ok(participants) => participants,
+        Err(reason) => {
+            tracing::debug!(user = %user_id, channel = %channel_id, reason, "Dropping LiveShare");
+            return;
+        }
+    };
+
+    let key = (user_id, channel_id);
+    let (expires_at, timer) = {
+        let mut shares = state.live_shares.shares.lock().unwrap();
+        let existing = shares.get(&key).filter(|_| expires_in_secs.is_none());
+        let (token, expires_at, timer) = match existing {
+            Some(share) => (share.token, share.expires_at, None),
+            None => {
+                let secs = expires_in_secs.unwrap_or(DEFAULT_SECS).clamp(1, MAX_SECS);
+                let token = Uuid::new_v4();
+                let expires_at = Utc::now() + chrono::Duration::seconds(secs as i64);
+                (token, expires_at, Some((token, Duration::from_secs(secs))))
+            }
+        };
+        shares.insert(
+            key,
+            Share {
+                token,
+                connection_id,
+                participants: participants.clone(),
+                expires_at,
+            },
+        );
+        (expires_at, timer)
+    };
+
+    if let Some((token, after)) = timer {
+        let state = state.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(after).await;
+            let expired = {
+                let mut shares = state.live_shares.shares.lock().unwrap();
+                match shares.get(&key) {
+                    Some(share) if share.token == token => shares.remove(&key),
+                    _ => None,
+                }
+            };
+            if let Some(share) = expired {
+                send_stop(&state, user_id, channel_id, &share.participants, StopReason::Expired);
+            }
+        });
+    }
+
+    for &participant in &participants {
+        let _ = state.broadcast.send(GatewayEvent {
+            event_type: event_types::LIVE_SHARE_UPDATE.into(),
+            data: serde_json::json!({
+                "channel_id": channel_id,
+                "user_id": user_id,
+                "data": data,
+                "expires_at": expires_at,
+            }),
+            server_id: None,
+            channel_id: Some(channel_id),
+            user_id: Some(participant),
+            trace_context: None,
+        });
+    }
+}
+
+/// End `user_id`'s share in `channel_id`, if there is one.
+pub fn stop(state: &GatewayState, user_id: Uuid, channel_id: Uuid) {
+    let removed = state.live_shares.shares.lock().unwrap().remove(&(user_id, channel_id));
+    if let Some(share) = removed {
+        send_stop(state, user_id, channel_id, &share.participants, StopReason::Stopped);
+    }
+}
+
+/// End every share started on `connection_id`, which has closed.
+pub fn stop_connection(state: &GatewayState, connection_id: Uuid) {
+    let ended: Vec<_> = {
+        let mut shares = state.live_shares.shares.lock().unwrap();
+        let keys: Vec<_> = shares
+            .iter()
+            .filter(|(_, share)| share.connection_id == connection_id)
+            .map(|(key, _)| *key)
+            .collect();
+        keys.into_iter()
+            .filter_map(|key| Some((key, shares.remove(&key)?)))
+            .collect()
+    };
+    for ((user_id, channel_id), share) in ended {
+        send_stop(state, user_id, channel_id, &share.participants, StopReason::Disconnected);
+    }
+}
+
+/// Everyone in the DM `channel_id`, if `user_id` may share `data` there.
+async fn participants(
+    state: &GatewayState,
+    user_id: Uuid,
+    channel_id: Uuid,
+    data: &serde_json::Value,
+) -> Result<Vec<Uuid>, &'static str> {
+    if serde_json::to_string(data).map_or(true, |s| s.len() > MAX_DATA_BYTES) {
+        return Err("payload too large");
+    }
+    let channel = channels::find_by_id(&state.db.pool, channel_id)
+        .await
+        .map_err(|_| "channel lookup failed")?
+        .ok_or("unknown channel")?;
+    if !matches!(channel.channel_type, ChannelType::Dm | ChannelType::GroupDm) {
+        return Err("not a DM");
+    }
+    if channel.encrypted && !is_encrypted_payload(data) {
+        return Err("plaintext payload in an E2EE channel");
+    }
+    let participants = channels::list_dm_participants(&state.db.pool, channel_id)
+        .await
+        .map_err(|_| "participant lookup failed")?;
+    if !participants.contains(&user_id) {
+        return Err("not a participant");
+    }
+    Ok(participants)
+}
+
+/// Whether `data` is only a per-device ciphertext map.
+fn is_encrypted_payload(data: &serde_json::Value) -> bool {
+    data.as_object().is_some_and(|fields| {
+        fields.len() == 1 && fields.get("ciphertext_map").is_some_and(|m| m.is_object())
+    })
+}
+
+fn send_stop(
+    state: &GatewayState,
+    user_id: Uuid,
+    channel_id: Uuid,
+    participants: &[Uuid],
+    reason: StopReason,
+) {
+    for &participant in participants {
+        let _ = state.broadcast.send(GatewayEvent {
+            event_type: event_types::LIVE_SHARE_STOP.into(),
+            data: serde_json::json!({
+                "channel_id": channel_id,
+                "user_id": user_id,
+                "reason": reason.as_str(),
+            }),
+            server_id: None,
+            channel_id: Some(channel_id),
+            user_id: Some(participant),
+            trace_context: None,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn e2ee_payload_is_ciphertext_only() {
+        assert!(is_encrypted_payload(&serde_json::json!({"ciphertext_map": {"dev": "abc"}})));
+        assert!(!is_encrypted_payload(&serde_json::json!({"lat": 1.0, "lng": 2.0})));
+        assert!(!is_encrypted_payload(&serde_json::json!({
+            "ciphertext_map": {"dev": "abc"},
+            "eta": "5 min",
+        })));
+        assert!(!is_encrypted_payload(&serde_json::json!({"ciphertext_map": "abc"})));
+    }
+}
@@ -0,0 +1,158 @@
+//! Per-connection limits on what a client sends (see [`RateLimitConfig`]).
+//!
+//! Every frame counts against the connection's frame budget; running it dry
+//! closes the socket with [`CLOSE_RATE_LIMITED`]. Chatty ops have their own,
+//! smaller budget on top: when it runs out the op is dropped and the client
+//! told so with a `RateLimited` op rather than disconnected.
+
+use nexus_common::config::RateLimitConfig;
+use nexus_common::ratelimit::{Limit, TokenBucket};
+use std::time::{Duration, Instant};
+
+use crate::GatewayMessage;
+
+/// Close code for a connection that exceeded its frame budget.
+pub const CLOSE_RATE_LIMITED: u16 = 4008;
+
+struct Bucket {
+    limit: Limit,
+    bucket: TokenBucket,
+}
+
+impl Bucket {
+    fn new(limit: Limit) -> Self {
+        Self {
+            limit,
+            bucket: TokenBucket::new(limit, 0),
+        }
+    }
+
+    fn take(&mut self, now_ms: u64) -> Result<(), Duration> {
+        let outcome = self.bucket.take(self.limit, now_ms);
+        if outcome.allowed { Ok(()) } else { Err(outcome.retry_after) }
+    }
+}
+
+pub struct ConnectionLimits {
+    enabled: bool,
+    started: Instant,
+    frames: Bucket,
+    typing: Bucket,
+    presence: Bucket,
+    request_members: Bucket,
+    live_share: Bucket,
+}
+
+impl ConnectionLimits {
+    pub fn new(config: &RateLimitConfig) -> Self {
+        Self {
+            enabled: config.enabled,
+            started: Instant::now(),
+            frames: Bucket::new(Limit::new(config.gateway_limit, config.gateway_window_secs)),
+            typing: Bucket::new(Limit::new(config.typing_limit, config.typing_window_secs)),
+            presence: Bucket::new(Limit::new(config.presence_limit, config.presence_window_secs)),
+            request_members: Bucket::new(Limit::new(
+                config.request_members_limit,
+                config.request_members_window_secs,
+            )),
+            live_share: Bucket::new(Limit::new(config.live_share_limit, config.live_share_window_secs)),
+        }
+    }
+
+    fn now_ms(&self) -> u64 {
+        self.started.elapsed().as_millis() as u64
+    }
+
+    /// Charge one frame. `false` means the connection is over its budget.
+    pub fn frame(&mut self) -> bool {
+        let now = self.now_ms();
+        !self.enabled || self.frames.take(now).is_ok()
+    }
+
+    /// Charge an op against its own budget, if it has one. On refusal,
+    /// returns how long until it would be accepted.
+    pub fn op(&mut self, msg: &GatewayMessage) -> Result<(), Duration> {
+        if !self.enabled {
+            return Ok(());
+        }
+        let now = self.now_ms();
+        match msg {
+            GatewayMessage::TypingStart { .. } => self.typing.take(now),
+            GatewayMessage::PresenceUpdate { .. } => self.presence.take(now),
+            GatewayMessage::RequestMembers { .. } => self.request_members.take(now),
+            GatewayMessage::LiveShare { .. } => self.live_share.take(now),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// The op's name on the wire, for `RateLimited`.
+pub fn op_name(msg: &GatewayMessage) -> String {
+    serde_json::to_value(msg)
+        .ok()
+        .and_then(|v| v.get("op").and_then(|op| op.as_str().map(str::to_owned)))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> RateLimitConfig {
+        RateLimitConfig {
+            enabled: true,
+            global_limit: 50,
+            global_window_secs: 1,
+            route_limit: 30,
+            route_window_secs: 10,
+            message_limit: 5,
+            message_window_secs: 5,
+            gateway_limit: 4,
+            gateway_window_secs: 60,
+            typing_limit: 2,
+            typing_window_secs: 10,
+            presence_limit: 1,
+            presence_window_secs: 60,
+            request_members_limit: 10,
+            request_members_window_secs: 60,
+            live_share_limit: 30,
+            live_share_window_secs: 60,
+        }
+    }
+
+    fn typing() -> GatewayMessage {
+        GatewayMessage::TypingStart { channel_id: "c".into() }
+    }
+
+    #[test]
+    fn frames_run_out() {
+        let mut limits = ConnectionLimits::new(&config());
+        for _ in 0..4 {
+            assert!(limits.frame());
+        }
+        assert!(!limits.frame());
+    }
+
+    #[test]
+    fn ops_have_their_own_budget() {
+        let mut limits = ConnectionLimits::new(&config());
+        assert!(limits.op(&typing()).is_ok());
+        assert!(limits.op(&typing()).is_ok());
+        assert!(limits.op(&typing()).unwrap_err() > Duration::ZERO);
+        // Heartbeats are never refused by op budget.
+        assert!(limits.op(&GatewayMessage::Heartbeat { timestamp: 0 }).is_ok());
+        assert_eq!(op_name(&typing()), "TypingStart");
+    }
+
+    #[test]
+    fn disabled_allows_everything() {
+        let mut limits = ConnectionLimits::new(&RateLimitConfig {
+            enabled: false,
+            ..config()
+        });
+        for _ in 0..10 {
+            assert!(limits.frame());
+            assert!(limits.op(&typing()).is_ok());
+        }
+    }
+}
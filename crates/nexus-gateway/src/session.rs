@@ -1,16 +1,44 @@
 //! Gateway session management.
+//!
+//! Live sessions are tracked in memory. When Redis is configured, each
+//! session's metadata (user, last sequence, subscriptions, intents) is
+//! mirrored there with a TTL, so a client can resume against any gateway
+//! node after the one it was connected to goes away.
+//!
+//! When a connection drops, its session is detached rather than removed and
+//! keeps its [`ReplayBuffer`] for [`RESUME_WINDOW_SECS`]. Only the node that
+//! served the session holds that buffer; another node [adopts](SessionManager::adopt)
+//! the session from Redis instead, keeping its subscriptions and numbering
+//! but without the dispatches sent while the client was away.
 
-use std::collections::HashMap;
-use std::sync::Arc;
+use redis::aio::ConnectionManager;
+use redis::AsyncCommands;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
 use tokio::sync::RwLock;
 use uuid::Uuid;
 
+use crate::fanout::DispatchFrame;
+
+/// How long a session stays resumable after its last checkpoint.
+pub const RESUME_WINDOW_SECS: u64 = 300;
+
+/// Dispatches kept per session for resending on resume.
+pub const REPLAY_BUFFER_LEN: usize = 1_000;
+
+fn session_key(session_id: &str) -> String {
+    format!("gateway:session:{session_id}")
+}
+
 /// Tracks all active gateway sessions.
 pub struct SessionManager {
     /// Map of session_id → Session
     sessions: Arc<RwLock<HashMap<String, Session>>>,
     /// Map of user_id → Vec<session_id> (a user can have multiple sessions/devices)
     user_sessions: Arc<RwLock<HashMap<Uuid, Vec<String>>>>,
+    /// Shared store for resume state; `None` keeps sessions node-local.
+    redis: Option<ConnectionManager>,
 }
 
 pub struct Session {
@@ -19,8 +47,118 @@ pub struct Session {
     pub sequence: u64,
     /// Server IDs this session is subscribed to
     pub subscribed_servers: Vec<Uuid>,
+    /// Opt-in event variants asked for in Identify (see
+    /// [`nexus_common::gateway_event::intents`]).
+    pub intents: u64,
     /// Last heartbeat time
     pub last_heartbeat: chrono::DateTime<chrono::Utc>,
+    /// Dispatches sent to this session, shared with its connection.
+    pub replay: Arc<Mutex<ReplayBuffer>>,
+    /// The connection serving this session; `None` while it waits to be
+    /// resumed.
+    pub connection_id: Option<Uuid>,
+    /// When the session was last detached.
+    pub detached_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Per-session dispatch numbering and the most recent dispatches sent.
+#[derive(Debug)]
+pub struct ReplayBuffer {
+    /// The connection allowed to send on this session. A connection that
+    /// finds itself replaced by a resume stops sending.
+    pub owner: Uuid,
+    /// Sequence of the last dispatch sent.
+    pub sequence: u64,
+    /// Bus index of the last frame this session has looked at, sent or not.
+    pub bus_index: u64,
+    frames: VecDeque<(u64, Arc<DispatchFrame>)>,
+}
+
+impl ReplayBuffer {
+    pub fn new(owner: Uuid, bus_index: u64) -> Self {
+        Self {
+            owner,
+            sequence: 0,
+            bus_index,
+            frames: VecDeque::new(),
+        }
+    }
+
+    /// Number and keep a frame being sent; returns its sequence.
+    pub fn push(&mut self, frame: Arc<DispatchFrame>) -> u64 {
+        self.sequence += 1;
+        self.bus_index = frame.index;
+        self.frames.push_back((self.sequence, frame));
+        if self.frames.len() > REPLAY_BUFFER_LEN {
+            self.frames.pop_front();
+        }
+        self.sequence
+    }
+
+    /// Note a frame that wasn't for this session.
+    pub fn skip(&mut self, index: u64) {
+        self.bus_index = index;
+    }
+
+    /// Dispatches sent after `sequence`, or `None` if the client claims a
+    /// sequence we never sent or some of them have been evicted.
+    pub fn since(&self, sequence: u64) -> Option<Vec<(u64, Arc<DispatchFrame>)>> {
+        if sequence > self.sequence {
+            return None;
+        }
+        if sequence == self.sequence {
+            return Some(Vec::new());
+        }
+        match self.frames.front() {
+            Some((oldest, _)) if *oldest <= sequence + 1 => Some(
+                self.frames
+                    .iter()
+                    .filter(|(seq, _)| *seq > sequence)
+                    .cloned()
+                    .collect(),
+            ),
+            _ => None,
+        }
+    }
+}
+
+/// Why a session couldn't be resumed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResumeError {
+    /// Unknown here, expired, or belongs to another user.
+    NotFound,
+}
+
+/// What a connection needs to take over a resumed session.
+pub struct Resumed {
+    pub replay: Arc<Mutex<ReplayBuffer>>,
+    pub subscribed_servers: Vec<Uuid>,
+    pub intents: u64,
+}
+
+/// Resume state persisted to Redis.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredSession {
+    pub session_id: String,
+    pub user_id: Uuid,
+    pub sequence: u64,
+    pub subscribed_servers: Vec<Uuid>,
+    #[serde(default)]
+    pub intents: u64,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<&Session> for StoredSession {
+    fn from(s: &Session) -> Self {
+        Self {
+            session_id: s.session_id.clone(),
+            user_id: s.user_id,
+            sequence: s.sequence,
+            subscribed_servers: s.subscribed_servers.clone(),
+            intents: s.intents,
+            updated_at: s.last_heartbeat,
+        }
+    }
 }
 
 impl SessionManager {
@@ -28,18 +166,42 @@ impl SessionManager {
         Self {
             sessions: Arc::new(RwLock::new(HashMap::new())),
             user_sessions: Arc::new(RwLock::new(HashMap::new())),
+            redis: None,
+        }
+    }
+
+    /// A session manager that mirrors resume state to Redis.
+    pub fn with_redis(redis: ConnectionManager) -> Self {
+        Self {
+            redis: Some(redis),
+            ..Self::new()
         }
     }
 
-    /// Register a new session.
-    pub async fn register(&self, session_id: String, user_id: Uuid, servers: Vec<Uuid>) {
+    /// Register a new session served by `connection_id`. Dispatches are
+    /// numbered from the bus position `bus_index` onwards.
+    pub async fn register(
+        &self,
+        session_id: String,
+        user_id: Uuid,
+        servers: Vec<Uuid>,
+        intents: u64,
+        connection_id: Uuid,
+        bus_index: u64,
+    ) -> Arc<Mutex<ReplayBuffer>> {
+        let replay = Arc::new(Mutex::new(ReplayBuffer::new(connection_id, bus_index)));
         let session = Session {
             session_id: session_id.clone(),
             user_id,
             sequence: 0,
             subscribed_servers: servers,
+            intents,
             last_heartbeat: chrono::Utc::now(),
+            replay: replay.clone(),
+            connection_id: Some(connection_id),
+            detached_at: None,
         };
+        self.persist(&StoredSession::from(&session)).await;
 
         self.sessions
             .write()
@@ -52,9 +214,155 @@ impl SessionManager {
             .entry(user_id)
             .or_default()
             .push(session_id);
+        replay
+    }
+
+    /// Hand a session over to a new connection.
+    ///
+    /// Works whether or not the old connection has noticed it's gone; once
+    /// the buffer changes owner, the old connection stops sending.
+    pub async fn resume(
+        &self,
+        session_id: &str,
+        user_id: Uuid,
+        connection_id: Uuid,
+    ) -> Result<Resumed, ResumeError> {
+        let resumed = {
+            let mut sessions = self.sessions.write().await;
+            let session = sessions
+                .get_mut(session_id)
+                .filter(|s| s.user_id == user_id)
+                .ok_or(ResumeError::NotFound)?;
+            session.replay.lock().unwrap().owner = connection_id;
+            session.connection_id = Some(connection_id);
+            session.detached_at = None;
+            session.last_heartbeat = chrono::Utc::now();
+            Resumed {
+                replay: session.replay.clone(),
+                subscribed_servers: session.subscribed_servers.clone(),
+                intents: session.intents,
+            }
+        };
+
+        let mut user_sessions = self.user_sessions.write().await;
+        let ids = user_sessions.entry(user_id).or_default();
+        if !ids.iter().any(|s| s == session_id) {
+            ids.push(session_id.to_owned());
+        }
+        Ok(resumed)
     }
 
-    /// Remove a session.
+    /// Take over a session another node served, from its Redis copy.
+    ///
+    /// The session keeps its ID, subscriptions and intents, and numbering
+    /// carries on from `sequence`, the client's last dispatch; a client
+    /// behind the last checkpoint is refused. Dispatches sent while it was
+    /// away are not kept anywhere, so none are replayed. New dispatches
+    /// start at the bus position `bus_index`.
+    pub async fn adopt(
+        &self,
+        session_id: &str,
+        user_id: Uuid,
+        sequence: u64,
+        connection_id: Uuid,
+        bus_index: u64,
+    ) -> Result<Resumed, ResumeError> {
+        // `load` checks this node first, so a session held here for another
+        // user is refused too.
+        let stored = self
+            .load(session_id)
+            .await
+            .filter(|s| s.user_id == user_id && s.sequence <= sequence)
+            .ok_or(ResumeError::NotFound)?;
+
+        let replay = self
+            .register(
+                session_id.to_owned(),
+                user_id,
+                stored.subscribed_servers.clone(),
+                stored.intents,
+                connection_id,
+                bus_index,
+            )
+            .await;
+        replay.lock().unwrap().sequence = sequence;
+        self.checkpoint(session_id, sequence).await;
+        Ok(Resumed {
+            replay,
+            subscribed_servers: stored.subscribed_servers,
+            intents: stored.intents,
+        })
+    }
+
+    /// Mark a session as disconnected, if `connection_id` still serves it.
+    ///
+    /// The session stays resumable on this node for [`RESUME_WINDOW_SECS`]
+    /// and is then removed unless resumed in the meantime.
+    pub async fn detach(&self, session_id: &str, connection_id: Uuid) {
+        let detached_at = chrono::Utc::now();
+        let user_id = {
+            let mut sessions = self.sessions.write().await;
+            let Some(session) = sessions.get_mut(session_id) else {
+                return;
+            };
+            if session.connection_id != Some(connection_id) {
+                // Already resumed by another connection.
+                return;
+            }
+            session.connection_id = None;
+            session.detached_at = Some(detached_at);
+            session.user_id
+        };
+        if let Some(ids) = self.user_sessions.write().await.get_mut(&user_id) {
+            ids.retain(|s| s != session_id);
+        }
+
+        let sessions = self.sessions.clone();
+        let session_id = session_id.to_owned();
+        tokio::spawn(async move {
+            tokio::time::sleep(std::time::Duration::from_secs(RESUME_WINDOW_SECS)).await;
+            let mut sessions = sessions.write().await;
+            if sessions
+                .get(&session_id)
+                .is_some_and(|s| s.detached_at == Some(detached_at))
+            {
+                sessions.remove(&session_id);
+            }
+        });
+    }
+
+    /// Record the last sequence delivered to a session and refresh its
+    /// heartbeat and resume window.
+    pub async fn checkpoint(&self, session_id: &str, sequence: u64) {
+        let stored = {
+            let mut sessions = self.sessions.write().await;
+            let Some(session) = sessions.get_mut(session_id) else {
+                return;
+            };
+            session.sequence = sequence;
+            session.last_heartbeat = chrono::Utc::now();
+            StoredSession::from(&*session)
+        };
+        self.persist(&stored).await;
+    }
+
+    /// Replace the server list a session receives events for.
+    pub async fn set_subscriptions(&self, session_id: &str, servers: Vec<Uuid>) {
+        let stored = {
+            let mut sessions = self.sessions.write().await;
+            let Some(session) = sessions.get_mut(session_id) else {
+                return;
+            };
+            session.subscribed_servers = servers;
+            StoredSession::from(&*session)
+        };
+        self.persist(&stored).await;
+    }
+
+    /// Remove a session from this node.
+    ///
+    /// The Redis copy is left to expire; see [`forget`](Self::forget) to
+    /// end the session everywhere.
     pub async fn remove(&self, session_id: &str) {
         if let Some(session) = self.sessions.write().await.remove(session_id) {
             if let Some(sessions) = self.user_sessions.write().await.get_mut(&session.user_id) {
@@ -63,7 +371,35 @@ impl SessionManager {
         }
     }
 
-    /// Get all session IDs for a user.
+    /// Look up resume state for a session, checking this node first and
+    /// then the shared store.
+    pub async fn load(&self, session_id: &str) -> Option<StoredSession> {
+        if let Some(session) = self.sessions.read().await.get(session_id) {
+            return Some(StoredSession::from(session));
+        }
+        let mut conn = self.redis.clone()?;
+        let raw: Option<String> = match conn.get(session_key(session_id)).await {
+            Ok(raw) => raw,
+            Err(e) => {
+                tracing::warn!(session = %session_id, error = %e, "Failed to load gateway session");
+                return None;
+            }
+        };
+        raw.and_then(|raw| serde_json::from_str(&raw).ok())
+    }
+
+    /// Drop resume state for good (e.g. session invalidated), here and in
+    /// the shared store.
+    pub async fn forget(&self, session_id: &str) {
+        self.remove(session_id).await;
+        if let Some(mut conn) = self.redis.clone() {
+            if let Err(e) = conn.del::<_, ()>(session_key(session_id)).await {
+                tracing::warn!(session = %session_id, error = %e, "Failed to delete gateway session");
+            }
+        }
+    }
+
+    /// Get all connected session IDs for a user.
     pub async fn get_user_sessions(&self, user_id: Uuid) -> Vec<String> {
         self.user_sessions
             .read()
@@ -73,7 +409,7 @@ impl SessionManager {
             .unwrap_or_default()
     }
 
-    /// Check if a user is online (has at least one active session).
+    /// Check if a user is online (has at least one connected session).
     pub async fn is_online(&self, user_id: Uuid) -> bool {
         self.user_sessions
             .read()
@@ -86,6 +422,22 @@ impl SessionManager {
     pub async fn active_count(&self) -> usize {
         self.sessions.read().await.len()
     }
+
+    async fn persist(&self, session: &StoredSession) {
+        let Some(mut conn) = self.redis.clone() else {
+            return;
+        };
+        let Ok(json) = serde_json::to_string(session) else {
+            return;
+        };
+        // Best-effort: a Redis hiccup costs resumability, not the connection.
+        if let Err(e) = conn
+            .set_ex::<_, _, ()>(session_key(&session.session_id), json, RESUME_WINDOW_SECS)
+            .await
+        {
+            tracing::warn!(session = %session.session_id, error = %e, "Failed to persist gateway session");
+        }
+    }
 }
 
 impl Default for SessionManager {
@@ -93,3 +445,83 @@ impl Default for SessionManager {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nexus_common::gateway_event::GatewayEvent;
+
+    fn frame(index: u64) -> Arc<DispatchFrame> {
+        Arc::new(DispatchFrame::new(
+            &GatewayEvent {
+                event_type: "MESSAGE_CREATE".into(),
+                data: serde_json::Value::Null,
+                server_id: None,
+                channel_id: None,
+                user_id: None,
+                trace_context: None,
+            },
+            index,
+        ))
+    }
+
+    #[test]
+    fn replays_dispatches_after_sequence() {
+        let mut buffer = ReplayBuffer::new(Uuid::new_v4(), 0);
+        assert_eq!(buffer.push(frame(1)), 1);
+        buffer.skip(2);
+        assert_eq!(buffer.push(frame(3)), 2);
+        assert_eq!(buffer.push(frame(4)), 3);
+        assert_eq!(buffer.bus_index, 4);
+
+        let missed = buffer.since(1).unwrap();
+        assert_eq!(missed.iter().map(|(seq, _)| *seq).collect::<Vec<_>>(), [2, 3]);
+        assert_eq!(missed[0].1.index, 3);
+        assert!(buffer.since(3).unwrap().is_empty());
+        assert!(buffer.since(4).is_none());
+    }
+
+    #[test]
+    fn cannot_replay_evicted_dispatches() {
+        let mut buffer = ReplayBuffer::new(Uuid::new_v4(), 0);
+        for index in 1..=(REPLAY_BUFFER_LEN as u64 + 10) {
+            buffer.push(frame(index));
+        }
+        assert!(buffer.since(0).is_none());
+        assert!(buffer.since(10).is_some());
+    }
+
+    #[tokio::test]
+    async fn resume_hands_session_to_new_connection() {
+        let manager = SessionManager::new();
+        let user = Uuid::new_v4();
+        let (old_conn, new_conn) = (Uuid::new_v4(), Uuid::new_v4());
+        manager.register("s1".into(), user, vec![], 0, old_conn, 0).await;
+
+        manager.detach("s1", old_conn).await;
+        assert!(!manager.is_online(user).await);
+
+        assert_eq!(
+            manager.resume("s1", Uuid::new_v4(), new_conn).await.err(),
+            Some(ResumeError::NotFound)
+        );
+        let resumed = manager.resume("s1", user, new_conn).await.unwrap();
+        assert_eq!(resumed.replay.lock().unwrap().owner, new_conn);
+        assert!(manager.is_online(user).await);
+
+        // The old connection's late cleanup must not detach the resumed session.
+        manager.detach("s1", old_conn).await;
+        assert!(manager.is_online(user).await);
+    }
+
+    #[tokio::test]
+    async fn adopting_needs_the_shared_store() {
+        let manager = SessionManager::new();
+        let user = Uuid::new_v4();
+        manager.register("s1".into(), user, vec![], 0, Uuid::new_v4(), 0).await;
+
+        // Held here for someone else, and unknown without Redis.
+        assert!(manager.adopt("s1", Uuid::new_v4(), 0, Uuid::new_v4(), 0).await.is_err());
+        assert!(manager.adopt("s2", user, 0, Uuid::new_v4(), 0).await.is_err());
+    }
+}
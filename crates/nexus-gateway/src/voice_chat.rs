@@ -0,0 +1,269 @@
+//! Which sessions receive events from voice channels' text chats.
+//!
+//! A voice channel's text chat is only readable by members who are in the
+//! call, or were within the last [`voice_chat::HISTORY_HOURS`], and by
+//! those with MANAGE_MESSAGES in the voice channel; the gateway forwards
+//! its events to them alone, as the API does for its history.
+//!
+//! Whether a channel is a voice chat never changes, so that is cached for
+//! the life of the process. Whether a user may read it is cached per
+//! session for [`RECHECK`], or until their own voice state changes.
+
+use crate::fanout::DispatchFrame;
+use nexus_common::gateway_event::event_types;
+use nexus_common::permissions::{compute_permissions, PermissionOverwrite, Permissions};
+use nexus_db::repository::{channels, members, roles, servers, voice_chat};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+/// How long a session trusts a participation check.
+const RECHECK: Duration = Duration::from_secs(30);
+
+/// The voice channel each channel seen so far is the text chat of, if any.
+#[derive(Debug, Default)]
+pub struct VoiceChats {
+    links: Mutex<HashMap<Uuid, Option<Uuid>>>,
+}
+
+impl VoiceChats {
+    /// The voice channel `channel_id` is the text chat of; `None` for every
+    /// other channel.
+    async fn voice_channel_of(
+        &self,
+        pool: &sqlx::AnyPool,
+        channel_id: Uuid,
+    ) -> Result<Option<Uuid>, sqlx::Error> {
+        if let Some(link) = self.links.lock().unwrap().get(&channel_id) {
+            return Ok(*link);
+        }
+        let link = channels::find_by_id(pool, channel_id)
+            .await?
+            .and_then(|c| c.voice_channel_id);
+        self.links.lock().unwrap().insert(channel_id, link);
+        Ok(link)
+    }
+}
+
+/// One session's access to voice chats.
+#[derive(Debug)]
+pub struct VoiceChatAccess {
+    user_id: Uuid,
+    /// Voice chat → whether the user may read it, and when that was checked.
+    checked: HashMap<Uuid, (bool, Instant)>,
+}
+
+impl VoiceChatAccess {
+    pub fn new(user_id: Uuid) -> Self {
+        Self {
+            user_id,
+            checked: HashMap::new(),
+        }
+    }
+
+    /// The voice chats among `frames`' channels that the user may not read.
+    pub async fn hidden_among(
+        &mut self,
+        chats: &VoiceChats,
+        pool: &sqlx::AnyPool,
+        frames: &[Arc<DispatchFrame>],
+    ) -> HashSet<Uuid> {
+        let mut seen = HashSet::new();
+        let mut hidden = HashSet::new();
+        for frame in frames {
+            if frame.event_type == event_types::VOICE_STATE_UPDATE && frame.user_id == Some(self.user_id) {
+                // Joined or left a call: recheck rather than wait out RECHECK.
+                self.checked.clear();
+            }
+            let (Some(_), Some(channel_id)) = (frame.server_id, frame.channel_id) else {
+                continue;
+            };
+            if seen.insert(channel_id) && !self.may_read(chats, pool, channel_id).await {
+                hidden.insert(channel_id);
+            }
+        }
+        hidden
+    }
+
+    /// Whether the user may read `channel_id`, as far as voice chats go.
+    /// Fails closed when the database can't say.
+    async fn may_read(&mut self, chats: &VoiceChats, pool: &sqlx::AnyPool, channel_id: Uuid) -> bool {
+        let voice_channel_id = match chats.voice_channel_of(pool, channel_id).await {
+            Ok(Some(voice_channel_id)) => voice_channel_id,
+            Ok(None) => return true,
+            Err(e) => {
+                tracing::warn!(channel = %channel_id, error = %e, "Failed to look up channel for dispatch");
+                return false;
+            }
+        };
+        if let Some((allowed, at)) = self.checked.get(&channel_id)
+            && at.elapsed() < RECHECK
+        {
+            return *allowed;
+        }
+
+        let allowed = match self.reads_as_participant_or_moderator(pool, voice_channel_id).await {
+            Ok(allowed) => allowed,
+            Err(e) => {
+                tracing::warn!(channel = %channel_id, error = %e, "Failed to check voice chat access");
+                false
+            }
+        };
+        self.checked.insert(channel_id, (allowed, Instant::now()));
+        allowed
+    }
+
+    /// In the call lately, or moderating the voice channel.
+    async fn reads_as_participant_or_moderator(
+        &self,
+        pool: &sqlx::AnyPool,
+        voice_channel_id: Uuid,
+    ) -> Result<bool, sqlx::Error> {
+        if moderates(pool, voice_channel_id, self.user_id).await? {
+            return Ok(true);
+        }
+        let since = chrono::Utc::now() - chrono::Duration::hours(voice_chat::HISTORY_HOURS);
+        voice_chat::has_participated(pool, voice_channel_id, self.user_id, since).await
+    }
+}
+
+/// Whether `user_id` has MANAGE_MESSAGES in the voice channel, and so reads
+/// its chat without joining the call.
+async fn moderates(pool: &sqlx::AnyPool, voice_channel_id: Uuid, user_id: Uuid) -> Result<bool, sqlx::Error> {
+    let Some(voice) = channels::find_by_id(pool, voice_channel_id).await? else {
+        return Ok(false);
+    };
+    let Some(server_id) = voice.server_id else {
+        return Ok(false);
+    };
+    let Some(server) = servers::find_by_id(pool, server_id).await? else {
+        return Ok(false);
+    };
+    if server.owner_id == user_id {
+        return Ok(true);
+    }
+    let Some(member) = members::find_member(pool, user_id, server_id).await? else {
+        return Ok(false);
+    };
+
+    let server_roles = roles::list_server_roles(pool, server_id).await?;
+    let everyone = server_roles.iter().find(|r| r.is_default);
+    let base = everyone
+        .map(|r| Permissions::from_bits_truncate(r.permissions))
+        .unwrap_or_else(Permissions::default_everyone);
+    let role_permissions: Vec<Permissions> = server_roles
+        .iter()
+        .filter(|r| member.roles.contains(&r.id))
+        .map(|r| Permissions::from_bits_truncate(r.permissions))
+        .collect();
+    let overwrites: Vec<PermissionOverwrite> =
+        serde_json::from_value(voice.permission_overwrites.clone()).unwrap_or_default();
+    let permissions = compute_permissions(
+        base,
+        &role_permissions,
+        &overwrites,
+        &member.roles,
+        user_id,
+        everyone.map(|r| r.id).unwrap_or(server.id),
+    );
+    Ok(permissions.has(Permissions::MANAGE_MESSAGES))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nexus_db::{Database, DbBackend};
+
+    async fn database() -> Database {
+        sqlx::any::install_default_drivers();
+        // Every connection to `sqlite::memory:` is a separate database.
+        let pool = sqlx::any::AnyPoolOptions::new()
+            .max_connections(1)
+            .min_connections(1)
+            .idle_timeout(None)
+            .max_lifetime(None)
+            .connect("sqlite::memory:")
+            .await
+            .expect("in-memory SQLite");
+        let db = Database {
+            pool,
+            redis: None,
+            backend: DbBackend::Sqlite,
+        };
+        db.migrate().await.expect("lite migrations");
+        db
+    }
+
+    async fn exec(pool: &sqlx::AnyPool, sql: &str, args: &[String]) {
+        let mut query = sqlx::query(sql);
+        for arg in args {
+            query = query.bind(arg.clone());
+        }
+        query.execute(pool).await.expect(sql);
+    }
+
+    #[tokio::test]
+    async fn moderators_read_voice_chats_without_joining() {
+        let db = database().await;
+        let pool = &db.pool;
+        let [owner, moderator, member] = [Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4()];
+        let [server, everyone, mods, voice, chat] =
+            [Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4()];
+
+        for (id, name) in [(owner, "owner"), (moderator, "moderator"), (member, "member")] {
+            exec(
+                pool,
+                "INSERT INTO users (id, username, password_hash) VALUES (?, ?, 'x')",
+                &[id.to_string(), name.into()],
+            )
+            .await;
+        }
+        exec(
+            pool,
+            "INSERT INTO servers (id, name, owner_id) VALUES (?, 'Voice', ?)",
+            &[server.to_string(), owner.to_string()],
+        )
+        .await;
+        exec(
+            pool,
+            "INSERT INTO roles (id, server_id, name, permissions, is_default) VALUES (?, ?, 'everyone', ?, 1)",
+            &[everyone.to_string(), server.to_string(), Permissions::default_everyone().bits().to_string()],
+        )
+        .await;
+        exec(
+            pool,
+            "INSERT INTO roles (id, server_id, name, position, permissions) VALUES (?, ?, 'mods', 1, ?)",
+            &[mods.to_string(), server.to_string(), Permissions::MANAGE_MESSAGES.bits().to_string()],
+        )
+        .await;
+        let moderator_roles = format!("[\"{mods}\"]");
+        for (user, roles) in [(owner, "[]"), (moderator, moderator_roles.as_str()), (member, "[]")] {
+            exec(
+                pool,
+                "INSERT INTO members (user_id, server_id, roles) VALUES (?, ?, ?)",
+                &[user.to_string(), server.to_string(), roles.into()],
+            )
+            .await;
+        }
+        exec(
+            pool,
+            "INSERT INTO channels (id, server_id, channel_type, name) VALUES (?, ?, 'voice', 'General')",
+            &[voice.to_string(), server.to_string()],
+        )
+        .await;
+        exec(
+            pool,
+            "INSERT INTO channels (id, server_id, channel_type, name, voice_channel_id) VALUES (?, ?, 'text', 'General', ?)",
+            &[chat.to_string(), server.to_string(), voice.to_string()],
+        )
+        .await;
+
+        let chats = VoiceChats::default();
+        // Neither has joined the call; only the moderator reads along.
+        assert!(VoiceChatAccess::new(moderator).may_read(&chats, pool, chat).await);
+        assert!(!VoiceChatAccess::new(member).may_read(&chats, pool, chat).await);
+        // Channels that aren't voice chats are left to the usual checks.
+        assert!(VoiceChatAccess::new(member).may_read(&chats, pool, voice).await);
+    }
+}
@@ -0,0 +1,9 @@
+// Generates the tonic client/server stubs. Requires `protoc` on PATH (or
+// `PROTOC` pointing at it).
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    tonic_build::configure().compile_protos(
+        &["proto/nexus/internal/v1/internal.proto"],
+        &["proto"],
+    )?;
+    Ok(())
+}
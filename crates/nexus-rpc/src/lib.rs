@@ -0,0 +1,131 @@
+//! # nexus-rpc
+//!
+//! Internal gRPC API between Nexus server roles.
+//!
+//! In a split-role deployment the gateway and voice nodes don't talk to the
+//! database directly for auth or permissions; they call the API/worker tier
+//! over the `nexus.internal.v1.Internal` service instead:
+//!
+//! ```text
+//!  gateway node ──┐  LookupSession / CheckPermission / PublishEvent
+//!                 ├──────────────────────────────────────────────►  API tier
+//!  voice node   ──┘  ◄──────────── SubscribeEvents (stream) ────────
+//! ```
+//!
+//! The server side lives in `nexus-api` (`rpc.rs`); this crate holds the
+//! generated stubs, the shared-secret interceptors, and conversions to the
+//! in-process [`GatewayEvent`] type.
+
+use nexus_common::gateway_event::GatewayEvent;
+use tonic::metadata::MetadataValue;
+use tonic::service::interceptor::InterceptedService;
+use tonic::transport::{Channel, Endpoint};
+use tonic::{Request, Status};
+use uuid::Uuid;
+
+/// Generated protobuf types and service stubs.
+pub mod pb {
+    tonic::include_proto!("nexus.internal.v1");
+}
+
+pub use pb::internal_client::InternalClient;
+pub use pb::internal_server::{Internal, InternalServer};
+
+/// Metadata header carrying the shared internal token.
+pub const TOKEN_METADATA_KEY: &str = "x-nexus-internal-token";
+
+/// Server-side interceptor rejecting calls without the shared token.
+#[derive(Clone)]
+pub struct RequireToken {
+    token: String,
+}
+
+impl RequireToken {
+    pub fn new(token: impl Into<String>) -> Self {
+        Self { token: token.into() }
+    }
+}
+
+impl tonic::service::Interceptor for RequireToken {
+    fn call(&mut self, request: Request<()>) -> Result<Request<()>, Status> {
+        let presented = request
+            .metadata()
+            .get(TOKEN_METADATA_KEY)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default();
+        if constant_time_eq(presented.as_bytes(), self.token.as_bytes()) {
+            Ok(request)
+        } else {
+            Err(Status::unauthenticated("invalid internal token"))
+        }
+    }
+}
+
+/// Client-side interceptor attaching the shared token to every call.
+#[derive(Clone)]
+pub struct AttachToken {
+    token: MetadataValue<tonic::metadata::Ascii>,
+}
+
+impl tonic::service::Interceptor for AttachToken {
+    fn call(&mut self, mut request: Request<()>) -> Result<Request<()>, Status> {
+        request.metadata_mut().insert(TOKEN_METADATA_KEY, self.token.clone());
+        Ok(request)
+    }
+}
+
+/// An authenticated internal API client.
+pub type Client = InternalClient<InterceptedService<Channel, AttachToken>>;
+
+/// Connect to the API tier at `endpoint` (e.g. `http://api-1:50051`).
+pub async fn connect(endpoint: &str, token: &str) -> Result<Client, Box<dyn std::error::Error + Send + Sync>> {
+    let token = MetadataValue::try_from(token)?;
+    let channel = Endpoint::from_shared(endpoint.to_owned())?
+        .connect_timeout(std::time::Duration::from_secs(5))
+        .connect()
+        .await?;
+    Ok(InternalClient::with_interceptor(channel, AttachToken { token }))
+}
+
+// ─── Event conversions ───────────────────────────────────────────────────────
+
+impl From<&GatewayEvent> for pb::Event {
+    fn from(e: &GatewayEvent) -> Self {
+        Self {
+            event_type: e.event_type.clone(),
+            data_json: e.data.to_string(),
+            server_id: e.server_id.map(|id| id.to_string()),
+            channel_id: e.channel_id.map(|id| id.to_string()),
+            user_id: e.user_id.map(|id| id.to_string()),
+            trace_context: e.trace_context.clone(),
+        }
+    }
+}
+
+impl TryFrom<pb::Event> for GatewayEvent {
+    type Error = Status;
+
+    fn try_from(e: pb::Event) -> Result<Self, Status> {
+        let parse_id = |field: &str, v: Option<String>| {
+            v.map(|s| Uuid::parse_str(&s))
+                .transpose()
+                .map_err(|_| Status::invalid_argument(format!("{field} is not a UUID")))
+        };
+        Ok(GatewayEvent {
+            data: serde_json::from_str(&e.data_json)
+                .map_err(|err| Status::invalid_argument(format!("data_json: {err}")))?,
+            server_id: parse_id("server_id", e.server_id)?,
+            channel_id: parse_id("channel_id", e.channel_id)?,
+            user_id: parse_id("user_id", e.user_id)?,
+            event_type: e.event_type,
+            trace_context: e.trace_context,
+        })
+    }
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
@@ -0,0 +1,178 @@
+//! `nexus admin …` — account management straight against the database, for
+//! operators without a staff account (or SQL) to fall back on.
+
+use std::io::BufRead;
+
+use anyhow::Context;
+use clap::Subcommand;
+use nexus_common::models::user::{user_flags, CreateUserRequest, User};
+use nexus_db::repository::{sessions, users};
+use nexus_db::Database;
+use validator::Validate;
+
+#[derive(Subcommand)]
+pub enum AdminCommand {
+    /// Create an account. Registration mode and invites don't apply.
+    CreateUser {
+        username: String,
+
+        /// Email, for password recovery.
+        #[arg(long)]
+        email: Option<String>,
+
+        /// Make the account an instance admin (staff).
+        #[arg(long)]
+        instance_admin: bool,
+
+        /// Read the password from the first line of stdin instead of
+        /// generating one.
+        #[arg(long)]
+        password_stdin: bool,
+    },
+
+    /// Set a new password and sign the account out everywhere.
+    ResetPassword {
+        username: String,
+
+        /// Read the password from the first line of stdin instead of
+        /// generating one.
+        #[arg(long)]
+        password_stdin: bool,
+    },
+
+    /// Make an account an instance admin (staff), which unlocks the admin API.
+    GrantInstanceAdmin {
+        username: String,
+
+        /// Take instance admin away instead.
+        #[arg(long)]
+        revoke: bool,
+    },
+}
+
+pub async fn run(command: AdminCommand, db: &Database) -> anyhow::Result<()> {
+    match command {
+        AdminCommand::CreateUser {
+            username,
+            email,
+            instance_admin,
+            password_stdin,
+        } => create_user(db, username, email, instance_admin, password_stdin).await,
+        AdminCommand::ResetPassword {
+            username,
+            password_stdin,
+        } => reset_password(db, &username, password_stdin).await,
+        AdminCommand::GrantInstanceAdmin { username, revoke } => grant_instance_admin(db, &username, revoke).await,
+    }
+}
+
+async fn create_user(
+    db: &Database,
+    username: String,
+    email: Option<String>,
+    instance_admin: bool,
+    password_stdin: bool,
+) -> anyhow::Result<()> {
+    let (password, generated) = password(password_stdin)?;
+    // The same rules as registering through the API
+    let request = CreateUserRequest {
+        username,
+        password,
+        email,
+        invite_code: None,
+        registration_token: None,
+        reason: None,
+        challenge: None,
+        challenge_response: None,
+    };
+    request.validate()?;
+
+    anyhow::ensure!(
+        users::find_by_username(&db.pool, &request.username).await?.is_none(),
+        "username '{}' is taken",
+        request.username
+    );
+    if let Some(email) = &request.email {
+        anyhow::ensure!(
+            users::find_by_email(&db.pool, email).await?.is_none(),
+            "email '{email}' is already in use"
+        );
+    }
+
+    let password_hash = hash(&request.password)?;
+    let user = users::create_user(
+        &db.pool,
+        nexus_common::snowflake::generate_id(),
+        &request.username,
+        request.email.as_deref(),
+        &password_hash,
+    )
+    .await?;
+    if instance_admin {
+        users::update_flags(&db.pool, user.id, user_flags::STAFF, 0).await?;
+    }
+
+    println!("Created {} ({})", user.username, user.id);
+    if instance_admin {
+        println!("{} is an instance admin", user.username);
+    }
+    if generated {
+        println!("Password: {}", request.password);
+    }
+    Ok(())
+}
+
+async fn reset_password(db: &Database, username: &str, password_stdin: bool) -> anyhow::Result<()> {
+    let user = find(db, username).await?;
+    let (password, generated) = password(password_stdin)?;
+    anyhow::ensure!(
+        (8..=128).contains(&password.chars().count()),
+        "Password must be 8-128 characters"
+    );
+
+    users::update_password(&db.pool, user.id, &hash(&password)?).await?;
+    let revoked = sessions::delete_others(&db.pool, user.id, None).await?;
+
+    println!("Reset the password of {}; signed out {revoked} session(s)", user.username);
+    if generated {
+        println!("Password: {password}");
+    }
+    Ok(())
+}
+
+async fn grant_instance_admin(db: &Database, username: &str, revoke: bool) -> anyhow::Result<()> {
+    let user = find(db, username).await?;
+    let (add, remove) = if revoke { (0, user_flags::STAFF) } else { (user_flags::STAFF, 0) };
+    users::update_flags(&db.pool, user.id, add, remove).await?;
+
+    if revoke {
+        println!("{} is no longer an instance admin", user.username);
+    } else {
+        println!("{} is now an instance admin", user.username);
+    }
+    Ok(())
+}
+
+async fn find(db: &Database, username: &str) -> anyhow::Result<User> {
+    users::find_by_username(&db.pool, username)
+        .await?
+        .with_context(|| format!("no user named '{username}'"))
+}
+
+/// The password to set, and whether it was generated (and so needs showing).
+fn password(from_stdin: bool) -> anyhow::Result<(String, bool)> {
+    if from_stdin {
+        let mut line = String::new();
+        std::io::stdin().lock().read_line(&mut line)?;
+        let password = line.trim_end_matches(['\r', '\n']).to_owned();
+        anyhow::ensure!(!password.is_empty(), "no password on stdin");
+        return Ok((password, false));
+    }
+    use rand::Rng;
+    let bytes: [u8; 16] = rand::rng().random();
+    Ok((hex::encode(bytes), true))
+}
+
+fn hash(password: &str) -> anyhow::Result<String> {
+    nexus_api::auth::hash_password(password).map_err(|e| anyhow::anyhow!("hashing password: {e}"))
+}
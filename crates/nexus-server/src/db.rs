@@ -0,0 +1,41 @@
+//! `nexus db …` — database maintenance.
+
+use clap::Subcommand;
+use nexus_db::Database;
+
+#[derive(Subcommand)]
+pub enum DbCommand {
+    /// Apply pending migrations. `serve` does this on startup too.
+    Migrate {
+        /// List what would be applied without applying it.
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+pub async fn run(command: DbCommand, db: &Database) -> anyhow::Result<()> {
+    match command {
+        DbCommand::Migrate { dry_run } => migrate(db, dry_run).await,
+    }
+}
+
+async fn migrate(db: &Database, dry_run: bool) -> anyhow::Result<()> {
+    let status = db.migration_status().await?;
+    for (version, description) in &status.modified {
+        println!("! {version} {description} was applied but has changed since");
+    }
+    if status.pending.is_empty() {
+        println!("No pending migrations");
+        return Ok(());
+    }
+    for (version, description) in &status.pending {
+        println!("  {version} {description}");
+    }
+    if dry_run {
+        println!("{} migration(s) would be applied", status.pending.len());
+        return Ok(());
+    }
+    db.migrate().await?;
+    println!("Applied {} migration(s)", status.pending.len());
+    Ok(())
+}
@@ -0,0 +1,100 @@
+//! `nexus fed …` — offline federation tools for debugging interop.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use clap::Subcommand;
+use nexus_federation::keys::ServerKeyDocument;
+use nexus_federation::signatures::{canonical_json, signed_content};
+use nexus_federation::types::VerifyKey;
+use serde_json::Value;
+
+#[derive(Subcommand)]
+pub enum FedCommand {
+    /// Check the signatures on a federation event.
+    ///
+    /// Keys come from a saved key document (the origin's
+    /// `/_nexus/key/v2/server` response) and/or `--key` arguments.
+    VerifyEvent {
+        /// Event JSON file.
+        file: PathBuf,
+
+        /// Saved key document of the signing server.
+        #[arg(long)]
+        keys: Option<PathBuf>,
+
+        /// A verify key, as `ed25519:<id>=<base64url public key>`. Repeatable.
+        #[arg(long = "key", value_name = "KEY_ID=PUBLIC_KEY")]
+        key: Vec<String>,
+
+        /// Server whose signatures to check (default: the event's `origin`,
+        /// or the key document's `server_name`).
+        #[arg(long)]
+        origin: Option<String>,
+
+        /// Print the canonical JSON the signatures cover.
+        #[arg(long)]
+        print_canonical: bool,
+    },
+}
+
+pub fn run(command: FedCommand) -> anyhow::Result<()> {
+    match command {
+        FedCommand::VerifyEvent {
+            file,
+            keys,
+            key,
+            origin,
+            print_canonical,
+        } => verify_event(file, keys, key, origin, print_canonical),
+    }
+}
+
+fn verify_event(
+    file: PathBuf,
+    keys: Option<PathBuf>,
+    key_args: Vec<String>,
+    origin: Option<String>,
+    print_canonical: bool,
+) -> anyhow::Result<()> {
+    let event = read_json(&file)?;
+
+    let mut verify_keys: HashMap<String, VerifyKey> = HashMap::new();
+    let mut document_origin = None;
+    if let Some(path) = keys {
+        let document: ServerKeyDocument = serde_json::from_value(read_json(&path)?)
+            .with_context(|| format!("{} is not a key document", path.display()))?;
+        verify_keys.extend(document.verify_keys);
+        document_origin = Some(document.server_name);
+    }
+    for arg in key_args {
+        let (key_id, key) = arg
+            .split_once('=')
+            .with_context(|| format!("--key '{arg}' should be KEY_ID=PUBLIC_KEY"))?;
+        verify_keys.insert(key_id.to_owned(), VerifyKey { key: key.to_owned() });
+    }
+    anyhow::ensure!(!verify_keys.is_empty(), "Pass --keys or at least one --key");
+
+    let origin = origin
+        .or_else(|| event.get("origin").and_then(Value::as_str).map(str::to_owned))
+        .or(document_origin)
+        .context("No --origin given and the event has no 'origin'")?;
+
+    if print_canonical {
+        println!("{}", canonical_json(&signed_content(&event))?);
+    }
+
+    let verified = nexus_federation::verify_event(&event, &origin, &verify_keys)
+        .with_context(|| format!("Signature check failed for {origin}"))?;
+    for key_id in verified {
+        println!("✓ valid signature by {origin} ({key_id})");
+    }
+    Ok(())
+}
+
+fn read_json(path: &Path) -> anyhow::Result<Value> {
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("Couldn't read {}", path.display()))?;
+    serde_json::from_str(&text).with_context(|| format!("{} is not valid JSON", path.display()))
+}
@@ -0,0 +1,216 @@
+//! TCP listeners for the server's services.
+//!
+//! Hosts come from config (`server.host`, `server.gateway_host`, ...). An
+//! IPv6 wildcard (`::`) is bound dual-stack, so one listener takes IPv4 and
+//! IPv6 clients whatever the OS default for `IPV6_V6ONLY` is.
+//!
+//! With built-in TLS (see [`crate::tls`]) the HTTP listeners are wrapped in
+//! a [`Listener`] that completes the handshake before handing a connection
+//! to axum.
+
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use socket2::{Domain, Protocol, Socket, Type};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+use tokio_rustls::server::TlsStream;
+use tokio_rustls::TlsAcceptor;
+
+/// How long a client gets to complete the TLS handshake.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Parse a host setting; brackets around IPv6 addresses are allowed.
+pub fn parse_host(host: &str, key: &str) -> anyhow::Result<IpAddr> {
+    let host = host.trim().trim_start_matches('[').trim_end_matches(']');
+    host.parse()
+        .map_err(|_| anyhow::anyhow!("{key} must be an IP address, got '{host}'"))
+}
+
+/// Where to reach a listener bound at `addr` from this host: loopback for
+/// wildcard binds, the address itself otherwise.
+pub fn local_target(addr: SocketAddr) -> SocketAddr {
+    let ip = match addr.ip() {
+        IpAddr::V4(ip) if ip.is_unspecified() => IpAddr::V4(Ipv4Addr::LOCALHOST),
+        IpAddr::V6(ip) if ip.is_unspecified() => IpAddr::V6(Ipv6Addr::LOCALHOST),
+        ip => ip,
+    };
+    SocketAddr::new(ip, addr.port())
+}
+
+pub fn bind(addr: SocketAddr) -> anyhow::Result<TcpListener> {
+    let socket = Socket::new(Domain::for_address(addr), Type::STREAM, Some(Protocol::TCP))?;
+    if addr.is_ipv6() && addr.ip().is_unspecified() {
+        socket.set_only_v6(false)?;
+    }
+    // Lets a restarted server rebind while old connections sit in TIME_WAIT;
+    // Windows' SO_REUSEADDR would let two servers share the port instead.
+    #[cfg(unix)]
+    socket.set_reuse_address(true)?;
+    socket.set_nonblocking(true)?;
+    socket
+        .bind(&addr.into())
+        .map_err(|e| anyhow::anyhow!("binding {addr}: {e}"))?;
+    socket.listen(1024)?;
+    Ok(TcpListener::from_std(socket.into())?)
+}
+
+/// An HTTP listener, speaking TLS when built-in TLS is on.
+pub enum Listener {
+    Plain(TcpListener),
+    Tls {
+        handshaken: mpsc::Receiver<(TlsStream<TcpStream>, SocketAddr)>,
+        local_addr: SocketAddr,
+    },
+}
+
+impl Listener {
+    /// Wrap `listener`, with TLS if `tls` is given. Handshakes run in the
+    /// background, so a slow client doesn't hold up the others.
+    pub fn new(listener: TcpListener, tls: Option<TlsAcceptor>) -> io::Result<Self> {
+        let Some(acceptor) = tls else {
+            return Ok(Self::Plain(listener));
+        };
+        let local_addr = listener.local_addr()?;
+        let (tx, handshaken) = mpsc::channel(64);
+        tokio::spawn(handshake(listener, acceptor, tx));
+        Ok(Self::Tls { handshaken, local_addr })
+    }
+}
+
+/// Accept connections and pass on those that complete a handshake, until
+/// the [`Listener`] is dropped.
+async fn handshake(
+    mut listener: TcpListener,
+    acceptor: TlsAcceptor,
+    handshaken: mpsc::Sender<(TlsStream<TcpStream>, SocketAddr)>,
+) {
+    loop {
+        let (stream, addr) = tokio::select! {
+            () = handshaken.closed() => return,
+            accepted = axum::serve::Listener::accept(&mut listener) => accepted,
+        };
+        let (acceptor, handshaken) = (acceptor.clone(), handshaken.clone());
+        tokio::spawn(async move {
+            match tokio::time::timeout(HANDSHAKE_TIMEOUT, acceptor.accept(stream)).await {
+                Ok(Ok(stream)) => {
+                    let _ = handshaken.send((stream, addr)).await;
+                }
+                Ok(Err(e)) => tracing::debug!(%addr, error = %e, "TLS handshake failed"),
+                Err(_) => tracing::debug!(%addr, "TLS handshake timed out"),
+            }
+        });
+    }
+}
+
+impl axum::serve::Listener for Listener {
+    type Io = Stream;
+    type Addr = SocketAddr;
+
+    async fn accept(&mut self) -> (Stream, SocketAddr) {
+        match self {
+            Self::Plain(listener) => {
+                let (stream, addr) = axum::serve::Listener::accept(listener).await;
+                (Stream::Plain(stream), addr)
+            }
+            Self::Tls { handshaken, .. } => match handshaken.recv().await {
+                Some((stream, addr)) => (Stream::Tls(Box::new(stream)), addr),
+                // The accept task outlives the receiver
+                None => std::future::pending().await,
+            },
+        }
+    }
+
+    fn local_addr(&self) -> io::Result<SocketAddr> {
+        match self {
+            Self::Plain(listener) => listener.local_addr(),
+            Self::Tls { local_addr, .. } => Ok(*local_addr),
+        }
+    }
+}
+
+/// A connection accepted by a [`Listener`].
+pub enum Stream {
+    Plain(TcpStream),
+    Tls(Box<TlsStream<TcpStream>>),
+}
+
+impl AsyncRead for Stream {
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Self::Plain(stream) => Pin::new(stream).poll_read(cx, buf),
+            Self::Tls(stream) => Pin::new(stream).poll_read(cx, buf),
+        }
+    }
+}
+
+impl AsyncWrite for Stream {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        match self.get_mut() {
+            Self::Plain(stream) => Pin::new(stream).poll_write(cx, buf),
+            Self::Tls(stream) => Pin::new(stream).poll_write(cx, buf),
+        }
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        match self.get_mut() {
+            Self::Plain(stream) => Pin::new(stream).poll_write_vectored(cx, bufs),
+            Self::Tls(stream) => Pin::new(stream).poll_write_vectored(cx, bufs),
+        }
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        match self {
+            Self::Plain(stream) => stream.is_write_vectored(),
+            Self::Tls(stream) => stream.is_write_vectored(),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Self::Plain(stream) => Pin::new(stream).poll_flush(cx),
+            Self::Tls(stream) => Pin::new(stream).poll_flush(cx),
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Self::Plain(stream) => Pin::new(stream).poll_shutdown(cx),
+            Self::Tls(stream) => Pin::new(stream).poll_shutdown(cx),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hosts_parse_with_or_without_brackets() {
+        assert_eq!(parse_host("::", "h").unwrap(), IpAddr::V6(Ipv6Addr::UNSPECIFIED));
+        assert_eq!(parse_host("[::1]", "h").unwrap(), IpAddr::V6(Ipv6Addr::LOCALHOST));
+        assert_eq!(parse_host(" 0.0.0.0 ", "h").unwrap(), IpAddr::V4(Ipv4Addr::UNSPECIFIED));
+        assert!(parse_host("localhost", "h").is_err());
+    }
+
+    #[tokio::test]
+    async fn dual_stack_listener_accepts_ipv4() {
+        // Not every sandbox has IPv6
+        let Ok(listener) = bind("[::]:0".parse().unwrap()) else {
+            return;
+        };
+        let port = listener.local_addr().unwrap().port();
+        let client = tokio::net::TcpStream::connect((Ipv4Addr::LOCALHOST, port));
+        let (accepted, connected) = tokio::join!(listener.accept(), client);
+        connected.unwrap();
+        assert!(accepted.unwrap().1.ip().to_canonical().is_ipv4());
+    }
+}
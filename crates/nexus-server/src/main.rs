@@ -6,7 +6,9 @@
 //! - Voice Server (WebRTC SFU + signaling)
 //!
 //! All services can run in a single process (simple deployment)
-//! or be split into separate processes (horizontal scaling).
+//! or be split into separate processes (horizontal scaling) with
+//! `nexus serve --role api|gateway|voice`, which share events and voice
+//! state through Redis.
 //!
 //! ## Lite mode
 //!
@@ -14,21 +16,34 @@
 //! - SQLite database (`nexus.db` in the current directory)
 //! - Local filesystem uploads (`./data/uploads/`)
 //! - No Docker, no MinIO, no MeiliSearch required.
+//!
+//! ## Administration
+//!
+//! `nexus admin` creates accounts, resets passwords and grants instance
+//! admin, and `nexus db migrate` applies migrations, without the server
+//! running; pass `--lite` for the lite-mode database.
+
+mod admin;
+mod db;
+mod fed;
+mod listen;
+mod prometheus;
+mod telemetry;
+mod tls;
 
 use clap::{Parser, Subcommand};
-use nexus_api::{build_router, AppState};
-use nexus_common::gateway_event::GatewayEvent;
+use nexus_api::{build_router, jobs::status_check::ProbeTargets, AppState};
+use nexus_common::{config::AppConfig, gateway_event::EventBus, shutdown::Shutdown};
 use nexus_db::{
     search::SearchClient,
     storage::{StorageClient, StorageConfig as DbStorageConfig},
     Database,
 };
-use nexus_federation::{FederationClient, KeyManager};
+use nexus_federation::{ActivityPubBridge, ActorKey, BridgeRegistry, FederationClient, KeyManager};
 use nexus_gateway::GatewayState;
-use nexus_voice::VoiceServer;
+use nexus_voice::{cluster::VoiceCluster, network::RtcNetwork, state::VoiceStateManager, VoiceServer};
 use std::net::SocketAddr;
 use std::sync::Arc;
-use tokio::sync::broadcast;
 
 // ── CLI ───────────────────────────────────────────────────────────────────────
 
@@ -63,7 +78,66 @@ enum Command {
         /// Voice signaling port (default: 8082).
         #[arg(long, env = "VOICE_PORT", default_value_t = 8082)]
         voice_port: u16,
+
+        /// Which services this process runs. Anything but `all` needs Redis.
+        #[arg(long, env = "NEXUS_ROLE", value_enum, default_value_t = Role::All)]
+        role: Role,
     },
+
+    /// Manage accounts.
+    Admin {
+        /// Use the lite-mode database (`nexus.db` unless DATABASE_URL is set).
+        #[arg(long, env = "NEXUS_LITE", default_value_t = false, global = true)]
+        lite: bool,
+
+        #[command(subcommand)]
+        command: admin::AdminCommand,
+    },
+
+    /// Database maintenance.
+    Db {
+        /// Use the lite-mode database (`nexus.db` unless DATABASE_URL is set).
+        #[arg(long, env = "NEXUS_LITE", default_value_t = false, global = true)]
+        lite: bool,
+
+        #[command(subcommand)]
+        command: db::DbCommand,
+    },
+
+    /// Federation tools.
+    Fed {
+        #[command(subcommand)]
+        command: fed::FedCommand,
+    },
+}
+
+/// The services a `serve` process runs.
+#[derive(Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum Role {
+    /// Every service, as one process.
+    All,
+    /// REST API, internal gRPC and background jobs.
+    Api,
+    /// WebSocket gateway.
+    Gateway,
+    /// Voice signaling and the SFU.
+    Voice,
+}
+
+impl Role {
+    fn as_str(self) -> &'static str {
+        match self {
+            Role::All => "all",
+            Role::Api => "api",
+            Role::Gateway => "gateway",
+            Role::Voice => "voice",
+        }
+    }
+
+    /// Whether this process runs `service`.
+    fn runs(self, service: Role) -> bool {
+        self == Role::All || self == service
+    }
 }
 
 // ── Entry point ───────────────────────────────────────────────────────────────
@@ -78,7 +152,34 @@ async fn main() -> anyhow::Result<()> {
             port,
             gateway_port,
             voice_port,
-        } => run_server(lite, port, gateway_port, voice_port).await,
+            role,
+        } => run_server(lite, role, port, gateway_port, voice_port).await,
+        Command::Admin { lite, command } => {
+            let db = connect_database(lite).await?;
+            anyhow::ensure!(
+                db.migration_status().await?.pending.is_empty(),
+                "the database has pending migrations; run `nexus db migrate` first"
+            );
+            admin::run(command, &db).await
+        }
+        Command::Db { lite, command } => db::run(command, &connect_database(lite).await?).await,
+        Command::Fed { command } => fed::run(command),
+    }
+}
+
+/// Connect to the configured database, for the offline subcommands.
+async fn connect_database(lite: bool) -> anyhow::Result<Database> {
+    if lite {
+        set_lite_database_default();
+    }
+    let config = nexus_common::config::init()?;
+    Database::connect(config).await
+}
+
+/// SQLite in the current directory, unless a database is configured.
+fn set_lite_database_default() {
+    if std::env::var("DATABASE_URL").is_err() {
+        std::env::set_var("DATABASE_URL", "sqlite://nexus.db?mode=rwc");
     }
 }
 
@@ -86,18 +187,21 @@ async fn main() -> anyhow::Result<()> {
 
 async fn run_server(
     lite: bool,
+    role: Role,
     port: u16,
     gateway_port: u16,
     voice_port: u16,
 ) -> anyhow::Result<()> {
+    anyhow::ensure!(
+        role == Role::All || !lite,
+        "lite mode runs every service in one process; drop --role"
+    );
+
     // ── Lite-mode environment bootstrap ──────────────────────────────────────
     // Before loading config, inject sensible defaults so the server works
     // out-of-the-box without any env vars or config files.
     if lite {
-        // SQLite database in current directory
-        if std::env::var("DATABASE_URL").is_err() {
-            std::env::set_var("DATABASE_URL", "sqlite://nexus.db?mode=rwc");
-        }
+        set_lite_database_default();
         // Auto-generate JWT secret on first run and store in NEXUS_JWT_SECRET
         if std::env::var("JWT_SECRET").is_err() {
             let secret = generate_or_load_lite_secret("nexus.toml")?;
@@ -116,14 +220,23 @@ async fn run_server(
     let config = nexus_common::config::init()?;
 
     // ── Tracing ───────────────────────────────────────────────────────────────
-    tracing_subscriber::fmt()
-        .with_env_filter(
+    use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+    let telemetry = telemetry::Telemetry::init(&config.telemetry)?;
+    tracing_subscriber::registry()
+        .with(
             tracing_subscriber::EnvFilter::try_from_default_env()
                 .unwrap_or_else(|_| "nexus=info,tower_http=info".into()),
         )
-        .with_target(!lite)          // less noisy in lite mode
-        .with_thread_ids(false)
+        .with(
+            tracing_subscriber::fmt::layer()
+                .with_target(!lite)          // less noisy in lite mode
+                .with_thread_ids(false),
+        )
+        .with(telemetry.as_ref().map(|t| tracing_opentelemetry::layer().with_tracer(t.tracer())))
         .init();
+    if telemetry.is_some() {
+        tracing::info!("🔭 Exporting traces to {}", config.telemetry.otlp_endpoint);
+    }
 
     if lite {
         tracing::info!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
@@ -135,20 +248,312 @@ async fn run_server(
         tracing::info!("   Privacy-first. Community-owned. No ID required.");
         tracing::info!("   ─────────────────────────────────────────────");
     }
+    if role != Role::All {
+        tracing::info!("🧩 Running the {} role", role.as_str());
+    }
+
+    // ── IDs ───────────────────────────────────────────────────────────────────
+    // A split process defaults to its own snowflake role, so an API and a
+    // gateway process with the same instance number don't mint the same IDs.
+    let snowflake_role = match role {
+        Role::All => config.snowflake.role.as_str(),
+        _ if config.snowflake.role == "server" => role.as_str(),
+        _ => config.snowflake.role.as_str(),
+    };
+    let worker_id = nexus_common::snowflake::worker_id(snowflake_role, config.snowflake.instance).ok_or_else(|| {
+        anyhow::anyhow!(
+            "invalid snowflake worker: role '{}' instance {} (roles: {}; instance 0-{})",
+            snowflake_role,
+            config.snowflake.instance,
+            nexus_common::snowflake::ROLES.join(", "),
+            nexus_common::snowflake::MAX_INSTANCE,
+        )
+    })?;
+    nexus_common::snowflake::init(worker_id);
+    tracing::info!(
+        "🆔 Snowflake worker {worker_id} ({} #{})",
+        snowflake_role,
+        config.snowflake.instance
+    );
+
+    // ── Metrics ───────────────────────────────────────────────────────────────
+    // Installed first so every service records from the start.
+    let metrics_handle = if config.metrics.enabled {
+        Some(prometheus::install()?)
+    } else {
+        None
+    };
 
     // ── Database ──────────────────────────────────────────────────────────────
     let db = Database::connect(config).await?;
     db.migrate().await?;
     tracing::info!("✅ Database ready");
 
+    anyhow::ensure!(
+        role == Role::All || db.redis.is_some(),
+        "--role {} needs redis.url: separate processes share events and voice state through Redis",
+        role.as_str()
+    );
+
     // ── Event bus ─────────────────────────────────────────────────────────────
-    let (gateway_tx, _) = broadcast::channel::<GatewayEvent>(10_000);
+    // With Redis, every node's events reach every other node's subscribers.
+    let gateway_tx = EventBus::new(config.gateway.event_bus_capacity);
+    if let (Some(redis), Some(url)) = (db.redis.clone(), config.redis.url.as_deref()) {
+        nexus_db::event_relay::spawn(url, redis, gateway_tx.clone())?;
+    }
+
+    // Triggered on SIGTERM / Ctrl-C; everything below drains on it.
+    let shutdown = Shutdown::new();
+
+    // Shared by the API and gateway; Redis-backed when configured so every
+    // node sees every session.
+    let presence = Arc::new(nexus_db::presence::PresenceService::new(db.redis.clone()));
 
     // ── Voice Server ──────────────────────────────────────────────────────────
-    let local_ip: std::net::IpAddr = "127.0.0.1".parse()?;
-    let voice_server = VoiceServer::new(db.clone(), gateway_tx.clone(), local_ip);
-    let voice_state = voice_server.state.voice_state.clone();
+    let voice_server = if role.runs(Role::Voice) {
+        let rtc_network = RtcNetwork::from_config(&config.voice)?;
+        tracing::info!(advertised = ?rtc_network.advertised(), "🎧 Voice media on {}", config.voice.rtc_host);
+        let mut voice_server = VoiceServer::new(db.clone(), gateway_tx.clone(), rtc_network);
+        if let (Some(redis), false) = (db.redis.clone(), config.voice.public_url.is_empty()) {
+            let node_id = if config.voice.node_id.is_empty() {
+                config.voice.public_url.clone()
+            } else {
+                config.voice.node_id.clone()
+            };
+            let heartbeat = std::time::Duration::from_secs(config.voice.heartbeat_secs);
+            let cluster = VoiceCluster::new(node_id, &config.voice.public_url, heartbeat, redis);
+            cluster.heartbeat(&[]).await?;
+            tracing::info!("🎛️  Voice clustering enabled (node {})", cluster.node_id());
+            voice_server = voice_server.with_cluster(cluster);
+            voice_server.spawn_heartbeat(heartbeat);
+        }
+        Some(voice_server)
+    } else {
+        None
+    };
+    // In one process the API reads the voice server's state; split, both
+    // keep it in Redis.
+    let voice_state = match &voice_server {
+        Some(voice_server) => voice_server.state.voice_state.clone(),
+        None => VoiceStateManager::for_redis(db.redis.clone()),
+    };
+
+    let server = &config.server;
+    let api_addr = SocketAddr::new(listen::parse_host(&server.host, "server.host")?, port);
+    let gateway_addr = SocketAddr::new(
+        listen::parse_host(server.host_or(&server.gateway_host), "server.gateway_host")?,
+        gateway_port,
+    );
+    let voice_addr = SocketAddr::new(
+        listen::parse_host(server.host_or(&server.voice_host), "server.voice_host")?,
+        voice_port,
+    );
+
+    // ── REST API and background jobs ─────────────────────────────────────────
+    let api_state = if role.runs(Role::Api) {
+        Some(build_api_state(config, lite, port, &db, gateway_tx.clone(), voice_state, presence.clone()).await?)
+    } else {
+        None
+    };
+    let outbox_worker = match &api_state {
+        Some(api_state) => {
+            // Services in other processes aren't probed from here
+            let probes = ProbeTargets {
+                gateway: role.runs(Role::Gateway).then(|| listen::local_target(gateway_addr)),
+                voice: role.runs(Role::Voice).then(|| listen::local_target(voice_addr)),
+            };
+            spawn_api_jobs(api_state, config, probes, &shutdown)?
+        }
+        None => None,
+    };
+
+    // ── Internal gRPC (gateway / voice nodes → API tier) ─────────────────────
+    let rpc_addr = SocketAddr::new(
+        listen::parse_host(server.host_or(&config.rpc.host), "rpc.host")?,
+        config.rpc.port,
+    );
+    let rpc_service = match &api_state {
+        Some(api_state) if config.rpc.enabled => {
+            anyhow::ensure!(
+                !config.rpc.token.is_empty(),
+                "rpc.token must be set when the internal gRPC API is enabled"
+            );
+            Some(nexus_api::rpc::InternalService::new(Arc::new(api_state.clone())).into_server(&config.rpc.token))
+        }
+        _ => None,
+    };
+
+    // ── WebSocket Gateway ─────────────────────────────────────────────────────
+    let gateway_state = role.runs(Role::Gateway).then(|| {
+        GatewayState::with_broadcast(db.clone(), gateway_tx.clone(), presence.clone()).with_shutdown(shutdown.clone())
+    });
+
+    let metrics_addr = SocketAddr::new(
+        listen::parse_host(&config.metrics.host, "metrics.host")?,
+        config.metrics.port,
+    );
+    let metrics_router = metrics_handle.map(|handle| prometheus::router(handle, db.clone()));
+
+    let api_router = api_state.map(build_router);
+    let gateway_drained = gateway_state.as_ref().map(GatewayState::drained);
+    let gateway_router = gateway_state.map(nexus_gateway::build_router);
+
+    // ── Voice Signaling ───────────────────────────────────────────────────────
+    let voice_router = voice_server.as_ref().map(VoiceServer::build_router);
+
+    // ── TLS ───────────────────────────────────────────────────────────────────
+    // API, gateway and voice signaling only; gRPC and metrics stay internal.
+    anyhow::ensure!(
+        !config.tls.enabled || role == Role::All,
+        "built-in TLS needs every service in one process; put a TLS proxy in front of split roles"
+    );
+    let tls = tls::Tls::start(&config.tls, api_addr.ip(), port, &shutdown).await?;
+    let acceptor = tls.as_ref().map(tls::Tls::acceptor);
+    let (http, ws) = if tls.is_some() { ("https", "wss") } else { ("http", "ws") };
+
+    if lite {
+        tracing::info!("");
+        tracing::info!("  ✅  Nexus is running!");
+        tracing::info!("  🌐  API:     {http}://127.0.0.1:{port}");
+        tracing::info!("  🔌  Gateway: {ws}://127.0.0.1:{gateway_port}");
+        tracing::info!("  🎙️   Voice:   {ws}://127.0.0.1:{voice_port}");
+        tracing::info!("");
+        tracing::info!("  Open your desktop client and connect to:");
+        tracing::info!("  {http}://127.0.0.1:{port}");
+        tracing::info!("");
+    } else {
+        if api_router.is_some() {
+            tracing::info!("📡 REST API      → {http}://{api_addr}");
+        }
+        if gateway_router.is_some() {
+            tracing::info!("🔌 Gateway       → {ws}://{gateway_addr}");
+        }
+        if voice_router.is_some() {
+            tracing::info!("🎙️  Voice server  → {ws}://{voice_addr}");
+        }
+        if rpc_service.is_some() {
+            tracing::info!("🛰️  Internal gRPC → http://{rpc_addr}");
+        }
+    }
+    if metrics_router.is_some() {
+        tracing::info!("📈 Metrics       → http://{metrics_addr}/metrics");
+    }
+
+    // Each listener stops accepting once `shutdown` triggers and finishes
+    // the requests it has in flight.
+    let servers = async {
+        tokio::try_join!(
+            async {
+                if let Some(router) = api_router {
+                    let listener = listen::Listener::new(listen::bind(api_addr)?, acceptor.clone())?;
+                    axum::serve(
+                        listener,
+                        router.into_make_service_with_connect_info::<SocketAddr>(),
+                    )
+                    .with_graceful_shutdown(shutdown.triggered())
+                    .await?;
+                }
+                Ok::<_, anyhow::Error>(())
+            },
+            async {
+                if let Some(router) = gateway_router {
+                    let listener = listen::Listener::new(listen::bind(gateway_addr)?, acceptor.clone())?;
+                    axum::serve(
+                        listener,
+                        router.into_make_service_with_connect_info::<SocketAddr>(),
+                    )
+                    .with_graceful_shutdown(shutdown.triggered())
+                    .await?;
+                }
+                Ok::<_, anyhow::Error>(())
+            },
+            async {
+                if let Some(router) = voice_router {
+                    let listener = listen::Listener::new(listen::bind(voice_addr)?, acceptor.clone())?;
+                    axum::serve(listener, router)
+                        .with_graceful_shutdown(shutdown.triggered())
+                        .await?;
+                }
+                Ok::<_, anyhow::Error>(())
+            },
+            async {
+                if let Some(router) = metrics_router {
+                    let listener = listen::bind(metrics_addr)?;
+                    axum::serve(listener, router)
+                        .with_graceful_shutdown(shutdown.triggered())
+                        .await?;
+                }
+                Ok::<_, anyhow::Error>(())
+            },
+            async {
+                if let Some(service) = rpc_service {
+                    let incoming = tonic::transport::server::TcpIncoming::from_listener(
+                        listen::bind(rpc_addr)?,
+                        true,
+                        None,
+                    )
+                    .map_err(|e| anyhow::anyhow!("binding {rpc_addr}: {e}"))?;
+                    tonic::transport::Server::builder()
+                        .add_service(service)
+                        .serve_with_incoming_shutdown(incoming, shutdown.triggered())
+                        .await?;
+                }
+                Ok::<_, anyhow::Error>(())
+            },
+        )
+    };
+    tokio::pin!(servers);
 
+    tokio::select! {
+        result = &mut servers => {
+            result?;
+        }
+        () = shutdown_signal() => {
+            let timeout = std::time::Duration::from_secs(config.server.shutdown_timeout_secs);
+            tracing::info!("Shutting down (waiting up to {}s)", timeout.as_secs());
+            let drain = async {
+                // Voice first: its clients are told over signaling
+                // connections, which stopping the listener doesn't close.
+                if let Some(voice_server) = &voice_server {
+                    voice_server.shutdown().await;
+                }
+                shutdown.trigger();
+                if let Err(e) = (&mut servers).await {
+                    tracing::error!(error = %e, "Server error while shutting down");
+                }
+                if let Some(drained) = gateway_drained {
+                    drained.await;
+                }
+                if let Some(worker) = outbox_worker {
+                    let _ = worker.await;
+                }
+            };
+            if tokio::time::timeout(timeout, drain).await.is_err() {
+                tracing::warn!("Shutdown timed out; exiting with work still in flight");
+            }
+        }
+    }
+
+    db.pool.close().await;
+    if let Some(telemetry) = telemetry {
+        telemetry.shutdown();
+    }
+
+    Ok(())
+}
+
+// ── Helpers ───────────────────────────────────────────────────────────────────
+
+/// Storage, search and federation for the REST API.
+async fn build_api_state(
+    config: &AppConfig,
+    lite: bool,
+    port: u16,
+    db: &Database,
+    gateway_tx: EventBus,
+    voice_state: VoiceStateManager,
+    presence: Arc<nexus_db::presence::PresenceService>,
+) -> anyhow::Result<AppState> {
     // ── Storage ───────────────────────────────────────────────────────────────
     let public_base = std::env::var("NEXUS_PUBLIC_URL")
         .unwrap_or_else(|_| format!("http://127.0.0.1:{port}"));
@@ -192,68 +597,113 @@ async fn run_server(
         federation_key.clone(),
     ));
 
-    // ── REST API ──────────────────────────────────────────────────────────────
-    let api_state = AppState {
+    let mut bridges = BridgeRegistry::new();
+    let activitypub = if config.activitypub.enabled {
+        let key = ActorKey::load_or_generate(&db.pool).await?;
+        let bridge = Arc::new(ActivityPubBridge::new(&config.server.name, key, db.pool.clone()));
+        bridges.register(bridge.clone());
+        Some(bridge)
+    } else {
+        None
+    };
+
+    Ok(AppState {
         db: db.clone(),
-        gateway_tx: gateway_tx.clone(),
-        voice_state: voice_state.clone(),
+        gateway_tx,
+        voice_state,
         storage,
         search,
         server_name: config.server.name.clone(),
         federation_key,
         federation_client,
-    };
-    let api_router = build_router(api_state);
-    let host: std::net::IpAddr = "0.0.0.0".parse()?;
-    let api_addr = SocketAddr::new(host, port);
-    let gateway_addr = SocketAddr::new(host, gateway_port);
-    let voice_addr = SocketAddr::new(host, voice_port);
-
-    // ── WebSocket Gateway ─────────────────────────────────────────────────────
-    let gateway_state = GatewayState::with_broadcast(db.clone(), gateway_tx);
-    let gateway_router = nexus_gateway::build_router(gateway_state);
-
-    // ── Voice Signaling ───────────────────────────────────────────────────────
-    let voice_router = voice_server.build_router();
+        bridges,
+        activitypub,
+        rate_limits: Arc::new(nexus_api::ratelimit::RateLimiter::new(config.rate_limit.clone())),
+        federation_limits: Arc::new(nexus_api::federation_limits::OriginLimiter::new(config.federation.clone())),
+        consents: Arc::new(nexus_api::legal::ConsentCache::new()),
+        live_sessions: Arc::new(nexus_api::session_cache::SessionCache::new()),
+        started_at: chrono::Utc::now(),
+        spam: Arc::new(nexus_api::spam::SpamDetector::new(config.spam.clone())),
+        transcription: nexus_api::transcription::TranscriptionClient::from_config(
+            &config.transcription,
+        )?,
+        media: nexus_api::media::MediaProcessor::from_config(&config.media),
+        presence,
+    })
+}
 
-    if lite {
-        tracing::info!("");
-        tracing::info!("  ✅  Nexus is running!");
-        tracing::info!("  🌐  API:     http://127.0.0.1:{port}");
-        tracing::info!("  🔌  Gateway: ws://127.0.0.1:{gateway_port}");
-        tracing::info!("  🎙️   Voice:   ws://127.0.0.1:{voice_port}");
-        tracing::info!("");
-        tracing::info!("  Open your desktop client and connect to:");
-        tracing::info!("  http://127.0.0.1:{port}");
-        tracing::info!("");
-    } else {
-        tracing::info!("📡 REST API      → http://{api_addr}");
-        tracing::info!("🔌 Gateway       → ws://{gateway_addr}");
-        tracing::info!("🎙️  Voice server  → ws://{voice_addr}");
+/// Start the API's background jobs. Returns the ActivityPub outbox worker,
+/// which shutdown waits on.
+fn spawn_api_jobs(
+    api_state: &AppState,
+    config: &AppConfig,
+    probes: ProbeTargets,
+    shutdown: &Shutdown,
+) -> anyhow::Result<Option<tokio::task::JoinHandle<()>>> {
+    nexus_api::jobs::status_check::spawn(Arc::new(api_state.clone()), probes);
+    nexus_api::jobs::ban_list_sync::spawn(Arc::new(api_state.clone()));
+    nexus_api::jobs::data_export::spawn(Arc::new(api_state.clone()));
+    nexus_api::jobs::federated_profiles::spawn(Arc::new(api_state.clone()));
+    nexus_api::jobs::federation_txn_log::spawn(Arc::new(api_state.clone()));
+    nexus_api::jobs::message_retention::spawn(Arc::new(api_state.clone()));
+    nexus_api::jobs::reminders::spawn(Arc::new(api_state.clone()));
+    if !config.security_webhooks.url_list().is_empty() {
+        tracing::info!("🛡️ Security webhooks enabled");
+        nexus_api::jobs::security_webhooks::spawn(Arc::new(api_state.clone()));
+    }
+    if let Some(client) = api_state.transcription.clone() {
+        tracing::info!(provider = client.provider_name(), "Speech-to-text transcription enabled");
+        nexus_api::jobs::transcription::spawn(Arc::new(api_state.clone()), client);
+    }
+    if let Some(processor) = api_state.media.clone() {
+        nexus_api::jobs::media::spawn(Arc::new(api_state.clone()), processor);
     }
 
-    tokio::try_join!(
-        async {
-            let listener = tokio::net::TcpListener::bind(api_addr).await?;
-            axum::serve(listener, api_router).await?;
-            Ok::<_, anyhow::Error>(())
-        },
-        async {
-            let listener = tokio::net::TcpListener::bind(gateway_addr).await?;
-            axum::serve(listener, gateway_router).await?;
-            Ok::<_, anyhow::Error>(())
-        },
-        async {
-            let listener = tokio::net::TcpListener::bind(voice_addr).await?;
-            axum::serve(listener, voice_router).await?;
-            Ok::<_, anyhow::Error>(())
-        },
-    )?;
+    // ── Push notifications (skipped for users connected on any node) ────────
+    if let Some(client) = nexus_api::push::PushClient::from_config(&config.push)? {
+        tracing::info!("🔔 Push notifications enabled ({})", client.services().join(", "));
+        let presence = api_state.presence.clone();
+        nexus_api::jobs::push::spawn(Arc::new(api_state.clone()), client, move |user_id| {
+            let presence = presence.clone();
+            async move { presence.is_online(user_id).await }
+        });
+    }
 
-    Ok(())
+    Ok(api_state.activitypub.clone().map(|bridge| {
+        tracing::info!("🌐 ActivityPub publishing enabled for public announcement channels");
+        nexus_api::jobs::federation_outbox::spawn(Arc::new(api_state.clone()), bridge, shutdown.clone())
+    }))
 }
 
-// ── Helpers ───────────────────────────────────────────────────────────────────
+/// Resolves on Ctrl-C, or SIGTERM (what container runtimes and systemd send)
+/// on Unix.
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            tracing::error!(error = %e, "Failed to listen for Ctrl-C");
+            std::future::pending::<()>().await;
+        }
+    };
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(e) => {
+                tracing::error!(error = %e, "Failed to listen for SIGTERM");
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        () = ctrl_c => {}
+        () = terminate => {}
+    }
+}
 
 /// Load the JWT secret from `nexus.toml`, or generate and persist a new one.
 /// The file is a minimal TOML with a single `jwt_secret` key so it survives
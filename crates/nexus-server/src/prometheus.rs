@@ -0,0 +1,71 @@
+//! Prometheus metrics, served at `/metrics` on their own listener
+//! (`metrics.host`:`metrics.port`) when `metrics.enabled` is set.
+//!
+//! The API, gateway, voice and database crates record through the `metrics`
+//! facade; without a recorder installed that costs next to nothing. This
+//! installs the Prometheus recorder and renders what it has collected.
+
+use axum::{routing::get, Router};
+use metrics::{describe_counter, describe_gauge, describe_histogram, Unit};
+use metrics_exporter_prometheus::{Matcher, PrometheusBuilder, PrometheusHandle};
+use nexus_db::Database;
+
+/// Buckets for the `*_seconds` histograms: 5 ms to 10 s.
+const LATENCY_BUCKETS: &[f64] = &[0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+/// Install the global recorder. Call once, before the services start.
+pub fn install() -> anyhow::Result<PrometheusHandle> {
+    let handle = PrometheusBuilder::new()
+        .set_buckets_for_metric(Matcher::Suffix("_seconds".into()), LATENCY_BUCKETS)?
+        .install_recorder()?;
+    describe();
+    Ok(handle)
+}
+
+/// `GET /metrics` in the Prometheus text format.
+pub fn router(handle: PrometheusHandle, db: Database) -> Router {
+    Router::new().route(
+        "/metrics",
+        get(move || {
+            // Pool gauges are sampled on scrape rather than on every query.
+            db.record_pool_metrics();
+            let body = handle.render();
+            async move { body }
+        }),
+    )
+}
+
+fn describe() {
+    describe_counter!("nexus_http_requests_total", "HTTP requests by method, route and status");
+    describe_histogram!(
+        "nexus_http_request_duration_seconds",
+        Unit::Seconds,
+        "HTTP request latency by method, route and status"
+    );
+
+    describe_gauge!("nexus_gateway_connections", "Open gateway WebSocket connections");
+    describe_counter!("nexus_gateway_events_total", "Events fanned out to gateway connections");
+    describe_counter!(
+        "nexus_gateway_fanout_dropped_total",
+        "Events the gateway fan-out fell behind on and dropped"
+    );
+    describe_counter!(
+        "nexus_gateway_connection_lagged_total",
+        "Frames gateway connections fell behind on"
+    );
+    describe_counter!("nexus_gateway_spilled_total", "Lagged frames sent anyway from the frame log");
+    describe_counter!(
+        "nexus_gateway_forced_resumes_total",
+        "Connections told to resume after falling behind"
+    );
+
+    describe_gauge!("nexus_voice_rooms", "SFU rooms running on this node");
+    describe_gauge!("nexus_voice_peers", "Peers connected to SFU rooms on this node");
+    describe_counter!(
+        "nexus_voice_packets_forwarded_total",
+        "Media packets forwarded to subscribers, by kind"
+    );
+
+    describe_gauge!("nexus_db_pool_connections", "SQL pool connections, by state (idle, in_use)");
+    describe_gauge!("nexus_db_pool_max_connections", "SQL pool size limit");
+}
@@ -0,0 +1,60 @@
+//! OpenTelemetry tracing: spans exported over OTLP/gRPC to
+//! `telemetry.otlp_endpoint` (Jaeger, Tempo, an OpenTelemetry Collector...),
+//! and the W3C trace context propagator `nexus_common::telemetry` uses to
+//! carry traces into gateway events, internal RPC and federation requests.
+
+use nexus_common::config::TelemetryConfig;
+use opentelemetry::{trace::TracerProvider as _, KeyValue};
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::{
+    propagation::TraceContextPropagator,
+    runtime,
+    trace::{Sampler, Tracer, TracerProvider},
+    Resource,
+};
+
+/// The exporter, while it runs.
+pub struct Telemetry {
+    provider: TracerProvider,
+}
+
+impl Telemetry {
+    /// Start exporting, or `None` if `telemetry.otlp_endpoint` is empty.
+    /// Must be called inside the Tokio runtime.
+    pub fn init(config: &TelemetryConfig) -> anyhow::Result<Option<Self>> {
+        if config.otlp_endpoint.is_empty() {
+            return Ok(None);
+        }
+        let exporter = opentelemetry_otlp::SpanExporter::builder()
+            .with_tonic()
+            .with_endpoint(&config.otlp_endpoint)
+            .build()?;
+        let sampler = Sampler::ParentBased(Box::new(Sampler::TraceIdRatioBased(
+            config.sample_ratio.clamp(0.0, 1.0),
+        )));
+        let provider = TracerProvider::builder()
+            .with_batch_exporter(exporter, runtime::Tokio)
+            .with_sampler(sampler)
+            .with_resource(Resource::new([
+                KeyValue::new("service.name", config.service_name.clone()),
+                KeyValue::new("service.version", env!("CARGO_PKG_VERSION")),
+            ]))
+            .build();
+
+        opentelemetry::global::set_text_map_propagator(TraceContextPropagator::new());
+        opentelemetry::global::set_tracer_provider(provider.clone());
+        Ok(Some(Self { provider }))
+    }
+
+    /// The tracer for the `tracing-opentelemetry` layer.
+    pub fn tracer(&self) -> Tracer {
+        self.provider.tracer("nexus")
+    }
+
+    /// Flush spans still buffered. Call before exiting.
+    pub fn shutdown(self) {
+        if let Err(e) = self.provider.shutdown() {
+            eprintln!("Failed to flush traces: {e}");
+        }
+    }
+}
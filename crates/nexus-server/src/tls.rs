@@ -0,0 +1,460 @@
+//! Built-in TLS with certificates from an ACME CA.
+//!
+//! With `tls.enabled`, the API, gateway and voice signaling listeners speak
+//! TLS with a certificate for `tls.domains` from `tls.acme_directory` (Let's
+//! Encrypt by default). Domains are validated over HTTP-01: the CA fetches
+//! `http://<domain>/.well-known/acme-challenge/<token>`, which a plain-HTTP
+//! listener on `tls.http_port` answers. Everything else sent to that
+//! listener is redirected to HTTPS.
+//!
+//! The ACME account and the certificate are kept in `tls.cache_dir`, one
+//! directory per CA, so restarts reuse them. The certificate is renewed
+//! `tls.renew_days` before it expires; new connections get the new one
+//! without a restart.
+
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+
+use anyhow::Context;
+use axum::extract::{Path as UrlPath, State};
+use axum::http::{header, HeaderMap, StatusCode, Uri};
+use axum::response::{IntoResponse, Redirect, Response};
+use axum::routing::get;
+use axum::Router;
+use instant_acme::{
+    Account, AccountCredentials, AuthorizationStatus, ChallengeType, Identifier, NewAccount, NewOrder, Order,
+    OrderStatus,
+};
+use nexus_common::config::TlsConfig;
+use nexus_common::shutdown::Shutdown;
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use rustls::server::{ClientHello, ResolvesServerCert};
+use rustls::sign::CertifiedKey;
+use tokio::net::TcpListener;
+use tokio_rustls::TlsAcceptor;
+use x509_parser::extensions::GeneralName;
+
+use crate::listen;
+
+/// How long to wait before retrying a failed renewal.
+const RETRY_DELAY: Duration = Duration::from_secs(60 * 60);
+
+/// How many times to poll the CA while it validates an order or issues the
+/// certificate, backing off up to [`MAX_POLL_DELAY`] between polls.
+const POLL_ATTEMPTS: u32 = 15;
+const MAX_POLL_DELAY: Duration = Duration::from_secs(10);
+
+/// Key authorizations to answer HTTP-01 challenges with, by token.
+type Challenges = Arc<Mutex<HashMap<String, String>>>;
+
+/// Hands every handshake the current certificate.
+#[derive(Debug, Default)]
+struct Resolver(RwLock<Option<Arc<CertifiedKey>>>);
+
+impl Resolver {
+    fn set(&self, key: Arc<CertifiedKey>) {
+        *self.0.write().expect("certificate lock poisoned") = Some(key);
+    }
+}
+
+impl ResolvesServerCert for Resolver {
+    fn resolve(&self, _hello: ClientHello<'_>) -> Option<Arc<CertifiedKey>> {
+        self.0.read().expect("certificate lock poisoned").clone()
+    }
+}
+
+/// A certificate ready to serve.
+struct Issued {
+    key: Arc<CertifiedKey>,
+    /// DNS names it covers.
+    names: Vec<String>,
+    /// Unix time it expires at.
+    not_after: i64,
+}
+
+/// Built-in TLS, once a certificate is in hand.
+pub struct Tls {
+    acceptor: TlsAcceptor,
+}
+
+impl Tls {
+    /// Start answering challenges on `tls.http_port` of `host`, get a
+    /// certificate unless a current one is cached, and keep it renewed.
+    /// Plain HTTP is redirected to `https_port`. `None` when TLS is off.
+    pub async fn start(
+        config: &TlsConfig,
+        host: IpAddr,
+        https_port: u16,
+        shutdown: &Shutdown,
+    ) -> anyhow::Result<Option<Self>> {
+        if !config.enabled {
+            return Ok(None);
+        }
+        let acme = Acme::new(config)?;
+
+        // Up before the first order, so the CA can reach it
+        let listener = listen::bind(SocketAddr::new(host, config.http_port))?;
+        let redirect = ChallengeState {
+            challenges: acme.challenges.clone(),
+            domains: acme.domains.clone(),
+            https_port,
+        };
+        tokio::spawn(serve_challenges(listener, redirect, shutdown.clone()));
+
+        let cached = acme.cached()?;
+        let current = match cached {
+            Some(cached) if !acme.due(&cached) => cached,
+            cached => match acme.issue().await {
+                Ok(issued) => issued,
+                // Still valid: serve it and let renewal keep trying
+                Err(e) if cached.as_ref().is_some_and(|c| c.not_after > now()) => {
+                    tracing::warn!(error = %e, "Renewing the TLS certificate failed; using the cached one");
+                    cached.expect("checked above")
+                }
+                Err(e) => return Err(e.context("obtaining a TLS certificate")),
+            },
+        };
+        tracing::info!(domains = ?current.names, "🔒 TLS certificate ready");
+
+        let resolver = Arc::new(Resolver::default());
+        let not_after = current.not_after;
+        resolver.set(current.key);
+        tokio::spawn(renew(acme, resolver.clone(), not_after, shutdown.clone()));
+
+        let mut server = rustls::ServerConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+            .with_safe_default_protocol_versions()?
+            .with_no_client_auth()
+            .with_cert_resolver(resolver);
+        server.alpn_protocols = vec![b"http/1.1".to_vec()];
+        Ok(Some(Self {
+            acceptor: TlsAcceptor::from(Arc::new(server)),
+        }))
+    }
+
+    pub fn acceptor(&self) -> TlsAcceptor {
+        self.acceptor.clone()
+    }
+}
+
+/// Renew the certificate before `not_after`, until shutdown.
+async fn renew(acme: Acme, resolver: Arc<Resolver>, mut not_after: i64, shutdown: Shutdown) {
+    let mut wait = acme.until_due(not_after);
+    loop {
+        tokio::select! {
+            () = shutdown.triggered() => return,
+            () = tokio::time::sleep(wait) => {}
+        }
+        match acme.issue().await {
+            Ok(issued) => {
+                tracing::info!(domains = ?issued.names, "🔒 Renewed the TLS certificate");
+                not_after = issued.not_after;
+                resolver.set(issued.key);
+                wait = acme.until_due(not_after);
+            }
+            Err(e) => {
+                tracing::error!(error = %e, "Renewing the TLS certificate failed; retrying in an hour");
+                wait = RETRY_DELAY;
+            }
+        }
+    }
+}
+
+struct Acme {
+    config: TlsConfig,
+    domains: Vec<String>,
+    /// This CA's part of `tls.cache_dir`.
+    dir: PathBuf,
+    challenges: Challenges,
+}
+
+impl Acme {
+    fn new(config: &TlsConfig) -> anyhow::Result<Self> {
+        let domains = config.domain_list();
+        anyhow::ensure!(!domains.is_empty(), "tls.enabled needs tls.domains");
+        Ok(Self {
+            config: config.clone(),
+            domains,
+            dir: Path::new(&config.cache_dir).join(directory_host(&config.acme_directory)),
+            challenges: Challenges::default(),
+        })
+    }
+
+    /// The cached certificate, if there is one for every configured domain.
+    fn cached(&self) -> anyhow::Result<Option<Issued>> {
+        let (Ok(chain), Ok(key)) = (std::fs::read(self.dir.join("cert.pem")), std::fs::read(self.dir.join("key.pem")))
+        else {
+            return Ok(None);
+        };
+        let issued = certified(&chain, &key).context("reading the cached TLS certificate")?;
+        let covered = self
+            .domains
+            .iter()
+            .all(|domain| issued.names.iter().any(|name| name.eq_ignore_ascii_case(domain)));
+        Ok(covered.then_some(issued))
+    }
+
+    fn due(&self, issued: &Issued) -> bool {
+        self.until_due(issued.not_after).is_zero()
+    }
+
+    /// How long until a certificate expiring at `not_after` needs renewing.
+    fn until_due(&self, not_after: i64) -> Duration {
+        let renew_at = not_after.saturating_sub(self.config.renew_days as i64 * 86_400);
+        Duration::from_secs(renew_at.saturating_sub(now()).max(0) as u64)
+    }
+
+    /// Order a certificate for the configured domains and cache it.
+    async fn issue(&self) -> anyhow::Result<Issued> {
+        tracing::info!(domains = ?self.domains, "Ordering a TLS certificate");
+        let account = self.account().await?;
+        let identifiers: Vec<_> = self.domains.iter().cloned().map(Identifier::Dns).collect();
+        let mut order = account.new_order(&NewOrder { identifiers: &identifiers }).await?;
+
+        let mut tokens = Vec::new();
+        for authorization in order.authorizations().await? {
+            match authorization.status {
+                AuthorizationStatus::Pending => {}
+                AuthorizationStatus::Valid => continue,
+                status => anyhow::bail!("authorization for {:?} is {status:?}", authorization.identifier),
+            }
+            let challenge = authorization
+                .challenges
+                .iter()
+                .find(|c| c.r#type == ChallengeType::Http01)
+                .context("the CA offered no HTTP-01 challenge")?;
+            let key_authorization = order.key_authorization(challenge).as_str().to_owned();
+            self.challenges
+                .lock()
+                .expect("challenges lock poisoned")
+                .insert(challenge.token.clone(), key_authorization);
+            tokens.push(challenge.token.clone());
+            order.set_challenge_ready(&challenge.url).await?;
+        }
+
+        let finished = self.finish(&mut order).await;
+        let mut challenges = self.challenges.lock().expect("challenges lock poisoned");
+        for token in &tokens {
+            challenges.remove(token);
+        }
+        finished
+    }
+
+    /// Wait for the CA to validate `order`, then have it sign a new key.
+    async fn finish(&self, order: &mut Order) -> anyhow::Result<Issued> {
+        let mut delay = Duration::from_secs(1);
+        let mut status = OrderStatus::Pending;
+        for _ in 0..POLL_ATTEMPTS {
+            tokio::time::sleep(delay).await;
+            delay = (delay * 2).min(MAX_POLL_DELAY);
+            status = order.refresh().await?.status;
+            if status != OrderStatus::Pending {
+                break;
+            }
+        }
+        anyhow::ensure!(
+            status == OrderStatus::Ready,
+            "the CA didn't validate {} ({status:?}); does port 80 of each domain reach tls.http_port?",
+            self.domains.join(", ")
+        );
+
+        let key = rcgen::KeyPair::generate()?;
+        let mut params = rcgen::CertificateParams::new(self.domains.clone())?;
+        params.distinguished_name = rcgen::DistinguishedName::new();
+        let csr = params.serialize_request(&key)?;
+        order.finalize(csr.der()).await?;
+
+        let mut chain = None;
+        for _ in 0..POLL_ATTEMPTS {
+            chain = order.certificate().await?;
+            if chain.is_some() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_secs(1)).await;
+        }
+        let chain = chain.context("the CA didn't issue the certificate in time")?;
+        let key = key.serialize_pem();
+        let issued = certified(chain.as_bytes(), key.as_bytes())?;
+
+        std::fs::create_dir_all(&self.dir)?;
+        std::fs::write(self.dir.join("cert.pem"), &chain)?;
+        write_private(&self.dir.join("key.pem"), key.as_bytes())?;
+        Ok(issued)
+    }
+
+    /// The cached ACME account, or a new one.
+    async fn account(&self) -> anyhow::Result<Account> {
+        let path = self.dir.join("account.json");
+        if let Ok(saved) = std::fs::read(&path) {
+            let credentials: AccountCredentials =
+                serde_json::from_slice(&saved).with_context(|| format!("reading {}", path.display()))?;
+            return Ok(Account::from_credentials(credentials).await?);
+        }
+
+        let contact = format!("mailto:{}", self.config.acme_email);
+        let contact: Vec<&str> = if self.config.acme_email.is_empty() {
+            Vec::new()
+        } else {
+            vec![&contact]
+        };
+        let (account, credentials) = Account::create(
+            &NewAccount {
+                contact: &contact,
+                terms_of_service_agreed: true,
+                only_return_existing: false,
+            },
+            &self.config.acme_directory,
+            None,
+        )
+        .await?;
+        std::fs::create_dir_all(&self.dir)?;
+        write_private(&path, &serde_json::to_vec(&credentials)?)?;
+        tracing::info!(directory = %self.config.acme_directory, "Registered an ACME account");
+        Ok(account)
+    }
+}
+
+/// Load a PEM certificate chain and private key.
+fn certified(chain: &[u8], key: &[u8]) -> anyhow::Result<Issued> {
+    let chain = CertificateDer::pem_slice_iter(chain).collect::<Result<Vec<_>, _>>()?;
+    let leaf = chain.first().context("no certificate in the PEM")?;
+    let (_, parsed) =
+        x509_parser::parse_x509_certificate(leaf).map_err(|e| anyhow::anyhow!("parsing the certificate: {e}"))?;
+    let names = parsed
+        .subject_alternative_name()?
+        .map(|san| {
+            san.value
+                .general_names
+                .iter()
+                .filter_map(|name| match name {
+                    GeneralName::DNSName(name) => Some((*name).to_owned()),
+                    _ => None,
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    let not_after = parsed.validity().not_after.timestamp();
+
+    let key = PrivateKeyDer::from_pem_slice(key)?;
+    let signer = rustls::crypto::ring::sign::any_supported_type(&key)?;
+    Ok(Issued {
+        key: Arc::new(CertifiedKey::new(chain, signer)),
+        names,
+        not_after,
+    })
+}
+
+/// Only the owner may read keys.
+fn write_private(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    use std::io::Write;
+
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options.open(path)?.write_all(contents)
+}
+
+/// A directory name for an ACME directory URL: its host (and port).
+fn directory_host(url: &str) -> String {
+    let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let host = rest.split('/').next().unwrap_or(rest);
+    host.replace(':', "_")
+}
+
+fn now() -> i64 {
+    chrono::Utc::now().timestamp()
+}
+
+#[derive(Clone)]
+struct ChallengeState {
+    challenges: Challenges,
+    domains: Vec<String>,
+    https_port: u16,
+}
+
+async fn serve_challenges(listener: TcpListener, state: ChallengeState, shutdown: Shutdown) {
+    let router = Router::new()
+        .route("/.well-known/acme-challenge/{token}", get(challenge))
+        .fallback(to_https)
+        .with_state(state);
+    if let Err(e) = axum::serve(listener, router)
+        .with_graceful_shutdown(shutdown.triggered())
+        .await
+    {
+        tracing::error!(error = %e, "ACME challenge listener failed");
+    }
+}
+
+async fn challenge(State(state): State<ChallengeState>, UrlPath(token): UrlPath<String>) -> Response {
+    match state.challenges.lock().expect("challenges lock poisoned").get(&token) {
+        Some(key_authorization) => key_authorization.clone().into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+async fn to_https(State(state): State<ChallengeState>, headers: HeaderMap, uri: Uri) -> Redirect {
+    let host = headers.get(header::HOST).and_then(|host| host.to_str().ok()).unwrap_or_default();
+    let path = uri.path_and_query().map_or("/", |path| path.as_str());
+    Redirect::permanent(&https_location(host, &state.domains, state.https_port, path))
+}
+
+/// Where to send a plain-HTTP request for `host`: the same name if it's one
+/// of `domains`, so the redirect can't point anywhere else, or the first.
+fn https_location(host: &str, domains: &[String], port: u16, path: &str) -> String {
+    let name = match host.rsplit_once(':') {
+        Some((name, port)) if port.bytes().all(|b| b.is_ascii_digit()) => name,
+        _ => host,
+    };
+    let name = domains
+        .iter()
+        .find(|domain| domain.eq_ignore_ascii_case(name))
+        .unwrap_or(&domains[0]);
+    if port == 443 {
+        format!("https://{name}{path}")
+    } else {
+        format!("https://{name}:{port}{path}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redirects_stay_on_configured_domains() {
+        let domains = vec!["chat.example.com".to_owned(), "nexus.example.org".to_owned()];
+        assert_eq!(
+            https_location("nexus.example.org:80", &domains, 443, "/invite/abc?x=1"),
+            "https://nexus.example.org/invite/abc?x=1"
+        );
+        assert_eq!(
+            https_location("evil.example.net", &domains, 8443, "/"),
+            "https://chat.example.com:8443/"
+        );
+    }
+
+    #[test]
+    fn certificates_load_with_names_and_expiry() {
+        let key = rcgen::KeyPair::generate().unwrap();
+        let mut params = rcgen::CertificateParams::new(vec!["chat.example.com".to_owned()]).unwrap();
+        params.not_after = rcgen::date_time_ymd(2031, 1, 1);
+        let cert = params.self_signed(&key).unwrap();
+
+        let issued = certified(cert.pem().as_bytes(), key.serialize_pem().as_bytes()).unwrap();
+        assert_eq!(issued.names, ["chat.example.com"]);
+        assert_eq!(issued.not_after, 1_924_992_000);
+    }
+
+    #[test]
+    fn each_ca_gets_its_own_cache() {
+        assert_eq!(
+            directory_host("https://acme-staging-v02.api.letsencrypt.org/directory"),
+            "acme-staging-v02.api.letsencrypt.org"
+        );
+        assert_eq!(directory_host("https://localhost:14000/dir"), "localhost_14000");
+    }
+}
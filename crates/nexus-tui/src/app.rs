@@ -0,0 +1,232 @@
+//! Client state and input handling. Everything here is synchronous; the
+//! event loop in `main` performs the [`Action`]s that need the network.
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use nexus_client::models::channel::{Channel, ChannelType};
+use nexus_client::models::user::{UserPresence, UserResponse};
+use nexus_client::{ChannelMessage, Event};
+use uuid::Uuid;
+
+/// Messages kept for the open channel.
+pub const MAX_MESSAGES: usize = 500;
+
+/// A channel in the sidebar, with the server it belongs to.
+pub struct ChannelEntry {
+    pub server: String,
+    pub channel: Channel,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Focus {
+    Channels,
+    Input,
+}
+
+/// Work for the event loop.
+#[derive(Debug, PartialEq)]
+pub enum Action {
+    None,
+    /// Load the channel's history.
+    Open(Uuid),
+    Send { channel_id: Uuid, content: String },
+    SetPresence(UserPresence),
+    Quit,
+}
+
+pub struct App {
+    pub me: UserResponse,
+    pub channels: Vec<ChannelEntry>,
+    /// Highlighted sidebar entry.
+    pub selected: usize,
+    /// Channel shown in the message view.
+    pub open: Option<Uuid>,
+    /// The open channel's messages, oldest first.
+    pub messages: Vec<ChannelMessage>,
+    pub input: String,
+    pub focus: Focus,
+    pub presence: UserPresence,
+    /// Connection state or the last error, for the status bar.
+    pub status: String,
+}
+
+impl App {
+    /// `channels` keeps only those that hold messages.
+    pub fn new(me: UserResponse, channels: Vec<ChannelEntry>) -> Self {
+        let channels = channels
+            .into_iter()
+            .filter(|c| {
+                matches!(
+                    c.channel.channel_type,
+                    ChannelType::Text | ChannelType::Announcement | ChannelType::Dm | ChannelType::GroupDm
+                )
+            })
+            .collect();
+        Self {
+            me,
+            channels,
+            selected: 0,
+            open: None,
+            messages: Vec::new(),
+            input: String::new(),
+            focus: Focus::Channels,
+            presence: UserPresence::Online,
+            status: "Connecting…".into(),
+        }
+    }
+
+    pub fn on_key(&mut self, key: KeyEvent) -> Action {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            return Action::Quit;
+        }
+        match key.code {
+            KeyCode::Tab => {
+                self.focus = match self.focus {
+                    Focus::Channels => Focus::Input,
+                    Focus::Input => Focus::Channels,
+                };
+                return Action::None;
+            }
+            KeyCode::F(2) => {
+                self.presence = next_presence(self.presence);
+                return Action::SetPresence(self.presence);
+            }
+            _ => {}
+        }
+        match self.focus {
+            Focus::Channels => self.on_channels_key(key.code),
+            Focus::Input => self.on_input_key(key.code),
+        }
+    }
+
+    fn on_channels_key(&mut self, code: KeyCode) -> Action {
+        match code {
+            KeyCode::Up | KeyCode::Char('k') => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => {
+                if self.selected + 1 < self.channels.len() {
+                    self.selected += 1;
+                }
+            }
+            KeyCode::Enter => {
+                if let Some(entry) = self.channels.get(self.selected) {
+                    self.focus = Focus::Input;
+                    return Action::Open(entry.channel.id);
+                }
+            }
+            KeyCode::Char('q') | KeyCode::Esc => return Action::Quit,
+            _ => {}
+        }
+        Action::None
+    }
+
+    fn on_input_key(&mut self, code: KeyCode) -> Action {
+        match code {
+            KeyCode::Char(c) => self.input.push(c),
+            KeyCode::Backspace => {
+                self.input.pop();
+            }
+            KeyCode::Esc => self.focus = Focus::Channels,
+            KeyCode::Enter => {
+                let content = self.input.trim().to_owned();
+                if let (Some(channel_id), false) = (self.open, content.is_empty()) {
+                    self.input.clear();
+                    return Action::Send { channel_id, content };
+                }
+            }
+            _ => {}
+        }
+        Action::None
+    }
+
+    /// Show `channel_id` with its history, which the API returns newest first.
+    pub fn set_history(&mut self, channel_id: Uuid, mut history: Vec<ChannelMessage>) {
+        history.reverse();
+        self.open = Some(channel_id);
+        self.messages = history;
+    }
+
+    pub fn on_event(&mut self, event: Event) {
+        match event {
+            Event::Ready { .. } | Event::Resumed { .. } => self.status = "Connected".into(),
+            Event::Reconnecting { attempt } => {
+                self.status = format!("Reconnecting (attempt {attempt})…");
+            }
+            Event::RateLimited { op, retry_after_ms } => {
+                self.status = format!("{op} rate limited, retry in {retry_after_ms} ms");
+            }
+            Event::PresenceUpdate { user_id, status, .. } if user_id == self.me.id => {
+                self.presence = status;
+            }
+            Event::MessageCreate(message) if Some(message.channel_id) == self.open => {
+                // Our own sends are added when the REST call returns.
+                if !self.messages.iter().any(|m| m.id == message.id) {
+                    self.push(*message);
+                }
+            }
+            Event::MessageUpdate(message) if Some(message.channel_id) == self.open => {
+                if let Some(existing) = self.messages.iter_mut().find(|m| m.id == message.id) {
+                    *existing = *message;
+                }
+            }
+            Event::MessageDelete { id, .. } => self.messages.retain(|m| m.id != id),
+            _ => {}
+        }
+    }
+
+    /// A message we sent, as the API returned it.
+    pub fn sent(&mut self, message: ChannelMessage) {
+        if Some(message.channel_id) == self.open && !self.messages.iter().any(|m| m.id == message.id) {
+            self.push(message);
+        }
+    }
+
+    fn push(&mut self, message: ChannelMessage) {
+        self.messages.push(message);
+        if self.messages.len() > MAX_MESSAGES {
+            self.messages.remove(0);
+        }
+    }
+
+    /// Name of the open channel for the message pane title.
+    pub fn open_channel_name(&self) -> Option<String> {
+        let open = self.open?;
+        self.channels
+            .iter()
+            .find(|c| c.channel.id == open)
+            .map(|c| format!("{} / #{}", c.server, c.channel.name.as_deref().unwrap_or("unnamed")))
+    }
+}
+
+/// The status F2 switches to.
+fn next_presence(current: UserPresence) -> UserPresence {
+    match current {
+        UserPresence::Online => UserPresence::Idle,
+        UserPresence::Idle => UserPresence::DoNotDisturb,
+        UserPresence::DoNotDisturb => UserPresence::Invisible,
+        UserPresence::Invisible | UserPresence::Offline => UserPresence::Online,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn presence_cycles_through_settable_statuses() {
+        let mut presence = UserPresence::Online;
+        let mut seen = Vec::new();
+        for _ in 0..4 {
+            presence = next_presence(presence);
+            seen.push(presence);
+        }
+        assert_eq!(
+            seen,
+            [
+                UserPresence::Idle,
+                UserPresence::DoNotDisturb,
+                UserPresence::Invisible,
+                UserPresence::Online
+            ]
+        );
+        assert_eq!(next_presence(UserPresence::Offline), UserPresence::Online);
+    }
+}
@@ -0,0 +1,137 @@
+//! # Nexus TUI
+//!
+//! Minimal terminal client built on the `nexus-client` crate: log in, pick
+//! a channel, read and send messages, and set your status. It doubles as a
+//! reference for the client crate and a way to poke at a server headless.
+//!
+//! ```text
+//! NEXUS_PASSWORD=… nexus-tui --username alice
+//! ```
+
+mod app;
+mod ui;
+
+use anyhow::Context;
+use clap::Parser;
+use crossterm::event::{Event as TermEvent, EventStream, KeyEventKind};
+use futures_util::StreamExt;
+use nexus_client::{GatewayClient, HistoryQuery, RestClient};
+
+use crate::app::{Action, App, ChannelEntry};
+
+#[derive(Parser)]
+#[command(
+    name = "nexus-tui",
+    about = "Terminal client for Nexus",
+    version = env!("CARGO_PKG_VERSION"),
+)]
+struct Cli {
+    /// REST API base URL.
+    #[arg(long, env = "NEXUS_API_URL", default_value = "http://localhost:3000/api/v1")]
+    api_url: String,
+
+    /// WebSocket gateway URL.
+    #[arg(long, env = "NEXUS_GATEWAY_URL", default_value = "ws://localhost:3001/gateway")]
+    gateway_url: String,
+
+    #[arg(long, env = "NEXUS_USERNAME")]
+    username: String,
+
+    #[arg(long, env = "NEXUS_PASSWORD", hide_env_values = true)]
+    password: String,
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let cli = Cli::parse();
+
+    // Log in and load the sidebar before taking over the terminal, so
+    // errors print normally.
+    let rest = RestClient::new(Some(&cli.api_url))?;
+    let session = rest
+        .login(&cli.username, &cli.password)
+        .await
+        .context("Login failed")?;
+    let mut channels = Vec::new();
+    for server in rest.servers().await? {
+        for channel in rest.server_channels(server.id).await? {
+            channels.push(ChannelEntry {
+                server: server.name.clone(),
+                channel,
+            });
+        }
+    }
+    channels.sort_by(|a, b| (&a.server, a.channel.position).cmp(&(&b.server, b.channel.position)));
+
+    let gateway = GatewayClient::connect(
+        Some(&cli.gateway_url),
+        &session.tokens.access_token,
+        0,
+    );
+    let mut app = App::new(session.user, channels);
+
+    let mut terminal = ratatui::init();
+    let result = run(&mut terminal, &mut app, &rest, &gateway).await;
+    ratatui::restore();
+    gateway.close();
+    result
+}
+
+async fn run(
+    terminal: &mut ratatui::DefaultTerminal,
+    app: &mut App,
+    rest: &RestClient,
+    gateway: &GatewayClient,
+) -> anyhow::Result<()> {
+    let mut keys = EventStream::new();
+    let mut events = gateway.subscribe();
+
+    loop {
+        terminal.draw(|frame| ui::draw(frame, app))?;
+
+        let action = tokio::select! {
+            key = keys.next() => match key {
+                Some(Ok(TermEvent::Key(key))) if key.kind == KeyEventKind::Press => app.on_key(key),
+                Some(Ok(_)) => Action::None,
+                Some(Err(e)) => return Err(e.into()),
+                None => Action::Quit,
+            },
+            event = events.recv() => {
+                if let Ok(event) = event {
+                    app.on_event(event);
+                }
+                Action::None
+            }
+        };
+
+        match action {
+            Action::None => {}
+            Action::Quit => return Ok(()),
+            Action::Open(channel_id) => {
+                let query = HistoryQuery {
+                    limit: Some(100),
+                    ..Default::default()
+                };
+                match rest.message_history(channel_id, &query).await {
+                    Ok(history) => app.set_history(channel_id, history),
+                    Err(e) => app.status = format!("Couldn't load messages: {e}"),
+                }
+            }
+            Action::Send { channel_id, content } => match rest.say(channel_id, &content).await {
+                Ok(message) => app.sent(message),
+                Err(e) => app.status = format!("Couldn't send: {e}"),
+            },
+            Action::SetPresence(status) => {
+                if let Err(e) = gateway.set_presence(status, None) {
+                    app.status = format!("Couldn't set status: {e}");
+                }
+            }
+        }
+
+        // The REST client may have refreshed; identify with the new token
+        // if the gateway reconnects.
+        if let Some(token) = rest.access_token() {
+            gateway.set_token(&token);
+        }
+    }
+}
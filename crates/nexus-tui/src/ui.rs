@@ -0,0 +1,126 @@
+//! Rendering: channel sidebar, message view, input line and status bar.
+
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap};
+use ratatui::Frame;
+
+use crate::app::{App, Focus};
+
+pub fn draw(frame: &mut Frame, app: &App) {
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(3), Constraint::Length(3), Constraint::Length(1)])
+        .split(frame.area());
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Length(28), Constraint::Min(20)])
+        .split(rows[0]);
+
+    draw_channels(frame, app, columns[0]);
+    draw_messages(frame, app, columns[1]);
+    draw_input(frame, app, rows[1]);
+    draw_status(frame, app, rows[2]);
+}
+
+fn focused_block(title: String, focused: bool) -> Block<'static> {
+    let style = if focused {
+        Style::default().fg(Color::Cyan)
+    } else {
+        Style::default()
+    };
+    Block::default().borders(Borders::ALL).border_style(style).title(title)
+}
+
+fn draw_channels(frame: &mut Frame, app: &App, area: Rect) {
+    let mut last_server = None;
+    let items: Vec<ListItem> = app
+        .channels
+        .iter()
+        .map(|entry| {
+            let name = entry.channel.name.as_deref().unwrap_or("unnamed");
+            let mut lines = Vec::new();
+            if last_server != Some(&entry.server) {
+                last_server = Some(&entry.server);
+                lines.push(Line::styled(
+                    entry.server.clone(),
+                    Style::default().add_modifier(Modifier::BOLD),
+                ));
+            }
+            let marker = if Some(entry.channel.id) == app.open { "▸" } else { " " };
+            lines.push(Line::raw(format!("{marker} #{name}")));
+            ListItem::new(lines)
+        })
+        .collect();
+    let list = List::new(items)
+        .block(focused_block("Channels".into(), app.focus == Focus::Channels))
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+    let mut state = ListState::default().with_selected(Some(app.selected));
+    frame.render_stateful_widget(list, area, &mut state);
+}
+
+fn draw_messages(frame: &mut Frame, app: &App, area: Rect) {
+    let title = app
+        .open_channel_name()
+        .unwrap_or_else(|| "Select a channel and press Enter".into());
+    let lines: Vec<Line> = app
+        .messages
+        .iter()
+        .map(|m| {
+            let author = m
+                .author_username
+                .clone()
+                .unwrap_or_else(|| m.author_id.to_string()[..8].to_owned());
+            let mut spans = vec![
+                Span::styled(
+                    m.created_at.with_timezone(&chrono::Local).format("%H:%M ").to_string(),
+                    Style::default().fg(Color::DarkGray),
+                ),
+                Span::styled(format!("{author}: "), Style::default().fg(Color::Yellow)),
+                Span::raw(m.content.clone()),
+            ];
+            if m.edited {
+                spans.push(Span::styled(" (edited)", Style::default().fg(Color::DarkGray)));
+            }
+            Line::from(spans)
+        })
+        .collect();
+    // Keep the newest messages in view.
+    let height = area.height.saturating_sub(2) as usize;
+    let scroll = lines.len().saturating_sub(height) as u16;
+    let messages = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .wrap(Wrap { trim: false })
+        .scroll((scroll, 0));
+    frame.render_widget(messages, area);
+}
+
+fn draw_input(frame: &mut Frame, app: &App, area: Rect) {
+    let focused = app.focus == Focus::Input;
+    let input = Paragraph::new(app.input.as_str()).block(focused_block("Message".into(), focused));
+    frame.render_widget(input, area);
+    if focused {
+        let x = area.x + 1 + app.input.chars().count() as u16;
+        frame.set_cursor_position((x.min(area.right().saturating_sub(2)), area.y + 1));
+    }
+}
+
+fn draw_status(frame: &mut Frame, app: &App, area: Rect) {
+    let status = Line::from(vec![
+        Span::styled(
+            format!(" {} ", app.me.username),
+            Style::default().add_modifier(Modifier::BOLD),
+        ),
+        Span::styled(
+            format!("[{}] ", app.presence.as_str()),
+            Style::default().fg(Color::Green),
+        ),
+        Span::raw(app.status.clone()),
+        Span::styled(
+            "  Tab: focus  Enter: open/send  F2: status  Ctrl-C: quit",
+            Style::default().fg(Color::DarkGray),
+        ),
+    ]);
+    frame.render_widget(Paragraph::new(status), area);
+}
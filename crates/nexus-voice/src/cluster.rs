@@ -0,0 +1,326 @@
+//! Voice clustering — which node owns which voice room.
+//!
+//! An SFU room must live on exactly one node, otherwise participants who
+//! land on different nodes can't hear each other. Ownership is tracked in
+//! Redis:
+//!
+//! - `voice:nodes` — set of node ids that have registered
+//! - `voice:node:{id}` — the node's public signaling URL, with a TTL that
+//!   the node refreshes every heartbeat (expired key = dead node)
+//! - `voice:room:{channel_id}` — id of the owning node, also TTL-refreshed
+//!   by the owner while the room has participants
+//!
+//! Unowned rooms are placed with a consistent-hash ring over the live
+//! nodes, so adding or removing a node only moves ~1/N of the rooms. A
+//! client that sends `Join` to the wrong node gets a `Redirect` to the
+//! owner. On shutdown a node hands its rooms to their next owner on the
+//! ring and redirects its clients there.
+
+use redis::aio::ConnectionManager;
+use redis::AsyncCommands;
+use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
+use uuid::Uuid;
+
+const NODES_KEY: &str = "voice:nodes";
+
+/// Virtual nodes per physical node; smooths the room distribution.
+const VNODES_PER_NODE: u32 = 64;
+
+fn node_key(node_id: &str) -> String {
+    format!("voice:node:{node_id}")
+}
+
+fn room_key(channel_id: Uuid) -> String {
+    format!("voice:room:{channel_id}")
+}
+
+/// 64-bit FNV-1a with a murmur3 `fmix64` finalizer. Every node must hash
+/// identically, so this can't be `DefaultHasher` (whose algorithm may change
+/// between Rust releases). Plain FNV-1a barely moves the high bits for inputs
+/// that differ only in their last bytes, which clustered sequential ids on a
+/// single arc of the ring.
+fn ring_hash(bytes: &[u8]) -> u64 {
+    let mut hash = bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, b| {
+        (hash ^ u64::from(*b)).wrapping_mul(0x0000_0100_0000_01b3)
+    });
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xff51_afd7_ed55_8ccd);
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xc4ce_b9fe_1a85_ec53);
+    hash ^ (hash >> 33)
+}
+
+/// Consistent-hash ring of voice node ids.
+#[derive(Debug, Clone, Default)]
+pub struct HashRing {
+    ring: BTreeMap<u64, String>,
+}
+
+impl HashRing {
+    pub fn new<I, S>(nodes: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut ring = BTreeMap::new();
+        for node in nodes {
+            let node = node.as_ref();
+            for vnode in 0..VNODES_PER_NODE {
+                ring.insert(ring_hash(format!("{node}#{vnode}").as_bytes()), node.to_owned());
+            }
+        }
+        Self { ring }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ring.is_empty()
+    }
+
+    /// The node a channel's room belongs on, or `None` for an empty ring.
+    pub fn owner(&self, channel_id: Uuid) -> Option<&str> {
+        let hash = ring_hash(channel_id.as_bytes());
+        self.ring
+            .range(hash..)
+            .next()
+            .or_else(|| self.ring.iter().next())
+            .map(|(_, node)| node.as_str())
+    }
+}
+
+/// Where a room is hosted, from this node's point of view.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RoomOwner {
+    /// This node hosts the room.
+    Local,
+    /// Another node hosts it; clients must reconnect to `url`.
+    Remote { node_id: String, url: String },
+}
+
+/// A room moved to another node during shutdown.
+#[derive(Debug, Clone)]
+pub struct RoomHandoff {
+    pub channel_id: Uuid,
+    /// Signaling URL of the new owner; `None` if no other node is alive.
+    pub url: Option<String>,
+}
+
+/// Deletes a room key only if it still names the given node, so a node
+/// can't release a room another node has since taken over.
+const RELEASE_SCRIPT: &str = r#"
+if redis.call("GET", KEYS[1]) == ARGV[1] then
+    return redis.call("DEL", KEYS[1])
+end
+return 0
+"#;
+
+/// This node's handle on the Redis-backed ownership registry.
+#[derive(Clone)]
+pub struct VoiceCluster {
+    node_id: String,
+    public_url: String,
+    ttl_secs: u64,
+    redis: ConnectionManager,
+}
+
+impl VoiceCluster {
+    /// `heartbeat` is the refresh interval; keys live for three of them so
+    /// a single missed beat doesn't orphan rooms.
+    pub fn new(
+        node_id: impl Into<String>,
+        public_url: impl Into<String>,
+        heartbeat: Duration,
+        redis: ConnectionManager,
+    ) -> Self {
+        Self {
+            node_id: node_id.into(),
+            public_url: public_url.into(),
+            ttl_secs: heartbeat.as_secs().max(1) * 3,
+            redis,
+        }
+    }
+
+    pub fn node_id(&self) -> &str {
+        &self.node_id
+    }
+
+    /// Register this node as alive and renew ownership of `rooms`.
+    pub async fn heartbeat(&self, rooms: &[Uuid]) -> Result<(), redis::RedisError> {
+        let mut conn = self.redis.clone();
+        let mut pipe = redis::pipe();
+        pipe.sadd(NODES_KEY, &self.node_id)
+            .ignore()
+            .set_ex(node_key(&self.node_id), &self.public_url, self.ttl_secs)
+            .ignore();
+        for &channel_id in rooms {
+            pipe.set_ex(room_key(channel_id), &self.node_id, self.ttl_secs)
+                .ignore();
+        }
+        pipe.query_async(&mut conn).await
+    }
+
+    /// Live nodes and their signaling URLs. Nodes whose liveness key has
+    /// expired are pruned from the node set as a side effect.
+    pub async fn live_nodes(&self) -> Result<HashMap<String, String>, redis::RedisError> {
+        let mut conn = self.redis.clone();
+        let ids: Vec<String> = conn.smembers(NODES_KEY).await?;
+        if ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+        let keys: Vec<String> = ids.iter().map(|id| node_key(id)).collect();
+        let urls: Vec<Option<String>> = conn.mget(&keys).await?;
+
+        let mut live = HashMap::new();
+        let mut dead = Vec::new();
+        for (id, url) in ids.into_iter().zip(urls) {
+            match url {
+                Some(url) => {
+                    live.insert(id, url);
+                }
+                None => dead.push(id),
+            }
+        }
+        if !dead.is_empty() {
+            let _: () = conn.srem(NODES_KEY, &dead).await?;
+        }
+        Ok(live)
+    }
+
+    /// Resolve (and if needed, assign) the owner of a channel's room.
+    pub async fn owner_of(&self, channel_id: Uuid) -> Result<RoomOwner, redis::RedisError> {
+        let mut conn = self.redis.clone();
+        let key = room_key(channel_id);
+        let live = self.live_nodes().await?;
+
+        if let Some(current) = conn.get::<_, Option<String>>(&key).await? {
+            if let Some(owner) = self.describe(&current, &live) {
+                return Ok(owner);
+            }
+            // The recorded owner died without handing off — reassign.
+            let _: () = conn.del(&key).await?;
+        }
+
+        let ring = HashRing::new(live.keys().chain(std::iter::once(&self.node_id)));
+        let candidate = ring.owner(channel_id).unwrap_or(&self.node_id).to_owned();
+        let claimed: bool = redis::cmd("SET")
+            .arg(&key)
+            .arg(&candidate)
+            .arg("NX")
+            .arg("EX")
+            .arg(self.ttl_secs)
+            .query_async::<Option<String>>(&mut conn)
+            .await?
+            .is_some();
+
+        // Lost a race with another node placing the same room — use theirs.
+        let owner = if claimed {
+            candidate
+        } else {
+            conn.get::<_, Option<String>>(&key)
+                .await?
+                .unwrap_or(candidate)
+        };
+        Ok(self
+            .describe(&owner, &live)
+            .unwrap_or(RoomOwner::Local))
+    }
+
+    /// Give up ownership of an empty room.
+    pub async fn release(&self, channel_id: Uuid) -> Result<(), redis::RedisError> {
+        let mut conn = self.redis.clone();
+        let _: i64 = redis::Script::new(RELEASE_SCRIPT)
+            .key(room_key(channel_id))
+            .arg(&self.node_id)
+            .invoke_async(&mut conn)
+            .await?;
+        Ok(())
+    }
+
+    /// Leave the cluster, moving each of `rooms` to its next owner on the
+    /// ring of the remaining nodes.
+    pub async fn handoff(&self, rooms: &[Uuid]) -> Result<Vec<RoomHandoff>, redis::RedisError> {
+        let mut conn = self.redis.clone();
+        let _: () = conn.srem(NODES_KEY, &self.node_id).await?;
+        let _: () = conn.del(node_key(&self.node_id)).await?;
+
+        let mut live = self.live_nodes().await?;
+        live.remove(&self.node_id);
+        let ring = HashRing::new(live.keys());
+
+        let mut handoffs = Vec::with_capacity(rooms.len());
+        for &channel_id in rooms {
+            let next = ring.owner(channel_id).map(str::to_owned);
+            match &next {
+                Some(node_id) => {
+                    let _: () = conn
+                        .set_ex(room_key(channel_id), node_id, self.ttl_secs)
+                        .await?;
+                }
+                None => self.release(channel_id).await?,
+            }
+            handoffs.push(RoomHandoff {
+                channel_id,
+                url: next.and_then(|id| live.get(&id).cloned()),
+            });
+        }
+        Ok(handoffs)
+    }
+
+    fn describe(&self, node_id: &str, live: &HashMap<String, String>) -> Option<RoomOwner> {
+        if node_id == self.node_id {
+            return Some(RoomOwner::Local);
+        }
+        live.get(node_id).map(|url| RoomOwner::Remote {
+            node_id: node_id.to_owned(),
+            url: url.clone(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn channels(n: usize) -> Vec<Uuid> {
+        (0..n as u128).map(Uuid::from_u128).collect()
+    }
+
+    #[test]
+    fn empty_ring_has_no_owner() {
+        let ring = HashRing::new(Vec::<String>::new());
+        assert!(ring.is_empty());
+        assert_eq!(ring.owner(Uuid::nil()), None);
+    }
+
+    #[test]
+    fn ownership_is_independent_of_node_order() {
+        let a = HashRing::new(["a", "b", "c"]);
+        let b = HashRing::new(["c", "a", "b"]);
+        for ch in channels(200) {
+            assert_eq!(a.owner(ch), b.owner(ch));
+        }
+    }
+
+    #[test]
+    fn rooms_spread_across_nodes() {
+        let ring = HashRing::new(["a", "b", "c"]);
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        for ch in channels(3000) {
+            *counts.entry(ring.owner(ch).unwrap()).or_default() += 1;
+        }
+        assert_eq!(counts.len(), 3);
+        assert!(counts.values().all(|&c| c > 600), "{counts:?}");
+    }
+
+    #[test]
+    fn removing_a_node_only_moves_its_rooms() {
+        let before = HashRing::new(["a", "b", "c"]);
+        let after = HashRing::new(["a", "b"]);
+        for ch in channels(1000) {
+            let old = before.owner(ch).unwrap();
+            if old != "c" {
+                assert_eq!(after.owner(ch), Some(old));
+            }
+        }
+    }
+}
@@ -9,15 +9,19 @@
 //! 4. Server creates SFU peer, sends SDP answer + ICE servers
 //! 5. Client and server exchange ICE candidates
 //! 6. WebRTC connection established — media flows via UDP
-//! 7. Client sends "Leave" or disconnects → cleanup
+//! 7. As others publish or leave, server sends "ServerOffer"s, the client
+//!    replies "ClientAnswer"
+//! 8. Client sends "Leave" or disconnects → cleanup
 //!
 //! This is intentionally separate from the main gateway because:
 //! - Voice connections have different lifecycle (join/leave vs persistent)
 //! - SDP/ICE exchange is voice-specific
 //! - Allows independent scaling of voice servers
 
+use crate::cluster::{RoomHandoff, RoomOwner, VoiceCluster};
 use crate::sfu::{SfuCommand, SfuManager, SfuResponse};
 use crate::state::{VoiceState, VoiceStateManager, VoiceStateUpdate};
+use crate::turn;
 use axum::{
     extract::{
         ws::{Message, WebSocket},
@@ -28,8 +32,11 @@ use axum::{
     Router,
 };
 use futures_util::{SinkExt, StreamExt};
-use nexus_common::gateway_event::GatewayEvent;
-use serde::{Deserialize, Serialize};
+use nexus_common::gateway_event::{EventBus, GatewayEvent};
+use nexus_common::models::channel::ChannelType;
+use nexus_common::snowflake;
+pub use nexus_common::voice::{IceServerConfig, VoiceSignal};
+use nexus_db::repository::{channels, voice_chat};
 use std::sync::Arc;
 use tokio::sync::{broadcast, mpsc};
 use uuid::Uuid;
@@ -40,127 +47,31 @@ pub struct VoiceServerState {
     pub sfu: SfuManager,
     pub voice_state: VoiceStateManager,
     /// Broadcast sender to push voice events to the main gateway.
-    pub gateway_tx: broadcast::Sender<GatewayEvent>,
+    pub gateway_tx: EventBus,
     pub db: nexus_db::Database,
+    /// Room ownership registry; `None` on a single-node deployment.
+    pub cluster: Option<VoiceCluster>,
+    /// Rooms this node is handing off on shutdown — connections in them
+    /// redirect their client to the new owner.
+    pub handoff_tx: broadcast::Sender<RoomHandoff>,
 }
 
-/// Voice signaling messages (client ↔ server).
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(tag = "op", content = "d")]
-pub enum VoiceSignal {
-    // === Client → Server ===
-    /// Authenticate with JWT token.
-    Identify {
-        token: String,
-    },
-
-    /// Join a voice channel.
-    Join {
-        channel_id: Uuid,
-        server_id: Option<Uuid>,
-    },
-
-    /// Send SDP offer to establish WebRTC connection.
-    Offer {
-        sdp: String,
-    },
-
-    /// Send ICE candidate.
-    IceCandidate {
-        candidate: String,
-        sdp_mid: Option<String>,
-        sdp_m_line_index: Option<u32>,
-    },
-
-    /// Update self mute/deaf/video state.
-    StateUpdate {
-        self_mute: Option<bool>,
-        self_deaf: Option<bool>,
-        self_video: Option<bool>,
-        self_stream: Option<bool>,
-    },
-
-    /// Set speaking state (voice activity detection result).
-    Speaking {
-        speaking: bool,
-    },
-
-    /// Leave voice channel.
-    Leave,
-
-    // === Server → Client ===
-    /// Authentication successful.
-    Ready {
-        session_id: String,
-    },
-
-    /// Joined voice channel — here's the current state.
-    Joined {
-        channel_id: Uuid,
-        voice_states: Vec<serde_json::Value>,
-        ice_servers: Vec<IceServerConfig>,
-    },
-
-    /// SDP answer from the SFU.
-    Answer {
-        sdp: String,
-    },
-
-    /// ICE candidate from the server.
-    ServerIceCandidate {
-        candidate: String,
-        sdp_mid: Option<String>,
-        sdp_m_line_index: Option<u32>,
-    },
-
-    /// Another user's voice state changed (joined, left, mute, etc.).
-    VoiceStateUpdate {
-        state: serde_json::Value,
-    },
-
-    /// Speaking state changed for a user.
-    SpeakingUpdate {
-        user_id: Uuid,
-        speaking: bool,
-    },
-
-    /// Error occurred.
-    Error {
-        code: u32,
-        message: String,
-    },
-}
-
-/// ICE server configuration sent to clients.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct IceServerConfig {
-    pub urls: Vec<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub username: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub credential: Option<String>,
-}
-
-impl IceServerConfig {
-    /// Default STUN servers (free, public).
-    /// In production, add TURN servers for NAT traversal.
-    pub fn defaults() -> Vec<Self> {
-        vec![
-            Self {
-                urls: vec![
-                    "stun:stun.l.google.com:19302".into(),
-                    "stun:stun1.l.google.com:19302".into(),
-                ],
-                username: None,
-                credential: None,
-            },
-            Self {
-                urls: vec!["stun:stun.cloudflare.com:3478".into()],
-                username: None,
-                credential: None,
-            },
-        ]
+/// The STUN servers, plus the configured TURN server with credentials for
+/// `user_id` that expire after `voice.turn_ttl_secs`.
+pub fn ice_servers_for(user_id: Uuid) -> Vec<IceServerConfig> {
+    let mut servers = IceServerConfig::defaults();
+    let voice = &nexus_common::config::get().voice;
+    let urls = voice.turn_url_list();
+    if !urls.is_empty() {
+        let expires_at = chrono::Utc::now().timestamp() + voice.turn_ttl_secs as i64;
+        let creds = turn::credentials(&voice.turn_secret, user_id, expires_at);
+        servers.push(IceServerConfig {
+            urls,
+            username: Some(creds.username),
+            credential: Some(creds.credential),
+        });
     }
+    servers
 }
 
 /// Build the voice signaling WebSocket router.
@@ -188,11 +99,53 @@ async fn handle_voice_connection(socket: WebSocket, state: Arc<VoiceServerState>
     let mut username = String::new();
     let mut current_channel: Option<Uuid> = None;
     let mut peer_id: Option<Uuid> = None;
+    // Renegotiation offers from the SFU for the current peer.
+    let mut sfu_rx: Option<mpsc::Receiver<SfuResponse>> = None;
+    let mut handoff_rx = state.handoff_tx.subscribe();
 
     tracing::debug!(session = %session_id, "Voice WebSocket connected");
 
     // Receive loop
-    while let Some(Ok(msg)) = receiver.next().await {
+    loop {
+        let msg = tokio::select! {
+            msg = receiver.next() => match msg {
+                Some(Ok(msg)) => msg,
+                _ => break,
+            },
+            handoff = handoff_rx.recv() => {
+                let Ok(handoff) = handoff else { continue };
+                if current_channel != Some(handoff.channel_id) {
+                    continue;
+                }
+                if let Some(uid) = user_id {
+                    leave_channel(&state, uid, handoff.channel_id, peer_id.take()).await;
+                }
+                current_channel = None;
+                match handoff.url {
+                    Some(url) => {
+                        let redirect = VoiceSignal::Redirect {
+                            channel_id: handoff.channel_id,
+                            url,
+                        };
+                        send_signal(&mut sender, &redirect).await;
+                    }
+                    None => send_error(&mut sender, 5003, "Voice node shutting down").await,
+                }
+                continue;
+            }
+            response = next_sfu_response(&mut sfu_rx) => {
+                match response {
+                    Some(SfuResponse::Offer { sdp }) => {
+                        send_signal(&mut sender, &VoiceSignal::ServerOffer { sdp }).await;
+                    }
+                    Some(_) => {}
+                    // The SFU dropped the peer
+                    None => sfu_rx = None,
+                }
+                continue;
+            }
+        };
+
         match msg {
             Message::Text(text) => {
                 let signal = match serde_json::from_str::<VoiceSignal>(&text) {
@@ -257,6 +210,32 @@ async fn handle_voice_connection(socket: WebSocket, state: Arc<VoiceServerState>
                         }
                         let uid = user_id.unwrap();
 
+                        // Rooms live on exactly one node; send the client there.
+                        if let Some(cluster) = &state.cluster {
+                            match cluster.owner_of(channel_id).await {
+                                Ok(RoomOwner::Local) => {}
+                                Ok(RoomOwner::Remote { node_id, url }) => {
+                                    if let Some(old_channel) = current_channel.take() {
+                                        leave_channel(&state, uid, old_channel, peer_id.take()).await;
+                                    }
+                                    tracing::debug!(
+                                        session = %session_id,
+                                        channel = %channel_id,
+                                        node = %node_id,
+                                        "Redirecting voice client to owning node"
+                                    );
+                                    let redirect = VoiceSignal::Redirect { channel_id, url };
+                                    send_signal(&mut sender, &redirect).await;
+                                    continue;
+                                }
+                                Err(e) => {
+                                    tracing::error!(error = %e, "Voice room ownership lookup failed");
+                                    send_error(&mut sender, 5004, "Voice cluster unavailable").await;
+                                    continue;
+                                }
+                            }
+                        }
+
                         // If already in a channel, leave first
                         if let Some(old_channel) = current_channel.take() {
                             leave_channel(&state, uid, old_channel, peer_id.take()).await;
@@ -281,7 +260,8 @@ async fn handle_voice_connection(socket: WebSocket, state: Arc<VoiceServerState>
                         let joined = VoiceSignal::Joined {
                             channel_id,
                             voice_states,
-                            ice_servers: IceServerConfig::defaults(),
+                            ice_servers: ice_servers_for(uid),
+                            text_channel_id: open_text_chat(&state, uid, channel_id).await,
                         };
                         send_signal(&mut sender, &joined).await;
 
@@ -308,7 +288,7 @@ async fn handle_voice_connection(socket: WebSocket, state: Arc<VoiceServerState>
                         let new_peer_id = Uuid::new_v4();
                         let room_tx = state.sfu.get_or_create_room(channel_id).await;
 
-                        let (reply_tx, mut reply_rx) = mpsc::channel(1);
+                        let (reply_tx, mut reply_rx) = mpsc::channel(8);
                         let cmd = SfuCommand::AddPeer {
                             peer_id: new_peer_id,
                             user_id: uid,
@@ -325,6 +305,7 @@ async fn handle_voice_connection(socket: WebSocket, state: Arc<VoiceServerState>
                         match reply_rx.recv().await {
                             Some(SfuResponse::Answer { sdp }) => {
                                 peer_id = Some(new_peer_id);
+                                sfu_rx = Some(reply_rx);
                                 let answer = VoiceSignal::Answer { sdp };
                                 send_signal(&mut sender, &answer).await;
 
@@ -343,6 +324,18 @@ async fn handle_voice_connection(socket: WebSocket, state: Arc<VoiceServerState>
                         }
                     }
 
+                    VoiceSignal::ClientAnswer { sdp } => {
+                        if let (Some(pid), Some(channel_id)) = (peer_id, current_channel) {
+                            let room_tx = state.sfu.get_or_create_room(channel_id).await;
+                            let _ = room_tx
+                                .send(SfuCommand::Answer {
+                                    peer_id: pid,
+                                    answer_sdp: sdp,
+                                })
+                                .await;
+                        }
+                    }
+
                     VoiceSignal::IceCandidate {
                         candidate,
                         sdp_mid: _,
@@ -400,6 +393,7 @@ async fn handle_voice_connection(socket: WebSocket, state: Arc<VoiceServerState>
                                         server_id: new_state.server_id,
                                         channel_id: Some(channel_id),
                                         user_id: Some(uid),
+                                        trace_context: None,
                                     });
                                 }
                             }
@@ -442,6 +436,26 @@ async fn handle_voice_connection(socket: WebSocket, state: Arc<VoiceServerState>
     tracing::info!(session = %session_id, "Voice WebSocket disconnected");
 }
 
+/// Record `user_id` as in the call and return the channel's text chat,
+/// creating it for voice channels that don't have one yet.
+async fn open_text_chat(state: &VoiceServerState, user_id: Uuid, channel_id: Uuid) -> Option<Uuid> {
+    let pool = &state.db.pool;
+    if let Err(e) = voice_chat::record_join(pool, channel_id, user_id).await {
+        tracing::warn!(channel = %channel_id, error = %e, "Failed to record voice participation");
+    }
+    let channel = channels::find_by_id(pool, channel_id).await.ok().flatten()?;
+    if !matches!(channel.channel_type, ChannelType::Voice | ChannelType::Stage) {
+        return None;
+    }
+    match voice_chat::find_or_create_chat(pool, snowflake::generate_id(), &channel).await {
+        Ok(chat) => Some(chat.id),
+        Err(e) => {
+            tracing::warn!(channel = %channel_id, error = %e, "Failed to open voice text chat");
+            None
+        }
+    }
+}
+
 /// Leave a voice channel — remove from state, SFU, and broadcast.
 async fn leave_channel(
     state: &VoiceServerState,
@@ -452,6 +466,9 @@ async fn leave_channel(
     // Remove from voice state
     let old_state = state.voice_state.get_user_state(user_id).await;
     state.voice_state.leave(user_id).await;
+    if let Err(e) = voice_chat::record_leave(&state.db.pool, channel_id, user_id).await {
+        tracing::warn!(channel = %channel_id, error = %e, "Failed to record voice participation");
+    }
 
     // Remove from SFU
     if let Some(pid) = peer_id {
@@ -461,6 +478,16 @@ async fn leave_channel(
             .await;
     }
 
+    // Last one out releases the room so it can be placed afresh.
+    if state.voice_state.get_channel_count(channel_id).await == 0 {
+        state.sfu.remove_room(channel_id).await;
+        if let Some(cluster) = &state.cluster {
+            if let Err(e) = cluster.release(channel_id).await {
+                tracing::warn!(channel = %channel_id, error = %e, "Failed to release voice room");
+            }
+        }
+    }
+
     // Broadcast leave event
     if let Some(vs) = old_state {
         let _ = state.gateway_tx.send(GatewayEvent {
@@ -475,6 +502,7 @@ async fn leave_channel(
             server_id: vs.server_id,
             channel_id: Some(channel_id),
             user_id: Some(user_id),
+            trace_context: None,
         });
     }
 }
@@ -487,9 +515,19 @@ fn broadcast_voice_state(state: &VoiceServerState, voice_state: &VoiceState) {
         server_id: voice_state.server_id,
         channel_id: Some(voice_state.channel_id),
         user_id: Some(voice_state.user_id),
+        trace_context: None,
     });
 }
 
+/// The next message from the SFU for this connection's peer; never resolves
+/// without one.
+async fn next_sfu_response(rx: &mut Option<mpsc::Receiver<SfuResponse>>) -> Option<SfuResponse> {
+    match rx {
+        Some(rx) => rx.recv().await,
+        None => std::future::pending().await,
+    }
+}
+
 /// Send a voice signal to the client.
 async fn send_signal(
     sender: &mut futures_util::stream::SplitSink<WebSocket, Message>,
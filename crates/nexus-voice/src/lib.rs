@@ -23,19 +23,30 @@
 //! - [`handler`] — WebSocket signaling handler (SDP/ICE exchange)
 //! - [`room`] — Voice room abstraction (participant tracking)
 //! - [`signaling`] — Signaling message types
+//! - [`cluster`] — Redis-backed room ownership for multi-node deployments
+//! - [`network`] — UDP bind address, port range and advertised ICE addresses
+//! - [`mux`] — Single-port mode: one UDP socket shared by every peer
+//! - [`turn`] — Time-limited TURN credentials for clients behind NAT
 
+pub mod cluster;
 pub mod handler;
+pub mod mux;
+pub mod network;
 pub mod room;
 pub mod sfu;
 pub mod signaling;
 pub mod state;
+pub mod turn;
 
+use cluster::{RoomHandoff, VoiceCluster};
 use handler::VoiceServerState;
-use nexus_common::gateway_event::GatewayEvent;
+use network::RtcNetwork;
+use nexus_common::gateway_event::EventBus;
 use sfu::SfuManager;
 use state::VoiceStateManager;
-use std::net::IpAddr;
+use std::time::Duration;
 use tokio::sync::broadcast;
+use uuid::Uuid;
 
 /// Voice server — the top-level coordinator for all voice functionality.
 ///
@@ -54,25 +65,92 @@ impl VoiceServer {
     /// # Arguments
     /// - `db` — Database connection for checking permissions
     /// - `gateway_tx` — Broadcast sender to push voice events to the main gateway
-    /// - `local_ip` — Local IP address for binding UDP sockets (SFU)
+    /// - `network` — Where the SFU binds its UDP sockets and what it advertises
     pub fn new(
         db: nexus_db::Database,
-        gateway_tx: broadcast::Sender<GatewayEvent>,
-        local_ip: IpAddr,
+        gateway_tx: EventBus,
+        network: RtcNetwork,
     ) -> Self {
-        let sfu = SfuManager::new(local_ip);
-        let voice_state = VoiceStateManager::new();
+        let sfu = SfuManager::new(network);
+        // Shared with API nodes through Redis when there is one
+        let voice_state = VoiceStateManager::for_redis(db.redis.clone());
 
         let state = VoiceServerState {
             sfu,
             voice_state,
             gateway_tx,
             db,
+            cluster: None,
+            handoff_tx: broadcast::channel(64).0,
         };
 
         Self { state }
     }
 
+    /// Join a voice cluster: rooms owned by other nodes are redirected there.
+    pub fn with_cluster(mut self, cluster: VoiceCluster) -> Self {
+        self.state.cluster = Some(cluster);
+        self
+    }
+
+    /// Keep this node's liveness and room ownership fresh in Redis.
+    /// No-op without a cluster.
+    pub fn spawn_heartbeat(&self, every: Duration) {
+        let Some(cluster) = self.state.cluster.clone() else {
+            return;
+        };
+        let sfu = self.state.sfu.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(every);
+            loop {
+                interval.tick().await;
+                let rooms = sfu.room_ids().await;
+                if let Err(e) = cluster.heartbeat(&rooms).await {
+                    tracing::warn!(node = cluster.node_id(), error = %e, "Voice cluster heartbeat failed");
+                }
+            }
+        });
+    }
+
+    /// Close every active room for shutdown. With a cluster the rooms go to
+    /// their next owners and clients are redirected there; otherwise
+    /// clients are told the node is going away. Either way they leave their
+    /// rooms (so the gateway hears about it) before the SFU rooms are shut
+    /// down. Call before the signaling listener stops.
+    pub async fn shutdown(&self) {
+        let rooms = self.state.sfu.room_ids().await;
+        let handoffs = match &self.state.cluster {
+            Some(cluster) => match cluster.handoff(&rooms).await {
+                Ok(handoffs) => {
+                    tracing::info!(node = cluster.node_id(), rooms = handoffs.len(), "Voice rooms handed off");
+                    handoffs
+                }
+                Err(e) => {
+                    tracing::error!(error = %e, "Voice room handoff failed");
+                    Vec::new()
+                }
+            },
+            None => Vec::new(),
+        };
+        let handed_off: Vec<Uuid> = handoffs.iter().map(|h| h.channel_id).collect();
+        let closing = rooms
+            .into_iter()
+            .filter(|channel_id| !handed_off.contains(channel_id))
+            .map(|channel_id| RoomHandoff { channel_id, url: None });
+        let mut notified = false;
+        for handoff in handoffs.into_iter().chain(closing) {
+            notified |= self.state.handoff_tx.send(handoff).is_ok();
+        }
+        if notified {
+            // Give connection tasks a moment to push the redirects out.
+            tokio::time::sleep(Duration::from_millis(500)).await;
+        }
+        let closed = self.state.sfu.shutdown_all().await;
+        if closed > 0 {
+            tracing::info!(rooms = closed, "SFU rooms closed");
+        }
+    }
+
     /// Build the Axum router for the voice signaling WebSocket.
     pub fn build_router(&self) -> axum::Router {
         handler::build_router(self.state.clone())
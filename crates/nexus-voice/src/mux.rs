@@ -0,0 +1,212 @@
+//! Single-port mode: every peer's media on one UDP socket.
+//!
+//! With `voice.rtc_single_port` set, the SFU binds that one port instead of
+//! a port per peer, so a firewall only needs it open. Datagrams are handed
+//! to the right peer by ICE username: a client's STUN binding requests carry
+//! `USERNAME` = `<SFU ufrag>:<client ufrag>`, and the SFU's ufrag is unique
+//! per peer. The address a binding request came from is remembered, and the
+//! DTLS and RTP that follow from it go to the same peer.
+
+use std::collections::HashMap;
+use std::io;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+use tokio::net::UdpSocket;
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+use crate::network;
+
+/// A datagram for a room: the peer it's for, its contents and sender.
+pub(crate) type RoomPacket = (Uuid, (Vec<u8>, SocketAddr));
+
+/// STUN `USERNAME` attribute.
+const STUN_USERNAME: u16 = 0x0006;
+const STUN_MAGIC_COOKIE: [u8; 4] = [0x21, 0x12, 0xA4, 0x42];
+
+/// Where a peer's packets go.
+#[derive(Debug, Clone)]
+struct Route {
+    peer_id: Uuid,
+    room: mpsc::Sender<RoomPacket>,
+}
+
+#[derive(Debug, Default)]
+struct Routes {
+    /// SFU-side ICE ufrag → peer.
+    by_ufrag: HashMap<String, Route>,
+    /// Remote address → the ufrag it last sent a binding request for.
+    by_addr: HashMap<SocketAddr, String>,
+}
+
+impl Routes {
+    /// The peer a datagram from `source` is for, learning the address from
+    /// STUN binding requests.
+    fn route(&mut self, source: SocketAddr, data: &[u8]) -> Option<Route> {
+        if let Some(ufrag) = stun_ufrag(data) {
+            let route = self.by_ufrag.get(ufrag)?.clone();
+            self.by_addr.insert(source, ufrag.to_owned());
+            return Some(route);
+        }
+        let ufrag = self.by_addr.get(&source)?;
+        self.by_ufrag.get(ufrag).cloned()
+    }
+
+    fn remove(&mut self, ufrag: &str) {
+        self.by_ufrag.remove(ufrag);
+        self.by_addr.retain(|_, u| u != ufrag);
+    }
+}
+
+/// The shared socket and who its datagrams are for.
+#[derive(Debug)]
+pub(crate) struct UdpMux {
+    socket: Arc<UdpSocket>,
+    routes: Arc<Mutex<Routes>>,
+}
+
+impl UdpMux {
+    /// Bind `addr` and start handing out what arrives on it. Needs a Tokio
+    /// runtime.
+    pub(crate) fn bind(addr: SocketAddr) -> io::Result<Self> {
+        let socket = Arc::new(network::bind_udp(addr)?);
+        let routes = Arc::new(Mutex::new(Routes::default()));
+        tokio::spawn(demux(socket.clone(), routes.clone()));
+        Ok(Self { socket, routes })
+    }
+
+    pub(crate) fn socket(&self) -> Arc<UdpSocket> {
+        self.socket.clone()
+    }
+
+    /// Send datagrams for the peer whose SFU-side ufrag is `ufrag` to `room`.
+    pub(crate) fn register(&self, ufrag: String, peer_id: Uuid, room: mpsc::Sender<RoomPacket>) -> Registration {
+        self.routes
+            .lock()
+            .expect("mux routes poisoned")
+            .by_ufrag
+            .insert(ufrag.clone(), Route { peer_id, room });
+        Registration {
+            ufrag,
+            routes: self.routes.clone(),
+        }
+    }
+}
+
+/// A peer's place on the shared socket; [`release`](Self::release) it when
+/// the peer goes.
+#[derive(Debug)]
+pub(crate) struct Registration {
+    ufrag: String,
+    routes: Arc<Mutex<Routes>>,
+}
+
+impl Registration {
+    pub(crate) fn release(&self) {
+        self.routes.lock().expect("mux routes poisoned").remove(&self.ufrag);
+    }
+}
+
+async fn demux(socket: Arc<UdpSocket>, routes: Arc<Mutex<Routes>>) {
+    let mut buf = vec![0u8; 2000]; // MTU-sized buffer
+    loop {
+        let (len, source) = match socket.recv_from(&mut buf).await {
+            Ok(received) => received,
+            Err(e) => {
+                // Errors on an unconnected UDP socket concern one datagram
+                tracing::debug!(error = %e, "UDP recv error on shared socket");
+                continue;
+            }
+        };
+        let source = network::canonical(source);
+        let data = &buf[..len];
+        let route = routes.lock().expect("mux routes poisoned").route(source, data);
+        if let Some(route) = route {
+            // One busy room mustn't hold up the others; its packet is dropped.
+            let _ = route.room.try_send((route.peer_id, (data.to_vec(), source)));
+        }
+    }
+}
+
+/// The recipient's ufrag from a STUN message's `USERNAME`
+/// (`recipient:sender`), if it is STUN and has one.
+fn stun_ufrag(data: &[u8]) -> Option<&str> {
+    // 20-byte header: type (top two bits zero), length, cookie, transaction
+    if data.len() < 20 || data[0] & 0xC0 != 0 || data[4..8] != STUN_MAGIC_COOKIE {
+        return None;
+    }
+    let mut attributes = &data[20..];
+    while attributes.len() >= 4 {
+        let kind = u16::from_be_bytes([attributes[0], attributes[1]]);
+        let len = usize::from(u16::from_be_bytes([attributes[2], attributes[3]]));
+        let value = attributes.get(4..4 + len)?;
+        if kind == STUN_USERNAME {
+            return std::str::from_utf8(value).ok()?.split(':').next();
+        }
+        // Values are padded to four bytes
+        attributes = attributes.get(4 + len.div_ceil(4) * 4..)?;
+    }
+    None
+}
+
+/// The ICE ufrag an SDP declares.
+pub(crate) fn sdp_ufrag(sdp: &str) -> Option<&str> {
+    sdp.lines()
+        .find_map(|line| line.strip_prefix("a=ice-ufrag:"))
+        .map(str::trim)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A binding request with a PRIORITY attribute before USERNAME.
+    fn binding_request(username: &str) -> Vec<u8> {
+        let mut attributes = vec![0x00, 0x24, 0x00, 0x04, 0x6E, 0x7F, 0x1E, 0xFF];
+        attributes.extend(STUN_USERNAME.to_be_bytes());
+        attributes.extend((username.len() as u16).to_be_bytes());
+        attributes.extend(username.as_bytes());
+        attributes.resize(attributes.len().div_ceil(4) * 4, 0);
+
+        let mut message = vec![0x00, 0x01];
+        message.extend((attributes.len() as u16).to_be_bytes());
+        message.extend(STUN_MAGIC_COOKIE);
+        message.extend([7; 12]);
+        message.extend(attributes);
+        message
+    }
+
+    #[test]
+    fn ufrag_is_read_from_binding_requests() {
+        assert_eq!(stun_ufrag(&binding_request("sfuA:client")), Some("sfuA"));
+        assert_eq!(stun_ufrag(&binding_request("x1y:z")), Some("x1y"));
+        // DTLS and RTP aren't STUN
+        assert_eq!(stun_ufrag(&[0x16, 0xFE, 0xFD, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]), None);
+        assert_eq!(stun_ufrag(&[0x80, 0x6F, 0, 1]), None);
+    }
+
+    #[test]
+    fn packets_follow_the_address_a_binding_request_came_from() {
+        let (tx, _rx) = mpsc::channel(1);
+        let peer = Uuid::new_v4();
+        let mut routes = Routes::default();
+        routes.by_ufrag.insert("sfuA".into(), Route { peer_id: peer, room: tx });
+
+        let client: SocketAddr = "198.51.100.1:5000".parse().unwrap();
+        let dtls = [0x16, 0xFE, 0xFD];
+        assert!(routes.route(client, &dtls).is_none());
+        assert_eq!(routes.route(client, &binding_request("sfuA:c")).unwrap().peer_id, peer);
+        assert_eq!(routes.route(client, &dtls).unwrap().peer_id, peer);
+        assert!(routes.route(client, &binding_request("other:c")).is_none());
+
+        routes.remove("sfuA");
+        assert!(routes.route(client, &dtls).is_none());
+    }
+
+    #[test]
+    fn sdp_ufrag_is_found() {
+        let sdp = "v=0\r\nm=audio 9 UDP/TLS/RTP/SAVPF 111\r\na=ice-ufrag:Ab12\r\na=ice-pwd:x\r\n";
+        assert_eq!(sdp_ufrag(sdp), Some("Ab12"));
+    }
+}
@@ -0,0 +1,246 @@
+//! Where the SFU listens for media and what it tells clients to send to.
+//!
+//! Every peer gets its own UDP socket, bound on `voice.rtc_host` at a port
+//! from `voice.rtc_port_min..=voice.rtc_port_max` (any free port when
+//! unset). Its ICE candidates advertise each of `voice.public_ips` with
+//! that port, for servers behind 1:1 NAT or with both IPv4 and IPv6
+//! addresses. Without public IPs the bound address is advertised, or, when
+//! bound on all interfaces, the address the host reaches the internet from.
+//!
+//! With `voice.rtc_single_port` set, every peer shares one socket on that
+//! port instead (see [`crate::mux`]).
+//!
+//! Binding on `::` accepts IPv4 as well; those peers' addresses are handled
+//! as plain IPv4 everywhere except on the socket itself.
+
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, UdpSocket as StdUdpSocket};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use nexus_common::config::VoiceConfig;
+use socket2::{Domain, Protocol, Socket, Type};
+use tokio::net::UdpSocket;
+
+use crate::mux::UdpMux;
+
+/// Bind address, advertised addresses and port range of the SFU's sockets.
+#[derive(Debug, Clone)]
+pub struct RtcNetwork {
+    bind_ip: IpAddr,
+    advertised: Vec<IpAddr>,
+    ports: Option<(u16, u16)>,
+    /// Where the next search for a free port in `ports` starts.
+    next_port: Arc<AtomicUsize>,
+    /// The shared socket, in single-port mode.
+    mux: Option<Arc<UdpMux>>,
+}
+
+impl RtcNetwork {
+    /// Bind and advertise `ip`, on any free port. Only reachable from
+    /// elsewhere if `ip` is.
+    pub fn local(ip: IpAddr) -> Self {
+        Self {
+            bind_ip: ip,
+            advertised: vec![ip],
+            ports: None,
+            next_port: Arc::new(AtomicUsize::new(0)),
+            mux: None,
+        }
+    }
+
+    /// Needs a Tokio runtime in single-port mode, to read the shared socket.
+    pub fn from_config(config: &VoiceConfig) -> anyhow::Result<Self> {
+        let bind_ip = parse_ip(&config.rtc_host, "voice.rtc_host")?;
+        let mut advertised = config
+            .public_ip_list()
+            .iter()
+            .map(|ip| parse_ip(ip, "voice.public_ips"))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        if advertised.is_empty() {
+            advertised.push(if bind_ip.is_unspecified() {
+                outbound_ip(bind_ip).unwrap_or(IpAddr::V4(Ipv4Addr::LOCALHOST))
+            } else {
+                bind_ip
+            });
+        }
+        if bind_ip.is_ipv4() && advertised.iter().any(IpAddr::is_ipv6) {
+            anyhow::bail!("voice.public_ips has an IPv6 address but voice.rtc_host is IPv4; bind on \"::\"");
+        }
+
+        let ports = match (config.rtc_port_min, config.rtc_port_max) {
+            (0, 0) => None,
+            (min, max) if min > 0 && min <= max => Some((min, max)),
+            (min, max) => anyhow::bail!("invalid voice.rtc_port_min/max range {min}-{max}"),
+        };
+        let mux = match config.rtc_single_port {
+            0 => None,
+            _ if ports.is_some() => {
+                anyhow::bail!("set voice.rtc_single_port or voice.rtc_port_min/max, not both")
+            }
+            port => {
+                let addr = SocketAddr::new(bind_ip, port);
+                let mux = UdpMux::bind(addr).map_err(|e| anyhow::anyhow!("binding voice media port {addr}: {e}"))?;
+                Some(Arc::new(mux))
+            }
+        };
+        Ok(Self {
+            bind_ip,
+            advertised,
+            ports,
+            next_port: Arc::new(AtomicUsize::new(0)),
+            mux,
+        })
+    }
+
+    /// The shared socket, in single-port mode.
+    pub(crate) fn mux(&self) -> Option<&UdpMux> {
+        self.mux.as_deref()
+    }
+
+    /// Addresses peers are told to send to.
+    pub fn advertised(&self) -> &[IpAddr] {
+        &self.advertised
+    }
+
+    /// Bind a socket for a new peer.
+    pub(crate) fn bind(&self) -> io::Result<UdpSocket> {
+        let Some((min, max)) = self.ports else {
+            return bind_udp(SocketAddr::new(self.bind_ip, 0));
+        };
+        let count = (max - min) as usize + 1;
+        let start = self.next_port.fetch_add(1, Ordering::Relaxed);
+        for i in 0..count {
+            let port = min + ((start + i) % count) as u16;
+            match bind_udp(SocketAddr::new(self.bind_ip, port)) {
+                Err(e) if e.kind() == io::ErrorKind::AddrInUse => continue,
+                result => return result,
+            }
+        }
+        Err(io::Error::new(
+            io::ErrorKind::AddrInUse,
+            format!("no free UDP port in {min}-{max}"),
+        ))
+    }
+
+    /// The ICE host candidates for a socket bound at `port`.
+    pub(crate) fn candidates(&self, port: u16) -> Vec<SocketAddr> {
+        self.advertised.iter().map(|&ip| SocketAddr::new(ip, port)).collect()
+    }
+
+    /// The advertised address a datagram from `source` was sent to: the
+    /// first of the same family as the sender.
+    pub(crate) fn destination(&self, source: SocketAddr, port: u16) -> SocketAddr {
+        let ip = self
+            .advertised
+            .iter()
+            .find(|ip| ip.is_ipv4() == source.is_ipv4())
+            .unwrap_or(&self.advertised[0]);
+        SocketAddr::new(*ip, port)
+    }
+}
+
+/// `addr` as the rest of the SFU sees it: IPv4 peers of a dual-stack socket
+/// arrive as IPv4-mapped IPv6 addresses.
+pub(crate) fn canonical(addr: SocketAddr) -> SocketAddr {
+    SocketAddr::new(addr.ip().to_canonical(), addr.port())
+}
+
+/// `addr` as a socket bound at `local` can send to: IPv4 destinations
+/// of an IPv6 socket are mapped.
+pub(crate) fn for_socket(local: SocketAddr, addr: SocketAddr) -> SocketAddr {
+    match (local, addr) {
+        (SocketAddr::V6(_), SocketAddr::V4(v4)) => SocketAddr::new(IpAddr::V6(v4.ip().to_ipv6_mapped()), v4.port()),
+        _ => addr,
+    }
+}
+
+/// Bind a non-blocking UDP socket; `::` takes IPv4 too.
+pub(crate) fn bind_udp(addr: SocketAddr) -> io::Result<UdpSocket> {
+    let socket = Socket::new(Domain::for_address(addr), Type::DGRAM, Some(Protocol::UDP))?;
+    if addr.is_ipv6() && addr.ip().is_unspecified() {
+        socket.set_only_v6(false)?;
+    }
+    socket.set_nonblocking(true)?;
+    socket.bind(&addr.into())?;
+    UdpSocket::from_std(socket.into())
+}
+
+/// The local address the host routes public traffic from, found by
+/// "connecting" a UDP socket (nothing is sent).
+fn outbound_ip(bind_ip: IpAddr) -> Option<IpAddr> {
+    let probe = |local: IpAddr, remote: IpAddr| {
+        let socket = StdUdpSocket::bind(SocketAddr::new(local, 0)).ok()?;
+        socket.connect(SocketAddr::new(remote, 9)).ok()?;
+        Some(socket.local_addr().ok()?.ip()).filter(|ip| !ip.is_unspecified())
+    };
+    let v4 = || probe(Ipv4Addr::UNSPECIFIED.into(), Ipv4Addr::new(192, 0, 2, 1).into());
+    let v6 = || probe(Ipv6Addr::UNSPECIFIED.into(), Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1).into());
+    match bind_ip {
+        IpAddr::V4(_) => v4(),
+        // A dual-stack socket can use either; nearly every client has IPv4.
+        IpAddr::V6(_) => v4().or_else(v6),
+    }
+}
+
+fn parse_ip(value: &str, key: &str) -> anyhow::Result<IpAddr> {
+    let value = value.trim().trim_start_matches('[').trim_end_matches(']');
+    value
+        .parse()
+        .map_err(|_| anyhow::anyhow!("{key} must be an IP address, got '{value}'"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn network(advertised: &[&str]) -> RtcNetwork {
+        RtcNetwork {
+            bind_ip: "::".parse().unwrap(),
+            advertised: advertised.iter().map(|ip| ip.parse().unwrap()).collect(),
+            ports: None,
+            next_port: Arc::new(AtomicUsize::new(0)),
+            mux: None,
+        }
+    }
+
+    #[test]
+    fn destination_matches_the_senders_family() {
+        let net = network(&["203.0.113.7", "2001:db8::7"]);
+        assert_eq!(
+            net.destination("198.51.100.1:5000".parse().unwrap(), 40000),
+            "203.0.113.7:40000".parse().unwrap()
+        );
+        assert_eq!(
+            net.destination("[2001:db8::1]:5000".parse().unwrap(), 40000),
+            "[2001:db8::7]:40000".parse().unwrap()
+        );
+        // Only IPv4 advertised: everyone is told the same address
+        let net = network(&["203.0.113.7"]);
+        assert_eq!(
+            net.destination("[2001:db8::1]:5000".parse().unwrap(), 40000),
+            "203.0.113.7:40000".parse().unwrap()
+        );
+    }
+
+    #[test]
+    fn mapped_addresses_round_trip() {
+        let mapped: SocketAddr = "[::ffff:198.51.100.1]:5000".parse().unwrap();
+        let plain: SocketAddr = "198.51.100.1:5000".parse().unwrap();
+        assert_eq!(canonical(mapped), plain);
+        assert_eq!(for_socket("[::]:40000".parse().unwrap(), plain), mapped);
+        assert_eq!(for_socket("0.0.0.0:40000".parse().unwrap(), plain), plain);
+    }
+
+    #[tokio::test]
+    async fn port_range_is_respected() {
+        let mut net = RtcNetwork::local(Ipv4Addr::LOCALHOST.into());
+        // An unprivileged range that's rarely in use
+        net.ports = Some((47_800, 47_803));
+        let sockets: Vec<_> = (0..3).map(|_| net.bind().unwrap()).collect();
+        for socket in &sockets {
+            let port = socket.local_addr().unwrap().port();
+            assert!((47_800..=47_803).contains(&port), "{port}");
+        }
+    }
+}
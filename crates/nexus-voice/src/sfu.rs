@@ -21,26 +21,41 @@
 //! - We drive the I/O (UDP sockets) ourselves
 //! - str0m handles DTLS, SRTP, ICE, SDP negotiation
 //! - We get full control over packet routing
+//!
+//! Rooms, peers and forwarded packets are counted in the `nexus_voice_*`
+//! Prometheus metrics.
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::net::SocketAddr;
 use std::sync::Arc;
-use str0m::channel::ChannelId;
-use str0m::media::{MediaKind, Mid};
-use str0m::{Candidate, Rtc, RtcError};
+use std::time::{Duration, Instant};
+use str0m::change::{SdpAnswer, SdpPendingOffer};
+use str0m::media::{Direction, KeyframeRequest, KeyframeRequestKind, MediaData, MediaKind, Mid, Rid};
+use str0m::net::{Protocol, Receive};
+use str0m::{Candidate, Event, IceConnectionState, Input, Output, Rtc, RtcError};
 use tokio::net::UdpSocket;
 use tokio::sync::{mpsc, RwLock};
+use tokio::task::JoinHandle;
 use uuid::Uuid;
 
+use crate::mux::{self, Registration};
+use crate::network::{self, RtcNetwork};
+
 /// Unique identifier for a peer connection within the SFU.
 pub type PeerId = Uuid;
 
+/// Shortest gap between the keyframe requests the SFU makes of a publisher
+/// on its own, after losing packets of its track.
+const KEYFRAME_REQUEST_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Packets received for a room's peers, queued before the room task.
+const ROOM_QUEUE: usize = 4096;
+
 /// An SFU session for a single voice channel (room).
 ///
 /// Manages all WebRTC peer connections for participants in one room.
 /// Media received from any peer is forwarded to all other peers.
-#[allow(dead_code)]
 pub struct SfuRoom {
     pub channel_id: Uuid,
     /// All peer connections in this room.
@@ -49,6 +64,10 @@ pub struct SfuRoom {
     tracks: HashMap<(PeerId, Mid), TrackInfo>,
     /// Maps receiving track Mid → source (peer_id, Mid) for forwarding.
     subscriptions: HashMap<(PeerId, Mid), (PeerId, Mid)>,
+    /// Every peer's packets, tagged with the peer they were addressed to.
+    packet_tx: mpsc::Sender<(PeerId, (Vec<u8>, SocketAddr))>,
+    /// Where peers' sockets bind and what they advertise.
+    network: RtcNetwork,
 }
 
 /// Information about a published media track.
@@ -59,6 +78,10 @@ pub struct TrackInfo {
     pub mid: Mid,
     pub kind: MediaKind,
     pub label: TrackLabel,
+    /// Simulcast layer being forwarded; `None` without simulcast.
+    pub rid: Option<Rid>,
+    /// When the SFU last asked the publisher for a keyframe.
+    pub last_keyframe_request: Option<Instant>,
 }
 
 /// What this track carries.
@@ -70,34 +93,86 @@ pub enum TrackLabel {
     ScreenShareAudio,
 }
 
+impl TrackLabel {
+    /// Label of a newly published track. The SDP doesn't say whether video
+    /// is a camera or a screen, so tracks start out as plain audio/video.
+    pub fn for_kind(kind: MediaKind) -> Self {
+        match kind {
+            MediaKind::Audio => Self::Audio,
+            MediaKind::Video => Self::Video,
+        }
+    }
+}
+
 /// A single participant's WebRTC connection managed by str0m.
 pub struct PeerSession {
     pub peer_id: PeerId,
     pub user_id: Uuid,
     /// The str0m RTC instance for this peer.
     pub rtc: Rtc,
-    /// UDP socket for this peer's media.
+    /// UDP socket for this peer's media; shared by all peers in single-port
+    /// mode.
     pub socket: Arc<UdpSocket>,
+    /// Address the socket is bound to.
+    pub local_addr: SocketAddr,
     /// Remote address (updated as ICE candidates resolve).
     pub remote_addr: Option<SocketAddr>,
     /// Published track Mids (what this peer is sending).
     pub published_tracks: Vec<Mid>,
     /// Subscribed track Mids (what this peer is receiving — forwarded from others).
     pub subscribed_tracks: Vec<Mid>,
-    /// Data channel for signaling within the connection.
-    pub data_channel: Option<ChannelId>,
+    /// Whether this peer's audio is forwarded to the others.
+    pub audio_enabled: bool,
+    /// Whether this peer's video is forwarded to the others.
+    pub video_enabled: bool,
+    /// Other peers' tracks to add in the next offer to this peer.
+    to_subscribe: Vec<(PeerId, Mid)>,
+    /// Subscribed Mids whose publisher left, to deactivate in the next offer.
+    to_close: Vec<Mid>,
+    /// The offer awaiting the client's answer, and the Mids it adds.
+    pending: Option<(SdpPendingOffer, Vec<Mid>)>,
+    /// Where the SDP answer and later renegotiation offers go.
+    signal_tx: mpsc::Sender<SfuResponse>,
+    /// When str0m next wants `Input::Timeout`.
+    timeout: Instant,
+    /// Moves this peer's packets into the room.
+    relay: Relay,
+}
+
+impl Drop for PeerSession {
+    fn drop(&mut self) {
+        match &self.relay {
+            Relay::Socket(task) => task.abort(),
+            Relay::Shared(registration) => registration.release(),
+        }
+    }
+}
+
+/// How a peer's packets reach its room.
+enum Relay {
+    /// A task reading the peer's own socket; stopping it frees the socket.
+    Socket(JoinHandle<()>),
+    /// The peer's route on the shared socket.
+    Shared(Registration),
 }
 
 /// Commands sent to the SFU room task.
 #[derive(Debug)]
 pub enum SfuCommand {
-    /// Add a new peer with their SDP offer.
+    /// Add a new peer with their SDP offer. `reply` gets the SDP answer,
+    /// then an [`SfuResponse::Offer`] whenever tracks are added to or
+    /// removed from what the peer receives.
     AddPeer {
         peer_id: PeerId,
         user_id: Uuid,
         offer_sdp: String,
         reply: mpsc::Sender<SfuResponse>,
     },
+    /// The client's answer to the last [`SfuResponse::Offer`].
+    Answer {
+        peer_id: PeerId,
+        answer_sdp: String,
+    },
     /// Remove a peer (disconnected or left).
     RemovePeer {
         peer_id: PeerId,
@@ -126,6 +201,9 @@ pub enum SfuCommand {
 pub enum SfuResponse {
     /// SDP answer to send back to the peer.
     Answer { sdp: String },
+    /// SDP offer renegotiating the tracks forwarded to the peer; each
+    /// track's stream id is the publishing user's id.
+    Offer { sdp: String },
     /// Room stats.
     Stats(RoomStats),
     /// Error occurred.
@@ -146,15 +224,15 @@ pub struct RoomStats {
 pub struct SfuManager {
     /// Command senders for each active room.
     rooms: Arc<RwLock<HashMap<Uuid, mpsc::Sender<SfuCommand>>>>,
-    /// Local IP for binding UDP sockets.
-    local_ip: std::net::IpAddr,
+    /// Where peers' UDP sockets bind and what they advertise.
+    network: RtcNetwork,
 }
 
 impl SfuManager {
-    pub fn new(local_ip: std::net::IpAddr) -> Self {
+    pub fn new(network: RtcNetwork) -> Self {
         Self {
             rooms: Arc::new(RwLock::new(HashMap::new())),
-            local_ip,
+            network,
         }
     }
 
@@ -177,12 +255,12 @@ impl SfuManager {
         }
 
         let (cmd_tx, cmd_rx) = mpsc::channel::<SfuCommand>(256);
-        let local_ip = self.local_ip;
+        let network = self.network.clone();
         let rooms_ref = self.rooms.clone();
 
         // Spawn the room task
         tokio::spawn(async move {
-            run_sfu_room(channel_id, cmd_rx, local_ip).await;
+            run_sfu_room(channel_id, cmd_rx, network).await;
             // Clean up when room shuts down
             rooms_ref.write().await.remove(&channel_id);
             tracing::info!(channel = %channel_id, "SFU room shut down");
@@ -202,6 +280,21 @@ impl SfuManager {
         }
     }
 
+    /// Shut down every room, e.g. when the server stops. Returns how many
+    /// there were.
+    pub async fn shutdown_all(&self) -> usize {
+        let rooms: Vec<_> = self.rooms.write().await.drain().collect();
+        for (_, sender) in &rooms {
+            let _ = sender.send(SfuCommand::Shutdown).await;
+        }
+        rooms.len()
+    }
+
+    /// Channels with a room on this node.
+    pub async fn room_ids(&self) -> Vec<Uuid> {
+        self.rooms.read().await.keys().copied().collect()
+    }
+
     /// Get the number of active rooms.
     pub async fn active_room_count(&self) -> usize {
         self.rooms.read().await.len()
@@ -218,22 +311,73 @@ impl SfuManager {
 async fn run_sfu_room(
     channel_id: Uuid,
     mut cmd_rx: mpsc::Receiver<SfuCommand>,
-    local_ip: std::net::IpAddr,
+    network: RtcNetwork,
 ) {
-    let mut peers: HashMap<PeerId, ActivePeer> = HashMap::new();
+    let (packet_tx, mut packet_rx) = mpsc::channel(ROOM_QUEUE);
+    let mut room = SfuRoom::new(channel_id, packet_tx, network);
+    metrics::gauge!("nexus_voice_rooms").increment(1);
 
     // Main event loop
     loop {
-        // Process commands from the API/signaling layer
-        let cmd = tokio::select! {
+        let timeout = room.next_timeout();
+        tokio::select! {
             cmd = cmd_rx.recv() => {
-                match cmd {
-                    Some(c) => c,
-                    None => break, // Channel closed, shut down
+                let Some(cmd) = cmd else {
+                    break; // Channel closed, shut down
+                };
+                if !room.handle_command(cmd).await {
+                    break;
                 }
             }
-        };
+            Some((peer_id, (data, source))) = packet_rx.recv() => {
+                room.receive(peer_id, &data, source, Instant::now());
+            }
+            _ = sleep_until(timeout) => room.handle_timeout(Instant::now()),
+        }
+        room.drive();
+    }
+
+    metrics::gauge!("nexus_voice_rooms").decrement(1);
+    metrics::gauge!("nexus_voice_peers").decrement(room.peers.len() as f64);
+}
 
+/// Wait until `deadline`, or forever without one.
+async fn sleep_until(deadline: Option<Instant>) {
+    match deadline {
+        Some(deadline) => tokio::time::sleep_until(deadline.into()).await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Something one peer's connection produced that concerns the others.
+enum Propagated {
+    /// The peer started publishing a track.
+    TrackOpen { mid: Mid, kind: MediaKind },
+    /// Media on one of the peer's published tracks.
+    MediaData(Box<MediaData>),
+    /// The peer wants a keyframe on a track it's subscribed to.
+    KeyframeRequest(KeyframeRequest),
+}
+
+impl SfuRoom {
+    fn new(
+        channel_id: Uuid,
+        packet_tx: mpsc::Sender<(PeerId, (Vec<u8>, SocketAddr))>,
+        network: RtcNetwork,
+    ) -> Self {
+        Self {
+            channel_id,
+            peers: HashMap::new(),
+            tracks: HashMap::new(),
+            subscriptions: HashMap::new(),
+            packet_tx,
+            network,
+        }
+    }
+
+    /// Apply a command; `false` once the room should shut down.
+    async fn handle_command(&mut self, cmd: SfuCommand) -> bool {
+        let channel_id = self.channel_id;
         match cmd {
             SfuCommand::AddPeer {
                 peer_id,
@@ -241,7 +385,7 @@ async fn run_sfu_room(
                 offer_sdp,
                 reply,
             } => {
-                match create_peer(peer_id, user_id, &offer_sdp, local_ip).await {
+                match create_peer(peer_id, user_id, &offer_sdp, &self.network, &self.packet_tx, reply.clone()).await {
                     Ok((peer, answer_sdp)) => {
                         tracing::info!(
                             channel = %channel_id,
@@ -249,34 +393,8 @@ async fn run_sfu_room(
                             user = %user_id,
                             "Peer added to SFU room"
                         );
-                        peers.insert(peer_id, peer);
                         let _ = reply.send(SfuResponse::Answer { sdp: answer_sdp }).await;
-
-                        // Start the peer's media relay task
-                        let peer_ref = peers.get(&peer_id);
-                        if let Some(active_peer) = peer_ref {
-                            let socket = active_peer.socket.clone();
-                            let media_tx = active_peer.media_tx.clone();
-
-                            // Spawn UDP receive task for this peer
-                            tokio::spawn(async move {
-                                let mut buf = vec![0u8; 2000]; // MTU-sized buffer
-                                loop {
-                                    match socket.recv_from(&mut buf).await {
-                                        Ok((len, src)) => {
-                                            let packet = buf[..len].to_vec();
-                                            if media_tx.send((packet, src)).await.is_err() {
-                                                break;
-                                            }
-                                        }
-                                        Err(e) => {
-                                            tracing::warn!(error = %e, "UDP recv error");
-                                            break;
-                                        }
-                                    }
-                                }
-                            });
-                        }
+                        self.add_peer(peer);
                     }
                     Err(e) => {
                         tracing::error!(
@@ -292,8 +410,22 @@ async fn run_sfu_room(
                 }
             }
 
+            SfuCommand::Answer {
+                peer_id,
+                answer_sdp,
+            } => {
+                if let Err(e) = self.accept_answer(peer_id, &answer_sdp) {
+                    tracing::warn!(
+                        channel = %channel_id,
+                        peer = %peer_id,
+                        error = %e,
+                        "Failed to apply renegotiation answer"
+                    );
+                }
+            }
+
             SfuCommand::RemovePeer { peer_id } => {
-                if peers.remove(&peer_id).is_some() {
+                if self.remove_peer(peer_id) {
                     tracing::info!(
                         channel = %channel_id,
                         peer = %peer_id,
@@ -302,9 +434,9 @@ async fn run_sfu_room(
                 }
 
                 // If room is empty, shut down
-                if peers.is_empty() {
+                if self.peers.is_empty() {
                     tracing::info!(channel = %channel_id, "Room empty, shutting down");
-                    break;
+                    return false;
                 }
             }
 
@@ -312,7 +444,7 @@ async fn run_sfu_room(
                 peer_id,
                 candidate,
             } => {
-                if let Some(peer) = peers.get_mut(&peer_id) {
+                if let Some(peer) = self.peers.get_mut(&peer_id) {
                     // Parse and add ICE candidate to the str0m Rtc instance
                     match Candidate::from_sdp_string(&candidate) {
                         Ok(cand) => {
@@ -331,61 +463,370 @@ async fn run_sfu_room(
 
             SfuCommand::UpdateMedia {
                 peer_id,
-                audio_enabled: _,
-                video_enabled: _,
+                audio_enabled,
+                video_enabled,
             } => {
-                if let Some(_peer) = peers.get_mut(&peer_id) {
-                    // Track enable/disable is handled at the WebRTC level
-                    // by the client sending empty frames or stopping the track.
-                    // We just need to stop forwarding if disabled.
+                if let Some(peer) = self.peers.get_mut(&peer_id) {
+                    // Muted tracks stay negotiated; their media just isn't forwarded.
+                    if let Some(enabled) = audio_enabled {
+                        peer.audio_enabled = enabled;
+                    }
+                    if let Some(enabled) = video_enabled {
+                        peer.video_enabled = enabled;
+                    }
                     tracing::debug!(peer = %peer_id, "Media update received");
                 }
             }
 
             SfuCommand::GetStats { reply } => {
+                let count = |kind| self.tracks.values().filter(|t| t.kind == kind).count();
                 let stats = RoomStats {
                     channel_id,
-                    peer_count: peers.len(),
-                    audio_tracks: peers.len(), // Each peer publishes 1 audio track
-                    video_tracks: peers
-                        .values()
-                        .filter(|p| p.has_video)
-                        .count(),
+                    peer_count: self.peers.len(),
+                    audio_tracks: count(MediaKind::Audio),
+                    video_tracks: count(MediaKind::Video),
                 };
                 let _ = reply.send(SfuResponse::Stats(stats)).await;
             }
 
             SfuCommand::Shutdown => {
                 tracing::info!(channel = %channel_id, "SFU room shutting down by command");
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Add a peer, subscribing it to every track already published.
+    fn add_peer(&mut self, mut peer: PeerSession) {
+        peer.to_subscribe = self.tracks.keys().copied().collect();
+        if self.peers.insert(peer.peer_id, peer).is_none() {
+            metrics::gauge!("nexus_voice_peers").increment(1);
+        }
+    }
+
+    /// Remove a peer with its tracks; subscribers of those tracks have them
+    /// deactivated in their next offer.
+    fn remove_peer(&mut self, peer_id: PeerId) -> bool {
+        if self.peers.remove(&peer_id).is_none() {
+            return false;
+        }
+        metrics::gauge!("nexus_voice_peers").decrement(1);
+        self.tracks.retain(|(publisher, _), _| *publisher != peer_id);
+        let peers = &mut self.peers;
+        self.subscriptions.retain(|&(subscriber, mid), (publisher, _)| {
+            if subscriber == peer_id {
+                return false;
+            }
+            if *publisher == peer_id {
+                if let Some(peer) = peers.get_mut(&subscriber) {
+                    peer.subscribed_tracks.retain(|m| *m != mid);
+                    peer.to_close.push(mid);
+                }
+                return false;
+            }
+            true
+        });
+        for peer in self.peers.values_mut() {
+            peer.to_subscribe.retain(|(publisher, _)| *publisher != peer_id);
+        }
+        true
+    }
+
+    /// Feed a datagram received on `peer_id`'s socket to its connection.
+    fn receive(&mut self, peer_id: PeerId, data: &[u8], source: SocketAddr, now: Instant) {
+        let Some(peer) = self.peers.get_mut(&peer_id) else {
+            return;
+        };
+        let destination = self.network.destination(source, peer.local_addr.port());
+        let Ok(receive) = Receive::new(Protocol::Udp, source, destination, data) else {
+            return; // Not STUN, DTLS or RTP
+        };
+        if let Err(e) = peer.rtc.handle_input(Input::Receive(now, receive)) {
+            tracing::warn!(peer = %peer_id, error = %e, "Peer failed handling input");
+            peer.rtc.disconnect();
+        }
+    }
+
+    /// Advance the clock of every peer whose timeout has passed.
+    fn handle_timeout(&mut self, now: Instant) {
+        for peer in self.peers.values_mut().filter(|p| p.timeout <= now) {
+            if let Err(e) = peer.rtc.handle_input(Input::Timeout(now)) {
+                tracing::warn!(peer = %peer.peer_id, error = %e, "Peer failed handling timeout");
+                peer.rtc.disconnect();
+            }
+        }
+    }
+
+    /// The earliest time a peer wants `Input::Timeout`.
+    fn next_timeout(&self) -> Option<Instant> {
+        self.peers.values().map(|p| p.timeout).min()
+    }
+
+    /// Drain every peer's output, passing tracks, media and keyframe
+    /// requests between peers until nothing more comes out; then offer new
+    /// tracks to their subscribers and drop peers whose connection died.
+    fn drive(&mut self) {
+        loop {
+            let mut propagated = Vec::new();
+            for peer in self.peers.values_mut() {
+                peer.poll(&mut propagated);
+            }
+            if propagated.is_empty() {
                 break;
             }
+            for (peer_id, event) in propagated {
+                self.propagate(peer_id, event);
+            }
+        }
+
+        self.negotiate();
+
+        let dead: Vec<PeerId> = self
+            .peers
+            .values()
+            .filter(|p| !p.rtc.is_alive())
+            .map(|p| p.peer_id)
+            .collect();
+        for peer_id in dead {
+            self.remove_peer(peer_id);
+            tracing::info!(channel = %self.channel_id, peer = %peer_id, "Peer connection closed");
         }
     }
+
+    fn propagate(&mut self, peer_id: PeerId, event: Propagated) {
+        match event {
+            Propagated::TrackOpen { mid, kind } => {
+                let Some(peer) = self.peers.get_mut(&peer_id) else {
+                    return;
+                };
+                peer.published_tracks.push(mid);
+                let track = TrackInfo {
+                    peer_id,
+                    user_id: peer.user_id,
+                    mid,
+                    kind,
+                    label: TrackLabel::for_kind(kind),
+                    rid: None,
+                    last_keyframe_request: None,
+                };
+                tracing::debug!(channel = %self.channel_id, peer = %peer_id, ?mid, ?kind, "Track published");
+                self.tracks.insert((peer_id, mid), track);
+                for other in self.peers.values_mut().filter(|p| p.peer_id != peer_id) {
+                    other.to_subscribe.push((peer_id, mid));
+                }
+            }
+
+            Propagated::MediaData(data) => {
+                let source = (peer_id, data.mid);
+                let (Some(track), Some(publisher)) =
+                    (self.tracks.get_mut(&source), self.peers.get_mut(&peer_id))
+                else {
+                    return;
+                };
+                // Of simulcast layers, only the highest ("h") is forwarded.
+                if data.rid.is_some() && data.rid != Some("h".into()) {
+                    return;
+                }
+                track.rid = data.rid;
+                let enabled = match track.kind {
+                    MediaKind::Audio => publisher.audio_enabled,
+                    MediaKind::Video => publisher.video_enabled,
+                };
+                if !enabled {
+                    return;
+                }
+
+                // Subscribers can't decode past a gap until the next keyframe.
+                let recently_requested = track
+                    .last_keyframe_request
+                    .is_some_and(|t| t.elapsed() < KEYFRAME_REQUEST_INTERVAL);
+                if !data.contiguous && track.kind == MediaKind::Video && !recently_requested {
+                    if let Some(mut writer) = publisher.rtc.writer(data.mid) {
+                        let _ = writer.request_keyframe(data.rid, KeyframeRequestKind::Fir);
+                    }
+                    track.last_keyframe_request = Some(Instant::now());
+                }
+
+                let kind = match track.kind {
+                    MediaKind::Audio => "audio",
+                    MediaKind::Video => "video",
+                };
+                let mut forwarded = 0u64;
+                for (&(subscriber, mid), _) in self.subscriptions.iter().filter(|(_, s)| **s == source) {
+                    let Some(peer) = self.peers.get_mut(&subscriber) else {
+                        continue;
+                    };
+                    let Some(writer) = peer.rtc.writer(mid) else {
+                        continue; // Not negotiated yet
+                    };
+                    let Some(pt) = writer.match_params(data.params) else {
+                        continue;
+                    };
+                    match writer.write(pt, data.network_time, data.time, data.data.clone()) {
+                        Ok(()) => forwarded += 1,
+                        Err(e) => {
+                            tracing::warn!(peer = %subscriber, error = %e, "Failed to forward media");
+                            peer.rtc.disconnect();
+                        }
+                    }
+                }
+                metrics::counter!("nexus_voice_packets_forwarded_total", "kind" => kind).increment(forwarded);
+            }
+
+            Propagated::KeyframeRequest(req) => {
+                let Some(&(publisher, mid)) = self.subscriptions.get(&(peer_id, req.mid)) else {
+                    return;
+                };
+                let rid = self.tracks.get(&(publisher, mid)).and_then(|t| t.rid);
+                let Some(peer) = self.peers.get_mut(&publisher) else {
+                    return;
+                };
+                if let Some(mut writer) = peer.rtc.writer(mid)
+                    && let Err(e) = writer.request_keyframe(rid, req.kind)
+                {
+                    tracing::debug!(peer = %publisher, error = %e, "Keyframe request failed");
+                }
+            }
+        }
+    }
+
+    /// Send each peer with tracks to add or deactivate an offer for them,
+    /// unless it still owes an answer to the previous one.
+    fn negotiate(&mut self) {
+        for peer in self.peers.values_mut() {
+            if peer.pending.is_some() || (peer.to_subscribe.is_empty() && peer.to_close.is_empty()) {
+                continue;
+            }
+
+            let mut change = peer.rtc.sdp_api();
+            let mut added = Vec::new();
+            for source in peer.to_subscribe.drain(..) {
+                let Some(track) = self.tracks.get(&source) else {
+                    continue;
+                };
+                let mid = change.add_media(
+                    track.kind,
+                    Direction::SendOnly,
+                    Some(track.user_id.to_string()),
+                    None,
+                    None,
+                );
+                self.subscriptions.insert((peer.peer_id, mid), source);
+                added.push(mid);
+            }
+            for mid in peer.to_close.drain(..) {
+                change.set_direction(mid, Direction::Inactive);
+            }
+
+            let Some((offer, pending)) = change.apply() else {
+                continue;
+            };
+            peer.subscribed_tracks.extend(&added);
+            peer.pending = Some((pending, added));
+            if peer
+                .signal_tx
+                .try_send(SfuResponse::Offer { sdp: offer.to_sdp_string() })
+                .is_err()
+            {
+                tracing::warn!(peer = %peer.peer_id, "Signaling backed up, renegotiation offer dropped");
+            }
+        }
+    }
+
+    /// Apply the client's answer to the offer it was last sent. If it is
+    /// rejected the tracks that offer added aren't forwarded.
+    fn accept_answer(&mut self, peer_id: PeerId, answer_sdp: &str) -> Result<(), SfuError> {
+        let peer = self.peers.get_mut(&peer_id).ok_or(SfuError::PeerNotFound(peer_id))?;
+        let (pending, added) = peer
+            .pending
+            .take()
+            .ok_or_else(|| SfuError::Sdp("no offer is awaiting an answer".into()))?;
+
+        let accepted = SdpAnswer::from_sdp_string(answer_sdp)
+            .map_err(|e| SfuError::Sdp(e.to_string()))
+            .and_then(|answer| Ok(peer.rtc.sdp_api().accept_answer(pending, answer)?));
+        if accepted.is_err() {
+            peer.subscribed_tracks.retain(|m| !added.contains(m));
+            for mid in added {
+                self.subscriptions.remove(&(peer_id, mid));
+            }
+        }
+        accepted
+    }
 }
 
-/// An active peer in an SFU room with its str0m RTC instance and UDP socket.
-#[allow(dead_code)]
-struct ActivePeer {
-    peer_id: PeerId,
-    user_id: Uuid,
-    rtc: Rtc,
-    socket: Arc<UdpSocket>,
-    local_addr: SocketAddr,
-    /// Channel to receive UDP packets from the socket read task.
-    media_tx: mpsc::Sender<(Vec<u8>, SocketAddr)>,
-    /// Whether this peer is currently sending video.
-    has_video: bool,
+impl PeerSession {
+    /// Poll the connection until it asks for a timeout: transmit what it
+    /// sends and collect what the other peers need to see.
+    fn poll(&mut self, propagated: &mut Vec<(PeerId, Propagated)>) {
+        loop {
+            let output = match self.rtc.poll_output() {
+                Ok(output) => output,
+                Err(e) => {
+                    tracing::warn!(peer = %self.peer_id, error = %e, "Peer poll failed");
+                    self.rtc.disconnect();
+                    return;
+                }
+            };
+            match output {
+                Output::Timeout(t) => {
+                    self.timeout = t;
+                    return;
+                }
+                Output::Transmit(t) => {
+                    self.remote_addr = Some(t.destination);
+                    // Media is loss-tolerant; a full socket buffer drops the packet.
+                    let destination = network::for_socket(self.local_addr, t.destination);
+                    if let Err(e) = self.socket.try_send_to(&t.contents, destination) {
+                        tracing::trace!(peer = %self.peer_id, error = %e, "UDP send failed");
+                    }
+                }
+                Output::Event(event) => match event {
+                    Event::IceConnectionStateChange(IceConnectionState::Disconnected) => {
+                        self.rtc.disconnect();
+                        return;
+                    }
+                    Event::MediaAdded(m) if m.direction.is_receiving() => {
+                        propagated.push((self.peer_id, Propagated::TrackOpen { mid: m.mid, kind: m.kind }));
+                    }
+                    Event::MediaData(data) => {
+                        propagated.push((self.peer_id, Propagated::MediaData(Box::new(data))));
+                    }
+                    Event::KeyframeRequest(req) => {
+                        propagated.push((self.peer_id, Propagated::KeyframeRequest(req)));
+                    }
+                    _ => {}
+                },
+            }
+        }
+    }
 }
 
-/// Create a new peer connection with an SDP offer, return the peer and SDP answer.
+/// Create a new peer connection with an SDP offer, return the peer and the
+/// SDP answer. The packets addressed to the peer are relayed into
+/// `packet_tx` until the peer is dropped.
 async fn create_peer(
     peer_id: PeerId,
     user_id: Uuid,
     offer_sdp: &str,
-    local_ip: std::net::IpAddr,
-) -> Result<(ActivePeer, String), SfuError> {
-    // Bind a UDP socket for this peer
-    let socket = UdpSocket::bind(SocketAddr::new(local_ip, 0)).await?;
+    network: &RtcNetwork,
+    packet_tx: &mpsc::Sender<(PeerId, (Vec<u8>, SocketAddr))>,
+    signal_tx: mpsc::Sender<SfuResponse>,
+) -> Result<(PeerSession, String), SfuError> {
+    // Create the str0m RTC instance
+    let start = Instant::now();
+    let mut rtc = Rtc::builder()
+        // Enable ICE lite mode for server-side (simplifies ICE)
+        .set_ice_lite(true)
+        // Set as the answerer
+        .build(start);
+
+    // Bind a UDP socket for this peer, unless all peers share one
+    let socket = match network.mux() {
+        Some(mux) => mux.socket(),
+        None => Arc::new(network.bind()?),
+    };
     let local_addr = socket.local_addr()?;
 
     tracing::debug!(
@@ -394,24 +835,20 @@ async fn create_peer(
         "Bound UDP socket for peer"
     );
 
-    // Create the str0m RTC instance
-    let start = std::time::Instant::now();
-    let mut rtc = Rtc::builder()
-        // Enable ICE lite mode for server-side (simplifies ICE)
-        .set_ice_lite(true)
-        // Set as the answerer
-        .build(start);
-
-    // Add our local candidate (the UDP socket we bound)
-    let candidate = Candidate::host(local_addr, str0m::net::Protocol::Udp)
-        .map_err(|e| SfuError::Sdp(e.to_string()))?;
-    rtc.add_local_candidate(candidate);
+    // Add our local candidates: the socket's port at each advertised address
+    for addr in network.candidates(local_addr.port()) {
+        let candidate = Candidate::host(addr, str0m::net::Protocol::Udp)
+            .map_err(|e| SfuError::Sdp(e.to_string()))?;
+        rtc.add_local_candidate(candidate);
+    }
 
     // Parse the SDP offer from the client
     let offer = str0m::change::SdpOffer::from_sdp_string(offer_sdp)
         .map_err(|e| SfuError::Sdp(e.to_string()))?;
 
-    // Accept the offer — this adds receiving media lines for what the client publishes
+    // Accept the offer — this adds receiving media lines for what the client
+    // publishes. Other peers' tracks are added to the connection afterwards,
+    // by renegotiation (see `SfuRoom::negotiate`).
     let answer = rtc
         .sdp_api()
         .accept_offer(offer)
@@ -420,26 +857,62 @@ async fn create_peer(
     // Generate SDP answer string
     let answer_sdp = answer.to_sdp_string();
 
-    // Set up media forwarding: add send-only media lines so we can forward
-    // other peers' media to this peer
-    // (This is done dynamically when other peers join — for now the answer
-    // includes recv-only lines matching the offer)
-
-    let (media_tx, _media_rx) = mpsc::channel(1024);
+    // Relay UDP packets addressed to this peer into the room
+    let relay = match network.mux() {
+        Some(mux) => {
+            let ufrag = mux::sdp_ufrag(&answer_sdp).ok_or_else(|| SfuError::Sdp("answer has no ICE ufrag".into()))?;
+            Relay::Shared(mux.register(ufrag.to_owned(), peer_id, packet_tx.clone()))
+        }
+        None => Relay::Socket(relay_socket(socket.clone(), peer_id, packet_tx.clone())),
+    };
 
-    let peer = ActivePeer {
+    let peer = PeerSession {
         peer_id,
         user_id,
         rtc,
-        socket: Arc::new(socket),
+        socket,
         local_addr,
-        media_tx,
-        has_video: false,
+        remote_addr: None,
+        published_tracks: Vec::new(),
+        subscribed_tracks: Vec::new(),
+        audio_enabled: true,
+        video_enabled: true,
+        to_subscribe: Vec::new(),
+        to_close: Vec::new(),
+        pending: None,
+        signal_tx,
+        timeout: start,
+        relay,
     };
 
     Ok((peer, answer_sdp))
 }
 
+/// Read a peer's own socket into the room until aborted.
+fn relay_socket(
+    socket: Arc<UdpSocket>,
+    peer_id: PeerId,
+    room_tx: mpsc::Sender<(PeerId, (Vec<u8>, SocketAddr))>,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut buf = vec![0u8; 2000]; // MTU-sized buffer
+        loop {
+            match socket.recv_from(&mut buf).await {
+                Ok((len, src)) => {
+                    let src = network::canonical(src);
+                    if room_tx.send((peer_id, (buf[..len].to_vec(), src))).await.is_err() {
+                        break;
+                    }
+                }
+                Err(e) => {
+                    tracing::warn!(peer = %peer_id, error = %e, "UDP recv error");
+                    break;
+                }
+            }
+        }
+    })
+}
+
 /// SFU-specific errors.
 #[derive(Debug, thiserror::Error)]
 pub enum SfuError {
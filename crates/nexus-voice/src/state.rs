@@ -5,10 +5,17 @@
 //! - Mute/deaf/video/screen share state per user
 //! - Server-side mute/deaf (moderation)
 //!
-//! State is held in-memory for speed and mirrored to Redis for
-//! multi-node coordination in production.
+//! Without Redis, state is held in memory, which is enough when the API and
+//! voice run in one process. With Redis it lives there instead, so API
+//! nodes see who's connected to voice nodes running elsewhere:
+//!
+//! - `voice:state:{user_id}` — the user's [`VoiceState`] as JSON
+//! - `voice:channel:{channel_id}` — set of user ids in the channel
+//! - `voice:channels` — set of channels with anyone in them
 
 use chrono::{DateTime, Utc};
+use redis::aio::ConnectionManager;
+use redis::AsyncCommands;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -50,15 +57,28 @@ pub struct VoiceModAction {
     pub server_deaf: Option<bool>,
 }
 
+const CHANNELS_KEY: &str = "voice:channels";
+
+fn state_key(user_id: Uuid) -> String {
+    format!("voice:state:{user_id}")
+}
+
+fn channel_key(channel_id: Uuid) -> String {
+    format!("voice:channel:{channel_id}")
+}
+
 /// Manages voice state across all channels.
 ///
 /// Two indexes for fast lookups:
 /// - `by_user`: user_id → VoiceState (quick "where is this user?")
 /// - `by_channel`: channel_id → [user_id] (quick "who's in this channel?")
+///
+/// With Redis both are kept there and the in-memory maps stay empty.
 #[derive(Clone)]
 pub struct VoiceStateManager {
     by_user: Arc<RwLock<HashMap<Uuid, VoiceState>>>,
     by_channel: Arc<RwLock<HashMap<Uuid, Vec<Uuid>>>>,
+    redis: Option<ConnectionManager>,
 }
 
 impl VoiceStateManager {
@@ -66,9 +86,23 @@ impl VoiceStateManager {
         Self {
             by_user: Arc::new(RwLock::new(HashMap::new())),
             by_channel: Arc::new(RwLock::new(HashMap::new())),
+            redis: None,
         }
     }
 
+    /// State shared through Redis by every API and voice node.
+    pub fn with_redis(redis: ConnectionManager) -> Self {
+        Self {
+            redis: Some(redis),
+            ..Self::new()
+        }
+    }
+
+    /// [`with_redis`](Self::with_redis) when Redis is configured.
+    pub fn for_redis(redis: Option<ConnectionManager>) -> Self {
+        redis.map_or_else(Self::new, Self::with_redis)
+    }
+
     /// User joins a voice channel. If already in another channel, leaves it first.
     /// Returns (new_state, Option<old_channel_id>).
     pub async fn join(
@@ -96,16 +130,20 @@ impl VoiceStateManager {
             connected_at: Utc::now(),
         };
 
-        // Add to user index
-        self.by_user.write().await.insert(user_id, state.clone());
-
-        // Add to channel index
-        self.by_channel
-            .write()
-            .await
-            .entry(channel_id)
-            .or_default()
-            .push(user_id);
+        if let Some(redis) = &self.redis {
+            logged(redis_insert(&mut redis.clone(), &state).await);
+        } else {
+            // Add to user index
+            self.by_user.write().await.insert(user_id, state.clone());
+
+            // Add to channel index
+            self.by_channel
+                .write()
+                .await
+                .entry(channel_id)
+                .or_default()
+                .push(user_id);
+        }
 
         tracing::info!(
             user = %user_id,
@@ -119,6 +157,14 @@ impl VoiceStateManager {
 
     /// User leaves their current voice channel. Returns the channel they left.
     pub async fn leave(&self, user_id: Uuid) -> Option<Uuid> {
+        if let Some(redis) = &self.redis {
+            let state = logged(redis_remove(&mut redis.clone(), user_id).await);
+            if let Some(ref s) = state {
+                tracing::info!(user = %user_id, channel = %s.channel_id, "User left voice channel");
+            }
+            return state.map(|s| s.channel_id);
+        }
+
         let state = self.by_user.write().await.remove(&user_id);
 
         if let Some(ref s) = state {
@@ -147,8 +193,7 @@ impl VoiceStateManager {
         user_id: Uuid,
         update: &VoiceStateUpdate,
     ) -> Option<VoiceState> {
-        let mut users = self.by_user.write().await;
-        if let Some(state) = users.get_mut(&user_id) {
+        self.modify(user_id, |state| {
             if let Some(m) = update.self_mute {
                 state.self_mute = m;
             }
@@ -162,46 +207,53 @@ impl VoiceStateManager {
             if let Some(s) = update.self_stream {
                 state.self_stream = s;
             }
-            Some(state.clone())
-        } else {
-            None
-        }
+        })
+        .await
     }
 
     /// Moderator action: server-mute or server-deaf a user.
     pub async fn apply_mod_action(&self, action: &VoiceModAction) -> Option<VoiceState> {
-        let mut users = self.by_user.write().await;
-        if let Some(state) = users.get_mut(&action.target_user_id) {
+        self.modify(action.target_user_id, |state| {
             if let Some(m) = action.server_mute {
                 state.server_mute = m;
             }
             if let Some(d) = action.server_deaf {
                 state.server_deaf = d;
             }
-            Some(state.clone())
-        } else {
-            None
-        }
+        })
+        .await
     }
 
     /// Update speaking state (from voice activity detection).
     pub async fn set_speaking(&self, user_id: Uuid, speaking: bool) -> Option<VoiceState> {
-        let mut users = self.by_user.write().await;
-        if let Some(state) = users.get_mut(&user_id) {
-            state.speaking = speaking;
-            Some(state.clone())
-        } else {
-            None
+        self.modify(user_id, |state| state.speaking = speaking).await
+    }
+
+    /// Apply `change` to a connected user's state and return the result.
+    async fn modify(&self, user_id: Uuid, change: impl FnOnce(&mut VoiceState)) -> Option<VoiceState> {
+        if let Some(redis) = &self.redis {
+            return logged(redis_modify(&mut redis.clone(), user_id, change).await);
         }
+
+        let mut users = self.by_user.write().await;
+        let state = users.get_mut(&user_id)?;
+        change(state);
+        Some(state.clone())
     }
 
     /// Get a user's current voice state.
     pub async fn get_user_state(&self, user_id: Uuid) -> Option<VoiceState> {
+        if let Some(redis) = &self.redis {
+            return logged(redis_get(&mut redis.clone(), user_id).await);
+        }
         self.by_user.read().await.get(&user_id).cloned()
     }
 
     /// Get all users in a voice channel.
     pub async fn get_channel_members(&self, channel_id: Uuid) -> Vec<VoiceState> {
+        if let Some(redis) = &self.redis {
+            return logged(redis_members(&mut redis.clone(), channel_id).await);
+        }
         let channels = self.by_channel.read().await;
         let users = self.by_user.read().await;
 
@@ -218,6 +270,9 @@ impl VoiceStateManager {
 
     /// Get the count of users in a voice channel.
     pub async fn get_channel_count(&self, channel_id: Uuid) -> usize {
+        if let Some(redis) = &self.redis {
+            return logged(redis.clone().scard(channel_key(channel_id)).await);
+        }
         self.by_channel
             .read()
             .await
@@ -228,25 +283,42 @@ impl VoiceStateManager {
 
     /// Check if a user is in any voice channel.
     pub async fn is_in_voice(&self, user_id: Uuid) -> bool {
+        if let Some(redis) = &self.redis {
+            return logged(redis.clone().exists(state_key(user_id)).await);
+        }
         self.by_user.read().await.contains_key(&user_id)
     }
 
+    /// Channels with at least one connected user.
+    pub async fn active_channels(&self) -> Vec<Uuid> {
+        if let Some(redis) = &self.redis {
+            return logged(redis_channels(&mut redis.clone()).await);
+        }
+        self.by_channel.read().await.keys().copied().collect()
+    }
+
     /// Disconnect all users from a channel (e.g., channel deleted).
     pub async fn disconnect_channel(&self, channel_id: Uuid) -> Vec<VoiceState> {
-        let member_ids = self
-            .by_channel
-            .write()
-            .await
-            .remove(&channel_id)
-            .unwrap_or_default();
-
-        let mut disconnected = Vec::new();
-        let mut users = self.by_user.write().await;
-        for uid in member_ids {
-            if let Some(state) = users.remove(&uid) {
-                disconnected.push(state);
+        let disconnected = match &self.redis {
+            Some(redis) => logged(redis_clear_channel(&mut redis.clone(), channel_id).await),
+            None => {
+                let member_ids = self
+                    .by_channel
+                    .write()
+                    .await
+                    .remove(&channel_id)
+                    .unwrap_or_default();
+
+                let mut disconnected = Vec::new();
+                let mut users = self.by_user.write().await;
+                for uid in member_ids {
+                    if let Some(state) = users.remove(&uid) {
+                        disconnected.push(state);
+                    }
+                }
+                disconnected
             }
-        }
+        };
 
         if !disconnected.is_empty() {
             tracing::info!(
@@ -261,6 +333,19 @@ impl VoiceStateManager {
 
     /// Get global voice stats.
     pub async fn stats(&self) -> VoiceGlobalStats {
+        if let Some(redis) = &self.redis {
+            let channels = self.active_channels().await;
+            let mut states = Vec::new();
+            for channel_id in &channels {
+                states.extend(logged(redis_members(&mut redis.clone(), *channel_id).await));
+            }
+            return VoiceGlobalStats {
+                active_channels: channels.len(),
+                total_connections: states.len(),
+                streaming_count: states.iter().filter(|s| s.self_stream).count(),
+                video_count: states.iter().filter(|s| s.self_video).count(),
+            };
+        }
         let users = self.by_user.read().await;
         let channels = self.by_channel.read().await;
 
@@ -279,6 +364,107 @@ impl Default for VoiceStateManager {
     }
 }
 
+/// A Redis result, or the empty value after logging the error: voice keeps
+/// going on what it knows rather than failing the caller.
+fn logged<T: Default>(result: redis::RedisResult<T>) -> T {
+    result.unwrap_or_else(|e| {
+        tracing::warn!(error = %e, "Voice state Redis error");
+        T::default()
+    })
+}
+
+async fn redis_get(conn: &mut ConnectionManager, user_id: Uuid) -> redis::RedisResult<Option<VoiceState>> {
+    let json: Option<String> = conn.get(state_key(user_id)).await?;
+    Ok(json.and_then(|json| serde_json::from_str(&json).ok()))
+}
+
+async fn redis_modify(
+    conn: &mut ConnectionManager,
+    user_id: Uuid,
+    change: impl FnOnce(&mut VoiceState),
+) -> redis::RedisResult<Option<VoiceState>> {
+    let Some(mut state) = redis_get(conn, user_id).await? else {
+        return Ok(None);
+    };
+    change(&mut state);
+    // XX: a user who left meanwhile stays gone
+    let json = serde_json::to_string(&state).expect("voice state serializes");
+    redis::cmd("SET")
+        .arg(state_key(user_id))
+        .arg(json)
+        .arg("XX")
+        .query_async::<()>(conn)
+        .await?;
+    Ok(Some(state))
+}
+
+async fn redis_insert(conn: &mut ConnectionManager, state: &VoiceState) -> redis::RedisResult<()> {
+    let json = serde_json::to_string(state).expect("voice state serializes");
+    redis::pipe()
+        .atomic()
+        .set(state_key(state.user_id), json)
+        .sadd(channel_key(state.channel_id), state.user_id.to_string())
+        .sadd(CHANNELS_KEY, state.channel_id.to_string())
+        .query_async(conn)
+        .await
+}
+
+async fn redis_remove(conn: &mut ConnectionManager, user_id: Uuid) -> redis::RedisResult<Option<VoiceState>> {
+    let Some(state) = redis_get(conn, user_id).await? else {
+        return Ok(None);
+    };
+    let (_, _, remaining): ((), (), usize) = redis::pipe()
+        .atomic()
+        .del(state_key(user_id))
+        .srem(channel_key(state.channel_id), user_id.to_string())
+        .scard(channel_key(state.channel_id))
+        .query_async(conn)
+        .await?;
+    if remaining == 0 {
+        conn.srem::<_, _, ()>(CHANNELS_KEY, state.channel_id.to_string()).await?;
+    }
+    Ok(Some(state))
+}
+
+/// A channel's members, earliest to join first.
+async fn redis_members(conn: &mut ConnectionManager, channel_id: Uuid) -> redis::RedisResult<Vec<VoiceState>> {
+    let user_ids: Vec<String> = conn.smembers(channel_key(channel_id)).await?;
+    let keys: Vec<String> = user_ids.iter().filter_map(|id| id.parse().ok()).map(state_key).collect();
+    if keys.is_empty() {
+        return Ok(Vec::new());
+    }
+    let json: Vec<Option<String>> = redis::cmd("MGET").arg(keys).query_async(conn).await?;
+    let mut states: Vec<VoiceState> = json
+        .into_iter()
+        .flatten()
+        .filter_map(|json| serde_json::from_str(&json).ok())
+        .filter(|state: &VoiceState| state.channel_id == channel_id)
+        .collect();
+    states.sort_by_key(|state| state.connected_at);
+    Ok(states)
+}
+
+async fn redis_channels(conn: &mut ConnectionManager) -> redis::RedisResult<Vec<Uuid>> {
+    let ids: Vec<String> = conn.smembers(CHANNELS_KEY).await?;
+    Ok(ids.iter().filter_map(|id| id.parse().ok()).collect())
+}
+
+async fn redis_clear_channel(conn: &mut ConnectionManager, channel_id: Uuid) -> redis::RedisResult<Vec<VoiceState>> {
+    let states = redis_members(conn, channel_id).await?;
+    let mut pipe = redis::pipe();
+    pipe.atomic();
+    for state in &states {
+        pipe.del(state_key(state.user_id)).ignore();
+    }
+    pipe.del(channel_key(channel_id))
+        .ignore()
+        .srem(CHANNELS_KEY, channel_id.to_string())
+        .ignore()
+        .query_async::<()>(conn)
+        .await?;
+    Ok(states)
+}
+
 /// Global voice statistics.
 #[derive(Debug, Serialize)]
 pub struct VoiceGlobalStats {
@@ -0,0 +1,51 @@
+//! TURN credentials for clients that can't reach the SFU directly.
+//!
+//! Uses the "TURN REST API" scheme coturn implements with `use-auth-secret`:
+//! the username is `<expiry unix time>:<user id>` and the password is the
+//! base64 HMAC-SHA1 of the username keyed by the secret shared with the
+//! TURN server. The TURN server recomputes the HMAC and rejects the
+//! credentials once the expiry has passed, so nothing has to be stored or
+//! revoked.
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use hmac::{Hmac, Mac};
+use sha1::Sha1;
+use uuid::Uuid;
+
+/// Time-limited long-term credentials for a TURN server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TurnCredentials {
+    pub username: String,
+    pub credential: String,
+}
+
+/// Credentials for `user_id` that expire at `expires_at` (Unix seconds).
+pub fn credentials(secret: &str, user_id: Uuid, expires_at: i64) -> TurnCredentials {
+    let username = format!("{expires_at}:{user_id}");
+    let mut mac = Hmac::<Sha1>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(username.as_bytes());
+    let credential = STANDARD.encode(mac.finalize().into_bytes());
+    TurnCredentials { username, credential }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_the_turn_rest_api_scheme() {
+        let user: Uuid = "5c1d6e1a-0000-4000-8000-000000000001".parse().unwrap();
+        let creds = credentials("north-sea", user, 1_700_000_000);
+        assert_eq!(creds.username, "1700000000:5c1d6e1a-0000-4000-8000-000000000001");
+        // base64(HMAC-SHA1("north-sea", username)), as coturn computes it
+        assert_eq!(creds.credential, "jdJcZQAuGi9WBkP3qhpLI20Y1Ag=");
+    }
+
+    #[test]
+    fn credentials_depend_on_secret_and_expiry() {
+        let user = Uuid::new_v4();
+        let creds = credentials("a", user, 100);
+        assert_ne!(creds.credential, credentials("b", user, 100).credential);
+        assert_ne!(creds.credential, credentials("a", user, 101).credential);
+    }
+}
@@ -0,0 +1,54 @@
+//! Error types for the Nexus client.
+
+use nexus_common::error::ErrorCode;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ClientError {
+    /// The API answered with an error. `code` is the server's stable error
+    /// code (e.g. `INVALID_TOKEN`), when the body carried one.
+    #[error("API error {status}: {message}")]
+    Api {
+        status: u16,
+        code: Option<String>,
+        message: String,
+    },
+
+    /// An error from the underlying HTTP client.
+    #[error("HTTP error: {0}")]
+    Http(#[from] reqwest::Error),
+
+    /// An error from the WebSocket layer.
+    #[error("WebSocket error: {0}")]
+    WebSocket(#[from] tokio_tungstenite::tungstenite::Error),
+
+    /// A JSON (de)serialization error.
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+
+    /// A request needed a session, but the client hasn't logged in.
+    #[error("Not logged in")]
+    NotLoggedIn,
+
+    /// The account was created but staff must approve it before it can log
+    /// in.
+    #[error("Account awaits approval")]
+    ApprovalPending,
+
+    /// The gateway connection has shut down.
+    #[error("Gateway is closed")]
+    GatewayClosed,
+}
+
+impl ClientError {
+    /// Whether the server refused the access token, so refreshing may help.
+    pub fn is_token_rejected(&self) -> bool {
+        matches!(
+            self,
+            ClientError::Api { status: 401, code: Some(code), .. }
+                if code == ErrorCode::TokenExpired.name() || code == ErrorCode::InvalidToken.name()
+        )
+    }
+}
+
+pub type Result<T> = std::result::Result<T, ClientError>;
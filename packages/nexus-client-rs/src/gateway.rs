@@ -0,0 +1,413 @@
+//! Async gateway client with typed events, heartbeats and session resume.
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use futures_util::{SinkExt, StreamExt};
+use nexus_common::gateway_event::{event_types, GatewayMessage};
+use nexus_common::models::user::UserPresence;
+use serde::Deserialize;
+use serde_json::Value;
+use tokio::sync::{broadcast, mpsc};
+use tokio::task::JoinHandle;
+use tokio_tungstenite::{connect_async, tungstenite::Message};
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+use crate::error::{ClientError, Result};
+use crate::types::ChannelMessage;
+
+const DEFAULT_GW: &str = "ws://localhost:3001/gateway";
+/// Longest wait between reconnect attempts.
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Something that happened on the gateway.
+///
+/// Dispatches without a typed variant, or whose payload didn't parse, come
+/// through as [`Event::Dispatch`] so nothing is lost.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub enum Event {
+    /// Identified; a new session started.
+    Ready {
+        session_id: String,
+        user: Value,
+        servers: Vec<Value>,
+    },
+    /// Reconnected to the previous session; `replayed` missed dispatches
+    /// were delivered just before this. When `complete` is false the session
+    /// moved to another gateway node and dispatches from while the client
+    /// was away are lost: refetch anything cached.
+    Resumed { replayed: usize, complete: bool },
+    MessageCreate(Box<ChannelMessage>),
+    MessageUpdate(Box<ChannelMessage>),
+    MessageDelete {
+        id: Uuid,
+        channel_id: Uuid,
+        server_id: Option<Uuid>,
+    },
+    TypingStart {
+        channel_id: Uuid,
+        user_id: Uuid,
+        timestamp: i64,
+    },
+    /// A user's aggregate presence changed, including the current user's
+    /// from another session.
+    PresenceUpdate {
+        user_id: Uuid,
+        status: UserPresence,
+        custom_status: Option<String>,
+    },
+    /// An op sent with [`GatewayClient::send`] was dropped; it may be sent
+    /// again after `retry_after_ms`.
+    RateLimited { op: String, retry_after_ms: u64 },
+    /// The connection dropped and the client is about to reconnect.
+    Reconnecting { attempt: u32 },
+    /// Any other dispatch.
+    Dispatch { event: String, data: Value },
+}
+
+#[derive(Deserialize)]
+struct MessageDelete {
+    id: Uuid,
+    channel_id: Uuid,
+    server_id: Option<Uuid>,
+}
+
+#[derive(Deserialize)]
+struct PresenceUpdate {
+    user_id: Uuid,
+    status: UserPresence,
+    custom_status: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct TypingStart {
+    channel_id: Uuid,
+    user_id: Uuid,
+    timestamp: i64,
+}
+
+impl Event {
+    /// Type a `Dispatch` frame's payload.
+    pub fn from_dispatch(event: String, data: Value) -> Self {
+        let typed = match event.as_str() {
+            event_types::MESSAGE_CREATE => {
+                serde_json::from_value(data.clone()).map(|m| Event::MessageCreate(Box::new(m)))
+            }
+            event_types::MESSAGE_UPDATE => {
+                serde_json::from_value(data.clone()).map(|m| Event::MessageUpdate(Box::new(m)))
+            }
+            event_types::MESSAGE_DELETE => serde_json::from_value::<MessageDelete>(data.clone())
+                .map(|d| Event::MessageDelete {
+                    id: d.id,
+                    channel_id: d.channel_id,
+                    server_id: d.server_id,
+                }),
+            event_types::TYPING_START => serde_json::from_value::<TypingStart>(data.clone())
+                .map(|t| Event::TypingStart {
+                    channel_id: t.channel_id,
+                    user_id: t.user_id,
+                    timestamp: t.timestamp,
+                }),
+            event_types::PRESENCE_UPDATE => serde_json::from_value::<PresenceUpdate>(data.clone())
+                .map(|p| Event::PresenceUpdate {
+                    user_id: p.user_id,
+                    status: p.status,
+                    custom_status: p.custom_status,
+                }),
+            _ => return Event::Dispatch { event, data },
+        };
+        typed.unwrap_or_else(|e| {
+            debug!("Gateway: untyped {event}: {e}");
+            Event::Dispatch { event, data }
+        })
+    }
+}
+
+/// Why one connection ended.
+enum Disconnect {
+    /// The [`GatewayClient`] was dropped or closed.
+    Shutdown,
+    /// Connect again, resuming if there's a session.
+    Reconnect,
+}
+
+/// State carried across reconnects.
+struct Session {
+    url: String,
+    token: Arc<Mutex<String>>,
+    intents: u64,
+    /// Session id and last dispatch sequence, once Ready.
+    resume: Option<(String, u64)>,
+    events: broadcast::Sender<Event>,
+}
+
+/// Async gateway client.
+///
+/// Connecting spawns a background task that identifies, heartbeats at the
+/// interval the server asks for, and on a dropped connection reconnects
+/// with backoff and resumes the session, so missed dispatches are replayed.
+///
+/// ```rust,no_run
+/// use nexus_client::{Event, GatewayClient, RestClient};
+///
+/// #[tokio::main]
+/// async fn main() -> nexus_client::Result<()> {
+///     let rest = RestClient::new(None)?;
+///     rest.login("alice", "correct horse battery").await?;
+///
+///     let gateway = GatewayClient::connect(None, &rest.access_token().unwrap(), 0);
+///     let mut events = gateway.subscribe();
+///     while let Ok(event) = events.recv().await {
+///         if let Event::MessageCreate(message) = event {
+///             println!("{}: {}", message.author_id, message.content);
+///         }
+///     }
+///     Ok(())
+/// }
+/// ```
+pub struct GatewayClient {
+    token: Arc<Mutex<String>>,
+    outgoing: mpsc::UnboundedSender<GatewayMessage>,
+    events: broadcast::Sender<Event>,
+    task: JoinHandle<()>,
+}
+
+impl GatewayClient {
+    /// Connect with an access token, opting in to `intents` (see
+    /// [`nexus_common::gateway_event::intents`]). Returns immediately; use
+    /// [`subscribe`](Self::subscribe) to receive events.
+    pub fn connect(gateway_url: Option<&str>, token: &str, intents: u64) -> Self {
+        let token = Arc::new(Mutex::new(token.to_owned()));
+        let (events, _) = broadcast::channel(256);
+        let (outgoing, incoming) = mpsc::unbounded_channel();
+        let session = Session {
+            url: gateway_url.unwrap_or(DEFAULT_GW).to_owned(),
+            token: Arc::clone(&token),
+            intents,
+            resume: None,
+            events: events.clone(),
+        };
+        Self {
+            token,
+            outgoing,
+            events,
+            task: tokio::spawn(run(session, incoming)),
+        }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<Event> {
+        self.events.subscribe()
+    }
+
+    /// Replace the access token used to identify on the next reconnect,
+    /// e.g. after [`RestClient::refresh`](crate::RestClient::refresh).
+    pub fn set_token(&self, token: &str) {
+        *self.token.lock().unwrap() = token.to_owned();
+    }
+
+    /// Send a client op, such as `PresenceUpdate`, `TypingStart` or
+    /// `VoiceStateUpdate`. Ops sent while reconnecting go out once the
+    /// connection is back.
+    pub fn send(&self, message: GatewayMessage) -> Result<()> {
+        self.outgoing.send(message).map_err(|_| ClientError::GatewayClosed)
+    }
+
+    /// Set the current user's status on this session.
+    pub fn set_presence(&self, status: UserPresence, custom_status: Option<String>) -> Result<()> {
+        self.send(GatewayMessage::PresenceUpdate {
+            status: status.as_str().to_owned(),
+            custom_status,
+        })
+    }
+
+    /// Disconnect and stop reconnecting.
+    pub fn close(self) {
+        self.task.abort();
+    }
+}
+
+async fn run(mut session: Session, mut incoming: mpsc::UnboundedReceiver<GatewayMessage>) {
+    let mut attempt = 0u32;
+    loop {
+        match run_once(&mut session, &mut incoming, &mut attempt).await {
+            Ok(Disconnect::Shutdown) => return,
+            Ok(Disconnect::Reconnect) => info!("Gateway: reconnecting"),
+            Err(e) => warn!("Gateway: disconnected: {e}"),
+        }
+        if incoming.is_closed() {
+            return;
+        }
+        attempt += 1;
+        let _ = session.events.send(Event::Reconnecting { attempt });
+        tokio::time::sleep(backoff(attempt)).await;
+    }
+}
+
+fn backoff(attempt: u32) -> Duration {
+    Duration::from_secs(1 << attempt.min(5)).min(MAX_BACKOFF)
+}
+
+async fn run_once(
+    session: &mut Session,
+    incoming: &mut mpsc::UnboundedReceiver<GatewayMessage>,
+    attempt: &mut u32,
+) -> Result<Disconnect> {
+    let (ws, _) = connect_async(session.url.as_str()).await?;
+    let (mut sink, mut stream) = ws.split();
+
+    let heartbeat_interval = loop {
+        match next_message(&mut stream).await? {
+            Some(GatewayMessage::Hello { heartbeat_interval }) => break heartbeat_interval,
+            Some(_) => continue,
+            None => return Ok(Disconnect::Reconnect),
+        }
+    };
+
+    let token = session.token.lock().unwrap().clone();
+    let hello = match &session.resume {
+        Some((session_id, sequence)) => GatewayMessage::Resume {
+            session_id: session_id.clone(),
+            token,
+            sequence: *sequence,
+        },
+        None => GatewayMessage::Identify {
+            token,
+            intents: session.intents,
+        },
+    };
+    sink.send(encode(&hello)?).await?;
+
+    let mut heartbeat = tokio::time::interval(Duration::from_millis(heartbeat_interval));
+    heartbeat.tick().await;
+
+    loop {
+        tokio::select! {
+            _ = heartbeat.tick() => {
+                let beat = GatewayMessage::Heartbeat {
+                    timestamp: chrono::Utc::now().timestamp_millis(),
+                };
+                sink.send(encode(&beat)?).await?;
+            }
+            outgoing = incoming.recv() => match outgoing {
+                Some(message) => sink.send(encode(&message)?).await?,
+                None => {
+                    let _ = sink.close().await;
+                    return Ok(Disconnect::Shutdown);
+                }
+            },
+            message = next_message(&mut stream) => match message? {
+                Some(message) => {
+                    if let Some(disconnect) = handle(session, message, attempt) {
+                        return Ok(disconnect);
+                    }
+                }
+                None => return Ok(Disconnect::Reconnect),
+            },
+        }
+    }
+}
+
+/// Track session state and forward events. Returns when the server wants
+/// the connection dropped.
+fn handle(session: &mut Session, message: GatewayMessage, attempt: &mut u32) -> Option<Disconnect> {
+    let event = match message {
+        GatewayMessage::Ready {
+            session_id,
+            user,
+            servers,
+        } => {
+            *attempt = 0;
+            session.resume = Some((session_id.clone(), 0));
+            Event::Ready {
+                session_id,
+                user,
+                servers,
+            }
+        }
+        GatewayMessage::Resumed { replayed, complete, .. } => {
+            *attempt = 0;
+            Event::Resumed { replayed, complete }
+        }
+        GatewayMessage::Dispatch {
+            event,
+            data,
+            sequence,
+        } => {
+            if let Some((_, last)) = &mut session.resume {
+                *last = sequence;
+            }
+            Event::from_dispatch(event, data)
+        }
+        GatewayMessage::RateLimited { op, retry_after_ms } => {
+            Event::RateLimited { op, retry_after_ms }
+        }
+        GatewayMessage::Reconnect => {
+            info!("Gateway: server requested reconnect");
+            return Some(Disconnect::Reconnect);
+        }
+        GatewayMessage::InvalidSession => {
+            info!("Gateway: session invalidated, identifying again");
+            session.resume = None;
+            return Some(Disconnect::Reconnect);
+        }
+        GatewayMessage::HeartbeatAck { .. } => {
+            debug!("Gateway: heartbeat ack");
+            return None;
+        }
+        _ => return None,
+    };
+    let _ = session.events.send(event);
+    None
+}
+
+/// The next gateway message, or `None` once the server closes.
+async fn next_message<S>(stream: &mut S) -> Result<Option<GatewayMessage>>
+where
+    S: futures_util::Stream<Item = tokio_tungstenite::tungstenite::Result<Message>> + Unpin,
+{
+    while let Some(frame) = stream.next().await {
+        match frame? {
+            Message::Text(text) => match serde_json::from_str(text.as_str()) {
+                Ok(message) => return Ok(Some(message)),
+                Err(e) => debug!("Gateway: ignoring unknown frame: {e}"),
+            },
+            Message::Close(_) => return Ok(None),
+            _ => {}
+        }
+    }
+    Ok(None)
+}
+
+fn encode(message: &GatewayMessage) -> Result<Message> {
+    Ok(Message::Text(serde_json::to_string(message)?.into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn dispatches_are_typed_or_passed_through() {
+        let channel_id = Uuid::new_v4();
+        let event = Event::from_dispatch(
+            event_types::MESSAGE_DELETE.into(),
+            json!({"id": Uuid::new_v4(), "channel_id": channel_id, "server_id": null}),
+        );
+        assert!(matches!(event, Event::MessageDelete { channel_id: c, server_id: None, .. } if c == channel_id));
+
+        let event = Event::from_dispatch(event_types::MESSAGE_CREATE.into(), json!({"id": "bad"}));
+        assert!(matches!(event, Event::Dispatch { ref event, .. } if event == event_types::MESSAGE_CREATE));
+
+        let event = Event::from_dispatch("SOMETHING_NEW".into(), json!({}));
+        assert!(matches!(event, Event::Dispatch { .. }));
+    }
+
+    #[test]
+    fn backoff_is_capped() {
+        assert_eq!(backoff(1), Duration::from_secs(2));
+        assert_eq!(backoff(20), MAX_BACKOFF);
+    }
+}
@@ -0,0 +1,43 @@
+//! Nexus client library for Rust — for native clients and TUIs acting as a
+//! user, where [`nexus-sdk`](https://docs.rs/nexus-sdk) is for bots.
+//!
+//! Models, gateway ops and voice signaling types are shared with the server
+//! through `nexus-common` and re-exported here.
+//!
+//! # Quick start
+//!
+//! ```rust,no_run
+//! use nexus_client::{Event, GatewayClient, RestClient};
+//!
+//! #[tokio::main]
+//! async fn main() -> nexus_client::Result<()> {
+//!     let rest = RestClient::new(None)?;
+//!     rest.login("alice", "correct horse battery").await?;
+//!
+//!     let gateway = GatewayClient::connect(None, &rest.access_token().unwrap(), 0);
+//!     let mut events = gateway.subscribe();
+//!     while let Ok(event) = events.recv().await {
+//!         if let Event::MessageCreate(message) = event {
+//!             if message.content == "!ping" {
+//!                 rest.say(message.channel_id, "pong").await?;
+//!             }
+//!         }
+//!     }
+//!     Ok(())
+//! }
+//! ```
+
+pub mod error;
+pub mod gateway;
+pub mod rest;
+pub mod types;
+
+pub use error::{ClientError, Result};
+pub use gateway::{Event, GatewayClient};
+pub use rest::RestClient;
+pub use types::*;
+
+pub use nexus_common::auth::TokenPair;
+pub use nexus_common::gateway_event::{event_types, intents, GatewayMessage};
+pub use nexus_common::models;
+pub use nexus_common::voice;
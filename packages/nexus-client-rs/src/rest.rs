@@ -0,0 +1,229 @@
+//! Async REST client for the user-facing Nexus API.
+
+use std::sync::{Arc, RwLock};
+
+use nexus_common::auth::TokenPair;
+use nexus_common::models::channel::Channel;
+use nexus_common::models::message::CreateMessageRequest;
+use nexus_common::models::server::ServerResponse;
+use nexus_common::models::user::{CreateUserRequest, LoginRequest, UserResponse};
+use reqwest::{Client, Method, RequestBuilder};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::error::{ClientError, Result};
+use crate::types::{AuthResponse, ChannelMessage, HistoryQuery};
+
+const DEFAULT_BASE: &str = "http://localhost:3000/api/v1";
+
+/// Async Nexus REST client.
+///
+/// Holds the session's token pair once logged in. When the server rejects
+/// the access token, the client refreshes it once and retries, so callers
+/// don't need to track expiry.
+///
+/// ```rust,no_run
+/// use nexus_client::RestClient;
+///
+/// #[tokio::main]
+/// async fn main() -> nexus_client::Result<()> {
+///     let rest = RestClient::new(None)?;
+///     let session = rest.login("alice", "correct horse battery").await?;
+///     println!("Logged in as {}", session.user.username);
+///     Ok(())
+/// }
+/// ```
+#[derive(Clone)]
+pub struct RestClient {
+    client: Client,
+    base_url: String,
+    tokens: Arc<RwLock<Option<TokenPair>>>,
+}
+
+impl RestClient {
+    pub fn new(base_url: Option<&str>) -> Result<Self> {
+        Ok(Self {
+            client: Client::builder().build()?,
+            base_url: base_url.unwrap_or(DEFAULT_BASE).trim_end_matches('/').to_owned(),
+            tokens: Arc::new(RwLock::new(None)),
+        })
+    }
+
+    /// Resume a session saved from [`tokens`](Self::tokens).
+    pub fn with_tokens(self, tokens: TokenPair) -> Self {
+        *self.tokens.write().unwrap() = Some(tokens);
+        self
+    }
+
+    /// The current token pair, to persist between runs. Changes on refresh.
+    pub fn tokens(&self) -> Option<TokenPair> {
+        self.tokens.read().unwrap().clone()
+    }
+
+    /// The current access token, e.g. to identify on the gateway.
+    pub fn access_token(&self) -> Option<String> {
+        self.tokens.read().unwrap().as_ref().map(|t| t.access_token.clone())
+    }
+
+    // ── Internal ──────────────────────────────────────────────────────────────
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path)
+    }
+
+    async fn send<T: DeserializeOwned>(request: RequestBuilder) -> Result<T> {
+        let resp = request.send().await?;
+        let status = resp.status();
+        if !status.is_success() {
+            let body = resp.json::<Value>().await.unwrap_or_default();
+            let field = |name: &str| body.get(name).and_then(Value::as_str).map(str::to_owned);
+            return Err(ClientError::Api {
+                status: status.as_u16(),
+                code: field("error"),
+                message: field("message").unwrap_or_else(|| status.to_string()),
+            });
+        }
+        if status == reqwest::StatusCode::NO_CONTENT {
+            return Ok(serde_json::from_value(Value::Null)?);
+        }
+        Ok(resp.json::<T>().await?)
+    }
+
+    /// An authenticated request, refreshing and retrying once if the access
+    /// token was rejected.
+    async fn request<T: DeserializeOwned>(
+        &self,
+        method: Method,
+        path: &str,
+        build: impl Fn(RequestBuilder) -> RequestBuilder,
+    ) -> Result<T> {
+        let attempt = |token: String| {
+            let request = self.client.request(method.clone(), self.url(path)).bearer_auth(token);
+            Self::send::<T>(build(request))
+        };
+        let token = self.access_token().ok_or(ClientError::NotLoggedIn)?;
+        match attempt(token).await {
+            Err(e) if e.is_token_rejected() => {
+                self.refresh().await?;
+                let token = self.access_token().ok_or(ClientError::NotLoggedIn)?;
+                attempt(token).await
+            }
+            result => result,
+        }
+    }
+
+    async fn get<T: DeserializeOwned>(&self, path: &str, query: &impl Serialize) -> Result<T> {
+        self.request(Method::GET, path, |r| r.query(query)).await
+    }
+
+    async fn post<T: DeserializeOwned>(&self, path: &str, body: &impl Serialize) -> Result<T> {
+        self.request(Method::POST, path, |r| r.json(body)).await
+    }
+
+    fn store(&self, tokens: TokenPair) {
+        *self.tokens.write().unwrap() = Some(tokens);
+    }
+
+    // ── Authentication ────────────────────────────────────────────────────────
+
+    /// Create an account and log in as it. On instances that approve sign-ups
+    /// this fails with [`ClientError::ApprovalPending`]; log in once approved.
+    pub async fn register(&self, request: &CreateUserRequest) -> Result<AuthResponse> {
+        let body: Value =
+            Self::send(self.client.post(self.url("/auth/register")).json(request)).await?;
+        if body.get("approval_pending").and_then(Value::as_bool) == Some(true) {
+            return Err(ClientError::ApprovalPending);
+        }
+        let auth: AuthResponse = serde_json::from_value(body)?;
+        self.store(auth.tokens.clone());
+        Ok(auth)
+    }
+
+    pub async fn login(&self, username: &str, password: &str) -> Result<AuthResponse> {
+        let request = LoginRequest {
+            username: username.to_owned(),
+            password: password.to_owned(),
+        };
+        let auth: AuthResponse =
+            Self::send(self.client.post(self.url("/auth/login")).json(&request)).await?;
+        self.store(auth.tokens.clone());
+        Ok(auth)
+    }
+
+    /// Exchange the refresh token for a new pair. Requests do this on their
+    /// own when the access token expires.
+    pub async fn refresh(&self) -> Result<TokenPair> {
+        let refresh_token = self
+            .tokens
+            .read()
+            .unwrap()
+            .as_ref()
+            .map(|t| t.refresh_token.clone())
+            .ok_or(ClientError::NotLoggedIn)?;
+        let tokens: TokenPair = Self::send(
+            self.client
+                .post(self.url("/auth/refresh"))
+                .json(&serde_json::json!({ "refresh_token": refresh_token })),
+        )
+        .await?;
+        self.store(tokens.clone());
+        Ok(tokens)
+    }
+
+    /// Forget the session locally.
+    pub fn logout(&self) {
+        *self.tokens.write().unwrap() = None;
+    }
+
+    // ── Users ─────────────────────────────────────────────────────────────────
+
+    pub async fn current_user(&self) -> Result<UserResponse> {
+        self.request(Method::GET, "/users/@me", |r| r).await
+    }
+
+    // ── Servers & channels ────────────────────────────────────────────────────
+
+    /// Servers the current user is a member of.
+    pub async fn servers(&self) -> Result<Vec<ServerResponse>> {
+        self.request(Method::GET, "/servers", |r| r).await
+    }
+
+    /// Join a public server.
+    pub async fn join_server(&self, server_id: Uuid) -> Result<Value> {
+        self.request(Method::POST, &format!("/servers/{server_id}/join"), |r| r).await
+    }
+
+    pub async fn server_channels(&self, server_id: Uuid) -> Result<Vec<Channel>> {
+        self.request(Method::GET, &format!("/servers/{server_id}/channels"), |r| r).await
+    }
+
+    // ── Messages ──────────────────────────────────────────────────────────────
+
+    pub async fn send_message(
+        &self,
+        channel_id: Uuid,
+        message: &CreateMessageRequest,
+    ) -> Result<ChannelMessage> {
+        self.post(&format!("/channels/{channel_id}/messages"), message).await
+    }
+
+    /// Send plain text.
+    pub async fn say(&self, channel_id: Uuid, content: &str) -> Result<ChannelMessage> {
+        let message = CreateMessageRequest {
+            content: content.to_owned(),
+            ..Default::default()
+        };
+        self.send_message(channel_id, &message).await
+    }
+
+    /// A page of the channel's history, newest first.
+    pub async fn message_history(
+        &self,
+        channel_id: Uuid,
+        query: &HistoryQuery,
+    ) -> Result<Vec<ChannelMessage>> {
+        self.get(&format!("/channels/{channel_id}/messages"), query).await
+    }
+}
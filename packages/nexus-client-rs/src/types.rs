@@ -0,0 +1,73 @@
+//! Response shapes specific to the client API. Request bodies and most
+//! models come straight from [`nexus_common::models`].
+
+use chrono::{DateTime, Utc};
+use nexus_common::auth::TokenPair;
+use nexus_common::models::{message::MessageReference, user::UserResponse};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Returned by register and login: the account and its first token pair.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuthResponse {
+    pub user: UserResponse,
+    #[serde(flatten)]
+    pub tokens: TokenPair,
+}
+
+/// A message as the REST API and `MESSAGE_CREATE` / `MESSAGE_UPDATE`
+/// dispatches return it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChannelMessage {
+    pub id: Uuid,
+    pub channel_id: Uuid,
+    pub author_id: Uuid,
+    /// Included in history and new-message dispatches.
+    #[serde(default)]
+    pub author_username: Option<String>,
+    pub content: String,
+    pub message_type: i32,
+    pub edited: bool,
+    pub edited_at: Option<DateTime<Utc>>,
+    pub pinned: bool,
+    #[serde(default)]
+    pub embeds: serde_json::Value,
+    #[serde(default)]
+    pub attachments: serde_json::Value,
+    #[serde(default)]
+    pub mentions: Vec<Uuid>,
+    #[serde(default)]
+    pub mention_roles: Vec<Uuid>,
+    #[serde(default)]
+    pub mention_everyone: bool,
+    pub reference: Option<MessageReference>,
+    pub thread_id: Option<Uuid>,
+    #[serde(default)]
+    pub flags: i32,
+    /// `{id, username, avatar_url}` for webhook-authored messages.
+    #[serde(default)]
+    pub webhook: Option<serde_json::Value>,
+    #[serde(default)]
+    pub reactions: Vec<ReactionSummary>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// One emoji's reactions on a message.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReactionSummary {
+    pub emoji: String,
+    pub count: i64,
+    /// Whether the current user reacted with it.
+    pub me: bool,
+}
+
+/// Which page of a channel's history to fetch. Newest first; at most 100.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct HistoryQuery {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub before: Option<Uuid>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub after: Option<Uuid>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<i64>,
+}
@@ -190,3 +190,73 @@ export const GatewayEvents = {
 
 export type GatewayEventName =
   (typeof GatewayEvents)[keyof typeof GatewayEvents];
+
+// ============================================================================
+// Permissions
+// ============================================================================
+
+/**
+ * Permission bits, mirroring `nexus_common::permissions::Permissions`.
+ * Values exceed 32 bits, so they are bigints; the live catalog is served
+ * at `GET /api/v1/permissions`.
+ */
+export const Permissions = {
+  VIEW_CHANNEL: 1n << 0n,
+  MANAGE_SERVER: 1n << 1n,
+  MANAGE_CHANNELS: 1n << 2n,
+  MANAGE_ROLES: 1n << 3n,
+  CREATE_INVITES: 1n << 4n,
+  KICK_MEMBERS: 1n << 5n,
+  BAN_MEMBERS: 1n << 6n,
+  VIEW_AUDIT_LOG: 1n << 7n,
+  CHANGE_NICKNAME: 1n << 8n,
+  MANAGE_NICKNAMES: 1n << 9n,
+  MANAGE_EMOJIS: 1n << 10n,
+  MANAGE_WEBHOOKS: 1n << 11n,
+  SEND_MESSAGES: 1n << 12n,
+  SEND_MESSAGES_IN_THREADS: 1n << 13n,
+  CREATE_PUBLIC_THREADS: 1n << 14n,
+  CREATE_PRIVATE_THREADS: 1n << 15n,
+  MANAGE_THREADS: 1n << 16n,
+  EMBED_LINKS: 1n << 17n,
+  ATTACH_FILES: 1n << 18n,
+  ADD_REACTIONS: 1n << 19n,
+  USE_EXTERNAL_EMOJIS: 1n << 20n,
+  MENTION_EVERYONE: 1n << 21n,
+  MANAGE_MESSAGES: 1n << 22n,
+  READ_MESSAGE_HISTORY: 1n << 23n,
+  USE_COMMANDS: 1n << 24n,
+  CONNECT: 1n << 25n,
+  SPEAK: 1n << 26n,
+  VIDEO: 1n << 27n,
+  MUTE_MEMBERS: 1n << 28n,
+  DEAFEN_MEMBERS: 1n << 29n,
+  MOVE_MEMBERS: 1n << 30n,
+  USE_VAD: 1n << 31n,
+  SCREEN_SHARE: 1n << 32n,
+  STAGE_SPEAKER: 1n << 33n,
+  RECORD_VOICE: 1n << 34n,
+  MANAGE_POLLS: 1n << 35n,
+  MANAGE_EVENTS: 1n << 36n,
+  PIN_MESSAGES: 1n << 37n,
+  MANAGE_PLUGINS: 1n << 38n,
+  VIEW_ANALYTICS: 1n << 39n,
+  ADMINISTRATOR: 1n << 40n,
+} as const;
+
+export type PermissionName = keyof typeof Permissions;
+
+/** Whether `bits` grant every permission in `required` (ADMINISTRATOR grants all). */
+export function hasPermission(bits: bigint | number | string, required: bigint): boolean {
+  const value = BigInt(bits);
+  if ((value & Permissions.ADMINISTRATOR) !== 0n) return true;
+  return (value & required) === required;
+}
+
+/** Names of the permissions set in `bits`, in bit order. */
+export function permissionNames(bits: bigint | number | string): PermissionName[] {
+  const value = BigInt(bits);
+  return (Object.keys(Permissions) as PermissionName[]).filter(
+    (name) => (value & Permissions[name]) !== 0n,
+  );
+}
@@ -0,0 +1,32 @@
+#!/usr/bin/env bash
+# Nexus — run the federation interop suite against a Synapse homeserver.
+#
+# Starts Synapse in Docker, runs nexus-federation's synapse_interop tests,
+# and removes the container afterwards. Extra arguments go to cargo test.
+#
+#   SYNAPSE_VERSION=v1.120.0 scripts/synapse-interop.sh
+#   KEEP_SYNAPSE=1 scripts/synapse-interop.sh   # leave it running
+set -euo pipefail
+
+CYAN='\033[0;36m'; GREEN='\033[0;32m'; RESET='\033[0m'
+info()    { echo -e "${CYAN}[nexus]${RESET} $*"; }
+success() { echo -e "${GREEN}[nexus]${RESET} $*"; }
+
+ROOT_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")/.." && pwd)"
+COMPOSE_FILE="$ROOT_DIR/crates/nexus-federation/tests/synapse/docker-compose.yml"
+
+cleanup() {
+  if [[ -z "${KEEP_SYNAPSE:-}" ]]; then
+    info "Removing Synapse"
+    docker compose -f "$COMPOSE_FILE" down -v >/dev/null 2>&1 || true
+  fi
+}
+trap cleanup EXIT
+
+info "Starting Synapse ${SYNAPSE_VERSION:-latest}"
+docker compose -f "$COMPOSE_FILE" up -d --wait
+
+export NEXUS_SYNAPSE_URL="http://localhost:${SYNAPSE_PORT:-8008}"
+cd "$ROOT_DIR"
+cargo test -p nexus-federation --features synapse-interop --test synapse_interop "$@"
+success "Synapse interop suite passed"